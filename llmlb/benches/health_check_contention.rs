@@ -0,0 +1,112 @@
+//! ヘルスチェックとルーティングのロック競合ベンチマーク
+//!
+//! 1000エンドポイントを登録し、ヘルスチェックを連続実行している間の
+//! エンドポイント選択レイテンシを、ヘルスチェック無しの場合と比較する。
+//!
+//! ```text
+//! cargo bench -p llmlb --bench health_check_contention
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use llmlb::balancer::LoadManager;
+use llmlb::health::EndpointHealthChecker;
+use llmlb::registry::endpoints::EndpointRegistry;
+use llmlb::types::endpoint::{Endpoint, EndpointStatus, EndpointType};
+use std::hint::black_box;
+use std::sync::Arc;
+use wiremock::matchers::{method, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ENDPOINT_COUNT: usize = 1000;
+
+struct Fixture {
+    _mock: MockServer,
+    _db_dir: tempfile::TempDir,
+    registry: EndpointRegistry,
+    load_manager: LoadManager,
+}
+
+async fn setup() -> Fixture {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/ep\d+/v1/models$"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .mount(&mock)
+        .await;
+
+    let db_dir = tempfile::tempdir().expect("tempdir");
+    let db_url = format!("sqlite:{}", db_dir.path().join("bench.db").display());
+    let pool = llmlb::db::migrations::initialize_database(&db_url)
+        .await
+        .expect("database");
+    let registry = EndpointRegistry::new(pool).await.expect("registry");
+
+    for i in 0..ENDPOINT_COUNT {
+        let mut endpoint = Endpoint::new(
+            format!("bench-{}", i),
+            format!("{}/ep{}", mock.uri(), i),
+            EndpointType::OpenaiCompatible,
+        );
+        endpoint.status = EndpointStatus::Online;
+        registry.add(endpoint).await.expect("add endpoint");
+    }
+
+    let load_manager = LoadManager::new(Arc::new(registry.clone()));
+    Fixture {
+        _mock: mock,
+        _db_dir: db_dir,
+        registry,
+        load_manager,
+    }
+}
+
+fn bench_routing_under_health_checks(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("runtime");
+    let fixture = rt.block_on(setup());
+
+    let mut group = c.benchmark_group("routing_with_1000_endpoints");
+
+    group.bench_function("select_idle", |b| {
+        b.to_async(&rt).iter(|| async {
+            black_box(
+                fixture
+                    .load_manager
+                    .select_endpoint_round_robin_direct()
+                    .await
+                    .expect("select"),
+            )
+        })
+    });
+
+    // ヘルスチェックを連続実行しながら同じ選択処理を計測する
+    let checker = EndpointHealthChecker::new(fixture.registry.clone());
+    let health_task = rt.spawn(async move {
+        loop {
+            let _ = checker.check_all_endpoints().await;
+        }
+    });
+
+    group.bench_function("select_during_health_checks", |b| {
+        b.to_async(&rt).iter(|| async {
+            black_box(
+                fixture
+                    .load_manager
+                    .select_endpoint_round_robin_direct()
+                    .await
+                    .expect("select"),
+            )
+        })
+    });
+
+    health_task.abort();
+    group.finish();
+}
+
+criterion_group!(benches, bench_routing_under_health_checks);
+criterion_main!(benches);
@@ -0,0 +1,2 @@
+-- エンドポイントのタグ（ラベル）: ラベルベースのルーティングポリシーで使用
+ALTER TABLE endpoints ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
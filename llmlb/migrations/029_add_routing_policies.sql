@@ -0,0 +1,20 @@
+-- ラベルベースのルーティングポリシー
+-- モデル名パターン（+ 任意のリクエスト種別）に合致したリクエストは、
+-- required_labels をすべて持つエンドポイントのみを候補とする
+
+CREATE TABLE IF NOT EXISTS routing_policies (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    model_pattern TEXT NOT NULL,              -- 完全一致、または末尾 '*' の前方一致
+    api_kind TEXT,                            -- chat_completions / completions / responses（NULL = 全種別）
+    required_labels TEXT NOT NULL DEFAULT '[]', -- JSON配列
+    on_no_match TEXT NOT NULL DEFAULT 'error', -- error / fallback
+    priority INTEGER NOT NULL DEFAULT 0,      -- 大きいほど優先
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    CONSTRAINT valid_on_no_match CHECK (on_no_match IN ('error', 'fallback')),
+    CONSTRAINT valid_enabled CHECK (enabled IN (0, 1))
+);
+
+CREATE INDEX IF NOT EXISTS idx_routing_policies_priority ON routing_policies(priority DESC);
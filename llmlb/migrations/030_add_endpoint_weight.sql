@@ -0,0 +1,2 @@
+-- エンドポイントの重み（目標値）: ramp中の実効重みはLoadManagerがメモリ上で補間する
+ALTER TABLE endpoints ADD COLUMN weight INTEGER NOT NULL DEFAULT 1;
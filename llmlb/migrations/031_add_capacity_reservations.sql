@@ -0,0 +1,20 @@
+-- エンドポイント容量予約
+-- APIキー/テナント単位で特定エンドポイントの同時スロットを予約する
+
+CREATE TABLE IF NOT EXISTS capacity_reservations (
+    id TEXT PRIMARY KEY,
+    endpoint_id TEXT NOT NULL,
+    principal_type TEXT NOT NULL,            -- api_key / tenant
+    principal_id TEXT NOT NULL,              -- APIキーID、またはテナント（ユーザー）ID
+    slots INTEGER NOT NULL,
+    soft INTEGER NOT NULL DEFAULT 0,         -- 1: 予約主体が使っていない間は共有プールへ戻す
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    CONSTRAINT valid_principal_type CHECK (principal_type IN ('api_key', 'tenant')),
+    CONSTRAINT valid_slots CHECK (slots > 0),
+    CONSTRAINT valid_soft CHECK (soft IN (0, 1)),
+    UNIQUE (endpoint_id, principal_type, principal_id),
+    FOREIGN KEY (endpoint_id) REFERENCES endpoints(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_capacity_reservations_endpoint ON capacity_reservations(endpoint_id);
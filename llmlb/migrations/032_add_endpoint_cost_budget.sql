@@ -0,0 +1,12 @@
+-- エンドポイント別の単価・月次予算と、当月累計コスト
+ALTER TABLE endpoints ADD COLUMN monthly_budget_usd REAL;
+ALTER TABLE endpoints ADD COLUMN input_cost_per_million_tokens REAL;
+ALTER TABLE endpoints ADD COLUMN output_cost_per_million_tokens REAL;
+
+CREATE TABLE IF NOT EXISTS endpoint_monthly_costs (
+    endpoint_id TEXT NOT NULL,
+    month TEXT NOT NULL,
+    cost_usd REAL NOT NULL DEFAULT 0,
+    PRIMARY KEY (endpoint_id, month),
+    FOREIGN KEY (endpoint_id) REFERENCES endpoints(id) ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- リクエスト単位の課金額（USD）: 単価設定のあるエンドポイントのみ記録する
+ALTER TABLE request_history ADD COLUMN cost_usd REAL;
@@ -0,0 +1,2 @@
+-- max_tokens が手動設定かどうか（1: 手動設定。モデル同期・メタデータ取得で上書きしない）
+ALTER TABLE endpoint_models ADD COLUMN max_tokens_manual INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,9 @@
+-- リクエストキューの待機数・拒否数の時系列サンプル
+CREATE TABLE IF NOT EXISTS queue_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sampled_at TEXT NOT NULL,
+    waiting INTEGER NOT NULL,
+    rejected INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_queue_history_sampled_at ON queue_history(sampled_at);
@@ -0,0 +1,19 @@
+-- A/Bテスト（実験）
+-- model_pattern に合致したリクエストを assign_by の属性の安定ハッシュで A/B に割り当て、
+-- グループごとのモデル/必須ラベルへ送る
+
+CREATE TABLE IF NOT EXISTS experiments (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    model_pattern TEXT NOT NULL,                -- 完全一致、または末尾 '*' の前方一致
+    assign_by TEXT NOT NULL DEFAULT 'api_key',  -- api_key / client_ip / user
+    b_percent INTEGER NOT NULL DEFAULT 50,      -- グループBへ割り当てる割合（0〜100）
+    variant_a TEXT NOT NULL DEFAULT '{}',       -- JSON: {"model": ..., "required_labels": [...]}
+    variant_b TEXT NOT NULL DEFAULT '{}',
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    CONSTRAINT valid_assign_by CHECK (assign_by IN ('api_key', 'client_ip', 'user')),
+    CONSTRAINT valid_b_percent CHECK (b_percent BETWEEN 0 AND 100),
+    CONSTRAINT valid_enabled CHECK (enabled IN (0, 1))
+);
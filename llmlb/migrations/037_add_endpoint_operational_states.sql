@@ -0,0 +1,12 @@
+-- エンドポイントの運用状態（drain / disable / maintenance）
+-- 再起動後も維持するために永続化する。通常運用（active）の行は保持しない
+
+CREATE TABLE IF NOT EXISTS endpoint_operational_states (
+    endpoint_id TEXT PRIMARY KEY,
+    state TEXT NOT NULL,                     -- draining / disabled / maintenance
+    reason TEXT,
+    updated_by TEXT,
+    updated_at TEXT NOT NULL,
+    CONSTRAINT valid_state CHECK (state IN ('draining', 'disabled', 'maintenance')),
+    FOREIGN KEY (endpoint_id) REFERENCES endpoints(id) ON DELETE CASCADE
+);
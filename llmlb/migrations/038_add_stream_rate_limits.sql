@@ -0,0 +1,16 @@
+-- ストリーミング応答の出力レート上限
+-- APIキー/テナント単位でトークン/秒・バイト/秒の上限を設定する（NULL は無制限）
+
+CREATE TABLE IF NOT EXISTS stream_rate_limits (
+    id TEXT PRIMARY KEY,
+    principal_type TEXT NOT NULL,            -- api_key / tenant
+    principal_id TEXT NOT NULL,              -- APIキーID、またはテナント（ユーザー）ID
+    max_tokens_per_sec INTEGER,
+    max_bytes_per_sec INTEGER,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    CONSTRAINT valid_principal_type CHECK (principal_type IN ('api_key', 'tenant')),
+    CONSTRAINT valid_max_tokens CHECK (max_tokens_per_sec IS NULL OR max_tokens_per_sec > 0),
+    CONSTRAINT valid_max_bytes CHECK (max_bytes_per_sec IS NULL OR max_bytes_per_sec > 0),
+    UNIQUE (principal_type, principal_id)
+);
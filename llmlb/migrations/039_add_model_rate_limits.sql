@@ -0,0 +1,11 @@
+-- モデル別のレートリミット
+-- 高コストモデルへのアクセスをモデル単位で制限する（NULL は無制限）
+
+CREATE TABLE IF NOT EXISTS model_rate_limits (
+    model_id TEXT PRIMARY KEY,               -- リクエストの model と完全一致で照合
+    requests_per_minute INTEGER,
+    tokens_per_minute INTEGER,
+    updated_at TEXT NOT NULL,
+    CONSTRAINT valid_requests_per_minute CHECK (requests_per_minute IS NULL OR requests_per_minute > 0),
+    CONSTRAINT valid_tokens_per_minute CHECK (tokens_per_minute IS NULL OR tokens_per_minute > 0)
+);
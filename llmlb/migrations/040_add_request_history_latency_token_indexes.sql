@@ -0,0 +1,6 @@
+-- レイテンシ・トークン数による履歴検索（query_history）用のインデックス
+CREATE INDEX IF NOT EXISTS idx_request_history_duration_ms ON request_history(duration_ms);
+-- total_tokens が NULL の旧レコードは入出力トークンの和で扱うため、式インデックスにする
+CREATE INDEX IF NOT EXISTS idx_request_history_effective_total_tokens ON request_history(
+    COALESCE(total_tokens, COALESCE(input_tokens, 0) + COALESCE(output_tokens, 0))
+);
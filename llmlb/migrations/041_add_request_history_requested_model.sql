@@ -0,0 +1,2 @@
+-- コンテキスト超過による自動モデル切替時に、クライアントが要求したモデルを記録する
+ALTER TABLE request_history ADD COLUMN requested_model TEXT;
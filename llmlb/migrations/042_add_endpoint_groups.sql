@@ -0,0 +1,20 @@
+-- エンドポイントグループ（論理プール）
+-- 1エンドポイントは複数のグループに所属できる。グループを削除してもエンドポイントは削除しない
+
+CREATE TABLE IF NOT EXISTS endpoint_groups (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS endpoint_group_members (
+    group_id TEXT NOT NULL,
+    endpoint_id TEXT NOT NULL,
+    PRIMARY KEY (group_id, endpoint_id),
+    FOREIGN KEY (group_id) REFERENCES endpoint_groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (endpoint_id) REFERENCES endpoints(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_endpoint_group_members_endpoint ON endpoint_group_members(endpoint_id);
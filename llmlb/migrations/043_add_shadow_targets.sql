@@ -0,0 +1,17 @@
+-- シャドウトラフィック（本番複製）
+-- model_pattern に合致した本番リクエストの sample_percent % をシャドウエンドポイントへ複製送信する。
+-- 応答はクライアントへ返さず、レイテンシ/エラーのみを本番とは別に集計する
+
+CREATE TABLE IF NOT EXISTS shadow_targets (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    model_pattern TEXT NOT NULL,                  -- 完全一致、または末尾 '*' の前方一致
+    endpoint_id TEXT NOT NULL,                    -- 複製先（シャドウ）エンドポイント
+    sample_percent INTEGER NOT NULL DEFAULT 10,   -- 複製する割合（0〜100）
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (endpoint_id) REFERENCES endpoints(id) ON DELETE CASCADE,
+    CONSTRAINT valid_sample_percent CHECK (sample_percent BETWEEN 0 AND 100),
+    CONSTRAINT valid_enabled CHECK (enabled IN (0, 1))
+);
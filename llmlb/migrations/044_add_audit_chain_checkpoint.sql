@@ -0,0 +1,9 @@
+-- 監査ログハッシュチェーンの検証チェックポイント
+-- 前回検証に成功した最終バッチを1行だけ保持し、差分検証はそれ以降のバッチのみを検証する
+
+CREATE TABLE IF NOT EXISTS audit_chain_checkpoint (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    sequence_number INTEGER NOT NULL,
+    hash TEXT NOT NULL,
+    verified_at TEXT NOT NULL
+);
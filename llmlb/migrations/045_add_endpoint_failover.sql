@@ -0,0 +1,3 @@
+-- エンドポイントの優先フェイルオーバー先
+-- 選択不可になったときに通常選択より先に振り替えるエンドポイント（循環参照は登録時に拒否）
+ALTER TABLE endpoints ADD COLUMN failover_to TEXT;
@@ -0,0 +1,21 @@
+-- 品質監視用のリクエスト入出力サンプル（レダクション済み）
+-- LLMLB_SAMPLE_IO_RATE の割合で保存し（エラー応答は常に保存）、expires_at を過ぎたものと
+-- 容量上限を超えた古いものは保存時に削除する
+CREATE TABLE IF NOT EXISTS io_samples (
+    id TEXT PRIMARY KEY,                  -- request_history.id と同じ
+    sampled_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    request_type TEXT NOT NULL,
+    model TEXT NOT NULL,
+    endpoint_id TEXT NOT NULL,
+    endpoint_name TEXT NOT NULL,
+    status TEXT NOT NULL,                 -- success / error
+    error_message TEXT,
+    reason TEXT NOT NULL,                 -- sampled（採用率による） / error（エラー応答）
+    request_body TEXT NOT NULL,
+    response_body TEXT,
+    size_bytes INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_io_samples_sampled_at ON io_samples(sampled_at);
+CREATE INDEX IF NOT EXISTS idx_io_samples_expires_at ON io_samples(expires_at);
@@ -0,0 +1,2 @@
+-- エンドポイントのカナリア割合（%）。NULL は通常のエンドポイント
+ALTER TABLE endpoints ADD COLUMN canary_percent INTEGER;
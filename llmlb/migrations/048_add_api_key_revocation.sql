@@ -0,0 +1,2 @@
+-- APIキーの失効日時。NULL は有効なキー（失効済みのキーは認証で拒否する）
+ALTER TABLE api_keys ADD COLUMN revoked_at TEXT;
@@ -0,0 +1,2 @@
+-- エンドポイントの依存先（JSON配列のエンドポイントID）: 起動時の再判別・初回ヘルスチェックの順序に使用
+ALTER TABLE endpoints ADD COLUMN depends_on TEXT NOT NULL DEFAULT '[]';
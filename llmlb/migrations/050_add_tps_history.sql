@@ -0,0 +1,14 @@
+-- エンドポイント×モデル単位のTPS（EMA）の時系列スナップショット
+CREATE TABLE IF NOT EXISTS tps_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sampled_at TEXT NOT NULL,
+    endpoint_id TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    api_kind TEXT NOT NULL,
+    tps REAL NOT NULL,
+    request_count INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_tps_history_endpoint_sampled_at
+    ON tps_history(endpoint_id, sampled_at);
+CREATE INDEX IF NOT EXISTS idx_tps_history_sampled_at ON tps_history(sampled_at);
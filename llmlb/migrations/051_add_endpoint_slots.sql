@@ -0,0 +1,2 @@
+-- エンドポイントの同時スロット数（容量予約の判定に使う）。NULL は LLMLB_ENDPOINT_SLOTS の既定値
+ALTER TABLE endpoints ADD COLUMN slots INTEGER;
@@ -8,9 +8,10 @@ use crate::api::models::load_registered_model;
 use crate::api::proxy::{
     forward_streaming_response, forward_to_endpoint, record_endpoint_request_stats,
     save_request_record, select_available_endpoint_with_queue_for_model, QueueSelection,
+    RoutingHeaders,
 };
 use crate::auth::middleware::ApiKeyAuthContext;
-use crate::balancer::RequestOutcome;
+use crate::balancer::{RequestOutcome, SelectionContext};
 use crate::cloud_metrics;
 use crate::common::error::{CommonError, LbError};
 use crate::common::protocol::{RecordStatus, RequestResponseRecord, RequestType, TpsApiKind};
@@ -63,6 +64,7 @@ struct AnthropicStreamTracker {
     public_model: String,
     stop_reason: Option<&'static str>,
     stop_sequence: Option<String>,
+    throttle: Option<crate::balancer::stream_rate::StreamThrottle>,
     stats_recorded: bool,
 }
 
@@ -122,15 +124,29 @@ async fn handle_messages(
         Err(response) => return Ok(response),
     };
 
-    proxy_local_anthropic_messages(
+    let mut selection =
+        SelectionContext::from_request(&headers, auth_ctx.as_ref().map(|axum::Extension(ctx)| ctx));
+    selection.client_ip = Some(crate::common::ip::resolve_trusted_client_ip(
+        &addr, &headers,
+    ));
+    // プロンプトキャッシュ: 同じプレフィックスのリクエストを同じエンドポイントへ寄せる
+    super::prompt_cache::apply_prefix_affinity(&mut selection, &model, &request_body);
+    let mut routed: Option<RoutingHeaders> = None;
+    let mut response = proxy_local_anthropic_messages(
         &state,
         request_body,
         model,
         converted,
         client_ip,
         api_key_id,
+        &selection,
+        &mut routed,
     )
-    .await
+    .await?;
+    if let Some(routed) = routed {
+        routed.apply(&mut response);
+    }
+    Ok(response)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -258,6 +274,13 @@ async fn proxy_anthropic_cloud_messages(
                 record.output_tokens = usage.output_tokens;
                 record.total_tokens = usage.total_tokens;
             }
+            if let Some(cache) = super::prompt_cache::usage_from_response(&body) {
+                crate::metrics::exporter::record_prompt_cache_usage(
+                    endpoint_id,
+                    cache.input_tokens,
+                    cache.cached_tokens,
+                );
+            }
         } else {
             record.status = RecordStatus::Error {
                 message: body
@@ -278,6 +301,7 @@ async fn proxy_anthropic_cloud_messages(
     Ok(build_response_from_upstream(status, &headers, bytes))
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn proxy_local_anthropic_messages(
     state: &AppState,
     request_body: Value,
@@ -285,6 +309,8 @@ async fn proxy_local_anthropic_messages(
     converted: ConvertedAnthropicRequest,
     client_ip: Option<IpAddr>,
     api_key_id: Option<Uuid>,
+    selection: &SelectionContext,
+    routed: &mut Option<RoutingHeaders>,
 ) -> Result<Response, AppError> {
     if state
         .endpoint_registry
@@ -302,7 +328,7 @@ async fn proxy_local_anthropic_messages(
         }
     }
 
-    let queue_config = state.queue_config;
+    let queue_config = crate::config::effective_queue_config(state.queue_config);
     let request_type = RequestType::AnthropicMessages;
     let tps_api_kind = Some(TpsApiKind::ChatCompletions);
     let mut queued_wait_ms = None;
@@ -312,6 +338,7 @@ async fn proxy_local_anthropic_messages(
         queue_config,
         &model,
         tps_api_kind,
+        selection,
     )
     .await
     {
@@ -396,9 +423,19 @@ async fn proxy_local_anthropic_messages(
     let endpoint_id = endpoint.id;
     let endpoint_name = endpoint.name.clone();
     let endpoint_type = endpoint.endpoint_type;
+    *routed = Some(RoutingHeaders {
+        endpoint: endpoint_name.clone(),
+        model: converted
+            .openai_payload
+            .get("model")
+            .and_then(Value::as_str)
+            .unwrap_or(&model)
+            .to_string(),
+        retries: 0,
+    });
     let request_lease = state
         .load_manager
-        .begin_request(endpoint_id)
+        .begin_request(endpoint_id, selection.principal.as_ref())
         .await
         .map_err(AppError::from)?;
     let body_bytes = serde_json::to_vec(&converted.openai_payload).map_err(|err| {
@@ -411,10 +448,12 @@ async fn proxy_local_anthropic_messages(
 
     let upstream = match forward_to_endpoint(
         &state.http_client,
+        &state.load_manager,
         &endpoint,
         "/v1/chat/completions",
         body_bytes,
         converted.stream,
+        selection.priority,
     )
     .await
     {
@@ -479,7 +518,7 @@ async fn proxy_local_anthropic_messages(
             .map_err(AppError::from)?;
 
         if succeeded {
-            update_inference_latency(&state.endpoint_registry, endpoint_id, duration);
+            update_inference_latency(state, endpoint_id, duration);
         } else {
             record_endpoint_request_stats(
                 state.endpoint_registry.clone(),
@@ -539,6 +578,10 @@ async fn proxy_local_anthropic_messages(
             state.endpoint_registry.clone(),
             state.load_manager.clone(),
             state.event_bus.clone(),
+            state
+                .load_manager
+                .stream_throttle_for(selection.principal.as_ref(), selection.client_ip)
+                .await,
         );
         if let Some(wait_ms) = queued_wait_ms {
             add_queue_headers(&mut response, wait_ms);
@@ -633,7 +676,7 @@ async fn proxy_local_anthropic_messages(
                 .complete_with_tokens(RequestOutcome::Success, duration, Some(token_usage.clone()))
                 .await
                 .map_err(AppError::from)?;
-            update_inference_latency(&state.endpoint_registry, endpoint_id, duration);
+            update_inference_latency(state, endpoint_id, duration);
 
             let output_tokens = token_usage.output_tokens.unwrap_or(0) as u64;
             let duration_ms = if output_tokens > 0 {
@@ -735,6 +778,7 @@ fn transform_openai_streaming_response_to_anthropic(
     endpoint_registry: crate::registry::endpoints::EndpointRegistry,
     load_manager: crate::balancer::LoadManager,
     event_bus: crate::events::SharedEventBus,
+    throttle: Option<crate::balancer::StreamThrottle>,
 ) -> Response {
     let headers = response.headers().clone();
     let mut accumulator = StreamingTokenAccumulator::new(&model_id);
@@ -760,12 +804,24 @@ fn transform_openai_streaming_response_to_anthropic(
         public_model: model_id,
         stop_reason: None,
         stop_sequence: None,
+        throttle,
         stats_recorded: false,
     };
 
     let transformed_stream = futures::stream::try_unfold(state, |mut state| async move {
         loop {
             if let Some(chunk) = state.output_queue.pop_front() {
+                // 出力レート上限: 送出を待つ間はアップストリームを読み進めない
+                if let Some(throttle) = state.throttle.as_mut() {
+                    let delay = throttle.delay_for(
+                        Instant::now(),
+                        chunk.len(),
+                        crate::balancer::stream_rate::count_stream_tokens(&chunk),
+                    );
+                    if !delay.is_zero() {
+                        tokio::time::sleep(delay).await;
+                    }
+                }
                 return Ok(Some((chunk, state)));
             }
 
@@ -1628,76 +1684,22 @@ fn extract_client_info(
     auth_ctx: &Option<axum::Extension<ApiKeyAuthContext>>,
 ) -> (Option<IpAddr>, Option<Uuid>) {
     let client_ip = Some(
-        extract_client_ip_from_headers(headers)
+        crate::common::ip::extract_client_ip_from_headers(headers)
             .unwrap_or_else(|| crate::common::ip::normalize_socket_ip(addr)),
     );
     let api_key_id = auth_ctx.as_ref().map(|ext| ext.0.id);
     (client_ip, api_key_id)
 }
 
-fn extract_client_ip_from_headers(headers: &HeaderMap) -> Option<IpAddr> {
-    extract_x_forwarded_for(headers).or_else(|| extract_forwarded_for(headers))
-}
-
-fn extract_x_forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
-    let value = headers.get("x-forwarded-for")?.to_str().ok()?;
-    value
-        .split(',')
-        .map(str::trim)
-        .find_map(parse_client_ip_from_forwarded_value)
-}
-
-fn extract_forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
-    let value = headers.get("forwarded")?.to_str().ok()?;
-    value.split(',').find_map(|entry| {
-        entry
-            .split(';')
-            .filter_map(|pair| pair.split_once('='))
-            .find_map(|(key, value)| {
-                if key.trim().eq_ignore_ascii_case("for") {
-                    parse_client_ip_from_forwarded_value(value.trim())
-                } else {
-                    None
-                }
-            })
-    })
-}
-
-fn parse_client_ip_from_forwarded_value(value: &str) -> Option<IpAddr> {
-    let trimmed = value.trim().trim_matches('"');
-    if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("unknown") || trimmed.starts_with('_') {
-        return None;
-    }
-
-    let host = if let Some(stripped) = trimmed.strip_prefix('[') {
-        stripped.split(']').next().unwrap_or_default().trim()
-    } else {
-        trimmed
-    };
-
-    if let Ok(ip) = host.parse::<IpAddr>() {
-        return Some(crate::common::ip::normalize_ip(ip));
-    }
-
-    if let Some((ip_candidate, _port)) = host.rsplit_once(':') {
-        if !ip_candidate.contains(':') {
-            if let Ok(ip) = ip_candidate.parse::<IpAddr>() {
-                return Some(crate::common::ip::normalize_ip(ip));
-            }
-        }
-    }
-
-    None
-}
-
-fn update_inference_latency(
-    registry: &crate::registry::endpoints::EndpointRegistry,
-    endpoint_id: Uuid,
-    duration: std::time::Duration,
-) {
-    let registry = registry.clone();
+fn update_inference_latency(state: &AppState, endpoint_id: Uuid, duration: std::time::Duration) {
+    let registry = state.endpoint_registry.clone();
+    let load_manager = state.load_manager.clone();
     let latency_ms = duration.as_millis() as f64;
     tokio::spawn(async move {
+        // 外れ値は EMA に入れる前に直近分布のパーセンタイルへクリップする
+        let latency_ms = load_manager
+            .filter_latency_sample(endpoint_id, latency_ms)
+            .await;
         if let Err(err) = registry
             .update_inference_latency(endpoint_id, latency_ms)
             .await
@@ -2,7 +2,9 @@
 //!
 //! 認証済みユーザーが自分自身のAPIキーを管理するためのAPI。
 
-use crate::common::auth::{ApiKey, ApiKeyPermission, ApiKeyWithPlaintext, Claims, UserRole};
+use crate::common::auth::{
+    ApiKey, ApiKeyPermission, ApiKeyScope, ApiKeyWithPlaintext, Claims, UserRole,
+};
 use crate::common::error::{CommonError, LbError};
 use crate::AppState;
 use axum::{
@@ -26,6 +28,9 @@ pub struct CreateApiKeyRequest {
     /// 付与する権限（adminのみ指定可）
     #[serde(default)]
     pub permissions: Option<Vec<ApiKeyPermission>>,
+    /// 権限プリセット（`read-only` / `inference` / `admin`、`permissions` と併用不可）
+    #[serde(default)]
+    pub scope: Option<ApiKeyScope>,
     /// 旧互換: `scopes` は廃止
     #[serde(default)]
     pub scopes: Option<serde_json::Value>,
@@ -48,6 +53,8 @@ pub struct ApiKeyResponse {
     pub expires_at: Option<String>,
     /// 付与された権限
     pub permissions: Vec<ApiKeyPermission>,
+    /// 失効日時（失効済みの場合のみ）
+    pub revoked_at: Option<String>,
 }
 
 impl From<ApiKey> for ApiKeyResponse {
@@ -60,6 +67,7 @@ impl From<ApiKey> for ApiKeyResponse {
             created_at: api_key.created_at.to_rfc3339(),
             expires_at: api_key.expires_at.map(|dt| dt.to_rfc3339()),
             permissions: api_key.permissions,
+            revoked_at: api_key.revoked_at.map(|dt| dt.to_rfc3339()),
         }
     }
 }
@@ -124,12 +132,23 @@ fn default_viewer_api_key_permissions() -> Vec<ApiKeyPermission> {
 fn resolve_permissions_for_role(
     role: UserRole,
     requested_permissions: Option<Vec<ApiKeyPermission>>,
+    requested_scope: Option<ApiKeyScope>,
 ) -> Result<Vec<ApiKeyPermission>, Response> {
+    if requested_permissions.is_some() && requested_scope.is_some() {
+        return Err(AppError(LbError::Common(CommonError::Validation(
+            "Fields 'permissions' and 'scope' cannot be used together.".to_string(),
+        )))
+        .into_response());
+    }
+
     match role {
         UserRole::Admin => {
+            if let Some(scope) = requested_scope {
+                return Ok(scope.permissions());
+            }
             let permissions = requested_permissions.ok_or_else(|| {
                 AppError(LbError::Common(CommonError::Validation(
-                    "Field 'permissions' is required for admin users.".to_string(),
+                    "Field 'permissions' or 'scope' is required for admin users.".to_string(),
                 )))
                 .into_response()
             })?;
@@ -152,6 +171,13 @@ fn resolve_permissions_for_role(
                 .into_response());
             }
 
+            if requested_scope.is_some_and(|scope| scope != ApiKeyScope::Inference) {
+                return Err(AppError(LbError::Common(CommonError::Validation(
+                    "Viewer users can only use the 'inference' scope.".to_string(),
+                )))
+                .into_response());
+            }
+
             Ok(default_viewer_api_key_permissions())
         }
     }
@@ -217,7 +243,8 @@ pub async fn create_api_key(
         .into_response());
     }
 
-    let permissions = resolve_permissions_for_role(claims.role, request.permissions)?;
+    let permissions =
+        resolve_permissions_for_role(claims.role, request.permissions, request.scope)?;
     let user_id = parse_user_id_from_claims(&claims)?;
     let expires_at = parse_expires_at(request.expires_at.as_ref())?;
 
@@ -269,6 +296,29 @@ pub async fn update_api_key(
     }
 }
 
+/// POST /api/me/api-keys/:id/revoke - 自分のAPIキー失効
+///
+/// 失効したキーは一覧に残り、以降の認証は即座に拒否される。
+pub async fn revoke_api_key(
+    Extension(claims): Extension<Claims>,
+    State(app_state): State<AppState>,
+    Path(key_id): Path<Uuid>,
+) -> Result<Json<ApiKeyResponse>, Response> {
+    let user_id = parse_user_id_from_claims(&claims)?;
+
+    let revoked = crate::db::api_keys::revoke_by_creator(&app_state.db_pool, key_id, user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke API key: {}", e);
+            AppError(e).into_response()
+        })?;
+
+    match revoked {
+        Some(api_key) => Ok(Json(ApiKeyResponse::from(api_key))),
+        None => Err(AppError(LbError::NotFound("API key not found".to_string())).into_response()),
+    }
+}
+
 /// DELETE /api/me/api-keys/:id - 自分のAPIキー削除
 pub async fn delete_api_key(
     Extension(claims): Extension<Claims>,
@@ -388,6 +438,7 @@ mod tests {
             created_by: Uuid::new_v4(),
             created_at: now,
             expires_at: None,
+            revoked_at: None,
             permissions: vec![ApiKeyPermission::OpenaiInference],
         };
         let resp = ApiKeyResponse::from(key.clone());
@@ -411,6 +462,7 @@ mod tests {
             created_by: Uuid::new_v4(),
             created_at: now,
             expires_at: Some(now),
+            revoked_at: None,
             permissions: vec![],
         };
         let resp = ApiKeyResponse::from(key);
@@ -431,6 +483,7 @@ mod tests {
                 ApiKeyPermission::OpenaiInference,
                 ApiKeyPermission::OpenaiModelsRead,
             ],
+            revoked_at: None,
         };
         let json = serde_json::to_string(&resp).unwrap();
         assert!(json.contains("\"id\":\"key-id-1\""));
@@ -499,6 +552,7 @@ mod tests {
                     created_at: "2025-01-01T00:00:00+00:00".to_string(),
                     expires_at: None,
                     permissions: vec![],
+                    revoked_at: None,
                 },
                 ApiKeyResponse {
                     id: "2".to_string(),
@@ -508,6 +562,7 @@ mod tests {
                     created_at: "2025-06-01T00:00:00+00:00".to_string(),
                     expires_at: Some("2026-06-01T00:00:00+00:00".to_string()),
                     permissions: vec![ApiKeyPermission::OpenaiInference],
+                    revoked_at: None,
                 },
             ],
         };
@@ -536,6 +591,7 @@ mod tests {
                 ApiKeyPermission::OpenaiInference,
                 ApiKeyPermission::EndpointsRead,
             ]),
+            None,
         );
         assert!(perms.is_ok());
         let perms = perms.unwrap();
@@ -544,19 +600,19 @@ mod tests {
 
     #[test]
     fn resolve_permissions_admin_without_permissions_fails() {
-        let result = resolve_permissions_for_role(UserRole::Admin, None);
+        let result = resolve_permissions_for_role(UserRole::Admin, None, None);
         assert!(result.is_err());
     }
 
     #[test]
     fn resolve_permissions_admin_empty_permissions_fails() {
-        let result = resolve_permissions_for_role(UserRole::Admin, Some(vec![]));
+        let result = resolve_permissions_for_role(UserRole::Admin, Some(vec![]), None);
         assert!(result.is_err());
     }
 
     #[test]
     fn resolve_permissions_viewer_without_permissions() {
-        let perms = resolve_permissions_for_role(UserRole::Viewer, None);
+        let perms = resolve_permissions_for_role(UserRole::Viewer, None, None);
         assert!(perms.is_ok());
         let perms = perms.unwrap();
         assert_eq!(perms, default_viewer_api_key_permissions());
@@ -567,10 +623,36 @@ mod tests {
         let result = resolve_permissions_for_role(
             UserRole::Viewer,
             Some(vec![ApiKeyPermission::OpenaiInference]),
+            None,
         );
         assert!(result.is_err());
     }
 
+    #[test]
+    fn resolve_permissions_with_scope() {
+        let perms =
+            resolve_permissions_for_role(UserRole::Admin, None, Some(ApiKeyScope::ReadOnly))
+                .unwrap();
+        assert_eq!(perms, ApiKeyScope::ReadOnly.permissions());
+
+        // permissions と scope の併用は不可
+        assert!(resolve_permissions_for_role(
+            UserRole::Admin,
+            Some(vec![ApiKeyPermission::OpenaiInference]),
+            Some(ApiKeyScope::Admin),
+        )
+        .is_err());
+
+        // viewer は inference スコープのみ
+        let perms =
+            resolve_permissions_for_role(UserRole::Viewer, None, Some(ApiKeyScope::Inference))
+                .unwrap();
+        assert_eq!(perms, default_viewer_api_key_permissions());
+        assert!(
+            resolve_permissions_for_role(UserRole::Viewer, None, Some(ApiKeyScope::Admin)).is_err()
+        );
+    }
+
     // --- parse_user_id_from_claims ---
 
     #[test]
@@ -2,6 +2,7 @@
 //!
 //! OpenAI互換の音声認識（ASR）・音声合成（TTS）API
 
+use crate::balancer::priority::{apply_priority_header, priority_from_headers};
 use crate::common::{
     error::LbError,
     protocol::{RequestResponseRecord, RequestType, SpeechRequest},
@@ -26,7 +27,7 @@ use crate::{
         error::AppError,
         model_name::parse_quantized_model_name,
         models::load_registered_model,
-        proxy::{forward_streaming_response, save_request_record},
+        proxy::{forward_routed_response, save_request_record, RoutingHeaders},
     },
     auth::middleware::ApiKeyAuthContext,
     common::ip::{normalize_ip, normalize_socket_ip},
@@ -331,7 +332,11 @@ pub async fn transcriptions(
         form = form.text("response_format", fmt);
     }
 
-    let response = match client.post(&url).multipart(form).send().await {
+    let response = match apply_priority_header(client.post(&url), priority_from_headers(&headers))
+        .multipart(form)
+        .send()
+        .await
+    {
         Ok(r) => r,
         Err(e) => {
             return openai_error(
@@ -361,9 +366,8 @@ pub async fn transcriptions(
     save_request_record(state.request_history.clone(), record);
 
     // レスポンスを転送
-    forward_streaming_response(response)
+    forward_routed_response(response, &RoutingHeaders::direct(&backend.0, &model))
         .map_err(AppError::from)
-        .map(|r| r.into_response())
 }
 
 /// POST /v1/audio/speech - 音声合成（TTS）
@@ -432,7 +436,11 @@ pub async fn speech(
     let client = &state.http_client;
     let url = backend.url("/v1/audio/speech");
 
-    let response = match client.post(&url).json(&payload).send().await {
+    let response = match apply_priority_header(client.post(&url), priority_from_headers(&headers))
+        .json(&payload)
+        .send()
+        .await
+    {
         Ok(r) => r,
         Err(e) => {
             return openai_error(
@@ -461,6 +469,7 @@ pub async fn speech(
 
     save_request_record(state.request_history.clone(), record);
 
+    let routed = RoutingHeaders::direct(&backend.0, &payload.model);
     if status.is_success() {
         // 音声バイナリをストリーミング転送
         // reqwestとaxumで異なるhttp crateバージョンを使うため、文字列経由で変換
@@ -474,17 +483,16 @@ pub async fn speech(
         let stream = response.bytes_stream();
         let body = Body::from_stream(stream);
 
-        Ok(Response::builder()
+        let mut response = Response::builder()
             .status(StatusCode::OK)
             .header(header::CONTENT_TYPE, content_type)
             .body(body)
-            .expect("Response builder should not fail with valid status and body")
-            .into_response())
+            .expect("Response builder should not fail with valid status and body");
+        routed.apply(&mut response);
+        Ok(response)
     } else {
         // エラーレスポンスを転送
-        forward_streaming_response(response)
-            .map_err(AppError::from)
-            .map(|r| r.into_response())
+        forward_routed_response(response, &routed).map_err(AppError::from)
     }
 }
 
@@ -3,14 +3,23 @@
 //! `/api/dashboard/audit-logs` 系のエンドポイント
 
 use super::error::AppError;
+use crate::audit::export::{
+    parse_date_or_datetime, ExportRecord, ExportSource, CSV_COLUMNS, EXPORT_PAGE_SIZE,
+};
 use crate::audit::hash_chain::{self, ChainVerificationResult};
 use crate::audit::types::{AuditLogEntry, AuditLogFilter};
+use crate::common::error::{CommonError, LbError};
+use crate::db::audit_log::AuditLogStorage;
 use crate::AppState;
 use axum::{
+    body::{Body, Bytes},
     extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
     Json,
 };
 use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 
 /// 監査ログ一覧取得のクエリパラメータ
@@ -134,7 +143,7 @@ pub async fn list_audit_logs(
 
     let (items, total) = match (include_archive, state.audit_archive_pool.as_ref()) {
         (true, Some(archive_pool)) => {
-            query_with_archive(storage, archive_pool, &filter, search_text.as_deref()).await?
+            AuditLogStorage::search_across(storage, archive_pool, &filter).await?
         }
         _ => {
             if let Some(ref query) = search_text {
@@ -157,58 +166,6 @@ pub async fn list_audit_logs(
     }))
 }
 
-async fn query_with_archive(
-    storage: &crate::db::audit_log::AuditLogStorage,
-    archive_pool: &sqlx::SqlitePool,
-    filter: &AuditLogFilter,
-    search_text: Option<&str>,
-) -> Result<(Vec<AuditLogEntry>, i64), AppError> {
-    let page = filter.page.unwrap_or(1).max(1);
-    let per_page = filter.per_page.unwrap_or(50).max(1);
-    let fetch_limit = page.saturating_mul(per_page);
-
-    let mut merged_filter = filter.clone();
-    merged_filter.page = Some(1);
-    merged_filter.per_page = Some(fetch_limit);
-
-    let (main_items, main_total, archive_items, archive_total) = if let Some(query) = search_text {
-        (
-            storage.search_fts(query, &merged_filter).await?,
-            storage.count_fts(query, filter).await?,
-            storage
-                .search_fts_archive(query, &merged_filter, archive_pool)
-                .await?,
-            storage
-                .count_fts_archive(query, filter, archive_pool)
-                .await?,
-        )
-    } else {
-        (
-            storage.query(&merged_filter).await?,
-            storage.count(filter).await?,
-            storage.query_archive(&merged_filter, archive_pool).await?,
-            storage.count_archive(filter, archive_pool).await?,
-        )
-    };
-
-    let mut all_items = Vec::with_capacity(main_items.len() + archive_items.len());
-    all_items.extend(main_items);
-    all_items.extend(archive_items);
-    all_items.sort_by(|a, b| {
-        b.timestamp
-            .cmp(&a.timestamp)
-            .then_with(|| b.id.cmp(&a.id))
-            .then_with(|| b.request_path.cmp(&a.request_path))
-    });
-
-    let offset = ((page - 1) * per_page) as usize;
-    let limit = per_page as usize;
-    let paged_items = all_items.into_iter().skip(offset).take(limit).collect();
-    let total = main_total + archive_total;
-
-    Ok((paged_items, total))
-}
-
 /// GET /api/dashboard/audit-logs/stats - 監査ログ統計取得
 pub async fn get_audit_log_stats(
     State(state): State<AppState>,
@@ -248,6 +205,197 @@ pub async fn verify_hash_chain(
     Ok(Json(result))
 }
 
+/// 監査ログエクスポートのクエリパラメータ
+#[derive(Debug, Deserialize)]
+pub struct AuditExportParams {
+    /// 出力形式（`ndjson`（デフォルト）/ `csv`）
+    pub format: Option<String>,
+    /// 開始日時（RFC 3339 または YYYY-MM-DD、当日0時から）
+    pub from: Option<String>,
+    /// 終了日時（RFC 3339 または YYYY-MM-DD、当日末まで）
+    pub to: Option<String>,
+}
+
+/// エクスポート出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AuditExportFormat {
+    Ndjson,
+    Csv,
+}
+
+/// NDJSON 先頭のメタ行
+#[derive(Serialize)]
+struct AuditExportMeta<'a> {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    /// メインDBのハッシュチェーン検証結果
+    main_chain: &'a ChainVerificationResult,
+    /// アーカイブDBのハッシュチェーン検証結果
+    archive_chain: Option<&'a ChainVerificationResult>,
+}
+
+fn export_validation_error(message: String) -> AppError {
+    AppError(LbError::Common(CommonError::Validation(message)))
+}
+
+/// GET /api/audit/export - 監査ログのストリーミングエクスポート
+///
+/// 期間内のエントリをアーカイブDB → メインDBの順（それぞれID昇順）に、
+/// ページ単位のチャンクで NDJSON または CSV として返す。
+/// 出力前に各DBのハッシュチェーンを検証し、各レコードの `chain_verified` 列に
+/// 「検証に成功したチェーンのバッチに属するか」を出力する（NDJSON は先頭にメタ行も出力）。
+pub async fn export_audit_logs(
+    State(state): State<AppState>,
+    Query(params): Query<AuditExportParams>,
+) -> Result<Response, AppError> {
+    let format = match params.format.as_deref().unwrap_or("ndjson") {
+        "ndjson" => AuditExportFormat::Ndjson,
+        "csv" => AuditExportFormat::Csv,
+        other => {
+            return Err(export_validation_error(format!(
+                "Unsupported format: '{}'. Supported formats are 'ndjson' and 'csv'.",
+                other
+            )))
+        }
+    };
+    let from = params
+        .from
+        .as_deref()
+        .map(|v| parse_date_or_datetime(v, false))
+        .transpose()
+        .map_err(export_validation_error)?;
+    let to = params
+        .to
+        .as_deref()
+        .map(|v| parse_date_or_datetime(v, true))
+        .transpose()
+        .map_err(export_validation_error)?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(export_validation_error(
+                "'from' must not be later than 'to'".to_string(),
+            ));
+        }
+    }
+
+    let storage = state.audit_log_storage.as_ref().clone();
+    let archive_pool = state.audit_archive_pool.clone();
+    let main_chain = hash_chain::verify_chain(&storage).await?;
+    let archive_chain = match &archive_pool {
+        Some(pool) => Some(hash_chain::verify_chain(&AuditLogStorage::new(pool.clone())).await?),
+        None => None,
+    };
+
+    let header_chunk = match format {
+        AuditExportFormat::Ndjson => {
+            let meta = AuditExportMeta {
+                from,
+                to,
+                main_chain: &main_chain,
+                archive_chain: archive_chain.as_ref(),
+            };
+            let mut line = serde_json::to_vec(&serde_json::json!({ "_meta": meta }))
+                .map_err(|e| AppError(LbError::Internal(e.to_string())))?;
+            line.push(b'\n');
+            line
+        }
+        AuditExportFormat::Csv => {
+            let mut columns: Vec<&str> = CSV_COLUMNS.to_vec();
+            columns.push("chain_verified");
+            encode_csv_rows(std::iter::once(columns)).map_err(AppError)?
+        }
+    };
+
+    let main_valid = main_chain.valid;
+    let archive_valid = archive_chain.as_ref().is_some_and(|result| result.valid);
+    let filter = AuditLogFilter {
+        time_from: from,
+        time_to: to,
+        ..Default::default()
+    };
+    let pages = storage
+        .export_stream(filter, archive_pool, EXPORT_PAGE_SIZE)
+        .map_ok(move |(source, page)| {
+            let chain_valid = match source {
+                ExportSource::Main => main_valid,
+                ExportSource::Archive => archive_valid,
+            };
+            encode_export_page(format, source, &page, chain_valid)
+        })
+        .and_then(futures::future::ready);
+    let body = futures::stream::once(futures::future::ready(Ok::<_, LbError>(Bytes::from(
+        header_chunk,
+    ))))
+    .chain(pages)
+    .map_err(|e| {
+        tracing::error!("Audit log export aborted: {}", e);
+        std::io::Error::other(e.to_string())
+    });
+
+    let (content_type, filename) = match format {
+        AuditExportFormat::Ndjson => ("application/x-ndjson", "audit-logs.ndjson"),
+        AuditExportFormat::Csv => ("text/csv; charset=utf-8", "audit-logs.csv"),
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+/// 1ページ分のエントリを出力チャンクに変換する
+fn encode_export_page(
+    format: AuditExportFormat,
+    source: ExportSource,
+    page: &[AuditLogEntry],
+    chain_valid: bool,
+) -> Result<Bytes, LbError> {
+    let records = page.iter().map(|entry| ExportRecord {
+        entry,
+        source,
+        record_hash: None,
+        chain_verified: Some(chain_valid && entry.batch_id.is_some()),
+    });
+    let chunk = match format {
+        AuditExportFormat::Ndjson => {
+            let mut buf = Vec::new();
+            for record in records {
+                serde_json::to_writer(&mut buf, &record)
+                    .map_err(|e| LbError::Internal(e.to_string()))?;
+                buf.push(b'\n');
+            }
+            buf
+        }
+        AuditExportFormat::Csv => encode_csv_rows(records.map(|record| record.csv_row()))?,
+    };
+    Ok(Bytes::from(chunk))
+}
+
+fn encode_csv_rows<I, R>(rows: I) -> Result<Vec<u8>, LbError>
+where
+    I: IntoIterator<Item = R>,
+    R: IntoIterator,
+    R::Item: AsRef<[u8]>,
+{
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    for row in rows {
+        writer
+            .write_record(row)
+            .map_err(|e| LbError::Internal(e.to_string()))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| LbError::Internal(e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -694,6 +842,112 @@ mod tests {
         assert_eq!(response.items.len(), 2);
     }
 
+    async fn export_body(app: Router, uri: &str) -> (StatusCode, Option<String>, String) {
+        let res = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = res.status();
+        let content_type = res
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(res.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        (
+            status,
+            content_type,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_export_audit_logs_ndjson_spans_archive_and_main() {
+        let pool = create_test_pool().await;
+        AuditLogStorage::new(pool.clone())
+            .insert_batch(&[create_test_entry(
+                "/api/main-only",
+                "GET",
+                ActorType::User,
+                Some("admin"),
+            )])
+            .await
+            .unwrap();
+        let archive_pool = crate::db::audit_log::create_archive_pool(":memory:")
+            .await
+            .unwrap();
+        AuditLogStorage::new(archive_pool.clone())
+            .insert_batch(&[create_test_entry(
+                "/api/archive-only",
+                "GET",
+                ActorType::User,
+                Some("admin"),
+            )])
+            .await
+            .unwrap();
+
+        let state = create_test_state_with_archive(pool, Some(archive_pool)).await;
+        let app = Router::new()
+            .route("/audit/export", get(export_audit_logs))
+            .with_state(state);
+
+        let (status, content_type, body) = export_body(app, "/audit/export?from=2000-01-01").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("application/x-ndjson"));
+        let lines: Vec<serde_json::Value> = body
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["_meta"]["main_chain"]["valid"], true);
+        assert_eq!(lines[0]["_meta"]["archive_chain"]["valid"], true);
+        assert_eq!(lines[1]["request_path"], "/api/archive-only");
+        assert_eq!(lines[1]["source"], "archive");
+        assert_eq!(lines[2]["request_path"], "/api/main-only");
+        assert_eq!(lines[2]["source"], "main");
+        // バッチ未割当のエントリはチェーン検証の対象外
+        assert_eq!(lines[2]["chain_verified"], false);
+    }
+
+    #[tokio::test]
+    async fn test_export_audit_logs_csv_and_validation() {
+        let pool = create_test_pool().await;
+        AuditLogStorage::new(pool.clone())
+            .insert_batch(&[
+                create_test_entry("/api/users", "GET", ActorType::User, Some("admin")),
+                create_test_entry("/api/endpoints", "POST", ActorType::User, Some("admin")),
+            ])
+            .await
+            .unwrap();
+
+        let state = create_test_state(pool).await;
+        let app = Router::new()
+            .route("/audit/export", get(export_audit_logs))
+            .with_state(state);
+
+        let (status, content_type, body) =
+            export_body(app.clone(), "/audit/export?format=csv").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("text/csv; charset=utf-8"));
+        let mut reader = csv::Reader::from_reader(body.as_bytes());
+        let headers = reader.headers().unwrap().clone();
+        assert_eq!(headers.len(), CSV_COLUMNS.len() + 1);
+        assert_eq!(&headers[CSV_COLUMNS.len()], "chain_verified");
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[0][3], "/api/users");
+
+        for uri in [
+            "/audit/export?format=xml",
+            "/audit/export?from=yesterday",
+            "/audit/export?from=2026-02-01&to=2026-01-01",
+        ] {
+            let (status, _, _) = export_body(app.clone(), uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+
     #[tokio::test]
     async fn test_list_audit_logs_include_archive_with_search() {
         let pool = create_test_pool().await;
@@ -134,7 +134,7 @@ pub async fn login(
         let token = crate::auth::jwt::create_jwt(
             &dev_user_id,
             crate::common::auth::UserRole::Admin,
-            &app_state.jwt_secret,
+            &crate::jwt_secret::signing_secret(&app_state.jwt_secret),
             false,
         )
         .map_err(|e| {
@@ -214,7 +214,7 @@ pub async fn login(
     let token = crate::auth::jwt::create_jwt(
         &user.id.to_string(),
         user.role,
-        &app_state.jwt_secret,
+        &crate::jwt_secret::signing_secret(&app_state.jwt_secret),
         user.must_change_password,
     )
     .map_err(|e| {
@@ -617,6 +617,104 @@ pub async fn create_invitation(
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// 旧JWTシークレット猶予期間の上限（30日）
+const MAX_JWT_ROTATION_GRACE_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// JWTシークレットローテーションリクエスト
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateJwtSecretRequest {
+    /// 旧シークレットの猶予期間（秒）。省略時はJWT有効期限と同じ24時間
+    #[serde(default)]
+    pub grace_period_secs: Option<u64>,
+}
+
+/// JWTシークレットローテーションレスポンス
+#[derive(Debug, Serialize)]
+pub struct RotateJwtSecretResponse {
+    /// ローテーション日時
+    pub rotated_at: String,
+    /// 旧シークレットの失効日時
+    pub previous_secret_expires_at: String,
+    /// 検証に使用される旧シークレット数
+    pub retained_previous_secrets: usize,
+}
+
+/// POST /api/admin/rotate-jwt-secret - JWTシークレットのローテーション
+///
+/// 新しいシークレットを生成して以降のJWT署名に使用する。
+/// 旧シークレットは猶予期間中は検証に使用され、期限後に破棄される。
+///
+/// # Returns
+/// * `200 OK` - ローテーション結果（シークレット自体は含まない）
+/// * `400 Bad Request` - 環境変数でシークレットが指定されている
+/// * `403 Forbidden` - admin ロール権限がない
+pub async fn rotate_jwt_secret(
+    Extension(claims): Extension<Claims>,
+    body: axum::body::Bytes,
+) -> Result<Response, Response> {
+    if claims.role != UserRole::Admin {
+        return Err(AppError(LbError::Authorization(
+            "Only admin can rotate the JWT secret".to_string(),
+        ))
+        .into_response());
+    }
+
+    // ボディは省略可能
+    let request: RotateJwtSecretRequest = if body.is_empty() {
+        RotateJwtSecretRequest::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|e| {
+            AppError(LbError::Common(CommonError::Validation(format!(
+                "Invalid request body: {}",
+                e
+            ))))
+            .into_response()
+        })?
+    };
+    let grace_secs = request
+        .grace_period_secs
+        .unwrap_or(crate::jwt_secret::DEFAULT_ROTATION_GRACE_SECS);
+    if grace_secs > MAX_JWT_ROTATION_GRACE_SECS {
+        return Err(AppError(LbError::Common(CommonError::Validation(format!(
+            "grace_period_secs must be at most {}",
+            MAX_JWT_ROTATION_GRACE_SECS
+        ))))
+        .into_response());
+    }
+    let grace = chrono::Duration::seconds(grace_secs as i64);
+
+    let rotation = crate::jwt_secret::rotate_jwt_secret(grace).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::Unsupported {
+            AppError(LbError::Common(CommonError::Validation(e.to_string()))).into_response()
+        } else {
+            tracing::error!("Failed to rotate JWT secret: {}", e);
+            AppError(LbError::Internal(format!(
+                "Failed to rotate JWT secret: {}",
+                e
+            )))
+            .into_response()
+        }
+    })?;
+
+    tracing::info!("JWT secret rotated by admin: {}", claims.sub);
+
+    let body = RotateJwtSecretResponse {
+        rotated_at: rotation.rotated_at.to_rfc3339(),
+        previous_secret_expires_at: rotation.previous_secret_expires_at.to_rfc3339(),
+        retained_previous_secrets: rotation.retained_previous_secrets,
+    };
+    let mut response = (StatusCode::OK, Json(&body)).into_response();
+    response
+        .extensions_mut()
+        .insert(crate::audit::types::AuditDetail(serde_json::json!({
+            "event": "jwt_secret_rotated",
+            "rotated_at": body.rotated_at,
+            "previous_secret_expires_at": body.previous_secret_expires_at,
+            "retained_previous_secrets": body.retained_previous_secrets,
+        })));
+    Ok(response)
+}
+
 /// POST /api/auth/accept-invitation - 招待受け入れ（T-0011）
 ///
 /// ユーザーが招待キーで招待を受け入れ、setup_token を取得
@@ -669,7 +767,7 @@ pub async fn accept_invitation(
     let setup_token = crate::auth::jwt::create_jwt_with_expiration(
         &temp_user_id,
         UserRole::Admin, // 一時的
-        &app_state.jwt_secret,
+        &crate::jwt_secret::signing_secret(&app_state.jwt_secret),
         false,
         3600, // 1時間
     )
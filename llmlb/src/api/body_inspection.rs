@@ -0,0 +1,128 @@
+//! 推論リクエストボディの共通検査レイヤー
+//!
+//! ストリーム数制限・モデル別レートリミット・モデル別同時実行上限・プロンプトフィルタは
+//! いずれもリクエストボディの JSON を参照する。各ミドルウェアが個別にボディを読むと
+//! Content-Type の判定がずれて検査漏れが生じ、同じボディを何度もパースすることになるため、
+//! このレイヤーで一度だけ読み取り、パース結果を [`InspectedBody`] としてリクエスト拡張に載せる。
+//!
+//! Content-Type の判定は [`is_json_content_type`]（axum の `Json` 抽出子と同じ規則）に統一する。
+//! JSON でないボディ（multipart 等）は読まずに素通しする。
+
+use axum::{body::Body, extract::Request, http::StatusCode, middleware::Next, response::Response};
+use serde_json::Value;
+use std::sync::Arc;
+
+use super::openai_util::{is_json_content_type, openai_error_response};
+
+/// パース済みのリクエストボディ（JSON として解釈できた場合のみ拡張に入る）
+#[derive(Clone, Debug)]
+pub struct InspectedBody(pub Arc<Value>);
+
+impl InspectedBody {
+    /// `model` フィールド
+    pub fn model(&self) -> Option<&str> {
+        self.0.get("model").and_then(Value::as_str)
+    }
+
+    /// `stream: true` が指定されているか
+    pub fn is_stream(&self) -> bool {
+        self.0
+            .get("stream")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+}
+
+/// ボディ検査ミドルウェア
+///
+/// ボディを参照する推論ミドルウェア群より外側、APIキー認証より内側に配置する。
+pub async fn inspect_body_middleware(request: Request, next: Next) -> Response {
+    if !is_json_content_type(request.headers()) {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, super::OPENAI_BODY_LIMIT_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return openai_error_response("Request body too large", StatusCode::PAYLOAD_TOO_LARGE)
+        }
+    };
+    if let Ok(payload) = serde_json::from_slice::<Value>(&bytes) {
+        parts.extensions.insert(InspectedBody(Arc::new(payload)));
+    }
+
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    async fn inspect(content_type: &str, body: &'static str) -> Option<String> {
+        let app = Router::new()
+            .route(
+                "/v1/test",
+                post(|request: Request| async move {
+                    let inspected = request.extensions().get::<InspectedBody>().cloned();
+                    let bytes = axum::body::to_bytes(request.into_body(), usize::MAX)
+                        .await
+                        .unwrap();
+                    // 後続のハンドラには元のボディがそのまま届く
+                    assert_eq!(&bytes[..], body.as_bytes());
+                    inspected
+                        .and_then(|body| body.model().map(str::to_string))
+                        .unwrap_or_default()
+                }),
+            )
+            .layer(middleware::from_fn(inspect_body_middleware));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/test")
+                    .header("content-type", content_type)
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        Some(String::from_utf8(bytes.to_vec()).unwrap()).filter(|model| !model.is_empty())
+    }
+
+    #[tokio::test]
+    async fn parses_json_bodies_including_suffixed_and_parameterized_types() {
+        let body = r#"{"model":"m","stream":true}"#;
+        assert_eq!(
+            inspect("application/json", body).await.as_deref(),
+            Some("m")
+        );
+        assert_eq!(
+            inspect("Application/JSON; charset=utf-8", body)
+                .await
+                .as_deref(),
+            Some("m")
+        );
+        assert_eq!(
+            inspect("application/vnd.api+json", body).await.as_deref(),
+            Some("m")
+        );
+        assert_eq!(inspect("text/plain", body).await, None);
+        assert_eq!(inspect("application/json", "not json").await, None);
+    }
+
+    #[test]
+    fn reads_stream_flag() {
+        let inspected = InspectedBody(Arc::new(serde_json::json!({"stream": true})));
+        assert!(inspected.is_stream());
+        assert_eq!(inspected.model(), None);
+        let inspected = InspectedBody(Arc::new(serde_json::json!({"stream": "yes"})));
+        assert!(!inspected.is_stream());
+    }
+}
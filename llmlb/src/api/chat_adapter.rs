@@ -0,0 +1,565 @@
+//! チャット→補完変換アダプタ
+//!
+//! `/v1/completions` しか提供しないレガシーなアップストリーム向けに、
+//! Chat Completions リクエストの `messages` をモデル別プロンプトテンプレートで
+//! 単一プロンプトへ組み立てて `/v1/completions` に送り、応答を chat 形式へ戻す。
+//!
+//! 変換対象は環境変数 `LLMLB_CHAT_TO_COMPLETIONS_MODELS` で指定する
+//! （例: `llama2-*=llama3,legacy-model=chatml,old-*`）。
+//! テンプレート未指定・未定義のモデルは汎用テンプレートを使用する。
+
+use axum::body::{Body, Bytes};
+use axum::http::header;
+use axum::response::Response;
+use futures::{Stream, StreamExt};
+use serde_json::{json, Map, Value};
+use std::io;
+use std::sync::OnceLock;
+
+use crate::balancer::routing_policy::model_pattern_matches;
+
+/// 変換対象モデルを指定する環境変数
+const ADAPTER_MODELS_ENV: &str = "LLMLB_CHAT_TO_COMPLETIONS_MODELS";
+
+/// 起動時に読み込んだ変換設定
+static ADAPTER_CONFIG: OnceLock<ChatAdapterConfig> = OnceLock::new();
+
+/// 補完リクエストへそのまま引き継ぐパラメータ
+const PASSTHROUGH_PARAMS: &[&str] = &[
+    "model",
+    "max_tokens",
+    "temperature",
+    "top_p",
+    "n",
+    "stream",
+    "stream_options",
+    "presence_penalty",
+    "frequency_penalty",
+    "logit_bias",
+    "seed",
+    "user",
+];
+
+/// チャットメッセージを単一プロンプトへ組み立てるテンプレート
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptTemplate {
+    /// テンプレート名
+    pub name: &'static str,
+    /// プロンプト先頭に付与する文字列
+    pub bos: &'static str,
+    /// systemメッセージの前後
+    pub system: (&'static str, &'static str),
+    /// userメッセージの前後
+    pub user: (&'static str, &'static str),
+    /// assistantメッセージの前後
+    pub assistant: (&'static str, &'static str),
+    /// 生成開始位置に付与する文字列
+    pub generation_prompt: &'static str,
+    /// 追加する停止シーケンス
+    pub stop: &'static [&'static str],
+}
+
+/// 汎用テンプレート（未定義モデルのフォールバック）
+pub const GENERIC_TEMPLATE: PromptTemplate = PromptTemplate {
+    name: "generic",
+    bos: "",
+    system: ("### System:\n", "\n\n"),
+    user: ("### User:\n", "\n\n"),
+    assistant: ("### Assistant:\n", "\n\n"),
+    generation_prompt: "### Assistant:\n",
+    stop: &["### User:"],
+};
+
+/// ChatML形式
+pub const CHATML_TEMPLATE: PromptTemplate = PromptTemplate {
+    name: "chatml",
+    bos: "",
+    system: ("<|im_start|>system\n", "<|im_end|>\n"),
+    user: ("<|im_start|>user\n", "<|im_end|>\n"),
+    assistant: ("<|im_start|>assistant\n", "<|im_end|>\n"),
+    generation_prompt: "<|im_start|>assistant\n",
+    stop: &["<|im_end|>"],
+};
+
+/// Llama 3 Instruct形式
+pub const LLAMA3_TEMPLATE: PromptTemplate = PromptTemplate {
+    name: "llama3",
+    bos: "<|begin_of_text|>",
+    system: (
+        "<|start_header_id|>system<|end_header_id|>\n\n",
+        "<|eot_id|>",
+    ),
+    user: ("<|start_header_id|>user<|end_header_id|>\n\n", "<|eot_id|>"),
+    assistant: (
+        "<|start_header_id|>assistant<|end_header_id|>\n\n",
+        "<|eot_id|>",
+    ),
+    generation_prompt: "<|start_header_id|>assistant<|end_header_id|>\n\n",
+    stop: &["<|eot_id|>"],
+};
+
+const BUILTIN_TEMPLATES: &[PromptTemplate] = &[GENERIC_TEMPLATE, CHATML_TEMPLATE, LLAMA3_TEMPLATE];
+
+/// 名前から組み込みテンプレートを取得
+pub fn find_template(name: &str) -> Option<PromptTemplate> {
+    let name = name.trim();
+    BUILTIN_TEMPLATES
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+/// 変換対象モデルの設定（パターン → テンプレート）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChatAdapterConfig {
+    rules: Vec<(String, PromptTemplate)>,
+}
+
+impl ChatAdapterConfig {
+    /// `pattern[=template]` のカンマ区切りをパース
+    ///
+    /// テンプレート未指定・未知の名前は汎用テンプレートにフォールバックする。
+    pub fn parse(value: &str) -> Self {
+        let rules = value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let (pattern, template) = match entry.split_once('=') {
+                    Some((pattern, name)) => (
+                        pattern.trim(),
+                        find_template(name).unwrap_or_else(|| {
+                            tracing::warn!(
+                                template = name.trim(),
+                                "Unknown prompt template, falling back to generic"
+                            );
+                            GENERIC_TEMPLATE
+                        }),
+                    ),
+                    None => (entry, GENERIC_TEMPLATE),
+                };
+                if pattern.is_empty() {
+                    None
+                } else {
+                    Some((pattern.to_string(), template))
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// 環境変数 `LLMLB_CHAT_TO_COMPLETIONS_MODELS` から読み込む
+    pub fn from_env() -> Self {
+        std::env::var(ADAPTER_MODELS_ENV)
+            .map(|value| Self::parse(&value))
+            .unwrap_or_default()
+    }
+
+    /// モデルが変換対象であれば使用するテンプレートを返す
+    pub fn template_for(&self, model: &str) -> Option<PromptTemplate> {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| model_pattern_matches(pattern, model))
+            .map(|(_, template)| *template)
+    }
+}
+
+/// 変換設定を環境変数から読み込んで保持する（起動時に1回呼ぶ）
+pub fn init() {
+    let config = ADAPTER_CONFIG.get_or_init(ChatAdapterConfig::from_env);
+    if !config.rules.is_empty() {
+        tracing::info!(
+            rules = config.rules.len(),
+            "Chat-to-completions adapter enabled"
+        );
+    }
+}
+
+/// 保持している変換設定（未初期化の場合は環境変数から1回だけ読み込む）
+pub fn config() -> &'static ChatAdapterConfig {
+    ADAPTER_CONFIG.get_or_init(ChatAdapterConfig::from_env)
+}
+
+/// メッセージの content からテキストを取り出す（文字列または text パーツ配列）
+fn message_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part {
+                Value::String(text) => Some(text.as_str()),
+                Value::Object(obj) => obj.get("text").and_then(Value::as_str),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// chat messages をテンプレートで単一プロンプトへ組み立てる
+pub fn render_prompt(template: &PromptTemplate, messages: &[Value]) -> String {
+    let mut prompt = String::from(template.bos);
+    for message in messages {
+        let role = message
+            .get("role")
+            .and_then(Value::as_str)
+            .unwrap_or("user");
+        let text = message.get("content").map(message_text).unwrap_or_default();
+        let (prefix, suffix) = match role {
+            "system" | "developer" => template.system,
+            "assistant" => template.assistant,
+            _ => template.user,
+        };
+        prompt.push_str(prefix);
+        prompt.push_str(&text);
+        prompt.push_str(suffix);
+    }
+    prompt.push_str(template.generation_prompt);
+    prompt
+}
+
+/// Completions では表現できない指定（ツール呼び出し・構造化出力）を拒否する
+fn ensure_representable(payload: &Value) -> Result<(), String> {
+    for key in ["tools", "functions"] {
+        if payload
+            .get(key)
+            .and_then(Value::as_array)
+            .is_some_and(|items| !items.is_empty())
+        {
+            return Err(format!(
+                "{} is not supported for models served through the completions adapter",
+                key
+            ));
+        }
+    }
+    if let Some(format) = payload.get("response_format") {
+        let kind = format.get("type").and_then(Value::as_str);
+        if !format.is_null() && kind != Some("text") {
+            return Err(
+                "response_format is not supported for models served through the completions adapter"
+                    .to_string(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Chat Completions リクエストを Completions リクエストへ変換
+///
+/// `tools` / `functions`（空でない場合）と `text` 以外の `response_format` は
+/// 変換後に失われるため、黙って落とさずエラーを返す。
+pub fn chat_to_completions_payload(
+    payload: &Value,
+    template: &PromptTemplate,
+) -> Result<Value, String> {
+    let messages = payload
+        .get("messages")
+        .and_then(Value::as_array)
+        .ok_or_else(|| "messages must be an array".to_string())?;
+    ensure_representable(payload)?;
+
+    let mut out = Map::new();
+    for key in PASSTHROUGH_PARAMS {
+        if let Some(value) = payload.get(*key) {
+            out.insert((*key).to_string(), value.clone());
+        }
+    }
+    if !out.contains_key("max_tokens") {
+        if let Some(value) = payload.get("max_completion_tokens") {
+            out.insert("max_tokens".to_string(), value.clone());
+        }
+    }
+    out.insert(
+        "prompt".to_string(),
+        Value::String(render_prompt(template, messages)),
+    );
+
+    let mut stop: Vec<Value> = match payload.get("stop") {
+        Some(Value::String(s)) => vec![Value::String(s.clone())],
+        Some(Value::Array(items)) => items.clone(),
+        _ => Vec::new(),
+    };
+    for seq in template.stop {
+        if !stop.iter().any(|s| s.as_str() == Some(*seq)) {
+            stop.push(Value::String((*seq).to_string()));
+        }
+    }
+    if !stop.is_empty() {
+        out.insert("stop".to_string(), Value::Array(stop));
+    }
+
+    Ok(Value::Object(out))
+}
+
+fn completion_choice_to_chat(choice: &Value, delta: bool, include_role: bool) -> Value {
+    let text = choice.get("text").and_then(Value::as_str).unwrap_or("");
+    let mut content = Map::new();
+    if include_role {
+        content.insert("role".to_string(), json!("assistant"));
+    }
+    content.insert("content".to_string(), json!(text));
+    let mut out = Map::new();
+    out.insert(
+        "index".to_string(),
+        choice.get("index").cloned().unwrap_or(json!(0)),
+    );
+    out.insert(
+        if delta { "delta" } else { "message" }.to_string(),
+        Value::Object(content),
+    );
+    out.insert(
+        "finish_reason".to_string(),
+        choice.get("finish_reason").cloned().unwrap_or(Value::Null),
+    );
+    Value::Object(out)
+}
+
+fn completion_to_chat(body: &Value, delta: bool, include_role: bool) -> Value {
+    let choices: Vec<Value> = body
+        .get("choices")
+        .and_then(Value::as_array)
+        .map(|choices| {
+            choices
+                .iter()
+                .map(|c| completion_choice_to_chat(c, delta, include_role))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut out = Map::new();
+    for key in ["id", "created", "model", "system_fingerprint", "usage"] {
+        if let Some(value) = body.get(key) {
+            out.insert(key.to_string(), value.clone());
+        }
+    }
+    out.insert(
+        "object".to_string(),
+        json!(if delta {
+            "chat.completion.chunk"
+        } else {
+            "chat.completion"
+        }),
+    );
+    out.insert("choices".to_string(), Value::Array(choices));
+    Value::Object(out)
+}
+
+/// Completions の非ストリーミング応答を chat.completion 形式へ変換
+pub fn completion_response_to_chat(body: &Value) -> Value {
+    completion_to_chat(body, false, true)
+}
+
+/// Completions のストリーミングチャンクを chat.completion.chunk 形式へ変換
+pub fn completion_chunk_to_chat(chunk: &Value, first: bool) -> Value {
+    completion_to_chat(chunk, true, first)
+}
+
+/// SSEの1行（バイト列）を変換
+///
+/// 行単位で区切った後に UTF-8 として解釈するため、マルチバイト文字が
+/// チャンク境界で分割されていても壊れない。
+fn convert_sse_line_bytes(line: &[u8], first: &mut bool) -> Bytes {
+    match std::str::from_utf8(line) {
+        Ok(line) => Bytes::from(convert_sse_line(line, first)),
+        Err(_) => Bytes::copy_from_slice(line),
+    }
+}
+
+/// SSEの1行を変換（`data: {...}` 以外はそのまま）
+fn convert_sse_line(line: &str, first: &mut bool) -> String {
+    let Some(data) = line.strip_prefix("data:") else {
+        return line.to_string();
+    };
+    let data = data.trim_start();
+    match serde_json::from_str::<Value>(data) {
+        Ok(chunk) if chunk.get("choices").is_some() => {
+            let converted = completion_chunk_to_chat(&chunk, *first);
+            *first = false;
+            format!("data: {}", converted)
+        }
+        _ => line.to_string(),
+    }
+}
+
+/// Completions のSSEストリームを chat.completion.chunk のSSEストリームへ変換
+pub fn convert_completion_stream<S>(upstream: S) -> impl Stream<Item = Result<Bytes, io::Error>>
+where
+    S: Stream<Item = Result<Bytes, axum::Error>> + Send + Unpin + 'static,
+{
+    futures::stream::unfold(
+        (upstream, Vec::<u8>::new(), true, false),
+        |(mut upstream, mut buffer, mut first, done)| async move {
+            if done {
+                return None;
+            }
+            loop {
+                if let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=pos).collect();
+                    let mut line = &line[..line.len() - 1];
+                    if let Some(stripped) = line.strip_suffix(b"\r") {
+                        line = stripped;
+                    }
+                    let mut converted = convert_sse_line_bytes(line, &mut first).to_vec();
+                    converted.push(b'\n');
+                    return Some((Ok(Bytes::from(converted)), (upstream, buffer, first, false)));
+                }
+                match upstream.next().await {
+                    Some(Ok(bytes)) => buffer.extend_from_slice(&bytes),
+                    Some(Err(e)) => {
+                        return Some((
+                            Err(io::Error::other(e.to_string())),
+                            (upstream, buffer, first, true),
+                        ))
+                    }
+                    None => {
+                        if buffer.is_empty() {
+                            return None;
+                        }
+                        let rest = std::mem::take(&mut buffer);
+                        let converted = convert_sse_line_bytes(&rest, &mut first);
+                        return Some((Ok(converted), (upstream, buffer, first, true)));
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// アップストリームの Completions 応答を chat 形式の応答へ変換
+///
+/// エラー応答（非2xx）はそのまま返す。
+pub async fn completion_http_response_to_chat(response: Response, stream: bool) -> Response {
+    if !response.status().is_success() {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+
+    if stream {
+        let converted = convert_completion_stream(body.into_data_stream());
+        return Response::from_parts(parts, Body::from_stream(converted));
+    }
+
+    let bytes = match axum::body::to_bytes(body, super::OPENAI_BODY_LIMIT_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Failed to read completions response body: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(value) => Body::from(completion_response_to_chat(&value).to_string()),
+        Err(_) => Body::from(bytes),
+    };
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_falls_back_to_generic_template() {
+        let config = ChatAdapterConfig::parse("llama2-*=llama3, legacy=chatml, old-*, x=unknown");
+        assert_eq!(config.template_for("llama2-7b"), Some(LLAMA3_TEMPLATE));
+        assert_eq!(config.template_for("legacy"), Some(CHATML_TEMPLATE));
+        assert_eq!(config.template_for("old-model"), Some(GENERIC_TEMPLATE));
+        assert_eq!(config.template_for("x"), Some(GENERIC_TEMPLATE));
+        assert_eq!(config.template_for("gpt-4o"), None);
+    }
+
+    #[test]
+    fn chat_payload_is_rendered_into_prompt() {
+        let payload = json!({
+            "model": "legacy",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": [{"type": "text", "text": "Hi"}]}
+            ],
+            "max_completion_tokens": 16,
+            "stop": "END",
+            "tools": [],
+            "response_format": {"type": "text"}
+        });
+        let converted = chat_to_completions_payload(&payload, &CHATML_TEMPLATE).unwrap();
+        assert_eq!(
+            converted["prompt"],
+            "<|im_start|>system\nBe brief.<|im_end|>\n<|im_start|>user\nHi<|im_end|>\n<|im_start|>assistant\n"
+        );
+        assert_eq!(converted["max_tokens"], 16);
+        assert_eq!(converted["stop"], json!(["END", "<|im_end|>"]));
+        assert!(converted.get("messages").is_none());
+        assert!(converted.get("tools").is_none());
+        assert!(converted.get("response_format").is_none());
+    }
+
+    #[test]
+    fn tools_and_structured_output_are_rejected() {
+        let messages = json!([{"role": "user", "content": "Hi"}]);
+        let with_tools = json!({
+            "model": "legacy",
+            "messages": messages,
+            "tools": [{"type": "function", "function": {"name": "f"}}]
+        });
+        assert!(chat_to_completions_payload(&with_tools, &GENERIC_TEMPLATE)
+            .unwrap_err()
+            .contains("tools"));
+
+        let with_schema = json!({
+            "model": "legacy",
+            "messages": messages,
+            "response_format": {"type": "json_object"}
+        });
+        assert!(chat_to_completions_payload(&with_schema, &GENERIC_TEMPLATE)
+            .unwrap_err()
+            .contains("response_format"));
+    }
+
+    #[tokio::test]
+    async fn stream_keeps_multibyte_chars_split_across_chunks() {
+        let line = "data: {\"choices\":[{\"index\":0,\"text\":\"こんにちは\"}]}\n\n";
+        let bytes = line.as_bytes();
+        let split = line.find('こ').unwrap() + 1;
+        let chunks = vec![
+            Ok(Bytes::copy_from_slice(&bytes[..split])),
+            Ok(Bytes::copy_from_slice(&bytes[split..])),
+        ];
+        let converted: Vec<Bytes> = convert_completion_stream(futures::stream::iter(chunks))
+            .map(|r| r.unwrap())
+            .collect()
+            .await;
+        let text = String::from_utf8(converted.concat()).unwrap();
+        let data = text.lines().next().unwrap().strip_prefix("data: ").unwrap();
+        let chunk: Value = serde_json::from_str(data).unwrap();
+        assert_eq!(chunk["choices"][0]["delta"]["content"], "こんにちは");
+    }
+
+    #[test]
+    fn completion_response_is_converted_to_chat() {
+        let body = json!({
+            "id": "cmpl-1",
+            "object": "text_completion",
+            "model": "legacy",
+            "choices": [{"index": 0, "text": "Hello", "finish_reason": "stop"}],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 1, "total_tokens": 4}
+        });
+        let chat = completion_response_to_chat(&body);
+        assert_eq!(chat["object"], "chat.completion");
+        assert_eq!(chat["choices"][0]["message"]["role"], "assistant");
+        assert_eq!(chat["choices"][0]["message"]["content"], "Hello");
+        assert_eq!(chat["usage"]["total_tokens"], 4);
+
+        let mut first = true;
+        let line = convert_sse_line(
+            r#"data: {"id":"c","choices":[{"index":0,"text":"He","finish_reason":null}]}"#,
+            &mut first,
+        );
+        let chunk: Value = serde_json::from_str(line.strip_prefix("data: ").unwrap()).unwrap();
+        assert_eq!(chunk["object"], "chat.completion.chunk");
+        assert_eq!(chunk["choices"][0]["delta"]["content"], "He");
+        assert_eq!(chunk["choices"][0]["delta"]["role"], "assistant");
+        assert!(!first);
+        assert_eq!(convert_sse_line("data: [DONE]", &mut first), "data: [DONE]");
+    }
+}
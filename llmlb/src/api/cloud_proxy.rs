@@ -7,6 +7,7 @@ use crate::api::error::AppError;
 use crate::api::openai_util::{
     map_openai_messages_to_anthropic, map_openai_messages_to_google_contents,
 };
+use crate::api::prompt_cache::{carry_cache_control_to_anthropic, usage_from_response};
 use crate::api::proxy::forward_streaming_response;
 use crate::cloud_metrics;
 use crate::common::error::LbError;
@@ -395,6 +396,7 @@ impl CloudProvider for AnthropicProvider {
         if let Some(s) = system {
             body["system"] = Value::String(s);
         }
+        carry_cache_control_to_anthropic(&messages, &mut body);
         if let Some(obj) = body.as_object_mut() {
             obj.retain(|_, v| !v.is_null());
         }
@@ -422,7 +424,7 @@ impl CloudProvider for AnthropicProvider {
             .map(|s| s.to_string())
             .unwrap_or_else(|| model.to_string());
 
-        json!({
+        let mut response = json!({
             "id": id,
             "object": "chat.completion",
             "model": format!("anthropic:{}", model_label),
@@ -431,7 +433,22 @@ impl CloudProvider for AnthropicProvider {
                 "message": {"role": "assistant", "content": text},
                 "finish_reason": "stop"
             }],
-        })
+        });
+        // usage はOpenAI形式に変換する（キャッシュ読み出し分は prompt_tokens_details へ）
+        if let Some(cache) = usage_from_response(data) {
+            let completion_tokens = data
+                .get("usage")
+                .and_then(|u| u.get("output_tokens"))
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            response["usage"] = json!({
+                "prompt_tokens": cache.input_tokens,
+                "completion_tokens": completion_tokens,
+                "total_tokens": cache.input_tokens + completion_tokens,
+                "prompt_tokens_details": {"cached_tokens": cache.cached_tokens},
+            });
+        }
+        response
     }
 }
 
@@ -554,6 +571,39 @@ mod tests {
         assert_eq!(result["id"].as_str().unwrap(), "msg-abc");
     }
 
+    #[test]
+    #[serial]
+    fn anthropic_provider_forwards_cache_control_and_maps_cached_usage() {
+        let provider = AnthropicProvider;
+        std::env::set_var("ANTHROPIC_API_BASE_URL", "http://localhost:9999");
+        let payload = serde_json::json!({
+            "messages": [
+                {"role":"system","content":[
+                    {"type":"text","text":"long doc","cache_control":{"type":"ephemeral"}}
+                ]},
+                {"role":"user","content":"Hi"}
+            ]
+        });
+        let (_, body) = provider
+            .transform_request(&payload, "claude-3", false)
+            .expect("transform");
+        assert_eq!(body["system"][0]["cache_control"]["type"], "ephemeral");
+        assert_eq!(body["messages"][0]["content"][0]["text"], "Hi");
+        std::env::remove_var("ANTHROPIC_API_BASE_URL");
+
+        let data = serde_json::json!({
+            "content": [{"text": "ok"}],
+            "usage": {"input_tokens": 10, "cache_read_input_tokens": 990, "output_tokens": 5}
+        });
+        let result = provider.transform_response(&data, "claude-3");
+        assert_eq!(result["usage"]["prompt_tokens"], 1000);
+        assert_eq!(result["usage"]["total_tokens"], 1005);
+        assert_eq!(
+            result["usage"]["prompt_tokens_details"]["cached_tokens"],
+            990
+        );
+    }
+
     #[test]
     fn openai_provider_transform_response_passthrough() {
         let provider = OpenAiProvider;
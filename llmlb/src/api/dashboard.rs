@@ -301,6 +301,8 @@ pub struct MonthlyTokenStats {
     pub total_output_tokens: u64,
     /// 総トークン合計
     pub total_tokens: u64,
+    /// 課金額合計（USD）
+    pub total_cost_usd: f64,
     /// リクエスト数
     pub request_count: u64,
 }
@@ -326,6 +328,7 @@ pub async fn get_monthly_token_stats(
                 total_input_tokens: s.total_input_tokens,
                 total_output_tokens: s.total_output_tokens,
                 total_tokens: s.total_tokens,
+                total_cost_usd: s.total_cost_usd,
                 request_count: s.request_count,
             })
             .collect(),
@@ -950,6 +953,52 @@ pub struct EndpointDailyStatsQuery {
     pub days: Option<u32>,
 }
 
+/// GET /api/queue/history - リクエストキューの待機数・拒否数の時系列
+pub async fn get_queue_history(
+    State(state): State<AppState>,
+    Query(query): Query<QueueHistoryQuery>,
+) -> Result<Json<Vec<crate::db::queue_history::QueueHistoryPoint>>, AppError> {
+    let minutes = query.minutes.unwrap_or(60).clamp(1, 7 * 24 * 60);
+    let since = Utc::now() - chrono::Duration::minutes(i64::from(minutes));
+    let history = crate::db::queue_history::get_history(&state.db_pool, since)
+        .await
+        .map_err(|e| AppError(crate::common::error::LbError::Database(e.to_string())))?;
+    Ok(Json(history))
+}
+
+/// キュー時系列クエリパラメータ
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueueHistoryQuery {
+    /// 取得する期間（分、デフォルト: 60、最大: 10080）
+    #[serde(default)]
+    pub minutes: Option<u32>,
+}
+
+/// レイテンシ基準レスポンス
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyBaselineResponse {
+    /// 現在の基準値（未算出なら null）
+    pub baseline: Option<crate::balancer::LatencyBaseline>,
+    /// penalty 判定の倍率（`LLMLB_LATENCY_PENALTY_FACTOR`）
+    pub penalty_factor: f64,
+    /// penalty 判定の閾値（ミリ秒、基準値未算出または判定無効なら null）
+    pub penalty_threshold_ms: Option<f64>,
+    /// penalty 対象のエンドポイントID
+    pub penalized_endpoints: Vec<Uuid>,
+}
+
+/// GET /api/balancer/baseline - エンドポイントのレイテンシ基準と penalty 対象
+pub async fn get_latency_baseline(State(state): State<AppState>) -> Json<LatencyBaselineResponse> {
+    let baseline = state.load_manager.latency_baseline();
+    let penalty_factor = crate::config::latency_penalty_factor();
+    Json(LatencyBaselineResponse {
+        baseline,
+        penalty_factor,
+        penalty_threshold_ms: baseline.and_then(|b| b.penalty_threshold_ms(penalty_factor)),
+        penalized_endpoints: state.load_manager.latency_penalized_endpoints().await,
+    })
+}
+
 /// GET /api/endpoints/{id}/model-stats - モデル別リクエスト統計
 ///
 /// SPEC-8c32349f: エンドポイント単位リクエスト統計 (Phase 7)
@@ -1224,6 +1273,68 @@ pub async fn get_endpoint_model_tps(
     )
 }
 
+/// TPS時系列クエリパラメータ
+#[derive(Debug, Clone, Deserialize)]
+pub struct TpsHistoryQuery {
+    /// 対象モデル（省略時は全モデル）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 取得する期間（`30m` / `24h` / `7d` 形式、デフォルト: `24h`、最大: 30日）
+    #[serde(default)]
+    pub range: Option<String>,
+    /// 集計粒度（`minute` / `hour`、デフォルト: `minute`）
+    #[serde(default)]
+    pub granularity: crate::db::tps_history::TpsHistoryGranularity,
+}
+
+/// TPS時系列の最大取得期間
+const TPS_HISTORY_MAX_RANGE_DAYS: i64 = 30;
+
+/// `30m` / `24h` / `7d` 形式の期間を解釈する
+fn parse_history_range(range: &str) -> Option<chrono::Duration> {
+    let range = range.trim();
+    let unit = range.chars().last()?;
+    let value: i64 = range[..range.len() - unit.len_utf8()].parse().ok()?;
+    if value <= 0 {
+        return None;
+    }
+    let duration = match unit {
+        'm' => chrono::Duration::minutes(value),
+        'h' => chrono::Duration::hours(value),
+        'd' => chrono::Duration::days(value),
+        _ => return None,
+    };
+    Some(duration.min(chrono::Duration::days(TPS_HISTORY_MAX_RANGE_DAYS)))
+}
+
+/// GET /api/endpoints/{id}/tps/history - エンドポイント×モデル単位のTPS時系列
+///
+/// 定期スナップショット（`LLMLB_TPS_HISTORY_INTERVAL_SECS`）を分/時単位で平均して返す。
+/// スナップショットの無い区間は補間せずに省く。
+pub async fn get_endpoint_tps_history(
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Query(query): Query<TpsHistoryQuery>,
+) -> Result<Json<Vec<crate::db::tps_history::TpsHistoryPoint>>, AppError> {
+    let range = query.range.as_deref().unwrap_or("24h");
+    let range = parse_history_range(range).ok_or_else(|| {
+        AppError(LbError::Common(CommonError::Validation(format!(
+            "Invalid range: {} (expected e.g. 30m, 24h or 7d)",
+            range
+        ))))
+    })?;
+    let history = crate::db::tps_history::get_history(
+        &state.db_pool,
+        id,
+        query.model.as_deref().filter(|m| !m.is_empty()),
+        Utc::now() - range,
+        query.granularity,
+    )
+    .await
+    .map_err(|e| AppError(LbError::Database(e.to_string())))?;
+    Ok(Json(history))
+}
+
 /// Clientsランキングのクエリパラメータ
 #[derive(Debug, Deserialize)]
 pub struct ClientsQuery {
@@ -2040,6 +2151,7 @@ mod tests {
             total_input_tokens: 50000,
             total_output_tokens: 25000,
             total_tokens: 75000,
+            total_cost_usd: 0.0,
             request_count: 500,
         };
         let json = serde_json::to_value(&stats).unwrap();
@@ -2157,6 +2269,23 @@ mod tests {
 
     // ===== EndpointDailyStatsQuery deserialization =====
 
+    #[test]
+    fn test_parse_history_range() {
+        assert_eq!(
+            parse_history_range("30m"),
+            Some(chrono::Duration::minutes(30))
+        );
+        assert_eq!(
+            parse_history_range("24h"),
+            Some(chrono::Duration::hours(24))
+        );
+        assert_eq!(parse_history_range("90d"), Some(chrono::Duration::days(30)));
+        assert_eq!(parse_history_range("0h"), None);
+        assert_eq!(parse_history_range("1w"), None);
+        assert_eq!(parse_history_range("h"), None);
+        assert_eq!(parse_history_range(""), None);
+    }
+
     #[test]
     fn test_endpoint_daily_stats_query_default() {
         use super::EndpointDailyStatsQuery;
@@ -2922,6 +3051,7 @@ mod tests {
             total_input_tokens: 10000,
             total_output_tokens: 5000,
             total_tokens: 15000,
+            total_cost_usd: 0.0,
             request_count: 100,
         };
         let cloned = stats.clone();
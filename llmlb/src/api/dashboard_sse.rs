@@ -0,0 +1,188 @@
+//! Server-Sent Events endpoint for real-time dashboard updates
+//!
+//! `GET /api/events/stream` streams the same `DashboardEvent`s as `/ws/dashboard`
+//! (`data:` is the JSON-serialized event, `id:` is its sequence number).
+//!
+//! - On connect, a full `EndpointSnapshot` is sent first, followed by incremental events.
+//! - `Last-Event-ID` resumes from the event after the given ID when it is still in the
+//!   bus backlog; otherwise the client is resynchronized with a fresh snapshot.
+//! - Slow clients keep at most `EVENT_BACKLOG_CAPACITY` pending events; older ones are
+//!   dropped and a fresh snapshot is sent instead.
+//! - The subscription is released as soon as the client disconnects.
+//!
+//! Authentication is required (JWT via Authorization header or cookie, admin only).
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+    Extension,
+};
+use futures::{stream, StreamExt};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::{debug, warn};
+
+use crate::balancer::LoadManager;
+use crate::common::auth::{Claims, UserRole};
+use crate::events::{DashboardEvent, SequencedEvent, SharedEventBus};
+use crate::AppState;
+
+/// Header sent by `EventSource` when reconnecting
+const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+/// Interval of keep-alive comments (also used to detect disconnected clients)
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// GET /api/events/stream - dashboard events as Server-Sent Events
+pub async fn dashboard_events_stream(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // Only admin users can access dashboard events (same as the WebSocket endpoint)
+    if claims.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Admin access required".to_string()));
+    }
+
+    let last_event_id = parse_last_event_id(&headers);
+    // Subscribe before reading the backlog so that no event is lost in between;
+    // duplicates are filtered out by event ID.
+    let receiver = state.event_bus.subscribe_sequenced();
+    let mut subscription = Subscription {
+        receiver,
+        event_bus: state.event_bus.clone(),
+        load_manager: state.load_manager.clone(),
+        delivered: 0,
+    };
+
+    let initial = match last_event_id.and_then(|id| state.event_bus.events_after(id)) {
+        Some(missed) => {
+            debug!(
+                last_event_id = ?last_event_id,
+                missed = missed.len(),
+                "Dashboard SSE client resumed"
+            );
+            subscription.delivered = missed.last().map_or(last_event_id.unwrap_or(0), |e| e.id);
+            missed.iter().filter_map(sse_event).collect()
+        }
+        None => {
+            debug!("Dashboard SSE client connected");
+            vec![subscription.snapshot().await]
+        }
+    };
+
+    let events = stream::iter(initial)
+        .chain(stream::unfold(
+            subscription,
+            |mut subscription| async move {
+                let event = subscription.next_event().await?;
+                Some((event, subscription))
+            },
+        ))
+        .map(Ok::<_, Infallible>);
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::new().interval(KEEP_ALIVE_INTERVAL)))
+}
+
+/// Parse the `Last-Event-ID` header (ignored unless it is a sequence number)
+fn parse_last_event_id(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(LAST_EVENT_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Convert a bus event into an SSE event carrying its sequence number
+fn sse_event(event: &SequencedEvent) -> Option<Event> {
+    match Event::default()
+        .id(event.id.to_string())
+        .json_data(&event.event)
+    {
+        Ok(sse) => Some(sse),
+        Err(e) => {
+            warn!("Failed to serialize dashboard event: {}", e);
+            None
+        }
+    }
+}
+
+/// Per-client subscription state
+///
+/// Dropping it (when the client disconnects and the response stream is dropped)
+/// unsubscribes from the event bus.
+struct Subscription {
+    receiver: Receiver<SequencedEvent>,
+    event_bus: SharedEventBus,
+    load_manager: LoadManager,
+    /// ID of the last event already sent to the client
+    delivered: u64,
+}
+
+impl Subscription {
+    /// Full endpoint snapshot, tagged with the latest event ID it covers
+    async fn snapshot(&mut self) -> Event {
+        self.delivered = self.event_bus.last_event_id();
+        let snapshot = SequencedEvent {
+            id: self.delivered,
+            event: DashboardEvent::EndpointSnapshot {
+                endpoints: self.load_manager.snapshots().await,
+            },
+        };
+        sse_event(&snapshot).unwrap_or_else(|| Event::default().comment("snapshot unavailable"))
+    }
+
+    /// Next event to send, or `None` when the event bus is closed
+    async fn next_event(&mut self) -> Option<Event> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if event.id <= self.delivered => continue,
+                Ok(event) => {
+                    self.delivered = event.id;
+                    if let Some(sse) = sse_event(&event) {
+                        return Some(sse);
+                    }
+                }
+                Err(RecvError::Lagged(n)) => {
+                    warn!(
+                        "Dashboard SSE client lagged by {} events; resending snapshot",
+                        n
+                    );
+                    return Some(self.snapshot().await);
+                }
+                Err(RecvError::Closed) => {
+                    debug!("Event bus closed");
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        debug!("Dashboard SSE client disconnected");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_event_id_must_be_a_sequence_number() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_last_event_id(&headers), None);
+
+        headers.insert(LAST_EVENT_ID_HEADER, " 42 ".parse().unwrap());
+        assert_eq!(parse_last_event_id(&headers), Some(42));
+
+        headers.insert(LAST_EVENT_ID_HEADER, "abc".parse().unwrap());
+        assert_eq!(parse_last_event_id(&headers), None);
+    }
+}
@@ -15,7 +15,8 @@ use axum::{
 use futures::{SinkExt, StreamExt};
 use tracing::{debug, warn};
 
-use crate::events::SharedEventBus;
+use crate::balancer::LoadManager;
+use crate::events::{DashboardEvent, SharedEventBus};
 use crate::AppState;
 
 /// Query parameter for WebSocket token authentication
@@ -31,6 +32,7 @@ pub struct WsAuthQuery {
 /// - Node registration/removal
 /// - Node status changes
 /// - Metrics updates
+/// - Endpoint snapshot (full on connect, field-level deltas afterwards)
 ///
 /// Authentication is always required (JWT via Authorization header, cookie, or query parameter).
 pub async fn dashboard_ws_handler(
@@ -59,10 +61,11 @@ pub async fn dashboard_ws_handler(
             .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Missing JWT cookie".to_string()))?
     };
 
-    let claims = crate::auth::jwt::verify_jwt(&token, &state.jwt_secret).map_err(|e| {
-        warn!("WebSocket JWT verification failed: {}", e);
-        (StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e))
-    })?;
+    let claims =
+        crate::auth::jwt::verify_jwt_with_rotation(&token, &state.jwt_secret).map_err(|e| {
+            warn!("WebSocket JWT verification failed: {}", e);
+            (StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e))
+        })?;
 
     // Only admin users can access the dashboard WebSocket
     if claims.role != UserRole::Admin {
@@ -71,10 +74,12 @@ pub async fn dashboard_ws_handler(
 
     debug!("WebSocket authenticated for user: {}", claims.sub);
 
-    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state.event_bus.clone())))
+    Ok(ws.on_upgrade(move |socket| {
+        handle_socket(socket, state.event_bus.clone(), state.load_manager.clone())
+    }))
 }
 
-async fn handle_socket(socket: WebSocket, event_bus: SharedEventBus) {
+async fn handle_socket(socket: WebSocket, event_bus: SharedEventBus, load_manager: LoadManager) {
     let (mut sender, mut receiver) = socket.split();
     let mut event_rx = event_bus.subscribe();
 
@@ -90,6 +95,17 @@ async fn handle_socket(socket: WebSocket, event_bus: SharedEventBus) {
         return;
     }
 
+    // Send a full endpoint snapshot so that subsequent deltas can be applied
+    let snapshot = DashboardEvent::EndpointSnapshot {
+        endpoints: load_manager.snapshots().await,
+    };
+    if let Ok(json) = serde_json::to_string(&snapshot) {
+        if let Err(e) = sender.send(Message::Text(json.into())).await {
+            warn!("Failed to send initial snapshot: {}", e);
+            return;
+        }
+    }
+
     // Spawn task to handle incoming messages (ping/pong, close)
     let mut recv_task = tokio::spawn(async move {
         while let Some(msg) = receiver.next().await {
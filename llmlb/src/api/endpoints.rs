@@ -9,18 +9,22 @@ use crate::api::openai_util::{
 use crate::common::auth::{Claims, UserRole};
 use crate::common::error::{CommonError, LbError};
 use crate::db::{download_tasks as tasks_db, endpoints as db};
-use crate::detection::{detect_endpoint_type_with_client, DetectionError};
+use crate::detection::{
+    detect_endpoint_type_cached, redetect_endpoint, DetectionError, RedetectError,
+    REDETECTION_TIMEOUT,
+};
+use crate::registry::endpoints::{preferred_duplicate, OnDuplicateUrl};
 use crate::sync::{self, SyncError};
 use crate::system_info;
 use crate::types::endpoint::{
-    DeviceInfo, Endpoint, EndpointCapability, EndpointModel, EndpointStatus, EndpointType,
-    ModelDownloadTask,
+    DeviceInfo, Endpoint, EndpointCapability, EndpointModel, EndpointOperationalState,
+    EndpointStatus, EndpointType, ModelDownloadTask, OperationalState,
 };
 use crate::AppState;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Extension, Json,
 };
 use reqwest::Url;
@@ -32,7 +36,9 @@ use uuid::Uuid;
 /// - フィールドなし → None
 /// - フィールドがnull → Some(None)
 /// - フィールドに値あり → Some(Some(value))
-fn deserialize_optional_field<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+pub(crate) fn deserialize_optional_field<'de, T, D>(
+    deserializer: D,
+) -> Result<Option<Option<T>>, D::Error>
 where
     T: Deserialize<'de>,
     D: Deserializer<'de>,
@@ -62,6 +68,25 @@ pub struct CreateEndpointRequest {
     /// エンドポイントの機能一覧（画像生成、音声認識等）
     #[serde(default)]
     pub capabilities: Vec<EndpointCapability>,
+    /// タグ（ラベル）
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 優先フェイルオーバー先のエンドポイントID
+    #[serde(default)]
+    pub failover_to: Option<Uuid>,
+    /// カナリア割合（0〜100%）
+    #[serde(default)]
+    pub canary_percent: Option<u8>,
+    /// 依存先のエンドポイントID（起動時の再判別・初回ヘルスチェックの順序）
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+    /// 同時スロット数（容量予約の判定に使う。省略時は `LLMLB_ENDPOINT_SLOTS`）
+    #[serde(default)]
+    pub slots: Option<u32>,
+    /// 正規化後の base_url が既存エンドポイントと重複した場合の扱い
+    /// （`error`: 409を返す / `return_existing`: 既存エンドポイントを200で返す）
+    #[serde(default)]
+    pub on_duplicate: OnDuplicateUrl,
 }
 
 fn default_health_check_interval() -> u32 {
@@ -149,6 +174,200 @@ pub struct UpdateEndpointRequest {
     /// メモ（None=未指定, Some(None)=削除, Some(Some(v))=設定）
     #[serde(default, deserialize_with = "deserialize_optional_field")]
     pub notes: Option<Option<String>>,
+    /// タグ（指定時は置き換え）
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// 優先フェイルオーバー先（None=未指定, Some(None)=解除, Some(Some(v))=設定）
+    #[serde(default, deserialize_with = "deserialize_optional_field")]
+    pub failover_to: Option<Option<Uuid>>,
+    /// 依存先のエンドポイントID（指定時は置き換え）
+    #[serde(default)]
+    pub depends_on: Option<Vec<Uuid>>,
+    /// 同時スロット数（None=未指定, Some(None)=既定値に戻す, Some(Some(v))=設定）
+    #[serde(default, deserialize_with = "deserialize_optional_field")]
+    pub slots: Option<Option<u32>>,
+}
+
+/// 重み変更リクエスト
+#[derive(Debug, Deserialize)]
+pub struct SetEndpointWeightRequest {
+    /// 目標重み
+    pub weight: u32,
+    /// 目標重みまでの変化時間（秒）。省略または0で即時反映
+    #[serde(default)]
+    pub ramp_secs: Option<u64>,
+}
+
+/// 重み変更レスポンス
+#[derive(Debug, Serialize)]
+pub struct SetEndpointWeightResponse {
+    /// エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 目標重み
+    pub weight: u32,
+    /// 変更前の実効重み（ramp の起点）
+    pub previous_effective_weight: f64,
+    /// 現在の実効重み
+    pub effective_weight: f64,
+    /// ramp 時間（秒）
+    pub ramp_secs: u64,
+}
+
+/// タグ追加リクエスト
+#[derive(Debug, Deserialize)]
+pub struct AddEndpointTagsRequest {
+    /// 追加するタグ（既存のタグは重複させない）
+    pub tags: Vec<String>,
+}
+
+/// タグ変更レスポンス
+#[derive(Debug, Serialize)]
+pub struct EndpointTagsResponse {
+    /// エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 変更後のタグ
+    pub tags: Vec<String>,
+}
+
+/// ramp 時間の上限（秒）
+pub(crate) const MAX_WEIGHT_RAMP_SECS: u64 = 86_400;
+
+/// 一括更新の1エンドポイント分の設定（未指定の項目は変更しない）
+#[derive(Debug, Default, Deserialize)]
+pub struct BulkEndpointSettings {
+    /// 目標重み
+    #[serde(default)]
+    pub weight: Option<u32>,
+    /// 目標重みまでの変化時間（秒）。`weight` 指定時のみ有効
+    #[serde(default)]
+    pub ramp_secs: Option<u64>,
+    /// `false` で運用状態を `disabled`、`true` で `active` にする
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// タグ（指定時は置き換え）
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
+/// 一括更新の1エンドポイント分の結果
+#[derive(Debug, Serialize)]
+pub struct BulkEndpointUpdateResult {
+    /// リクエストで指定されたエンドポイントID
+    pub endpoint_id: String,
+    /// 更新に成功したか
+    pub success: bool,
+    /// 失敗理由
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 一括更新レスポンス
+#[derive(Debug, Serialize)]
+pub struct BulkEndpointUpdateResponse {
+    /// 更新に成功した件数
+    pub updated: usize,
+    /// 更新に失敗した件数
+    pub failed: usize,
+    /// エンドポイントごとの結果（ID順）
+    pub results: Vec<BulkEndpointUpdateResult>,
+}
+
+/// 検証済みの一括更新（1エンドポイント分）
+struct PlannedBulkUpdate {
+    previous: Endpoint,
+    ramp: Option<Duration>,
+    update: db::EndpointSettingsUpdate,
+}
+
+/// 単価・月次予算の設定リクエスト（未指定・`null` は解除）
+#[derive(Debug, Deserialize)]
+pub struct SetEndpointBudgetRequest {
+    /// 月次予算（USD）
+    #[serde(default)]
+    pub monthly_budget_usd: Option<f64>,
+    /// 入力100万トークンあたりの単価（USD）
+    #[serde(default)]
+    pub input_cost_per_million_tokens: Option<f64>,
+    /// 出力100万トークンあたりの単価（USD）
+    #[serde(default)]
+    pub output_cost_per_million_tokens: Option<f64>,
+}
+
+/// カナリア割合の設定リクエスト
+#[derive(Debug, Deserialize)]
+pub struct SetEndpointCanaryRequest {
+    /// カナリア割合（0〜100%）。`null` で通常のエンドポイントに戻す
+    pub canary_percent: Option<u8>,
+}
+
+/// カナリア割合の設定レスポンス
+#[derive(Debug, Serialize)]
+pub struct EndpointCanaryResponse {
+    /// エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 変更前のカナリア割合
+    pub previous_canary_percent: Option<u8>,
+    /// カナリア割合
+    pub canary_percent: Option<u8>,
+}
+
+/// 単価・月次予算の設定レスポンス
+#[derive(Debug, Serialize)]
+pub struct EndpointBudgetResponse {
+    /// エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 月次予算（USD）
+    pub monthly_budget_usd: Option<f64>,
+    /// 入力100万トークンあたりの単価（USD）
+    pub input_cost_per_million_tokens: Option<f64>,
+    /// 出力100万トークンあたりの単価（USD）
+    pub output_cost_per_million_tokens: Option<f64>,
+    /// 当月累計コスト（USD）
+    pub monthly_cost_usd: f64,
+    /// 予算超過によりルーティング候補から除外中か
+    pub over_budget: bool,
+}
+
+/// タイプ再判別レスポンス
+#[derive(Debug, Serialize)]
+pub struct RedetectEndpointResponse {
+    /// エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 再判別前のタイプ
+    pub old_type: EndpointType,
+    /// 再判別後のタイプ
+    pub new_type: EndpointType,
+    /// タイプが変わったか
+    pub changed: bool,
+    /// 判定理由
+    pub reason: String,
+}
+
+/// 運用状態の設定リクエスト
+#[derive(Debug, Deserialize)]
+pub struct SetOperationalStateRequest {
+    /// 運用状態（`active` / `draining` / `disabled` / `maintenance`）
+    pub state: OperationalState,
+    /// 設定理由
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// 運用状態レスポンス
+#[derive(Debug, Serialize)]
+pub struct OperationalStateResponse {
+    /// エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 運用状態
+    pub state: OperationalState,
+    /// 設定理由
+    pub reason: Option<String>,
+    /// 設定したユーザーID
+    pub updated_by: Option<String>,
+    /// 設定日時（通常運用の場合は `None`）
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 処理中リクエスト数（ドレイン完了の判定用）
+    pub active_requests: u32,
 }
 
 /// エンドポイントレスポンス
@@ -183,12 +402,41 @@ pub struct EndpointResponse {
     /// デバイス情報（SPEC-f8e3a1b7: /api/systemから取得）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub device_info: Option<crate::types::endpoint::DeviceInfo>,
+    /// タグ（ラベル）
+    pub tags: Vec<String>,
+    /// 重み（目標値）
+    pub weight: u32,
+    /// 月次予算（USD）
+    pub monthly_budget_usd: Option<f64>,
+    /// 入力100万トークンあたりの単価（USD）
+    pub input_cost_per_million_tokens: Option<f64>,
+    /// 出力100万トークンあたりの単価（USD）
+    pub output_cost_per_million_tokens: Option<f64>,
+    /// 当月累計コスト（USD）
+    pub monthly_cost_usd: f64,
+    /// 優先フェイルオーバー先のエンドポイントID
+    pub failover_to: Option<Uuid>,
+    /// カナリア割合（0〜100%、通常のエンドポイントは None）
+    pub canary_percent: Option<u8>,
+    /// 依存先のエンドポイントID
+    pub depends_on: Vec<Uuid>,
+    /// 同時スロット数（未設定時は `LLMLB_ENDPOINT_SLOTS` の既定値を使う）
+    pub slots: Option<u32>,
+    /// TLS証明書の有効期限（HTTPSで取得できた場合のみ）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cert_expires_at: Option<String>,
     /// モデル数（一覧取得時）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model_count: Option<usize>,
+    /// 処理中リクエスト数（一覧取得時）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_requests: Option<u32>,
     /// 関連モデル一覧（詳細取得時のみ）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub models: Option<Vec<EndpointModelResponse>>,
+    /// 最後のエラーの分類と対処提案（詳細取得時、エラーがある場合のみ）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnosis: Option<crate::health::EndpointDiagnosis>,
 }
 
 impl From<Endpoint> for EndpointResponse {
@@ -208,8 +456,22 @@ impl From<Endpoint> for EndpointResponse {
             registered_at: ep.registered_at.to_rfc3339(),
             notes: ep.notes,
             device_info: ep.device_info,
+            tags: ep.tags,
+            weight: ep.weight,
+            monthly_budget_usd: ep.monthly_budget_usd,
+            input_cost_per_million_tokens: ep.input_cost_per_million_tokens,
+            output_cost_per_million_tokens: ep.output_cost_per_million_tokens,
+            monthly_cost_usd: crate::cloud_metrics::endpoint_monthly_cost(ep.id),
+            failover_to: ep.failover_to,
+            canary_percent: ep.canary_percent,
+            depends_on: ep.depends_on,
+            slots: ep.slots,
+            cert_expires_at: crate::health::cert_monitor::endpoint_cert_expires_at(ep.id)
+                .map(|dt| dt.to_rfc3339()),
             model_count: None,
+            active_requests: None,
             models: None,
+            diagnosis: None,
         }
     }
 }
@@ -223,6 +485,28 @@ pub struct ListEndpointsResponse {
     pub total: usize,
 }
 
+/// base_url が重複しているエンドポイントのグループ
+#[derive(Debug, Serialize)]
+pub struct DuplicateEndpointGroup {
+    /// 正規化後のURL
+    pub normalized_url: String,
+    /// 重複しているエンドポイント（登録日時の昇順）
+    pub endpoints: Vec<EndpointResponse>,
+    /// 残すことを提案するエンドポイントID
+    pub suggested_keep: Uuid,
+    /// 統合して削除することを提案するエンドポイントID
+    pub suggested_remove: Vec<Uuid>,
+}
+
+/// 重複エンドポイント一覧レスポンス
+#[derive(Debug, Serialize)]
+pub struct ListDuplicateEndpointsResponse {
+    /// 重複グループ一覧
+    pub groups: Vec<DuplicateEndpointGroup>,
+    /// グループ数
+    pub total: usize,
+}
+
 /// エンドポイント一覧クエリパラメータ
 #[derive(Debug, Deserialize)]
 pub struct ListEndpointsQuery {
@@ -392,6 +676,29 @@ pub struct ErrorResponse {
     pub code: String,
 }
 
+/// タグを正規化（前後空白除去・空文字除外・重複除去、順序は維持）
+pub(crate) fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+/// 依存先IDの重複を除去する（指定順は保持）
+fn dedupe_ids(ids: Vec<Uuid>) -> Vec<Uuid> {
+    let mut deduped: Vec<Uuid> = Vec::with_capacity(ids.len());
+    for id in ids {
+        if !deduped.contains(&id) {
+            deduped.push(id);
+        }
+    }
+    deduped
+}
+
 /// Admin権限を確認
 fn ensure_admin(claims: &Claims) -> Result<(), AppError> {
     if claims.role != UserRole::Admin {
@@ -543,6 +850,18 @@ pub async fn create_endpoint(
         .into_response();
     }
 
+    // 冪等登録: 同じURLが登録済みなら名前の重複より先に既存エンドポイントを返す
+    let on_duplicate = req.on_duplicate;
+    if on_duplicate == OnDuplicateUrl::ReturnExisting {
+        if let Some(existing) = state
+            .endpoint_registry
+            .find_by_base_url(&req.base_url)
+            .await
+        {
+            return (StatusCode::OK, Json(EndpointResponse::from(existing))).into_response();
+        }
+    }
+
     // 名前の重複チェック
     match db::find_by_name(&state.db_pool, &req.name).await {
         Ok(Some(_)) => {
@@ -562,10 +881,27 @@ pub async fn create_endpoint(
         Ok(None) => {} // OK - 名前は一意
     }
 
+    // URLの重複チェック（正規化後に比較）
+    if let Some(existing) = state
+        .endpoint_registry
+        .find_by_base_url(&req.base_url)
+        .await
+    {
+        return AppError(LbError::Conflict(format!(
+            "Endpoint with URL '{}' is already registered as '{}'",
+            req.base_url, existing.name
+        )))
+        .into_response();
+    }
+
     // SPEC-e8e9326e: 自動検出（手動指定は廃止、対応タイプのみ許可）
-    let detection_result =
-        detect_endpoint_type_with_client(&state.http_client, &req.base_url, req.api_key.as_deref())
-            .await;
+    let detection_result = detect_endpoint_type_cached(
+        &state.http_client,
+        &req.base_url,
+        req.api_key.as_deref(),
+        true,
+    )
+    .await;
 
     let detected_type = match detection_result {
         Ok(result) => result.endpoint_type,
@@ -583,6 +919,39 @@ pub async fn create_endpoint(
     };
 
     let mut endpoint = Endpoint::new(req.name, req.base_url.clone(), detected_type);
+    if let Some(percent) = req.canary_percent {
+        if let Err(e) = validate_canary_percent(percent) {
+            return e.into_response();
+        }
+        endpoint.canary_percent = Some(percent);
+    }
+    if let Some(failover_to) = req.failover_to {
+        if let Err(message) = state
+            .endpoint_registry
+            .validate_failover_to(endpoint.id, failover_to)
+            .await
+        {
+            return AppError(LbError::Common(CommonError::Validation(message))).into_response();
+        }
+        endpoint.failover_to = Some(failover_to);
+    }
+    if !req.depends_on.is_empty() {
+        let depends_on = dedupe_ids(req.depends_on);
+        if let Err(message) = state
+            .endpoint_registry
+            .validate_depends_on(endpoint.id, &depends_on)
+            .await
+        {
+            return AppError(LbError::Common(CommonError::Validation(message))).into_response();
+        }
+        endpoint.depends_on = depends_on;
+    }
+    if let Some(slots) = req.slots {
+        if let Err(e) = validate_slots(slots) {
+            return e.into_response();
+        }
+        endpoint.slots = Some(slots);
+    }
     endpoint.api_key = req.api_key.clone();
     endpoint.health_check_interval_secs = req.health_check_interval_secs;
     endpoint.inference_timeout_secs = req.inference_timeout_secs;
@@ -590,12 +959,21 @@ pub async fn create_endpoint(
     if !req.capabilities.is_empty() {
         endpoint.capabilities = req.capabilities;
     }
+    endpoint.tags = normalize_tags(req.tags);
 
-    match db::create_endpoint(&state.db_pool, &endpoint).await {
-        Ok(()) => {
-            // EndpointRegistryキャッシュも更新（DBは既に保存済みなのでキャッシュのみ）
-            state.endpoint_registry.add_to_cache(endpoint.clone()).await;
-
+    match state
+        .endpoint_registry
+        .add_with_policy(endpoint.clone(), on_duplicate)
+        .await
+    {
+        // 検出・登録の間に同じURLが登録された場合
+        Ok(id) if id != endpoint.id => match state.endpoint_registry.get(id).await {
+            Some(existing) => {
+                (StatusCode::OK, Json(EndpointResponse::from(existing))).into_response()
+            }
+            None => AppError(LbError::EndpointNotFound(id)).into_response(),
+        },
+        Ok(_) => {
             // SPEC-f8e3a1b7, SPEC-e8e9326e: エンドポイント固有の方法でデバイス情報を取得
             let endpoint_id = endpoint.id;
             let base_url = endpoint.base_url.clone();
@@ -668,6 +1046,12 @@ pub async fn create_endpoint(
                                 "Failed to refresh model mappings"
                             );
                         }
+                        state_clone.endpoint_registry.notify_models_changed(
+                            endpoint_clone.id,
+                            &result.added_models,
+                            &result.removed_models,
+                            &result.updated_models,
+                        );
                         tracing::info!(
                             endpoint_id = %endpoint_clone.id,
                             added = result.added,
@@ -688,21 +1072,51 @@ pub async fn create_endpoint(
 
             (StatusCode::CREATED, Json(EndpointResponse::from(endpoint))).into_response()
         }
+        Err(LbError::Conflict(message)) => AppError(LbError::Conflict(message)).into_response(),
         Err(e) => {
-            let error_str = e.to_string();
-            if error_str.contains("UNIQUE constraint failed") {
-                AppError(LbError::Conflict(
-                    "Endpoint with this name or URL already exists".to_string(),
-                ))
-                .into_response()
-            } else {
-                tracing::error!("Failed to create endpoint: {}", e);
-                AppError(LbError::Database("Failed to create endpoint".to_string())).into_response()
-            }
+            tracing::error!("Failed to create endpoint: {}", e);
+            AppError(LbError::Database("Failed to create endpoint".to_string())).into_response()
         }
     }
 }
 
+/// GET /api/endpoints/duplicates - base_url が重複しているエンドポイントの検出
+///
+/// URLを正規化（スキーム・ホストの小文字化、末尾スラッシュ除去、デフォルトポート補完）
+/// したうえで一致するエンドポイントをグループ化し、残す候補と統合候補を提案する。
+/// 残す候補はオンラインのものを優先し、同条件なら最も古く登録されたもの。
+pub async fn list_duplicate_endpoints(State(state): State<AppState>) -> impl IntoResponse {
+    let groups: Vec<DuplicateEndpointGroup> = state
+        .endpoint_registry
+        .find_duplicate_groups()
+        .await
+        .into_iter()
+        .map(|(normalized_url, endpoints)| {
+            let suggested_keep = preferred_duplicate(&endpoints)
+                .map(|ep| ep.id)
+                .unwrap_or(endpoints[0].id);
+            let suggested_remove = endpoints
+                .iter()
+                .map(|ep| ep.id)
+                .filter(|id| *id != suggested_keep)
+                .collect();
+            DuplicateEndpointGroup {
+                normalized_url,
+                endpoints: endpoints.into_iter().map(EndpointResponse::from).collect(),
+                suggested_keep,
+                suggested_remove,
+            }
+        })
+        .collect();
+
+    let total = groups.len();
+    (
+        StatusCode::OK,
+        Json(ListDuplicateEndpointsResponse { groups, total }),
+    )
+        .into_response()
+}
+
 /// GET /api/endpoints - エンドポイント一覧
 pub async fn list_endpoints(
     State(state): State<AppState>,
@@ -727,6 +1141,13 @@ pub async fn list_endpoints(
 
             let total = filtered_endpoints.len();
             let mut response_endpoints = Vec::with_capacity(total);
+            let active_requests: std::collections::HashMap<Uuid, u32> = state
+                .load_manager
+                .snapshots()
+                .await
+                .into_iter()
+                .map(|snapshot| (snapshot.endpoint_id, snapshot.active_requests))
+                .collect();
 
             for ep in filtered_endpoints {
                 let ep_id = ep.id;
@@ -738,6 +1159,7 @@ pub async fn list_endpoints(
                 } else {
                     response.model_count = Some(0);
                 }
+                response.active_requests = Some(active_requests.get(&ep_id).copied().unwrap_or(0));
 
                 response_endpoints.push(response);
             }
@@ -767,8 +1189,11 @@ pub async fn get_endpoint(
                 Ok(m) => Some(m.into_iter().map(EndpointModelResponse::from).collect()),
                 Err(_) => None,
             };
+            let diagnosis =
+                crate::health::EndpointDiagnosis::from_last_error(endpoint.last_error.as_deref());
             let mut response = EndpointResponse::from(endpoint);
             response.models = models;
+            response.diagnosis = diagnosis;
             (StatusCode::OK, Json(response)).into_response()
         }
         Ok(None) => AppError(LbError::EndpointNotFound(id)).into_response(),
@@ -845,6 +1270,19 @@ pub async fn update_endpoint(
         }
     }
 
+    // URL変更時の重複チェック（正規化後に比較）
+    if let Some(ref new_url) = req.base_url {
+        if let Some(other) = state.endpoint_registry.find_by_base_url(new_url).await {
+            if other.id != existing.id {
+                return AppError(LbError::Conflict(format!(
+                    "Endpoint with URL '{}' is already registered as '{}'",
+                    new_url, other.name
+                )))
+                .into_response();
+            }
+        }
+    }
+
     // 更新内容を適用
     let original_base_url = existing.base_url.clone();
     let mut updated = existing;
@@ -867,13 +1305,50 @@ pub async fn update_endpoint(
     if let Some(notes_value) = req.notes {
         updated.notes = notes_value;
     }
+    if let Some(tags) = req.tags {
+        updated.tags = normalize_tags(tags);
+    }
+    // failover_to: None=未指定(そのまま), Some(None)=解除, Some(Some(v))=設定（循環は拒否）
+    if let Some(failover_to) = req.failover_to {
+        if let Some(target) = failover_to {
+            if let Err(message) = state
+                .endpoint_registry
+                .validate_failover_to(updated.id, target)
+                .await
+            {
+                return AppError(LbError::Common(CommonError::Validation(message))).into_response();
+            }
+        }
+        updated.failover_to = failover_to;
+    }
+    if let Some(depends_on) = req.depends_on {
+        let depends_on = dedupe_ids(depends_on);
+        if let Err(message) = state
+            .endpoint_registry
+            .validate_depends_on(updated.id, &depends_on)
+            .await
+        {
+            return AppError(LbError::Common(CommonError::Validation(message))).into_response();
+        }
+        updated.depends_on = depends_on;
+    }
+    // slots: None=未指定(そのまま), Some(None)=既定値に戻す, Some(Some(v))=設定
+    if let Some(slots) = req.slots {
+        if let Some(value) = slots {
+            if let Err(e) = validate_slots(value) {
+                return e.into_response();
+            }
+        }
+        updated.slots = slots;
+    }
 
     // SPEC-e8e9326e: base_url変更時はタイプを再検出
     if updated.base_url != original_base_url {
-        let detection_result = detect_endpoint_type_with_client(
+        let detection_result = detect_endpoint_type_cached(
             &state.http_client,
             &updated.base_url,
             updated.api_key.as_deref(),
+            true,
         )
         .await;
 
@@ -935,86 +1410,724 @@ pub async fn delete_endpoint(
     }
 }
 
-/// POST /api/endpoints/:id/test - 接続テスト
-pub async fn test_endpoint(
+/// PUT /api/endpoints/:id/weight - 重み変更（`ramp_secs` 指定で段階的に変更）
+pub async fn set_endpoint_weight(
     Extension(claims): Extension<Claims>,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Json(req): Json<SetEndpointWeightRequest>,
 ) -> impl IntoResponse {
     // Admin権限チェック
     if let Err(e) = ensure_admin(&claims) {
         return e.into_response();
     }
 
-    // エンドポイントを取得
-    let endpoint = match db::get_endpoint(&state.db_pool, id).await {
-        Ok(Some(ep)) => ep,
-        Ok(None) => return AppError(LbError::EndpointNotFound(id)).into_response(),
-        Err(e) => {
-            tracing::error!("Failed to get endpoint for test: {}", e);
-            return AppError(LbError::Database("Failed to get endpoint".to_string()))
-                .into_response();
-        }
+    let ramp_secs = req.ramp_secs.unwrap_or(0);
+    if ramp_secs > MAX_WEIGHT_RAMP_SECS {
+        return AppError(LbError::Common(CommonError::Validation(format!(
+            "ramp_secs must be at most {} seconds",
+            MAX_WEIGHT_RAMP_SECS
+        ))))
+        .into_response();
+    }
+
+    let ramp = (ramp_secs > 0).then_some(std::time::Duration::from_secs(ramp_secs));
+    let previous_effective_weight = match state
+        .load_manager
+        .set_endpoint_weight(id, req.weight, ramp)
+        .await
+    {
+        Ok(previous) => previous,
+        Err(e) => return AppError(e).into_response(),
     };
 
-    let response = run_connection_test(&state, &endpoint).await;
-    (StatusCode::OK, Json(response)).into_response()
+    let effective_weight = match state.endpoint_registry.get(id).await {
+        Some(endpoint) => state.load_manager.effective_weight(&endpoint).await,
+        None => req.weight as f64,
+    };
+
+    (
+        StatusCode::OK,
+        Json(SetEndpointWeightResponse {
+            endpoint_id: id,
+            weight: req.weight,
+            previous_effective_weight,
+            effective_weight,
+            ramp_secs,
+        }),
+    )
+        .into_response()
 }
 
-/// POST /api/endpoints/:id/sync - モデル一覧同期
-pub async fn sync_endpoint_models(
+/// POST /api/endpoints/:id/tags - タグ追加
+pub async fn add_endpoint_tags(
     Extension(claims): Extension<Claims>,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Json(req): Json<AddEndpointTagsRequest>,
 ) -> impl IntoResponse {
     // Admin権限チェック
     if let Err(e) = ensure_admin(&claims) {
         return e.into_response();
     }
 
-    // エンドポイントを取得
-    let endpoint = match db::get_endpoint(&state.db_pool, id).await {
-        Ok(Some(ep)) => ep,
-        Ok(None) => return AppError(LbError::EndpointNotFound(id)).into_response(),
-        Err(e) => {
-            tracing::error!("Failed to get endpoint for sync: {}", e);
-            return AppError(LbError::Database("Failed to get endpoint".to_string()))
-                .into_response();
-        }
+    let Some(endpoint) = state.endpoint_registry.get(id).await else {
+        return AppError(LbError::EndpointNotFound(id)).into_response();
     };
+    let tags = normalize_tags(endpoint.tags.into_iter().chain(req.tags).collect());
+    save_endpoint_tags(&state, id, tags).await
+}
 
-    let result = sync::sync_models_with_type(
-        &state.db_pool,
-        &state.http_client,
-        id,
-        &endpoint.base_url,
-        endpoint.api_key.as_deref(),
-        endpoint.inference_timeout_secs as u64,
-        Some(endpoint.endpoint_type),
-    )
-    .await;
-
-    match result {
-        Ok(result) => {
-            // EndpointRegistryキャッシュをリロードしてモデルマッピングを更新
-            let _ = state.endpoint_registry.reload().await;
+/// DELETE /api/endpoints/:id/tags/:tag - タグ削除（付いていないタグは何もしない）
+pub async fn remove_endpoint_tag(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path((id, tag)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    // Admin権限チェック
+    if let Err(e) = ensure_admin(&claims) {
+        return e.into_response();
+    }
 
-            let synced_models = result
-                .models
-                .into_iter()
-                .map(EndpointModelResponse::from)
-                .collect();
+    let Some(endpoint) = state.endpoint_registry.get(id).await else {
+        return AppError(LbError::EndpointNotFound(id)).into_response();
+    };
+    let tag = tag.trim();
+    let tags = endpoint.tags.into_iter().filter(|t| t != tag).collect();
+    save_endpoint_tags(&state, id, tags).await
+}
 
-            (
-                StatusCode::OK,
-                Json(SyncModelsResponse {
-                    synced_models,
-                    added: result.added,
-                    removed: result.removed,
-                    updated: result.updated,
-                }),
-            )
-                .into_response()
+async fn save_endpoint_tags(state: &AppState, id: Uuid, tags: Vec<String>) -> Response {
+    match state.endpoint_registry.update_tags(id, tags.clone()).await {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(EndpointTagsResponse {
+                endpoint_id: id,
+                tags,
+            }),
+        )
+            .into_response(),
+        Ok(false) => AppError(LbError::EndpointNotFound(id)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to update endpoint tags: {}", e);
+            AppError(LbError::Database(
+                "Failed to update endpoint tags".to_string(),
+            ))
+            .into_response()
+        }
+    }
+}
+
+/// 一括更新の1件分を検証し、DBへ書き込む内容を組み立てる
+async fn plan_bulk_update(
+    state: &AppState,
+    updated_by: &str,
+    key: &str,
+    settings: BulkEndpointSettings,
+) -> Result<PlannedBulkUpdate, String> {
+    let id = Uuid::parse_str(key).map_err(|_| format!("Invalid endpoint id: {}", key))?;
+    let previous = state
+        .endpoint_registry
+        .get(id)
+        .await
+        .ok_or_else(|| format!("Endpoint not found: {}", id))?;
+
+    if settings.weight.is_none() && settings.enabled.is_none() && settings.tags.is_none() {
+        return Err("No settings to update".to_string());
+    }
+    let ramp_secs = settings.ramp_secs.unwrap_or(0);
+    if ramp_secs > 0 && settings.weight.is_none() {
+        return Err("ramp_secs requires weight".to_string());
+    }
+    if ramp_secs > MAX_WEIGHT_RAMP_SECS {
+        return Err(format!(
+            "ramp_secs must be at most {} seconds",
+            MAX_WEIGHT_RAMP_SECS
+        ));
+    }
+
+    let operational_state = settings.enabled.map(|enabled| EndpointOperationalState {
+        endpoint_id: id,
+        state: if enabled {
+            OperationalState::Active
+        } else {
+            OperationalState::Disabled
+        },
+        reason: (!enabled).then(|| "bulk update".to_string()),
+        updated_by: Some(updated_by.to_string()),
+        updated_at: chrono::Utc::now(),
+    });
+
+    Ok(PlannedBulkUpdate {
+        previous,
+        ramp: (ramp_secs > 0).then_some(Duration::from_secs(ramp_secs)),
+        update: db::EndpointSettingsUpdate {
+            endpoint_id: id,
+            weight: settings.weight,
+            tags: settings.tags.map(normalize_tags),
+            operational_state,
+        },
+    })
+}
+
+/// PATCH /api/endpoints/bulk-update - 重み・enabled・タグの一括更新
+///
+/// リクエストボディはエンドポイントID → 設定のマップ。検証に通った更新は
+/// 1トランザクションでまとめて保存し、IDごとの成否を返す。`ramp_secs` を
+/// 指定した重み変更は `PUT /api/endpoints/:id/weight` と同様に段階的に反映する。
+pub async fn bulk_update_endpoints(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Json(req): Json<std::collections::BTreeMap<String, BulkEndpointSettings>>,
+) -> impl IntoResponse {
+    // Admin権限チェック
+    if let Err(e) = ensure_admin(&claims) {
+        return e.into_response();
+    }
+    if req.is_empty() {
+        return AppError(LbError::Common(CommonError::Validation(
+            "At least one endpoint must be specified".to_string(),
+        )))
+        .into_response();
+    }
+
+    let mut results = Vec::with_capacity(req.len());
+    let mut planned = Vec::new();
+    for (key, settings) in req {
+        match plan_bulk_update(&state, &claims.sub, &key, settings).await {
+            Ok(plan) => planned.push(plan),
+            Err(error) => results.push(BulkEndpointUpdateResult {
+                endpoint_id: key,
+                success: false,
+                error: Some(error),
+            }),
+        }
+    }
+
+    let updates: Vec<_> = planned.iter().map(|plan| plan.update.clone()).collect();
+    let mut applied = Vec::new();
+    match state
+        .endpoint_registry
+        .apply_settings_updates(&updates)
+        .await
+    {
+        Ok(()) => {
+            for plan in planned {
+                let update = plan.update;
+                if let Some(weight) = update.weight {
+                    state
+                        .load_manager
+                        .apply_weight_ramp(&plan.previous, weight, plan.ramp)
+                        .await;
+                }
+                if let Some(operational_state) = update.operational_state.clone() {
+                    state
+                        .load_manager
+                        .set_operational_state(operational_state)
+                        .await;
+                }
+                applied.push(serde_json::json!({
+                    "endpoint_id": update.endpoint_id,
+                    "weight": update.weight,
+                    "ramp_secs": plan.ramp.map(|ramp| ramp.as_secs()),
+                    "enabled": update
+                        .operational_state
+                        .as_ref()
+                        .map(|s| s.state.accepts_requests()),
+                    "tags": update.tags,
+                }));
+                results.push(BulkEndpointUpdateResult {
+                    endpoint_id: update.endpoint_id.to_string(),
+                    success: true,
+                    error: None,
+                });
+            }
+        }
+        Err(e) => {
+            tracing::error!("Failed to bulk update endpoints: {}", e);
+            for plan in planned {
+                results.push(BulkEndpointUpdateResult {
+                    endpoint_id: plan.update.endpoint_id.to_string(),
+                    success: false,
+                    error: Some("Failed to update endpoint settings".to_string()),
+                });
+            }
+        }
+    }
+    results.sort_by(|a, b| a.endpoint_id.cmp(&b.endpoint_id));
+
+    let updated = results.iter().filter(|r| r.success).count();
+    let failed = results.len() - updated;
+    tracing::info!(updated, failed, "Endpoint settings bulk updated");
+
+    let failed_ids: Vec<_> = results
+        .iter()
+        .filter(|r| !r.success)
+        .map(|r| r.endpoint_id.clone())
+        .collect();
+    let mut response = (
+        StatusCode::OK,
+        Json(BulkEndpointUpdateResponse {
+            updated,
+            failed,
+            results,
+        }),
+    )
+        .into_response();
+    response
+        .extensions_mut()
+        .insert(crate::audit::types::AuditDetail(serde_json::json!({
+            "updated": applied,
+            "failed": failed_ids,
+        })));
+    response
+}
+
+fn validate_canary_percent(percent: u8) -> Result<(), AppError> {
+    if percent > 100 {
+        return Err(AppError(LbError::Common(CommonError::Validation(
+            "canary_percent must be between 0 and 100".to_string(),
+        ))));
+    }
+    Ok(())
+}
+
+fn validate_slots(slots: u32) -> Result<(), AppError> {
+    if slots == 0 {
+        return Err(AppError(LbError::Common(CommonError::Validation(
+            "slots must be at least 1".to_string(),
+        ))));
+    }
+    Ok(())
+}
+
+/// PUT /api/endpoints/:id/canary - カナリア割合の変更
+///
+/// 実行中に反映される。変更前後の割合を監査ログに記録する。
+pub async fn set_endpoint_canary(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetEndpointCanaryRequest>,
+) -> impl IntoResponse {
+    // Admin権限チェック
+    if let Err(e) = ensure_admin(&claims) {
+        return e.into_response();
+    }
+
+    if let Some(percent) = req.canary_percent {
+        if let Err(e) = validate_canary_percent(percent) {
+            return e.into_response();
+        }
+    }
+
+    let Some(endpoint) = state.endpoint_registry.get(id).await else {
+        return AppError(LbError::EndpointNotFound(id)).into_response();
+    };
+    match state
+        .endpoint_registry
+        .update_canary_percent(id, req.canary_percent)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return AppError(LbError::EndpointNotFound(id)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to update endpoint canary percent: {}", e);
+            return AppError(LbError::Database(
+                "Failed to update endpoint canary percent".to_string(),
+            ))
+            .into_response();
+        }
+    }
+    state.load_manager.reset_canary_outcomes(id).await;
+
+    let mut response = (
+        StatusCode::OK,
+        Json(EndpointCanaryResponse {
+            endpoint_id: id,
+            previous_canary_percent: endpoint.canary_percent,
+            canary_percent: req.canary_percent,
+        }),
+    )
+        .into_response();
+    response
+        .extensions_mut()
+        .insert(crate::audit::types::AuditDetail(serde_json::json!({
+            "previous_canary_percent": endpoint.canary_percent,
+            "canary_percent": req.canary_percent,
+        })));
+    response
+}
+
+/// PUT /api/endpoints/:id/budget - 単価・月次予算の設定
+///
+/// 単価が設定された（有料の）エンドポイントは、当月累計コストが予算に達すると
+/// 月末までルーティング候補から除外される。
+pub async fn set_endpoint_budget(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetEndpointBudgetRequest>,
+) -> impl IntoResponse {
+    // Admin権限チェック
+    if let Err(e) = ensure_admin(&claims) {
+        return e.into_response();
+    }
+
+    for (field, value) in [
+        ("monthly_budget_usd", req.monthly_budget_usd),
+        (
+            "input_cost_per_million_tokens",
+            req.input_cost_per_million_tokens,
+        ),
+        (
+            "output_cost_per_million_tokens",
+            req.output_cost_per_million_tokens,
+        ),
+    ] {
+        if value.is_some_and(|v| !v.is_finite() || v < 0.0) {
+            return AppError(LbError::Common(CommonError::Validation(format!(
+                "{} must be a non-negative number",
+                field
+            ))))
+            .into_response();
+        }
+    }
+
+    match state
+        .endpoint_registry
+        .update_budget(
+            id,
+            req.monthly_budget_usd,
+            req.input_cost_per_million_tokens,
+            req.output_cost_per_million_tokens,
+        )
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return AppError(LbError::EndpointNotFound(id)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to update endpoint budget: {}", e);
+            return AppError(LbError::Database(
+                "Failed to update endpoint budget".to_string(),
+            ))
+            .into_response();
+        }
+    }
+
+    let over_budget = match state.endpoint_registry.get(id).await {
+        Some(endpoint) => state.load_manager.is_over_budget(&endpoint),
+        None => false,
+    };
+
+    (
+        StatusCode::OK,
+        Json(EndpointBudgetResponse {
+            endpoint_id: id,
+            monthly_budget_usd: req.monthly_budget_usd,
+            input_cost_per_million_tokens: req.input_cost_per_million_tokens,
+            output_cost_per_million_tokens: req.output_cost_per_million_tokens,
+            monthly_cost_usd: crate::cloud_metrics::endpoint_monthly_cost(id),
+            over_budget,
+        }),
+    )
+        .into_response()
+}
+
+/// POST /api/endpoints/:id/redetect - エンドポイントタイプの再判別
+///
+/// 起動時の一括再判別と同じ処理を単一エンドポイントに即時実行する。
+/// タイプが変わった場合は `EndpointTypeChanged` イベントを発行する。
+pub async fn redetect_endpoint_type(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    // Admin権限チェック
+    if let Err(e) = ensure_admin(&claims) {
+        return e.into_response();
+    }
+
+    let Some(endpoint) = state.endpoint_registry.get(id).await else {
+        return AppError(LbError::EndpointNotFound(id)).into_response();
+    };
+
+    let outcome = match redetect_endpoint(
+        &state.endpoint_registry,
+        &state.http_client,
+        &endpoint,
+        REDETECTION_TIMEOUT,
+        true,
+    )
+    .await
+    {
+        Ok(outcome) => outcome,
+        Err(RedetectError::Detection(DetectionError::Unreachable(msg))) => {
+            return AppError(LbError::Http(format!("Endpoint unreachable: {}", msg)))
+                .into_response();
+        }
+        Err(RedetectError::Detection(DetectionError::UnsupportedType(msg))) => {
+            return AppError(LbError::Common(CommonError::Validation(format!(
+                "Unsupported endpoint type: {}",
+                msg
+            ))))
+            .into_response();
+        }
+        Err(RedetectError::Timeout(timeout)) => {
+            return AppError(LbError::Timeout(format!(
+                "Endpoint type detection timed out after {}s",
+                timeout.as_secs()
+            )))
+            .into_response();
+        }
+        Err(RedetectError::Database(msg)) => {
+            tracing::error!("Failed to update endpoint type: {}", msg);
+            return AppError(LbError::Database(
+                "Failed to update endpoint type".to_string(),
+            ))
+            .into_response();
+        }
+    };
+
+    if outcome.changed() {
+        tracing::info!(
+            endpoint_id = %id,
+            name = %endpoint.name,
+            old_type = ?outcome.old_type,
+            new_type = ?outcome.new_type,
+            "Endpoint type changed by manual re-detection"
+        );
+        state
+            .event_bus
+            .publish(crate::events::DashboardEvent::EndpointTypeChanged {
+                endpoint_id: id,
+                old_type: outcome.old_type,
+                new_type: outcome.new_type,
+            });
+    }
+
+    let mut response = (
+        StatusCode::OK,
+        Json(RedetectEndpointResponse {
+            endpoint_id: id,
+            old_type: outcome.old_type,
+            new_type: outcome.new_type,
+            changed: outcome.changed(),
+            reason: outcome.reason.clone(),
+        }),
+    )
+        .into_response();
+    response
+        .extensions_mut()
+        .insert(crate::audit::types::AuditDetail(serde_json::json!({
+            "endpoint_id": id,
+            "old_type": outcome.old_type,
+            "new_type": outcome.new_type,
+            "changed": outcome.changed(),
+        })));
+    response
+}
+
+async fn operational_state_response(state: &AppState, id: Uuid) -> OperationalStateResponse {
+    let current = state.load_manager.operational_state(id).await;
+    let active_requests = state
+        .load_manager
+        .snapshot(id)
+        .await
+        .map(|s| s.active_requests)
+        .unwrap_or(0);
+    match current {
+        Some(current) => OperationalStateResponse {
+            endpoint_id: id,
+            state: current.state,
+            reason: current.reason,
+            updated_by: current.updated_by,
+            updated_at: Some(current.updated_at),
+            active_requests,
+        },
+        None => OperationalStateResponse {
+            endpoint_id: id,
+            state: OperationalState::Active,
+            reason: None,
+            updated_by: None,
+            updated_at: None,
+            active_requests,
+        },
+    }
+}
+
+/// GET /api/endpoints/:id/operational-state - 運用状態の取得
+pub async fn get_operational_state(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if state.endpoint_registry.get(id).await.is_none() {
+        return AppError(LbError::EndpointNotFound(id)).into_response();
+    }
+    (
+        StatusCode::OK,
+        Json(operational_state_response(&state, id).await),
+    )
+        .into_response()
+}
+
+/// PUT /api/endpoints/:id/operational-state - 運用状態の設定
+///
+/// `draining` / `disabled` / `maintenance` のエンドポイントは新規リクエストの
+/// ルーティング候補から外れる。設定はDBに永続化され、再起動後も維持される。
+pub async fn set_operational_state(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetOperationalStateRequest>,
+) -> impl IntoResponse {
+    // Admin権限チェック
+    if let Err(e) = ensure_admin(&claims) {
+        return e.into_response();
+    }
+
+    if state.endpoint_registry.get(id).await.is_none() {
+        return AppError(LbError::EndpointNotFound(id)).into_response();
+    }
+
+    let previous = state
+        .load_manager
+        .operational_state(id)
+        .await
+        .map(|s| s.state)
+        .unwrap_or_default();
+    let operational_state = EndpointOperationalState {
+        endpoint_id: id,
+        state: req.state,
+        reason: req
+            .reason
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty()),
+        updated_by: Some(claims.sub.clone()),
+        updated_at: chrono::Utc::now(),
+    };
+
+    if let Err(e) =
+        crate::db::endpoint_operational_states::save(&state.db_pool, &operational_state).await
+    {
+        tracing::error!("Failed to persist endpoint operational state: {}", e);
+        return AppError(e).into_response();
+    }
+    state
+        .load_manager
+        .set_operational_state(operational_state.clone())
+        .await;
+
+    tracing::info!(
+        endpoint_id = %id,
+        previous = %previous,
+        state = %operational_state.state,
+        reason = operational_state.reason.as_deref().unwrap_or(""),
+        "Endpoint operational state changed"
+    );
+
+    let mut response = (
+        StatusCode::OK,
+        Json(operational_state_response(&state, id).await),
+    )
+        .into_response();
+    response
+        .extensions_mut()
+        .insert(crate::audit::types::AuditDetail(serde_json::json!({
+            "endpoint_id": id,
+            "previous_state": previous,
+            "state": operational_state.state,
+            "reason": operational_state.reason,
+        })));
+    response
+}
+
+/// POST /api/endpoints/:id/test - 接続テスト
+pub async fn test_endpoint(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    // Admin権限チェック
+    if let Err(e) = ensure_admin(&claims) {
+        return e.into_response();
+    }
+
+    // エンドポイントを取得
+    let endpoint = match db::get_endpoint(&state.db_pool, id).await {
+        Ok(Some(ep)) => ep,
+        Ok(None) => return AppError(LbError::EndpointNotFound(id)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get endpoint for test: {}", e);
+            return AppError(LbError::Database("Failed to get endpoint".to_string()))
+                .into_response();
+        }
+    };
+
+    let response = run_connection_test(&state, &endpoint).await;
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// POST /api/endpoints/:id/sync - モデル一覧同期
+pub async fn sync_endpoint_models(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    // Admin権限チェック
+    if let Err(e) = ensure_admin(&claims) {
+        return e.into_response();
+    }
+
+    // エンドポイントを取得
+    let endpoint = match db::get_endpoint(&state.db_pool, id).await {
+        Ok(Some(ep)) => ep,
+        Ok(None) => return AppError(LbError::EndpointNotFound(id)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get endpoint for sync: {}", e);
+            return AppError(LbError::Database("Failed to get endpoint".to_string()))
+                .into_response();
+        }
+    };
+
+    let result = sync::sync_models_with_type(
+        &state.db_pool,
+        &state.http_client,
+        id,
+        &endpoint.base_url,
+        endpoint.api_key.as_deref(),
+        endpoint.inference_timeout_secs as u64,
+        Some(endpoint.endpoint_type),
+    )
+    .await;
+
+    match result {
+        Ok(result) => {
+            // EndpointRegistryキャッシュをリロードしてモデルマッピングを更新
+            let _ = state.endpoint_registry.reload().await;
+            state.endpoint_registry.notify_models_changed(
+                id,
+                &result.added_models,
+                &result.removed_models,
+                &result.updated_models,
+            );
+
+            let synced_models = result
+                .models
+                .into_iter()
+                .map(EndpointModelResponse::from)
+                .collect();
+
+            (
+                StatusCode::OK,
+                Json(SyncModelsResponse {
+                    synced_models,
+                    added: result.added,
+                    removed: result.removed,
+                    updated: result.updated,
+                }),
+            )
+                .into_response()
         }
         Err(err) => {
             let lb_error = match err {
@@ -1423,6 +2536,73 @@ pub struct ModelInfoPath {
     pub model: String,
 }
 
+/// モデルの最大トークン数の手動設定リクエスト（`null` で手動設定を解除）
+#[derive(Debug, Deserialize)]
+pub struct SetModelMaxTokensRequest {
+    /// 最大トークン数（コンテキスト長）
+    pub max_tokens: Option<u32>,
+}
+
+/// PUT /api/endpoints/:id/models/:model/max-tokens - モデルの最大トークン数を手動設定
+///
+/// 手動設定した値はモデル同期・メタデータ取得による自動反映より優先される。
+pub async fn set_model_max_tokens(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(params): Path<ModelInfoPath>,
+    Json(req): Json<SetModelMaxTokensRequest>,
+) -> impl IntoResponse {
+    // Admin権限チェック
+    if let Err(e) = ensure_admin(&claims) {
+        return e.into_response();
+    }
+
+    let ModelInfoPath { id, model } = params;
+    if req.max_tokens == Some(0) {
+        return AppError(LbError::Common(CommonError::Validation(
+            "max_tokens must be greater than 0".to_string(),
+        )))
+        .into_response();
+    }
+    if state.endpoint_registry.get(id).await.is_none() {
+        return AppError(LbError::EndpointNotFound(id)).into_response();
+    }
+
+    match db::set_model_max_tokens_manual(&state.db_pool, id, &model, req.max_tokens).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return AppError(LbError::NotFound(format!(
+                "Model '{}' not found on endpoint",
+                model
+            )))
+            .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to set model max_tokens: {}", e);
+            return AppError(LbError::Database(
+                "Failed to set model max_tokens".to_string(),
+            ))
+            .into_response();
+        }
+    }
+
+    // /v1/models のモデル一覧キャッシュへ反映
+    if let Err(e) = state.endpoint_registry.refresh_model_mappings(id).await {
+        tracing::warn!(endpoint_id = %id, error = %e, "Failed to refresh model mappings");
+    }
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "endpoint_id": id,
+            "model": model,
+            "max_tokens": req.max_tokens,
+            "manual": req.max_tokens.is_some(),
+        })),
+    )
+        .into_response()
+}
+
 /// GET /api/endpoints/:id/models/:model/info - モデルメタデータ取得
 pub async fn get_model_info(
     State(state): State<AppState>,
@@ -1509,6 +2689,17 @@ mod tests {
         assert_eq!(default_inference_timeout(), 120);
     }
 
+    #[test]
+    fn test_normalize_tags_trims_and_dedupes() {
+        let tags = normalize_tags(vec![
+            " gpu-a100 ".to_string(),
+            "".to_string(),
+            "prod".to_string(),
+            "gpu-a100".to_string(),
+        ]);
+        assert_eq!(tags, vec!["gpu-a100", "prod"]);
+    }
+
     #[test]
     fn test_create_endpoint_request_minimal() {
         let json = json!({
@@ -1627,6 +2818,10 @@ mod tests {
                 health_check_interval_secs: None,
                 inference_timeout_secs: Some(1),
                 notes: None,
+                tags: None,
+                failover_to: None,
+                depends_on: None,
+                slots: None,
             }),
         )
         .await
@@ -1641,6 +2836,302 @@ mod tests {
         assert_eq!(updated.inference_timeout_secs, 1);
     }
 
+    #[tokio::test]
+    async fn add_and_remove_endpoint_tags() {
+        let _guard = TEST_LOCK.lock().await;
+        let state = TestAppStateBuilder::new().await.build().await;
+
+        let mut endpoint = Endpoint::new(
+            "tagged".to_string(),
+            "http://localhost:8080".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        endpoint.tags = vec!["prod".to_string()];
+        let endpoint_id = endpoint.id;
+        state
+            .endpoint_registry
+            .add(endpoint)
+            .await
+            .expect("add endpoint");
+
+        let claims = Claims {
+            sub: "admin-user".to_string(),
+            role: UserRole::Admin,
+            exp: 0,
+            must_change_password: false,
+        };
+
+        let response = add_endpoint_tags(
+            Extension(claims.clone()),
+            State(state.clone()),
+            Path(endpoint_id),
+            Json(AddEndpointTagsRequest {
+                tags: vec![" gpu ".to_string(), "prod".to_string()],
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.endpoint_registry.list_by_tag("gpu").await.len(), 1);
+
+        let response = remove_endpoint_tag(
+            Extension(claims.clone()),
+            State(state.clone()),
+            Path((endpoint_id, "prod".to_string())),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let updated = state.endpoint_registry.get(endpoint_id).await.unwrap();
+        assert_eq!(updated.tags, vec!["gpu"]);
+
+        let response = remove_endpoint_tag(
+            Extension(claims),
+            State(state.clone()),
+            Path((Uuid::new_v4(), "gpu".to_string())),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn redetect_endpoint_type_updates_type_and_publishes_event() {
+        let _guard = TEST_LOCK.lock().await;
+        let state = TestAppStateBuilder::new().await.build().await;
+        let mut events = state.event_bus.subscribe();
+
+        let server = MockServer::start().await;
+        for not_found in ["/api/system", "/api/v1/models", "/api/tags"] {
+            Mock::given(method("GET"))
+                .and(path(not_found))
+                .respond_with(ResponseTemplate::new(404))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "object": "list",
+                "data": [{"id": "gpt-test", "object": "model"}]
+            })))
+            .mount(&server)
+            .await;
+
+        let endpoint = Endpoint::new("upgraded".to_string(), server.uri(), EndpointType::Ollama);
+        let endpoint_id = endpoint.id;
+        state
+            .endpoint_registry
+            .add(endpoint)
+            .await
+            .expect("add endpoint");
+
+        let claims = Claims {
+            sub: "admin-user".to_string(),
+            role: UserRole::Admin,
+            exp: 0,
+            must_change_password: false,
+        };
+        let response =
+            redetect_endpoint_type(Extension(claims), State(state.clone()), Path(endpoint_id))
+                .await
+                .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["old_type"], "ollama");
+        assert_eq!(json["new_type"], "openai_compatible");
+        assert_eq!(json["changed"], true);
+
+        let updated = state.endpoint_registry.get(endpoint_id).await.unwrap();
+        assert_eq!(updated.endpoint_type, EndpointType::OpenaiCompatible);
+        let event = events.try_recv().expect("type change event");
+        assert!(matches!(
+            event,
+            crate::events::DashboardEvent::EndpointTypeChanged {
+                new_type: EndpointType::OpenaiCompatible,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn set_operational_state_persists_and_excludes_endpoint() {
+        let _guard = TEST_LOCK.lock().await;
+        let state = TestAppStateBuilder::new().await.build().await;
+
+        let endpoint = Endpoint::new(
+            "drain-target".to_string(),
+            "http://localhost:8080".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        let endpoint_id = endpoint.id;
+        state
+            .endpoint_registry
+            .add(endpoint)
+            .await
+            .expect("add endpoint");
+
+        let claims = Claims {
+            sub: "admin-user".to_string(),
+            role: UserRole::Admin,
+            exp: 0,
+            must_change_password: false,
+        };
+
+        let response = set_operational_state(
+            Extension(claims.clone()),
+            State(state.clone()),
+            Path(endpoint_id),
+            Json(SetOperationalStateRequest {
+                state: OperationalState::Draining,
+                reason: Some(" node upgrade ".to_string()),
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["state"], "draining");
+        assert_eq!(json["reason"], "node upgrade");
+        assert_eq!(json["updated_by"], "admin-user");
+
+        let persisted = crate::db::endpoint_operational_states::list(&state.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(persisted.len(), 1);
+        assert_eq!(persisted[0].state, OperationalState::Draining);
+        assert!(state
+            .load_manager
+            .operational_state(endpoint_id)
+            .await
+            .is_some());
+
+        let response = set_operational_state(
+            Extension(claims),
+            State(state.clone()),
+            Path(endpoint_id),
+            Json(SetOperationalStateRequest {
+                state: OperationalState::Active,
+                reason: None,
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(crate::db::endpoint_operational_states::list(&state.db_pool)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(state
+            .load_manager
+            .operational_state(endpoint_id)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn bulk_update_endpoints_applies_valid_entries_and_reports_failures() {
+        let _guard = TEST_LOCK.lock().await;
+        let state = TestAppStateBuilder::new().await.build().await;
+
+        let mut ids = Vec::new();
+        for index in 0..2 {
+            let endpoint = Endpoint::new(
+                format!("bulk-target-{}", index),
+                format!("http://localhost:{}", 8180 + index),
+                EndpointType::OpenaiCompatible,
+            );
+            ids.push(endpoint.id);
+            state
+                .endpoint_registry
+                .add(endpoint)
+                .await
+                .expect("add endpoint");
+        }
+        let missing = Uuid::new_v4();
+
+        let claims = Claims {
+            sub: "admin-user".to_string(),
+            role: UserRole::Admin,
+            exp: 0,
+            must_change_password: false,
+        };
+        let req = std::collections::BTreeMap::from([
+            (
+                ids[0].to_string(),
+                BulkEndpointSettings {
+                    weight: Some(5),
+                    tags: Some(vec![" gpu ".to_string(), "gpu".to_string()]),
+                    ..Default::default()
+                },
+            ),
+            (
+                ids[1].to_string(),
+                BulkEndpointSettings {
+                    weight: Some(3),
+                    ramp_secs: Some(600),
+                    enabled: Some(false),
+                    ..Default::default()
+                },
+            ),
+            (
+                missing.to_string(),
+                BulkEndpointSettings {
+                    weight: Some(2),
+                    ..Default::default()
+                },
+            ),
+            ("not-a-uuid".to_string(), BulkEndpointSettings::default()),
+        ]);
+
+        let response = bulk_update_endpoints(Extension(claims), State(state.clone()), Json(req))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["updated"], 2);
+        assert_eq!(json["failed"], 2);
+        let failed: Vec<_> = json["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|r| r["success"] == false)
+            .map(|r| r["endpoint_id"].as_str().unwrap().to_string())
+            .collect();
+        assert!(failed.contains(&missing.to_string()));
+        assert!(failed.contains(&"not-a-uuid".to_string()));
+
+        let first = db::get_endpoint(&state.db_pool, ids[0])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.weight, 5);
+        assert_eq!(first.tags, vec!["gpu"]);
+        let second = state.endpoint_registry.get(ids[1]).await.unwrap();
+        assert_eq!(second.weight, 3);
+        // ramp 中は変更前の重みから段階的に変化する
+        let effective = state.load_manager.effective_weight(&second).await;
+        assert!(effective < 3.0, "effective={effective}");
+        assert_eq!(
+            state
+                .load_manager
+                .operational_state(ids[1])
+                .await
+                .map(|s| s.state),
+            Some(OperationalState::Disabled)
+        );
+        assert_eq!(
+            crate::db::endpoint_operational_states::list(&state.db_pool)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
     #[tokio::test]
     async fn proxy_chat_completions_keeps_endpoint_online_on_client_error() {
         let _guard = TEST_LOCK.lock().await;
@@ -153,6 +153,13 @@ impl From<LbError> for AppError {
 #[allow(clippy::items_after_test_module)]
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
+        // 失敗コンテキストはレスポンス拡張へ移し、詳細エラー出力の判定に使う
+        if let LbError::WithContext { error, context } = self.0 {
+            let mut response = AppError(*error).into_response();
+            response.extensions_mut().insert(*context);
+            return response;
+        }
+
         let status = self.0.status_code();
 
         // Determine the user-facing message.
@@ -192,6 +199,7 @@ impl IntoResponse for AppError {
             LbError::Authentication(msg) => msg.clone(),
             LbError::InvalidModelName(msg) => msg.clone(),
             LbError::InsufficientStorage(msg) => msg.clone(),
+            LbError::WithContext { error, .. } => error.external_message().to_string(),
         };
 
         let payload = json!({
@@ -0,0 +1,299 @@
+//! A/Bテスト（実験）管理API
+//!
+//! A/Bテスト設定のCRUD操作とグループ別統計の取得。
+//! 変更はDBへ保存した後、LoadManagerへ即時反映する。
+
+use crate::balancer::experiment::{AssignBy, ExperimentVariant};
+use crate::balancer::{Experiment, ExperimentGroupStats};
+use crate::common::auth::{Claims, UserRole};
+use crate::common::error::{CommonError, LbError};
+use crate::db::experiments as db;
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::endpoints::normalize_tags;
+use super::error::AppError;
+
+/// A/Bテスト作成リクエスト
+#[derive(Debug, Deserialize)]
+pub struct CreateExperimentRequest {
+    /// 実験名
+    pub name: String,
+    /// 対象モデル名パターン
+    pub model_pattern: String,
+    /// 割り当てに使うリクエスト属性（デフォルト: api_key）
+    #[serde(default)]
+    pub assign_by: AssignBy,
+    /// グループBへ割り当てる割合（0〜100、デフォルト: 50）
+    #[serde(default = "default_b_percent")]
+    pub b_percent: u8,
+    /// グループAの送信先
+    #[serde(default)]
+    pub variant_a: ExperimentVariant,
+    /// グループBの送信先
+    #[serde(default)]
+    pub variant_b: ExperimentVariant,
+    /// 有効フラグ（デフォルト: true）
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_b_percent() -> u8 {
+    50
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A/Bテスト更新リクエスト
+#[derive(Debug, Deserialize)]
+pub struct UpdateExperimentRequest {
+    /// 実験名
+    pub name: Option<String>,
+    /// 対象モデル名パターン
+    pub model_pattern: Option<String>,
+    /// 割り当てに使うリクエスト属性
+    pub assign_by: Option<AssignBy>,
+    /// グループBへ割り当てる割合
+    pub b_percent: Option<u8>,
+    /// グループAの送信先
+    pub variant_a: Option<ExperimentVariant>,
+    /// グループBの送信先
+    pub variant_b: Option<ExperimentVariant>,
+    /// 有効フラグ
+    pub enabled: Option<bool>,
+}
+
+/// グループ別統計付きのA/Bテスト
+#[derive(Debug, Serialize)]
+pub struct ExperimentWithStats {
+    /// A/Bテスト設定
+    #[serde(flatten)]
+    pub experiment: Experiment,
+    /// グループ別統計（プロセス起動以降の集計）
+    pub stats: Vec<ExperimentGroupStats>,
+}
+
+/// A/Bテスト一覧レスポンス
+#[derive(Debug, Serialize)]
+pub struct ListExperimentsResponse {
+    /// A/Bテスト一覧（作成日時順、先に作成されたものが優先して適用される）
+    pub experiments: Vec<ExperimentWithStats>,
+}
+
+fn ensure_admin(claims: &Claims) -> Result<(), AppError> {
+    if claims.role != UserRole::Admin {
+        return Err(AppError(LbError::Authorization(
+            "Admin permission required".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+fn normalize_variant(variant: ExperimentVariant) -> ExperimentVariant {
+    ExperimentVariant {
+        model: variant
+            .model
+            .map(|model| model.trim().to_string())
+            .filter(|model| !model.is_empty()),
+        required_labels: normalize_tags(variant.required_labels),
+    }
+}
+
+fn validate_experiment(experiment: &Experiment) -> Result<(), AppError> {
+    if experiment.name.trim().is_empty() {
+        return Err(AppError(
+            CommonError::Validation("Name is required".to_string()).into(),
+        ));
+    }
+    if experiment.model_pattern.trim().is_empty() {
+        return Err(AppError(
+            CommonError::Validation("Model pattern is required".to_string()).into(),
+        ));
+    }
+    if experiment.b_percent > 100 {
+        return Err(AppError(
+            CommonError::Validation("b_percent must be between 0 and 100".to_string()).into(),
+        ));
+    }
+    if experiment.variant_a == experiment.variant_b {
+        return Err(AppError(
+            CommonError::Validation("Variants A and B must differ".to_string()).into(),
+        ));
+    }
+    Ok(())
+}
+
+fn with_stats(state: &AppState, experiment: Experiment) -> ExperimentWithStats {
+    let stats = state.load_manager.experiment_stats(experiment.id);
+    ExperimentWithStats { experiment, stats }
+}
+
+/// DBの内容をLoadManagerへ再読込する
+async fn reload_experiments(state: &AppState) -> Result<(), AppError> {
+    let experiments = db::list(&state.db_pool).await?;
+    state.load_manager.set_experiments(experiments).await;
+    Ok(())
+}
+
+/// GET /api/experiments - A/Bテスト一覧（グループ別統計付き）
+pub async fn list_experiments(
+    State(state): State<AppState>,
+) -> Result<Json<ListExperimentsResponse>, AppError> {
+    let experiments = db::list(&state.db_pool)
+        .await?
+        .into_iter()
+        .map(|experiment| with_stats(&state, experiment))
+        .collect();
+    Ok(Json(ListExperimentsResponse { experiments }))
+}
+
+/// GET /api/experiments/:id - A/Bテスト詳細（グループ別統計付き）
+pub async fn get_experiment(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ExperimentWithStats>, AppError> {
+    let experiment = db::get(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| AppError(LbError::NotFound(format!("Experiment {} not found", id))))?;
+    Ok(Json(with_stats(&state, experiment)))
+}
+
+/// POST /api/experiments - A/Bテスト作成
+pub async fn create_experiment(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Json(req): Json<CreateExperimentRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    ensure_admin(&claims)?;
+
+    let now = Utc::now();
+    let experiment = Experiment {
+        id: Uuid::new_v4(),
+        name: req.name.trim().to_string(),
+        model_pattern: req.model_pattern.trim().to_string(),
+        assign_by: req.assign_by,
+        b_percent: req.b_percent,
+        variant_a: normalize_variant(req.variant_a),
+        variant_b: normalize_variant(req.variant_b),
+        enabled: req.enabled,
+        created_at: now,
+        updated_at: now,
+    };
+    validate_experiment(&experiment)?;
+
+    db::create(&state.db_pool, &experiment).await?;
+    reload_experiments(&state).await?;
+
+    Ok((StatusCode::CREATED, Json(experiment)))
+}
+
+/// PUT /api/experiments/:id - A/Bテスト更新
+pub async fn update_experiment(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateExperimentRequest>,
+) -> Result<Json<Experiment>, AppError> {
+    ensure_admin(&claims)?;
+
+    let mut experiment = db::get(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| AppError(LbError::NotFound(format!("Experiment {} not found", id))))?;
+
+    if let Some(name) = req.name {
+        experiment.name = name.trim().to_string();
+    }
+    if let Some(model_pattern) = req.model_pattern {
+        experiment.model_pattern = model_pattern.trim().to_string();
+    }
+    if let Some(assign_by) = req.assign_by {
+        experiment.assign_by = assign_by;
+    }
+    if let Some(b_percent) = req.b_percent {
+        experiment.b_percent = b_percent;
+    }
+    if let Some(variant_a) = req.variant_a {
+        experiment.variant_a = normalize_variant(variant_a);
+    }
+    if let Some(variant_b) = req.variant_b {
+        experiment.variant_b = normalize_variant(variant_b);
+    }
+    if let Some(enabled) = req.enabled {
+        experiment.enabled = enabled;
+    }
+    experiment.updated_at = Utc::now();
+    validate_experiment(&experiment)?;
+
+    db::update(&state.db_pool, &experiment).await?;
+    reload_experiments(&state).await?;
+
+    Ok(Json(experiment))
+}
+
+/// DELETE /api/experiments/:id - A/Bテスト削除
+pub async fn delete_experiment(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    if !db::delete(&state.db_pool, id).await? {
+        return Err(AppError(LbError::NotFound(format!(
+            "Experiment {} not found",
+            id
+        ))));
+    }
+    reload_experiments(&state).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_request_defaults_and_variant_validation() {
+        let req: CreateExperimentRequest = serde_json::from_str(
+            r#"{"name":"ab","model_pattern":"llama*","variant_b":{"model":"qwen"}}"#,
+        )
+        .unwrap();
+        assert_eq!(req.assign_by, AssignBy::ApiKey);
+        assert_eq!(req.b_percent, 50);
+        assert!(req.enabled);
+
+        let now = Utc::now();
+        let mut experiment = Experiment {
+            id: Uuid::new_v4(),
+            name: req.name,
+            model_pattern: req.model_pattern,
+            assign_by: req.assign_by,
+            b_percent: 50,
+            variant_a: normalize_variant(req.variant_a),
+            variant_b: normalize_variant(ExperimentVariant {
+                model: Some("  ".to_string()),
+                required_labels: Vec::new(),
+            }),
+            enabled: true,
+            created_at: now,
+            updated_at: now,
+        };
+        // 空白のみのモデル指定は未指定扱いになり、A/Bが同一になる
+        assert!(validate_experiment(&experiment).is_err());
+        experiment.variant_b = normalize_variant(req.variant_b);
+        assert!(validate_experiment(&experiment).is_ok());
+        experiment.b_percent = 101;
+        assert!(validate_experiment(&experiment).is_err());
+    }
+}
@@ -0,0 +1,487 @@
+//! エンドポイントグループ管理API
+//!
+//! エンドポイントグループ（論理プール）のCRUDと、グループ単位の
+//! drain / disable / 重み設定を提供する。グループ操作はメンバー全体を
+//! 1トランザクションで更新し、LoadManagerへ即時反映する。
+
+use crate::common::auth::{Claims, UserRole};
+use crate::common::error::{CommonError, LbError};
+use crate::db::{endpoint_groups as db, endpoints as endpoints_db};
+use crate::registry::EndpointGroup;
+use crate::types::endpoint::EndpointOperationalState;
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+use super::endpoints::{
+    deserialize_optional_field, SetEndpointWeightRequest, SetOperationalStateRequest,
+    MAX_WEIGHT_RAMP_SECS,
+};
+use super::error::AppError;
+
+/// グループ作成リクエスト
+#[derive(Debug, Deserialize)]
+pub struct CreateEndpointGroupRequest {
+    /// グループ名
+    pub name: String,
+    /// 説明
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 所属エンドポイントID
+    #[serde(default)]
+    pub endpoint_ids: Vec<Uuid>,
+}
+
+/// グループ更新リクエスト
+#[derive(Debug, Deserialize)]
+pub struct UpdateEndpointGroupRequest {
+    /// グループ名
+    pub name: Option<String>,
+    /// 説明（`null` 指定で削除）
+    #[serde(default, deserialize_with = "deserialize_optional_field")]
+    pub description: Option<Option<String>>,
+    /// 所属エンドポイントID（指定時は置き換え）
+    pub endpoint_ids: Option<Vec<Uuid>>,
+}
+
+/// グループ一覧レスポンス
+#[derive(Debug, Serialize)]
+pub struct ListEndpointGroupsResponse {
+    /// グループ一覧（名前順）
+    pub groups: Vec<EndpointGroup>,
+}
+
+/// グループ単位操作のレスポンス
+#[derive(Debug, Serialize)]
+pub struct EndpointGroupOperationResponse {
+    /// グループID
+    pub group_id: Uuid,
+    /// 更新したエンドポイントID
+    pub endpoint_ids: Vec<Uuid>,
+}
+
+fn ensure_admin(claims: &Claims) -> Result<(), AppError> {
+    if claims.role != UserRole::Admin {
+        return Err(AppError(LbError::Authorization(
+            "Admin permission required".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+fn group_not_found(id: Uuid) -> AppError {
+    AppError(LbError::NotFound(format!(
+        "Endpoint group {} not found",
+        id
+    )))
+}
+
+fn normalize_description(description: Option<String>) -> Option<String> {
+    description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+}
+
+/// 名前と所属エンドポイントを検証し、重複IDを除去する
+async fn validate_group(state: &AppState, group: &mut EndpointGroup) -> Result<(), AppError> {
+    if group.name.is_empty() {
+        return Err(AppError(
+            CommonError::Validation("Name is required".to_string()).into(),
+        ));
+    }
+    let mut endpoint_ids: Vec<Uuid> = Vec::with_capacity(group.endpoint_ids.len());
+    for id in &group.endpoint_ids {
+        if state.endpoint_registry.get(*id).await.is_none() {
+            return Err(AppError(LbError::EndpointNotFound(*id)));
+        }
+        if !endpoint_ids.contains(id) {
+            endpoint_ids.push(*id);
+        }
+    }
+    endpoint_ids.sort();
+    group.endpoint_ids = endpoint_ids;
+    Ok(())
+}
+
+async fn load_group(state: &AppState, id: Uuid) -> Result<EndpointGroup, AppError> {
+    db::get(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| group_not_found(id))
+}
+
+/// GET /api/groups - グループ一覧
+pub async fn list_groups(
+    State(state): State<AppState>,
+) -> Result<Json<ListEndpointGroupsResponse>, AppError> {
+    let groups = db::list(&state.db_pool).await?;
+    Ok(Json(ListEndpointGroupsResponse { groups }))
+}
+
+/// GET /api/groups/:id - グループ詳細
+pub async fn get_group(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EndpointGroup>, AppError> {
+    Ok(Json(load_group(&state, id).await?))
+}
+
+/// POST /api/groups - グループ作成
+pub async fn create_group(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Json(req): Json<CreateEndpointGroupRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    ensure_admin(&claims)?;
+
+    let now = Utc::now();
+    let mut group = EndpointGroup {
+        id: Uuid::new_v4(),
+        name: req.name.trim().to_string(),
+        description: normalize_description(req.description),
+        endpoint_ids: req.endpoint_ids,
+        created_at: now,
+        updated_at: now,
+    };
+    validate_group(&state, &mut group).await?;
+
+    db::create(&state.db_pool, &group).await?;
+
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+/// PUT /api/groups/:id - グループ更新
+pub async fn update_group(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateEndpointGroupRequest>,
+) -> Result<Json<EndpointGroup>, AppError> {
+    ensure_admin(&claims)?;
+
+    let mut group = load_group(&state, id).await?;
+    if let Some(name) = req.name {
+        group.name = name.trim().to_string();
+    }
+    if let Some(description) = req.description {
+        group.description = normalize_description(description);
+    }
+    if let Some(endpoint_ids) = req.endpoint_ids {
+        group.endpoint_ids = endpoint_ids;
+    }
+    group.updated_at = Utc::now();
+    validate_group(&state, &mut group).await?;
+
+    if !db::update(&state.db_pool, &group).await? {
+        return Err(group_not_found(id));
+    }
+
+    Ok(Json(group))
+}
+
+/// DELETE /api/groups/:id - グループ削除（メンバーのエンドポイントは削除しない）
+pub async fn delete_group(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    if !db::delete(&state.db_pool, id).await? {
+        return Err(group_not_found(id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /api/groups/:id/operational-state - グループ全体の運用状態を設定
+///
+/// `draining` / `disabled` / `maintenance` / `active` をメンバー全員に適用する。
+/// 個別の `PUT /api/endpoints/:id/operational-state` と同様に永続化される。
+pub async fn set_group_operational_state(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetOperationalStateRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    ensure_admin(&claims)?;
+
+    let group = load_group(&state, id).await?;
+    let members = group.members(&state.endpoint_registry).await;
+    let reason = req
+        .reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    let now = Utc::now();
+    let updates: Vec<_> = members
+        .iter()
+        .map(|endpoint| endpoints_db::EndpointSettingsUpdate {
+            endpoint_id: endpoint.id,
+            weight: None,
+            tags: None,
+            operational_state: Some(EndpointOperationalState {
+                endpoint_id: endpoint.id,
+                state: req.state,
+                reason: reason.clone(),
+                updated_by: Some(claims.sub.clone()),
+                updated_at: now,
+            }),
+        })
+        .collect();
+
+    state
+        .endpoint_registry
+        .apply_settings_updates(&updates)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update endpoint group operational state: {}", e);
+            AppError(LbError::Database(
+                "Failed to update endpoint group operational state".to_string(),
+            ))
+        })?;
+    for update in &updates {
+        if let Some(operational_state) = update.operational_state.clone() {
+            state
+                .load_manager
+                .set_operational_state(operational_state)
+                .await;
+        }
+    }
+
+    let endpoint_ids: Vec<Uuid> = members.iter().map(|endpoint| endpoint.id).collect();
+    tracing::info!(
+        group_id = %id,
+        group = %group.name,
+        state = %req.state,
+        endpoints = endpoint_ids.len(),
+        "Endpoint group operational state changed"
+    );
+
+    let mut response = (
+        StatusCode::OK,
+        Json(EndpointGroupOperationResponse {
+            group_id: id,
+            endpoint_ids: endpoint_ids.clone(),
+        }),
+    )
+        .into_response();
+    response
+        .extensions_mut()
+        .insert(crate::audit::types::AuditDetail(serde_json::json!({
+            "group_id": id,
+            "group": group.name,
+            "state": req.state,
+            "reason": reason,
+            "endpoint_ids": endpoint_ids,
+        })));
+    Ok(response)
+}
+
+/// PUT /api/groups/:id/weight - グループ全体の重みを設定（`ramp_secs` 指定で段階的に変更）
+pub async fn set_group_weight(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetEndpointWeightRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    ensure_admin(&claims)?;
+
+    let ramp_secs = req.ramp_secs.unwrap_or(0);
+    if ramp_secs > MAX_WEIGHT_RAMP_SECS {
+        return Err(AppError(LbError::Common(CommonError::Validation(format!(
+            "ramp_secs must be at most {} seconds",
+            MAX_WEIGHT_RAMP_SECS
+        )))));
+    }
+    let ramp = (ramp_secs > 0).then_some(Duration::from_secs(ramp_secs));
+
+    let group = load_group(&state, id).await?;
+    let members = group.members(&state.endpoint_registry).await;
+    let updates: Vec<_> = members
+        .iter()
+        .map(|endpoint| endpoints_db::EndpointSettingsUpdate {
+            endpoint_id: endpoint.id,
+            weight: Some(req.weight),
+            tags: None,
+            operational_state: None,
+        })
+        .collect();
+
+    state
+        .endpoint_registry
+        .apply_settings_updates(&updates)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update endpoint group weight: {}", e);
+            AppError(LbError::Database(
+                "Failed to update endpoint group weight".to_string(),
+            ))
+        })?;
+    for previous in &members {
+        state
+            .load_manager
+            .apply_weight_ramp(previous, req.weight, ramp)
+            .await;
+    }
+
+    let endpoint_ids: Vec<Uuid> = members.iter().map(|endpoint| endpoint.id).collect();
+    tracing::info!(
+        group_id = %id,
+        group = %group.name,
+        weight = req.weight,
+        ramp_secs,
+        endpoints = endpoint_ids.len(),
+        "Endpoint group weight changed"
+    );
+
+    Ok(Json(EndpointGroupOperationResponse {
+        group_id: id,
+        endpoint_ids,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::{TestAppStateBuilder, TEST_LOCK};
+    use crate::types::endpoint::{Endpoint, EndpointType, OperationalState};
+    use axum::body::to_bytes;
+
+    fn admin_claims() -> Claims {
+        Claims {
+            sub: "admin-user".to_string(),
+            role: UserRole::Admin,
+            exp: 0,
+            must_change_password: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn group_operations_apply_to_all_members() {
+        let _guard = TEST_LOCK.lock().await;
+        let state = TestAppStateBuilder::new().await.build().await;
+
+        let mut ids = Vec::new();
+        for index in 0..3 {
+            let endpoint = Endpoint::new(
+                format!("group-member-{}", index),
+                format!("http://localhost:{}", 8280 + index),
+                EndpointType::OpenaiCompatible,
+            );
+            ids.push(endpoint.id);
+            state
+                .endpoint_registry
+                .add(endpoint)
+                .await
+                .expect("add endpoint");
+        }
+
+        let response = create_group(
+            Extension(admin_claims()),
+            State(state.clone()),
+            Json(CreateEndpointGroupRequest {
+                name: " gpu-pool ".to_string(),
+                description: None,
+                endpoint_ids: vec![ids[1], ids[0], ids[0]],
+            }),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let group: EndpointGroup = serde_json::from_slice(&body).unwrap();
+        assert_eq!(group.name, "gpu-pool");
+        assert_eq!(group.endpoint_ids.len(), 2);
+
+        let response = set_group_operational_state(
+            Extension(admin_claims()),
+            State(state.clone()),
+            Path(group.id),
+            Json(SetOperationalStateRequest {
+                state: OperationalState::Draining,
+                reason: Some("rack maintenance".to_string()),
+            }),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        for id in &ids[..2] {
+            assert_eq!(
+                state
+                    .load_manager
+                    .operational_state(*id)
+                    .await
+                    .map(|s| s.state),
+                Some(OperationalState::Draining)
+            );
+        }
+        assert!(state.load_manager.operational_state(ids[2]).await.is_none());
+        assert_eq!(
+            crate::db::endpoint_operational_states::list(&state.db_pool)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let response = set_group_weight(
+            Extension(admin_claims()),
+            State(state.clone()),
+            Path(group.id),
+            Json(SetEndpointWeightRequest {
+                weight: 7,
+                ramp_secs: None,
+            }),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        for id in &ids[..2] {
+            assert_eq!(state.endpoint_registry.get(*id).await.unwrap().weight, 7);
+        }
+        assert_ne!(state.endpoint_registry.get(ids[2]).await.unwrap().weight, 7);
+
+        // グループを削除してもメンバーは残る
+        let status = delete_group(
+            Extension(admin_claims()),
+            State(state.clone()),
+            Path(group.id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(state.endpoint_registry.get(ids[0]).await.is_some());
+        assert!(db::get(&state.db_pool, group.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn create_group_rejects_unknown_endpoint() {
+        let _guard = TEST_LOCK.lock().await;
+        let state = TestAppStateBuilder::new().await.build().await;
+
+        let result = create_group(
+            Extension(admin_claims()),
+            State(state),
+            Json(CreateEndpointGroupRequest {
+                name: "missing".to_string(),
+                description: None,
+                endpoint_ids: vec![Uuid::new_v4()],
+            }),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(AppError(LbError::EndpointNotFound(_)))
+        ));
+    }
+}
@@ -1,5 +1,8 @@
 //! ヘルスチェックAPIハンドラー
 //!
+//! `GET/PATCH /api/health/config` でヘルスチェックのヒステリシス設定（復帰閾値・失敗閾値・
+//! バックオフ係数）を取得・変更する。変更は実行中のヘルスチェッカーへ即時に反映される。
+//!
 //! # 廃止済み
 //!
 //! プッシュ型ヘルスチェック（POST /api/health）は廃止されました。
@@ -7,3 +10,62 @@
 //! リクエストすることで行われます（PULL型）。
 //!
 //! 参照: SPEC-e8e9326e
+
+use crate::common::auth::{Claims, UserRole};
+use crate::common::error::{CommonError, LbError};
+use crate::health::{HealthHysteresis, HealthHysteresisUpdate};
+use crate::AppState;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+
+use super::error::AppError;
+
+fn ensure_admin(claims: &Claims) -> Result<(), AppError> {
+    if claims.role != UserRole::Admin {
+        return Err(AppError(LbError::Authorization(
+            "Admin permission required".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+/// GET /api/health/config - ヘルスチェックのヒステリシス設定
+pub async fn get_health_config(State(state): State<AppState>) -> Json<HealthHysteresis> {
+    Json(state.endpoint_registry.health_hysteresis().get())
+}
+
+/// PATCH /api/health/config - ヘルスチェックのヒステリシス設定を変更
+///
+/// 指定した項目のみ変更する。不正な値が含まれる場合は 400 を返し、現行の設定を維持する。
+pub async fn update_health_config(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Json(req): Json<HealthHysteresisUpdate>,
+) -> Result<Response, AppError> {
+    ensure_admin(&claims)?;
+
+    let (previous, current) = state
+        .endpoint_registry
+        .health_hysteresis()
+        .update(req)
+        .map_err(|e| AppError(CommonError::Validation(e).into()))?;
+
+    tracing::info!(
+        recovery_threshold = current.recovery_threshold,
+        failure_threshold = current.failure_threshold,
+        backoff_factor = current.backoff_factor,
+        "Health check hysteresis updated"
+    );
+
+    let mut response = Json(current).into_response();
+    response
+        .extensions_mut()
+        .insert(crate::audit::types::AuditDetail(serde_json::json!({
+            "previous": previous,
+            "current": current,
+        })));
+    Ok(response)
+}
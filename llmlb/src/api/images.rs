@@ -2,6 +2,7 @@
 //!
 //! OpenAI互換の画像生成（Text-to-Image）・編集（Inpainting）・バリエーションAPI
 
+use crate::balancer::priority::{apply_priority_header, priority_from_headers};
 use crate::common::{
     error::LbError,
     protocol::{ImageGenerationRequest, RequestResponseRecord, RequestType},
@@ -24,7 +25,7 @@ use crate::{
         error::AppError,
         model_name::parse_quantized_model_name,
         models::load_registered_model,
-        proxy::{forward_streaming_response, save_request_record},
+        proxy::{forward_routed_response, save_request_record, RoutingHeaders},
     },
     auth::middleware::ApiKeyAuthContext,
     common::ip::{normalize_ip, normalize_socket_ip},
@@ -236,7 +237,11 @@ pub async fn generations(
     let client = &state.http_client;
     let url = backend.url("/v1/images/generations");
 
-    let response = match client.post(&url).json(&payload).send().await {
+    let response = match apply_priority_header(client.post(&url), priority_from_headers(&headers))
+        .json(&payload)
+        .send()
+        .await
+    {
         Ok(r) => r,
         Err(e) => {
             return openai_error(
@@ -266,9 +271,11 @@ pub async fn generations(
     save_request_record(state.request_history.clone(), record);
 
     // レスポンスを転送
-    forward_streaming_response(response)
-        .map_err(AppError::from)
-        .map(|r| r.into_response())
+    forward_routed_response(
+        response,
+        &RoutingHeaders::direct(&backend.0, &payload.model),
+    )
+    .map_err(AppError::from)
 }
 
 /// POST /v1/images/edits - 画像編集（Inpainting）
@@ -462,7 +469,11 @@ pub async fn edits(
         form = form.text("response_format", fmt);
     }
 
-    let response = match client.post(&url).multipart(form).send().await {
+    let response = match apply_priority_header(client.post(&url), priority_from_headers(&headers))
+        .multipart(form)
+        .send()
+        .await
+    {
         Ok(r) => r,
         Err(e) => {
             return openai_error(
@@ -492,9 +503,8 @@ pub async fn edits(
     save_request_record(state.request_history.clone(), record);
 
     // レスポンスを転送
-    forward_streaming_response(response)
+    forward_routed_response(response, &RoutingHeaders::direct(&backend.0, &model))
         .map_err(AppError::from)
-        .map(|r| r.into_response())
 }
 
 /// POST /v1/images/variations - 画像バリエーション生成
@@ -646,7 +656,11 @@ pub async fn variations(
         form = form.text("response_format", fmt);
     }
 
-    let response = match client.post(&url).multipart(form).send().await {
+    let response = match apply_priority_header(client.post(&url), priority_from_headers(&headers))
+        .multipart(form)
+        .send()
+        .await
+    {
         Ok(r) => r,
         Err(e) => {
             return openai_error(
@@ -676,9 +690,8 @@ pub async fn variations(
     save_request_record(state.request_history.clone(), record);
 
     // レスポンスを転送
-    forward_streaming_response(response)
+    forward_routed_response(response, &RoutingHeaders::direct(&backend.0, &model))
         .map_err(AppError::from)
-        .map(|r| r.into_response())
 }
 
 #[cfg(test)]
@@ -0,0 +1,68 @@
+//! リクエスト入出力サンプルAPIハンドラー
+//!
+//! `/api/dashboard/io-samples` 系のエンドポイント（adminロールのみ）
+
+use super::error::AppError;
+use crate::common::error::{CommonError, LbError};
+use crate::db::io_samples::{self, IoSample, SAMPLE_LIST_DEFAULT_LIMIT, SAMPLE_LIST_MAX_LIMIT};
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// サンプル一覧取得のクエリパラメータ
+#[derive(Debug, Deserialize)]
+pub struct IoSampleQueryParams {
+    /// 結果でフィルタ（success / error）
+    pub status: Option<String>,
+    /// 取得件数（デフォルト: 50、最大: 500）
+    pub limit: Option<i64>,
+}
+
+/// サンプル一覧レスポンス
+#[derive(Debug, Serialize)]
+pub struct IoSampleListResponse {
+    /// サンプル一覧（新しい順）
+    pub items: Vec<IoSample>,
+}
+
+/// GET /api/dashboard/io-samples - 入出力サンプル一覧取得
+pub async fn list_io_samples(
+    State(state): State<AppState>,
+    Query(params): Query<IoSampleQueryParams>,
+) -> Result<Json<IoSampleListResponse>, AppError> {
+    if let Some(status) = params.status.as_deref() {
+        if status != "success" && status != "error" {
+            return Err(AppError(LbError::Common(CommonError::Validation(format!(
+                "Invalid status: '{}'. Expected 'success' or 'error'.",
+                status
+            )))));
+        }
+    }
+    let limit = params
+        .limit
+        .unwrap_or(SAMPLE_LIST_DEFAULT_LIMIT)
+        .clamp(1, SAMPLE_LIST_MAX_LIMIT);
+
+    let items =
+        io_samples::list_samples(&state.db_pool, params.status.as_deref(), limit, Utc::now())
+            .await
+            .map_err(|e| AppError(LbError::Database(e.to_string())))?;
+    Ok(Json(IoSampleListResponse { items }))
+}
+
+/// GET /api/dashboard/io-samples/{id} - 入出力サンプル詳細取得
+pub async fn get_io_sample(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<IoSample>, AppError> {
+    io_samples::get_sample(&state.db_pool, id, Utc::now())
+        .await
+        .map_err(|e| AppError(LbError::Database(e.to_string())))?
+        .map(Json)
+        .ok_or_else(|| AppError(LbError::NotFound(format!("I/O sample {} not found", id))))
+}
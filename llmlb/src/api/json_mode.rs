@@ -0,0 +1,144 @@
+//! JSONモード応答の検証
+//!
+//! `response_format: {"type": "json_object"}` を指定したリクエストについて、
+//! エンドポイントの応答本文がJSONとしてパース可能かを検証する。
+//! 検証は `LLMLB_JSON_MODE_VALIDATION` によるオプトインで、非ストリーミングでは
+//! 違反時に別エンドポイントでの再試行またはエラー応答を行い、ストリーミングでは
+//! 最終集約後に検証して違反を記録する。
+//!
+//! 違反はエンドポイント単位で `llmlb_json_mode_violations_total` に記録する。
+
+use once_cell::sync::Lazy;
+use prometheus::{IntCounterVec, Opts};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// 違反時にクライアントへ返すエラーメッセージ
+pub const VIOLATION_MESSAGE: &str = "Endpoint returned non-JSON content for a JSON mode request";
+
+/// 違反時のエラー種別
+pub const VIOLATION_ERROR_TYPE: &str = "invalid_json_response";
+
+static VIOLATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "llmlb_json_mode_violations_total",
+            "JSON mode responses that could not be parsed as JSON, per endpoint",
+        ),
+        &["endpoint_id", "stream"],
+    )
+    .expect("counter vec");
+    crate::metrics::registry()
+        .register(Box::new(counter.clone()))
+        .ok();
+    counter
+});
+
+/// リクエストがJSONモード（`response_format.type == "json_object"`）か
+pub fn requests_json_object(payload: &Value) -> bool {
+    payload
+        .get("response_format")
+        .and_then(|format| format.get("type"))
+        .and_then(Value::as_str)
+        == Some("json_object")
+}
+
+/// 文字列がJSONとしてパース可能か
+pub fn is_json_content(content: &str) -> bool {
+    serde_json::from_str::<Value>(content.trim()).is_ok()
+}
+
+/// Chat Completions 応答の全 `choices[].message.content` がJSONか
+///
+/// `content` が `null`（ツール呼び出しのみ等）の choice は検証対象外とする。
+pub fn response_content_is_json(body: &Value) -> bool {
+    body.get("choices")
+        .and_then(Value::as_array)
+        .map(|choices| {
+            choices
+                .iter()
+                .filter_map(|choice| choice.get("message").and_then(|m| m.get("content")))
+                .filter_map(Value::as_str)
+                .all(is_json_content)
+        })
+        .unwrap_or(true)
+}
+
+/// JSONモード違反を記録する
+pub fn record_violation(endpoint_id: Uuid, model: &str, stream: bool) {
+    VIOLATIONS
+        .with_label_values(&[endpoint_id.to_string().as_str(), bool_label(stream)])
+        .inc();
+    tracing::warn!(
+        endpoint_id = %endpoint_id,
+        model = %model,
+        stream,
+        "Endpoint returned non-JSON content for a JSON mode request"
+    );
+}
+
+/// エンドポイントのJSONモード違反数（ストリーミング/非ストリーミング合計）
+pub fn violation_count(endpoint_id: Uuid) -> u64 {
+    let endpoint_id = endpoint_id.to_string();
+    [false, true]
+        .iter()
+        .map(|stream| {
+            VIOLATIONS
+                .with_label_values(&[endpoint_id.as_str(), bool_label(*stream)])
+                .get()
+        })
+        .sum()
+}
+
+fn bool_label(value: bool) -> &'static str {
+    if value {
+        "true"
+    } else {
+        "false"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn detects_json_object_requests() {
+        assert!(requests_json_object(
+            &json!({"response_format": {"type": "json_object"}})
+        ));
+        assert!(!requests_json_object(
+            &json!({"response_format": {"type": "text"}})
+        ));
+        assert!(!requests_json_object(&json!({"model": "m"})));
+    }
+
+    #[test]
+    fn validates_choice_contents() {
+        let valid = json!({"choices": [{"message": {"content": " {\"a\": 1}\n"}}]});
+        assert!(response_content_is_json(&valid));
+
+        let invalid = json!({"choices": [
+            {"message": {"content": "{\"a\": 1}"}},
+            {"message": {"content": "Sure! Here is the JSON: {\"a\": 1}"}}
+        ]});
+        assert!(!response_content_is_json(&invalid));
+
+        // ツール呼び出しのみ（content: null）は対象外
+        let tool_only = json!({"choices": [{"message": {"content": null, "tool_calls": []}}]});
+        assert!(response_content_is_json(&tool_only));
+
+        assert!(!is_json_content(""));
+    }
+
+    #[test]
+    fn violations_are_counted_per_endpoint() {
+        let endpoint_id = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        record_violation(endpoint_id, "m", false);
+        record_violation(endpoint_id, "m", true);
+        assert_eq!(violation_count(endpoint_id), 2);
+        assert_eq!(violation_count(other), 0);
+    }
+}
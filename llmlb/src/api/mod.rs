@@ -9,33 +9,72 @@ pub mod audio;
 pub mod audit_log;
 pub mod auth;
 pub mod benchmarks;
+/// 推論リクエストボディの共通検査レイヤー
+pub mod body_inspection;
 /// カタログ検索API（HuggingFaceラッパー）
 pub mod catalog;
+/// チャット→補完変換アダプタ（レガシーな補完専用アップストリーム向け）
+pub mod chat_adapter;
 pub mod cloud_models;
 /// クラウドプロバイダプロキシ（CloudProvider trait）
 pub mod cloud_proxy;
 pub mod dashboard;
+pub mod dashboard_sse;
 pub mod dashboard_ws;
 /// エンドポイント管理API
 pub mod endpoints;
 /// APIエラーレスポンス型
 pub mod error;
+pub mod experiments;
+/// エンドポイントグループ（論理プール）管理API
+pub mod groups;
 pub mod health;
 pub mod images;
 pub mod invitations;
+/// リクエスト入出力サンプル閲覧API
+pub mod io_samples;
+/// JSONモード応答の検証
+pub mod json_mode;
 pub mod logs;
 /// モデル名のパース（量子化サフィックス対応）
 pub mod model_name;
+pub mod model_rate_limit;
 pub mod models;
 pub mod openai;
+/// OpenAI互換APIの別エンドポイントへのやり直し（再接続・フェイルオーバー・再試行）
+pub mod openai_retry;
 /// OpenAI互換APIユーティリティ
 pub mod openai_util;
+/// プロンプトキャッシュヒントの転送とプレフィックスアフィニティ
+pub mod prompt_cache;
+/// プロンプトの簡易インジェクション検査
+pub mod prompt_filter;
 pub mod proxy;
+/// 応答の簡易品質フィルタ
+pub mod quality_filter;
+/// リクエスト単位のアップストリームタイムアウト
+pub mod request_timeout;
+/// エンドポイント容量予約管理API
+pub mod reservations;
 /// Open Responses API (SPEC-0f1de549)
 pub mod responses;
+/// ルーティングポリシー管理API
+pub mod routing_policies;
+/// シャドウトラフィック管理API
+pub mod shadow;
+/// アップストリームSSEの正規化
+pub mod sse_normalize;
+/// クライアント単位の同時ストリーミング数制限
+pub mod stream_limit;
+/// ストリーミング出力レート上限管理API
+pub mod stream_rate_limits;
 /// System API (self-update)
 pub mod system;
+/// リクエストトレースのサンプリング（traceparent 対応）
+pub mod trace_sampling;
 pub mod users;
+/// 失敗時の詳細エラーコンテキスト返却（LLMLB_VERBOSE_ERRORS）
+pub mod verbose_errors;
 
 use crate::cloud_metrics;
 use crate::common::auth::{ApiKeyPermission, UserRole};
@@ -47,7 +86,7 @@ use axum::{
     http::{header, StatusCode},
     middleware,
     response::{IntoResponse, Response},
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use include_dir::{include_dir, Dir, File};
@@ -55,7 +94,7 @@ use mime_guess::MimeGuess;
 
 static DASHBOARD_ASSETS: Dir = include_dir!("$CARGO_MANIFEST_DIR/src/web/static");
 const DASHBOARD_INDEX: &str = "index.html";
-const OPENAI_BODY_LIMIT_BYTES: usize = 20 * 1024 * 1024;
+pub(crate) const OPENAI_BODY_LIMIT_BYTES: usize = 20 * 1024 * 1024;
 // NOTE: Playground機能は廃止され、ダッシュボード内のエンドポイント別Playgroundに移行
 // const PLAYGROUND_INDEX: &str = "playground.html";
 // Force rebuild when embedded dashboard/playground assets change.
@@ -121,6 +160,7 @@ pub fn create_app(state: AppState) -> Router {
             "/me/api-keys/{id}",
             put(api_keys::update_api_key).delete(api_keys::delete_api_key),
         )
+        .route("/me/api-keys/{id}/revoke", post(api_keys::revoke_api_key))
         .layer(middleware::from_fn(
             crate::auth::middleware::require_password_changed_middleware,
         ))
@@ -173,7 +213,10 @@ pub fn create_app(state: AppState) -> Router {
     // モデル管理API (Admin のみ: register/delete)
     let models_manage_routes = Router::new()
         .route("/models/register", post(models::register_model))
-        .route("/models/{*model_name}", delete(models::delete_model))
+        .route(
+            "/models/{*model_name}",
+            delete(models::delete_model).put(model_rate_limit::put_model_subresource),
+        )
         .layer(middleware::from_fn(
             crate::auth::middleware::require_password_changed_middleware,
         ))
@@ -190,9 +233,10 @@ pub fn create_app(state: AppState) -> Router {
             crate::auth::middleware::jwt_or_api_key_permission_middleware,
         ));
 
-    // Prometheus metrics（cloud prefix含む独自メトリクス）
+    // Prometheus metrics（cloud prefix含む独自メトリクス）とクラウドコスト集計
     let metrics_routes = Router::new()
         .route("/metrics/cloud", get(cloud_metrics::export_metrics))
+        .route("/cloud/cost", get(cloud_metrics::get_cloud_cost))
         .layer(middleware::from_fn(
             crate::auth::middleware::require_password_changed_middleware,
         ))
@@ -214,11 +258,26 @@ pub fn create_app(state: AppState) -> Router {
             crate::auth::middleware::jwt_auth_middleware,
         ));
 
+    // JWTシークレットローテーションAPI（JWTのみ）
+    let jwt_rotation_routes = Router::new()
+        .route("/admin/rotate-jwt-secret", post(auth::rotate_jwt_secret))
+        .layer(middleware::from_fn(
+            crate::auth::middleware::require_password_changed_middleware,
+        ))
+        .layer(middleware::from_fn(
+            crate::auth::middleware::csrf_protect_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.jwt_secret.clone(),
+            crate::auth::middleware::jwt_auth_middleware,
+        ));
+
     let admin_routes = Router::new()
         .merge(users_routes)
         .merge(my_api_keys_routes)
         .merge(invitations_routes)
         .merge(new_invitations_routes)
+        .merge(jwt_rotation_routes)
         .merge(node_logs_routes)
         .merge(models_manage_routes)
         .merge(metrics_routes);
@@ -260,6 +319,11 @@ pub fn create_app(state: AppState) -> Router {
             get(dashboard::get_monthly_token_stats),
         )
         .route("/dashboard/logs/lb", get(logs::get_lb_logs))
+        // ダッシュボードイベントのSSE配信（adminのみ）
+        .route(
+            "/events/stream",
+            get(dashboard_sse::dashboard_events_stream),
+        )
         // モデル別リクエスト統計（全エンドポイント横断）
         .route(
             "/dashboard/model-stats",
@@ -315,7 +379,11 @@ pub fn create_app(state: AppState) -> Router {
         .route(
             "/dashboard/audit-logs/verify",
             post(audit_log::verify_hash_chain),
-        );
+        )
+        .route("/audit/export", get(audit_log::export_audit_logs))
+        // リクエスト入出力サンプル: レダクション済みでも本文を含むためadminロールのみ
+        .route("/dashboard/io-samples", get(io_samples::list_io_samples))
+        .route("/dashboard/io-samples/{id}", get(io_samples::get_io_sample));
 
     let dashboard_api_routes = {
         let dashboard_general_routes = dashboard_general_routes
@@ -374,6 +442,10 @@ pub fn create_app(state: AppState) -> Router {
     // WRITE: endpoints.manage (JWTはadminのみ)
     let endpoint_read_routes = Router::new()
         .route("/endpoints", get(endpoints::list_endpoints))
+        .route(
+            "/endpoints/duplicates",
+            get(endpoints::list_duplicate_endpoints),
+        )
         .route("/endpoints/{id}", get(endpoints::get_endpoint))
         .route(
             "/endpoints/{id}/models",
@@ -404,7 +476,51 @@ pub fn create_app(state: AppState) -> Router {
         .route(
             "/endpoints/{id}/model-tps",
             get(dashboard::get_endpoint_model_tps),
-        );
+        )
+        .route(
+            "/endpoints/{id}/tps/history",
+            get(dashboard::get_endpoint_tps_history),
+        )
+        .route(
+            "/endpoints/{id}/operational-state",
+            get(endpoints::get_operational_state),
+        )
+        // ラベルベースルーティングポリシー
+        .route(
+            "/routing-policies",
+            get(routing_policies::list_routing_policies),
+        )
+        .route(
+            "/routing-policies/{id}",
+            get(routing_policies::get_routing_policy),
+        )
+        // エンドポイントグループ
+        .route("/groups", get(groups::list_groups))
+        .route("/groups/{id}", get(groups::get_group))
+        // A/Bテスト
+        .route("/experiments", get(experiments::list_experiments))
+        .route("/experiments/{id}", get(experiments::get_experiment))
+        // シャドウトラフィック
+        .route("/shadow", get(shadow::list_shadow_targets))
+        .route("/shadow/{id}", get(shadow::get_shadow_target))
+        // エンドポイント容量予約
+        .route("/reservations", get(reservations::list_reservations))
+        .route("/reservations/{id}", get(reservations::get_reservation))
+        // ストリーミング出力レート上限
+        .route(
+            "/stream-rate-limits",
+            get(stream_rate_limits::list_stream_rate_limits),
+        )
+        .route(
+            "/stream-rate-limits/{id}",
+            get(stream_rate_limits::get_stream_rate_limit),
+        )
+        // リクエストキューの時系列
+        .route("/queue/history", get(dashboard::get_queue_history))
+        // レイテンシ基準（自動キャリブレーション）
+        .route("/balancer/baseline", get(dashboard::get_latency_baseline))
+        // ヘルスチェックのヒステリシス設定
+        .route("/health/config", get(health::get_health_config));
     let endpoint_read_routes = endpoint_read_routes
         .layer(middleware::from_fn(
             crate::auth::middleware::csrf_protect_middleware,
@@ -421,11 +537,44 @@ pub fn create_app(state: AppState) -> Router {
 
     let endpoint_manage_routes = Router::new()
         .route("/endpoints", post(endpoints::create_endpoint))
+        .route(
+            "/endpoints/bulk-update",
+            patch(endpoints::bulk_update_endpoints),
+        )
         .route(
             "/endpoints/{id}",
             put(endpoints::update_endpoint).delete(endpoints::delete_endpoint),
         )
         .route("/endpoints/{id}/test", post(endpoints::test_endpoint))
+        .route(
+            "/endpoints/{id}/redetect",
+            post(endpoints::redetect_endpoint_type),
+        )
+        .route(
+            "/endpoints/{id}/weight",
+            put(endpoints::set_endpoint_weight),
+        )
+        .route(
+            "/endpoints/{id}/budget",
+            put(endpoints::set_endpoint_budget),
+        )
+        .route(
+            "/endpoints/{id}/canary",
+            put(endpoints::set_endpoint_canary),
+        )
+        .route("/endpoints/{id}/tags", post(endpoints::add_endpoint_tags))
+        .route(
+            "/endpoints/{id}/tags/{tag}",
+            delete(endpoints::remove_endpoint_tag),
+        )
+        .route(
+            "/endpoints/{id}/operational-state",
+            put(endpoints::set_operational_state),
+        )
+        .route(
+            "/endpoints/{id}/models/{model}/max-tokens",
+            put(endpoints::set_model_max_tokens),
+        )
         .route(
             "/endpoints/{id}/sync",
             post(endpoints::sync_endpoint_models),
@@ -436,7 +585,51 @@ pub fn create_app(state: AppState) -> Router {
         .route(
             "/endpoints/{id}/models/delete",
             post(endpoints::delete_endpoint_model_handler),
-        );
+        )
+        .route(
+            "/routing-policies",
+            post(routing_policies::create_routing_policy),
+        )
+        .route(
+            "/routing-policies/{id}",
+            put(routing_policies::update_routing_policy)
+                .delete(routing_policies::delete_routing_policy),
+        )
+        .route("/groups", post(groups::create_group))
+        .route(
+            "/groups/{id}",
+            put(groups::update_group).delete(groups::delete_group),
+        )
+        .route(
+            "/groups/{id}/operational-state",
+            put(groups::set_group_operational_state),
+        )
+        .route("/groups/{id}/weight", put(groups::set_group_weight))
+        .route("/experiments", post(experiments::create_experiment))
+        .route(
+            "/experiments/{id}",
+            put(experiments::update_experiment).delete(experiments::delete_experiment),
+        )
+        .route("/shadow", post(shadow::create_shadow_target))
+        .route(
+            "/shadow/{id}",
+            put(shadow::update_shadow_target).delete(shadow::delete_shadow_target),
+        )
+        .route("/reservations", post(reservations::create_reservation))
+        .route(
+            "/reservations/{id}",
+            put(reservations::update_reservation).delete(reservations::delete_reservation),
+        )
+        .route(
+            "/stream-rate-limits",
+            post(stream_rate_limits::create_stream_rate_limit),
+        )
+        .route(
+            "/stream-rate-limits/{id}",
+            put(stream_rate_limits::update_stream_rate_limit)
+                .delete(stream_rate_limits::delete_stream_rate_limit),
+        )
+        .route("/health/config", patch(health::update_health_config));
     let endpoint_manage_routes = endpoint_manage_routes
         .layer(middleware::from_fn(
             crate::auth::middleware::csrf_protect_middleware,
@@ -500,6 +693,8 @@ pub fn create_app(state: AppState) -> Router {
     // モデル一覧API (Admin OR Runtime スコープで利用可能)
     // /api/models はランタイム同期用の登録済みモデル一覧
     // /api/models/hub はダッシュボード向けの対応モデル一覧 + ステータス
+    // /api/models/deployment はモデル×エンドポイントのデプロイ状態マトリクス
+    // /api/models/{id}/usage はモデル別レートリミットの現在消費量
     let models_list_routes = {
         let cfg = crate::auth::middleware::JwtOrApiKeyPermissionConfig {
             app_state: state.clone(),
@@ -510,6 +705,12 @@ pub fn create_app(state: AppState) -> Router {
         Router::new()
             .route("/models", get(models::list_models))
             .route("/models/hub", get(models::list_models_with_status))
+            .route("/models/deployment", get(models::get_model_deployment))
+            // /api/models/{id}/usage・/api/models/{id}/rate-limit（モデルIDは `/` を含み得る）
+            .route(
+                "/models/{*model_name}",
+                get(model_rate_limit::get_model_subresource),
+            )
             .layer(middleware::from_fn_with_state(
                 cfg,
                 crate::auth::middleware::jwt_or_api_key_permission_middleware,
@@ -533,29 +734,80 @@ pub fn create_app(state: AppState) -> Router {
         .route("/v1/images/generations", post(images::generations))
         .route("/v1/images/edits", post(images::edits))
         .route("/v1/images/variations", post(images::variations))
-        .layer(DefaultBodyLimit::max(OPENAI_BODY_LIMIT_BYTES));
+        .layer(DefaultBodyLimit::max(OPENAI_BODY_LIMIT_BYTES))
+        .layer(middleware::from_fn(prompt_filter::prompt_filter_middleware))
+        // モデル別の同時実行上限（レートリミットで拒否されたリクエストは枠を消費しない）
+        .layer(middleware::from_fn_with_state(
+            state.inference_gate.clone(),
+            crate::inference_gate::model_concurrency_middleware,
+        ))
+        .layer(middleware::from_fn(
+            model_rate_limit::model_rate_limit_middleware,
+        ))
+        .layer(middleware::from_fn(stream_limit::stream_limit_middleware))
+        // ボディを参照する上記ミドルウェア向けに、JSONボディを一度だけ読み取って拡張に載せる
+        .layer(middleware::from_fn(
+            body_inspection::inspect_body_middleware,
+        ))
+        // 失敗コンテキストは operator 権限のAPIキーにのみ返す（APIキー認証より内側）
+        .layer(middleware::from_fn(
+            verbose_errors::verbose_error_middleware,
+        ));
     let inference_routes = inference_routes
         .layer(middleware::from_fn_with_state(
             ApiKeyPermission::OpenaiInference,
             crate::auth::middleware::require_api_key_permission_middleware,
         ))
+        // 使用済み nonce の拒否（レート制限で拒否されたリクエストは nonce を消費しない）
+        .layer(middleware::from_fn(crate::auth::nonce::nonce_middleware))
+        // APIキー・クライアントIPごとのレート制限（APIキー認証より内側）
+        .layer(middleware::from_fn(
+            crate::auth::rate_limit::rate_limit_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             state.db_pool.clone(),
             crate::auth::middleware::api_key_auth_middleware,
         ));
     // Self-update drain gate: reject new inference requests and track in-flight requests.
-    let inference_routes = inference_routes.layer(middleware::from_fn_with_state(
-        state.inference_gate.clone(),
-        crate::inference_gate::inference_gate_middleware,
-    ));
+    let inference_routes = inference_routes
+        .layer(middleware::from_fn_with_state(
+            state.inference_gate.clone(),
+            crate::inference_gate::inference_gate_middleware,
+        ))
+        // 段階別タイムライン計測の起点（認証より外側）
+        .layer(middleware::from_fn(
+            crate::metrics::timeline::request_timeline_middleware,
+        ))
+        // トレースのサンプリング判定（最も外側で全ての応答を対象にする）
+        .layer(middleware::from_fn(
+            trace_sampling::trace_sampling_middleware,
+        ));
 
     let anthropic_inference_routes = Router::new()
         .route("/v1/messages", post(anthropic::messages))
         .layer(DefaultBodyLimit::max(OPENAI_BODY_LIMIT_BYTES))
+        .layer(middleware::from_fn(prompt_filter::prompt_filter_middleware))
+        // モデル別の同時実行上限（レートリミットで拒否されたリクエストは枠を消費しない）
+        .layer(middleware::from_fn_with_state(
+            state.inference_gate.clone(),
+            crate::inference_gate::model_concurrency_middleware,
+        ))
+        .layer(middleware::from_fn(
+            model_rate_limit::model_rate_limit_middleware,
+        ))
+        .layer(middleware::from_fn(stream_limit::stream_limit_middleware))
+        // ボディを参照する上記ミドルウェア向けに、JSONボディを一度だけ読み取って拡張に載せる
+        .layer(middleware::from_fn(
+            body_inspection::inspect_body_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             ApiKeyPermission::OpenaiInference,
             crate::auth::middleware::require_anthropic_api_key_permission_middleware,
         ))
+        .layer(middleware::from_fn(crate::auth::nonce::nonce_middleware))
+        .layer(middleware::from_fn(
+            crate::auth::rate_limit::rate_limit_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             state.db_pool.clone(),
             crate::auth::middleware::anthropic_api_key_auth_middleware,
@@ -563,6 +815,9 @@ pub fn create_app(state: AppState) -> Router {
         .layer(middleware::from_fn_with_state(
             state.inference_gate.clone(),
             crate::inference_gate::inference_gate_middleware,
+        ))
+        .layer(middleware::from_fn(
+            trace_sampling::trace_sampling_middleware,
         ));
 
     // `/v1/models*` は外部クライアント(APIキー)からのみ参照される
@@ -575,6 +830,10 @@ pub fn create_app(state: AppState) -> Router {
             ApiKeyPermission::OpenaiModelsRead,
             crate::auth::middleware::require_api_key_permission_middleware,
         ))
+        .layer(middleware::from_fn(crate::auth::nonce::nonce_middleware))
+        .layer(middleware::from_fn(
+            crate::auth::rate_limit::rate_limit_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             state.db_pool.clone(),
             crate::auth::middleware::api_key_auth_middleware,
@@ -614,6 +873,27 @@ pub fn create_app(state: AppState) -> Router {
 
     let ws_routes = Router::new().route("/ws/dashboard", get(dashboard_ws::dashboard_ws_handler));
 
+    // Prometheus /metrics（LLMLB_METRICS_AUTH: 既定はループバックのみ）
+    let prometheus_routes =
+        Router::new().route("/metrics", get(crate::metrics::exporter::export_metrics));
+    let prometheus_routes = match crate::config::metrics_auth_mode() {
+        crate::config::MetricsAuthMode::Local => prometheus_routes.layer(middleware::from_fn(
+            crate::metrics::exporter::local_only_middleware,
+        )),
+        crate::config::MetricsAuthMode::ApiKey => {
+            prometheus_routes.layer(middleware::from_fn_with_state(
+                crate::auth::middleware::JwtOrApiKeyPermissionConfig {
+                    app_state: state.clone(),
+                    required_permission: ApiKeyPermission::MetricsRead,
+                    jwt_required_role: Some(UserRole::Admin),
+                    api_key_role: UserRole::Admin,
+                },
+                crate::auth::middleware::jwt_or_api_key_permission_middleware,
+            ))
+        }
+        crate::config::MetricsAuthMode::Open => prometheus_routes,
+    };
+
     Router::new()
         // `/api/*`: llmlb独自API（互換不要・versioned）
         .nest("/api", api_routes)
@@ -625,6 +905,7 @@ pub fn create_app(state: AppState) -> Router {
         // NOTE: Playground機能は廃止され、ダッシュボード内のエンドポイント別Playgroundに移行
         // /playground/* ルートは削除済み
         .merge(ws_routes)
+        .merge(prometheus_routes)
         .fallback(|| async { StatusCode::NOT_FOUND })
         // 監査ログミドルウェア (SPEC-8301d106): 全リクエストをキャプチャ（最外層）
         .layer(middleware::from_fn_with_state(
@@ -718,6 +999,56 @@ mod tests {
         assert!(bytes.starts_with(b"<!DOCTYPE html"));
     }
 
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_prometheus_metrics_is_local_only_by_default() {
+        std::env::remove_var("LLMLB_METRICS_AUTH");
+        crate::metrics::exporter::record_endpoint_request(uuid::Uuid::new_v4(), true, 10);
+        let state = test_state().await;
+        let mut app = create_app(state);
+
+        let request = |peer: &str| {
+            let mut request = Request::builder()
+                .method(axum::http::Method::GET)
+                .uri("/metrics")
+                .body(Body::empty())
+                .unwrap();
+            request.extensions_mut().insert(axum::extract::ConnectInfo(
+                peer.parse::<std::net::SocketAddr>().unwrap(),
+            ));
+            request
+        };
+        // 信頼済みでないローカルのプロキシが中継したリクエスト
+        let forwarded = |peer: &str| {
+            let mut request = Request::builder()
+                .method(axum::http::Method::GET)
+                .uri("/metrics")
+                .header("x-forwarded-for", "192.0.2.10")
+                .body(Body::empty())
+                .unwrap();
+            request.extensions_mut().insert(axum::extract::ConnectInfo(
+                peer.parse::<std::net::SocketAddr>().unwrap(),
+            ));
+            request
+        };
+
+        let response = app.call(request("192.0.2.10:50000")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = app.call(forwarded("127.0.0.1:50000")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.call(request("127.0.0.1:50000")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response.headers()[axum::http::header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(content_type.starts_with("text/plain"));
+        let bytes = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let body = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(body.contains("llmlb_endpoint_requests_total"));
+    }
+
     // NOTE: test_playground_static_served は廃止
     // Playground機能はダッシュボード内のエンドポイント別Playgroundに移行 (#playground/:endpointId)
 
@@ -0,0 +1,239 @@
+//! モデル別レートリミット（RPM/TPM）
+//!
+//! 推論リクエストの `model` ごとに直近1分間のリクエスト数・トークン消費量を数え、
+//! 上限を超えたリクエストを 429 で拒否するミドルウェアと、上限設定・消費量参照の
+//! 管理APIを提供する。リミッタ本体は [`crate::balancer::model_rate_limit`]。
+//!
+//! モデルIDは `/` を含み得るため、管理APIは `/api/models/{*path}` のワイルドカードで
+//! 受けて末尾のサブリソース名（`/usage`・`/rate-limit`）で振り分ける。
+
+use crate::audit::types::AuditDetail;
+use crate::balancer::{model_rate_limiter, ModelRateLimit, ModelUsage};
+use crate::common::error::{CommonError, LbError};
+use crate::db::model_rate_limits as db;
+use crate::AppState;
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::body_inspection::InspectedBody;
+use super::error::AppError;
+use super::openai_util::queue_error_response;
+
+/// 消費量参照のサブリソース名
+const USAGE_SUFFIX: &str = "/usage";
+/// 上限設定のサブリソース名
+const RATE_LIMIT_SUFFIX: &str = "/rate-limit";
+
+/// 上限設定リクエスト（両方 `null` で設定を削除）
+#[derive(Debug, Deserialize)]
+pub struct SetModelRateLimitRequest {
+    /// 1分あたりのリクエスト数上限
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// 1分あたりのトークン数上限
+    #[serde(default)]
+    pub tokens_per_minute: Option<u64>,
+}
+
+fn not_found(path: &str) -> AppError {
+    AppError(LbError::NotFound(format!(
+        "Not found: /api/models/{}",
+        path
+    )))
+}
+
+/// 429 応答を生成する（`/v1/messages` はAnthropic形式、それ以外はOpenAI形式。`Retry-After` 付き）
+pub(crate) fn limited_response(path: &str, message: &str, retry_after_secs: u64) -> Response {
+    if path.starts_with("/v1/messages") {
+        let mut response = (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(json!({
+                "type": "error",
+                "error": {
+                    "type": "rate_limit_error",
+                    "message": message
+                }
+            })),
+        )
+            .into_response();
+        if let Ok(value) = HeaderValue::from_str(&retry_after_secs.to_string()) {
+            response.headers_mut().insert(header::RETRY_AFTER, value);
+        }
+        response
+    } else {
+        queue_error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            message,
+            "rate_limit_exceeded",
+            Some(retry_after_secs),
+        )
+    }
+}
+
+/// モデル別レートリミットミドルウェア
+///
+/// APIキー認証ミドルウェアより内側に配置する。上限の有無に関わらず、受け付けた
+/// リクエストはモデル別のRPM消費として記録する（`/api/models/{id}/usage` 用）。
+/// `model` は共通のボディ検査レイヤー（[`InspectedBody`]）から取得する。
+pub async fn model_rate_limit_middleware(request: Request, next: Next) -> Response {
+    let Some(model) = request
+        .extensions()
+        .get::<InspectedBody>()
+        .and_then(|body| body.model().map(str::to_string))
+    else {
+        return next.run(request).await;
+    };
+    let path = request.uri().path().to_string();
+
+    if let Err(exceeded) = model_rate_limiter().try_acquire(&model) {
+        let retry_after_secs = exceeded.retry_after().as_secs().max(1);
+        tracing::warn!(
+            path = %path,
+            model = %model,
+            limit = exceeded.kind(),
+            retry_after_secs,
+            "Rejected request: model rate limit reached"
+        );
+        let mut response = limited_response(&path, &exceeded.to_string(), retry_after_secs);
+        response.extensions_mut().insert(AuditDetail(json!({
+            "event": "model_rate_limited",
+            "model": model,
+            "limit": exceeded.kind(),
+        })));
+        return response;
+    }
+
+    next.run(request).await
+}
+
+/// GET /api/models/:model_id/usage - モデル別の現在消費量
+/// GET /api/models/:model_id/rate-limit - モデル別の上限設定
+pub async fn get_model_subresource(Path(path): Path<String>) -> Result<Response, AppError> {
+    if let Some(model_id) = path.strip_suffix(USAGE_SUFFIX) {
+        return Ok(Json(model_rate_limiter().usage(model_id)).into_response());
+    }
+    if let Some(model_id) = path.strip_suffix(RATE_LIMIT_SUFFIX) {
+        let limit = model_rate_limiter().limit(model_id).ok_or_else(|| {
+            AppError(LbError::NotFound(format!(
+                "Rate limit for model {} not found",
+                model_id
+            )))
+        })?;
+        return Ok(Json(limit).into_response());
+    }
+    Err(not_found(&path))
+}
+
+/// PUT /api/models/:model_id/rate-limit - モデル別の上限設定
+///
+/// 両方の上限が `null` の場合は設定を削除する。変更はDBへ保存した後、
+/// リミッタへ即時反映する。
+pub async fn put_model_subresource(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+    Json(req): Json<SetModelRateLimitRequest>,
+) -> Result<Json<ModelUsage>, AppError> {
+    let Some(model_id) = path
+        .strip_suffix(RATE_LIMIT_SUFFIX)
+        .filter(|id| !id.is_empty())
+    else {
+        return Err(not_found(&path));
+    };
+    if req.requests_per_minute == Some(0) || req.tokens_per_minute == Some(0) {
+        return Err(AppError(
+            CommonError::Validation("rate limits must be greater than 0".to_string()).into(),
+        ));
+    }
+
+    if req.requests_per_minute.is_none() && req.tokens_per_minute.is_none() {
+        db::delete(&state.db_pool, model_id).await?;
+        model_rate_limiter().remove_limit(model_id);
+        tracing::info!(model = %model_id, "Model rate limit removed");
+    } else {
+        let limit = ModelRateLimit {
+            model_id: model_id.to_string(),
+            requests_per_minute: req.requests_per_minute,
+            tokens_per_minute: req.tokens_per_minute,
+            updated_at: Utc::now(),
+        };
+        db::upsert(&state.db_pool, &limit).await?;
+        model_rate_limiter().set_limit(limit);
+        tracing::info!(
+            model = %model_id,
+            requests_per_minute = ?req.requests_per_minute,
+            tokens_per_minute = ?req.tokens_per_minute,
+            "Model rate limit updated"
+        );
+    }
+
+    Ok(Json(model_rate_limiter().usage(model_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::{TestAppStateBuilder, TEST_LOCK};
+    use axum::body::to_bytes;
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn put_rate_limit_persists_and_usage_reports_limits() {
+        let _guard = TEST_LOCK.lock().await;
+        let state = TestAppStateBuilder::new().await.build().await;
+        let model_id = "org/costly-model-put-test";
+
+        let usage = put_model_subresource(
+            State(state.clone()),
+            Path(format!("{}/rate-limit", model_id)),
+            Json(SetModelRateLimitRequest {
+                requests_per_minute: Some(1),
+                tokens_per_minute: Some(5000),
+            }),
+        )
+        .await
+        .expect("set rate limit")
+        .0;
+        assert_eq!(usage.requests_per_minute, Some(1));
+        assert_eq!(usage.tokens_per_minute, Some(5000));
+        assert!(db::get(&state.db_pool, model_id).await.unwrap().is_some());
+
+        assert!(model_rate_limiter().try_acquire(model_id).is_ok());
+        assert!(model_rate_limiter().try_acquire(model_id).is_err());
+
+        let response = get_model_subresource(Path(format!("{}/usage", model_id)))
+            .await
+            .expect("usage")
+            .into_response();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["model_id"], model_id);
+        assert_eq!(json["requests_last_minute"], 1);
+        assert_eq!(json["requests_per_minute"], 1);
+
+        put_model_subresource(
+            State(state.clone()),
+            Path(format!("{}/rate-limit", model_id)),
+            Json(SetModelRateLimitRequest {
+                requests_per_minute: None,
+                tokens_per_minute: None,
+            }),
+        )
+        .await
+        .expect("clear rate limit");
+        assert!(db::get(&state.db_pool, model_id).await.unwrap().is_none());
+        assert!(model_rate_limiter().limit(model_id).is_none());
+
+        let err = get_model_subresource(Path(format!("{}/unknown", model_id)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.0, LbError::NotFound(_)));
+    }
+}
@@ -362,6 +362,170 @@ pub async fn list_models_with_status(
     Ok(Json(result))
 }
 
+/// エンドポイント上のモデルのデプロイ状態
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentState {
+    /// オンラインかつ初期化完了で、推論に使用可能
+    Ready,
+    /// モデルは存在するが、エンドポイントが初期化中または未確認
+    Loading,
+    /// モデルが存在しない、またはエンドポイントがオフライン/エラー
+    NotAvailable,
+}
+
+/// マトリクスの列となるエンドポイント
+#[derive(Debug, Clone, Serialize)]
+pub struct DeploymentEndpoint {
+    /// エンドポイントID
+    pub id: uuid::Uuid,
+    /// エンドポイント名
+    pub name: String,
+    /// エンドポイントの状態
+    pub status: crate::types::endpoint::EndpointStatus,
+}
+
+/// モデル×エンドポイントのセル
+#[derive(Debug, Clone, Serialize)]
+pub struct DeploymentCell {
+    /// エンドポイントID
+    pub endpoint_id: uuid::Uuid,
+    /// デプロイ状態
+    pub state: DeploymentState,
+    /// このエンドポイントでのモデルTPS（未計測はNone）
+    pub tps: Option<f64>,
+}
+
+/// モデル別のデプロイ状態
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDeployment {
+    /// モデルID
+    pub model_id: String,
+    /// readyなエンドポイント数
+    pub ready_endpoints: usize,
+    /// readyなエンドポイントのTPS合計（未計測のみの場合はNone）
+    pub total_tps: Option<f64>,
+    /// エンドポイントごとの状態（`endpoints` と同じ順序）
+    pub endpoints: Vec<DeploymentCell>,
+}
+
+/// デプロイ状態マトリクス
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDeploymentMatrix {
+    /// マトリクスの列（エンドポイント）
+    pub endpoints: Vec<DeploymentEndpoint>,
+    /// マトリクスの行（モデル）
+    pub models: Vec<ModelDeployment>,
+}
+
+/// `EndpointModel` と `LoadManager` の状態からデプロイ状態マトリクスを構築する
+///
+/// `tps` はエンドポイント×モデル単位のTPS（API種別の最大値）。
+fn build_deployment_matrix(
+    mut endpoints: Vec<crate::types::endpoint::Endpoint>,
+    endpoint_models: &HashMap<uuid::Uuid, Vec<crate::types::endpoint::EndpointModel>>,
+    initializing: &std::collections::HashSet<uuid::Uuid>,
+    tps: &HashMap<(uuid::Uuid, String), f64>,
+) -> ModelDeploymentMatrix {
+    use crate::types::endpoint::EndpointStatus;
+
+    endpoints.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+    let model_ids: std::collections::BTreeSet<&str> = endpoint_models
+        .values()
+        .flatten()
+        .map(|model| model.model_id.as_str())
+        .collect();
+
+    let models = model_ids
+        .into_iter()
+        .map(|model_id| {
+            let cells: Vec<DeploymentCell> = endpoints
+                .iter()
+                .map(|endpoint| {
+                    let has_model = endpoint_models
+                        .get(&endpoint.id)
+                        .map(|models| models.iter().any(|m| m.model_id == model_id))
+                        .unwrap_or(false);
+                    let state = match endpoint.status {
+                        _ if !has_model => DeploymentState::NotAvailable,
+                        EndpointStatus::Online if !initializing.contains(&endpoint.id) => {
+                            DeploymentState::Ready
+                        }
+                        EndpointStatus::Online | EndpointStatus::Pending => {
+                            DeploymentState::Loading
+                        }
+                        EndpointStatus::Offline | EndpointStatus::Error => {
+                            DeploymentState::NotAvailable
+                        }
+                    };
+                    DeploymentCell {
+                        endpoint_id: endpoint.id,
+                        state,
+                        tps: tps.get(&(endpoint.id, model_id.to_string())).copied(),
+                    }
+                })
+                .collect();
+
+            let ready: Vec<&DeploymentCell> = cells
+                .iter()
+                .filter(|cell| cell.state == DeploymentState::Ready)
+                .collect();
+            let measured: Vec<f64> = ready.iter().filter_map(|cell| cell.tps).collect();
+            ModelDeployment {
+                model_id: model_id.to_string(),
+                ready_endpoints: ready.len(),
+                total_tps: (!measured.is_empty()).then(|| measured.iter().sum()),
+                endpoints: cells,
+            }
+        })
+        .collect();
+
+    ModelDeploymentMatrix {
+        endpoints: endpoints
+            .into_iter()
+            .map(|endpoint| DeploymentEndpoint {
+                id: endpoint.id,
+                name: endpoint.name,
+                status: endpoint.status,
+            })
+            .collect(),
+        models,
+    }
+}
+
+/// GET /api/models/deployment - モデル×エンドポイントのデプロイ状態マトリクス
+pub async fn get_model_deployment(
+    State(state): State<AppState>,
+) -> Result<Json<ModelDeploymentMatrix>, AppError> {
+    let endpoints = state.endpoint_registry.list().await;
+    let initializing = state.load_manager.initializing_endpoint_ids().await;
+
+    let mut endpoint_models = HashMap::new();
+    let mut tps: HashMap<(uuid::Uuid, String), f64> = HashMap::new();
+    for endpoint in &endpoints {
+        let models = state
+            .endpoint_registry
+            .list_models(endpoint.id)
+            .await
+            .map_err(|e| AppError(LbError::Database(e.to_string())))?;
+        endpoint_models.insert(endpoint.id, models);
+
+        for info in state.load_manager.get_model_tps(endpoint.id).await {
+            if let Some(value) = info.tps {
+                let entry = tps.entry((endpoint.id, info.model_id)).or_insert(value);
+                *entry = entry.max(value);
+            }
+        }
+    }
+
+    Ok(Json(build_deployment_matrix(
+        endpoints,
+        &endpoint_models,
+        &initializing,
+        &tps,
+    )))
+}
+
 /// 登録済みモデルを名前で取得
 pub async fn load_registered_model(
     pool: &SqlitePool,
@@ -469,6 +633,87 @@ async fn fetch_hf_file_bytes(
     Ok(bytes.to_vec())
 }
 
+/// プレフライト検証で巨大モデルとして警告するサイズ（50GB）
+const LARGE_MODEL_WARNING_BYTES: u64 = 50 * 1024 * 1024 * 1024;
+
+/// HEADリクエストでアーティファクトの存在を確認し、サイズ（取得できた場合）を返す
+///
+/// リダイレクト先（LFS）の `Content-Length`、またはHFの `X-Linked-Size` をサイズとして扱う。
+async fn head_hf_file(
+    http_client: &reqwest::Client,
+    repo: &str,
+    filename: &str,
+) -> Result<Option<u64>, LbError> {
+    let base_url = hf_base_url();
+    let url = hf_resolve_url(&base_url, repo, filename);
+    let mut req = http_client.head(&url);
+    if let Ok(token) = std::env::var("HF_TOKEN") {
+        req = req.bearer_auth(token);
+    }
+    let resp = req.timeout(HF_HTTP_TIMEOUT).send().await.map_err(|e| {
+        let msg = format!("Failed to reach source for file: {}", filename);
+        if e.is_timeout() {
+            LbError::Timeout(msg)
+        } else {
+            LbError::Http(msg)
+        }
+    })?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let reason = match status {
+            reqwest::StatusCode::NOT_FOUND => "file does not exist at the source",
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                "access denied (gated or private repository; set HF_TOKEN)"
+            }
+            _ => "source returned an error",
+        };
+        return Err(LbError::Common(CommonError::Validation(format!(
+            "Preflight check failed for {}/{}: {} (HTTP {})",
+            repo,
+            filename,
+            reason,
+            status.as_u16()
+        ))));
+    }
+
+    let header_size = |name: &str| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+    };
+    Ok(header_size("x-linked-size")
+        .or_else(|| header_size(reqwest::header::CONTENT_LENGTH.as_str())))
+}
+
+/// 登録対象の重みファイルをすべてHEADで確認し、合計サイズを返す（サイズ不明分は0）
+async fn preflight_artifacts(
+    http_client: &reqwest::Client,
+    repo: &str,
+    filenames: &[String],
+) -> Result<u64, LbError> {
+    let sizes = futures::future::try_join_all(
+        filenames
+            .iter()
+            .map(|filename| head_hf_file(http_client, repo, filename)),
+    )
+    .await?;
+    Ok(sizes.into_iter().map(|size| size.unwrap_or(0)).sum())
+}
+
+/// 登録するアーティファクトを構成する重みファイル
+fn artifact_weight_files(siblings: &[HfSibling], selection: &ArtifactSelection) -> Vec<String> {
+    match selection.format {
+        ArtifactFormat::Gguf => vec![selection.filename.clone()],
+        ArtifactFormat::Safetensors => siblings
+            .iter()
+            .filter(|s| s.rfilename.to_ascii_lowercase().ends_with(".safetensors"))
+            .map(|s| s.rfilename.clone())
+            .collect(),
+    }
+}
+
 fn is_gguf_filename(filename: &str) -> bool {
     filename.to_ascii_lowercase().ends_with(".gguf")
 }
@@ -974,6 +1219,9 @@ pub struct RegisterModelRequest {
     /// オプションのchat_template（GGUFに含まれない場合の補助）
     #[serde(default)]
     pub chat_template: Option<String>,
+    /// `true` でプレフライト検証（HEADによる存在確認・サイズ取得）を省略する
+    #[serde(default)]
+    pub skip_preflight: bool,
 }
 
 async fn compute_gpu_warnings(
@@ -1017,6 +1265,8 @@ async fn compute_gpu_warnings(
 /// - `filename` を指定するとそのアーティファクトを主として登録
 /// - 未指定の場合、リポジトリ内のアーティファクトが一意であれば自動選択
 /// - safetensors では `config.json` / `tokenizer.json` が必須
+/// - 登録前に重みファイルをHEADで確認し（`skip_preflight: true` で省略）、存在しなければ失敗、
+///   50GBを超える場合は推奨メモリ超過の警告を返す
 #[allow(deprecated)] // NodeRegistry migration in progress
 pub async fn register_model(
     State(state): State<AppState>,
@@ -1051,38 +1301,47 @@ pub async fn register_model(
     let siblings = fetch_repo_siblings(&state.http_client, &repo).await?;
     let selection = resolve_primary_artifact(&siblings, filename_hint)?;
 
+    // プレフライト検証: 重みファイルの存在確認とサイズ取得
+    let preflight_size = if req.skip_preflight {
+        None
+    } else {
+        let files = artifact_weight_files(&siblings, &selection);
+        Some(preflight_artifacts(&state.http_client, &repo, &files).await?)
+    };
+
     let (content_length, required_memory, warnings) = {
-        let (size, required) = match selection.format {
-            ArtifactFormat::Gguf => {
-                let size = siblings
-                    .iter()
-                    .find(|s| s.rfilename == selection.filename)
-                    .map(sibling_size_bytes)
-                    .unwrap_or(0);
-                const REQUIRED_MEMORY_RATIO: f64 = 1.5;
-                let required = if size > 0 {
-                    ((size as f64) * REQUIRED_MEMORY_RATIO).ceil() as u64
-                } else {
-                    0
-                };
-                (size, required)
-            }
-            ArtifactFormat::Safetensors => {
-                let total = siblings
-                    .iter()
-                    .filter(|s| s.rfilename.to_ascii_lowercase().ends_with(".safetensors"))
-                    .map(sibling_size_bytes)
-                    .sum::<u64>();
-                const REQUIRED_MEMORY_RATIO: f64 = 1.5;
-                let required = if total > 0 {
-                    ((total as f64) * REQUIRED_MEMORY_RATIO).ceil() as u64
-                } else {
-                    0
-                };
-                (total, required)
-            }
+        let size = match selection.format {
+            ArtifactFormat::Gguf => siblings
+                .iter()
+                .find(|s| s.rfilename == selection.filename)
+                .map(sibling_size_bytes)
+                .unwrap_or(0),
+            ArtifactFormat::Safetensors => siblings
+                .iter()
+                .filter(|s| s.rfilename.to_ascii_lowercase().ends_with(".safetensors"))
+                .map(sibling_size_bytes)
+                .sum::<u64>(),
+        };
+        // siblings にサイズが無い場合はプレフライトで取得したサイズを使う
+        let size = if size == 0 {
+            preflight_size.unwrap_or(0)
+        } else {
+            size
+        };
+        const REQUIRED_MEMORY_RATIO: f64 = 1.5;
+        let required = if size > 0 {
+            ((size as f64) * REQUIRED_MEMORY_RATIO).ceil() as u64
+        } else {
+            0
         };
-        let warnings = compute_gpu_warnings(&state.endpoint_registry, required).await;
+        let mut warnings = compute_gpu_warnings(&state.endpoint_registry, required).await;
+        if preflight_size.is_some() && size > LARGE_MODEL_WARNING_BYTES {
+            warnings.push(format!(
+                "Model size is {:.1}GB (over 50GB); recommended memory {:.1}GB may exceed endpoint capacity",
+                size as f64 / (1024.0 * 1024.0 * 1024.0),
+                required as f64 / (1024.0 * 1024.0 * 1024.0),
+            ));
+        }
         (size, required, warnings)
     };
 
@@ -1140,6 +1399,7 @@ pub async fn register_model(
         "filename": selection.filename,
         "size_bytes": content_length,
         "required_memory_bytes": required_memory,
+        "preflight": if req.skip_preflight { "skipped" } else { "passed" },
         "warnings": warnings,
     });
 
@@ -1301,6 +1561,71 @@ mod tests {
     use serial_test::serial;
     use std::net::TcpListener;
 
+    #[test]
+    fn test_build_deployment_matrix_states_and_tps() {
+        use crate::types::endpoint::{
+            Endpoint, EndpointModel, EndpointStatus, EndpointType, SupportedAPI,
+        };
+
+        let endpoint = |name: &str, status: EndpointStatus| {
+            let mut endpoint = Endpoint::new(
+                name.to_string(),
+                format!("http://{}:8000", name),
+                EndpointType::Vllm,
+            );
+            endpoint.status = status;
+            endpoint
+        };
+        let model = |endpoint_id: uuid::Uuid, model_id: &str| EndpointModel {
+            endpoint_id,
+            model_id: model_id.to_string(),
+            capabilities: None,
+            max_tokens: None,
+            last_checked: None,
+            supported_apis: vec![SupportedAPI::ChatCompletions],
+            canonical_name: None,
+        };
+
+        let a = endpoint("a", EndpointStatus::Online);
+        let b = endpoint("b", EndpointStatus::Online);
+        let c = endpoint("c", EndpointStatus::Offline);
+        let (a_id, b_id, c_id) = (a.id, b.id, c.id);
+        let endpoint_models = HashMap::from([
+            (a_id, vec![model(a_id, "llama"), model(a_id, "qwen")]),
+            (b_id, vec![model(b_id, "llama")]),
+            (c_id, vec![model(c_id, "llama")]),
+        ]);
+        let initializing = std::collections::HashSet::from([b_id]);
+        let tps = HashMap::from([
+            ((a_id, "llama".to_string()), 12.5),
+            ((b_id, "llama".to_string()), 30.0),
+        ]);
+
+        let matrix = build_deployment_matrix(vec![c, b, a], &endpoint_models, &initializing, &tps);
+        let names: Vec<&str> = matrix.endpoints.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+
+        let llama = &matrix.models[0];
+        assert_eq!(llama.model_id, "llama");
+        let states: Vec<DeploymentState> = llama.endpoints.iter().map(|c| c.state).collect();
+        assert_eq!(
+            states,
+            vec![
+                DeploymentState::Ready,
+                DeploymentState::Loading,
+                DeploymentState::NotAvailable
+            ]
+        );
+        assert_eq!(llama.ready_endpoints, 1);
+        // 初期化中エンドポイントのTPSは合計に含めない
+        assert_eq!(llama.total_tps, Some(12.5));
+
+        let qwen = &matrix.models[1];
+        assert_eq!(qwen.ready_endpoints, 1);
+        assert_eq!(qwen.total_tps, None);
+        assert_eq!(qwen.endpoints[1].state, DeploymentState::NotAvailable);
+    }
+
     #[test]
     fn test_validate_model_name_valid() {
         assert!(validate_model_name("gpt-oss").is_ok());
@@ -1483,6 +1808,55 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn preflight_reports_missing_file_and_sums_sizes() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path(
+                "/org/repo/resolve/main/model-00001-of-00002.safetensors",
+            ))
+            .respond_with(ResponseTemplate::new(200).insert_header("x-linked-size", "30000"))
+            .mount(&mock)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path(
+                "/org/repo/resolve/main/model-00002-of-00002.safetensors",
+            ))
+            .respond_with(ResponseTemplate::new(200).insert_header("x-linked-size", "12000"))
+            .mount(&mock)
+            .await;
+
+        let previous = std::env::var("HF_BASE_URL").ok();
+        std::env::set_var("HF_BASE_URL", mock.uri());
+
+        let client = reqwest::Client::new();
+        let shards = vec![
+            "model-00001-of-00002.safetensors".to_string(),
+            "model-00002-of-00002.safetensors".to_string(),
+        ];
+        let total = preflight_artifacts(&client, "org/repo", &shards)
+            .await
+            .expect("preflight should pass");
+        assert_eq!(total, 42000);
+
+        let err = preflight_artifacts(&client, "org/repo", &["absent.gguf".to_string()])
+            .await
+            .expect_err("missing file should fail");
+        assert_eq!(err.status_code(), axum::http::StatusCode::BAD_REQUEST);
+        let message = err.to_string();
+        assert!(message.contains("org/repo/absent.gguf"));
+        assert!(message.contains("HTTP 404"));
+
+        match previous {
+            Some(v) => std::env::set_var("HF_BASE_URL", v),
+            None => std::env::remove_var("HF_BASE_URL"),
+        }
+    }
+
     // ===== is_gguf_filename tests =====
 
     #[test]
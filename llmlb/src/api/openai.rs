@@ -6,12 +6,12 @@
 const UNSPECIFIED_IP: std::net::IpAddr = std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED);
 
 use crate::common::{
-    error::{CommonError, LbError},
+    error::{CommonError, FailureContext, FailureStage, LbError},
     protocol::{RecordStatus, RequestResponseRecord, RequestType, TpsApiKind},
 };
 use crate::types::model::{ModelCapabilities, ModelCapability};
 use axum::{
-    extract::{ConnectInfo, Path, State},
+    extract::{ConnectInfo, Path, Query, State},
     http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
@@ -22,16 +22,19 @@ use tracing::{error, warn};
 use uuid::Uuid;
 
 use crate::auth::middleware::ApiKeyAuthContext;
-use crate::common::ip::{normalize_ip, normalize_socket_ip};
+use crate::common::ip::{extract_client_ip_from_headers, normalize_socket_ip};
 
 use crate::{
     api::{
+        chat_adapter,
         cloud_proxy::{proxy_cloud_provider, resolve_provider},
         error::AppError,
+        json_mode,
         model_name::{
             parse_quantized_model_name, rewrite_payload_model_for_endpoint, ParsedModelName,
         },
         models::{list_registered_models, load_registered_model, LifecycleStatus},
+        openai_retry::{RetryPolicy, RetryStep},
         openai_util::{
             classify_upstream_request_error, model_unavailable_response, openai_error_response,
             openai_error_response_with_type, probe_ollama_model_loaded, queue_error_response,
@@ -39,24 +42,36 @@ use crate::{
         },
         proxy::{
             forward_streaming_response, forward_streaming_response_with_tps_tracking,
-            record_endpoint_request_stats, save_request_record, select_available_endpoint,
-            select_available_endpoint_with_queue_for_model, QueueSelection,
+            prime_upstream_stream, record_endpoint_request_stats, save_request_record,
+            select_available_endpoint, select_available_endpoint_with_queue_for_model,
+            send_with_same_node_retry, QueueSelection, RoutingHeaders, UpstreamStream,
         },
+        quality_filter,
+        request_timeout::{
+            parse_timeout_header, upstream_timeout_message, upstream_timeout_response,
+            UPSTREAM_TIMEOUT_ERROR_TYPE,
+        },
+    },
+    balancer::{experiment::ExperimentSubject, RequestLease, RequestOutcome, SelectionContext},
+    config::{JsonModeValidation, StreamReconnectCause},
+    metrics::timeline::{RequestTimeline, TimelineStage},
+    models::context_fallback::{resolve_model_switch, ModelSwitch},
+    token::{
+        complete_usage_for_endpoint, extract_or_estimate_embedding_tokens, extract_request_text,
     },
-    balancer::RequestOutcome,
-    token::extract_usage_from_response,
     AppState,
 };
 
 /// SPEC-f8e3a1b7: 推論リクエスト成功時にエンドポイントのレイテンシを更新（Fire-and-forget）
-fn update_inference_latency(
-    registry: &crate::registry::endpoints::EndpointRegistry,
-    endpoint_id: Uuid,
-    duration: std::time::Duration,
-) {
-    let registry = registry.clone();
+fn update_inference_latency(state: &AppState, endpoint_id: Uuid, duration: std::time::Duration) {
+    let registry = state.endpoint_registry.clone();
+    let load_manager = state.load_manager.clone();
     let latency_ms = duration.as_millis() as f64;
     tokio::spawn(async move {
+        // 外れ値は EMA に入れる前に直近分布のパーセンタイルへクリップする
+        let latency_ms = load_manager
+            .filter_latency_sample(endpoint_id, latency_ms)
+            .await;
         if let Err(e) = registry
             .update_inference_latency(endpoint_id, latency_ms)
             .await
@@ -83,6 +98,18 @@ fn add_queue_headers(response: &mut Response, wait_ms: u128) {
     }
 }
 
+/// 失敗段階と試行済みエンドポイントをレスポンス拡張に記録する（詳細エラー返却用）
+fn attach_failure_context(
+    response: &mut Response,
+    stage: FailureStage,
+    attempted_endpoint_ids: &[Uuid],
+    detail: impl Into<String>,
+) {
+    response
+        .extensions_mut()
+        .insert(FailureContext::new(stage, attempted_endpoint_ids, detail));
+}
+
 /// クライアントIPとAPIキーIDを抽出するヘルパー
 fn extract_client_info(
     addr: &SocketAddr,
@@ -95,59 +122,50 @@ fn extract_client_info(
     (client_ip, api_key_id)
 }
 
-fn extract_client_ip_from_headers(headers: &HeaderMap) -> Option<IpAddr> {
-    extract_x_forwarded_for(headers).or_else(|| extract_forwarded_for(headers))
-}
-
-fn extract_x_forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
-    let value = headers.get("x-forwarded-for")?.to_str().ok()?;
-    value
-        .split(',')
-        .map(str::trim)
-        .find_map(parse_client_ip_from_forwarded_value)
+/// ミドルウェアで開始したタイムラインを取り出し、認証段階を記録する
+fn begin_handler_timeline(timeline: Option<axum::Extension<RequestTimeline>>) -> RequestTimeline {
+    let mut timeline = timeline
+        .map(|axum::Extension(timeline)| timeline)
+        .unwrap_or_else(RequestTimeline::start);
+    timeline.mark(TimelineStage::Auth);
+    timeline
 }
 
-fn extract_forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
-    let value = headers.get("forwarded")?.to_str().ok()?;
-    value.split(',').find_map(|entry| {
-        entry
-            .split(';')
-            .filter_map(|pair| pair.split_once('='))
-            .find_map(|(key, value)| {
-                if key.trim().eq_ignore_ascii_case("for") {
-                    parse_client_ip_from_forwarded_value(value.trim())
-                } else {
-                    None
-                }
-            })
-    })
+/// アップストリーム転送に引き継ぐリクエスト単位の指定
+#[derive(Debug, Default)]
+struct RequestOptions {
+    /// エンドポイント選択の入力
+    selection: SelectionContext,
+    /// `X-LLMLB-Timeout-Ms` で指定されたタイムアウト
+    request_timeout: Option<std::time::Duration>,
+    /// コンテキスト超過によるモデル自動切替
+    model_switch: Option<ModelSwitch>,
 }
 
-fn parse_client_ip_from_forwarded_value(value: &str) -> Option<IpAddr> {
-    let trimmed = value.trim().trim_matches('"');
-    if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("unknown") || trimmed.starts_with('_') {
-        return None;
-    }
-
-    let host = if let Some(stripped) = trimmed.strip_prefix('[') {
-        stripped.split(']').next().unwrap_or_default().trim()
-    } else {
-        trimmed
-    };
-
-    if let Ok(ip) = host.parse::<IpAddr>() {
-        return Some(normalize_ip(ip));
+impl RequestOptions {
+    fn new(
+        addr: &SocketAddr,
+        headers: &HeaderMap,
+        auth_ctx: &Option<axum::Extension<ApiKeyAuthContext>>,
+    ) -> Self {
+        let mut selection = SelectionContext::from_request(
+            headers,
+            auth_ctx.as_ref().map(|axum::Extension(ctx)| ctx),
+        );
+        selection.client_ip = Some(crate::common::ip::resolve_trusted_client_ip(addr, headers));
+        Self {
+            selection,
+            ..Default::default()
+        }
     }
 
-    if let Some((ip_candidate, _port)) = host.rsplit_once(':') {
-        if !ip_candidate.contains(':') {
-            if let Ok(ip) = ip_candidate.parse::<IpAddr>() {
-                return Some(normalize_ip(ip));
-            }
+    /// リクエスト履歴を保存する（モデルを自動切替した場合は要求元のモデルも記録する）
+    fn save_record(&self, state: &AppState, mut record: RequestResponseRecord) {
+        if let Some(switch) = &self.model_switch {
+            record.requested_model = Some(switch.requested.clone());
         }
+        save_request_record(state.request_history.clone(), record);
     }
-
-    None
 }
 
 /// POST /v1/chat/completions - OpenAI互換チャットAPI
@@ -157,11 +175,14 @@ pub async fn chat_completions(
     headers: HeaderMap,
     State(state): State<AppState>,
     auth_ctx: Option<axum::Extension<ApiKeyAuthContext>>,
-    Json(payload): Json<Value>,
+    timeline: Option<axum::Extension<RequestTimeline>>,
+    Json(mut payload): Json<Value>,
 ) -> Result<Response, AppError> {
+    let timeline = begin_handler_timeline(timeline);
     let (client_ip, api_key_id) = extract_client_info(&addr, &headers, &auth_ctx);
+    let request_timeout = parse_timeout_header(&headers).map_err(validation_error)?;
     let model = extract_model(&payload)?;
-    let parsed = if parse_cloud_model(&model).is_some() {
+    let mut parsed = if parse_cloud_model(&model).is_some() {
         ParsedModelName {
             raw: model.clone(),
             base: model.clone(),
@@ -187,7 +208,45 @@ pub async fn chat_completions(
     }
 
     let stream = extract_stream(&payload);
-    proxy_openai_post(
+
+    // コンテキストに収まらない入力は同じファミリのより大きいコンテキストのモデルへ切り替える
+    let model_switch = resolve_auto_downgrade(&state, &parsed.raw, &payload).await;
+    if let Some(switch) = &model_switch {
+        payload["model"] = Value::String(switch.selected.clone());
+        parsed.raw = switch.selected.clone();
+    }
+
+    let options = RequestOptions {
+        request_timeout,
+        model_switch: model_switch.clone(),
+        ..RequestOptions::new(&addr, &headers, &auth_ctx)
+    };
+
+    // 補完専用アップストリーム向けモデルはプロンプトへ変換して /v1/completions に送る
+    if let Some(template) = chat_adapter::config().template_for(&parsed.raw) {
+        let completions_payload = chat_adapter::chat_to_completions_payload(&payload, &template)
+            .map_err(|msg| AppError::from(LbError::Common(CommonError::Validation(msg))))?;
+        let response = proxy_openai_post(
+            &state,
+            completions_payload,
+            "/v1/completions",
+            parsed.raw,
+            stream,
+            RequestType::Chat,
+            client_ip,
+            api_key_id,
+            timeline,
+            options,
+        )
+        .await?;
+        let mut response = chat_adapter::completion_http_response_to_chat(response, stream).await;
+        if let Some(switch) = &model_switch {
+            switch.apply_header(&mut response);
+        }
+        return Ok(response);
+    }
+
+    let mut response = proxy_openai_post(
         &state,
         payload,
         "/v1/chat/completions",
@@ -196,8 +255,28 @@ pub async fn chat_completions(
         RequestType::Chat,
         client_ip,
         api_key_id,
+        timeline,
+        options,
     )
-    .await
+    .await?;
+    if let Some(switch) = &model_switch {
+        switch.apply_header(&mut response);
+    }
+    Ok(response)
+}
+
+/// `LLMLB_AUTO_DOWNGRADE` が有効な場合、コンテキスト超過時の切替先モデルを決定する
+///
+/// クラウドモデルは対象外。
+async fn resolve_auto_downgrade(
+    state: &AppState,
+    model: &str,
+    payload: &Value,
+) -> Option<ModelSwitch> {
+    if !crate::config::auto_downgrade_enabled() || parse_cloud_model(model).is_some() {
+        return None;
+    }
+    resolve_model_switch(&state.endpoint_registry, model, payload).await
 }
 
 /// POST /v1/completions - OpenAI互換テキスト補完API
@@ -206,15 +285,26 @@ pub async fn completions(
     headers: HeaderMap,
     State(state): State<AppState>,
     auth_ctx: Option<axum::Extension<ApiKeyAuthContext>>,
-    Json(payload): Json<Value>,
+    timeline: Option<axum::Extension<RequestTimeline>>,
+    Json(mut payload): Json<Value>,
 ) -> Result<Response, AppError> {
+    let timeline = begin_handler_timeline(timeline);
     let (client_ip, api_key_id) = extract_client_info(&addr, &headers, &auth_ctx);
-    let model = extract_model(&payload)?;
+    let mut model = extract_model(&payload)?;
     if parse_cloud_model(&model).is_none() {
         parse_quantized_model_name(&model).map_err(AppError::from)?;
     }
     let stream = extract_stream(&payload);
-    proxy_openai_post(
+    let model_switch = resolve_auto_downgrade(&state, &model, &payload).await;
+    if let Some(switch) = &model_switch {
+        payload["model"] = Value::String(switch.selected.clone());
+        model = switch.selected.clone();
+    }
+    let options = RequestOptions {
+        model_switch: model_switch.clone(),
+        ..RequestOptions::new(&addr, &headers, &auth_ctx)
+    };
+    let mut response = proxy_openai_post(
         &state,
         payload,
         "/v1/completions",
@@ -223,8 +313,14 @@ pub async fn completions(
         RequestType::Generate,
         client_ip,
         api_key_id,
+        timeline,
+        options,
     )
-    .await
+    .await?;
+    if let Some(switch) = &model_switch {
+        switch.apply_header(&mut response);
+    }
+    Ok(response)
 }
 
 /// POST /v1/embeddings - OpenAI互換Embeddings API
@@ -233,13 +329,18 @@ pub async fn embeddings(
     headers: HeaderMap,
     State(state): State<AppState>,
     auth_ctx: Option<axum::Extension<ApiKeyAuthContext>>,
+    timeline: Option<axum::Extension<RequestTimeline>>,
     Json(payload): Json<Value>,
 ) -> Result<Response, AppError> {
+    let timeline = begin_handler_timeline(timeline);
     let (client_ip, api_key_id) = extract_client_info(&addr, &headers, &auth_ctx);
     let model = extract_model_with_default(&payload, crate::config::get_default_embedding_model());
     if parse_cloud_model(&model).is_none() {
         parse_quantized_model_name(&model).map_err(AppError::from)?;
     }
+    // `embeddings` 対応エンドポイントのみを候補に、通常のモード・sticky session・再試行で選択する
+    let mut options = RequestOptions::new(&addr, &headers, &auth_ctx);
+    options.selection.required_api = Some(crate::types::endpoint::SupportedAPI::Embeddings);
     proxy_openai_post(
         &state,
         payload,
@@ -249,19 +350,54 @@ pub async fn embeddings(
         RequestType::Embeddings,
         client_ip,
         api_key_id,
+        timeline,
+        options,
     )
     .await
 }
 
+/// GET /v1/models のクエリパラメータ
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ListModelsQuery {
+    /// `true` の場合はキャッシュを無視して全エンドポイントから再取得
+    /// （`endpoints.manage` 権限を持つAPIキーのみ）
+    #[serde(default)]
+    pub refresh: Option<bool>,
+    /// `false` の場合は拡張フィールドを省いた OpenAI 互換の形状
+    /// （`id` / `object` / `created` / `owned_by`）で返す（既定: `true`）
+    #[serde(default)]
+    pub verbose: Option<bool>,
+}
+
 /// GET /v1/models - モデル一覧取得（OpenAI互換 + Azure capabilities + ダッシュボード拡張）
 ///
 /// OpenAI API 互換形式に Azure OpenAI 形式の capabilities と
 /// ダッシュボード用の拡張フィールド（lifecycle_status, download_progress, ready）を追加。
 /// 登録済みの全モデルを返す（ダウンロード中・待機中含む）。
-pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppError> {
+///
+/// エンドポイント別のモデル一覧は `LLMLB_MODEL_LIST_TTL_SECS` のTTLでキャッシュされ、
+/// `?refresh=true` 指定時は全エンドポイントから再取得する。再取得は全エンドポイントへの
+/// 問い合わせを伴うため、`endpoints.manage` 権限を持つ（管理者の）APIキーに限る。
+pub async fn list_models(
+    State(state): State<AppState>,
+    auth_ctx: Option<axum::Extension<ApiKeyAuthContext>>,
+    Query(query): Query<ListModelsQuery>,
+) -> Result<Response, AppError> {
     use crate::types::endpoint::SupportedAPI;
     use std::collections::HashSet;
 
+    let force_refresh = query.refresh.unwrap_or(false);
+    if force_refresh
+        && !auth_ctx.as_ref().is_some_and(|ctx| {
+            ctx.permissions
+                .contains(&crate::common::auth::ApiKeyPermission::EndpointsManage)
+        })
+    {
+        return Err(AppError(LbError::Authorization(
+            "refresh=true requires the endpoints.manage permission".to_string(),
+        )));
+    }
+
     // Load registered models from the database.
     let mut registered_map: std::collections::HashMap<String, crate::registry::models::ModelInfo> =
         HashMap::new();
@@ -273,19 +409,52 @@ pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppE
     let mut endpoint_model_apis: HashMap<String, HashSet<SupportedAPI>> = HashMap::new();
     let mut endpoint_model_max_tokens: HashMap<String, Option<u32>> = HashMap::new();
     let mut endpoint_model_ids: HashMap<String, HashSet<String>> = HashMap::new();
+    // オンラインでないエンドポイントのモデル（total_endpoints 用、DBに同期済みのモデル一覧）
+    let mut offline_model_ids: HashMap<String, HashSet<String>> = HashMap::new();
     // canonical name解決マップを構築
     let canonical_resolution;
     {
         let registry = &state.endpoint_registry;
         let online_endpoints = registry.list_online().await;
+        for ep in registry
+            .list()
+            .await
+            .into_iter()
+            .filter(|ep| ep.status != crate::types::endpoint::EndpointStatus::Online)
+        {
+            if let Ok(models) = registry.list_models(ep.id).await {
+                for model in models {
+                    let display_key = model.canonical_name.unwrap_or(model.model_id);
+                    offline_model_ids
+                        .entry(display_key)
+                        .or_default()
+                        .insert(ep.id.to_string());
+                }
+            }
+        }
+
+        // エンドポイント別モデル一覧をTTLキャッシュ経由で並行取得
+        let fetched = futures::future::join_all(online_endpoints.iter().map(|ep| {
+            crate::sync::list_endpoint_models_cached(
+                registry,
+                &state.http_client,
+                ep,
+                force_refresh,
+            )
+        }))
+        .await;
+        let endpoint_models: Vec<(Uuid, Vec<crate::types::endpoint::EndpointModel>)> =
+            online_endpoints
+                .iter()
+                .zip(fetched)
+                .filter_map(|(ep, result)| result.ok().map(|models| (ep.id, models)))
+                .collect();
 
         // 全エンドポイントモデルを収集してcanonical解決マップを構築
         let mut all_models: Vec<(String, Option<String>)> = Vec::new();
-        for ep in &online_endpoints {
-            if let Ok(models) = registry.list_models(ep.id).await {
-                for model in &models {
-                    all_models.push((model.model_id.clone(), model.canonical_name.clone()));
-                }
+        for (_, models) in &endpoint_models {
+            for model in models {
+                all_models.push((model.model_id.clone(), model.canonical_name.clone()));
             }
         }
         canonical_resolution = crate::models::mapping::build_canonical_maps(
@@ -294,38 +463,36 @@ pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppE
                 .map(|(id, cn)| (id.as_str(), cn.as_deref())),
         );
 
-        for ep in online_endpoints {
-            if let Ok(models) = registry.list_models(ep.id).await {
-                for model in models {
-                    // 表示用キーの決定: canonical_nameがあればそれを使用
-                    let display_key = model
-                        .canonical_name
-                        .clone()
-                        .unwrap_or_else(|| model.model_id.clone());
-
+        for (ep_id, models) in endpoint_models {
+            for model in models {
+                // 表示用キーの決定: canonical_nameがあればそれを使用
+                let display_key = model
+                    .canonical_name
+                    .clone()
+                    .unwrap_or_else(|| model.model_id.clone());
+
+                endpoint_model_ids
+                    .entry(display_key.clone())
+                    .or_default()
+                    .insert(ep_id.to_string());
+                // エイリアス名でもエンドポイントIDを登録（ルーティング用）
+                if display_key != model.model_id {
                     endpoint_model_ids
-                        .entry(display_key.clone())
+                        .entry(model.model_id.clone())
                         .or_default()
-                        .insert(ep.id.to_string());
-                    // エイリアス名でもエンドポイントIDを登録（ルーティング用）
-                    if display_key != model.model_id {
-                        endpoint_model_ids
-                            .entry(model.model_id.clone())
-                            .or_default()
-                            .insert(ep.id.to_string());
-                    }
-                    let apis = endpoint_model_apis.entry(display_key.clone()).or_default();
-                    for api in model.supported_apis {
-                        apis.insert(api);
-                    }
-                    // Responses APIは全エンドポイント対応前提（判定/フラグは廃止）
-                    apis.insert(SupportedAPI::Responses);
+                        .insert(ep_id.to_string());
+                }
+                let apis = endpoint_model_apis.entry(display_key.clone()).or_default();
+                for api in model.supported_apis {
+                    apis.insert(api);
+                }
+                // Responses APIは全エンドポイント対応前提（判定/フラグは廃止）
+                apis.insert(SupportedAPI::Responses);
 
-                    // max_tokens を集約（複数エンドポイントにある場合は最大値を採用）
-                    let entry = endpoint_model_max_tokens.entry(display_key).or_insert(None);
-                    if let Some(mt) = model.max_tokens {
-                        *entry = Some(entry.map_or(mt, |existing| existing.max(mt)));
-                    }
+                // max_tokens を集約（複数エンドポイントで異なる場合は安全側の最小値を採用）
+                let entry = endpoint_model_max_tokens.entry(display_key).or_insert(None);
+                if let Some(mt) = model.max_tokens {
+                    *entry = Some(entry.map_or(mt, |existing| existing.min(mt)));
                 }
             }
         }
@@ -337,6 +504,19 @@ pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppE
     let available_set: std::collections::HashSet<String> =
         available_models.iter().cloned().collect();
 
+    // モデルごとの ready なエンドポイント数と所属エンドポイント数
+    let endpoint_counts = |model_id: &str| -> (usize, usize) {
+        let ready: HashSet<&String> = endpoint_model_ids
+            .get(model_id)
+            .map(|ids| ids.iter().collect())
+            .unwrap_or_default();
+        let mut total = ready.clone();
+        if let Some(ids) = offline_model_ids.get(model_id) {
+            total.extend(ids.iter());
+        }
+        (ready.len(), total.len())
+    };
+
     // 追跡用：モデルID一覧
     let mut seen_models: HashSet<String> = HashSet::new();
 
@@ -362,6 +542,7 @@ pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppE
             })
             .unwrap_or_default();
 
+        let (ready_endpoints, total_endpoints) = endpoint_counts(model_id);
         // エイリアス情報を取得
         let aliases = canonical_resolution.aliases_for(model_id);
         // canonical_nameを取得（表示用）
@@ -378,6 +559,8 @@ pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppE
                 "lifecycle_status": LifecycleStatus::Registered,
                 "download_progress": null,
                 "ready": ready,
+                "ready_endpoints": ready_endpoints,
+                "total_endpoints": total_endpoints,
                 "repo": m.repo,
                 "filename": m.filename,
                 "size_bytes": m.size,
@@ -402,6 +585,8 @@ pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppE
                 "lifecycle_status": LifecycleStatus::Registered,
                 "download_progress": null,
                 "ready": ready,
+                "ready_endpoints": ready_endpoints,
+                "total_endpoints": total_endpoints,
                 "supported_apis": supported_apis,
                 "max_tokens": endpoint_model_max_tokens.get(model_id).copied().flatten(),
                 "endpoint_ids": endpoint_ids,
@@ -428,6 +613,7 @@ pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppE
                 ids
             })
             .unwrap_or_default();
+        let (ready_endpoints, total_endpoints) = endpoint_counts(model_id);
         let obj = json!({
             "id": model_id,
             "object": "model",
@@ -436,6 +622,8 @@ pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppE
             "lifecycle_status": LifecycleStatus::Registered,
             "download_progress": null,
             "ready": true,
+            "ready_endpoints": ready_endpoints,
+            "total_endpoints": total_endpoints,
             "supported_apis": supported_apis,
             "max_tokens": endpoint_model_max_tokens.get(model_id).copied().flatten(),
             "endpoint_ids": endpoint_ids,
@@ -443,6 +631,36 @@ pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppE
         data.push(obj);
     }
 
+    // 所属エンドポイントが全てオンラインでないモデルも ready:false として含める
+    let mut unready_models: Vec<&String> = offline_model_ids
+        .keys()
+        .filter(|model_id| !seen_models.contains(*model_id))
+        .collect();
+    unready_models.sort();
+    for model_id in unready_models {
+        let (_, total_endpoints) = endpoint_counts(model_id);
+        let owned_by = if registered_map.contains_key(model_id) {
+            "load balancer"
+        } else {
+            "endpoint"
+        };
+        let obj = json!({
+            "id": model_id,
+            "object": "model",
+            "created": 0,
+            "owned_by": owned_by,
+            "lifecycle_status": LifecycleStatus::Registered,
+            "download_progress": null,
+            "ready": false,
+            "ready_endpoints": 0,
+            "total_endpoints": total_endpoints,
+            "supported_apis": Vec::<String>::new(),
+            "max_tokens": null,
+            "endpoint_ids": Vec::<String>::new(),
+        });
+        data.push(obj);
+    }
+
     // NOTE: SPEC-6cd7f960 FR-6により、登録済みだがオンラインエンドポイントにないモデルは
     // /v1/models に含めない（利用可能なモデルのみを返す）
 
@@ -466,6 +684,21 @@ pub async fn list_models(State(state): State<AppState>) -> Result<Response, AppE
         data.push(obj);
     }
 
+    // `?verbose=false` では OpenAI 互換のフィールドのみに絞る
+    if !query.verbose.unwrap_or(true) {
+        data = data
+            .into_iter()
+            .map(|model| {
+                json!({
+                    "id": model["id"],
+                    "object": model["object"],
+                    "created": model["created"],
+                    "owned_by": model["owned_by"],
+                })
+            })
+            .collect();
+    }
+
     let body = json!({
         "object": "list",
         "data": data,
@@ -749,14 +982,34 @@ async fn proxy_openai_cloud_post(
         }
         if status.is_success() {
             record.response_body = outcome.response_body.clone();
+            // クラウドのコスト集計に使うため usage を記録する
+            if let Some(usage) = outcome
+                .response_body
+                .as_ref()
+                .and_then(crate::token::extract_usage_from_response)
+            {
+                record.input_tokens = usage.input_tokens;
+                record.output_tokens = usage.output_tokens;
+                record.total_tokens = usage.total_tokens;
+            }
         }
         save_request_record(state.request_history.clone(), record);
     }
+    if let Some(cache) = outcome
+        .response_body
+        .as_ref()
+        .and_then(super::prompt_cache::usage_from_response)
+    {
+        crate::metrics::exporter::record_prompt_cache_usage(
+            endpoint_id,
+            cache.input_tokens,
+            cache.cached_tokens,
+        );
+    }
 
     Ok(outcome.response)
 }
 
-#[allow(deprecated)] // NodeRegistry migration in progress
 #[allow(clippy::too_many_arguments)]
 async fn proxy_openai_post(
     state: &AppState,
@@ -767,6 +1020,86 @@ async fn proxy_openai_post(
     request_type: RequestType,
     client_ip: Option<IpAddr>,
     api_key_id: Option<Uuid>,
+    timeline: RequestTimeline,
+    mut options: RequestOptions,
+) -> Result<Response, AppError> {
+    // A/Bテスト: リクエスト属性の安定ハッシュでグループを割り当て、送信先を差し替える
+    let subject = ExperimentSubject {
+        api_key_id,
+        client_ip,
+        user: payload
+            .get("user")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    };
+    let assignment = state.load_manager.assign_experiment(&model, &subject).await;
+    let (payload, model) = match assignment
+        .as_ref()
+        .and_then(|assignment| assignment.variant.model.clone())
+    {
+        Some(variant_model) => {
+            let mut payload = payload;
+            if let Some(payload_object) = payload.as_object_mut() {
+                payload_object.insert("model".to_string(), Value::String(variant_model.clone()));
+            }
+            (payload, variant_model)
+        }
+        None => (payload, model),
+    };
+    // シャドウトラフィック: 抽選に当たった分を応答を待たずにシャドウエンドポイントへ複製
+    super::shadow::mirror_request(state, target_path, &model, &payload).await;
+
+    // プロンプトキャッシュ: 同じプレフィックスのリクエストを同じエンドポイントへ寄せる
+    super::prompt_cache::apply_prefix_affinity(&mut options.selection, &model, &payload);
+    // コンテキスト長ルーティング: 推定入力トークン数をエンドポイント選択へ渡す
+    options.selection.input_tokens = crate::balancer::context_routing::current()
+        .and_then(|_| crate::token::estimate_tokens(&extract_request_text(&payload), &model));
+    options.selection.assignment = assignment.clone();
+
+    let mut routed: Option<RoutingHeaders> = None;
+    let started = Instant::now();
+    let result = proxy_openai_post_routed(
+        state,
+        payload,
+        target_path,
+        model,
+        stream,
+        request_type,
+        client_ip,
+        api_key_id,
+        timeline,
+        &options,
+        &mut routed,
+    )
+    .await;
+    if let Some(assignment) = &assignment {
+        let success = matches!(&result, Ok(response) if response.status().is_success());
+        state
+            .load_manager
+            .record_experiment_result(assignment, success, started.elapsed());
+    }
+
+    let mut response = result?;
+    if let Some(routed) = routed {
+        routed.apply(&mut response);
+    }
+    Ok(response)
+}
+
+#[allow(deprecated)] // NodeRegistry migration in progress
+#[allow(clippy::too_many_arguments)]
+async fn proxy_openai_post_routed(
+    state: &AppState,
+    payload: Value,
+    target_path: &str,
+    model: String,
+    stream: bool,
+    request_type: RequestType,
+    client_ip: Option<IpAddr>,
+    api_key_id: Option<Uuid>,
+    mut timeline: RequestTimeline,
+    options: &RequestOptions,
+    routed: &mut Option<RoutingHeaders>,
 ) -> Result<Response, AppError> {
     // Cloud-prefixed model -> forward to provider API
     if parse_cloud_model(&model).is_some() {
@@ -819,18 +1152,27 @@ async fn proxy_openai_post(
 
     let request_body = sanitize_openai_payload_for_history(&payload);
     let tps_api_kind = TpsApiKind::from_request_type(request_type);
-    let queue_config = state.queue_config;
+    let queue_config = crate::config::effective_queue_config(state.queue_config);
     let mut queued_wait_ms: Option<u128> = None;
+    let json_mode_validation = if json_mode::requests_json_object(&payload) {
+        crate::config::json_mode_validation()
+    } else {
+        JsonModeValidation::Off
+    };
 
-    // FR-004: エンドポイント選択失敗時もリクエスト履歴に記録する
-    let endpoint = match select_available_endpoint_with_queue_for_model(
+    timeline.mark(TimelineStage::TokenEstimation);
+    let selection = select_available_endpoint_with_queue_for_model(
         state,
         queue_config,
         &resolved_model,
         tps_api_kind,
+        &options.selection,
     )
-    .await
-    {
+    .await;
+    timeline.mark(TimelineStage::EndpointSelection);
+
+    // FR-004: エンドポイント選択失敗時もリクエスト履歴に記録する
+    let mut endpoint = match selection {
         Ok(QueueSelection::Ready {
             endpoint,
             queued_wait_ms: wait_ms,
@@ -840,8 +1182,8 @@ async fn proxy_openai_post(
         }
         Ok(QueueSelection::CapacityExceeded) => {
             let message = "Request queue is full".to_string();
-            save_request_record(
-                state.request_history.clone(),
+            options.save_record(
+                state,
                 RequestResponseRecord::error(
                     model.clone(),
                     request_type,
@@ -853,17 +1195,19 @@ async fn proxy_openai_post(
                 ),
             );
             let retry_after = queue_config.timeout.as_secs().max(1);
-            return Ok(queue_error_response(
+            let mut response = queue_error_response(
                 StatusCode::TOO_MANY_REQUESTS,
                 &message,
                 "rate_limit_exceeded",
                 Some(retry_after),
-            ));
+            );
+            attach_failure_context(&mut response, FailureStage::Selection, &[], message);
+            return Ok(response);
         }
         Ok(QueueSelection::Timeout { waited_ms }) => {
             let message = "Queue wait timeout".to_string();
-            save_request_record(
-                state.request_history.clone(),
+            options.save_record(
+                state,
                 RequestResponseRecord::error(
                     model.clone(),
                     request_type,
@@ -874,18 +1218,18 @@ async fn proxy_openai_post(
                     api_key_id,
                 ),
             );
-            return Ok(queue_error_response(
-                StatusCode::GATEWAY_TIMEOUT,
-                &message,
-                "timeout",
-                None,
-            ));
+            let mut response =
+                queue_error_response(StatusCode::GATEWAY_TIMEOUT, &message, "timeout", None);
+            attach_failure_context(&mut response, FailureStage::Selection, &[], message);
+            return Ok(response);
         }
         Err(e) => {
-            let error_message = if matches!(e, LbError::NoCapableEndpoints(_)) {
-                format!("No available nodes support model: {}", model)
-            } else {
-                format!("Node selection failed: {}", e)
+            let error_message = match &e {
+                LbError::NoCapableEndpoints(_) => {
+                    format!("No available nodes support model: {}", model)
+                }
+                LbError::ServiceUnavailable(message) => message.clone(),
+                _ => format!("Node selection failed: {}", e),
             };
             error!(
                 endpoint = %target_path,
@@ -893,8 +1237,8 @@ async fn proxy_openai_post(
                 error = %e,
                 "Failed to select available node"
             );
-            save_request_record(
-                state.request_history.clone(),
+            options.save_record(
+                state,
                 RequestResponseRecord::error(
                     model.clone(),
                     request_type,
@@ -905,273 +1249,706 @@ async fn proxy_openai_post(
                     api_key_id,
                 ),
             );
-            if matches!(e, LbError::NoCapableEndpoints(_)) {
-                return Ok(model_unavailable_response(
-                    error_message,
-                    "no_capable_nodes",
-                ));
+            let unavailable_code = match e {
+                LbError::NoCapableEndpoints(_) => Some("no_capable_nodes"),
+                LbError::ServiceUnavailable(_) => Some("unsupported_api"),
+                _ => None,
+            };
+            if let Some(code) = unavailable_code {
+                let mut response = model_unavailable_response(error_message.clone(), code);
+                attach_failure_context(&mut response, FailureStage::Selection, &[], error_message);
+                return Ok(response);
             }
-            return Err(e.into());
+            let context = FailureContext::new(FailureStage::Selection, &[], error_message);
+            return Err(e.with_context(context).into());
         }
     };
-    let endpoint_id = endpoint.id;
-    let endpoint_name = endpoint.name.clone();
-    let endpoint_type = endpoint.endpoint_type;
-    // RequestResponseRecordの互換性のため、デフォルトIP使用
-    // (今後、RequestResponseRecordのフィールドをリネームすべき)
-    let endpoint_host: std::net::IpAddr = UNSPECIFIED_IP;
-
-    let request_lease = state
-        .load_manager
-        .begin_request(endpoint_id)
-        .await
-        .map_err(AppError::from)?;
 
-    let client = state.http_client.clone();
-    let runtime_url = format!("{}{}", endpoint.base_url.trim_end_matches('/'), target_path);
-    let start = Instant::now();
-    let endpoint_models = match state.endpoint_registry.list_models(endpoint_id).await {
-        Ok(models) => models,
-        Err(error) => {
-            warn!(
-                endpoint_id = %endpoint_id,
-                model = %resolved_model,
-                error = %error,
-                "Failed to load endpoint models for request rewrite; falling back to static mapping"
-            );
-            Vec::new()
-        }
-    };
-    let outbound_payload = rewrite_payload_model_for_endpoint(
+    let mut request = RoutedRequest {
+        state,
+        options,
         payload,
-        &resolved_model,
-        &endpoint_type,
-        &endpoint_models,
-    );
-
-    let upstream_model = state
-        .endpoint_registry
-        .list_models(endpoint_id)
-        .await
-        .ok()
-        .and_then(|endpoint_models| {
-            endpoint_models
-                .into_iter()
-                .find(|endpoint_model| {
-                    endpoint_model.model_id == model
-                        || endpoint_model.model_id == resolved_model
-                        || endpoint_model.canonical_name.as_deref() == Some(model.as_str())
-                        || endpoint_model.canonical_name.as_deref() == Some(resolved_model.as_str())
-                })
-                .map(|endpoint_model| endpoint_model.model_id)
-        })
-        .or_else(|| {
-            crate::models::mapping::resolve_engine_name(&model, &endpoint_type).map(str::to_string)
-        })
-        .or_else(|| {
-            crate::models::mapping::resolve_engine_name(&resolved_model, &endpoint_type)
-                .map(str::to_string)
-        })
-        .unwrap_or_else(|| resolved_model.clone());
-
-    let mut upstream_payload = outbound_payload;
-    if let Some(payload_object) = upstream_payload.as_object_mut() {
-        payload_object.insert("model".to_string(), Value::String(upstream_model.clone()));
-
-        // SPEC-8c32349f: ストリーミングリクエストに stream_options.include_usage を注入
-        // Ollama 等のエンドポイントが最終チャンクに usage を含めるようにする
-        if stream {
-            if let Some(opts) = payload_object
-                .entry("stream_options".to_string())
-                .or_insert_with(|| json!({}))
-                .as_object_mut()
-            {
-                opts.entry("include_usage".to_string())
-                    .or_insert(json!(true));
-            }
-        }
+        target_path,
+        model,
+        resolved_model,
+        stream,
+        request_type,
+        request_body,
+        client_ip,
+        api_key_id,
+        tps_api_kind,
+        queued_wait_ms,
+        json_mode_validation,
+        timeline,
+        retry: RetryPolicy::new(stream, options.request_timeout),
+    };
+    // 失敗時はやり直しの種類ごとの上限まで、別エンドポイントで最初から送り直す
+    loop {
+        endpoint = match request.attempt(endpoint, routed).await? {
+            AttemptOutcome::Respond(response) => return Ok(response),
+            AttemptOutcome::Retry(next) => next,
+        };
     }
+}
 
-    let mut request_builder = client
-        .post(&runtime_url)
-        .timeout(std::time::Duration::from_secs(
-            endpoint.inference_timeout_secs as u64,
-        ))
-        .json(&upstream_payload);
-    if let Some(api_key) = &endpoint.api_key {
-        request_builder = request_builder.bearer_auth(api_key);
-    }
+/// 1回の送信試行の結果
+enum AttemptOutcome {
+    /// クライアントへ返す応答が確定した
+    Respond(Response),
+    /// 別エンドポイントでやり直す
+    Retry(crate::types::endpoint::Endpoint),
+}
 
-    let response = match request_builder.send().await {
-        Ok(res) => res,
-        Err(e) => {
-            let duration = start.elapsed();
-            let ollama_loading_model = if e.is_timeout()
-                && endpoint_type == crate::types::endpoint::EndpointType::Ollama
-            {
-                match probe_ollama_model_loaded(
-                    &client,
-                    &endpoint.base_url,
-                    endpoint.api_key.as_deref(),
-                    &upstream_model,
-                )
-                .await
-                {
-                    Some(false) => Some(upstream_model.clone()),
-                    _ => None,
-                }
-            } else {
-                None
-            };
-            let classified_error = classify_upstream_request_error(
-                &e,
-                endpoint.inference_timeout_secs,
-                ollama_loading_model.as_deref(),
-            );
-            request_lease
-                .complete(RequestOutcome::Error, duration)
-                .await
-                .map_err(AppError::from)?;
-            record_endpoint_request_stats(
-                state.endpoint_registry.clone(),
-                endpoint_id,
-                model.clone(),
-                false,
-                0,
-                0,
-                tps_api_kind,
-                endpoint_type,
-                state.load_manager.clone(),
-                state.event_bus.clone(),
-            );
+/// 送信中の試行
+struct Attempt {
+    /// 送信先エンドポイント
+    endpoint: crate::types::endpoint::Endpoint,
+    /// 負荷・容量予約の記録
+    lease: RequestLease,
+    /// 送信開始時刻
+    start: Instant,
+}
+
+/// 別エンドポイントへのやり直しをまたいで引き継ぐ、1リクエスト分の転送状態
+struct RoutedRequest<'a> {
+    state: &'a AppState,
+    options: &'a RequestOptions,
+    payload: Value,
+    target_path: &'a str,
+    /// クライアントが指定したモデル（品質フィルタの再試行で切り替わる）
+    model: String,
+    /// エイリアス解決後のモデル
+    resolved_model: String,
+    stream: bool,
+    request_type: RequestType,
+    /// 履歴保存用のリクエスト本文
+    request_body: Value,
+    client_ip: Option<IpAddr>,
+    api_key_id: Option<Uuid>,
+    tps_api_kind: Option<TpsApiKind>,
+    queued_wait_ms: Option<u128>,
+    json_mode_validation: JsonModeValidation,
+    timeline: RequestTimeline,
+    retry: RetryPolicy,
+}
 
-            // Note: Model exclusion is handled by the health check system
-            // which will mark the endpoint as offline/error if requests fail repeatedly
+impl RoutedRequest<'_> {
+    /// 選択済みエンドポイントへ1回送信し、応答を確定するかやり直し先を返す
+    async fn attempt(
+        &mut self,
+        endpoint: crate::types::endpoint::Endpoint,
+        routed: &mut Option<RoutingHeaders>,
+    ) -> Result<AttemptOutcome, AppError> {
+        self.retry.begin_attempt(endpoint.id);
+        let lease = self
+            .state
+            .load_manager
+            .begin_request(endpoint.id, self.options.selection.principal.as_ref())
+            .await
+            .map_err(AppError::from)?;
 
-            {
-                let mut record = RequestResponseRecord::new(
-                    endpoint_id,
-                    endpoint_name.clone(),
-                    endpoint_host,
-                    model.clone(),
-                    request_type,
-                    request_body.clone(),
-                    classified_error.status_code,
-                    duration,
-                    client_ip,
-                    api_key_id,
+        let runtime_url = format!(
+            "{}{}",
+            endpoint.base_url.trim_end_matches('/'),
+            self.target_path
+        );
+        let start = Instant::now();
+        let endpoint_models = match self.state.endpoint_registry.list_models(endpoint.id).await {
+            Ok(models) => models,
+            Err(error) => {
+                warn!(
+                    endpoint_id = %endpoint.id,
+                    model = %self.resolved_model,
+                    error = %error,
+                    "Failed to load endpoint models for request rewrite; falling back to static mapping"
                 );
-                record.status = RecordStatus::Error {
-                    message: classified_error.record_message,
-                };
-                save_request_record(state.request_history.clone(), record);
+                Vec::new()
             }
+        };
+        let upstream_model = self.upstream_model(&endpoint, &endpoint_models);
+        *routed = Some(RoutingHeaders {
+            endpoint: endpoint.name.clone(),
+            model: upstream_model.clone(),
+            retries: self.retry.total_retries(),
+        });
 
-            let mut response = openai_error_response_with_type(
-                classified_error.client_message,
-                classified_error.error_type,
-                classified_error.status_code,
-            );
-            if let Some(wait_ms) = queued_wait_ms {
-                add_queue_headers(&mut response, wait_ms);
+        let mut upstream_payload = rewrite_payload_model_for_endpoint(
+            self.payload.clone(),
+            &self.resolved_model,
+            &endpoint.endpoint_type,
+            &endpoint_models,
+        );
+        if let Some(payload_object) = upstream_payload.as_object_mut() {
+            payload_object.insert("model".to_string(), Value::String(upstream_model.clone()));
+
+            // SPEC-8c32349f: ストリーミングリクエストに stream_options.include_usage を注入
+            // Ollama 等のエンドポイントが最終チャンクに usage を含めるようにする
+            if self.stream {
+                if let Some(opts) = payload_object
+                    .entry("stream_options".to_string())
+                    .or_insert_with(|| json!({}))
+                    .as_object_mut()
+                {
+                    opts.entry("include_usage".to_string())
+                        .or_insert(json!(true));
+                }
             }
-            return Ok(response);
         }
-    };
 
-    // ストリームの場合はレスポンスをそのままパススルー
-    if stream {
+        // X-LLMLB-Timeout-Ms が指定されていればエンドポイント既定値より優先する
+        let upstream_timeout = self
+            .retry
+            .upstream_timeout(self.options.request_timeout.unwrap_or(
+                std::time::Duration::from_secs(endpoint.inference_timeout_secs as u64),
+            ));
+        let mut request_builder = self
+            .state
+            .http_client
+            .post(&runtime_url)
+            .timeout(upstream_timeout)
+            .json(&upstream_payload);
+        if let Some(api_key) = &endpoint.api_key {
+            request_builder = request_builder.bearer_auth(api_key);
+        }
+
+        let sent = send_with_same_node_retry(
+            request_builder,
+            &endpoint.name,
+            self.options.selection.priority,
+        )
+        .await;
+        let attempt = Attempt {
+            endpoint,
+            lease,
+            start,
+        };
+        match sent {
+            Ok(response) => {
+                self.timeline.mark(TimelineStage::UpstreamConnect);
+                self.state
+                    .load_manager
+                    .record_upstream_status(attempt.endpoint.id, response.status().as_u16())
+                    .await;
+                if self.stream {
+                    self.on_stream_response(attempt, response).await
+                } else {
+                    self.on_response(attempt, response).await
+                }
+            }
+            Err(e) => {
+                self.on_send_error(attempt, e, upstream_timeout, &upstream_model)
+                    .await
+            }
+        }
+    }
+
+    /// エンドポイントへ送信するモデル名（エンドポイント上のモデルID、なければ静的マッピング）
+    fn upstream_model(
+        &self,
+        endpoint: &crate::types::endpoint::Endpoint,
+        endpoint_models: &[crate::types::endpoint::EndpointModel],
+    ) -> String {
+        let (model, resolved_model) = (self.model.as_str(), self.resolved_model.as_str());
+        endpoint_models
+            .iter()
+            .find(|endpoint_model| {
+                endpoint_model.model_id == model
+                    || endpoint_model.model_id == resolved_model
+                    || endpoint_model.canonical_name.as_deref() == Some(model)
+                    || endpoint_model.canonical_name.as_deref() == Some(resolved_model)
+            })
+            .map(|endpoint_model| endpoint_model.model_id.clone())
+            .or_else(|| {
+                crate::models::mapping::resolve_engine_name(model, &endpoint.endpoint_type)
+                    .map(str::to_string)
+            })
+            .or_else(|| {
+                crate::models::mapping::resolve_engine_name(resolved_model, &endpoint.endpoint_type)
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| resolved_model.to_string())
+    }
+
+    /// 接続エラー・タイムアウト時は再接続・エスカレーション・フェイルオーバーの順にやり直し先を探す
+    async fn on_send_error(
+        &mut self,
+        attempt: Attempt,
+        e: reqwest::Error,
+        upstream_timeout: std::time::Duration,
+        upstream_model: &str,
+    ) -> Result<AttemptOutcome, AppError> {
+        let Attempt {
+            endpoint,
+            lease,
+            start,
+        } = attempt;
         let duration = start.elapsed();
-        let succeeded = response.status().is_success();
-        let outcome = if succeeded {
-            RequestOutcome::Success
+        let request_timeout = self.options.request_timeout;
+        let request_timed_out = e.is_timeout() && request_timeout.is_some();
+        let ollama_loading_model = if e.is_timeout()
+            && !request_timed_out
+            && endpoint.endpoint_type == crate::types::endpoint::EndpointType::Ollama
+        {
+            match probe_ollama_model_loaded(
+                &self.state.http_client,
+                &endpoint.base_url,
+                endpoint.api_key.as_deref(),
+                upstream_model,
+            )
+            .await
+            {
+                Some(false) => Some(upstream_model.to_string()),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        let mut classified_error = classify_upstream_request_error(
+            &e,
+            upstream_timeout.as_secs() as u32,
+            ollama_loading_model.as_deref(),
+        );
+        if let Some(timeout) = request_timeout.filter(|_| request_timed_out) {
+            let message = upstream_timeout_message(timeout);
+            classified_error.status_code = StatusCode::GATEWAY_TIMEOUT;
+            classified_error.error_type = UPSTREAM_TIMEOUT_ERROR_TYPE;
+            classified_error.record_message = message.clone();
+            classified_error.client_message = message;
+        }
+        let failure_stage = if e.is_timeout() {
+            FailureStage::Timeout
+        } else {
+            FailureStage::Connection
+        };
+        let failure_detail = classified_error.record_message.clone();
+        self.complete_failed(&endpoint, lease, duration).await?;
+
+        // Note: Model exclusion is handled by the health check system
+        // which will mark the endpoint as offline/error if requests fail repeatedly
+
+        // リクエスト指定のタイムアウトを超えた場合は再接続・再試行しない
+        let mut next = None;
+        if self.stream && !request_timed_out {
+            next = self
+                .retry
+                .reconnect(
+                    self.state,
+                    StreamReconnectCause::ConnectError,
+                    &self.resolved_model,
+                    self.tps_api_kind,
+                    &self.options.selection,
+                )
+                .await;
+        }
+        if next.is_none() && e.is_timeout() {
+            next = self
+                .retry
+                .escalation(
+                    self.state,
+                    &self.resolved_model,
+                    self.tps_api_kind,
+                    &self.options.selection,
+                )
+                .await;
+        }
+        if next.is_none() && !self.stream && !request_timed_out {
+            next = self
+                .retry
+                .failover(
+                    self.state,
+                    &self.resolved_model,
+                    self.tps_api_kind,
+                    &self.options.selection,
+                    &classified_error.record_message,
+                )
+                .await;
+        }
+
+        let reason = self.retry.failure_reason(classified_error.record_message);
+        let message = match &next {
+            Some(step) => self.retry.message(&reason, step),
+            None => reason,
+        };
+        self.save_error(
+            &endpoint,
+            classified_error.status_code,
+            duration,
+            message,
+            None,
+        );
+        if let Some(step) = next {
+            return Ok(AttemptOutcome::Retry(self.retry.advance(step)));
+        }
+        self.retry.log_failover_exhausted(&self.resolved_model);
+
+        let response = openai_error_response_with_type(
+            classified_error.client_message,
+            classified_error.error_type,
+            classified_error.status_code,
+        );
+        Ok(AttemptOutcome::Respond(self.failure_response(
+            response,
+            failure_stage,
+            failure_detail,
+        )))
+    }
+
+    /// ストリーミング応答はそのままパススルーする（first-token前の失敗は別エンドポイントへ再接続する）
+    async fn on_stream_response(
+        &mut self,
+        attempt: Attempt,
+        response: reqwest::Response,
+    ) -> Result<AttemptOutcome, AppError> {
+        let Attempt {
+            endpoint,
+            lease,
+            start,
+        } = attempt;
+        let upstream_status = response.status();
+
+        if upstream_status.is_server_error() {
+            if let Some(step) = self
+                .retry
+                .reconnect(
+                    self.state,
+                    StreamReconnectCause::ServerError,
+                    &self.resolved_model,
+                    self.tps_api_kind,
+                    &self.options.selection,
+                )
+                .await
+            {
+                let duration = start.elapsed();
+                self.complete_failed(&endpoint, lease, duration).await?;
+                let message = self.retry.message(
+                    &format!("Upstream stream returned status {}", upstream_status),
+                    &step,
+                );
+                self.save_error(&endpoint, upstream_status, duration, message, None);
+                return Ok(AttemptOutcome::Retry(self.retry.advance(step)));
+            }
+        }
+
+        if !upstream_status.is_success() {
+            let duration = start.elapsed();
+            self.complete_failed(&endpoint, lease, duration).await?;
+            let detail = format!("Upstream stream returned status {}", upstream_status);
+            self.save_error(&endpoint, upstream_status, duration, detail.clone(), None);
+            let axum_response = forward_streaming_response(response).map_err(AppError::from)?;
+            return Ok(AttemptOutcome::Respond(self.failure_response(
+                axum_response,
+                FailureStage::Upstream,
+                detail,
+            )));
+        }
+
+        // 再接続が有効な場合は最初のチャンクまで読み進め、first-token前の切断を検出する
+        let upstream: UpstreamStream = if self
+            .retry
+            .reconnects_on(StreamReconnectCause::EarlyDisconnect)
+        {
+            match prime_upstream_stream(response).await {
+                Ok(primed) => primed,
+                Err(reason) => {
+                    return self
+                        .on_early_disconnect(
+                            Attempt {
+                                endpoint,
+                                lease,
+                                start,
+                            },
+                            reason,
+                        )
+                        .await;
+                }
+            }
         } else {
-            RequestOutcome::Error
+            UpstreamStream::from(response)
         };
-        request_lease
-            .complete(outcome, duration)
+
+        let duration = start.elapsed();
+        lease
+            .complete(RequestOutcome::Success, duration)
             .await
             .map_err(AppError::from)?;
-        if succeeded {
-            // SPEC-f8e3a1b7: 成功時に推論レイテンシを更新
-            update_inference_latency(&state.endpoint_registry, endpoint_id, duration);
+        // SPEC-f8e3a1b7: 成功時に推論レイテンシを更新
+        update_inference_latency(self.state, endpoint.id, duration);
+
+        // 履歴はストリーム完了時にトークン数・課金額とあわせて保存する
+        // （保存はリクエストのスコープ外になるため、切替前のモデルはここで記録する）
+        let mut record = self.record(&endpoint, upstream_status, duration);
+        record.requested_model = self
+            .options
+            .model_switch
+            .as_ref()
+            .map(|switch| switch.requested.clone());
+
+        // アップストリームごとのSSE書式の差異をOpenAI標準形式へそろえる
+        let axum_response = forward_streaming_response_with_tps_tracking(
+            upstream.with_timeline(self.timeline).normalize_sse(),
+            endpoint.id,
+            self.model.clone(),
+            self.tps_api_kind,
+            endpoint.endpoint_type,
+            start,
+            self.state.endpoint_registry.clone(),
+            self.state.load_manager.clone(),
+            self.state.event_bus.clone(),
+            Some((self.state.request_history.clone(), record)),
+            self.json_mode_validation != JsonModeValidation::Off,
+            self.state
+                .load_manager
+                .stream_throttle_for(
+                    self.options.selection.principal.as_ref(),
+                    self.options.selection.client_ip,
+                )
+                .await,
+            self.options.request_timeout,
+        )
+        .map_err(AppError::from)?;
+        Ok(AttemptOutcome::Respond(
+            self.with_queue_headers(axum_response),
+        ))
+    }
+
+    /// first-token前にストリームが切れた場合は再接続先を探し、無ければ 502（タイムアウト時は 504）を返す
+    async fn on_early_disconnect(
+        &mut self,
+        attempt: Attempt,
+        reason: String,
+    ) -> Result<AttemptOutcome, AppError> {
+        let Attempt {
+            endpoint,
+            lease,
+            start,
+        } = attempt;
+        let duration = start.elapsed();
+        let timed_out_after = self
+            .options
+            .request_timeout
+            .filter(|timeout| duration >= *timeout);
+        self.complete_failed(&endpoint, lease, duration).await?;
+
+        let next = if timed_out_after.is_some() {
+            None
         } else {
-            record_endpoint_request_stats(
-                state.endpoint_registry.clone(),
-                endpoint_id,
-                model.clone(),
-                false,
-                0,
-                0,
-                tps_api_kind,
-                endpoint_type,
-                state.load_manager.clone(),
-                state.event_bus.clone(),
-            );
+            self.retry
+                .reconnect(
+                    self.state,
+                    StreamReconnectCause::EarlyDisconnect,
+                    &self.resolved_model,
+                    self.tps_api_kind,
+                    &self.options.selection,
+                )
+                .await
+        };
+        let (reason, status_code) = match timed_out_after {
+            Some(timeout) => (
+                upstream_timeout_message(timeout),
+                StatusCode::GATEWAY_TIMEOUT,
+            ),
+            None => (reason, StatusCode::BAD_GATEWAY),
+        };
+        let message = match &next {
+            Some(step) => self.retry.message(&reason, step),
+            None => reason.clone(),
+        };
+        self.save_error(&endpoint, status_code, duration, message, None);
+        if let Some(step) = next {
+            return Ok(AttemptOutcome::Retry(self.retry.advance(step)));
+        }
+
+        let (failure_stage, response) = match timed_out_after {
+            Some(timeout) => (FailureStage::Timeout, upstream_timeout_response(timeout)),
+            None => (
+                FailureStage::Upstream,
+                openai_error_response_with_type(
+                    reason.clone(),
+                    "endpoint_stream_disconnected",
+                    StatusCode::BAD_GATEWAY,
+                ),
+            ),
+        };
+        Ok(AttemptOutcome::Respond(self.failure_response(
+            response,
+            failure_stage,
+            reason,
+        )))
+    }
+
+    /// 非ストリーミング応答を処理する（品質フィルタ・JSONモード違反は別エンドポイントで再試行する）
+    async fn on_response(
+        &mut self,
+        attempt: Attempt,
+        response: reqwest::Response,
+    ) -> Result<AttemptOutcome, AppError> {
+        if !response.status().is_success() {
+            return self.on_error_status(attempt, response).await;
         }
 
+        let Attempt {
+            endpoint,
+            lease,
+            start,
+        } = attempt;
+        let parsed = response.json::<Value>().await;
+        let duration = start.elapsed();
+        let mut body = match parsed {
+            Ok(body) => body,
+            Err(e) => return self.on_invalid_body(&endpoint, lease, duration, e).await,
+        };
+
+        if self.json_mode_validation != JsonModeValidation::Off
+            && !json_mode::response_content_is_json(&body)
         {
-            let mut record = RequestResponseRecord::new(
-                endpoint_id,
-                endpoint_name.clone(),
-                endpoint_host,
-                model.clone(),
-                request_type,
-                request_body.clone(),
-                response.status(),
+            return self
+                .on_json_mode_violation(&endpoint, lease, duration, body)
+                .await;
+        }
+
+        if let Some((rule, step)) = self.quality_retry(&endpoint, &body).await {
+            self.complete_failed(&endpoint, lease, duration).await?;
+            let message = self
+                .retry
+                .message(&quality_filter::violation_message(&rule), &step);
+            self.save_error(
+                &endpoint,
+                StatusCode::BAD_GATEWAY,
                 duration,
-                client_ip,
-                api_key_id,
+                message,
+                Some(body),
             );
-            if !succeeded {
-                record.status = RecordStatus::Error {
-                    message: format!("Upstream stream returned status {}", response.status()),
-                };
+            if let RetryStep::Quality { model, .. } = &step {
+                if *model != self.resolved_model {
+                    self.model = model.clone();
+                    self.resolved_model = model.clone();
+                }
             }
-            save_request_record(state.request_history.clone(), record);
+            return Ok(AttemptOutcome::Retry(self.retry.advance(step)));
         }
 
-        let mut axum_response = if succeeded {
-            forward_streaming_response_with_tps_tracking(
-                response,
-                endpoint_id,
-                model.clone(),
-                tps_api_kind,
-                endpoint_type,
-                start,
-                state.endpoint_registry.clone(),
-                state.load_manager.clone(),
-                state.event_bus.clone(),
+        self.timeline.mark(TimelineStage::Completion);
+        self.timeline.finish();
+
+        if let Some(body_object) = body.as_object_mut() {
+            body_object.insert("model".to_string(), Value::String(self.model.clone()));
+        }
+
+        // レスポンスからトークン使用量を抽出（usageがなければ推定）
+        let (token_usage, _) = if self.request_type == RequestType::Embeddings {
+            extract_or_estimate_embedding_tokens(
+                &body,
+                self.payload.get("input"),
+                &self.model,
+                endpoint.endpoint_type,
             )
-            .map_err(AppError::from)?
         } else {
-            forward_streaming_response(response).map_err(AppError::from)?
+            // usageの無いアップストリームにはexact推定でusageを補完する
+            complete_usage_for_endpoint(
+                &mut body,
+                Some(&extract_request_text(&self.payload)),
+                &self.model,
+                endpoint.endpoint_type,
+            )
         };
-        if let Some(wait_ms) = queued_wait_ms {
-            add_queue_headers(&mut axum_response, wait_ms);
+        let token_usage = Some(token_usage);
+        if let Some(cache) = super::prompt_cache::usage_from_response(&body) {
+            crate::metrics::exporter::record_prompt_cache_usage(
+                endpoint.id,
+                cache.input_tokens,
+                cache.cached_tokens,
+            );
         }
-        return Ok(axum_response);
-    }
 
-    if !response.status().is_success() {
-        let duration = start.elapsed();
-        request_lease
-            .complete(RequestOutcome::Error, duration)
+        lease
+            .complete_with_tokens(RequestOutcome::Success, duration, token_usage.clone())
             .await
             .map_err(AppError::from)?;
+        // SPEC-f8e3a1b7: 成功時に推論レイテンシを更新
+        update_inference_latency(self.state, endpoint.id, duration);
+        // SPEC-4bb5b55f: TPS計測用にoutput_tokensとdurationを渡す
+        let tps_output_tokens = token_usage
+            .as_ref()
+            .and_then(|u| u.output_tokens)
+            .unwrap_or(0) as u64;
+        let tps_duration_ms = if tps_output_tokens > 0 {
+            duration.as_millis().max(1) as u64
+        } else {
+            0
+        };
         record_endpoint_request_stats(
-            state.endpoint_registry.clone(),
-            endpoint_id,
-            model.clone(),
-            false,
-            0,
-            0,
-            tps_api_kind,
-            endpoint_type,
-            state.load_manager.clone(),
-            state.event_bus.clone(),
+            self.state.endpoint_registry.clone(),
+            endpoint.id,
+            self.model.clone(),
+            true,
+            tps_output_tokens,
+            tps_duration_ms,
+            self.tps_api_kind,
+            endpoint.endpoint_type,
+            self.state.load_manager.clone(),
+            self.state.event_bus.clone(),
         );
 
+        // RequestResponseRecordにトークン情報を保存
+        let (input_tokens, output_tokens, total_tokens) = token_usage
+            .as_ref()
+            .map(|u| (u.input_tokens, u.output_tokens, u.total_tokens))
+            .unwrap_or((None, None, None));
+        let mut record = self.record(&endpoint, StatusCode::OK, duration);
+        record.response_body = Some(body.clone());
+        record.input_tokens = input_tokens;
+        record.output_tokens = output_tokens;
+        record.total_tokens = total_tokens;
+        self.options.save_record(self.state, record);
+
+        Ok(AttemptOutcome::Respond(self.with_queue_headers(
+            (StatusCode::OK, Json(body)).into_response(),
+        )))
+    }
+
+    /// 品質ルールに違反した応答は別エンドポイント/モデルで1回だけ再試行する
+    async fn quality_retry(
+        &self,
+        endpoint: &crate::types::endpoint::Endpoint,
+        body: &Value,
+    ) -> Option<(String, RetryStep)> {
+        if self.request_type == RequestType::Embeddings || !self.retry.allows_quality_retry() {
+            return None;
+        }
+        let filter = quality_filter::current()?;
+        let rule = filter.check(&self.model, body)?;
+        let retry_model = filter
+            .fallback_model()
+            .unwrap_or(self.resolved_model.as_str())
+            .to_string();
+        let step = self
+            .retry
+            .quality_retry(
+                self.state,
+                &retry_model,
+                &self.resolved_model,
+                self.tps_api_kind,
+                &self.options.selection,
+            )
+            .await;
+        quality_filter::record_violation(endpoint.id, &self.model, &rule, step.is_some());
+        step.map(|step| (rule, step))
+    }
+
+    /// 非2xx応答は 502 に正規化して返す（5xxは別エンドポイントへフェイルオーバーする）
+    async fn on_error_status(
+        &mut self,
+        attempt: Attempt,
+        response: reqwest::Response,
+    ) -> Result<AttemptOutcome, AppError> {
+        let Attempt {
+            endpoint,
+            lease,
+            start,
+        } = attempt;
+        let duration = start.elapsed();
+        self.complete_failed(&endpoint, lease, duration).await?;
+
         // Note: Model exclusion is handled by the health check system
         // which will mark the endpoint as offline/error if requests fail repeatedly
 
@@ -1184,24 +1961,29 @@ async fn proxy_openai_post(
         } else {
             String::from_utf8_lossy(&body_bytes).trim().to_string()
         };
-
-        {
-            let mut record = RequestResponseRecord::new(
-                endpoint_id,
-                endpoint_name.clone(),
-                endpoint_host,
-                model.clone(),
-                request_type,
-                request_body.clone(),
-                StatusCode::BAD_GATEWAY,
-                duration,
-                client_ip,
-                api_key_id,
-            );
-            record.status = RecordStatus::Error {
-                message: message.clone(),
-            };
-            save_request_record(state.request_history.clone(), record);
+        let next = if status.is_server_error() {
+            self.retry
+                .failover(
+                    self.state,
+                    &self.resolved_model,
+                    self.tps_api_kind,
+                    &self.options.selection,
+                    &format!("Upstream returned status {}", status),
+                )
+                .await
+        } else {
+            None
+        };
+        let record_message = match &next {
+            Some(step) => self.retry.message(&message, step),
+            None => message.clone(),
+        };
+        self.save_error(&endpoint, status_code, duration, record_message, None);
+        if let Some(step) = next {
+            return Ok(AttemptOutcome::Retry(self.retry.advance(step)));
+        }
+        if status.is_server_error() {
+            self.retry.log_failover_exhausted(&self.resolved_model);
         }
 
         let payload = json!({
@@ -1211,129 +1993,186 @@ async fn proxy_openai_post(
                 "code": status_code.as_u16(),
             }
         });
-
-        let mut response = (status_code, Json(payload)).into_response();
-        if let Some(wait_ms) = queued_wait_ms {
-            add_queue_headers(&mut response, wait_ms);
-        }
-        return Ok(response);
+        Ok(AttemptOutcome::Respond(self.failure_response(
+            (status_code, Json(payload)).into_response(),
+            FailureStage::Upstream,
+            format!("Upstream returned status {}", status),
+        )))
     }
 
-    let parsed = response.json::<Value>().await;
-    let duration = start.elapsed();
-
-    match parsed {
-        Ok(mut body) => {
-            if let Some(body_object) = body.as_object_mut() {
-                body_object.insert("model".to_string(), Value::String(model.clone()));
-            }
-
-            // レスポンスからトークン使用量を抽出
-            let token_usage = extract_usage_from_response(&body);
-
-            request_lease
-                .complete_with_tokens(RequestOutcome::Success, duration, token_usage.clone())
+    /// JSONモード違反の応答は設定に応じて別エンドポイントで再試行し、無ければ 502 を返す
+    async fn on_json_mode_violation(
+        &mut self,
+        endpoint: &crate::types::endpoint::Endpoint,
+        lease: RequestLease,
+        duration: std::time::Duration,
+        body: Value,
+    ) -> Result<AttemptOutcome, AppError> {
+        json_mode::record_violation(endpoint.id, &self.model, false);
+        self.complete_failed(endpoint, lease, duration).await?;
+
+        let next = if self.json_mode_validation == JsonModeValidation::Retry {
+            self.retry
+                .json_mode_retry(
+                    self.state,
+                    &self.resolved_model,
+                    self.tps_api_kind,
+                    &self.options.selection,
+                )
                 .await
-                .map_err(AppError::from)?;
-            // SPEC-f8e3a1b7: 成功時に推論レイテンシを更新
-            update_inference_latency(&state.endpoint_registry, endpoint_id, duration);
-            // SPEC-4bb5b55f: TPS計測用にoutput_tokensとdurationを渡す
-            let tps_output_tokens = token_usage
-                .as_ref()
-                .and_then(|u| u.output_tokens)
-                .unwrap_or(0) as u64;
-            let tps_duration_ms = if tps_output_tokens > 0 {
-                duration.as_millis().max(1) as u64
-            } else {
-                0
-            };
-            record_endpoint_request_stats(
-                state.endpoint_registry.clone(),
-                endpoint_id,
-                model.clone(),
-                true,
-                tps_output_tokens,
-                tps_duration_ms,
-                tps_api_kind,
-                endpoint_type,
-                state.load_manager.clone(),
-                state.event_bus.clone(),
-            );
+        } else {
+            None
+        };
+        let message = match &next {
+            Some(step) => self.retry.message(json_mode::VIOLATION_MESSAGE, step),
+            None => json_mode::VIOLATION_MESSAGE.to_string(),
+        };
+        self.save_error(
+            endpoint,
+            StatusCode::BAD_GATEWAY,
+            duration,
+            message,
+            Some(body),
+        );
+        if let Some(step) = next {
+            return Ok(AttemptOutcome::Retry(self.retry.advance(step)));
+        }
 
-            // RequestResponseRecordにトークン情報を保存
-            let (input_tokens, output_tokens, total_tokens) = token_usage
-                .as_ref()
-                .map(|u| (u.input_tokens, u.output_tokens, u.total_tokens))
-                .unwrap_or((None, None, None));
+        let response = openai_error_response_with_type(
+            json_mode::VIOLATION_MESSAGE,
+            json_mode::VIOLATION_ERROR_TYPE,
+            StatusCode::BAD_GATEWAY,
+        );
+        Ok(AttemptOutcome::Respond(self.failure_response(
+            response,
+            FailureStage::Upstream,
+            json_mode::VIOLATION_MESSAGE,
+        )))
+    }
 
-            {
-                let mut record = RequestResponseRecord::new(
-                    endpoint_id,
-                    endpoint_name,
-                    endpoint_host,
-                    model,
-                    request_type,
-                    request_body,
-                    StatusCode::OK,
-                    duration,
-                    client_ip,
-                    api_key_id,
-                );
-                record.response_body = Some(body.clone());
-                record.input_tokens = input_tokens;
-                record.output_tokens = output_tokens;
-                record.total_tokens = total_tokens;
-                save_request_record(state.request_history.clone(), record);
-            }
+    /// 応答本文を読めなかった場合（本文受信中のタイムアウトを含む）
+    async fn on_invalid_body(
+        &mut self,
+        endpoint: &crate::types::endpoint::Endpoint,
+        lease: RequestLease,
+        duration: std::time::Duration,
+        e: reqwest::Error,
+    ) -> Result<AttemptOutcome, AppError> {
+        self.complete_failed(endpoint, lease, duration).await?;
 
-            let mut response = (StatusCode::OK, Json(body)).into_response();
-            if let Some(wait_ms) = queued_wait_ms {
-                add_queue_headers(&mut response, wait_ms);
-            }
-            Ok(response)
-        }
-        Err(e) => {
-            request_lease
-                .complete(RequestOutcome::Error, duration)
-                .await
-                .map_err(AppError::from)?;
-            record_endpoint_request_stats(
-                state.endpoint_registry.clone(),
-                endpoint_id,
-                model.clone(),
-                false,
-                0,
-                0,
-                tps_api_kind,
-                endpoint_type,
-                state.load_manager.clone(),
-                state.event_bus.clone(),
-            );
+        // Note: Model exclusion is handled by the health check system
+        // which will mark the endpoint as offline/error if requests fail repeatedly
 
-            // Note: Model exclusion is handled by the health check system
-            // which will mark the endpoint as offline/error if requests fail repeatedly
+        // 本文受信中にリクエスト指定のタイムアウトを超えた
+        let timed_out = self.options.request_timeout.filter(|_| e.is_timeout());
+        let (status_code, message) = match timed_out {
+            Some(timeout) => (
+                StatusCode::GATEWAY_TIMEOUT,
+                upstream_timeout_message(timeout),
+            ),
+            None => (
+                StatusCode::BAD_GATEWAY,
+                format!("Failed to parse OpenAI response: {}", e),
+            ),
+        };
+        self.save_error(endpoint, status_code, duration, message.clone(), None);
+
+        if let Some(timeout) = timed_out {
+            return Ok(AttemptOutcome::Respond(self.failure_response(
+                upstream_timeout_response(timeout),
+                FailureStage::Timeout,
+                message,
+            )));
+        }
+        let context = FailureContext::new(
+            FailureStage::Upstream,
+            self.retry.attempted(),
+            message.clone(),
+        );
+        Err(LbError::Http(message).with_context(context).into())
+    }
 
-            {
-                let mut record = RequestResponseRecord::new(
-                    endpoint_id,
-                    endpoint_name,
-                    endpoint_host,
-                    model,
-                    request_type,
-                    request_body,
-                    StatusCode::BAD_GATEWAY,
-                    duration,
-                    client_ip,
-                    api_key_id,
-                );
-                record.status = RecordStatus::Error {
-                    message: format!("Failed to parse OpenAI response: {}", e),
-                };
-                save_request_record(state.request_history.clone(), record);
-            }
+    /// 失敗した試行を負荷・エンドポイント統計に記録する
+    async fn complete_failed(
+        &self,
+        endpoint: &crate::types::endpoint::Endpoint,
+        lease: RequestLease,
+        duration: std::time::Duration,
+    ) -> Result<(), AppError> {
+        lease
+            .complete(RequestOutcome::Error, duration)
+            .await
+            .map_err(AppError::from)?;
+        record_endpoint_request_stats(
+            self.state.endpoint_registry.clone(),
+            endpoint.id,
+            self.model.clone(),
+            false,
+            0,
+            0,
+            self.tps_api_kind,
+            endpoint.endpoint_type,
+            self.state.load_manager.clone(),
+            self.state.event_bus.clone(),
+        );
+        Ok(())
+    }
+
+    /// 試行の履歴レコードを作る
+    fn record(
+        &self,
+        endpoint: &crate::types::endpoint::Endpoint,
+        status: StatusCode,
+        duration: std::time::Duration,
+    ) -> RequestResponseRecord {
+        // RequestResponseRecordの互換性のため、デフォルトIP使用
+        // (今後、RequestResponseRecordのフィールドをリネームすべき)
+        RequestResponseRecord::new(
+            endpoint.id,
+            endpoint.name.clone(),
+            UNSPECIFIED_IP,
+            self.model.clone(),
+            self.request_type,
+            self.request_body.clone(),
+            status,
+            duration,
+            self.client_ip,
+            self.api_key_id,
+        )
+    }
 
-            Err(LbError::Http(format!("Failed to parse OpenAI response: {}", e)).into())
+    /// 失敗した試行を履歴に保存する
+    fn save_error(
+        &self,
+        endpoint: &crate::types::endpoint::Endpoint,
+        status: StatusCode,
+        duration: std::time::Duration,
+        message: String,
+        response_body: Option<Value>,
+    ) {
+        let mut record = self.record(endpoint, status, duration);
+        record.response_body = response_body;
+        record.status = RecordStatus::Error { message };
+        self.options.save_record(self.state, record);
+    }
+
+    /// エラー応答に失敗段階・試行済みエンドポイントとキュー待ち時間を付与する
+    fn failure_response(
+        &self,
+        mut response: Response,
+        stage: FailureStage,
+        detail: impl Into<String>,
+    ) -> Response {
+        attach_failure_context(&mut response, stage, self.retry.attempted(), detail);
+        self.with_queue_headers(response)
+    }
+
+    /// キュー待ちした場合は待ち時間ヘッダを付与する
+    fn with_queue_headers(&self, mut response: Response) -> Response {
+        if let Some(wait_ms) = self.queued_wait_ms {
+            add_queue_headers(&mut response, wait_ms);
         }
+        response
     }
 }
 
@@ -1344,7 +2183,7 @@ async fn proxy_openai_get(state: &AppState, target_path: &str) -> Result<Respons
 
     let request_lease = state
         .load_manager
-        .begin_request(endpoint_id)
+        .begin_request(endpoint_id, None)
         .await
         .map_err(AppError::from)?;
 
@@ -1409,10 +2248,11 @@ fn validation_error(message: impl Into<String>) -> AppError {
 #[cfg(test)]
 mod tests {
     use super::{
-        extract_client_ip_from_headers, parse_client_ip_from_forwarded_value, parse_cloud_model,
-        proxy_openai_cloud_post, proxy_openai_post,
+        list_models, parse_cloud_model, proxy_openai_cloud_post, proxy_openai_post, ListModelsQuery,
     };
+    use crate::common::ip::{extract_client_ip_from_headers, parse_client_ip_from_forwarded_value};
     use crate::common::protocol::{RecordStatus, RequestType};
+    use crate::metrics::timeline::RequestTimeline;
     use crate::{
         db::test_utils::{TestAppStateBuilder, TEST_LOCK},
         AppState,
@@ -1456,19 +2296,58 @@ mod tests {
         .await
     }
 
-    async fn add_online_chat_endpoint_with_type(
-        state: &AppState,
-        endpoint_name: &str,
-        base_url: String,
-        model_id: &str,
-        inference_timeout_secs: u32,
-        endpoint_type: crate::types::endpoint::EndpointType,
-    ) -> uuid::Uuid {
-        use crate::types::endpoint::{Endpoint, EndpointModel, EndpointStatus, SupportedAPI};
+    async fn add_online_chat_endpoint_with_type(
+        state: &AppState,
+        endpoint_name: &str,
+        base_url: String,
+        model_id: &str,
+        inference_timeout_secs: u32,
+        endpoint_type: crate::types::endpoint::EndpointType,
+    ) -> uuid::Uuid {
+        use crate::types::endpoint::{Endpoint, EndpointModel, EndpointStatus, SupportedAPI};
+
+        let mut endpoint = Endpoint::new(endpoint_name.to_string(), base_url, endpoint_type);
+        endpoint.status = EndpointStatus::Online;
+        endpoint.inference_timeout_secs = inference_timeout_secs;
+        let endpoint_id = endpoint.id;
+        state
+            .endpoint_registry
+            .add(endpoint)
+            .await
+            .expect("add endpoint");
+        state
+            .endpoint_registry
+            .add_model(&EndpointModel {
+                endpoint_id,
+                model_id: model_id.to_string(),
+                capabilities: None,
+                max_tokens: None,
+                last_checked: None,
+                supported_apis: vec![SupportedAPI::ChatCompletions],
+                canonical_name: None,
+            })
+            .await
+            .expect("add endpoint model");
+        endpoint_id
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn list_models_includes_models_on_offline_endpoints_as_not_ready() {
+        use crate::types::endpoint::{
+            Endpoint, EndpointModel, EndpointStatus, EndpointType, SupportedAPI,
+        };
+        use axum::extract::{Query, State};
 
-        let mut endpoint = Endpoint::new(endpoint_name.to_string(), base_url, endpoint_type);
-        endpoint.status = EndpointStatus::Online;
-        endpoint.inference_timeout_secs = inference_timeout_secs;
+        let _guard = TEST_LOCK.lock().await;
+        let (state, _dir) = create_state_with_tempdir().await;
+
+        let mut endpoint = Endpoint::new(
+            "offline-endpoint".to_string(),
+            "http://127.0.0.1:9".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        endpoint.status = EndpointStatus::Offline;
         let endpoint_id = endpoint.id;
         state
             .endpoint_registry
@@ -1479,7 +2358,7 @@ mod tests {
             .endpoint_registry
             .add_model(&EndpointModel {
                 endpoint_id,
-                model_id: model_id.to_string(),
+                model_id: "offline-model".to_string(),
                 capabilities: None,
                 max_tokens: None,
                 last_checked: None,
@@ -1488,7 +2367,49 @@ mod tests {
             })
             .await
             .expect("add endpoint model");
-        endpoint_id
+
+        let list = |verbose: Option<bool>| {
+            let state = state.clone();
+            async move {
+                let response = list_models(
+                    State(state),
+                    None,
+                    Query(ListModelsQuery {
+                        refresh: None,
+                        verbose,
+                    }),
+                )
+                .await
+                .expect("list models");
+                let body = to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .expect("read body");
+                let body: serde_json::Value = serde_json::from_slice(&body).expect("json body");
+                body["data"]
+                    .as_array()
+                    .and_then(|data| data.iter().find(|m| m["id"] == "offline-model").cloned())
+                    .expect("offline model listed")
+            }
+        };
+
+        let model = list(None).await;
+        assert_eq!(model["ready"], false);
+        assert_eq!(model["ready_endpoints"], 0);
+        assert_eq!(model["total_endpoints"], 1);
+        assert_eq!(model["owned_by"], "endpoint");
+
+        // verbose=false は OpenAI 互換のフィールドのみ
+        let model = list(Some(false)).await;
+        let keys: Vec<&str> = model
+            .as_object()
+            .expect("model object")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(keys.len(), 4);
+        for key in ["id", "object", "created", "owned_by"] {
+            assert!(keys.contains(&key), "missing {key}");
+        }
     }
 
     #[test]
@@ -1825,6 +2746,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await
         .expect("cloud proxy succeeds");
@@ -1959,6 +2882,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await;
         // モデルが登録されておらず、どのノードも報告していない場合は404
@@ -2035,6 +2960,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await;
 
@@ -2096,6 +3023,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await
         .expect("timeout should return response");
@@ -2125,6 +3054,145 @@ mod tests {
         assert!(matches!(records[0].status, RecordStatus::Error { .. }));
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn auto_downgrade_switches_to_larger_context_model_in_family() {
+        use crate::types::endpoint::{EndpointModel, SupportedAPI};
+
+        let _guard = TEST_LOCK.lock().await;
+        let (state, _dir) = create_state_with_tempdir().await;
+        let endpoint_id = add_online_chat_endpoint(
+            &state,
+            "auto-downgrade-endpoint",
+            "http://127.0.0.1:9".to_string(),
+            "ctxfam:8b",
+            5,
+        )
+        .await;
+        for (model_id, max_tokens) in [
+            ("ctxfam:8b", 16),
+            ("ctxfam:70b", 65536),
+            ("other:7b", 131072),
+        ] {
+            state
+                .endpoint_registry
+                .add_model(&EndpointModel {
+                    endpoint_id,
+                    model_id: model_id.to_string(),
+                    capabilities: None,
+                    max_tokens: Some(max_tokens),
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("add endpoint model");
+        }
+
+        let long_prompt = json!({
+            "model": "ctxfam:8b",
+            "messages": [{"role": "user", "content": "word ".repeat(200)}]
+        });
+        let short_prompt = json!({
+            "model": "ctxfam:8b",
+            "messages": [{"role": "user", "content": "hi"}]
+        });
+
+        std::env::remove_var("LLMLB_AUTO_DOWNGRADE");
+        assert!(resolve_auto_downgrade(&state, "ctxfam:8b", &long_prompt)
+            .await
+            .is_none());
+
+        std::env::set_var("LLMLB_AUTO_DOWNGRADE", "1");
+        let switch = resolve_auto_downgrade(&state, "ctxfam:8b", &long_prompt)
+            .await
+            .expect("switch to larger context");
+        assert_eq!(switch.requested, "ctxfam:8b");
+        assert_eq!(switch.selected, "ctxfam:70b");
+        assert!(resolve_auto_downgrade(&state, "ctxfam:8b", &short_prompt)
+            .await
+            .is_none());
+        std::env::remove_var("LLMLB_AUTO_DOWNGRADE");
+
+        let mut response = Response::new(axum::body::Body::empty());
+        switch.apply_header(&mut response);
+        assert_eq!(
+            response.headers()["x-llmlb-auto-downgrade-from"],
+            "ctxfam:8b"
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn request_timeout_header_overrides_endpoint_timeout() {
+        let _guard = TEST_LOCK.lock().await;
+        let (state, _dir) = create_state_with_tempdir().await;
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_delay(Duration::from_millis(500))
+                    .set_body_json(json!({
+                        "id": "chatcmpl-request-timeout",
+                        "object": "chat.completion",
+                        "choices": [{
+                            "index": 0,
+                            "message": {"role": "assistant", "content": "slow"},
+                            "finish_reason": "stop"
+                        }]
+                    })),
+            )
+            .mount(&server)
+            .await;
+
+        add_online_chat_endpoint(
+            &state,
+            "request-timeout-endpoint",
+            server.uri(),
+            "request-timeout-model",
+            1,
+        )
+        .await;
+
+        let request = |request_timeout| {
+            proxy_openai_post(
+                &state,
+                json!({
+                    "model": "request-timeout-model",
+                    "messages": [{"role":"user","content":"hello"}]
+                }),
+                "/v1/chat/completions",
+                "request-timeout-model".to_string(),
+                false,
+                RequestType::Chat,
+                None,
+                None,
+                RequestTimeline::start(),
+                RequestOptions {
+                    request_timeout,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let response = request(Some(Duration::from_millis(100)))
+            .await
+            .expect("timeout should return response");
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let body = to_bytes(response.into_body(), 1_000_000)
+            .await
+            .expect("timeout body");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("timeout json");
+        assert_eq!(json["error"]["type"], "upstream_timeout");
+        assert_eq!(json["error"]["code"], 504);
+
+        // ヘッダ未指定時はエンドポイントの既定タイムアウト（1秒）が使われる
+        let response = request(None).await.expect("default timeout response");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
     #[tokio::test]
     #[serial]
     async fn canonical_model_routes_to_alias_backed_endpoint_and_rewrites_payload() {
@@ -2195,6 +3263,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await
         .expect("canonical request should succeed");
@@ -2256,6 +3326,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await
         .expect("ollama cold-start timeout should return response");
@@ -2323,6 +3395,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await
         .expect("ollama success should return response");
@@ -2406,6 +3480,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await
         .expect("canonical request should succeed");
@@ -2446,6 +3522,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await
         .expect("connect failure should return response");
@@ -2463,6 +3541,322 @@ mod tests {
         assert_eq!(json["error"]["code"], 502);
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn non_stream_server_error_fails_over_to_another_endpoint() {
+        let _guard = TEST_LOCK.lock().await;
+        let (state, _dir) = create_state_with_tempdir().await;
+
+        let failing = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("boom"))
+            .mount(&failing)
+            .await;
+        let healthy = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "choices": [{"index": 0, "message": {"role": "assistant", "content": "ok"}, "finish_reason": "stop"}]
+            })))
+            .mount(&healthy)
+            .await;
+
+        add_online_chat_endpoint(
+            &state,
+            "failing-endpoint",
+            failing.uri(),
+            "failover-model",
+            5,
+        )
+        .await;
+        add_online_chat_endpoint(
+            &state,
+            "healthy-endpoint",
+            healthy.uri(),
+            "failover-model",
+            5,
+        )
+        .await;
+
+        std::env::set_var("LLMLB_FAILOVER_MAX_RETRIES", "1");
+        // どちらが先に選ばれても、5xx の場合は別エンドポイントで再試行される
+        for _ in 0..4 {
+            let response = proxy_openai_post(
+                &state,
+                json!({
+                    "model": "failover-model",
+                    "messages": [{"role":"user","content":"hello"}]
+                }),
+                "/v1/chat/completions",
+                "failover-model".to_string(),
+                false,
+                RequestType::Chat,
+                None,
+                None,
+                RequestTimeline::start(),
+                RequestOptions::default(),
+            )
+            .await
+            .expect("failover should return response");
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        std::env::remove_var("LLMLB_FAILOVER_MAX_RETRIES");
+        assert!(failing
+            .received_requests()
+            .await
+            .is_some_and(|r| !r.is_empty()));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn non_stream_timeout_escalates_to_another_endpoint_within_budget() {
+        let _guard = TEST_LOCK.lock().await;
+        let (state, _dir) = create_state_with_tempdir().await;
+
+        let completion = json!({
+            "id": "chatcmpl-escalation",
+            "object": "chat.completion",
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": "ok"}, "finish_reason": "stop"}]
+        });
+        let slow = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(completion.clone())
+                    .set_delay(Duration::from_secs(3)),
+            )
+            .mount(&slow)
+            .await;
+        let fast = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion))
+            .mount(&fast)
+            .await;
+
+        add_online_chat_endpoint(&state, "slow-endpoint", slow.uri(), "escalation-model", 30).await;
+        add_online_chat_endpoint(&state, "fast-endpoint", fast.uri(), "escalation-model", 30).await;
+
+        std::env::set_var("LLMLB_ESCALATION", "1");
+        std::env::set_var("LLMLB_ESCALATION_STAGE_TIMEOUTS", "1,5");
+        // どちらが先に選ばれても、1段目のタイムアウト後に別エンドポイントで応答する
+        for _ in 0..3 {
+            let started = std::time::Instant::now();
+            let response = proxy_openai_post(
+                &state,
+                json!({
+                    "model": "escalation-model",
+                    "messages": [{"role":"user","content":"hello"}]
+                }),
+                "/v1/chat/completions",
+                "escalation-model".to_string(),
+                false,
+                RequestType::Chat,
+                None,
+                None,
+                RequestTimeline::start(),
+                RequestOptions::default(),
+            )
+            .await
+            .expect("escalation should return response");
+            assert_eq!(response.status(), StatusCode::OK);
+            assert!(started.elapsed() < Duration::from_secs(3));
+        }
+        std::env::remove_var("LLMLB_ESCALATION");
+        std::env::remove_var("LLMLB_ESCALATION_STAGE_TIMEOUTS");
+
+        sleep(Duration::from_millis(50)).await;
+        let records = state.request_history.load_records().await.expect("records");
+        let escalations = records
+            .iter()
+            .filter(|record| {
+                matches!(&record.status, RecordStatus::Error { message }
+                    if message.contains("(escalation stage 1/2); escalating to endpoint 'fast-endpoint'"))
+            })
+            .count();
+        let slow_requests = slow.received_requests().await.map_or(0, |r| r.len());
+        assert_eq!(escalations, slow_requests);
+    }
+
+    /// SSEを返すモックサーバー（`body` が空なら最初のトークン前に切断する）
+    async fn start_stream_server(body: &'static str) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_raw(body, "text/event-stream"),
+            )
+            .mount(&server)
+            .await;
+        server
+    }
+
+    async fn post_stream_request(state: &AppState, model: &str) -> axum::response::Response {
+        proxy_openai_post(
+            state,
+            json!({
+                "model": model,
+                "messages": [{"role":"user","content":"hello"}],
+                "stream": true
+            }),
+            "/v1/chat/completions",
+            model.to_string(),
+            true,
+            RequestType::Chat,
+            None,
+            None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
+        )
+        .await
+        .expect("streaming request should return response")
+    }
+
+    const RECONNECT_STREAM_BODY: &str = concat!(
+        "data: {\"id\":\"chatcmpl-1\",\"choices\":[{\"delta\":{\"content\":\"Hello\"}}]}\n\n",
+        "data: [DONE]\n\n"
+    );
+
+    #[tokio::test]
+    #[serial]
+    async fn stream_early_disconnect_reconnects_to_another_endpoint() {
+        let _guard = TEST_LOCK.lock().await;
+        let (state, _dir) = create_state_with_tempdir().await;
+
+        let dropping = start_stream_server("").await;
+        let healthy = start_stream_server(RECONNECT_STREAM_BODY).await;
+        add_online_chat_endpoint(
+            &state,
+            "dropping-stream",
+            dropping.uri(),
+            "reconnect-model",
+            5,
+        )
+        .await;
+        add_online_chat_endpoint(
+            &state,
+            "healthy-stream",
+            healthy.uri(),
+            "reconnect-model",
+            5,
+        )
+        .await;
+
+        std::env::remove_var("LLMLB_STREAM_RECONNECT_ON");
+        std::env::set_var("LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS", "1");
+        // どちらが先に選ばれても、最初のトークン前の切断は別エンドポイントへ再接続される
+        for _ in 0..4 {
+            let response = post_stream_request(&state, "reconnect-model").await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1_000_000)
+                .await
+                .expect("stream body");
+            assert!(String::from_utf8_lossy(&body).contains("Hello"));
+        }
+        std::env::remove_var("LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS");
+
+        sleep(Duration::from_millis(50)).await;
+        let records = state.request_history.load_records().await.expect("records");
+        let reconnects = records
+            .iter()
+            .filter(|record| {
+                matches!(&record.status, RecordStatus::Error { message }
+                    if message == "Upstream stream closed before first token; \
+                        reconnecting stream to endpoint 'healthy-stream' (reconnect 1/1)")
+            })
+            .count();
+        let dropped = dropping.received_requests().await.map_or(0, |r| r.len());
+        assert_eq!(reconnects, dropped);
+        assert_eq!(healthy.received_requests().await.map_or(0, |r| r.len()), 4);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn stream_early_disconnect_returns_bad_gateway_after_attempts_exhausted() {
+        let _guard = TEST_LOCK.lock().await;
+        let (state, _dir) = create_state_with_tempdir().await;
+
+        let first = start_stream_server("").await;
+        let second = start_stream_server("").await;
+        add_online_chat_endpoint(&state, "dropping-a", first.uri(), "exhausted-model", 5).await;
+        add_online_chat_endpoint(&state, "dropping-b", second.uri(), "exhausted-model", 5).await;
+
+        std::env::remove_var("LLMLB_STREAM_RECONNECT_ON");
+        std::env::set_var("LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS", "1");
+        let response = post_stream_request(&state, "exhausted-model").await;
+        std::env::remove_var("LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS");
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let body = to_bytes(response.into_body(), 1_000_000)
+            .await
+            .expect("error body");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("error json");
+        assert_eq!(json["error"]["type"], "endpoint_stream_disconnected");
+        assert_eq!(
+            json["error"]["message"],
+            "Upstream stream closed before first token"
+        );
+        // 再接続は1回だけ行い、両方のエンドポイントに1回ずつ送信する
+        assert_eq!(first.received_requests().await.map_or(0, |r| r.len()), 1);
+        assert_eq!(second.received_requests().await.map_or(0, |r| r.len()), 1);
+
+        sleep(Duration::from_millis(50)).await;
+        let records = state.request_history.load_records().await.expect("records");
+        let mut messages: Vec<String> = records
+            .iter()
+            .filter_map(|record| match &record.status {
+                RecordStatus::Error { message } => Some(message.clone()),
+                _ => None,
+            })
+            .collect();
+        messages.sort();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0], "Upstream stream closed before first token");
+        assert!(messages[1].starts_with(
+            "Upstream stream closed before first token; reconnecting stream to endpoint 'dropping-"
+        ));
+        assert!(messages[1].ends_with("(reconnect 1/1)"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn stream_is_not_reconnected_after_first_bytes() {
+        let _guard = TEST_LOCK.lock().await;
+        let (state, _dir) = create_state_with_tempdir().await;
+
+        // 最初のチャンクを送った後、[DONE] なしで切断する
+        let partial_body =
+            "data: {\"id\":\"chatcmpl-1\",\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n";
+        let first = start_stream_server(partial_body).await;
+        let second = start_stream_server(partial_body).await;
+        add_online_chat_endpoint(&state, "partial-a", first.uri(), "partial-model", 5).await;
+        add_online_chat_endpoint(&state, "partial-b", second.uri(), "partial-model", 5).await;
+
+        std::env::remove_var("LLMLB_STREAM_RECONNECT_ON");
+        std::env::set_var("LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS", "3");
+        for _ in 0..3 {
+            let response = post_stream_request(&state, "partial-model").await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), 1_000_000)
+                .await
+                .expect("stream body");
+            assert!(String::from_utf8_lossy(&body).contains("Hel"));
+        }
+        std::env::remove_var("LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS");
+
+        // 送信開始後は再接続しないため、1リクエストにつき1エンドポイントにしか送らない
+        let sent = first.received_requests().await.map_or(0, |r| r.len())
+            + second.received_requests().await.map_or(0, |r| r.len());
+        assert_eq!(sent, 3);
+    }
+
     #[tokio::test]
     #[serial]
     async fn local_streaming_request_updates_model_tps_after_stream_completion() {
@@ -2528,6 +3922,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await
         .expect("streaming request should succeed");
@@ -2616,6 +4012,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await
         .expect("streaming request should succeed");
@@ -2713,6 +4111,8 @@ mod tests {
             RequestType::Chat,
             None,
             None,
+            RequestTimeline::start(),
+            RequestOptions::default(),
         )
         .await
         .expect("request should succeed");
@@ -3186,7 +4586,7 @@ mod tests {
 
     #[test]
     fn extract_x_forwarded_for_multiple_ips() {
-        use super::extract_x_forwarded_for;
+        use crate::common::ip::extract_x_forwarded_for;
         let mut headers = HeaderMap::new();
         headers.insert(
             "x-forwarded-for",
@@ -3198,21 +4598,21 @@ mod tests {
 
     #[test]
     fn extract_x_forwarded_for_missing_header_returns_none() {
-        use super::extract_x_forwarded_for;
+        use crate::common::ip::extract_x_forwarded_for;
         let headers = HeaderMap::new();
         assert!(extract_x_forwarded_for(&headers).is_none());
     }
 
     #[test]
     fn extract_forwarded_for_missing_header_returns_none() {
-        use super::extract_forwarded_for;
+        use crate::common::ip::extract_forwarded_for;
         let headers = HeaderMap::new();
         assert!(extract_forwarded_for(&headers).is_none());
     }
 
     #[test]
     fn extract_forwarded_for_multiple_entries() {
-        use super::extract_forwarded_for;
+        use crate::common::ip::extract_forwarded_for;
         let mut headers = HeaderMap::new();
         headers.insert(
             "forwarded",
@@ -3224,7 +4624,7 @@ mod tests {
 
     #[test]
     fn extract_forwarded_for_case_insensitive_key() {
-        use super::extract_forwarded_for;
+        use crate::common::ip::extract_forwarded_for;
         let mut headers = HeaderMap::new();
         headers.insert(
             "forwarded",
@@ -3509,7 +4909,7 @@ mod tests {
 
     #[test]
     fn extract_x_forwarded_for_all_unknown() {
-        use super::extract_x_forwarded_for;
+        use crate::common::ip::extract_x_forwarded_for;
         let mut headers = HeaderMap::new();
         headers.insert(
             "x-forwarded-for",
@@ -3520,7 +4920,7 @@ mod tests {
 
     #[test]
     fn extract_x_forwarded_for_single_valid_ip() {
-        use super::extract_x_forwarded_for;
+        use crate::common::ip::extract_x_forwarded_for;
         let mut headers = HeaderMap::new();
         headers.insert("x-forwarded-for", HeaderValue::from_static("172.16.0.1"));
         let ip = extract_x_forwarded_for(&headers).unwrap();
@@ -3529,7 +4929,7 @@ mod tests {
 
     #[test]
     fn extract_x_forwarded_for_with_ipv6() {
-        use super::extract_x_forwarded_for;
+        use crate::common::ip::extract_x_forwarded_for;
         let mut headers = HeaderMap::new();
         headers.insert(
             "x-forwarded-for",
@@ -3543,7 +4943,7 @@ mod tests {
 
     #[test]
     fn extract_forwarded_for_no_for_key() {
-        use super::extract_forwarded_for;
+        use crate::common::ip::extract_forwarded_for;
         let mut headers = HeaderMap::new();
         headers.insert(
             "forwarded",
@@ -3554,7 +4954,7 @@ mod tests {
 
     #[test]
     fn extract_forwarded_for_empty_value() {
-        use super::extract_forwarded_for;
+        use crate::common::ip::extract_forwarded_for;
         let mut headers = HeaderMap::new();
         headers.insert("forwarded", HeaderValue::from_static(""));
         assert!(extract_forwarded_for(&headers).is_none());
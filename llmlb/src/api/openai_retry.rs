@@ -0,0 +1,408 @@
+//! OpenAI互換APIの別エンドポイントへのやり直し
+//!
+//! first-token前のストリーミング再接続、非ストリーミングの5xx・接続エラー時のフェイルオーバー、
+//! 段階的タイムアウトエスカレーション、JSONモード違反・品質フィルタ違反時の再試行を扱う。
+//! 回数はやり直しの種類ごとに数え、やり直し先はロードバランサーモードに従って
+//! 試行済みのエンドポイントを除外して選択する。
+
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    balancer::SelectionContext,
+    common::protocol::TpsApiKind,
+    config::{EscalationConfig, StreamReconnectCause, StreamReconnectConfig},
+    types::endpoint::Endpoint,
+    AppState,
+};
+
+/// 別エンドポイントへのやり直し
+#[derive(Debug)]
+pub(crate) enum RetryStep {
+    /// first-token前のストリーミング再接続
+    Reconnect(Endpoint),
+    /// タイムアウトした段階の次の段階で送り直す
+    Escalate {
+        /// 次の段階（0始まり）
+        stage: usize,
+        /// 送り直し先
+        endpoint: Endpoint,
+    },
+    /// 非ストリーミングの5xx・接続エラー時の再試行
+    Failover(Endpoint),
+    /// JSONモード違反時の再試行
+    JsonMode(Endpoint),
+    /// 品質フィルタ違反時の再試行（フォールバックモデルへの切替を含む）
+    Quality {
+        /// 再試行先
+        endpoint: Endpoint,
+        /// 再試行するモデル
+        model: String,
+    },
+}
+
+/// 1リクエスト分のやり直し回数と試行済みエンドポイント
+#[derive(Debug)]
+pub(crate) struct RetryPolicy {
+    reconnect: StreamReconnectConfig,
+    escalation: Option<EscalationConfig>,
+    escalation_started: Instant,
+    escalation_stage: usize,
+    attempted: Vec<Uuid>,
+    reconnects: u32,
+    failover_retries: u32,
+    json_mode_retries: u32,
+    quality_retried: bool,
+}
+
+impl RetryPolicy {
+    /// 環境変数の設定からリクエスト単位の状態を作る
+    ///
+    /// 段階的タイムアウトエスカレーションは、非ストリーミングかつリクエスト個別の
+    /// タイムアウト指定が無い場合のみ有効にする。
+    pub(crate) fn new(stream: bool, request_timeout: Option<Duration>) -> Self {
+        let escalation = if stream || request_timeout.is_some() {
+            None
+        } else {
+            EscalationConfig::from_env()
+        };
+        Self {
+            reconnect: StreamReconnectConfig::from_env(),
+            escalation,
+            escalation_started: Instant::now(),
+            escalation_stage: 0,
+            attempted: Vec::new(),
+            reconnects: 0,
+            failover_retries: 0,
+            json_mode_retries: 0,
+            quality_retried: false,
+        }
+    }
+
+    /// 送信先エンドポイントを試行済みとして記録する
+    pub(crate) fn begin_attempt(&mut self, endpoint_id: Uuid) {
+        self.attempted.push(endpoint_id);
+    }
+
+    /// 試行済みエンドポイント（送信順）
+    pub(crate) fn attempted(&self) -> &[Uuid] {
+        &self.attempted
+    }
+
+    /// 種類を問わず別エンドポイントへやり直した回数
+    pub(crate) fn total_retries(&self) -> usize {
+        self.attempted.len().saturating_sub(1)
+    }
+
+    /// 指定条件でストリーミング再接続が有効か
+    pub(crate) fn reconnects_on(&self, cause: StreamReconnectCause) -> bool {
+        self.reconnect.allows(cause)
+    }
+
+    /// 今回の試行のアップストリームタイムアウト
+    ///
+    /// エスカレーション中は現在の段階のタイムアウト、それ以外は `default` を使う。
+    pub(crate) fn upstream_timeout(&self, default: Duration) -> Duration {
+        match &self.escalation {
+            Some(config) => {
+                config.stage_timeout(self.escalation_stage, self.escalation_started.elapsed())
+            }
+            None => default,
+        }
+    }
+
+    /// エスカレーション中は失敗理由に現在の段階を付記する
+    pub(crate) fn failure_reason(&self, reason: String) -> String {
+        match &self.escalation {
+            Some(config) => format!(
+                "{} (escalation stage {}/{})",
+                reason,
+                self.escalation_stage + 1,
+                config.stages()
+            ),
+            None => reason,
+        }
+    }
+
+    /// first-token前のストリーミング失敗時の再接続先
+    ///
+    /// 再接続が無効、再接続回数を使い切った、または未試行の候補がない場合は `None` を返す。
+    pub(crate) async fn reconnect(
+        &self,
+        state: &AppState,
+        cause: StreamReconnectCause,
+        model: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+    ) -> Option<RetryStep> {
+        if !self.reconnect.allows(cause) || self.reconnects >= self.reconnect.max_attempts {
+            return None;
+        }
+        let endpoint = self.select(state, model, api_kind, ctx, true).await?;
+        warn!(
+            model = %model,
+            ?cause,
+            next_endpoint = %endpoint.name,
+            reconnect = self.reconnects + 1,
+            max_attempts = self.reconnect.max_attempts,
+            "Reconnecting stream to another endpoint before first token"
+        );
+        Some(RetryStep::Reconnect(endpoint))
+    }
+
+    /// タイムアウト時に次の段階で送り直すエンドポイント
+    ///
+    /// エスカレーションが無効、段階または合計予算を使い切った、もしくは未試行の候補がない
+    /// 場合は `None` を返す。
+    pub(crate) async fn escalation(
+        &self,
+        state: &AppState,
+        model: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+    ) -> Option<RetryStep> {
+        let config = self.escalation.as_ref()?;
+        let stage = config.next_stage(self.escalation_stage, self.escalation_started.elapsed())?;
+        let endpoint = self.select(state, model, api_kind, ctx, true).await?;
+        warn!(
+            model = %model,
+            next_endpoint = %endpoint.name,
+            stage = stage + 1,
+            stages = config.stages(),
+            elapsed_ms = self.escalation_started.elapsed().as_millis() as u64,
+            budget_ms = config.budget.as_millis() as u64,
+            "Escalating timed-out request to another endpoint"
+        );
+        Some(RetryStep::Escalate { stage, endpoint })
+    }
+
+    /// 非ストリーミングの5xx・接続エラー時の再試行先
+    ///
+    /// 再試行が無効、再試行回数を使い切った、または未試行の候補がない場合は `None` を返す。
+    /// 応答本文の受信を始めた後の失敗は再試行しない（呼び出し側で判定する）。
+    pub(crate) async fn failover(
+        &self,
+        state: &AppState,
+        model: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+        reason: &str,
+    ) -> Option<RetryStep> {
+        let max_retries = crate::config::failover_max_retries();
+        if self.failover_retries >= max_retries {
+            return None;
+        }
+        let endpoint = self.select(state, model, api_kind, ctx, true).await?;
+        warn!(
+            model = %model,
+            reason = %reason,
+            next_endpoint = %endpoint.name,
+            retry = self.failover_retries + 1,
+            max_retries,
+            attempted_endpoints = ?self.attempted,
+            "Retrying request on another endpoint"
+        );
+        Some(RetryStep::Failover(endpoint))
+    }
+
+    /// JSONモード違反時の再試行先（`LLMLB_JSON_MODE_MAX_RETRIES` 回まで）
+    pub(crate) async fn json_mode_retry(
+        &self,
+        state: &AppState,
+        model: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+    ) -> Option<RetryStep> {
+        if self.json_mode_retries >= crate::config::json_mode_max_retries() {
+            return None;
+        }
+        let endpoint = self.select(state, model, api_kind, ctx, true).await?;
+        Some(RetryStep::JsonMode(endpoint))
+    }
+
+    /// 品質フィルタ違反時に再試行できるか（1回まで）
+    pub(crate) fn allows_quality_retry(&self) -> bool {
+        !self.quality_retried
+    }
+
+    /// 品質フィルタ違反時の再試行先
+    ///
+    /// 同じモデルで再試行する場合のみ試行済みのエンドポイントを除外する。
+    pub(crate) async fn quality_retry(
+        &self,
+        state: &AppState,
+        retry_model: &str,
+        resolved_model: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+    ) -> Option<RetryStep> {
+        if self.quality_retried {
+            return None;
+        }
+        let same_model = retry_model == resolved_model;
+        let endpoint = self
+            .select(state, retry_model, api_kind, ctx, same_model)
+            .await?;
+        Some(RetryStep::Quality {
+            endpoint,
+            model: retry_model.to_string(),
+        })
+    }
+
+    /// やり直しを履歴に残すためのエラーメッセージを組み立てる
+    pub(crate) fn message(&self, reason: &str, step: &RetryStep) -> String {
+        match step {
+            RetryStep::Reconnect(next) => format!(
+                "{}; reconnecting stream to endpoint '{}' (reconnect {}/{})",
+                reason,
+                next.name,
+                self.reconnects + 1,
+                self.reconnect.max_attempts
+            ),
+            RetryStep::Escalate {
+                stage,
+                endpoint: next,
+            } => {
+                let next_timeout = self
+                    .escalation
+                    .as_ref()
+                    .map(|config| config.stage_timeout(*stage, self.escalation_started.elapsed()))
+                    .unwrap_or_default();
+                format!(
+                    "{}; escalating to endpoint '{}' with {:.1}s timeout",
+                    reason,
+                    next.name,
+                    next_timeout.as_secs_f64()
+                )
+            }
+            RetryStep::Failover(next) => format!(
+                "{}; retrying on endpoint '{}' (retry {}/{})",
+                reason,
+                next.name,
+                self.failover_retries + 1,
+                crate::config::failover_max_retries()
+            ),
+            RetryStep::JsonMode(next) => {
+                format!("{}; retrying on endpoint '{}'", reason, next.name)
+            }
+            RetryStep::Quality {
+                endpoint: next,
+                model,
+            } => format!(
+                "{}; retrying on endpoint '{}' with model '{}'",
+                reason, next.name, model
+            ),
+        }
+    }
+
+    /// やり直しの種類ごとの回数を進め、やり直し先のエンドポイントを返す
+    pub(crate) fn advance(&mut self, step: RetryStep) -> Endpoint {
+        match step {
+            RetryStep::Reconnect(endpoint) => {
+                self.reconnects += 1;
+                endpoint
+            }
+            RetryStep::Escalate { stage, endpoint } => {
+                self.escalation_stage = stage;
+                endpoint
+            }
+            RetryStep::Failover(endpoint) => {
+                self.failover_retries += 1;
+                endpoint
+            }
+            RetryStep::JsonMode(endpoint) => {
+                self.json_mode_retries += 1;
+                endpoint
+            }
+            RetryStep::Quality { endpoint, .. } => {
+                self.quality_retried = true;
+                endpoint
+            }
+        }
+    }
+
+    /// フェイルオーバーした上で全エンドポイントが失敗した場合に、選択履歴をログに残す
+    pub(crate) fn log_failover_exhausted(&self, model: &str) {
+        if self.failover_retries > 0 {
+            warn!(
+                model = %model,
+                retries = self.failover_retries,
+                attempted_endpoints = ?self.attempted,
+                "All failover attempts failed; returning the last error"
+            );
+        }
+    }
+
+    /// ロードバランサーモードに従ってやり直し先を選択する
+    ///
+    /// `exclude_attempted` が真なら試行済みのエンドポイントを除外する
+    /// （最後に失敗したエンドポイントの優先フェイルオーバー先があればそちらを先に試す）。
+    async fn select(
+        &self,
+        state: &AppState,
+        model: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+        exclude_attempted: bool,
+    ) -> Option<Endpoint> {
+        let excluded: &[Uuid] = if exclude_attempted {
+            &self.attempted
+        } else {
+            &[]
+        };
+        state
+            .load_manager
+            .select_endpoint_by_mode_excluding(
+                crate::config::load_balancer_mode(),
+                model,
+                api_kind,
+                ctx,
+                excluded,
+            )
+            .await
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::EndpointType;
+
+    fn endpoint(name: &str) -> Endpoint {
+        Endpoint::new(
+            name.to_string(),
+            "http://localhost:8080".to_string(),
+            EndpointType::OpenaiCompatible,
+        )
+    }
+
+    #[test]
+    fn retry_counters_are_tracked_per_kind() {
+        let mut policy = RetryPolicy::new(true, None);
+        policy.reconnect.max_attempts = 1;
+        policy.begin_attempt(Uuid::new_v4());
+
+        // フェイルオーバーや品質再試行は再接続回数を消費しない
+        policy.advance(RetryStep::Failover(endpoint("a")));
+        policy.advance(RetryStep::Quality {
+            endpoint: endpoint("b"),
+            model: "m".to_string(),
+        });
+        policy.begin_attempt(Uuid::new_v4());
+        policy.begin_attempt(Uuid::new_v4());
+        assert_eq!(policy.total_retries(), 2);
+
+        let reconnect = RetryStep::Reconnect(endpoint("c"));
+        assert_eq!(
+            policy.message("closed", &reconnect),
+            "closed; reconnecting stream to endpoint 'c' (reconnect 1/1)"
+        );
+        policy.advance(reconnect);
+        assert_eq!(policy.reconnects, 1);
+        assert_eq!(policy.failover_retries, 1);
+        assert!(!policy.allows_quality_retry());
+    }
+}
@@ -3,7 +3,7 @@
 //! ペイロードのサニタイズ、メッセージ変換、エラーレスポンス生成など。
 
 use axum::{
-    http::{HeaderName, HeaderValue, StatusCode},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -192,6 +192,48 @@ pub fn sanitize_openai_payload_for_history(payload: &Value) -> Value {
     redact_data_url(payload)
 }
 
+/// リダクション後の値
+pub const REDACTED: &str = "[redacted]";
+
+/// 値をリダクションするキー（小文字で完全一致）
+const SECRET_KEYS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "x-api-key",
+    "authorization",
+    "password",
+    "secret",
+    "client_secret",
+    "access_token",
+    "refresh_token",
+    "id_token",
+];
+
+/// 履歴を外部へ出力・保存する際にペイロードをリダクション
+///
+/// base64データに加え、APIキー・パスワード等の秘匿情報キーの値を伏せる。
+pub fn redact_payload_for_history(payload: &Value) -> Value {
+    fn redact_secrets(value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| {
+                        if SECRET_KEYS.contains(&key.to_ascii_lowercase().as_str()) {
+                            (key, Value::String(REDACTED.to_string()))
+                        } else {
+                            (key, redact_secrets(value))
+                        }
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.into_iter().map(redact_secrets).collect()),
+            other => other,
+        }
+    }
+
+    redact_secrets(sanitize_openai_payload_for_history(payload))
+}
+
 /// OpenAIメッセージ形式をGoogle Generative AI形式に変換
 pub fn map_openai_messages_to_google_contents(messages: &[Value]) -> Vec<Value> {
     messages
@@ -238,6 +280,29 @@ pub fn map_openai_messages_to_anthropic(messages: &[Value]) -> (Option<String>,
     (system, regular)
 }
 
+/// `Content-Type` が JSON（`application/json` または `application/*+json`）か
+///
+/// axum の `Json` 抽出子と同じ判定（大文字小文字・パラメータを無視）で、
+/// ハンドラが JSON として受け付けるボディをミドルウェアでも漏れなく検査できるようにする。
+pub fn is_json_content_type(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let Some((type_, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+    type_ == "application" && (subtype == "json" || subtype.ends_with("+json"))
+}
+
 /// OpenAI互換のエラーレスポンスを生成
 pub fn openai_error_response_with_type(
     message: impl Into<String>,
@@ -309,6 +374,23 @@ mod tests {
     use axum::body::to_bytes;
     use serde_json::json;
 
+    #[test]
+    fn json_content_type_matches_axum_json_extractor() {
+        let check = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::CONTENT_TYPE, HeaderValue::from_str(value).unwrap());
+            is_json_content_type(&headers)
+        };
+        assert!(check("application/json"));
+        assert!(check("application/json; charset=utf-8"));
+        assert!(check("Application/JSON"));
+        assert!(check("application/vnd.api+json"));
+        assert!(!check("text/plain"));
+        assert!(!check("application/jsonp"));
+        assert!(!check("multipart/form-data; boundary=x"));
+        assert!(!is_json_content_type(&HeaderMap::new()));
+    }
+
     // ========================================================================
     // sanitize_openai_payload_for_history
     // ========================================================================
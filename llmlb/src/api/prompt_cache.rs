@@ -0,0 +1,298 @@
+//! プロンプトキャッシュヒントの転送とプレフィックスアフィニティ
+//!
+//! クライアントが付けた `cache_control`（Anthropic形式）や `prompt_cache_key`（OpenAI形式）を
+//! アップストリームへ届け、同じプレフィックスを持つリクエストを同じエンドポイントへ寄せる。
+//! 寄せ先の管理はセッションアフィニティの対応表（TTL付き）をそのまま使う。
+//! キャッシュヒット率はレスポンスの usage に含まれるキャッシュ読み出しトークン数から集計する。
+
+use crate::balancer::SelectionContext;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+/// プレフィックスアフィニティ用セッションキーの接頭辞
+const AFFINITY_KEY_PREFIX: &str = "prompt-cache:";
+
+/// `value` 以下のどこかに `cache_control` が含まれるか
+fn contains_cache_control(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => {
+            map.contains_key("cache_control") || map.values().any(contains_cache_control)
+        }
+        Value::Array(items) => items.iter().any(contains_cache_control),
+        _ => false,
+    }
+}
+
+fn hex_digest(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .take(16)
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// キャッシュヒントからプレフィックスアフィニティのキーを算出する（ヒントが無ければ `None`）
+///
+/// `prompt_cache_key` があればその値を、無ければ tools → system → messages の順で
+/// 最初の `cache_control` までの内容のハッシュをキーにする。
+/// 会話の末尾に付け直されるブレークポイントではなく最初のものを使い、ターンをまたいでも
+/// キーが変わらないようにする。
+pub fn affinity_key(model: &str, payload: &Value) -> Option<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+
+    if let Some(key) = payload
+        .get("prompt_cache_key")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+    {
+        hasher.update(b"\0key\0");
+        hasher.update(key.as_bytes());
+        return Some(format!(
+            "{AFFINITY_KEY_PREFIX}{model}:{}",
+            hex_digest(hasher)
+        ));
+    }
+
+    let segments: Vec<&Value> = [payload.get("tools"), payload.get("system")]
+        .into_iter()
+        .flatten()
+        .chain(
+            payload
+                .get("messages")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten(),
+        )
+        .collect();
+    let breakpoint = segments.iter().position(|v| contains_cache_control(v))?;
+    for segment in &segments[..=breakpoint] {
+        hasher.update(b"\0");
+        hasher.update(segment.to_string().as_bytes());
+    }
+    Some(format!(
+        "{AFFINITY_KEY_PREFIX}{model}:{}",
+        hex_digest(hasher)
+    ))
+}
+
+/// プレフィックスアフィニティのキーをセッションIDとして選択の入力に設定する
+///
+/// `X-LLMLB-Session-Id` が指定されている場合はそちらを優先し、何もしない。
+pub fn apply_prefix_affinity(ctx: &mut SelectionContext, model: &str, payload: &Value) {
+    if ctx.session_id.is_none() {
+        ctx.session_id = affinity_key(model, payload);
+    }
+}
+
+/// レスポンスの usage から読み取ったプロンプトキャッシュの利用状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptCacheUsage {
+    /// 入力トークン数（キャッシュ読み出し分を含む）
+    pub input_tokens: u64,
+    /// キャッシュから読み出された入力トークン数
+    pub cached_tokens: u64,
+}
+
+/// レスポンスボディからプロンプトキャッシュの利用状況を取り出す
+///
+/// OpenAI（`prompt_tokens_details.cached_tokens` / `input_tokens_details.cached_tokens`）と
+/// Anthropic（`cache_read_input_tokens`）の形式に対応する。キャッシュ情報が無ければ `None`。
+pub fn usage_from_response(body: &Value) -> Option<PromptCacheUsage> {
+    let usage = body.get("usage")?;
+    let field = |name: &str| usage.get(name).and_then(Value::as_u64);
+
+    if let Some(cached_tokens) = field("cache_read_input_tokens") {
+        // Anthropic の input_tokens はキャッシュ読み出し・書き込み分を含まない
+        let input_tokens = field("input_tokens").unwrap_or(0)
+            + field("cache_creation_input_tokens").unwrap_or(0)
+            + cached_tokens;
+        return Some(PromptCacheUsage {
+            input_tokens,
+            cached_tokens,
+        });
+    }
+
+    let (input_tokens, cached_tokens) = [
+        ("prompt_tokens", "prompt_tokens_details"),
+        ("input_tokens", "input_tokens_details"),
+    ]
+    .into_iter()
+    .find_map(|(total, details)| {
+        let cached = usage.get(details)?.get("cached_tokens")?.as_u64()?;
+        Some((field(total).unwrap_or(cached), cached))
+    })?;
+    Some(PromptCacheUsage {
+        input_tokens,
+        cached_tokens,
+    })
+}
+
+/// OpenAIメッセージの内容をAnthropicのテキストブロックへ変換する（`cache_control` を保持）
+fn anthropic_text_blocks(message: &Value) -> Vec<Value> {
+    let mut blocks: Vec<Value> = match message.get("content") {
+        Some(Value::String(text)) => vec![json!({"type": "text", "text": text})],
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter(|part| part.get("type").and_then(Value::as_str) == Some("text"))
+            .map(|part| {
+                let text = part.get("text").and_then(Value::as_str).unwrap_or("");
+                let mut block = json!({"type": "text", "text": text});
+                if let Some(cache_control) = part.get("cache_control") {
+                    block["cache_control"] = cache_control.clone();
+                }
+                block
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    if blocks.is_empty() {
+        blocks.push(json!({"type": "text", "text": ""}));
+    }
+    // メッセージ単位の指定は最後のブロックへ付ける
+    if let (Some(cache_control), Some(last)) = (message.get("cache_control"), blocks.last_mut()) {
+        last["cache_control"] = cache_control.clone();
+    }
+    blocks
+}
+
+/// OpenAI形式のメッセージに付いた `cache_control` を変換後のAnthropicリクエストへ反映する
+///
+/// `body` は `map_openai_messages_to_anthropic` の結果から組み立てたもの。ヒントが無ければ何もしない。
+/// system に `cache_control` がある場合は文字列ではなくブロック配列で送る。
+pub fn carry_cache_control_to_anthropic(openai_messages: &[Value], body: &mut Value) {
+    if !openai_messages.iter().any(contains_cache_control) {
+        return;
+    }
+    let mut system_blocks = Vec::new();
+    let mut message_blocks = Vec::new();
+    for message in openai_messages {
+        let blocks = anthropic_text_blocks(message);
+        match message.get("role").and_then(Value::as_str) {
+            Some("system") => system_blocks.extend(blocks),
+            _ => message_blocks.push(blocks),
+        }
+    }
+    if system_blocks.iter().any(contains_cache_control) {
+        body["system"] = Value::Array(system_blocks);
+    }
+    if let Some(messages) = body.get_mut("messages").and_then(Value::as_array_mut) {
+        for (message, blocks) in messages.iter_mut().zip(message_blocks) {
+            message["content"] = Value::Array(blocks);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::openai_util::map_openai_messages_to_anthropic;
+
+    #[test]
+    fn affinity_key_requires_cache_hint() {
+        let payload = json!({"messages": [{"role": "user", "content": "hi"}]});
+        assert_eq!(affinity_key("m", &payload), None);
+    }
+
+    #[test]
+    fn affinity_key_is_stable_across_turns_with_same_prefix() {
+        let system = json!({
+            "role": "system",
+            "content": [{"type": "text", "text": "long doc", "cache_control": {"type": "ephemeral"}}]
+        });
+        let turn1 = json!({"messages": [system.clone(), {"role": "user", "content": "q1"}]});
+        let turn2 = json!({"messages": [
+            system,
+            {"role": "user", "content": "q1"},
+            {"role": "assistant", "content": "a1"},
+            {"role": "user", "content": "q2", "cache_control": {"type": "ephemeral"}}
+        ]});
+        let other = json!({"messages": [{
+            "role": "system",
+            "content": [{"type": "text", "text": "other doc", "cache_control": {"type": "ephemeral"}}]
+        }]});
+
+        let key = affinity_key("m", &turn1).unwrap();
+        assert!(key.starts_with("prompt-cache:m:"));
+        assert_eq!(affinity_key("m", &turn2).as_deref(), Some(key.as_str()));
+        assert_ne!(affinity_key("m", &other).as_deref(), Some(key.as_str()));
+        assert_ne!(affinity_key("m2", &turn1).as_deref(), Some(key.as_str()));
+    }
+
+    #[test]
+    fn affinity_key_uses_prompt_cache_key() {
+        let a =
+            json!({"prompt_cache_key": "tenant-1", "messages": [{"role": "user", "content": "x"}]});
+        let b =
+            json!({"prompt_cache_key": "tenant-1", "messages": [{"role": "user", "content": "y"}]});
+        assert!(affinity_key("m", &a).is_some());
+        assert_eq!(affinity_key("m", &a), affinity_key("m", &b));
+    }
+
+    #[test]
+    fn usage_from_response_reads_openai_and_anthropic_formats() {
+        let openai = json!({"usage": {
+            "prompt_tokens": 1200, "completion_tokens": 10,
+            "prompt_tokens_details": {"cached_tokens": 1024}
+        }});
+        assert_eq!(
+            usage_from_response(&openai),
+            Some(PromptCacheUsage {
+                input_tokens: 1200,
+                cached_tokens: 1024
+            })
+        );
+
+        let anthropic = json!({"usage": {
+            "input_tokens": 20, "cache_creation_input_tokens": 0,
+            "cache_read_input_tokens": 1000, "output_tokens": 5
+        }});
+        assert_eq!(
+            usage_from_response(&anthropic),
+            Some(PromptCacheUsage {
+                input_tokens: 1020,
+                cached_tokens: 1000
+            })
+        );
+
+        let no_cache = json!({"usage": {"prompt_tokens": 10, "completion_tokens": 2}});
+        assert_eq!(usage_from_response(&no_cache), None);
+    }
+
+    #[test]
+    fn carries_cache_control_into_anthropic_blocks() {
+        let messages = vec![
+            json!({"role": "system", "content": [
+                {"type": "text", "text": "doc", "cache_control": {"type": "ephemeral"}}
+            ]}),
+            json!({"role": "user", "content": "hi", "cache_control": {"type": "ephemeral"}}),
+        ];
+        let (system, mapped) = map_openai_messages_to_anthropic(&messages);
+        let mut body = json!({"messages": mapped, "system": system});
+        carry_cache_control_to_anthropic(&messages, &mut body);
+
+        assert_eq!(
+            body["system"],
+            json!([{"type": "text", "text": "doc", "cache_control": {"type": "ephemeral"}}])
+        );
+        assert_eq!(
+            body["messages"][0]["content"],
+            json!([{"type": "text", "text": "hi", "cache_control": {"type": "ephemeral"}}])
+        );
+    }
+
+    #[test]
+    fn leaves_anthropic_body_untouched_without_hints() {
+        let messages = vec![
+            json!({"role": "system", "content": "sys"}),
+            json!({"role": "user", "content": "hi"}),
+        ];
+        let (system, mapped) = map_openai_messages_to_anthropic(&messages);
+        let mut body = json!({"messages": mapped, "system": system});
+        let before = body.clone();
+        carry_cache_control_to_anthropic(&messages, &mut body);
+        assert_eq!(body, before);
+    }
+}
@@ -4,11 +4,14 @@
 //!
 //! このモジュールはEndpoint型を使用しています。
 
+use crate::api::sse_normalize::SseNormalizer;
+use crate::balancer::{priority::RequestPriority, SelectionContext};
 use crate::common::{
     error::LbError,
     protocol::{RequestResponseRecord, TpsApiKind},
 };
-use crate::token::StreamingTokenAccumulator;
+use crate::metrics::timeline::{RequestTimeline, TimelineStage};
+use crate::token::{StreamingTokenAccumulator, TokenUsage};
 use crate::{config::QueueConfig, types::endpoint::Endpoint, AppState};
 use axum::{
     body::Body,
@@ -42,24 +45,50 @@ pub(crate) enum QueueSelection {
     Timeout { waited_ms: u128 },
 }
 
-/// モデル対応のエンドポイントをTPS優先・キュー付きで選択
+/// モデル対応のエンドポイントをキュー付きで選択
+///
+/// `LLMLB_LOAD_BALANCER_MODE=weighted` の場合は重み付き、それ以外はTPS優先で選択する。
+/// セッションID（`X-LLMLB-Session-Id`）付きのリクエストはセッションに割り当て済みの
+/// エンドポイントを優先する。必須API（`selection.required_api`）の指定があれば
+/// そのAPIに対応するエンドポイントのみから選択する。
 pub(crate) async fn select_available_endpoint_with_queue_for_model(
     state: &AppState,
     _queue_config: QueueConfig,
     model_id: &str,
     api_kind: Option<TpsApiKind>,
+    selection: &SelectionContext,
 ) -> Result<QueueSelection, LbError> {
-    let endpoint = state
-        .load_manager
-        .select_endpoint_by_tps_ready_for_model(model_id, api_kind)
-        .await?;
+    let mode = crate::config::load_balancer_mode();
+    let session_id = selection.session_id.as_deref();
+    let endpoint = match (selection.required_api, session_id) {
+        (Some(api), _) => {
+            state
+                .load_manager
+                .select_endpoint_for_api(mode, model_id, api, selection)
+                .await?
+        }
+        (None, Some(session_id)) => {
+            state
+                .load_manager
+                .select_endpoint_sticky(mode, model_id, session_id, api_kind, selection)
+                .await?
+        }
+        (None, None) => {
+            state
+                .load_manager
+                .select_endpoint_by_mode(mode, model_id, api_kind, selection)
+                .await?
+        }
+    };
 
     tracing::debug!(
         model = %model_id,
         endpoint_id = %endpoint.id,
         endpoint_name = %endpoint.name,
         ?api_kind,
-        "Selected ready endpoint by TPS priority"
+        ?mode,
+        sticky = session_id.is_some(),
+        "Selected ready endpoint"
     );
 
     Ok(QueueSelection::Ready {
@@ -68,6 +97,60 @@ pub(crate) async fn select_available_endpoint_with_queue_for_model(
     })
 }
 
+/// 応答に付与するルーティング結果
+#[derive(Debug, Clone)]
+pub(crate) struct RoutingHeaders {
+    /// 処理したエンドポイント名
+    pub endpoint: String,
+    /// アップストリームへ送信したモデル名
+    pub model: String,
+    /// 別エンドポイントへのやり直し回数
+    pub retries: usize,
+}
+
+impl RoutingHeaders {
+    /// 別エンドポイントへのやり直しを行わない経路（音声・画像など）のルーティング結果
+    pub(crate) fn direct(endpoint: &crate::types::endpoint::Endpoint, model: &str) -> Self {
+        Self {
+            endpoint: endpoint.name.clone(),
+            model: model.to_string(),
+            retries: 0,
+        }
+    }
+
+    /// `LLMLB_EXPOSE_ROUTING_HEADERS` が有効な場合のみルーティング結果ヘッダを付与する
+    pub(crate) fn apply(&self, response: &mut Response) {
+        if crate::config::expose_routing_headers() {
+            self.insert_into(response);
+        }
+    }
+
+    /// ヘッダ値に使えない文字を含む項目は省略する
+    fn insert_into(&self, response: &mut Response) {
+        let headers = response.headers_mut();
+        let values = [
+            ("x-llmlb-endpoint", self.endpoint.clone()),
+            ("x-llmlb-model", self.model.clone()),
+            ("x-llmlb-retries", self.retries.to_string()),
+        ];
+        for (name, value) in values {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                headers.insert(HeaderName::from_static(name), value);
+            }
+        }
+    }
+}
+
+/// アップストリームの応答をルーティング結果ヘッダ付きで転送する
+pub(crate) fn forward_routed_response(
+    response: reqwest::Response,
+    routed: &RoutingHeaders,
+) -> Result<Response, LbError> {
+    let mut response = forward_streaming_response(response)?;
+    routed.apply(&mut response);
+    Ok(response)
+}
+
 pub(crate) fn forward_streaming_response(response: reqwest::Response) -> Result<Response, LbError> {
     let status = response.status();
     let headers = response.headers().clone();
@@ -115,10 +198,155 @@ fn process_sse_lines(
     }
 }
 
+type UpstreamByteStream =
+    Pin<Box<dyn Stream<Item = Result<axum::body::Bytes, reqwest::Error>> + Send>>;
+
+/// アップストリームのストリーミングレスポンス（ステータス・ヘッダ・本文）
+pub(crate) struct UpstreamStream {
+    status: reqwest::StatusCode,
+    headers: reqwest::header::HeaderMap,
+    body: UpstreamByteStream,
+}
+
+impl From<reqwest::Response> for UpstreamStream {
+    fn from(response: reqwest::Response) -> Self {
+        Self {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: Box::pin(response.bytes_stream()),
+        }
+    }
+}
+
+impl UpstreamStream {
+    /// 最初のチャンク受信を `Ttfb`、ストリーム終端を `Completion` としてタイムラインに記録する
+    pub(crate) fn with_timeline(self, timeline: RequestTimeline) -> Self {
+        let body = futures::stream::unfold(
+            (self.body, timeline, false),
+            |(mut body, mut timeline, mut first_chunk_seen)| async move {
+                match body.next().await {
+                    Some(item) => {
+                        if !first_chunk_seen {
+                            timeline.mark(TimelineStage::Ttfb);
+                            first_chunk_seen = true;
+                        }
+                        Some((item, (body, timeline, first_chunk_seen)))
+                    }
+                    None => {
+                        timeline.mark(TimelineStage::Completion);
+                        timeline.finish();
+                        None
+                    }
+                }
+            },
+        );
+        Self {
+            status: self.status,
+            headers: self.headers,
+            body: Box::pin(body),
+        }
+    }
+
+    /// 本文をOpenAI標準のSSE形式へ正規化する（[`SseNormalizer`] 参照）
+    ///
+    /// 本文の長さが変わるため `Content-Length` は外し、`Content-Type` は
+    /// `text/event-stream` にそろえる。非ストリーミングのJSON応答はそのまま返す。
+    pub(crate) fn normalize_sse(mut self) -> Self {
+        use reqwest::header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
+
+        let is_json = self
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        if is_json {
+            return self;
+        }
+        self.headers.remove(CONTENT_LENGTH);
+        self.headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+
+        let body = futures::stream::unfold(
+            (self.body, SseNormalizer::new(), false),
+            |(mut body, mut normalizer, finished)| async move {
+                if finished {
+                    return None;
+                }
+                match body.next().await {
+                    Some(Ok(chunk)) => {
+                        let normalized = normalizer.push(&chunk);
+                        Some((Ok(normalized), (body, normalizer, false)))
+                    }
+                    // 途中切断時は [DONE] を付けない
+                    Some(Err(err)) => Some((Err(err), (body, normalizer, true))),
+                    None => {
+                        let rest = normalizer.finish();
+                        Some((Ok(rest), (body, normalizer, true)))
+                    }
+                }
+            },
+        );
+        Self {
+            status: self.status,
+            headers: self.headers,
+            body: Box::pin(body),
+        }
+    }
+}
+
+/// 最初のチャンクを受信するまでストリームを読み進める。
+///
+/// first-token前に切断された場合はエラーメッセージを返し、呼び出し側が
+/// 別エンドポイントでやり直せるようにする。受信済みチャンクは先頭に戻して返す。
+pub(crate) async fn prime_upstream_stream(
+    response: reqwest::Response,
+) -> Result<UpstreamStream, String> {
+    let UpstreamStream {
+        status,
+        headers,
+        mut body,
+    } = UpstreamStream::from(response);
+
+    loop {
+        match body.next().await {
+            Some(Ok(chunk)) if chunk.is_empty() => continue,
+            Some(Ok(chunk)) => {
+                let rest = futures::stream::once(async move { Ok(chunk) }).chain(body);
+                return Ok(UpstreamStream {
+                    status,
+                    headers,
+                    body: Box::pin(rest),
+                });
+            }
+            Some(Err(err)) => {
+                return Err(format!(
+                    "Upstream stream failed before first token: {}",
+                    err
+                ));
+            }
+            None => return Err("Upstream stream closed before first token".to_string()),
+        }
+    }
+}
+
+/// 完了時に保存するストリーミングのリクエスト履歴
+pub(crate) type StreamHistory = (
+    Arc<crate::db::request_history::RequestHistoryStorage>,
+    RequestResponseRecord,
+);
+
 /// SSEストリームを透過しながら、完了時にTPS計測用のトークンを集計する。
+///
+/// 確定したトークン使用量から単価設定のあるエンドポイントのコストを計上し、
+/// `history` が指定されていればトークン数と課金額を反映した履歴を保存する。
+/// 途中で切断された場合の課金は `LLMLB_BILL_PARTIAL_STREAMS` に従う。
+/// `validate_json` が真の場合、完了時に集約した本文がJSONかを検証し、違反を記録する。
+/// `throttle` が指定されていれば、上限を超えないようチャンクの送出を遅延させる
+/// （待機中はアップストリームを読み進めない）。
+/// 最初のチャンクまでの時間はTTFTとしてエンドポイントの負荷状態に記録する。
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn forward_streaming_response_with_tps_tracking(
-    response: reqwest::Response,
+    response: impl Into<UpstreamStream>,
     endpoint_id: uuid::Uuid,
     model_id: String,
     api_kind: Option<TpsApiKind>,
@@ -127,9 +355,13 @@ pub(crate) fn forward_streaming_response_with_tps_tracking(
     endpoint_registry: crate::registry::endpoints::EndpointRegistry,
     load_manager: crate::balancer::LoadManager,
     event_bus: crate::events::SharedEventBus,
+    history: Option<StreamHistory>,
+    validate_json: bool,
+    throttle: Option<crate::balancer::StreamThrottle>,
+    request_timeout: Option<std::time::Duration>,
 ) -> Result<Response, LbError> {
     struct TpsTrackingState {
-        upstream: Pin<Box<dyn Stream<Item = Result<axum::body::Bytes, reqwest::Error>> + Send>>,
+        upstream: UpstreamByteStream,
         accumulator: StreamingTokenAccumulator,
         sse_buffer: String,
         endpoint_id: uuid::Uuid,
@@ -140,11 +372,18 @@ pub(crate) fn forward_streaming_response_with_tps_tracking(
         endpoint_registry: crate::registry::endpoints::EndpointRegistry,
         load_manager: crate::balancer::LoadManager,
         event_bus: crate::events::SharedEventBus,
+        history: Option<StreamHistory>,
+        validate_json: bool,
+        throttle: Option<crate::balancer::stream_rate::StreamThrottle>,
         stats_recorded: bool,
+        usage_settled: bool,
+        ttft_recorded: bool,
+        request_timeout: Option<std::time::Duration>,
+        terminated: bool,
     }
 
     impl TpsTrackingState {
-        fn finalize_output_tokens_and_duration(&mut self) -> (u64, u64) {
+        fn finalize_usage_and_duration(&mut self) -> (TokenUsage, u64) {
             if !self.sse_buffer.is_empty() {
                 let pending = std::mem::take(&mut self.sse_buffer);
                 self.accumulator
@@ -159,7 +398,7 @@ pub(crate) fn forward_streaming_response_with_tps_tracking(
                 0
             };
 
-            (output_tokens, duration_ms)
+            (usage, duration_ms)
         }
 
         fn record_stats_once(&mut self, success: bool, output_tokens: u64, duration_ms: u64) {
@@ -181,18 +420,63 @@ pub(crate) fn forward_streaming_response_with_tps_tracking(
                 self.event_bus.clone(),
             );
         }
+
+        /// 確定したトークン使用量を課金し、履歴を保存する（一度だけ）
+        fn settle_usage_once(&mut self, usage: TokenUsage, completed: bool) {
+            if self.usage_settled {
+                return;
+            }
+            self.usage_settled = true;
+            crate::token::record_usage_source(self.endpoint_type, self.accumulator.usage_source());
+
+            record_model_token_usage(
+                &self.model_id,
+                usage.input_tokens,
+                usage.output_tokens,
+                usage.total_tokens,
+            );
+
+            let billable = completed || crate::config::bill_partial_streams();
+            let history = self.history.take();
+            if !billable && history.is_none() {
+                return;
+            }
+
+            let load_manager = self.load_manager.clone();
+            let endpoint_id = self.endpoint_id;
+            tokio::spawn(async move {
+                let cost_usd = if billable {
+                    load_manager.charge_usage(endpoint_id, &usage).await
+                } else {
+                    None
+                };
+
+                if let Some((storage, mut record)) = history {
+                    record.input_tokens = usage.input_tokens;
+                    record.output_tokens = usage.output_tokens;
+                    record.total_tokens = usage.total_tokens;
+                    record.cost_usd = cost_usd;
+                    if let Err(e) = storage.save_record(&record).await {
+                        tracing::error!("Failed to save request record: {}", e);
+                    }
+                }
+            });
+        }
     }
 
     impl Drop for TpsTrackingState {
         fn drop(&mut self) {
-            if self.stats_recorded {
+            if self.stats_recorded && self.usage_settled {
                 return;
             }
 
-            let (output_tokens, duration_ms) = self.finalize_output_tokens_and_duration();
+            let (usage, duration_ms) = self.finalize_usage_and_duration();
+            let output_tokens = usage.output_tokens.unwrap_or(0) as u64;
+            let completed = self.accumulator.is_done();
 
             if tokio::runtime::Handle::try_current().is_ok() {
                 self.record_stats_once(true, output_tokens, duration_ms);
+                self.settle_usage_once(usage, completed);
             } else {
                 tracing::warn!(
                     endpoint_id = %self.endpoint_id,
@@ -203,11 +487,14 @@ pub(crate) fn forward_streaming_response_with_tps_tracking(
         }
     }
 
-    let status = response.status();
-    let headers = response.headers().clone();
+    let UpstreamStream {
+        status,
+        headers,
+        body: upstream,
+    } = response.into();
 
     let state = TpsTrackingState {
-        upstream: Box::pin(response.bytes_stream()),
+        upstream,
         accumulator: StreamingTokenAccumulator::new(&model_id),
         sse_buffer: String::new(),
         endpoint_id,
@@ -218,23 +505,82 @@ pub(crate) fn forward_streaming_response_with_tps_tracking(
         endpoint_registry,
         load_manager,
         event_bus,
+        history,
+        validate_json,
+        throttle,
         stats_recorded: false,
+        usage_settled: false,
+        ttft_recorded: false,
+        request_timeout,
+        terminated: false,
     };
 
     let tracked_stream = futures::stream::try_unfold(state, |mut state| async move {
+        if state.terminated {
+            return Ok(None);
+        }
         match state.upstream.next().await {
             Some(Ok(chunk)) => {
+                if !state.ttft_recorded && !chunk.is_empty() {
+                    state.ttft_recorded = true;
+                    let ttft = state.request_started_at.elapsed();
+                    let load_manager = state.load_manager.clone();
+                    let endpoint_id = state.endpoint_id;
+                    tokio::spawn(async move {
+                        load_manager.record_ttft(endpoint_id, ttft).await;
+                    });
+                }
                 let chunk_text = String::from_utf8_lossy(chunk.as_ref());
                 process_sse_lines(&mut state.sse_buffer, &chunk_text, &mut state.accumulator);
+                if let Some(throttle) = state.throttle.as_mut() {
+                    let delay = throttle.delay_for(
+                        Instant::now(),
+                        chunk.len(),
+                        crate::balancer::stream_rate::count_stream_tokens(&chunk),
+                    );
+                    if !delay.is_zero() {
+                        tokio::time::sleep(delay).await;
+                    }
+                }
                 Ok(Some((chunk, state)))
             }
             Some(Err(err)) => {
+                let (usage, _) = state.finalize_usage_and_duration();
+                // 途中切断: usage が届いていなければデルタごとに数えた部分的な出力トークン数を記録する
+                tracing::debug!(
+                    endpoint_id = %state.endpoint_id,
+                    model_id = %state.model_id,
+                    output_tokens = ?usage.output_tokens,
+                    counted_output_tokens = state.accumulator.counted_output_tokens(),
+                    "Upstream stream ended with an error; recording partial usage"
+                );
                 state.record_stats_once(false, 0, 0);
+                state.settle_usage_once(usage, false);
+                // X-LLMLB-Timeout-Ms 超過: エラーイベントを送ってストリームを正常に閉じる
+                if let Some(timeout) = state.request_timeout.filter(|_| err.is_timeout()) {
+                    state.terminated = true;
+                    let event = crate::api::request_timeout::upstream_timeout_sse_event(timeout);
+                    return Ok(Some((event, state)));
+                }
                 Err(io::Error::other(err))
             }
             None => {
-                let (output_tokens, duration_ms) = state.finalize_output_tokens_and_duration();
+                let (usage, duration_ms) = state.finalize_usage_and_duration();
+                let output_tokens = usage.output_tokens.unwrap_or(0) as u64;
+                // JSONモード: 送信済みのため再試行はできず、最終集約後の本文で違反のみ記録する
+                if state.validate_json
+                    && !crate::api::json_mode::is_json_content(
+                        state.accumulator.accumulated_content(),
+                    )
+                {
+                    crate::api::json_mode::record_violation(
+                        state.endpoint_id,
+                        &state.model_id,
+                        true,
+                    );
+                }
                 state.record_stats_once(true, output_tokens, duration_ms);
+                state.settle_usage_once(usage, true);
                 Ok(None)
             }
         }
@@ -269,11 +615,32 @@ pub(crate) fn forward_streaming_response_with_tps_tracking(
     Ok(axum_response)
 }
 
+/// モデル別レートリミット（TPM）向けにトークン消費量を記録する
+fn record_model_token_usage(
+    model: &str,
+    input_tokens: Option<u32>,
+    output_tokens: Option<u32>,
+    total_tokens: Option<u32>,
+) {
+    let total = total_tokens.map(u64::from).unwrap_or_else(|| {
+        u64::from(input_tokens.unwrap_or(0)) + u64::from(output_tokens.unwrap_or(0))
+    });
+    crate::balancer::model_rate_limiter().record_tokens(model, total);
+}
+
 /// リクエスト/レスポンスレコードを保存（Fire-and-forget）
+///
+/// 記録されたトークン使用量はモデル別レートリミットの消費量にも計上する。
 pub(crate) fn save_request_record(
     storage: Arc<crate::db::request_history::RequestHistoryStorage>,
     record: RequestResponseRecord,
 ) {
+    record_model_token_usage(
+        &record.model,
+        record.input_tokens,
+        record.output_tokens,
+        record.total_tokens,
+    );
     tokio::spawn(async move {
         if let Err(e) = storage.save_record(&record).await {
             tracing::error!("Failed to save request record: {}", e);
@@ -286,6 +653,8 @@ pub(crate) fn save_request_record(
 /// endpointsテーブルの累計カウンタとendpoint_daily_statsの日次集計を
 /// 非同期で更新する。リクエスト処理のレイテンシに影響を与えない。
 /// SPEC-4bb5b55f: TPS計測対象の場合はインメモリEMAも更新する。
+/// 成功応答の出力トークン数は応答サイズの異常検知にも使う。
+/// `GET /metrics` のエンドポイント別リクエスト数・レイテンシも記録する。
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn record_endpoint_request_stats(
     endpoint_registry: crate::registry::endpoints::EndpointRegistry,
@@ -299,6 +668,7 @@ pub(crate) fn record_endpoint_request_stats(
     load_manager: crate::balancer::LoadManager,
     event_bus: crate::events::SharedEventBus,
 ) {
+    crate::metrics::exporter::record_endpoint_request(endpoint_id, success, duration_ms);
     tokio::spawn(async move {
         let date = chrono::Local::now().format("%Y-%m-%d").to_string();
         let pool = endpoint_registry.pool().clone();
@@ -342,6 +712,12 @@ pub(crate) fn record_endpoint_request_stats(
             tracing::error!("Failed to upsert daily stats: {}", e);
         }
 
+        if success && output_tokens > 0 {
+            load_manager
+                .record_response_tokens(endpoint_id, &model_id, output_tokens)
+                .await;
+        }
+
         // SPEC-4bb5b55f: インメモリTPS EMAを更新 & イベント発行
         if should_update_tps {
             let api_kind = api_kind.expect("checked above");
@@ -366,15 +742,75 @@ pub(crate) fn record_endpoint_request_stats(
         }
     });
 }
+/// 同一ノード再試行の最大回数
+const SAME_NODE_MAX_RETRIES: u32 = 1;
+
+/// 同一ノード再試行のバックオフ基準値（試行ごとに倍増）
+const SAME_NODE_RETRY_BASE_BACKOFF: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// アップストリームへ送信し、接続エラー時のみ同一ノードへ再試行する
+///
+/// `LLMLB_SAME_NODE_RETRY=1` で有効。別ノードへのリトライより前段で動作し、
+/// 再試行しても接続できなければ元のエラーを返す（以降は呼び出し元の別ノード処理に委ねる）。
+/// 受信リクエストの優先度は `X-LLMLB-Priority` ヘッダとしてアップストリームへ引き継ぐ。
+pub(crate) async fn send_with_same_node_retry(
+    request_builder: reqwest::RequestBuilder,
+    endpoint_name: &str,
+    priority: RequestPriority,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let request_builder =
+        crate::balancer::priority::apply_priority_header(request_builder, priority);
+    let max_retries = if crate::config::same_node_retry_enabled() {
+        SAME_NODE_MAX_RETRIES
+    } else {
+        0
+    };
+    send_with_retries(request_builder, endpoint_name, max_retries).await
+}
+
+async fn send_with_retries(
+    request_builder: reqwest::RequestBuilder,
+    endpoint_name: &str,
+    max_retries: u32,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let mut attempt = 0;
+    let mut current = request_builder;
+    loop {
+        // ストリーミングボディ等で複製できない場合は再試行しない
+        let retry_builder = (attempt < max_retries)
+            .then(|| current.try_clone())
+            .flatten();
+        match current.send().await {
+            Err(err) if err.is_connect() && retry_builder.is_some() => {
+                let backoff = SAME_NODE_RETRY_BASE_BACKOFF * 2u32.pow(attempt);
+                tracing::warn!(
+                    endpoint = %endpoint_name,
+                    attempt = attempt + 1,
+                    backoff_ms = backoff.as_millis() as u64,
+                    "Upstream connection failed, retrying same endpoint: {}",
+                    err
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+                current = retry_builder.expect("checked above");
+            }
+            result => return result,
+        }
+    }
+}
+
 /// エンドポイントにリクエストを転送
 ///
-/// OpenAI互換APIエンドポイントにリクエストを転送し、レスポンスを返す
+/// OpenAI互換APIエンドポイントにリクエストを転送し、レスポンスを返す。
+/// 応答ステータスはアダプティブレートリミッタへ反映する。
 pub(crate) async fn forward_to_endpoint(
     client: &reqwest::Client,
+    load_manager: &crate::balancer::LoadManager,
     endpoint: &Endpoint,
     path: &str,
     body: Vec<u8>,
     stream: bool,
+    priority: RequestPriority,
 ) -> Result<reqwest::Response, LbError> {
     let url = format!("{}{}", endpoint.base_url.trim_end_matches('/'), path);
 
@@ -391,17 +827,22 @@ pub(crate) async fn forward_to_endpoint(
         request_builder = request_builder.bearer_auth(api_key);
     }
 
-    let response = request_builder.send().await.map_err(|e| {
-        tracing::error!(
-            "Failed to forward request to endpoint {}: {}",
-            endpoint.name,
-            e
-        );
-        LbError::Http(format!("Endpoint request failed: {}", e))
-    })?;
+    let response = send_with_same_node_retry(request_builder, &endpoint.name, priority)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to forward request to endpoint {}: {}",
+                endpoint.name,
+                e
+            );
+            LbError::Http(format!("Endpoint request failed: {}", e))
+        })?;
 
     // エラーステータスをチェック
     let status = response.status();
+    load_manager
+        .record_upstream_status(endpoint.id, status.as_u16())
+        .await;
     if !status.is_success() && !stream {
         // 非ストリーミングの場合はエラー内容を取得してログ
         let error_body = match response.text().await {
@@ -436,6 +877,64 @@ pub(crate) async fn forward_to_endpoint(
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    async fn closed_port_url() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        format!("http://{}/v1/chat/completions", addr)
+    }
+
+    #[tokio::test]
+    async fn same_node_retry_backs_off_once_on_connect_error() {
+        let url = closed_port_url().await;
+        let client = reqwest::Client::new();
+
+        let started = Instant::now();
+        let err = send_with_retries(client.post(&url).body("{}"), "node", 1)
+            .await
+            .unwrap_err();
+        assert!(err.is_connect());
+        assert!(started.elapsed() >= SAME_NODE_RETRY_BASE_BACKOFF);
+
+        // 無効時は即座に失敗する
+        let started = Instant::now();
+        let err = send_with_retries(client.post(&url).body("{}"), "node", 0)
+            .await
+            .unwrap_err();
+        assert!(err.is_connect());
+        assert!(started.elapsed() < SAME_NODE_RETRY_BASE_BACKOFF);
+    }
+
+    #[test]
+    fn routing_headers_insert_endpoint_model_and_retries() {
+        let mut response = Response::new(Body::empty());
+        RoutingHeaders {
+            endpoint: "gpu-node-1".to_string(),
+            model: "llama3:8b".to_string(),
+            retries: 1,
+        }
+        .insert_into(&mut response);
+
+        let headers = response.headers();
+        assert_eq!(headers.get("x-llmlb-endpoint").unwrap(), "gpu-node-1");
+        assert_eq!(headers.get("x-llmlb-model").unwrap(), "llama3:8b");
+        assert_eq!(headers.get("x-llmlb-retries").unwrap(), "1");
+    }
+
+    #[test]
+    fn routing_headers_skip_invalid_header_values() {
+        let mut response = Response::new(Body::empty());
+        RoutingHeaders {
+            endpoint: "bad\nname".to_string(),
+            model: "m".to_string(),
+            retries: 0,
+        }
+        .insert_into(&mut response);
+
+        assert!(response.headers().get("x-llmlb-endpoint").is_none());
+        assert_eq!(response.headers().get("x-llmlb-model").unwrap(), "m");
+    }
     use crate::token::StreamingTokenAccumulator;
 
     // --- QueueSelection enum ---
@@ -707,6 +1206,36 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn normalize_sse_rewrites_body_and_drops_content_length() {
+        let body = "data:{\"a\":1}\r\n\r\n";
+        let response = axum::http::Response::builder()
+            .status(200)
+            .header("content-type", "application/x-ndjson")
+            .header("content-length", body.len())
+            .body(body)
+            .unwrap();
+        let upstream = UpstreamStream::from(reqwest::Response::from(response)).normalize_sse();
+        assert!(upstream.headers.get("content-length").is_none());
+        assert_eq!(upstream.headers["content-type"], "text/event-stream");
+
+        let chunks: Vec<_> = upstream.body.try_collect().await.unwrap();
+        assert_eq!(
+            chunks.concat(),
+            b"data: {\"a\":1}\n\ndata: [DONE]\n\n".to_vec()
+        );
+
+        // 非ストリーミングのJSON応答は変更しない
+        let response = axum::http::Response::builder()
+            .status(200)
+            .header("content-type", "application/json")
+            .body("{\"a\":1}")
+            .unwrap();
+        let upstream = UpstreamStream::from(reqwest::Response::from(response)).normalize_sse();
+        let chunks: Vec<_> = upstream.body.try_collect().await.unwrap();
+        assert_eq!(chunks.concat(), b"{\"a\":1}".to_vec());
+    }
+
     #[tokio::test]
     async fn forward_streaming_response_preserves_sse_content_type() {
         let response = axum::http::Response::builder()
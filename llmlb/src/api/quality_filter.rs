@@ -0,0 +1,285 @@
+//! 応答の簡易品質フィルタ
+//!
+//! 非ストリーミングの Chat Completions / Completions 応答を、設定ファイルのルール
+//! （最小出力トークン数・禁止パターン）で評価する。違反した場合は別エンドポイント
+//! （`fallback_model` 指定時はそのモデル）で1回だけ再試行し、違反した応答は再試行先と
+//! あわせてリクエスト履歴にエラーとして記録する。再試行先が無い場合や再試行後の応答は
+//! そのまま返す。
+//!
+//! `LLMLB_QUALITY_FILTER=1` で有効化し、ルールは `LLMLB_QUALITY_FILTER_FILE`
+//! （未設定時は `~/.llmlb/quality_filter.yaml`）から読み込む。YAML/JSONのどちらでもよい。
+//!
+//! ```yaml
+//! min_output_tokens: 3          # 出力がこれ未満なら違反（空応答を含む）
+//! deny_patterns:                # 出力に一致したら違反
+//!   - "(?i)^\\s*error:"
+//! models: []                    # 対象モデル（空なら全モデル）
+//! fallback_model: null          # 再試行に使うモデル（省略時は同じモデルの別エンドポイント）
+//! ```
+//!
+//! 違反はエンドポイント単位で `llmlb_quality_filter_violations_total` に記録する。
+
+use once_cell::sync::Lazy;
+use prometheus::{IntCounterVec, Opts};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// ルールファイルのパスを指定する環境変数
+const QUALITY_FILTER_FILE_ENV: &str = "LLMLB_QUALITY_FILTER_FILE";
+
+/// ルールファイルのデフォルト名（`~/.llmlb` 配下）
+const DEFAULT_QUALITY_FILTER_FILE: &str = "quality_filter.yaml";
+
+static QUALITY_FILTER: Lazy<Option<QualityFilter>> = Lazy::new(|| {
+    if !crate::config::quality_filter_enabled() {
+        return None;
+    }
+    let path = quality_filter_path()?;
+    match QualityFilter::load(&path) {
+        Ok(filter) => {
+            tracing::info!(
+                path = %path.display(),
+                min_output_tokens = filter.min_output_tokens,
+                deny_patterns = filter.deny_patterns.len(),
+                "Response quality filter enabled"
+            );
+            Some(filter)
+        }
+        Err(err) => {
+            tracing::error!(
+                path = %path.display(),
+                error = %err,
+                "Failed to load quality filter rules; quality filter disabled"
+            );
+            None
+        }
+    }
+});
+
+static VIOLATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "llmlb_quality_filter_violations_total",
+            "Responses that failed the quality filter rules, per endpoint",
+        ),
+        &["endpoint_id", "retried"],
+    )
+    .expect("counter vec");
+    crate::metrics::registry()
+        .register(Box::new(counter.clone()))
+        .ok();
+    counter
+});
+
+/// ルールファイルの内容
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct QualityFilterRules {
+    /// 出力の最小トークン数（`0` で判定しない）
+    pub min_output_tokens: u32,
+    /// 出力に一致したら違反とする正規表現
+    pub deny_patterns: Vec<String>,
+    /// 対象モデル（空なら全モデル）
+    pub models: Vec<String>,
+    /// 再試行に使うモデル（省略時は同じモデルの別エンドポイント）
+    pub fallback_model: Option<String>,
+}
+
+/// コンパイル済みの品質フィルタ
+#[derive(Debug)]
+pub struct QualityFilter {
+    min_output_tokens: u32,
+    deny_patterns: Vec<Regex>,
+    models: Vec<String>,
+    fallback_model: Option<String>,
+}
+
+impl QualityFilter {
+    /// ルールをコンパイルする（不正な正規表現はエラー）
+    pub fn new(rules: QualityFilterRules) -> Result<Self, regex::Error> {
+        let deny_patterns = rules
+            .deny_patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            min_output_tokens: rules.min_output_tokens,
+            deny_patterns,
+            models: rules.models,
+            fallback_model: rules
+                .fallback_model
+                .map(|model| model.trim().to_string())
+                .filter(|model| !model.is_empty()),
+        })
+    }
+
+    /// ルールファイルを読み込む
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let rules: QualityFilterRules =
+            serde_yaml::from_str(&content).map_err(|e| e.to_string())?;
+        Self::new(rules).map_err(|e| e.to_string())
+    }
+
+    /// 再試行に使うモデル（未設定なら同じモデル）
+    pub fn fallback_model(&self) -> Option<&str> {
+        self.fallback_model.as_deref()
+    }
+
+    /// 応答本文を評価し、違反したルールを返す
+    ///
+    /// ツール呼び出しのみの応答（出力テキストが空で `tool_calls` を含む）は評価しない。
+    pub fn check(&self, model: &str, body: &Value) -> Option<String> {
+        if !self.models.is_empty() && !self.models.iter().any(|m| m == model) {
+            return None;
+        }
+        let text = crate::token::extract_response_text(body);
+        if text.trim().is_empty() && has_tool_calls(body) {
+            return None;
+        }
+        if self.min_output_tokens > 0 {
+            let tokens = crate::token::estimate_tokens_exact(model, text.trim()).tokens;
+            if tokens < self.min_output_tokens as usize {
+                return Some(format!("min_output_tokens:{}", self.min_output_tokens));
+            }
+        }
+        self.deny_patterns
+            .iter()
+            .find(|pattern| pattern.is_match(&text))
+            .map(|pattern| format!("deny_pattern:{}", pattern.as_str()))
+    }
+}
+
+/// 有効な品質フィルタ（無効時は `None`）
+pub fn current() -> Option<&'static QualityFilter> {
+    QUALITY_FILTER.as_ref()
+}
+
+/// 品質ルール違反時の履歴メッセージ
+pub fn violation_message(rule: &str) -> String {
+    format!("Response failed quality rule '{}'", rule)
+}
+
+/// 品質ルール違反を記録する
+pub fn record_violation(endpoint_id: Uuid, model: &str, rule: &str, retried: bool) {
+    VIOLATIONS
+        .with_label_values(&[
+            endpoint_id.to_string().as_str(),
+            if retried { "true" } else { "false" },
+        ])
+        .inc();
+    tracing::warn!(
+        endpoint_id = %endpoint_id,
+        model = %model,
+        rule = %rule,
+        retried,
+        "Endpoint response failed the quality filter"
+    );
+}
+
+fn has_tool_calls(body: &Value) -> bool {
+    body.get("choices")
+        .and_then(Value::as_array)
+        .is_some_and(|choices| {
+            choices.iter().any(|choice| {
+                choice
+                    .get("message")
+                    .and_then(|message| message.get("tool_calls"))
+                    .is_some_and(|calls| !calls.is_null())
+            })
+        })
+}
+
+fn quality_filter_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(QUALITY_FILTER_FILE_ENV) {
+        if !path.trim().is_empty() {
+            return Some(PathBuf::from(path));
+        }
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok();
+    match home {
+        Some(home) => Some(
+            PathBuf::from(home)
+                .join(".llmlb")
+                .join(DEFAULT_QUALITY_FILTER_FILE),
+        ),
+        None => {
+            tracing::error!(
+                "Quality filter is enabled but {} is not set and the home directory is unknown",
+                QUALITY_FILTER_FILE_ENV
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filter(rules: &str) -> QualityFilter {
+        QualityFilter::new(serde_yaml::from_str(rules).unwrap()).unwrap()
+    }
+
+    fn chat(content: &str) -> Value {
+        json!({"choices": [{"message": {"role": "assistant", "content": content}}]})
+    }
+
+    #[test]
+    fn detects_short_and_denied_responses() {
+        let filter = filter(
+            r#"
+min_output_tokens: 3
+deny_patterns: ["(?i)^\\s*error:"]
+"#,
+        );
+
+        assert_eq!(
+            filter.check("m", &chat("  ")),
+            Some("min_output_tokens:3".to_string())
+        );
+        assert!(filter.check("m", &chat("ok")).is_some());
+        assert_eq!(
+            filter.check("m", &chat("Error: model crashed while generating")),
+            Some("deny_pattern:(?i)^\\s*error:".to_string())
+        );
+        assert!(filter
+            .check("m", &chat("Here is a complete and useful answer."))
+            .is_none());
+
+        // Completions 形式（choices[].text）も評価する
+        let completion = json!({"choices": [{"text": ""}]});
+        assert!(filter.check("m", &completion).is_some());
+
+        // ツール呼び出しのみの応答は対象外
+        let tool_only =
+            json!({"choices": [{"message": {"content": null, "tool_calls": [{"id": "call_1"}]}}]});
+        assert!(filter.check("m", &tool_only).is_none());
+    }
+
+    #[test]
+    fn models_limit_scope_and_fallback_is_trimmed() {
+        let scoped = filter("min_output_tokens: 1\nmodels: [target]\nfallback_model: ' larger '\n");
+        assert!(scoped.check("target", &chat("")).is_some());
+        assert!(scoped.check("other", &chat("")).is_none());
+        assert_eq!(scoped.fallback_model(), Some("larger"));
+
+        let same_model = filter("fallback_model: ''\n");
+        assert_eq!(same_model.fallback_model(), None);
+    }
+
+    #[test]
+    fn invalid_pattern_is_rejected() {
+        let rules = QualityFilterRules {
+            deny_patterns: vec!["(unclosed".to_string()],
+            ..Default::default()
+        };
+        assert!(QualityFilter::new(rules).is_err());
+    }
+}
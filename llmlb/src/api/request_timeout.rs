@@ -0,0 +1,93 @@
+//! リクエスト単位のアップストリームタイムアウト
+//!
+//! `X-LLMLB-Timeout-Ms` ヘッダでリクエストごとにアップストリーム呼び出しの上限時間を指定する。
+//! ハンドラでヘッダを検証してプロキシ処理へ渡し、エンドポイントの
+//! `inference_timeout_secs` の代わりに適用させる。
+
+use axum::{
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use serde_json::json;
+use std::time::Duration;
+
+use super::openai_util::openai_error_response_with_type;
+
+/// タイムアウトを指定するリクエストヘッダ
+pub const TIMEOUT_HEADER: &str = "x-llmlb-timeout-ms";
+
+/// タイムアウト超過時のエラー種別
+pub const UPSTREAM_TIMEOUT_ERROR_TYPE: &str = "upstream_timeout";
+
+/// `X-LLMLB-Timeout-Ms` ヘッダを解釈する
+///
+/// 未指定は `Ok(None)`。非数値・負数・0 はエラーメッセージを返す。
+pub fn parse_timeout_header(headers: &HeaderMap) -> Result<Option<Duration>, String> {
+    let Some(value) = headers.get(TIMEOUT_HEADER) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|ms| *ms > 0)
+        .map(|ms| Some(Duration::from_millis(ms)))
+        .ok_or_else(|| "X-LLMLB-Timeout-Ms must be a positive integer (milliseconds)".to_string())
+}
+
+/// タイムアウト超過時のメッセージ
+pub fn upstream_timeout_message(timeout: Duration) -> String {
+    format!(
+        "Upstream request exceeded X-LLMLB-Timeout-Ms ({} ms)",
+        timeout.as_millis()
+    )
+}
+
+/// タイムアウト超過時の 504 応答
+pub fn upstream_timeout_response(timeout: Duration) -> Response {
+    openai_error_response_with_type(
+        upstream_timeout_message(timeout),
+        UPSTREAM_TIMEOUT_ERROR_TYPE,
+        StatusCode::GATEWAY_TIMEOUT,
+    )
+}
+
+/// ストリーミング中にタイムアウトした場合に送出する最終SSEイベント
+pub fn upstream_timeout_sse_event(timeout: Duration) -> Bytes {
+    let payload = json!({
+        "error": {
+            "message": upstream_timeout_message(timeout),
+            "type": UPSTREAM_TIMEOUT_ERROR_TYPE,
+            "code": StatusCode::GATEWAY_TIMEOUT.as_u16(),
+        }
+    });
+    Bytes::from(format!("data: {}\n\n", payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers_with(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(TIMEOUT_HEADER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn parse_timeout_header_accepts_positive_milliseconds() {
+        assert_eq!(parse_timeout_header(&HeaderMap::new()), Ok(None));
+        assert_eq!(
+            parse_timeout_header(&headers_with("1500")),
+            Ok(Some(Duration::from_millis(1500)))
+        );
+        for invalid in ["abc", "-5", "0", "1.5", ""] {
+            assert!(
+                parse_timeout_header(&headers_with(invalid)).is_err(),
+                "{invalid:?} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! エンドポイント容量予約管理API
+//!
+//! APIキー/テナント単位の容量予約のCRUD操作。
+//! 変更はDBへ保存した後、LoadManagerへ即時反映する。
+
+use crate::balancer::{CapacityReservation, PrincipalType};
+use crate::common::auth::{Claims, UserRole};
+use crate::common::error::{CommonError, LbError};
+use crate::db::reservations as db;
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::error::AppError;
+
+/// 容量予約作成リクエスト
+#[derive(Debug, Deserialize)]
+pub struct CreateReservationRequest {
+    /// 対象エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 予約主体の種別（`api_key` / `tenant`）
+    pub principal_type: PrincipalType,
+    /// 予約主体ID（APIキーID、またはテナントのユーザーID）
+    pub principal_id: Uuid,
+    /// 予約する同時スロット数
+    pub slots: u32,
+    /// soft予約（デフォルト: false）
+    #[serde(default)]
+    pub soft: bool,
+}
+
+/// 容量予約更新リクエスト
+#[derive(Debug, Deserialize)]
+pub struct UpdateReservationRequest {
+    /// 予約する同時スロット数
+    pub slots: Option<u32>,
+    /// soft予約
+    pub soft: Option<bool>,
+}
+
+/// 容量予約（使用状況付き）
+#[derive(Debug, Serialize)]
+pub struct ReservationView {
+    /// 予約内容
+    #[serde(flatten)]
+    pub reservation: CapacityReservation,
+    /// 現在使用中の予約スロット数
+    pub in_use: u32,
+    /// 対象エンドポイントの同時スロット数
+    pub endpoint_slots: u32,
+}
+
+/// 容量予約一覧レスポンス
+#[derive(Debug, Serialize)]
+pub struct ListReservationsResponse {
+    /// 同時スロット数の既定値（`slots` 未設定のエンドポイントに適用）
+    pub default_endpoint_slots: u32,
+    /// 予約一覧
+    pub reservations: Vec<ReservationView>,
+}
+
+fn ensure_admin(claims: &Claims) -> Result<(), AppError> {
+    if claims.role != UserRole::Admin {
+        return Err(AppError(LbError::Authorization(
+            "Admin permission required".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+fn not_found(id: Uuid) -> AppError {
+    AppError(LbError::NotFound(format!("Reservation {} not found", id)))
+}
+
+/// 予約スロット数を検証する（同一エンドポイントの予約合計がスロット数を超えないこと）
+async fn validate_slots(
+    state: &AppState,
+    reservation: &CapacityReservation,
+) -> Result<(), AppError> {
+    if reservation.slots == 0 {
+        return Err(AppError(
+            CommonError::Validation("slots must be greater than 0".to_string()).into(),
+        ));
+    }
+
+    let endpoint = state
+        .endpoint_registry
+        .get(reservation.endpoint_id)
+        .await
+        .ok_or(LbError::EndpointNotFound(reservation.endpoint_id))?;
+    let capacity = state.load_manager.endpoint_slots(&endpoint);
+    let reserved: u32 = db::list(&state.db_pool)
+        .await?
+        .iter()
+        .filter(|r| r.endpoint_id == reservation.endpoint_id && r.id != reservation.id)
+        .map(|r| r.slots)
+        .sum();
+    if reserved.saturating_add(reservation.slots) > capacity {
+        return Err(AppError(
+            CommonError::Validation(format!(
+                "Total reserved slots for endpoint {} would exceed its capacity ({} slots, {} already reserved)",
+                reservation.endpoint_id, capacity, reserved
+            ))
+            .into(),
+        ));
+    }
+    Ok(())
+}
+
+/// DBの内容をLoadManagerへ再読込する
+async fn reload_reservations(state: &AppState) -> Result<(), AppError> {
+    let reservations = db::list(&state.db_pool).await?;
+    state.load_manager.set_reservations(reservations).await;
+    Ok(())
+}
+
+async fn to_view(state: &AppState, reservation: CapacityReservation) -> ReservationView {
+    let in_use = state.load_manager.reservation_usage(reservation.id);
+    let endpoint_slots = match state.endpoint_registry.get(reservation.endpoint_id).await {
+        Some(endpoint) => state.load_manager.endpoint_slots(&endpoint),
+        None => state.load_manager.default_endpoint_slots(),
+    };
+    ReservationView {
+        reservation,
+        in_use,
+        endpoint_slots,
+    }
+}
+
+/// GET /api/reservations - 容量予約一覧
+pub async fn list_reservations(
+    State(state): State<AppState>,
+) -> Result<Json<ListReservationsResponse>, AppError> {
+    let mut reservations = Vec::new();
+    for reservation in db::list(&state.db_pool).await? {
+        reservations.push(to_view(&state, reservation).await);
+    }
+    Ok(Json(ListReservationsResponse {
+        default_endpoint_slots: state.load_manager.default_endpoint_slots(),
+        reservations,
+    }))
+}
+
+/// GET /api/reservations/:id - 容量予約詳細
+pub async fn get_reservation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ReservationView>, AppError> {
+    let reservation = db::get(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    Ok(Json(to_view(&state, reservation).await))
+}
+
+/// POST /api/reservations - 容量予約作成
+pub async fn create_reservation(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Json(req): Json<CreateReservationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    ensure_admin(&claims)?;
+
+    if state.endpoint_registry.get(req.endpoint_id).await.is_none() {
+        return Err(AppError(LbError::EndpointNotFound(req.endpoint_id)));
+    }
+
+    let now = Utc::now();
+    let reservation = CapacityReservation {
+        id: Uuid::new_v4(),
+        endpoint_id: req.endpoint_id,
+        principal_type: req.principal_type,
+        principal_id: req.principal_id,
+        slots: req.slots,
+        soft: req.soft,
+        created_at: now,
+        updated_at: now,
+    };
+    validate_slots(&state, &reservation).await?;
+
+    db::create(&state.db_pool, &reservation).await?;
+    reload_reservations(&state).await?;
+
+    Ok((StatusCode::CREATED, Json(reservation)))
+}
+
+/// PUT /api/reservations/:id - 容量予約更新
+pub async fn update_reservation(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateReservationRequest>,
+) -> Result<Json<CapacityReservation>, AppError> {
+    ensure_admin(&claims)?;
+
+    let mut reservation = db::get(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| not_found(id))?;
+
+    if let Some(slots) = req.slots {
+        reservation.slots = slots;
+    }
+    if let Some(soft) = req.soft {
+        reservation.soft = soft;
+    }
+    reservation.updated_at = Utc::now();
+    validate_slots(&state, &reservation).await?;
+
+    db::update(&state.db_pool, &reservation).await?;
+    reload_reservations(&state).await?;
+
+    Ok(Json(reservation))
+}
+
+/// DELETE /api/reservations/:id - 容量予約削除
+pub async fn delete_reservation(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    if !db::delete(&state.db_pool, id).await? {
+        return Err(not_found(id));
+    }
+    reload_reservations(&state).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
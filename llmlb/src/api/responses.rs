@@ -9,12 +9,13 @@ use crate::common::error::LbError;
 use crate::common::protocol::TpsApiKind;
 use axum::{
     body::Body,
-    extract::State,
-    http::{HeaderName, HeaderValue, StatusCode},
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::{json, Value};
+use std::net::SocketAddr;
 use std::time::Instant;
 use tracing::{error, info, warn};
 use uuid::Uuid;
@@ -27,23 +28,25 @@ use crate::{
         proxy::{
             forward_streaming_response, forward_streaming_response_with_tps_tracking,
             forward_to_endpoint, record_endpoint_request_stats,
-            select_available_endpoint_with_queue_for_model, QueueSelection,
+            select_available_endpoint_with_queue_for_model, QueueSelection, RoutingHeaders,
         },
     },
-    balancer::RequestOutcome,
+    auth::middleware::ApiKeyAuthContext,
+    balancer::{RequestOutcome, SelectionContext},
     token::extract_usage_from_response,
     AppState,
 };
 
 /// SPEC-f8e3a1b7: 推論リクエスト成功時にエンドポイントのレイテンシを更新（Fire-and-forget）
-fn update_inference_latency(
-    registry: &crate::registry::endpoints::EndpointRegistry,
-    endpoint_id: Uuid,
-    duration: std::time::Duration,
-) {
-    let registry = registry.clone();
+fn update_inference_latency(state: &AppState, endpoint_id: Uuid, duration: std::time::Duration) {
+    let registry = state.endpoint_registry.clone();
+    let load_manager = state.load_manager.clone();
     let latency_ms = duration.as_millis() as f64;
     tokio::spawn(async move {
+        // 外れ値は EMA に入れる前に直近分布のパーセンタイルへクリップする
+        let latency_ms = load_manager
+            .filter_latency_sample(endpoint_id, latency_ms)
+            .await;
         if let Err(e) = registry
             .update_inference_latency(endpoint_id, latency_ms)
             .await
@@ -141,9 +144,17 @@ fn extract_stream(payload: &Value) -> bool {
 ///
 /// リクエストをバックエンドにパススルーする（判定/フラグは廃止）。
 pub async fn post_responses(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     State(state): State<AppState>,
+    auth_ctx: Option<axum::Extension<ApiKeyAuthContext>>,
     Json(payload): Json<Value>,
 ) -> Result<Response, AppError> {
+    let mut selection =
+        SelectionContext::from_request(&headers, auth_ctx.as_ref().map(|axum::Extension(ctx)| ctx));
+    selection.client_ip = Some(crate::common::ip::resolve_trusted_client_ip(
+        &addr, &headers,
+    ));
     let model = extract_model(&payload)?;
     let stream = extract_stream(&payload);
     let tps_api_kind = Some(TpsApiKind::Responses);
@@ -168,7 +179,7 @@ pub async fn post_responses(
         }
     }
 
-    let queue_config = state.queue_config;
+    let queue_config = crate::config::effective_queue_config(state.queue_config);
 
     // モデル対応エンドポイントをキュー付きで選択（モデル集合内で分散）
     let (endpoint, queued_wait_ms) = match select_available_endpoint_with_queue_for_model(
@@ -176,6 +187,7 @@ pub async fn post_responses(
         queue_config,
         &model,
         tps_api_kind,
+        &selection,
     )
     .await
     {
@@ -234,6 +246,15 @@ pub async fn post_responses(
         &endpoint.endpoint_type,
         &endpoint_models,
     );
+    let routed = RoutingHeaders {
+        endpoint: endpoint.name.clone(),
+        model: outbound_payload
+            .get("model")
+            .and_then(Value::as_str)
+            .unwrap_or(&model)
+            .to_string(),
+        retries: 0,
+    };
     let body = serde_json::to_vec(&outbound_payload).map_err(|e| {
         error!("Failed to serialize request: {}", e);
         AppError::from(LbError::Http(e.to_string()))
@@ -241,7 +262,7 @@ pub async fn post_responses(
 
     let request_lease = state
         .load_manager
-        .begin_request(endpoint.id)
+        .begin_request(endpoint.id, selection.principal.as_ref())
         .await
         .map_err(AppError::from)?;
 
@@ -253,31 +274,39 @@ pub async fn post_responses(
     // NOTE: Responses APIはレスポンス本文（ステータス含む）をそのまま返したい。
     // forward_to_endpoint() は stream=false の場合に非2xxをErr化するため、
     // ここでは常に "stream=true 相当"（= エラーもレスポンスとして受け取る）で呼び出す。
-    let response =
-        match forward_to_endpoint(&state.http_client, &endpoint, "/v1/responses", body, true).await
-        {
-            Ok(response) => response,
-            Err(e) => {
-                let duration = start.elapsed();
-                request_lease
-                    .complete(RequestOutcome::Error, duration)
-                    .await
-                    .map_err(AppError::from)?;
-                record_endpoint_request_stats(
-                    state.endpoint_registry.clone(),
-                    endpoint.id,
-                    model.clone(),
-                    false,
-                    0,
-                    0,
-                    tps_api_kind,
-                    endpoint.endpoint_type,
-                    state.load_manager.clone(),
-                    state.event_bus.clone(),
-                );
-                return Err(AppError::from(e));
-            }
-        };
+    let response = match forward_to_endpoint(
+        &state.http_client,
+        &state.load_manager,
+        &endpoint,
+        "/v1/responses",
+        body,
+        true,
+        selection.priority,
+    )
+    .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            let duration = start.elapsed();
+            request_lease
+                .complete(RequestOutcome::Error, duration)
+                .await
+                .map_err(AppError::from)?;
+            record_endpoint_request_stats(
+                state.endpoint_registry.clone(),
+                endpoint.id,
+                model.clone(),
+                false,
+                0,
+                0,
+                tps_api_kind,
+                endpoint.endpoint_type,
+                state.load_manager.clone(),
+                state.event_bus.clone(),
+            );
+            return Err(AppError::from(e));
+        }
+    };
 
     let duration = start.elapsed();
     let response_status = response.status();
@@ -297,7 +326,7 @@ pub async fn post_responses(
 
         // SPEC-f8e3a1b7: 成功時に推論レイテンシを更新
         if succeeded {
-            update_inference_latency(&state.endpoint_registry, endpoint.id, duration);
+            update_inference_latency(&state, endpoint.id, duration);
         } else {
             record_endpoint_request_stats(
                 state.endpoint_registry.clone(),
@@ -324,6 +353,13 @@ pub async fn post_responses(
                 state.endpoint_registry.clone(),
                 state.load_manager.clone(),
                 state.event_bus.clone(),
+                None,
+                false,
+                state
+                    .load_manager
+                    .stream_throttle_for(selection.principal.as_ref(), selection.client_ip)
+                    .await,
+                None,
             )
             .map_err(AppError::from)?
         } else {
@@ -332,6 +368,7 @@ pub async fn post_responses(
         if let Some(wait_ms) = queued_wait_ms {
             add_queue_headers(&mut axum_response, wait_ms);
         }
+        routed.apply(&mut axum_response);
         return Ok(axum_response);
     }
 
@@ -405,7 +442,7 @@ pub async fn post_responses(
 
     // SPEC-f8e3a1b7: 成功時に推論レイテンシを更新
     if status.is_success() {
-        update_inference_latency(&state.endpoint_registry, endpoint.id, duration);
+        update_inference_latency(&state, endpoint.id, duration);
     }
 
     // バックエンドのレスポンス（ステータス/ヘッダ/本文）をパススルー
@@ -426,6 +463,7 @@ pub async fn post_responses(
     if let Some(wait_ms) = queued_wait_ms {
         add_queue_headers(&mut axum_response, wait_ms);
     }
+    routed.apply(&mut axum_response);
 
     Ok(axum_response)
 }
@@ -438,8 +476,14 @@ mod tests {
         types::endpoint::{Endpoint, EndpointModel, EndpointStatus, EndpointType, SupportedAPI},
         AppState,
     };
-    use axum::{body::to_bytes, extract::State, http::StatusCode, Json};
+    use axum::{
+        body::to_bytes,
+        extract::{ConnectInfo, State},
+        http::{HeaderMap, StatusCode},
+        Json,
+    };
     use serde_json::json;
+    use std::net::SocketAddr;
     use tokio::time::{sleep, Duration};
     use wiremock::matchers::{method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -503,7 +547,10 @@ mod tests {
         let endpoint_id = register_vllm_endpoint(&state, server.uri(), "responses-tps-model").await;
 
         let response = post_responses(
+            ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))),
+            HeaderMap::new(),
             State(state.clone()),
+            None,
             Json(json!({
                 "model": "responses-tps-model",
                 "input": "hello"
@@ -553,7 +600,10 @@ mod tests {
             register_vllm_endpoint(&state, server.uri(), "responses-stream-model").await;
 
         let response = post_responses(
+            ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))),
+            HeaderMap::new(),
             State(state.clone()),
+            None,
             Json(json!({
                 "model": "responses-stream-model",
                 "input": "hello",
@@ -607,7 +657,10 @@ mod tests {
             register_vllm_endpoint(&state, server.uri(), "responses-stream-interrupted").await;
 
         let response = post_responses(
+            ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))),
+            HeaderMap::new(),
             State(state.clone()),
+            None,
             Json(json!({
                 "model": "responses-stream-interrupted",
                 "input": "hello",
@@ -0,0 +1,241 @@
+//! ルーティングポリシー管理API
+//!
+//! ラベルベースのルーティングポリシーのCRUD操作。
+//! 変更はDBへ保存した後、LoadManagerへ即時反映する。
+
+use crate::balancer::{NoMatchBehavior, RoutingPolicy};
+use crate::common::auth::{Claims, UserRole};
+use crate::common::error::{CommonError, LbError};
+use crate::common::protocol::TpsApiKind;
+use crate::db::routing_policies as db;
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::endpoints::{deserialize_optional_field, normalize_tags};
+use super::error::AppError;
+
+/// ルーティングポリシー作成リクエスト
+#[derive(Debug, Deserialize)]
+pub struct CreateRoutingPolicyRequest {
+    /// ポリシー名
+    pub name: String,
+    /// モデル名パターン
+    pub model_pattern: String,
+    /// 対象API種別（省略時は全種別）
+    #[serde(default)]
+    pub api_kind: Option<TpsApiKind>,
+    /// 必須ラベル
+    pub required_labels: Vec<String>,
+    /// 合致ゼロ時の挙動（デフォルト: error）
+    #[serde(default)]
+    pub on_no_match: NoMatchBehavior,
+    /// 優先度（デフォルト: 0）
+    #[serde(default)]
+    pub priority: i32,
+    /// 有効フラグ（デフォルト: true）
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// ルーティングポリシー更新リクエスト
+#[derive(Debug, Deserialize)]
+pub struct UpdateRoutingPolicyRequest {
+    /// ポリシー名
+    pub name: Option<String>,
+    /// モデル名パターン
+    pub model_pattern: Option<String>,
+    /// 対象API種別（`null` 指定で全種別）
+    #[serde(default, deserialize_with = "deserialize_optional_field")]
+    pub api_kind: Option<Option<TpsApiKind>>,
+    /// 必須ラベル
+    pub required_labels: Option<Vec<String>>,
+    /// 合致ゼロ時の挙動
+    pub on_no_match: Option<NoMatchBehavior>,
+    /// 優先度
+    pub priority: Option<i32>,
+    /// 有効フラグ
+    pub enabled: Option<bool>,
+}
+
+/// ルーティングポリシー一覧レスポンス
+#[derive(Debug, Serialize)]
+pub struct ListRoutingPoliciesResponse {
+    /// ポリシー一覧（優先度降順）
+    pub policies: Vec<RoutingPolicy>,
+}
+
+fn ensure_admin(claims: &Claims) -> Result<(), AppError> {
+    if claims.role != UserRole::Admin {
+        return Err(AppError(LbError::Authorization(
+            "Admin permission required".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+fn validate_policy(policy: &RoutingPolicy) -> Result<(), AppError> {
+    if policy.name.trim().is_empty() {
+        return Err(AppError(
+            CommonError::Validation("Name is required".to_string()).into(),
+        ));
+    }
+    if policy.model_pattern.trim().is_empty() {
+        return Err(AppError(
+            CommonError::Validation("Model pattern is required".to_string()).into(),
+        ));
+    }
+    if policy.required_labels.is_empty() {
+        return Err(AppError(
+            CommonError::Validation("At least one required label is needed".to_string()).into(),
+        ));
+    }
+    Ok(())
+}
+
+/// DBの内容をLoadManagerへ再読込する
+async fn reload_policies(state: &AppState) -> Result<(), AppError> {
+    let policies = db::list(&state.db_pool).await?;
+    state.load_manager.set_routing_policies(policies).await;
+    Ok(())
+}
+
+/// GET /api/routing-policies - ルーティングポリシー一覧
+pub async fn list_routing_policies(
+    State(state): State<AppState>,
+) -> Result<Json<ListRoutingPoliciesResponse>, AppError> {
+    let policies = db::list(&state.db_pool).await?;
+    Ok(Json(ListRoutingPoliciesResponse { policies }))
+}
+
+/// GET /api/routing-policies/:id - ルーティングポリシー詳細
+pub async fn get_routing_policy(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RoutingPolicy>, AppError> {
+    db::get(&state.db_pool, id).await?.map(Json).ok_or_else(|| {
+        AppError(LbError::NotFound(format!(
+            "Routing policy {} not found",
+            id
+        )))
+    })
+}
+
+/// POST /api/routing-policies - ルーティングポリシー作成
+pub async fn create_routing_policy(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Json(req): Json<CreateRoutingPolicyRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    ensure_admin(&claims)?;
+
+    let now = Utc::now();
+    let policy = RoutingPolicy {
+        id: Uuid::new_v4(),
+        name: req.name.trim().to_string(),
+        model_pattern: req.model_pattern.trim().to_string(),
+        api_kind: req.api_kind,
+        required_labels: normalize_tags(req.required_labels),
+        on_no_match: req.on_no_match,
+        priority: req.priority,
+        enabled: req.enabled,
+        created_at: now,
+        updated_at: now,
+    };
+    validate_policy(&policy)?;
+
+    db::create(&state.db_pool, &policy).await?;
+    reload_policies(&state).await?;
+
+    Ok((StatusCode::CREATED, Json(policy)))
+}
+
+/// PUT /api/routing-policies/:id - ルーティングポリシー更新
+pub async fn update_routing_policy(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateRoutingPolicyRequest>,
+) -> Result<Json<RoutingPolicy>, AppError> {
+    ensure_admin(&claims)?;
+
+    let mut policy = db::get(&state.db_pool, id).await?.ok_or_else(|| {
+        AppError(LbError::NotFound(format!(
+            "Routing policy {} not found",
+            id
+        )))
+    })?;
+
+    if let Some(name) = req.name {
+        policy.name = name.trim().to_string();
+    }
+    if let Some(model_pattern) = req.model_pattern {
+        policy.model_pattern = model_pattern.trim().to_string();
+    }
+    if let Some(api_kind) = req.api_kind {
+        policy.api_kind = api_kind;
+    }
+    if let Some(labels) = req.required_labels {
+        policy.required_labels = normalize_tags(labels);
+    }
+    if let Some(on_no_match) = req.on_no_match {
+        policy.on_no_match = on_no_match;
+    }
+    if let Some(priority) = req.priority {
+        policy.priority = priority;
+    }
+    if let Some(enabled) = req.enabled {
+        policy.enabled = enabled;
+    }
+    policy.updated_at = Utc::now();
+    validate_policy(&policy)?;
+
+    db::update(&state.db_pool, &policy).await?;
+    reload_policies(&state).await?;
+
+    Ok(Json(policy))
+}
+
+/// DELETE /api/routing-policies/:id - ルーティングポリシー削除
+pub async fn delete_routing_policy(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    if !db::delete(&state.db_pool, id).await? {
+        return Err(AppError(LbError::NotFound(format!(
+            "Routing policy {} not found",
+            id
+        ))));
+    }
+    reload_policies(&state).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_request_distinguishes_null_api_kind() {
+        let req: UpdateRoutingPolicyRequest = serde_json::from_str(r#"{"api_kind":null}"#).unwrap();
+        assert_eq!(req.api_kind, Some(None));
+
+        let req: UpdateRoutingPolicyRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(req.api_kind, None);
+    }
+}
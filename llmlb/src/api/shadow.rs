@@ -0,0 +1,379 @@
+//! シャドウトラフィック管理API と複製送信
+//!
+//! シャドウトラフィック設定のCRUD操作と送信統計の取得。
+//! 変更はDBへ保存した後、LoadManagerへ即時反映する。
+//! 複製送信は本番リクエストとは独立したタスクで行い、クライアントへの応答・
+//! リクエスト履歴・課金（トークン使用量）・エンドポイントの負荷/TPS統計には影響しない。
+
+use crate::balancer::priority::{apply_priority_header, RequestPriority};
+use crate::balancer::{ShadowStats, ShadowTarget};
+use crate::common::auth::{Claims, UserRole};
+use crate::common::error::{CommonError, LbError};
+use crate::db::shadow_targets as db;
+use crate::types::endpoint::{Endpoint, EndpointStatus};
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use super::error::AppError;
+
+/// シャドウトラフィック設定作成リクエスト
+#[derive(Debug, Deserialize)]
+pub struct CreateShadowTargetRequest {
+    /// 設定名
+    pub name: String,
+    /// 対象モデル名パターン
+    pub model_pattern: String,
+    /// 複製先（シャドウ）エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 複製する割合（0〜100、デフォルト: 10）
+    #[serde(default = "default_sample_percent")]
+    pub sample_percent: u8,
+    /// 有効フラグ（デフォルト: true）
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_sample_percent() -> u8 {
+    10
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// シャドウトラフィック設定更新リクエスト
+#[derive(Debug, Deserialize)]
+pub struct UpdateShadowTargetRequest {
+    /// 設定名
+    pub name: Option<String>,
+    /// 対象モデル名パターン
+    pub model_pattern: Option<String>,
+    /// 複製先（シャドウ）エンドポイントID
+    pub endpoint_id: Option<Uuid>,
+    /// 複製する割合
+    pub sample_percent: Option<u8>,
+    /// 有効フラグ
+    pub enabled: Option<bool>,
+}
+
+/// 送信統計付きのシャドウトラフィック設定
+#[derive(Debug, Serialize)]
+pub struct ShadowTargetWithStats {
+    /// シャドウトラフィック設定
+    #[serde(flatten)]
+    pub target: ShadowTarget,
+    /// 送信統計（プロセス起動以降の集計）
+    pub stats: ShadowStats,
+}
+
+/// シャドウトラフィック設定一覧レスポンス
+#[derive(Debug, Serialize)]
+pub struct ListShadowTargetsResponse {
+    /// シャドウトラフィック設定一覧（作成日時順）
+    pub shadow_targets: Vec<ShadowTargetWithStats>,
+}
+
+fn ensure_admin(claims: &Claims) -> Result<(), AppError> {
+    if claims.role != UserRole::Admin {
+        return Err(AppError(LbError::Authorization(
+            "Admin permission required".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+fn validate_shadow_target(target: &ShadowTarget) -> Result<(), AppError> {
+    if target.name.trim().is_empty() {
+        return Err(AppError(
+            CommonError::Validation("Name is required".to_string()).into(),
+        ));
+    }
+    if target.model_pattern.trim().is_empty() {
+        return Err(AppError(
+            CommonError::Validation("Model pattern is required".to_string()).into(),
+        ));
+    }
+    if target.sample_percent > 100 {
+        return Err(AppError(
+            CommonError::Validation("sample_percent must be between 0 and 100".to_string()).into(),
+        ));
+    }
+    Ok(())
+}
+
+async fn ensure_endpoint_exists(state: &AppState, endpoint_id: Uuid) -> Result<(), AppError> {
+    if state.endpoint_registry.get(endpoint_id).await.is_none() {
+        return Err(AppError(
+            CommonError::Validation(format!("Endpoint {} not found", endpoint_id)).into(),
+        ));
+    }
+    Ok(())
+}
+
+fn with_stats(state: &AppState, target: ShadowTarget) -> ShadowTargetWithStats {
+    let stats = state.load_manager.shadow_stats(target.id);
+    ShadowTargetWithStats { target, stats }
+}
+
+/// DBの内容をLoadManagerへ再読込する
+async fn reload_shadow_targets(state: &AppState) -> Result<(), AppError> {
+    let targets = db::list(&state.db_pool).await?;
+    state.load_manager.set_shadow_targets(targets).await;
+    Ok(())
+}
+
+/// GET /api/shadow - シャドウトラフィック設定一覧（送信統計付き）
+pub async fn list_shadow_targets(
+    State(state): State<AppState>,
+) -> Result<Json<ListShadowTargetsResponse>, AppError> {
+    let shadow_targets = db::list(&state.db_pool)
+        .await?
+        .into_iter()
+        .map(|target| with_stats(&state, target))
+        .collect();
+    Ok(Json(ListShadowTargetsResponse { shadow_targets }))
+}
+
+/// GET /api/shadow/:id - シャドウトラフィック設定詳細（送信統計付き）
+pub async fn get_shadow_target(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ShadowTargetWithStats>, AppError> {
+    let target = db::get(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| AppError(LbError::NotFound(format!("Shadow target {} not found", id))))?;
+    Ok(Json(with_stats(&state, target)))
+}
+
+/// POST /api/shadow - シャドウトラフィック設定作成
+pub async fn create_shadow_target(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Json(req): Json<CreateShadowTargetRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    ensure_admin(&claims)?;
+
+    let now = Utc::now();
+    let target = ShadowTarget {
+        id: Uuid::new_v4(),
+        name: req.name.trim().to_string(),
+        model_pattern: req.model_pattern.trim().to_string(),
+        endpoint_id: req.endpoint_id,
+        sample_percent: req.sample_percent,
+        enabled: req.enabled,
+        created_at: now,
+        updated_at: now,
+    };
+    validate_shadow_target(&target)?;
+    ensure_endpoint_exists(&state, target.endpoint_id).await?;
+
+    db::create(&state.db_pool, &target).await?;
+    reload_shadow_targets(&state).await?;
+
+    Ok((StatusCode::CREATED, Json(target)))
+}
+
+/// PUT /api/shadow/:id - シャドウトラフィック設定更新
+pub async fn update_shadow_target(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateShadowTargetRequest>,
+) -> Result<Json<ShadowTarget>, AppError> {
+    ensure_admin(&claims)?;
+
+    let mut target = db::get(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| AppError(LbError::NotFound(format!("Shadow target {} not found", id))))?;
+
+    if let Some(name) = req.name {
+        target.name = name.trim().to_string();
+    }
+    if let Some(model_pattern) = req.model_pattern {
+        target.model_pattern = model_pattern.trim().to_string();
+    }
+    if let Some(endpoint_id) = req.endpoint_id {
+        ensure_endpoint_exists(&state, endpoint_id).await?;
+        target.endpoint_id = endpoint_id;
+    }
+    if let Some(sample_percent) = req.sample_percent {
+        target.sample_percent = sample_percent;
+    }
+    if let Some(enabled) = req.enabled {
+        target.enabled = enabled;
+    }
+    target.updated_at = Utc::now();
+    validate_shadow_target(&target)?;
+
+    db::update(&state.db_pool, &target).await?;
+    reload_shadow_targets(&state).await?;
+
+    Ok(Json(target))
+}
+
+/// DELETE /api/shadow/:id - シャドウトラフィック設定削除
+pub async fn delete_shadow_target(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    if !db::delete(&state.db_pool, id).await? {
+        return Err(AppError(LbError::NotFound(format!(
+            "Shadow target {} not found",
+            id
+        ))));
+    }
+    reload_shadow_targets(&state).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// シャドウエンドポイントへ1件送信し、成否を返す（応答本文は受信して破棄する）
+async fn send_shadow_request(
+    client: &reqwest::Client,
+    endpoint: &Endpoint,
+    target_path: &str,
+    payload: &Value,
+) -> bool {
+    let url = format!("{}{}", endpoint.base_url.trim_end_matches('/'), target_path);
+    let mut request_builder = apply_priority_header(client.post(&url), RequestPriority::Low)
+        .timeout(Duration::from_secs(endpoint.inference_timeout_secs as u64))
+        .json(payload);
+    if let Some(api_key) = &endpoint.api_key {
+        request_builder = request_builder.bearer_auth(api_key);
+    }
+
+    match request_builder.send().await {
+        Ok(response) => {
+            let status = response.status();
+            let body_received = response.bytes().await.is_ok();
+            if !status.is_success() {
+                tracing::debug!(
+                    endpoint = %endpoint.name,
+                    status = %status,
+                    "Shadow request returned non-success status"
+                );
+            }
+            status.is_success() && body_received
+        }
+        Err(e) => {
+            tracing::debug!(endpoint = %endpoint.name, error = %e, "Shadow request failed");
+            false
+        }
+    }
+}
+
+/// 抽選に当たったシャドウ設定の複製先へリクエストを送信する（応答は待たない）
+///
+/// 受信リクエストのスコープ外で送るため、アップストリームへの優先度は `low` になる。
+/// オンラインでない複製先はスキップし、統計にも計上しない。
+pub(crate) async fn mirror_request(
+    state: &AppState,
+    target_path: &str,
+    model: &str,
+    payload: &Value,
+) {
+    for target in state.load_manager.sample_shadow_targets(model).await {
+        let Some(endpoint) = state.endpoint_registry.get(target.endpoint_id).await else {
+            continue;
+        };
+        if endpoint.status != EndpointStatus::Online {
+            continue;
+        }
+
+        let client = state.http_client.clone();
+        let load_manager = state.load_manager.clone();
+        let target_path = target_path.to_string();
+        let payload = payload.clone();
+        tokio::spawn(async move {
+            let started = Instant::now();
+            let success = send_shadow_request(&client, &endpoint, &target_path, &payload).await;
+            load_manager.record_shadow_result(target.id, success, started.elapsed());
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::EndpointType;
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[test]
+    fn create_request_defaults_and_validation() {
+        let req: CreateShadowTargetRequest = serde_json::from_str(&format!(
+            r#"{{"name":"shadow","model_pattern":"llama*","endpoint_id":"{}"}}"#,
+            Uuid::new_v4()
+        ))
+        .unwrap();
+        assert_eq!(req.sample_percent, 10);
+        assert!(req.enabled);
+
+        let now = Utc::now();
+        let mut target = ShadowTarget {
+            id: Uuid::new_v4(),
+            name: req.name,
+            model_pattern: req.model_pattern,
+            endpoint_id: req.endpoint_id,
+            sample_percent: req.sample_percent,
+            enabled: req.enabled,
+            created_at: now,
+            updated_at: now,
+        };
+        assert!(validate_shadow_target(&target).is_ok());
+        target.sample_percent = 101;
+        assert!(validate_shadow_target(&target).is_err());
+        target.sample_percent = 10;
+        target.model_pattern = " ".to_string();
+        assert!(validate_shadow_target(&target).is_err());
+    }
+
+    #[tokio::test]
+    async fn send_shadow_request_reports_success_and_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("authorization", "Bearer sk-shadow"))
+            .and(header("x-llmlb-priority", "low"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": []
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/embeddings"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let mut endpoint = Endpoint::new(
+            "shadow".to_string(),
+            server.uri(),
+            EndpointType::OpenaiCompatible,
+        );
+        endpoint.api_key = Some("sk-shadow".to_string());
+        let payload = serde_json::json!({"model": "llama3", "messages": []});
+
+        assert!(send_shadow_request(&client, &endpoint, "/v1/chat/completions", &payload).await);
+        assert!(!send_shadow_request(&client, &endpoint, "/v1/embeddings", &payload).await);
+
+        endpoint.base_url = "http://127.0.0.1:1".to_string();
+        assert!(!send_shadow_request(&client, &endpoint, "/v1/chat/completions", &payload).await);
+    }
+}
@@ -0,0 +1,222 @@
+//! アップストリームSSEの正規化
+//!
+//! アップストリームによって異なるSSEの書式（`\r\n` / `\r` 改行、`data:` 直後の空白の有無、
+//! イベント区切りの空行の欠落、`data:` の付かないJSON行、`[DONE]` の有無や重複）を、
+//! OpenAI標準の `data: <payload>\n\n` 形式にそろえる。
+//! チャンクが行やUTF-8文字の途中で分割されていても、完全な行がそろってから出力する。
+
+use axum::body::Bytes;
+use serde_json::Value;
+
+/// ストリーム終端イベント
+pub const DONE_EVENT: &[u8] = b"data: [DONE]\n\n";
+
+/// SSEストリームの正規化器
+///
+/// `push` で受信チャンクを渡し、返されたバイト列をクライアントへ送る。
+/// アップストリームが正常終了したら `finish` で未出力のイベントと `[DONE]` を受け取る。
+/// `id:` / `retry:` フィールドは OpenAI 形式では使われないため破棄する。
+#[derive(Debug, Default)]
+pub struct SseNormalizer {
+    /// 改行未到達の受信バイト
+    buffer: Vec<u8>,
+    /// 組み立て中イベントの `event:` フィールド
+    event: Option<String>,
+    /// 組み立て中イベントの `data:` 行
+    data: Vec<String>,
+    /// `[DONE]` を送出済みか
+    done_sent: bool,
+}
+
+impl SseNormalizer {
+    /// 正規化器を作成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 受信チャンクを取り込み、完成したイベントを正規化して返す
+    pub fn push(&mut self, chunk: &[u8]) -> Bytes {
+        self.buffer.extend_from_slice(chunk);
+        let mut out = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+        while i < self.buffer.len() {
+            let next = match self.buffer[i] {
+                b'\n' => i + 1,
+                // `\r` 単独も改行として扱う。チャンク末尾では続く `\n` を待つ
+                b'\r' if i + 1 == self.buffer.len() => break,
+                b'\r' if self.buffer[i + 1] == b'\n' => i + 2,
+                b'\r' => i + 1,
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            };
+            let line = String::from_utf8_lossy(&self.buffer[start..i]).into_owned();
+            self.process_line(&line, &mut out);
+            start = next;
+            i = next;
+        }
+        self.buffer.drain(..start);
+        Bytes::from(out)
+    }
+
+    /// ストリーム終端: 残りの行とイベントを出力し、未送出なら `[DONE]` を付与する
+    pub fn finish(&mut self) -> Bytes {
+        let mut out = Vec::new();
+        if !self.buffer.is_empty() {
+            let rest = std::mem::take(&mut self.buffer);
+            let line = String::from_utf8_lossy(&rest);
+            self.process_line(line.trim_end_matches('\r'), &mut out);
+        }
+        self.dispatch(&mut out);
+        if !self.done_sent {
+            self.done_sent = true;
+            out.extend_from_slice(DONE_EVENT);
+        }
+        Bytes::from(out)
+    }
+
+    fn process_line(&mut self, line: &str, out: &mut Vec<u8>) {
+        if line.is_empty() {
+            self.dispatch(out);
+            return;
+        }
+        // コメント（keep-alive 等）はイベントの組み立て中でなければそのまま送る
+        if line.starts_with(':') {
+            if self.data.is_empty() && self.event.is_none() && !self.done_sent {
+                out.extend_from_slice(line.as_bytes());
+                out.extend_from_slice(b"\n\n");
+            }
+            return;
+        }
+        // `data:` の付かないJSON行 / `[DONE]` は1行で1イベントとみなす
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('{') || trimmed.starts_with('[') {
+            self.dispatch(out);
+            self.data.push(trimmed.to_string());
+            self.dispatch(out);
+            return;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "data" => {
+                // 空行なしで次のJSONが続いた場合は前のイベントを確定させる
+                if is_complete_json(&self.data) {
+                    self.dispatch(out);
+                }
+                self.data.push(value.to_string());
+            }
+            "event" => {
+                if !self.data.is_empty() {
+                    self.dispatch(out);
+                }
+                self.event = Some(value.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn dispatch(&mut self, out: &mut Vec<u8>) {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return;
+        }
+        let data = std::mem::take(&mut self.data).join("\n");
+        if self.done_sent {
+            return;
+        }
+        if data.trim() == "[DONE]" {
+            self.done_sent = true;
+            out.extend_from_slice(DONE_EVENT);
+            return;
+        }
+
+        if let Some(event) = event {
+            out.extend_from_slice(format!("event: {}\n", event).as_bytes());
+        }
+        if !data.contains('\n') {
+            out.extend_from_slice(format!("data: {}\n\n", data).as_bytes());
+            return;
+        }
+        // 複数行に分かれたJSONは1行へ詰め直す（JSONでなければ行ごとの data: にする）
+        match serde_json::from_str::<Value>(&data) {
+            Ok(json) => out.extend_from_slice(format!("data: {}\n\n", json).as_bytes()),
+            Err(_) => {
+                for line in data.split('\n') {
+                    out.extend_from_slice(format!("data: {}\n", line).as_bytes());
+                }
+                out.push(b'\n');
+            }
+        }
+    }
+}
+
+fn is_complete_json(lines: &[String]) -> bool {
+    !lines.is_empty() && serde_json::from_str::<serde::de::IgnoredAny>(&lines.join("\n")).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(chunks: &[&[u8]]) -> String {
+        let mut normalizer = SseNormalizer::new();
+        let mut out = Vec::new();
+        for chunk in chunks {
+            out.extend_from_slice(&normalizer.push(chunk));
+        }
+        out.extend_from_slice(&normalizer.finish());
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn normalizes_line_endings_prefix_and_missing_separators() {
+        let out = normalize(&[
+            b"data:{\"a\":1}\r\n\r\ndata:  {\"b\":2}\rdata: {\"c\":3}\n",
+            b"data: [DONE]\n\n",
+        ]);
+        assert_eq!(
+            out,
+            "data: {\"a\":1}\n\ndata:  {\"b\":2}\n\ndata: {\"c\":3}\n\ndata: [DONE]\n\n"
+        );
+    }
+
+    #[test]
+    fn wraps_bare_json_lines_and_appends_missing_done() {
+        let out = normalize(&[b"{\"a\":1}\n{\"b\":2}\n"]);
+        assert_eq!(
+            out,
+            "data: {\"a\":1}\n\ndata: {\"b\":2}\n\ndata: [DONE]\n\n"
+        );
+    }
+
+    #[test]
+    fn duplicate_done_and_events_after_done_are_dropped() {
+        let out = normalize(&[b"data: {\"a\":1}\n\ndata: [DONE]\n\ndata: [DONE]\n\ndata: {}\n\n"]);
+        assert_eq!(out, "data: {\"a\":1}\n\ndata: [DONE]\n\n");
+    }
+
+    #[test]
+    fn reassembles_lines_split_at_byte_boundaries() {
+        // 「あ」(E3 81 82) と CRLF をチャンク境界で分断する
+        let out = normalize(&[b"data: {\"c\":\"\xE3", b"\x81", b"\x82\"}\r", b"\n\r\n"]);
+        assert_eq!(out, "data: {\"c\":\"あ\"}\n\ndata: [DONE]\n\n");
+    }
+
+    #[test]
+    fn keeps_event_field_and_joins_multiline_json() {
+        let out = normalize(&[b"event: error\ndata: {\ndata: \"m\": 1\ndata: }\n\n"]);
+        assert_eq!(out, "event: error\ndata: {\"m\":1}\n\ndata: [DONE]\n\n");
+    }
+
+    #[test]
+    fn forwards_comments_and_flushes_unterminated_event() {
+        let out = normalize(&[b": keep-alive\n\nid: 1\ndata: {\"a\":1}"]);
+        assert_eq!(out, ": keep-alive\n\ndata: {\"a\":1}\n\ndata: [DONE]\n\n");
+    }
+}
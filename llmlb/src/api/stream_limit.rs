@@ -0,0 +1,238 @@
+//! クライアント単位の同時ストリーミング数制限
+//!
+//! `stream: true` の推論リクエストについて、APIキー（APIキー認証でない場合は
+//! クライアントIP）ごとの同時ストリーミング接続数を数え、上限を超えたリクエストを
+//! 429 で拒否する。上限は `LLMLB_MAX_STREAMS_PER_CLIENT`（`0` で無制限）。
+//!
+//! 接続数はレスポンスボディに紐づく [`StreamPermit`] が保持し、ボディの送信完了・
+//! クライアント切断・ハンドラ中断のいずれでもドロップ時に減算されるため、
+//! 切断検知漏れでカウントがリークしない。
+
+use crate::auth::middleware::ApiKeyAuthContext;
+use crate::common::ip::client_ip_from_request;
+use axum::{
+    body::Body,
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::body_inspection::InspectedBody;
+use super::openai_util::openai_error_response;
+
+/// 上限超過時のエラーメッセージ
+const LIMIT_MESSAGE: &str = "Too many concurrent streaming requests for this client";
+
+/// プロセス全体のストリーム数リミッタ（上限は起動時の設定値）
+static STREAM_LIMITER: Lazy<StreamLimiter> =
+    Lazy::new(|| StreamLimiter::new(crate::config::max_streams_per_client()));
+
+/// クライアント単位の同時ストリーミング数リミッタ
+#[derive(Debug)]
+pub struct StreamLimiter {
+    limit: u32,
+    active: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+/// ストリーミング1本分の枠（ドロップ時に解放される）
+#[derive(Debug)]
+pub struct StreamPermit {
+    active: Arc<Mutex<HashMap<String, u32>>>,
+    client: String,
+}
+
+impl StreamLimiter {
+    /// 上限を指定して作成（`0` は無制限）
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            active: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 制限が有効か
+    pub fn is_enabled(&self) -> bool {
+        self.limit > 0
+    }
+
+    /// クライアントの枠を確保する。上限に達している場合は `None`
+    pub fn try_acquire(&self, client: &str) -> Option<StreamPermit> {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        let count = active.entry(client.to_string()).or_insert(0);
+        if self.limit > 0 && *count >= self.limit {
+            return None;
+        }
+        *count += 1;
+        Some(StreamPermit {
+            active: self.active.clone(),
+            client: client.to_string(),
+        })
+    }
+
+    /// クライアントの現在の同時ストリーミング数
+    pub fn active_streams(&self, client: &str) -> u32 {
+        self.active
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(client)
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+impl Drop for StreamPermit {
+    fn drop(&mut self) {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = active.get_mut(&self.client) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                active.remove(&self.client);
+            }
+        }
+    }
+}
+
+/// レスポンスボディが破棄されるまで `permit` を保持させる
+fn hold_until_body_end(response: Response, permit: StreamPermit) -> Response {
+    let (parts, body) = response.into_parts();
+    let stream = body.into_data_stream().map(move |chunk| {
+        let _permit = &permit;
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+/// リクエストのクライアント識別子（APIキーID優先、なければクライアントIP）
+///
+/// クライアントIPは接続元アドレスで、転送ヘッダは `LLMLB_TRUSTED_PROXIES` 経由の場合のみ参照する。
+fn client_key(request: &Request) -> Option<String> {
+    if let Some(ctx) = request.extensions().get::<ApiKeyAuthContext>() {
+        return Some(format!("api_key:{}", ctx.id));
+    }
+    client_ip_from_request(request).map(|ip| format!("ip:{}", ip))
+}
+
+fn limited_response(path: &str) -> Response {
+    if path.starts_with("/v1/messages") {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(json!({
+                "type": "error",
+                "error": {
+                    "type": "rate_limit_error",
+                    "message": LIMIT_MESSAGE
+                }
+            })),
+        )
+            .into_response()
+    } else {
+        openai_error_response(LIMIT_MESSAGE, StatusCode::TOO_MANY_REQUESTS)
+    }
+}
+
+/// 同時ストリーミング数制限ミドルウェア
+///
+/// APIキー認証ミドルウェアより内側、[`inspect_body_middleware`] より内側に配置する。
+/// `stream` の判定には検査レイヤーがパースしたボディ（[`InspectedBody`]）を使う。
+///
+/// [`inspect_body_middleware`]: super::body_inspection::inspect_body_middleware
+pub async fn stream_limit_middleware(request: Request, next: Next) -> Response {
+    let limiter = &*STREAM_LIMITER;
+    let is_stream = request
+        .extensions()
+        .get::<InspectedBody>()
+        .is_some_and(InspectedBody::is_stream);
+    if !limiter.is_enabled() || !is_stream {
+        return next.run(request).await;
+    }
+    let Some(client) = client_key(&request) else {
+        return next.run(request).await;
+    };
+
+    let path = request.uri().path().to_string();
+    let Some(permit) = limiter.try_acquire(&client) else {
+        tracing::warn!(
+            path = %path,
+            client = %client,
+            limit = limiter.limit,
+            "Rejected streaming request: per-client stream limit reached"
+        );
+        return limited_response(&path);
+    };
+
+    let response = next.run(request).await;
+    hold_until_body_end(response, permit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permits_are_limited_per_client_and_released_on_drop() {
+        let limiter = StreamLimiter::new(2);
+        let a1 = limiter.try_acquire("a").unwrap();
+        let _a2 = limiter.try_acquire("a").unwrap();
+        assert!(limiter.try_acquire("a").is_none());
+        // 他クライアントは独立して数える
+        assert!(limiter.try_acquire("b").is_some());
+
+        drop(a1);
+        assert_eq!(limiter.active_streams("a"), 1);
+        assert!(limiter.try_acquire("a").is_some());
+    }
+
+    #[test]
+    fn zero_limit_is_unlimited() {
+        let limiter = StreamLimiter::new(0);
+        assert!(!limiter.is_enabled());
+        let permits: Vec<_> = (0..10).map(|_| limiter.try_acquire("a").unwrap()).collect();
+        assert_eq!(limiter.active_streams("a"), 10);
+        drop(permits);
+        assert_eq!(limiter.active_streams("a"), 0);
+    }
+
+    #[tokio::test]
+    async fn permit_is_released_when_body_is_consumed_or_dropped() {
+        let limiter = StreamLimiter::new(1);
+
+        let permit = limiter.try_acquire("a").unwrap();
+        let response = hold_until_body_end(Response::new(Body::from("data: done\n\n")), permit);
+        assert_eq!(limiter.active_streams("a"), 1);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"data: done\n\n");
+        assert_eq!(limiter.active_streams("a"), 0);
+
+        // 読み切らずに破棄（クライアント切断）しても解放される
+        let permit = limiter.try_acquire("a").unwrap();
+        let response = hold_until_body_end(Response::new(Body::from("partial")), permit);
+        drop(response);
+        assert_eq!(limiter.active_streams("a"), 0);
+    }
+
+    #[test]
+    fn client_key_uses_connection_address_instead_of_forwarded_headers() {
+        use axum::extract::ConnectInfo;
+        use std::net::SocketAddr;
+
+        let mut request = Request::builder()
+            .header("x-forwarded-for", "203.0.113.1")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(client_key(&request), None);
+
+        request.extensions_mut().insert(ConnectInfo(
+            "198.51.100.7:5000".parse::<SocketAddr>().unwrap(),
+        ));
+        assert_eq!(client_key(&request).as_deref(), Some("ip:198.51.100.7"));
+    }
+}
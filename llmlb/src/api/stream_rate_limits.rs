@@ -0,0 +1,216 @@
+//! ストリーミング出力レート上限管理API
+//!
+//! APIキー/テナント単位のトークン/秒・バイト/秒の上限のCRUD操作。
+//! 変更はDBへ保存した後、LoadManagerへ即時反映する（以降に開始したストリームに適用）。
+
+use crate::balancer::{PrincipalType, StreamRateLimit};
+use crate::common::auth::{Claims, UserRole};
+use crate::common::error::{CommonError, LbError};
+use crate::db::stream_rate_limits as db;
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::error::AppError;
+
+/// レート上限作成リクエスト
+#[derive(Debug, Deserialize)]
+pub struct CreateStreamRateLimitRequest {
+    /// 対象主体の種別（`api_key` / `tenant`）
+    pub principal_type: PrincipalType,
+    /// 対象主体ID（APIキーID、またはテナントのユーザーID）
+    pub principal_id: Uuid,
+    /// トークン/秒の上限
+    #[serde(default)]
+    pub max_tokens_per_sec: Option<u32>,
+    /// バイト/秒の上限
+    #[serde(default)]
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+/// レート上限更新リクエスト（`null` は無制限）
+#[derive(Debug, Deserialize)]
+pub struct UpdateStreamRateLimitRequest {
+    /// トークン/秒の上限
+    #[serde(default)]
+    pub max_tokens_per_sec: Option<u32>,
+    /// バイト/秒の上限
+    #[serde(default)]
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+/// レート上限一覧レスポンス
+#[derive(Debug, Serialize)]
+pub struct ListStreamRateLimitsResponse {
+    /// 個別設定の無いクライアントに適用する既定のトークン/秒上限
+    pub default_max_tokens_per_sec: Option<u32>,
+    /// 個別設定の無いクライアントに適用する既定のバイト/秒上限
+    pub default_max_bytes_per_sec: Option<u64>,
+    /// 個別設定一覧
+    pub limits: Vec<StreamRateLimit>,
+}
+
+fn ensure_admin(claims: &Claims) -> Result<(), AppError> {
+    if claims.role != UserRole::Admin {
+        return Err(AppError(LbError::Authorization(
+            "Admin permission required".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+fn not_found(id: Uuid) -> AppError {
+    AppError(LbError::NotFound(format!(
+        "Stream rate limit {} not found",
+        id
+    )))
+}
+
+/// 上限値を検証する（`0` は不可、少なくとも一方は指定すること）
+fn validate_limit(limit: &StreamRateLimit) -> Result<(), AppError> {
+    if limit.max_tokens_per_sec == Some(0) || limit.max_bytes_per_sec == Some(0) {
+        return Err(AppError(
+            CommonError::Validation("rate limits must be greater than 0".to_string()).into(),
+        ));
+    }
+    if limit.max_tokens_per_sec.is_none() && limit.max_bytes_per_sec.is_none() {
+        return Err(AppError(
+            CommonError::Validation(
+                "max_tokens_per_sec or max_bytes_per_sec is required".to_string(),
+            )
+            .into(),
+        ));
+    }
+    Ok(())
+}
+
+/// DBの内容をLoadManagerへ再読込する
+async fn reload_stream_rate_limits(state: &AppState) -> Result<(), AppError> {
+    let limits = db::list(&state.db_pool).await?;
+    state.load_manager.set_stream_rate_limits(limits).await;
+    Ok(())
+}
+
+/// GET /api/stream-rate-limits - レート上限一覧
+pub async fn list_stream_rate_limits(
+    State(state): State<AppState>,
+) -> Result<Json<ListStreamRateLimitsResponse>, AppError> {
+    let default = state.load_manager.default_stream_rate();
+    Ok(Json(ListStreamRateLimitsResponse {
+        default_max_tokens_per_sec: default.max_tokens_per_sec,
+        default_max_bytes_per_sec: default.max_bytes_per_sec,
+        limits: db::list(&state.db_pool).await?,
+    }))
+}
+
+/// GET /api/stream-rate-limits/:id - レート上限詳細
+pub async fn get_stream_rate_limit(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<StreamRateLimit>, AppError> {
+    let limit = db::get(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    Ok(Json(limit))
+}
+
+/// POST /api/stream-rate-limits - レート上限作成
+pub async fn create_stream_rate_limit(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Json(req): Json<CreateStreamRateLimitRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    ensure_admin(&claims)?;
+
+    let now = Utc::now();
+    let limit = StreamRateLimit {
+        id: Uuid::new_v4(),
+        principal_type: req.principal_type,
+        principal_id: req.principal_id,
+        max_tokens_per_sec: req.max_tokens_per_sec,
+        max_bytes_per_sec: req.max_bytes_per_sec,
+        created_at: now,
+        updated_at: now,
+    };
+    validate_limit(&limit)?;
+
+    db::create(&state.db_pool, &limit).await?;
+    reload_stream_rate_limits(&state).await?;
+
+    Ok((StatusCode::CREATED, Json(limit)))
+}
+
+/// PUT /api/stream-rate-limits/:id - レート上限更新
+pub async fn update_stream_rate_limit(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateStreamRateLimitRequest>,
+) -> Result<Json<StreamRateLimit>, AppError> {
+    ensure_admin(&claims)?;
+
+    let mut limit = db::get(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    limit.max_tokens_per_sec = req.max_tokens_per_sec;
+    limit.max_bytes_per_sec = req.max_bytes_per_sec;
+    limit.updated_at = Utc::now();
+    validate_limit(&limit)?;
+
+    db::update(&state.db_pool, &limit).await?;
+    reload_stream_rate_limits(&state).await?;
+
+    Ok(Json(limit))
+}
+
+/// DELETE /api/stream-rate-limits/:id - レート上限削除
+pub async fn delete_stream_rate_limit(
+    Extension(claims): Extension<Claims>,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    if !db::delete(&state.db_pool, id).await? {
+        return Err(not_found(id));
+    }
+    reload_stream_rate_limits(&state).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_limit_requires_positive_rate() {
+        let now = Utc::now();
+        let mut limit = StreamRateLimit {
+            id: Uuid::new_v4(),
+            principal_type: PrincipalType::Tenant,
+            principal_id: Uuid::new_v4(),
+            max_tokens_per_sec: None,
+            max_bytes_per_sec: None,
+            created_at: now,
+            updated_at: now,
+        };
+        assert!(validate_limit(&limit).is_err());
+
+        limit.max_tokens_per_sec = Some(0);
+        assert!(validate_limit(&limit).is_err());
+
+        limit.max_tokens_per_sec = Some(30);
+        assert!(validate_limit(&limit).is_ok());
+
+        limit.max_bytes_per_sec = Some(0);
+        assert!(validate_limit(&limit).is_err());
+    }
+}
@@ -4,7 +4,7 @@ use crate::common::auth::{Claims, UserRole};
 use crate::common::error::LbError;
 use crate::AppState;
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     Extension, Json,
@@ -326,19 +326,40 @@ pub async fn cancel_schedule(
     }
 }
 
+/// Query parameters for `POST /api/system/update/rollback`.
+#[derive(Debug, Default, Deserialize)]
+pub struct RollbackQuery {
+    /// Roll back even if the previous version does not support the current DB schema.
+    #[serde(default)]
+    pub force: bool,
+}
+
 /// POST /api/system/update/rollback
 ///
 /// Admin only. Restores the previous version from `.bak` if available.
+/// Returns 409 when the previous version cannot read the current DB schema, unless `?force=true`.
 pub async fn rollback(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
+    Query(query): Query<RollbackQuery>,
 ) -> Response {
     if claims.role != UserRole::Admin {
         return AppError(LbError::Authorization("Admin access required".to_string()))
             .into_response();
     }
 
-    match state.update_manager.request_rollback() {
+    let db_schema_version =
+        match crate::db::migrations::applied_migration_version(&state.db_pool).await {
+            Ok(version) => version,
+            Err(e) => {
+                tracing::warn!("Failed to read DB schema version for rollback check: {}", e);
+                None
+            }
+        };
+    match state
+        .update_manager
+        .request_rollback(db_schema_version, query.force)
+    {
         Ok(()) => {
             state
                 .event_bus
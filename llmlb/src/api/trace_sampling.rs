@@ -0,0 +1,252 @@
+//! リクエストトレースのサンプリング
+//!
+//! W3C Trace Context の `traceparent` ヘッダを解釈し、推論リクエストのトレースを記録するかを判定する。
+//!
+//! - `traceparent` で親がサンプリング判定済みの場合はその判定（sampled フラグ）に従う
+//! - それ以外はトレースIDから決定論的に判定する（同じトレースIDは常に同じ判定になる）。
+//!   採用率は `LLMLB_TRACE_SAMPLE_RATE`（0.0〜1.0）
+//! - 5xx で終わったリクエストは事前の判定に関わらず記録する（tail-based）
+//!
+//! 記録したトレースは `llmlb::trace` ターゲットのログとして出力し、レスポンスの `traceparent` で
+//! トレースIDと最終的な判定結果を返す。
+
+use axum::{
+    extract::Request,
+    http::{HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use rand::RngExt;
+use std::time::Instant;
+
+use crate::config;
+
+/// W3C Trace Context のヘッダ名
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// `traceparent` ヘッダの内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceParent {
+    /// トレースID（16バイト）
+    pub trace_id: [u8; 16],
+    /// 呼び出し元のスパンID（8バイト）
+    pub parent_id: [u8; 8],
+    /// 呼び出し元のサンプリング判定
+    pub sampled: bool,
+}
+
+/// `traceparent` ヘッダ値を解釈する
+///
+/// 形式は `version-trace_id-parent_id-flags`（小文字16進）。不正な値・全ゼロのIDは `None`。
+pub fn parse_traceparent(value: &str) -> Option<TraceParent> {
+    let mut parts = value.trim().split('-');
+    let version: [u8; 1] = decode_hex(parts.next()?)?;
+    let trace_id: [u8; 16] = decode_hex(parts.next()?)?;
+    let parent_id: [u8; 8] = decode_hex(parts.next()?)?;
+    let flags: [u8; 1] = decode_hex(parts.next()?)?;
+    // version ff は無効。version 00 は4要素ちょうどで、将来のバージョンは後続要素を許容する
+    if version[0] == 0xff || (version[0] == 0 && parts.next().is_some()) {
+        return None;
+    }
+    if trace_id == [0; 16] || parent_id == [0; 8] {
+        return None;
+    }
+    Some(TraceParent {
+        trace_id,
+        parent_id,
+        sampled: flags[0] & 0x01 != 0,
+    })
+}
+
+/// `traceparent` ヘッダ値（version 00）を組み立てる
+pub fn format_traceparent(trace_id: &[u8; 16], span_id: &[u8; 8], sampled: bool) -> String {
+    format!(
+        "00-{}-{}-{:02x}",
+        encode_hex(trace_id),
+        encode_hex(span_id),
+        u8::from(sampled)
+    )
+}
+
+/// トレースIDから決定論的にサンプリングする
+///
+/// トレースIDの下位8バイトを整数として `rate × 2^64` 未満なら採用する
+/// （OpenTelemetry の TraceIdRatioBased と同じ方式）。
+pub fn ratio_sampled(trace_id: &[u8; 16], rate: f64) -> bool {
+    if rate >= 1.0 {
+        return true;
+    }
+    if rate.is_nan() || rate <= 0.0 {
+        return false;
+    }
+    let mut low = [0u8; 8];
+    low.copy_from_slice(&trace_id[8..]);
+    let threshold = (rate * u64::MAX as f64) as u64;
+    u64::from_be_bytes(low) < threshold
+}
+
+/// リクエスト開始時のサンプリング判定
+///
+/// 親が判定済みならそれに従い、無ければトレースIDから採用率で判定する。
+pub fn head_sampled(parent: Option<&TraceParent>, trace_id: &[u8; 16], rate: f64) -> bool {
+    match parent {
+        Some(parent) => parent.sampled,
+        None => ratio_sampled(trace_id, rate),
+    }
+}
+
+/// レスポンス確定後にトレースを記録する理由（記録しない場合は `None`）
+///
+/// 開始時に採用されていなくても、5xx で終わったリクエストは必ず記録する。
+pub fn record_reason(head_sampled: bool, status: StatusCode) -> Option<&'static str> {
+    if head_sampled {
+        Some("sampled")
+    } else if status.is_server_error() {
+        Some("error")
+    } else {
+        None
+    }
+}
+
+/// リクエストのトレースをサンプリングして記録する
+///
+/// 所要時間はレスポンスヘッダを返すまでの時間（ストリーミングの本文転送は含まない）。
+pub async fn trace_sampling_middleware(request: Request, next: Next) -> Response {
+    let parent = request
+        .headers()
+        .get(TRACEPARENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_traceparent);
+    // ThreadRng は Send ではないため await をまたいで保持しない
+    let (trace_id, span_id) = {
+        let mut rng = rand::rng();
+        let trace_id = parent
+            .map(|p| p.trace_id)
+            .unwrap_or_else(|| rng.random::<u128>().max(1).to_be_bytes());
+        (trace_id, rng.random::<u64>().max(1).to_be_bytes())
+    };
+    let sampled = head_sampled(parent.as_ref(), &trace_id, config::trace_sample_rate());
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let started = Instant::now();
+    let mut response = next.run(request).await;
+    let status = response.status();
+
+    let reason = record_reason(sampled, status);
+    if let Some(reason) = reason {
+        tracing::info!(
+            target: "llmlb::trace",
+            trace_id = %encode_hex(&trace_id),
+            span_id = %encode_hex(&span_id),
+            parent_span_id = ?parent.map(|p| encode_hex(&p.parent_id)),
+            method = %method,
+            path = %path,
+            status = status.as_u16(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            reason,
+            "Request trace"
+        );
+    }
+
+    if let Ok(value) =
+        HeaderValue::from_str(&format_traceparent(&trace_id, &span_id, reason.is_some()))
+    {
+        response.headers_mut().insert(TRACEPARENT_HEADER, value);
+    }
+    response
+}
+
+fn decode_hex<const N: usize>(s: &str) -> Option<[u8; N]> {
+    if s.len() != N * 2 || !s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return None;
+    }
+    let mut out = [0u8; N];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn parses_and_formats_traceparent() {
+        let parent = parse_traceparent(TRACEPARENT).unwrap();
+        assert!(parent.sampled);
+        assert_eq!(
+            format_traceparent(&parent.trace_id, &parent.parent_id, true),
+            TRACEPARENT
+        );
+
+        let unsampled = parse_traceparent(&TRACEPARENT.replace("-01", "-00")).unwrap();
+        assert!(!unsampled.sampled);
+    }
+
+    #[test]
+    fn rejects_invalid_traceparent() {
+        for value in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ] {
+            assert_eq!(parse_traceparent(value), None, "{value}");
+        }
+        // 将来のバージョンは後続要素を無視して解釈する
+        assert!(
+            parse_traceparent("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra")
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn ratio_sampling_is_deterministic() {
+        let ids: Vec<[u8; 16]> = (0..1000u64)
+            .map(|i| u128::from(i.wrapping_mul(0x9e37_79b9_7f4a_7c15)).to_be_bytes())
+            .collect();
+        let sampled = ids.iter().filter(|id| ratio_sampled(id, 0.25)).count();
+        assert!((200..300).contains(&sampled), "sampled {sampled}");
+        for id in &ids {
+            assert_eq!(ratio_sampled(id, 0.25), ratio_sampled(id, 0.25));
+            // 採用率を上げても採用済みのトレースは外れない
+            if ratio_sampled(id, 0.25) {
+                assert!(ratio_sampled(id, 0.5));
+            }
+            assert!(ratio_sampled(id, 1.0));
+            assert!(!ratio_sampled(id, 0.0));
+        }
+    }
+
+    #[test]
+    fn parent_decision_takes_precedence() {
+        let parent = parse_traceparent(TRACEPARENT).unwrap();
+        assert!(head_sampled(Some(&parent), &parent.trace_id, 0.0));
+
+        let unsampled = TraceParent {
+            sampled: false,
+            ..parent
+        };
+        assert!(!head_sampled(Some(&unsampled), &parent.trace_id, 1.0));
+        assert!(head_sampled(None, &parent.trace_id, 1.0));
+    }
+
+    #[test]
+    fn server_errors_are_always_recorded() {
+        assert_eq!(record_reason(true, StatusCode::OK), Some("sampled"));
+        assert_eq!(record_reason(false, StatusCode::OK), None);
+        assert_eq!(record_reason(false, StatusCode::BAD_REQUEST), None);
+        assert_eq!(record_reason(false, StatusCode::BAD_GATEWAY), Some("error"));
+    }
+}
@@ -0,0 +1,164 @@
+//! 失敗時の詳細エラーコンテキスト返却
+//!
+//! 推論ハンドラは失敗時のレスポンス拡張に [`FailureContext`]（失敗段階・試行した
+//! エンドポイント・内部メッセージ）を載せる。`LLMLB_VERBOSE_ERRORS=1` かつ
+//! operator 権限（`endpoints.manage`）を持つAPIキーからのリクエストの場合のみ、
+//! エラー本文の `error.details` にその内容を追加する。それ以外のクライアントには
+//! 従来どおり汎用エラーのみを返す。
+
+use crate::auth::middleware::ApiKeyAuthContext;
+use crate::common::auth::ApiKeyPermission;
+use crate::common::error::FailureContext;
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+
+/// 書き換え対象とするエラー本文の最大サイズ
+const MAX_ERROR_BODY_BYTES: usize = 1024 * 1024;
+
+/// operator 権限（詳細エラーの閲覧可）を持つか
+fn is_operator(auth: Option<&ApiKeyAuthContext>) -> bool {
+    auth.is_some_and(|auth| {
+        auth.permissions
+            .contains(&ApiKeyPermission::EndpointsManage)
+    })
+}
+
+/// エラー本文へ詳細を追加する（JSONでない場合は `None`）
+fn add_details(body: &[u8], context: &FailureContext) -> Option<Vec<u8>> {
+    let mut json: Value = serde_json::from_slice(body).ok()?;
+    let details = serde_json::to_value(context).ok()?;
+    match json.get_mut("error") {
+        Some(Value::Object(error)) => {
+            error.insert("details".to_string(), details);
+        }
+        _ => {
+            json.as_object_mut()?.insert("details".to_string(), details);
+        }
+    }
+    serde_json::to_vec(&json).ok()
+}
+
+/// 権限のあるクライアントにのみ失敗コンテキストを返すミドルウェア
+pub async fn verbose_error_middleware(request: Request, next: Next) -> Response {
+    let operator = is_operator(request.extensions().get::<ApiKeyAuthContext>());
+
+    let mut response = next.run(request).await;
+    let Some(context) = response.extensions_mut().remove::<FailureContext>() else {
+        return response;
+    };
+    if !operator || !crate::config::verbose_errors_enabled() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to read error body for verbose details");
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    match add_details(&bytes, &context) {
+        Some(rewritten) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(rewritten))
+        }
+        None => Response::from_parts(parts, Body::from(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::error::FailureStage;
+    use axum::{http::StatusCode, middleware, response::IntoResponse, routing::post, Json, Router};
+    use serial_test::serial;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    fn app(permissions: Vec<ApiKeyPermission>, endpoint_id: Uuid) -> Router {
+        Router::new()
+            .route(
+                "/v1/chat/completions",
+                post(move || async move {
+                    let mut response = (
+                        StatusCode::BAD_GATEWAY,
+                        Json(serde_json::json!({
+                            "error": {"message": "upstream failed", "type": "endpoint_upstream_error"}
+                        })),
+                    )
+                        .into_response();
+                    response.extensions_mut().insert(FailureContext::new(
+                        FailureStage::Upstream,
+                        &[endpoint_id],
+                        "500 Internal Server Error",
+                    ));
+                    response
+                }),
+            )
+            .layer(middleware::from_fn(verbose_error_middleware))
+            .layer(middleware::from_fn(move |mut request: Request, next: Next| {
+                let permissions = permissions.clone();
+                async move {
+                    request.extensions_mut().insert(ApiKeyAuthContext {
+                        id: Uuid::new_v4(),
+                        created_by: Uuid::new_v4(),
+                        permissions,
+                        expires_at: None,
+                    });
+                    next.run(request).await
+                }
+            }))
+    }
+
+    async fn call(app: Router) -> Value {
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/chat/completions")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn details_are_returned_only_to_operators_when_enabled() {
+        let endpoint_id = Uuid::new_v4();
+        std::env::set_var("LLMLB_VERBOSE_ERRORS", "1");
+
+        let body = call(app(
+            vec![
+                ApiKeyPermission::OpenaiInference,
+                ApiKeyPermission::EndpointsManage,
+            ],
+            endpoint_id,
+        ))
+        .await;
+        assert_eq!(body["error"]["details"]["stage"], "upstream");
+        assert_eq!(
+            body["error"]["details"]["attempted_endpoints"][0],
+            endpoint_id.to_string()
+        );
+
+        let body = call(app(vec![ApiKeyPermission::OpenaiInference], endpoint_id)).await;
+        assert!(body["error"].get("details").is_none());
+        assert_eq!(body["error"]["message"], "upstream failed");
+
+        std::env::remove_var("LLMLB_VERBOSE_ERRORS");
+        let body = call(app(vec![ApiKeyPermission::EndpointsManage], endpoint_id)).await;
+        assert!(body["error"].get("details").is_none());
+    }
+}
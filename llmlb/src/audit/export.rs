@@ -0,0 +1,172 @@
+//! 監査ログのエクスポート形式
+//!
+//! CLI (`llmlb audit export`) と `GET /api/audit/export` で共通の
+//! レコード形式（NDJSON の1行・CSV の列）と期間指定の解釈を提供する。
+
+use crate::audit::types::AuditLogEntry;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::Serialize;
+
+/// 1回のDB読み出しで取得する件数（エクスポート中のメモリ使用量の上限を決める）
+pub const EXPORT_PAGE_SIZE: i64 = 1000;
+
+/// CSVの基本列（末尾に `record_hash` / `chain_verified` を追加する場合がある）
+pub const CSV_COLUMNS: [&str; 20] = [
+    "id",
+    "timestamp",
+    "http_method",
+    "request_path",
+    "status_code",
+    "actor_type",
+    "actor_id",
+    "actor_username",
+    "api_key_owner_id",
+    "client_ip",
+    "duration_ms",
+    "input_tokens",
+    "output_tokens",
+    "total_tokens",
+    "model_name",
+    "endpoint_id",
+    "detail",
+    "batch_id",
+    "is_migrated",
+    "source",
+];
+
+/// エントリの取得元DB
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportSource {
+    /// アーカイブDB
+    Archive,
+    /// メインDB
+    Main,
+}
+
+impl ExportSource {
+    /// 文字列表現
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExportSource::Archive => "archive",
+            ExportSource::Main => "main",
+        }
+    }
+}
+
+/// NDJSON の1行
+#[derive(Serialize)]
+pub struct ExportRecord<'a> {
+    /// エントリ本体
+    #[serde(flatten)]
+    pub entry: &'a AuditLogEntry,
+    /// 取得元DB
+    pub source: ExportSource,
+    /// エントリ単体のハッシュ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_hash: Option<String>,
+    /// ハッシュチェーン検証済みのバッチに属するか
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_verified: Option<bool>,
+}
+
+impl ExportRecord<'_> {
+    /// CSVの1行（`CSV_COLUMNS` の順、指定された追加列が後に続く）
+    pub fn csv_row(&self) -> Vec<String> {
+        let entry = self.entry;
+        let mut row = vec![
+            opt(&entry.id),
+            entry.timestamp.to_rfc3339(),
+            entry.http_method.clone(),
+            entry.request_path.clone(),
+            entry.status_code.to_string(),
+            entry.actor_type.as_str().to_string(),
+            opt(&entry.actor_id),
+            opt(&entry.actor_username),
+            opt(&entry.api_key_owner_id),
+            opt(&entry.client_ip),
+            opt(&entry.duration_ms),
+            opt(&entry.input_tokens),
+            opt(&entry.output_tokens),
+            opt(&entry.total_tokens),
+            opt(&entry.model_name),
+            opt(&entry.endpoint_id),
+            opt(&entry.detail),
+            opt(&entry.batch_id),
+            entry.is_migrated.to_string(),
+            self.source.as_str().to_string(),
+        ];
+        if let Some(hash) = &self.record_hash {
+            row.push(hash.clone());
+        }
+        if let Some(verified) = self.chain_verified {
+            row.push(verified.to_string());
+        }
+        row
+    }
+}
+
+fn opt<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(ToString::to_string).unwrap_or_default()
+}
+
+/// 期間指定を解釈する（RFC 3339 または `YYYY-MM-DD`）
+///
+/// 日付のみの場合、`end_of_day` なら当日の 23:59:59.999、そうでなければ 00:00:00 (UTC) とする。
+pub fn parse_date_or_datetime(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("invalid date '{}': expected RFC 3339 or YYYY-MM-DD", value))?;
+    let time = if end_of_day {
+        NaiveTime::from_hms_milli_opt(23, 59, 59, 999).expect("valid time")
+    } else {
+        NaiveTime::MIN
+    };
+    Ok(date.and_time(time).and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::types::ActorType;
+
+    #[test]
+    fn csv_row_appends_optional_columns() {
+        let entry = AuditLogEntry {
+            id: Some(7),
+            timestamp: parse_date_or_datetime("2026-01-02", false).unwrap(),
+            http_method: "GET".to_string(),
+            request_path: "/api/users".to_string(),
+            status_code: 200,
+            actor_type: ActorType::User,
+            actor_id: Some("user-1".to_string()),
+            actor_username: None,
+            api_key_owner_id: None,
+            client_ip: None,
+            duration_ms: Some(5),
+            input_tokens: None,
+            output_tokens: None,
+            total_tokens: None,
+            model_name: None,
+            endpoint_id: None,
+            detail: None,
+            batch_id: Some(3),
+            is_migrated: false,
+        };
+        let record = ExportRecord {
+            entry: &entry,
+            source: ExportSource::Main,
+            record_hash: None,
+            chain_verified: Some(true),
+        };
+        let row = record.csv_row();
+        assert_eq!(row.len(), CSV_COLUMNS.len() + 1);
+        assert_eq!(row[0], "7");
+        assert_eq!(row[1], "2026-01-02T00:00:00+00:00");
+        assert_eq!(row[17], "3");
+        assert_eq!(row[19], "main");
+        assert_eq!(row[20], "true");
+    }
+}
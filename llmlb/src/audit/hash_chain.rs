@@ -2,8 +2,9 @@
 //!
 //! 監査ログの改ざん検知のためのハッシュチェーン実装。
 //! バッチ単位でSHA-256ハッシュを計算し、前バッチのハッシュを含むチェーンを構成する。
+//! メインDBは前回検証に成功した最終バッチをチェックポイントとして保存し、それ以降のみを差分検証できる。
 
-use crate::audit::types::AuditLogEntry;
+use crate::audit::types::{AuditBatchHash, AuditLogEntry, ChainCheckpoint};
 use crate::common::error::RouterResult;
 use crate::db::audit_log::AuditLogStorage;
 use chrono::{DateTime, Utc};
@@ -100,9 +101,111 @@ pub async fn verify_chain(storage: &AuditLogStorage) -> RouterResult<ChainVerifi
         });
     }
 
-    let mut expected_previous_hash = GENESIS_HASH.to_string();
+    if let Some(tampered) = find_tampered_batch(storage, &batches, GENESIS_HASH.to_string()).await?
+    {
+        return Ok(tampered);
+    }
+
+    Ok(ChainVerificationResult {
+        valid: true,
+        batches_checked: batches.len() as i64,
+        tampered_batch: None,
+        message: None,
+    })
+}
 
-    for batch in &batches {
+/// 前回検証済みのバッチより後のみを検証する差分検証
+///
+/// `from_batch` は前回検証に成功した最終バッチ（`None` の場合は全走査）。
+/// 起点のバッチが見つからない・ハッシュが変わっている場合や、差分で改ざんを検出した場合は
+/// 全走査にフォールバックして改ざん箇所を特定する。
+/// 差分検証で完了した場合の `batches_checked` は新たに検証したバッチ数。
+pub async fn verify_chain_incremental(
+    storage: &AuditLogStorage,
+    from_batch: Option<&ChainCheckpoint>,
+) -> RouterResult<ChainVerificationResult> {
+    let Some(from_batch) = from_batch else {
+        return verify_chain(storage).await;
+    };
+
+    let batches = storage
+        .get_batch_hashes_from(from_batch.sequence_number)
+        .await?;
+    let anchored = batches.first().is_some_and(|batch| {
+        batch.sequence_number == from_batch.sequence_number && batch.hash == from_batch.hash
+    });
+    if !anchored {
+        // アーカイブによるチェーン再構築でもハッシュは変わるため、全走査で改ざんの有無を判定する
+        warn!(
+            batch_seq = from_batch.sequence_number,
+            "Verified batch changed or missing; falling back to full hash chain verification"
+        );
+        return verify_chain(storage).await;
+    }
+
+    let new_batches = &batches[1..];
+    if find_tampered_batch(storage, new_batches, from_batch.hash.clone())
+        .await?
+        .is_some()
+    {
+        warn!("Incremental hash chain verification failed; locating tampered batch with full verification");
+        return verify_chain(storage).await;
+    }
+
+    Ok(ChainVerificationResult {
+        valid: true,
+        batches_checked: new_batches.len() as i64,
+        tampered_batch: None,
+        message: None,
+    })
+}
+
+/// 検証方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationMode {
+    /// 前回検証済みのバッチ以降のみ
+    Incremental,
+    /// 全バッチ
+    Full,
+}
+
+/// メインDBのハッシュチェーンを検証し、成功した場合はチェックポイントを更新
+///
+/// チェックポイントには検証開始時点の最新バッチを保存する（検証中に追加されたバッチは次回に検証する）。
+/// 検証に失敗した場合はチェックポイントを更新しないため、次回の差分検証も全走査で改ざんを検出する。
+pub async fn verify_chain_with_checkpoint(
+    storage: &AuditLogStorage,
+    mode: VerificationMode,
+) -> RouterResult<ChainVerificationResult> {
+    let latest = storage.get_latest_batch_hash().await?;
+    let result = match mode {
+        VerificationMode::Incremental => {
+            let checkpoint = storage.get_chain_checkpoint().await?;
+            verify_chain_incremental(storage, checkpoint.as_ref()).await?
+        }
+        VerificationMode::Full => verify_chain(storage).await?,
+    };
+
+    if result.valid {
+        if let Some(latest) = latest {
+            storage
+                .save_chain_checkpoint(&ChainCheckpoint {
+                    sequence_number: latest.sequence_number,
+                    hash: latest.hash,
+                })
+                .await?;
+        }
+    }
+    Ok(result)
+}
+
+/// 連続するバッチを `expected_previous_hash` から順に検証し、改ざんを検出した場合はその結果を返す
+async fn find_tampered_batch(
+    storage: &AuditLogStorage,
+    batches: &[AuditBatchHash],
+    mut expected_previous_hash: String,
+) -> RouterResult<Option<ChainVerificationResult>> {
+    for batch in batches {
         // 前バッチハッシュの整合性チェック
         if batch.previous_hash != expected_previous_hash {
             warn!(
@@ -111,7 +214,7 @@ pub async fn verify_chain(storage: &AuditLogStorage) -> RouterResult<ChainVerifi
                 actual = %batch.previous_hash,
                 "Hash chain broken: previous_hash mismatch"
             );
-            return Ok(ChainVerificationResult {
+            return Ok(Some(ChainVerificationResult {
                 valid: false,
                 batches_checked: batch.sequence_number,
                 tampered_batch: Some(batch.sequence_number),
@@ -119,7 +222,7 @@ pub async fn verify_chain(storage: &AuditLogStorage) -> RouterResult<ChainVerifi
                     "Previous hash mismatch at batch {}",
                     batch.sequence_number
                 )),
-            });
+            }));
         }
 
         // バッチ内エントリを取得してハッシュを再計算
@@ -142,7 +245,7 @@ pub async fn verify_chain(storage: &AuditLogStorage) -> RouterResult<ChainVerifi
                 recomputed = %recomputed,
                 "Hash chain broken: batch hash mismatch"
             );
-            return Ok(ChainVerificationResult {
+            return Ok(Some(ChainVerificationResult {
                 valid: false,
                 batches_checked: batch.sequence_number,
                 tampered_batch: Some(batch.sequence_number),
@@ -150,18 +253,13 @@ pub async fn verify_chain(storage: &AuditLogStorage) -> RouterResult<ChainVerifi
                     "Batch hash mismatch at batch {}",
                     batch.sequence_number
                 )),
-            });
+            }));
         }
 
         expected_previous_hash = batch.hash.clone();
     }
 
-    Ok(ChainVerificationResult {
-        valid: true,
-        batches_checked: batches.len() as i64,
-        tampered_batch: None,
-        message: None,
-    })
+    Ok(None)
 }
 
 #[cfg(test)]
@@ -359,4 +457,130 @@ mod tests {
         assert!(!result.valid);
         assert_eq!(result.tampered_batch, Some(1));
     }
+
+    /// 未割当のエントリを1件追加し、正しいハッシュのバッチとして登録する
+    async fn append_valid_batch(
+        storage: &AuditLogStorage,
+        sequence_number: i64,
+        previous_hash: &str,
+    ) -> String {
+        storage
+            .insert_batch(&[create_test_entry("/api/test")])
+            .await
+            .unwrap();
+        let entries = storage.get_unbatched_entries().await.unwrap();
+        let now = Utc::now();
+        let hash = compute_batch_hash(
+            previous_hash,
+            sequence_number,
+            &now,
+            &now,
+            entries.len() as i64,
+            &entries,
+        );
+        let batch_id = storage
+            .insert_batch_hash(&AuditBatchHash {
+                id: None,
+                sequence_number,
+                batch_start: now,
+                batch_end: now,
+                record_count: entries.len() as i64,
+                hash: hash.clone(),
+                previous_hash: previous_hash.to_string(),
+            })
+            .await
+            .unwrap();
+        let entry_ids: Vec<i64> = entries.iter().filter_map(|e| e.id).collect();
+        storage
+            .update_entries_batch_id(&entry_ids, batch_id)
+            .await
+            .unwrap();
+        hash
+    }
+
+    #[tokio::test]
+    async fn test_incremental_verification_checks_only_new_batches() {
+        let pool = create_test_pool().await;
+        let storage = AuditLogStorage::new(pool);
+
+        let hash1 = append_valid_batch(&storage, 1, GENESIS_HASH).await;
+        let hash2 = append_valid_batch(&storage, 2, &hash1).await;
+
+        // チェックポイントが無い初回は全走査
+        let first = verify_chain_with_checkpoint(&storage, VerificationMode::Incremental)
+            .await
+            .unwrap();
+        assert!(first.valid);
+        assert_eq!(first.batches_checked, 2);
+        assert_eq!(
+            storage.get_chain_checkpoint().await.unwrap(),
+            Some(ChainCheckpoint {
+                sequence_number: 2,
+                hash: hash2.clone(),
+            })
+        );
+
+        let hash3 = append_valid_batch(&storage, 3, &hash2).await;
+        let second = verify_chain_with_checkpoint(&storage, VerificationMode::Incremental)
+            .await
+            .unwrap();
+        assert!(second.valid);
+        assert_eq!(second.batches_checked, 1);
+        assert_eq!(
+            storage.get_chain_checkpoint().await.unwrap().unwrap().hash,
+            hash3
+        );
+
+        // 新しいバッチが無ければ検証対象は0件
+        let third = verify_chain_with_checkpoint(&storage, VerificationMode::Incremental)
+            .await
+            .unwrap();
+        assert!(third.valid);
+        assert_eq!(third.batches_checked, 0);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_verification_falls_back_to_full_scan_on_tampering() {
+        let pool = create_test_pool().await;
+        let storage = AuditLogStorage::new(pool.clone());
+
+        let hash1 = append_valid_batch(&storage, 1, GENESIS_HASH).await;
+        append_valid_batch(&storage, 2, &hash1).await;
+        let checkpoint = ChainCheckpoint {
+            sequence_number: 1,
+            hash: hash1.clone(),
+        };
+        storage.save_chain_checkpoint(&checkpoint).await.unwrap();
+
+        // 差分（バッチ2）のエントリを改ざん
+        sqlx::query(
+            "UPDATE audit_log_entries SET request_path = '/api/forged' \
+             WHERE batch_id = (SELECT id FROM audit_batch_hashes WHERE sequence_number = 2)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let result = verify_chain_with_checkpoint(&storage, VerificationMode::Incremental)
+            .await
+            .unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.tampered_batch, Some(2));
+        // 失敗時はチェックポイントを進めない
+        assert_eq!(
+            storage.get_chain_checkpoint().await.unwrap(),
+            Some(checkpoint.clone())
+        );
+
+        // 検証済みバッチのハッシュが書き換えられた場合も全走査で特定する
+        sqlx::query("UPDATE audit_batch_hashes SET hash = ? WHERE sequence_number = 1")
+            .bind("f".repeat(64))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let result = verify_chain_incremental(&storage, Some(&checkpoint))
+            .await
+            .unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.tampered_batch, Some(1));
+    }
 }
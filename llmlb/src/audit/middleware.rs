@@ -3,7 +3,7 @@
 //! 全HTTPリクエストのメタデータを自動記録する。
 //! WebSocket・静的アセット・ヘルスチェック等のノイズパスは除外。
 
-use crate::audit::types::{ActorType, AuditLogEntry, AuthFailureInfo, TokenUsage};
+use crate::audit::types::{ActorType, AuditDetail, AuditLogEntry, AuthFailureInfo, TokenUsage};
 use crate::auth::middleware::ApiKeyAuthContext;
 use crate::common::auth::Claims;
 use crate::AppState;
@@ -92,14 +92,21 @@ pub async fn audit_middleware(
     // response extensionsから認証失敗情報を取得
     let auth_failure = response.extensions().get::<AuthFailureInfo>().cloned();
 
-    // 認証失敗の場合はdetailに理由を記録
-    let detail = auth_failure.map(|info| {
-        serde_json::json!({
-            "auth_failure_reason": info.reason,
-            "attempted_username": info.attempted_username,
+    // 認証失敗の場合はdetailに理由を記録し、それ以外はハンドラーが設定した追加情報を記録
+    let detail = auth_failure
+        .map(|info| {
+            serde_json::json!({
+                "auth_failure_reason": info.reason,
+                "attempted_username": info.attempted_username,
+            })
+            .to_string()
         })
-        .to_string()
-    });
+        .or_else(|| {
+            response
+                .extensions()
+                .get::<AuditDetail>()
+                .map(|d| d.0.to_string())
+        });
 
     trace!(
         method = %method,
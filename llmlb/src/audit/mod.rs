@@ -13,3 +13,6 @@ pub mod middleware;
 
 /// SHA-256バッチハッシュチェーン（改ざん検知）
 pub mod hash_chain;
+
+/// エクスポート形式（NDJSON / CSV）
+pub mod export;
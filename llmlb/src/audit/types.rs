@@ -104,6 +104,15 @@ pub struct AuditBatchHash {
     pub previous_hash: String,
 }
 
+/// ハッシュチェーン検証のチェックポイント（前回検証に成功した最終バッチ）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainCheckpoint {
+    /// バッチ連番
+    pub sequence_number: i64,
+    /// 検証時点のバッチハッシュ
+    pub hash: String,
+}
+
 /// 監査ログフィルタ
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AuditLogFilter {
@@ -157,6 +166,12 @@ pub struct AuthFailureInfo {
     pub reason: String,
 }
 
+/// 監査ログdetailに記録する追加情報（ハンドラーから監査ミドルウェアへの受け渡し用）
+///
+/// 機密情報（シークレット・パスワード等）は含めないこと。
+#[derive(Debug, Clone)]
+pub struct AuditDetail(pub serde_json::Value);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,6 +2,11 @@
 //!
 //! mpscチャネルでエントリを受信し、定期的にDBへ一括書き込みする。
 //! バッチ間隔ごとにSHA-256ハッシュチェーンのバッチを生成する。
+//!
+//! ミラー（セカンダリDB）を指定した場合はプライマリと同じエントリを両方へ書き込む。
+//! 片方への書き込みが失敗しても他方への書き込みは継続し、失敗したエントリは
+//! その書き込み先の未反映キューに残して次回以降のフラッシュで再送する（リコンサイル）。
+//! ハッシュチェーンは書き込み先ごとに独立して生成するため、それぞれ単独で検証できる。
 
 use crate::audit::hash_chain;
 use crate::audit::types::{AuditBatchHash, AuditLogEntry};
@@ -49,12 +54,46 @@ pub struct AuditLogWriter {
     sender: mpsc::Sender<AuditLogEntry>,
 }
 
+/// 書き込み先（プライマリ/ミラー）ごとの状態
+struct FlushTarget {
+    /// ログ用の名前
+    name: &'static str,
+    storage: AuditLogStorage,
+    /// 書き込みに失敗し、まだ反映できていないエントリ
+    pending: VecDeque<AuditLogEntry>,
+}
+
+impl FlushTarget {
+    fn new(name: &'static str, storage: AuditLogStorage) -> Self {
+        Self {
+            name,
+            storage,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
 impl AuditLogWriter {
     /// 新しいAuditLogWriterを作成し、バックグラウンドタスクを起動
     pub fn new(storage: AuditLogStorage, config: AuditLogWriterConfig) -> Self {
+        Self::with_mirror(storage, None, config)
+    }
+
+    /// ミラー（セカンダリDB）付きでAuditLogWriterを作成し、バックグラウンドタスクを起動
+    ///
+    /// `mirror` が `None` の場合は [`AuditLogWriter::new`] と同じ。
+    pub fn with_mirror(
+        storage: AuditLogStorage,
+        mirror: Option<AuditLogStorage>,
+        config: AuditLogWriterConfig,
+    ) -> Self {
         let (tx, rx) = mpsc::channel(config.buffer_capacity);
 
-        tokio::spawn(Self::background_task(rx, storage, config));
+        let mut targets = vec![FlushTarget::new("primary", storage)];
+        if let Some(mirror) = mirror {
+            targets.push(FlushTarget::new("mirror", mirror));
+        }
+        tokio::spawn(Self::background_task(rx, targets, config));
 
         Self { sender: tx }
     }
@@ -69,7 +108,7 @@ impl AuditLogWriter {
     /// バックグラウンドフラッシュタスク
     async fn background_task(
         mut rx: mpsc::Receiver<AuditLogEntry>,
-        storage: AuditLogStorage,
+        mut targets: Vec<FlushTarget>,
         config: AuditLogWriterConfig,
     ) {
         let mut buffer = VecDeque::with_capacity(config.buffer_capacity);
@@ -85,9 +124,10 @@ impl AuditLogWriter {
             tokio::select! {
                 // フラッシュ間隔
                 _ = interval.tick() => {
-                    if !buffer.is_empty() {
+                    let has_pending = targets.iter().any(|t| !t.pending.is_empty());
+                    if !buffer.is_empty() || has_pending {
                         let should_create_batch = last_batch_time.elapsed() >= batch_interval;
-                        Self::flush_buffer(&mut buffer, &storage, should_create_batch).await;
+                        Self::flush_buffer(&mut buffer, &mut targets, should_create_batch, config.buffer_capacity).await;
                         if should_create_batch {
                             last_batch_time = tokio::time::Instant::now();
                         }
@@ -112,7 +152,14 @@ impl AuditLogWriter {
                             // チャネルが閉じられた → 残りをフラッシュして終了
                             if !buffer.is_empty() {
                                 info!("Audit log writer shutting down, flushing {} remaining entries", buffer.len());
-                                Self::flush_buffer(&mut buffer, &storage, true).await;
+                                Self::flush_buffer(&mut buffer, &mut targets, true, config.buffer_capacity).await;
+                            }
+                            for target in targets.iter().filter(|t| !t.pending.is_empty()) {
+                                warn!(
+                                    target = target.name,
+                                    "Audit log writer stopped with {} entries not written",
+                                    target.pending.len()
+                                );
                             }
                             info!("Audit log writer background task stopped");
                             return;
@@ -123,32 +170,57 @@ impl AuditLogWriter {
         }
     }
 
-    /// バッファ内エントリをDBに一括書き込み
+    /// バッファ内エントリを全書き込み先のDBに一括書き込み
     ///
-    /// `create_batch`がtrueの場合、バッチハッシュを生成してハッシュチェーンに組み込む
+    /// 書き込み先ごとに未反映キューのエントリを先に書き込み、失敗した場合は
+    /// その書き込み先の未反映キューに残す（上限 `capacity` 件、超過分は古い順に破棄）。
+    /// `create_batch`がtrueの場合、書き込みに成功した書き込み先ごとに
+    /// バッチハッシュを生成してハッシュチェーンに組み込む
     async fn flush_buffer(
         buffer: &mut VecDeque<AuditLogEntry>,
-        storage: &AuditLogStorage,
+        targets: &mut [FlushTarget],
         create_batch: bool,
+        capacity: usize,
     ) {
         let entries: Vec<AuditLogEntry> = buffer.drain(..).collect();
-        let count = entries.len();
 
-        // まずエントリをDB挿入（batch_id=NULL）
-        if let Err(e) = storage.insert_batch(&entries).await {
-            warn!(
-                "Failed to flush audit log entries: {}. {} entries lost.",
-                e, count
-            );
-            return;
-        }
+        for target in targets.iter_mut() {
+            let mut pending: Vec<AuditLogEntry> = target.pending.drain(..).collect();
+            let retried = pending.len();
+            pending.extend(entries.iter().cloned());
+            let count = pending.len();
+
+            // まずエントリをDB挿入（batch_id=NULL）
+            if let Err(e) = target.storage.insert_batch(&pending).await {
+                target.pending = pending.into();
+                let dropped = target.pending.len().saturating_sub(capacity);
+                target.pending.drain(..dropped);
+                warn!(
+                    target = target.name,
+                    "Failed to flush audit log entries: {}. {} entries kept for retry, {} entries lost.",
+                    e,
+                    target.pending.len(),
+                    dropped
+                );
+                continue;
+            }
 
-        info!("Flushed {} audit log entries to database", count);
+            if retried > 0 {
+                info!(
+                    target = target.name,
+                    "Reconciled {} previously failed audit log entries", retried
+                );
+            }
+            info!(
+                target = target.name,
+                "Flushed {} audit log entries to database", count
+            );
 
-        // バッチ作成が必要な場合、未割当エントリをまとめてバッチ化
-        if create_batch {
-            if let Err(e) = Self::create_batch_hash(storage).await {
-                warn!("Failed to create batch hash: {}", e);
+            // バッチ作成が必要な場合、未割当エントリをまとめてバッチ化
+            if create_batch {
+                if let Err(e) = Self::create_batch_hash(&target.storage).await {
+                    warn!(target = target.name, "Failed to create batch hash: {}", e);
+                }
             }
         }
     }
@@ -426,4 +498,53 @@ mod tests {
         assert!(result.valid, "Hash chain should be valid");
         assert_eq!(result.batches_checked, 1);
     }
+
+    #[tokio::test]
+    async fn test_mirror_failure_is_reconciled_with_independent_chains() {
+        let pool = create_test_pool().await;
+        let primary = AuditLogStorage::new(pool.clone());
+        let dir = tempfile::tempdir().unwrap();
+        let mirror_path = dir.path().join("audit_mirror.db");
+        let mirror_path = mirror_path.to_string_lossy().to_string();
+
+        // テーブル未作成のミラー → 書き込みに失敗する
+        let broken_pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(&format!("sqlite:{}?mode=rwc", mirror_path))
+            .await
+            .unwrap();
+        let mut targets = vec![
+            FlushTarget::new("primary", primary.clone()),
+            FlushTarget::new("mirror", AuditLogStorage::new(broken_pool.clone())),
+        ];
+        let mut buffer: VecDeque<AuditLogEntry> = vec![create_test_entry("/api/mirror-1")].into();
+        AuditLogWriter::flush_buffer(&mut buffer, &mut targets, true, 100).await;
+
+        // プライマリへの書き込みは継続し、ミラー分は未反映キューに残る
+        assert_eq!(primary.get_all_batch_hashes().await.unwrap().len(), 1);
+        assert_eq!(targets[1].pending.len(), 1);
+
+        // ミラーが復旧したら次回のフラッシュで未反映分も書き込む
+        broken_pool.close().await;
+        let mirror = AuditLogStorage::new(
+            crate::db::audit_log::create_archive_pool(&mirror_path)
+                .await
+                .unwrap(),
+        );
+        targets[1].storage = mirror.clone();
+        let mut buffer: VecDeque<AuditLogEntry> = vec![create_test_entry("/api/mirror-2")].into();
+        AuditLogWriter::flush_buffer(&mut buffer, &mut targets, true, 100).await;
+        assert!(targets[1].pending.is_empty());
+
+        let mirrored = mirror.query(&AuditLogFilter::default()).await.unwrap();
+        assert_eq!(mirrored.len(), 2);
+
+        // ハッシュチェーンは書き込み先ごとに独立して検証できる
+        assert_eq!(primary.get_all_batch_hashes().await.unwrap().len(), 2);
+        assert_eq!(mirror.get_all_batch_hashes().await.unwrap().len(), 1);
+        for storage in [&primary, &mirror] {
+            let result = hash_chain::verify_chain(storage).await.unwrap();
+            assert!(result.valid, "Hash chain should be valid");
+        }
+    }
 }
@@ -102,6 +102,31 @@ pub fn verify_jwt(token: &str, secret: &str) -> Result<Claims, LbError> {
     .map_err(|e| LbError::Jwt(format!("Failed to verify JWT: {}", e)))
 }
 
+/// ローテーション猶予中の旧シークレットも含めてJWTを検証
+///
+/// 現行シークレットから順に試し、いずれかで検証できればそのクレームを返す。
+/// キーリング未初期化時は `fallback_secret` のみで検証する。
+///
+/// # Arguments
+/// * `token` - 検証するJWTトークン
+/// * `fallback_secret` - キーリング未初期化時に使うJWTシークレットキー
+///
+/// # Returns
+/// * `Ok(Claims)` - 検証済みクレーム
+/// * `Err(LbError)` - 全シークレットで検証失敗（エラーは現行シークレットでの結果）
+pub fn verify_jwt_with_rotation(token: &str, fallback_secret: &str) -> Result<Claims, LbError> {
+    let mut first_err = None;
+    for secret in crate::jwt_secret::verification_secrets(fallback_secret) {
+        match verify_jwt(token, &secret) {
+            Ok(claims) => return Ok(claims),
+            Err(e) => {
+                first_err.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_err.unwrap_or_else(|| LbError::Jwt("No JWT secret configured".to_string())))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
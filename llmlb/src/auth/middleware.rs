@@ -274,6 +274,11 @@ async fn authenticate_api_key(
         })?
         .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Invalid API key".to_string()).into_response())?;
 
+    // 失効済みのキーは即座に拒否する（キャッシュせず毎回DBの状態で判定）
+    if api_key_record.revoked_at.is_some() {
+        return Err((StatusCode::UNAUTHORIZED, "API key revoked".to_string()).into_response());
+    }
+
     if let Some(expires_at) = api_key_record.expires_at {
         if expires_at < chrono::Utc::now() {
             return Err((StatusCode::UNAUTHORIZED, "API key expired".to_string()).into_response());
@@ -364,7 +369,7 @@ pub async fn jwt_auth_middleware(
     };
 
     // JWTを検証
-    let claims = crate::auth::jwt::verify_jwt(&token, &jwt_secret).map_err(|e| {
+    let claims = crate::auth::jwt::verify_jwt_with_rotation(&token, &jwt_secret).map_err(|e| {
         tracing::warn!("JWT verification failed: {}", e);
         (StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e)).into_response()
     })?;
@@ -629,7 +634,7 @@ pub async fn require_anthropic_api_key_permission_middleware(
 /// JWTまたはAPIキー(permissions)で認証し、必要な権限を満たすことを要求するミドルウェア。
 ///
 /// - JWTが存在する場合はJWTを優先（Authorization Bearer / Cookie）。
-/// - APIキーは `X-API-Key` または `Authorization: Bearer sk_...` を許可。
+/// - APIキーは `X-API-Key` または `Authorization: Bearer llmlb_sk_...`（旧形式 `sk_...`）を許可。
 ///
 /// NOTE:
 /// - `jwt_required_role` が `Some(Admin)` の場合、JWTはadminのみ許可。
@@ -655,10 +660,11 @@ pub async fn jwt_or_api_key_permission_middleware(
     // JWTがあれば優先
     if let Some(token) = extract_jwt_from_headers(request.headers()) {
         let claims =
-            crate::auth::jwt::verify_jwt(&token, &config.app_state.jwt_secret).map_err(|e| {
-                tracing::warn!("JWT verification failed: {}", e);
-                (StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e)).into_response()
-            })?;
+            crate::auth::jwt::verify_jwt_with_rotation(&token, &config.app_state.jwt_secret)
+                .map_err(|e| {
+                    tracing::warn!("JWT verification failed: {}", e);
+                    (StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e)).into_response()
+                })?;
 
         if let Some(required_role) = config.jwt_required_role {
             if claims.role != required_role {
@@ -15,6 +15,12 @@ pub mod middleware;
 /// 初回起動時の管理者アカウント作成
 pub mod bootstrap;
 
+/// nonce によるリクエストの再生防止
+pub mod nonce;
+
+/// 認証主体ごとのレート制限（per APIキー / per IP）
+pub mod rate_limit;
+
 /// ダッシュボードJWT Cookie名
 pub const DASHBOARD_JWT_COOKIE: &str = "llmlb_jwt";
 /// ダッシュボードCSRF Cookie名
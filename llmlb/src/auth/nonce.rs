@@ -0,0 +1,548 @@
+//! nonce によるリクエストの再生防止
+//!
+//! 指定したAPIキー（`LLMLB_NONCE_API_KEYS`）またはスコープ（`LLMLB_NONCE_SCOPES`）の
+//! リクエストに `X-LLMLB-Nonce`・`X-LLMLB-Timestamp`・`X-LLMLB-Signature` ヘッダを必須とする。
+//! 署名はAPIキーを鍵とした `"{timestamp}.{nonce}"` の HMAC-SHA256（16進小文字）で、
+//! タイムスタンプ（UNIX秒）が現在時刻から `LLMLB_NONCE_TTL_SECS` 以上ずれたリクエストと、
+//! 受付期間内に同じ nonce を再利用したリクエストを 401 で拒否する。
+//! 対象外のAPIキーではヘッダを無視する。
+//!
+//! nonce はAPIキーごとに受付期間が終わるまでメモリにのみ保持し（再起動でリセット）、
+//! 1キーあたり `LLMLB_NONCE_MAX_PER_KEY` 件を超える場合は 429 で拒否する。
+//! 期限切れの nonce はバックグラウンドタスクで定期的に削除する。
+
+use crate::audit::types::AuditDetail;
+use crate::auth::middleware::ApiKeyAuthContext;
+use crate::common::auth::ApiKeyScope;
+use axum::{
+    extract::Request,
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use once_cell::sync::Lazy;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// nonce を指定するヘッダ名
+pub const NONCE_HEADER: &str = "x-llmlb-nonce";
+
+/// リクエスト時刻（UNIX秒）を指定するヘッダ名
+pub const TIMESTAMP_HEADER: &str = "x-llmlb-timestamp";
+
+/// タイムスタンプと nonce の署名を指定するヘッダ名
+pub const SIGNATURE_HEADER: &str = "x-llmlb-signature";
+
+/// nonce の最小長
+pub const NONCE_MIN_LEN: usize = 8;
+
+/// nonce の最大長
+pub const NONCE_MAX_LEN: usize = 128;
+
+/// プロセス全体の nonce ストア
+static NONCE_STORE: Lazy<NonceStore> = Lazy::new(|| {
+    NonceStore::new(
+        NoncePolicy {
+            api_keys: crate::config::nonce_api_keys(),
+            scopes: crate::config::nonce_scopes(),
+        },
+        crate::config::nonce_ttl(),
+        crate::config::nonce_max_per_key(),
+    )
+});
+
+/// プロセス全体の nonce ストアを取得
+pub fn nonce_store() -> &'static NonceStore {
+    &NONCE_STORE
+}
+
+/// nonce を必須にするAPIキーの条件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoncePolicy {
+    /// 対象のAPIキーID
+    pub api_keys: Vec<Uuid>,
+    /// 対象のスコープ（権限がプリセットと一致するAPIキー）
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+impl NoncePolicy {
+    /// いずれかのAPIキーで nonce が必須か
+    pub fn is_enabled(&self) -> bool {
+        !self.api_keys.is_empty() || !self.scopes.is_empty()
+    }
+
+    /// APIキーに nonce が必須か
+    pub fn requires_nonce(&self, api_key: &ApiKeyAuthContext) -> bool {
+        self.api_keys.contains(&api_key.id)
+            || ApiKeyScope::from_permissions(&api_key.permissions)
+                .is_some_and(|scope| self.scopes.contains(&scope))
+    }
+}
+
+/// 検証対象のリクエストヘッダ
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SignedNonce<'a> {
+    /// `X-LLMLB-Nonce`
+    pub nonce: Option<&'a str>,
+    /// `X-LLMLB-Timestamp`
+    pub timestamp: Option<&'a str>,
+    /// `X-LLMLB-Signature`
+    pub signature: Option<&'a str>,
+}
+
+impl<'a> SignedNonce<'a> {
+    fn from_headers(headers: &'a HeaderMap) -> Self {
+        let get = move |name: &str| {
+            headers
+                .get(name)
+                .map(|value| value.to_str().unwrap_or_default())
+        };
+        Self {
+            nonce: get(NONCE_HEADER),
+            timestamp: get(TIMESTAMP_HEADER),
+            signature: get(SIGNATURE_HEADER),
+        }
+    }
+}
+
+/// nonce の検証エラー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceError {
+    /// ヘッダが無い
+    Missing,
+    /// 長さ・文字種が不正
+    Invalid,
+    /// タイムスタンプが受付期間外
+    Expired,
+    /// 署名が一致しない
+    BadSignature,
+    /// 受付期間内に使用済み
+    Reused,
+    /// APIキーごとの保持上限に達した
+    TooMany,
+}
+
+impl NonceError {
+    /// 監査ログ用の理由
+    pub fn reason(self) -> &'static str {
+        match self {
+            NonceError::Missing => "missing",
+            NonceError::Invalid => "invalid",
+            NonceError::Expired => "expired",
+            NonceError::BadSignature => "bad_signature",
+            NonceError::Reused => "reused",
+            NonceError::TooMany => "too_many",
+        }
+    }
+
+    /// クライアントに返すステータス
+    pub fn status(self) -> StatusCode {
+        match self {
+            NonceError::TooMany => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    /// クライアントに返すメッセージ
+    pub fn message(self) -> String {
+        match self {
+            NonceError::Missing => {
+                "Missing X-LLMLB-Nonce, X-LLMLB-Timestamp or X-LLMLB-Signature header".to_string()
+            }
+            NonceError::Invalid => format!(
+                "Invalid X-LLMLB-Nonce header (expected {}-{} visible ASCII characters)",
+                NONCE_MIN_LEN, NONCE_MAX_LEN
+            ),
+            NonceError::Expired => {
+                "X-LLMLB-Timestamp is missing, malformed or outside the accepted window".to_string()
+            }
+            NonceError::BadSignature => "Invalid X-LLMLB-Signature header".to_string(),
+            NonceError::Reused => "Nonce has already been used".to_string(),
+            NonceError::TooMany => "Too many outstanding nonces for this API key".to_string(),
+        }
+    }
+}
+
+/// `"{timestamp}.{nonce}"` をAPIキーで署名する（HMAC-SHA256、16進小文字）
+pub fn sign(api_key: &str, timestamp: u64, nonce: &str) -> String {
+    hmac_sha256(
+        api_key.as_bytes(),
+        format!("{timestamp}.{nonce}").as_bytes(),
+    )
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> String {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(Sha256::digest(key).as_slice());
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// 長さに依存しない時間で文字列を比較する
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// 受付期間付きの使用済み nonce ストア
+#[derive(Debug)]
+pub struct NonceStore {
+    policy: NoncePolicy,
+    ttl: Duration,
+    max_per_key: usize,
+    /// APIキーID → (nonce → 受付期間の終了時刻（UNIX秒）)
+    used: Mutex<HashMap<Uuid, HashMap<String, u64>>>,
+}
+
+impl NonceStore {
+    /// 条件・受付期間・APIキーごとの保持上限を指定してストアを作成
+    pub fn new(policy: NoncePolicy, ttl: Duration, max_per_key: usize) -> Self {
+        Self {
+            policy,
+            ttl,
+            max_per_key,
+            used: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// nonce を必須にする条件
+    pub fn policy(&self) -> &NoncePolicy {
+        &self.policy
+    }
+
+    /// タイムスタンプの受付期間（現在時刻からの前後のずれ）
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// 署名付き nonce を検証し、未使用なら使用済みとして記録する
+    pub fn check_and_record(
+        &self,
+        api_key_id: Uuid,
+        api_key: &str,
+        signed: SignedNonce<'_>,
+    ) -> Result<(), NonceError> {
+        self.check_and_record_at(api_key_id, api_key, signed, unix_now())
+    }
+
+    fn check_and_record_at(
+        &self,
+        api_key_id: Uuid,
+        api_key: &str,
+        signed: SignedNonce<'_>,
+        now: u64,
+    ) -> Result<(), NonceError> {
+        let (Some(nonce), Some(timestamp), Some(signature)) =
+            (signed.nonce, signed.timestamp, signed.signature)
+        else {
+            return Err(NonceError::Missing);
+        };
+        let valid = (NONCE_MIN_LEN..=NONCE_MAX_LEN).contains(&nonce.len())
+            && nonce.bytes().all(|b| b.is_ascii_graphic());
+        if !valid {
+            return Err(NonceError::Invalid);
+        }
+        let ttl = self.ttl.as_secs();
+        let timestamp = timestamp
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .filter(|timestamp| timestamp.abs_diff(now) < ttl)
+            .ok_or(NonceError::Expired)?;
+        let expected = sign(api_key, timestamp, nonce);
+        if !constant_time_eq(
+            expected.as_bytes(),
+            signature.trim().to_ascii_lowercase().as_bytes(),
+        ) {
+            return Err(NonceError::BadSignature);
+        }
+
+        // タイムスタンプの受付期間が終わるまでは同じ nonce を拒否する
+        let expires_at = timestamp.saturating_add(ttl);
+        let mut used = self.used.lock().unwrap_or_else(|e| e.into_inner());
+        let nonces = used.entry(api_key_id).or_default();
+        if nonces
+            .get(nonce)
+            .is_some_and(|expires_at| now <= *expires_at)
+        {
+            return Err(NonceError::Reused);
+        }
+        if nonces.len() >= self.max_per_key {
+            nonces.retain(|_, expires_at| now <= *expires_at);
+            if nonces.len() >= self.max_per_key {
+                return Err(NonceError::TooMany);
+            }
+        }
+        nonces.insert(nonce.to_string(), expires_at);
+        Ok(())
+    }
+
+    /// 期限切れの nonce を削除し、削除件数を返す
+    pub fn cleanup(&self) -> usize {
+        self.cleanup_at(unix_now())
+    }
+
+    fn cleanup_at(&self, now: u64) -> usize {
+        let mut used = self.used.lock().unwrap_or_else(|e| e.into_inner());
+        let mut removed = 0;
+        used.retain(|_, nonces| {
+            let before = nonces.len();
+            nonces.retain(|_, expires_at| now <= *expires_at);
+            removed += before - nonces.len();
+            !nonces.is_empty()
+        });
+        removed
+    }
+}
+
+/// 期限切れ nonce の定期クリーンアップタスクを起動する（nonce が無効な場合は起動しない）
+pub fn spawn_nonce_cleanup_task() {
+    let store = nonce_store();
+    if !store.policy().is_enabled() {
+        return;
+    }
+    tracing::info!(
+        api_keys = store.policy().api_keys.len(),
+        scopes = ?store.policy().scopes,
+        ttl_secs = store.ttl().as_secs(),
+        max_per_key = store.max_per_key,
+        "Nonce replay protection enabled"
+    );
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(store.ttl());
+        interval.tick().await; // 最初のtickをスキップ
+        loop {
+            interval.tick().await;
+            let removed = store.cleanup();
+            if removed > 0 {
+                tracing::debug!(removed, "Removed expired nonces");
+            }
+        }
+    });
+}
+
+/// 認証に使われたAPIキー（`X-API-Key` または `Authorization: Bearer`）
+fn presented_api_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("X-API-Key")
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+        })
+}
+
+/// nonce 検証ミドルウェア
+///
+/// APIキー認証ミドルウェアより内側に配置する。対象のAPIキーで nonce・タイムスタンプ・署名が
+/// 無い・不正・期間外・使用済みの場合は 401（保持上限超過は 429）を返し、
+/// `AuditDetail` として監査ログに記録する。
+pub async fn nonce_middleware(request: Request, next: Next) -> Response {
+    let store = nonce_store();
+    let Some(api_key) = request
+        .extensions()
+        .get::<ApiKeyAuthContext>()
+        .filter(|ctx| store.policy().requires_nonce(ctx))
+    else {
+        return next.run(request).await;
+    };
+
+    let headers = request.headers();
+    let result = store.check_and_record(
+        api_key.id,
+        presented_api_key(headers).unwrap_or_default(),
+        SignedNonce::from_headers(headers),
+    );
+    if let Err(error) = result {
+        tracing::warn!(
+            api_key_id = %api_key.id,
+            path = %request.uri().path(),
+            reason = error.reason(),
+            "Rejected request: nonce check failed"
+        );
+        let mut response = (error.status(), error.message()).into_response();
+        response.extensions_mut().insert(AuditDetail(json!({
+            "event": "nonce_rejected",
+            "reason": error.reason(),
+        })));
+        return response;
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "llmlb_sk_test";
+
+    fn api_key(id: Uuid, scope: ApiKeyScope) -> ApiKeyAuthContext {
+        ApiKeyAuthContext {
+            id,
+            created_by: Uuid::new_v4(),
+            permissions: scope.permissions(),
+            expires_at: None,
+        }
+    }
+
+    fn check(
+        store: &NonceStore,
+        key_id: Uuid,
+        nonce: &str,
+        timestamp: u64,
+        now: u64,
+    ) -> Result<(), NonceError> {
+        let timestamp_header = timestamp.to_string();
+        let signature = sign(KEY, timestamp, nonce);
+        store.check_and_record_at(
+            key_id,
+            KEY,
+            SignedNonce {
+                nonce: Some(nonce),
+                timestamp: Some(&timestamp_header),
+                signature: Some(&signature),
+            },
+            now,
+        )
+    }
+
+    #[test]
+    fn policy_applies_to_listed_keys_and_scopes() {
+        let listed = Uuid::new_v4();
+        let policy = NoncePolicy {
+            api_keys: vec![listed],
+            scopes: vec![ApiKeyScope::Admin],
+        };
+        assert!(policy.is_enabled());
+        assert!(policy.requires_nonce(&api_key(listed, ApiKeyScope::Inference)));
+        assert!(policy.requires_nonce(&api_key(Uuid::new_v4(), ApiKeyScope::Admin)));
+        assert!(!policy.requires_nonce(&api_key(Uuid::new_v4(), ApiKeyScope::Inference)));
+        assert!(!NoncePolicy::default().is_enabled());
+    }
+
+    #[test]
+    fn hmac_matches_rfc4231_test_vectors() {
+        assert_eq!(
+            hmac_sha256(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // ブロック長を超える鍵はハッシュしてから使う
+        assert_eq!(
+            hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            ),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn reused_nonce_is_rejected_until_window_ends() {
+        let store = NonceStore::new(NoncePolicy::default(), Duration::from_secs(60), 100);
+        let key = Uuid::new_v4();
+        let start = 1_700_000_000;
+
+        assert_eq!(
+            store.check_and_record_at(key, KEY, SignedNonce::default(), start),
+            Err(NonceError::Missing)
+        );
+        assert_eq!(
+            check(&store, key, "short", start, start),
+            Err(NonceError::Invalid)
+        );
+        assert!(check(&store, key, "nonce-0001", start, start).is_ok());
+        assert_eq!(
+            check(&store, key, "nonce-0001", start, start + 59),
+            Err(NonceError::Reused)
+        );
+        // 別のAPIキーは独立に管理する
+        assert!(check(&store, Uuid::new_v4(), "nonce-0001", start, start).is_ok());
+
+        // 受付期間を過ぎたタイムスタンプは nonce の記録が消えた後も拒否する
+        let expired = start + 61;
+        assert_eq!(store.cleanup_at(expired), 2);
+        assert_eq!(
+            check(&store, key, "nonce-0001", start, expired),
+            Err(NonceError::Expired)
+        );
+        assert!(check(&store, key, "nonce-0001", expired, expired).is_ok());
+    }
+
+    #[test]
+    fn timestamp_and_signature_are_verified() {
+        let store = NonceStore::new(NoncePolicy::default(), Duration::from_secs(60), 100);
+        let key = Uuid::new_v4();
+        let now = 1_700_000_000;
+
+        assert_eq!(
+            check(&store, key, "nonce-0001", now + 60, now),
+            Err(NonceError::Expired)
+        );
+        assert_eq!(
+            check(&store, key, "nonce-0001", now - 60, now),
+            Err(NonceError::Expired)
+        );
+        let timestamp = now.to_string();
+        let forged = sign("another-key", now, "nonce-0001");
+        assert_eq!(
+            store.check_and_record_at(
+                key,
+                KEY,
+                SignedNonce {
+                    nonce: Some("nonce-0001"),
+                    timestamp: Some(&timestamp),
+                    signature: Some(&forged),
+                },
+                now,
+            ),
+            Err(NonceError::BadSignature)
+        );
+        assert!(check(&store, key, "nonce-0001", now - 59, now).is_ok());
+    }
+
+    #[test]
+    fn stored_nonces_are_capped_per_key() {
+        let store = NonceStore::new(NoncePolicy::default(), Duration::from_secs(60), 2);
+        let key = Uuid::new_v4();
+        let now = 1_700_000_000;
+
+        assert!(check(&store, key, "nonce-0001", now, now).is_ok());
+        assert!(check(&store, key, "nonce-0002", now, now).is_ok());
+        assert_eq!(
+            check(&store, key, "nonce-0003", now, now),
+            Err(NonceError::TooMany)
+        );
+        // 他のキーには影響しない
+        assert!(check(&store, Uuid::new_v4(), "nonce-0003", now, now).is_ok());
+        // 期限切れ分は上限判定の前に削除する
+        assert!(check(&store, key, "nonce-0003", now + 61, now + 61).is_ok());
+    }
+}
@@ -0,0 +1,392 @@
+//! 認証主体ごとのレート制限（per APIキー / per IP）
+//!
+//! トークンバケット方式で、APIキーごと・クライアントIPごとのリクエスト数（req/s）を制限する。
+//! バケットの容量は1秒分（最低1件）で、上限を超えたリクエストは 429 と `Retry-After`
+//! ヘッダで拒否し、監査ログに記録する。
+//!
+//! 上限は環境変数で設定する（`0` は無制限）。
+//! - `LLMLB_RATE_LIMIT_API_KEY_RPS`: APIキーごとの上限
+//! - `LLMLB_RATE_LIMIT_SCOPE_RPS`: スコープ別のAPIキー上限（例: `read-only=5,inference=20`）
+//! - `LLMLB_RATE_LIMIT_IP_RPS`: クライアントIPごとの上限
+//!
+//! クライアントIPは接続元アドレスを使う。転送ヘッダは接続元が `LLMLB_TRUSTED_PROXIES`
+//! に含まれる場合のみ参照する（[`crate::common::ip::resolve_client_ip`]）。
+//!
+//! 制限状態はメモリにのみ保持し、再起動でリセットされる。モデル別レートリミット
+//! （[`crate::balancer::model_rate_limit`]）とは独立に判定される。
+
+use crate::audit::types::AuditDetail;
+use crate::auth::middleware::ApiKeyAuthContext;
+use crate::common::auth::{ApiKeyPermission, ApiKeyScope};
+use axum::{extract::Request, middleware::Next, response::Response};
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// 満杯のバケットの掃除を行う追跡主体数の閾値
+const BUCKET_SWEEP_THRESHOLD: usize = 4096;
+
+/// プロセス全体の認証主体別レートリミッタ
+static AUTH_RATE_LIMITER: Lazy<AuthRateLimiter> =
+    Lazy::new(|| AuthRateLimiter::new(AuthRateLimitConfig::from_env()));
+
+/// プロセス全体の認証主体別レートリミッタを取得
+pub fn auth_rate_limiter() -> &'static AuthRateLimiter {
+    &AUTH_RATE_LIMITER
+}
+
+/// レート制限の上限設定（req/s、`0` 以下は無制限）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthRateLimitConfig {
+    /// APIキーごとの上限
+    pub api_key_rps: f64,
+    /// スコープ別のAPIキー上限（`api_key_rps` より優先）
+    pub scope_rps: Vec<(ApiKeyScope, f64)>,
+    /// クライアントIPごとの上限
+    pub ip_rps: f64,
+}
+
+impl AuthRateLimitConfig {
+    /// 環境変数から上限設定を読み込む
+    pub fn from_env() -> Self {
+        Self {
+            api_key_rps: crate::config::rate_limit_api_key_rps(),
+            scope_rps: crate::config::rate_limit_scope_rps(),
+            ip_rps: crate::config::rate_limit_ip_rps(),
+        }
+    }
+
+    /// APIキーの権限に対応する上限
+    pub fn api_key_limit(&self, permissions: &[ApiKeyPermission]) -> f64 {
+        ApiKeyScope::from_permissions(permissions)
+            .and_then(|scope| {
+                self.scope_rps
+                    .iter()
+                    .find(|(configured, _)| *configured == scope)
+                    .map(|(_, rps)| *rps)
+            })
+            .unwrap_or(self.api_key_rps)
+    }
+}
+
+/// レート制限の対象
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RateLimitSubject {
+    /// APIキー
+    ApiKey(Uuid),
+    /// クライアントIP
+    Ip(String),
+}
+
+impl RateLimitSubject {
+    /// 監査ログ・ログ出力用の種別名
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RateLimitSubject::ApiKey(_) => "api_key",
+            RateLimitSubject::Ip(_) => "ip",
+        }
+    }
+}
+
+/// 上限超過の内容
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimited {
+    /// 超過した対象
+    pub subject: RateLimitSubject,
+    /// 上限値（req/s）
+    pub limit_rps: f64,
+    /// 次の1件が許可されるまでの目安
+    pub retry_after: Duration,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    /// 補充レート（req/s）
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, now: Instant) -> Self {
+        Self {
+            rate,
+            tokens: Self::capacity(rate),
+            last_refill: now,
+        }
+    }
+
+    /// バースト上限（1秒分、最低1件）
+    fn capacity(rate: f64) -> f64 {
+        rate.max(1.0)
+    }
+
+    /// 経過時間分のトークンを補充する（上限設定の変更にも追従する）
+    fn refill(&mut self, rate: f64, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.rate = rate;
+        self.tokens = (self.tokens + elapsed * rate).min(Self::capacity(rate));
+        self.last_refill = now;
+    }
+
+    fn is_full(&self) -> bool {
+        self.tokens >= Self::capacity(self.rate)
+    }
+}
+
+/// 認証主体別のトークンバケット型レートリミッタ
+#[derive(Debug)]
+pub struct AuthRateLimiter {
+    config: AuthRateLimitConfig,
+    buckets: Mutex<HashMap<RateLimitSubject, TokenBucket>>,
+}
+
+impl AuthRateLimiter {
+    /// 上限設定を指定してリミッタを作成
+    pub fn new(config: AuthRateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// APIキー・クライアントIPの順に枠を確認し、両方に空きがあれば1件ずつ消費する
+    pub fn try_acquire(
+        &self,
+        api_key: Option<&ApiKeyAuthContext>,
+        client_ip: Option<&str>,
+    ) -> Result<(), RateLimited> {
+        self.try_acquire_at(api_key, client_ip, Instant::now())
+    }
+
+    fn try_acquire_at(
+        &self,
+        api_key: Option<&ApiKeyAuthContext>,
+        client_ip: Option<&str>,
+        now: Instant,
+    ) -> Result<(), RateLimited> {
+        let mut checks = Vec::with_capacity(2);
+        if let Some(ctx) = api_key {
+            let rps = self.config.api_key_limit(&ctx.permissions);
+            if rps > 0.0 {
+                checks.push((RateLimitSubject::ApiKey(ctx.id), rps));
+            }
+        }
+        if let Some(ip) = client_ip {
+            if self.config.ip_rps > 0.0 {
+                checks.push((RateLimitSubject::Ip(ip.to_string()), self.config.ip_rps));
+            }
+        }
+        if checks.is_empty() {
+            return Ok(());
+        }
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() > BUCKET_SWEEP_THRESHOLD {
+            // 満杯まで回復したバケットは初期状態と同じなので捨ててよい
+            buckets.retain(|_, bucket| {
+                bucket.refill(bucket.rate, now);
+                !bucket.is_full()
+            });
+        }
+
+        // いずれかで超過した場合はどちらの枠も消費しない
+        for (subject, rps) in &checks {
+            let bucket = buckets
+                .entry(subject.clone())
+                .or_insert_with(|| TokenBucket::new(*rps, now));
+            bucket.refill(*rps, now);
+            if bucket.tokens < 1.0 {
+                return Err(RateLimited {
+                    subject: subject.clone(),
+                    limit_rps: *rps,
+                    retry_after: Duration::from_secs_f64((1.0 - bucket.tokens) / rps),
+                });
+            }
+        }
+        for (subject, _) in &checks {
+            if let Some(bucket) = buckets.get_mut(subject) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 認証主体別レート制限ミドルウェア
+///
+/// APIキー認証ミドルウェアより内側に配置する。上限を超えたリクエストは 429 と
+/// `Retry-After` ヘッダで拒否し、`AuditDetail` として監査ログに記録する。
+pub async fn rate_limit_middleware(request: Request, next: Next) -> Response {
+    let ip = crate::common::ip::client_ip_from_request(&request).map(|ip| ip.to_string());
+    let result = auth_rate_limiter().try_acquire(
+        request.extensions().get::<ApiKeyAuthContext>(),
+        ip.as_deref(),
+    );
+    let Err(limited) = result else {
+        return next.run(request).await;
+    };
+
+    let path = request.uri().path();
+    let retry_after_secs = limited.retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    tracing::warn!(
+        path = %path,
+        limit = limited.subject.kind(),
+        limit_rps = limited.limit_rps,
+        client_ip = ?ip,
+        retry_after_secs,
+        "Rejected request: rate limit reached"
+    );
+    let message = match limited.subject {
+        RateLimitSubject::ApiKey(_) => format!(
+            "Rate limit exceeded for this API key ({} requests/sec)",
+            limited.limit_rps
+        ),
+        RateLimitSubject::Ip(_) => format!(
+            "Rate limit exceeded for this client ({} requests/sec)",
+            limited.limit_rps
+        ),
+    };
+    let mut response =
+        crate::api::model_rate_limit::limited_response(path, &message, retry_after_secs);
+    response.extensions_mut().insert(AuditDetail(json!({
+        "event": "rate_limited",
+        "limit": limited.subject.kind(),
+        "limit_rps": limited.limit_rps,
+        "retry_after_secs": retry_after_secs,
+    })));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_key(permissions: Vec<ApiKeyPermission>) -> ApiKeyAuthContext {
+        ApiKeyAuthContext {
+            id: Uuid::new_v4(),
+            created_by: Uuid::new_v4(),
+            permissions,
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn api_key_bucket_refills_over_time() {
+        let limiter = AuthRateLimiter::new(AuthRateLimitConfig {
+            api_key_rps: 2.0,
+            ..Default::default()
+        });
+        let key = api_key(ApiKeyScope::Inference.permissions());
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire_at(Some(&key), None, start).is_ok());
+        assert!(limiter.try_acquire_at(Some(&key), None, start).is_ok());
+        let limited = limiter.try_acquire_at(Some(&key), None, start).unwrap_err();
+        assert_eq!(limited.subject, RateLimitSubject::ApiKey(key.id));
+        assert_eq!(limited.retry_after, Duration::from_millis(500));
+
+        // 0.5秒で1件分回復する
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.try_acquire_at(Some(&key), None, later).is_ok());
+        assert!(limiter.try_acquire_at(Some(&key), None, later).is_err());
+
+        // 別のAPIキーは独立に数える
+        let other = api_key(ApiKeyScope::Inference.permissions());
+        assert!(limiter.try_acquire_at(Some(&other), None, later).is_ok());
+    }
+
+    #[test]
+    fn scope_limit_overrides_default_and_zero_disables() {
+        let config = AuthRateLimitConfig {
+            api_key_rps: 1.0,
+            scope_rps: vec![(ApiKeyScope::ReadOnly, 5.0), (ApiKeyScope::Admin, 0.0)],
+            ip_rps: 0.0,
+        };
+        assert_eq!(
+            config.api_key_limit(&ApiKeyScope::ReadOnly.permissions()),
+            5.0
+        );
+        assert_eq!(
+            config.api_key_limit(&ApiKeyScope::Inference.permissions()),
+            1.0
+        );
+        assert_eq!(config.api_key_limit(&[ApiKeyPermission::LogsRead]), 1.0);
+
+        let limiter = AuthRateLimiter::new(config);
+        let admin = api_key(ApiKeyScope::Admin.permissions());
+        let now = Instant::now();
+        for _ in 0..10 {
+            assert!(limiter.try_acquire_at(Some(&admin), None, now).is_ok());
+        }
+    }
+
+    #[test]
+    fn ip_limit_rejects_without_consuming_api_key_budget() {
+        let limiter = AuthRateLimiter::new(AuthRateLimitConfig {
+            api_key_rps: 1.0,
+            ip_rps: 1.0,
+            ..Default::default()
+        });
+        let first = api_key(ApiKeyScope::Inference.permissions());
+        let second = api_key(ApiKeyScope::Inference.permissions());
+        let now = Instant::now();
+
+        assert!(limiter
+            .try_acquire_at(Some(&first), Some("10.0.0.1"), now)
+            .is_ok());
+        let limited = limiter
+            .try_acquire_at(Some(&second), Some("10.0.0.1"), now)
+            .unwrap_err();
+        assert_eq!(
+            limited.subject,
+            RateLimitSubject::Ip("10.0.0.1".to_string())
+        );
+
+        // IPで拒否されたAPIキーの枠は消費されていない
+        assert!(limiter
+            .try_acquire_at(Some(&second), Some("10.0.0.2"), now)
+            .is_ok());
+    }
+
+    #[test]
+    fn spoofed_forwarded_headers_share_the_peer_ip_bucket() {
+        use axum::{body::Body, extract::ConnectInfo};
+        use std::net::SocketAddr;
+
+        let limiter = AuthRateLimiter::new(AuthRateLimitConfig {
+            ip_rps: 1.0,
+            ..Default::default()
+        });
+        let client_ip = |spoofed: &str| {
+            let mut request = Request::builder()
+                .header("x-forwarded-for", spoofed)
+                .header("x-real-ip", spoofed)
+                .body(Body::empty())
+                .unwrap();
+            request.extensions_mut().insert(ConnectInfo(
+                "198.51.100.7:5000".parse::<SocketAddr>().unwrap(),
+            ));
+            crate::common::ip::client_ip_from_request(&request).map(|ip| ip.to_string())
+        };
+        let now = Instant::now();
+
+        // 信頼済みプロキシ以外からの転送ヘッダは無視され、接続元IPで数える
+        let first = client_ip("203.0.113.1");
+        assert_eq!(first.as_deref(), Some("198.51.100.7"));
+        assert!(limiter.try_acquire_at(None, first.as_deref(), now).is_ok());
+        let second = client_ip("203.0.113.2");
+        let limited = limiter
+            .try_acquire_at(None, second.as_deref(), now)
+            .unwrap_err();
+        assert_eq!(
+            limited.subject,
+            RateLimitSubject::Ip("198.51.100.7".to_string())
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! アップストリーム保護のアダプティブレートリミッタ
+//!
+//! エンドポイント別にアップストリームの 429 率を監視し、AIMD（加算増加・乗算減少）で
+//! 送信許可レートを調整する。調整区間ごとに 429 率が閾値を超えていれば許可レートを半減し、
+//! 収まっていれば一定量ずつ増やす。制限を始めた時点の送信レートまで戻れば制限を解除する。
+//! 許可レートはトークンバケットで適用し、トークンの無いエンドポイントはルーティング候補から外す。
+
+use std::time::{Duration, Instant};
+
+/// 429 率を評価して許可レートを調整する間隔
+pub const ADJUST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 乗算減少の係数
+pub const DECREASE_FACTOR: f64 = 0.5;
+
+/// 許可レートの下限（req/s）
+pub const MIN_ALLOWED_RPS: f64 = 0.1;
+
+/// 許可レートの変化（変化前, 変化後。`None` は制限なし）
+pub type RateChange = (Option<f64>, Option<f64>);
+
+/// エンドポイント単位のアダプティブレート状態
+#[derive(Debug, Clone, Default)]
+pub struct AdaptiveRate {
+    /// 現在の送信許可レート（req/s、None=制限なし）
+    allowed_rps: Option<f64>,
+    /// 制限を始めた時点の送信レート（許可レートがここまで戻れば制限を解除する）
+    ceiling_rps: f64,
+    /// 調整区間の開始時刻
+    window_start: Option<Instant>,
+    /// 調整区間内の応答数
+    responses: u32,
+    /// 調整区間内の 429 応答数
+    throttled: u32,
+    /// トークンバケットの残量
+    tokens: f64,
+    /// トークンを最後に補充した時刻
+    last_refill: Option<Instant>,
+}
+
+impl AdaptiveRate {
+    /// 現在の送信許可レート（req/s、None=制限なし）
+    pub fn allowed_rps(&self) -> Option<f64> {
+        self.allowed_rps
+    }
+
+    /// 指定時刻に送信できるか（制限なし、またはトークンが残っている）
+    pub fn has_capacity(&self, now: Instant) -> bool {
+        match self.allowed_rps {
+            None => true,
+            Some(rate) => self.available_tokens(rate, now) >= 1.0,
+        }
+    }
+
+    /// 送信を記録してトークンを1つ消費する
+    ///
+    /// 選択から割り当てまでの間に競合した場合はトークンが負になり、その分だけ次の送信が遅れる。
+    pub fn consume(&mut self, now: Instant) {
+        if let Some(rate) = self.allowed_rps {
+            self.tokens = self.available_tokens(rate, now) - 1.0;
+            self.last_refill = Some(now);
+        }
+    }
+
+    /// アップストリームの応答を記録し、調整区間が経過していれば許可レートを調整する
+    ///
+    /// `threshold` は乗算減少する 429 率（`0` 以下で無効化し、制限も解除する）、
+    /// `increase_rps` は 429 が収まっている区間ごとに増やすレート。
+    /// 許可レートが変化した場合のみ変化を返す。
+    pub fn record_response(
+        &mut self,
+        throttled: bool,
+        now: Instant,
+        threshold: f64,
+        increase_rps: f64,
+    ) -> Option<RateChange> {
+        if threshold <= 0.0 {
+            let previous = self.allowed_rps;
+            *self = Self::default();
+            return previous.map(|rate| (Some(rate), None));
+        }
+
+        let start = *self.window_start.get_or_insert(now);
+        self.responses = self.responses.saturating_add(1);
+        if throttled {
+            self.throttled = self.throttled.saturating_add(1);
+        }
+        let elapsed = now.saturating_duration_since(start);
+        if elapsed < ADJUST_INTERVAL {
+            return None;
+        }
+
+        let ratio = f64::from(self.throttled) / f64::from(self.responses);
+        let observed_rps = f64::from(self.responses) / elapsed.as_secs_f64();
+        self.window_start = Some(now);
+        self.responses = 0;
+        self.throttled = 0;
+
+        let previous = self.allowed_rps;
+        let next = if ratio > threshold {
+            let base = previous.unwrap_or_else(|| {
+                self.ceiling_rps = observed_rps;
+                observed_rps
+            });
+            Some((base * DECREASE_FACTOR).max(MIN_ALLOWED_RPS))
+        } else {
+            previous
+                .map(|rate| rate + increase_rps.max(0.0))
+                .filter(|rate| *rate < self.ceiling_rps)
+        };
+        if next == previous {
+            return None;
+        }
+        if previous.is_none() {
+            // 制限開始時は1件分だけ送信できる状態から始める
+            self.tokens = 1.0;
+            self.last_refill = Some(now);
+        }
+        self.allowed_rps = next;
+        Some((previous, next))
+    }
+
+    /// 補充を反映したトークン残量（バースト上限は1秒分、最低1件）
+    fn available_tokens(&self, rate: f64, now: Instant) -> f64 {
+        let elapsed = self
+            .last_refill
+            .map(|at| now.saturating_duration_since(at).as_secs_f64())
+            .unwrap_or(0.0);
+        (self.tokens + elapsed * rate).min(rate.max(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `start` から `secs` 秒間、毎秒 `per_sec` 件の応答（うち `throttled` 件が 429）を記録する
+    fn record(
+        rate: &mut AdaptiveRate,
+        start: Instant,
+        secs: u64,
+        per_sec: u32,
+        throttled: u32,
+    ) -> Vec<RateChange> {
+        let mut changes = Vec::new();
+        for sec in 0..secs {
+            for i in 0..per_sec {
+                let at = start
+                    + Duration::from_secs(sec)
+                    + Duration::from_millis(u64::from(i) * 1000 / u64::from(per_sec));
+                if let Some(change) = rate.record_response(i < throttled, at, 0.05, 1.0) {
+                    changes.push(change);
+                }
+            }
+        }
+        changes
+    }
+
+    #[test]
+    fn frequent_429_halves_rate_and_recovery_lifts_limit() {
+        let start = Instant::now();
+        let mut rate = AdaptiveRate::default();
+        assert!(rate.has_capacity(start));
+
+        // 10 req/s のうち半分が 429 → 観測レートの半分に制限
+        let changes = record(&mut rate, start, 2, 10, 5);
+        let (previous, next) = changes[0];
+        assert_eq!(previous, None);
+        let limited = next.unwrap();
+        assert!(limited > 4.0 && limited < 6.0, "limited {limited}");
+
+        // 429 が続く限り乗算減少する
+        let changes = record(&mut rate, start + Duration::from_secs(2), 2, 10, 5);
+        assert!(changes.iter().all(|(p, n)| n.unwrap() < p.unwrap()));
+        let reduced = rate.allowed_rps().unwrap();
+        assert!(reduced < limited);
+
+        // 429 が収まれば加算増加し、制限開始時のレートに戻ると解除される
+        let changes = record(&mut rate, start + Duration::from_secs(4), 20, 10, 0);
+        assert!(changes
+            .iter()
+            .any(|(p, n)| n.unwrap_or(f64::MAX) > p.unwrap()));
+        assert_eq!(rate.allowed_rps(), None);
+    }
+
+    #[test]
+    fn token_bucket_limits_sends() {
+        let start = Instant::now();
+        let mut rate = AdaptiveRate::default();
+        // 5 req/s がすべて 429 → 2.5 req/s に制限
+        record(&mut rate, start, 2, 4, 4);
+        let allowed = rate.allowed_rps().unwrap();
+        assert_eq!(allowed, 2.5);
+
+        // バースト上限（1秒分）まで送ると以降は送れない
+        let now = start + Duration::from_secs(2);
+        let mut sent = 0;
+        while rate.has_capacity(now) {
+            rate.consume(now);
+            sent += 1;
+        }
+        assert_eq!(sent, 2);
+        // 1 / allowed 秒後には次の1件を送れる
+        assert!(rate.has_capacity(now + Duration::from_secs_f64(1.0 / allowed)));
+    }
+
+    #[test]
+    fn zero_threshold_disables_limiter() {
+        let start = Instant::now();
+        let mut rate = AdaptiveRate::default();
+        record(&mut rate, start, 2, 10, 10);
+        assert!(rate.allowed_rps().is_some());
+
+        let change = rate.record_response(true, start + Duration::from_secs(3), 0.0, 1.0);
+        assert!(matches!(change, Some((Some(_), None))));
+        assert_eq!(rate.allowed_rps(), None);
+        assert!(rate
+            .record_response(true, start + Duration::from_secs(5), 0.0, 1.0)
+            .is_none());
+    }
+}
@@ -0,0 +1,172 @@
+//! カナリアルーティング
+//!
+//! `canary_percent` が設定されたエンドポイントへ、リクエストのその割合だけを流す
+//! （新しく追加したエンドポイントへ少量のトラフィックだけを流す）。
+//! リクエストごとに乱数を1回だけ引き（`SelectionContext::canary_roll`）、
+//! カナリアかそれ以外かを選択前に振り分ける。セッションに固定済みのリクエストは振り分けない。
+//! カナリアのサーキットブレーカーが open になるか、直近のエラー率が
+//! `LLMLB_CANARY_MAX_ERROR_RATE` を超えた場合は割合を自動で 0% に落とす。
+
+use std::collections::VecDeque;
+
+/// エラー率を判定するのに必要な最小リクエスト数
+pub const CANARY_MIN_SAMPLES: usize = 20;
+
+/// エラー率を計算する直近リクエスト数
+pub const CANARY_WINDOW: usize = 100;
+
+/// 振り分けに使う乱数（0〜99）を引く
+pub fn draw() -> u8 {
+    use rand::RngExt;
+    rand::rng().random_range(0..100u8)
+}
+
+/// カナリア割合に従って候補を振り分ける
+///
+/// 候補順にカナリアの割合を積み上げ、`roll`（0〜99）が入った区間のカナリアのみを残す。
+/// どの区間にも入らない場合はカナリア以外の候補のみを残す。
+/// 振り分け先に候補が無い場合（モデルを提供するのがカナリアのみの場合など）は
+/// 503 にせず元の候補をそのまま返す。
+pub fn apply_canary<T>(candidates: Vec<T>, percent: impl Fn(&T) -> Option<u8>, roll: u8) -> Vec<T> {
+    if candidates.iter().all(|c| percent(c).is_none()) {
+        return candidates;
+    }
+    let mut upper: u32 = 0;
+    let chosen = candidates.iter().position(|c| match percent(c) {
+        Some(p) => {
+            upper += u32::from(p);
+            u32::from(roll) < upper
+        }
+        None => false,
+    });
+    let split: Vec<usize> = match chosen {
+        Some(index) => vec![index],
+        None => (0..candidates.len())
+            .filter(|&index| percent(&candidates[index]).is_none())
+            .collect(),
+    };
+    if split.is_empty() {
+        return candidates;
+    }
+    candidates
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, c)| split.contains(&index).then_some(c))
+        .collect()
+}
+
+/// 停止中（0%）のカナリアを除外する（振り分けを行わない経路向け）
+///
+/// 除外で候補が無くなる場合は元の候補をそのまま返す。
+pub fn exclude_stopped<T>(candidates: Vec<T>, percent: impl Fn(&T) -> Option<u8>) -> Vec<T> {
+    if !candidates.iter().any(|c| percent(c) == Some(0)) {
+        return candidates;
+    }
+    let (running, stopped): (Vec<T>, Vec<T>) =
+        candidates.into_iter().partition(|c| percent(c) != Some(0));
+    if running.is_empty() {
+        stopped
+    } else {
+        running
+    }
+}
+
+/// カナリアの直近リクエスト結果
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CanaryOutcomes {
+    recent: VecDeque<bool>,
+}
+
+impl CanaryOutcomes {
+    /// 結果を記録し、エラー率が `max_error_rate` を超えた場合は `true` を返す
+    ///
+    /// `max_error_rate` が `0` 以下の場合はエラー率による判定を行わない。
+    pub(crate) fn record(&mut self, success: bool, max_error_rate: f64) -> bool {
+        self.recent.push_back(success);
+        while self.recent.len() > CANARY_WINDOW {
+            self.recent.pop_front();
+        }
+        if max_error_rate <= 0.0 || self.recent.len() < CANARY_MIN_SAMPLES {
+            return false;
+        }
+        let errors = self.recent.iter().filter(|ok| !**ok).count();
+        errors as f64 / self.recent.len() as f64 > max_error_rate
+    }
+
+    /// 記録をリセットする（割合の変更時・自動停止時）
+    pub(crate) fn clear(&mut self) {
+        self.recent.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_roll_splits_between_canaries_and_stable() {
+        // (name, canary_percent)
+        let candidates = vec![
+            ("stable-a", None),
+            ("canary-1", Some(10u8)),
+            ("stable-b", None),
+            ("canary-2", Some(20u8)),
+        ];
+
+        // 0〜9 は1つ目、10〜29 は2つ目のカナリアのみ
+        assert_eq!(
+            apply_canary(candidates.clone(), |c| c.1, 9),
+            vec![("canary-1", Some(10))]
+        );
+        assert_eq!(
+            apply_canary(candidates.clone(), |c| c.1, 10),
+            vec![("canary-2", Some(20))]
+        );
+        // それ以外はカナリアを含めない
+        assert_eq!(
+            apply_canary(candidates.clone(), |c| c.1, 30),
+            vec![("stable-a", None), ("stable-b", None)]
+        );
+
+        // 0% のカナリアは他に候補があれば選ばれない
+        let candidates = vec![("stable", None), ("canary", Some(0u8))];
+        assert_eq!(apply_canary(candidates, |c| c.1, 0), vec![("stable", None)]);
+
+        // カナリアしか無い場合は候補を残す
+        let candidates = vec![("canary", Some(0u8))];
+        assert_eq!(apply_canary(candidates.clone(), |c| c.1, 50), candidates);
+    }
+
+    #[test]
+    fn exclude_stopped_keeps_running_canaries() {
+        let candidates = vec![
+            ("stable", None),
+            ("running", Some(10u8)),
+            ("stopped", Some(0u8)),
+        ];
+        assert_eq!(
+            exclude_stopped(candidates, |c| c.1),
+            vec![("stable", None), ("running", Some(10))]
+        );
+        let candidates = vec![("stopped", Some(0u8))];
+        assert_eq!(exclude_stopped(candidates.clone(), |c| c.1), candidates);
+    }
+
+    #[test]
+    fn trips_when_recent_error_rate_exceeds_threshold() {
+        let mut outcomes = CanaryOutcomes::default();
+        for _ in 0..CANARY_MIN_SAMPLES - 1 {
+            assert!(!outcomes.record(false, 0.2));
+        }
+        // 最小サンプル数に達した時点で判定する
+        assert!(outcomes.record(false, 0.2));
+
+        outcomes.clear();
+        for i in 0..CANARY_WINDOW {
+            // 10% のエラー率は閾値未満
+            assert!(!outcomes.record(i % 10 != 0, 0.2));
+        }
+        // 閾値 0 は判定しない
+        assert!(!outcomes.record(false, 0.0));
+    }
+}
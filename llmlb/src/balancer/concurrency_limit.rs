@@ -0,0 +1,198 @@
+//! レイテンシに応じた動的同時実行上限
+//!
+//! Netflix concurrency-limits の Gradient2 に倣い、エンドポイント別に完了リクエストの
+//! レイテンシから同時実行上限を調整する。長期EMA（基準）と短期EMA（直近）の比を勾配とし、
+//! 直近のレイテンシが基準より悪化すると上限を減らし、安定していれば少しずつ増やす。
+//! 上限に達したエンドポイントは、余裕のある他のエンドポイントがあればルーティング候補から外す。
+
+/// 調整を始めるまでに必要な完了リクエスト数
+pub const WARMUP_SAMPLES: u32 = 10;
+
+/// 上限の初期値
+pub const INITIAL_LIMIT: f64 = 20.0;
+
+/// 上限の下限
+pub const MIN_LIMIT: f64 = 1.0;
+
+/// 基準に対して許容するレイテンシの悪化率（これを超えると上限を減らす）
+const RTT_TOLERANCE: f64 = 1.5;
+
+/// 短期EMAの平滑化係数（直近約10件）
+const SHORT_ALPHA: f64 = 2.0 / 11.0;
+
+/// 長期EMAの平滑化係数（直近約600件）
+const LONG_ALPHA: f64 = 2.0 / 601.0;
+
+/// 新しい上限へ寄せる割合
+const SMOOTHING: f64 = 0.2;
+
+/// 上限の変化（変化前, 変化後）
+pub type LimitChange = (u32, u32);
+
+/// エンドポイント単位の動的同時実行上限
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit {
+    /// 現在の上限（小数で保持し、適用時は切り捨てる）
+    limit: f64,
+    /// 直近レイテンシの短期EMA（ms）
+    short_rtt_ms: Option<f64>,
+    /// 基準レイテンシの長期EMA（ms）
+    long_rtt_ms: Option<f64>,
+    /// 記録した完了リクエスト数
+    samples: u32,
+}
+
+impl Default for ConcurrencyLimit {
+    fn default() -> Self {
+        Self {
+            limit: INITIAL_LIMIT,
+            short_rtt_ms: None,
+            long_rtt_ms: None,
+            samples: 0,
+        }
+    }
+}
+
+impl ConcurrencyLimit {
+    /// 現在の同時実行上限
+    pub fn limit(&self) -> u32 {
+        self.limit.floor().max(MIN_LIMIT) as u32
+    }
+
+    /// 直近レイテンシの短期EMA（ms）
+    pub fn short_rtt_ms(&self) -> Option<f64> {
+        self.short_rtt_ms
+    }
+
+    /// 基準レイテンシの長期EMA（ms）
+    pub fn long_rtt_ms(&self) -> Option<f64> {
+        self.long_rtt_ms
+    }
+
+    /// 処理中リクエスト数 `in_flight` で、さらに1件受け付けられるか
+    pub fn has_capacity(&self, in_flight: u32) -> bool {
+        in_flight < self.limit()
+    }
+
+    /// 完了リクエストのレイテンシを記録し、上限を調整する
+    ///
+    /// `in_flight` は完了時点の処理中リクエスト数（完了分を含む）、`max_limit` は上限の最大値。
+    /// 処理中が上限の半分未満（負荷が掛かっていない）の間は上限を増やさない。
+    /// 上限（整数値）が変化した場合のみ変化を返す。
+    pub fn record_latency(
+        &mut self,
+        rtt_ms: f64,
+        in_flight: u32,
+        max_limit: u32,
+    ) -> Option<LimitChange> {
+        if !rtt_ms.is_finite() || rtt_ms < 0.0 {
+            return None;
+        }
+        let rtt_ms = rtt_ms.max(1.0);
+        let short = ema(self.short_rtt_ms, rtt_ms, SHORT_ALPHA);
+        let mut long = ema(self.long_rtt_ms, rtt_ms, LONG_ALPHA);
+        // 直近のレイテンシが基準より大幅に短い場合は、基準を早めに追従させる
+        if long / short > 2.0 {
+            long *= 0.95;
+        }
+        self.short_rtt_ms = Some(short);
+        self.long_rtt_ms = Some(long);
+        self.samples = self.samples.saturating_add(1);
+        if self.samples < WARMUP_SAMPLES {
+            return None;
+        }
+
+        let previous = self.limit();
+        let max_limit = f64::from(max_limit.max(1));
+        let gradient = (RTT_TOLERANCE * long / short).clamp(0.5, 1.0);
+        if gradient >= 1.0 && f64::from(in_flight) < self.limit / 2.0 {
+            return None;
+        }
+        let target = self.limit * gradient + self.limit.sqrt();
+        self.limit =
+            (self.limit * (1.0 - SMOOTHING) + target * SMOOTHING).clamp(MIN_LIMIT, max_limit);
+
+        let next = self.limit();
+        (next != previous).then_some((previous, next))
+    }
+}
+
+/// 上限に達した候補を外す
+///
+/// `has_capacity` が `false` の候補を除く。全ての候補が上限に達している場合は
+/// リクエストを失敗させず元の候補をそのまま返す。
+pub fn filter_by_capacity<T>(candidates: Vec<T>, has_capacity: impl Fn(&T) -> bool) -> Vec<T> {
+    let capacity: Vec<bool> = candidates.iter().map(has_capacity).collect();
+    if !capacity.contains(&true) || !capacity.contains(&false) {
+        return candidates;
+    }
+    candidates
+        .into_iter()
+        .zip(capacity)
+        .filter_map(|(c, free)| free.then_some(c))
+        .collect()
+}
+
+fn ema(previous: Option<f64>, sample: f64, alpha: f64) -> f64 {
+    match previous {
+        Some(previous) => previous + alpha * (sample - previous),
+        None => sample,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_while_latency_is_stable_under_load() {
+        let mut limit = ConcurrencyLimit::default();
+        for _ in 0..200 {
+            limit.record_latency(100.0, limit.limit(), 100);
+        }
+        assert!(limit.limit() > INITIAL_LIMIT as u32);
+        assert!(limit.limit() <= 100);
+
+        // 負荷が掛かっていない間は増やさない
+        let mut idle = ConcurrencyLimit::default();
+        for _ in 0..200 {
+            idle.record_latency(100.0, 1, 100);
+        }
+        assert_eq!(idle.limit(), INITIAL_LIMIT as u32);
+    }
+
+    #[test]
+    fn shrinks_when_latency_degrades() {
+        let mut limit = ConcurrencyLimit::default();
+        for _ in 0..100 {
+            limit.record_latency(100.0, limit.limit(), 100);
+        }
+        let stable = limit.limit();
+
+        let mut changes = Vec::new();
+        for _ in 0..50 {
+            changes.extend(limit.record_latency(1000.0, limit.limit(), 100));
+        }
+        assert!(limit.limit() < stable);
+        assert!(changes.iter().any(|(previous, next)| next < previous));
+        assert!(limit.short_rtt_ms().unwrap() > limit.long_rtt_ms().unwrap());
+        assert!(limit.limit() >= MIN_LIMIT as u32);
+    }
+
+    #[test]
+    fn capacity_and_filter() {
+        let limit = ConcurrencyLimit::default();
+        assert!(limit.has_capacity(19));
+        assert!(!limit.has_capacity(20));
+
+        // (name, has_capacity)
+        let candidates = vec![("full", false), ("free", true)];
+        assert_eq!(
+            filter_by_capacity(candidates, |c| c.1),
+            vec![("free", true)]
+        );
+        // 全て上限に達している場合は候補を残す
+        let candidates = vec![("a", false), ("b", false)];
+        assert_eq!(filter_by_capacity(candidates.clone(), |c| c.1), candidates);
+    }
+}
@@ -0,0 +1,232 @@
+//! コンテキスト長に基づくエンドポイント選択
+//!
+//! リクエストの推定入力トークン数に応じて、タグで指定したエンドポイントのグループへ
+//! 候補を絞り込む（例: 長文は大VRAMノード、短文は高速ノード）。
+//!
+//! `LLMLB_CONTEXT_ROUTING=1` で有効化し、ルールは `LLMLB_CONTEXT_ROUTING_FILE`
+//! （未設定時は `~/.llmlb/context_routing.yaml`）から読み込む。YAML/JSONのどちらでもよい。
+//!
+//! ```yaml
+//! rules:
+//!   - min_input_tokens: 8000    # 推定入力トークン数がこの値以上（省略時: 0）
+//!     tags: [large-vram]        # いずれかのタグを持つエンドポイントを候補にする
+//!   - max_input_tokens: 1000    # 推定入力トークン数がこの値未満（省略時: 上限なし）
+//!     tags: [fast]
+//!     models: []                # 対象モデル（空なら全モデル）
+//! ```
+//!
+//! ルールは上から順に評価し、最初に一致したものを適用する。一致するルールが無い場合や、
+//! 該当するタグのエンドポイントが候補に無い場合は通常の候補から選択する。
+
+use crate::types::endpoint::Endpoint;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// ルールファイルのパスを指定する環境変数
+const CONTEXT_ROUTING_FILE_ENV: &str = "LLMLB_CONTEXT_ROUTING_FILE";
+
+/// ルールファイルのデフォルト名（`~/.llmlb` 配下）
+const DEFAULT_CONTEXT_ROUTING_FILE: &str = "context_routing.yaml";
+
+static CONTEXT_ROUTING: Lazy<Option<ContextRouting>> = Lazy::new(|| {
+    if !crate::config::context_routing_enabled() {
+        return None;
+    }
+    let path = context_routing_path()?;
+    match ContextRouting::load(&path) {
+        Ok(routing) => {
+            tracing::info!(
+                path = %path.display(),
+                rules = routing.rules.len(),
+                "Context-length routing enabled"
+            );
+            Some(routing)
+        }
+        Err(err) => {
+            tracing::error!(
+                path = %path.display(),
+                error = %err,
+                "Failed to load context routing rules; context-length routing disabled"
+            );
+            None
+        }
+    }
+});
+
+/// コンテキスト長ルール
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ContextRule {
+    /// 推定入力トークン数の下限（この値を含む）
+    pub min_input_tokens: u32,
+    /// 推定入力トークン数の上限（この値を含まない、`None` で上限なし）
+    pub max_input_tokens: Option<u32>,
+    /// 候補にするエンドポイントのタグ（いずれかを持てばよい）
+    pub tags: Vec<String>,
+    /// 対象モデル（空なら全モデル）
+    pub models: Vec<String>,
+}
+
+impl ContextRule {
+    /// モデルと推定入力トークン数がルールに一致するか
+    pub fn matches(&self, model_id: &str, input_tokens: u32) -> bool {
+        (self.models.is_empty() || self.models.iter().any(|m| m == model_id))
+            && input_tokens >= self.min_input_tokens
+            && self.max_input_tokens.is_none_or(|max| input_tokens < max)
+    }
+}
+
+/// コンテキスト長ルールの一覧
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ContextRouting {
+    /// ルール（上から順に評価）
+    pub rules: Vec<ContextRule>,
+}
+
+impl ContextRouting {
+    /// ルールファイルを読み込む
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_yaml::from_str(&content).map_err(|e| e.to_string())
+    }
+
+    /// 最初に一致したルール
+    pub fn find_rule(&self, model_id: &str, input_tokens: u32) -> Option<&ContextRule> {
+        self.rules
+            .iter()
+            .find(|rule| !rule.tags.is_empty() && rule.matches(model_id, input_tokens))
+    }
+
+    /// ルールに従って候補を絞り込む
+    ///
+    /// 一致するルールが無い場合や、ルールのタグを持つ候補が無い場合は元の候補をそのまま返す。
+    pub fn apply(
+        &self,
+        endpoints: Vec<Endpoint>,
+        model_id: &str,
+        input_tokens: u32,
+    ) -> Vec<Endpoint> {
+        let Some(rule) = self.find_rule(model_id, input_tokens) else {
+            return endpoints;
+        };
+        let in_group =
+            |endpoint: &Endpoint| endpoint.tags.iter().any(|tag| rule.tags.contains(tag));
+        if !endpoints.iter().any(in_group) {
+            tracing::debug!(
+                model = %model_id,
+                input_tokens,
+                tags = ?rule.tags,
+                "No endpoint matches the context routing rule; falling back to normal selection"
+            );
+            return endpoints;
+        }
+        endpoints.into_iter().filter(in_group).collect()
+    }
+}
+
+/// 有効なコンテキスト長ルール（無効時は `None`）
+pub fn current() -> Option<&'static ContextRouting> {
+    CONTEXT_ROUTING.as_ref()
+}
+
+/// 推定入力トークン数で候補エンドポイントを絞り込む（無効時・`None` の場合はそのまま返す）
+pub(crate) fn apply_context_routing(
+    endpoints: Vec<Endpoint>,
+    model_id: &str,
+    input_tokens: Option<u32>,
+) -> Vec<Endpoint> {
+    let (Some(routing), Some(input_tokens)) = (current(), input_tokens) else {
+        return endpoints;
+    };
+    routing.apply(endpoints, model_id, input_tokens)
+}
+
+fn context_routing_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(CONTEXT_ROUTING_FILE_ENV) {
+        if !path.trim().is_empty() {
+            return Some(PathBuf::from(path));
+        }
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok();
+    match home {
+        Some(home) => Some(
+            PathBuf::from(home)
+                .join(".llmlb")
+                .join(DEFAULT_CONTEXT_ROUTING_FILE),
+        ),
+        None => {
+            tracing::error!(
+                "Context routing is enabled but {} is not set and the home directory is unknown",
+                CONTEXT_ROUTING_FILE_ENV
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::EndpointType;
+
+    fn endpoint(name: &str, tags: &[&str]) -> Endpoint {
+        let mut endpoint = Endpoint::new(
+            name.to_string(),
+            format!("http://{}:8080", name),
+            EndpointType::OpenaiCompatible,
+        );
+        endpoint.tags = tags.iter().map(|t| t.to_string()).collect();
+        endpoint
+    }
+
+    fn names(endpoints: &[Endpoint]) -> Vec<&str> {
+        endpoints.iter().map(|ep| ep.name.as_str()).collect()
+    }
+
+    fn routing() -> ContextRouting {
+        serde_yaml::from_str(
+            r#"
+rules:
+  - min_input_tokens: 8000
+    tags: [large-vram]
+  - max_input_tokens: 1000
+    tags: [fast]
+    models: [chat]
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn routes_by_input_tokens() {
+        let routing = routing();
+        let endpoints = vec![
+            endpoint("big", &["large-vram"]),
+            endpoint("quick", &["fast"]),
+            endpoint("plain", &[]),
+        ];
+
+        assert_eq!(
+            names(&routing.apply(endpoints.clone(), "chat", 8000)),
+            vec!["big"]
+        );
+        assert_eq!(
+            names(&routing.apply(endpoints.clone(), "chat", 999)),
+            vec!["quick"]
+        );
+        // 中間の長さ・対象外モデルはルールに一致しない
+        assert_eq!(routing.apply(endpoints.clone(), "chat", 4000).len(), 3);
+        assert_eq!(routing.apply(endpoints, "other", 10).len(), 3);
+    }
+
+    #[test]
+    fn falls_back_when_no_endpoint_in_group() {
+        let routing = routing();
+        let endpoints = vec![endpoint("quick", &["fast"]), endpoint("plain", &[])];
+        assert_eq!(routing.apply(endpoints, "chat", 20_000).len(), 2);
+    }
+}
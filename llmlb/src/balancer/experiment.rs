@@ -0,0 +1,358 @@
+//! リクエストのA/Bテスト（実験）
+//!
+//! モデル名パターンに合致したリクエストを、指定したリクエスト属性の安定ハッシュで
+//! A/B グループへ決定論的に割り当てる。グループごとに送信先モデルと必須ラベル
+//! （エンドポイントのタグ）を差し替え、レイテンシ・エラー率をグループ別に集計する。
+
+use super::routing_policy::model_pattern_matches;
+use crate::common::error::{CommonError, LbError};
+use crate::types::endpoint::Endpoint;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// 割り当てに使うリクエスト属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssignBy {
+    /// APIキーID
+    #[default]
+    ApiKey,
+    /// クライアントIP
+    ClientIp,
+    /// リクエストボディの `user` フィールド
+    User,
+}
+
+impl AssignBy {
+    /// DB保存用の文字列表現
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssignBy::ApiKey => "api_key",
+            AssignBy::ClientIp => "client_ip",
+            AssignBy::User => "user",
+        }
+    }
+}
+
+impl std::str::FromStr for AssignBy {
+    type Err = LbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "api_key" => Ok(AssignBy::ApiKey),
+            "client_ip" => Ok(AssignBy::ClientIp),
+            "user" => Ok(AssignBy::User),
+            _ => Err(CommonError::Validation(format!("Invalid assign_by: {}", s)).into()),
+        }
+    }
+}
+
+/// A/B グループ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExperimentGroup {
+    /// グループA（対照）
+    A,
+    /// グループB
+    B,
+}
+
+/// グループごとの送信先
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentVariant {
+    /// 送信先モデル（None = リクエストのモデルのまま）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 必須ラベル（すべて持つエンドポイントのみ候補）
+    #[serde(default)]
+    pub required_labels: Vec<String>,
+}
+
+/// A/Bテスト設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Experiment {
+    /// 一意識別子
+    pub id: Uuid,
+    /// 実験名
+    pub name: String,
+    /// 対象モデル名パターン（完全一致、末尾 `*` で前方一致、`*` 単体で全モデル）
+    pub model_pattern: String,
+    /// 割り当てに使うリクエスト属性
+    #[serde(default)]
+    pub assign_by: AssignBy,
+    /// グループBへ割り当てる割合（0〜100）
+    pub b_percent: u8,
+    /// グループAの送信先
+    #[serde(default)]
+    pub variant_a: ExperimentVariant,
+    /// グループBの送信先
+    #[serde(default)]
+    pub variant_b: ExperimentVariant,
+    /// 有効フラグ
+    pub enabled: bool,
+    /// 作成日時
+    pub created_at: DateTime<Utc>,
+    /// 更新日時
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Experiment {
+    /// グループの送信先
+    pub fn variant(&self, group: ExperimentGroup) -> &ExperimentVariant {
+        match group {
+            ExperimentGroup::A => &self.variant_a,
+            ExperimentGroup::B => &self.variant_b,
+        }
+    }
+
+    /// 割り当てキーからグループを決定する（同じキーは常に同じグループ）
+    pub fn assign(&self, key: &str) -> ExperimentGroup {
+        let mut hasher = Sha256::new();
+        hasher.update(self.id.as_bytes());
+        hasher.update(key.as_bytes());
+        let digest = hasher.finalize();
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        if u64::from_be_bytes(bytes) % 100 < self.b_percent as u64 {
+            ExperimentGroup::B
+        } else {
+            ExperimentGroup::A
+        }
+    }
+}
+
+/// 割り当てに使えるリクエスト属性
+#[derive(Debug, Clone, Default)]
+pub struct ExperimentSubject {
+    /// APIキーID
+    pub api_key_id: Option<Uuid>,
+    /// クライアントIP
+    pub client_ip: Option<IpAddr>,
+    /// リクエストボディの `user`
+    pub user: Option<String>,
+}
+
+impl ExperimentSubject {
+    /// 属性の値（無い場合は割り当て対象外）
+    fn key(&self, assign_by: AssignBy) -> Option<String> {
+        match assign_by {
+            AssignBy::ApiKey => self.api_key_id.map(|id| id.to_string()),
+            AssignBy::ClientIp => self.client_ip.map(|ip| ip.to_string()),
+            AssignBy::User => self.user.clone().filter(|user| !user.is_empty()),
+        }
+    }
+}
+
+/// リクエストへの実験割り当て結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExperimentAssignment {
+    /// 実験ID
+    pub experiment_id: Uuid,
+    /// 実験名
+    pub experiment_name: String,
+    /// 割り当てグループ
+    pub group: ExperimentGroup,
+    /// 送信先
+    pub variant: ExperimentVariant,
+}
+
+/// 最初に合致した有効な実験でリクエストを割り当てる
+///
+/// 割り当て属性を持たないリクエストは実験の対象外とする。
+pub fn assign(
+    experiments: &[Experiment],
+    model_id: &str,
+    subject: &ExperimentSubject,
+) -> Option<ExperimentAssignment> {
+    experiments
+        .iter()
+        .filter(|e| e.enabled && model_pattern_matches(&e.model_pattern, model_id))
+        .find_map(|experiment| {
+            let key = subject.key(experiment.assign_by)?;
+            let group = experiment.assign(&key);
+            Some(ExperimentAssignment {
+                experiment_id: experiment.id,
+                experiment_name: experiment.name.clone(),
+                group,
+                variant: experiment.variant(group).clone(),
+            })
+        })
+}
+
+/// 割り当てグループの必須ラベルで候補エンドポイントを絞り込む
+pub(crate) fn apply_assignment(
+    endpoints: Vec<Endpoint>,
+    model_id: &str,
+    assignment: Option<&ExperimentAssignment>,
+) -> Result<Vec<Endpoint>, LbError> {
+    let Some(assignment) = assignment else {
+        return Ok(endpoints);
+    };
+    if assignment.variant.required_labels.is_empty() {
+        return Ok(endpoints);
+    }
+    let matched: Vec<Endpoint> = endpoints
+        .into_iter()
+        .filter(|ep| ep.has_all_tags(&assignment.variant.required_labels))
+        .collect();
+    if matched.is_empty() {
+        tracing::warn!(
+            experiment = %assignment.experiment_name,
+            group = ?assignment.group,
+            model = %model_id,
+            required_labels = ?assignment.variant.required_labels,
+            "No endpoint satisfies experiment group labels"
+        );
+        return Err(LbError::NoCapableEndpoints(model_id.to_string()));
+    }
+    Ok(matched)
+}
+
+/// グループ別の集計値
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct GroupCounters {
+    pub requests: u64,
+    pub errors: u64,
+    pub total_latency_ms: u64,
+}
+
+/// グループ別の統計
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExperimentGroupStats {
+    /// グループ
+    pub group: ExperimentGroup,
+    /// リクエスト数
+    pub requests: u64,
+    /// エラー数
+    pub errors: u64,
+    /// エラー率（0.0〜1.0、リクエストなしはNone）
+    pub error_rate: Option<f64>,
+    /// 平均レイテンシ（応答ヘッダまで、ミリ秒）
+    pub average_latency_ms: Option<f64>,
+}
+
+impl ExperimentGroupStats {
+    pub(crate) fn from_counters(group: ExperimentGroup, counters: GroupCounters) -> Self {
+        let per_request =
+            |value: u64| (counters.requests > 0).then(|| value as f64 / counters.requests as f64);
+        Self {
+            group,
+            requests: counters.requests,
+            errors: counters.errors,
+            error_rate: per_request(counters.errors),
+            average_latency_ms: per_request(counters.total_latency_ms),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::EndpointType;
+
+    fn experiment(b_percent: u8) -> Experiment {
+        let now = Utc::now();
+        Experiment {
+            id: Uuid::new_v4(),
+            name: "llama-vs-qwen".to_string(),
+            model_pattern: "llama*".to_string(),
+            assign_by: AssignBy::User,
+            b_percent,
+            variant_a: ExperimentVariant::default(),
+            variant_b: ExperimentVariant {
+                model: Some("qwen".to_string()),
+                required_labels: vec!["gpu-b".to_string()],
+            },
+            enabled: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn subject(user: &str) -> ExperimentSubject {
+        ExperimentSubject {
+            user: Some(user.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn assignment_is_deterministic_and_follows_percentage() {
+        let exp = experiment(30);
+        let b_count = (0..1000)
+            .filter(|i| exp.assign(&format!("user-{}", i)) == ExperimentGroup::B)
+            .count();
+        assert!((200..400).contains(&b_count), "b_count = {}", b_count);
+        for i in 0..50 {
+            let key = format!("user-{}", i);
+            assert_eq!(exp.assign(&key), exp.assign(&key));
+        }
+
+        assert_eq!(experiment(0).assign("x"), ExperimentGroup::A);
+        assert_eq!(experiment(100).assign("x"), ExperimentGroup::B);
+    }
+
+    #[test]
+    fn assign_requires_matching_model_and_attribute() {
+        let experiments = vec![experiment(100)];
+        let assignment = assign(&experiments, "llama3", &subject("alice")).unwrap();
+        assert_eq!(assignment.group, ExperimentGroup::B);
+        assert_eq!(assignment.variant.model.as_deref(), Some("qwen"));
+
+        assert!(assign(&experiments, "mistral", &subject("alice")).is_none());
+        assert!(assign(&experiments, "llama3", &ExperimentSubject::default()).is_none());
+
+        let mut disabled = experiment(100);
+        disabled.enabled = false;
+        assert!(assign(&[disabled], "llama3", &subject("alice")).is_none());
+    }
+
+    #[test]
+    fn assignment_filters_endpoints_by_labels() {
+        let mut tagged = Endpoint::new(
+            "b".to_string(),
+            "http://b:8000".to_string(),
+            EndpointType::Vllm,
+        );
+        tagged.tags = vec!["gpu-b".to_string()];
+        let untagged = Endpoint::new(
+            "a".to_string(),
+            "http://a:8000".to_string(),
+            EndpointType::Vllm,
+        );
+        let tagged_id = tagged.id;
+
+        // 割り当てが無ければそのまま
+        let all = apply_assignment(vec![tagged.clone(), untagged.clone()], "m", None).unwrap();
+        assert_eq!(all.len(), 2);
+
+        let assignment = assign(&[experiment(100)], "llama3", &subject("alice")).unwrap();
+        let filtered =
+            apply_assignment(vec![tagged, untagged.clone()], "m", Some(&assignment)).unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, tagged_id);
+
+        let none = apply_assignment(vec![untagged], "m", Some(&assignment));
+        assert!(none.is_err());
+    }
+
+    #[test]
+    fn group_stats_compute_rates() {
+        let stats = ExperimentGroupStats::from_counters(
+            ExperimentGroup::A,
+            GroupCounters {
+                requests: 4,
+                errors: 1,
+                total_latency_ms: 400,
+            },
+        );
+        assert_eq!(stats.error_rate, Some(0.25));
+        assert_eq!(stats.average_latency_ms, Some(100.0));
+
+        let empty =
+            ExperimentGroupStats::from_counters(ExperimentGroup::B, GroupCounters::default());
+        assert_eq!(empty.error_rate, None);
+    }
+}
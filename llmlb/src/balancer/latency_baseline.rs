@@ -0,0 +1,110 @@
+//! レイテンシ基準の自動キャリブレーション
+//!
+//! 環境全体のレイテンシは時間帯で変動するため、固定閾値ではなく全エンドポイントの
+//! p50 レイテンシの中央値を基準値として定期的に再計算する。基準値の一定倍を超えて
+//! 遅いエンドポイントのみを penalty 対象とし、全体が遅い時間帯に一律で後回しに
+//! なることを防ぐ。
+
+use super::LoadManager;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// レイテンシ基準値
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LatencyBaseline {
+    /// 全エンドポイントの p50 レイテンシの中央値（ミリ秒）
+    pub median_latency_ms: f64,
+    /// 算出に使ったエンドポイント数（レイテンシ計測済みのもの）
+    pub sample_endpoints: usize,
+    /// 算出日時
+    pub computed_at: DateTime<Utc>,
+}
+
+impl LatencyBaseline {
+    /// エンドポイントごとの p50 レイテンシから基準値を算出する（計測済みが無ければ `None`）
+    pub fn from_latencies(latencies: &[f64], now: DateTime<Utc>) -> Option<Self> {
+        let mut sorted: Vec<f64> = latencies
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f64::total_cmp);
+        let mid = sorted.len() / 2;
+        let median_latency_ms = if sorted.len() % 2 == 0 {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        };
+        Some(Self {
+            median_latency_ms,
+            sample_endpoints: sorted.len(),
+            computed_at: now,
+        })
+    }
+
+    /// penalty 判定の閾値（ミリ秒）。`factor` が 0 以下なら `None`（判定無効）
+    pub fn penalty_threshold_ms(&self, factor: f64) -> Option<f64> {
+        (factor > 0.0).then(|| self.median_latency_ms * factor)
+    }
+
+    /// p50 レイテンシ `latency_ms` のエンドポイントが penalty 対象か
+    pub fn is_penalized(&self, latency_ms: f64, factor: f64) -> bool {
+        self.penalty_threshold_ms(factor)
+            .is_some_and(|threshold| latency_ms > threshold)
+    }
+}
+
+/// レイテンシ基準を定期的に再計算するタスクを起動する
+///
+/// 間隔は `LLMLB_LATENCY_BASELINE_INTERVAL_SECS`（既定: 60秒）。
+pub fn start_latency_calibration_task(load_manager: LoadManager) {
+    let interval = crate::config::latency_baseline_interval();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            if let Some(baseline) = load_manager.recalibrate_latency_baseline().await {
+                tracing::debug!(
+                    median_latency_ms = baseline.median_latency_ms,
+                    sample_endpoints = baseline.sample_endpoints,
+                    "Recalibrated endpoint latency baseline"
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baseline_is_median_of_endpoint_latencies() {
+        let now = Utc::now();
+        assert_eq!(LatencyBaseline::from_latencies(&[], now), None);
+
+        let odd = LatencyBaseline::from_latencies(&[900.0, 100.0, 200.0], now).unwrap();
+        assert_eq!(odd.median_latency_ms, 200.0);
+        assert_eq!(odd.sample_endpoints, 3);
+
+        let even = LatencyBaseline::from_latencies(&[100.0, 400.0, 200.0, 300.0], now).unwrap();
+        assert_eq!(even.median_latency_ms, 250.0);
+    }
+
+    #[test]
+    fn penalty_is_relative_to_baseline() {
+        let now = Utc::now();
+        // 全体が遅い時間帯は基準値も上がるため、同じレイテンシでも penalty にならない
+        let fast_hours = LatencyBaseline::from_latencies(&[100.0, 120.0, 110.0], now).unwrap();
+        let slow_hours = LatencyBaseline::from_latencies(&[800.0, 900.0, 850.0], now).unwrap();
+        assert!(fast_hours.is_penalized(1000.0, 3.0));
+        assert!(!slow_hours.is_penalized(1000.0, 3.0));
+
+        assert_eq!(fast_hours.penalty_threshold_ms(0.0), None);
+        assert!(!fast_hours.is_penalized(1_000_000.0, 0.0));
+    }
+}
@@ -11,23 +11,40 @@ use super::LoadManager;
 
 /// リクエスト処理中のlease
 ///
-/// `complete*` が呼ばれずに破棄された場合でも、Drop時にエラーとして
-/// activeカウンタを減算することでカウンタ残留を防ぐ。
+/// `complete*` が呼ばれずに破棄された場合でも、Drop時に取り消し（`Cancelled`）として
+/// activeカウンタを減算することでカウンタ残留を防ぐ。取り消しはサーキットブレーカーや
+/// パッシブヘルス検知のエラーには数えない。
 pub struct RequestLease {
     load_manager: Option<LoadManager>,
     endpoint_id: Uuid,
     started_at: std::time::Instant,
+    /// 使用中の予約スロット（容量予約経由で割り当てた場合）
+    reservation_id: Option<Uuid>,
+    /// サーキットブレーカーの試験リクエストとして割り当てたか
+    breaker_probe: bool,
 }
 
 impl RequestLease {
-    pub(crate) fn new(load_manager: LoadManager, endpoint_id: Uuid) -> Self {
+    pub(crate) fn new(
+        load_manager: LoadManager,
+        endpoint_id: Uuid,
+        reservation_id: Option<Uuid>,
+        breaker_probe: bool,
+    ) -> Self {
         Self {
             load_manager: Some(load_manager),
             endpoint_id,
             started_at: std::time::Instant::now(),
+            reservation_id,
+            breaker_probe,
         }
     }
 
+    /// 予約スロットを使用している場合はその予約IDを返す。
+    pub fn reservation_id(&self) -> Option<Uuid> {
+        self.reservation_id
+    }
+
     /// 紐づくエンドポイントIDを返す。
     pub fn endpoint_id(&self) -> Uuid {
         self.endpoint_id
@@ -47,8 +64,17 @@ impl RequestLease {
         let Some(load_manager) = self.load_manager.take() else {
             return Ok(());
         };
+        if let Some(reservation_id) = self.reservation_id {
+            load_manager.release_reservation_slot(reservation_id);
+        }
         load_manager
-            .finish_request(self.endpoint_id, outcome, duration)
+            .record_finish(
+                self.endpoint_id,
+                outcome,
+                duration,
+                None,
+                self.breaker_probe,
+            )
             .await
     }
 
@@ -62,8 +88,17 @@ impl RequestLease {
         let Some(load_manager) = self.load_manager.take() else {
             return Ok(());
         };
+        if let Some(reservation_id) = self.reservation_id {
+            load_manager.release_reservation_slot(reservation_id);
+        }
         load_manager
-            .finish_request_with_tokens(self.endpoint_id, outcome, duration, token_usage)
+            .record_finish(
+                self.endpoint_id,
+                outcome,
+                duration,
+                token_usage,
+                self.breaker_probe,
+            )
             .await
     }
 }
@@ -73,14 +108,24 @@ impl Drop for RequestLease {
         let Some(load_manager) = self.load_manager.take() else {
             return;
         };
+        if let Some(reservation_id) = self.reservation_id {
+            load_manager.release_reservation_slot(reservation_id);
+        }
 
         let endpoint_id = self.endpoint_id;
         let duration = self.started_at.elapsed();
+        let breaker_probe = self.breaker_probe;
 
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             handle.spawn(async move {
                 if let Err(err) = load_manager
-                    .finish_request(endpoint_id, RequestOutcome::Error, duration)
+                    .record_finish(
+                        endpoint_id,
+                        RequestOutcome::Cancelled,
+                        duration,
+                        None,
+                        breaker_probe,
+                    )
                     .await
                 {
                     tracing::warn!(
@@ -114,6 +159,8 @@ mod tests {
             load_manager: None,
             endpoint_id: id,
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         assert_eq!(lease.endpoint_id(), id);
     }
@@ -125,6 +172,8 @@ mod tests {
             load_manager: None,
             endpoint_id: id,
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         assert_eq!(lease.endpoint_id(), id);
         // Call again to verify determinism
@@ -137,6 +186,8 @@ mod tests {
             load_manager: None,
             endpoint_id: Uuid::new_v4(),
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         assert!(lease.elapsed() >= StdDuration::ZERO);
     }
@@ -147,6 +198,8 @@ mod tests {
             load_manager: None,
             endpoint_id: Uuid::new_v4(),
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         let e1 = lease.elapsed();
         // Busy wait briefly
@@ -163,6 +216,8 @@ mod tests {
             load_manager: None,
             endpoint_id: Uuid::new_v4(),
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         drop(lease);
         // No panic means the test passes
@@ -175,9 +230,11 @@ mod tests {
         let success = format!("{:?}", RequestOutcome::Success);
         let error = format!("{:?}", RequestOutcome::Error);
         let queued = format!("{:?}", RequestOutcome::Queued);
+        let cancelled = format!("{:?}", RequestOutcome::Cancelled);
         assert_eq!(success, "Success");
         assert_eq!(error, "Error");
         assert_eq!(queued, "Queued");
+        assert_eq!(cancelled, "Cancelled");
     }
 
     #[test]
@@ -195,6 +252,8 @@ mod tests {
             load_manager: None,
             endpoint_id: Uuid::nil(),
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         assert_eq!(
             lease.endpoint_id(),
@@ -209,6 +268,8 @@ mod tests {
             load_manager: None,
             endpoint_id: id,
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         assert_eq!(lease.endpoint_id(), id);
     }
@@ -223,11 +284,15 @@ mod tests {
             load_manager: None,
             endpoint_id: id1,
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         let lease2 = RequestLease {
             load_manager: None,
             endpoint_id: id2,
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         assert_ne!(lease1.endpoint_id(), lease2.endpoint_id());
     }
@@ -240,6 +305,8 @@ mod tests {
             load_manager: None,
             endpoint_id: Uuid::new_v4(),
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         let result = lease
             .complete(RequestOutcome::Success, StdDuration::from_millis(100))
@@ -253,6 +320,8 @@ mod tests {
             load_manager: None,
             endpoint_id: Uuid::new_v4(),
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         let result = lease
             .complete_with_tokens(RequestOutcome::Error, StdDuration::from_millis(200), None)
@@ -269,6 +338,8 @@ mod tests {
             load_manager: None,
             endpoint_id: Uuid::new_v4(),
             started_at: std::time::Instant::now(),
+            reservation_id: None,
+            breaker_probe: false,
         };
         let after = std::time::Instant::now();
         // elapsed should be between 0 and (after - before)
@@ -8,39 +8,98 @@
 //! このモジュールはEndpointRegistryを使用してエンドポイント情報を管理します。
 //! 負荷分散はTPS優先、同一TPS時はラウンドロビンで行われます。
 
+pub mod adaptive_rate;
+pub mod canary;
+pub mod concurrency_limit;
+pub mod context_routing;
+pub mod experiment;
+pub mod latency_baseline;
 pub mod lease;
+pub mod model_rate_limit;
+pub mod optimize;
+pub mod priority;
+pub mod required_tag;
+pub mod reservation;
+pub mod routing_policy;
+pub mod selection;
+pub mod session_affinity;
+pub mod shadow;
+pub mod stream_rate;
 pub mod types;
+pub mod weight_ramp;
 
 // Re-export all public types for backward compatibility
+pub use experiment::{Experiment, ExperimentAssignment, ExperimentGroup, ExperimentGroupStats};
+pub use latency_baseline::LatencyBaseline;
 pub use lease::RequestLease;
+pub use model_rate_limit::{model_rate_limiter, ModelRateLimit, ModelUsage};
+pub use reservation::{CapacityReservation, PrincipalType};
+pub use routing_policy::{NoMatchBehavior, RoutingPolicy};
+pub use selection::SelectionContext;
+pub use shadow::{ShadowStats, ShadowTarget};
+pub use stream_rate::{StreamRate, StreamRateLimit, StreamThrottle};
 #[allow(deprecated)]
 pub use types::NodeLoadSnapshot;
 pub use types::{
-    AdmissionDecision, EndpointLoadSnapshot, EndpointTpsSummary, MetricsUpdate, ModelTpsInfo,
-    ModelTpsState, RequestHistoryPoint, RequestOutcome, SystemSummary, WaitResult,
+    AdmissionDecision, CircuitState, EndpointLoadSnapshot, EndpointTpsSummary, MetricsUpdate,
+    ModelTpsInfo, ModelTpsState, RequestHistoryPoint, RequestOutcome, SystemSummary, WaitResult,
 };
+pub use weight_ramp::WeightRamp;
 
 use types::{EndpointLoadState, QueueWaiterGuard, TpsTrackerMap, REQUEST_HISTORY_WINDOW_MINUTES};
 
 use crate::common::error::{LbError, RouterResult};
 use crate::common::protocol::{TpsApiKind, TpsSource};
 use crate::registry::endpoints::EndpointRegistry;
+use crate::types::endpoint::{EndpointOperationalState, SupportedAPI};
 use crate::types::HealthMetrics;
 use chrono::{DateTime, Duration as ChronoDuration, Timelike, Utc};
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     sync::{
         atomic::{AtomicU64, AtomicUsize, Ordering as AtomicOrdering},
         Arc,
     },
-    time::Duration as StdDuration,
+    time::{Duration as StdDuration, Instant},
 };
 use tokio::sync::{Notify, RwLock};
 use uuid::Uuid;
 
+/// 重みに比例してエンドポイントを選ぶ
+///
+/// `point` は `[0, 重みの合計)` の値。重み0以下の候補は選ばれない。
+fn pick_weighted<T>(candidates: &[(T, f64)], point: f64) -> Option<&T> {
+    let mut cumulative = 0.0;
+    let mut last_positive = None;
+    for (candidate, weight) in candidates {
+        if *weight <= 0.0 {
+            continue;
+        }
+        cumulative += weight;
+        last_positive = Some(candidate);
+        if point < cumulative {
+            return Some(candidate);
+        }
+    }
+    // 浮動小数点誤差で合計に届いた場合は最後の候補
+    last_positive
+}
+
 /// LoadManagerインスタンスIDの採番カウンタ
 static NEXT_LOAD_MANAGER_ID: AtomicU64 = AtomicU64::new(1);
 
+/// ramp中であれば補間値、それ以外はエンドポイントの重みを返す
+fn effective_weight_at(
+    endpoint: &crate::types::endpoint::Endpoint,
+    ramp: Option<&WeightRamp>,
+    now: Instant,
+) -> f64 {
+    match ramp {
+        Some(ramp) if !ramp.is_finished(now) => ramp.effective_at(now),
+        _ => endpoint.weight as f64,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -225,7 +284,7 @@ mod tests {
         let (load_manager, endpoint_id) = setup_test_load_manager().await;
 
         let lease = load_manager
-            .begin_request(endpoint_id)
+            .begin_request(endpoint_id, None)
             .await
             .expect("begin_request should succeed");
 
@@ -256,7 +315,7 @@ mod tests {
 
         {
             let _lease = load_manager
-                .begin_request(endpoint_id)
+                .begin_request(endpoint_id, None)
                 .await
                 .expect("begin_request should succeed");
         }
@@ -354,419 +413,1926 @@ mod tests {
         }
     }
 
-    // SPEC-4bb5b55f T002: ModelTpsState EMA計算テスト
-
     #[test]
-    fn test_model_tps_state_initial_none() {
-        let state = ModelTpsState::default();
-        assert!(state.tps_ema.is_none());
-        assert_eq!(state.request_count, 0);
-        assert_eq!(state.total_output_tokens, 0);
-        assert_eq!(state.total_duration_ms, 0);
+    fn pick_weighted_is_proportional_and_skips_zero_weight() {
+        let candidates = [("zero", 0.0), ("a", 1.0), ("b", 3.0)];
+        assert_eq!(pick_weighted(&candidates, 0.0), Some(&"a"));
+        assert_eq!(pick_weighted(&candidates, 0.99), Some(&"a"));
+        assert_eq!(pick_weighted(&candidates, 1.0), Some(&"b"));
+        assert_eq!(pick_weighted(&candidates, 3.99), Some(&"b"));
+        assert_eq!(pick_weighted(&candidates, 4.0), Some(&"b"));
+        assert_eq!(pick_weighted(&[("zero", 0.0)], 0.0), None);
     }
 
-    #[test]
-    fn test_model_tps_state_first_update() {
-        let mut state = ModelTpsState::default();
-        state.update_tps(100, 2000);
-        assert!(state.tps_ema.is_some());
-        let tps = state.tps_ema.unwrap();
-        assert!(
-            (tps - 50.0).abs() < 0.01,
-            "初回TPS: expected 50.0, got {tps}"
-        );
-        assert_eq!(state.request_count, 1);
-        assert_eq!(state.total_output_tokens, 100);
-        assert_eq!(state.total_duration_ms, 2000);
-    }
+    #[tokio::test]
+    async fn select_endpoint_weighted_for_model_follows_weights() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
 
-    #[test]
-    fn test_model_tps_state_ema_smoothing() {
-        let mut state = ModelTpsState::default();
+        let model_id = "gpt-oss:latest".to_string();
+        let mut ids = Vec::new();
+        for (index, weight) in [3u32, 1, 0, 5].into_iter().enumerate() {
+            let mut endpoint = Endpoint::new(
+                format!("weighted-{}", index),
+                format!("http://localhost:{}", 11090 + index),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            endpoint.weight = weight;
+            ids.push(endpoint.id);
+            registry.add(endpoint).await.expect("add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id: ids[index],
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("add endpoint model");
+        }
+        let (heavy, light, zero, initializing) = (ids[0], ids[1], ids[2], ids[3]);
 
-        state.update_tps(100, 2000);
-        assert!((state.tps_ema.unwrap() - 50.0).abs() < 0.01);
+        let load_manager = LoadManager::new(Arc::new(registry));
+        load_manager
+            .upsert_initial_state(initializing, true, Some((0, 1)))
+            .await;
 
-        state.update_tps(200, 2000);
+        let mut counts: HashMap<Uuid, u32> = HashMap::new();
+        for _ in 0..2000 {
+            let selected = load_manager
+                .select_endpoint_weighted_for_model(&model_id, None, &SelectionContext::default())
+                .await
+                .expect("selection should succeed");
+            *counts.entry(selected.id).or_default() += 1;
+        }
+        assert!(!counts.contains_key(&zero), "weight 0 must not be selected");
         assert!(
-            (state.tps_ema.unwrap() - 60.0).abs() < 0.01,
-            "2回目EMA: expected 60.0, got {}",
-            state.tps_ema.unwrap()
+            !counts.contains_key(&initializing),
+            "initializing endpoint must not be selected"
         );
+        let heavy_count = counts.get(&heavy).copied().unwrap_or(0);
+        let light_count = counts.get(&light).copied().unwrap_or(0);
+        // 期待値は 1500 : 500
+        assert!((1300..=1700).contains(&heavy_count), "heavy={heavy_count}");
+        assert_eq!(heavy_count + light_count, 2000);
+    }
+
+    /// 同一モデルを提供する2エンドポイントのうち1つ目を `holder` 専用に全枠予約した
+    /// LoadManagerを作る（戻り値: LoadManager, 予約済みID, 空きID, モデルID, holder）
+    async fn setup_fully_reserved_pair(
+        port_base: u16,
+    ) -> (
+        LoadManager,
+        Uuid,
+        Uuid,
+        String,
+        reservation::RequestPrincipal,
+    ) {
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
 
-        state.update_tps(50, 1000);
-        assert!(
-            (state.tps_ema.unwrap() - 58.0).abs() < 0.01,
-            "3回目EMA: expected 58.0, got {}",
-            state.tps_ema.unwrap()
-        );
+        let model_id = "gpt-oss:latest".to_string();
+        let mut ids = Vec::new();
+        for index in 0..2u16 {
+            let mut endpoint = Endpoint::new(
+                format!("reserved-pair-{}", index),
+                format!("http://localhost:{}", port_base + index),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            ids.push(endpoint.id);
+            registry.add(endpoint).await.expect("add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id: ids[index as usize],
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("add endpoint model");
+        }
 
-        assert_eq!(state.request_count, 3);
-        assert_eq!(state.total_output_tokens, 350);
-        assert_eq!(state.total_duration_ms, 5000);
+        let mut load_manager = LoadManager::new(Arc::new(registry));
+        load_manager.endpoint_slots = 1;
+        let holder = reservation::RequestPrincipal {
+            api_key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+        };
+        let now = Utc::now();
+        load_manager
+            .set_reservations(vec![CapacityReservation {
+                id: Uuid::new_v4(),
+                endpoint_id: ids[0],
+                principal_type: PrincipalType::ApiKey,
+                principal_id: holder.api_key_id,
+                slots: 1,
+                soft: false,
+                created_at: now,
+                updated_at: now,
+            }])
+            .await;
+        (load_manager, ids[0], ids[1], model_id, holder)
     }
 
-    #[test]
-    fn test_model_tps_state_zero_duration_skipped() {
-        let mut state = ModelTpsState::default();
-        state.update_tps(100, 0);
-        assert!(state.tps_ema.is_none(), "duration=0ではTPS更新しない");
+    fn principal_ctx(principal: reservation::RequestPrincipal) -> SelectionContext {
+        SelectionContext {
+            principal: Some(principal),
+            ..Default::default()
+        }
     }
 
     #[tokio::test]
-    async fn test_get_model_tps_empty_for_unknown_endpoint() {
+    async fn select_endpoint_weighted_for_model_skips_fully_reserved_endpoint() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let unknown_id = Uuid::new_v4();
-        let result = load_manager.get_model_tps(unknown_id).await;
-        assert!(result.is_empty(), "未計測エンドポイントは空Vecを返す");
+        let (load_manager, reserved, free, model_id, holder) =
+            setup_fully_reserved_pair(11095).await;
+        let other = reservation::RequestPrincipal {
+            api_key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+        };
+
+        for _ in 0..50 {
+            let selected = load_manager
+                .select_endpoint_weighted_for_model(&model_id, None, &principal_ctx(other))
+                .await
+                .expect("selection should succeed");
+            assert_eq!(selected.id, free, "reserved endpoint must be skipped");
+        }
+
+        let mut selected = std::collections::HashSet::new();
+        for _ in 0..50 {
+            let endpoint = load_manager
+                .select_endpoint_weighted_for_model(&model_id, None, &principal_ctx(holder))
+                .await
+                .expect("selection should succeed");
+            selected.insert(endpoint.id);
+        }
+        assert!(
+            selected.contains(&reserved),
+            "holder may use its reservation"
+        );
     }
 
     #[tokio::test]
-    async fn test_get_model_tps_returns_entries_after_update() {
+    async fn select_endpoint_least_connections_for_model_prefers_fewest_active() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
+
+        let model_id = "gpt-oss:latest".to_string();
+        let mut ids = Vec::new();
+        for index in 0..4 {
+            let mut endpoint = Endpoint::new(
+                format!("least-conn-{}", index),
+                format!("http://localhost:{}", 11100 + index),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            ids.push(endpoint.id);
+            registry.add(endpoint).await.expect("add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id: ids[index],
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("add endpoint model");
+        }
+        let (slow, fast, busy, initializing) = (ids[0], ids[1], ids[2], ids[3]);
 
+        let load_manager = LoadManager::new(Arc::new(registry));
         load_manager
-            .update_tps(
-                endpoint_id,
-                "model-a".to_string(),
-                TpsApiKind::ChatCompletions,
-                100,
-                2000,
-            )
+            .upsert_initial_state(initializing, true, Some((0, 1)))
             .await;
+        for (endpoint_id, latency_ms) in [(slow, 900), (fast, 50)] {
+            load_manager
+                .finish_request(
+                    endpoint_id,
+                    RequestOutcome::Success,
+                    StdDuration::from_millis(latency_ms),
+                )
+                .await
+                .expect("finish_request should succeed");
+        }
 
-        let result = load_manager.get_model_tps(endpoint_id).await;
-        assert_eq!(result.len(), 1, "1モデル分のTPS情報が返る");
-        assert_eq!(result[0].model_id, "model-a");
-        assert_eq!(result[0].api_kind, TpsApiKind::ChatCompletions);
-        assert_eq!(result[0].request_count, 1);
-        assert_eq!(result[0].total_output_tokens, 100);
-        let tps = result[0].tps.expect("TPS値がSomeであること");
-        assert!((tps - 50.0).abs() < 0.01, "TPS = 50.0");
+        // 全エンドポイントがアイドルならラウンドロビン
+        let mut selected = std::collections::HashSet::new();
+        for _ in 0..3 {
+            let endpoint = load_manager
+                .select_endpoint_least_connections_for_model(
+                    &model_id,
+                    None,
+                    &SelectionContext::default(),
+                )
+                .await
+                .expect("selection should succeed");
+            selected.insert(endpoint.id);
+        }
+        assert_eq!(
+            selected,
+            std::collections::HashSet::from([slow, fast, busy])
+        );
+
+        // 処理中リクエストが最小のエンドポイントを選ぶ（同数ならレイテンシが低い方）
+        let mut leases = Vec::new();
+        for endpoint_id in [slow, fast, busy, busy] {
+            leases.push(load_manager.begin_request(endpoint_id, None).await.unwrap());
+        }
+        let endpoint = load_manager
+            .select_endpoint_least_connections_for_model(
+                &model_id,
+                None,
+                &SelectionContext::default(),
+            )
+            .await
+            .expect("selection should succeed");
+        assert_eq!(endpoint.id, fast);
+
+        leases.push(load_manager.begin_request(fast, None).await.unwrap());
+        let endpoint = load_manager
+            .select_endpoint_least_connections_for_model(
+                &model_id,
+                None,
+                &SelectionContext::default(),
+            )
+            .await
+            .expect("selection should succeed");
+        assert_eq!(endpoint.id, slow);
     }
 
     #[tokio::test]
-    async fn test_get_model_tps_multiple_models() {
+    async fn select_endpoint_least_connections_for_model_skips_fully_reserved_endpoint() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let (load_manager, reserved, free, model_id, holder) =
+            setup_fully_reserved_pair(11105).await;
+        let other = reservation::RequestPrincipal {
+            api_key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+        };
 
-        load_manager
-            .update_tps(
-                endpoint_id,
-                "model-a".to_string(),
-                TpsApiKind::ChatCompletions,
-                100,
-                2000,
-            )
-            .await;
-        load_manager
-            .update_tps(
-                endpoint_id,
-                "model-b".to_string(),
-                TpsApiKind::Completions,
-                200,
-                1000,
-            )
-            .await;
+        // 全エンドポイントがアイドルのラウンドロビンでも予約済みエンドポイントは選ばない
+        for _ in 0..4 {
+            let selected = load_manager
+                .select_endpoint_least_connections_for_model(&model_id, None, &principal_ctx(other))
+                .await
+                .expect("selection should succeed");
+            assert_eq!(selected.id, free, "reserved endpoint must be skipped");
+        }
 
-        let result = load_manager.get_model_tps(endpoint_id).await;
-        assert_eq!(result.len(), 2, "2モデル分のTPS情報が返る");
+        // 空きエンドポイントが処理中でも予約済みエンドポイントへは流さない
+        let lease = load_manager.begin_request(free, None).await.unwrap();
+        let selected = load_manager
+            .select_endpoint_least_connections_for_model(&model_id, None, &principal_ctx(other))
+            .await
+            .expect("selection should succeed");
+        assert_eq!(selected.id, free);
 
-        let model_ids: Vec<&str> = result.iter().map(|e| e.model_id.as_str()).collect();
-        assert!(model_ids.contains(&"model-a"));
-        assert!(model_ids.contains(&"model-b"));
+        let selected = load_manager
+            .select_endpoint_least_connections_for_model(&model_id, None, &principal_ctx(holder))
+            .await
+            .expect("selection should succeed");
+        assert_eq!(selected.id, reserved);
+        lease
+            .complete(RequestOutcome::Success, StdDuration::from_millis(1))
+            .await
+            .unwrap();
     }
 
     #[tokio::test]
-    async fn test_get_model_tps_separates_api_kind_for_same_model() {
+    async fn select_endpoint_sticky_reuses_binding_and_rebinds_when_unavailable() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
 
-        load_manager
-            .update_tps(
-                endpoint_id,
-                "shared-model".to_string(),
-                TpsApiKind::ChatCompletions,
-                100,
-                2000,
-            )
-            .await;
-        load_manager
-            .update_tps(
-                endpoint_id,
-                "shared-model".to_string(),
-                TpsApiKind::Responses,
-                80,
-                2000,
+        let model_id = "gpt-oss:latest".to_string();
+        let mut ids = Vec::new();
+        for index in 0..2 {
+            let mut endpoint = Endpoint::new(
+                format!("sticky-{}", index),
+                format!("http://localhost:{}", 11200 + index),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            ids.push(endpoint.id);
+            registry.add(endpoint).await.expect("add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id: ids[index],
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("add endpoint model");
+        }
+
+        let load_manager = LoadManager::new(Arc::new(registry));
+        let mode = crate::config::LoadBalancerMode::Auto;
+
+        // 同じセッションIDは同じエンドポイントへ固定される
+        let first = load_manager
+            .select_endpoint_sticky(
+                mode,
+                &model_id,
+                "session-a",
+                None,
+                &SelectionContext::default(),
             )
+            .await
+            .expect("selection should succeed");
+        for _ in 0..5 {
+            let endpoint = load_manager
+                .select_endpoint_sticky(
+                    mode,
+                    &model_id,
+                    "session-a",
+                    None,
+                    &SelectionContext::default(),
+                )
+                .await
+                .expect("selection should succeed");
+            assert_eq!(endpoint.id, first.id);
+        }
+
+        // 割り当て先が初期化中になったら別のエンドポイントへ再バインドする
+        load_manager
+            .upsert_initial_state(first.id, true, Some((0, 1)))
             .await;
+        let rebound = load_manager
+            .select_endpoint_sticky(
+                mode,
+                &model_id,
+                "session-a",
+                None,
+                &SelectionContext::default(),
+            )
+            .await
+            .expect("selection should succeed");
+        assert_ne!(rebound.id, first.id);
 
-        let result = load_manager.get_model_tps(endpoint_id).await;
-        assert_eq!(result.len(), 2, "同一モデルでもAPI種別ごとに分離される");
-        assert!(result.iter().any(|entry| entry.model_id == "shared-model"
-            && entry.api_kind == TpsApiKind::ChatCompletions));
-        assert!(result.iter().any(
-            |entry| entry.model_id == "shared-model" && entry.api_kind == TpsApiKind::Responses
-        ));
+        load_manager
+            .upsert_initial_state(first.id, false, Some((1, 1)))
+            .await;
+        let endpoint = load_manager
+            .select_endpoint_sticky(
+                mode,
+                &model_id,
+                "session-a",
+                None,
+                &SelectionContext::default(),
+            )
+            .await
+            .expect("selection should succeed");
+        assert_eq!(endpoint.id, rebound.id);
+
+        assert_eq!(load_manager.purge_expired_sessions(), 0);
     }
 
     #[tokio::test]
-    async fn test_update_tps_skips_zero_tokens() {
+    async fn canary_split_uses_request_roll_and_keeps_sticky_binding() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
 
-        load_manager
-            .update_tps(
-                endpoint_id,
-                "model-a".to_string(),
-                TpsApiKind::ChatCompletions,
-                0,
-                2000,
-            )
-            .await;
+        let model_id = "gpt-oss:latest".to_string();
+        let mut ids = Vec::new();
+        for (index, canary_percent) in [None, Some(10u8)].into_iter().enumerate() {
+            let mut endpoint = Endpoint::new(
+                format!("canary-split-{}", index),
+                format!("http://localhost:{}", 11210 + index),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            endpoint.canary_percent = canary_percent;
+            ids.push(endpoint.id);
+            registry.add(endpoint).await.expect("add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id: ids[index],
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("add endpoint model");
+        }
 
-        let result = load_manager.get_model_tps(endpoint_id).await;
-        assert!(result.is_empty(), "output_tokens=0はTPS更新しない");
+        let load_manager = LoadManager::new(Arc::new(registry));
+        let mode = crate::config::LoadBalancerMode::Auto;
+        let ctx = |roll: u8| SelectionContext {
+            canary_roll: Some(roll),
+            ..Default::default()
+        };
+
+        // 振り分けはリクエストの乱数1回で決まる（カナリア10%: 0〜9）
+        for _ in 0..5 {
+            let stable = load_manager
+                .select_endpoint_by_mode(mode, &model_id, None, &ctx(10))
+                .await
+                .expect("selection should succeed");
+            assert_eq!(stable.id, ids[0]);
+            let canary = load_manager
+                .select_endpoint_by_mode(mode, &model_id, None, &ctx(9))
+                .await
+                .expect("selection should succeed");
+            assert_eq!(canary.id, ids[1]);
+        }
+
+        // カナリアに固定されたセッションは、後続リクエストの乱数に関わらず同じエンドポイントを使う
+        let bound = load_manager
+            .select_endpoint_sticky(mode, &model_id, "canary-session", None, &ctx(0))
+            .await
+            .expect("selection should succeed");
+        assert_eq!(bound.id, ids[1]);
+        for roll in [10, 50, 99] {
+            let endpoint = load_manager
+                .select_endpoint_sticky(mode, &model_id, "canary-session", None, &ctx(roll))
+                .await
+                .expect("selection should succeed");
+            assert_eq!(endpoint.id, ids[1]);
+        }
     }
 
     #[tokio::test]
-    async fn test_get_model_tps_isolates_endpoints() {
+    async fn failover_to_is_preferred_when_endpoint_unavailable() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let other_endpoint_id = Uuid::new_v4();
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
+
+        let model_id = "gpt-oss:latest".to_string();
+        let mut ids = Vec::new();
+        for index in 0..3 {
+            let mut endpoint = Endpoint::new(
+                format!("failover-{}", index),
+                format!("http://localhost:{}", 11300 + index),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            ids.push(endpoint.id);
+            registry.add(endpoint).await.expect("add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id: ids[index],
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("add endpoint model");
+        }
+        // 0 の優先フェイルオーバー先は 2
+        let mut primary = registry.get(ids[0]).await.expect("endpoint exists");
+        primary.failover_to = Some(ids[2]);
+        registry.update(primary).await.expect("update endpoint");
+
+        let load_manager = LoadManager::new(Arc::new(registry));
+
+        // 0 が失敗したら 2 へ回す
+        for _ in 0..5 {
+            let endpoint = load_manager
+                .select_endpoint_by_tps_ready_for_model_excluding(
+                    &model_id,
+                    None,
+                    &SelectionContext::default(),
+                    &[ids[0]],
+                )
+                .await
+                .expect("selection should succeed");
+            assert_eq!(endpoint.id, ids[2]);
+        }
 
+        // セッションの割り当て先 0 が初期化中になった場合も 2 へ再バインドする
+        load_manager.bind_session("session-a", ids[0]);
         load_manager
-            .update_tps(
-                endpoint_id,
-                "model-a".to_string(),
-                TpsApiKind::ChatCompletions,
-                100,
-                2000,
-            )
+            .upsert_initial_state(ids[0], true, Some((0, 1)))
             .await;
-        load_manager
-            .update_tps(
-                other_endpoint_id,
-                "model-b".to_string(),
-                TpsApiKind::Completions,
-                200,
-                1000,
+        let endpoint = load_manager
+            .select_endpoint_sticky(
+                crate::config::LoadBalancerMode::Auto,
+                &model_id,
+                "session-a",
+                None,
+                &SelectionContext::default(),
             )
-            .await;
+            .await
+            .expect("selection should succeed");
+        assert_eq!(endpoint.id, ids[2]);
 
-        let result = load_manager.get_model_tps(endpoint_id).await;
-        assert_eq!(result.len(), 1, "他エンドポイントのデータは含まない");
-        assert_eq!(result[0].model_id, "model-a");
+        // 代替も選択不可なら通常選択に戻る
+        load_manager
+            .upsert_initial_state(ids[2], true, Some((0, 1)))
+            .await;
+        let endpoint = load_manager
+            .select_endpoint_by_tps_ready_for_model_excluding(
+                &model_id,
+                None,
+                &SelectionContext::default(),
+                &[ids[0]],
+            )
+            .await
+            .expect("selection should succeed");
+        assert_eq!(endpoint.id, ids[1]);
     }
 
     #[tokio::test]
-    async fn test_get_all_endpoint_tps_empty() {
+    async fn endpoints_short_of_free_vram_are_skipped() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
 
-        let result = load_manager.get_all_endpoint_tps().await;
-        assert!(result.is_empty(), "TPS未計測の場合は空");
+        let model_id = "llama3:8b".to_string();
+        let mut ids = Vec::new();
+        for index in 0..2 {
+            let mut endpoint = Endpoint::new(
+                format!("vram-{}", index),
+                format!("http://localhost:{}", 11400 + index),
+                EndpointType::Ollama,
+            );
+            endpoint.status = EndpointStatus::Online;
+            ids.push(endpoint.id);
+            registry.add(endpoint).await.expect("add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id: ids[index],
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("add endpoint model");
+            crate::models::vram::record_required_vram(ids[index], &model_id, Some(8000));
+        }
+
+        let load_manager = LoadManager::new(Arc::new(registry));
+        let record_used = |endpoint_id: Uuid, used_mb: u64| MetricsUpdate {
+            endpoint_id,
+            cpu_usage: 10.0,
+            memory_usage: 20.0,
+            gpu_usage: None,
+            gpu_memory_usage: None,
+            gpu_memory_total_mb: Some(16384),
+            gpu_memory_used_mb: Some(used_mb),
+            gpu_temperature: None,
+            gpu_model_name: None,
+            gpu_compute_capability: None,
+            gpu_capability_score: None,
+            active_requests: 0,
+            average_response_time_ms: None,
+            initializing: false,
+            ready_models: Some((1, 1)),
+        };
+
+        // 空き 4GB と 12GB → 必要 8000MB を満たすのは 1 のみ
+        for (endpoint_id, used_mb) in [(ids[0], 12288), (ids[1], 4096)] {
+            load_manager
+                .record_metrics(record_used(endpoint_id, used_mb))
+                .await
+                .expect("record metrics");
+        }
+        for _ in 0..5 {
+            let endpoint = load_manager
+                .select_endpoint_by_tps_ready_for_model_excluding(
+                    &model_id,
+                    None,
+                    &SelectionContext::default(),
+                    &[],
+                )
+                .await
+                .expect("selection should succeed");
+            assert_eq!(endpoint.id, ids[1]);
+        }
+
+        // 全候補が不足している場合は空きが最大のエンドポイントを選ぶ
+        for (endpoint_id, used_mb) in [(ids[0], 10240), (ids[1], 14336)] {
+            load_manager
+                .record_metrics(record_used(endpoint_id, used_mb))
+                .await
+                .expect("record metrics");
+        }
+        for _ in 0..5 {
+            let endpoint = load_manager
+                .select_endpoint_by_tps_ready_for_model_excluding(
+                    &model_id,
+                    None,
+                    &SelectionContext::default(),
+                    &[],
+                )
+                .await
+                .expect("selection should succeed");
+            assert_eq!(endpoint.id, ids[0]);
+        }
+
+        for endpoint_id in ids {
+            crate::models::vram::record_required_vram(endpoint_id, &model_id, None);
+        }
     }
 
     #[tokio::test]
-    async fn test_get_all_endpoint_tps_returns_per_endpoint_summary() {
+    async fn select_endpoint_by_mode_prefers_lowest_ttft_when_requested() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let other_endpoint_id = Uuid::new_v4();
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
+
+        let model_id = "gpt-oss:latest".to_string();
+        let mut ids = Vec::new();
+        for index in 0..3 {
+            let mut endpoint = Endpoint::new(
+                format!("ttft-{}", index),
+                format!("http://localhost:{}", 11300 + index),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            ids.push(endpoint.id);
+            registry.add(endpoint).await.expect("add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id: ids[index],
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("add endpoint model");
+        }
+        let (slow, fast) = (ids[0], ids[1]);
+
+        let load_manager = LoadManager::new(Arc::new(registry));
+        let mode = crate::config::LoadBalancerMode::Auto;
+        let ttft = SelectionContext {
+            optimize_target: Some(optimize::OptimizeTarget::Ttft),
+            ..Default::default()
+        };
+
+        // TTFT計測済みの候補が無ければモードに従う
+        let endpoint = load_manager
+            .select_endpoint_by_mode(mode, &model_id, None, &ttft)
+            .await
+            .expect("selection should succeed");
+        assert!(ids.contains(&endpoint.id));
 
         load_manager
-            .update_tps(
-                endpoint_id,
-                "model-a".to_string(),
-                TpsApiKind::ChatCompletions,
-                100,
-                2000,
-            )
+            .record_ttft(slow, StdDuration::from_millis(800))
             .await;
         load_manager
-            .update_tps(
-                endpoint_id,
-                "model-b".to_string(),
-                TpsApiKind::Completions,
-                200,
-                1000,
-            )
+            .record_ttft(fast, StdDuration::from_millis(120))
             .await;
-        load_manager
-            .update_tps(
-                other_endpoint_id,
-                "model-c".to_string(),
-                TpsApiKind::Responses,
-                50,
-                500,
-            )
-            .await;
-
-        let result = load_manager.get_all_endpoint_tps().await;
-        assert_eq!(result.len(), 2, "2エンドポイント分のサマリ");
-
-        let ep1 = result
-            .iter()
-            .find(|s| s.endpoint_id == endpoint_id)
-            .expect("endpoint_id存在");
-        assert_eq!(ep1.model_count, 2);
-        assert_eq!(ep1.total_output_tokens, 300);
-        assert!(ep1.aggregate_tps.is_some());
+        for _ in 0..3 {
+            let endpoint = load_manager
+                .select_endpoint_by_mode(mode, &model_id, None, &ttft)
+                .await
+                .expect("selection should succeed");
+            assert_eq!(endpoint.id, fast);
+        }
 
-        let ep2 = result
-            .iter()
-            .find(|s| s.endpoint_id == other_endpoint_id)
-            .expect("other存在");
-        assert_eq!(ep2.model_count, 1);
-        assert_eq!(ep2.total_output_tokens, 50);
+        let snapshot = load_manager.snapshot(fast).await.expect("snapshot");
+        assert_eq!(snapshot.ttft_ema_ms, Some(120.0));
     }
 
-    /// T013 [US5]: seed_history_from_db が MinuteHistoryPoint を VecDeque に
-    /// 正しく投入できることを検証
     #[tokio::test]
-    async fn test_seed_history_from_db() {
+    async fn select_endpoint_by_tps_ready_for_model_excluding_skips_excluded() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
 
-        let now = Utc::now();
-        let minute1 = super::align_to_minute(now - chrono::Duration::minutes(5));
-        let minute2 = super::align_to_minute(now - chrono::Duration::minutes(3));
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
 
-        let points = vec![
-            crate::db::request_history::MinuteHistoryPoint {
-                minute: minute1.to_rfc3339(),
-                success_count: 10,
-                error_count: 2,
-            },
-            crate::db::request_history::MinuteHistoryPoint {
-                minute: minute2.to_rfc3339(),
-                success_count: 5,
-                error_count: 1,
-            },
-        ];
+        let model_id = "gpt-oss:latest".to_string();
+        let mut endpoint_ids = Vec::new();
+        for (name, url) in [
+            ("failed-endpoint", "http://localhost:11082"),
+            ("fallback-endpoint", "http://localhost:11083"),
+        ] {
+            let mut endpoint = Endpoint::new(
+                name.to_string(),
+                url.to_string(),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            let endpoint_id = endpoint.id;
+            registry
+                .add(endpoint)
+                .await
+                .expect("Failed to add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id,
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("Failed to add endpoint model");
+            endpoint_ids.push(endpoint_id);
+        }
 
-        load_manager.seed_history_from_db(points).await;
+        let load_manager = LoadManager::new(Arc::new(registry));
+        for _ in 0..4 {
+            let selected = load_manager
+                .select_endpoint_by_tps_ready_for_model_excluding(
+                    &model_id,
+                    Some(TpsApiKind::ChatCompletions),
+                    &SelectionContext::default(),
+                    &endpoint_ids[..1],
+                )
+                .await
+                .expect("selection should succeed");
+            assert_eq!(selected.id, endpoint_ids[1]);
+        }
 
-        let history = load_manager.request_history().await;
-        // VecDeque には seed したデータが含まれる
-        let total_success: u64 = history.iter().map(|h| h.success).sum();
-        let total_error: u64 = history.iter().map(|h| h.error).sum();
-        assert_eq!(total_success, 15, "seeded success count");
-        assert_eq!(total_error, 3, "seeded error count");
+        let exhausted = load_manager
+            .select_endpoint_by_tps_ready_for_model_excluding(
+                &model_id,
+                Some(TpsApiKind::ChatCompletions),
+                &SelectionContext::default(),
+                &endpoint_ids,
+            )
+            .await;
+        assert!(exhausted.is_err());
+
+        // モードに従う選択でも試行済みエンドポイントを除外する
+        for mode in [
+            crate::config::LoadBalancerMode::Auto,
+            crate::config::LoadBalancerMode::Weighted,
+            crate::config::LoadBalancerMode::LeastConn,
+        ] {
+            for _ in 0..4 {
+                let selected = load_manager
+                    .select_endpoint_by_mode_excluding(
+                        mode,
+                        &model_id,
+                        Some(TpsApiKind::ChatCompletions),
+                        &SelectionContext::default(),
+                        &endpoint_ids[..1],
+                    )
+                    .await
+                    .expect("selection should succeed");
+                assert_eq!(selected.id, endpoint_ids[1], "mode={mode:?}");
+            }
+            let exhausted = load_manager
+                .select_endpoint_by_mode_excluding(
+                    mode,
+                    &model_id,
+                    Some(TpsApiKind::ChatCompletions),
+                    &SelectionContext::default(),
+                    &endpoint_ids,
+                )
+                .await;
+            assert!(exhausted.is_err(), "mode={mode:?}");
+        }
     }
 
-    /// T018 [US4]: seed_tps_from_db が TpsSeedEntry から TpsTrackerMap に
-    /// 正しく TPS EMA を計算して投入できることを検証
     #[tokio::test]
-    async fn test_seed_tps_from_db() {
+    async fn select_endpoint_for_api_routes_only_to_supporting_endpoints() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
 
-        let entries = vec![
-            crate::db::endpoint_daily_stats::TpsSeedEntry {
-                endpoint_id,
-                model_id: "test-model".to_string(),
-                api_kind: "chat_completions".to_string(),
-                total_output_tokens: 100,
-                total_duration_ms: 2000,
-                successful_requests: 5,
-            },
-            // duration_ms=0 のエントリはスキップされること
-            crate::db::endpoint_daily_stats::TpsSeedEntry {
-                endpoint_id,
-                model_id: "skip-model".to_string(),
-                api_kind: "chat_completions".to_string(),
-                total_output_tokens: 100,
-                total_duration_ms: 0,
-                successful_requests: 1,
-            },
-            // tokens=0 のエントリもスキップされること
-            crate::db::endpoint_daily_stats::TpsSeedEntry {
-                endpoint_id,
-                model_id: "skip-model2".to_string(),
-                api_kind: "chat_completions".to_string(),
-                total_output_tokens: 0,
-                total_duration_ms: 1000,
-                successful_requests: 1,
-            },
-        ];
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
 
-        load_manager.seed_tps_from_db(entries).await;
+        let model_id = "bge-m3".to_string();
+        let mut endpoint_ids = Vec::new();
+        for (name, url, apis) in [
+            (
+                "chat-endpoint",
+                "http://localhost:11084",
+                vec![SupportedAPI::ChatCompletions],
+            ),
+            (
+                "embedding-endpoint",
+                "http://localhost:11085",
+                vec![SupportedAPI::Embeddings],
+            ),
+        ] {
+            let mut endpoint = Endpoint::new(
+                name.to_string(),
+                url.to_string(),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            let endpoint_id = endpoint.id;
+            registry
+                .add(endpoint)
+                .await
+                .expect("Failed to add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id,
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: apis,
+                    canonical_name: None,
+                })
+                .await
+                .expect("Failed to add endpoint model");
+            endpoint_ids.push(endpoint_id);
+        }
 
-        let tps_data = load_manager.get_all_endpoint_tps().await;
-        // test-model のみ seed されている
-        assert!(!tps_data.is_empty(), "should have TPS data");
-        let ep_tps = tps_data
-            .iter()
-            .find(|t| t.endpoint_id == endpoint_id)
-            .expect("should have endpoint TPS");
-        // TPS = 100 / (2000/1000) = 50.0
-        assert_eq!(ep_tps.model_count, 1, "only valid entry should be seeded");
-        assert!(ep_tps.aggregate_tps.is_some(), "should have aggregate TPS");
-        let tps = ep_tps.aggregate_tps.unwrap();
-        assert!((tps - 50.0).abs() < 0.1, "TPS should be ~50.0, got {tps}");
+        let load_manager = LoadManager::new(Arc::new(registry));
+        // どのモードでも、sticky sessionでも対応エンドポイントのみから選ぶ
+        for mode in [
+            crate::config::LoadBalancerMode::Auto,
+            crate::config::LoadBalancerMode::Weighted,
+            crate::config::LoadBalancerMode::LeastConn,
+        ] {
+            for _ in 0..4 {
+                let selected = load_manager
+                    .select_endpoint_for_api(
+                        mode,
+                        &model_id,
+                        SupportedAPI::Embeddings,
+                        &SelectionContext::default(),
+                    )
+                    .await
+                    .expect("selection should succeed");
+                assert_eq!(selected.id, endpoint_ids[1], "mode={mode:?}");
+            }
+        }
+        let sticky_ctx = SelectionContext {
+            session_id: Some("embedding-session".to_string()),
+            ..Default::default()
+        };
+        let selected = load_manager
+            .select_endpoint_for_api(
+                crate::config::LoadBalancerMode::Auto,
+                &model_id,
+                SupportedAPI::Embeddings,
+                &sticky_ctx,
+            )
+            .await
+            .expect("selection should succeed");
+        assert_eq!(selected.id, endpoint_ids[1]);
+
+        let unsupported = load_manager
+            .select_endpoint_for_api(
+                crate::config::LoadBalancerMode::Auto,
+                &model_id,
+                SupportedAPI::Responses,
+                &SelectionContext::default(),
+            )
+            .await;
+        assert!(matches!(unsupported, Err(LbError::ServiceUnavailable(_))));
     }
 
-    // ===== 追加テスト: LoadManager 基本機能 =====
+    #[tokio::test]
+    async fn select_endpoint_by_tps_ready_for_model_prefers_lower_p95_on_tie() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
 
-    #[test]
-    fn endpoint_load_state_default_values() {
-        let state = EndpointLoadState::default();
-        assert_eq!(state.assigned_active, 0);
-        assert_eq!(state.total_assigned, 0);
-        assert_eq!(state.success_count, 0);
-        assert_eq!(state.error_count, 0);
-        assert_eq!(state.total_latency_ms, 0);
-        assert!(!state.initializing);
-        assert!(state.ready_models.is_none());
-        assert!(state.last_metrics.is_none());
-        assert!(state.metrics_history.is_empty());
-        assert_eq!(state.total_input_tokens, 0);
-        assert_eq!(state.total_output_tokens, 0);
-        assert_eq!(state.total_tokens, 0);
-    }
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
 
-    #[test]
-    fn endpoint_load_state_combined_active_no_metrics() {
-        let state = EndpointLoadState {
-            assigned_active: 3,
-            ..Default::default()
-        };
-        assert_eq!(state.combined_active(), 3);
-    }
+        let model_id = "gpt-oss:latest".to_string();
+        let mut endpoint_ids = Vec::new();
+        for (name, url) in [
+            ("slow-endpoint", "http://localhost:11084"),
+            ("fast-endpoint", "http://localhost:11085"),
+        ] {
+            let mut endpoint = Endpoint::new(
+                name.to_string(),
+                url.to_string(),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            let endpoint_id = endpoint.id;
+            registry
+                .add(endpoint)
+                .await
+                .expect("Failed to add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id,
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("Failed to add endpoint model");
+            endpoint_ids.push(endpoint_id);
+        }
 
-    #[test]
-    fn endpoint_load_state_combined_active_metrics_higher() {
-        let state = EndpointLoadState {
-            assigned_active: 2,
-            last_metrics: Some(HealthMetrics {
-                endpoint_id: Uuid::new_v4(),
-                cpu_usage: 0.0,
-                memory_usage: 0.0,
-                gpu_usage: None,
-                gpu_memory_usage: None,
-                gpu_memory_total_mb: None,
-                gpu_memory_used_mb: None,
-                gpu_temperature: None,
-                gpu_model_name: None,
-                gpu_compute_capability: None,
-                gpu_capability_score: None,
-                active_requests: 7,
-                total_requests: 0,
-                average_response_time_ms: None,
-                timestamp: Utc::now(),
-            }),
-            ..Default::default()
-        };
-        assert_eq!(state.combined_active(), 7);
+        let load_manager = LoadManager::new(Arc::new(registry));
+        for (endpoint_id, latency_ms) in [(endpoint_ids[0], 900), (endpoint_ids[1], 50)] {
+            load_manager
+                .finish_request(
+                    endpoint_id,
+                    RequestOutcome::Success,
+                    StdDuration::from_millis(latency_ms),
+                )
+                .await
+                .expect("finish_request should succeed");
+        }
+
+        for _ in 0..4 {
+            let selected = load_manager
+                .select_endpoint_by_tps_ready_for_model(
+                    &model_id,
+                    Some(TpsApiKind::ChatCompletions),
+                    &SelectionContext::default(),
+                )
+                .await
+                .expect("selection should succeed");
+            assert_eq!(selected.id, endpoint_ids[1]);
+        }
     }
 
-    #[test]
-    fn endpoint_load_state_combined_active_assigned_higher() {
-        let state = EndpointLoadState {
-            assigned_active: 10,
+    #[tokio::test]
+    async fn select_endpoint_by_tps_deprioritizes_latency_penalized_endpoint() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
+
+        let model_id = "gpt-oss:latest".to_string();
+        let mut endpoint_ids = Vec::new();
+        for (name, url) in [
+            ("lagging-endpoint", "http://localhost:11086"),
+            ("steady-endpoint-1", "http://localhost:11087"),
+            ("steady-endpoint-2", "http://localhost:11088"),
+        ] {
+            let mut endpoint = Endpoint::new(
+                name.to_string(),
+                url.to_string(),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            let endpoint_id = endpoint.id;
+            registry
+                .add(endpoint)
+                .await
+                .expect("Failed to add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id,
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("Failed to add endpoint model");
+            endpoint_ids.push(endpoint_id);
+        }
+
+        let load_manager = LoadManager::new(Arc::new(registry));
+        for (endpoint_id, latency_ms) in [
+            (endpoint_ids[0], 3000),
+            (endpoint_ids[1], 100),
+            (endpoint_ids[2], 120),
+        ] {
+            load_manager
+                .finish_request(
+                    endpoint_id,
+                    RequestOutcome::Success,
+                    StdDuration::from_millis(latency_ms),
+                )
+                .await
+                .expect("finish_request should succeed");
+        }
+        // 遅いエンドポイントのTPSを最も高くしておく
+        load_manager
+            .update_tps(
+                endpoint_ids[0],
+                model_id.clone(),
+                TpsApiKind::ChatCompletions,
+                1000,
+                1000,
+            )
+            .await;
+
+        async fn select(load_manager: &LoadManager, model_id: &str) -> Uuid {
+            load_manager
+                .select_endpoint_by_tps_ready_for_model(
+                    model_id,
+                    Some(TpsApiKind::ChatCompletions),
+                    &SelectionContext::default(),
+                )
+                .await
+                .expect("selection should succeed")
+                .id
+        }
+
+        // 基準値が未算出の間はTPS順のまま
+        assert!(load_manager.latency_baseline().is_none());
+        assert_eq!(select(&load_manager, &model_id).await, endpoint_ids[0]);
+
+        let baseline = load_manager
+            .recalibrate_latency_baseline()
+            .await
+            .expect("baseline should be computed");
+        assert_eq!(baseline.median_latency_ms, 120.0);
+        assert_eq!(baseline.sample_endpoints, 3);
+        assert_eq!(
+            load_manager.latency_penalized_endpoints().await,
+            vec![endpoint_ids[0]]
+        );
+        assert_ne!(select(&load_manager, &model_id).await, endpoint_ids[0]);
+    }
+
+    #[tokio::test]
+    async fn select_endpoint_by_tps_ready_for_model_applies_routing_policy() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
+
+        let model_id = "llama3:70b".to_string();
+        let mut labeled_id = Uuid::nil();
+        for (name, url, tags) in [
+            ("a100-endpoint", "http://localhost:11084", vec!["gpu-a100"]),
+            ("cpu-endpoint", "http://localhost:11085", vec!["cpu"]),
+        ] {
+            let mut endpoint = Endpoint::new(
+                name.to_string(),
+                url.to_string(),
+                EndpointType::OpenaiCompatible,
+            );
+            endpoint.status = EndpointStatus::Online;
+            endpoint.tags = tags.into_iter().map(str::to_string).collect();
+            let endpoint_id = endpoint.id;
+            if name == "a100-endpoint" {
+                labeled_id = endpoint_id;
+            }
+            registry
+                .add(endpoint)
+                .await
+                .expect("Failed to add endpoint");
+            registry
+                .add_model(&EndpointModel {
+                    endpoint_id,
+                    model_id: model_id.clone(),
+                    capabilities: None,
+                    max_tokens: None,
+                    last_checked: None,
+                    supported_apis: vec![SupportedAPI::ChatCompletions],
+                    canonical_name: None,
+                })
+                .await
+                .expect("Failed to add endpoint model");
+        }
+
+        let load_manager = LoadManager::new(Arc::new(registry));
+        let now = Utc::now();
+        load_manager
+            .set_routing_policies(vec![RoutingPolicy {
+                id: Uuid::new_v4(),
+                name: "llama-on-a100".to_string(),
+                model_pattern: "llama3*".to_string(),
+                api_kind: None,
+                required_labels: vec!["gpu-a100".to_string()],
+                on_no_match: NoMatchBehavior::Error,
+                priority: 10,
+                enabled: true,
+                created_at: now,
+                updated_at: now,
+            }])
+            .await;
+
+        for _ in 0..4 {
+            let selected = load_manager
+                .select_endpoint_by_tps_ready_for_model(
+                    &model_id,
+                    None,
+                    &SelectionContext::default(),
+                )
+                .await
+                .expect("selection should succeed");
+            assert_eq!(selected.id, labeled_id);
+        }
+
+        let no_match = load_manager
+            .select_endpoint_by_tps_ready_for_model_excluding(
+                &model_id,
+                None,
+                &SelectionContext::default(),
+                &[labeled_id],
+            )
+            .await;
+        assert!(matches!(no_match, Err(LbError::NoCapableEndpoints(_))));
+    }
+
+    #[tokio::test]
+    async fn begin_request_uses_reserved_slot_and_withholds_capacity() {
+        let _lock = TEST_LOCK.lock().await;
+        let (mut load_manager, endpoint_id) = setup_test_load_manager().await;
+        load_manager.endpoint_slots = 2;
+        let endpoint = load_manager
+            .endpoint_registry
+            .get(endpoint_id)
+            .await
+            .expect("endpoint exists");
+
+        let holder = reservation::RequestPrincipal {
+            api_key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+        };
+        let other = reservation::RequestPrincipal {
+            api_key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+        };
+        let now = Utc::now();
+        let reservation_id = Uuid::new_v4();
+        load_manager
+            .set_reservations(vec![CapacityReservation {
+                id: reservation_id,
+                endpoint_id,
+                principal_type: PrincipalType::ApiKey,
+                principal_id: holder.api_key_id,
+                slots: 1,
+                soft: false,
+                created_at: now,
+                updated_at: now,
+            }])
+            .await;
+
+        let holder_lease = load_manager
+            .begin_request(endpoint_id, Some(&holder))
+            .await
+            .unwrap();
+        assert_eq!(holder_lease.reservation_id(), Some(reservation_id));
+        assert_eq!(load_manager.reservation_usage(reservation_id), 1);
+
+        let other_lease = load_manager
+            .begin_request(endpoint_id, Some(&other))
+            .await
+            .unwrap();
+        assert_eq!(other_lease.reservation_id(), None);
+
+        // 共有プール（2 - 予約1）を使い切ったので他クライアントには割り当てない
+        let filtered = load_manager
+            .filter_by_reservations(vec![endpoint.clone()], Some(&other))
+            .await;
+        assert!(filtered.is_empty());
+        // 事前絞り込みをすり抜けても、確保時に拒否して共有プールへ黙って流さない
+        let rejected = load_manager.begin_request(endpoint_id, Some(&other)).await;
+        assert!(matches!(rejected, Err(LbError::ServiceUnavailable(_))));
+
+        // エンドポイント単位のスロット数を増やすと共有プールに空きができる
+        let mut widened = endpoint.clone();
+        widened.slots = Some(3);
+        load_manager
+            .endpoint_registry
+            .update(widened)
+            .await
+            .unwrap();
+        let third_lease = load_manager
+            .begin_request(endpoint_id, Some(&other))
+            .await
+            .unwrap();
+        assert_eq!(third_lease.reservation_id(), None);
+        third_lease
+            .complete(RequestOutcome::Success, StdDuration::from_millis(1))
+            .await
+            .unwrap();
+
+        holder_lease
+            .complete(RequestOutcome::Success, StdDuration::from_millis(1))
+            .await
+            .unwrap();
+        other_lease
+            .complete(RequestOutcome::Success, StdDuration::from_millis(1))
+            .await
+            .unwrap();
+        assert_eq!(load_manager.reservation_usage(reservation_id), 0);
+        let filtered = load_manager
+            .filter_by_reservations(vec![endpoint], Some(&other))
+            .await;
+        assert_eq!(filtered.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn non_active_operational_state_excludes_endpoint() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        load_manager
+            .endpoint_registry
+            .update_status(endpoint_id, EndpointStatus::Online, Some(10), None)
+            .await
+            .unwrap();
+        assert!(load_manager.select_endpoint_direct().await.is_ok());
+
+        let draining = EndpointOperationalState {
+            endpoint_id,
+            state: crate::types::endpoint::OperationalState::Draining,
+            reason: None,
+            updated_by: None,
+            updated_at: Utc::now(),
+        };
+        load_manager
+            .set_operational_states(vec![draining.clone()])
+            .await;
+        assert!(matches!(
+            load_manager.select_endpoint_direct().await,
+            Err(LbError::NoEndpointsAvailable)
+        ));
+        assert!(load_manager.select_idle_endpoint().await.unwrap().is_none());
+        assert_eq!(
+            load_manager.operational_states().await,
+            vec![draining.clone()]
+        );
+
+        load_manager
+            .set_operational_state(EndpointOperationalState {
+                state: crate::types::endpoint::OperationalState::Active,
+                ..draining
+            })
+            .await;
+        assert!(load_manager.operational_state(endpoint_id).await.is_none());
+        assert!(load_manager.select_endpoint_direct().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn endpoint_over_monthly_budget_is_excluded_and_notified() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let bus = crate::events::create_shared_event_bus();
+        let mut events = bus.subscribe();
+        load_manager.set_event_bus(bus);
+
+        let registry = load_manager.endpoint_registry.clone();
+        registry
+            .update_status(endpoint_id, EndpointStatus::Online, Some(10), None)
+            .await
+            .unwrap();
+        registry
+            .update_budget(endpoint_id, Some(5.0), Some(10.0), Some(30.0))
+            .await
+            .unwrap();
+        assert!(load_manager.select_endpoint_direct().await.is_ok());
+
+        // 入力50万トークン × $10/1M = $5 → 予算到達
+        let lease = load_manager.begin_request(endpoint_id, None).await.unwrap();
+        lease
+            .complete_with_tokens(
+                RequestOutcome::Success,
+                StdDuration::from_millis(10),
+                Some(crate::token::TokenUsage::new(Some(500_000), Some(0), None)),
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            load_manager.select_endpoint_direct().await,
+            Err(LbError::NoEndpointsAvailable)
+        ));
+        match events.try_recv().unwrap() {
+            crate::events::DashboardEvent::EndpointBudgetExceeded {
+                endpoint_id: id,
+                monthly_cost_usd,
+                monthly_budget_usd,
+            } => {
+                assert_eq!(id, endpoint_id);
+                assert!((monthly_cost_usd - 5.0).abs() < 1e-9);
+                assert_eq!(monthly_budget_usd, 5.0);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        // 単価を外すと無料扱いになり除外されない
+        registry
+            .update_budget(endpoint_id, Some(5.0), None, None)
+            .await
+            .unwrap();
+        assert!(load_manager.select_endpoint_direct().await.is_ok());
+    }
+
+    // SPEC-4bb5b55f T002: ModelTpsState EMA計算テスト
+
+    #[test]
+    fn test_model_tps_state_initial_none() {
+        let state = ModelTpsState::default();
+        assert!(state.tps_ema.is_none());
+        assert_eq!(state.request_count, 0);
+        assert_eq!(state.total_output_tokens, 0);
+        assert_eq!(state.total_duration_ms, 0);
+    }
+
+    #[test]
+    fn test_model_tps_state_first_update() {
+        let mut state = ModelTpsState::default();
+        state.update_tps(100, 2000);
+        assert!(state.tps_ema.is_some());
+        let tps = state.tps_ema.unwrap();
+        assert!(
+            (tps - 50.0).abs() < 0.01,
+            "初回TPS: expected 50.0, got {tps}"
+        );
+        assert_eq!(state.request_count, 1);
+        assert_eq!(state.total_output_tokens, 100);
+        assert_eq!(state.total_duration_ms, 2000);
+    }
+
+    #[test]
+    fn test_model_tps_state_ema_smoothing() {
+        let mut state = ModelTpsState::default();
+
+        state.update_tps(100, 2000);
+        assert!((state.tps_ema.unwrap() - 50.0).abs() < 0.01);
+
+        state.update_tps(200, 2000);
+        assert!(
+            (state.tps_ema.unwrap() - 60.0).abs() < 0.01,
+            "2回目EMA: expected 60.0, got {}",
+            state.tps_ema.unwrap()
+        );
+
+        state.update_tps(50, 1000);
+        assert!(
+            (state.tps_ema.unwrap() - 58.0).abs() < 0.01,
+            "3回目EMA: expected 58.0, got {}",
+            state.tps_ema.unwrap()
+        );
+
+        assert_eq!(state.request_count, 3);
+        assert_eq!(state.total_output_tokens, 350);
+        assert_eq!(state.total_duration_ms, 5000);
+    }
+
+    #[test]
+    fn test_model_tps_state_zero_duration_skipped() {
+        let mut state = ModelTpsState::default();
+        state.update_tps(100, 0);
+        assert!(state.tps_ema.is_none(), "duration=0ではTPS更新しない");
+    }
+
+    #[tokio::test]
+    async fn test_get_model_tps_empty_for_unknown_endpoint() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let unknown_id = Uuid::new_v4();
+        let result = load_manager.get_model_tps(unknown_id).await;
+        assert!(result.is_empty(), "未計測エンドポイントは空Vecを返す");
+    }
+
+    #[tokio::test]
+    async fn test_get_model_tps_returns_entries_after_update() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+
+        load_manager
+            .update_tps(
+                endpoint_id,
+                "model-a".to_string(),
+                TpsApiKind::ChatCompletions,
+                100,
+                2000,
+            )
+            .await;
+
+        let result = load_manager.get_model_tps(endpoint_id).await;
+        assert_eq!(result.len(), 1, "1モデル分のTPS情報が返る");
+        assert_eq!(result[0].model_id, "model-a");
+        assert_eq!(result[0].api_kind, TpsApiKind::ChatCompletions);
+        assert_eq!(result[0].request_count, 1);
+        assert_eq!(result[0].total_output_tokens, 100);
+        let tps = result[0].tps.expect("TPS値がSomeであること");
+        assert!((tps - 50.0).abs() < 0.01, "TPS = 50.0");
+    }
+
+    #[tokio::test]
+    async fn test_get_model_tps_multiple_models() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+
+        load_manager
+            .update_tps(
+                endpoint_id,
+                "model-a".to_string(),
+                TpsApiKind::ChatCompletions,
+                100,
+                2000,
+            )
+            .await;
+        load_manager
+            .update_tps(
+                endpoint_id,
+                "model-b".to_string(),
+                TpsApiKind::Completions,
+                200,
+                1000,
+            )
+            .await;
+
+        let result = load_manager.get_model_tps(endpoint_id).await;
+        assert_eq!(result.len(), 2, "2モデル分のTPS情報が返る");
+
+        let model_ids: Vec<&str> = result.iter().map(|e| e.model_id.as_str()).collect();
+        assert!(model_ids.contains(&"model-a"));
+        assert!(model_ids.contains(&"model-b"));
+    }
+
+    #[tokio::test]
+    async fn test_get_model_tps_separates_api_kind_for_same_model() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+
+        load_manager
+            .update_tps(
+                endpoint_id,
+                "shared-model".to_string(),
+                TpsApiKind::ChatCompletions,
+                100,
+                2000,
+            )
+            .await;
+        load_manager
+            .update_tps(
+                endpoint_id,
+                "shared-model".to_string(),
+                TpsApiKind::Responses,
+                80,
+                2000,
+            )
+            .await;
+
+        let result = load_manager.get_model_tps(endpoint_id).await;
+        assert_eq!(result.len(), 2, "同一モデルでもAPI種別ごとに分離される");
+        assert!(result.iter().any(|entry| entry.model_id == "shared-model"
+            && entry.api_kind == TpsApiKind::ChatCompletions));
+        assert!(result.iter().any(
+            |entry| entry.model_id == "shared-model" && entry.api_kind == TpsApiKind::Responses
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_update_tps_skips_zero_tokens() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+
+        load_manager
+            .update_tps(
+                endpoint_id,
+                "model-a".to_string(),
+                TpsApiKind::ChatCompletions,
+                0,
+                2000,
+            )
+            .await;
+
+        let result = load_manager.get_model_tps(endpoint_id).await;
+        assert!(result.is_empty(), "output_tokens=0はTPS更新しない");
+    }
+
+    #[tokio::test]
+    async fn test_get_model_tps_isolates_endpoints() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let other_endpoint_id = Uuid::new_v4();
+
+        load_manager
+            .update_tps(
+                endpoint_id,
+                "model-a".to_string(),
+                TpsApiKind::ChatCompletions,
+                100,
+                2000,
+            )
+            .await;
+        load_manager
+            .update_tps(
+                other_endpoint_id,
+                "model-b".to_string(),
+                TpsApiKind::Completions,
+                200,
+                1000,
+            )
+            .await;
+
+        let result = load_manager.get_model_tps(endpoint_id).await;
+        assert_eq!(result.len(), 1, "他エンドポイントのデータは含まない");
+        assert_eq!(result[0].model_id, "model-a");
+    }
+
+    #[tokio::test]
+    async fn test_get_all_endpoint_tps_empty() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+
+        let result = load_manager.get_all_endpoint_tps().await;
+        assert!(result.is_empty(), "TPS未計測の場合は空");
+    }
+
+    #[tokio::test]
+    async fn test_get_all_endpoint_tps_returns_per_endpoint_summary() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let other_endpoint_id = Uuid::new_v4();
+
+        load_manager
+            .update_tps(
+                endpoint_id,
+                "model-a".to_string(),
+                TpsApiKind::ChatCompletions,
+                100,
+                2000,
+            )
+            .await;
+        load_manager
+            .update_tps(
+                endpoint_id,
+                "model-b".to_string(),
+                TpsApiKind::Completions,
+                200,
+                1000,
+            )
+            .await;
+        load_manager
+            .update_tps(
+                other_endpoint_id,
+                "model-c".to_string(),
+                TpsApiKind::Responses,
+                50,
+                500,
+            )
+            .await;
+
+        let result = load_manager.get_all_endpoint_tps().await;
+        assert_eq!(result.len(), 2, "2エンドポイント分のサマリ");
+
+        let ep1 = result
+            .iter()
+            .find(|s| s.endpoint_id == endpoint_id)
+            .expect("endpoint_id存在");
+        assert_eq!(ep1.model_count, 2);
+        assert_eq!(ep1.total_output_tokens, 300);
+        assert!(ep1.aggregate_tps.is_some());
+
+        let ep2 = result
+            .iter()
+            .find(|s| s.endpoint_id == other_endpoint_id)
+            .expect("other存在");
+        assert_eq!(ep2.model_count, 1);
+        assert_eq!(ep2.total_output_tokens, 50);
+    }
+
+    /// T013 [US5]: seed_history_from_db が MinuteHistoryPoint を VecDeque に
+    /// 正しく投入できることを検証
+    #[tokio::test]
+    async fn test_seed_history_from_db() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+
+        let now = Utc::now();
+        let minute1 = super::align_to_minute(now - chrono::Duration::minutes(5));
+        let minute2 = super::align_to_minute(now - chrono::Duration::minutes(3));
+
+        let points = vec![
+            crate::db::request_history::MinuteHistoryPoint {
+                minute: minute1.to_rfc3339(),
+                success_count: 10,
+                error_count: 2,
+            },
+            crate::db::request_history::MinuteHistoryPoint {
+                minute: minute2.to_rfc3339(),
+                success_count: 5,
+                error_count: 1,
+            },
+        ];
+
+        load_manager.seed_history_from_db(points).await;
+
+        let history = load_manager.request_history().await;
+        // VecDeque には seed したデータが含まれる
+        let total_success: u64 = history.iter().map(|h| h.success).sum();
+        let total_error: u64 = history.iter().map(|h| h.error).sum();
+        assert_eq!(total_success, 15, "seeded success count");
+        assert_eq!(total_error, 3, "seeded error count");
+    }
+
+    /// T018 [US4]: seed_tps_from_db が TpsSeedEntry から TpsTrackerMap に
+    /// 正しく TPS EMA を計算して投入できることを検証
+    #[tokio::test]
+    async fn test_seed_tps_from_db() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+
+        let entries = vec![
+            crate::db::endpoint_daily_stats::TpsSeedEntry {
+                endpoint_id,
+                model_id: "test-model".to_string(),
+                api_kind: "chat_completions".to_string(),
+                total_output_tokens: 100,
+                total_duration_ms: 2000,
+                successful_requests: 5,
+            },
+            // duration_ms=0 のエントリはスキップされること
+            crate::db::endpoint_daily_stats::TpsSeedEntry {
+                endpoint_id,
+                model_id: "skip-model".to_string(),
+                api_kind: "chat_completions".to_string(),
+                total_output_tokens: 100,
+                total_duration_ms: 0,
+                successful_requests: 1,
+            },
+            // tokens=0 のエントリもスキップされること
+            crate::db::endpoint_daily_stats::TpsSeedEntry {
+                endpoint_id,
+                model_id: "skip-model2".to_string(),
+                api_kind: "chat_completions".to_string(),
+                total_output_tokens: 0,
+                total_duration_ms: 1000,
+                successful_requests: 1,
+            },
+        ];
+
+        load_manager.seed_tps_from_db(entries).await;
+
+        let tps_data = load_manager.get_all_endpoint_tps().await;
+        // test-model のみ seed されている
+        assert!(!tps_data.is_empty(), "should have TPS data");
+        let ep_tps = tps_data
+            .iter()
+            .find(|t| t.endpoint_id == endpoint_id)
+            .expect("should have endpoint TPS");
+        // TPS = 100 / (2000/1000) = 50.0
+        assert_eq!(ep_tps.model_count, 1, "only valid entry should be seeded");
+        assert!(ep_tps.aggregate_tps.is_some(), "should have aggregate TPS");
+        let tps = ep_tps.aggregate_tps.unwrap();
+        assert!((tps - 50.0).abs() < 0.1, "TPS should be ~50.0, got {tps}");
+    }
+
+    // ===== 追加テスト: LoadManager 基本機能 =====
+
+    #[test]
+    fn endpoint_load_state_default_values() {
+        let state = EndpointLoadState::default();
+        assert_eq!(state.assigned_active, 0);
+        assert_eq!(state.total_assigned, 0);
+        assert_eq!(state.success_count, 0);
+        assert_eq!(state.error_count, 0);
+        assert_eq!(state.total_latency_ms, 0);
+        assert!(!state.initializing);
+        assert!(state.ready_models.is_none());
+        assert!(state.last_metrics.is_none());
+        assert!(state.metrics_history.is_empty());
+        assert_eq!(state.total_input_tokens, 0);
+        assert_eq!(state.total_output_tokens, 0);
+        assert_eq!(state.total_tokens, 0);
+    }
+
+    #[test]
+    fn endpoint_load_state_combined_active_no_metrics() {
+        let state = EndpointLoadState {
+            assigned_active: 3,
+            ..Default::default()
+        };
+        assert_eq!(state.combined_active(), 3);
+    }
+
+    #[test]
+    fn endpoint_load_state_combined_active_metrics_higher() {
+        let state = EndpointLoadState {
+            assigned_active: 2,
+            last_metrics: Some(HealthMetrics {
+                endpoint_id: Uuid::new_v4(),
+                cpu_usage: 0.0,
+                memory_usage: 0.0,
+                gpu_usage: None,
+                gpu_memory_usage: None,
+                gpu_memory_total_mb: None,
+                gpu_memory_used_mb: None,
+                gpu_temperature: None,
+                gpu_model_name: None,
+                gpu_compute_capability: None,
+                gpu_capability_score: None,
+                active_requests: 7,
+                total_requests: 0,
+                average_response_time_ms: None,
+                timestamp: Utc::now(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(state.combined_active(), 7);
+    }
+
+    #[test]
+    fn endpoint_load_state_combined_active_assigned_higher() {
+        let state = EndpointLoadState {
+            assigned_active: 10,
+            last_metrics: Some(HealthMetrics {
+                endpoint_id: Uuid::new_v4(),
+                cpu_usage: 0.0,
+                memory_usage: 0.0,
+                gpu_usage: None,
+                gpu_memory_usage: None,
+                gpu_memory_total_mb: None,
+                gpu_memory_used_mb: None,
+                gpu_temperature: None,
+                gpu_model_name: None,
+                gpu_compute_capability: None,
+                gpu_capability_score: None,
+                active_requests: 3,
+                total_requests: 0,
+                average_response_time_ms: None,
+                timestamp: Utc::now(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(state.combined_active(), 10);
+    }
+
+    #[test]
+    fn endpoint_load_state_average_latency_ms_no_completed() {
+        let state = EndpointLoadState::default();
+        assert!(state.average_latency_ms().is_none());
+    }
+
+    #[test]
+    fn endpoint_load_state_average_latency_ms_with_data() {
+        let state = EndpointLoadState {
+            success_count: 8,
+            error_count: 2,
+            total_latency_ms: 1000,
+            ..Default::default()
+        };
+        let avg = state.average_latency_ms().unwrap();
+        assert!((avg - 100.0).abs() < 0.01, "expected 100.0, got {avg}");
+    }
+
+    #[test]
+    fn endpoint_load_state_is_stale_no_metrics() {
+        let state = EndpointLoadState::default();
+        assert!(state.is_stale(Utc::now()));
+    }
+
+    #[test]
+    fn endpoint_load_state_is_stale_fresh() {
+        let now = Utc::now();
+        let state = EndpointLoadState {
+            last_metrics: Some(HealthMetrics {
+                endpoint_id: Uuid::new_v4(),
+                cpu_usage: 0.0,
+                memory_usage: 0.0,
+                gpu_usage: None,
+                gpu_memory_usage: None,
+                gpu_memory_total_mb: None,
+                gpu_memory_used_mb: None,
+                gpu_temperature: None,
+                gpu_model_name: None,
+                gpu_compute_capability: None,
+                gpu_capability_score: None,
+                active_requests: 0,
+                total_requests: 0,
+                average_response_time_ms: None,
+                timestamp: now,
+            }),
+            ..Default::default()
+        };
+        assert!(!state.is_stale(now));
+    }
+
+    #[test]
+    fn endpoint_load_state_effective_average_ms_no_data() {
+        let state = EndpointLoadState::default();
+        assert!(state.effective_average_ms().is_none());
+    }
+
+    #[test]
+    fn endpoint_load_state_effective_average_ms_fallback_to_computed() {
+        let now = Utc::now();
+        let state = EndpointLoadState {
+            success_count: 5,
+            error_count: 0,
+            total_latency_ms: 500,
             last_metrics: Some(HealthMetrics {
                 endpoint_id: Uuid::new_v4(),
                 cpu_usage: 0.0,
@@ -779,991 +2345,2160 @@ mod tests {
                 gpu_model_name: None,
                 gpu_compute_capability: None,
                 gpu_capability_score: None,
-                active_requests: 3,
+                active_requests: 0,
                 total_requests: 0,
                 average_response_time_ms: None,
-                timestamp: Utc::now(),
+                timestamp: now,
+            }),
+            ..Default::default()
+        };
+        let avg = state.effective_average_ms().unwrap();
+        assert!(
+            (avg - 100.0).abs() < 0.01,
+            "fallback to computed: expected 100.0, got {avg}"
+        );
+    }
+
+    #[test]
+    fn endpoint_load_state_last_updated_none() {
+        let state = EndpointLoadState::default();
+        assert!(state.last_updated().is_none());
+    }
+
+    #[test]
+    fn endpoint_load_state_last_updated_some() {
+        let now = Utc::now();
+        let state = EndpointLoadState {
+            last_metrics: Some(HealthMetrics {
+                endpoint_id: Uuid::new_v4(),
+                cpu_usage: 0.0,
+                memory_usage: 0.0,
+                gpu_usage: None,
+                gpu_memory_usage: None,
+                gpu_memory_total_mb: None,
+                gpu_memory_used_mb: None,
+                gpu_temperature: None,
+                gpu_model_name: None,
+                gpu_compute_capability: None,
+                gpu_capability_score: None,
+                active_requests: 0,
+                total_requests: 0,
+                average_response_time_ms: None,
+                timestamp: now,
             }),
             ..Default::default()
         };
-        assert_eq!(state.combined_active(), 10);
+        assert_eq!(state.last_updated(), Some(now));
+    }
+
+    // ===== align_to_minute テスト =====
+
+    #[test]
+    fn align_to_minute_strips_seconds_and_nanos() {
+        let ts = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 6, 15, 10, 30, 45).unwrap();
+        let aligned = super::align_to_minute(ts);
+        assert_eq!(aligned.second(), 0);
+        assert_eq!(aligned.nanosecond(), 0);
+        assert_eq!(aligned.minute(), 30);
+        assert_eq!(aligned.hour(), 10);
+    }
+
+    #[test]
+    fn align_to_minute_already_aligned() {
+        let ts = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 1, 1, 0, 0, 0).unwrap();
+        let aligned = super::align_to_minute(ts);
+        assert_eq!(aligned, ts);
+    }
+
+    // ===== prune_history テスト =====
+
+    #[test]
+    fn prune_history_removes_old_entries() {
+        let now = super::align_to_minute(Utc::now());
+        let mut history = std::collections::VecDeque::new();
+        // 120分前のエントリ（60分窓より古い）
+        let old_minute = now - chrono::Duration::minutes(120);
+        history.push_back(RequestHistoryPoint {
+            minute: old_minute,
+            success: 10,
+            error: 1,
+        });
+        // 30分前のエントリ（窓内）
+        let recent_minute = now - chrono::Duration::minutes(30);
+        history.push_back(RequestHistoryPoint {
+            minute: recent_minute,
+            success: 5,
+            error: 0,
+        });
+
+        super::prune_history(&mut history, now);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].minute, recent_minute);
+    }
+
+    #[test]
+    fn prune_history_keeps_all_within_window() {
+        let now = super::align_to_minute(Utc::now());
+        let mut history = std::collections::VecDeque::new();
+        for i in 0..5 {
+            history.push_back(RequestHistoryPoint {
+                minute: now - chrono::Duration::minutes(i),
+                success: 1,
+                error: 0,
+            });
+        }
+        super::prune_history(&mut history, now);
+        assert_eq!(history.len(), 5);
+    }
+
+    #[test]
+    fn prune_history_empty_is_noop() {
+        let now = super::align_to_minute(Utc::now());
+        let mut history = std::collections::VecDeque::new();
+        super::prune_history(&mut history, now);
+        assert!(history.is_empty());
+    }
+
+    // ===== new_history_point テスト =====
+
+    #[test]
+    fn new_history_point_success() {
+        let now = super::align_to_minute(Utc::now());
+        let point = super::new_history_point(now, RequestOutcome::Success);
+        assert_eq!(point.minute, now);
+        assert_eq!(point.success, 1);
+        assert_eq!(point.error, 0);
+    }
+
+    #[test]
+    fn new_history_point_error() {
+        let now = super::align_to_minute(Utc::now());
+        let point = super::new_history_point(now, RequestOutcome::Error);
+        assert_eq!(point.minute, now);
+        assert_eq!(point.success, 0);
+        assert_eq!(point.error, 1);
+    }
+
+    #[test]
+    fn new_history_point_queued() {
+        let now = super::align_to_minute(Utc::now());
+        let point = super::new_history_point(now, RequestOutcome::Queued);
+        assert_eq!(point.minute, now);
+        assert_eq!(point.success, 0);
+        assert_eq!(point.error, 0);
+    }
+
+    // ===== increment_history テスト =====
+
+    #[test]
+    fn increment_history_success() {
+        let mut point = RequestHistoryPoint {
+            minute: Utc::now(),
+            success: 5,
+            error: 2,
+        };
+        super::increment_history(&mut point, RequestOutcome::Success);
+        assert_eq!(point.success, 6);
+        assert_eq!(point.error, 2);
+    }
+
+    #[test]
+    fn increment_history_error() {
+        let mut point = RequestHistoryPoint {
+            minute: Utc::now(),
+            success: 5,
+            error: 2,
+        };
+        super::increment_history(&mut point, RequestOutcome::Error);
+        assert_eq!(point.success, 5);
+        assert_eq!(point.error, 3);
+    }
+
+    #[test]
+    fn increment_history_queued_no_change() {
+        let mut point = RequestHistoryPoint {
+            minute: Utc::now(),
+            success: 5,
+            error: 2,
+        };
+        super::increment_history(&mut point, RequestOutcome::Queued);
+        assert_eq!(point.success, 5);
+        assert_eq!(point.error, 2);
+    }
+
+    // ===== compute_round_robin_priority_for_endpoints テスト =====
+
+    #[test]
+    fn compute_round_robin_priority_empty() {
+        let endpoints: Vec<crate::types::endpoint::Endpoint> = vec![];
+        let priority = super::compute_round_robin_priority_for_endpoints(&endpoints, 0);
+        assert!(priority.is_empty());
+    }
+
+    #[test]
+    fn compute_round_robin_priority_single() {
+        let ep = Endpoint::new(
+            "ep1".to_string(),
+            "http://localhost:1".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        let id = ep.id;
+        let endpoints = vec![ep];
+        let priority = super::compute_round_robin_priority_for_endpoints(&endpoints, 0);
+        assert_eq!(priority.len(), 1);
+        assert_eq!(priority[&id], 0);
+    }
+
+    #[test]
+    fn compute_round_robin_priority_wraps_around() {
+        let ep1 = Endpoint::new(
+            "ep1".to_string(),
+            "http://localhost:1".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        let ep2 = Endpoint::new(
+            "ep2".to_string(),
+            "http://localhost:2".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        let ep3 = Endpoint::new(
+            "ep3".to_string(),
+            "http://localhost:3".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        let id1 = ep1.id;
+        let id2 = ep2.id;
+        let id3 = ep3.id;
+        let endpoints = vec![ep1, ep2, ep3];
+        // start_index=1 => priority: ep2=0, ep3=1, ep1=2
+        let priority = super::compute_round_robin_priority_for_endpoints(&endpoints, 1);
+        assert_eq!(priority[&id2], 0);
+        assert_eq!(priority[&id3], 1);
+        assert_eq!(priority[&id1], 2);
+    }
+
+    // ===== fill_history テスト =====
+
+    #[test]
+    fn fill_history_fills_gaps() {
+        let now = super::align_to_minute(Utc::now());
+        let mut map = std::collections::HashMap::new();
+        // 10分前にデータがある
+        let ten_ago = now - chrono::Duration::minutes(10);
+        map.insert(
+            ten_ago,
+            RequestHistoryPoint {
+                minute: ten_ago,
+                success: 42,
+                error: 0,
+            },
+        );
+
+        let result = super::fill_history(now, &mut map);
+        // REQUEST_HISTORY_WINDOW_MINUTESは60なので、結果は60エントリ
+        assert_eq!(
+            result.len(),
+            super::types::REQUEST_HISTORY_WINDOW_MINUTES as usize
+        );
+        // 10分前のエントリには success=42 が含まれる
+        let ten_ago_entry = result.iter().find(|p| p.minute == ten_ago);
+        assert!(ten_ago_entry.is_some());
+        assert_eq!(ten_ago_entry.unwrap().success, 42);
+        // 他のエントリは success=0, error=0
+        let zero_entries: Vec<_> = result.iter().filter(|p| p.minute != ten_ago).collect();
+        for e in zero_entries {
+            assert_eq!(e.success, 0);
+            assert_eq!(e.error, 0);
+        }
+    }
+
+    // ===== admission_control テスト =====
+
+    #[tokio::test]
+    async fn admission_control_accept_when_low_load() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let decision = load_manager.admission_control(&crate::config::QueueConfig {
+            max_waiters: 100,
+            timeout: StdDuration::from_secs(60),
+            soft_threshold: crate::config::DEFAULT_QUEUE_SOFT_THRESHOLD,
+            hard_threshold: crate::config::DEFAULT_QUEUE_HARD_THRESHOLD,
+        });
+        assert_eq!(decision, AdmissionDecision::Accept);
+    }
+
+    #[test]
+    fn admission_decision_uses_configured_thresholds() {
+        let config = crate::config::QueueConfig {
+            max_waiters: 100,
+            timeout: StdDuration::from_secs(60),
+            soft_threshold: 0.2,
+            hard_threshold: 0.6,
+        };
+        assert_eq!(admission_decision(19, &config), AdmissionDecision::Accept);
+        assert_eq!(
+            admission_decision(20, &config),
+            AdmissionDecision::AcceptWithDelay(StdDuration::from_millis(10))
+        );
+        // soft〜hard の中間では遅延も中間になる
+        assert_eq!(
+            admission_decision(40, &config),
+            AdmissionDecision::AcceptWithDelay(StdDuration::from_millis(55))
+        );
+        assert_eq!(admission_decision(60, &config), AdmissionDecision::Reject);
+    }
+
+    #[test]
+    fn admission_decision_tightens_when_estimate_exceeds_slo() {
+        let config = crate::config::QueueConfig {
+            max_waiters: 100,
+            timeout: StdDuration::from_secs(60),
+            soft_threshold: 0.2,
+            hard_threshold: 0.6,
+        };
+        let slo = Some(StdDuration::from_secs(10));
+        let decide = |waiters, estimate_secs| {
+            admission_decision_with_estimate(
+                waiters,
+                &config,
+                Some(StdDuration::from_secs(estimate_secs)),
+                slo,
+                2.0,
+            )
+        };
+        // SLO×倍率以内なら通常のしきい値
+        assert_eq!(decide(19, 20), AdmissionDecision::Accept);
+        assert_eq!(decide(30, 20), admission_decision(30, &config));
+        // 大きく超える場合はしきい値が半分になる
+        assert_eq!(decide(9, 21), AdmissionDecision::Accept);
+        assert_eq!(
+            decide(10, 21),
+            AdmissionDecision::AcceptWithDelay(StdDuration::from_millis(10))
+        );
+        assert_eq!(decide(30, 21), AdmissionDecision::Reject);
+        // SLO 未設定・推定なしでは変化しない
+        assert_eq!(
+            admission_decision_with_estimate(30, &config, None, slo, 2.0),
+            admission_decision(30, &config)
+        );
+    }
+
+    #[tokio::test]
+    async fn estimate_response_time_uses_model_tps_and_ttft() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+
+        assert!(load_manager
+            .estimate_response_time("model-a", 100, Some(100))
+            .await
+            .is_none());
+
+        // 50 tok/s、平均出力200トークン
+        load_manager
+            .update_tps(
+                endpoint_id,
+                "model-a".to_string(),
+                TpsApiKind::ChatCompletions,
+                200,
+                4000,
+            )
+            .await;
+        load_manager
+            .record_ttft(endpoint_id, StdDuration::from_millis(500))
+            .await;
+
+        // 0.5s + 1000 / 500 + 100 / 50 = 4.5s
+        let estimate = load_manager
+            .estimate_response_time("model-a", 1000, Some(100))
+            .await
+            .unwrap();
+        assert!((estimate.as_secs_f64() - 4.5).abs() < 1e-6, "{estimate:?}");
+        // max_tokens 未指定時は平均出力トークン数（200）を使う
+        let estimate = load_manager
+            .estimate_response_time("model-a", 0, None)
+            .await
+            .unwrap();
+        assert!((estimate.as_secs_f64() - 4.5).abs() < 1e-6, "{estimate:?}");
+    }
+
+    // ===== has_ready_nodes / all_initializing テスト =====
+
+    #[tokio::test]
+    async fn has_ready_nodes_empty_state() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        // 初期状態ではstateにエントリなし
+        assert!(!load_manager.has_ready_nodes().await);
+    }
+
+    #[tokio::test]
+    async fn has_ready_nodes_with_ready_endpoint() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        load_manager
+            .upsert_initial_state(endpoint_id, false, Some((1, 1)))
+            .await;
+        assert!(load_manager.has_ready_nodes().await);
+    }
+
+    #[tokio::test]
+    async fn has_ready_nodes_all_initializing() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        load_manager
+            .upsert_initial_state(endpoint_id, true, Some((0, 1)))
+            .await;
+        assert!(!load_manager.has_ready_nodes().await);
+    }
+
+    #[tokio::test]
+    async fn all_initializing_empty() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        // stateが空の場合はfalse
+        assert!(!load_manager.all_initializing().await);
+    }
+
+    #[tokio::test]
+    async fn all_initializing_true_when_all_are() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        load_manager
+            .upsert_initial_state(endpoint_id, true, Some((0, 1)))
+            .await;
+        assert!(load_manager.all_initializing().await);
+    }
+
+    #[tokio::test]
+    async fn all_initializing_false_when_one_ready() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        load_manager
+            .upsert_initial_state(endpoint_id, false, Some((1, 1)))
+            .await;
+        assert!(!load_manager.all_initializing().await);
+    }
+
+    #[tokio::test]
+    async fn initializing_endpoint_ids_lists_only_initializing() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        load_manager
+            .upsert_initial_state(endpoint_id, true, Some((0, 1)))
+            .await;
+        assert!(load_manager
+            .initializing_endpoint_ids()
+            .await
+            .contains(&endpoint_id));
+        load_manager
+            .upsert_initial_state(endpoint_id, false, Some((1, 1)))
+            .await;
+        assert!(load_manager.initializing_endpoint_ids().await.is_empty());
+    }
+
+    // ===== queue_waiters テスト =====
+
+    #[tokio::test]
+    async fn queue_waiters_starts_at_zero() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        assert_eq!(load_manager.queue_waiters(), 0);
+    }
+
+    // ===== endpoint_registry テスト =====
+
+    #[tokio::test]
+    async fn endpoint_registry_returns_registry() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let registry = load_manager.endpoint_registry();
+        let ep = registry.get(endpoint_id).await;
+        assert!(ep.is_some());
+    }
+
+    // ===== record_metrics テスト =====
+
+    #[tokio::test]
+    async fn record_metrics_unknown_endpoint_returns_error() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let update = MetricsUpdate {
+            endpoint_id: Uuid::new_v4(),
+            cpu_usage: 10.0,
+            memory_usage: 20.0,
+            gpu_usage: None,
+            gpu_memory_usage: None,
+            gpu_memory_total_mb: None,
+            gpu_memory_used_mb: None,
+            gpu_temperature: None,
+            gpu_model_name: None,
+            gpu_compute_capability: None,
+            gpu_capability_score: None,
+            active_requests: 0,
+            average_response_time_ms: None,
+            initializing: false,
+            ready_models: None,
+        };
+        let result = load_manager.record_metrics(update).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn record_metrics_valid_endpoint_succeeds() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let update = MetricsUpdate {
+            endpoint_id,
+            cpu_usage: 50.0,
+            memory_usage: 60.0,
+            gpu_usage: Some(70.0),
+            gpu_memory_usage: Some(80.0),
+            gpu_memory_total_mb: Some(8192),
+            gpu_memory_used_mb: Some(4096),
+            gpu_temperature: Some(65.0),
+            gpu_model_name: Some("NVIDIA RTX 4090".to_string()),
+            gpu_compute_capability: Some("8.9".to_string()),
+            gpu_capability_score: Some(90),
+            active_requests: 2,
+            average_response_time_ms: Some(150.0),
+            initializing: false,
+            ready_models: Some((1, 1)),
+        };
+        let result = load_manager.record_metrics(update).await;
+        assert!(result.is_ok());
+
+        let snapshot = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snapshot.cpu_usage, Some(50.0));
+        assert_eq!(snapshot.memory_usage, Some(60.0));
+        assert_eq!(snapshot.gpu_usage, Some(70.0));
+    }
+
+    // ===== begin_request / finish_request テスト =====
+
+    #[tokio::test]
+    async fn begin_request_unknown_endpoint_returns_error() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let result = load_manager.begin_request(Uuid::new_v4(), None).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn finish_request_unknown_endpoint_returns_error() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let result = load_manager
+            .finish_request(
+                Uuid::new_v4(),
+                RequestOutcome::Success,
+                StdDuration::from_millis(100),
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn finish_request_with_tokens_unknown_endpoint_returns_error() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let result = load_manager
+            .finish_request_with_tokens(
+                Uuid::new_v4(),
+                RequestOutcome::Success,
+                StdDuration::from_millis(100),
+                None,
+            )
+            .await;
+        assert!(result.is_err());
     }
 
-    #[test]
-    fn endpoint_load_state_average_latency_ms_no_completed() {
-        let state = EndpointLoadState::default();
-        assert!(state.average_latency_ms().is_none());
-    }
+    #[tokio::test]
+    async fn finish_request_success_updates_counts() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let _lease = load_manager.begin_request(endpoint_id, None).await.unwrap();
+        load_manager
+            .finish_request(
+                endpoint_id,
+                RequestOutcome::Success,
+                StdDuration::from_millis(100),
+            )
+            .await
+            .unwrap();
 
-    #[test]
-    fn endpoint_load_state_average_latency_ms_with_data() {
-        let state = EndpointLoadState {
-            success_count: 8,
-            error_count: 2,
-            total_latency_ms: 1000,
-            ..Default::default()
-        };
-        let avg = state.average_latency_ms().unwrap();
-        assert!((avg - 100.0).abs() < 0.01, "expected 100.0, got {avg}");
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snap.successful_requests, 1);
+        assert_eq!(snap.p50_latency_ms, Some(100.0));
+        assert_eq!(snap.p95_latency_ms, Some(100.0));
     }
 
-    #[test]
-    fn endpoint_load_state_is_stale_no_metrics() {
-        let state = EndpointLoadState::default();
-        assert!(state.is_stale(Utc::now()));
+    #[tokio::test]
+    async fn record_response_tokens_reports_anomalous_models_in_snapshot() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        for _ in 0..types::RESPONSE_TOKENS_MIN_SAMPLES {
+            load_manager
+                .record_response_tokens(endpoint_id, "model-a", 200)
+                .await;
+        }
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert!(snap.response_anomaly_models.is_empty());
+
+        for _ in 0..10 {
+            load_manager
+                .record_response_tokens(endpoint_id, "model-a", 1)
+                .await;
+        }
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snap.response_anomaly_models, vec!["model-a".to_string()]);
     }
 
-    #[test]
-    fn endpoint_load_state_is_stale_fresh() {
-        let now = Utc::now();
-        let state = EndpointLoadState {
-            last_metrics: Some(HealthMetrics {
-                endpoint_id: Uuid::new_v4(),
-                cpu_usage: 0.0,
-                memory_usage: 0.0,
-                gpu_usage: None,
-                gpu_memory_usage: None,
-                gpu_memory_total_mb: None,
-                gpu_memory_used_mb: None,
-                gpu_temperature: None,
-                gpu_model_name: None,
-                gpu_compute_capability: None,
-                gpu_capability_score: None,
-                active_requests: 0,
-                total_requests: 0,
-                average_response_time_ms: None,
-                timestamp: now,
-            }),
-            ..Default::default()
-        };
-        assert!(!state.is_stale(now));
+    #[tokio::test]
+    async fn finish_request_error_updates_counts() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let _lease = load_manager.begin_request(endpoint_id, None).await.unwrap();
+        load_manager
+            .finish_request(
+                endpoint_id,
+                RequestOutcome::Error,
+                StdDuration::from_millis(100),
+            )
+            .await
+            .unwrap();
+
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snap.failed_requests, 1);
     }
 
-    #[test]
-    fn endpoint_load_state_effective_average_ms_no_data() {
-        let state = EndpointLoadState::default();
-        assert!(state.effective_average_ms().is_none());
+    #[tokio::test]
+    async fn finish_request_queued_does_not_decrement_active() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let _lease = load_manager.begin_request(endpoint_id, None).await.unwrap();
+        load_manager
+            .finish_request(
+                endpoint_id,
+                RequestOutcome::Queued,
+                StdDuration::from_millis(0),
+            )
+            .await
+            .unwrap();
+
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        // Queued does not decrement active
+        assert_eq!(snap.successful_requests, 0);
+        assert_eq!(snap.failed_requests, 0);
+        // 完了リクエストが0件でもパーセンタイルはNone
+        assert!(snap.p50_latency_ms.is_none());
+        assert!(snap.p95_latency_ms.is_none());
     }
 
-    #[test]
-    fn endpoint_load_state_effective_average_ms_fallback_to_computed() {
-        let now = Utc::now();
-        let state = EndpointLoadState {
-            success_count: 5,
-            error_count: 0,
-            total_latency_ms: 500,
-            last_metrics: Some(HealthMetrics {
-                endpoint_id: Uuid::new_v4(),
-                cpu_usage: 0.0,
-                memory_usage: 0.0,
-                gpu_usage: None,
-                gpu_memory_usage: None,
-                gpu_memory_total_mb: None,
-                gpu_memory_used_mb: None,
-                gpu_temperature: None,
-                gpu_model_name: None,
-                gpu_compute_capability: None,
-                gpu_capability_score: None,
-                active_requests: 0,
-                total_requests: 0,
-                average_response_time_ms: None,
-                timestamp: now,
-            }),
-            ..Default::default()
-        };
-        let avg = state.effective_average_ms().unwrap();
-        assert!(
-            (avg - 100.0).abs() < 0.01,
-            "fallback to computed: expected 100.0, got {avg}"
-        );
+    // ===== finish_request_with_tokens テスト =====
+
+    #[tokio::test]
+    async fn finish_request_with_tokens_records_token_usage() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let _lease = load_manager.begin_request(endpoint_id, None).await.unwrap();
+
+        let token_usage = Some(crate::token::TokenUsage {
+            input_tokens: Some(100),
+            output_tokens: Some(50),
+            total_tokens: Some(150),
+        });
+
+        load_manager
+            .finish_request_with_tokens(
+                endpoint_id,
+                RequestOutcome::Success,
+                StdDuration::from_millis(200),
+                token_usage,
+            )
+            .await
+            .unwrap();
+
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snap.total_input_tokens, 100);
+        assert_eq!(snap.total_output_tokens, 50);
+        assert_eq!(snap.total_tokens, 150);
     }
 
-    #[test]
-    fn endpoint_load_state_last_updated_none() {
-        let state = EndpointLoadState::default();
-        assert!(state.last_updated().is_none());
+    #[tokio::test]
+    async fn finish_request_with_tokens_none_usage() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let _lease = load_manager.begin_request(endpoint_id, None).await.unwrap();
+
+        load_manager
+            .finish_request_with_tokens(
+                endpoint_id,
+                RequestOutcome::Success,
+                StdDuration::from_millis(100),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snap.total_input_tokens, 0);
+        assert_eq!(snap.total_output_tokens, 0);
+        assert_eq!(snap.total_tokens, 0);
     }
 
-    #[test]
-    fn endpoint_load_state_last_updated_some() {
-        let now = Utc::now();
-        let state = EndpointLoadState {
-            last_metrics: Some(HealthMetrics {
-                endpoint_id: Uuid::new_v4(),
-                cpu_usage: 0.0,
-                memory_usage: 0.0,
-                gpu_usage: None,
-                gpu_memory_usage: None,
-                gpu_memory_total_mb: None,
-                gpu_memory_used_mb: None,
-                gpu_temperature: None,
-                gpu_model_name: None,
-                gpu_compute_capability: None,
-                gpu_capability_score: None,
-                active_requests: 0,
-                total_requests: 0,
-                average_response_time_ms: None,
-                timestamp: now,
-            }),
-            ..Default::default()
-        };
-        assert_eq!(state.last_updated(), Some(now));
+    #[tokio::test]
+    async fn finish_request_with_tokens_partial_usage() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let _lease = load_manager.begin_request(endpoint_id, None).await.unwrap();
+
+        let token_usage = Some(crate::token::TokenUsage {
+            input_tokens: Some(100),
+            output_tokens: None,
+            total_tokens: None,
+        });
+
+        load_manager
+            .finish_request_with_tokens(
+                endpoint_id,
+                RequestOutcome::Success,
+                StdDuration::from_millis(100),
+                token_usage,
+            )
+            .await
+            .unwrap();
+
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snap.total_input_tokens, 100);
+        assert_eq!(snap.total_output_tokens, 0);
+        // total_tokens is derived: input(100) + output(None) = Some(100)
+        assert_eq!(snap.total_tokens, 100);
     }
 
-    // ===== align_to_minute テスト =====
+    // ===== snapshot / snapshots テスト =====
 
-    #[test]
-    fn align_to_minute_strips_seconds_and_nanos() {
-        let ts = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 6, 15, 10, 30, 45).unwrap();
-        let aligned = super::align_to_minute(ts);
-        assert_eq!(aligned.second(), 0);
-        assert_eq!(aligned.nanosecond(), 0);
-        assert_eq!(aligned.minute(), 30);
-        assert_eq!(aligned.hour(), 10);
+    #[tokio::test]
+    async fn snapshot_unknown_endpoint_returns_error() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let result = load_manager.snapshot(Uuid::new_v4()).await;
+        assert!(result.is_err());
     }
 
-    #[test]
-    fn align_to_minute_already_aligned() {
-        let ts = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 1, 1, 0, 0, 0).unwrap();
-        let aligned = super::align_to_minute(ts);
-        assert_eq!(aligned, ts);
+    #[tokio::test]
+    async fn snapshots_returns_all_endpoints() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let snaps = load_manager.snapshots().await;
+        assert_eq!(snaps.len(), 1);
     }
 
-    // ===== prune_history テスト =====
+    #[tokio::test]
+    async fn set_endpoint_weight_ramps_and_reports_in_snapshot() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
 
-    #[test]
-    fn prune_history_removes_old_entries() {
-        let now = super::align_to_minute(Utc::now());
-        let mut history = std::collections::VecDeque::new();
-        // 120分前のエントリ（60分窓より古い）
-        let old_minute = now - chrono::Duration::minutes(120);
-        history.push_back(RequestHistoryPoint {
-            minute: old_minute,
-            success: 10,
-            error: 1,
-        });
-        // 30分前のエントリ（窓内）
-        let recent_minute = now - chrono::Duration::minutes(30);
-        history.push_back(RequestHistoryPoint {
-            minute: recent_minute,
-            success: 5,
-            error: 0,
-        });
+        // 即時変更（rampなし）
+        let previous = load_manager
+            .set_endpoint_weight(endpoint_id, 10, None)
+            .await
+            .unwrap();
+        assert_eq!(previous, 1.0);
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snap.weight, 10);
+        assert_eq!(snap.effective_weight, 10.0);
+        assert!(snap.weight_ramp_remaining_secs.is_none());
 
-        super::prune_history(&mut history, now);
-        assert_eq!(history.len(), 1);
-        assert_eq!(history[0].minute, recent_minute);
+        // ramp中は実効重みが旧値と目標の間にある
+        load_manager
+            .set_endpoint_weight(endpoint_id, 110, Some(StdDuration::from_secs(3600)))
+            .await
+            .unwrap();
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snap.weight, 110);
+        assert!(snap.effective_weight >= 10.0 && snap.effective_weight < 110.0);
+        assert!(snap.weight_ramp_remaining_secs.is_some());
+
+        // ramp中の再変更は現在の実効重みを起点に補間し直す
+        let restarted_from = load_manager
+            .set_endpoint_weight(endpoint_id, 0, Some(StdDuration::from_secs(3600)))
+            .await
+            .unwrap();
+        assert!((10.0..110.0).contains(&restarted_from));
+        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snap.weight, 0);
+        assert!(snap.effective_weight <= restarted_from);
+
+        let unknown = load_manager
+            .set_endpoint_weight(Uuid::new_v4(), 1, None)
+            .await;
+        assert!(matches!(unknown, Err(LbError::EndpointNotFound(_))));
     }
 
-    #[test]
-    fn prune_history_keeps_all_within_window() {
-        let now = super::align_to_minute(Utc::now());
-        let mut history = std::collections::VecDeque::new();
-        for i in 0..5 {
-            history.push_back(RequestHistoryPoint {
-                minute: now - chrono::Duration::minutes(i),
-                success: 1,
-                error: 0,
-            });
-        }
-        super::prune_history(&mut history, now);
-        assert_eq!(history.len(), 5);
+    // ===== metrics_history テスト =====
+
+    #[tokio::test]
+    async fn metrics_history_unknown_endpoint_returns_error() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let result = load_manager.metrics_history(Uuid::new_v4()).await;
+        assert!(result.is_err());
     }
 
-    #[test]
-    fn prune_history_empty_is_noop() {
-        let now = super::align_to_minute(Utc::now());
-        let mut history = std::collections::VecDeque::new();
-        super::prune_history(&mut history, now);
+    #[tokio::test]
+    async fn metrics_history_empty_for_no_metrics() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+        let history = load_manager.metrics_history(endpoint_id).await.unwrap();
         assert!(history.is_empty());
     }
 
-    // ===== new_history_point テスト =====
+    // ===== summary テスト =====
 
-    #[test]
-    fn new_history_point_success() {
-        let now = super::align_to_minute(Utc::now());
-        let point = super::new_history_point(now, RequestOutcome::Success);
-        assert_eq!(point.minute, now);
-        assert_eq!(point.success, 1);
-        assert_eq!(point.error, 0);
+    #[tokio::test]
+    async fn summary_empty_state() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let summary = load_manager.summary().await;
+        assert_eq!(summary.total_nodes, 1); // setup creates 1 endpoint
+        assert_eq!(summary.total_requests, 0);
+        assert_eq!(summary.successful_requests, 0);
+        assert_eq!(summary.failed_requests, 0);
     }
 
-    #[test]
-    fn new_history_point_error() {
-        let now = super::align_to_minute(Utc::now());
-        let point = super::new_history_point(now, RequestOutcome::Error);
-        assert_eq!(point.minute, now);
-        assert_eq!(point.success, 0);
-        assert_eq!(point.error, 1);
-    }
+    // ===== record_request_history テスト =====
 
-    #[test]
-    fn new_history_point_queued() {
-        let now = super::align_to_minute(Utc::now());
-        let point = super::new_history_point(now, RequestOutcome::Queued);
-        assert_eq!(point.minute, now);
-        assert_eq!(point.success, 0);
-        assert_eq!(point.error, 0);
+    #[tokio::test]
+    async fn record_request_history_creates_entry() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let ts = Utc::now();
+        load_manager
+            .record_request_history(RequestOutcome::Success, ts)
+            .await;
+        let history = load_manager.request_history().await;
+        let total_success: u64 = history.iter().map(|h| h.success).sum();
+        assert_eq!(total_success, 1);
     }
 
-    // ===== increment_history テスト =====
+    #[tokio::test]
+    async fn record_request_history_same_minute_aggregates() {
+        let _lock = TEST_LOCK.lock().await;
+        let (load_manager, _) = setup_test_load_manager().await;
+        let ts = Utc::now();
+        load_manager
+            .record_request_history(RequestOutcome::Success, ts)
+            .await;
+        load_manager
+            .record_request_history(RequestOutcome::Success, ts)
+            .await;
+        load_manager
+            .record_request_history(RequestOutcome::Error, ts)
+            .await;
 
-    #[test]
-    fn increment_history_success() {
-        let mut point = RequestHistoryPoint {
-            minute: Utc::now(),
-            success: 5,
-            error: 2,
-        };
-        super::increment_history(&mut point, RequestOutcome::Success);
-        assert_eq!(point.success, 6);
-        assert_eq!(point.error, 2);
+        let history = load_manager.request_history().await;
+        let total_success: u64 = history.iter().map(|h| h.success).sum();
+        let total_error: u64 = history.iter().map(|h| h.error).sum();
+        assert_eq!(total_success, 2);
+        assert_eq!(total_error, 1);
     }
 
-    #[test]
-    fn increment_history_error() {
-        let mut point = RequestHistoryPoint {
-            minute: Utc::now(),
-            success: 5,
-            error: 2,
-        };
-        super::increment_history(&mut point, RequestOutcome::Error);
-        assert_eq!(point.success, 5);
-        assert_eq!(point.error, 3);
-    }
+    // ===== select_endpoint_direct テスト =====
 
-    #[test]
-    fn increment_history_queued_no_change() {
-        let mut point = RequestHistoryPoint {
-            minute: Utc::now(),
-            success: 5,
-            error: 2,
-        };
-        super::increment_history(&mut point, RequestOutcome::Queued);
-        assert_eq!(point.success, 5);
-        assert_eq!(point.error, 2);
+    #[tokio::test]
+    async fn select_endpoint_direct_no_online_returns_error() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
+        let load_manager = LoadManager::new(Arc::new(registry));
+
+        let result = load_manager.select_endpoint_direct().await;
+        assert!(result.is_err());
     }
 
-    // ===== compute_round_robin_priority_for_endpoints テスト =====
+    #[tokio::test]
+    async fn select_endpoint_direct_selects_online() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
+        let mut ep = Endpoint::new(
+            "online-ep".to_string(),
+            "http://localhost:11434".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        ep.status = EndpointStatus::Online;
+        registry.add(ep).await.expect("Failed to add endpoint");
 
-    #[test]
-    fn compute_round_robin_priority_empty() {
-        let endpoints: Vec<crate::types::endpoint::Endpoint> = vec![];
-        let priority = super::compute_round_robin_priority_for_endpoints(&endpoints, 0);
-        assert!(priority.is_empty());
+        let load_manager = LoadManager::new(Arc::new(registry));
+        let result = load_manager.select_endpoint_direct().await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().name, "online-ep");
     }
 
-    #[test]
-    fn compute_round_robin_priority_single() {
-        let ep = Endpoint::new(
-            "ep1".to_string(),
-            "http://localhost:1".to_string(),
-            EndpointType::OpenaiCompatible,
-        );
-        let id = ep.id;
-        let endpoints = vec![ep];
-        let priority = super::compute_round_robin_priority_for_endpoints(&endpoints, 0);
-        assert_eq!(priority.len(), 1);
-        assert_eq!(priority[&id], 0);
+    #[tokio::test]
+    async fn circuit_breaker_excludes_failing_endpoint_until_probe_succeeds() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
+        let mut ep = Endpoint::new(
+            "breaker-ep".to_string(),
+            "http://localhost:11434".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        ep.status = EndpointStatus::Online;
+        let endpoint_id = ep.id;
+        registry.add(ep).await.expect("Failed to add endpoint");
+        let load_manager = LoadManager::new(Arc::new(registry));
+
+        // open 前から処理中のリクエスト
+        let in_flight = load_manager.begin_request(endpoint_id, None).await.unwrap();
+
+        // 既定閾値（5回）の連続エラーで open になり、選択対象から外れる
+        for _ in 0..5 {
+            let lease = load_manager.begin_request(endpoint_id, None).await.unwrap();
+            lease
+                .complete(RequestOutcome::Error, StdDuration::from_millis(10))
+                .await
+                .unwrap();
+        }
+        let snapshot = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snapshot.circuit_state, CircuitState::Open);
+        assert!(load_manager.select_endpoint_direct().await.is_err());
+
+        // cooldown 経過後、試験リクエスト中は他のリクエストを振り分けない
+        load_manager
+            .state
+            .write()
+            .await
+            .get_mut(&endpoint_id)
+            .unwrap()
+            .breaker_open_until = Some(Instant::now() - StdDuration::from_secs(1));
+        assert!(load_manager.select_endpoint_direct().await.is_ok());
+        let probe = load_manager.begin_request(endpoint_id, None).await.unwrap();
+        assert!(load_manager.select_endpoint_direct().await.is_err());
+
+        // 試験リクエスト以外の成功では closed に戻らない
+        in_flight
+            .complete(RequestOutcome::Success, StdDuration::from_millis(10))
+            .await
+            .unwrap();
+        let snapshot = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snapshot.circuit_state, CircuitState::HalfOpen);
+
+        // 取り消された試験リクエストは失敗に数えず、次の割り当てを試験リクエストにする
+        probe
+            .complete(RequestOutcome::Cancelled, StdDuration::from_millis(10))
+            .await
+            .unwrap();
+        let snapshot = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snapshot.circuit_state, CircuitState::HalfOpen);
+        assert!(load_manager.select_endpoint_direct().await.is_ok());
+        let probe = load_manager.begin_request(endpoint_id, None).await.unwrap();
+
+        // 試験リクエストが成功すれば closed に戻る
+        probe
+            .complete(RequestOutcome::Success, StdDuration::from_millis(10))
+            .await
+            .unwrap();
+        let snapshot = load_manager.snapshot(endpoint_id).await.unwrap();
+        assert_eq!(snapshot.circuit_state, CircuitState::Closed);
+        assert!(load_manager.select_endpoint_direct().await.is_ok());
     }
 
-    #[test]
-    fn compute_round_robin_priority_wraps_around() {
-        let ep1 = Endpoint::new(
-            "ep1".to_string(),
-            "http://localhost:1".to_string(),
-            EndpointType::OpenaiCompatible,
-        );
-        let ep2 = Endpoint::new(
-            "ep2".to_string(),
-            "http://localhost:2".to_string(),
+    #[tokio::test]
+    async fn adaptive_rate_limit_routes_to_other_endpoint_while_throttled() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to create test database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+        let registry = EndpointRegistry::new(pool)
+            .await
+            .expect("Failed to create endpoint registry");
+        let mut throttled = Endpoint::new(
+            "throttled-ep".to_string(),
+            "http://localhost:11434".to_string(),
             EndpointType::OpenaiCompatible,
         );
-        let ep3 = Endpoint::new(
-            "ep3".to_string(),
-            "http://localhost:3".to_string(),
+        throttled.status = EndpointStatus::Online;
+        let throttled_id = throttled.id;
+        registry
+            .add(throttled)
+            .await
+            .expect("Failed to add endpoint");
+        let mut other = Endpoint::new(
+            "other-ep".to_string(),
+            "http://localhost:11435".to_string(),
             EndpointType::OpenaiCompatible,
         );
-        let id1 = ep1.id;
-        let id2 = ep2.id;
-        let id3 = ep3.id;
-        let endpoints = vec![ep1, ep2, ep3];
-        // start_index=1 => priority: ep2=0, ep3=1, ep1=2
-        let priority = super::compute_round_robin_priority_for_endpoints(&endpoints, 1);
-        assert_eq!(priority[&id2], 0);
-        assert_eq!(priority[&id3], 1);
-        assert_eq!(priority[&id1], 2);
-    }
-
-    // ===== fill_history テスト =====
+        other.status = EndpointStatus::Online;
+        registry.add(other).await.expect("Failed to add endpoint");
+        let load_manager = LoadManager::new(Arc::new(registry));
 
-    #[test]
-    fn fill_history_fills_gaps() {
-        let now = super::align_to_minute(Utc::now());
-        let mut map = std::collections::HashMap::new();
-        // 10分前にデータがある
-        let ten_ago = now - chrono::Duration::minutes(10);
-        map.insert(
-            ten_ago,
-            RequestHistoryPoint {
-                minute: ten_ago,
-                success: 42,
-                error: 0,
-            },
-        );
+        // 1秒間の応答がすべて 429（2 req/s）→ 許可レートを 1 req/s に半減
+        {
+            let mut state = load_manager.state.write().await;
+            let rate = &mut state.entry(throttled_id).or_default().adaptive_rate;
+            let now = Instant::now();
+            rate.record_response(true, now - StdDuration::from_secs(2), 0.05, 1.0);
+            rate.record_response(true, now - StdDuration::from_secs(1), 0.05, 1.0);
+        }
+        let snapshot = load_manager.snapshot(throttled_id).await.unwrap();
+        assert_eq!(snapshot.adaptive_rate_limit_rps, Some(1.0));
 
-        let result = super::fill_history(now, &mut map);
-        // REQUEST_HISTORY_WINDOW_MINUTESは60なので、結果は60エントリ
-        assert_eq!(
-            result.len(),
-            super::types::REQUEST_HISTORY_WINDOW_MINUTES as usize
-        );
-        // 10分前のエントリには success=42 が含まれる
-        let ten_ago_entry = result.iter().find(|p| p.minute == ten_ago);
-        assert!(ten_ago_entry.is_some());
-        assert_eq!(ten_ago_entry.unwrap().success, 42);
-        // 他のエントリは success=0, error=0
-        let zero_entries: Vec<_> = result.iter().filter(|p| p.minute != ten_ago).collect();
-        for e in zero_entries {
-            assert_eq!(e.success, 0);
-            assert_eq!(e.error, 0);
+        // 送信枠を使い切ると、枠が戻るまで他のエンドポイントへ回す
+        let _lease = load_manager
+            .begin_request(throttled_id, None)
+            .await
+            .unwrap();
+        for _ in 0..4 {
+            let selected = load_manager.select_endpoint_direct().await.unwrap();
+            assert_eq!(selected.name, "other-ep");
         }
     }
 
-    // ===== admission_control テスト =====
+    // ===== select_endpoint_direct_for_model テスト =====
 
     #[tokio::test]
-    async fn admission_control_accept_when_low_load() {
+    async fn select_endpoint_direct_for_model_no_match() {
         let _lock = TEST_LOCK.lock().await;
         let (load_manager, _) = setup_test_load_manager().await;
-        let decision = load_manager.admission_control(100);
-        assert_eq!(decision, AdmissionDecision::Accept);
+        let result = load_manager
+            .select_endpoint_direct_for_model("nonexistent-model")
+            .await;
+        assert!(result.is_err());
     }
 
-    // ===== has_ready_nodes / all_initializing テスト =====
-
-    #[tokio::test]
-    async fn has_ready_nodes_empty_state() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        // 初期状態ではstateにエントリなし
-        assert!(!load_manager.has_ready_nodes().await);
-    }
+    // ===== wait_for_ready_with_timeout テスト =====
 
     #[tokio::test]
-    async fn has_ready_nodes_with_ready_endpoint() {
+    async fn wait_for_ready_with_timeout_already_ready() {
         let _lock = TEST_LOCK.lock().await;
         let (load_manager, endpoint_id) = setup_test_load_manager().await;
         load_manager
             .upsert_initial_state(endpoint_id, false, Some((1, 1)))
             .await;
-        assert!(load_manager.has_ready_nodes().await);
+        let result = load_manager
+            .wait_for_ready_with_timeout(10, StdDuration::from_millis(100))
+            .await;
+        assert_eq!(result, WaitResult::Ready);
     }
 
     #[tokio::test]
-    async fn has_ready_nodes_all_initializing() {
+    async fn wait_for_ready_with_timeout_capacity_exceeded() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        load_manager
-            .upsert_initial_state(endpoint_id, true, Some((0, 1)))
+        let (load_manager, _) = setup_test_load_manager().await;
+        // max_waiters=0 なので即座に CapacityExceeded
+        let result = load_manager
+            .wait_for_ready_with_timeout(0, StdDuration::from_millis(100))
             .await;
-        assert!(!load_manager.has_ready_nodes().await);
+        assert_eq!(result, WaitResult::CapacityExceeded);
     }
 
+    // ===== wait_for_idle_node_with_timeout テスト =====
+
     #[tokio::test]
-    async fn all_initializing_empty() {
+    async fn wait_for_idle_node_capacity_exceeded() {
         let _lock = TEST_LOCK.lock().await;
         let (load_manager, _) = setup_test_load_manager().await;
-        // stateが空の場合はfalse
-        assert!(!load_manager.all_initializing().await);
+        let result = load_manager
+            .wait_for_idle_node_with_timeout(0, StdDuration::from_millis(100))
+            .await;
+        assert_eq!(result, WaitResult::CapacityExceeded);
+        // 拒否はキュー時系列用の累計に数えられる
+        assert_eq!(load_manager.queue_rejections(), 1);
     }
 
+    // ===== wait_for_idle_node_with_timeout_for_model テスト =====
+
     #[tokio::test]
-    async fn all_initializing_true_when_all_are() {
+    async fn wait_for_idle_node_for_model_capacity_exceeded() {
         let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        load_manager
-            .upsert_initial_state(endpoint_id, true, Some((0, 1)))
+        let (load_manager, _) = setup_test_load_manager().await;
+        let result = load_manager
+            .wait_for_idle_node_with_timeout_for_model(
+                "test-model",
+                0,
+                StdDuration::from_millis(100),
+            )
             .await;
-        assert!(load_manager.all_initializing().await);
+        assert_eq!(result, WaitResult::CapacityExceeded);
     }
 
+    // ===== seed_tps_from_db api_kind マッピングテスト =====
+
     #[tokio::test]
-    async fn all_initializing_false_when_one_ready() {
+    async fn seed_tps_from_db_maps_api_kind_correctly() {
         let _lock = TEST_LOCK.lock().await;
         let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        load_manager
-            .upsert_initial_state(endpoint_id, false, Some((1, 1)))
-            .await;
-        assert!(!load_manager.all_initializing().await);
+
+        let entries = vec![
+            crate::db::endpoint_daily_stats::TpsSeedEntry {
+                endpoint_id,
+                model_id: "model-completions".to_string(),
+                api_kind: "completions".to_string(),
+                total_output_tokens: 100,
+                total_duration_ms: 1000,
+                successful_requests: 1,
+            },
+            crate::db::endpoint_daily_stats::TpsSeedEntry {
+                endpoint_id,
+                model_id: "model-responses".to_string(),
+                api_kind: "responses".to_string(),
+                total_output_tokens: 200,
+                total_duration_ms: 2000,
+                successful_requests: 2,
+            },
+            crate::db::endpoint_daily_stats::TpsSeedEntry {
+                endpoint_id,
+                model_id: "model-default".to_string(),
+                api_kind: "unknown_type".to_string(),
+                total_output_tokens: 50,
+                total_duration_ms: 500,
+                successful_requests: 1,
+            },
+        ];
+
+        load_manager.seed_tps_from_db(entries).await;
+
+        let tps = load_manager.get_model_tps(endpoint_id).await;
+        assert_eq!(tps.len(), 3);
+        assert!(tps
+            .iter()
+            .any(|t| t.model_id == "model-completions" && t.api_kind == TpsApiKind::Completions));
+        assert!(tps
+            .iter()
+            .any(|t| t.model_id == "model-responses" && t.api_kind == TpsApiKind::Responses));
+        assert!(tps
+            .iter()
+            .any(|t| t.model_id == "model-default" && t.api_kind == TpsApiKind::ChatCompletions));
     }
 
-    // ===== queue_waiters テスト =====
+    // ===== build_history_window テスト =====
 
-    #[tokio::test]
-    async fn queue_waiters_starts_at_zero() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        assert_eq!(load_manager.queue_waiters(), 0);
+    #[test]
+    fn build_history_window_returns_full_window() {
+        let history = std::collections::VecDeque::new();
+        let result = super::build_history_window(&history);
+        assert_eq!(
+            result.len(),
+            super::types::REQUEST_HISTORY_WINDOW_MINUTES as usize
+        );
     }
+}
 
-    // ===== endpoint_registry テスト =====
+/// ロードマネージャー
+///
+/// # EndpointRegistry統合
+///
+/// EndpointRegistryを使用してエンドポイント情報を管理します。
+#[derive(Clone)]
+pub struct LoadManager {
+    /// インスタンス固有ID（キャッシュキー用途）
+    instance_id: u64,
+    /// エンドポイントレジストリ
+    endpoint_registry: Arc<EndpointRegistry>,
+    state: Arc<RwLock<HashMap<Uuid, EndpointLoadState>>>,
+    round_robin: Arc<AtomicUsize>,
+    history: Arc<RwLock<VecDeque<RequestHistoryPoint>>>,
+    /// 待機中リクエスト数（簡易カウンタ）
+    #[allow(dead_code)]
+    pending: Arc<AtomicUsize>,
+    /// ready通知
+    ready_notify: Arc<Notify>,
+    /// 待機中リクエスト数（上限判定用）
+    waiters: Arc<AtomicUsize>,
+    /// リクエストキュー待機中の通知
+    queue_notify: Arc<Notify>,
+    /// リクエストキュー待機数
+    queue_waiters: Arc<AtomicUsize>,
+    /// リクエストキューで拒否された累計数（キュー満杯・待機タイムアウト）
+    queue_rejections: Arc<AtomicU64>,
+    /// エンドポイント×モデル単位のTPS状態（SPEC-4bb5b55f）
+    tps_tracker: Arc<RwLock<TpsTrackerMap>>,
+    /// ラベルベースのルーティングポリシー（優先度降順）
+    routing_policies: Arc<RwLock<Vec<RoutingPolicy>>>,
+    /// A/Bテスト設定（作成日時順）
+    experiments: Arc<RwLock<Vec<Experiment>>>,
+    /// (実験ID, グループ) → 集計値
+    experiment_stats:
+        Arc<std::sync::Mutex<HashMap<(Uuid, ExperimentGroup), experiment::GroupCounters>>>,
+    /// シャドウトラフィック設定（作成日時順）
+    shadow_targets: Arc<RwLock<Vec<ShadowTarget>>>,
+    /// シャドウ設定ID → 集計値（本番のリクエスト統計とは分離）
+    shadow_stats: Arc<std::sync::Mutex<HashMap<Uuid, shadow::ShadowCounters>>>,
+    /// 進行中の重み ramp
+    weight_ramps: Arc<RwLock<HashMap<Uuid, WeightRamp>>>,
+    /// エンドポイントID → 運用状態（`active` 以外のみ保持）
+    operational_states: Arc<RwLock<HashMap<Uuid, EndpointOperationalState>>>,
+    /// エンドポイント容量予約
+    reservations: Arc<RwLock<Vec<CapacityReservation>>>,
+    /// 予約ID → 使用中の予約スロット数
+    reservation_usage: Arc<std::sync::Mutex<HashMap<Uuid, u32>>>,
+    /// APIキー/テナント単位のストリーミング出力レート上限
+    stream_rate_limits: Arc<RwLock<Vec<StreamRateLimit>>>,
+    /// 個別設定の無い主体に適用するストリーミング出力レート（起動時の環境変数）
+    default_stream_rate: StreamRate,
+    /// 主体/クライアントIP単位で共有するストリーミング出力スロットル
+    stream_throttles: Arc<stream_rate::StreamThrottles>,
+    /// 予約のあるエンドポイントの同時スロット数
+    endpoint_slots: u32,
+    /// 予算超過通知用のダッシュボードイベントバス
+    event_bus: Arc<std::sync::OnceLock<crate::events::SharedEventBus>>,
+    /// サーキットブレーカーの状態変化を記録する監査ログライター
+    audit_log_writer: Arc<std::sync::OnceLock<crate::audit::writer::AuditLogWriter>>,
+    /// エンドポイントID → 予算超過を通知済みの請求月（`YYYY-MM`）
+    budget_notified: Arc<std::sync::Mutex<HashMap<Uuid, String>>>,
+    /// セッションID → 割り当てエンドポイント（sticky session）
+    session_bindings: Arc<std::sync::Mutex<session_affinity::SessionBindings>>,
+    /// レイテンシ基準（定期的に再計算される全エンドポイントの中央値）
+    latency_baseline: Arc<std::sync::Mutex<Option<LatencyBaseline>>>,
+}
+
+/// サーキットブレーカーの状態遷移（旧状態, 新状態, 連続エラー数）
+type CircuitTransition = (CircuitState, CircuitState, u32);
+
+/// リクエスト結果をサーキットブレーカーへ反映し、状態が変化した場合は遷移を返す
+///
+/// `probe` は half-open 中の試験リクエストか。試験リクエストが取り消された場合は
+/// 状態を変えずに次の割り当てを試験リクエストにする。
+fn record_breaker_outcome(
+    entry: &mut EndpointLoadState,
+    outcome: RequestOutcome,
+    probe: bool,
+) -> Option<CircuitTransition> {
+    let success = match outcome {
+        RequestOutcome::Success => true,
+        RequestOutcome::Error => false,
+        RequestOutcome::Cancelled => {
+            if probe {
+                entry.release_breaker_probe();
+            }
+            return None;
+        }
+        RequestOutcome::Queued => return None,
+    };
+    let now = Instant::now();
+    let old_state = entry.circuit_state(now);
+    let new_state = entry.record_breaker_outcome(
+        success,
+        probe,
+        crate::config::circuit_breaker_threshold(),
+        crate::config::circuit_breaker_cooldown(),
+        now,
+    )?;
+    Some((old_state, new_state, entry.consecutive_errors))
+}
 
-    #[tokio::test]
-    async fn endpoint_registry_returns_registry() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let registry = load_manager.endpoint_registry();
-        let ep = registry.get(endpoint_id).await;
-        assert!(ep.is_some());
-    }
+/// カナリアのリクエスト結果を記録し、自動停止（0%）すべき場合は `true` を返す
+///
+/// サーキットブレーカーが open になった場合と、直近のエラー率が
+/// `LLMLB_CANARY_MAX_ERROR_RATE` を超えた場合に停止する。
+fn record_canary_outcome(
+    entry: &mut EndpointLoadState,
+    endpoint: &crate::types::endpoint::Endpoint,
+    outcome: RequestOutcome,
+    circuit_transition: Option<CircuitTransition>,
+) -> bool {
+    if !endpoint.canary_percent.is_some_and(|percent| percent > 0) {
+        return false;
+    }
+    let success = match outcome {
+        RequestOutcome::Success => true,
+        RequestOutcome::Error => false,
+        RequestOutcome::Queued | RequestOutcome::Cancelled => return false,
+    };
+    let error_rate_exceeded = entry
+        .canary_outcomes
+        .record(success, crate::config::canary_max_error_rate());
+    let breaker_opened = matches!(circuit_transition, Some((_, CircuitState::Open, _)));
+    error_rate_exceeded || breaker_opened
+}
 
-    // ===== record_metrics テスト =====
+/// 成功したリクエストのレイテンシを動的同時実行上限へ反映する
+///
+/// `LLMLB_DYNAMIC_CONCURRENCY_MAX` が `0`（無効）の場合は何もしない。
+/// 完了を反映する前（処理中に自身を含む）に呼び出す。
+fn record_concurrency_sample(
+    entry: &mut EndpointLoadState,
+    outcome: RequestOutcome,
+    duration: StdDuration,
+) -> Option<concurrency_limit::LimitChange> {
+    let max_limit = crate::config::dynamic_concurrency_max();
+    if max_limit == 0 || !matches!(outcome, RequestOutcome::Success) {
+        return None;
+    }
+    let in_flight = entry.combined_active();
+    entry
+        .concurrency_limit
+        .record_latency(duration.as_secs_f64() * 1000.0, in_flight, max_limit)
+}
 
-    #[tokio::test]
-    async fn record_metrics_unknown_endpoint_returns_error() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let update = MetricsUpdate {
-            endpoint_id: Uuid::new_v4(),
-            cpu_usage: 10.0,
-            memory_usage: 20.0,
-            gpu_usage: None,
-            gpu_memory_usage: None,
-            gpu_memory_total_mb: None,
-            gpu_memory_used_mb: None,
-            gpu_temperature: None,
-            gpu_model_name: None,
-            gpu_compute_capability: None,
-            gpu_capability_score: None,
-            active_requests: 0,
-            average_response_time_ms: None,
-            initializing: false,
-            ready_models: None,
-        };
-        let result = load_manager.record_metrics(update).await;
-        assert!(result.is_err());
+fn log_concurrency_change(endpoint_id: Uuid, (previous, next): concurrency_limit::LimitChange) {
+    if next < previous {
+        tracing::info!(
+            endpoint_id = %endpoint_id,
+            previous_limit = previous,
+            limit = next,
+            "Latency degraded; lowering endpoint concurrency limit"
+        );
+    } else {
+        tracing::debug!(
+            endpoint_id = %endpoint_id,
+            previous_limit = previous,
+            limit = next,
+            "Raising endpoint concurrency limit"
+        );
     }
+}
 
-    #[tokio::test]
-    async fn record_metrics_valid_endpoint_succeeds() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let update = MetricsUpdate {
-            endpoint_id,
-            cpu_usage: 50.0,
-            memory_usage: 60.0,
-            gpu_usage: Some(70.0),
-            gpu_memory_usage: Some(80.0),
-            gpu_memory_total_mb: Some(8192),
-            gpu_memory_used_mb: Some(4096),
-            gpu_temperature: Some(65.0),
-            gpu_model_name: Some("NVIDIA RTX 4090".to_string()),
-            gpu_compute_capability: Some("8.9".to_string()),
-            gpu_capability_score: Some(90),
-            active_requests: 2,
-            average_response_time_ms: Some(150.0),
-            initializing: false,
-            ready_models: Some((1, 1)),
-        };
-        let result = load_manager.record_metrics(update).await;
-        assert!(result.is_ok());
-
-        let snapshot = load_manager.snapshot(endpoint_id).await.unwrap();
-        assert_eq!(snapshot.cpu_usage, Some(50.0));
-        assert_eq!(snapshot.memory_usage, Some(60.0));
-        assert_eq!(snapshot.gpu_usage, Some(70.0));
+/// `AcceptWithDelay` の最小遅延（soft しきい値ちょうど）
+const ADMISSION_MIN_DELAY_MS: f64 = 10.0;
+/// `AcceptWithDelay` の最大遅延（hard しきい値直前）
+const ADMISSION_MAX_DELAY_MS: f64 = 100.0;
+
+/// 待機数とキュー設定からアドミッション判断を算出する
+fn admission_decision(
+    waiters: usize,
+    queue_config: &crate::config::QueueConfig,
+) -> AdmissionDecision {
+    let threshold_accept = queue_config.soft_limit();
+    let threshold_reject = queue_config.hard_limit();
+
+    if waiters < threshold_accept {
+        AdmissionDecision::Accept
+    } else if waiters < threshold_reject {
+        let load_ratio =
+            (waiters - threshold_accept) as f64 / (threshold_reject - threshold_accept) as f64;
+        let delay_ms =
+            ADMISSION_MIN_DELAY_MS + load_ratio * (ADMISSION_MAX_DELAY_MS - ADMISSION_MIN_DELAY_MS);
+        AdmissionDecision::AcceptWithDelay(StdDuration::from_millis(delay_ms as u64))
+    } else {
+        AdmissionDecision::Reject
     }
+}
 
-    // ===== begin_request / finish_request テスト =====
+/// 推定応答時間が SLO を大きく超える場合にキューしきい値へ掛ける係数
+const SLO_EXCEEDED_THRESHOLD_SCALE: f64 = 0.5;
+/// プリフィル（入力トークン処理）の速度を出力TPSの何倍とみなすか
+const PREFILL_TPS_MULTIPLIER: f64 = 10.0;
 
-    #[tokio::test]
-    async fn begin_request_unknown_endpoint_returns_error() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let result = load_manager.begin_request(Uuid::new_v4()).await;
-        assert!(result.is_err());
+/// 推定応答時間を考慮したアドミッション判断を算出する
+///
+/// 推定値が `slo × factor` を超える場合は soft/hard しきい値を縮めて早めに遅延・拒否する。
+fn admission_decision_with_estimate(
+    waiters: usize,
+    queue_config: &crate::config::QueueConfig,
+    estimate: Option<StdDuration>,
+    slo: Option<StdDuration>,
+    slo_factor: f64,
+) -> AdmissionDecision {
+    let exceeds_slo = match (estimate, slo) {
+        (Some(estimate), Some(slo)) => estimate.as_secs_f64() > slo.as_secs_f64() * slo_factor,
+        _ => false,
+    };
+    if !exceeds_slo {
+        return admission_decision(waiters, queue_config);
     }
+    let tightened = crate::config::QueueConfig {
+        soft_threshold: queue_config.soft_threshold * SLO_EXCEEDED_THRESHOLD_SCALE,
+        hard_threshold: queue_config.hard_threshold * SLO_EXCEEDED_THRESHOLD_SCALE,
+        ..*queue_config
+    };
+    admission_decision(waiters, &tightened)
+}
 
-    #[tokio::test]
-    async fn finish_request_unknown_endpoint_returns_error() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let result = load_manager
-            .finish_request(
-                Uuid::new_v4(),
-                RequestOutcome::Success,
-                StdDuration::from_millis(100),
-            )
-            .await;
-        assert!(result.is_err());
+/// TPS計測状態を API 向けの TPS 情報に変換
+fn model_tps_info(model_id: &str, api_kind: TpsApiKind, state: &ModelTpsState) -> ModelTpsInfo {
+    ModelTpsInfo {
+        model_id: model_id.to_string(),
+        api_kind,
+        source: TpsSource::Production,
+        tps: state.tps_ema,
+        request_count: state.request_count,
+        total_output_tokens: state.total_output_tokens,
+        average_duration_ms: if state.request_count > 0 {
+            Some(state.total_duration_ms as f64 / state.request_count as f64)
+        } else {
+            None
+        },
     }
+}
 
-    #[tokio::test]
-    async fn finish_request_with_tokens_unknown_endpoint_returns_error() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let result = load_manager
-            .finish_request_with_tokens(
-                Uuid::new_v4(),
-                RequestOutcome::Success,
-                StdDuration::from_millis(100),
-                None,
-            )
-            .await;
-        assert!(result.is_err());
+impl LoadManager {
+    /// 新しいロードマネージャーを作成
+    pub fn new(endpoint_registry: Arc<EndpointRegistry>) -> Self {
+        Self {
+            instance_id: NEXT_LOAD_MANAGER_ID.fetch_add(1, AtomicOrdering::Relaxed),
+            endpoint_registry,
+            state: Arc::new(RwLock::new(HashMap::new())),
+            round_robin: Arc::new(AtomicUsize::new(0)),
+            history: Arc::new(RwLock::new(VecDeque::new())),
+            pending: Arc::new(AtomicUsize::new(0)),
+            ready_notify: Arc::new(Notify::new()),
+            waiters: Arc::new(AtomicUsize::new(0)),
+            queue_notify: Arc::new(Notify::new()),
+            queue_waiters: Arc::new(AtomicUsize::new(0)),
+            queue_rejections: Arc::new(AtomicU64::new(0)),
+            tps_tracker: Arc::new(RwLock::new(HashMap::new())),
+            routing_policies: Arc::new(RwLock::new(Vec::new())),
+            experiments: Arc::new(RwLock::new(Vec::new())),
+            experiment_stats: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            shadow_targets: Arc::new(RwLock::new(Vec::new())),
+            shadow_stats: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            weight_ramps: Arc::new(RwLock::new(HashMap::new())),
+            operational_states: Arc::new(RwLock::new(HashMap::new())),
+            reservations: Arc::new(RwLock::new(Vec::new())),
+            reservation_usage: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            stream_rate_limits: Arc::new(RwLock::new(Vec::new())),
+            default_stream_rate: StreamRate::from_env(),
+            stream_throttles: Arc::new(stream_rate::StreamThrottles::default()),
+            endpoint_slots: crate::config::endpoint_slots(),
+            event_bus: Arc::new(std::sync::OnceLock::new()),
+            audit_log_writer: Arc::new(std::sync::OnceLock::new()),
+            budget_notified: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            session_bindings: Arc::new(std::sync::Mutex::new(
+                session_affinity::SessionBindings::new(StdDuration::from_secs(
+                    crate::config::session_affinity_ttl_secs(),
+                )),
+            )),
+            latency_baseline: Arc::new(std::sync::Mutex::new(None)),
+        }
     }
 
-    #[tokio::test]
-    async fn finish_request_success_updates_counts() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let _lease = load_manager.begin_request(endpoint_id).await.unwrap();
-        load_manager
-            .finish_request(
-                endpoint_id,
-                RequestOutcome::Success,
-                StdDuration::from_millis(100),
-            )
-            .await
-            .unwrap();
-
-        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
-        assert_eq!(snap.successful_requests, 1);
+    /// ダッシュボードイベントバスを設定する。
+    ///
+    /// 設定後、エンドポイントが月次予算に達したときに `EndpointBudgetExceeded` を発行する。
+    pub fn set_event_bus(&self, bus: crate::events::SharedEventBus) {
+        let _ = self.event_bus.set(bus);
     }
 
-    #[tokio::test]
-    async fn finish_request_error_updates_counts() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let _lease = load_manager.begin_request(endpoint_id).await.unwrap();
-        load_manager
-            .finish_request(
-                endpoint_id,
-                RequestOutcome::Error,
-                StdDuration::from_millis(100),
-            )
-            .await
-            .unwrap();
-
-        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
-        assert_eq!(snap.failed_requests, 1);
-    }
+    /// 監査ログライターを設定する。
+    ///
+    /// 設定後、サーキットブレーカーの状態変化を監査ログへ記録する。
+    pub fn set_audit_log_writer(&self, writer: crate::audit::writer::AuditLogWriter) {
+        let _ = self.audit_log_writer.set(writer);
+    }
+
+    /// サーキットブレーカーの状態変化をログ・ダッシュボード・監査ログへ通知する
+    fn notify_circuit_transition(&self, endpoint_id: Uuid, transition: CircuitTransition) {
+        let (old_state, new_state, consecutive_errors) = transition;
+        if new_state == CircuitState::Open {
+            tracing::warn!(
+                endpoint_id = %endpoint_id,
+                old_state = old_state.as_str(),
+                consecutive_errors,
+                "Circuit breaker opened; excluding endpoint from routing until cooldown"
+            );
+        } else {
+            tracing::info!(
+                endpoint_id = %endpoint_id,
+                old_state = old_state.as_str(),
+                new_state = new_state.as_str(),
+                "Circuit breaker state changed"
+            );
+        }
 
-    #[tokio::test]
-    async fn finish_request_queued_does_not_decrement_active() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let _lease = load_manager.begin_request(endpoint_id).await.unwrap();
-        load_manager
-            .finish_request(
+        if let Some(bus) = self.event_bus.get() {
+            bus.publish(crate::events::DashboardEvent::EndpointCircuitStateChanged {
                 endpoint_id,
-                RequestOutcome::Queued,
-                StdDuration::from_millis(0),
-            )
-            .await
-            .unwrap();
-
-        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
-        // Queued does not decrement active
-        assert_eq!(snap.successful_requests, 0);
-        assert_eq!(snap.failed_requests, 0);
+                old_state,
+                new_state,
+                consecutive_errors,
+            });
+        }
+        self.send_system_audit(
+            "/system/circuit-breaker",
+            endpoint_id,
+            serde_json::json!({
+                "event": "endpoint_circuit_state_changed",
+                "old_state": old_state.as_str(),
+                "new_state": new_state.as_str(),
+                "consecutive_errors": consecutive_errors,
+            }),
+        );
     }
 
-    // ===== finish_request_with_tokens テスト =====
-
-    #[tokio::test]
-    async fn finish_request_with_tokens_records_token_usage() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let _lease = load_manager.begin_request(endpoint_id).await.unwrap();
-
-        let token_usage = Some(crate::token::TokenUsage {
-            input_tokens: Some(100),
-            output_tokens: Some(50),
-            total_tokens: Some(150),
-        });
+    /// システム起因のエンドポイント状態変化を監査ログへ記録する
+    fn send_system_audit(&self, request_path: &str, endpoint_id: Uuid, detail: serde_json::Value) {
+        if let Some(writer) = self.audit_log_writer.get() {
+            writer.send(crate::audit::types::AuditLogEntry {
+                id: None,
+                timestamp: Utc::now(),
+                http_method: "SYSTEM".to_string(),
+                request_path: request_path.to_string(),
+                status_code: 200,
+                actor_type: crate::audit::types::ActorType::Anonymous,
+                actor_id: Some("system".to_string()),
+                actor_username: None,
+                api_key_owner_id: None,
+                client_ip: None,
+                duration_ms: None,
+                input_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                model_name: None,
+                endpoint_id: Some(endpoint_id.to_string()),
+                detail: Some(detail.to_string()),
+                batch_id: None,
+                is_migrated: false,
+            });
+        }
+    }
 
-        load_manager
-            .finish_request_with_tokens(
-                endpoint_id,
-                RequestOutcome::Success,
-                StdDuration::from_millis(200),
-                token_usage,
-            )
+    /// カナリアを自動停止する（割合を 0% にして監査ログへ記録）
+    async fn trip_canary(&self, endpoint: &crate::types::endpoint::Endpoint) {
+        // 並行して完了したリクエストで既に停止済みなら何もしない
+        let current = self
+            .endpoint_registry
+            .get(endpoint.id)
             .await
-            .unwrap();
+            .and_then(|endpoint| endpoint.canary_percent);
+        let Some(previous_percent) = current.filter(|percent| *percent > 0) else {
+            return;
+        };
+        match self
+            .endpoint_registry
+            .update_canary_percent(endpoint.id, Some(0))
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                tracing::error!(endpoint_id = %endpoint.id, "Failed to stop canary endpoint: {}", e);
+                return;
+            }
+        }
+        self.reset_canary_outcomes(endpoint.id).await;
 
-        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
-        assert_eq!(snap.total_input_tokens, 100);
-        assert_eq!(snap.total_output_tokens, 50);
-        assert_eq!(snap.total_tokens, 150);
+        tracing::warn!(
+            endpoint_id = %endpoint.id,
+            endpoint_name = %endpoint.name,
+            previous_percent,
+            "Canary endpoint is failing; reduced its traffic share to 0%"
+        );
+        self.send_system_audit(
+            "/system/canary",
+            endpoint.id,
+            serde_json::json!({
+                "event": "endpoint_canary_tripped",
+                "previous_percent": previous_percent,
+                "canary_percent": 0,
+            }),
+        );
     }
 
-    #[tokio::test]
-    async fn finish_request_with_tokens_none_usage() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let _lease = load_manager.begin_request(endpoint_id).await.unwrap();
+    /// カナリアの直近リクエスト結果をリセットする（割合の変更時）
+    pub async fn reset_canary_outcomes(&self, endpoint_id: Uuid) {
+        if let Some(entry) = self.state.write().await.get_mut(&endpoint_id) {
+            entry.canary_outcomes.clear();
+        }
+    }
 
-        load_manager
-            .finish_request_with_tokens(
-                endpoint_id,
-                RequestOutcome::Success,
-                StdDuration::from_millis(100),
-                None,
-            )
-            .await
-            .unwrap();
+    /// エンドポイントが当月の予算を使い切っているか
+    ///
+    /// 単価未設定（無料）のエンドポイントや予算未設定のエンドポイントは対象外。
+    pub fn is_over_budget(&self, endpoint: &crate::types::endpoint::Endpoint) -> bool {
+        match endpoint.monthly_budget_usd {
+            Some(budget) if endpoint.is_billable() => {
+                crate::cloud_metrics::endpoint_monthly_cost(endpoint.id) >= budget
+            }
+            _ => false,
+        }
+    }
 
-        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
-        assert_eq!(snap.total_input_tokens, 0);
-        assert_eq!(snap.total_output_tokens, 0);
-        assert_eq!(snap.total_tokens, 0);
+    /// ストリーミング完了（または途中切断）時のトークン使用量を課金する
+    ///
+    /// 単価未設定のエンドポイントや未登録のエンドポイントは `None` を返す。
+    pub async fn charge_usage(
+        &self,
+        endpoint_id: Uuid,
+        usage: &crate::token::TokenUsage,
+    ) -> Option<f64> {
+        let endpoint = self.endpoint_registry.get(endpoint_id).await?;
+        self.charge_request_cost(&endpoint, usage)
     }
 
-    #[tokio::test]
-    async fn finish_request_with_tokens_partial_usage() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let _lease = load_manager.begin_request(endpoint_id).await.unwrap();
+    /// リクエストのコストを当月累計へ加算し、予算到達時に一度だけ通知する
+    ///
+    /// 課金対象のエンドポイントであれば加算したコスト（USD）を返す。
+    fn charge_request_cost(
+        &self,
+        endpoint: &crate::types::endpoint::Endpoint,
+        usage: &crate::token::TokenUsage,
+    ) -> Option<f64> {
+        if !endpoint.is_billable() {
+            return None;
+        }
+        let cost = endpoint.request_cost_usd(
+            usage.input_tokens.unwrap_or(0) as u64,
+            usage.output_tokens.unwrap_or(0) as u64,
+        );
+        if cost <= 0.0 {
+            return Some(0.0);
+        }
 
-        let token_usage = Some(crate::token::TokenUsage {
-            input_tokens: Some(100),
-            output_tokens: None,
-            total_tokens: None,
-        });
+        let month = crate::cloud_metrics::billing_month(Utc::now());
+        let monthly_cost = crate::cloud_metrics::record_endpoint_cost(endpoint.id, cost);
 
-        load_manager
-            .finish_request_with_tokens(
+        let pool = self.endpoint_registry.pool().clone();
+        let endpoint_id = endpoint.id;
+        let persist_month = month.clone();
+        tokio::spawn(async move {
+            if let Err(e) = crate::db::endpoints::add_endpoint_monthly_cost(
+                &pool,
                 endpoint_id,
-                RequestOutcome::Success,
-                StdDuration::from_millis(100),
-                token_usage,
+                &persist_month,
+                cost,
             )
             .await
-            .unwrap();
+            {
+                tracing::error!("Failed to persist endpoint monthly cost: {}", e);
+            }
+        });
 
-        let snap = load_manager.snapshot(endpoint_id).await.unwrap();
-        assert_eq!(snap.total_input_tokens, 100);
-        assert_eq!(snap.total_output_tokens, 0);
-        // total_tokens is derived: input(100) + output(None) = Some(100)
-        assert_eq!(snap.total_tokens, 100);
+        let Some(budget) = endpoint.monthly_budget_usd else {
+            return Some(cost);
+        };
+        if monthly_cost < budget {
+            return Some(cost);
+        }
+        let newly_exceeded = {
+            let mut notified = self
+                .budget_notified
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            notified.insert(endpoint.id, month.clone()).as_deref() != Some(month.as_str())
+        };
+        if newly_exceeded {
+            tracing::warn!(
+                endpoint_id = %endpoint.id,
+                endpoint_name = %endpoint.name,
+                monthly_cost_usd = monthly_cost,
+                monthly_budget_usd = budget,
+                "Endpoint reached its monthly budget; excluding it from routing until next month"
+            );
+            if let Some(bus) = self.event_bus.get() {
+                bus.publish(crate::events::DashboardEvent::EndpointBudgetExceeded {
+                    endpoint_id: endpoint.id,
+                    monthly_cost_usd: monthly_cost,
+                    monthly_budget_usd: budget,
+                });
+            }
+        }
+        Some(cost)
     }
 
-    // ===== snapshot / snapshots テスト =====
+    /// インスタンス単位のキャッシュキーを返す。
+    pub fn cache_key(&self) -> u64 {
+        self.instance_id
+    }
 
-    #[tokio::test]
-    async fn snapshot_unknown_endpoint_returns_error() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let result = load_manager.snapshot(Uuid::new_v4()).await;
-        assert!(result.is_err());
+    /// ルーティングポリシーを置き換える（優先度降順に並べ替えて保持）
+    pub async fn set_routing_policies(&self, mut policies: Vec<RoutingPolicy>) {
+        policies.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then_with(|| a.created_at.cmp(&b.created_at))
+        });
+        *self.routing_policies.write().await = policies;
     }
 
-    #[tokio::test]
-    async fn snapshots_returns_all_endpoints() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let snaps = load_manager.snapshots().await;
-        assert_eq!(snaps.len(), 1);
+    /// 現在のルーティングポリシー一覧を返す
+    pub async fn routing_policies(&self) -> Vec<RoutingPolicy> {
+        self.routing_policies.read().await.clone()
     }
 
-    // ===== metrics_history テスト =====
+    /// 候補エンドポイントにルーティングポリシーとリクエスト単位の絞り込みを適用する
+    ///
+    /// `split_canary` が偽の場合（セッションに固定済みのエンドポイントの確認など）は
+    /// カナリアの振り分けを行わず、停止中のカナリアのみを除外する。
+    async fn apply_routing_policies(
+        &self,
+        endpoints: Vec<crate::types::endpoint::Endpoint>,
+        model_id: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+        split_canary: bool,
+    ) -> RouterResult<Vec<crate::types::endpoint::Endpoint>> {
+        // やり直し時は試行済みのエンドポイントを候補から外す
+        let endpoints = if ctx.excluded_endpoints.is_empty() {
+            endpoints
+        } else {
+            endpoints
+                .into_iter()
+                .filter(|ep| !ctx.excluded_endpoints.contains(&ep.id))
+                .collect()
+        };
+        // 必須APIに対応するエンドポイントのみに絞り込む（embeddings 等）
+        let endpoints = match ctx.required_api {
+            Some(api) => {
+                self.filter_by_supported_api(endpoints, model_id, api)
+                    .await?
+            }
+            None => endpoints,
+        };
+        let policies = self.routing_policies.read().await;
+        let endpoints = match routing_policy::find_applicable_policy(&policies, model_id, api_kind)
+        {
+            Some(policy) => routing_policy::apply_policy(policy, endpoints, model_id)?,
+            None => endpoints,
+        };
+        // A/Bテストで割り当てられたグループの必須ラベルを適用
+        let endpoints = experiment::apply_assignment(endpoints, model_id, ctx.assignment.as_ref())?;
+        // X-LLMLB-Require-Tag で指定されたタグを適用
+        let endpoints =
+            required_tag::apply_required_tag(endpoints, model_id, ctx.required_tag.as_deref())?;
+        // 推定入力トークン数に応じたグループ（タグ）に絞り込む（該当なしは通常選択）
+        let endpoints =
+            context_routing::apply_context_routing(endpoints, model_id, ctx.input_tokens);
+        // カナリアのエンドポイントは設定された割合のリクエストにのみ振り分ける
+        // （同じリクエストで選択をやり直しても乱数は引き直さない）
+        let endpoints = if split_canary {
+            canary::apply_canary(
+                endpoints,
+                |endpoint| endpoint.canary_percent,
+                ctx.canary_roll.unwrap_or_else(canary::draw),
+            )
+        } else {
+            canary::exclude_stopped(endpoints, |endpoint| endpoint.canary_percent)
+        };
+        let endpoints = self.filter_by_free_vram(endpoints, model_id).await;
+        // 実トラフィックでエラーが連続した（suspect）エンドポイントは後回しにする
+        Ok(crate::health::passive::passive_health().deprioritize(endpoints))
+    }
 
-    #[tokio::test]
-    async fn metrics_history_unknown_endpoint_returns_error() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let result = load_manager.metrics_history(Uuid::new_v4()).await;
-        assert!(result.is_err());
+    /// モデルの推定必要VRAMに対して空きVRAMが不足するエンドポイントを除外する
+    ///
+    /// 必要VRAMを推定できないモデル・GPUメモリを報告していないエンドポイントは従来通り扱う。
+    /// 全候補が不足している場合は空きVRAMが最大のエンドポイントのみを残す。
+    async fn filter_by_free_vram(
+        &self,
+        endpoints: Vec<crate::types::endpoint::Endpoint>,
+        model_id: &str,
+    ) -> Vec<crate::types::endpoint::Endpoint> {
+        let state = self.state.read().await;
+        let before = endpoints.len();
+        let endpoints = crate::models::vram::filter_by_free_vram(endpoints, |endpoint| {
+            let required = crate::models::vram::required_vram_mb(endpoint.id, model_id)?;
+            let free = state
+                .get(&endpoint.id)
+                .and_then(|load| load.last_metrics.as_ref())
+                .and_then(crate::models::vram::free_vram_mb)?;
+            Some((required, free))
+        });
+        if endpoints.len() < before {
+            tracing::debug!(
+                model = %model_id,
+                excluded = before - endpoints.len(),
+                "Excluded endpoints without enough free VRAM"
+            );
+        }
+        endpoints
     }
 
-    #[tokio::test]
-    async fn metrics_history_empty_for_no_metrics() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        let history = load_manager.metrics_history(endpoint_id).await.unwrap();
-        assert!(history.is_empty());
+    /// A/Bテスト設定を置き換える
+    pub async fn set_experiments(&self, experiments: Vec<Experiment>) {
+        let ids: std::collections::HashSet<Uuid> = experiments.iter().map(|e| e.id).collect();
+        *self.experiments.write().await = experiments;
+        self.experiment_stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(id, _), _| ids.contains(id));
     }
 
-    // ===== summary テスト =====
+    /// リクエストをA/Bテストのグループへ割り当てる（対象外は `None`）
+    pub async fn assign_experiment(
+        &self,
+        model_id: &str,
+        subject: &experiment::ExperimentSubject,
+    ) -> Option<ExperimentAssignment> {
+        let experiments = self.experiments.read().await;
+        experiment::assign(&experiments, model_id, subject)
+    }
 
-    #[tokio::test]
-    async fn summary_empty_state() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let summary = load_manager.summary().await;
-        assert_eq!(summary.total_nodes, 1); // setup creates 1 endpoint
-        assert_eq!(summary.total_requests, 0);
-        assert_eq!(summary.successful_requests, 0);
-        assert_eq!(summary.failed_requests, 0);
+    /// A/Bテストのグループ別結果を記録する
+    pub fn record_experiment_result(
+        &self,
+        assignment: &ExperimentAssignment,
+        success: bool,
+        latency: StdDuration,
+    ) {
+        let mut stats = self
+            .experiment_stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let counters = stats
+            .entry((assignment.experiment_id, assignment.group))
+            .or_default();
+        counters.requests += 1;
+        if !success {
+            counters.errors += 1;
+        }
+        counters.total_latency_ms += latency.as_millis() as u64;
     }
 
-    // ===== record_request_history テスト =====
+    /// A/Bテストのグループ別統計（A, B の順）
+    pub fn experiment_stats(&self, experiment_id: Uuid) -> Vec<ExperimentGroupStats> {
+        let stats = self
+            .experiment_stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        [ExperimentGroup::A, ExperimentGroup::B]
+            .into_iter()
+            .map(|group| {
+                let counters = stats
+                    .get(&(experiment_id, group))
+                    .copied()
+                    .unwrap_or_default();
+                ExperimentGroupStats::from_counters(group, counters)
+            })
+            .collect()
+    }
 
-    #[tokio::test]
-    async fn record_request_history_creates_entry() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let ts = Utc::now();
-        load_manager
-            .record_request_history(RequestOutcome::Success, ts)
-            .await;
-        let history = load_manager.request_history().await;
-        let total_success: u64 = history.iter().map(|h| h.success).sum();
-        assert_eq!(total_success, 1);
+    /// シャドウトラフィック設定を置き換える
+    pub async fn set_shadow_targets(&self, targets: Vec<ShadowTarget>) {
+        let ids: std::collections::HashSet<Uuid> = targets.iter().map(|t| t.id).collect();
+        *self.shadow_targets.write().await = targets;
+        self.shadow_stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|id, _| ids.contains(id));
     }
 
-    #[tokio::test]
-    async fn record_request_history_same_minute_aggregates() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let ts = Utc::now();
-        load_manager
-            .record_request_history(RequestOutcome::Success, ts)
-            .await;
-        load_manager
-            .record_request_history(RequestOutcome::Success, ts)
-            .await;
-        load_manager
-            .record_request_history(RequestOutcome::Error, ts)
-            .await;
+    /// このリクエストを複製するシャドウ設定を抽選する（設定ごとに独立して抽選）
+    pub async fn sample_shadow_targets(&self, model_id: &str) -> Vec<ShadowTarget> {
+        let targets = self.shadow_targets.read().await;
+        if targets.is_empty() {
+            return Vec::new();
+        }
+        use rand::RngExt;
+        let mut rng = rand::rng();
+        targets
+            .iter()
+            .filter(|target| target.samples(model_id, rng.random_range(0..100u8)))
+            .cloned()
+            .collect()
+    }
 
-        let history = load_manager.request_history().await;
-        let total_success: u64 = history.iter().map(|h| h.success).sum();
-        let total_error: u64 = history.iter().map(|h| h.error).sum();
-        assert_eq!(total_success, 2);
-        assert_eq!(total_error, 1);
+    /// シャドウ送信の結果を記録する
+    pub fn record_shadow_result(&self, target_id: Uuid, success: bool, latency: StdDuration) {
+        let mut stats = self.shadow_stats.lock().unwrap_or_else(|e| e.into_inner());
+        let counters = stats.entry(target_id).or_default();
+        counters.requests += 1;
+        if !success {
+            counters.errors += 1;
+        }
+        counters.total_latency_ms += latency.as_millis() as u64;
     }
 
-    // ===== select_endpoint_direct テスト =====
+    /// シャドウ送信の統計
+    pub fn shadow_stats(&self, target_id: Uuid) -> ShadowStats {
+        let stats = self.shadow_stats.lock().unwrap_or_else(|e| e.into_inner());
+        ShadowStats::from_counters(stats.get(&target_id).copied().unwrap_or_default())
+    }
 
-    #[tokio::test]
-    async fn select_endpoint_direct_no_online_returns_error() {
-        let _lock = TEST_LOCK.lock().await;
-        let pool = SqlitePool::connect("sqlite::memory:")
-            .await
-            .expect("Failed to create test database");
-        sqlx::migrate!("./migrations")
-            .run(&pool)
-            .await
-            .expect("Failed to run migrations");
-        let registry = EndpointRegistry::new(pool)
-            .await
-            .expect("Failed to create endpoint registry");
-        let load_manager = LoadManager::new(Arc::new(registry));
+    /// 運用状態を置き換える（起動時の復元用）
+    pub async fn set_operational_states(&self, states: Vec<EndpointOperationalState>) {
+        *self.operational_states.write().await = states
+            .into_iter()
+            .filter(|s| !s.state.accepts_requests())
+            .map(|s| (s.endpoint_id, s))
+            .collect();
+    }
 
-        let result = load_manager.select_endpoint_direct().await;
-        assert!(result.is_err());
+    /// エンドポイントの運用状態を更新する（`active` は解除扱い）
+    pub async fn set_operational_state(&self, state: EndpointOperationalState) {
+        let mut states = self.operational_states.write().await;
+        if state.state.accepts_requests() {
+            states.remove(&state.endpoint_id);
+        } else {
+            states.insert(state.endpoint_id, state);
+        }
     }
 
-    #[tokio::test]
-    async fn select_endpoint_direct_selects_online() {
-        let _lock = TEST_LOCK.lock().await;
-        let pool = SqlitePool::connect("sqlite::memory:")
-            .await
-            .expect("Failed to create test database");
-        sqlx::migrate!("./migrations")
-            .run(&pool)
-            .await
-            .expect("Failed to run migrations");
-        let registry = EndpointRegistry::new(pool)
+    /// エンドポイントの運用状態（未設定は `None` = 通常運用）
+    pub async fn operational_state(&self, endpoint_id: Uuid) -> Option<EndpointOperationalState> {
+        self.operational_states
+            .read()
             .await
-            .expect("Failed to create endpoint registry");
-        let mut ep = Endpoint::new(
-            "online-ep".to_string(),
-            "http://localhost:11434".to_string(),
-            EndpointType::OpenaiCompatible,
-        );
-        ep.status = EndpointStatus::Online;
-        registry.add(ep).await.expect("Failed to add endpoint");
-
-        let load_manager = LoadManager::new(Arc::new(registry));
-        let result = load_manager.select_endpoint_direct().await;
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap().name, "online-ep");
+            .get(&endpoint_id)
+            .cloned()
     }
 
-    // ===== select_endpoint_direct_for_model テスト =====
-
-    #[tokio::test]
-    async fn select_endpoint_direct_for_model_no_match() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let result = load_manager
-            .select_endpoint_direct_for_model("nonexistent-model")
-            .await;
-        assert!(result.is_err());
+    /// 通常運用以外の運用状態一覧
+    pub async fn operational_states(&self) -> Vec<EndpointOperationalState> {
+        self.operational_states
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect()
     }
 
-    // ===== wait_for_ready_with_timeout テスト =====
-
-    #[tokio::test]
-    async fn wait_for_ready_with_timeout_already_ready() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
-        load_manager
-            .upsert_initial_state(endpoint_id, false, Some((1, 1)))
-            .await;
-        let result = load_manager
-            .wait_for_ready_with_timeout(10, StdDuration::from_millis(100))
-            .await;
-        assert_eq!(result, WaitResult::Ready);
+    /// 容量予約を置き換える
+    pub async fn set_reservations(&self, reservations: Vec<CapacityReservation>) {
+        let ids: std::collections::HashSet<Uuid> = reservations.iter().map(|r| r.id).collect();
+        *self.reservations.write().await = reservations;
+        self.reservation_usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|id, _| ids.contains(id));
     }
 
-    #[tokio::test]
-    async fn wait_for_ready_with_timeout_capacity_exceeded() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        // max_waiters=0 なので即座に CapacityExceeded
-        let result = load_manager
-            .wait_for_ready_with_timeout(0, StdDuration::from_millis(100))
-            .await;
-        assert_eq!(result, WaitResult::CapacityExceeded);
+    /// 現在の容量予約一覧を返す
+    pub async fn reservations(&self) -> Vec<CapacityReservation> {
+        self.reservations.read().await.clone()
     }
 
-    // ===== wait_for_idle_node_with_timeout テスト =====
-
-    #[tokio::test]
-    async fn wait_for_idle_node_capacity_exceeded() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let result = load_manager
-            .wait_for_idle_node_with_timeout(0, StdDuration::from_millis(100))
-            .await;
-        assert_eq!(result, WaitResult::CapacityExceeded);
+    /// ストリーミング出力レート上限を置き換える
+    pub async fn set_stream_rate_limits(&self, limits: Vec<StreamRateLimit>) {
+        *self.stream_rate_limits.write().await = limits;
     }
 
-    // ===== wait_for_idle_node_with_timeout_for_model テスト =====
+    /// 個別設定の無い主体に適用するストリーミング出力レート
+    pub fn default_stream_rate(&self) -> StreamRate {
+        self.default_stream_rate
+    }
 
-    #[tokio::test]
-    async fn wait_for_idle_node_for_model_capacity_exceeded() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, _) = setup_test_load_manager().await;
-        let result = load_manager
-            .wait_for_idle_node_with_timeout_for_model(
-                "test-model",
-                0,
-                StdDuration::from_millis(100),
-            )
-            .await;
-        assert_eq!(result, WaitResult::CapacityExceeded);
+    /// リクエスト主体に適用するストリーミング出力スロットル（制限なしは `None`）
+    ///
+    /// 同じAPIキー/テナント（主体が無い場合はクライアントIP）のストリームは
+    /// 1つの上限を共有する。
+    pub async fn stream_throttle_for(
+        &self,
+        principal: Option<&reservation::RequestPrincipal>,
+        client_ip: Option<std::net::IpAddr>,
+    ) -> Option<StreamThrottle> {
+        let limits = self.stream_rate_limits.read().await;
+        let (rate, key) =
+            stream_rate::resolve(&limits, principal, client_ip, self.default_stream_rate)?;
+        Some(self.stream_throttles.throttle(key, rate))
+    }
+
+    /// 同時スロット数の既定値（エンドポイントに `slots` が未設定の場合に使う）
+    pub fn default_endpoint_slots(&self) -> u32 {
+        self.endpoint_slots
+    }
+
+    /// エンドポイントの同時スロット数
+    pub fn endpoint_slots(&self, endpoint: &crate::types::endpoint::Endpoint) -> u32 {
+        endpoint.slots.unwrap_or(self.endpoint_slots)
+    }
+
+    /// 予約ごとの使用中スロット数
+    pub fn reservation_usage(&self, reservation_id: Uuid) -> u32 {
+        self.reservation_usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&reservation_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// 予約スロットを解放する
+    pub(crate) fn release_reservation_slot(&self, reservation_id: Uuid) {
+        let mut usage = self
+            .reservation_usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = usage.get_mut(&reservation_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                usage.remove(&reservation_id);
+            }
+        }
     }
 
-    // ===== seed_tps_from_db api_kind マッピングテスト =====
+    /// リクエスト主体から見た、エンドポイントへの割り当て可否
+    fn admission_for(
+        &self,
+        endpoint: &crate::types::endpoint::Endpoint,
+        reservations: &[CapacityReservation],
+        state: &HashMap<Uuid, EndpointLoadState>,
+        usage: &HashMap<Uuid, u32>,
+        principal: Option<&reservation::RequestPrincipal>,
+    ) -> reservation::Admission {
+        let for_endpoint: Vec<&CapacityReservation> = reservations
+            .iter()
+            .filter(|r| r.endpoint_id == endpoint.id)
+            .collect();
+        let total_active = state
+            .get(&endpoint.id)
+            .map(|load| load.combined_active())
+            .unwrap_or(0);
+        reservation::admit(
+            &for_endpoint,
+            usage,
+            total_active,
+            self.endpoint_slots(endpoint),
+            principal,
+        )
+    }
+
+    /// 容量予約により割り当てできないエンドポイントを候補から除外する
+    ///
+    /// 選択前の事前絞り込みで、確定判定は `begin_request` がスロット確保と同時に行う。
+    async fn filter_by_reservations(
+        &self,
+        endpoints: Vec<crate::types::endpoint::Endpoint>,
+        principal: Option<&reservation::RequestPrincipal>,
+    ) -> Vec<crate::types::endpoint::Endpoint> {
+        let reservations = self.reservations.read().await;
+        if reservations.is_empty() {
+            return endpoints;
+        }
+        let state = self.state.read().await;
+        let usage = self
+            .reservation_usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        endpoints
+            .into_iter()
+            .filter(|ep| {
+                self.admission_for(ep, &reservations, &state, &usage, principal)
+                    != reservation::Admission::Rejected
+            })
+            .collect()
+    }
 
-    #[tokio::test]
-    async fn seed_tps_from_db_maps_api_kind_correctly() {
-        let _lock = TEST_LOCK.lock().await;
-        let (load_manager, endpoint_id) = setup_test_load_manager().await;
+    /// エンドポイントの重みを変更し、変更前の実効重みを返す
+    ///
+    /// `ramp` を指定すると現在の実効重みから目標重みまで線形に変化させる。
+    /// ramp中に再変更された場合は、その時点の実効重みを起点に新しい目標へ補間し直す。
+    pub async fn set_endpoint_weight(
+        &self,
+        endpoint_id: Uuid,
+        weight: u32,
+        ramp: Option<StdDuration>,
+    ) -> RouterResult<f64> {
+        let endpoint = self
+            .endpoint_registry
+            .get(endpoint_id)
+            .await
+            .ok_or(LbError::EndpointNotFound(endpoint_id))?;
 
-        let entries = vec![
-            crate::db::endpoint_daily_stats::TpsSeedEntry {
-                endpoint_id,
-                model_id: "model-completions".to_string(),
-                api_kind: "completions".to_string(),
-                total_output_tokens: 100,
-                total_duration_ms: 1000,
-                successful_requests: 1,
-            },
-            crate::db::endpoint_daily_stats::TpsSeedEntry {
-                endpoint_id,
-                model_id: "model-responses".to_string(),
-                api_kind: "responses".to_string(),
-                total_output_tokens: 200,
-                total_duration_ms: 2000,
-                successful_requests: 2,
-            },
-            crate::db::endpoint_daily_stats::TpsSeedEntry {
-                endpoint_id,
-                model_id: "model-default".to_string(),
-                api_kind: "unknown_type".to_string(),
-                total_output_tokens: 50,
-                total_duration_ms: 500,
-                successful_requests: 1,
-            },
-        ];
+        let mut ramps = self.weight_ramps.write().await;
+        let now = Instant::now();
+        let current = effective_weight_at(&endpoint, ramps.get(&endpoint_id), now);
 
-        load_manager.seed_tps_from_db(entries).await;
+        self.endpoint_registry
+            .update_weight(endpoint_id, weight)
+            .await
+            .map_err(|e| LbError::Database(format!("Failed to update endpoint weight: {}", e)))?;
 
-        let tps = load_manager.get_model_tps(endpoint_id).await;
-        assert_eq!(tps.len(), 3);
-        assert!(tps
-            .iter()
-            .any(|t| t.model_id == "model-completions" && t.api_kind == TpsApiKind::Completions));
-        assert!(tps
-            .iter()
-            .any(|t| t.model_id == "model-responses" && t.api_kind == TpsApiKind::Responses));
-        assert!(tps
-            .iter()
-            .any(|t| t.model_id == "model-default" && t.api_kind == TpsApiKind::ChatCompletions));
+        match ramp.filter(|duration| !duration.is_zero()) {
+            Some(duration) if current != weight as f64 => {
+                ramps.insert(endpoint_id, WeightRamp::start(current, weight, duration));
+            }
+            _ => {
+                ramps.remove(&endpoint_id);
+            }
+        }
+
+        Ok(current)
     }
 
-    // ===== build_history_window テスト =====
+    /// 保存済みの重み変更を ramp 状態へ反映し、変更前の実効重みを返す。
+    ///
+    /// `previous` は重み変更前のエンドポイント。一括更新のようにDB更新を
+    /// 呼び出し側でまとめて行う場合に使う。
+    pub async fn apply_weight_ramp(
+        &self,
+        previous: &crate::types::endpoint::Endpoint,
+        weight: u32,
+        ramp: Option<StdDuration>,
+    ) -> f64 {
+        let mut ramps = self.weight_ramps.write().await;
+        let current = effective_weight_at(previous, ramps.get(&previous.id), Instant::now());
+        match ramp.filter(|duration| !duration.is_zero()) {
+            Some(duration) if current != weight as f64 => {
+                ramps.insert(previous.id, WeightRamp::start(current, weight, duration));
+            }
+            _ => {
+                ramps.remove(&previous.id);
+            }
+        }
+        current
+    }
 
-    #[test]
-    fn build_history_window_returns_full_window() {
-        let history = std::collections::VecDeque::new();
-        let result = super::build_history_window(&history);
-        assert_eq!(
-            result.len(),
-            super::types::REQUEST_HISTORY_WINDOW_MINUTES as usize
-        );
+    /// ストリーミングの最初のチャンクまでの時間（TTFT）を記録する
+    pub async fn record_ttft(&self, endpoint_id: Uuid, ttft: StdDuration) {
+        let mut state = self.state.write().await;
+        state.entry(endpoint_id).or_default().update_ttft(ttft);
     }
-}
 
-/// ロードマネージャー
-///
-/// # EndpointRegistry統合
-///
-/// EndpointRegistryを使用してエンドポイント情報を管理します。
-#[derive(Clone)]
-pub struct LoadManager {
-    /// インスタンス固有ID（キャッシュキー用途）
-    instance_id: u64,
-    /// エンドポイントレジストリ
-    endpoint_registry: Arc<EndpointRegistry>,
-    state: Arc<RwLock<HashMap<Uuid, EndpointLoadState>>>,
-    round_robin: Arc<AtomicUsize>,
-    history: Arc<RwLock<VecDeque<RequestHistoryPoint>>>,
-    /// 待機中リクエスト数（簡易カウンタ）
-    #[allow(dead_code)]
-    pending: Arc<AtomicUsize>,
-    /// ready通知
-    ready_notify: Arc<Notify>,
-    /// 待機中リクエスト数（上限判定用）
-    waiters: Arc<AtomicUsize>,
-    /// リクエストキュー待機中の通知
-    queue_notify: Arc<Notify>,
-    /// リクエストキュー待機数
-    queue_waiters: Arc<AtomicUsize>,
-    /// エンドポイント×モデル単位のTPS状態（SPEC-4bb5b55f）
-    tps_tracker: Arc<RwLock<TpsTrackerMap>>,
-}
+    /// 応答の出力トークン数を記録する
+    ///
+    /// モデルの通常範囲から大きく外れた応答が直近ウィンドウで続いた場合は
+    /// degraded 相当として警告を出す（閾値は `LLMLB_RESPONSE_ANOMALY_ZSCORE` /
+    /// `LLMLB_RESPONSE_ANOMALY_WINDOW`）。
+    pub async fn record_response_tokens(
+        &self,
+        endpoint_id: Uuid,
+        model_id: &str,
+        output_tokens: u64,
+    ) {
+        let z_threshold = crate::config::response_anomaly_zscore();
+        let window = crate::config::response_anomaly_window();
 
-impl LoadManager {
-    /// 新しいロードマネージャーを作成
-    pub fn new(endpoint_registry: Arc<EndpointRegistry>) -> Self {
-        Self {
-            instance_id: NEXT_LOAD_MANAGER_ID.fetch_add(1, AtomicOrdering::Relaxed),
-            endpoint_registry,
-            state: Arc::new(RwLock::new(HashMap::new())),
-            round_robin: Arc::new(AtomicUsize::new(0)),
-            history: Arc::new(RwLock::new(VecDeque::new())),
-            pending: Arc::new(AtomicUsize::new(0)),
-            ready_notify: Arc::new(Notify::new()),
-            waiters: Arc::new(AtomicUsize::new(0)),
-            queue_notify: Arc::new(Notify::new()),
-            queue_waiters: Arc::new(AtomicUsize::new(0)),
-            tps_tracker: Arc::new(RwLock::new(HashMap::new())),
+        let mut state = self.state.write().await;
+        let stats = state
+            .entry(endpoint_id)
+            .or_default()
+            .response_token_stats
+            .entry(model_id.to_string())
+            .or_default();
+        match stats.record(output_tokens, z_threshold, window) {
+            Some(true) => tracing::warn!(
+                endpoint_id = %endpoint_id,
+                model = %model_id,
+                output_tokens,
+                expected_mean = stats.mean,
+                expected_std_dev = stats.variance.sqrt(),
+                "Endpoint responses are persistently outside the expected token range (degraded)"
+            ),
+            Some(false) => tracing::info!(
+                endpoint_id = %endpoint_id,
+                model = %model_id,
+                "Endpoint response token counts returned to the expected range"
+            ),
+            None => {}
         }
     }
 
-    /// インスタンス単位のキャッシュキーを返す。
-    pub fn cache_key(&self) -> u64 {
-        self.instance_id
+    /// エンドポイントの実効重み（ramp中は補間値）
+    pub async fn effective_weight(&self, endpoint: &crate::types::endpoint::Endpoint) -> f64 {
+        let ramps = self.weight_ramps.read().await;
+        effective_weight_at(endpoint, ramps.get(&endpoint.id), Instant::now())
     }
 
     /// TPS計測値を更新（SPEC-4bb5b55f）
@@ -1793,24 +4528,47 @@ impl LoadManager {
         tracker.retain(|(eid, _, _), _| *eid != endpoint_id);
     }
 
+    /// 推論レイテンシの EMA に投入する値を返す（SPEC-f8e3a1b7）
+    ///
+    /// 直近分布に対する外れ値（`LLMLB_LATENCY_OUTLIER_PERCENTILE` 超過）はパーセンタイル値に
+    /// クリップし、外れ値の回数をエンドポイントの統計（`latency_outliers`）に記録する。
+    /// 外れ値が連続する場合は実際の悪化とみなしてクリップしない。
+    pub async fn filter_latency_sample(&self, endpoint_id: Uuid, latency_ms: f64) -> f64 {
+        let percentile = crate::config::latency_outlier_percentile();
+        let mut state = self.state.write().await;
+        let Some(entry) = state.get_mut(&endpoint_id) else {
+            return latency_ms;
+        };
+        let filtered = entry.clip_latency_outlier(latency_ms, percentile);
+        if filtered < latency_ms {
+            tracing::debug!(
+                endpoint_id = %endpoint_id,
+                latency_ms,
+                clipped_ms = filtered,
+                total_outliers = entry.latency_outliers,
+                "Clipped latency outlier before updating inference latency EMA"
+            );
+        }
+        filtered
+    }
+
     /// エンドポイントのモデル別TPS情報を取得（SPEC-4bb5b55f）
     pub async fn get_model_tps(&self, endpoint_id: Uuid) -> Vec<ModelTpsInfo> {
         let tracker = self.tps_tracker.read().await;
         tracker
             .iter()
             .filter(|((eid, _, _), _)| *eid == endpoint_id)
-            .map(|((_, model_id, api_kind), state)| ModelTpsInfo {
-                model_id: model_id.clone(),
-                api_kind: *api_kind,
-                source: TpsSource::Production,
-                tps: state.tps_ema,
-                request_count: state.request_count,
-                total_output_tokens: state.total_output_tokens,
-                average_duration_ms: if state.request_count > 0 {
-                    Some(state.total_duration_ms as f64 / state.request_count as f64)
-                } else {
-                    None
-                },
+            .map(|((_, model_id, api_kind), state)| model_tps_info(model_id, *api_kind, state))
+            .collect()
+    }
+
+    /// 全エンドポイントのモデル別TPS情報を取得（TPS履歴のスナップショット用）
+    pub async fn get_all_model_tps(&self) -> Vec<(Uuid, ModelTpsInfo)> {
+        let tracker = self.tps_tracker.read().await;
+        tracker
+            .iter()
+            .map(|((endpoint_id, model_id, api_kind), state)| {
+                (*endpoint_id, model_tps_info(model_id, *api_kind, state))
             })
             .collect()
     }
@@ -1924,6 +4682,7 @@ impl LoadManager {
         endpoints: Vec<crate::types::endpoint::Endpoint>,
         model_id: Option<&str>,
         api_kind: Option<TpsApiKind>,
+        principal: Option<&reservation::RequestPrincipal>,
     ) -> RouterResult<crate::types::endpoint::Endpoint> {
         if endpoints.is_empty() {
             return Err(match model_id {
@@ -1945,6 +4704,8 @@ impl LoadManager {
                 .collect()
         };
 
+        let candidates = self.filter_by_reservations(candidates, principal).await;
+
         if candidates.is_empty() {
             return Err(LbError::NoEndpointsAvailable);
         }
@@ -1952,6 +4713,25 @@ impl LoadManager {
         let scores = self
             .compute_endpoint_tps_scores(&candidates, model_id, api_kind)
             .await;
+        // レイテンシ基準に対して遅すぎるエンドポイントは後回しにする（除外はしない）
+        let penalized: std::collections::HashSet<Uuid> = self
+            .latency_penalized_endpoints()
+            .await
+            .into_iter()
+            .collect();
+        // TPSが同点の場合はp95レイテンシが低い方を優先する（未計測は0扱い）
+        let p95_latencies: HashMap<Uuid, f32> = {
+            let state = self.state.read().await;
+            candidates
+                .iter()
+                .filter_map(|ep| {
+                    state
+                        .get(&ep.id)
+                        .and_then(|load| load.percentile_latency_ms(95.0))
+                        .map(|p95| (ep.id, p95))
+                })
+                .collect()
+        };
         let round_robin_cursor = self.round_robin.fetch_add(1, AtomicOrdering::SeqCst);
         let round_robin_start = round_robin_cursor % candidates.len().max(1);
         let round_robin_priority =
@@ -1962,9 +4742,19 @@ impl LoadManager {
             let a_score = scores.get(&a.id).copied().unwrap_or(0.0);
             let b_score = scores.get(&b.id).copied().unwrap_or(0.0);
 
-            b_score
-                .partial_cmp(&a_score)
-                .unwrap_or(std::cmp::Ordering::Equal)
+            penalized
+                .contains(&a.id)
+                .cmp(&penalized.contains(&b.id))
+                .then_with(|| {
+                    b_score
+                        .partial_cmp(&a_score)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .then_with(|| {
+                    let a_p95 = p95_latencies.get(&a.id).copied().unwrap_or(0.0);
+                    let b_p95 = p95_latencies.get(&b.id).copied().unwrap_or(0.0);
+                    a_p95.total_cmp(&b_p95)
+                })
                 .then_with(|| {
                     let a_rank = round_robin_priority
                         .get(&a.id)
@@ -2114,6 +4904,16 @@ impl LoadManager {
         state.values().any(|s| !s.initializing)
     }
 
+    /// 初期化中のエンドポイントID一覧
+    pub async fn initializing_endpoint_ids(&self) -> std::collections::HashSet<Uuid> {
+        let state = self.state.read().await;
+        state
+            .iter()
+            .filter(|(_, load)| load.initializing)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
     /// 全ノードが初期化中かを判定
     pub async fn all_initializing(&self) -> bool {
         let state = self.state.read().await;
@@ -2168,6 +4968,11 @@ impl LoadManager {
         self.queue_waiters.load(AtomicOrdering::Relaxed)
     }
 
+    /// リクエストキューで拒否された累計数を取得（キュー満杯・待機タイムアウト）
+    pub fn queue_rejections(&self) -> u64 {
+        self.queue_rejections.load(AtomicOrdering::Relaxed)
+    }
+
     async fn has_idle_nodes(&self) -> bool {
         let endpoints = self.endpoint_registry.list_online().await;
         if endpoints.is_empty() {
@@ -2207,6 +5012,7 @@ impl LoadManager {
         let current = self.queue_waiters.fetch_add(1, AtomicOrdering::SeqCst) + 1;
         if current > max_waiters {
             self.queue_waiters.fetch_sub(1, AtomicOrdering::SeqCst);
+            self.queue_rejections.fetch_add(1, AtomicOrdering::Relaxed);
             return WaitResult::CapacityExceeded;
         }
 
@@ -2220,7 +5026,10 @@ impl LoadManager {
 
         match result {
             Ok(_) => WaitResult::Ready,
-            Err(_) => WaitResult::Timeout,
+            Err(_) => {
+                self.queue_rejections.fetch_add(1, AtomicOrdering::Relaxed);
+                WaitResult::Timeout
+            }
         }
     }
 
@@ -2234,6 +5043,7 @@ impl LoadManager {
         let current = self.queue_waiters.fetch_add(1, AtomicOrdering::SeqCst) + 1;
         if current > max_waiters {
             self.queue_waiters.fetch_sub(1, AtomicOrdering::SeqCst);
+            self.queue_rejections.fetch_add(1, AtomicOrdering::Relaxed);
             return WaitResult::CapacityExceeded;
         }
 
@@ -2247,99 +5057,208 @@ impl LoadManager {
 
         match result {
             Ok(_) => WaitResult::Ready,
-            Err(_) => WaitResult::Timeout,
+            Err(_) => {
+                self.queue_rejections.fetch_add(1, AtomicOrdering::Relaxed);
+                WaitResult::Timeout
+            }
         }
     }
 
     /// アドミッション制御（段階的バックプレッシャー）
-    pub fn admission_control(&self, max_waiters: usize) -> AdmissionDecision {
-        let waiters = self.waiters.load(AtomicOrdering::Relaxed);
-        let threshold_accept = max_waiters / 2;
-        let threshold_reject = max_waiters * 4 / 5;
-
-        if waiters < threshold_accept {
-            AdmissionDecision::Accept
-        } else if waiters < threshold_reject {
-            let load_ratio =
-                (waiters - threshold_accept) as f64 / (threshold_reject - threshold_accept) as f64;
-            let delay_ms = 10 + (load_ratio * 90.0) as u64;
-            AdmissionDecision::AcceptWithDelay(StdDuration::from_millis(delay_ms))
-        } else {
-            AdmissionDecision::Reject
-        }
+    ///
+    /// 待機数が `soft_threshold` 未満なら即時受け入れ、`hard_threshold` 以上ならリジェクト。
+    /// その間は `soft_threshold`〜`hard_threshold` 内の位置に比例した遅延で受け入れる。
+    pub fn admission_control(
+        &self,
+        queue_config: &crate::config::QueueConfig,
+    ) -> AdmissionDecision {
+        admission_decision(self.waiters.load(AtomicOrdering::Relaxed), queue_config)
     }
 
-    /// リクエスト開始を記録
-    pub async fn begin_request(&self, endpoint_id: Uuid) -> RouterResult<RequestLease> {
-        if self.endpoint_registry.get(endpoint_id).await.is_none() {
-            return Err(LbError::EndpointNotFound(endpoint_id));
-        }
+    /// 推定応答時間を考慮したアドミッション制御
+    ///
+    /// `LLMLB_RESPONSE_TIME_SLO_MS` が設定され、推定応答時間がその
+    /// `LLMLB_RESPONSE_TIME_SLO_FACTOR` 倍を超える場合は、soft/hard しきい値を半分にして判定する。
+    /// 推定値が無い場合や SLO が未設定の場合は `admission_control` と同じ。
+    pub fn admission_control_with_estimate(
+        &self,
+        queue_config: &crate::config::QueueConfig,
+        estimate: Option<StdDuration>,
+    ) -> AdmissionDecision {
+        admission_decision_with_estimate(
+            self.waiters.load(AtomicOrdering::Relaxed),
+            queue_config,
+            estimate,
+            crate::config::response_time_slo(),
+            crate::config::response_time_slo_factor(),
+        )
+    }
+
+    /// 推定応答時間を算出する
+    ///
+    /// モデル別TPS EMA（全エンドポイント・API種別での最大値）から、
+    /// `TTFT + 入力トークン / (TPS × 10) + 出力トークン / TPS` として見積もる。
+    /// TTFT はモデルを処理したエンドポイントの TTFT EMA の最小値、出力トークン数は
+    /// `max_tokens`（未指定時はモデルの平均出力トークン数）を使う。TPS が未計測の場合は `None`。
+    /// 応答ヘッダ等で返せるよう、算出した値はそのまま呼び出し元へ返す。
+    pub async fn estimate_response_time(
+        &self,
+        model_id: &str,
+        input_tokens: u32,
+        max_tokens: Option<u32>,
+    ) -> Option<StdDuration> {
+        let (tps, average_output_tokens, endpoint_ids) = {
+            let tracker = self.tps_tracker.read().await;
+            let mut tps: Option<f64> = None;
+            let mut output_tokens = 0u64;
+            let mut requests = 0u64;
+            let mut endpoint_ids = Vec::new();
+            for ((endpoint_id, _, _), state) in
+                tracker.iter().filter(|((_, mid, _), _)| mid == model_id)
+            {
+                if let Some(ema) = state.tps_ema.filter(|ema| *ema > 0.0) {
+                    tps = Some(tps.map_or(ema, |current| current.max(ema)));
+                }
+                output_tokens = output_tokens.saturating_add(state.total_output_tokens);
+                requests = requests.saturating_add(state.request_count);
+                endpoint_ids.push(*endpoint_id);
+            }
+            let average_output_tokens = if requests > 0 {
+                output_tokens as f64 / requests as f64
+            } else {
+                0.0
+            };
+            (tps?, average_output_tokens, endpoint_ids)
+        };
 
+        let ttft_ms = {
+            let state = self.state.read().await;
+            endpoint_ids
+                .iter()
+                .filter_map(|id| state.get(id).and_then(|load| load.ttft_ema_ms))
+                .fold(None, |min: Option<f64>, ttft| {
+                    Some(min.map_or(ttft, |current| current.min(ttft)))
+                })
+                .unwrap_or(0.0)
+        };
+        let output_tokens = max_tokens.map_or(average_output_tokens, f64::from);
+        let seconds = ttft_ms / 1000.0
+            + f64::from(input_tokens) / (tps * PREFILL_TPS_MULTIPLIER)
+            + output_tokens / tps;
+        Some(StdDuration::from_secs_f64(seconds))
+    }
+
+    /// アップストリームの応答ステータスをアダプティブレートリミッタへ反映する
+    ///
+    /// 429 率が `LLMLB_ADAPTIVE_RATE_429_THRESHOLD` を超えると送信許可レートを半減し、
+    /// 収まれば `LLMLB_ADAPTIVE_RATE_INCREASE_RPS` ずつ戻す。
+    pub async fn record_upstream_status(&self, endpoint_id: Uuid, status: u16) {
         let mut state = self.state.write().await;
         let entry = state.entry(endpoint_id).or_default();
-        entry.assigned_active = entry.assigned_active.saturating_add(1);
-        entry.total_assigned = entry.total_assigned.saturating_add(1);
+        let change = entry.adaptive_rate.record_response(
+            status == 429,
+            Instant::now(),
+            crate::config::adaptive_rate_429_threshold(),
+            crate::config::adaptive_rate_increase_rps(),
+        );
+        drop(state);
 
-        Ok(RequestLease::new(self.clone(), endpoint_id))
+        match change {
+            Some((previous, Some(next))) if previous.is_none_or(|p| next < p) => {
+                tracing::warn!(
+                    endpoint_id = %endpoint_id,
+                    allowed_rps = next,
+                    "Upstream is returning 429; throttling requests to endpoint"
+                );
+            }
+            Some((_, Some(next))) => {
+                tracing::debug!(
+                    endpoint_id = %endpoint_id,
+                    allowed_rps = next,
+                    "Increasing allowed request rate to endpoint"
+                );
+            }
+            Some((_, None)) => {
+                tracing::info!(
+                    endpoint_id = %endpoint_id,
+                    "Upstream 429s subsided; request rate limit lifted"
+                );
+            }
+            None => {}
+        }
     }
 
-    /// リクエスト完了を記録
-    pub async fn finish_request(
+    /// リクエスト開始を記録
+    ///
+    /// `principal` はリクエスト主体（容量予約の判定に使う）。
+    /// 容量予約の判定とスロット確保は同じロック内で行い、空きスロットが無ければ
+    /// `ServiceUnavailable` を返す（呼び出し側は次の候補へ進むか 503 を返す）。
+    pub async fn begin_request(
         &self,
         endpoint_id: Uuid,
-        outcome: RequestOutcome,
-        duration: StdDuration,
-    ) -> RouterResult<()> {
-        if self.endpoint_registry.get(endpoint_id).await.is_none() {
+        principal: Option<&reservation::RequestPrincipal>,
+    ) -> RouterResult<RequestLease> {
+        let Some(endpoint) = self.endpoint_registry.get(endpoint_id).await else {
             return Err(LbError::EndpointNotFound(endpoint_id));
-        }
+        };
 
+        let reservations = self.reservations.read().await;
         let mut state = self.state.write().await;
-        let entry = state.entry(endpoint_id).or_default();
-
-        if let RequestOutcome::Queued = outcome {
-        } else {
-            if entry.assigned_active > 0 {
-                entry.assigned_active -= 1;
-            }
 
-            match outcome {
-                RequestOutcome::Success => {
-                    entry.success_count = entry.success_count.saturating_add(1)
+        // 予約スロットが使えればそちらを優先して割り当てる
+        let mut reservation_id = None;
+        if !reservations.is_empty() {
+            let mut usage = self
+                .reservation_usage
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            match self.admission_for(&endpoint, &reservations, &state, &usage, principal) {
+                reservation::Admission::Reserved(id) => {
+                    *usage.entry(id).or_insert(0) += 1;
+                    reservation_id = Some(id);
                 }
-                RequestOutcome::Error => entry.error_count = entry.error_count.saturating_add(1),
-                RequestOutcome::Queued => {}
-            }
-
-            entry.total_latency_ms = entry.total_latency_ms.saturating_add(duration.as_millis());
-        }
-
-        let updated_average = entry.average_latency_ms();
-
-        if let Some(metrics) = entry.last_metrics.as_mut() {
-            metrics.total_requests = entry.total_assigned;
-            if updated_average.is_some() {
-                metrics.average_response_time_ms = updated_average;
-            }
-            if let Some(latest) = entry.metrics_history.back_mut() {
-                latest.total_requests = metrics.total_requests;
-                if let Some(avg) = metrics.average_response_time_ms {
-                    latest.average_response_time_ms = Some(avg);
+                reservation::Admission::Shared => {}
+                reservation::Admission::Rejected => {
+                    return Err(LbError::ServiceUnavailable(format!(
+                        "No free slot on endpoint {} (reserved capacity is in use)",
+                        endpoint.name
+                    )));
                 }
-                latest.gpu_usage = metrics.gpu_usage;
-                latest.gpu_memory_usage = metrics.gpu_memory_usage;
             }
         }
 
-        let should_notify_idle = entry.combined_active() == 0;
-
+        let entry = state.entry(endpoint_id).or_default();
+        entry.assigned_active = entry.assigned_active.saturating_add(1);
+        entry.total_assigned = entry.total_assigned.saturating_add(1);
+        entry.adaptive_rate.consume(Instant::now());
+        // half-open 中の最初の割り当てを試験リクエストとする
+        let probe = entry
+            .claim_breaker_probe(Instant::now())
+            .map(|new_state| (CircuitState::Open, new_state, entry.consecutive_errors));
         drop(state);
-        if should_notify_idle {
-            self.queue_notify.notify_waiters();
+        drop(reservations);
+        let breaker_probe = probe.is_some();
+        if let Some(transition) = probe {
+            self.notify_circuit_transition(endpoint_id, transition);
         }
-        self.record_request_history(outcome, Utc::now()).await;
 
-        Ok(())
+        Ok(RequestLease::new(
+            self.clone(),
+            endpoint_id,
+            reservation_id,
+            breaker_probe,
+        ))
+    }
+
+    /// リクエスト完了を記録
+    pub async fn finish_request(
+        &self,
+        endpoint_id: Uuid,
+        outcome: RequestOutcome,
+        duration: StdDuration,
+    ) -> RouterResult<()> {
+        self.record_finish(endpoint_id, outcome, duration, None, false)
+            .await
     }
 
     /// リクエスト完了を記録（トークン使用量含む）
@@ -2350,15 +5269,38 @@ impl LoadManager {
         duration: StdDuration,
         token_usage: Option<crate::token::TokenUsage>,
     ) -> RouterResult<()> {
-        if self.endpoint_registry.get(endpoint_id).await.is_none() {
+        self.record_finish(endpoint_id, outcome, duration, token_usage, false)
+            .await
+    }
+
+    /// リクエスト完了を記録する
+    ///
+    /// `breaker_probe` は half-open 中の試験リクエストとして割り当てた lease か。
+    /// サーキットブレーカーは試験リクエストの結果でのみ half-open から遷移する。
+    pub(crate) async fn record_finish(
+        &self,
+        endpoint_id: Uuid,
+        outcome: RequestOutcome,
+        duration: StdDuration,
+        token_usage: Option<crate::token::TokenUsage>,
+        breaker_probe: bool,
+    ) -> RouterResult<()> {
+        let Some(endpoint) = self.endpoint_registry.get(endpoint_id).await else {
             return Err(LbError::EndpointNotFound(endpoint_id));
+        };
+        if let Some(usage) = token_usage.as_ref() {
+            self.charge_request_cost(&endpoint, usage);
         }
 
         let mut state = self.state.write().await;
         let entry = state.entry(endpoint_id).or_default();
+        let mut circuit_transition = None;
+        let mut canary_tripped = false;
+        let mut concurrency_change = None;
 
         if let RequestOutcome::Queued = outcome {
         } else {
+            concurrency_change = record_concurrency_sample(entry, outcome, duration);
             if entry.assigned_active > 0 {
                 entry.assigned_active -= 1;
             }
@@ -2368,10 +5310,17 @@ impl LoadManager {
                     entry.success_count = entry.success_count.saturating_add(1)
                 }
                 RequestOutcome::Error => entry.error_count = entry.error_count.saturating_add(1),
-                RequestOutcome::Queued => {}
+                RequestOutcome::Queued | RequestOutcome::Cancelled => {}
+            }
+            circuit_transition = record_breaker_outcome(entry, outcome, breaker_probe);
+            canary_tripped = record_canary_outcome(entry, &endpoint, outcome, circuit_transition);
+
+            // 取り消されたリクエストは成否・レイテンシの統計に含めない
+            if !matches!(outcome, RequestOutcome::Cancelled) {
+                entry.total_latency_ms =
+                    entry.total_latency_ms.saturating_add(duration.as_millis());
+                entry.push_latency(duration);
             }
-
-            entry.total_latency_ms = entry.total_latency_ms.saturating_add(duration.as_millis());
 
             if let Some(ref usage) = token_usage {
                 if let Some(input) = usage.input_tokens {
@@ -2419,6 +5368,19 @@ impl LoadManager {
         if should_notify_idle {
             self.queue_notify.notify_waiters();
         }
+        if let Some(transition) = circuit_transition {
+            self.notify_circuit_transition(endpoint_id, transition);
+        }
+        if canary_tripped {
+            self.trip_canary(&endpoint).await;
+        }
+        if let Some(change) = concurrency_change {
+            log_concurrency_change(endpoint_id, change);
+        }
+        if matches!(outcome, RequestOutcome::Success | RequestOutcome::Error) {
+            crate::health::passive::passive_health()
+                .record(endpoint_id, matches!(outcome, RequestOutcome::Success));
+        }
         self.record_request_history(outcome, Utc::now()).await;
 
         Ok(())
@@ -2433,14 +5395,16 @@ impl LoadManager {
             .ok_or(LbError::EndpointNotFound(endpoint_id))?;
         let state = self.state.read().await;
         let load_state = state.get(&endpoint_id).cloned().unwrap_or_default();
+        let weight_ramp = self.weight_ramps.read().await.get(&endpoint_id).copied();
 
-        Ok(self.build_snapshot_from_endpoint(&endpoint, load_state, Utc::now()))
+        Ok(self.build_snapshot_from_endpoint(&endpoint, load_state, weight_ramp, Utc::now()))
     }
 
     /// すべてのエンドポイントのロードスナップショットを取得
     pub async fn snapshots(&self) -> Vec<EndpointLoadSnapshot> {
         let endpoints = self.endpoint_registry.list().await;
         let state = self.state.read().await;
+        let weight_ramps = self.weight_ramps.read().await;
 
         let now = Utc::now();
 
@@ -2448,8 +5412,61 @@ impl LoadManager {
             .iter()
             .map(|endpoint| {
                 let load_state = state.get(&endpoint.id).cloned().unwrap_or_default();
-                self.build_snapshot_from_endpoint(endpoint, load_state, now)
+                let weight_ramp = weight_ramps.get(&endpoint.id).copied();
+                self.build_snapshot_from_endpoint(endpoint, load_state, weight_ramp, now)
+            })
+            .collect()
+    }
+
+    /// 全エンドポイントの p50 レイテンシの中央値からレイテンシ基準を再計算する
+    ///
+    /// レイテンシ計測済みのエンドポイントが無い場合は基準値を更新せず `None` を返す。
+    pub async fn recalibrate_latency_baseline(&self) -> Option<LatencyBaseline> {
+        let endpoints = self.endpoint_registry.list().await;
+        let latencies: Vec<f64> = {
+            let state = self.state.read().await;
+            endpoints
+                .iter()
+                .filter_map(|ep| state.get(&ep.id)?.percentile_latency_ms(50.0))
+                .map(f64::from)
+                .collect()
+        };
+        let baseline = LatencyBaseline::from_latencies(&latencies, Utc::now())?;
+        *self
+            .latency_baseline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(baseline);
+        Some(baseline)
+    }
+
+    /// 現在のレイテンシ基準（未算出なら `None`）
+    pub fn latency_baseline(&self) -> Option<LatencyBaseline> {
+        *self
+            .latency_baseline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// レイテンシ基準に対して penalty 対象のエンドポイントID
+    ///
+    /// p50 レイテンシが基準値の `LLMLB_LATENCY_PENALTY_FACTOR` 倍を超えるものを返す。
+    /// 基準値が未算出の場合は空。
+    pub async fn latency_penalized_endpoints(&self) -> Vec<Uuid> {
+        let Some(baseline) = self.latency_baseline() else {
+            return Vec::new();
+        };
+        let factor = crate::config::latency_penalty_factor();
+        let endpoints = self.endpoint_registry.list().await;
+        let state = self.state.read().await;
+        endpoints
+            .iter()
+            .filter(|ep| {
+                state
+                    .get(&ep.id)
+                    .and_then(|load| load.percentile_latency_ms(50.0))
+                    .is_some_and(|p50| baseline.is_penalized(f64::from(p50), factor))
             })
+            .map(|ep| ep.id)
             .collect()
     }
 
@@ -2661,6 +5678,7 @@ impl LoadManager {
         &self,
         endpoint: &crate::types::endpoint::Endpoint,
         load_state: EndpointLoadState,
+        weight_ramp: Option<WeightRamp>,
         now: DateTime<Utc>,
     ) -> EndpointLoadSnapshot {
         let cpu_usage = load_state
@@ -2704,6 +5722,20 @@ impl LoadManager {
             .as_ref()
             .and_then(|metrics| metrics.gpu_capability_score);
         let active_requests = load_state.combined_active();
+        let mut response_anomaly_models: Vec<String> = load_state
+            .response_token_stats
+            .iter()
+            .filter(|(_, stats)| stats.degraded)
+            .map(|(model, _)| model.clone())
+            .collect();
+        response_anomaly_models.sort();
+        let ramp_now = Instant::now();
+        let circuit_state = load_state.circuit_state(ramp_now);
+        let effective_weight = effective_weight_at(endpoint, weight_ramp.as_ref(), ramp_now);
+        let weight_ramp_remaining_secs = weight_ramp
+            .filter(|ramp| !ramp.is_finished(ramp_now))
+            .map(|ramp| ramp.remaining_at(ramp_now).as_secs_f64());
+        let dynamic_concurrency = crate::config::dynamic_concurrency_max() > 0;
 
         EndpointLoadSnapshot {
             endpoint_id: endpoint.id,
@@ -2724,11 +5756,28 @@ impl LoadManager {
             successful_requests: load_state.success_count,
             failed_requests: load_state.error_count,
             average_response_time_ms: load_state.effective_average_ms(),
+            p50_latency_ms: load_state.percentile_latency_ms(50.0),
+            p95_latency_ms: load_state.percentile_latency_ms(95.0),
+            ttft_ema_ms: load_state.ttft_ema_ms.map(|ms| ms as f32),
             last_updated: load_state.last_updated(),
             is_stale: load_state.is_stale(now),
             total_input_tokens: load_state.total_input_tokens,
             total_output_tokens: load_state.total_output_tokens,
             total_tokens: load_state.total_tokens,
+            weight: endpoint.weight,
+            effective_weight,
+            weight_ramp_remaining_secs,
+            response_anomaly_models,
+            circuit_state,
+            adaptive_rate_limit_rps: load_state.adaptive_rate.allowed_rps(),
+            concurrency_limit: dynamic_concurrency.then(|| load_state.concurrency_limit.limit()),
+            concurrency_rtt_ms: dynamic_concurrency
+                .then(|| load_state.concurrency_limit.short_rtt_ms())
+                .flatten(),
+            concurrency_baseline_rtt_ms: dynamic_concurrency
+                .then(|| load_state.concurrency_limit.long_rtt_ms())
+                .flatten(),
+            latency_outliers: load_state.latency_outliers,
         }
     }
 
@@ -2736,15 +5785,49 @@ impl LoadManager {
         &self,
         model_id: Option<&str>,
     ) -> RouterResult<Vec<crate::types::endpoint::Endpoint>> {
-        if let Some(model_id) = model_id {
+        let endpoints = if let Some(model_id) = model_id {
             let endpoints = self.endpoint_registry.find_by_model(model_id).await;
             if endpoints.is_empty() {
                 return Err(LbError::NoCapableEndpoints(model_id.to_string()));
             }
-            return Ok(endpoints);
-        }
+            endpoints
+        } else {
+            let endpoints = self.endpoint_registry.list_online().await;
+            if endpoints.is_empty() {
+                return Err(LbError::NoEndpointsAvailable);
+            }
+            endpoints
+        };
 
-        let endpoints = self.endpoint_registry.list_online().await;
+        // 月次予算に達したエンドポイントは当月中は候補から除外する
+        // drain / disable / maintenance 中、サーキットブレーカーが open、
+        // アダプティブレートリミッタで送信枠の無いエンドポイントも除外する（他ノードへ回す）
+        let operational_states = self.operational_states.read().await;
+        let state = self.state.read().await;
+        let now = Instant::now();
+        let endpoints: Vec<_> = endpoints
+            .into_iter()
+            .filter(|ep| !self.is_over_budget(ep) && !operational_states.contains_key(&ep.id))
+            .filter(|ep| {
+                state
+                    .get(&ep.id)
+                    .map(|load| load.accepts_routing(now) && load.adaptive_rate.has_capacity(now))
+                    .unwrap_or(true)
+            })
+            .collect();
+        // 動的同時実行上限に達したエンドポイントは、余裕のある候補があれば外す
+        let endpoints = if crate::config::dynamic_concurrency_max() > 0 {
+            concurrency_limit::filter_by_capacity(endpoints, |ep| {
+                state
+                    .get(&ep.id)
+                    .map(|load| load.concurrency_limit.has_capacity(load.combined_active()))
+                    .unwrap_or(true)
+            })
+        } else {
+            endpoints
+        };
+        drop(state);
+        drop(operational_states);
         if endpoints.is_empty() {
             return Err(LbError::NoEndpointsAvailable);
         }
@@ -2752,6 +5835,36 @@ impl LoadManager {
         Ok(endpoints)
     }
 
+    /// 指定モデルを指定APIで提供するエンドポイントのみに絞り込む
+    ///
+    /// モデルを持つエンドポイントがあっても該当APIに対応するものが無い場合は
+    /// `ServiceUnavailable` を返す。
+    async fn filter_by_supported_api(
+        &self,
+        endpoints: Vec<crate::types::endpoint::Endpoint>,
+        model_id: &str,
+        api: SupportedAPI,
+    ) -> RouterResult<Vec<crate::types::endpoint::Endpoint>> {
+        let mut supported = Vec::with_capacity(endpoints.len());
+        for endpoint in endpoints {
+            let supports = self
+                .endpoint_registry
+                .supports_api(endpoint.id, model_id, api)
+                .await
+                .map_err(|e| LbError::Database(format!("Failed to load endpoint models: {}", e)))?;
+            if supports {
+                supported.push(endpoint);
+            }
+        }
+        if supported.is_empty() {
+            return Err(LbError::ServiceUnavailable(format!(
+                "No available endpoints support the {} API for model: {}",
+                api, model_id
+            )));
+        }
+        Ok(supported)
+    }
+
     /// エンドポイントを直接選択（ラウンドロビン）
     pub async fn select_endpoint_direct(&self) -> RouterResult<crate::types::endpoint::Endpoint> {
         let endpoints = self.collect_online_endpoints(None).await?;
@@ -2767,7 +5880,7 @@ impl LoadManager {
         api_kind: Option<TpsApiKind>,
     ) -> RouterResult<crate::types::endpoint::Endpoint> {
         let endpoints = self.collect_online_endpoints(None).await?;
-        self.select_endpoint_by_tps_from_endpoints(endpoints, None, api_kind)
+        self.select_endpoint_by_tps_from_endpoints(endpoints, None, api_kind, None)
             .await
     }
 
@@ -2787,10 +5900,19 @@ impl LoadManager {
         &self,
         model_id: &str,
         api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
     ) -> RouterResult<crate::types::endpoint::Endpoint> {
         let endpoints = self.collect_online_endpoints(Some(model_id)).await?;
-        self.select_endpoint_by_tps_from_endpoints(endpoints, Some(model_id), api_kind)
-            .await
+        let endpoints = self
+            .apply_routing_policies(endpoints, model_id, api_kind, ctx, true)
+            .await?;
+        self.select_endpoint_by_tps_from_endpoints(
+            endpoints,
+            Some(model_id),
+            api_kind,
+            ctx.principal.as_ref(),
+        )
+        .await
     }
 
     /// アイドルエンドポイントを選択
@@ -2802,13 +5924,20 @@ impl LoadManager {
             return Err(LbError::NoEndpointsAvailable);
         }
 
+        let operational_states = self.operational_states.read().await;
         let state = self.state.read().await;
+        let now = Instant::now();
         let non_initializing: Vec<_> = endpoints
             .iter()
+            .filter(|ep| !operational_states.contains_key(&ep.id))
             .filter(|ep| {
                 state
                     .get(&ep.id)
-                    .map(|load| !load.initializing)
+                    .map(|load| {
+                        !load.initializing
+                            && load.accepts_routing(now)
+                            && load.adaptive_rate.has_capacity(now)
+                    })
                     .unwrap_or(true)
             })
             .cloned()
@@ -2943,6 +6072,352 @@ impl LoadManager {
         self.select_endpoint_round_robin_from_endpoints(ready_endpoints)
     }
 
+    /// ロードバランサーモードに応じて指定モデルのエンドポイントを選択する。
+    ///
+    /// `X-LLMLB-Optimize: ttft` 指定のリクエストはTTFT優先で選択し、TTFT計測済みの
+    /// 候補が無い場合のみモードに従う。
+    pub async fn select_endpoint_by_mode(
+        &self,
+        mode: crate::config::LoadBalancerMode,
+        model_id: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+    ) -> RouterResult<crate::types::endpoint::Endpoint> {
+        use crate::config::LoadBalancerMode;
+        if ctx.optimize_target == Some(optimize::OptimizeTarget::Ttft) {
+            if let Some(endpoint) = self
+                .select_endpoint_by_ttft_for_model(model_id, api_kind, ctx)
+                .await?
+            {
+                return Ok(endpoint);
+            }
+        }
+        match mode {
+            LoadBalancerMode::Weighted => {
+                self.select_endpoint_weighted_for_model(model_id, api_kind, ctx)
+                    .await
+            }
+            LoadBalancerMode::LeastConn => {
+                self.select_endpoint_least_connections_for_model(model_id, api_kind, ctx)
+                    .await
+            }
+            LoadBalancerMode::Auto => {
+                self.select_endpoint_by_tps_ready_for_model(model_id, api_kind, ctx)
+                    .await
+            }
+        }
+    }
+
+    /// 指定モデルに対応する初期化完了エンドポイントのうち、TTFT EMAが最小のものを選択する。
+    ///
+    /// TTFT未計測のエンドポイントは候補外とし、同値の場合は処理中リクエスト数が
+    /// 少ない方を優先する。TTFT計測済みの候補が無い場合は `None` を返す。
+    pub async fn select_endpoint_by_ttft_for_model(
+        &self,
+        model_id: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+    ) -> RouterResult<Option<crate::types::endpoint::Endpoint>> {
+        let endpoints = self.collect_online_endpoints(Some(model_id)).await?;
+        let endpoints = self
+            .apply_routing_policies(endpoints, model_id, api_kind, ctx, true)
+            .await?;
+        let endpoints = self
+            .filter_by_reservations(endpoints, ctx.principal.as_ref())
+            .await;
+
+        let state = self.state.read().await;
+        let best = endpoints
+            .into_iter()
+            .filter_map(|ep| {
+                let load = state.get(&ep.id)?;
+                if load.initializing {
+                    return None;
+                }
+                let ttft = load.ttft_ema_ms?;
+                Some((ep, ttft, load.combined_active()))
+            })
+            .min_by(|(_, a_ttft, a_active), (_, b_ttft, b_active)| {
+                a_ttft
+                    .total_cmp(b_ttft)
+                    .then_with(|| a_active.cmp(b_active))
+            })
+            .map(|(ep, _, _)| ep);
+        Ok(best)
+    }
+
+    /// セッションIDに割り当て済みのエンドポイントを優先して選択する（sticky session）。
+    ///
+    /// 割り当て先がオンラインかつ初期化完了で、指定モデルを提供していれば再利用して
+    /// 割り当ての期限を延長する。そうでなければ割り当て先の優先フェイルオーバー先、
+    /// それも選択不可なら `mode` に従って選択し、選ばれたエンドポイントへ再バインドする。
+    pub async fn select_endpoint_sticky(
+        &self,
+        mode: crate::config::LoadBalancerMode,
+        model_id: &str,
+        session_id: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+    ) -> RouterResult<crate::types::endpoint::Endpoint> {
+        let bound = self
+            .session_bindings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(session_id, Instant::now());
+
+        if let Some(endpoint_id) = bound {
+            if let Some(endpoint) = self
+                .sticky_candidate(endpoint_id, model_id, api_kind, ctx)
+                .await
+            {
+                self.bind_session(session_id, endpoint.id);
+                return Ok(endpoint);
+            }
+            tracing::debug!(
+                session_id,
+                endpoint_id = %endpoint_id,
+                model = %model_id,
+                "Session-bound endpoint unavailable; rebinding"
+            );
+            if let Some(endpoint) = self
+                .failover_candidate(endpoint_id, model_id, api_kind, ctx, &[])
+                .await
+            {
+                self.bind_session(session_id, endpoint.id);
+                return Ok(endpoint);
+            }
+        }
+
+        let endpoint = self
+            .select_endpoint_by_mode(mode, model_id, api_kind, ctx)
+            .await?;
+        self.bind_session(session_id, endpoint.id);
+        Ok(endpoint)
+    }
+
+    /// 指定モデルを指定APIで提供するエンドポイントのみから選択する。
+    ///
+    /// `/v1/embeddings` など対応エンドポイントが限られるAPI向け。候補を `api` 対応の
+    /// エンドポイントに絞った上で、セッションID付きなら sticky session、それ以外は
+    /// `mode` に従って選択する。該当APIに対応するものが無い場合は `ServiceUnavailable` を返す。
+    pub async fn select_endpoint_for_api(
+        &self,
+        mode: crate::config::LoadBalancerMode,
+        model_id: &str,
+        api: SupportedAPI,
+        ctx: &SelectionContext,
+    ) -> RouterResult<crate::types::endpoint::Endpoint> {
+        let ctx = SelectionContext {
+            required_api: Some(api),
+            ..ctx.clone()
+        };
+        match ctx.session_id.as_deref() {
+            Some(session_id) => {
+                self.select_endpoint_sticky(mode, model_id, session_id, None, &ctx)
+                    .await
+            }
+            None => {
+                self.select_endpoint_by_mode(mode, model_id, None, &ctx)
+                    .await
+            }
+        }
+    }
+
+    /// 割り当て済みエンドポイントが現在も選択可能なら返す
+    async fn sticky_candidate(
+        &self,
+        endpoint_id: Uuid,
+        model_id: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+    ) -> Option<crate::types::endpoint::Endpoint> {
+        let endpoints = self.collect_online_endpoints(Some(model_id)).await.ok()?;
+        // 固定済み・指定済みのエンドポイントにはカナリアの振り分けを適用しない
+        let endpoints = self
+            .apply_routing_policies(endpoints, model_id, api_kind, ctx, false)
+            .await
+            .ok()?;
+        let endpoint = endpoints.into_iter().find(|ep| ep.id == endpoint_id)?;
+        let initializing = self
+            .state
+            .read()
+            .await
+            .get(&endpoint_id)
+            .is_some_and(|load| load.initializing);
+        if initializing {
+            return None;
+        }
+        self.filter_by_reservations(vec![endpoint], ctx.principal.as_ref())
+            .await
+            .pop()
+    }
+
+    /// 選択不可になったエンドポイントの優先フェイルオーバー先を順に辿り、
+    /// 最初に選択可能なものを返す（`excluded` に含まれるものは飛ばす）
+    async fn failover_candidate(
+        &self,
+        endpoint_id: Uuid,
+        model_id: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+        excluded: &[Uuid],
+    ) -> Option<crate::types::endpoint::Endpoint> {
+        let mut visited = HashSet::from([endpoint_id]);
+        let mut next = self.endpoint_registry.get(endpoint_id).await?.failover_to;
+        while let Some(id) = next {
+            // 登録時に循環は拒否しているが、念のため同じエンドポイントは二度辿らない
+            if !visited.insert(id) {
+                break;
+            }
+            if !excluded.contains(&id) {
+                if let Some(endpoint) = self.sticky_candidate(id, model_id, api_kind, ctx).await {
+                    tracing::debug!(
+                        from_endpoint_id = %endpoint_id,
+                        endpoint_id = %id,
+                        model = %model_id,
+                        "Routing to failover endpoint"
+                    );
+                    return Some(endpoint);
+                }
+            }
+            next = self
+                .endpoint_registry
+                .get(id)
+                .await
+                .and_then(|ep| ep.failover_to);
+        }
+        None
+    }
+
+    fn bind_session(&self, session_id: &str, endpoint_id: Uuid) {
+        self.session_bindings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .bind(session_id, endpoint_id, Instant::now());
+    }
+
+    /// セッション割り当てのTTL
+    pub fn session_affinity_ttl(&self) -> StdDuration {
+        self.session_bindings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .ttl()
+    }
+
+    /// 期限切れのセッション割り当てを削除し、削除件数を返す
+    pub fn purge_expired_sessions(&self) -> usize {
+        self.session_bindings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .purge_expired(Instant::now())
+    }
+
+    /// 指定モデルに対応する初期化完了エンドポイントのうち、処理中リクエスト数
+    /// （`combined_active`）が最小のものを選択する（least-connections）。
+    ///
+    /// 処理中リクエスト数が同数の場合は平均レイテンシが低い方を優先する（未計測は0扱い）。
+    /// 全エンドポイントの処理中リクエスト数が0の場合はラウンドロビンで選択する。
+    pub async fn select_endpoint_least_connections_for_model(
+        &self,
+        model_id: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+    ) -> RouterResult<crate::types::endpoint::Endpoint> {
+        let endpoints = self.collect_online_endpoints(Some(model_id)).await?;
+        let endpoints = self
+            .apply_routing_policies(endpoints, model_id, api_kind, ctx, true)
+            .await?;
+        let endpoints = self
+            .filter_by_reservations(endpoints, ctx.principal.as_ref())
+            .await;
+
+        let candidates: Vec<_> = {
+            let state = self.state.read().await;
+            endpoints
+                .into_iter()
+                .filter_map(|ep| {
+                    let load = state.get(&ep.id);
+                    if load.is_some_and(|load| load.initializing) {
+                        return None;
+                    }
+                    let active = load.map(|load| load.combined_active()).unwrap_or(0);
+                    let latency = load
+                        .and_then(|load| load.effective_average_ms())
+                        .unwrap_or(0.0);
+                    Some((ep, active, latency))
+                })
+                .collect()
+        };
+
+        if candidates.iter().all(|(_, active, _)| *active == 0) {
+            return self.select_endpoint_round_robin_from_endpoints(
+                candidates.into_iter().map(|(ep, _, _)| ep).collect(),
+            );
+        }
+
+        candidates
+            .into_iter()
+            .min_by(|(_, a_active, a_latency), (_, b_active, b_latency)| {
+                a_active
+                    .cmp(b_active)
+                    .then_with(|| a_latency.total_cmp(b_latency))
+            })
+            .map(|(ep, _, _)| ep)
+            .ok_or(LbError::NoEndpointsAvailable)
+    }
+
+    /// 指定モデルに対応する初期化完了エンドポイントを重みに比例した確率で選択する。
+    ///
+    /// 重みはramp中であれば実効重みを用いる。重み0のエンドポイントは
+    /// メンテナンス扱いとして選択対象外にする。
+    pub async fn select_endpoint_weighted_for_model(
+        &self,
+        model_id: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+    ) -> RouterResult<crate::types::endpoint::Endpoint> {
+        let endpoints = self.collect_online_endpoints(Some(model_id)).await?;
+        let endpoints = self
+            .apply_routing_policies(endpoints, model_id, api_kind, ctx, true)
+            .await?;
+        let endpoints = self
+            .filter_by_reservations(endpoints, ctx.principal.as_ref())
+            .await;
+
+        let candidates: Vec<_> = {
+            let state = self.state.read().await;
+            let ramps = self.weight_ramps.read().await;
+            let now = Instant::now();
+            endpoints
+                .into_iter()
+                .filter(|ep| {
+                    state
+                        .get(&ep.id)
+                        .map(|load| !load.initializing)
+                        .unwrap_or(true)
+                })
+                .map(|ep| {
+                    let weight = effective_weight_at(&ep, ramps.get(&ep.id), now);
+                    (ep, weight)
+                })
+                .filter(|(_, weight)| *weight > 0.0)
+                .collect()
+        };
+
+        let total: f64 = candidates.iter().map(|(_, weight)| weight).sum();
+        if candidates.is_empty() || total <= 0.0 {
+            return Err(LbError::NoEndpointsAvailable);
+        }
+
+        let point = {
+            use rand::RngExt;
+            rand::rng().random_range(0.0..total)
+        };
+        pick_weighted(&candidates, point)
+            .cloned()
+            .ok_or(LbError::NoEndpointsAvailable)
+    }
+
     /// 指定モデルに対応する初期化完了エンドポイントをTPS優先で選択する。
     ///
     /// 実装上は初期化中除外を共通処理で行うため、TPS優先選択の標準経路として使う。
@@ -2950,11 +6425,86 @@ impl LoadManager {
         &self,
         model_id: &str,
         api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
     ) -> RouterResult<crate::types::endpoint::Endpoint> {
         let endpoints = self.collect_online_endpoints(Some(model_id)).await?;
-        self.select_endpoint_by_tps_from_endpoints(endpoints, Some(model_id), api_kind)
+        let endpoints = self
+            .apply_routing_policies(endpoints, model_id, api_kind, ctx, true)
+            .await?;
+        self.select_endpoint_by_tps_from_endpoints(
+            endpoints,
+            Some(model_id),
+            api_kind,
+            ctx.principal.as_ref(),
+        )
+        .await
+    }
+
+    /// 指定エンドポイントを除外して、ロードバランサーモードに従って選択する。
+    ///
+    /// 再接続・フェイルオーバーなど、既に失敗したエンドポイントを避けて別エンドポイントで
+    /// やり直す経路で使用する。最後に失敗したエンドポイント（`excluded` の末尾）に
+    /// 優先フェイルオーバー先があれば、そちらを先に試す。
+    pub async fn select_endpoint_by_mode_excluding(
+        &self,
+        mode: crate::config::LoadBalancerMode,
+        model_id: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+        excluded: &[Uuid],
+    ) -> RouterResult<crate::types::endpoint::Endpoint> {
+        if let Some(&failed) = excluded.last() {
+            if let Some(endpoint) = self
+                .failover_candidate(failed, model_id, api_kind, ctx, excluded)
+                .await
+            {
+                return Ok(endpoint);
+            }
+        }
+        let mut ctx = ctx.clone();
+        ctx.excluded_endpoints.extend_from_slice(excluded);
+        self.select_endpoint_by_mode(mode, model_id, api_kind, &ctx)
             .await
     }
+
+    /// 指定エンドポイントを除外して、モデル対応エンドポイントをTPS優先で選択する。
+    ///
+    /// ストリーミング再接続など、既に失敗したエンドポイントを避けて
+    /// 別エンドポイントでやり直す経路で使用する。最後に失敗したエンドポイント
+    /// （`excluded` の末尾）に優先フェイルオーバー先があれば、そちらを先に試す。
+    pub async fn select_endpoint_by_tps_ready_for_model_excluding(
+        &self,
+        model_id: &str,
+        api_kind: Option<TpsApiKind>,
+        ctx: &SelectionContext,
+        excluded: &[Uuid],
+    ) -> RouterResult<crate::types::endpoint::Endpoint> {
+        if let Some(&failed) = excluded.last() {
+            if let Some(endpoint) = self
+                .failover_candidate(failed, model_id, api_kind, ctx, excluded)
+                .await
+            {
+                return Ok(endpoint);
+            }
+        }
+        let endpoints: Vec<_> = self
+            .collect_online_endpoints(Some(model_id))
+            .await?
+            .into_iter()
+            .filter(|ep| !excluded.contains(&ep.id))
+            .collect();
+        let endpoints = self
+            .apply_routing_policies(endpoints, model_id, api_kind, ctx, true)
+            .await?;
+        self.select_endpoint_by_tps_from_endpoints(
+            endpoints,
+            Some(model_id),
+            api_kind,
+            ctx.principal.as_ref(),
+        )
+        .await
+    }
+
     fn select_endpoint_round_robin_from_endpoints(
         &self,
         endpoints: Vec<crate::types::endpoint::Endpoint>,
@@ -2999,7 +6549,7 @@ fn increment_history(point: &mut RequestHistoryPoint, outcome: RequestOutcome) {
     match outcome {
         RequestOutcome::Success => point.success = point.success.saturating_add(1),
         RequestOutcome::Error => point.error = point.error.saturating_add(1),
-        RequestOutcome::Queued => {}
+        RequestOutcome::Queued | RequestOutcome::Cancelled => {}
     }
 }
 
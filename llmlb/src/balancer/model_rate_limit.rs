@@ -0,0 +1,359 @@
+//! モデル単位のレートリミット（RPM/TPM）
+//!
+//! 高コストモデルへのアクセスをモデル単位で制限する。直近1分間のリクエスト数と
+//! トークン消費量をスライディングウィンドウで保持し、上限に達したモデルへの
+//! 新規リクエストを拒否する。
+//!
+//! トークン消費量はレスポンス完了後にしか確定しないため、TPMは「直近1分の消費量が
+//! 上限に達していれば新規受付を止める」形で適用する。APIキー単位の制限など
+//! 他の制限とは独立に判定されるため、併用時はより厳しい方で拒否される。
+//!
+//! 上限設定はDBに永続化され、起動時と変更時にプロセス全体のリミッタ
+//! （[`model_rate_limiter`]）へ反映される。
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// RPM/TPMの集計ウィンドウ
+pub const MODEL_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// 空ウィンドウの掃除を行う追跡モデル数の閾値
+const WINDOW_SWEEP_THRESHOLD: usize = 1024;
+
+/// プロセス全体のモデル別レートリミッタ
+static MODEL_RATE_LIMITER: Lazy<ModelRateLimiter> = Lazy::new(ModelRateLimiter::default);
+
+/// プロセス全体のモデル別レートリミッタを取得
+pub fn model_rate_limiter() -> &'static ModelRateLimiter {
+    &MODEL_RATE_LIMITER
+}
+
+/// モデル別のRPM/TPM上限
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelRateLimit {
+    /// 対象モデルID（リクエストの `model` と完全一致で照合）
+    pub model_id: String,
+    /// 1分あたりのリクエスト数上限（`None` は無制限）
+    pub requests_per_minute: Option<u32>,
+    /// 1分あたりのトークン数上限（`None` は無制限）
+    pub tokens_per_minute: Option<u64>,
+    /// 更新日時
+    pub updated_at: DateTime<Utc>,
+}
+
+/// モデル別の現在消費量
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelUsage {
+    /// 対象モデルID
+    pub model_id: String,
+    /// 直近1分間に受け付けたリクエスト数
+    pub requests_last_minute: u32,
+    /// 直近1分間に消費したトークン数
+    pub tokens_last_minute: u64,
+    /// 1分あたりのリクエスト数上限
+    pub requests_per_minute: Option<u32>,
+    /// 1分あたりのトークン数上限
+    pub tokens_per_minute: Option<u64>,
+}
+
+/// 上限超過の内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelRateLimitExceeded {
+    /// RPM上限に到達
+    Requests {
+        /// 上限値
+        limit: u32,
+        /// 枠が空くまでの目安
+        retry_after: Duration,
+    },
+    /// TPM上限に到達
+    Tokens {
+        /// 上限値
+        limit: u64,
+        /// 枠が空くまでの目安
+        retry_after: Duration,
+    },
+}
+
+impl ModelRateLimitExceeded {
+    /// 枠が空くまでの目安
+    pub fn retry_after(&self) -> Duration {
+        match self {
+            Self::Requests { retry_after, .. } | Self::Tokens { retry_after, .. } => *retry_after,
+        }
+    }
+
+    /// 超過した上限の種別（`rpm` / `tpm`）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Requests { .. } => "rpm",
+            Self::Tokens { .. } => "tpm",
+        }
+    }
+}
+
+impl std::fmt::Display for ModelRateLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Requests { limit, .. } => {
+                write!(
+                    f,
+                    "Model rate limit exceeded: {} requests per minute",
+                    limit
+                )
+            }
+            Self::Tokens { limit, .. } => {
+                write!(f, "Model rate limit exceeded: {} tokens per minute", limit)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct UsageWindow {
+    requests: VecDeque<Instant>,
+    tokens: VecDeque<(Instant, u64)>,
+}
+
+impl UsageWindow {
+    fn prune(&mut self, now: Instant) {
+        while self
+            .requests
+            .front()
+            .is_some_and(|ts| now.saturating_duration_since(*ts) >= MODEL_RATE_WINDOW)
+        {
+            self.requests.pop_front();
+        }
+        while self
+            .tokens
+            .front()
+            .is_some_and(|(ts, _)| now.saturating_duration_since(*ts) >= MODEL_RATE_WINDOW)
+        {
+            self.tokens.pop_front();
+        }
+    }
+
+    fn tokens_total(&self) -> u64 {
+        self.tokens
+            .iter()
+            .fold(0u64, |acc, (_, tokens)| acc.saturating_add(*tokens))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.requests.is_empty() && self.tokens.is_empty()
+    }
+
+    fn retry_after(oldest: Option<Instant>, now: Instant) -> Duration {
+        oldest
+            .map(|ts| MODEL_RATE_WINDOW.saturating_sub(now.saturating_duration_since(ts)))
+            .unwrap_or(MODEL_RATE_WINDOW)
+    }
+}
+
+/// モデル別のRPM/TPMリミッタ
+#[derive(Debug, Default)]
+pub struct ModelRateLimiter {
+    limits: RwLock<HashMap<String, ModelRateLimit>>,
+    windows: Mutex<HashMap<String, UsageWindow>>,
+}
+
+impl ModelRateLimiter {
+    /// 上限設定を一括で置き換える
+    pub fn set_limits(&self, limits: Vec<ModelRateLimit>) {
+        let mut current = self.limits.write().unwrap_or_else(|e| e.into_inner());
+        *current = limits
+            .into_iter()
+            .map(|limit| (limit.model_id.clone(), limit))
+            .collect();
+    }
+
+    /// モデルの上限設定を追加・更新する
+    pub fn set_limit(&self, limit: ModelRateLimit) {
+        self.limits
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(limit.model_id.clone(), limit);
+    }
+
+    /// モデルの上限設定を削除する
+    pub fn remove_limit(&self, model_id: &str) {
+        self.limits
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(model_id);
+    }
+
+    /// モデルの上限設定を取得
+    pub fn limit(&self, model_id: &str) -> Option<ModelRateLimit> {
+        self.limits
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(model_id)
+            .cloned()
+    }
+
+    /// リクエストの受付を試みる。受け付けた場合はRPMの消費として記録する
+    pub fn try_acquire(&self, model_id: &str) -> Result<(), ModelRateLimitExceeded> {
+        self.try_acquire_at(model_id, Instant::now())
+    }
+
+    pub(crate) fn try_acquire_at(
+        &self,
+        model_id: &str,
+        now: Instant,
+    ) -> Result<(), ModelRateLimitExceeded> {
+        let limit = self.limit(model_id);
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        if windows.len() > WINDOW_SWEEP_THRESHOLD {
+            windows.retain(|_, window| {
+                window.prune(now);
+                !window.is_empty()
+            });
+        }
+        let window = windows.entry(model_id.to_string()).or_default();
+        window.prune(now);
+
+        if let Some(limit) = limit.as_ref() {
+            if let Some(rpm) = limit.requests_per_minute {
+                if window.requests.len() >= rpm as usize {
+                    return Err(ModelRateLimitExceeded::Requests {
+                        limit: rpm,
+                        retry_after: UsageWindow::retry_after(
+                            window.requests.front().copied(),
+                            now,
+                        ),
+                    });
+                }
+            }
+            if let Some(tpm) = limit.tokens_per_minute {
+                if window.tokens_total() >= tpm {
+                    return Err(ModelRateLimitExceeded::Tokens {
+                        limit: tpm,
+                        retry_after: UsageWindow::retry_after(
+                            window.tokens.front().map(|(ts, _)| *ts),
+                            now,
+                        ),
+                    });
+                }
+            }
+        }
+
+        window.requests.push_back(now);
+        Ok(())
+    }
+
+    /// 確定したトークン消費量を記録する
+    pub fn record_tokens(&self, model_id: &str, tokens: u64) {
+        self.record_tokens_at(model_id, tokens, Instant::now());
+    }
+
+    pub(crate) fn record_tokens_at(&self, model_id: &str, tokens: u64, now: Instant) {
+        if tokens == 0 {
+            return;
+        }
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let window = windows.entry(model_id.to_string()).or_default();
+        window.prune(now);
+        window.tokens.push_back((now, tokens));
+    }
+
+    /// モデルの現在消費量を取得
+    pub fn usage(&self, model_id: &str) -> ModelUsage {
+        self.usage_at(model_id, Instant::now())
+    }
+
+    pub(crate) fn usage_at(&self, model_id: &str, now: Instant) -> ModelUsage {
+        let limit = self.limit(model_id);
+        let (requests_last_minute, tokens_last_minute) = {
+            let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+            match windows.get_mut(model_id) {
+                Some(window) => {
+                    window.prune(now);
+                    (window.requests.len() as u32, window.tokens_total())
+                }
+                None => (0, 0),
+            }
+        };
+
+        ModelUsage {
+            model_id: model_id.to_string(),
+            requests_last_minute,
+            tokens_last_minute,
+            requests_per_minute: limit.as_ref().and_then(|l| l.requests_per_minute),
+            tokens_per_minute: limit.as_ref().and_then(|l| l.tokens_per_minute),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(model_id: &str, rpm: Option<u32>, tpm: Option<u64>) -> ModelRateLimit {
+        ModelRateLimit {
+            model_id: model_id.to_string(),
+            requests_per_minute: rpm,
+            tokens_per_minute: tpm,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn requests_per_minute_rejects_until_window_slides() {
+        let limiter = ModelRateLimiter::default();
+        limiter.set_limits(vec![limit("gpt-big", Some(2), None)]);
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire_at("gpt-big", start).is_ok());
+        assert!(limiter
+            .try_acquire_at("gpt-big", start + Duration::from_secs(10))
+            .is_ok());
+        let err = limiter
+            .try_acquire_at("gpt-big", start + Duration::from_secs(20))
+            .unwrap_err();
+        assert_eq!(err.kind(), "rpm");
+        assert_eq!(err.retry_after(), Duration::from_secs(40));
+
+        // 他モデルには影響しない
+        assert!(limiter.try_acquire_at("gpt-small", start).is_ok());
+
+        // 最古のリクエストがウィンドウから外れると再び受け付ける
+        assert!(limiter
+            .try_acquire_at("gpt-big", start + Duration::from_secs(60))
+            .is_ok());
+        let usage = limiter.usage_at("gpt-big", start + Duration::from_secs(60));
+        assert_eq!(usage.requests_last_minute, 2);
+        assert_eq!(usage.requests_per_minute, Some(2));
+    }
+
+    #[test]
+    fn tokens_per_minute_rejects_after_consumption_reaches_limit() {
+        let limiter = ModelRateLimiter::default();
+        limiter.set_limit(limit("gpt-big", None, Some(1000)));
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire_at("gpt-big", start).is_ok());
+        limiter.record_tokens_at("gpt-big", 600, start);
+        assert!(limiter
+            .try_acquire_at("gpt-big", start + Duration::from_secs(1))
+            .is_ok());
+        limiter.record_tokens_at("gpt-big", 400, start + Duration::from_secs(2));
+
+        let err = limiter
+            .try_acquire_at("gpt-big", start + Duration::from_secs(3))
+            .unwrap_err();
+        assert_eq!(err.kind(), "tpm");
+        let usage = limiter.usage_at("gpt-big", start + Duration::from_secs(3));
+        assert_eq!(usage.tokens_last_minute, 1000);
+        assert_eq!(usage.requests_last_minute, 2);
+
+        limiter.remove_limit("gpt-big");
+        assert!(limiter
+            .try_acquire_at("gpt-big", start + Duration::from_secs(4))
+            .is_ok());
+    }
+}
@@ -0,0 +1,61 @@
+//! リクエスト単位の選択最適化指定
+//!
+//! リクエストヘッダ `X-LLMLB-Optimize` でエンドポイント選択の優先指標を切り替える。
+//! 現在は `ttft`（最初のトークンまでの時間のEMAが小さいエンドポイントを優先）のみ対応し、
+//! 未知の値は無視して通常の選択を行う。
+
+use axum::http::HeaderMap;
+
+/// 最適化指標を指定するリクエストヘッダ
+pub const OPTIMIZE_HEADER: &str = "x-llmlb-optimize";
+
+/// エンドポイント選択で優先する指標
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptimizeTarget {
+    /// 最初のトークンまでの時間（TTFT）
+    Ttft,
+}
+
+impl OptimizeTarget {
+    /// ヘッダ値から解釈する（大文字小文字・前後空白は無視）
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ttft" => Some(Self::Ttft),
+            _ => None,
+        }
+    }
+}
+
+/// `X-LLMLB-Optimize` ヘッダの最適化指定（未指定・未知の値は `None`）
+pub fn optimize_target_from_headers(headers: &HeaderMap) -> Option<OptimizeTarget> {
+    headers
+        .get(OPTIMIZE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(OptimizeTarget::parse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_ttft_case_insensitively() {
+        assert_eq!(OptimizeTarget::parse("ttft"), Some(OptimizeTarget::Ttft));
+        assert_eq!(OptimizeTarget::parse(" TTFT "), Some(OptimizeTarget::Ttft));
+        assert_eq!(OptimizeTarget::parse("throughput"), None);
+        assert_eq!(OptimizeTarget::parse(""), None);
+    }
+
+    #[test]
+    fn optimize_target_from_headers_ignores_unknown_values() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(optimize_target_from_headers(&headers), None);
+        headers.insert(OPTIMIZE_HEADER, "ttft".parse().unwrap());
+        assert_eq!(
+            optimize_target_from_headers(&headers),
+            Some(OptimizeTarget::Ttft)
+        );
+        headers.insert(OPTIMIZE_HEADER, "throughput".parse().unwrap());
+        assert_eq!(optimize_target_from_headers(&headers), None);
+    }
+}
@@ -0,0 +1,103 @@
+//! リクエスト優先度の継承
+//!
+//! 受信リクエストの `X-LLMLB-Priority` ヘッダ（`low` / `normal` / `high`）を
+//! アップストリームへの送信ヘッダにも付与する。
+//! 上流が別の llmlb の場合も同じヘッダで優先度が引き継がれる。
+//! ヘッダの無い（または不正な）受信リクエストは `normal`、受信リクエストに
+//! 紐付かない内部リクエスト（warmup・シャドウ等）は `low` 固定とする。
+
+use axum::http::HeaderMap;
+
+/// 優先度を指定するリクエストヘッダ
+pub const PRIORITY_HEADER: &str = "x-llmlb-priority";
+
+/// リクエストの優先度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RequestPriority {
+    /// 低（内部生成リクエスト）
+    #[default]
+    Low,
+    /// 通常
+    Normal,
+    /// 高
+    High,
+}
+
+impl RequestPriority {
+    /// ヘッダ値から解釈する（大文字小文字・前後空白は無視）
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "normal" => Some(Self::Normal),
+            "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+
+    /// ヘッダ値としての文字列表現
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        }
+    }
+}
+
+/// 受信リクエストの `X-LLMLB-Priority` ヘッダの優先度（未指定・不正な値は `Normal`）
+pub fn priority_from_headers(headers: &HeaderMap) -> RequestPriority {
+    headers
+        .get(PRIORITY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(RequestPriority::parse)
+        .unwrap_or(RequestPriority::Normal)
+}
+
+/// アップストリーム送信リクエストに優先度ヘッダを付与する
+pub fn apply_priority_header(
+    builder: reqwest::RequestBuilder,
+    priority: RequestPriority,
+) -> reqwest::RequestBuilder {
+    builder.header(PRIORITY_HEADER, priority.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_levels_case_insensitively() {
+        assert_eq!(RequestPriority::parse("high"), Some(RequestPriority::High));
+        assert_eq!(RequestPriority::parse(" LOW "), Some(RequestPriority::Low));
+        assert_eq!(
+            RequestPriority::parse("Normal"),
+            Some(RequestPriority::Normal)
+        );
+        assert_eq!(RequestPriority::parse("urgent"), None);
+        assert_eq!(RequestPriority::parse(""), None);
+    }
+
+    #[test]
+    fn priority_from_headers_defaults_to_normal() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(priority_from_headers(&headers), RequestPriority::Normal);
+        headers.insert(PRIORITY_HEADER, "high".parse().unwrap());
+        assert_eq!(priority_from_headers(&headers), RequestPriority::High);
+        headers.insert(PRIORITY_HEADER, "urgent".parse().unwrap());
+        assert_eq!(priority_from_headers(&headers), RequestPriority::Normal);
+        // 内部生成リクエストの既定値は low
+        assert_eq!(RequestPriority::default(), RequestPriority::Low);
+    }
+
+    #[test]
+    fn apply_priority_header_sets_given_priority() {
+        let client = reqwest::Client::new();
+        let request = apply_priority_header(
+            client.post("http://127.0.0.1/v1/chat/completions"),
+            RequestPriority::High,
+        )
+        .build()
+        .unwrap();
+        assert_eq!(request.headers()[PRIORITY_HEADER], "high");
+    }
+}
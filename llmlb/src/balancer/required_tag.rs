@@ -0,0 +1,99 @@
+//! リクエスト単位のタグ限定ルーティング
+//!
+//! リクエストヘッダ `X-LLMLB-Require-Tag` で、指定したタグを持つエンドポイントのみを
+//! 選択候補にする（例: `prod` / `experimental`）。ルーティングポリシー・A/Bテストの
+//! 必須ラベルを適用した後に絞り込み、該当するエンドポイントが無ければ 503 を返す。
+
+use axum::http::HeaderMap;
+
+use crate::common::error::LbError;
+use crate::types::endpoint::Endpoint;
+
+/// 必須タグを指定するリクエストヘッダ
+pub const REQUIRE_TAG_HEADER: &str = "x-llmlb-require-tag";
+
+/// `X-LLMLB-Require-Tag` ヘッダの必須タグ
+///
+/// 前後の空白は除去し、空文字は無視する。
+pub fn required_tag_from_headers(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(REQUIRE_TAG_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// 必須タグで候補エンドポイントを絞り込む（`None` の場合はそのまま返す）
+pub(crate) fn apply_required_tag(
+    endpoints: Vec<Endpoint>,
+    model_id: &str,
+    tag: Option<&str>,
+) -> Result<Vec<Endpoint>, LbError> {
+    let Some(tag) = tag else {
+        return Ok(endpoints);
+    };
+    let matched: Vec<Endpoint> = endpoints
+        .into_iter()
+        .filter(|ep| ep.tags.iter().any(|t| t == tag))
+        .collect();
+    if matched.is_empty() {
+        tracing::warn!(
+            model = %model_id,
+            required_tag = %tag,
+            "No endpoint has the required tag"
+        );
+        return Err(LbError::ServiceUnavailable(format!(
+            "No endpoint with tag '{}' is available for model {}",
+            tag, model_id
+        )));
+    }
+    Ok(matched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::EndpointType;
+
+    fn endpoint(tags: &[&str]) -> Endpoint {
+        let mut endpoint = Endpoint::new(
+            "ep".to_string(),
+            "http://localhost:8080".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        endpoint.tags = tags.iter().map(|t| t.to_string()).collect();
+        endpoint
+    }
+
+    #[test]
+    fn filters_by_required_tag_only_when_given() {
+        let endpoints = vec![endpoint(&["prod"]), endpoint(&["experimental"])];
+
+        let all = apply_required_tag(endpoints.clone(), "m", None).unwrap();
+        assert_eq!(all.len(), 2);
+
+        let prod = apply_required_tag(endpoints, "m", Some("prod")).unwrap();
+        assert_eq!(prod.len(), 1);
+        assert_eq!(prod[0].tags, vec!["prod"]);
+    }
+
+    #[test]
+    fn no_tagged_endpoint_is_service_unavailable() {
+        let err = apply_required_tag(vec![endpoint(&["prod"])], "m", Some("gpu")).unwrap_err();
+        assert_eq!(
+            err.status_code(),
+            axum::http::StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[test]
+    fn required_tag_from_headers_trims_and_ignores_empty() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(required_tag_from_headers(&headers), None);
+        headers.insert(REQUIRE_TAG_HEADER, " prod ".parse().unwrap());
+        assert_eq!(required_tag_from_headers(&headers).as_deref(), Some("prod"));
+        headers.insert(REQUIRE_TAG_HEADER, "  ".parse().unwrap());
+        assert_eq!(required_tag_from_headers(&headers), None);
+    }
+}
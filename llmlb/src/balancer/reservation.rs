@@ -0,0 +1,235 @@
+//! エンドポイント容量予約
+//!
+//! APIキー/テナント（APIキー発行ユーザー）単位で、特定エンドポイントの同時スロットを予約する。
+//! 予約分は他クライアントへ割り当てない。soft予約は予約主体が使っていない間だけ共有プールへ戻す。
+//!
+//! 予約が1件も無いエンドポイントには容量制限を適用しない。
+
+use crate::common::error::{CommonError, LbError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 予約主体の種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrincipalType {
+    /// APIキー単位
+    ApiKey,
+    /// テナント単位（APIキー発行ユーザーの全キー）
+    Tenant,
+}
+
+impl PrincipalType {
+    /// DB保存用の文字列表現
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrincipalType::ApiKey => "api_key",
+            PrincipalType::Tenant => "tenant",
+        }
+    }
+}
+
+impl std::str::FromStr for PrincipalType {
+    type Err = LbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "api_key" => Ok(PrincipalType::ApiKey),
+            "tenant" => Ok(PrincipalType::Tenant),
+            _ => Err(CommonError::Validation(format!("Invalid principal_type: {}", s)).into()),
+        }
+    }
+}
+
+/// エンドポイント容量予約
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapacityReservation {
+    /// 一意識別子
+    pub id: Uuid,
+    /// 対象エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 予約主体の種別
+    pub principal_type: PrincipalType,
+    /// 予約主体ID（APIキーID、またはテナントのユーザーID）
+    pub principal_id: Uuid,
+    /// 予約する同時スロット数
+    pub slots: u32,
+    /// soft予約（予約主体が使っていない間は共有プールへ戻す）
+    pub soft: bool,
+    /// 作成日時
+    pub created_at: DateTime<Utc>,
+    /// 更新日時
+    pub updated_at: DateTime<Utc>,
+}
+
+/// リクエスト元の主体
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestPrincipal {
+    /// APIキーID
+    pub api_key_id: Uuid,
+    /// APIキー発行ユーザーID（テナント）
+    pub tenant_id: Uuid,
+}
+
+impl RequestPrincipal {
+    /// 予約の主体と一致するか
+    pub fn holds(&self, reservation: &CapacityReservation) -> bool {
+        match reservation.principal_type {
+            PrincipalType::ApiKey => reservation.principal_id == self.api_key_id,
+            PrincipalType::Tenant => reservation.principal_id == self.tenant_id,
+        }
+    }
+}
+
+/// APIキー認証済みリクエストの主体（APIキー認証されていない場合は `None`）
+pub fn principal_from_auth(
+    auth: Option<&crate::auth::middleware::ApiKeyAuthContext>,
+) -> Option<RequestPrincipal> {
+    auth.map(|ctx| RequestPrincipal {
+        api_key_id: ctx.id,
+        tenant_id: ctx.created_by,
+    })
+}
+
+/// 容量判定の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    /// 予約スロットを使用する
+    Reserved(Uuid),
+    /// 共有プールを使用する
+    Shared,
+    /// 空きスロットなし
+    Rejected,
+}
+
+/// エンドポイントへの割り当て可否を判定する
+///
+/// # Arguments
+/// * `reservations` - 対象エンドポイントの予約一覧
+/// * `usage` - 予約ID → 予約スロット使用数
+/// * `total_active` - エンドポイントの処理中リクエスト数
+/// * `capacity` - エンドポイントの同時スロット数
+/// * `principal` - リクエスト主体
+pub fn admit(
+    reservations: &[&CapacityReservation],
+    usage: &HashMap<Uuid, u32>,
+    total_active: u32,
+    capacity: u32,
+    principal: Option<&RequestPrincipal>,
+) -> Admission {
+    if reservations.is_empty() {
+        return Admission::Shared;
+    }
+
+    let mut withheld = 0u32;
+    let mut reserved_in_use = 0u32;
+    for reservation in reservations {
+        let used = usage.get(&reservation.id).copied().unwrap_or(0);
+        reserved_in_use = reserved_in_use.saturating_add(used.min(reservation.slots));
+        if !reservation.soft || used > 0 {
+            withheld = withheld.saturating_add(reservation.slots);
+        }
+    }
+
+    if let Some(principal) = principal {
+        if let Some(reservation) = reservations
+            .iter()
+            .find(|r| principal.holds(r) && usage.get(&r.id).copied().unwrap_or(0) < r.slots)
+        {
+            return Admission::Reserved(reservation.id);
+        }
+    }
+
+    let shared_capacity = capacity.saturating_sub(withheld);
+    let shared_in_use = total_active.saturating_sub(reserved_in_use);
+    if shared_in_use < shared_capacity {
+        Admission::Shared
+    } else {
+        Admission::Rejected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reservation(principal_id: Uuid, slots: u32, soft: bool) -> CapacityReservation {
+        let now = Utc::now();
+        CapacityReservation {
+            id: Uuid::new_v4(),
+            endpoint_id: Uuid::new_v4(),
+            principal_type: PrincipalType::ApiKey,
+            principal_id,
+            slots,
+            soft,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn hard_reservation_is_withheld_from_other_clients() {
+        let holder = RequestPrincipal {
+            api_key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+        };
+        let other = RequestPrincipal {
+            api_key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+        };
+        let r = reservation(holder.api_key_id, 2, false);
+        let reservations = [&r];
+        let mut usage = HashMap::new();
+
+        // capacity 4, reserved 2 → others may use 2
+        assert_eq!(
+            admit(&reservations, &usage, 1, 4, Some(&other)),
+            Admission::Shared
+        );
+        assert_eq!(
+            admit(&reservations, &usage, 2, 4, Some(&other)),
+            Admission::Rejected
+        );
+        assert_eq!(
+            admit(&reservations, &usage, 2, 4, Some(&holder)),
+            Admission::Reserved(r.id)
+        );
+
+        // holder exhausted its reservation → falls back to the shared pool
+        usage.insert(r.id, 2);
+        assert_eq!(
+            admit(&reservations, &usage, 3, 4, Some(&holder)),
+            Admission::Shared
+        );
+        assert_eq!(
+            admit(&reservations, &usage, 4, 4, Some(&holder)),
+            Admission::Rejected
+        );
+    }
+
+    #[test]
+    fn soft_reservation_returns_to_shared_pool_while_idle() {
+        let holder = RequestPrincipal {
+            api_key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+        };
+        let mut r = reservation(holder.tenant_id, 2, true);
+        r.principal_type = PrincipalType::Tenant;
+        let reservations = [&r];
+        let mut usage = HashMap::new();
+
+        assert_eq!(admit(&reservations, &usage, 3, 4, None), Admission::Shared);
+
+        usage.insert(r.id, 1);
+        assert_eq!(
+            admit(&reservations, &usage, 3, 4, None),
+            Admission::Rejected
+        );
+        assert_eq!(
+            admit(&reservations, &usage, 3, 4, Some(&holder)),
+            Admission::Reserved(r.id)
+        );
+    }
+}
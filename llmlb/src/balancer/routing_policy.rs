@@ -0,0 +1,222 @@
+//! ラベルベースのルーティングポリシー
+//!
+//! モデル名パターン（と任意のAPI種別）に合致したリクエストについて、
+//! 必須ラベル（エンドポイントのタグ）をすべて持つエンドポイントのみを候補にする。
+
+use crate::common::error::{CommonError, LbError};
+use crate::common::protocol::TpsApiKind;
+use crate::types::endpoint::Endpoint;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// ポリシーに合致するエンドポイントが無い場合の挙動
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoMatchBehavior {
+    /// エラーを返す（候補なし）
+    #[default]
+    Error,
+    /// ポリシーを無視して通常の候補から選択する
+    Fallback,
+}
+
+impl NoMatchBehavior {
+    /// DB保存用の文字列表現
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoMatchBehavior::Error => "error",
+            NoMatchBehavior::Fallback => "fallback",
+        }
+    }
+}
+
+impl std::str::FromStr for NoMatchBehavior {
+    type Err = LbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(NoMatchBehavior::Error),
+            "fallback" => Ok(NoMatchBehavior::Fallback),
+            _ => Err(CommonError::Validation(format!("Invalid on_no_match: {}", s)).into()),
+        }
+    }
+}
+
+/// ルーティングポリシー
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingPolicy {
+    /// 一意識別子
+    pub id: Uuid,
+    /// ポリシー名
+    pub name: String,
+    /// モデル名パターン（完全一致、末尾 `*` で前方一致、`*` 単体で全モデル）
+    pub model_pattern: String,
+    /// 対象API種別（None = 全種別）
+    #[serde(default)]
+    pub api_kind: Option<TpsApiKind>,
+    /// 必須ラベル（すべて満たすエンドポイントのみ候補）
+    pub required_labels: Vec<String>,
+    /// 合致ゼロ時の挙動
+    #[serde(default)]
+    pub on_no_match: NoMatchBehavior,
+    /// 優先度（大きいほど優先して評価）
+    #[serde(default)]
+    pub priority: i32,
+    /// 有効フラグ
+    pub enabled: bool,
+    /// 作成日時
+    pub created_at: DateTime<Utc>,
+    /// 更新日時
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RoutingPolicy {
+    /// リクエスト属性がこのポリシーの対象か判定する
+    pub fn applies_to(&self, model_id: &str, api_kind: Option<TpsApiKind>) -> bool {
+        if !self.enabled || !model_pattern_matches(&self.model_pattern, model_id) {
+            return false;
+        }
+        match self.api_kind {
+            Some(expected) => api_kind == Some(expected),
+            None => true,
+        }
+    }
+}
+
+/// モデル名パターンの判定
+pub fn model_pattern_matches(pattern: &str, model_id: &str) -> bool {
+    let pattern = pattern.trim();
+    if pattern == "*" {
+        return true;
+    }
+    match pattern.strip_suffix('*') {
+        Some(prefix) => model_id.starts_with(prefix),
+        None => pattern == model_id,
+    }
+}
+
+/// 優先度順（降順）に並んだポリシーから、最初に合致したものを返す
+pub(crate) fn find_applicable_policy<'a>(
+    policies: &'a [RoutingPolicy],
+    model_id: &str,
+    api_kind: Option<TpsApiKind>,
+) -> Option<&'a RoutingPolicy> {
+    policies.iter().find(|p| p.applies_to(model_id, api_kind))
+}
+
+/// ポリシーを候補エンドポイントに適用する
+///
+/// 合致ポリシーが無ければ候補をそのまま返す。合致エンドポイントがゼロの場合は
+/// `on_no_match` に従いエラーまたは元の候補を返す。
+pub(crate) fn apply_policy(
+    policy: &RoutingPolicy,
+    endpoints: Vec<Endpoint>,
+    model_id: &str,
+) -> Result<Vec<Endpoint>, LbError> {
+    let (matched, rest): (Vec<_>, Vec<_>) = endpoints
+        .into_iter()
+        .partition(|ep| ep.has_all_tags(&policy.required_labels));
+
+    if !matched.is_empty() {
+        return Ok(matched);
+    }
+
+    match policy.on_no_match {
+        NoMatchBehavior::Error => {
+            tracing::warn!(
+                policy = %policy.name,
+                model = %model_id,
+                required_labels = ?policy.required_labels,
+                "No endpoint satisfies routing policy"
+            );
+            Err(LbError::NoCapableEndpoints(model_id.to_string()))
+        }
+        NoMatchBehavior::Fallback => {
+            tracing::warn!(
+                policy = %policy.name,
+                model = %model_id,
+                required_labels = ?policy.required_labels,
+                "No endpoint satisfies routing policy; falling back to all candidates"
+            );
+            Ok(rest)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::EndpointType;
+
+    fn policy(pattern: &str, labels: &[&str], on_no_match: NoMatchBehavior) -> RoutingPolicy {
+        RoutingPolicy {
+            id: Uuid::new_v4(),
+            name: format!("policy-{}", pattern),
+            model_pattern: pattern.to_string(),
+            api_kind: None,
+            required_labels: labels.iter().map(|s| s.to_string()).collect(),
+            on_no_match,
+            priority: 0,
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn endpoint(name: &str, tags: &[&str]) -> Endpoint {
+        let mut ep = Endpoint::new(
+            name.to_string(),
+            format!("http://{}:8000", name),
+            EndpointType::Vllm,
+        );
+        ep.tags = tags.iter().map(|s| s.to_string()).collect();
+        ep
+    }
+
+    #[test]
+    fn model_pattern_exact_prefix_and_wildcard() {
+        assert!(model_pattern_matches("llama3:70b", "llama3:70b"));
+        assert!(!model_pattern_matches("llama3:70b", "llama3:8b"));
+        assert!(model_pattern_matches("llama3*", "llama3:8b"));
+        assert!(model_pattern_matches("*", "anything"));
+    }
+
+    #[test]
+    fn applies_to_respects_api_kind_and_enabled() {
+        let mut p = policy("gpt-oss*", &["gpu-a100"], NoMatchBehavior::Error);
+        p.api_kind = Some(TpsApiKind::ChatCompletions);
+        assert!(p.applies_to("gpt-oss:20b", Some(TpsApiKind::ChatCompletions)));
+        assert!(!p.applies_to("gpt-oss:20b", Some(TpsApiKind::Completions)));
+
+        p.enabled = false;
+        assert!(!p.applies_to("gpt-oss:20b", Some(TpsApiKind::ChatCompletions)));
+    }
+
+    #[test]
+    fn apply_policy_keeps_only_labeled_endpoints() {
+        let p = policy("*", &["gpu-a100"], NoMatchBehavior::Error);
+        let result = apply_policy(
+            &p,
+            vec![
+                endpoint("a", &["gpu-a100", "prod"]),
+                endpoint("b", &["cpu"]),
+            ],
+            "m",
+        )
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].name, "a");
+    }
+
+    #[test]
+    fn apply_policy_no_match_error_or_fallback() {
+        let strict = policy("*", &["gpu-a100"], NoMatchBehavior::Error);
+        let err = apply_policy(&strict, vec![endpoint("b", &["cpu"])], "m").unwrap_err();
+        assert!(matches!(err, LbError::NoCapableEndpoints(_)));
+
+        let lenient = policy("*", &["gpu-a100"], NoMatchBehavior::Fallback);
+        let result = apply_policy(&lenient, vec![endpoint("b", &["cpu"])], "m").unwrap();
+        assert_eq!(result.len(), 1);
+    }
+}
@@ -0,0 +1,101 @@
+//! エンドポイント選択の入力
+//!
+//! リクエストヘッダ・認証情報・A/Bテストの割り当てなど、エンドポイント選択に影響する
+//! リクエスト単位の値をまとめて保持し、選択関数へ明示的に渡す。
+//! タスクローカルに置くと `tokio::spawn` したタスクやストリーミングレスポンスの
+//! ボディ処理では参照できず、黙って既定の選択に戻ってしまうため使わない。
+
+use axum::http::HeaderMap;
+
+use super::canary;
+use super::experiment::ExperimentAssignment;
+use super::optimize::{self, OptimizeTarget};
+use super::priority::{self, RequestPriority};
+use super::required_tag;
+use super::reservation::{self, RequestPrincipal};
+use super::session_affinity;
+use crate::auth::middleware::ApiKeyAuthContext;
+use crate::types::endpoint::SupportedAPI;
+
+/// エンドポイント選択に使うリクエスト単位の値
+///
+/// `Default` は受信リクエストに紐付かない内部リクエスト（warmup・シャドウ等）向けで、
+/// 優先度は `Low` になる。
+#[derive(Debug, Clone, Default)]
+pub struct SelectionContext {
+    /// リクエスト主体（APIキー認証されていない場合は `None`）
+    pub principal: Option<RequestPrincipal>,
+    /// セッションID（sticky session）
+    pub session_id: Option<String>,
+    /// 必須タグ（`X-LLMLB-Require-Tag`）
+    pub required_tag: Option<String>,
+    /// 必須API（対応エンドポイントのみを候補にする）
+    pub required_api: Option<SupportedAPI>,
+    /// 選択の最適化指定（`X-LLMLB-Optimize`）
+    pub optimize_target: Option<OptimizeTarget>,
+    /// A/Bテストの割り当て
+    pub assignment: Option<ExperimentAssignment>,
+    /// 推定入力トークン数（コンテキスト長ルーティング）
+    pub input_tokens: Option<u32>,
+    /// アップストリームへ引き継ぐ優先度
+    pub priority: RequestPriority,
+    /// 候補から除外するエンドポイント（別エンドポイントでのやり直し時の試行済み分）
+    pub excluded_endpoints: Vec<uuid::Uuid>,
+    /// カナリア振り分けの乱数（0〜99、`None` は選択のたびに引く）
+    pub canary_roll: Option<u8>,
+    /// クライアントIP（信頼済みプロキシの転送ヘッダを考慮して決定したもの）
+    pub client_ip: Option<std::net::IpAddr>,
+}
+
+impl SelectionContext {
+    /// 受信リクエストのヘッダと認証情報から作成する
+    pub fn from_request(headers: &HeaderMap, auth: Option<&ApiKeyAuthContext>) -> Self {
+        Self {
+            principal: reservation::principal_from_auth(auth),
+            session_id: session_affinity::session_id_from_headers(headers),
+            required_tag: required_tag::required_tag_from_headers(headers),
+            required_api: None,
+            optimize_target: optimize::optimize_target_from_headers(headers),
+            assignment: None,
+            input_tokens: None,
+            priority: priority::priority_from_headers(headers),
+            excluded_endpoints: Vec::new(),
+            canary_roll: Some(canary::draw()),
+            client_ip: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_request_reads_selection_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(session_affinity::SESSION_ID_HEADER, "s1".parse().unwrap());
+        headers.insert(required_tag::REQUIRE_TAG_HEADER, "prod".parse().unwrap());
+        headers.insert(optimize::OPTIMIZE_HEADER, "ttft".parse().unwrap());
+        headers.insert(priority::PRIORITY_HEADER, "high".parse().unwrap());
+        let auth = ApiKeyAuthContext {
+            id: uuid::Uuid::new_v4(),
+            created_by: uuid::Uuid::new_v4(),
+            permissions: vec![],
+            expires_at: None,
+        };
+
+        let ctx = SelectionContext::from_request(&headers, Some(&auth));
+        assert_eq!(ctx.session_id.as_deref(), Some("s1"));
+        assert_eq!(ctx.required_tag.as_deref(), Some("prod"));
+        assert_eq!(ctx.optimize_target, Some(OptimizeTarget::Ttft));
+        assert_eq!(ctx.priority, RequestPriority::High);
+        let principal = ctx.principal.unwrap();
+        assert_eq!(principal.api_key_id, auth.id);
+        assert_eq!(principal.tenant_id, auth.created_by);
+
+        let ctx = SelectionContext::from_request(&HeaderMap::new(), None);
+        assert!(ctx.principal.is_none() && ctx.session_id.is_none());
+        assert_eq!(ctx.priority, RequestPriority::Normal);
+        assert_eq!(SelectionContext::default().priority, RequestPriority::Low);
+    }
+}
@@ -0,0 +1,169 @@
+//! セッションアフィニティ（sticky session）
+//!
+//! マルチターン会話でエンドポイント側のKVキャッシュを活かすため、リクエストヘッダ
+//! `X-LLMLB-Session-Id` が同じリクエストを同じエンドポイントへルーティングする。
+//!
+//! セッションID → エンドポイントIDの対応はTTL付きで保持し、利用のたびに期限を延長する。
+//! 割り当て先がオフライン・初期化中・モデル非対応になった場合は通常選択にフォールバックし、
+//! 選ばれたエンドポイントへ再バインドする。期限切れの対応は定期タスクで掃除する。
+
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use super::LoadManager;
+
+/// セッションIDを指定するリクエストヘッダ
+pub const SESSION_ID_HEADER: &str = "x-llmlb-session-id";
+
+/// 受け付けるセッションIDの最大長
+const MAX_SESSION_ID_LEN: usize = 256;
+
+/// 期限切れセッションの掃除間隔の上限
+const MAX_CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// `X-LLMLB-Session-Id` ヘッダのセッションID
+///
+/// 空文字や長すぎる値は無視する。
+pub fn session_id_from_headers(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(SESSION_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty() && v.len() <= MAX_SESSION_ID_LEN)
+        .map(str::to_string)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SessionBinding {
+    endpoint_id: Uuid,
+    expires_at: Instant,
+}
+
+/// セッションID → エンドポイントIDの対応表（TTL付き）
+#[derive(Debug)]
+pub struct SessionBindings {
+    ttl: Duration,
+    bindings: HashMap<String, SessionBinding>,
+}
+
+impl SessionBindings {
+    /// TTLを指定して作成
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// TTL
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// 有効な割り当て先を取得する
+    pub fn get(&self, session_id: &str, now: Instant) -> Option<Uuid> {
+        self.bindings
+            .get(session_id)
+            .filter(|binding| binding.expires_at > now)
+            .map(|binding| binding.endpoint_id)
+    }
+
+    /// 割り当てを作成・更新し、期限を `now + TTL` に延長する
+    pub fn bind(&mut self, session_id: &str, endpoint_id: Uuid, now: Instant) {
+        self.bindings.insert(
+            session_id.to_string(),
+            SessionBinding {
+                endpoint_id,
+                expires_at: now + self.ttl,
+            },
+        );
+    }
+
+    /// 期限切れの割り当てを削除し、削除件数を返す
+    pub fn purge_expired(&mut self, now: Instant) -> usize {
+        let before = self.bindings.len();
+        self.bindings.retain(|_, binding| binding.expires_at > now);
+        before - self.bindings.len()
+    }
+
+    /// 保持中の割り当て数
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    /// 割り当てが無いか
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+}
+
+/// 期限切れセッションを定期的に掃除するタスクを起動する
+///
+/// 掃除間隔はTTLと60秒の短い方。
+pub fn start_session_cleanup_task(load_manager: LoadManager) {
+    let interval = load_manager
+        .session_affinity_ttl()
+        .min(MAX_CLEANUP_INTERVAL)
+        .max(Duration::from_secs(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            let purged = load_manager.purge_expired_sessions();
+            if purged > 0 {
+                tracing::debug!(purged, "Purged expired session affinity bindings");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_expire_after_ttl_and_refresh_on_bind() {
+        let mut bindings = SessionBindings::new(Duration::from_secs(30));
+        let start = Instant::now();
+        let endpoint_id = Uuid::new_v4();
+
+        bindings.bind("session-a", endpoint_id, start);
+        assert_eq!(
+            bindings.get("session-a", start + Duration::from_secs(29)),
+            Some(endpoint_id)
+        );
+        assert_eq!(
+            bindings.get("session-a", start + Duration::from_secs(30)),
+            None
+        );
+
+        // 再バインドで期限が延長される
+        bindings.bind("session-a", endpoint_id, start + Duration::from_secs(20));
+        assert_eq!(
+            bindings.get("session-a", start + Duration::from_secs(45)),
+            Some(endpoint_id)
+        );
+
+        bindings.bind("session-b", Uuid::new_v4(), start);
+        assert_eq!(bindings.purge_expired(start + Duration::from_secs(31)), 1);
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings.purge_expired(start + Duration::from_secs(60)), 1);
+        assert!(bindings.is_empty());
+    }
+
+    #[test]
+    fn session_id_from_headers_ignores_empty_and_oversized() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(session_id_from_headers(&headers), None);
+        headers.insert(SESSION_ID_HEADER, " abc ".parse().unwrap());
+        assert_eq!(session_id_from_headers(&headers).as_deref(), Some("abc"));
+        headers.insert(
+            SESSION_ID_HEADER,
+            "x".repeat(MAX_SESSION_ID_LEN + 1).parse().unwrap(),
+        );
+        assert_eq!(session_id_from_headers(&headers), None);
+    }
+}
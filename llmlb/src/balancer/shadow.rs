@@ -0,0 +1,124 @@
+//! シャドウトラフィック（本番リクエストの複製送信）
+//!
+//! モデル名パターンに合致した本番リクエストのうち指定割合を、クライアントへの応答とは
+//! 無関係にシャドウエンドポイントへ複製送信する。シャドウの応答は破棄し、
+//! レイテンシ・エラーのみを本番とは別に集計する。
+
+use super::routing_policy::model_pattern_matches;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// シャドウトラフィック設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShadowTarget {
+    /// 一意識別子
+    pub id: Uuid,
+    /// 設定名
+    pub name: String,
+    /// 対象モデル名パターン（完全一致、末尾 `*` で前方一致、`*` 単体で全モデル）
+    pub model_pattern: String,
+    /// 複製先（シャドウ）エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 複製する割合（0〜100）
+    pub sample_percent: u8,
+    /// 有効フラグ
+    pub enabled: bool,
+    /// 作成日時
+    pub created_at: DateTime<Utc>,
+    /// 更新日時
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ShadowTarget {
+    /// `roll`（0〜99の乱数）のリクエストを複製するか
+    pub fn samples(&self, model_id: &str, roll: u8) -> bool {
+        self.enabled
+            && model_pattern_matches(&self.model_pattern, model_id)
+            && roll < self.sample_percent
+    }
+}
+
+/// シャドウ送信の集計値
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct ShadowCounters {
+    pub requests: u64,
+    pub errors: u64,
+    pub total_latency_ms: u64,
+}
+
+/// シャドウ送信の統計
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShadowStats {
+    /// 複製送信数
+    pub requests: u64,
+    /// エラー数（送信失敗・非2xx・応答本文の受信失敗）
+    pub errors: u64,
+    /// エラー率（0.0〜1.0、送信なしはNone）
+    pub error_rate: Option<f64>,
+    /// 平均レイテンシ（応答本文の受信完了まで、ミリ秒）
+    pub average_latency_ms: Option<f64>,
+}
+
+impl ShadowStats {
+    pub(crate) fn from_counters(counters: ShadowCounters) -> Self {
+        let per_request =
+            |value: u64| (counters.requests > 0).then(|| value as f64 / counters.requests as f64);
+        Self {
+            requests: counters.requests,
+            errors: counters.errors,
+            error_rate: per_request(counters.errors),
+            average_latency_ms: per_request(counters.total_latency_ms),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(sample_percent: u8) -> ShadowTarget {
+        let now = Utc::now();
+        ShadowTarget {
+            id: Uuid::new_v4(),
+            name: "llama-shadow".to_string(),
+            model_pattern: "llama*".to_string(),
+            endpoint_id: Uuid::new_v4(),
+            sample_percent,
+            enabled: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn samples_by_model_pattern_and_percentage() {
+        let shadow = target(30);
+        assert!(shadow.samples("llama3", 0));
+        assert!(shadow.samples("llama3", 29));
+        assert!(!shadow.samples("llama3", 30));
+        assert!(!shadow.samples("qwen", 0));
+
+        assert!(!target(0).samples("llama3", 0));
+        assert!(target(100).samples("llama3", 99));
+
+        let mut disabled = target(100);
+        disabled.enabled = false;
+        assert!(!disabled.samples("llama3", 0));
+    }
+
+    #[test]
+    fn stats_compute_rates() {
+        let stats = ShadowStats::from_counters(ShadowCounters {
+            requests: 4,
+            errors: 1,
+            total_latency_ms: 400,
+        });
+        assert_eq!(stats.error_rate, Some(0.25));
+        assert_eq!(stats.average_latency_ms, Some(100.0));
+
+        let empty = ShadowStats::from_counters(ShadowCounters::default());
+        assert_eq!(empty.error_rate, None);
+        assert_eq!(empty.average_latency_ms, None);
+    }
+}
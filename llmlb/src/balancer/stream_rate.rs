@@ -0,0 +1,310 @@
+//! ストリーミング応答の出力レート制限
+//!
+//! APIキー/テナント単位でストリーミング応答のトークン/秒・バイト/秒の上限を設け、
+//! チャンクの送出を遅延させる。送出を待つ間はアップストリームを読み進めないため、
+//! 遅いクライアントのためにバッファが膨らむことはない。
+//!
+//! 個別設定の優先順位は APIキー > テナント > 環境変数の既定値。
+//!
+//! 上限は同じ主体の同時ストリーム全体で共有する（APIキー、テナント設定の場合は
+//! テナント、主体が無い場合はクライアントIP単位）。ストリームを並列に張っても
+//! 合計の出力レートは上限を超えない。
+
+use super::reservation::{PrincipalType, RequestPrincipal};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// APIキー/テナント単位のストリーミング出力レート上限
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamRateLimit {
+    /// 一意識別子
+    pub id: Uuid,
+    /// 対象主体の種別
+    pub principal_type: PrincipalType,
+    /// 対象主体ID（APIキーID、またはテナントのユーザーID）
+    pub principal_id: Uuid,
+    /// トークン/秒の上限（`None` は無制限）
+    pub max_tokens_per_sec: Option<u32>,
+    /// バイト/秒の上限（`None` は無制限）
+    pub max_bytes_per_sec: Option<u64>,
+    /// 作成日時
+    pub created_at: DateTime<Utc>,
+    /// 更新日時
+    pub updated_at: DateTime<Utc>,
+}
+
+impl StreamRateLimit {
+    fn rate(&self) -> StreamRate {
+        StreamRate {
+            max_tokens_per_sec: self.max_tokens_per_sec.filter(|v| *v > 0),
+            max_bytes_per_sec: self.max_bytes_per_sec.filter(|v| *v > 0),
+        }
+    }
+}
+
+/// 1本のストリームに適用する出力レート
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamRate {
+    /// トークン/秒の上限
+    pub max_tokens_per_sec: Option<u32>,
+    /// バイト/秒の上限
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl StreamRate {
+    /// 環境変数の既定値（`0` は無制限）
+    pub fn from_env() -> Self {
+        let tokens = crate::config::stream_max_tokens_per_sec();
+        let bytes = crate::config::stream_max_bytes_per_sec();
+        Self {
+            max_tokens_per_sec: (tokens > 0).then_some(tokens),
+            max_bytes_per_sec: (bytes > 0).then_some(bytes),
+        }
+    }
+
+    /// 上限が設定されていないか
+    pub fn is_unlimited(&self) -> bool {
+        self.max_tokens_per_sec.is_none() && self.max_bytes_per_sec.is_none()
+    }
+}
+
+/// 出力レートを共有する単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThrottleKey {
+    /// APIキー
+    ApiKey(Uuid),
+    /// テナント（テナント単位の設定が適用された場合）
+    Tenant(Uuid),
+    /// クライアントIP（リクエスト主体が無い場合）
+    ClientIp(IpAddr),
+}
+
+/// リクエスト主体に適用するレートと共有単位を決める（制限なしの場合は `None`）
+pub fn resolve(
+    limits: &[StreamRateLimit],
+    principal: Option<&RequestPrincipal>,
+    client_ip: Option<IpAddr>,
+    default: StreamRate,
+) -> Option<(StreamRate, Option<ThrottleKey>)> {
+    let matching = |principal_type: PrincipalType, principal_id: Uuid| {
+        limits
+            .iter()
+            .find(|l| l.principal_type == principal_type && l.principal_id == principal_id)
+    };
+    let (rate, key) = match principal {
+        Some(p) => {
+            if let Some(limit) = matching(PrincipalType::ApiKey, p.api_key_id) {
+                (limit.rate(), ThrottleKey::ApiKey(p.api_key_id))
+            } else if let Some(limit) = matching(PrincipalType::Tenant, p.tenant_id) {
+                (limit.rate(), ThrottleKey::Tenant(p.tenant_id))
+            } else {
+                (default, ThrottleKey::ApiKey(p.api_key_id))
+            }
+        }
+        None => match client_ip {
+            Some(ip) => (default, ThrottleKey::ClientIp(ip)),
+            None => return (!default.is_unlimited()).then_some((default, None)),
+        },
+    };
+    (!rate.is_unlimited()).then_some((rate, Some(key)))
+}
+
+/// SSEチャンクに含まれる出力トークン数の概算
+///
+/// 推論サーバーは概ね1イベントにつき1トークンを送るため、`[DONE]` 以外の
+/// `data:` 行の数をトークン数とみなす。
+pub fn count_stream_tokens(chunk: &[u8]) -> u32 {
+    String::from_utf8_lossy(chunk)
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("data:"))
+        .filter(|data| {
+            let data = data.trim();
+            !data.is_empty() && data != "[DONE]"
+        })
+        .count() as u32
+}
+
+#[derive(Debug)]
+struct Pace {
+    rate: StreamRate,
+    next_send_at: Option<Instant>,
+}
+
+/// ストリームの送出ペースを制御する
+///
+/// チャンクごとに上限から算出した送出コストを積み上げ、次に送出できる時刻を求める。
+/// 最初のチャンクは待たずに送出する。[`StreamThrottles`] から取得したスロットルは
+/// 同じ共有単位のストリーム間で送出コストを共有する。
+#[derive(Debug, Clone)]
+pub struct StreamThrottle {
+    pace: Arc<Mutex<Pace>>,
+}
+
+impl StreamThrottle {
+    /// 他のストリームと共有しないスロットルを作成
+    pub fn new(rate: StreamRate) -> Self {
+        Self {
+            pace: Arc::new(Mutex::new(Pace {
+                rate,
+                next_send_at: None,
+            })),
+        }
+    }
+
+    /// `now` に受信したチャンクを送出するまでの待ち時間を返し、送出コストを積み上げる
+    pub fn delay_for(&mut self, now: Instant, bytes: usize, tokens: u32) -> Duration {
+        let mut pace = self.pace.lock().unwrap_or_else(|e| e.into_inner());
+        let send_at = pace.next_send_at.map_or(now, |at| at.max(now));
+        let by_bytes = pace
+            .rate
+            .max_bytes_per_sec
+            .map(|limit| Duration::from_secs_f64(bytes as f64 / limit as f64))
+            .unwrap_or_default();
+        let by_tokens = pace
+            .rate
+            .max_tokens_per_sec
+            .map(|limit| Duration::from_secs_f64(tokens as f64 / limit as f64))
+            .unwrap_or_default();
+        pace.next_send_at = Some(send_at + by_bytes.max(by_tokens));
+        send_at.saturating_duration_since(now)
+    }
+}
+
+/// 共有単位ごとのスロットル
+///
+/// 実行中のストリームが無くなった単位は次回の取得時に破棄する。
+#[derive(Debug, Default)]
+pub struct StreamThrottles {
+    buckets: Mutex<HashMap<ThrottleKey, Weak<Mutex<Pace>>>>,
+}
+
+impl StreamThrottles {
+    /// 共有単位のスロットルを取得する（`key` が無い場合は共有しない）
+    ///
+    /// 既存の単位には最新のレートを反映する。
+    pub fn throttle(&self, key: Option<ThrottleKey>, rate: StreamRate) -> StreamThrottle {
+        let Some(key) = key else {
+            return StreamThrottle::new(rate);
+        };
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(pace) = buckets.get(&key).and_then(Weak::upgrade) {
+            pace.lock().unwrap_or_else(|e| e.into_inner()).rate = rate;
+            return StreamThrottle { pace };
+        }
+        buckets.retain(|_, pace| pace.strong_count() > 0);
+        let throttle = StreamThrottle::new(rate);
+        buckets.insert(key, Arc::downgrade(&throttle.pace));
+        throttle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(principal_type: PrincipalType, principal_id: Uuid, tokens: u32) -> StreamRateLimit {
+        let now = Utc::now();
+        StreamRateLimit {
+            id: Uuid::new_v4(),
+            principal_type,
+            principal_id,
+            max_tokens_per_sec: Some(tokens),
+            max_bytes_per_sec: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn resolve_prefers_api_key_over_tenant_and_default() {
+        let principal = RequestPrincipal {
+            api_key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+        };
+        let default = StreamRate {
+            max_tokens_per_sec: Some(100),
+            max_bytes_per_sec: None,
+        };
+        let tenant = limit(PrincipalType::Tenant, principal.tenant_id, 20);
+        let key = limit(PrincipalType::ApiKey, principal.api_key_id, 5);
+
+        let ip: IpAddr = "203.0.113.9".parse().unwrap();
+
+        let (rate, key) = resolve(&[tenant.clone(), key], Some(&principal), None, default).unwrap();
+        assert_eq!(rate.max_tokens_per_sec, Some(5));
+        assert_eq!(key, Some(ThrottleKey::ApiKey(principal.api_key_id)));
+        let (rate, key) = resolve(&[tenant], Some(&principal), Some(ip), default).unwrap();
+        assert_eq!(rate.max_tokens_per_sec, Some(20));
+        assert_eq!(key, Some(ThrottleKey::Tenant(principal.tenant_id)));
+        let (rate, key) = resolve(&[], Some(&principal), Some(ip), default).unwrap();
+        assert_eq!(rate.max_tokens_per_sec, Some(100));
+        assert_eq!(key, Some(ThrottleKey::ApiKey(principal.api_key_id)));
+        let (_, key) = resolve(&[], None, Some(ip), default).unwrap();
+        assert_eq!(key, Some(ThrottleKey::ClientIp(ip)));
+        let (_, key) = resolve(&[], None, None, default).unwrap();
+        assert_eq!(key, None);
+        assert!(resolve(&[], Some(&principal), None, StreamRate::default()).is_none());
+    }
+
+    #[test]
+    fn throttles_with_same_key_share_the_budget() {
+        let throttles = StreamThrottles::default();
+        let rate = StreamRate {
+            max_tokens_per_sec: Some(10),
+            max_bytes_per_sec: None,
+        };
+        let key = Some(ThrottleKey::ApiKey(Uuid::new_v4()));
+        let mut first = throttles.throttle(key, rate);
+        let mut second = throttles.throttle(key, rate);
+        let mut other = throttles.throttle(Some(ThrottleKey::ApiKey(Uuid::new_v4())), rate);
+        let start = Instant::now();
+
+        assert_eq!(first.delay_for(start, 0, 1), Duration::ZERO);
+        // 別ストリームでも同じ単位なら前のストリームの送出コストを待つ
+        assert_eq!(second.delay_for(start, 0, 1), Duration::from_millis(100));
+        assert_eq!(other.delay_for(start, 0, 1), Duration::ZERO);
+
+        // 実行中のストリームが無くなった単位は作り直される
+        drop((first, second));
+        let mut fresh = throttles.throttle(key, rate);
+        assert_eq!(fresh.delay_for(start, 0, 1), Duration::ZERO);
+    }
+
+    #[test]
+    fn count_stream_tokens_skips_done_and_non_data_lines() {
+        let chunk = b"data: {\"a\":1}\n\n: keep-alive\ndata: {\"b\":2}\n\ndata: [DONE]\n\n";
+        assert_eq!(count_stream_tokens(chunk), 2);
+    }
+
+    #[test]
+    fn throttle_paces_chunks_by_strictest_limit() {
+        let mut throttle = StreamThrottle::new(StreamRate {
+            max_tokens_per_sec: Some(10),
+            max_bytes_per_sec: Some(1_000),
+        });
+        let start = Instant::now();
+        // 最初のチャンクは即時送出
+        assert_eq!(throttle.delay_for(start, 100, 1), Duration::ZERO);
+        // 1トークン=100ms と 100バイト=100ms のうち厳しい方
+        assert_eq!(
+            throttle.delay_for(start, 100, 1),
+            Duration::from_millis(100)
+        );
+        // 500バイト=500ms がトークン制限より厳しい
+        assert_eq!(
+            throttle.delay_for(start, 500, 1),
+            Duration::from_millis(200)
+        );
+        assert_eq!(throttle.delay_for(start, 0, 0), Duration::from_millis(700));
+        // 上限より遅く受信した場合は待たない
+        assert_eq!(
+            throttle.delay_for(start + Duration::from_secs(5), 10, 1),
+            Duration::ZERO
+        );
+    }
+}
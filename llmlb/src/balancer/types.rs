@@ -2,6 +2,7 @@
 //!
 //! ロードバランシングに使用する構造体・列挙型・定数を集約する。
 
+use super::adaptive_rate::AdaptiveRate;
 use crate::common::protocol::{TpsApiKind, TpsSource};
 use crate::types::HealthMetrics;
 use chrono::{DateTime, Utc};
@@ -12,7 +13,7 @@ use std::{
         atomic::{AtomicUsize, Ordering as AtomicOrdering},
         Arc,
     },
-    time::Duration as StdDuration,
+    time::{Duration as StdDuration, Instant},
 };
 use uuid::Uuid;
 
@@ -22,6 +23,20 @@ pub(crate) const METRICS_STALE_THRESHOLD_SECS: i64 = 120;
 pub(crate) const REQUEST_HISTORY_WINDOW_MINUTES: i64 = 60;
 /// ノードメトリクス履歴の最大保持件数
 pub(crate) const METRICS_HISTORY_CAPACITY: usize = 360;
+/// パーセンタイル算出用に保持する直近レイテンシの件数
+pub(crate) const LATENCY_WINDOW_CAPACITY: usize = 256;
+/// TTFT EMAの平滑化係数
+const TTFT_EMA_ALPHA: f64 = 0.2;
+/// 応答トークン数の平均・分散（EMA）の平滑化係数
+///
+/// 持続的な変化は徐々に新しい通常範囲として取り込まれるよう、小さめの値にする。
+const RESPONSE_TOKENS_EMA_ALPHA: f64 = 0.05;
+/// 応答トークン数の異常判定を始めるまでに必要なサンプル数
+pub(crate) const RESPONSE_TOKENS_MIN_SAMPLES: u64 = 20;
+/// レイテンシの外れ値判定を始めるまでに必要な直近サンプル数
+const LATENCY_OUTLIER_MIN_SAMPLES: usize = 20;
+/// 連続でこの回数外れ値になった場合は実際の悪化とみなしてクリップしない
+const LATENCY_OUTLIER_MAX_CONSECUTIVE: u32 = 3;
 
 pub(crate) type TpsTrackerKey = (Uuid, String, TpsApiKind);
 pub(crate) type TpsTrackerMap = HashMap<TpsTrackerKey, ModelTpsState>;
@@ -35,6 +50,8 @@ pub enum RequestOutcome {
     Error,
     /// キュー待ち
     Queued,
+    /// 結果を得ずに終了（完了前に lease が破棄された。クライアント切断など）
+    Cancelled,
 }
 
 /// 待機結果
@@ -170,6 +187,135 @@ pub(crate) struct EndpointLoadState {
     pub(crate) total_output_tokens: u64,
     /// 総トークン累計
     pub(crate) total_tokens: u64,
+    /// 直近の完了リクエストのレイテンシ（ms、リングバッファ）
+    pub(crate) recent_latencies_ms: VecDeque<u64>,
+    /// ストリーミングの最初のチャンクまでの時間（TTFT）のEMA（ms、None=未計測）
+    pub(crate) ttft_ema_ms: Option<f64>,
+    /// モデル別の応答トークン数統計
+    pub(crate) response_token_stats: HashMap<String, ResponseTokenStats>,
+    /// 連続エラー数（成功でリセット）
+    pub(crate) consecutive_errors: u32,
+    /// サーキットブレーカーの open 期限（None=closed、期限経過後は half-open）
+    pub(crate) breaker_open_until: Option<Instant>,
+    /// half-open 中の試験リクエストを投入済みか
+    pub(crate) breaker_probe_in_flight: bool,
+    /// アップストリームの 429 率に応じた送信許可レート
+    pub(crate) adaptive_rate: AdaptiveRate,
+    /// カナリアとしての直近リクエスト結果（エラー率による自動停止用）
+    pub(crate) canary_outcomes: super::canary::CanaryOutcomes,
+    /// レイテンシに応じた動的同時実行上限
+    pub(crate) concurrency_limit: super::concurrency_limit::ConcurrencyLimit,
+    /// 推論レイテンシの外れ値と判定した回数（累計）
+    pub(crate) latency_outliers: u64,
+    /// 連続した推論レイテンシの外れ値の数（外れ値でない計測でリセット）
+    pub(crate) consecutive_latency_outliers: u32,
+}
+
+/// サーキットブレーカーの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// 通常（ルーティング対象）
+    #[default]
+    Closed,
+    /// 遮断中（クールダウンまでルーティング対象外）
+    Open,
+    /// クールダウン経過後、試験リクエストを1本だけ受け付ける
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// closed か
+    pub fn is_closed(&self) -> bool {
+        *self == CircuitState::Closed
+    }
+
+    /// 表示・監査ログ用の文字列表現
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// 応答トークン数の統計（エンドポイント×モデル単位）
+///
+/// 平均・分散をEMAで保持し、直近ウィンドウ内で通常範囲（zスコアの閾値内）から
+/// 外れた応答が過半数になった場合に degraded 相当とみなす。単発の外れ値では反応しない。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ResponseTokenStats {
+    /// 記録済みサンプル数
+    pub(crate) samples: u64,
+    /// 出力トークン数の平均（EMA）
+    pub(crate) mean: f64,
+    /// 出力トークン数の分散（EMA）
+    pub(crate) variance: f64,
+    /// 直近の応答が外れ値だったか（リングバッファ）
+    pub(crate) recent_outliers: VecDeque<bool>,
+    /// 異常な応答が続いている状態か
+    pub(crate) degraded: bool,
+}
+
+impl ResponseTokenStats {
+    /// 出力トークン数を記録する
+    ///
+    /// `z_threshold` が `0` 以下の場合は判定しない。degraded 状態が変化した場合のみ
+    /// 新しい状態を返す（`Some(true)`=異常検知、`Some(false)`=回復）。
+    pub(crate) fn record(
+        &mut self,
+        output_tokens: u64,
+        z_threshold: f64,
+        window: usize,
+    ) -> Option<bool> {
+        let mut value = output_tokens as f64;
+
+        if z_threshold > 0.0 && self.samples >= RESPONSE_TOKENS_MIN_SAMPLES {
+            let outlier = self.z_score(value) > z_threshold;
+            self.recent_outliers.push_back(outlier);
+            while self.recent_outliers.len() > window.max(1) {
+                self.recent_outliers.pop_front();
+            }
+            if outlier {
+                // 外れ値で分散が膨らみ以降の異常を見逃さないよう、通常範囲の境界に丸めて取り込む
+                let bound = z_threshold * self.std_dev();
+                value = value.clamp(self.mean - bound, self.mean + bound);
+            }
+        }
+
+        if self.samples == 0 {
+            self.mean = value;
+            self.variance = 0.0;
+        } else {
+            let diff = value - self.mean;
+            let increment = RESPONSE_TOKENS_EMA_ALPHA * diff;
+            self.mean += increment;
+            self.variance = (1.0 - RESPONSE_TOKENS_EMA_ALPHA) * (self.variance + diff * increment);
+        }
+        self.samples = self.samples.saturating_add(1);
+
+        let outliers = self.recent_outliers.iter().filter(|o| **o).count();
+        let degraded = outliers * 2 > window.max(1);
+        if degraded == self.degraded {
+            return None;
+        }
+        self.degraded = degraded;
+        Some(degraded)
+    }
+
+    /// 判定に使う標準偏差
+    ///
+    /// 応答長がほぼ一定のモデルで僅かな差を外れ値としないよう、
+    /// 平均の10%（最低1トークン）を下限とする。
+    fn std_dev(&self) -> f64 {
+        self.variance.sqrt().max(self.mean * 0.1).max(1.0)
+    }
+
+    /// 平均からの乖離（標準偏差単位）
+    pub(crate) fn z_score(&self, value: f64) -> f64 {
+        (value - self.mean).abs() / self.std_dev()
+    }
 }
 
 // SPEC-f8e3a1b7: NodeLoadState型エイリアスは削除されました
@@ -185,6 +331,84 @@ impl EndpointLoadState {
         heartbeat_active.max(self.assigned_active)
     }
 
+    /// サーキットブレーカーの状態
+    pub(crate) fn circuit_state(&self, now: Instant) -> CircuitState {
+        match self.breaker_open_until {
+            None => CircuitState::Closed,
+            Some(until) if now < until => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// サーキットブレーカー上、ルーティング候補にできるか
+    ///
+    /// open 中、および試験リクエストを投入済みの half-open は候補にしない。
+    pub(crate) fn accepts_routing(&self, now: Instant) -> bool {
+        match self.circuit_state(now) {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => !self.breaker_probe_in_flight,
+        }
+    }
+
+    /// リクエスト割り当てを記録する
+    ///
+    /// half-open で最初の割り当てを試験リクエストとし、`Some(HalfOpen)` を返す。
+    pub(crate) fn claim_breaker_probe(&mut self, now: Instant) -> Option<CircuitState> {
+        if self.circuit_state(now) != CircuitState::HalfOpen || self.breaker_probe_in_flight {
+            return None;
+        }
+        self.breaker_probe_in_flight = true;
+        Some(CircuitState::HalfOpen)
+    }
+
+    /// リクエスト結果をサーキットブレーカーへ反映する
+    ///
+    /// 連続 `threshold` 回のエラーで open にし（`0` は無効）、`cooldown` 後の half-open で
+    /// 試験リクエスト（`probe`）が成功すれば closed、失敗すれば再び open にする。
+    /// open / half-open 中に完了した試験リクエスト以外の結果では状態を変えない。
+    /// 状態が変化した場合のみ遷移後の状態を返す。
+    pub(crate) fn record_breaker_outcome(
+        &mut self,
+        success: bool,
+        probe: bool,
+        threshold: u32,
+        cooldown: StdDuration,
+        now: Instant,
+    ) -> Option<CircuitState> {
+        let state = self.circuit_state(now);
+        if state == CircuitState::HalfOpen && !probe {
+            return None;
+        }
+        if success {
+            self.consecutive_errors = 0;
+            if state != CircuitState::HalfOpen {
+                return None;
+            }
+            self.breaker_open_until = None;
+            self.breaker_probe_in_flight = false;
+            return Some(CircuitState::Closed);
+        }
+
+        self.consecutive_errors = self.consecutive_errors.saturating_add(1);
+        let open = match state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => threshold > 0 && self.consecutive_errors >= threshold,
+            CircuitState::Open => false,
+        };
+        if !open {
+            return None;
+        }
+        self.breaker_open_until = Some(now + cooldown);
+        self.breaker_probe_in_flight = false;
+        Some(CircuitState::Open)
+    }
+
+    /// 結果を得ずに終わった試験リクエストを取り消し、次の割り当てを試験リクエストにする
+    pub(crate) fn release_breaker_probe(&mut self) {
+        self.breaker_probe_in_flight = false;
+    }
+
     pub(crate) fn average_latency_ms(&self) -> Option<f32> {
         let completed = self.success_count + self.error_count;
         if completed == 0 {
@@ -212,6 +436,68 @@ impl EndpointLoadState {
             .or_else(|| self.average_latency_ms())
     }
 
+    pub(crate) fn push_latency(&mut self, duration: StdDuration) {
+        let latency_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        self.recent_latencies_ms.push_back(latency_ms);
+        if self.recent_latencies_ms.len() > LATENCY_WINDOW_CAPACITY {
+            self.recent_latencies_ms.pop_front();
+        }
+    }
+
+    /// 直近ウィンドウのレイテンシのパーセンタイル（nearest-rank法）
+    ///
+    /// `p` は 0.0〜100.0 にクランプする。ウィンドウが空の場合は `None`。
+    pub(crate) fn percentile_latency_ms(&self, p: f64) -> Option<f32> {
+        let mut sorted: Vec<u64> = self.recent_latencies_ms.iter().copied().collect();
+        sorted.sort_unstable();
+        nearest_rank(&sorted, p).map(|ms| ms as f32)
+    }
+
+    /// 推論レイテンシの EMA に投入する値を返す
+    ///
+    /// 計測値が直近ウィンドウ（この計測値を除く）の `percentile` パーセンタイルを超える場合は
+    /// 外れ値として回数を記録し、パーセンタイル値にクリップする。ただし連続して
+    /// `LATENCY_OUTLIER_MAX_CONSECUTIVE` 回以上外れ値になった場合は実際の悪化とみなし、
+    /// クリップせずに返す。`percentile` が `0` 以下、またはサンプル不足の場合はそのまま返す。
+    pub(crate) fn clip_latency_outlier(&mut self, latency_ms: f64, percentile: f64) -> f64 {
+        if percentile <= 0.0 || !latency_ms.is_finite() {
+            return latency_ms;
+        }
+        let mut sorted: Vec<u64> = self.recent_latencies_ms.iter().copied().collect();
+        // リクエスト完了時に記録済みの当該計測値は分布から除く
+        if let Some(index) = sorted.iter().position(|ms| *ms as f64 == latency_ms) {
+            sorted.swap_remove(index);
+        }
+        if sorted.len() < LATENCY_OUTLIER_MIN_SAMPLES {
+            return latency_ms;
+        }
+        sorted.sort_unstable();
+        let Some(threshold) = nearest_rank(&sorted, percentile).map(|ms| ms as f64) else {
+            return latency_ms;
+        };
+        if latency_ms <= threshold {
+            self.consecutive_latency_outliers = 0;
+            return latency_ms;
+        }
+
+        self.latency_outliers = self.latency_outliers.saturating_add(1);
+        self.consecutive_latency_outliers = self.consecutive_latency_outliers.saturating_add(1);
+        if self.consecutive_latency_outliers >= LATENCY_OUTLIER_MAX_CONSECUTIVE {
+            latency_ms
+        } else {
+            threshold
+        }
+    }
+
+    /// TTFT計測値でEMAを更新（α=0.2、初回は計測値そのもの）
+    pub(crate) fn update_ttft(&mut self, ttft: StdDuration) {
+        let ttft_ms = ttft.as_secs_f64() * 1000.0;
+        self.ttft_ema_ms = Some(match self.ttft_ema_ms {
+            Some(prev) => TTFT_EMA_ALPHA * ttft_ms + (1.0 - TTFT_EMA_ALPHA) * prev,
+            None => ttft_ms,
+        });
+    }
+
     pub(crate) fn push_metrics(&mut self, metrics: HealthMetrics) {
         self.metrics_history.push_back(metrics);
         if self.metrics_history.len() > METRICS_HISTORY_CAPACITY {
@@ -220,6 +506,17 @@ impl EndpointLoadState {
     }
 }
 
+/// 昇順ソート済みの値のパーセンタイル（nearest-rank法、`p` は 0.0〜100.0 にクランプ）
+fn nearest_rank(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let p = if p.is_nan() { 0.0 } else { p.clamp(0.0, 100.0) };
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    let index = rank.saturating_sub(1).min(sorted.len() - 1);
+    Some(sorted[index])
+}
+
 /// エンドポイント/ノードのロードスナップショット
 ///
 /// エンドポイントの負荷スナップショット
@@ -270,6 +567,13 @@ pub struct EndpointLoadSnapshot {
     pub failed_requests: u64,
     /// 平均レスポンスタイム (ms)
     pub average_response_time_ms: Option<f32>,
+    /// 直近ウィンドウのp50レイテンシ (ms)
+    pub p50_latency_ms: Option<f32>,
+    /// 直近ウィンドウのp95レイテンシ (ms)
+    pub p95_latency_ms: Option<f32>,
+    /// ストリーミングのTTFT EMA (ms)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttft_ema_ms: Option<f32>,
     /// メトリクス最終更新時刻
     pub last_updated: Option<DateTime<Utc>>,
     /// メトリクスが鮮度閾値を超えているか
@@ -280,6 +584,34 @@ pub struct EndpointLoadSnapshot {
     pub total_output_tokens: u64,
     /// 総トークン累計
     pub total_tokens: u64,
+    /// 重み（目標値）
+    pub weight: u32,
+    /// 実効重み（ramp中は補間値、それ以外は `weight` と同値）
+    pub effective_weight: f64,
+    /// 重み ramp の残り秒数（ramp中のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_ramp_remaining_secs: Option<f64>,
+    /// 応答トークン数が通常範囲から外れ続けている（degraded相当の）モデル
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response_anomaly_models: Vec<String>,
+    /// サーキットブレーカーの状態（closed 以外のみ出力）
+    #[serde(default, skip_serializing_if = "CircuitState::is_closed")]
+    pub circuit_state: CircuitState,
+    /// アップストリームの 429 に応じた現在の送信許可レート（req/s、制限中のみ出力）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adaptive_rate_limit_rps: Option<f64>,
+    /// 動的同時実行上限（`LLMLB_DYNAMIC_CONCURRENCY_MAX` 有効時のみ出力）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency_limit: Option<u32>,
+    /// 動的同時実行上限の判定に使う直近レイテンシ（短期EMA、ms）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency_rtt_ms: Option<f64>,
+    /// 動的同時実行上限の判定に使う基準レイテンシ（長期EMA、ms）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency_baseline_rtt_ms: Option<f64>,
+    /// 推論レイテンシの外れ値と判定した回数（累計、EMA へはクリップして反映）
+    #[serde(default)]
+    pub latency_outliers: u64,
 }
 
 /// ノードのロードスナップショット（後方互換エイリアス）
@@ -496,6 +828,152 @@ mod tests {
         assert!((s.average_latency_ms().unwrap() - 200.0).abs() < 0.01);
     }
 
+    #[test]
+    fn update_ttft_applies_ema() {
+        let mut s = EndpointLoadState::default();
+        assert!(s.ttft_ema_ms.is_none());
+        s.update_ttft(StdDuration::from_millis(500));
+        assert!((s.ttft_ema_ms.unwrap() - 500.0).abs() < 1e-9);
+        // 0.2 * 1000 + 0.8 * 500 = 600
+        s.update_ttft(StdDuration::from_millis(1000));
+        assert!((s.ttft_ema_ms.unwrap() - 600.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn percentile_latency_ms_empty_window_returns_none() {
+        let s = EndpointLoadState::default();
+        assert!(s.percentile_latency_ms(50.0).is_none());
+        assert!(s.percentile_latency_ms(95.0).is_none());
+    }
+
+    #[test]
+    fn percentile_latency_ms_uses_recent_window() {
+        let mut s = EndpointLoadState::default();
+        for ms in 1..=100u64 {
+            s.push_latency(StdDuration::from_millis(ms));
+        }
+        assert_eq!(s.percentile_latency_ms(50.0), Some(50.0));
+        assert_eq!(s.percentile_latency_ms(95.0), Some(95.0));
+        assert_eq!(s.percentile_latency_ms(0.0), Some(1.0));
+        assert_eq!(s.percentile_latency_ms(100.0), Some(100.0));
+
+        // 古いサンプルはウィンドウから押し出される
+        for _ in 0..LATENCY_WINDOW_CAPACITY {
+            s.push_latency(StdDuration::from_millis(500));
+        }
+        assert_eq!(s.recent_latencies_ms.len(), LATENCY_WINDOW_CAPACITY);
+        assert_eq!(s.percentile_latency_ms(50.0), Some(500.0));
+    }
+
+    #[test]
+    fn latency_outliers_are_clipped_until_they_persist() {
+        let mut s = EndpointLoadState::default();
+        // サンプル不足の間は判定しない
+        s.push_latency(StdDuration::from_millis(5000));
+        assert_eq!(s.clip_latency_outlier(5000.0, 99.0), 5000.0);
+        s.recent_latencies_ms.clear();
+
+        for ms in 1..=100u64 {
+            s.push_latency(StdDuration::from_millis(ms));
+        }
+        // 記録済みの当該計測値を除いた分布の p99 にクリップする
+        s.push_latency(StdDuration::from_millis(5000));
+        assert_eq!(s.clip_latency_outlier(5000.0, 99.0), 99.0);
+        assert_eq!(s.clip_latency_outlier(50.0, 99.0), 50.0);
+        assert_eq!(s.consecutive_latency_outliers, 0);
+        assert_eq!(s.clip_latency_outlier(5000.0, 0.0), 5000.0);
+
+        // 外れ値が続く場合は実際の悪化とみなしてクリップしない
+        // （ウィンドウに無い計測値は除かないため、5000ms を含む分布の p99 = 100ms）
+        assert_eq!(s.clip_latency_outlier(4000.0, 99.0), 100.0);
+        assert_eq!(s.clip_latency_outlier(4000.0, 99.0), 100.0);
+        assert_eq!(s.clip_latency_outlier(4000.0, 99.0), 4000.0);
+        assert_eq!(s.latency_outliers, 4);
+    }
+
+    #[test]
+    fn circuit_breaker_opens_probes_and_recovers() {
+        let cooldown = StdDuration::from_secs(30);
+        let start = Instant::now();
+        let mut s = EndpointLoadState::default();
+
+        // 閾値未満の連続エラーでは closed のまま。成功でカウントはリセットされる
+        assert_eq!(
+            s.record_breaker_outcome(false, false, 3, cooldown, start),
+            None
+        );
+        assert_eq!(
+            s.record_breaker_outcome(false, false, 3, cooldown, start),
+            None
+        );
+        assert_eq!(
+            s.record_breaker_outcome(true, false, 3, cooldown, start),
+            None
+        );
+        assert_eq!(s.consecutive_errors, 0);
+        for _ in 0..2 {
+            assert_eq!(
+                s.record_breaker_outcome(false, false, 3, cooldown, start),
+                None
+            );
+        }
+        assert_eq!(
+            s.record_breaker_outcome(false, false, 3, cooldown, start),
+            Some(CircuitState::Open)
+        );
+        assert!(!s.accepts_routing(start));
+
+        // cooldown 経過後は half-open。試験リクエストは1件のみ
+        let later = start + cooldown;
+        assert_eq!(s.circuit_state(later), CircuitState::HalfOpen);
+        assert!(s.accepts_routing(later));
+        assert_eq!(s.claim_breaker_probe(later), Some(CircuitState::HalfOpen));
+        assert_eq!(s.claim_breaker_probe(later), None);
+        assert!(!s.accepts_routing(later));
+
+        // open 前から処理中だったリクエストの結果では状態を変えない
+        assert_eq!(
+            s.record_breaker_outcome(true, false, 3, cooldown, later),
+            None
+        );
+        assert_eq!(
+            s.record_breaker_outcome(false, false, 3, cooldown, later),
+            None
+        );
+        assert_eq!(s.circuit_state(later), CircuitState::HalfOpen);
+
+        // 取り消された試験リクエストの後は次の割り当てを試験リクエストにする
+        s.release_breaker_probe();
+        assert!(s.accepts_routing(later));
+        assert_eq!(s.claim_breaker_probe(later), Some(CircuitState::HalfOpen));
+
+        // 試験リクエストの失敗で再び open、成功で closed
+        assert_eq!(
+            s.record_breaker_outcome(false, true, 3, cooldown, later),
+            Some(CircuitState::Open)
+        );
+        let retry = later + cooldown;
+        assert_eq!(s.claim_breaker_probe(retry), Some(CircuitState::HalfOpen));
+        assert_eq!(
+            s.record_breaker_outcome(true, true, 3, cooldown, retry),
+            Some(CircuitState::Closed)
+        );
+        assert!(s.accepts_routing(retry));
+    }
+
+    #[test]
+    fn circuit_breaker_zero_threshold_never_opens() {
+        let now = Instant::now();
+        let mut s = EndpointLoadState::default();
+        for _ in 0..100 {
+            assert_eq!(
+                s.record_breaker_outcome(false, false, 0, StdDuration::from_secs(30), now),
+                None
+            );
+        }
+        assert_eq!(s.circuit_state(now), CircuitState::Closed);
+    }
+
     #[test]
     fn is_stale_no_metrics_returns_true() {
         let s = EndpointLoadState::default();
@@ -655,11 +1133,24 @@ mod tests {
             successful_requests: 90,
             failed_requests: 10,
             average_response_time_ms: Some(150.0),
+            p50_latency_ms: Some(120.0),
+            p95_latency_ms: None,
+            ttft_ema_ms: None,
             last_updated: None,
             is_stale: false,
             total_input_tokens: 1000,
             total_output_tokens: 2000,
             total_tokens: 3000,
+            weight: 1,
+            effective_weight: 1.0,
+            weight_ramp_remaining_secs: None,
+            response_anomaly_models: Vec::new(),
+            circuit_state: CircuitState::Closed,
+            adaptive_rate_limit_rps: None,
+            concurrency_limit: None,
+            concurrency_rtt_ms: None,
+            concurrency_baseline_rtt_ms: None,
+            latency_outliers: 0,
         };
         let json = serde_json::to_value(&snap).unwrap();
         // endpoint_id is renamed to node_id for API compatibility
@@ -667,5 +1158,43 @@ mod tests {
         assert!(json.get("endpoint_id").is_none());
         assert_eq!(json["machine_name"], "test-node");
         assert_eq!(json["active_requests"], 3);
+        assert_eq!(json["p50_latency_ms"], 120.0);
+        assert!(json["p95_latency_ms"].is_null());
+    }
+
+    #[test]
+    fn response_token_stats_flags_only_sustained_outliers() {
+        let mut stats = ResponseTokenStats::default();
+        for i in 0..RESPONSE_TOKENS_MIN_SAMPLES {
+            assert_eq!(stats.record(100 + i % 5, 3.0, 10), None);
+        }
+
+        // 単発の外れ値では degraded にならない
+        assert_eq!(stats.record(2000, 3.0, 10), None);
+        for _ in 0..4 {
+            assert_eq!(stats.record(100, 3.0, 10), None);
+        }
+
+        // 直近ウィンドウの過半数が外れ値になったら検知する
+        let transitions: Vec<_> = (0..6).filter_map(|_| stats.record(1, 3.0, 10)).collect();
+        assert_eq!(transitions, vec![true]);
+        assert!(stats.degraded);
+
+        // 通常の応答に戻れば回復する
+        let transitions: Vec<_> = (0..10).filter_map(|_| stats.record(100, 3.0, 10)).collect();
+        assert_eq!(transitions, vec![false]);
+        assert!(!stats.degraded);
+    }
+
+    #[test]
+    fn response_token_stats_zero_threshold_disables_detection() {
+        let mut stats = ResponseTokenStats::default();
+        for _ in 0..(RESPONSE_TOKENS_MIN_SAMPLES + 20) {
+            assert_eq!(stats.record(1, 0.0, 10), None);
+        }
+        for _ in 0..20 {
+            assert_eq!(stats.record(5000, 0.0, 10), None);
+        }
+        assert!(stats.recent_outliers.is_empty());
     }
 }
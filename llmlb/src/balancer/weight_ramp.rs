@@ -0,0 +1,83 @@
+//! エンドポイント重みの段階的変更（ramp）
+//!
+//! 重みを目標値まで線形補間で変化させ、急激なトラフィック移動を避ける。
+
+use std::time::{Duration, Instant};
+
+/// 進行中の重み ramp
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightRamp {
+    /// ramp開始時点の実効重み
+    pub from: f64,
+    /// 目標重み
+    pub to: u32,
+    /// ramp開始時刻
+    pub started_at: Instant,
+    /// ramp所要時間
+    pub duration: Duration,
+}
+
+impl WeightRamp {
+    /// `from` から `to` へ `duration` かけて変化する ramp を開始する
+    pub fn start(from: f64, to: u32, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            started_at: Instant::now(),
+            duration,
+        }
+    }
+
+    /// 指定時刻における実効重み
+    pub fn effective_at(&self, now: Instant) -> f64 {
+        let target = self.to as f64;
+        if self.duration.is_zero() {
+            return target;
+        }
+        let elapsed = now.saturating_duration_since(self.started_at);
+        let progress = (elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.0);
+        self.from + (target - self.from) * progress
+    }
+
+    /// 指定時刻で ramp が完了しているか
+    pub fn is_finished(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started_at) >= self.duration
+    }
+
+    /// 指定時刻における残り時間
+    pub fn remaining_at(&self, now: Instant) -> Duration {
+        self.duration
+            .saturating_sub(now.saturating_duration_since(self.started_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_weight_interpolates_linearly() {
+        let ramp = WeightRamp::start(10.0, 110, Duration::from_secs(100));
+        let start = ramp.started_at;
+
+        assert_eq!(ramp.effective_at(start), 10.0);
+        assert!((ramp.effective_at(start + Duration::from_secs(50)) - 60.0).abs() < 1e-9);
+        assert_eq!(ramp.effective_at(start + Duration::from_secs(200)), 110.0);
+        assert!(ramp.is_finished(start + Duration::from_secs(100)));
+        assert!(!ramp.is_finished(start + Duration::from_secs(99)));
+    }
+
+    #[test]
+    fn ramp_down_and_zero_duration() {
+        let ramp = WeightRamp::start(100.0, 0, Duration::from_secs(10));
+        let start = ramp.started_at;
+        assert!((ramp.effective_at(start + Duration::from_secs(5)) - 50.0).abs() < 1e-9);
+        assert_eq!(
+            ramp.remaining_at(start + Duration::from_secs(4)),
+            Duration::from_secs(6)
+        );
+
+        let instant = WeightRamp::start(100.0, 3, Duration::ZERO);
+        assert_eq!(instant.effective_at(instant.started_at), 3.0);
+    }
+}
@@ -48,6 +48,12 @@ async fn initialize_inner(
     info!("LLM Load Balancer v{}", env!("CARGO_PKG_VERSION"));
     maybe_raise_nofile_limit();
 
+    // プロンプトフィルタのルールを読み込む（有効時に読み込めない場合は起動しない）
+    if let Err(e) = crate::api::prompt_filter::init() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+
     // シングル実行制約: ロックを取得
     let server_lock = match ServerLock::acquire(port) {
         Ok(lock) => {
@@ -61,13 +67,7 @@ async fn initialize_inner(
     };
 
     // データベース接続プールを最初に作成（他コンポーネントが依存）
-    let database_url = crate::config::get_env_with_fallback("LLMLB_DATABASE_URL", "DATABASE_URL")
-        .unwrap_or_else(|| {
-            let home = std::env::var("HOME")
-                .or_else(|_| std::env::var("USERPROFILE"))
-                .expect("Failed to get home directory");
-            format!("sqlite:{}/.llmlb/load balancer.db", home)
-        });
+    let database_url = resolve_database_url();
 
     let db_pool = init_db_pool(&database_url)
         .await
@@ -89,6 +89,64 @@ async fn initialize_inner(
 
     // LoadManagerをEndpointRegistryで初期化
     let load_manager = balancer::LoadManager::new(endpoint_registry_arc.clone());
+    // ルーティングポリシーをDBから読み込み
+    match crate::db::routing_policies::list(&db_pool).await {
+        Ok(policies) => load_manager.set_routing_policies(policies).await,
+        Err(err) => tracing::warn!("Failed to load routing policies: {}", err),
+    }
+    // 容量予約をDBから読み込み
+    match crate::db::reservations::list(&db_pool).await {
+        Ok(reservations) => load_manager.set_reservations(reservations).await,
+        Err(err) => tracing::warn!("Failed to load capacity reservations: {}", err),
+    }
+    // A/Bテスト設定をDBから読み込み
+    match crate::db::experiments::list(&db_pool).await {
+        Ok(experiments) => load_manager.set_experiments(experiments).await,
+        Err(err) => tracing::warn!("Failed to load experiments: {}", err),
+    }
+    // シャドウトラフィック設定をDBから読み込み
+    match crate::db::shadow_targets::list(&db_pool).await {
+        Ok(targets) => load_manager.set_shadow_targets(targets).await,
+        Err(err) => tracing::warn!("Failed to load shadow targets: {}", err),
+    }
+    // ストリーミング出力レート上限をDBから読み込み
+    match crate::db::stream_rate_limits::list(&db_pool).await {
+        Ok(limits) => load_manager.set_stream_rate_limits(limits).await,
+        Err(err) => tracing::warn!("Failed to load stream rate limits: {}", err),
+    }
+    // モデル別レートリミット（RPM/TPM）をDBから読み込み
+    match crate::db::model_rate_limits::list(&db_pool).await {
+        Ok(limits) => crate::balancer::model_rate_limiter().set_limits(limits),
+        Err(err) => tracing::warn!("Failed to load model rate limits: {}", err),
+    }
+    // エンドポイントの運用状態（drain / disable / maintenance）を復元
+    // 復元したエンドポイントは再起動後もルーティング候補から除外されたままになる
+    let restored_operational_states =
+        match crate::db::endpoint_operational_states::list(&db_pool).await {
+            Ok(states) => {
+                for restored in &states {
+                    warn!(
+                        endpoint_id = %restored.endpoint_id,
+                        state = %restored.state,
+                        reason = restored.reason.as_deref().unwrap_or(""),
+                        updated_at = %restored.updated_at,
+                        "Restored endpoint operational state; endpoint stays out of routing"
+                    );
+                }
+                load_manager.set_operational_states(states.clone()).await;
+                states
+            }
+            Err(err) => {
+                tracing::error!("Failed to load endpoint operational states: {}", err);
+                Vec::new()
+            }
+        };
+    // 当月のエンドポイント別累計コストを復元（予算超過判定用）
+    let billing_month = crate::cloud_metrics::billing_month(chrono::Utc::now());
+    match crate::db::endpoints::list_endpoint_monthly_costs(&db_pool, &billing_month).await {
+        Ok(costs) => crate::cloud_metrics::seed_endpoint_monthly_costs(&billing_month, costs),
+        Err(err) => tracing::warn!("Failed to load endpoint monthly costs: {}", err),
+    }
     info!("Storage initialized successfully");
 
     // HTTPクライアント（接続プーリング有効）を作成
@@ -112,6 +170,9 @@ async fn initialize_inner(
         .with_interval(health_check_interval_secs);
     endpoint_health_checker.start();
 
+    // HTTPSエンドポイントの証明書期限監視を開始
+    health::CertExpiryMonitor::new(endpoint_registry.clone()).start();
+
     let load_balancer_mode =
         get_env_with_fallback_or("LLMLB_LOAD_BALANCER_MODE", "LOAD_BALANCER_MODE", "auto");
     info!("Load balancer mode: {}", load_balancer_mode);
@@ -160,6 +221,27 @@ async fn initialize_inner(
 
     crate::db::request_history::start_cleanup_task(request_history.clone());
     crate::db::endpoint_daily_stats::start_daily_stats_task(db_pool.clone());
+    let queue_history_interval = crate::config::queue_history_interval_secs();
+    if queue_history_interval > 0 {
+        crate::db::queue_history::start_queue_history_task(
+            db_pool.clone(),
+            load_manager.clone(),
+            std::time::Duration::from_secs(queue_history_interval),
+            std::time::Duration::from_secs(crate::config::queue_history_retention_hours() * 3600),
+        );
+    }
+
+    let tps_history_interval = crate::config::tps_history_interval_secs();
+    if tps_history_interval > 0 {
+        crate::db::tps_history::start_tps_history_task(
+            db_pool.clone(),
+            load_manager.clone(),
+            std::time::Duration::from_secs(tps_history_interval),
+        );
+    }
+
+    crate::balancer::session_affinity::start_session_cleanup_task(load_manager.clone());
+    crate::balancer::latency_baseline::start_latency_calibration_task(load_manager.clone());
 
     // 管理者が存在しない場合は作成
     auth::bootstrap::ensure_admin_exists(&db_pool)
@@ -169,11 +251,18 @@ async fn initialize_inner(
     // JWT秘密鍵を取得または生成（ファイル永続化対応）
     let jwt_secret =
         crate::jwt_secret::get_or_create_jwt_secret().expect("Failed to get or create JWT secret");
+    // ローテーション用キーリングを初期化（猶予期間中の旧シークレットを読み込む）
+    crate::jwt_secret::init_keyring(&jwt_secret).expect("Failed to initialize JWT keyring");
+    crate::jwt_secret::start_prune_task(std::time::Duration::from_secs(3600));
 
     info!("Authentication system initialized");
 
     // Self-update components
     let inference_gate = crate::inference_gate::InferenceGate::default();
+    inference_gate.set_model_limit_mode(crate::config::model_concurrency_mode());
+    for (model_id, max) in crate::config::model_max_concurrency() {
+        inference_gate.set_model_limit(&model_id, max);
+    }
     let shutdown = crate::shutdown::ShutdownController::default();
     let update_manager = crate::update::UpdateManager::new(
         http_client.clone(),
@@ -202,80 +291,117 @@ async fn initialize_inner(
     // 監査ログシステムの初期化 (SPEC-8301d106)
     let audit_log_storage =
         std::sync::Arc::new(crate::db::audit_log::AuditLogStorage::new(db_pool.clone()));
-    let audit_log_writer = crate::audit::writer::AuditLogWriter::new(
+    // ミラー（セカンダリDB）が指定されていれば両方へ書き込む
+    let audit_mirror_storage = match resolve_audit_mirror_path() {
+        Some(path) => match crate::db::audit_log::create_archive_pool(&path).await {
+            Ok(pool) => {
+                info!(path = %path, "Audit log mirror DB initialized");
+                Some(crate::db::audit_log::AuditLogStorage::new(pool))
+            }
+            Err(e) => {
+                warn!(path = %path, "Failed to initialize audit log mirror DB: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let audit_log_writer = crate::audit::writer::AuditLogWriter::with_mirror(
         crate::db::audit_log::AuditLogStorage::new(db_pool.clone()),
+        audit_mirror_storage.clone(),
         crate::audit::writer::AuditLogWriterConfig::default(),
     );
     info!("Audit log system initialized");
 
+    // 起動時に復元した運用状態を監査ログへ記録
+    if !restored_operational_states.is_empty() {
+        audit_log_writer.send(crate::audit::types::AuditLogEntry {
+            id: None,
+            timestamp: chrono::Utc::now(),
+            http_method: "SYSTEM".to_string(),
+            request_path: "/startup/endpoint-operational-states".to_string(),
+            status_code: 200,
+            actor_type: crate::audit::types::ActorType::Anonymous,
+            actor_id: Some("system".to_string()),
+            actor_username: None,
+            api_key_owner_id: None,
+            client_ip: None,
+            duration_ms: None,
+            input_tokens: None,
+            output_tokens: None,
+            total_tokens: None,
+            model_name: None,
+            endpoint_id: None,
+            detail: Some(
+                serde_json::json!({
+                    "event": "endpoint_operational_states_restored",
+                    "endpoints": restored_operational_states,
+                })
+                .to_string(),
+            ),
+            batch_id: None,
+            is_migrated: false,
+        });
+    }
+
     // 起動時ハッシュチェーン検証 (SPEC-8301d106)
+    // 前回検証済みのバッチ以降のみを差分検証する（改ざん検知時は全走査で箇所を特定）
     {
-        let storage_ref = &*audit_log_storage;
-        match crate::audit::hash_chain::verify_chain(storage_ref).await {
-            Ok(result) => {
-                if result.valid {
-                    info!(
-                        batches_checked = result.batches_checked,
-                        "Audit log hash chain verification passed"
-                    );
-                } else {
-                    warn!(
-                        tampered_batch = ?result.tampered_batch,
-                        message = ?result.message,
-                        "Audit log hash chain verification FAILED - tampering detected"
-                    );
-                }
-            }
-            Err(e) => {
-                warn!("Audit log hash chain verification error: {}", e);
-            }
+        let result = crate::audit::hash_chain::verify_chain_with_checkpoint(
+            &audit_log_storage,
+            crate::audit::hash_chain::VerificationMode::Incremental,
+        )
+        .await;
+        log_hash_chain_verification("Startup", &result);
+        // ミラーのハッシュチェーンはプライマリと独立しているため単独で全走査する
+        if let Some(ref mirror) = audit_mirror_storage {
+            let result = crate::audit::hash_chain::verify_chain(mirror).await;
+            log_hash_chain_verification("Startup mirror", &result);
         }
     }
 
-    // 24時間ごとの定期ハッシュチェーン検証タスク (SPEC-8301d106)
-    {
+    // 定期ハッシュチェーン検証タスク (SPEC-8301d106)
+    // 差分検証（既定24時間ごと）と全走査（既定週次）を別スケジュールで実行する
+    for (mode, period) in [
+        (
+            crate::audit::hash_chain::VerificationMode::Incremental,
+            crate::config::audit_verify_interval(),
+        ),
+        (
+            crate::audit::hash_chain::VerificationMode::Full,
+            crate::config::audit_full_verify_interval(),
+        ),
+    ] {
+        if period.is_zero() {
+            continue;
+        }
         let periodic_storage = audit_log_storage.clone();
+        let periodic_mirror = audit_mirror_storage
+            .clone()
+            .filter(|_| mode == crate::audit::hash_chain::VerificationMode::Full);
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_secs(24 * 60 * 60));
+            let mut interval = tokio::time::interval(period);
             // 最初のtickはスキップ（起動時検証は上で実施済み）
             interval.tick().await;
+            let label = match mode {
+                crate::audit::hash_chain::VerificationMode::Incremental => "Periodic incremental",
+                crate::audit::hash_chain::VerificationMode::Full => "Periodic full",
+            };
             loop {
                 interval.tick().await;
-                match crate::audit::hash_chain::verify_chain(&periodic_storage).await {
-                    Ok(result) => {
-                        if result.valid {
-                            info!(
-                                batches_checked = result.batches_checked,
-                                "Periodic audit log hash chain verification passed"
-                            );
-                        } else {
-                            warn!(
-                                tampered_batch = ?result.tampered_batch,
-                                message = ?result.message,
-                                "Periodic audit log hash chain verification FAILED"
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Periodic audit log hash chain verification error: {}", e);
-                    }
+                let result =
+                    crate::audit::hash_chain::verify_chain_with_checkpoint(&periodic_storage, mode)
+                        .await;
+                log_hash_chain_verification(label, &result);
+                if let Some(ref mirror) = periodic_mirror {
+                    let result = crate::audit::hash_chain::verify_chain(mirror).await;
+                    log_hash_chain_verification("Periodic mirror", &result);
                 }
             }
         });
     }
 
     // アーカイブDBプールの初期化 (SPEC-8301d106)
-    let archive_path = std::env::var("LLMLB_AUDIT_ARCHIVE_PATH").unwrap_or_else(|_| {
-        let db_path =
-            std::env::var("LLMLB_DB_PATH").unwrap_or_else(|_| "load_balancer.db".to_string());
-        let parent = std::path::Path::new(&db_path)
-            .parent()
-            .unwrap_or(std::path::Path::new("."));
-        parent
-            .join("audit_archive.db")
-            .to_string_lossy()
-            .to_string()
-    });
+    let archive_path = resolve_audit_archive_path();
     let audit_archive_pool = match crate::db::audit_log::create_archive_pool(&archive_path).await {
         Ok(pool) => {
             info!(path = %archive_path, "Audit archive DB initialized");
@@ -317,6 +443,24 @@ async fn initialize_inner(
         });
     }
 
+    // チャット→補完変換アダプタの対象モデルを読み込む
+    crate::api::chat_adapter::init();
+
+    // 使用済み nonce の定期クリーンアップ（nonce 検証が有効な場合のみ）
+    crate::auth::nonce::spawn_nonce_cleanup_task();
+
+    let event_bus = crate::events::create_shared_event_bus();
+    update_manager.set_event_bus(event_bus.clone());
+    load_manager.set_event_bus(event_bus.clone());
+    load_manager.set_audit_log_writer(audit_log_writer.clone());
+    endpoint_registry.set_event_bus(event_bus.clone());
+    endpoint_registry.set_audit_log_writer(audit_log_writer.clone());
+    crate::events::snapshot_diff::spawn_snapshot_diff_task(
+        load_manager.clone(),
+        event_bus.clone(),
+        crate::events::snapshot_diff::SNAPSHOT_DIFF_INTERVAL,
+    );
+
     let state = AppState {
         load_manager,
         request_history,
@@ -324,11 +468,7 @@ async fn initialize_inner(
         jwt_secret,
         http_client,
         queue_config: crate::config::QueueConfig::from_env(),
-        event_bus: {
-            let bus = crate::events::create_shared_event_bus();
-            update_manager.set_event_bus(bus.clone());
-            bus
-        },
+        event_bus,
         endpoint_registry,
         inference_gate,
         shutdown: shutdown.clone(),
@@ -344,6 +484,66 @@ async fn initialize_inner(
     }
 }
 
+/// ハッシュチェーン検証結果をログに出力する
+fn log_hash_chain_verification(
+    label: &str,
+    result: &crate::common::error::RouterResult<crate::audit::hash_chain::ChainVerificationResult>,
+) {
+    match result {
+        Ok(result) if result.valid => {
+            info!(
+                batches_checked = result.batches_checked,
+                "{} audit log hash chain verification passed", label
+            );
+        }
+        Ok(result) => {
+            warn!(
+                tampered_batch = ?result.tampered_batch,
+                message = ?result.message,
+                "{} audit log hash chain verification FAILED - tampering detected", label
+            );
+        }
+        Err(e) => {
+            warn!("{} audit log hash chain verification error: {}", label, e);
+        }
+    }
+}
+
+/// 環境変数からデータベースURLを解決する（未設定時は `~/.llmlb/load balancer.db`）
+pub fn resolve_database_url() -> String {
+    crate::config::get_env_with_fallback("LLMLB_DATABASE_URL", "DATABASE_URL").unwrap_or_else(
+        || {
+            let home = std::env::var("HOME")
+                .or_else(|_| std::env::var("USERPROFILE"))
+                .expect("Failed to get home directory");
+            format!("sqlite:{}/.llmlb/load balancer.db", home)
+        },
+    )
+}
+
+/// 環境変数から監査ログアーカイブDBのパスを解決する (SPEC-8301d106)
+pub fn resolve_audit_archive_path() -> String {
+    std::env::var("LLMLB_AUDIT_ARCHIVE_PATH").unwrap_or_else(|_| {
+        let db_path =
+            std::env::var("LLMLB_DB_PATH").unwrap_or_else(|_| "load_balancer.db".to_string());
+        let parent = std::path::Path::new(&db_path)
+            .parent()
+            .unwrap_or(std::path::Path::new("."));
+        parent
+            .join("audit_archive.db")
+            .to_string_lossy()
+            .to_string()
+    })
+}
+
+/// 環境変数から監査ログミラーDBのパスを解決する（未設定・空の場合は `None`）
+pub fn resolve_audit_mirror_path() -> Option<String> {
+    std::env::var("LLMLB_AUDIT_MIRROR_PATH")
+        .ok()
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+}
+
 /// SQLite接続プールを初期化する
 pub async fn init_db_pool(database_url: &str) -> sqlx::Result<sqlx::SqlitePool> {
     // SQLiteファイルはディレクトリが存在しないと作成できないため、先に作成しておく
@@ -430,9 +630,7 @@ async fn redetect_all_endpoints(
     registry: &crate::registry::endpoints::EndpointRegistry,
     http_client: &reqwest::Client,
 ) {
-    use crate::detection::detect_endpoint_type_with_client;
-
-    const STARTUP_REDETECTION_TIMEOUT_SECS: u64 = 10;
+    use crate::detection::{redetect_endpoint, RedetectError, REDETECTION_TIMEOUT};
 
     let endpoints = registry.list().await;
     let total = endpoints.len();
@@ -450,47 +648,42 @@ async fn redetect_all_endpoints(
     let mut failed: usize = 0;
     let mut updated: usize = 0;
 
-    for ep in &endpoints {
-        match tokio::time::timeout(
-            std::time::Duration::from_secs(STARTUP_REDETECTION_TIMEOUT_SECS),
-            detect_endpoint_type_with_client(http_client, &ep.base_url, ep.api_key.as_deref()),
-        )
-        .await
-        {
-            Ok(Ok(result)) => {
-                if result.endpoint_type == ep.endpoint_type {
+    // 依存先を先に再判別する（循環依存がある場合は警告して残りをまとめて処理）
+    let ordered: Vec<_> = crate::registry::startup_order::startup_waves(endpoints)
+        .into_iter()
+        .flatten()
+        .collect();
+    for ep in &ordered {
+        match redetect_endpoint(registry, http_client, ep, REDETECTION_TIMEOUT, false).await {
+            Ok(outcome) => {
+                if !outcome.changed() {
                     continue;
                 }
-                if registry
-                    .update_endpoint_type(ep.id, result.endpoint_type)
-                    .await
-                    .is_ok()
-                {
-                    info!(
-                        endpoint_id = %ep.id,
-                        name = %ep.name,
-                        old_type = ?ep.endpoint_type,
-                        new_type = ?result.endpoint_type,
-                        "Endpoint type changed during re-detection"
-                    );
-                    updated += 1;
-                }
+                info!(
+                    endpoint_id = %ep.id,
+                    name = %ep.name,
+                    old_type = ?outcome.old_type,
+                    new_type = ?outcome.new_type,
+                    "Endpoint type changed during re-detection"
+                );
+                updated += 1;
             }
-            Ok(Err(err)) => {
+            Err(RedetectError::Timeout(timeout)) => {
                 warn!(
                     endpoint_id = %ep.id,
                     name = %ep.name,
-                    error = %err,
-                    "Endpoint type re-detection failed on startup; keeping existing configuration"
+                    timeout_secs = timeout.as_secs(),
+                    "Endpoint type re-detection timed out on startup; keeping existing configuration"
                 );
                 failed += 1;
             }
-            Err(_) => {
+            Err(RedetectError::Database(_)) => {}
+            Err(err) => {
                 warn!(
                     endpoint_id = %ep.id,
                     name = %ep.name,
-                    timeout_secs = STARTUP_REDETECTION_TIMEOUT_SECS,
-                    "Endpoint type re-detection timed out on startup; keeping existing configuration"
+                    error = %err,
+                    "Endpoint type re-detection failed on startup; keeping existing configuration"
                 );
                 failed += 1;
             }
@@ -533,6 +726,12 @@ fn spawn_startup_model_backfill(
             {
                 Ok(result) => match registry.refresh_model_mappings(ep.id).await {
                     Ok(()) => {
+                        registry.notify_models_changed(
+                            ep.id,
+                            &result.added_models,
+                            &result.removed_models,
+                            &result.updated_models,
+                        );
                         succeeded += 1;
                         info!(
                             endpoint_id = %ep.id,
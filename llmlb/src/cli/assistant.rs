@@ -135,12 +135,47 @@ pub enum GuideCategory {
     Dashboard,
 }
 
+/// CLIから llmlb API を呼ぶ際の認証情報
+///
+/// `LLMLB_API_KEY`（/v1/*）、`LLMLB_ADMIN_API_KEY`（/api/*）、`LLMLB_JWT_TOKEN` から読み込む。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CliCredentials {
+    pub(crate) api_key: Option<String>,
+    pub(crate) admin_api_key: Option<String>,
+    pub(crate) jwt_token: Option<String>,
+}
+
+impl CliCredentials {
+    /// 環境変数から読み込む（空文字は未設定扱い）
+    pub(crate) fn from_env() -> Self {
+        let read = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Self {
+            api_key: read("LLMLB_API_KEY"),
+            admin_api_key: read("LLMLB_ADMIN_API_KEY"),
+            jwt_token: read("LLMLB_JWT_TOKEN"),
+        }
+    }
+
+    /// 管理API（/api/*）に付与するヘッダ（管理者APIキー > JWT > APIキーの順）
+    pub(crate) fn management_header(&self) -> Option<(&'static str, String)> {
+        self.admin_api_key
+            .as_ref()
+            .map(|key| ("X-API-Key", key.clone()))
+            .or_else(|| self.jwt_header())
+            .or_else(|| self.api_key.as_ref().map(|key| ("X-API-Key", key.clone())))
+    }
+
+    fn jwt_header(&self) -> Option<(&'static str, String)> {
+        self.jwt_token
+            .as_ref()
+            .map(|token| ("Authorization", format!("Bearer {token}")))
+    }
+}
+
 #[derive(Debug, Clone)]
 struct AssistantConfig {
     router_url: Url,
-    api_key: Option<String>,
-    admin_api_key: Option<String>,
-    jwt_token: Option<String>,
+    credentials: CliCredentials,
     openapi_path: Option<PathBuf>,
     default_timeout: u64,
 }
@@ -160,15 +195,7 @@ impl AssistantConfig {
 
         Ok(Self {
             router_url,
-            api_key: std::env::var("LLMLB_API_KEY")
-                .ok()
-                .filter(|v| !v.is_empty()),
-            admin_api_key: std::env::var("LLMLB_ADMIN_API_KEY")
-                .ok()
-                .filter(|v| !v.is_empty()),
-            jwt_token: std::env::var("LLMLB_JWT_TOKEN")
-                .ok()
-                .filter(|v| !v.is_empty()),
+            credentials: CliCredentials::from_env(),
             openapi_path: std::env::var("LLMLB_OPENAPI_PATH").ok().map(PathBuf::from),
             default_timeout,
         })
@@ -482,42 +509,21 @@ fn inject_auth_headers(command: &str, url: &str, config: &AssistantConfig) -> St
     let is_management_endpoint = pathname.starts_with("/api/");
     let is_inference_endpoint = pathname.starts_with("/v1/");
 
-    let auth_header = if is_auth_endpoint {
-        config
-            .jwt_token
-            .as_ref()
-            .map(|token| format!("-H \"Authorization: Bearer {token}\""))
+    let credentials = &config.credentials;
+    let header = if is_auth_endpoint {
+        credentials.jwt_header()
     } else if is_management_endpoint {
-        config
-            .admin_api_key
-            .as_ref()
-            .map(|key| format!("-H \"X-API-Key: {key}\""))
-            .or_else(|| {
-                config
-                    .jwt_token
-                    .as_ref()
-                    .map(|token| format!("-H \"Authorization: Bearer {token}\""))
-            })
-            .or_else(|| {
-                config
-                    .api_key
-                    .as_ref()
-                    .map(|key| format!("-H \"X-API-Key: {key}\""))
-            })
+        credentials.management_header()
     } else if is_inference_endpoint {
-        config
+        credentials
             .api_key
             .as_ref()
-            .map(|key| format!("-H \"X-API-Key: {key}\""))
-            .or_else(|| {
-                config
-                    .admin_api_key
-                    .as_ref()
-                    .map(|key| format!("-H \"X-API-Key: {key}\""))
-            })
+            .or(credentials.admin_api_key.as_ref())
+            .map(|key| ("X-API-Key", key.clone()))
     } else {
         None
     };
+    let auth_header = header.map(|(name, value)| format!("-H \"{name}: {value}\""));
 
     match auth_header {
         Some(header) => command.replacen("curl ", &format!("curl {header} "), 1),
@@ -871,9 +877,7 @@ mod tests {
     fn test_config(overrides: impl FnOnce(&mut AssistantConfig)) -> AssistantConfig {
         let mut config = AssistantConfig {
             router_url: Url::parse(DEFAULT_ROUTER_URL).expect("valid url"),
-            api_key: None,
-            admin_api_key: None,
-            jwt_token: None,
+            credentials: CliCredentials::default(),
             openapi_path: None,
             default_timeout: DEFAULT_TIMEOUT_SECS,
         };
@@ -937,7 +941,7 @@ mod tests {
 
     #[test]
     fn inject_auth_for_v1_uses_api_key() {
-        let cfg = test_config(|c| c.api_key = Some("sk_api".to_string()));
+        let cfg = test_config(|c| c.credentials.api_key = Some("sk_api".to_string()));
         let out = inject_auth_headers(
             "curl http://localhost:32768/v1/models",
             "http://localhost:32768/v1/models",
@@ -951,7 +955,7 @@ mod tests {
 
     #[test]
     fn inject_auth_for_v1_falls_back_to_admin_key() {
-        let cfg = test_config(|c| c.admin_api_key = Some("sk_admin".to_string()));
+        let cfg = test_config(|c| c.credentials.admin_api_key = Some("sk_admin".to_string()));
         let out = inject_auth_headers(
             "curl http://localhost:32768/v1/models",
             "http://localhost:32768/v1/models",
@@ -965,7 +969,7 @@ mod tests {
 
     #[test]
     fn inject_auth_for_api_uses_admin_key() {
-        let cfg = test_config(|c| c.admin_api_key = Some("sk_admin".to_string()));
+        let cfg = test_config(|c| c.credentials.admin_api_key = Some("sk_admin".to_string()));
         let out = inject_auth_headers(
             "curl http://localhost:32768/api/dashboard/overview",
             "http://localhost:32768/api/dashboard/overview",
@@ -979,7 +983,7 @@ mod tests {
 
     #[test]
     fn inject_auth_for_api_falls_back_to_jwt() {
-        let cfg = test_config(|c| c.jwt_token = Some("jwt_legacy".to_string()));
+        let cfg = test_config(|c| c.credentials.jwt_token = Some("jwt_legacy".to_string()));
         let out = inject_auth_headers(
             "curl http://localhost:32768/api/dashboard/overview",
             "http://localhost:32768/api/dashboard/overview",
@@ -994,8 +998,8 @@ mod tests {
     #[test]
     fn inject_auth_for_api_auth_prefers_jwt() {
         let cfg = test_config(|c| {
-            c.admin_api_key = Some("sk_admin".to_string());
-            c.jwt_token = Some("jwt_legacy".to_string());
+            c.credentials.admin_api_key = Some("sk_admin".to_string());
+            c.credentials.jwt_token = Some("jwt_legacy".to_string());
         });
         let out = inject_auth_headers(
             "curl http://localhost:32768/api/auth/me",
@@ -1010,7 +1014,7 @@ mod tests {
 
     #[test]
     fn inject_auth_skips_if_header_exists() {
-        let cfg = test_config(|c| c.api_key = Some("sk_api".to_string()));
+        let cfg = test_config(|c| c.credentials.api_key = Some("sk_api".to_string()));
         let cmd = "curl -H \"X-API-Key: already\" http://localhost:32768/v1/models";
         let out = inject_auth_headers(cmd, "http://localhost:32768/v1/models", &cfg);
         assert_eq!(out, cmd);
@@ -1291,8 +1295,8 @@ paths: {}
     #[test]
     fn inject_auth_skips_for_non_api_non_v1_path() {
         let cfg = test_config(|c| {
-            c.api_key = Some("sk_api".to_string());
-            c.admin_api_key = Some("sk_admin".to_string());
+            c.credentials.api_key = Some("sk_api".to_string());
+            c.credentials.admin_api_key = Some("sk_admin".to_string());
         });
         let out = inject_auth_headers(
             "curl http://localhost:32768/health",
@@ -1305,7 +1309,7 @@ paths: {}
 
     #[test]
     fn inject_auth_for_api_falls_back_to_api_key() {
-        let cfg = test_config(|c| c.api_key = Some("sk_api".to_string()));
+        let cfg = test_config(|c| c.credentials.api_key = Some("sk_api".to_string()));
         let out = inject_auth_headers(
             "curl http://localhost:32768/api/endpoints",
             "http://localhost:32768/api/endpoints",
@@ -1320,7 +1324,7 @@ paths: {}
 
     #[test]
     fn inject_auth_skips_if_authorization_header_present() {
-        let cfg = test_config(|c| c.api_key = Some("sk_api".to_string()));
+        let cfg = test_config(|c| c.credentials.api_key = Some("sk_api".to_string()));
         let cmd = "curl -H \"Authorization: Bearer my_token\" http://localhost:32768/v1/models";
         let out = inject_auth_headers(cmd, "http://localhost:32768/v1/models", &cfg);
         assert_eq!(out, cmd);
@@ -1328,7 +1332,7 @@ paths: {}
 
     #[test]
     fn inject_auth_skips_if_node_token_header_present() {
-        let cfg = test_config(|c| c.api_key = Some("sk_api".to_string()));
+        let cfg = test_config(|c| c.credentials.api_key = Some("sk_api".to_string()));
         let cmd = "curl -H \"X-Node-Token: nt_abc\" http://localhost:32768/v1/models";
         let out = inject_auth_headers(cmd, "http://localhost:32768/v1/models", &cfg);
         assert_eq!(out, cmd);
@@ -0,0 +1,406 @@
+//! audit subcommand
+//!
+//! Exports audit log entries from the main DB and the archive DB to a file.
+
+use crate::audit::export::{
+    parse_date_or_datetime, ExportRecord, ExportSource, CSV_COLUMNS, EXPORT_PAGE_SIZE,
+};
+use crate::audit::hash_chain::{compute_record_hash, verify_chain, ChainVerificationResult};
+use crate::audit::types::{AuditBatchHash, AuditLogEntry, AuditLogFilter};
+use crate::db::audit_log::AuditLogStorage;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand, ValueEnum};
+use futures::TryStreamExt;
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::collections::BTreeSet;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Arguments for the audit subcommand
+#[derive(Args, Debug, Clone)]
+pub struct AuditArgs {
+    /// Audit subcommand
+    #[command(subcommand)]
+    pub command: AuditCommand,
+}
+
+/// Audit subcommands
+#[derive(Subcommand, Debug, Clone)]
+pub enum AuditCommand {
+    /// Export audit log entries (main DB and archive DB) to a file
+    Export(ExportArgs),
+}
+
+/// Output format for `audit export`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+/// Arguments for `audit export`
+#[derive(Args, Debug, Clone)]
+pub struct ExportArgs {
+    /// Start of the time range, inclusive (RFC 3339 or YYYY-MM-DD)
+    #[arg(long, value_parser = parse_from)]
+    pub from: Option<DateTime<Utc>>,
+
+    /// End of the time range, inclusive (RFC 3339 or YYYY-MM-DD)
+    #[arg(long, value_parser = parse_to)]
+    pub to: Option<DateTime<Utc>>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "jsonl")]
+    pub format: ExportFormat,
+
+    /// Output file path
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Add per-record hashes and write hash chain info to `<out>.chain.json`
+    #[arg(long, default_value_t = false)]
+    pub with_hash_chain: bool,
+
+    /// Do not read the archive DB
+    #[arg(long, default_value_t = false)]
+    pub no_archive: bool,
+}
+
+/// `<out>.chain.json` の内容
+#[derive(Serialize)]
+struct HashChainReport {
+    /// メインDBのハッシュチェーン検証結果
+    verification: ChainVerificationResult,
+    /// エクスポートしたエントリが属するバッチのハッシュ（連番順）
+    batches: Vec<AuditBatchHash>,
+}
+
+/// エクスポート結果
+#[derive(Debug, Default)]
+struct ExportSummary {
+    entries: u64,
+    batch_ids: BTreeSet<i64>,
+}
+
+enum RecordWriter<W: Write> {
+    Jsonl(W),
+    Csv(csv::Writer<W>),
+}
+
+impl<W: Write> RecordWriter<W> {
+    fn new(format: ExportFormat, with_hash: bool, writer: W) -> Result<Self> {
+        match format {
+            ExportFormat::Jsonl => Ok(Self::Jsonl(writer)),
+            ExportFormat::Csv => {
+                let mut csv = csv::Writer::from_writer(writer);
+                let mut header: Vec<&str> = CSV_COLUMNS.to_vec();
+                if with_hash {
+                    header.push("record_hash");
+                }
+                csv.write_record(&header)?;
+                Ok(Self::Csv(csv))
+            }
+        }
+    }
+
+    fn write(
+        &mut self,
+        entry: &AuditLogEntry,
+        source: ExportSource,
+        record_hash: Option<String>,
+    ) -> Result<()> {
+        let record = ExportRecord {
+            entry,
+            source,
+            record_hash,
+            chain_verified: None,
+        };
+        match self {
+            Self::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, &record)?;
+                writer.write_all(b"\n")?;
+            }
+            Self::Csv(writer) => writer.write_record(record.csv_row())?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Jsonl(mut writer) => writer.flush()?,
+            Self::Csv(mut writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
+fn parse_from(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_date_or_datetime(value, false)
+}
+
+fn parse_to(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_date_or_datetime(value, true)
+}
+
+/// Execute the audit command
+pub async fn execute(command: &AuditCommand) -> Result<()> {
+    match command {
+        AuditCommand::Export(args) => execute_export(args).await,
+    }
+}
+
+async fn open_read_only(options: SqliteConnectOptions) -> Result<SqlitePool> {
+    Ok(SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options.read_only(true))
+        .await?)
+}
+
+async fn execute_export(args: &ExportArgs) -> Result<()> {
+    let database_url = crate::bootstrap::resolve_database_url();
+    let options = SqliteConnectOptions::from_str(&database_url)
+        .with_context(|| format!("invalid database URL: {}", database_url))?;
+    let pool = open_read_only(options)
+        .await
+        .with_context(|| format!("failed to open database: {}", database_url))?;
+    let storage = AuditLogStorage::new(pool);
+
+    let archive_pool = if args.no_archive {
+        None
+    } else {
+        let archive_path = crate::bootstrap::resolve_audit_archive_path();
+        if Path::new(&archive_path).exists() {
+            let options = SqliteConnectOptions::new().filename(&archive_path);
+            Some(
+                open_read_only(options)
+                    .await
+                    .with_context(|| format!("failed to open archive DB: {}", archive_path))?,
+            )
+        } else {
+            None
+        }
+    };
+
+    let file = std::fs::File::create(&args.out)
+        .with_context(|| format!("failed to create {}", args.out.display()))?;
+    let summary =
+        export_entries(&storage, archive_pool.as_ref(), args, BufWriter::new(file)).await?;
+    println!(
+        "Exported {} audit log entries to {}",
+        summary.entries,
+        args.out.display()
+    );
+
+    if args.with_hash_chain {
+        let chain_path = chain_report_path(&args.out);
+        let report = build_chain_report(&storage, archive_pool.as_ref(), &summary).await?;
+        let file = std::fs::File::create(&chain_path)
+            .with_context(|| format!("failed to create {}", chain_path.display()))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &report)?;
+        println!(
+            "Hash chain info written to {} (valid: {})",
+            chain_path.display(),
+            report.verification.valid
+        );
+    }
+    Ok(())
+}
+
+/// アーカイブDB → メインDBの順に、ID昇順でページ単位に書き出す
+async fn export_entries<W: Write>(
+    storage: &AuditLogStorage,
+    archive_pool: Option<&SqlitePool>,
+    args: &ExportArgs,
+    writer: W,
+) -> Result<ExportSummary> {
+    let filter = AuditLogFilter {
+        time_from: args.from,
+        time_to: args.to,
+        ..Default::default()
+    };
+    let mut out = RecordWriter::new(args.format, args.with_hash_chain, writer)?;
+    let mut summary = ExportSummary::default();
+
+    let mut pages =
+        std::pin::pin!(storage.export_stream(filter, archive_pool.cloned(), EXPORT_PAGE_SIZE));
+    while let Some((source, page)) = pages.try_next().await? {
+        for entry in &page {
+            let record_hash = args.with_hash_chain.then(|| compute_record_hash(entry));
+            out.write(entry, source, record_hash)?;
+            if let Some(batch_id) = entry.batch_id.filter(|_| args.with_hash_chain) {
+                summary.batch_ids.insert(batch_id);
+            }
+            summary.entries += 1;
+        }
+    }
+
+    out.finish()?;
+    Ok(summary)
+}
+
+async fn build_chain_report(
+    storage: &AuditLogStorage,
+    archive_pool: Option<&SqlitePool>,
+    summary: &ExportSummary,
+) -> Result<HashChainReport> {
+    let mut batches = match archive_pool {
+        Some(pool) => storage.get_archive_batch_hashes(pool).await?,
+        None => Vec::new(),
+    };
+    batches.extend(storage.get_all_batch_hashes().await?);
+    batches.retain(|batch| batch.id.is_some_and(|id| summary.batch_ids.contains(&id)));
+    batches.sort_by_key(|batch| batch.sequence_number);
+    batches.dedup_by_key(|batch| batch.sequence_number);
+
+    Ok(HashChainReport {
+        verification: verify_chain(storage).await?,
+        batches,
+    })
+}
+
+fn chain_report_path(out: &Path) -> PathBuf {
+    let mut name = out.as_os_str().to_os_string();
+    name.push(".chain.json");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::types::ActorType;
+
+    fn make_entry(path: &str, timestamp: DateTime<Utc>) -> AuditLogEntry {
+        AuditLogEntry {
+            id: None,
+            timestamp,
+            http_method: "GET".to_string(),
+            request_path: path.to_string(),
+            status_code: 200,
+            actor_type: ActorType::User,
+            actor_id: Some("user-1".to_string()),
+            actor_username: Some("admin".to_string()),
+            api_key_owner_id: None,
+            client_ip: Some("127.0.0.1".to_string()),
+            duration_ms: Some(5),
+            input_tokens: None,
+            output_tokens: None,
+            total_tokens: None,
+            model_name: None,
+            endpoint_id: None,
+            detail: Some("a,\"quoted\" detail".to_string()),
+            batch_id: None,
+            is_migrated: false,
+        }
+    }
+
+    fn export_args(format: ExportFormat, with_hash_chain: bool) -> ExportArgs {
+        ExportArgs {
+            from: None,
+            to: None,
+            format,
+            out: PathBuf::from("audit.out"),
+            with_hash_chain,
+            no_archive: false,
+        }
+    }
+
+    #[test]
+    fn parses_date_bounds() {
+        let from = parse_from("2026-01-02").unwrap();
+        assert_eq!(from.to_rfc3339(), "2026-01-02T00:00:00+00:00");
+        let to = parse_to("2026-01-02").unwrap();
+        assert_eq!(to.to_rfc3339(), "2026-01-02T23:59:59.999+00:00");
+        let exact = parse_to("2026-01-02T10:00:00+09:00").unwrap();
+        assert_eq!(exact.to_rfc3339(), "2026-01-02T01:00:00+00:00");
+        assert!(parse_from("yesterday").is_err());
+    }
+
+    #[test]
+    fn chain_report_path_appends_suffix() {
+        assert_eq!(
+            chain_report_path(Path::new("/tmp/audit.jsonl")),
+            PathBuf::from("/tmp/audit.jsonl.chain.json")
+        );
+    }
+
+    #[tokio::test]
+    async fn exports_archive_then_main_as_jsonl_with_hashes() {
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let storage = AuditLogStorage::new(pool);
+        let archive_pool = crate::db::audit_log::create_archive_pool(":memory:")
+            .await
+            .unwrap();
+
+        let now = Utc::now();
+        storage
+            .insert_batch(&[make_entry("/api/old", now - chrono::Duration::days(100))])
+            .await
+            .unwrap();
+        storage
+            .archive_old_entries(90, &archive_pool)
+            .await
+            .unwrap();
+        storage
+            .insert_batch(&[make_entry("/api/new-1", now), make_entry("/api/new-2", now)])
+            .await
+            .unwrap();
+
+        let mut buf = Vec::new();
+        let summary = export_entries(
+            &storage,
+            Some(&archive_pool),
+            &export_args(ExportFormat::Jsonl, true),
+            &mut buf,
+        )
+        .await
+        .unwrap();
+        assert_eq!(summary.entries, 3);
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["request_path"], "/api/old");
+        assert_eq!(lines[0]["source"], "archive");
+        assert_eq!(lines[2]["source"], "main");
+        assert_eq!(lines[1]["record_hash"].as_str().unwrap().len(), 64);
+    }
+
+    #[tokio::test]
+    async fn exports_csv_filtered_by_time_range() {
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let storage = AuditLogStorage::new(pool);
+
+        let now = Utc::now();
+        storage
+            .insert_batch(&[
+                make_entry("/api/too-old", now - chrono::Duration::days(10)),
+                make_entry("/api/in-range", now - chrono::Duration::days(1)),
+            ])
+            .await
+            .unwrap();
+
+        let mut args = export_args(ExportFormat::Csv, false);
+        args.from = Some(now - chrono::Duration::days(2));
+        let mut buf = Vec::new();
+        let summary = export_entries(&storage, None, &args, &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(summary.entries, 1);
+
+        let mut reader = csv::Reader::from_reader(buf.as_slice());
+        assert_eq!(reader.headers().unwrap().len(), CSV_COLUMNS.len());
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(&rows[0][3], "/api/in-range");
+        assert_eq!(&rows[0][16], "a,\"quoted\" detail");
+    }
+}
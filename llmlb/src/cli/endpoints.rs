@@ -0,0 +1,76 @@
+//! endpoints subcommand
+//!
+//! Maintenance commands for registered endpoints.
+//!
+//! `dedupe` はDBを直接更新するため、サーバー停止中に実行するか、実行後にサーバーを
+//! 再起動してレジストリのキャッシュへ反映すること。
+
+use crate::registry::endpoints::EndpointRegistry;
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+
+/// Arguments for the endpoints subcommand
+#[derive(Args, Debug, Clone)]
+pub struct EndpointsArgs {
+    /// Endpoints subcommand
+    #[command(subcommand)]
+    pub command: EndpointsCommand,
+}
+
+/// Endpoints subcommands
+#[derive(Subcommand, Debug, Clone)]
+pub enum EndpointsCommand {
+    /// Merge endpoints registered with the same (normalized) base URL
+    Dedupe(DedupeArgs),
+}
+
+/// Arguments for `endpoints dedupe`
+#[derive(Args, Debug, Clone)]
+pub struct DedupeArgs {
+    /// Show what would be merged without changing the database
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Execute the endpoints command
+pub async fn execute(command: &EndpointsCommand) -> Result<()> {
+    match command {
+        EndpointsCommand::Dedupe(args) => execute_dedupe(args).await,
+    }
+}
+
+async fn execute_dedupe(args: &DedupeArgs) -> Result<()> {
+    let database_url = crate::bootstrap::resolve_database_url();
+    let pool = crate::db::migrations::initialize_database(&database_url)
+        .await
+        .with_context(|| format!("failed to open database: {}", database_url))?;
+    let registry = EndpointRegistry::new(pool)
+        .await
+        .context("failed to load endpoints")?;
+
+    let results = registry.dedupe(args.dry_run).await?;
+    if results.is_empty() {
+        println!("No duplicate endpoints found");
+        return Ok(());
+    }
+
+    let verb = if args.dry_run {
+        "Would merge"
+    } else {
+        "Merged"
+    };
+    for result in &results {
+        let removed: Vec<String> = result.removed.iter().map(|id| id.to_string()).collect();
+        println!(
+            "{} {} into {} ({})",
+            verb,
+            removed.join(", "),
+            result.kept,
+            result.normalized_url
+        );
+    }
+    if !args.dry_run {
+        println!("Restart running llmlb servers to reload the endpoint list");
+    }
+    Ok(())
+}
@@ -3,12 +3,16 @@
 //! Provides command-line interface for load balancer management.
 
 pub mod assistant;
+pub mod audit;
+pub mod endpoints;
 pub mod internal;
 pub mod serve;
+pub mod stats;
 pub mod status;
 pub mod stop;
 
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 /// LLM load balancer - Centralized management system for LLM inference nodes
 #[derive(Parser, Debug)]
@@ -23,8 +27,16 @@ use clap::{Parser, Subcommand};
     LLMLB_ADMIN_USERNAME    Initial admin username (default: admin)
     LLMLB_ADMIN_PASSWORD    Initial admin password (required on first run)
     LLMLB_DEFAULT_EMBEDDING_MODEL  Default embedding model
+
+CONFIG FILE:
+    Settings can also be written to ~/.llmlb/config.toml (or --config <PATH>).
+    Environment variables take precedence over the config file.
 "#)]
 pub struct Cli {
+    /// Path to the config file (default: ~/.llmlb/config.toml)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
     /// Subcommand to execute
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -41,6 +53,12 @@ pub enum Commands {
     Status(status::StatusArgs),
     /// Assistant helper commands (MCP replacement)
     Assistant(assistant::AssistantArgs),
+    /// Audit log commands (export)
+    Audit(audit::AuditArgs),
+    /// Request statistics commands (dump/import)
+    Stats(stats::StatsArgs),
+    /// Endpoint maintenance commands (dedupe)
+    Endpoints(endpoints::EndpointsArgs),
 
     /// Internal helper commands (self-update)
     #[command(name = "__internal", hide = true)]
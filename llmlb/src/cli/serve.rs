@@ -7,13 +7,13 @@ use clap::Args;
 /// Arguments for the serve subcommand
 #[derive(Args, Debug, Clone)]
 pub struct ServeArgs {
-    /// Listen port
-    #[arg(short, long, default_value = "32768", env = "LLMLB_PORT")]
-    pub port: u16,
+    /// Listen port (default: 32768)
+    #[arg(short, long, env = "LLMLB_PORT")]
+    pub port: Option<u16>,
 
-    /// Bind address
-    #[arg(short = 'H', long, default_value = "0.0.0.0", env = "LLMLB_HOST")]
-    pub host: String,
+    /// Bind address (default: 0.0.0.0)
+    #[arg(short = 'H', long, env = "LLMLB_HOST")]
+    pub host: Option<String>,
 
     /// Disable system tray (headless mode)
     #[arg(long, default_value_t = false)]
@@ -0,0 +1,532 @@
+//! stats subcommand
+//!
+//! Dumps request statistics (request history and daily aggregates) to a versioned
+//! JSON Lines file and imports them into another database.
+//!
+//! ファイル形式: 1行目がヘッダ（`format` / `version`）、以降は1行1レコードで
+//! `{"table": "...", "row": {...}}`。ダンプ・インポートともに1行ずつ処理する。
+
+use crate::api::openai_util::{redact_payload_for_history, REDACTED};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand, ValueEnum};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{SqliteConnection, SqlitePool};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// ダンプファイルの形式名
+pub const STATS_DUMP_FORMAT: &str = "llmlb-stats";
+
+/// ダンプファイルの形式バージョン
+pub const STATS_DUMP_VERSION: u32 = 1;
+
+/// Arguments for the stats subcommand
+#[derive(Args, Debug, Clone)]
+pub struct StatsArgs {
+    /// Stats subcommand
+    #[command(subcommand)]
+    pub command: StatsCommand,
+}
+
+/// Stats subcommands
+#[derive(Subcommand, Debug, Clone)]
+pub enum StatsCommand {
+    /// Dump request history and daily aggregates to a file
+    Dump(DumpArgs),
+    /// Import a file written by `stats dump`
+    Import(ImportArgs),
+}
+
+/// Arguments for `stats dump`
+#[derive(Args, Debug, Clone)]
+pub struct DumpArgs {
+    /// Output file path
+    #[arg(long)]
+    pub out: PathBuf,
+}
+
+/// How `stats import` treats existing data
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Keep existing rows and add rows that do not exist yet
+    Merge,
+    /// Delete existing request history and daily aggregates before importing
+    Replace,
+}
+
+/// Arguments for `stats import`
+#[derive(Args, Debug, Clone)]
+pub struct ImportArgs {
+    /// Dump file path
+    pub file: PathBuf,
+
+    /// Merge into or replace existing data
+    #[arg(long, value_enum, default_value = "merge")]
+    pub mode: ImportMode,
+}
+
+/// ダンプファイルのヘッダ行
+#[derive(Debug, Serialize, Deserialize)]
+struct DumpHeader {
+    format: String,
+    version: u32,
+    exported_at: DateTime<Utc>,
+    /// リクエスト/レスポンス本文の秘匿情報をリダクション済みか
+    redacted: bool,
+}
+
+/// ダンプファイルのレコード行
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "table", content = "row", rename_all = "snake_case")]
+enum StatsRecord {
+    RequestHistory(RequestHistoryRow),
+    EndpointDailyStats(EndpointDailyStatsRow),
+}
+
+/// request_history の1行（カラムをそのまま保持する）
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+struct RequestHistoryRow {
+    id: String,
+    timestamp: String,
+    request_type: String,
+    model: String,
+    endpoint_id: String,
+    endpoint_name: String,
+    endpoint_ip: String,
+    client_ip: Option<String>,
+    request_body: String,
+    response_body: Option<String>,
+    duration_ms: i64,
+    status: String,
+    error_message: Option<String>,
+    completed_at: String,
+    input_tokens: Option<i64>,
+    output_tokens: Option<i64>,
+    total_tokens: Option<i64>,
+    api_key_id: Option<String>,
+    cost_usd: Option<f64>,
+    requested_model: Option<String>,
+}
+
+/// endpoint_daily_stats の1行
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+struct EndpointDailyStatsRow {
+    endpoint_id: String,
+    model_id: String,
+    date: String,
+    api_kind: String,
+    total_requests: i64,
+    successful_requests: i64,
+    failed_requests: i64,
+    total_output_tokens: i64,
+    total_duration_ms: i64,
+}
+
+/// テーブルごとの処理件数
+#[derive(Debug, Default, PartialEq, Eq)]
+struct StatsSummary {
+    request_history: u64,
+    endpoint_daily_stats: u64,
+}
+
+/// Execute the stats command
+pub async fn execute(command: &StatsCommand) -> Result<()> {
+    match command {
+        StatsCommand::Dump(args) => execute_dump(args).await,
+        StatsCommand::Import(args) => execute_import(args).await,
+    }
+}
+
+async fn execute_dump(args: &DumpArgs) -> Result<()> {
+    let database_url = crate::bootstrap::resolve_database_url();
+    let options = SqliteConnectOptions::from_str(&database_url)
+        .with_context(|| format!("invalid database URL: {}", database_url))?;
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options.read_only(true))
+        .await
+        .with_context(|| format!("failed to open database: {}", database_url))?;
+
+    let file = std::fs::File::create(&args.out)
+        .with_context(|| format!("failed to create {}", args.out.display()))?;
+    let summary = dump_stats(&pool, BufWriter::new(file)).await?;
+    println!(
+        "Dumped {} request history records and {} daily stats rows to {}",
+        summary.request_history,
+        summary.endpoint_daily_stats,
+        args.out.display()
+    );
+    Ok(())
+}
+
+async fn execute_import(args: &ImportArgs) -> Result<()> {
+    let database_url = crate::bootstrap::resolve_database_url();
+    let pool = crate::db::migrations::initialize_database(&database_url)
+        .await
+        .with_context(|| format!("failed to open database: {}", database_url))?;
+
+    let file = std::fs::File::open(&args.file)
+        .with_context(|| format!("failed to open {}", args.file.display()))?;
+    let summary = import_stats(&pool, BufReader::new(file), args.mode).await?;
+    println!(
+        "Imported {} request history records and {} daily stats rows from {} ({:?})",
+        summary.request_history,
+        summary.endpoint_daily_stats,
+        args.file.display(),
+        args.mode
+    );
+    Ok(())
+}
+
+/// 統計をダンプする（単一の読み取りトランザクションで一貫したスナップショットを書き出す）
+async fn dump_stats<W: Write>(pool: &SqlitePool, mut writer: W) -> Result<StatsSummary> {
+    let header = DumpHeader {
+        format: STATS_DUMP_FORMAT.to_string(),
+        version: STATS_DUMP_VERSION,
+        exported_at: Utc::now(),
+        redacted: true,
+    };
+    write_line(&mut writer, &header)?;
+
+    let mut tx = pool.begin().await?;
+    let mut summary = StatsSummary::default();
+
+    let mut rows = sqlx::query_as::<_, RequestHistoryRow>(
+        r#"
+        SELECT id, timestamp, request_type, model, endpoint_id, endpoint_name, endpoint_ip,
+               client_ip, request_body, response_body, duration_ms, status, error_message,
+               completed_at, input_tokens, output_tokens, total_tokens, api_key_id, cost_usd,
+               requested_model
+        FROM request_history
+        ORDER BY timestamp, id
+        "#,
+    )
+    .fetch(&mut *tx);
+    while let Some(row) = rows.try_next().await? {
+        write_line(&mut writer, &StatsRecord::RequestHistory(redact_row(row)))?;
+        summary.request_history += 1;
+    }
+    drop(rows);
+
+    let mut rows = sqlx::query_as::<_, EndpointDailyStatsRow>(
+        r#"
+        SELECT endpoint_id, model_id, date, api_kind, total_requests, successful_requests,
+               failed_requests, total_output_tokens, total_duration_ms
+        FROM endpoint_daily_stats
+        ORDER BY date, endpoint_id, model_id, api_kind
+        "#,
+    )
+    .fetch(&mut *tx);
+    while let Some(row) = rows.try_next().await? {
+        write_line(&mut writer, &StatsRecord::EndpointDailyStats(row))?;
+        summary.endpoint_daily_stats += 1;
+    }
+    drop(rows);
+
+    tx.commit().await?;
+    writer.flush()?;
+    Ok(summary)
+}
+
+/// ダンプファイルを取り込む（全体を1トランザクションで適用し、失敗時は何も変更しない）
+async fn import_stats<R: BufRead>(
+    pool: &SqlitePool,
+    reader: R,
+    mode: ImportMode,
+) -> Result<StatsSummary> {
+    let mut lines = reader.lines();
+    let header_line = lines.next().context("dump file is empty")??;
+    let header: DumpHeader =
+        serde_json::from_str(&header_line).context("invalid dump file header")?;
+    if header.format != STATS_DUMP_FORMAT {
+        bail!("unsupported dump format: {}", header.format);
+    }
+    if header.version == 0 || header.version > STATS_DUMP_VERSION {
+        bail!(
+            "unsupported dump version {} (supported: up to {})",
+            header.version,
+            STATS_DUMP_VERSION
+        );
+    }
+
+    let mut tx = pool.begin().await?;
+    if mode == ImportMode::Replace {
+        sqlx::query("DELETE FROM request_history")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM endpoint_daily_stats")
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let mut summary = StatsSummary::default();
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // ヘッダが1行目のため、レコードは2行目から
+        let record: StatsRecord = serde_json::from_str(&line)
+            .with_context(|| format!("invalid record at line {}", index + 2))?;
+        match record {
+            StatsRecord::RequestHistory(row) => {
+                summary.request_history += insert_request_history(&mut tx, &row).await?;
+            }
+            StatsRecord::EndpointDailyStats(row) => {
+                summary.endpoint_daily_stats += insert_daily_stats(&mut tx, &row).await?;
+            }
+        }
+    }
+    tx.commit().await?;
+    Ok(summary)
+}
+
+/// 既存の行（同じ主キー）は維持する
+async fn insert_request_history(
+    conn: &mut SqliteConnection,
+    row: &RequestHistoryRow,
+) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO request_history (
+            id, timestamp, request_type, model, endpoint_id, endpoint_name,
+            endpoint_ip, client_ip, request_body, response_body, duration_ms,
+            status, error_message, completed_at, input_tokens, output_tokens, total_tokens,
+            api_key_id, cost_usd, requested_model
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&row.id)
+    .bind(&row.timestamp)
+    .bind(&row.request_type)
+    .bind(&row.model)
+    .bind(&row.endpoint_id)
+    .bind(&row.endpoint_name)
+    .bind(&row.endpoint_ip)
+    .bind(&row.client_ip)
+    .bind(&row.request_body)
+    .bind(&row.response_body)
+    .bind(row.duration_ms)
+    .bind(&row.status)
+    .bind(&row.error_message)
+    .bind(&row.completed_at)
+    .bind(row.input_tokens)
+    .bind(row.output_tokens)
+    .bind(row.total_tokens)
+    .bind(&row.api_key_id)
+    .bind(row.cost_usd)
+    .bind(&row.requested_model)
+    .execute(conn)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// 既存の集計（同じエンドポイント・モデル・日付・API種別）は維持する
+async fn insert_daily_stats(
+    conn: &mut SqliteConnection,
+    row: &EndpointDailyStatsRow,
+) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO endpoint_daily_stats (
+            endpoint_id, model_id, date, api_kind, total_requests, successful_requests,
+            failed_requests, total_output_tokens, total_duration_ms
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&row.endpoint_id)
+    .bind(&row.model_id)
+    .bind(&row.date)
+    .bind(&row.api_kind)
+    .bind(row.total_requests)
+    .bind(row.successful_requests)
+    .bind(row.failed_requests)
+    .bind(row.total_output_tokens)
+    .bind(row.total_duration_ms)
+    .execute(conn)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+fn write_line<W: Write, T: Serialize>(writer: &mut W, value: &T) -> Result<()> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// リクエスト/レスポンス本文から秘匿情報と埋め込みデータを取り除く
+fn redact_row(mut row: RequestHistoryRow) -> RequestHistoryRow {
+    row.request_body = redact_body(&row.request_body);
+    row.response_body = row.response_body.as_deref().map(redact_body);
+    row
+}
+
+/// JSON本文をリダクションする（JSONとして解釈できない本文は丸ごと伏せる）
+fn redact_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(value) => redact_payload_for_history(&value).to_string(),
+        Err(_) => Value::String(REDACTED.to_string()).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn insert_history(pool: &SqlitePool, id: &str, request_body: &str) {
+        sqlx::query(
+            r#"
+            INSERT INTO request_history (
+                id, timestamp, request_type, model, endpoint_id, endpoint_name, endpoint_ip,
+                request_body, duration_ms, status, completed_at, input_tokens
+            ) VALUES (?, '2026-01-01T00:00:00+00:00', 'Chat', 'model-a',
+                      '00000000-0000-0000-0000-000000000001', 'ep', '127.0.0.1',
+                      ?, 10, 'success', '2026-01-01T00:00:01+00:00', 5)
+            "#,
+        )
+        .bind(id)
+        .bind(request_body)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn insert_daily(pool: &SqlitePool, date: &str, total: i64) {
+        sqlx::query(
+            r#"
+            INSERT INTO endpoint_daily_stats (endpoint_id, model_id, date, total_requests)
+            VALUES ('00000000-0000-0000-0000-000000000001', 'model-a', ?, ?)
+            "#,
+        )
+        .bind(date)
+        .bind(total)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn count(pool: &SqlitePool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn redacts_secret_keys_and_embedded_data() {
+        let body = serde_json::json!({
+            "model": "m",
+            "max_tokens": 10,
+            "api_key": "sk-123",
+            "metadata": {"Authorization": "Bearer abc"},
+            "messages": [{"content": [{"image_url": {"url": "data:image/png;base64,AAAA"}}]}],
+        });
+        let redacted: Value = serde_json::from_str(&redact_body(&body.to_string())).unwrap();
+        assert_eq!(redacted["model"], "m");
+        assert_eq!(redacted["max_tokens"], 10);
+        assert_eq!(redacted["api_key"], REDACTED);
+        assert_eq!(redacted["metadata"]["Authorization"], REDACTED);
+        assert!(redacted["messages"][0]["content"][0]["image_url"]["url"]
+            .as_str()
+            .unwrap()
+            .starts_with("[redacted data-url"));
+        assert_eq!(redact_body("not json"), format!("\"{}\"", REDACTED));
+    }
+
+    #[tokio::test]
+    async fn dump_and_import_round_trip() {
+        let source = crate::db::test_utils::test_db_pool().await;
+        insert_history(&source, "req-1", r#"{"model":"model-a","password":"p"}"#).await;
+        insert_history(&source, "req-2", r#"{"model":"model-a"}"#).await;
+        insert_daily(&source, "2026-01-01", 2).await;
+
+        let mut buf = Vec::new();
+        let summary = dump_stats(&source, &mut buf).await.unwrap();
+        assert_eq!(
+            summary,
+            StatsSummary {
+                request_history: 2,
+                endpoint_daily_stats: 1
+            }
+        );
+        let dump = String::from_utf8(buf).unwrap();
+        let header: Value = serde_json::from_str(dump.lines().next().unwrap()).unwrap();
+        assert_eq!(header["format"], STATS_DUMP_FORMAT);
+        assert_eq!(header["version"], STATS_DUMP_VERSION);
+        let first: Value = serde_json::from_str(dump.lines().nth(1).unwrap()).unwrap();
+        assert_eq!(first["table"], "request_history");
+        assert_eq!(
+            first["row"]["request_body"],
+            r#"{"model":"model-a","password":"[redacted]"}"#
+        );
+
+        let target = crate::db::test_utils::test_db_pool().await;
+        let summary = import_stats(&target, dump.as_bytes(), ImportMode::Merge)
+            .await
+            .unwrap();
+        assert_eq!(summary.request_history, 2);
+        assert_eq!(summary.endpoint_daily_stats, 1);
+        let input_tokens: Option<i64> =
+            sqlx::query_scalar("SELECT input_tokens FROM request_history WHERE id = 'req-1'")
+                .fetch_one(&target)
+                .await
+                .unwrap();
+        assert_eq!(input_tokens, Some(5));
+    }
+
+    #[tokio::test]
+    async fn merge_keeps_existing_rows_and_replace_discards_them() {
+        let source = crate::db::test_utils::test_db_pool().await;
+        insert_history(&source, "req-1", "{}").await;
+        insert_daily(&source, "2026-01-01", 2).await;
+        let mut buf = Vec::new();
+        dump_stats(&source, &mut buf).await.unwrap();
+
+        let target = crate::db::test_utils::test_db_pool().await;
+        insert_history(&target, "req-1", "{}").await;
+        insert_history(&target, "local-only", "{}").await;
+        insert_daily(&target, "2026-01-01", 7).await;
+
+        let summary = import_stats(&target, buf.as_slice(), ImportMode::Merge)
+            .await
+            .unwrap();
+        assert_eq!(summary, StatsSummary::default());
+        assert_eq!(count(&target, "request_history").await, 2);
+        let total: i64 = sqlx::query_scalar("SELECT total_requests FROM endpoint_daily_stats")
+            .fetch_one(&target)
+            .await
+            .unwrap();
+        assert_eq!(total, 7);
+
+        import_stats(&target, buf.as_slice(), ImportMode::Replace)
+            .await
+            .unwrap();
+        assert_eq!(count(&target, "request_history").await, 1);
+        let total: i64 = sqlx::query_scalar("SELECT total_requests FROM endpoint_daily_stats")
+            .fetch_one(&target)
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_format_or_version() {
+        let pool = crate::db::test_utils::test_db_pool().await;
+        for header in [
+            r#"{"format":"other","version":1,"exported_at":"2026-01-01T00:00:00Z","redacted":true}"#,
+            r#"{"format":"llmlb-stats","version":99,"exported_at":"2026-01-01T00:00:00Z","redacted":true}"#,
+        ] {
+            assert!(import_stats(&pool, header.as_bytes(), ImportMode::Merge)
+                .await
+                .is_err());
+        }
+        assert!(import_stats(&pool, "".as_bytes(), ImportMode::Merge)
+            .await
+            .is_err());
+    }
+}
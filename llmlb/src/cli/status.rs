@@ -2,29 +2,91 @@
 //!
 //! Displays the current status of running servers.
 
-use crate::lock::{is_process_running, list_all_locks, read_lock_info};
+use super::assistant::CliCredentials;
+use crate::config::ServerConfig;
+use crate::lock::{is_process_running, list_all_locks, read_lock_info, LockInfo};
 use chrono::Utc;
 use clap::Args;
 use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 use std::time::Duration;
 
+/// `--json` 指定時、サーバが起動していない場合の終了コード
+pub const EXIT_NOT_RUNNING: i32 = 3;
+
 /// Arguments for the status subcommand
 #[derive(Args, Debug, Clone)]
 pub struct StatusArgs {
     /// Show status of specific port only
     #[arg(short, long)]
     pub port: Option<u16>,
+
+    /// Print machine-readable JSON for one server (--port, or LLMLB_PORT when omitted).
+    /// Exits with code 3 and `{"running":false}` when the server is not running.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+/// `--json` の出力
+#[derive(Debug, Default, Serialize, PartialEq)]
+struct JsonStatus {
+    running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    listen_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    endpoint_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    endpoints: Option<Vec<JsonEndpointStatus>>,
+    /// エンドポイント一覧を取得できなかった理由
+    #[serde(skip_serializing_if = "Option::is_none")]
+    endpoints_error: Option<String>,
+}
+
+/// `--json` のエンドポイントごとの状態
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct JsonEndpointStatus {
+    id: String,
+    name: String,
+    status: String,
+    #[serde(default)]
+    active_requests: u32,
+}
+
+/// `GET /api/system` のうち参照する項目
+#[derive(Deserialize)]
+struct SystemInfo {
+    version: String,
+    pid: u32,
+}
+
+/// `GET /api/endpoints` のうち参照する項目
+#[derive(Deserialize)]
+struct EndpointList {
+    endpoints: Vec<JsonEndpointStatus>,
 }
 
 /// Execute the status command
-pub async fn execute(args: &StatusArgs) -> Result<(), anyhow::Error> {
+///
+/// Returns the process exit code (non-zero only for `--json` when the server is not running).
+pub async fn execute(args: &StatusArgs) -> Result<i32, anyhow::Error> {
     let client = reqwest::Client::new();
+    if args.json {
+        let port = args.port.unwrap_or_else(|| ServerConfig::from_env().port);
+        let auth = CliCredentials::from_env().management_header();
+        let status = collect_json_status(&client, port, auth.as_ref()).await?;
+        println!("{}", serde_json::to_string(&status)?);
+        return Ok(if status.running { 0 } else { EXIT_NOT_RUNNING });
+    }
     if let Some(port) = args.port {
         // 特定ポートの状態を表示
         match read_lock_info(port)? {
             Some(info) => {
-                let base_url = format!("http://127.0.0.1:{}", info.port);
-                let dashboard_url = format!("{}/dashboard/", base_url);
+                let dashboard_url = format!("{}/dashboard/", base_url(&info));
                 if is_process_running(info.pid) {
                     let http_status = check_http(&client, &dashboard_url).await;
                     let status_label =
@@ -55,8 +117,7 @@ pub async fn execute(args: &StatusArgs) -> Result<(), anyhow::Error> {
         } else {
             println!("PORT\tPID\tSTARTED\t\t\t\tSTATUS\tURL\tHTTP");
             for info in locks {
-                let base_url = format!("http://127.0.0.1:{}", info.port);
-                let dashboard_url = format!("{}/dashboard/", base_url);
+                let dashboard_url = format!("{}/dashboard/", base_url(&info));
                 let is_running = info.pid != 0 && is_process_running(info.pid);
                 let http_status = if info.pid == 0 || is_running {
                     check_http(&client, &dashboard_url).await
@@ -77,7 +138,96 @@ pub async fn execute(args: &StatusArgs) -> Result<(), anyhow::Error> {
             }
         }
     }
-    Ok(())
+    Ok(0)
+}
+
+/// ロックファイルに記録されたリッスンアドレス（`host:port`）
+fn listen_address(info: &LockInfo) -> Option<String> {
+    let host = info.host.as_deref()?;
+    Some(match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(ip)) => format!("[{}]:{}", ip, info.port),
+        _ => format!("{}:{}", host, info.port),
+    })
+}
+
+/// サーバへ接続するためのベースURL
+///
+/// 全アドレスで待ち受けている場合（`0.0.0.0` / `::`）と、ホストが記録されていない
+/// 旧バージョンのロックファイルではループバックへ接続する。
+fn base_url(info: &LockInfo) -> String {
+    let host = match info.host.as_deref().map(|h| (h, h.parse::<IpAddr>())) {
+        None => "127.0.0.1".to_string(),
+        Some((_, Ok(IpAddr::V6(ip)))) if ip.is_unspecified() => "[::1]".to_string(),
+        Some((_, Ok(ip))) if ip.is_unspecified() => "127.0.0.1".to_string(),
+        Some((_, Ok(IpAddr::V6(ip)))) => format!("[{}]", ip),
+        Some((host, _)) => host.to_string(),
+    };
+    format!("http://{}:{}", host, info.port)
+}
+
+/// `--json` 用にサーバの状態を集める
+///
+/// ロックファイルのプロセスが生存し `/api/system` に応答した場合のみ起動中とみなす。
+/// エンドポイント一覧は他のCLIコマンドと同じ認証情報（[`CliCredentials`]、
+/// `endpoints.read` 権限）で取得する。
+async fn collect_json_status(
+    client: &reqwest::Client,
+    port: u16,
+    auth: Option<&(&'static str, String)>,
+) -> Result<JsonStatus, anyhow::Error> {
+    let Some(info) = read_lock_info(port)? else {
+        return Ok(JsonStatus::default());
+    };
+    if info.pid != 0 && !is_process_running(info.pid) {
+        return Ok(JsonStatus::default());
+    }
+
+    let base = base_url(&info);
+    let Ok(system) = fetch_json::<SystemInfo>(client, &format!("{}/api/system", base), None).await
+    else {
+        return Ok(JsonStatus::default());
+    };
+
+    let mut status = JsonStatus {
+        running: true,
+        version: Some(system.version),
+        listen_address: listen_address(&info),
+        pid: Some(system.pid),
+        ..Default::default()
+    };
+    let endpoints = match auth {
+        Some(auth) => {
+            fetch_json::<EndpointList>(client, &format!("{}/api/endpoints", base), Some(auth)).await
+        }
+        None => Err(
+            "no credentials set (LLMLB_ADMIN_API_KEY, LLMLB_JWT_TOKEN or LLMLB_API_KEY)"
+                .to_string(),
+        ),
+    };
+    match endpoints {
+        Ok(list) => {
+            status.endpoint_count = Some(list.endpoints.len());
+            status.endpoints = Some(list.endpoints);
+        }
+        Err(e) => status.endpoints_error = Some(e),
+    }
+    Ok(status)
+}
+
+async fn fetch_json<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    auth: Option<&(&'static str, String)>,
+) -> Result<T, String> {
+    let mut request = client.get(url).timeout(Duration::from_secs(2));
+    if let Some((name, value)) = auth {
+        request = request.header(*name, value);
+    }
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    response.json::<T>().await.map_err(|e| e.to_string())
 }
 
 fn determine_status_label(
@@ -136,7 +286,7 @@ mod tests {
     use super::*;
     use chrono::Duration as ChronoDuration;
     use wiremock::{
-        matchers::{method, path},
+        matchers::{header, method, path},
         Mock, MockServer, ResponseTemplate,
     };
 
@@ -188,6 +338,33 @@ mod tests {
         assert_eq!(format_http_status(None), "UNREACHABLE");
     }
 
+    #[test]
+    fn listen_address_and_base_url_follow_recorded_host() {
+        let info = |host: Option<&str>| LockInfo {
+            pid: 1,
+            started_at: Utc::now(),
+            port: 8080,
+            host: host.map(str::to_string),
+        };
+        assert_eq!(listen_address(&info(None)), None);
+        assert_eq!(base_url(&info(None)), "http://127.0.0.1:8080");
+        assert_eq!(
+            listen_address(&info(Some("0.0.0.0"))).as_deref(),
+            Some("0.0.0.0:8080")
+        );
+        assert_eq!(base_url(&info(Some("0.0.0.0"))), "http://127.0.0.1:8080");
+        assert_eq!(base_url(&info(Some("::"))), "http://[::1]:8080");
+        assert_eq!(
+            listen_address(&info(Some("::1"))).as_deref(),
+            Some("[::1]:8080")
+        );
+        assert_eq!(base_url(&info(Some("192.0.2.5"))), "http://192.0.2.5:8080");
+        assert_eq!(
+            base_url(&info(Some("llmlb.local"))),
+            "http://llmlb.local:8080"
+        );
+    }
+
     #[tokio::test]
     async fn check_http_returns_status_when_reachable() {
         let server = MockServer::start().await;
@@ -213,6 +390,7 @@ mod tests {
     async fn execute_succeeds_when_port_has_no_lock_file() {
         let args = StatusArgs {
             port: Some(unique_test_port()),
+            json: false,
         };
         execute(&args)
             .await
@@ -230,6 +408,7 @@ mod tests {
             pid: u32::MAX,
             started_at: Utc::now(),
             port,
+            host: None,
         };
         std::fs::write(
             &path,
@@ -237,10 +416,89 @@ mod tests {
         )
         .expect("failed to write lock file");
 
-        let args = StatusArgs { port: Some(port) };
+        let args = StatusArgs {
+            port: Some(port),
+            json: false,
+        };
         let result = execute(&args).await;
         let _ = std::fs::remove_file(&path);
 
         result.expect("status command should handle stale lock");
     }
+
+    #[tokio::test]
+    async fn json_status_reports_not_running_without_lock() {
+        let client = reqwest::Client::new();
+        let auth = ("X-API-Key", "sk_test".to_string());
+        let status = collect_json_status(&client, unique_test_port(), Some(&auth))
+            .await
+            .expect("json status should succeed for missing lock");
+        assert_eq!(
+            serde_json::to_string(&status).unwrap(),
+            r#"{"running":false}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn json_status_collects_version_and_endpoints() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/system"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "version": "1.2.3",
+                "pid": 4242,
+                "in_flight": 0
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/endpoints"))
+            .and(header("x-api-key", "sk_test"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "endpoints": [
+                    {"id": "a", "name": "gpu-1", "status": "online", "active_requests": 2},
+                    {"id": "b", "name": "gpu-2", "status": "offline", "active_requests": 0}
+                ],
+                "total": 2
+            })))
+            .mount(&server)
+            .await;
+
+        let port = server.address().port();
+        let path = crate::lock::lock_path(port);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("failed to create lock dir");
+        }
+        let info = crate::lock::LockInfo {
+            pid: std::process::id(),
+            started_at: Utc::now(),
+            port,
+            host: Some("127.0.0.1".to_string()),
+        };
+        std::fs::write(
+            &path,
+            serde_json::to_string(&info).expect("failed to serialize lock info"),
+        )
+        .expect("failed to write lock file");
+
+        let client = reqwest::Client::new();
+        let auth = ("X-API-Key", "sk_test".to_string());
+        let with_key = collect_json_status(&client, port, Some(&auth)).await;
+        let without_key = collect_json_status(&client, port, None).await;
+        let _ = std::fs::remove_file(&path);
+
+        let status = with_key.expect("json status should succeed");
+        assert!(status.running);
+        assert_eq!(status.version.as_deref(), Some("1.2.3"));
+        assert_eq!(status.listen_address, Some(format!("127.0.0.1:{}", port)));
+        assert_eq!(status.endpoint_count, Some(2));
+        let endpoints = status.endpoints.unwrap();
+        assert_eq!(endpoints[0].status, "online");
+        assert_eq!(endpoints[0].active_requests, 2);
+
+        let status = without_key.expect("json status should succeed");
+        assert!(status.running);
+        assert!(status.endpoints.is_none());
+        assert!(status.endpoints_error.is_some());
+    }
 }
@@ -2,10 +2,16 @@
 //!
 //! Stops a running server.
 
-use crate::lock::{is_process_running, lock_path, read_lock_info, stop_process};
+use crate::lock::{is_process_running, kill_process, lock_path, read_lock_info, stop_process};
 use clap::Args;
 use std::time::Duration;
 
+/// 強制終了後、プロセスの消滅を待つ最大時間
+const FORCE_KILL_WAIT: Duration = Duration::from_secs(5);
+
+/// 終了確認のポーリング間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// Arguments for the stop subcommand
 #[derive(Args, Debug, Clone)]
 pub struct StopArgs {
@@ -13,12 +19,18 @@ pub struct StopArgs {
     #[arg(short, long)]
     pub port: u16,
 
-    /// Timeout in seconds to wait for server to stop
+    /// Timeout in seconds to wait for graceful shutdown
     #[arg(short, long, default_value = "5")]
     pub timeout: u64,
+
+    /// Kill the process if it does not stop within the timeout
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
 }
 
 /// Execute the stop command
+///
+/// Fails when the server is still running after the timeout and `--force` is not given.
 pub async fn execute(args: &StopArgs) -> Result<(), anyhow::Error> {
     let port = args.port;
 
@@ -34,8 +46,7 @@ pub async fn execute(args: &StopArgs) -> Result<(), anyhow::Error> {
     // PIDが存在するか確認
     if !is_process_running(lock_info.pid) {
         // ロックファイルは存在するがプロセスは存在しない（残留ロック）
-        let path = lock_path(port);
-        std::fs::remove_file(&path)?;
+        remove_lock_file(port)?;
         println!(
             "Warning: Stale lock file found (PID {} not running), cleaned up",
             lock_info.pid
@@ -43,7 +54,7 @@ pub async fn execute(args: &StopArgs) -> Result<(), anyhow::Error> {
         return Ok(());
     }
 
-    // プロセスを停止
+    // グレースフルシャットダウンを要求（サーバー側で ShutdownController の停止要求になる）
     println!(
         "Stopping server on port {} (PID: {})...",
         port, lock_info.pid
@@ -51,24 +62,55 @@ pub async fn execute(args: &StopArgs) -> Result<(), anyhow::Error> {
     stop_process(lock_info.pid)?;
 
     // 終了を待機
-    let timeout = Duration::from_secs(args.timeout);
-    let start = std::time::Instant::now();
-    while start.elapsed() < timeout {
-        if !is_process_running(lock_info.pid) {
-            println!("Server stopped successfully");
-            return Ok(());
-        }
-        tokio::time::sleep(Duration::from_millis(100)).await;
+    if wait_for_exit(lock_info.pid, Duration::from_secs(args.timeout)).await {
+        println!("Server stopped successfully");
+        return Ok(());
+    }
+
+    if !args.force {
+        // タイムアウト（停止していないため失敗として終了コードを非0にする）
+        anyhow::bail!(
+            "Server did not stop within {} seconds. Re-run with --force to kill it: llmlb stop --port {} --force",
+            args.timeout,
+            port
+        );
     }
 
-    // タイムアウト
     println!(
-        "Warning: Server did not stop within {} seconds. You may need to kill it manually: kill -9 {}",
+        "Server did not stop within {} seconds; killing PID {}...",
         args.timeout, lock_info.pid
     );
+    kill_process(lock_info.pid)?;
+    if !wait_for_exit(lock_info.pid, FORCE_KILL_WAIT).await {
+        anyhow::bail!("Failed to kill server process (PID {})", lock_info.pid);
+    }
+    // 強制終了したプロセスはロックファイルを削除できないため代わりに削除する
+    remove_lock_file(port)?;
+    println!("Server killed");
     Ok(())
 }
 
+/// プロセスの終了を `timeout` までポーリングで待つ（終了したら `true`）
+async fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let start = std::time::Instant::now();
+    loop {
+        if !is_process_running(pid) {
+            return true;
+        }
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn remove_lock_file(port: u16) -> std::io::Result<()> {
+    match std::fs::remove_file(lock_path(port)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,6 +132,7 @@ mod tests {
         let args = StopArgs {
             port: unique_test_port(),
             timeout: 1,
+            force: false,
         };
         execute(&args)
             .await
@@ -107,6 +150,7 @@ mod tests {
             pid: u32::MAX,
             started_at: Utc::now(),
             port,
+            host: None,
         };
         std::fs::write(
             &path,
@@ -114,10 +158,93 @@ mod tests {
         )
         .expect("failed to write lock file");
 
-        let args = StopArgs { port, timeout: 1 };
+        let args = StopArgs {
+            port,
+            timeout: 1,
+            force: false,
+        };
         execute(&args)
             .await
             .expect("stop should clean stale lock and return Ok");
         assert!(!path.exists(), "stale lock file should be removed");
     }
+
+    /// SIGTERM を無視するプロセス（グレースフルシャットダウンに応じないサーバーの代わり）を
+    /// 起動し、そのPIDでロックファイルを書く
+    #[cfg(unix)]
+    async fn spawn_unstoppable_server(
+        port: u16,
+    ) -> (
+        u32,
+        std::thread::JoinHandle<std::io::Result<std::process::ExitStatus>>,
+    ) {
+        let path = crate::lock::lock_path(port);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("failed to create lock dir");
+        }
+
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "trap '' TERM; while :; do sleep 1; done"])
+            .spawn()
+            .expect("failed to spawn test process");
+        let pid = child.id();
+        // 強制終了後にゾンビとして残らないよう回収する
+        let reaper = std::thread::spawn(move || child.wait());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let info = crate::lock::LockInfo {
+            pid,
+            started_at: Utc::now(),
+            port,
+            host: None,
+        };
+        std::fs::write(
+            &path,
+            serde_json::to_string(&info).expect("failed to serialize lock info"),
+        )
+        .expect("failed to write lock file");
+        (pid, reaper)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn execute_fails_on_timeout_without_force() {
+        let port = unique_test_port();
+        let (pid, reaper) = spawn_unstoppable_server(port).await;
+
+        let args = StopArgs {
+            port,
+            timeout: 1,
+            force: false,
+        };
+        let result = execute(&args).await;
+        let still_running = is_process_running(pid);
+        let _ = kill_process(pid);
+        let _ = reaper.join();
+        let _ = std::fs::remove_file(crate::lock::lock_path(port));
+
+        let err = result.expect_err("stop should fail when the server keeps running");
+        assert!(err.to_string().contains("--force"));
+        assert!(still_running);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn execute_force_kills_process_ignoring_sigterm() {
+        let port = unique_test_port();
+        let (pid, reaper) = spawn_unstoppable_server(port).await;
+
+        let args = StopArgs {
+            port,
+            timeout: 1,
+            force: true,
+        };
+        let result = execute(&args).await;
+        let _ = kill_process(pid);
+        let _ = reaper.join();
+        let _ = std::fs::remove_file(crate::lock::lock_path(port));
+
+        result.expect("stop --force should kill the process");
+        assert!(!is_process_running(pid));
+    }
 }
@@ -1,8 +1,24 @@
-use axum::{http::header, http::StatusCode, response::IntoResponse};
+use crate::api::error::AppError;
+use crate::audit::export::parse_date_or_datetime;
+use crate::common::error::{CommonError, LbError};
+use crate::db::request_history::CloudDailyUsage;
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    http::header,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
 use once_cell::sync::Lazy;
 use prometheus::{
-    Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+    CounterVec, Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
 
 static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
 static COUNTER: Lazy<IntCounterVec> = Lazy::new(|| {
@@ -17,10 +33,89 @@ static HISTO: Lazy<HistogramVec> = Lazy::new(|| {
     HistogramVec::new(opts, &["provider"]).expect("histogram vec")
 });
 
+static ENDPOINT_COST: Lazy<CounterVec> = Lazy::new(|| {
+    let opts = Opts::new(
+        "cloud_endpoint_cost_usd_total",
+        "Accumulated cost per endpoint (USD)",
+    );
+    CounterVec::new(opts, &["endpoint_id"]).expect("counter vec")
+});
+
+/// Month-to-date cost per endpoint, reset when the calendar month (UTC) changes.
+static MONTHLY_COSTS: Lazy<Mutex<HashMap<Uuid, MonthlyCost>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, PartialEq)]
+struct MonthlyCost {
+    month: String,
+    usd: f64,
+}
+
 /// Register cloud metrics (idempotent).
 pub fn init_metrics() {
     REGISTRY.register(Box::new(COUNTER.clone())).ok();
     REGISTRY.register(Box::new(HISTO.clone())).ok();
+    REGISTRY.register(Box::new(ENDPOINT_COST.clone())).ok();
+}
+
+/// Billing month key (`YYYY-MM`, UTC) for the given time.
+pub fn billing_month(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m").to_string()
+}
+
+/// Add `usd` to the endpoint's accumulated cost and return its month-to-date total.
+pub fn record_endpoint_cost(endpoint_id: Uuid, usd: f64) -> f64 {
+    record_endpoint_cost_at(endpoint_id, usd, Utc::now())
+}
+
+fn record_endpoint_cost_at(endpoint_id: Uuid, usd: f64, now: DateTime<Utc>) -> f64 {
+    init_metrics();
+    if usd > 0.0 {
+        ENDPOINT_COST
+            .with_label_values(&[endpoint_id.to_string().as_str()])
+            .inc_by(usd);
+    }
+    let month = billing_month(now);
+    let mut costs = MONTHLY_COSTS.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = costs.entry(endpoint_id).or_insert_with(|| MonthlyCost {
+        month: month.clone(),
+        usd: 0.0,
+    });
+    if entry.month != month {
+        *entry = MonthlyCost { month, usd: 0.0 };
+    }
+    entry.usd += usd.max(0.0);
+    entry.usd
+}
+
+/// Month-to-date cost of the endpoint (0 when nothing was recorded this month).
+pub fn endpoint_monthly_cost(endpoint_id: Uuid) -> f64 {
+    endpoint_monthly_cost_at(endpoint_id, Utc::now())
+}
+
+fn endpoint_monthly_cost_at(endpoint_id: Uuid, now: DateTime<Utc>) -> f64 {
+    let month = billing_month(now);
+    MONTHLY_COSTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&endpoint_id)
+        .filter(|cost| cost.month == month)
+        .map(|cost| cost.usd)
+        .unwrap_or(0.0)
+}
+
+/// Restore month-to-date costs persisted before a restart.
+pub fn seed_endpoint_monthly_costs(month: &str, costs: impl IntoIterator<Item = (Uuid, f64)>) {
+    let mut monthly = MONTHLY_COSTS.lock().unwrap_or_else(|e| e.into_inner());
+    for (endpoint_id, usd) in costs {
+        monthly.insert(
+            endpoint_id,
+            MonthlyCost {
+                month: month.to_string(),
+                usd,
+            },
+        );
+    }
 }
 
 /// Record a cloud provider request with status and latency (ms).
@@ -58,6 +153,204 @@ pub async fn export_metrics() -> impl IntoResponse {
     res
 }
 
+/// Price of a cloud model in USD per 1K tokens (`LLMLB_CLOUD_PRICING`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CloudPrice {
+    /// USD per 1K input tokens
+    pub input_per_1k: f64,
+    /// USD per 1K output tokens
+    pub output_per_1k: f64,
+}
+
+impl CloudPrice {
+    /// Cost (USD) of the given token counts.
+    pub fn cost_usd(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input_per_1k + output_tokens as f64 * self.output_per_1k)
+            / 1000.0
+    }
+}
+
+/// Look up the price of a model called through `provider`.
+///
+/// Tries `provider:model`, the model name as recorded, then the `provider:*` default.
+pub fn lookup_price(
+    pricing: &[(String, CloudPrice)],
+    provider: &str,
+    model: &str,
+) -> Option<CloudPrice> {
+    let prefix = format!("{provider}:");
+    let qualified = if model.starts_with(&prefix) {
+        model.to_string()
+    } else {
+        format!("{prefix}{model}")
+    };
+    let wildcard = format!("{prefix}*");
+    [qualified.as_str(), model, wildcard.as_str()]
+        .into_iter()
+        .find_map(|key| {
+            pricing
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, price)| *price)
+        })
+}
+
+/// Cost and usage totals of one provider.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderCost {
+    /// Provider name (`openai`, `google`, `anthropic`, ...)
+    pub provider: String,
+    /// Total input tokens
+    pub input_tokens: u64,
+    /// Total output tokens
+    pub output_tokens: u64,
+    /// Number of requests
+    pub request_count: u64,
+    /// Total cost (USD)
+    pub cost_usd: f64,
+}
+
+/// Cost of one day (UTC), broken down by provider.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyCost {
+    /// Date (`YYYY-MM-DD`, UTC)
+    pub date: String,
+    /// Total cost of the day (USD)
+    pub cost_usd: f64,
+    /// Per-provider totals of the day
+    pub providers: Vec<ProviderCost>,
+}
+
+/// Response of `GET /api/cloud/cost`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CloudCostReport {
+    /// Start of the period
+    pub from: DateTime<Utc>,
+    /// End of the period (inclusive)
+    pub to: DateTime<Utc>,
+    /// Total cost of the period (USD)
+    pub total_cost_usd: f64,
+    /// Per-provider totals
+    pub providers: Vec<ProviderCost>,
+    /// Daily breakdown (oldest first)
+    pub daily: Vec<DailyCost>,
+    /// Models without a price; their usage is counted with a cost of 0.
+    pub unpriced_models: Vec<String>,
+    /// Human-readable warnings about the report
+    pub warnings: Vec<String>,
+}
+
+fn add_usage(costs: &mut Vec<ProviderCost>, usage: &CloudDailyUsage, cost_usd: f64) {
+    let index = match costs.iter().position(|c| c.provider == usage.provider) {
+        Some(index) => index,
+        None => {
+            costs.push(ProviderCost {
+                provider: usage.provider.clone(),
+                input_tokens: 0,
+                output_tokens: 0,
+                request_count: 0,
+                cost_usd: 0.0,
+            });
+            costs.len() - 1
+        }
+    };
+    let cost = &mut costs[index];
+    cost.input_tokens += usage.input_tokens;
+    cost.output_tokens += usage.output_tokens;
+    cost.request_count += usage.request_count;
+    cost.cost_usd += cost_usd;
+}
+
+/// Price daily usage rows and total them per day and per provider.
+pub fn build_cost_report(
+    usage: &[CloudDailyUsage],
+    pricing: &[(String, CloudPrice)],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> CloudCostReport {
+    let mut providers: Vec<ProviderCost> = Vec::new();
+    let mut daily: Vec<DailyCost> = Vec::new();
+    let mut unpriced_models: Vec<String> = Vec::new();
+
+    for row in usage {
+        let cost_usd = match lookup_price(pricing, &row.provider, &row.model) {
+            Some(price) => price.cost_usd(row.input_tokens, row.output_tokens),
+            None => {
+                if !unpriced_models.contains(&row.model) {
+                    unpriced_models.push(row.model.clone());
+                }
+                0.0
+            }
+        };
+        add_usage(&mut providers, row, cost_usd);
+        if daily.last().is_none_or(|day| day.date != row.date) {
+            daily.push(DailyCost {
+                date: row.date.clone(),
+                cost_usd: 0.0,
+                providers: Vec::new(),
+            });
+        }
+        let day = daily.last_mut().expect("day was just pushed");
+        day.cost_usd += cost_usd;
+        add_usage(&mut day.providers, row, cost_usd);
+    }
+
+    let warnings = unpriced_models
+        .iter()
+        .map(|model| format!("no price configured for '{model}'; counted as 0 USD"))
+        .collect();
+    CloudCostReport {
+        from,
+        to,
+        total_cost_usd: providers.iter().map(|p| p.cost_usd).sum(),
+        providers,
+        daily,
+        unpriced_models,
+        warnings,
+    }
+}
+
+/// Query parameters of `GET /api/cloud/cost` (RFC 3339 or `YYYY-MM-DD`).
+#[derive(Debug, Deserialize)]
+pub struct CloudCostQuery {
+    /// Start of the period (default: 30 days before `to`)
+    pub from: Option<String>,
+    /// End of the period, inclusive (default: now)
+    pub to: Option<String>,
+}
+
+/// `GET /api/cloud/cost` - cloud call costs for a period.
+pub async fn get_cloud_cost(
+    State(state): State<AppState>,
+    Query(query): Query<CloudCostQuery>,
+) -> Result<Json<CloudCostReport>, AppError> {
+    let invalid = |msg: String| AppError(LbError::Common(CommonError::Validation(msg)));
+    let to = match query.to.as_deref() {
+        Some(to) => parse_date_or_datetime(to, true).map_err(invalid)?,
+        None => Utc::now(),
+    };
+    let from = match query.from.as_deref() {
+        Some(from) => parse_date_or_datetime(from, false).map_err(invalid)?,
+        None => to - chrono::Duration::days(30),
+    };
+    if from > to {
+        return Err(invalid("'from' must not be after 'to'".to_string()));
+    }
+
+    let usage = state
+        .request_history
+        .get_cloud_daily_usage(from, to)
+        .await?;
+    let report = build_cost_report(&usage, &crate::config::cloud_pricing(), from, to);
+    if !report.unpriced_models.is_empty() {
+        tracing::warn!(
+            models = ?report.unpriced_models,
+            "Cloud cost report includes models without a price (LLMLB_CLOUD_PRICING)"
+        );
+    }
+    Ok(Json(report))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,6 +397,31 @@ mod tests {
         assert!(out.contains("500"));
     }
 
+    #[test]
+    fn endpoint_cost_accumulates_and_resets_monthly() {
+        let endpoint_id = Uuid::new_v4();
+        let october = DateTime::parse_from_rfc3339("2026-10-31T23:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let november = DateTime::parse_from_rfc3339("2026-11-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(record_endpoint_cost_at(endpoint_id, 1.5, october), 1.5);
+        assert_eq!(record_endpoint_cost_at(endpoint_id, 2.0, october), 3.5);
+        assert_eq!(endpoint_monthly_cost_at(endpoint_id, october), 3.5);
+
+        // 月初でリセット
+        assert_eq!(endpoint_monthly_cost_at(endpoint_id, november), 0.0);
+        assert_eq!(record_endpoint_cost_at(endpoint_id, 0.25, november), 0.25);
+
+        let encoder = TextEncoder::new();
+        let mut buf = Vec::new();
+        encoder.encode(&REGISTRY.gather(), &mut buf).unwrap();
+        let out = String::from_utf8(buf).unwrap();
+        assert!(out.contains("cloud_endpoint_cost_usd_total"));
+    }
+
     #[test]
     fn init_metrics_is_idempotent() {
         init_metrics();
@@ -137,4 +455,70 @@ mod tests {
         // 1500ms = 1.5s, bucket should contain this value
         assert!(out.contains("cloud_request_latency_seconds"));
     }
+
+    fn usage(date: &str, provider: &str, model: &str, input: u64, output: u64) -> CloudDailyUsage {
+        CloudDailyUsage {
+            date: date.to_string(),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            input_tokens: input,
+            output_tokens: output,
+            request_count: 1,
+        }
+    }
+
+    #[test]
+    fn lookup_price_prefers_model_over_provider_default() {
+        let gpt = CloudPrice {
+            input_per_1k: 1.0,
+            output_per_1k: 2.0,
+        };
+        let openai = CloudPrice {
+            input_per_1k: 3.0,
+            output_per_1k: 4.0,
+        };
+        let pricing = vec![
+            ("openai:gpt-4o".to_string(), gpt),
+            ("openai:*".to_string(), openai),
+        ];
+        assert_eq!(lookup_price(&pricing, "openai", "openai:gpt-4o"), Some(gpt));
+        assert_eq!(lookup_price(&pricing, "openai", "gpt-4o"), Some(gpt));
+        assert_eq!(lookup_price(&pricing, "openai", "openai:o3"), Some(openai));
+        assert_eq!(lookup_price(&pricing, "google", "google:gemini"), None);
+    }
+
+    #[test]
+    fn cost_report_totals_per_day_and_provider() {
+        let pricing = vec![(
+            "openai:*".to_string(),
+            CloudPrice {
+                input_per_1k: 1.0,
+                output_per_1k: 2.0,
+            },
+        )];
+        let rows = vec![
+            usage("2026-10-01", "openai", "openai:gpt-4o", 1000, 500),
+            usage("2026-10-01", "google", "google:gemini-pro", 4000, 4000),
+            usage("2026-10-02", "openai", "openai:o3", 2000, 0),
+        ];
+        let from = Utc::now();
+        let report = build_cost_report(&rows, &pricing, from, from);
+
+        assert_eq!(report.total_cost_usd, 4.0);
+        assert_eq!(report.providers.len(), 2);
+        assert_eq!(report.providers[0].provider, "openai");
+        assert_eq!(report.providers[0].cost_usd, 4.0);
+        assert_eq!(report.providers[0].request_count, 2);
+        // unpriced models are counted with a cost of 0 and reported
+        assert_eq!(report.providers[1].cost_usd, 0.0);
+        assert_eq!(report.providers[1].input_tokens, 4000);
+        assert_eq!(report.unpriced_models, vec!["google:gemini-pro"]);
+        assert_eq!(report.warnings.len(), 1);
+
+        assert_eq!(report.daily.len(), 2);
+        assert_eq!(report.daily[0].cost_usd, 2.0);
+        assert_eq!(report.daily[0].providers.len(), 2);
+        assert_eq!(report.daily[1].date, "2026-10-02");
+        assert_eq!(report.daily[1].cost_usd, 2.0);
+    }
 }
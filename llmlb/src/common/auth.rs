@@ -52,6 +52,9 @@ pub struct ApiKey {
     pub expires_at: Option<DateTime<Utc>>,
     /// 付与された権限
     pub permissions: Vec<ApiKeyPermission>,
+    /// 失効日時（失効済みのキーは認証で拒否する）
+    #[serde(default)]
+    pub revoked_at: Option<DateTime<Utc>>,
 }
 
 /// APIキー権限
@@ -111,6 +114,66 @@ impl ApiKeyPermission {
     }
 }
 
+/// APIキー発行時に指定できる権限プリセット
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApiKeyScope {
+    /// 参照のみ（モデル一覧・エンドポイント・レジストリ・ログ・メトリクス）
+    ReadOnly,
+    /// 推論（OpenAI互換の推論とモデル一覧）
+    Inference,
+    /// 管理（すべての権限）
+    Admin,
+}
+
+impl ApiKeyScope {
+    /// スコープに対応する権限
+    pub fn permissions(self) -> Vec<ApiKeyPermission> {
+        match self {
+            ApiKeyScope::ReadOnly => vec![
+                ApiKeyPermission::OpenaiModelsRead,
+                ApiKeyPermission::EndpointsRead,
+                ApiKeyPermission::RegistryRead,
+                ApiKeyPermission::LogsRead,
+                ApiKeyPermission::MetricsRead,
+            ],
+            ApiKeyScope::Inference => vec![
+                ApiKeyPermission::OpenaiInference,
+                ApiKeyPermission::OpenaiModelsRead,
+            ],
+            ApiKeyScope::Admin => ApiKeyPermission::all(),
+        }
+    }
+
+    /// 権限一覧がいずれかのスコープのプリセットと一致する場合、そのスコープを返す
+    pub fn from_permissions(permissions: &[ApiKeyPermission]) -> Option<Self> {
+        [
+            ApiKeyScope::ReadOnly,
+            ApiKeyScope::Inference,
+            ApiKeyScope::Admin,
+        ]
+        .into_iter()
+        .find(|scope| {
+            let preset = scope.permissions();
+            preset.iter().all(|p| permissions.contains(p))
+                && permissions.iter().all(|p| preset.contains(p))
+        })
+    }
+}
+
+impl std::str::FromStr for ApiKeyScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "read-only" => Ok(ApiKeyScope::ReadOnly),
+            "inference" => Ok(ApiKeyScope::Inference),
+            "admin" => Ok(ApiKeyScope::Admin),
+            other => Err(format!("unknown API key scope: {}", other)),
+        }
+    }
+}
+
 /// APIキー（平文付き、発行時のレスポンス用）
 #[derive(Debug, Clone, Serialize)]
 pub struct ApiKeyWithPlaintext {
@@ -226,6 +289,28 @@ mod tests {
         assert_eq!(set.len(), all.len());
     }
 
+    #[test]
+    fn api_key_scope_maps_to_permissions() {
+        let scope: ApiKeyScope = serde_json::from_str("\"read-only\"").unwrap();
+        assert_eq!(scope, ApiKeyScope::ReadOnly);
+        assert!(!scope
+            .permissions()
+            .contains(&ApiKeyPermission::OpenaiInference));
+        assert!(ApiKeyScope::Inference
+            .permissions()
+            .contains(&ApiKeyPermission::OpenaiInference));
+        assert_eq!(ApiKeyScope::Admin.permissions(), ApiKeyPermission::all());
+        assert_eq!(
+            ApiKeyScope::from_permissions(&ApiKeyScope::Inference.permissions()),
+            Some(ApiKeyScope::Inference)
+        );
+        assert_eq!(
+            ApiKeyScope::from_permissions(&[ApiKeyPermission::OpenaiInference]),
+            None
+        );
+        assert!(serde_json::from_str::<ApiKeyScope>("\"superuser\"").is_err());
+    }
+
     // --- ApiKey ---
 
     #[test]
@@ -238,6 +323,7 @@ mod tests {
             created_by: Uuid::new_v4(),
             created_at: Utc::now(),
             expires_at: None,
+            revoked_at: None,
             permissions: vec![
                 ApiKeyPermission::OpenaiInference,
                 ApiKeyPermission::EndpointsRead,
@@ -453,6 +539,7 @@ mod tests {
             created_by: Uuid::new_v4(),
             created_at: Utc::now(),
             expires_at: Some(Utc::now()),
+            revoked_at: None,
             permissions: ApiKeyPermission::all(),
         };
         let json = serde_json::to_string(&key).unwrap();
@@ -472,6 +559,7 @@ mod tests {
             created_by: Uuid::new_v4(),
             created_at: Utc::now(),
             expires_at: None,
+            revoked_at: None,
             permissions: vec![],
         };
         assert!(key.permissions.is_empty());
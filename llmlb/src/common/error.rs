@@ -110,9 +110,112 @@ pub enum LbError {
     /// Conflict error (e.g., duplicate resource)
     #[error("Conflict: {0}")]
     Conflict(String),
+
+    /// Error annotated with the failed stage and attempted endpoints
+    #[error("{error} ({context})")]
+    WithContext {
+        /// Underlying error
+        error: Box<LbError>,
+        /// Failure context
+        context: Box<FailureContext>,
+    },
+}
+
+/// Request processing stage where a failure occurred
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureStage {
+    /// Endpoint selection (no candidates, queue full, queue timeout)
+    Selection,
+    /// Connecting to the upstream endpoint
+    Connection,
+    /// Upstream returned an error or an invalid response
+    Upstream,
+    /// Upstream did not respond in time
+    Timeout,
+}
+
+impl FailureStage {
+    /// Returns the stage name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Selection => "selection",
+            Self::Connection => "connection",
+            Self::Upstream => "upstream",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+impl std::fmt::Display for FailureStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Detailed failure context for debugging
+///
+/// Only returned to clients when `LLMLB_VERBOSE_ERRORS=1` and the caller has
+/// operator permission. Other clients receive the generic error message.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailureContext {
+    /// Stage where the request failed
+    pub stage: FailureStage,
+    /// Endpoints tried in order
+    pub attempted_endpoints: Vec<Uuid>,
+    /// Internal failure detail (may contain upstream messages)
+    pub detail: String,
+}
+
+impl FailureContext {
+    /// Creates a failure context
+    pub fn new(
+        stage: FailureStage,
+        attempted_endpoints: &[Uuid],
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            stage,
+            attempted_endpoints: attempted_endpoints.to_vec(),
+            detail: detail.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for FailureContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stage: {}, attempted endpoints: {}",
+            self.stage,
+            self.attempted_endpoints.len()
+        )
+    }
 }
 
 impl LbError {
+    /// Attaches a failure context to this error
+    pub fn with_context(self, context: FailureContext) -> Self {
+        match self {
+            Self::WithContext { error, .. } => Self::WithContext {
+                error,
+                context: Box::new(context),
+            },
+            error => Self::WithContext {
+                error: Box::new(error),
+                context: Box::new(context),
+            },
+        }
+    }
+
+    /// Returns the failure context, if attached
+    pub fn failure_context(&self) -> Option<&FailureContext> {
+        match self {
+            Self::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
     /// Returns a safe error message for external clients.
     ///
     /// This method returns a generic error message that does not expose
@@ -141,6 +244,7 @@ impl LbError {
             Self::Authentication(_) => "Authentication failed",
             Self::Authorization(_) => "Access denied",
             Self::Conflict(_) => "Resource conflict",
+            Self::WithContext { error, .. } => error.external_message(),
         }
     }
 
@@ -176,6 +280,7 @@ impl LbError {
             Self::Authentication(_) => "authentication_error",
             Self::Authorization(_) => "permission_error",
             Self::Conflict(_) => "invalid_request_error",
+            Self::WithContext { error, .. } => error.error_type(),
         }
     }
 
@@ -201,6 +306,7 @@ impl LbError {
             Self::Authentication(_) => StatusCode::UNAUTHORIZED,
             Self::Authorization(_) => StatusCode::FORBIDDEN,
             Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::WithContext { error, .. } => error.status_code(),
         }
     }
 
@@ -316,6 +422,28 @@ mod tests {
         assert_eq!(error.to_string(), "No available endpoints");
     }
 
+    #[test]
+    fn test_lb_error_with_context_delegates_to_inner_error() {
+        let endpoint_id = Uuid::new_v4();
+        let error = LbError::Http("connection refused".to_string()).with_context(
+            FailureContext::new(FailureStage::Connection, &[endpoint_id], "refused"),
+        );
+        assert_eq!(error.status_code(), StatusCode::BAD_GATEWAY);
+        assert_eq!(error.external_message(), "Backend service unavailable");
+        assert!(error.to_string().contains("stage: connection"));
+
+        let context = error.failure_context().unwrap();
+        assert_eq!(context.attempted_endpoints, vec![endpoint_id]);
+
+        // 付け替えた場合は内側のエラーを保ったままコンテキストのみ差し替える
+        let error = error.with_context(FailureContext::new(FailureStage::Timeout, &[], ""));
+        assert_eq!(
+            error.failure_context().unwrap().stage,
+            FailureStage::Timeout
+        );
+        assert_eq!(error.status_code(), StatusCode::BAD_GATEWAY);
+    }
+
     #[test]
     fn test_node_error_router_connection() {
         let error = NodeError::RouterConnection("timeout".to_string());
@@ -1,9 +1,16 @@
 //! IPアドレス正規化ユーティリティ
 //!
-//! IPv4-mapped IPv6アドレスをIPv4に正規化する
+//! IPv4-mapped IPv6アドレスをIPv4に正規化する。転送ヘッダ（`X-Forwarded-For` /
+//! `Forwarded` / `X-Real-IP`）からのクライアントIP抽出もここに集約する。
 
+use axum::extract::ConnectInfo;
+use axum::http::{HeaderMap, Request};
+use once_cell::sync::Lazy;
 use std::net::{IpAddr, SocketAddr};
 
+/// 転送ヘッダを信頼するプロキシ（起動時の `LLMLB_TRUSTED_PROXIES`）
+static TRUSTED_PROXIES: Lazy<Vec<IpAddr>> = Lazy::new(crate::config::trusted_proxies);
+
 /// IPアドレスを正規化する
 ///
 /// IPv4-mapped IPv6（::ffff:x.x.x.x）をIPv4に変換。
@@ -26,6 +33,145 @@ pub fn normalize_socket_ip(addr: &SocketAddr) -> IpAddr {
     normalize_ip(addr.ip())
 }
 
+/// 転送ヘッダから元のクライアントIPを抽出する（`X-Forwarded-For` → `Forwarded` の順）
+///
+/// ヘッダはクライアントが自由に設定できるため、リクエスト履歴の表示など
+/// 識別に使わない用途に限る。制限の判定には [`resolve_client_ip`] を使う。
+pub fn extract_client_ip_from_headers(headers: &HeaderMap) -> Option<IpAddr> {
+    extract_x_forwarded_for(headers).or_else(|| extract_forwarded_for(headers))
+}
+
+/// `X-Forwarded-For` の先頭の有効なアドレス
+pub fn extract_x_forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    x_forwarded_for_hops(headers).into_iter().next()
+}
+
+/// `Forwarded` の `for=` のうち先頭の有効なアドレス
+pub fn extract_forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    forwarded_for_hops(headers).into_iter().next()
+}
+
+fn x_forwarded_for_hops(headers: &HeaderMap) -> Vec<IpAddr> {
+    let Some(value) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) else {
+        return Vec::new();
+    };
+    value
+        .split(',')
+        .map(str::trim)
+        .filter_map(parse_client_ip_from_forwarded_value)
+        .collect()
+}
+
+fn forwarded_for_hops(headers: &HeaderMap) -> Vec<IpAddr> {
+    let Some(value) = headers.get("forwarded").and_then(|v| v.to_str().ok()) else {
+        return Vec::new();
+    };
+    value
+        .split(',')
+        .filter_map(|entry| {
+            entry
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .find_map(|(key, value)| {
+                    if key.trim().eq_ignore_ascii_case("for") {
+                        parse_client_ip_from_forwarded_value(value.trim())
+                    } else {
+                        None
+                    }
+                })
+        })
+        .collect()
+}
+
+/// 転送ヘッダの1要素（`203.0.113.1`、`"[2001:db8::1]:443"` 等）をIPアドレスとして解釈する
+///
+/// `unknown` や難読化識別子（`_` 始まり）は `None`。
+pub fn parse_client_ip_from_forwarded_value(value: &str) -> Option<IpAddr> {
+    let trimmed = value.trim().trim_matches('"');
+    if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("unknown") || trimmed.starts_with('_') {
+        return None;
+    }
+
+    let host = if let Some(stripped) = trimmed.strip_prefix('[') {
+        stripped.split(']').next().unwrap_or_default().trim()
+    } else {
+        trimmed
+    };
+
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Some(normalize_ip(ip));
+    }
+
+    if let Some((ip_candidate, _port)) = host.rsplit_once(':') {
+        if !ip_candidate.contains(':') {
+            if let Ok(ip) = ip_candidate.parse::<IpAddr>() {
+                return Some(normalize_ip(ip));
+            }
+        }
+    }
+
+    None
+}
+
+/// 接続元アドレスと転送ヘッダからクライアントIPを決定する
+///
+/// 接続元が `trusted_proxies` に含まれる場合のみ転送ヘッダを採用し、それ以外は
+/// 接続元アドレスを返す。プロキシは `X-Forwarded-For` の末尾に追記していくため、
+/// 右から辿って最初の信頼済みプロキシ以外のアドレスをクライアントとみなす
+/// （左側はクライアントが自由に書ける）。
+pub fn resolve_client_ip(
+    addr: &SocketAddr,
+    headers: &HeaderMap,
+    trusted_proxies: &[IpAddr],
+) -> IpAddr {
+    let peer = normalize_socket_ip(addr);
+    if !trusted_proxies.contains(&peer) {
+        return peer;
+    }
+
+    let mut hops = x_forwarded_for_hops(headers);
+    if hops.is_empty() {
+        hops = forwarded_for_hops(headers);
+    }
+    hops.iter()
+        .rev()
+        .find(|ip| !trusted_proxies.contains(ip))
+        .or(hops.first())
+        .copied()
+        .or_else(|| {
+            headers
+                .get("x-real-ip")
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_client_ip_from_forwarded_value)
+        })
+        .unwrap_or(peer)
+}
+
+/// 接続元が `LLMLB_TRUSTED_PROXIES` に含まれるか
+pub fn is_trusted_proxy(ip: IpAddr) -> bool {
+    TRUSTED_PROXIES.contains(&normalize_ip(ip))
+}
+
+/// 転送ヘッダ（`X-Forwarded-For` / `Forwarded` / `X-Real-IP`）が付いているか
+pub fn has_forwarding_headers(headers: &HeaderMap) -> bool {
+    ["x-forwarded-for", "forwarded", "x-real-ip"]
+        .iter()
+        .any(|name| headers.contains_key(*name))
+}
+
+/// `LLMLB_TRUSTED_PROXIES` に基づいて [`resolve_client_ip`] でクライアントIPを決定する
+pub fn resolve_trusted_client_ip(addr: &SocketAddr, headers: &HeaderMap) -> IpAddr {
+    resolve_client_ip(addr, headers, &TRUSTED_PROXIES)
+}
+
+/// リクエストのクライアントIP（`LLMLB_TRUSTED_PROXIES` に基づいて [`resolve_client_ip`] で決定）
+///
+/// 接続元アドレス（`ConnectInfo`）が無い場合は `None`。
+pub fn client_ip_from_request<B>(request: &Request<B>) -> Option<IpAddr> {
+    let ConnectInfo(addr) = request.extensions().get::<ConnectInfo<SocketAddr>>()?;
+    Some(resolve_trusted_client_ip(addr, request.headers()))
+}
+
 /// IPv6アドレスを/64プレフィックスの文字列に変換する
 ///
 /// IPv4はそのまま返す。IPv6は上位64ビットを保持し下位64ビットをゼロにした
@@ -190,4 +336,44 @@ mod tests {
         let result = ipv6_to_prefix64("::ffff:192.168.1.1");
         assert!(result.ends_with("/64") || result == "::ffff:192.168.1.1");
     }
+
+    #[test]
+    fn resolve_client_ip_ignores_headers_from_untrusted_peer() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.9".parse().unwrap());
+        headers.insert("x-real-ip", "203.0.113.10".parse().unwrap());
+        let peer: SocketAddr = "198.51.100.7:5000".parse().unwrap();
+
+        assert_eq!(
+            resolve_client_ip(&peer, &headers, &[]),
+            "198.51.100.7".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn resolve_client_ip_takes_rightmost_untrusted_hop_behind_trusted_proxy() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let inner_proxy: IpAddr = "10.0.0.3".parse().unwrap();
+        let peer = SocketAddr::new(proxy, 443);
+
+        // 先頭はクライアントが偽装した値、末尾はプロキシが追記した値
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            "1.1.1.1, 203.0.113.9, 10.0.0.3".parse().unwrap(),
+        );
+        assert_eq!(
+            resolve_client_ip(&peer, &headers, &[proxy, inner_proxy]),
+            "203.0.113.9".parse::<IpAddr>().unwrap()
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", "203.0.113.10".parse().unwrap());
+        assert_eq!(
+            resolve_client_ip(&peer, &headers, &[proxy]),
+            "203.0.113.10".parse::<IpAddr>().unwrap()
+        );
+
+        assert_eq!(resolve_client_ip(&peer, &HeaderMap::new(), &[proxy]), proxy);
+    }
 }
@@ -100,6 +100,12 @@ pub struct RequestResponseRecord {
     /// APIキーID（api_keysテーブル参照）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub api_key_id: Option<Uuid>,
+    /// 課金額（USD、単価設定のあるエンドポイントのみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// クライアントが要求したモデル（コンテキスト超過で自動切替した場合のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_model: Option<String>,
 }
 
 /// リクエストタイプ
@@ -420,6 +426,8 @@ impl RequestResponseRecord {
             output_tokens: None,
             total_tokens: None,
             api_key_id,
+            cost_usd: None,
+            requested_model: None,
         }
     }
 
@@ -451,6 +459,8 @@ impl RequestResponseRecord {
             output_tokens: None,
             total_tokens: None,
             api_key_id,
+            cost_usd: None,
+            requested_model: None,
         }
     }
 }
@@ -821,6 +831,8 @@ mod tests {
             output_tokens: Some(50),
             total_tokens: Some(200),
             api_key_id: None,
+            cost_usd: None,
+            requested_model: None,
         };
 
         let json = serde_json::to_string(&record).unwrap();
@@ -858,6 +870,8 @@ mod tests {
             output_tokens: None,
             total_tokens: None,
             api_key_id: None,
+            cost_usd: None,
+            requested_model: None,
         };
 
         let json = serde_json::to_string(&record).unwrap();
@@ -3,6 +3,9 @@
 //! Provides helper functions for reading environment variables with fallback
 //! to deprecated variable names with warning logs.
 
+use once_cell::sync::Lazy;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 use std::time::Duration;
 
 /// Get an environment variable with fallback to a deprecated name
@@ -75,55 +78,1424 @@ pub fn get_env_with_fallback_parse<T: std::str::FromStr>(
         .unwrap_or(default)
 }
 
+/// Get an environment variable without a deprecated fallback name, parsing to a specific type
+///
+/// Used for settings that have only ever been read from their `LLMLB_*` name.
+///
+/// # Arguments
+/// * `name` - The environment variable name
+/// * `default` - The default value to return if it is unset or parsing fails
+///
+/// # Returns
+/// The parsed environment variable value or the default
+pub fn get_env_parse<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Default queue occupancy ratio below which requests are accepted immediately.
+pub const DEFAULT_QUEUE_SOFT_THRESHOLD: f64 = 0.5;
+/// Default queue occupancy ratio at or above which requests are rejected.
+pub const DEFAULT_QUEUE_HARD_THRESHOLD: f64 = 0.8;
+
 /// Queueing configuration (request wait queue)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct QueueConfig {
     /// Maximum number of requests allowed to wait in the queue.
     pub max_waiters: usize,
     /// Maximum time a request may wait in the queue before timing out.
     pub timeout: Duration,
+    /// Queue occupancy ratio (0.0–1.0) where delayed admission starts.
+    pub soft_threshold: f64,
+    /// Queue occupancy ratio (0.0–1.0) where admission is rejected.
+    pub hard_threshold: f64,
+}
+
+impl QueueConfig {
+    /// Load queue configuration from environment variables.
+    ///
+    /// Backpressure thresholds must satisfy `0.0 <= soft < hard <= 1.0`; otherwise a
+    /// warning is logged and both fall back to the defaults (50% / 80%).
+    pub fn from_env() -> Self {
+        let max_waiters = get_env_with_fallback_parse("LLMLB_QUEUE_MAX", "QUEUE_MAX", 100usize);
+        let timeout_secs =
+            get_env_with_fallback_parse("LLMLB_QUEUE_TIMEOUT_SECS", "QUEUE_TIMEOUT_SECS", 60u64);
+        let (soft_threshold, hard_threshold) = queue_thresholds_from_env();
+
+        Self {
+            max_waiters,
+            timeout: Duration::from_secs(timeout_secs),
+            soft_threshold,
+            hard_threshold,
+        }
+    }
+
+    /// Number of waiters where delayed admission starts.
+    pub fn soft_limit(&self) -> usize {
+        (self.max_waiters as f64 * self.soft_threshold) as usize
+    }
+
+    /// Number of waiters where admission is rejected.
+    pub fn hard_limit(&self) -> usize {
+        (self.max_waiters as f64 * self.hard_threshold) as usize
+    }
+}
+
+fn queue_thresholds_from_env() -> (f64, f64) {
+    let soft_raw = std::env::var("LLMLB_QUEUE_SOFT_THRESHOLD").ok();
+    let hard_raw = std::env::var("LLMLB_QUEUE_HARD_THRESHOLD").ok();
+    if soft_raw.is_none() && hard_raw.is_none() {
+        return (DEFAULT_QUEUE_SOFT_THRESHOLD, DEFAULT_QUEUE_HARD_THRESHOLD);
+    }
+
+    let parse = |raw: Option<&String>, default: f64| match raw {
+        Some(value) => value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| (0.0..=1.0).contains(v)),
+        None => Some(default),
+    };
+    let soft = parse(soft_raw.as_ref(), DEFAULT_QUEUE_SOFT_THRESHOLD);
+    let hard = parse(hard_raw.as_ref(), DEFAULT_QUEUE_HARD_THRESHOLD);
+    match (soft, hard) {
+        (Some(soft), Some(hard)) if soft < hard => (soft, hard),
+        _ => {
+            tracing::warn!(
+                soft = soft_raw.as_deref().unwrap_or("-"),
+                hard = hard_raw.as_deref().unwrap_or("-"),
+                "Invalid LLMLB_QUEUE_SOFT_THRESHOLD/LLMLB_QUEUE_HARD_THRESHOLD \
+                 (expected 0.0 <= soft < hard <= 1.0); using defaults"
+            );
+            (DEFAULT_QUEUE_SOFT_THRESHOLD, DEFAULT_QUEUE_HARD_THRESHOLD)
+        }
+    }
+}
+
+/// Environment variables that can be re-applied at runtime via SIGHUP
+pub const RELOADABLE_ENV_VARS: [&str; 5] = [
+    "LLMLB_HEALTH_CHECK_INTERVAL",
+    "LLMLB_LOAD_BALANCER_MODE",
+    "LLMLB_QUEUE_MAX",
+    "LLMLB_QUEUE_TIMEOUT_SECS",
+    "LLMLB_LOG_LEVEL",
+];
+
+const RELOAD_FILE_ENV: &str = "LLMLB_RELOAD_FILE";
+const DEFAULT_RELOAD_FILE: &str = "reload.env";
+
+static RELOADED_CONFIG: Lazy<RwLock<Option<ReloadableConfig>>> = Lazy::new(|| RwLock::new(None));
+
+/// Settings that can be safely reconfigured without restarting the process
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReloadableConfig {
+    /// Endpoint health check interval (seconds)
+    pub health_check_interval_secs: u64,
+    /// Load balancer mode
+    pub load_balancer_mode: String,
+    /// Request wait queue settings
+    pub queue: QueueConfig,
+    /// Log level directives (`EnvFilter` syntax); `None` keeps the startup filter
+    pub log_level: Option<String>,
+}
+
+impl ReloadableConfig {
+    /// Load reloadable settings from environment variables.
+    pub fn from_env() -> Self {
+        Self {
+            health_check_interval_secs: get_env_with_fallback_parse(
+                "LLMLB_HEALTH_CHECK_INTERVAL",
+                "HEALTH_CHECK_INTERVAL",
+                30u64,
+            ),
+            load_balancer_mode: get_env_with_fallback_or(
+                "LLMLB_LOAD_BALANCER_MODE",
+                "LOAD_BALANCER_MODE",
+                "auto",
+            ),
+            queue: QueueConfig::from_env(),
+            log_level: std::env::var("LLMLB_LOG_LEVEL")
+                .ok()
+                .filter(|level| !level.trim().is_empty()),
+        }
+    }
+
+    /// Apply reload file entries on top of these settings.
+    ///
+    /// Settings not present in `entries` keep their current value. Values that
+    /// cannot be parsed are ignored with a warning.
+    pub fn with_entries(&self, entries: &[(String, String)]) -> Self {
+        let mut config = self.clone();
+        for (key, value) in entries {
+            match key.as_str() {
+                "LLMLB_HEALTH_CHECK_INTERVAL" => {
+                    if let Some(secs) = parse_reload_value(key, value) {
+                        config.health_check_interval_secs = secs;
+                    }
+                }
+                "LLMLB_LOAD_BALANCER_MODE" => config.load_balancer_mode = value.clone(),
+                "LLMLB_QUEUE_MAX" => {
+                    if let Some(max) = parse_reload_value(key, value) {
+                        config.queue.max_waiters = max;
+                    }
+                }
+                "LLMLB_QUEUE_TIMEOUT_SECS" => {
+                    if let Some(secs) = parse_reload_value(key, value) {
+                        config.queue.timeout = Duration::from_secs(secs);
+                    }
+                }
+                "LLMLB_LOG_LEVEL" => {
+                    config.log_level = Some(value.clone()).filter(|level| !level.trim().is_empty())
+                }
+                _ => {}
+            }
+        }
+        config
+    }
+
+    /// Names of the settings that differ from `other`.
+    pub fn changed_fields(&self, other: &ReloadableConfig) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.health_check_interval_secs != other.health_check_interval_secs {
+            changed.push("health_check_interval");
+        }
+        if self.load_balancer_mode != other.load_balancer_mode {
+            changed.push("load_balancer_mode");
+        }
+        if self.queue != other.queue {
+            changed.push("queue");
+        }
+        if self.log_level != other.log_level {
+            changed.push("log_level");
+        }
+        changed
+    }
+}
+
+fn parse_reload_value<T: std::str::FromStr>(key: &str, value: &str) -> Option<T> {
+    let parsed = value.trim().parse().ok();
+    if parsed.is_none() {
+        tracing::warn!(key = %key, value = %value, "Invalid reload setting was ignored");
+    }
+    parsed
+}
+
+/// Result of a SIGHUP configuration reload
+#[derive(Debug, Clone)]
+pub struct ConfigReload {
+    /// Settings now in effect
+    pub config: ReloadableConfig,
+    /// Settings that changed by this reload
+    pub changed: Vec<&'static str>,
+    /// Keys in the reload file that require a restart and were ignored
+    pub ignored: Vec<String>,
+}
+
+/// Path of the file read on SIGHUP (`LLMLB_RELOAD_FILE`, default `~/.llmlb/reload.env`)
+pub fn reload_file_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(RELOAD_FILE_ENV) {
+        if !path.trim().is_empty() {
+            return Some(PathBuf::from(path));
+        }
+    }
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+        .map(|home| PathBuf::from(home).join(".llmlb").join(DEFAULT_RELOAD_FILE))
+}
+
+/// Parse `KEY=VALUE` lines of a reload file.
+///
+/// Returns the reloadable entries and the keys that require a restart.
+/// Blank lines, `#` comments and an optional `export ` prefix are accepted.
+pub fn parse_reload_file(content: &str) -> (Vec<(String, String)>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut ignored = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value.trim().trim_matches('"').trim_matches('\'');
+        if RELOADABLE_ENV_VARS.contains(&key) {
+            entries.push((key.to_string(), value.to_string()));
+        } else {
+            ignored.push(key.to_string());
+        }
+    }
+    (entries, ignored)
+}
+
+/// Re-read the reload file and apply the reloadable settings.
+///
+/// Keys that require a restart are ignored with a warning. Returns `None`
+/// when the reload file cannot be read; the current settings stay in effect.
+/// The process environment is left untouched; consumers read the applied
+/// settings through [`reloaded_config`].
+pub fn reload_config() -> Option<ConfigReload> {
+    let path = reload_file_path()?;
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) => {
+            tracing::warn!(
+                path = %path.display(),
+                "Failed to read reload file, keeping current settings: {}",
+                err
+            );
+            return None;
+        }
+    };
+
+    let previous = reloaded_config().unwrap_or_else(ReloadableConfig::from_env);
+    let (entries, ignored) = parse_reload_file(&content);
+    for key in &ignored {
+        tracing::warn!(key = %key, "Setting requires a restart and was ignored by reload");
+    }
+    let config = previous.with_entries(&entries);
+    let changed = config.changed_fields(&previous);
+    *RELOADED_CONFIG.write().unwrap_or_else(|e| e.into_inner()) = Some(config.clone());
+    Some(ConfigReload {
+        config,
+        changed,
+        ignored,
+    })
+}
+
+/// Settings applied by the last reload (`None` if never reloaded)
+pub fn reloaded_config() -> Option<ReloadableConfig> {
+    RELOADED_CONFIG
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Queue settings in effect (the reloaded value takes precedence over `initial`)
+pub fn effective_queue_config(initial: QueueConfig) -> QueueConfig {
+    reloaded_config()
+        .map(|config| config.queue)
+        .unwrap_or(initial)
+}
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Keys accepted in `config.toml` and the environment variables they map to
+///
+/// Keys in a `[section]` table are written as `section.key`. The first variable
+/// is set from the file; the others are legacy names that also take precedence.
+pub const CONFIG_FILE_KEYS: &[(&str, &[&str])] = &[
+    ("host", &["LLMLB_HOST"]),
+    ("port", &["LLMLB_PORT"]),
+    (
+        "log_level",
+        &["LLMLB_LOG_LEVEL", "LLM_LOG_LEVEL", "RUST_LOG"],
+    ),
+    ("database_url", &["LLMLB_DATABASE_URL", "DATABASE_URL"]),
+    (
+        "load_balancer_mode",
+        &["LLMLB_LOAD_BALANCER_MODE", "LOAD_BALANCER_MODE"],
+    ),
+    (
+        "health_check_interval",
+        &["LLMLB_HEALTH_CHECK_INTERVAL", "HEALTH_CHECK_INTERVAL"],
+    ),
+    ("failover_max_retries", &["LLMLB_FAILOVER_MAX_RETRIES"]),
+    ("queue.max", &["LLMLB_QUEUE_MAX", "QUEUE_MAX"]),
+    (
+        "queue.timeout_secs",
+        &["LLMLB_QUEUE_TIMEOUT_SECS", "QUEUE_TIMEOUT_SECS"],
+    ),
+    ("queue.soft_threshold", &["LLMLB_QUEUE_SOFT_THRESHOLD"]),
+    ("queue.hard_threshold", &["LLMLB_QUEUE_HARD_THRESHOLD"]),
+    (
+        "circuit_breaker.threshold",
+        &["LLMLB_CIRCUIT_BREAKER_THRESHOLD"],
+    ),
+    (
+        "circuit_breaker.cooldown_secs",
+        &["LLMLB_CIRCUIT_BREAKER_COOLDOWN_SECS"],
+    ),
+    (
+        "passive_health.min_samples",
+        &["LLMLB_PASSIVE_HEALTH_MIN_SAMPLES"],
+    ),
+    (
+        "passive_health.window_secs",
+        &["LLMLB_PASSIVE_HEALTH_WINDOW_SECS"],
+    ),
+];
+
+/// Error while loading `config.toml`
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigFileError {
+    /// The file could not be read
+    #[error("failed to read config file {}: {source}", .path.display())]
+    Read {
+        /// Path of the configuration file
+        path: PathBuf,
+        /// Underlying I/O error
+        source: std::io::Error,
+    },
+    /// The file is not valid TOML
+    #[error("failed to parse config file {}: {source}", .path.display())]
+    Parse {
+        /// Path of the configuration file
+        path: PathBuf,
+        /// Underlying TOML error
+        source: toml::de::Error,
+    },
+}
+
+/// Result of loading `config.toml` at startup
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFileLoad {
+    /// Path of the configuration file (`None` if no home directory is known)
+    pub path: Option<PathBuf>,
+    /// Whether the file existed and was applied
+    pub loaded: bool,
+    /// Keys applied from the file
+    pub applied: Vec<String>,
+    /// Keys ignored because the environment variable is already set
+    pub overridden: Vec<String>,
+    /// Unknown keys (or unsupported values) that were ignored
+    pub unknown: Vec<String>,
+}
+
+impl ConfigFileLoad {
+    /// Log which keys were applied and warn about ignored ones.
+    pub fn log(&self) {
+        let Some(path) = self.path.as_ref().filter(|_| self.loaded) else {
+            return;
+        };
+        for key in &self.unknown {
+            tracing::warn!(
+                path = %path.display(),
+                key = %key,
+                "Unknown config file key was ignored"
+            );
+        }
+        tracing::info!(
+            path = %path.display(),
+            applied = ?self.applied,
+            overridden_by_env = ?self.overridden,
+            "Loaded config file"
+        );
+    }
+}
+
+/// Path of the configuration file (`explicit`, default `~/.llmlb/config.toml`)
+pub fn config_file_path(explicit: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = explicit {
+        return Some(path.to_path_buf());
+    }
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+        .map(|home| PathBuf::from(home).join(".llmlb").join(CONFIG_FILE_NAME))
+}
+
+/// Parse `config.toml` into `(key, value)` pairs of known keys.
+///
+/// Returns the known entries and the keys that were not recognized. Values must be
+/// strings, numbers or booleans; other values are reported as unknown.
+pub fn parse_config_file(
+    content: &str,
+) -> Result<(Vec<(&'static str, String)>, Vec<String>), toml::de::Error> {
+    let table: toml::Table = toml::from_str(content)?;
+    let mut flattened = Vec::new();
+    for (key, value) in table {
+        match value {
+            toml::Value::Table(section) => {
+                for (sub_key, sub_value) in section {
+                    flattened.push((format!("{key}.{sub_key}"), sub_value));
+                }
+            }
+            value => flattened.push((key, value)),
+        }
+    }
+
+    let mut entries = Vec::new();
+    let mut unknown = Vec::new();
+    for (key, value) in flattened {
+        let known = CONFIG_FILE_KEYS.iter().find(|(name, _)| *name == key);
+        let value = match value {
+            toml::Value::String(value) => Some(value),
+            toml::Value::Integer(value) => Some(value.to_string()),
+            toml::Value::Float(value) => Some(value.to_string()),
+            toml::Value::Boolean(value) => Some(value.to_string()),
+            _ => None,
+        };
+        match (known, value) {
+            (Some((name, _)), Some(value)) => entries.push((*name, value)),
+            _ => unknown.push(key),
+        }
+    }
+    Ok((entries, unknown))
+}
+
+/// Load `config.toml` and apply its settings as environment variable defaults.
+///
+/// Settings whose environment variable is already set are left untouched, so the
+/// precedence is environment variable > config file > built-in default. A missing
+/// default file is not an error; a missing `explicit` file is.
+///
+/// The file is read at startup only; a `SIGHUP` reload re-reads the reload file
+/// ([`reload_config`]) but not `config.toml`.
+///
+/// Call this before logging is initialized and before any other thread starts
+/// (including the Tokio runtime's worker threads).
+pub fn load_config_file(explicit: Option<&Path>) -> Result<ConfigFileLoad, ConfigFileError> {
+    let Some(path) = config_file_path(explicit) else {
+        return Ok(ConfigFileLoad::default());
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if explicit.is_none() && err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(ConfigFileLoad {
+                path: Some(path),
+                ..Default::default()
+            });
+        }
+        Err(source) => return Err(ConfigFileError::Read { path, source }),
+    };
+    let (entries, unknown) = match parse_config_file(&content) {
+        Ok(parsed) => parsed,
+        Err(source) => return Err(ConfigFileError::Parse { path, source }),
+    };
+
+    let mut applied = Vec::new();
+    let mut overridden = Vec::new();
+    for (key, value) in entries {
+        let Some((_, env_vars)) = CONFIG_FILE_KEYS.iter().find(|(name, _)| *name == key) else {
+            continue;
+        };
+        if env_vars.iter().any(|name| std::env::var_os(name).is_some()) {
+            overridden.push(key.to_string());
+            continue;
+        }
+        std::env::set_var(env_vars[0], value);
+        applied.push(key.to_string());
+    }
+
+    Ok(ConfigFileLoad {
+        path: Some(path),
+        loaded: true,
+        applied,
+        overridden,
+        unknown,
+    })
+}
+
+/// Log the settings in effect at startup.
+pub fn log_effective_config(server: &ServerConfig, config_file: &ConfigFileLoad) {
+    let config = ReloadableConfig::from_env();
+    tracing::info!(
+        host = %server.host,
+        port = server.port,
+        load_balancer_mode = %config.load_balancer_mode,
+        health_check_interval_secs = config.health_check_interval_secs,
+        queue_max = config.queue.max_waiters,
+        queue_timeout_secs = config.queue.timeout.as_secs(),
+        queue_soft_threshold = config.queue.soft_threshold,
+        queue_hard_threshold = config.queue.hard_threshold,
+        log_level = config.log_level.as_deref().unwrap_or("info"),
+        config_file = config_file
+            .path
+            .as_ref()
+            .filter(|_| config_file.loaded)
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "-".to_string()),
+        "Effective configuration"
+    );
+}
+
+/// キュー時系列のサンプリング間隔（秒）を取得
+///
+/// 環境変数 `LLMLB_QUEUE_HISTORY_INTERVAL_SECS` から取得（既定: 10、`0` でサンプリング無効）。
+pub fn queue_history_interval_secs() -> u64 {
+    get_env_parse("LLMLB_QUEUE_HISTORY_INTERVAL_SECS", 10u64)
+}
+
+/// TPS時系列のスナップショット間隔（秒）を取得
+///
+/// 環境変数 `LLMLB_TPS_HISTORY_INTERVAL_SECS` から取得（既定: 60、`0` でスナップショット無効）。
+pub fn tps_history_interval_secs() -> u64 {
+    get_env_parse("LLMLB_TPS_HISTORY_INTERVAL_SECS", 60u64)
+}
+
+/// TPS時系列の保持期間（日）を取得
+///
+/// 環境変数 `LLMLB_TPS_HISTORY_RETENTION_DAYS` から取得（既定: 7、最小: 1）。
+pub fn tps_history_retention_days() -> u64 {
+    get_env_parse("LLMLB_TPS_HISTORY_RETENTION_DAYS", 7u64).max(1)
+}
+
+/// キュー時系列の保持期間（時間）を取得
+///
+/// 環境変数 `LLMLB_QUEUE_HISTORY_RETENTION_HOURS` から取得（既定: 24、最小: 1）。
+pub fn queue_history_retention_hours() -> u64 {
+    get_env_parse("LLMLB_QUEUE_HISTORY_RETENTION_HOURS", 24u64).max(1)
+}
+
+/// ストリーミング再接続の発動条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamReconnectCause {
+    /// アップストリームへの接続・送信に失敗した（タイムアウト含む）
+    ConnectError,
+    /// アップストリームが5xxを返した
+    ServerError,
+    /// 最初のチャンクを受信する前にストリームが切断された
+    EarlyDisconnect,
+}
+
+impl StreamReconnectCause {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "connect" | "connect_error" => Some(Self::ConnectError),
+            "5xx" | "server_error" => Some(Self::ServerError),
+            "disconnect" | "early_disconnect" => Some(Self::EarlyDisconnect),
+            _ => None,
+        }
+    }
+}
+
+/// ストリーミング再接続設定
+///
+/// first-token（最初のチャンク）送出前の失敗に限り、別エンドポイントで
+/// リクエストを最初からやり直す。token送出後の切断は救済しない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamReconnectConfig {
+    /// 別エンドポイントでやり直す最大回数（0で無効）
+    pub max_attempts: u32,
+    /// 接続エラー時に再接続する
+    pub on_connect_error: bool,
+    /// 5xx応答時に再接続する
+    pub on_server_error: bool,
+    /// first-token前の切断時に再接続する
+    pub on_early_disconnect: bool,
+}
+
+impl Default for StreamReconnectConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 0,
+            on_connect_error: true,
+            on_server_error: true,
+            on_early_disconnect: true,
+        }
+    }
+}
+
+impl StreamReconnectConfig {
+    /// 環境変数から読み込む
+    ///
+    /// - `LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS`: 再接続の最大回数（デフォルト: 0 = 無効）
+    /// - `LLMLB_STREAM_RECONNECT_ON`: 発動条件のカンマ区切り
+    ///   （`connect`, `5xx`, `disconnect`。デフォルト: すべて）
+    pub fn from_env() -> Self {
+        let max_attempts = get_env_parse("LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS", 0u32);
+        let mut config = Self {
+            max_attempts,
+            ..Self::default()
+        };
+
+        if let Ok(raw) = std::env::var("LLMLB_STREAM_RECONNECT_ON") {
+            let causes: Vec<StreamReconnectCause> = raw
+                .split(',')
+                .filter_map(StreamReconnectCause::parse)
+                .collect();
+            config.on_connect_error = causes.contains(&StreamReconnectCause::ConnectError);
+            config.on_server_error = causes.contains(&StreamReconnectCause::ServerError);
+            config.on_early_disconnect = causes.contains(&StreamReconnectCause::EarlyDisconnect);
+        }
+
+        config
+    }
+
+    /// 指定条件で再接続が有効か
+    pub fn allows(&self, cause: StreamReconnectCause) -> bool {
+        if self.max_attempts == 0 {
+            return false;
+        }
+        match cause {
+            StreamReconnectCause::ConnectError => self.on_connect_error,
+            StreamReconnectCause::ServerError => self.on_server_error,
+            StreamReconnectCause::EarlyDisconnect => self.on_early_disconnect,
+        }
+    }
+}
+
+/// 段階的タイムアウトエスカレーション設定
+///
+/// 非ストリーミングのリクエストを短いタイムアウトで送り、タイムアウトした場合は
+/// 別エンドポイントへより長いタイムアウトで送り直す。各段階のタイムアウトは
+/// 合計予算の残りで打ち切る。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscalationConfig {
+    /// 段階ごとのタイムアウト（先頭が最初の試行）
+    pub stage_timeouts: Vec<Duration>,
+    /// 全段階を合わせたタイムアウト
+    pub budget: Duration,
+}
+
+impl EscalationConfig {
+    /// 環境変数から読み込む（無効な場合は `None`）
+    ///
+    /// - `LLMLB_ESCALATION`: `1` / `true` で有効（デフォルト: 無効）
+    /// - `LLMLB_ESCALATION_STAGE_TIMEOUTS`: 段階ごとのタイムアウト秒のカンマ区切り
+    ///   （デフォルト: `5,15,40`。不正な値は無視する）
+    /// - `LLMLB_ESCALATION_BUDGET_SECS`: 合計予算（秒、デフォルト: 60）
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("LLMLB_ESCALATION")
+            .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let stage_timeouts: Vec<Duration> = std::env::var("LLMLB_ESCALATION_STAGE_TIMEOUTS")
+            .unwrap_or_else(|_| "5,15,40".to_string())
+            .split(',')
+            .filter_map(|value| value.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .collect();
+        let budget_secs = get_env_parse("LLMLB_ESCALATION_BUDGET_SECS", 60u64);
+        if stage_timeouts.is_empty() || budget_secs == 0 {
+            tracing::warn!(
+                "LLMLB_ESCALATION is enabled but no valid stage timeouts or budget are set; \
+                 escalation disabled"
+            );
+            return None;
+        }
+
+        Some(Self {
+            stage_timeouts,
+            budget: Duration::from_secs(budget_secs),
+        })
+    }
+
+    /// 段階数
+    pub fn stages(&self) -> usize {
+        self.stage_timeouts.len()
+    }
+
+    /// `stage` 段目（0始まり）のタイムアウトを、予算の残り（`budget - elapsed`）で打ち切って返す
+    pub fn stage_timeout(&self, stage: usize, elapsed: Duration) -> Duration {
+        let index = stage.min(self.stage_timeouts.len() - 1);
+        self.stage_timeouts[index].min(self.budget.saturating_sub(elapsed))
+    }
+
+    /// `stage` 段目の次の段階（段階を使い切った、または予算を使い切った場合は `None`）
+    pub fn next_stage(&self, stage: usize, elapsed: Duration) -> Option<usize> {
+        (stage + 1 < self.stage_timeouts.len() && elapsed < self.budget).then_some(stage + 1)
+    }
+}
+
+/// デフォルトembeddingモデルを取得
+///
+/// 環境変数 `LLMLB_DEFAULT_EMBEDDING_MODEL`（旧: `LLM_DEFAULT_EMBEDDING_MODEL`）から取得し、
+/// 未設定の場合は `nomic-embed-text-v1.5` を返す。
+pub fn get_default_embedding_model() -> String {
+    get_env_with_fallback(
+        "LLMLB_DEFAULT_EMBEDDING_MODEL",
+        "LLM_DEFAULT_EMBEDDING_MODEL",
+    )
+    .unwrap_or_else(|| "nomic-embed-text-v1.5".to_string())
+}
+
+/// エンドポイントのモデル自動同期の最短間隔を取得
+///
+/// ヘルスチェック成功時にモデル同期（`GET /v1/models` + DB反映）を実行する際、
+/// 同一エンドポイントに対して過剰に同期しないためのスロットリングに使用する。
+///
+/// 環境変数 `LLMLB_AUTO_SYNC_MODELS_INTERVAL_SECS` から取得し、
+/// 未設定の場合は 15 分（900 秒）を使用する。
+pub fn get_auto_sync_models_interval() -> Duration {
+    let secs = get_env_with_fallback_parse(
+        "LLMLB_AUTO_SYNC_MODELS_INTERVAL_SECS",
+        "AUTO_SYNC_MODELS_INTERVAL_SECS",
+        900u64,
+    );
+    Duration::from_secs(secs)
+}
+
+/// エンドポイント証明書の期限警告を出す残り日数を取得
+///
+/// 環境変数 `LLMLB_CERT_EXPIRY_WARNING_DAYS` から取得し、未設定の場合は 30 日を使用する。
+pub fn get_cert_expiry_warning_days() -> i64 {
+    get_env_parse("LLMLB_CERT_EXPIRY_WARNING_DAYS", 30i64)
+}
+
+/// `/v1/models` のエンドポイント別モデル一覧キャッシュのTTL（秒）を取得
+///
+/// 環境変数 `LLMLB_MODEL_LIST_TTL_SECS` から取得し、未設定の場合は 60 秒を使用する。
+/// `0` でキャッシュを無効化する。
+pub fn model_list_ttl_secs() -> u64 {
+    get_env_parse(
+        "LLMLB_MODEL_LIST_TTL_SECS",
+        crate::sync::cache::DEFAULT_MODEL_LIST_TTL_SECS,
+    )
+}
+
+/// エンドポイントタイプ検出結果キャッシュのTTL（秒）を取得
+///
+/// 環境変数 `LLMLB_DETECTION_CACHE_TTL` から取得し、未設定の場合は 600 秒を使用する。
+/// `0` でキャッシュを無効化する。
+pub fn detection_cache_ttl_secs() -> u64 {
+    get_env_parse(
+        "LLMLB_DETECTION_CACHE_TTL",
+        crate::detection::cache::DEFAULT_DETECTION_CACHE_TTL_SECS,
+    )
+}
+
+/// 容量予約のあるエンドポイントの同時スロット数を取得
+///
+/// 環境変数 `LLMLB_ENDPOINT_SLOTS` から取得し、未設定の場合は 4 を使用する。
+/// 予約の無いエンドポイントには適用しない。
+pub fn endpoint_slots() -> u32 {
+    get_env_parse("LLMLB_ENDPOINT_SLOTS", 4u32)
+}
+
+/// クライアント（APIキー/IP）あたりの同時ストリーミング接続数の上限を取得
+///
+/// 環境変数 `LLMLB_MAX_STREAMS_PER_CLIENT` から取得し、未設定または `0` の場合は無制限。
+pub fn max_streams_per_client() -> u32 {
+    get_env_parse("LLMLB_MAX_STREAMS_PER_CLIENT", 0u32)
+}
+
+/// ストリーミング応答の既定のトークン/秒上限を取得
+///
+/// 環境変数 `LLMLB_STREAM_MAX_TOKENS_PER_SEC` から取得し、未設定または `0` の場合は無制限。
+/// APIキー/テナント単位の個別設定（`/api/stream-rate-limits`）が無いクライアントに適用する。
+pub fn stream_max_tokens_per_sec() -> u32 {
+    get_env_parse("LLMLB_STREAM_MAX_TOKENS_PER_SEC", 0u32)
+}
+
+/// 起動時の更新ファイル掃除で保持する成功ペイロードの世代数を取得
+///
+/// 環境変数 `LLMLB_UPDATE_RETAIN_GENERATIONS` から取得（既定: 1、最小: 1）。
+/// 実行中バージョンのペイロードは常に1世代目として数える。
+pub fn update_retain_generations() -> usize {
+    get_env_parse("LLMLB_UPDATE_RETAIN_GENERATIONS", 1usize).max(1)
+}
+
+/// 自己更新で追従するリリースチャンネル
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpdateChannel {
+    /// 安定版のみ
+    #[default]
+    Stable,
+    /// 安定版と beta / rc プレリリース
+    Beta,
+    /// すべてのプレリリース
+    Alpha,
+}
+
+impl UpdateChannel {
+    /// チャンネル名
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+            Self::Alpha => "alpha",
+        }
+    }
+}
+
+/// 自己更新のリリースチャンネルを取得
+///
+/// 環境変数 `LLMLB_UPDATE_CHANNEL`（`alpha` / `beta` / `stable`）から取得し、既定は `stable`。
+/// 不明な値は警告を出して `stable` とする。
+pub fn update_channel() -> UpdateChannel {
+    let Ok(raw) = std::env::var("LLMLB_UPDATE_CHANNEL") else {
+        return UpdateChannel::Stable;
+    };
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "" | "stable" => UpdateChannel::Stable,
+        "beta" => UpdateChannel::Beta,
+        "alpha" => UpdateChannel::Alpha,
+        other => {
+            tracing::warn!(
+                channel = other,
+                "Unknown LLMLB_UPDATE_CHANNEL (expected alpha/beta/stable); using stable"
+            );
+            UpdateChannel::Stable
+        }
+    }
+}
+
+/// sticky sessionの割り当てTTL（秒）を取得
+///
+/// 環境変数 `LLMLB_SESSION_AFFINITY_TTL_SECS` から取得（既定: 1800、最小: 1）。
+/// 最後の利用からこの時間が経過した割り当ては破棄される。
+pub fn session_affinity_ttl_secs() -> u64 {
+    get_env_parse("LLMLB_SESSION_AFFINITY_TTL_SECS", 1800u64).max(1)
+}
+
+/// ストリーミング応答の既定のバイト/秒上限を取得
+///
+/// 環境変数 `LLMLB_STREAM_MAX_BYTES_PER_SEC` から取得し、未設定または `0` の場合は無制限。
+pub fn stream_max_bytes_per_sec() -> u64 {
+    get_env_parse("LLMLB_STREAM_MAX_BYTES_PER_SEC", 0u64)
+}
+
+/// プロンプトフィルタを有効化するか
+///
+/// 環境変数 `LLMLB_PROMPT_FILTER` が `1` / `true` の場合に、
+/// ルールファイルに一致するプロンプトを含む推論リクエストを 400 で拒否する。
+pub fn prompt_filter_enabled() -> bool {
+    std::env::var("LLMLB_PROMPT_FILTER")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// 応答の品質フィルタを有効化するか
+///
+/// 環境変数 `LLMLB_QUALITY_FILTER` が `1` / `true` の場合に、
+/// 非ストリーミング応答がルールファイルに違反したら別エンドポイント/モデルで1回再試行する。
+pub fn quality_filter_enabled() -> bool {
+    std::env::var("LLMLB_QUALITY_FILTER")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// コンテキスト長に基づくエンドポイント選択を有効化するか
+///
+/// 環境変数 `LLMLB_CONTEXT_ROUTING` が `1` / `true` の場合に、
+/// 推定入力トークン数に応じてルールファイルで指定したタグのエンドポイントを優先する。
+pub fn context_routing_enabled() -> bool {
+    std::env::var("LLMLB_CONTEXT_ROUTING")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// 接続エラー時に同一ノードへ1回だけ再試行するか
+///
+/// 環境変数 `LLMLB_SAME_NODE_RETRY` が `1` / `true` の場合に有効。既定は無効。
+pub fn same_node_retry_enabled() -> bool {
+    std::env::var("LLMLB_SAME_NODE_RETRY")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// 途中で切断されたストリーミングの送信済みトークンを課金対象にするか
+///
+/// 環境変数 `LLMLB_BILL_PARTIAL_STREAMS` が `0` / `false` の場合、
+/// 完了しなかったストリーミングはコストに計上しない。既定は有効。
+pub fn bill_partial_streams() -> bool {
+    std::env::var("LLMLB_BILL_PARTIAL_STREAMS")
+        .map(|value| !matches!(value.trim().to_ascii_lowercase().as_str(), "0" | "false"))
+        .unwrap_or(true)
+}
+
+/// ロードバランサーのエンドポイント選択方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadBalancerMode {
+    /// TPS優先（同一TPS時はラウンドロビン）
+    #[default]
+    Auto,
+    /// エンドポイントの重みに比例した確率で選択
+    Weighted,
+    /// 処理中リクエスト数が最小のエンドポイントを選択（least-connections）
+    LeastConn,
+}
+
+/// ロードバランサーのエンドポイント選択方式を取得
+///
+/// 環境変数 `LLMLB_LOAD_BALANCER_MODE` が `weighted` の場合は重み付き選択、
+/// `least_conn` の場合は処理中リクエスト数が最小のエンドポイントを選択、
+/// それ以外（既定: `auto`）はTPS優先。SIGHUPで再読込された値があればそちらを優先するため、
+/// 呼び出しごとに評価する。
+pub fn load_balancer_mode() -> LoadBalancerMode {
+    let mode = reloaded_config()
+        .map(|config| config.load_balancer_mode)
+        .unwrap_or_else(|| {
+            get_env_with_fallback_or("LLMLB_LOAD_BALANCER_MODE", "LOAD_BALANCER_MODE", "auto")
+        });
+    match mode.trim().to_ascii_lowercase().as_str() {
+        "weighted" => LoadBalancerMode::Weighted,
+        "least_conn" => LoadBalancerMode::LeastConn,
+        _ => LoadBalancerMode::Auto,
+    }
+}
+
+/// JSONモード応答検証の動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonModeValidation {
+    /// 検証しない
+    #[default]
+    Off,
+    /// 非JSON応答をエラーにする
+    Error,
+    /// 別エンドポイントで再試行し、候補がなければエラーにする
+    Retry,
+}
+
+/// JSONモード（`response_format: {type: json_object}`）応答の検証動作を取得
+///
+/// 環境変数 `LLMLB_JSON_MODE_VALIDATION` が `error` / `retry` の場合に有効。既定は `off`。
+/// ストリーミングは送信済みのため再試行・エラー化はせず、違反の記録のみ行う。
+pub fn json_mode_validation() -> JsonModeValidation {
+    match std::env::var("LLMLB_JSON_MODE_VALIDATION")
+        .map(|value| value.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        Ok("error") => JsonModeValidation::Error,
+        Ok("retry") => JsonModeValidation::Retry,
+        _ => JsonModeValidation::Off,
+    }
+}
+
+/// `GET /metrics`（Prometheus）のアクセス制御
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetricsAuthMode {
+    /// ループバックからの接続のみ許可
+    #[default]
+    Local,
+    /// JWT（admin）または `metrics.read` 権限のAPIキーを要求
+    ApiKey,
+    /// 認証なしで公開
+    Open,
+}
+
+/// `GET /metrics` のアクセス制御を取得
+///
+/// 環境変数 `LLMLB_METRICS_AUTH` が `api_key` の場合は認証必須、`none` の場合は公開、
+/// `local`（既定）はループバックからの接続のみ許可する。
+/// 未知の値は警告を出し、最も制限の強い `local` として扱う。
+pub fn metrics_auth_mode() -> MetricsAuthMode {
+    let Ok(raw) = std::env::var("LLMLB_METRICS_AUTH") else {
+        return MetricsAuthMode::Local;
+    };
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "api_key" => MetricsAuthMode::ApiKey,
+        "none" => MetricsAuthMode::Open,
+        "local" | "" => MetricsAuthMode::Local,
+        _ => {
+            tracing::warn!(
+                value = %raw,
+                "Unknown LLMLB_METRICS_AUTH value (expected local, api_key or none); using local"
+            );
+            MetricsAuthMode::Local
+        }
+    }
+}
+
+/// 非ストリーミングリクエストの5xx・接続エラー時に別エンドポイントで再試行する最大回数
+///
+/// 環境変数 `LLMLB_FAILOVER_MAX_RETRIES` から取得（既定: 0 = 無効）。
+/// ストリーミングは `LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS` で制御する。
+pub fn failover_max_retries() -> u32 {
+    get_env_parse("LLMLB_FAILOVER_MAX_RETRIES", 0u32)
+}
+
+/// JSONモード違反時に別エンドポイントで再試行する最大回数
+///
+/// 環境変数 `LLMLB_JSON_MODE_MAX_RETRIES` から取得（既定: 1）。
+pub fn json_mode_max_retries() -> u32 {
+    get_env_parse("LLMLB_JSON_MODE_MAX_RETRIES", 1u32)
+}
+
+/// ルーティング結果ヘッダを応答に付与するか
+///
+/// 環境変数 `LLMLB_EXPOSE_ROUTING_HEADERS` が `1` / `true` の場合に
+/// `X-LLMLB-Endpoint` / `X-LLMLB-Model` / `X-LLMLB-Retries` を付与する。
+/// 内部構成の漏洩を避けるため既定は無効。
+pub fn expose_routing_headers() -> bool {
+    std::env::var("LLMLB_EXPOSE_ROUTING_HEADERS")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// コンテキスト超過時に大きいコンテキストのモデルへ自動切替するか
+///
+/// 環境変数 `LLMLB_AUTO_DOWNGRADE` が `1` / `true` の場合に有効。既定は無効。
+pub fn auto_downgrade_enabled() -> bool {
+    std::env::var("LLMLB_AUTO_DOWNGRADE")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// 失敗時の詳細エラーコンテキストを応答に含めるか
+///
+/// 環境変数 `LLMLB_VERBOSE_ERRORS` が `1` / `true` の場合に有効。既定は無効。
+/// 有効時も詳細を返すのは operator 権限（`endpoints.manage`）を持つAPIキーのみ。
+pub fn verbose_errors_enabled() -> bool {
+    std::env::var("LLMLB_VERBOSE_ERRORS")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// 起動時にエンドポイントのウォームアップを行うか
+///
+/// 環境変数 `LLMLB_WARMUP_ON_START` が `1` / `true` の場合に有効。既定は無効。
+pub fn warmup_on_start() -> bool {
+    std::env::var("LLMLB_WARMUP_ON_START")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// モデル別の同時実行上限を取得
+///
+/// 環境変数 `LLMLB_MODEL_MAX_CONCURRENCY` に `モデルID=上限` をカンマ区切りで指定する
+/// （例: `gpt-oss:120b=2,llama3:70b=4`）。指定の無いモデルは無制限。
+/// 不正な項目と上限 `0` の項目は無視する。
+pub fn model_max_concurrency() -> Vec<(String, usize)> {
+    let Ok(raw) = std::env::var("LLMLB_MODEL_MAX_CONCURRENCY") else {
+        return Vec::new();
+    };
+    raw.split(',')
+        .filter_map(|item| {
+            let (model_id, max) = item.trim().rsplit_once('=')?;
+            let model_id = model_id.trim();
+            let max = max.trim().parse::<usize>().ok().filter(|max| *max > 0)?;
+            (!model_id.is_empty()).then(|| (model_id.to_string(), max))
+        })
+        .collect()
+}
+
+/// モデル別同時実行上限を超えたときの動作を取得
+///
+/// 環境変数 `LLMLB_MODEL_CONCURRENCY_MODE` が `queue` の場合は空きを待ち
+/// （最大 `LLMLB_QUEUE_TIMEOUT_SECS`）、それ以外（既定: `reject`）は即座に429を返す。
+pub fn model_concurrency_mode() -> crate::inference_gate::ModelLimitMode {
+    match std::env::var("LLMLB_MODEL_CONCURRENCY_MODE")
+        .unwrap_or_else(|_| "reject".to_string())
+        .trim()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "queue" => crate::inference_gate::ModelLimitMode::Queue {
+            timeout: QueueConfig::from_env().timeout,
+        },
+        _ => crate::inference_gate::ModelLimitMode::Reject,
+    }
+}
+
+/// 応答トークン数の異常判定に使うzスコア閾値を取得
+///
+/// 環境変数 `LLMLB_RESPONSE_ANOMALY_ZSCORE` から取得し、未設定の場合は 3.0 を使用する。
+/// `0` 以下で異常検知を無効化する。
+pub fn response_anomaly_zscore() -> f64 {
+    get_env_parse("LLMLB_RESPONSE_ANOMALY_ZSCORE", 3.0f64)
+}
+
+/// 応答トークン数の異常判定に使うウィンドウ（直近の応答件数）を取得
+///
+/// 環境変数 `LLMLB_RESPONSE_ANOMALY_WINDOW` から取得（既定: 10、最小: 1）。
+/// ウィンドウ内の過半数が外れ値になった場合に degraded 相当とみなす。
+pub fn response_anomaly_window() -> usize {
+    get_env_parse("LLMLB_RESPONSE_ANOMALY_WINDOW", 10usize).max(1)
+}
+
+/// サーキットブレーカーを open にする連続エラー数を取得
+///
+/// 環境変数 `LLMLB_CIRCUIT_BREAKER_THRESHOLD` から取得し、未設定の場合は 5 を使用する。
+/// `0` でサーキットブレーカーを無効化する。
+pub fn circuit_breaker_threshold() -> u32 {
+    get_env_parse("LLMLB_CIRCUIT_BREAKER_THRESHOLD", 5u32)
+}
+
+/// サーキットブレーカーの open から half-open までのクールダウンを取得
+///
+/// 環境変数 `LLMLB_CIRCUIT_BREAKER_COOLDOWN_SECS` から取得（既定: 30秒）。
+pub fn circuit_breaker_cooldown() -> Duration {
+    Duration::from_secs(get_env_parse("LLMLB_CIRCUIT_BREAKER_COOLDOWN_SECS", 30u64))
+}
+
+/// パッシブヘルス検知で suspect にする最小連続エラー数を取得
+///
+/// 環境変数 `LLMLB_PASSIVE_HEALTH_MIN_SAMPLES` から取得（既定: 3）。`0` で無効化する。
+pub fn passive_health_min_samples() -> u32 {
+    get_env_parse("LLMLB_PASSIVE_HEALTH_MIN_SAMPLES", 3u32)
+}
+
+/// パッシブヘルス検知で連続エラーを数える時間幅を取得
+///
+/// 環境変数 `LLMLB_PASSIVE_HEALTH_WINDOW_SECS` から取得（既定: 10秒、最小1秒）。
+pub fn passive_health_window() -> Duration {
+    Duration::from_secs(get_env_parse("LLMLB_PASSIVE_HEALTH_WINDOW_SECS", 10u64).max(1))
+}
+
+/// カナリアを自動停止（0%）する直近エラー率の閾値を取得
+///
+/// 環境変数 `LLMLB_CANARY_MAX_ERROR_RATE` から取得（既定: 0.2、0〜1に丸める）。
+/// `0` でエラー率による停止を無効化する（サーキットブレーカーの open では常に停止する）。
+pub fn canary_max_error_rate() -> f64 {
+    let rate = get_env_parse("LLMLB_CANARY_MAX_ERROR_RATE", 0.2f64);
+    if rate.is_nan() {
+        0.0
+    } else {
+        rate.clamp(0.0, 1.0)
+    }
+}
+
+/// レイテンシ基準（全エンドポイントの中央値）の再計算間隔を取得
+///
+/// 環境変数 `LLMLB_LATENCY_BASELINE_INTERVAL_SECS` から取得（既定: 60秒、最小: 1秒）。
+pub fn latency_baseline_interval() -> Duration {
+    Duration::from_secs(get_env_parse("LLMLB_LATENCY_BASELINE_INTERVAL_SECS", 60u64).max(1))
+}
+
+/// レイテンシ penalty の判定倍率を取得
+///
+/// 環境変数 `LLMLB_LATENCY_PENALTY_FACTOR` から取得し、未設定の場合は 3.0 を使用する。
+/// p50 レイテンシが基準値のこの倍数を超えたエンドポイントを後回しにする。`0` 以下で無効化する。
+pub fn latency_penalty_factor() -> f64 {
+    get_env_parse("LLMLB_LATENCY_PENALTY_FACTOR", 3.0f64)
+}
+
+/// 推論レイテンシの外れ値判定に使うパーセンタイルを取得
+///
+/// 環境変数 `LLMLB_LATENCY_OUTLIER_PERCENTILE` から取得し、未設定の場合は 99.0 を使用する（最大 100）。
+/// 直近分布のこのパーセンタイルを超える計測値は、推論レイテンシの EMA に入れる前にクリップする。
+/// `0` 以下で無効化する。
+pub fn latency_outlier_percentile() -> f64 {
+    get_env_parse("LLMLB_LATENCY_OUTLIER_PERCENTILE", 99.0f64).min(100.0)
+}
+
+/// 推定応答時間の SLO を取得
+///
+/// 環境変数 `LLMLB_RESPONSE_TIME_SLO_MS` から取得（既定: 0=無効）。
+/// 推定応答時間がこの値の `LLMLB_RESPONSE_TIME_SLO_FACTOR` 倍を超える場合はアドミッション判定を厳しくする。
+pub fn response_time_slo() -> Option<Duration> {
+    let slo_ms = get_env_parse("LLMLB_RESPONSE_TIME_SLO_MS", 0u64);
+    (slo_ms > 0).then(|| Duration::from_millis(slo_ms))
+}
+
+/// 推定応答時間が SLO を「大きく超える」とみなす倍率を取得
+///
+/// 環境変数 `LLMLB_RESPONSE_TIME_SLO_FACTOR` から取得（既定: 2.0、最小1.0）。
+pub fn response_time_slo_factor() -> f64 {
+    get_env_parse("LLMLB_RESPONSE_TIME_SLO_FACTOR", 2.0f64).max(1.0)
+}
+
+/// アダプティブレートリミッタが送信許可レートを半減する 429 率を取得
+///
+/// 環境変数 `LLMLB_ADAPTIVE_RATE_429_THRESHOLD` から取得し、未設定の場合は 0.05 を使用する。
+/// 1秒ごとの調整区間で、アップストリーム応答に占める 429 の割合がこの値を超えると許可レートを半減する。
+/// `0` 以下で無効化する。
+pub fn adaptive_rate_429_threshold() -> f64 {
+    get_env_parse("LLMLB_ADAPTIVE_RATE_429_THRESHOLD", 0.05f64)
+}
+
+/// アダプティブレートリミッタの加算増加量（req/s）を取得
+///
+/// 環境変数 `LLMLB_ADAPTIVE_RATE_INCREASE_RPS` から取得し、未設定の場合は 1.0 を使用する。
+/// 429 が収まっている調整区間ごとに、送信許可レートをこの値だけ増やす。
+pub fn adaptive_rate_increase_rps() -> f64 {
+    get_env_parse("LLMLB_ADAPTIVE_RATE_INCREASE_RPS", 1.0f64)
+}
+
+/// APIキーごとのレート制限（req/s）を取得
+///
+/// 環境変数 `LLMLB_RATE_LIMIT_API_KEY_RPS` から取得（既定: 0 = 無制限）。
+/// `LLMLB_RATE_LIMIT_SCOPE_RPS` でスコープ別の値が指定されている場合はそちらを優先する。
+pub fn rate_limit_api_key_rps() -> f64 {
+    get_env_parse("LLMLB_RATE_LIMIT_API_KEY_RPS", 0.0f64)
+}
+
+/// クライアントIPごとのレート制限（req/s）を取得
+///
+/// 環境変数 `LLMLB_RATE_LIMIT_IP_RPS` から取得（既定: 0 = 無制限）。
+pub fn rate_limit_ip_rps() -> f64 {
+    get_env_parse("LLMLB_RATE_LIMIT_IP_RPS", 0.0f64)
+}
+
+/// APIキーのスコープ別レート制限（req/s）を取得
+///
+/// 環境変数 `LLMLB_RATE_LIMIT_SCOPE_RPS` に `スコープ=上限` をカンマ区切りで指定する
+/// （例: `read-only=5,inference=20,admin=0`、`0` は無制限）。
+/// 権限がスコープのプリセットと一致するAPIキーに適用する。不正な項目は無視する。
+pub fn rate_limit_scope_rps() -> Vec<(crate::common::auth::ApiKeyScope, f64)> {
+    use crate::common::auth::ApiKeyScope;
+
+    let Ok(raw) = std::env::var("LLMLB_RATE_LIMIT_SCOPE_RPS") else {
+        return Vec::new();
+    };
+    raw.split(',')
+        .filter_map(|item| {
+            let (scope, rps) = item.trim().split_once('=')?;
+            let scope = scope.parse::<ApiKeyScope>().ok()?;
+            let rps = rps.trim().parse::<f64>().ok().filter(|rps| *rps >= 0.0)?;
+            Some((scope, rps))
+        })
+        .collect()
+}
+
+/// 転送ヘッダ（`X-Forwarded-For` / `Forwarded` / `X-Real-IP`）を信頼するプロキシのIPを取得
+///
+/// 環境変数 `LLMLB_TRUSTED_PROXIES` にIPアドレスをカンマ区切りで指定する。不正な項目は無視する。
+/// 未設定の場合はどの接続元の転送ヘッダも信頼せず、接続元アドレスをクライアントIPとする。
+pub fn trusted_proxies() -> Vec<std::net::IpAddr> {
+    std::env::var("LLMLB_TRUSTED_PROXIES")
+        .ok()
+        .map(|raw| {
+            raw.split(',')
+                .filter_map(|item| item.trim().parse().ok())
+                .map(crate::common::ip::normalize_ip)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// nonce（`X-LLMLB-Nonce`）付きリクエストの受付期間を取得
+///
+/// 環境変数 `LLMLB_NONCE_TTL_SECS` から取得（既定: 300秒、最小: 1秒）。
+/// `X-LLMLB-Timestamp` が現在時刻からこの期間以上ずれたリクエストと、
+/// 期間内に同じ nonce を再利用したリクエストは 401 で拒否する。
+pub fn nonce_ttl() -> Duration {
+    Duration::from_secs(get_env_parse("LLMLB_NONCE_TTL_SECS", 300u64).max(1))
+}
+
+/// APIキーごとに保持する使用済み nonce の上限を取得
+///
+/// 環境変数 `LLMLB_NONCE_MAX_PER_KEY` から取得（既定: 10000、最小: 1）。
+/// 受付期間内の nonce がこの件数に達したAPIキーのリクエストは 429 で拒否する。
+pub fn nonce_max_per_key() -> usize {
+    get_env_parse("LLMLB_NONCE_MAX_PER_KEY", 10_000usize).max(1)
+}
+
+/// nonce を必須にするAPIキーIDを取得
+///
+/// 環境変数 `LLMLB_NONCE_API_KEYS` にAPIキーIDをカンマ区切りで指定する。不正な項目は無視する。
+pub fn nonce_api_keys() -> Vec<uuid::Uuid> {
+    std::env::var("LLMLB_NONCE_API_KEYS")
+        .ok()
+        .map(|raw| {
+            raw.split(',')
+                .filter_map(|item| item.trim().parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// nonce を必須にするAPIキーのスコープを取得
+///
+/// 環境変数 `LLMLB_NONCE_SCOPES` にスコープ（`read-only` / `inference` / `admin`）を
+/// カンマ区切りで指定する。権限がスコープのプリセットと一致するAPIキーに適用する。
+pub fn nonce_scopes() -> Vec<crate::common::auth::ApiKeyScope> {
+    std::env::var("LLMLB_NONCE_SCOPES")
+        .ok()
+        .map(|raw| {
+            raw.split(',')
+                .filter_map(|item| item.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 動的同時実行上限の最大値を取得
+///
+/// 環境変数 `LLMLB_DYNAMIC_CONCURRENCY_MAX` から取得（既定: 0 = 無効）。
+/// `1` 以上の場合、エンドポイント別の同時実行上限をレイテンシに応じて 1〜この値の範囲で調整し、
+/// 上限に達したエンドポイントは余裕のある他のエンドポイントがあればルーティング候補から外す。
+pub fn dynamic_concurrency_max() -> u32 {
+    get_env_parse("LLMLB_DYNAMIC_CONCURRENCY_MAX", 0u32)
 }
 
-impl QueueConfig {
-    /// Load queue configuration from environment variables.
-    pub fn from_env() -> Self {
-        let max_waiters = get_env_with_fallback_parse("LLMLB_QUEUE_MAX", "QUEUE_MAX", 100usize);
-        let timeout_secs =
-            get_env_with_fallback_parse("LLMLB_QUEUE_TIMEOUT_SECS", "QUEUE_TIMEOUT_SECS", 60u64);
+/// リクエストトレースのサンプリング率を取得
+///
+/// 環境変数 `LLMLB_TRACE_SAMPLE_RATE` から取得し、未設定の場合は 1.0（全件）を使用する。
+/// 0.0〜1.0 の範囲に丸める。5xx で終わったリクエストはこの値に関わらず記録される。
+pub fn trace_sample_rate() -> f64 {
+    let rate = get_env_parse("LLMLB_TRACE_SAMPLE_RATE", 1.0f64);
+    if rate.is_nan() {
+        return 1.0;
+    }
+    rate.clamp(0.0, 1.0)
+}
 
-        Self {
-            max_waiters,
-            timeout: Duration::from_secs(timeout_secs),
-        }
+/// 監査ログハッシュチェーンの定期差分検証の間隔を取得
+///
+/// 環境変数 `LLMLB_AUDIT_VERIFY_INTERVAL_HOURS` から取得し、未設定の場合は24時間を使用する。
+/// `0` で定期差分検証を無効化する（起動時の差分検証は常に行う）。
+pub fn audit_verify_interval() -> Duration {
+    Duration::from_secs(
+        get_env_parse("LLMLB_AUDIT_VERIFY_INTERVAL_HOURS", 24u64).saturating_mul(60 * 60),
+    )
+}
+
+/// 監査ログハッシュチェーンの定期全走査の間隔を取得
+///
+/// 環境変数 `LLMLB_AUDIT_FULL_VERIFY_INTERVAL_HOURS` から取得し、未設定の場合は168時間（週次）を使用する。
+/// `0` で定期全走査を無効化する。
+pub fn audit_full_verify_interval() -> Duration {
+    Duration::from_secs(
+        get_env_parse("LLMLB_AUDIT_FULL_VERIFY_INTERVAL_HOURS", 168u64).saturating_mul(60 * 60),
+    )
+}
+
+/// リクエスト入出力サンプルの保存率を取得
+///
+/// 環境変数 `LLMLB_SAMPLE_IO_RATE` から取得し、未設定の場合は 0.0（保存しない）を使用する。
+/// 0.0〜1.0 の範囲に丸める。0 より大きい場合、エラー応答はこの値に関わらず保存される。
+pub fn sample_io_rate() -> f64 {
+    let rate = get_env_parse("LLMLB_SAMPLE_IO_RATE", 0.0f64);
+    if rate.is_nan() {
+        return 0.0;
     }
+    rate.clamp(0.0, 1.0)
 }
 
-/// デフォルトembeddingモデルを取得
+/// リクエスト入出力サンプルの保存期間を取得
 ///
-/// 環境変数 `LLMLB_DEFAULT_EMBEDDING_MODEL`（旧: `LLM_DEFAULT_EMBEDDING_MODEL`）から取得し、
-/// 未設定の場合は `nomic-embed-text-v1.5` を返す。
-pub fn get_default_embedding_model() -> String {
-    get_env_with_fallback(
-        "LLMLB_DEFAULT_EMBEDDING_MODEL",
-        "LLM_DEFAULT_EMBEDDING_MODEL",
+/// 環境変数 `LLMLB_SAMPLE_IO_TTL_HOURS` から取得し、未設定の場合は72時間を使用する（最小1時間）。
+pub fn sample_io_ttl() -> Duration {
+    Duration::from_secs(
+        get_env_parse("LLMLB_SAMPLE_IO_TTL_HOURS", 72u64)
+            .max(1)
+            .saturating_mul(60 * 60),
     )
-    .unwrap_or_else(|| "nomic-embed-text-v1.5".to_string())
 }
 
-/// エンドポイントのモデル自動同期の最短間隔を取得
+/// リクエスト入出力サンプルの保存容量の上限（バイト）を取得
 ///
-/// ヘルスチェック成功時にモデル同期（`GET /v1/models` + DB反映）を実行する際、
-/// 同一エンドポイントに対して過剰に同期しないためのスロットリングに使用する。
+/// 環境変数 `LLMLB_SAMPLE_IO_MAX_MB` から取得し、未設定の場合は100MBを使用する。
+/// 上限を超えた場合は古いサンプルから削除する。
+pub fn sample_io_max_bytes() -> u64 {
+    get_env_parse("LLMLB_SAMPLE_IO_MAX_MB", 100u64).saturating_mul(1024 * 1024)
+}
+
+/// クラウドモデルの単価テーブルを取得
 ///
-/// 環境変数 `LLMLB_AUTO_SYNC_MODELS_INTERVAL_SECS` から取得し、
-/// 未設定の場合は 15 分（900 秒）を使用する。
-pub fn get_auto_sync_models_interval() -> Duration {
-    let secs = get_env_with_fallback_parse(
-        "LLMLB_AUTO_SYNC_MODELS_INTERVAL_SECS",
-        "AUTO_SYNC_MODELS_INTERVAL_SECS",
-        900u64,
-    );
-    Duration::from_secs(secs)
+/// 環境変数 `LLMLB_CLOUD_PRICING` に `モデル=入力単価/出力単価`（USD / 1Kトークン）を
+/// カンマ区切りで指定する（例: `openai:gpt-4o=0.0025/0.01,anthropic:*=0.003/0.015`）。
+/// モデルを `プロバイダ:*` とするとそのプロバイダの既定単価になる。不正な項目と負の単価は無視する。
+pub fn cloud_pricing() -> Vec<(String, crate::cloud_metrics::CloudPrice)> {
+    let Ok(raw) = std::env::var("LLMLB_CLOUD_PRICING") else {
+        return Vec::new();
+    };
+    raw.split(',')
+        .filter_map(|item| {
+            let (model, prices) = item.trim().rsplit_once('=')?;
+            let (input, output) = prices.split_once('/')?;
+            let input_per_1k = input.trim().parse::<f64>().ok()?;
+            let output_per_1k = output.trim().parse::<f64>().ok()?;
+            let model = model.trim();
+            let valid = !model.is_empty()
+                && input_per_1k.is_finite()
+                && output_per_1k.is_finite()
+                && input_per_1k >= 0.0
+                && output_per_1k >= 0.0;
+            valid.then(|| {
+                (
+                    model.to_string(),
+                    crate::cloud_metrics::CloudPrice {
+                        input_per_1k,
+                        output_per_1k,
+                    },
+                )
+            })
+        })
+        .collect()
 }
 
 /// サーバーのホスト・ポート設定
@@ -144,8 +1516,14 @@ impl ServerConfig {
     }
 
     /// コマンドライン引数からサーバー設定を作成する
-    pub fn from_args(host: String, port: u16) -> Self {
-        Self { host, port }
+    ///
+    /// 指定されなかった値は環境変数（設定ファイルを含む）・デフォルトから補う。
+    pub fn from_args(host: Option<String>, port: Option<u16>) -> Self {
+        let defaults = Self::from_env();
+        Self {
+            host: host.unwrap_or(defaults.host),
+            port: port.unwrap_or(defaults.port),
+        }
     }
 
     /// バインドアドレス文字列を返す
@@ -304,4 +1682,724 @@ mod tests {
         assert_eq!(get_auto_sync_models_interval(), Duration::from_secs(60));
         std::env::remove_var("LLMLB_AUTO_SYNC_MODELS_INTERVAL_SECS");
     }
+
+    #[test]
+    #[serial]
+    fn test_expose_routing_headers_flag() {
+        std::env::remove_var("LLMLB_EXPOSE_ROUTING_HEADERS");
+        assert!(!expose_routing_headers());
+        std::env::set_var("LLMLB_EXPOSE_ROUTING_HEADERS", "1");
+        assert!(expose_routing_headers());
+        std::env::set_var("LLMLB_EXPOSE_ROUTING_HEADERS", "0");
+        assert!(!expose_routing_headers());
+        std::env::remove_var("LLMLB_EXPOSE_ROUTING_HEADERS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_auto_downgrade_flag() {
+        std::env::remove_var("LLMLB_AUTO_DOWNGRADE");
+        assert!(!auto_downgrade_enabled());
+        std::env::set_var("LLMLB_AUTO_DOWNGRADE", "1");
+        assert!(auto_downgrade_enabled());
+        std::env::remove_var("LLMLB_AUTO_DOWNGRADE");
+    }
+
+    #[test]
+    #[serial]
+    fn test_verbose_errors_flag() {
+        std::env::remove_var("LLMLB_VERBOSE_ERRORS");
+        assert!(!verbose_errors_enabled());
+        std::env::set_var("LLMLB_VERBOSE_ERRORS", "true");
+        assert!(verbose_errors_enabled());
+        std::env::remove_var("LLMLB_VERBOSE_ERRORS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_canary_max_error_rate() {
+        std::env::remove_var("LLMLB_CANARY_MAX_ERROR_RATE");
+        assert_eq!(canary_max_error_rate(), 0.2);
+        std::env::set_var("LLMLB_CANARY_MAX_ERROR_RATE", "0.5");
+        assert_eq!(canary_max_error_rate(), 0.5);
+        std::env::set_var("LLMLB_CANARY_MAX_ERROR_RATE", "3");
+        assert_eq!(canary_max_error_rate(), 1.0);
+        std::env::remove_var("LLMLB_CANARY_MAX_ERROR_RATE");
+    }
+
+    #[test]
+    #[serial]
+    fn test_warmup_on_start_flag() {
+        std::env::remove_var("LLMLB_WARMUP_ON_START");
+        assert!(!warmup_on_start());
+        std::env::set_var("LLMLB_WARMUP_ON_START", "1");
+        assert!(warmup_on_start());
+        std::env::set_var("LLMLB_WARMUP_ON_START", "0");
+        assert!(!warmup_on_start());
+        std::env::remove_var("LLMLB_WARMUP_ON_START");
+    }
+
+    #[test]
+    #[serial]
+    fn test_metrics_auth_mode() {
+        std::env::remove_var("LLMLB_METRICS_AUTH");
+        assert_eq!(metrics_auth_mode(), MetricsAuthMode::Local);
+        std::env::set_var("LLMLB_METRICS_AUTH", "API_KEY");
+        assert_eq!(metrics_auth_mode(), MetricsAuthMode::ApiKey);
+        std::env::set_var("LLMLB_METRICS_AUTH", "none");
+        assert_eq!(metrics_auth_mode(), MetricsAuthMode::Open);
+        std::env::set_var("LLMLB_METRICS_AUTH", "unknown");
+        assert_eq!(metrics_auth_mode(), MetricsAuthMode::Local);
+        std::env::remove_var("LLMLB_METRICS_AUTH");
+    }
+
+    #[test]
+    #[serial]
+    fn test_model_list_ttl_secs() {
+        std::env::remove_var("LLMLB_MODEL_LIST_TTL_SECS");
+        assert_eq!(model_list_ttl_secs(), 60);
+        std::env::set_var("LLMLB_MODEL_LIST_TTL_SECS", "0");
+        assert_eq!(model_list_ttl_secs(), 0);
+        std::env::remove_var("LLMLB_MODEL_LIST_TTL_SECS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_detection_cache_ttl_secs() {
+        std::env::remove_var("LLMLB_DETECTION_CACHE_TTL");
+        assert_eq!(detection_cache_ttl_secs(), 600);
+        std::env::set_var("LLMLB_DETECTION_CACHE_TTL", "0");
+        assert_eq!(detection_cache_ttl_secs(), 0);
+        std::env::remove_var("LLMLB_DETECTION_CACHE_TTL");
+    }
+
+    #[test]
+    #[serial]
+    fn test_model_concurrency_settings() {
+        std::env::remove_var("LLMLB_MODEL_MAX_CONCURRENCY");
+        std::env::remove_var("LLMLB_MODEL_CONCURRENCY_MODE");
+        std::env::remove_var("LLMLB_QUEUE_TIMEOUT_SECS");
+        std::env::remove_var("QUEUE_TIMEOUT_SECS");
+        assert!(model_max_concurrency().is_empty());
+        assert_eq!(
+            model_concurrency_mode(),
+            crate::inference_gate::ModelLimitMode::Reject
+        );
+
+        std::env::set_var(
+            "LLMLB_MODEL_MAX_CONCURRENCY",
+            "gpt-oss:120b=2, llama3:70b = 4,broken,zero=0",
+        );
+        std::env::set_var("LLMLB_MODEL_CONCURRENCY_MODE", "Queue");
+        assert_eq!(
+            model_max_concurrency(),
+            vec![
+                ("gpt-oss:120b".to_string(), 2),
+                ("llama3:70b".to_string(), 4)
+            ]
+        );
+        assert_eq!(
+            model_concurrency_mode(),
+            crate::inference_gate::ModelLimitMode::Queue {
+                timeout: Duration::from_secs(60)
+            }
+        );
+        std::env::remove_var("LLMLB_MODEL_MAX_CONCURRENCY");
+        std::env::remove_var("LLMLB_MODEL_CONCURRENCY_MODE");
+    }
+
+    #[test]
+    #[serial]
+    fn test_response_anomaly_settings() {
+        std::env::remove_var("LLMLB_RESPONSE_ANOMALY_ZSCORE");
+        std::env::remove_var("LLMLB_RESPONSE_ANOMALY_WINDOW");
+        assert_eq!(response_anomaly_zscore(), 3.0);
+        assert_eq!(response_anomaly_window(), 10);
+        std::env::set_var("LLMLB_RESPONSE_ANOMALY_ZSCORE", "4.5");
+        std::env::set_var("LLMLB_RESPONSE_ANOMALY_WINDOW", "0");
+        assert_eq!(response_anomaly_zscore(), 4.5);
+        assert_eq!(response_anomaly_window(), 1);
+        std::env::remove_var("LLMLB_RESPONSE_ANOMALY_ZSCORE");
+        std::env::remove_var("LLMLB_RESPONSE_ANOMALY_WINDOW");
+    }
+
+    #[test]
+    #[serial]
+    fn test_latency_baseline_settings() {
+        std::env::remove_var("LLMLB_LATENCY_BASELINE_INTERVAL_SECS");
+        std::env::remove_var("LLMLB_LATENCY_PENALTY_FACTOR");
+        assert_eq!(latency_baseline_interval(), Duration::from_secs(60));
+        assert_eq!(latency_penalty_factor(), 3.0);
+        std::env::set_var("LLMLB_LATENCY_BASELINE_INTERVAL_SECS", "0");
+        std::env::set_var("LLMLB_LATENCY_PENALTY_FACTOR", "2.5");
+        assert_eq!(latency_baseline_interval(), Duration::from_secs(1));
+        assert_eq!(latency_penalty_factor(), 2.5);
+        std::env::remove_var("LLMLB_LATENCY_BASELINE_INTERVAL_SECS");
+        std::env::remove_var("LLMLB_LATENCY_PENALTY_FACTOR");
+    }
+
+    #[test]
+    #[serial]
+    fn test_audit_verify_intervals() {
+        for name in [
+            "LLMLB_AUDIT_VERIFY_INTERVAL_HOURS",
+            "LLMLB_AUDIT_FULL_VERIFY_INTERVAL_HOURS",
+        ] {
+            std::env::remove_var(name);
+        }
+        assert_eq!(audit_verify_interval(), Duration::from_secs(24 * 60 * 60));
+        assert_eq!(
+            audit_full_verify_interval(),
+            Duration::from_secs(7 * 24 * 60 * 60)
+        );
+        std::env::set_var("LLMLB_AUDIT_VERIFY_INTERVAL_HOURS", "0");
+        std::env::set_var("LLMLB_AUDIT_FULL_VERIFY_INTERVAL_HOURS", "48");
+        assert!(audit_verify_interval().is_zero());
+        assert_eq!(
+            audit_full_verify_interval(),
+            Duration::from_secs(48 * 60 * 60)
+        );
+        std::env::remove_var("LLMLB_AUDIT_VERIFY_INTERVAL_HOURS");
+        std::env::remove_var("LLMLB_AUDIT_FULL_VERIFY_INTERVAL_HOURS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_adaptive_rate_settings() {
+        for name in [
+            "LLMLB_ADAPTIVE_RATE_429_THRESHOLD",
+            "LLMLB_ADAPTIVE_RATE_INCREASE_RPS",
+        ] {
+            std::env::remove_var(name);
+        }
+        assert_eq!(adaptive_rate_429_threshold(), 0.05);
+        assert_eq!(adaptive_rate_increase_rps(), 1.0);
+        std::env::set_var("LLMLB_ADAPTIVE_RATE_429_THRESHOLD", "0");
+        std::env::set_var("LLMLB_ADAPTIVE_RATE_INCREASE_RPS", "0.5");
+        assert_eq!(adaptive_rate_429_threshold(), 0.0);
+        assert_eq!(adaptive_rate_increase_rps(), 0.5);
+        std::env::remove_var("LLMLB_ADAPTIVE_RATE_429_THRESHOLD");
+        std::env::remove_var("LLMLB_ADAPTIVE_RATE_INCREASE_RPS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_dynamic_concurrency_max() {
+        std::env::remove_var("LLMLB_DYNAMIC_CONCURRENCY_MAX");
+        assert_eq!(dynamic_concurrency_max(), 0);
+        std::env::set_var("LLMLB_DYNAMIC_CONCURRENCY_MAX", "64");
+        assert_eq!(dynamic_concurrency_max(), 64);
+        std::env::remove_var("LLMLB_DYNAMIC_CONCURRENCY_MAX");
+    }
+
+    #[test]
+    #[serial]
+    fn test_trace_sample_rate() {
+        std::env::remove_var("LLMLB_TRACE_SAMPLE_RATE");
+        assert_eq!(trace_sample_rate(), 1.0);
+        std::env::set_var("LLMLB_TRACE_SAMPLE_RATE", "0.1");
+        assert_eq!(trace_sample_rate(), 0.1);
+        std::env::set_var("LLMLB_TRACE_SAMPLE_RATE", "1.5");
+        assert_eq!(trace_sample_rate(), 1.0);
+        std::env::set_var("LLMLB_TRACE_SAMPLE_RATE", "-1");
+        assert_eq!(trace_sample_rate(), 0.0);
+        std::env::remove_var("LLMLB_TRACE_SAMPLE_RATE");
+    }
+
+    #[test]
+    #[serial]
+    fn test_sample_io_settings() {
+        for key in [
+            "LLMLB_SAMPLE_IO_RATE",
+            "LLMLB_SAMPLE_IO_TTL_HOURS",
+            "LLMLB_SAMPLE_IO_MAX_MB",
+        ] {
+            std::env::remove_var(key);
+        }
+        assert_eq!(sample_io_rate(), 0.0);
+        assert_eq!(sample_io_ttl(), Duration::from_secs(72 * 60 * 60));
+        assert_eq!(sample_io_max_bytes(), 100 * 1024 * 1024);
+
+        std::env::set_var("LLMLB_SAMPLE_IO_RATE", "0.05");
+        std::env::set_var("LLMLB_SAMPLE_IO_TTL_HOURS", "0");
+        std::env::set_var("LLMLB_SAMPLE_IO_MAX_MB", "10");
+        assert_eq!(sample_io_rate(), 0.05);
+        assert_eq!(sample_io_ttl(), Duration::from_secs(60 * 60));
+        assert_eq!(sample_io_max_bytes(), 10 * 1024 * 1024);
+        std::env::set_var("LLMLB_SAMPLE_IO_RATE", "2");
+        assert_eq!(sample_io_rate(), 1.0);
+
+        std::env::remove_var("LLMLB_SAMPLE_IO_RATE");
+        std::env::remove_var("LLMLB_SAMPLE_IO_TTL_HOURS");
+        std::env::remove_var("LLMLB_SAMPLE_IO_MAX_MB");
+    }
+
+    #[test]
+    #[serial]
+    fn test_cloud_pricing() {
+        std::env::remove_var("LLMLB_CLOUD_PRICING");
+        assert!(cloud_pricing().is_empty());
+
+        std::env::set_var(
+            "LLMLB_CLOUD_PRICING",
+            "openai:gpt-4o=0.0025/0.01, anthropic:* = 0.003 / 0.015,broken,neg=-1/1,half=1",
+        );
+        let pricing = cloud_pricing();
+        assert_eq!(pricing.len(), 2);
+        assert_eq!(pricing[0].0, "openai:gpt-4o");
+        assert_eq!(pricing[0].1.input_per_1k, 0.0025);
+        assert_eq!(pricing[0].1.output_per_1k, 0.01);
+        assert_eq!(pricing[1].0, "anthropic:*");
+        assert_eq!(pricing[1].1.output_per_1k, 0.015);
+
+        std::env::remove_var("LLMLB_CLOUD_PRICING");
+    }
+
+    #[test]
+    #[serial]
+    fn test_cert_expiry_warning_days() {
+        std::env::remove_var("LLMLB_CERT_EXPIRY_WARNING_DAYS");
+        assert_eq!(get_cert_expiry_warning_days(), 30);
+        std::env::set_var("LLMLB_CERT_EXPIRY_WARNING_DAYS", "14");
+        assert_eq!(get_cert_expiry_warning_days(), 14);
+        std::env::remove_var("LLMLB_CERT_EXPIRY_WARNING_DAYS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_max_streams_per_client() {
+        std::env::remove_var("LLMLB_MAX_STREAMS_PER_CLIENT");
+        assert_eq!(max_streams_per_client(), 0);
+        std::env::set_var("LLMLB_MAX_STREAMS_PER_CLIENT", "3");
+        assert_eq!(max_streams_per_client(), 3);
+        std::env::remove_var("LLMLB_MAX_STREAMS_PER_CLIENT");
+    }
+
+    #[test]
+    #[serial]
+    fn test_stream_rate_defaults() {
+        std::env::remove_var("LLMLB_STREAM_MAX_TOKENS_PER_SEC");
+        std::env::remove_var("LLMLB_STREAM_MAX_BYTES_PER_SEC");
+        assert_eq!(stream_max_tokens_per_sec(), 0);
+        assert_eq!(stream_max_bytes_per_sec(), 0);
+        std::env::set_var("LLMLB_STREAM_MAX_TOKENS_PER_SEC", "20");
+        std::env::set_var("LLMLB_STREAM_MAX_BYTES_PER_SEC", "4096");
+        assert_eq!(stream_max_tokens_per_sec(), 20);
+        assert_eq!(stream_max_bytes_per_sec(), 4096);
+        std::env::remove_var("LLMLB_STREAM_MAX_TOKENS_PER_SEC");
+        std::env::remove_var("LLMLB_STREAM_MAX_BYTES_PER_SEC");
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_balancer_mode() {
+        std::env::remove_var("LLMLB_LOAD_BALANCER_MODE");
+        std::env::remove_var("LOAD_BALANCER_MODE");
+        assert_eq!(load_balancer_mode(), LoadBalancerMode::Auto);
+        std::env::set_var("LLMLB_LOAD_BALANCER_MODE", "Weighted");
+        assert_eq!(load_balancer_mode(), LoadBalancerMode::Weighted);
+        std::env::set_var("LLMLB_LOAD_BALANCER_MODE", "least_conn");
+        assert_eq!(load_balancer_mode(), LoadBalancerMode::LeastConn);
+        std::env::set_var("LLMLB_LOAD_BALANCER_MODE", "unknown");
+        assert_eq!(load_balancer_mode(), LoadBalancerMode::Auto);
+        std::env::remove_var("LLMLB_LOAD_BALANCER_MODE");
+    }
+
+    #[test]
+    #[serial]
+    fn test_update_retain_generations() {
+        std::env::remove_var("LLMLB_UPDATE_RETAIN_GENERATIONS");
+        assert_eq!(update_retain_generations(), 1);
+        std::env::set_var("LLMLB_UPDATE_RETAIN_GENERATIONS", "3");
+        assert_eq!(update_retain_generations(), 3);
+        std::env::set_var("LLMLB_UPDATE_RETAIN_GENERATIONS", "0");
+        assert_eq!(update_retain_generations(), 1);
+        std::env::remove_var("LLMLB_UPDATE_RETAIN_GENERATIONS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_session_affinity_ttl_secs() {
+        std::env::remove_var("LLMLB_SESSION_AFFINITY_TTL_SECS");
+        assert_eq!(session_affinity_ttl_secs(), 1800);
+        std::env::set_var("LLMLB_SESSION_AFFINITY_TTL_SECS", "60");
+        assert_eq!(session_affinity_ttl_secs(), 60);
+        std::env::set_var("LLMLB_SESSION_AFFINITY_TTL_SECS", "0");
+        assert_eq!(session_affinity_ttl_secs(), 1);
+        std::env::remove_var("LLMLB_SESSION_AFFINITY_TTL_SECS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_prompt_filter_enabled() {
+        std::env::remove_var("LLMLB_PROMPT_FILTER");
+        assert!(!prompt_filter_enabled());
+        std::env::set_var("LLMLB_PROMPT_FILTER", "1");
+        assert!(prompt_filter_enabled());
+        std::env::set_var("LLMLB_PROMPT_FILTER", "0");
+        assert!(!prompt_filter_enabled());
+        std::env::remove_var("LLMLB_PROMPT_FILTER");
+    }
+
+    #[test]
+    #[serial]
+    fn test_quality_filter_enabled() {
+        std::env::remove_var("LLMLB_QUALITY_FILTER");
+        assert!(!quality_filter_enabled());
+        std::env::set_var("LLMLB_QUALITY_FILTER", "true");
+        assert!(quality_filter_enabled());
+        std::env::set_var("LLMLB_QUALITY_FILTER", "no");
+        assert!(!quality_filter_enabled());
+        std::env::remove_var("LLMLB_QUALITY_FILTER");
+    }
+
+    #[test]
+    #[serial]
+    fn test_rate_limit_scope_rps() {
+        use crate::common::auth::ApiKeyScope;
+
+        std::env::remove_var("LLMLB_RATE_LIMIT_SCOPE_RPS");
+        assert!(rate_limit_scope_rps().is_empty());
+        std::env::set_var(
+            "LLMLB_RATE_LIMIT_SCOPE_RPS",
+            "read-only=5, inference=2.5,admin=0,unknown=1,inference=-1",
+        );
+        assert_eq!(
+            rate_limit_scope_rps(),
+            vec![
+                (ApiKeyScope::ReadOnly, 5.0),
+                (ApiKeyScope::Inference, 2.5),
+                (ApiKeyScope::Admin, 0.0),
+            ]
+        );
+        std::env::remove_var("LLMLB_RATE_LIMIT_SCOPE_RPS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_nonce_opt_in_settings() {
+        use crate::common::auth::ApiKeyScope;
+
+        std::env::remove_var("LLMLB_NONCE_API_KEYS");
+        std::env::remove_var("LLMLB_NONCE_SCOPES");
+        std::env::remove_var("LLMLB_NONCE_MAX_PER_KEY");
+        assert!(nonce_api_keys().is_empty());
+        assert!(nonce_scopes().is_empty());
+        assert_eq!(nonce_max_per_key(), 10_000);
+        std::env::set_var("LLMLB_NONCE_MAX_PER_KEY", "0");
+        assert_eq!(nonce_max_per_key(), 1);
+        std::env::remove_var("LLMLB_NONCE_MAX_PER_KEY");
+
+        let id = uuid::Uuid::new_v4();
+        std::env::set_var("LLMLB_NONCE_API_KEYS", format!("{}, not-a-uuid", id));
+        std::env::set_var("LLMLB_NONCE_SCOPES", "admin,unknown, inference");
+        assert_eq!(nonce_api_keys(), vec![id]);
+        assert_eq!(
+            nonce_scopes(),
+            vec![ApiKeyScope::Admin, ApiKeyScope::Inference]
+        );
+        std::env::remove_var("LLMLB_NONCE_API_KEYS");
+        std::env::remove_var("LLMLB_NONCE_SCOPES");
+    }
+
+    #[test]
+    #[serial]
+    fn test_context_routing_enabled() {
+        std::env::remove_var("LLMLB_CONTEXT_ROUTING");
+        assert!(!context_routing_enabled());
+        std::env::set_var("LLMLB_CONTEXT_ROUTING", "1");
+        assert!(context_routing_enabled());
+        std::env::remove_var("LLMLB_CONTEXT_ROUTING");
+    }
+
+    #[test]
+    #[serial]
+    fn test_same_node_retry_enabled() {
+        std::env::remove_var("LLMLB_SAME_NODE_RETRY");
+        assert!(!same_node_retry_enabled());
+        std::env::set_var("LLMLB_SAME_NODE_RETRY", "1");
+        assert!(same_node_retry_enabled());
+        std::env::remove_var("LLMLB_SAME_NODE_RETRY");
+    }
+
+    #[test]
+    #[serial]
+    fn test_bill_partial_streams() {
+        std::env::remove_var("LLMLB_BILL_PARTIAL_STREAMS");
+        assert!(bill_partial_streams());
+        std::env::set_var("LLMLB_BILL_PARTIAL_STREAMS", "false");
+        assert!(!bill_partial_streams());
+        std::env::set_var("LLMLB_BILL_PARTIAL_STREAMS", "1");
+        assert!(bill_partial_streams());
+        std::env::remove_var("LLMLB_BILL_PARTIAL_STREAMS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_json_mode_validation() {
+        std::env::remove_var("LLMLB_JSON_MODE_VALIDATION");
+        assert_eq!(json_mode_validation(), JsonModeValidation::Off);
+        std::env::set_var("LLMLB_JSON_MODE_VALIDATION", "Retry");
+        assert_eq!(json_mode_validation(), JsonModeValidation::Retry);
+        std::env::set_var("LLMLB_JSON_MODE_VALIDATION", "error");
+        assert_eq!(json_mode_validation(), JsonModeValidation::Error);
+        std::env::set_var("LLMLB_JSON_MODE_VALIDATION", "bogus");
+        assert_eq!(json_mode_validation(), JsonModeValidation::Off);
+        std::env::remove_var("LLMLB_JSON_MODE_VALIDATION");
+    }
+
+    #[test]
+    #[serial]
+    fn test_tps_history_settings() {
+        std::env::remove_var("LLMLB_TPS_HISTORY_INTERVAL_SECS");
+        std::env::remove_var("LLMLB_TPS_HISTORY_RETENTION_DAYS");
+        assert_eq!(tps_history_interval_secs(), 60);
+        assert_eq!(tps_history_retention_days(), 7);
+        std::env::set_var("LLMLB_TPS_HISTORY_RETENTION_DAYS", "0");
+        assert_eq!(tps_history_retention_days(), 1);
+        std::env::remove_var("LLMLB_TPS_HISTORY_RETENTION_DAYS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_queue_history_settings() {
+        std::env::remove_var("LLMLB_QUEUE_HISTORY_INTERVAL_SECS");
+        std::env::remove_var("LLMLB_QUEUE_HISTORY_RETENTION_HOURS");
+        assert_eq!(queue_history_interval_secs(), 10);
+        assert_eq!(queue_history_retention_hours(), 24);
+        std::env::set_var("LLMLB_QUEUE_HISTORY_INTERVAL_SECS", "0");
+        std::env::set_var("LLMLB_QUEUE_HISTORY_RETENTION_HOURS", "0");
+        assert_eq!(queue_history_interval_secs(), 0);
+        assert_eq!(queue_history_retention_hours(), 1);
+        std::env::remove_var("LLMLB_QUEUE_HISTORY_INTERVAL_SECS");
+        std::env::remove_var("LLMLB_QUEUE_HISTORY_RETENTION_HOURS");
+    }
+
+    #[test]
+    fn test_parse_reload_file() {
+        let (entries, ignored) = parse_reload_file(
+            "# comment\n\nLLMLB_QUEUE_MAX=5\nexport LLMLB_LOG_LEVEL=\"debug\"\nLLMLB_PORT=9000\ninvalid\n",
+        );
+        assert_eq!(
+            entries,
+            vec![
+                ("LLMLB_QUEUE_MAX".to_string(), "5".to_string()),
+                ("LLMLB_LOG_LEVEL".to_string(), "debug".to_string()),
+            ]
+        );
+        assert_eq!(ignored, vec!["LLMLB_PORT".to_string()]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_queue_backpressure_thresholds() {
+        for name in ["LLMLB_QUEUE_SOFT_THRESHOLD", "LLMLB_QUEUE_HARD_THRESHOLD"] {
+            std::env::remove_var(name);
+        }
+        let config = QueueConfig::from_env();
+        assert_eq!(config.soft_threshold, DEFAULT_QUEUE_SOFT_THRESHOLD);
+        assert_eq!(config.hard_threshold, DEFAULT_QUEUE_HARD_THRESHOLD);
+
+        std::env::set_var("LLMLB_QUEUE_SOFT_THRESHOLD", "0.6");
+        std::env::set_var("LLMLB_QUEUE_HARD_THRESHOLD", "0.9");
+        let config = QueueConfig::from_env();
+        assert_eq!(config.soft_threshold, 0.6);
+        assert_eq!(config.hard_threshold, 0.9);
+
+        // soft >= hard や範囲外の値は既定値へフォールバックする
+        std::env::set_var("LLMLB_QUEUE_SOFT_THRESHOLD", "0.9");
+        std::env::set_var("LLMLB_QUEUE_HARD_THRESHOLD", "0.7");
+        assert_eq!(
+            QueueConfig::from_env().soft_threshold,
+            DEFAULT_QUEUE_SOFT_THRESHOLD
+        );
+        std::env::set_var("LLMLB_QUEUE_SOFT_THRESHOLD", "0.2");
+        std::env::set_var("LLMLB_QUEUE_HARD_THRESHOLD", "1.5");
+        let config = QueueConfig::from_env();
+        assert_eq!(config.soft_threshold, DEFAULT_QUEUE_SOFT_THRESHOLD);
+        assert_eq!(config.hard_threshold, DEFAULT_QUEUE_HARD_THRESHOLD);
+
+        std::env::remove_var("LLMLB_QUEUE_SOFT_THRESHOLD");
+        std::env::remove_var("LLMLB_QUEUE_HARD_THRESHOLD");
+    }
+
+    #[test]
+    fn test_reloadable_config_changed_fields() {
+        let base = ReloadableConfig {
+            health_check_interval_secs: 30,
+            load_balancer_mode: "auto".to_string(),
+            queue: QueueConfig {
+                max_waiters: 100,
+                timeout: Duration::from_secs(60),
+                soft_threshold: DEFAULT_QUEUE_SOFT_THRESHOLD,
+                hard_threshold: DEFAULT_QUEUE_HARD_THRESHOLD,
+            },
+            log_level: None,
+        };
+        assert!(base.changed_fields(&base.clone()).is_empty());
+
+        let mut updated = base.clone();
+        updated.queue.max_waiters = 10;
+        updated.log_level = Some("debug".to_string());
+        assert_eq!(updated.changed_fields(&base), vec!["queue", "log_level"]);
+    }
+
+    #[test]
+    fn test_reloadable_config_with_entries() {
+        let base = ReloadableConfig {
+            health_check_interval_secs: 30,
+            load_balancer_mode: "auto".to_string(),
+            queue: QueueConfig {
+                max_waiters: 100,
+                timeout: Duration::from_secs(60),
+                soft_threshold: DEFAULT_QUEUE_SOFT_THRESHOLD,
+                hard_threshold: DEFAULT_QUEUE_HARD_THRESHOLD,
+            },
+            log_level: Some("info".to_string()),
+        };
+        let entries = [
+            ("LLMLB_HEALTH_CHECK_INTERVAL", "10"),
+            ("LLMLB_LOAD_BALANCER_MODE", "least_conn"),
+            ("LLMLB_QUEUE_MAX", "not-a-number"),
+            ("LLMLB_QUEUE_TIMEOUT_SECS", "5"),
+            ("LLMLB_LOG_LEVEL", ""),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()));
+
+        let reloaded = base.with_entries(&entries);
+        assert_eq!(reloaded.health_check_interval_secs, 10);
+        assert_eq!(reloaded.load_balancer_mode, "least_conn");
+        // 不正な値は無視して現在値を保つ
+        assert_eq!(reloaded.queue.max_waiters, 100);
+        assert_eq!(reloaded.queue.timeout, Duration::from_secs(5));
+        assert_eq!(reloaded.log_level, None);
+        assert_eq!(
+            reloaded.changed_fields(&base),
+            vec![
+                "health_check_interval",
+                "load_balancer_mode",
+                "queue",
+                "log_level"
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_endpoint_slots() {
+        std::env::remove_var("LLMLB_ENDPOINT_SLOTS");
+        assert_eq!(endpoint_slots(), 4);
+        std::env::set_var("LLMLB_ENDPOINT_SLOTS", "8");
+        assert_eq!(endpoint_slots(), 8);
+        std::env::remove_var("LLMLB_ENDPOINT_SLOTS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_stream_reconnect_config_default_disabled() {
+        std::env::remove_var("LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS");
+        std::env::remove_var("LLMLB_STREAM_RECONNECT_ON");
+        let config = StreamReconnectConfig::from_env();
+        assert_eq!(config.max_attempts, 0);
+        assert!(!config.allows(StreamReconnectCause::ConnectError));
+        assert!(!config.allows(StreamReconnectCause::EarlyDisconnect));
+    }
+
+    #[test]
+    #[serial]
+    fn test_stream_reconnect_config_from_env_conditions() {
+        std::env::set_var("LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS", "2");
+        std::env::set_var("LLMLB_STREAM_RECONNECT_ON", "connect, disconnect");
+        let config = StreamReconnectConfig::from_env();
+        assert_eq!(config.max_attempts, 2);
+        assert!(config.allows(StreamReconnectCause::ConnectError));
+        assert!(!config.allows(StreamReconnectCause::ServerError));
+        assert!(config.allows(StreamReconnectCause::EarlyDisconnect));
+        std::env::remove_var("LLMLB_STREAM_RECONNECT_MAX_ATTEMPTS");
+        std::env::remove_var("LLMLB_STREAM_RECONNECT_ON");
+    }
+
+    #[test]
+    fn test_parse_config_file() {
+        let (entries, unknown) = parse_config_file(
+            "port = 9000\nload_balancer_mode = \"metrics\"\nunknown_key = 1\n\n\
+             [queue]\nmax = 5\nsoft_threshold = 0.6\nretries = [1, 2]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ("load_balancer_mode", "metrics".to_string()),
+                ("port", "9000".to_string()),
+                ("queue.max", "5".to_string()),
+                ("queue.soft_threshold", "0.6".to_string()),
+            ]
+        );
+        assert_eq!(unknown, vec!["queue.retries", "unknown_key"]);
+        assert!(parse_config_file("port = ").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_file_env_takes_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "host = \"127.0.0.1\"\nport = 9000\n[queue]\nmax = 5\n",
+        )
+        .unwrap();
+        std::env::remove_var("LLMLB_HOST");
+        std::env::remove_var("LLMLB_QUEUE_MAX");
+        std::env::remove_var("QUEUE_MAX");
+        std::env::set_var("LLMLB_PORT", "9100");
+
+        let loaded = load_config_file(Some(&path)).unwrap();
+        assert!(loaded.loaded);
+        assert_eq!(loaded.applied, vec!["host", "queue.max"]);
+        assert_eq!(loaded.overridden, vec!["port"]);
+        let server = ServerConfig::from_env();
+        assert_eq!(server.host, "127.0.0.1");
+        assert_eq!(server.port, 9100);
+        assert_eq!(QueueConfig::from_env().max_waiters, 5);
+
+        // 明示したパスが存在しない場合はエラー
+        assert!(matches!(
+            load_config_file(Some(&dir.path().join("missing.toml"))),
+            Err(ConfigFileError::Read { .. })
+        ));
+
+        std::env::remove_var("LLMLB_HOST");
+        std::env::remove_var("LLMLB_PORT");
+        std::env::remove_var("LLMLB_QUEUE_MAX");
+    }
+
+    #[test]
+    #[serial]
+    fn test_escalation_config_stages_within_budget() {
+        std::env::remove_var("LLMLB_ESCALATION");
+        assert_eq!(EscalationConfig::from_env(), None);
+
+        std::env::set_var("LLMLB_ESCALATION", "1");
+        std::env::set_var("LLMLB_ESCALATION_STAGE_TIMEOUTS", "2, x, 10");
+        std::env::set_var("LLMLB_ESCALATION_BUDGET_SECS", "8");
+        let config = EscalationConfig::from_env().unwrap();
+        assert_eq!(config.stages(), 2);
+        assert_eq!(
+            config.stage_timeout(0, Duration::ZERO),
+            Duration::from_secs(2)
+        );
+        // 2段目は予算の残りで打ち切る
+        assert_eq!(
+            config.stage_timeout(1, Duration::from_secs(2)),
+            Duration::from_secs(6)
+        );
+        assert_eq!(config.next_stage(0, Duration::from_secs(2)), Some(1));
+        assert_eq!(config.next_stage(1, Duration::from_secs(3)), None);
+        assert_eq!(config.next_stage(0, Duration::from_secs(8)), None);
+
+        std::env::remove_var("LLMLB_ESCALATION");
+        std::env::remove_var("LLMLB_ESCALATION_STAGE_TIMEOUTS");
+        std::env::remove_var("LLMLB_ESCALATION_BUDGET_SECS");
+    }
 }
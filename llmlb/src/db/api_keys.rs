@@ -12,6 +12,12 @@ use uuid::Uuid;
 
 const DUPLICATE_NAME_VALIDATION_MSG: &str = "API key with this name already exists";
 
+/// 発行するAPIキーの接頭辞（旧形式の `sk_` キーも引き続き認証できる）
+pub const API_KEY_PREFIX: &str = "llmlb_sk_";
+
+/// 表示用の `key_prefix` に含めるランダム部分の文字数
+const KEY_PREFIX_RANDOM_CHARS: usize = 4;
+
 /// APIキーを生成
 ///
 /// # Arguments
@@ -33,7 +39,10 @@ pub async fn create(
     let id = Uuid::new_v4();
     let key = generate_api_key();
     let key_hash = hash_with_sha256(&key);
-    let key_prefix = key.chars().take(10).collect::<String>();
+    let key_prefix = key
+        .chars()
+        .take(API_KEY_PREFIX.len() + KEY_PREFIX_RANDOM_CHARS)
+        .collect::<String>();
     let created_at = Utc::now();
 
     let permissions_json = serialize_permissions(&permissions)?;
@@ -77,7 +86,7 @@ pub async fn create(
 /// * `Err(LbError)` - 検索失敗
 pub async fn find_by_hash(pool: &SqlitePool, key_hash: &str) -> Result<Option<ApiKey>, LbError> {
     let row = sqlx::query_as::<_, ApiKeyRow>(
-        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions FROM api_keys WHERE key_hash = ?"
+        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions, revoked_at FROM api_keys WHERE key_hash = ?"
     )
     .bind(key_hash)
     .fetch_optional(pool)
@@ -97,7 +106,7 @@ pub async fn find_by_hash(pool: &SqlitePool, key_hash: &str) -> Result<Option<Ap
 /// * `Err(LbError)` - 取得失敗
 pub async fn list(pool: &SqlitePool) -> Result<Vec<ApiKey>, LbError> {
     let rows = sqlx::query_as::<_, ApiKeyRow>(
-        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions FROM api_keys ORDER BY created_at DESC"
+        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions, revoked_at FROM api_keys ORDER BY created_at DESC"
     )
     .fetch_all(pool)
     .await
@@ -117,7 +126,7 @@ pub async fn list(pool: &SqlitePool) -> Result<Vec<ApiKey>, LbError> {
 /// * `Err(LbError)` - 取得失敗
 pub async fn list_by_creator(pool: &SqlitePool, created_by: Uuid) -> Result<Vec<ApiKey>, LbError> {
     let rows = sqlx::query_as::<_, ApiKeyRow>(
-        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions
+        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions, revoked_at
          FROM api_keys
          WHERE created_by = ?
          ORDER BY created_at DESC",
@@ -162,7 +171,7 @@ pub async fn update(
 
     // 更新後のAPIキーを取得
     let row = sqlx::query_as::<_, ApiKeyRow>(
-        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions FROM api_keys WHERE id = ?",
+        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions, revoked_at FROM api_keys WHERE id = ?",
     )
     .bind(id.to_string())
     .fetch_optional(pool)
@@ -193,7 +202,7 @@ pub async fn update_by_creator(
     expires_at: Option<DateTime<Utc>>,
 ) -> Result<Option<ApiKey>, LbError> {
     let existing = sqlx::query_as::<_, ApiKeyRow>(
-        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions
+        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions, revoked_at
          FROM api_keys
          WHERE id = ? AND created_by = ?",
     )
@@ -240,7 +249,7 @@ pub async fn update_by_creator(
     }
 
     let row = sqlx::query_as::<_, ApiKeyRow>(
-        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions
+        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions, revoked_at
          FROM api_keys
          WHERE id = ? AND created_by = ?",
     )
@@ -298,7 +307,52 @@ pub async fn delete_by_creator(
     Ok(result.rows_affected() > 0)
 }
 
-/// APIキーを生成（`sk_` + 32文字のランダム英数字）
+/// APIキーを失効させる（発行者限定）
+///
+/// 失効後もレコードは一覧に残り、認証では即座に拒否される。
+/// 失効済みのキーを再度失効させても失効日時は変わらない。
+///
+/// # Arguments
+/// * `pool` - データベース接続プール
+/// * `id` - APIキーID
+/// * `created_by` - 発行者ユーザーID
+///
+/// # Returns
+/// * `Ok(Some(ApiKey))` - 失効後のAPIキー
+/// * `Ok(None)` - APIキーが見つからなかった
+/// * `Err(LbError)` - 更新失敗
+pub async fn revoke_by_creator(
+    pool: &SqlitePool,
+    id: Uuid,
+    created_by: Uuid,
+) -> Result<Option<ApiKey>, LbError> {
+    sqlx::query(
+        "UPDATE api_keys
+         SET revoked_at = ?
+         WHERE id = ? AND created_by = ? AND revoked_at IS NULL",
+    )
+    .bind(Utc::now().to_rfc3339())
+    .bind(id.to_string())
+    .bind(created_by.to_string())
+    .execute(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to revoke API key by creator: {}", e)))?;
+
+    let row = sqlx::query_as::<_, ApiKeyRow>(
+        "SELECT id, key_hash, key_prefix, name, created_by, created_at, expires_at, permissions, revoked_at
+         FROM api_keys
+         WHERE id = ? AND created_by = ?",
+    )
+    .bind(id.to_string())
+    .bind(created_by.to_string())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to find revoked API key: {}", e)))?;
+
+    Ok(row.map(|r| r.into_api_key()))
+}
+
+/// APIキーを生成（`llmlb_sk_` + 32文字のランダム英数字）
 ///
 /// # Returns
 /// * `String` - 生成されたAPIキー
@@ -313,7 +367,7 @@ fn generate_api_key() -> String {
         })
         .collect();
 
-    format!("sk_{}", random_part)
+    format!("{}{}", API_KEY_PREFIX, random_part)
 }
 
 /// SHA-256ハッシュ化ヘルパー関数
@@ -341,6 +395,7 @@ struct ApiKeyRow {
     created_at: String,
     expires_at: Option<String>,
     permissions: Option<String>,
+    revoked_at: Option<String>,
 }
 
 impl ApiKeyRow {
@@ -357,6 +412,11 @@ impl ApiKeyRow {
         });
 
         let permissions = parse_permissions(self.permissions);
+        let revoked_at = self.revoked_at.as_ref().and_then(|s| {
+            DateTime::parse_from_rfc3339(s)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc))
+        });
 
         ApiKey {
             id,
@@ -367,6 +427,7 @@ impl ApiKeyRow {
             created_at,
             expires_at,
             permissions,
+            revoked_at,
         }
     }
 }
@@ -449,8 +510,8 @@ mod tests {
     #[tokio::test]
     async fn test_generate_api_key() {
         let key = generate_api_key();
-        assert!(key.starts_with("sk_"));
-        assert_eq!(key.len(), 3 + 32); // "sk_" + 32文字
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + 32); // "llmlb_sk_" + 32文字
     }
 
     #[tokio::test]
@@ -473,7 +534,11 @@ mod tests {
         .await
         .expect("Failed to create API key");
 
-        assert!(api_key_with_plaintext.key.starts_with("sk_"));
+        assert!(api_key_with_plaintext.key.starts_with(API_KEY_PREFIX));
+        assert_eq!(
+            api_key_with_plaintext.key_prefix.len(),
+            API_KEY_PREFIX.len() + 4
+        );
         assert_eq!(api_key_with_plaintext.name, "Test API Key");
 
         // ハッシュで検索
@@ -492,6 +557,50 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_revoke_api_key_by_creator() {
+        let pool = setup_test_db().await;
+        let owner = users::create(&pool, "owner", "hash", UserRole::Admin, false)
+            .await
+            .unwrap();
+        let other = users::create(&pool, "other", "hash", UserRole::Viewer, false)
+            .await
+            .unwrap();
+        let api_key = create(
+            &pool,
+            "to-revoke",
+            owner.id,
+            None,
+            vec![crate::common::auth::ApiKeyPermission::OpenaiInference],
+        )
+        .await
+        .unwrap();
+
+        // 発行者以外は失効できない
+        assert!(revoke_by_creator(&pool, api_key.id, other.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let revoked = revoke_by_creator(&pool, api_key.id, owner.id)
+            .await
+            .unwrap()
+            .expect("revoked key");
+        let revoked_at = revoked.revoked_at.expect("revoked_at is set");
+
+        // 再度失効させても失効日時は変わらず、ハッシュ検索では失効済みとして返る
+        let again = revoke_by_creator(&pool, api_key.id, owner.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.revoked_at, Some(revoked_at));
+        let found = find_by_hash(&pool, &hash_with_sha256(&api_key.key))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.revoked_at, Some(revoked_at));
+    }
+
     #[tokio::test]
     async fn test_list_api_keys() {
         let pool = setup_test_db().await;
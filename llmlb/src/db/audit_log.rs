@@ -1,8 +1,9 @@
 //! 監査ログストレージ (SPEC-8301d106)
 
 use crate::audit::{
+    export::ExportSource,
     hash_chain::{self, GENESIS_HASH},
-    types::{ActorType, AuditBatchHash, AuditLogEntry, AuditLogFilter},
+    types::{ActorType, AuditBatchHash, AuditLogEntry, AuditLogFilter, ChainCheckpoint},
 };
 use crate::common::error::{LbError, RouterResult};
 use serde::{Deserialize, Serialize};
@@ -13,6 +14,7 @@ use sqlx::SqlitePool;
 ///
 /// アーカイブDBファイルが存在しない場合は自動作成し、
 /// 必要なテーブル（audit_log_entries + audit_batch_hashes）を作成する。
+/// 監査ログのミラーDB（`LLMLB_AUDIT_MIRROR_PATH`）も同じスキーマで作成する。
 pub async fn create_archive_pool(path: &str) -> RouterResult<SqlitePool> {
     let url = format!("sqlite:{}?mode=rwc", path);
     let pool = SqlitePoolOptions::new()
@@ -387,7 +389,7 @@ impl AuditLogStorage {
              actor_type, actor_id, actor_username, api_key_owner_id, client_ip, \
              duration_ms, input_tokens, output_tokens, total_tokens, \
              model_name, endpoint_id, detail, batch_id, is_migrated \
-             FROM audit_log_entries {} ORDER BY timestamp DESC LIMIT ? OFFSET ?",
+             FROM audit_log_entries {} ORDER BY timestamp DESC, id DESC LIMIT ? OFFSET ?",
             where_clause
         );
 
@@ -611,6 +613,58 @@ impl AuditLogStorage {
             .collect::<Result<Vec<_>, _>>()
     }
 
+    /// 指定した連番以降のバッチハッシュを連番順で取得
+    pub async fn get_batch_hashes_from(
+        &self,
+        sequence_number: i64,
+    ) -> RouterResult<Vec<AuditBatchHash>> {
+        let rows = sqlx::query_as::<_, AuditBatchHashRow>(
+            "SELECT id, sequence_number, batch_start, batch_end, \
+             record_count, hash, previous_hash \
+             FROM audit_batch_hashes WHERE sequence_number >= ? \
+             ORDER BY sequence_number ASC",
+        )
+        .bind(sequence_number)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to get batch hashes: {}", e)))?;
+
+        rows.into_iter()
+            .map(AuditBatchHash::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// ハッシュチェーン検証のチェックポイントを取得（メインDBのみ）
+    pub async fn get_chain_checkpoint(&self) -> RouterResult<Option<ChainCheckpoint>> {
+        let row = sqlx::query_as::<_, (i64, String)>(
+            "SELECT sequence_number, hash FROM audit_chain_checkpoint WHERE id = 1",
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to get chain checkpoint: {}", e)))?;
+
+        Ok(row.map(|(sequence_number, hash)| ChainCheckpoint {
+            sequence_number,
+            hash,
+        }))
+    }
+
+    /// ハッシュチェーン検証のチェックポイントを保存（メインDBのみ）
+    pub async fn save_chain_checkpoint(&self, checkpoint: &ChainCheckpoint) -> RouterResult<()> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO audit_chain_checkpoint (id, sequence_number, hash, verified_at) \
+             VALUES (1, ?, ?, ?)",
+        )
+        .bind(checkpoint.sequence_number)
+        .bind(&checkpoint.hash)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to save chain checkpoint: {}", e)))?;
+
+        Ok(())
+    }
+
     /// 最新バッチハッシュを取得
     pub async fn get_latest_batch_hash(&self) -> RouterResult<Option<AuditBatchHash>> {
         let row = sqlx::query_as::<_, AuditBatchHashRow>(
@@ -702,7 +756,7 @@ impl AuditLogStorage {
              FROM audit_log_fts fts \
              JOIN audit_log_entries e ON fts.rowid = e.id \
              WHERE fts.audit_log_fts MATCH ? {} \
-             ORDER BY e.timestamp DESC LIMIT ? OFFSET ?",
+             ORDER BY e.timestamp DESC, e.id DESC LIMIT ? OFFSET ?",
             extra_where
         );
 
@@ -1039,7 +1093,7 @@ impl AuditLogStorage {
              actor_type, actor_id, actor_username, api_key_owner_id, client_ip, \
              duration_ms, input_tokens, output_tokens, total_tokens, \
              model_name, endpoint_id, detail, batch_id, is_migrated \
-             FROM audit_log_entries {} ORDER BY timestamp DESC LIMIT ? OFFSET ?",
+             FROM audit_log_entries {} ORDER BY timestamp DESC, id DESC LIMIT ? OFFSET ?",
             where_clause
         );
 
@@ -1082,6 +1136,230 @@ impl AuditLogStorage {
         Ok(count)
     }
 
+    /// メインDBとアーカイブDBを横断して検索する
+    ///
+    /// 両DBの結果をタイムスタンプ降順（同時刻はID降順）にマージしてページングする。
+    /// `filter.search_text` 指定時はFTS5全文検索を使う。アーカイブ処理はアーカイブDBへ
+    /// コピーした後にメインDBから削除するため、境界のエントリが一時的に両DBへ存在し得る。
+    /// そのようなエントリはIDで重複を除き、総件数からも差し引く。
+    pub async fn search_across(
+        main: &AuditLogStorage,
+        archive: &SqlitePool,
+        filter: &AuditLogFilter,
+    ) -> RouterResult<(Vec<AuditLogEntry>, i64)> {
+        let page = filter.page.unwrap_or(1).max(1);
+        let per_page = filter.per_page.unwrap_or(50).max(1);
+
+        // 各DBの先頭 page*per_page 件をマージすれば、該当ページは必ずその中に含まれる
+        let mut merged_filter = filter.clone();
+        merged_filter.page = Some(1);
+        merged_filter.per_page = Some(page.saturating_mul(per_page));
+
+        let search = filter
+            .search_text
+            .as_deref()
+            .filter(|query| !query.trim().is_empty());
+        let (main_items, main_total, archive_items, archive_total) = match search {
+            Some(query) => (
+                main.search_fts(query, &merged_filter).await?,
+                main.count_fts(query, filter).await?,
+                main.search_fts_archive(query, &merged_filter, archive)
+                    .await?,
+                main.count_fts_archive(query, filter, archive).await?,
+            ),
+            None => (
+                main.query(&merged_filter).await?,
+                main.count(filter).await?,
+                main.query_archive(&merged_filter, archive).await?,
+                main.count_archive(filter, archive).await?,
+            ),
+        };
+        let overlap = main.count_archive_overlap(filter, search, archive).await?;
+
+        let mut seen = std::collections::HashSet::new();
+        let mut items: Vec<AuditLogEntry> = main_items
+            .into_iter()
+            .chain(archive_items)
+            .filter(|entry| entry.id.is_none_or(|id| seen.insert(id)))
+            .collect();
+        items.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| b.id.cmp(&a.id)));
+
+        let offset = ((page - 1) * per_page) as usize;
+        let items = items
+            .into_iter()
+            .skip(offset)
+            .take(per_page as usize)
+            .collect();
+
+        Ok((items, main_total + archive_total - overlap))
+    }
+
+    /// フィルタに一致し、メインDBとアーカイブDBの両方に存在するエントリ数
+    ///
+    /// 重複し得るのはアーカイブ済みの最新エントリ以前のものに限られるため、
+    /// その範囲のメインDBエントリのみアーカイブDBと照合する。
+    async fn count_archive_overlap(
+        &self,
+        filter: &AuditLogFilter,
+        search: Option<&str>,
+        archive: &SqlitePool,
+    ) -> RouterResult<i64> {
+        let newest_archived: Option<String> =
+            sqlx::query_scalar("SELECT MAX(timestamp) FROM audit_log_entries")
+                .fetch_one(archive)
+                .await
+                .map_err(|e| {
+                    LbError::Database(format!("Failed to get newest archived entry: {}", e))
+                })?;
+        let Some(newest_archived) = newest_archived else {
+            return Ok(0);
+        };
+
+        let mut boundary_filter = filter.clone();
+        boundary_filter.page = Some(1);
+        boundary_filter.per_page = Some(i64::MAX);
+        if let Ok(newest) = chrono::DateTime::parse_from_rfc3339(&newest_archived) {
+            let newest = newest.with_timezone(&chrono::Utc);
+            boundary_filter.time_to = Some(filter.time_to.map_or(newest, |to| to.min(newest)));
+        }
+        let candidates = match search {
+            Some(query) => self.search_fts(query, &boundary_filter).await?,
+            None => self.query(&boundary_filter).await?,
+        };
+        let ids: Vec<i64> = candidates.iter().filter_map(|entry| entry.id).collect();
+
+        let mut overlap = 0;
+        for chunk in ids.chunks(500) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let sql = format!(
+                "SELECT COUNT(*) FROM audit_log_entries WHERE id IN ({})",
+                placeholders
+            );
+            let mut query = sqlx::query_scalar::<_, i64>(&sql);
+            for id in chunk {
+                query = query.bind(id);
+            }
+            overlap += query.fetch_one(archive).await.map_err(|e| {
+                LbError::Database(format!("Failed to count archived duplicates: {}", e))
+            })?;
+        }
+
+        Ok(overlap)
+    }
+
+    /// エクスポート用に `after_id` より大きいIDのエントリをID昇順で取得する
+    ///
+    /// `archive_pool` を指定した場合はアーカイブDBから取得する。
+    /// キーセットページングのため、呼び出し側は件数に依らず一定メモリで全件を走査できる。
+    pub async fn export_page(
+        &self,
+        filter: &AuditLogFilter,
+        after_id: i64,
+        limit: i64,
+        archive_pool: Option<&SqlitePool>,
+    ) -> RouterResult<Vec<AuditLogEntry>> {
+        let (where_clause, bind_values) = build_where_clause(filter);
+        let where_clause = if where_clause.is_empty() {
+            "WHERE id > ?".to_string()
+        } else {
+            format!("{} AND id > ?", where_clause)
+        };
+
+        let sql = format!(
+            "SELECT id, timestamp, http_method, request_path, status_code, \
+             actor_type, actor_id, actor_username, api_key_owner_id, client_ip, \
+             duration_ms, input_tokens, output_tokens, total_tokens, \
+             model_name, endpoint_id, detail, batch_id, is_migrated \
+             FROM audit_log_entries {} ORDER BY id ASC LIMIT ?",
+            where_clause
+        );
+
+        let mut query = sqlx::query_as::<_, AuditLogRow>(&sql);
+        for val in &bind_values {
+            query = query.bind(val.as_str());
+        }
+        query = query.bind(after_id).bind(limit.max(1));
+
+        let rows = query
+            .fetch_all(archive_pool.unwrap_or(&self.pool))
+            .await
+            .map_err(|e| LbError::Database(format!("Failed to export audit logs: {}", e)))?;
+
+        rows.into_iter()
+            .map(AuditLogEntry::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// エクスポート対象のエントリをページ単位のストリームで返す
+    ///
+    /// アーカイブDB（指定時）→ メインDBの順に、それぞれ `export_page` でID昇順に走査する。
+    /// 保持するのは1ページ分のみのため、期間が広くてもメモリに全件を展開しない。
+    pub fn export_stream(
+        &self,
+        filter: AuditLogFilter,
+        archive_pool: Option<SqlitePool>,
+        page_size: i64,
+    ) -> impl futures::Stream<Item = RouterResult<(ExportSource, Vec<AuditLogEntry>)>> + Send + 'static
+    {
+        struct ExportCursor {
+            storage: AuditLogStorage,
+            filter: AuditLogFilter,
+            sources: std::collections::VecDeque<(ExportSource, Option<SqlitePool>)>,
+            after_id: i64,
+        }
+
+        let page_size = page_size.max(1);
+        let cursor = ExportCursor {
+            storage: self.clone(),
+            filter,
+            sources: archive_pool
+                .map(|pool| (ExportSource::Archive, Some(pool)))
+                .into_iter()
+                .chain(std::iter::once((ExportSource::Main, None)))
+                .collect(),
+            after_id: 0,
+        };
+
+        futures::stream::try_unfold(cursor, move |mut cursor| async move {
+            while let Some((source, pool)) = cursor.sources.front().cloned() {
+                let page = cursor
+                    .storage
+                    .export_page(&cursor.filter, cursor.after_id, page_size, pool.as_ref())
+                    .await?;
+                match page.last().and_then(|entry| entry.id) {
+                    Some(last_id) if page.len() as i64 >= page_size => cursor.after_id = last_id,
+                    _ => {
+                        cursor.sources.pop_front();
+                        cursor.after_id = 0;
+                    }
+                }
+                if !page.is_empty() {
+                    return Ok(Some(((source, page), cursor)));
+                }
+            }
+            Ok(None)
+        })
+    }
+
+    /// アーカイブDBの全バッチハッシュを連番順に取得
+    pub async fn get_archive_batch_hashes(
+        &self,
+        archive_pool: &SqlitePool,
+    ) -> RouterResult<Vec<AuditBatchHash>> {
+        let rows = sqlx::query_as::<_, AuditBatchHashRow>(
+            "SELECT id, sequence_number, batch_start, batch_end, \
+             record_count, hash, previous_hash \
+             FROM audit_batch_hashes ORDER BY sequence_number ASC",
+        )
+        .fetch_all(archive_pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to get archive batch hashes: {}", e)))?;
+
+        rows.into_iter()
+            .map(AuditBatchHash::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
     /// アーカイブDBをFTS5全文検索
     pub async fn search_fts_archive(
         &self,
@@ -1108,7 +1386,7 @@ impl AuditLogStorage {
              FROM audit_log_fts fts \
              JOIN audit_log_entries e ON fts.rowid = e.id \
              WHERE fts.audit_log_fts MATCH ? {} \
-             ORDER BY e.timestamp DESC LIMIT ? OFFSET ?",
+             ORDER BY e.timestamp DESC, e.id DESC LIMIT ? OFFSET ?",
             extra_where
         );
 
@@ -2046,6 +2324,94 @@ mod tests {
         assert_eq!(by_model.len(), 2); // llama-3, gpt-4
     }
 
+    #[tokio::test]
+    async fn test_export_page_walks_entries_by_id() {
+        let pool = create_test_pool().await;
+        let storage = AuditLogStorage::new(pool);
+
+        let now = chrono::Utc::now();
+        let entries: Vec<AuditLogEntry> = (0..5)
+            .map(|i| AuditLogEntry {
+                timestamp: now - chrono::Duration::days(5 - i),
+                ..make_entry("GET", &format!("/api/e-{}", i), 200, ActorType::User)
+            })
+            .collect();
+        storage.insert_batch(&entries).await.unwrap();
+
+        // 2件ずつ取得しても全件を重複なく昇順で走査できる
+        let filter = AuditLogFilter::default();
+        let mut after_id = 0;
+        let mut paths = Vec::new();
+        loop {
+            let page = storage
+                .export_page(&filter, after_id, 2, None)
+                .await
+                .unwrap();
+            if page.is_empty() {
+                break;
+            }
+            after_id = page.last().unwrap().id.unwrap();
+            paths.extend(page.into_iter().map(|e| e.request_path));
+        }
+        assert_eq!(
+            paths,
+            vec!["/api/e-0", "/api/e-1", "/api/e-2", "/api/e-3", "/api/e-4"]
+        );
+
+        // 期間フィルタと組み合わせられる
+        let filter = AuditLogFilter {
+            time_from: Some(now - chrono::Duration::days(2) - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        let page = storage.export_page(&filter, 0, 100, None).await.unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].request_path, "/api/e-3");
+    }
+
+    #[tokio::test]
+    async fn test_export_stream_joins_archive_and_main_in_pages() {
+        use futures::TryStreamExt;
+
+        let pool = create_test_pool().await;
+        let storage = AuditLogStorage::new(pool);
+        let archive_pool = super::create_archive_pool(":memory:").await.unwrap();
+
+        let now = chrono::Utc::now();
+        let entries: Vec<AuditLogEntry> = (0..5)
+            .map(|i| AuditLogEntry {
+                timestamp: now - chrono::Duration::days(if i < 2 { 100 + i } else { 5 - i }),
+                ..make_entry("GET", &format!("/api/e-{}", i), 200, ActorType::User)
+            })
+            .collect();
+        storage.insert_batch(&entries).await.unwrap();
+        storage
+            .archive_old_entries(90, &archive_pool)
+            .await
+            .unwrap();
+
+        let pages: Vec<(ExportSource, Vec<AuditLogEntry>)> = storage
+            .export_stream(AuditLogFilter::default(), Some(archive_pool), 2)
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(pages.iter().all(|(_, page)| page.len() <= 2));
+
+        let exported: Vec<(ExportSource, String)> = pages
+            .into_iter()
+            .flat_map(|(source, page)| page.into_iter().map(move |e| (source, e.request_path)))
+            .collect();
+        assert_eq!(
+            exported,
+            vec![
+                (ExportSource::Archive, "/api/e-0".to_string()),
+                (ExportSource::Archive, "/api/e-1".to_string()),
+                (ExportSource::Main, "/api/e-2".to_string()),
+                (ExportSource::Main, "/api/e-3".to_string()),
+                (ExportSource::Main, "/api/e-4".to_string()),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_archive_old_entries() {
         let pool = create_test_pool().await;
@@ -3585,4 +3951,87 @@ mod tests {
             .unwrap();
         assert_eq!(count, 0);
     }
+
+    #[tokio::test]
+    async fn test_search_across_merges_and_dedups_archive_boundary() {
+        let pool = create_test_pool().await;
+        let storage = AuditLogStorage::new(pool.clone());
+        let archive_pool = super::create_archive_pool(":memory:").await.unwrap();
+
+        let now = chrono::Utc::now();
+        let entries = vec![
+            AuditLogEntry {
+                timestamp: now - chrono::Duration::days(101),
+                ..make_entry("GET", "/api/old-2", 200, ActorType::User)
+            },
+            AuditLogEntry {
+                timestamp: now - chrono::Duration::days(100),
+                ..make_entry("GET", "/api/old-1", 200, ActorType::User)
+            },
+            AuditLogEntry {
+                timestamp: now - chrono::Duration::days(2),
+                ..make_entry("GET", "/api/new-2", 200, ActorType::User)
+            },
+            AuditLogEntry {
+                timestamp: now - chrono::Duration::days(1),
+                ..make_entry("GET", "/api/new-1", 200, ActorType::User)
+            },
+        ];
+        storage.insert_batch(&entries).await.unwrap();
+        storage
+            .archive_old_entries(90, &archive_pool)
+            .await
+            .unwrap();
+
+        // アーカイブDBへのコピー後、メインDBから削除される前の状態を再現する
+        let boundary = storage
+            .query(&AuditLogFilter {
+                request_path: Some("/api/new-2".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .remove(0);
+        sqlx::query(
+            "INSERT INTO audit_log_entries \
+             (id, timestamp, http_method, request_path, status_code, actor_type) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(boundary.id)
+        .bind(boundary.timestamp.to_rfc3339())
+        .bind(&boundary.http_method)
+        .bind(&boundary.request_path)
+        .bind(boundary.status_code as i64)
+        .bind("user")
+        .execute(&archive_pool)
+        .await
+        .unwrap();
+
+        let (items, total) =
+            AuditLogStorage::search_across(&storage, &archive_pool, &AuditLogFilter::default())
+                .await
+                .unwrap();
+        assert_eq!(total, 4);
+        let paths: Vec<_> = items.iter().map(|e| e.request_path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["/api/new-1", "/api/new-2", "/api/old-1", "/api/old-2"]
+        );
+
+        // 2ページ目はアーカイブ側のみ（境界で重複・欠落しない）
+        let (items, total) = AuditLogStorage::search_across(
+            &storage,
+            &archive_pool,
+            &AuditLogFilter {
+                page: Some(2),
+                per_page: Some(2),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(total, 4);
+        let paths: Vec<_> = items.iter().map(|e| e.request_path.as_str()).collect();
+        assert_eq!(paths, vec!["/api/old-1", "/api/old-2"]);
+    }
 }
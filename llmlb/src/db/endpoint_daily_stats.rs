@@ -350,6 +350,7 @@ impl From<TpsSeedRow> for TpsSeedEntry {
 /// サーバーローカル時間の0:00に前日分の統計をログ出力する。
 /// リアルタイムUPSERTで統計は更新済みのため、
 /// このタスクは日次マーカーとログ記録の役割を担う。
+/// あわせて保持期間（`LLMLB_TPS_HISTORY_RETENTION_DAYS`）を過ぎたTPS時系列を削除する。
 pub fn start_daily_stats_task(pool: SqlitePool) {
     tokio::spawn(async move {
         loop {
@@ -398,6 +399,18 @@ pub fn start_daily_stats_task(pool: SqlitePool) {
                     tracing::error!("Daily stats batch failed: {}", e);
                 }
             }
+
+            let retention_days = crate::config::tps_history_retention_days() as i64;
+            let before = chrono::Utc::now() - chrono::Duration::days(retention_days);
+            match crate::db::tps_history::prune_before(&pool, before).await {
+                Ok(0) => {}
+                Ok(count) => {
+                    tracing::info!(count, retention_days, "Pruned old TPS history snapshots");
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to prune TPS history: {}", e);
+                }
+            }
         }
     });
 }
@@ -0,0 +1,294 @@
+//! エンドポイントグループのストレージ層
+//!
+//! グループ本体と所属エンドポイントをSQLiteに永続化する。
+//! エンドポイントが削除された場合、所属情報は外部キーで自動的に削除される。
+
+use crate::common::error::{LbError, RouterResult};
+use crate::registry::EndpointGroup;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow)]
+struct EndpointGroupRow {
+    id: String,
+    name: String,
+    description: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct EndpointGroupMemberRow {
+    group_id: String,
+    endpoint_id: String,
+}
+
+fn parse_timestamp(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+impl EndpointGroupRow {
+    fn into_group(self, endpoint_ids: Vec<Uuid>) -> EndpointGroup {
+        EndpointGroup {
+            id: Uuid::parse_str(&self.id).unwrap_or_default(),
+            name: self.name,
+            description: self.description,
+            endpoint_ids,
+            created_at: parse_timestamp(&self.created_at),
+            updated_at: parse_timestamp(&self.updated_at),
+        }
+    }
+}
+
+/// グループ一覧を取得（名前順）
+pub async fn list(pool: &SqlitePool) -> RouterResult<Vec<EndpointGroup>> {
+    let rows = sqlx::query_as::<_, EndpointGroupRow>(
+        r#"
+        SELECT id, name, description, created_at, updated_at
+        FROM endpoint_groups
+        ORDER BY name ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to list endpoint groups: {}", e)))?;
+
+    let member_rows = sqlx::query_as::<_, EndpointGroupMemberRow>(
+        "SELECT group_id, endpoint_id FROM endpoint_group_members ORDER BY endpoint_id ASC",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to list endpoint group members: {}", e)))?;
+
+    let mut members: HashMap<String, Vec<Uuid>> = HashMap::new();
+    for row in member_rows {
+        if let Ok(endpoint_id) = Uuid::parse_str(&row.endpoint_id) {
+            members.entry(row.group_id).or_default().push(endpoint_id);
+        }
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let endpoint_ids = members.remove(&row.id).unwrap_or_default();
+            row.into_group(endpoint_ids)
+        })
+        .collect())
+}
+
+/// IDでグループを取得
+pub async fn get(pool: &SqlitePool, id: Uuid) -> RouterResult<Option<EndpointGroup>> {
+    let row = sqlx::query_as::<_, EndpointGroupRow>(
+        r#"
+        SELECT id, name, description, created_at, updated_at
+        FROM endpoint_groups
+        WHERE id = ?
+        "#,
+    )
+    .bind(id.to_string())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to get endpoint group: {}", e)))?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let endpoint_ids: Vec<String> = sqlx::query_scalar(
+        "SELECT endpoint_id FROM endpoint_group_members WHERE group_id = ? ORDER BY endpoint_id ASC",
+    )
+    .bind(id.to_string())
+    .fetch_all(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to get endpoint group members: {}", e)))?;
+
+    Ok(Some(
+        row.into_group(
+            endpoint_ids
+                .iter()
+                .filter_map(|id| Uuid::parse_str(id).ok())
+                .collect(),
+        ),
+    ))
+}
+
+/// グループを作成（所属エンドポイントも同一トランザクションで保存）
+pub async fn create(pool: &SqlitePool, group: &EndpointGroup) -> RouterResult<()> {
+    let mut tx = pool.begin().await.map_err(map_write_error)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO endpoint_groups (id, name, description, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(group.id.to_string())
+    .bind(&group.name)
+    .bind(&group.description)
+    .bind(group.created_at.to_rfc3339())
+    .bind(group.updated_at.to_rfc3339())
+    .execute(&mut *tx)
+    .await
+    .map_err(map_write_error)?;
+
+    insert_members(&mut tx, group).await?;
+
+    tx.commit().await.map_err(map_write_error)
+}
+
+/// グループを更新（所属エンドポイントは置き換える）
+pub async fn update(pool: &SqlitePool, group: &EndpointGroup) -> RouterResult<bool> {
+    let mut tx = pool.begin().await.map_err(map_write_error)?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE endpoint_groups SET name = ?, description = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&group.name)
+    .bind(&group.description)
+    .bind(group.updated_at.to_rfc3339())
+    .bind(group.id.to_string())
+    .execute(&mut *tx)
+    .await
+    .map_err(map_write_error)?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query("DELETE FROM endpoint_group_members WHERE group_id = ?")
+        .bind(group.id.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(map_write_error)?;
+    insert_members(&mut tx, group).await?;
+
+    tx.commit().await.map_err(map_write_error)?;
+    Ok(true)
+}
+
+/// グループを削除（所属エンドポイント自体は削除しない）
+pub async fn delete(pool: &SqlitePool, id: Uuid) -> RouterResult<bool> {
+    let result = sqlx::query("DELETE FROM endpoint_groups WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to delete endpoint group: {}", e)))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+async fn insert_members(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    group: &EndpointGroup,
+) -> RouterResult<()> {
+    for endpoint_id in &group.endpoint_ids {
+        sqlx::query(
+            "INSERT OR IGNORE INTO endpoint_group_members (group_id, endpoint_id) VALUES (?, ?)",
+        )
+        .bind(group.id.to_string())
+        .bind(endpoint_id.to_string())
+        .execute(&mut **tx)
+        .await
+        .map_err(map_write_error)?;
+    }
+    Ok(())
+}
+
+fn map_write_error(e: sqlx::Error) -> LbError {
+    if e.to_string().contains("UNIQUE constraint failed") {
+        LbError::Conflict("Endpoint group with this name already exists".to_string())
+    } else {
+        LbError::Database(format!("Failed to save endpoint group: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::TEST_LOCK;
+    use crate::types::endpoint::{Endpoint, EndpointType};
+
+    async fn insert_endpoint(pool: &SqlitePool, name: &str) -> Uuid {
+        let endpoint = Endpoint::new(
+            name.to_string(),
+            format!("http://{}.local:11434", name),
+            EndpointType::OpenaiCompatible,
+        );
+        crate::db::endpoints::create_endpoint(pool, &endpoint)
+            .await
+            .unwrap();
+        endpoint.id
+    }
+
+    fn sample_group(name: &str, endpoint_ids: Vec<Uuid>) -> EndpointGroup {
+        let now = Utc::now();
+        EndpointGroup {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: None,
+            endpoint_ids,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn endpoint_group_crud_keeps_member_endpoints() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let a = insert_endpoint(&pool, "group-a").await;
+        let b = insert_endpoint(&pool, "group-b").await;
+
+        // 1エンドポイントは複数グループに所属できる
+        let gpu = sample_group("gpu", vec![a, b]);
+        let canary = sample_group("canary", vec![a]);
+        create(&pool, &gpu).await.unwrap();
+        create(&pool, &canary).await.unwrap();
+
+        let listed = list(&pool).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].name, "canary");
+        assert_eq!(listed[0].endpoint_ids, vec![a]);
+        assert_eq!(listed[1].endpoint_ids.len(), 2);
+
+        let mut changed = gpu.clone();
+        changed.description = Some("A100 pool".to_string());
+        changed.endpoint_ids = vec![b];
+        assert!(update(&pool, &changed).await.unwrap());
+        let fetched = get(&pool, gpu.id).await.unwrap().unwrap();
+        assert_eq!(fetched.description.as_deref(), Some("A100 pool"));
+        assert_eq!(fetched.endpoint_ids, vec![b]);
+
+        assert!(delete(&pool, gpu.id).await.unwrap());
+        assert!(get(&pool, gpu.id).await.unwrap().is_none());
+        assert!(crate::db::endpoints::get_endpoint(&pool, b)
+            .await
+            .unwrap()
+            .is_some());
+
+        // エンドポイント削除時は所属情報のみ外れる
+        crate::db::endpoints::delete_endpoint(&pool, a)
+            .await
+            .unwrap();
+        let fetched = get(&pool, canary.id).await.unwrap().unwrap();
+        assert!(fetched.endpoint_ids.is_empty());
+    }
+
+    #[tokio::test]
+    async fn endpoint_group_duplicate_name_is_conflict() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+
+        create(&pool, &sample_group("dup", vec![])).await.unwrap();
+        let err = create(&pool, &sample_group("dup", vec![]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LbError::Conflict(_)));
+    }
+}
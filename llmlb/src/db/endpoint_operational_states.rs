@@ -0,0 +1,160 @@
+//! エンドポイント運用状態のストレージ層
+//!
+//! drain / disable / maintenance 状態をSQLiteに永続化し、起動時に復元できるようにする。
+//! 通常運用（`active`）に戻したエンドポイントの行は削除する。
+
+use crate::common::error::{LbError, RouterResult};
+use crate::types::endpoint::{EndpointOperationalState, OperationalState};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow)]
+struct OperationalStateRow {
+    endpoint_id: String,
+    state: String,
+    reason: Option<String>,
+    updated_by: Option<String>,
+    updated_at: String,
+}
+
+impl TryFrom<OperationalStateRow> for EndpointOperationalState {
+    type Error = LbError;
+
+    fn try_from(row: OperationalStateRow) -> Result<Self, Self::Error> {
+        Ok(EndpointOperationalState {
+            endpoint_id: Uuid::parse_str(&row.endpoint_id)
+                .map_err(|e| LbError::Database(format!("Invalid endpoint id: {}", e)))?,
+            state: row.state.parse().map_err(LbError::Database)?,
+            reason: row.reason,
+            updated_by: row.updated_by,
+            updated_at: DateTime::parse_from_rfc3339(&row.updated_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+        })
+    }
+}
+
+/// 永続化された運用状態の一覧を取得
+pub async fn list(pool: &SqlitePool) -> RouterResult<Vec<EndpointOperationalState>> {
+    let rows = sqlx::query_as::<_, OperationalStateRow>(
+        r#"
+        SELECT endpoint_id, state, reason, updated_by, updated_at
+        FROM endpoint_operational_states
+        ORDER BY updated_at ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to list endpoint operational states: {}", e)))?;
+
+    rows.into_iter()
+        .map(EndpointOperationalState::try_from)
+        .collect()
+}
+
+/// 運用状態を保存する（`active` の場合は行を削除する）
+pub async fn save(pool: &SqlitePool, state: &EndpointOperationalState) -> RouterResult<()> {
+    save_with(pool, state).await.map_err(|e| {
+        if state.state == OperationalState::Active {
+            LbError::Database(format!("Failed to clear endpoint operational state: {}", e))
+        } else {
+            LbError::Database(format!("Failed to save endpoint operational state: {}", e))
+        }
+    })
+}
+
+/// 運用状態を指定のエグゼキュータ（トランザクション等）で保存する
+pub async fn save_with<'e, E>(
+    executor: E,
+    state: &EndpointOperationalState,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    if state.state == OperationalState::Active {
+        sqlx::query("DELETE FROM endpoint_operational_states WHERE endpoint_id = ?")
+            .bind(state.endpoint_id.to_string())
+            .execute(executor)
+            .await?;
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO endpoint_operational_states (endpoint_id, state, reason, updated_by, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(endpoint_id) DO UPDATE SET
+            state = excluded.state,
+            reason = excluded.reason,
+            updated_by = excluded.updated_by,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(state.endpoint_id.to_string())
+    .bind(state.state.as_str())
+    .bind(&state.reason)
+    .bind(&state.updated_by)
+    .bind(state.updated_at.to_rfc3339())
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::TEST_LOCK;
+    use crate::types::endpoint::{Endpoint, EndpointType};
+
+    async fn insert_endpoint(pool: &SqlitePool) -> Uuid {
+        let endpoint = Endpoint::new(
+            "operational-state-endpoint".to_string(),
+            "http://localhost:11434".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        crate::db::endpoints::create_endpoint(pool, &endpoint)
+            .await
+            .unwrap();
+        endpoint.id
+    }
+
+    #[tokio::test]
+    async fn operational_state_save_and_clear() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let endpoint_id = insert_endpoint(&pool).await;
+
+        let draining = EndpointOperationalState {
+            endpoint_id,
+            state: OperationalState::Draining,
+            reason: Some("kernel upgrade".to_string()),
+            updated_by: Some("admin".to_string()),
+            updated_at: Utc::now(),
+        };
+        save(&pool, &draining).await.unwrap();
+        let listed = list(&pool).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].state, OperationalState::Draining);
+        assert_eq!(listed[0].reason.as_deref(), Some("kernel upgrade"));
+
+        let maintenance = EndpointOperationalState {
+            state: OperationalState::Maintenance,
+            reason: None,
+            ..draining.clone()
+        };
+        save(&pool, &maintenance).await.unwrap();
+        let listed = list(&pool).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].state, OperationalState::Maintenance);
+        assert!(listed[0].reason.is_none());
+
+        let active = EndpointOperationalState {
+            state: OperationalState::Active,
+            ..draining
+        };
+        save(&pool, &active).await.unwrap();
+        assert!(list(&pool).await.unwrap().is_empty());
+    }
+}
@@ -3,7 +3,8 @@
 //! SPEC-e8e9326e: llmlb主導エンドポイント登録システム
 
 use crate::types::endpoint::{
-    Endpoint, EndpointHealthCheck, EndpointModel, EndpointStatus, SupportedAPI,
+    Endpoint, EndpointHealthCheck, EndpointModel, EndpointOperationalState, EndpointStatus,
+    SupportedAPI,
 };
 use sqlx::SqlitePool;
 use uuid::Uuid;
@@ -15,6 +16,9 @@ pub async fn create_endpoint(pool: &SqlitePool, endpoint: &Endpoint) -> Result<(
     let registered_at = endpoint.registered_at.to_rfc3339();
     let last_seen = endpoint.last_seen.map(|dt| dt.to_rfc3339());
     let capabilities = serde_json::to_string(&endpoint.capabilities).unwrap_or_default();
+    let tags = serde_json::to_string(&endpoint.tags).unwrap_or_else(|_| "[]".to_string());
+    let depends_on =
+        serde_json::to_string(&endpoint.depends_on).unwrap_or_else(|_| "[]".to_string());
     // SPEC-f8e3a1b7: デバイス情報と推論レイテンシ
     let device_info = endpoint
         .device_info
@@ -28,8 +32,10 @@ pub async fn create_endpoint(pool: &SqlitePool, endpoint: &Endpoint) -> Result<(
             id, name, base_url, api_key_encrypted, status, endpoint_type,
             health_check_interval_secs, inference_timeout_secs,
             latency_ms, last_seen, last_error, error_count,
-            registered_at, notes, capabilities, device_info, inference_latency_ms
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            registered_at, notes, capabilities, device_info, inference_latency_ms, tags, weight,
+            monthly_budget_usd, input_cost_per_million_tokens, output_cost_per_million_tokens,
+            failover_to, canary_percent, depends_on, slots
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(&id)
@@ -49,6 +55,15 @@ pub async fn create_endpoint(pool: &SqlitePool, endpoint: &Endpoint) -> Result<(
     .bind(&capabilities)
     .bind(&device_info)
     .bind(endpoint.inference_latency_ms)
+    .bind(&tags)
+    .bind(endpoint.weight as i64)
+    .bind(endpoint.monthly_budget_usd)
+    .bind(endpoint.input_cost_per_million_tokens)
+    .bind(endpoint.output_cost_per_million_tokens)
+    .bind(endpoint.failover_to.map(|id| id.to_string()))
+    .bind(endpoint.canary_percent.map(i64::from))
+    .bind(&depends_on)
+    .bind(endpoint.slots.map(i64::from))
     .execute(pool)
     .await?;
 
@@ -64,7 +79,9 @@ pub async fn list_endpoints(pool: &SqlitePool) -> Result<Vec<Endpoint>, sqlx::Er
                latency_ms, last_seen, last_error, error_count,
                registered_at, notes, capabilities,
                device_info, inference_latency_ms,
-               total_requests, successful_requests, failed_requests
+               total_requests, successful_requests, failed_requests, tags, weight,
+               monthly_budget_usd, input_cost_per_million_tokens, output_cost_per_million_tokens,
+               failover_to, canary_percent, depends_on, slots
         FROM endpoints
         ORDER BY registered_at DESC
         "#,
@@ -84,7 +101,9 @@ pub async fn get_endpoint(pool: &SqlitePool, id: Uuid) -> Result<Option<Endpoint
                latency_ms, last_seen, last_error, error_count,
                registered_at, notes, capabilities,
                device_info, inference_latency_ms,
-               total_requests, successful_requests, failed_requests
+               total_requests, successful_requests, failed_requests, tags, weight,
+               monthly_budget_usd, input_cost_per_million_tokens, output_cost_per_million_tokens,
+               failover_to, canary_percent, depends_on, slots
         FROM endpoints
         WHERE id = ?
         "#,
@@ -103,6 +122,9 @@ pub async fn update_endpoint(pool: &SqlitePool, endpoint: &Endpoint) -> Result<b
     let endpoint_type = endpoint.endpoint_type.as_str();
     let last_seen = endpoint.last_seen.map(|dt| dt.to_rfc3339());
     let capabilities = serde_json::to_string(&endpoint.capabilities).unwrap_or_default();
+    let tags = serde_json::to_string(&endpoint.tags).unwrap_or_else(|_| "[]".to_string());
+    let depends_on =
+        serde_json::to_string(&endpoint.depends_on).unwrap_or_else(|_| "[]".to_string());
     // SPEC-f8e3a1b7: デバイス情報と推論レイテンシ
     let device_info = endpoint
         .device_info
@@ -115,7 +137,10 @@ pub async fn update_endpoint(pool: &SqlitePool, endpoint: &Endpoint) -> Result<b
             name = ?, base_url = ?, api_key_encrypted = ?, status = ?, endpoint_type = ?,
             health_check_interval_secs = ?, inference_timeout_secs = ?,
             latency_ms = ?, last_seen = ?, last_error = ?, error_count = ?,
-            notes = ?, capabilities = ?, device_info = ?, inference_latency_ms = ?
+            notes = ?, capabilities = ?, device_info = ?, inference_latency_ms = ?, tags = ?,
+            weight = ?, monthly_budget_usd = ?, input_cost_per_million_tokens = ?,
+            output_cost_per_million_tokens = ?, failover_to = ?, canary_percent = ?,
+            depends_on = ?, slots = ?
         WHERE id = ?
         "#,
     )
@@ -134,6 +159,15 @@ pub async fn update_endpoint(pool: &SqlitePool, endpoint: &Endpoint) -> Result<b
     .bind(&capabilities)
     .bind(&device_info)
     .bind(endpoint.inference_latency_ms)
+    .bind(&tags)
+    .bind(endpoint.weight as i64)
+    .bind(endpoint.monthly_budget_usd)
+    .bind(endpoint.input_cost_per_million_tokens)
+    .bind(endpoint.output_cost_per_million_tokens)
+    .bind(endpoint.failover_to.map(|id| id.to_string()))
+    .bind(endpoint.canary_percent.map(i64::from))
+    .bind(&depends_on)
+    .bind(endpoint.slots.map(i64::from))
     .bind(&id)
     .execute(pool)
     .await?;
@@ -141,6 +175,165 @@ pub async fn update_endpoint(pool: &SqlitePool, endpoint: &Endpoint) -> Result<b
     Ok(result.rows_affected() > 0)
 }
 
+/// エンドポイントの重みを更新
+pub async fn update_endpoint_weight(
+    pool: &SqlitePool,
+    id: Uuid,
+    weight: u32,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE endpoints SET weight = ? WHERE id = ?")
+        .bind(weight as i64)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// エンドポイントのタグを更新
+pub async fn update_endpoint_tags(
+    pool: &SqlitePool,
+    id: Uuid,
+    tags: &[String],
+) -> Result<bool, sqlx::Error> {
+    let tags = serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string());
+    let result = sqlx::query("UPDATE endpoints SET tags = ? WHERE id = ?")
+        .bind(tags)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// 一括更新で変更するエンドポイント設定（`None` の項目は変更しない）
+#[derive(Debug, Clone)]
+pub struct EndpointSettingsUpdate {
+    /// 対象エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 重み
+    pub weight: Option<u32>,
+    /// タグ（置き換え）
+    pub tags: Option<Vec<String>>,
+    /// 運用状態
+    pub operational_state: Option<EndpointOperationalState>,
+}
+
+/// 複数エンドポイントの重み・タグ・運用状態を1トランザクションで更新
+///
+/// いずれかの更新に失敗した場合は全件ロールバックする。
+pub async fn apply_settings_updates(
+    pool: &SqlitePool,
+    updates: &[EndpointSettingsUpdate],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    for update in updates {
+        let id = update.endpoint_id.to_string();
+        if let Some(weight) = update.weight {
+            sqlx::query("UPDATE endpoints SET weight = ? WHERE id = ?")
+                .bind(weight as i64)
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        if let Some(tags) = &update.tags {
+            let tags = serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string());
+            sqlx::query("UPDATE endpoints SET tags = ? WHERE id = ?")
+                .bind(tags)
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        if let Some(state) = &update.operational_state {
+            crate::db::endpoint_operational_states::save_with(&mut *tx, state).await?;
+        }
+    }
+
+    tx.commit().await
+}
+
+/// エンドポイントの単価・月次予算を更新
+pub async fn update_endpoint_budget(
+    pool: &SqlitePool,
+    id: Uuid,
+    monthly_budget_usd: Option<f64>,
+    input_cost_per_million_tokens: Option<f64>,
+    output_cost_per_million_tokens: Option<f64>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE endpoints SET
+            monthly_budget_usd = ?, input_cost_per_million_tokens = ?,
+            output_cost_per_million_tokens = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(monthly_budget_usd)
+    .bind(input_cost_per_million_tokens)
+    .bind(output_cost_per_million_tokens)
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// エンドポイントのカナリア割合を更新（`None` で通常のエンドポイントに戻す）
+pub async fn update_endpoint_canary_percent(
+    pool: &SqlitePool,
+    id: Uuid,
+    canary_percent: Option<u8>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE endpoints SET canary_percent = ? WHERE id = ?")
+        .bind(canary_percent.map(i64::from))
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// 当月累計コストを加算
+pub async fn add_endpoint_monthly_cost(
+    pool: &SqlitePool,
+    id: Uuid,
+    month: &str,
+    cost_usd: f64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO endpoint_monthly_costs (endpoint_id, month, cost_usd)
+        VALUES (?, ?, ?)
+        ON CONFLICT(endpoint_id, month) DO UPDATE SET cost_usd = cost_usd + excluded.cost_usd
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(month)
+    .bind(cost_usd)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// 指定月のエンドポイント別累計コストを取得
+pub async fn list_endpoint_monthly_costs(
+    pool: &SqlitePool,
+    month: &str,
+) -> Result<Vec<(Uuid, f64)>, sqlx::Error> {
+    let rows: Vec<(String, f64)> =
+        sqlx::query_as("SELECT endpoint_id, cost_usd FROM endpoint_monthly_costs WHERE month = ?")
+            .bind(month)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, cost)| Uuid::parse_str(&id).ok().map(|id| (id, cost)))
+        .collect())
+}
+
 /// エンドポイントを削除
 pub async fn delete_endpoint(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
     let result = sqlx::query("DELETE FROM endpoints WHERE id = ?")
@@ -160,7 +353,9 @@ pub async fn find_by_name(pool: &SqlitePool, name: &str) -> Result<Option<Endpoi
                latency_ms, last_seen, last_error, error_count,
                registered_at, notes, capabilities,
                device_info, inference_latency_ms,
-               total_requests, successful_requests, failed_requests
+               total_requests, successful_requests, failed_requests, tags, weight,
+               monthly_budget_usd, input_cost_per_million_tokens, output_cost_per_million_tokens,
+               failover_to, canary_percent, depends_on, slots
         FROM endpoints
         WHERE name = ?
         "#,
@@ -184,7 +379,9 @@ pub async fn list_endpoints_by_status(
                latency_ms, last_seen, last_error, error_count,
                registered_at, notes, capabilities,
                device_info, inference_latency_ms,
-               total_requests, successful_requests, failed_requests
+               total_requests, successful_requests, failed_requests, tags, weight,
+               monthly_budget_usd, input_cost_per_million_tokens, output_cost_per_million_tokens,
+               failover_to, canary_percent, depends_on, slots
         FROM endpoints
         WHERE status = ?
         ORDER BY registered_at DESC
@@ -209,7 +406,9 @@ pub async fn list_endpoints_by_type(
                latency_ms, last_seen, last_error, error_count,
                registered_at, notes, capabilities,
                device_info, inference_latency_ms,
-               total_requests, successful_requests, failed_requests
+               total_requests, successful_requests, failed_requests, tags, weight,
+               monthly_budget_usd, input_cost_per_million_tokens, output_cost_per_million_tokens,
+               failover_to, canary_percent, depends_on, slots
         FROM endpoints
         WHERE endpoint_type = ?
         ORDER BY registered_at DESC
@@ -235,7 +434,9 @@ pub async fn list_endpoints_by_type_and_status(
                latency_ms, last_seen, last_error, error_count,
                registered_at, notes, capabilities,
                device_info, inference_latency_ms,
-               total_requests, successful_requests, failed_requests
+               total_requests, successful_requests, failed_requests, tags, weight,
+               monthly_budget_usd, input_cost_per_million_tokens, output_cost_per_million_tokens,
+               failover_to, canary_percent, depends_on, slots
         FROM endpoints
         WHERE endpoint_type = ? AND status = ?
         ORDER BY registered_at DESC
@@ -395,10 +596,15 @@ pub async fn get_request_totals(pool: &SqlitePool) -> Result<EndpointRequestTota
 // --- EndpointModel CRUD ---
 
 /// エンドポイントにモデルを追加
-pub async fn add_endpoint_model(
-    pool: &SqlitePool,
+///
+/// `executor` にはプールのほかトランザクション（`&mut *tx`）も渡せる。
+pub async fn add_endpoint_model<'e, E>(
+    executor: E,
     model: &EndpointModel,
-) -> Result<(), sqlx::Error> {
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
     let capabilities_json = model
         .capabilities
         .as_ref()
@@ -417,17 +623,22 @@ pub async fn add_endpoint_model(
     .bind(model.max_tokens.map(|v| v as i32))
     .bind(&last_checked)
     .bind(&model.canonical_name)
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
 /// エンドポイントのモデル情報を更新
-pub async fn update_endpoint_model(
-    pool: &SqlitePool,
+///
+/// `max_tokens` が `None` の場合は既存値を保持し、手動設定済みの `max_tokens` は上書きしない。
+pub async fn update_endpoint_model<'e, E>(
+    executor: E,
     model: &EndpointModel,
-) -> Result<bool, sqlx::Error> {
+) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
     let capabilities_json = model
         .capabilities
         .as_ref()
@@ -436,7 +647,13 @@ pub async fn update_endpoint_model(
     let result = sqlx::query(
         r#"
         UPDATE endpoint_models
-        SET capabilities = ?, max_tokens = ?, last_checked = ?, canonical_name = ?
+        SET capabilities = ?,
+            max_tokens = CASE
+                WHEN max_tokens_manual = 1 THEN max_tokens
+                ELSE COALESCE(?, max_tokens)
+            END,
+            last_checked = ?,
+            canonical_name = ?
         WHERE endpoint_id = ? AND model_id = ?
         "#,
     )
@@ -446,31 +663,82 @@ pub async fn update_endpoint_model(
     .bind(&model.canonical_name)
     .bind(model.endpoint_id.to_string())
     .bind(&model.model_id)
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(result.rows_affected() > 0)
 }
 
+/// エンドポイントの全モデルの最終確認時刻を更新する
+///
+/// 内容に変化が無いモデルも同期で存在を確認できたことを記録するため、行ごとではなく一括で更新する。
+pub async fn touch_endpoint_models<'e, E>(
+    executor: E,
+    endpoint_id: Uuid,
+    last_checked: chrono::DateTime<chrono::Utc>,
+) -> Result<u64, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    let result = sqlx::query("UPDATE endpoint_models SET last_checked = ? WHERE endpoint_id = ?")
+        .bind(last_checked.to_rfc3339())
+        .bind(endpoint_id.to_string())
+        .execute(executor)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
 /// モデルのmax_tokensのみを更新（SPEC-e8e9326e）
 ///
 /// メタデータ取得後にcontext_lengthをmax_tokensとして保存する。
-pub async fn update_model_max_tokens(
-    pool: &SqlitePool,
+/// 手動設定済みのモデルは更新せず `false` を返す。
+pub async fn update_model_max_tokens<'e, E>(
+    executor: E,
     endpoint_id: Uuid,
     model_id: &str,
     max_tokens: u32,
-) -> Result<bool, sqlx::Error> {
+) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
     let result = sqlx::query(
         r#"
         UPDATE endpoint_models
         SET max_tokens = ?
-        WHERE endpoint_id = ? AND model_id = ?
+        WHERE endpoint_id = ? AND model_id = ? AND max_tokens_manual = 0
         "#,
     )
     .bind(max_tokens as i32)
     .bind(endpoint_id.to_string())
     .bind(model_id)
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// モデルのmax_tokensを手動設定する
+///
+/// `Some` の場合は手動値として固定し、以後の自動反映で上書きしない。
+/// `None` の場合は手動設定を解除し、次回のモデル同期で自動反映される。
+pub async fn set_model_max_tokens_manual(
+    pool: &SqlitePool,
+    endpoint_id: Uuid,
+    model_id: &str,
+    max_tokens: Option<u32>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE endpoint_models
+        SET max_tokens = ?, max_tokens_manual = ?
+        WHERE endpoint_id = ? AND model_id = ?
+        "#,
+    )
+    .bind(max_tokens.map(|v| v as i32))
+    .bind(max_tokens.is_some())
+    .bind(endpoint_id.to_string())
+    .bind(model_id)
     .execute(pool)
     .await?;
 
@@ -478,10 +746,13 @@ pub async fn update_model_max_tokens(
 }
 
 /// エンドポイントのモデル一覧を取得
-pub async fn list_endpoint_models(
-    pool: &SqlitePool,
+pub async fn list_endpoint_models<'e, E>(
+    executor: E,
     endpoint_id: Uuid,
-) -> Result<Vec<EndpointModel>, sqlx::Error> {
+) -> Result<Vec<EndpointModel>, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
     let rows = sqlx::query_as::<_, EndpointModelRow>(
         r#"
         SELECT endpoint_id, model_id, capabilities, max_tokens, last_checked, supported_apis, canonical_name
@@ -490,18 +761,21 @@ pub async fn list_endpoint_models(
         "#,
     )
     .bind(endpoint_id.to_string())
-    .fetch_all(pool)
+    .fetch_all(executor)
     .await?;
 
     Ok(rows.into_iter().map(|r| r.into()).collect())
 }
 
 /// エンドポイントからモデルを削除
-pub async fn delete_endpoint_model(
-    pool: &SqlitePool,
+pub async fn delete_endpoint_model<'e, E>(
+    executor: E,
     endpoint_id: Uuid,
     model_id: &str,
-) -> Result<bool, sqlx::Error> {
+) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
     let result = sqlx::query(
         r#"
         DELETE FROM endpoint_models
@@ -510,7 +784,7 @@ pub async fn delete_endpoint_model(
     )
     .bind(endpoint_id.to_string())
     .bind(model_id)
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(result.rows_affected() > 0)
@@ -643,6 +917,24 @@ struct EndpointRow {
     successful_requests: i64,
     /// SPEC-8c32349f: 累計失敗リクエスト数
     failed_requests: i64,
+    /// タグ（JSON配列）
+    tags: Option<String>,
+    /// 重み（目標値）
+    weight: Option<i64>,
+    /// 月次予算（USD）
+    monthly_budget_usd: Option<f64>,
+    /// 入力100万トークンあたりの単価（USD）
+    input_cost_per_million_tokens: Option<f64>,
+    /// 出力100万トークンあたりの単価（USD）
+    output_cost_per_million_tokens: Option<f64>,
+    /// 優先フェイルオーバー先のエンドポイントID
+    failover_to: Option<String>,
+    /// カナリア割合（%）
+    canary_percent: Option<i64>,
+    /// 依存先のエンドポイントID（JSON配列）
+    depends_on: Option<String>,
+    /// 同時スロット数
+    slots: Option<i64>,
 }
 
 impl From<EndpointRow> for Endpoint {
@@ -688,6 +980,21 @@ impl From<EndpointRow> for Endpoint {
             total_requests: row.total_requests,
             successful_requests: row.successful_requests,
             failed_requests: row.failed_requests,
+            tags: row
+                .tags
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+            weight: row.weight.map(|v| v.max(0) as u32).unwrap_or(1),
+            monthly_budget_usd: row.monthly_budget_usd,
+            input_cost_per_million_tokens: row.input_cost_per_million_tokens,
+            output_cost_per_million_tokens: row.output_cost_per_million_tokens,
+            failover_to: row.failover_to.and_then(|s| Uuid::parse_str(&s).ok()),
+            canary_percent: row.canary_percent.map(|v| v.clamp(0, 100) as u8),
+            depends_on: row
+                .depends_on
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+            slots: row.slots.map(|v| v.clamp(0, u32::MAX as i64) as u32),
         }
     }
 }
@@ -802,6 +1109,96 @@ mod tests {
         assert!(not_found.is_none());
     }
 
+    #[tokio::test]
+    async fn test_endpoint_tags_roundtrip() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+
+        let mut endpoint = Endpoint::new(
+            "Tagged Endpoint".to_string(),
+            "http://localhost:8082".to_string(),
+            crate::types::endpoint::EndpointType::Vllm,
+        );
+        endpoint.tags = vec!["gpu-a100".to_string(), "prod".to_string()];
+        create_endpoint(&pool, &endpoint).await.unwrap();
+
+        let fetched = get_endpoint(&pool, endpoint.id).await.unwrap().unwrap();
+        assert_eq!(fetched.tags, vec!["gpu-a100", "prod"]);
+
+        let mut updated = fetched;
+        updated.tags = vec!["gpu-h100".to_string()];
+        update_endpoint(&pool, &updated).await.unwrap();
+
+        let fetched_again = get_endpoint(&pool, endpoint.id).await.unwrap().unwrap();
+        assert_eq!(fetched_again.tags, vec!["gpu-h100"]);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_failover_roundtrip() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+
+        let backup = Endpoint::new(
+            "Backup Endpoint".to_string(),
+            "http://localhost:8084".to_string(),
+            crate::types::endpoint::EndpointType::Vllm,
+        );
+        create_endpoint(&pool, &backup).await.unwrap();
+
+        let mut primary = Endpoint::new(
+            "Primary Endpoint".to_string(),
+            "http://localhost:8083".to_string(),
+            crate::types::endpoint::EndpointType::Vllm,
+        );
+        primary.failover_to = Some(backup.id);
+        create_endpoint(&pool, &primary).await.unwrap();
+
+        let fetched = get_endpoint(&pool, primary.id).await.unwrap().unwrap();
+        assert_eq!(fetched.failover_to, Some(backup.id));
+
+        let mut updated = fetched;
+        updated.failover_to = None;
+        update_endpoint(&pool, &updated).await.unwrap();
+
+        let fetched_again = get_endpoint(&pool, primary.id).await.unwrap().unwrap();
+        assert_eq!(fetched_again.failover_to, None);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_budget_and_monthly_costs() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+
+        let endpoint = Endpoint::new(
+            "Cloud Endpoint".to_string(),
+            "https://api.example.com".to_string(),
+            crate::types::endpoint::EndpointType::OpenaiCompatible,
+        );
+        create_endpoint(&pool, &endpoint).await.unwrap();
+
+        assert!(
+            update_endpoint_budget(&pool, endpoint.id, Some(50.0), Some(2.5), Some(10.0))
+                .await
+                .unwrap()
+        );
+        let fetched = get_endpoint(&pool, endpoint.id).await.unwrap().unwrap();
+        assert_eq!(fetched.monthly_budget_usd, Some(50.0));
+        assert_eq!(fetched.input_cost_per_million_tokens, Some(2.5));
+        assert_eq!(fetched.output_cost_per_million_tokens, Some(10.0));
+
+        add_endpoint_monthly_cost(&pool, endpoint.id, "2026-10", 1.25)
+            .await
+            .unwrap();
+        add_endpoint_monthly_cost(&pool, endpoint.id, "2026-10", 0.75)
+            .await
+            .unwrap();
+        add_endpoint_monthly_cost(&pool, endpoint.id, "2026-09", 9.0)
+            .await
+            .unwrap();
+        let costs = list_endpoint_monthly_costs(&pool, "2026-10").await.unwrap();
+        assert_eq!(costs, vec![(endpoint.id, 2.0)]);
+    }
+
     #[tokio::test]
     async fn test_endpoint_model_crud() {
         let _lock = TEST_LOCK.lock().await;
@@ -1319,6 +1716,62 @@ mod tests {
         assert_eq!(models[0].max_tokens, Some(8192));
     }
 
+    #[tokio::test]
+    async fn test_manual_max_tokens_takes_priority_over_auto() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+
+        let ep = Endpoint::new(
+            "manual-max-tokens-ep".to_string(),
+            "http://localhost:7011".to_string(),
+            crate::types::endpoint::EndpointType::Xllm,
+        );
+        create_endpoint(&pool, &ep).await.unwrap();
+
+        let model = EndpointModel {
+            endpoint_id: ep.id,
+            model_id: "qwen2.5:7b".to_string(),
+            capabilities: None,
+            max_tokens: Some(32768),
+            last_checked: None,
+            supported_apis: vec![SupportedAPI::ChatCompletions],
+            canonical_name: None,
+        };
+        add_endpoint_model(&pool, &model).await.unwrap();
+
+        // 同期で max_tokens が取得できなかった場合は既存値を保持
+        let mut resynced = model.clone();
+        resynced.max_tokens = None;
+        update_endpoint_model(&pool, &resynced).await.unwrap();
+        let models = list_endpoint_models(&pool, ep.id).await.unwrap();
+        assert_eq!(models[0].max_tokens, Some(32768));
+
+        // 手動設定後は自動反映で上書きされない
+        assert!(
+            set_model_max_tokens_manual(&pool, ep.id, "qwen2.5:7b", Some(8192))
+                .await
+                .unwrap()
+        );
+        assert!(!update_model_max_tokens(&pool, ep.id, "qwen2.5:7b", 131072)
+            .await
+            .unwrap());
+        let mut auto = model.clone();
+        auto.max_tokens = Some(131072);
+        update_endpoint_model(&pool, &auto).await.unwrap();
+        let models = list_endpoint_models(&pool, ep.id).await.unwrap();
+        assert_eq!(models[0].max_tokens, Some(8192));
+
+        // 手動設定を解除すると自動反映が再開される
+        set_model_max_tokens_manual(&pool, ep.id, "qwen2.5:7b", None)
+            .await
+            .unwrap();
+        assert!(update_model_max_tokens(&pool, ep.id, "qwen2.5:7b", 131072)
+            .await
+            .unwrap());
+        let models = list_endpoint_models(&pool, ep.id).await.unwrap();
+        assert_eq!(models[0].max_tokens, Some(131072));
+    }
+
     #[tokio::test]
     async fn test_delete_all_endpoint_models() {
         let _lock = TEST_LOCK.lock().await;
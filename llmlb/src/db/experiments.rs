@@ -0,0 +1,214 @@
+//! A/Bテスト（実験）のストレージ層
+//!
+//! A/Bテスト設定をSQLiteに永続化する。グループ別統計はメモリ上で集計する。
+
+use crate::balancer::experiment::{AssignBy, ExperimentVariant};
+use crate::balancer::Experiment;
+use crate::common::error::{LbError, RouterResult};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow)]
+struct ExperimentRow {
+    id: String,
+    name: String,
+    model_pattern: String,
+    assign_by: String,
+    b_percent: i64,
+    variant_a: String,
+    variant_b: String,
+    enabled: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+fn parse_timestamp(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+fn variant_to_json(variant: &ExperimentVariant) -> String {
+    serde_json::to_string(variant).unwrap_or_else(|_| "{}".to_string())
+}
+
+impl From<ExperimentRow> for Experiment {
+    fn from(row: ExperimentRow) -> Self {
+        Experiment {
+            id: Uuid::parse_str(&row.id).unwrap_or_default(),
+            name: row.name,
+            model_pattern: row.model_pattern,
+            assign_by: row.assign_by.parse().unwrap_or(AssignBy::ApiKey),
+            b_percent: row.b_percent.clamp(0, 100) as u8,
+            variant_a: serde_json::from_str(&row.variant_a).unwrap_or_default(),
+            variant_b: serde_json::from_str(&row.variant_b).unwrap_or_default(),
+            enabled: row.enabled != 0,
+            created_at: parse_timestamp(&row.created_at),
+            updated_at: parse_timestamp(&row.updated_at),
+        }
+    }
+}
+
+/// A/Bテスト一覧を取得（作成日時順）
+pub async fn list(pool: &SqlitePool) -> RouterResult<Vec<Experiment>> {
+    let rows = sqlx::query_as::<_, ExperimentRow>(
+        r#"
+        SELECT id, name, model_pattern, assign_by, b_percent, variant_a, variant_b,
+               enabled, created_at, updated_at
+        FROM experiments
+        ORDER BY created_at ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to list experiments: {}", e)))?;
+
+    Ok(rows.into_iter().map(Experiment::from).collect())
+}
+
+/// IDでA/Bテストを取得
+pub async fn get(pool: &SqlitePool, id: Uuid) -> RouterResult<Option<Experiment>> {
+    let row = sqlx::query_as::<_, ExperimentRow>(
+        r#"
+        SELECT id, name, model_pattern, assign_by, b_percent, variant_a, variant_b,
+               enabled, created_at, updated_at
+        FROM experiments
+        WHERE id = ?
+        "#,
+    )
+    .bind(id.to_string())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to get experiment: {}", e)))?;
+
+    Ok(row.map(Experiment::from))
+}
+
+/// A/Bテストを作成
+pub async fn create(pool: &SqlitePool, experiment: &Experiment) -> RouterResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO experiments (
+            id, name, model_pattern, assign_by, b_percent, variant_a, variant_b,
+            enabled, created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(experiment.id.to_string())
+    .bind(&experiment.name)
+    .bind(&experiment.model_pattern)
+    .bind(experiment.assign_by.as_str())
+    .bind(experiment.b_percent as i64)
+    .bind(variant_to_json(&experiment.variant_a))
+    .bind(variant_to_json(&experiment.variant_b))
+    .bind(experiment.enabled as i64)
+    .bind(experiment.created_at.to_rfc3339())
+    .bind(experiment.updated_at.to_rfc3339())
+    .execute(pool)
+    .await
+    .map_err(map_write_error)?;
+
+    Ok(())
+}
+
+/// A/Bテストを更新
+pub async fn update(pool: &SqlitePool, experiment: &Experiment) -> RouterResult<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE experiments SET
+            name = ?, model_pattern = ?, assign_by = ?, b_percent = ?,
+            variant_a = ?, variant_b = ?, enabled = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&experiment.name)
+    .bind(&experiment.model_pattern)
+    .bind(experiment.assign_by.as_str())
+    .bind(experiment.b_percent as i64)
+    .bind(variant_to_json(&experiment.variant_a))
+    .bind(variant_to_json(&experiment.variant_b))
+    .bind(experiment.enabled as i64)
+    .bind(experiment.updated_at.to_rfc3339())
+    .bind(experiment.id.to_string())
+    .execute(pool)
+    .await
+    .map_err(map_write_error)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// A/Bテストを削除
+pub async fn delete(pool: &SqlitePool, id: Uuid) -> RouterResult<bool> {
+    let result = sqlx::query("DELETE FROM experiments WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to delete experiment: {}", e)))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+fn map_write_error(e: sqlx::Error) -> LbError {
+    if e.to_string().contains("UNIQUE constraint failed") {
+        LbError::Conflict("Experiment with this name already exists".to_string())
+    } else {
+        LbError::Database(format!("Failed to save experiment: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::TEST_LOCK;
+
+    fn sample_experiment(name: &str) -> Experiment {
+        let now = Utc::now();
+        Experiment {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            model_pattern: "llama3*".to_string(),
+            assign_by: AssignBy::ClientIp,
+            b_percent: 20,
+            variant_a: ExperimentVariant::default(),
+            variant_b: ExperimentVariant {
+                model: Some("qwen2.5".to_string()),
+                required_labels: vec!["gpu-h100".to_string()],
+            },
+            enabled: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn experiment_crud_roundtrip() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+
+        let experiment = sample_experiment("llama-vs-qwen");
+        create(&pool, &experiment).await.unwrap();
+
+        let listed = list(&pool).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].assign_by, AssignBy::ClientIp);
+        assert_eq!(listed[0].b_percent, 20);
+        assert_eq!(listed[0].variant_b, experiment.variant_b);
+
+        let mut changed = experiment.clone();
+        changed.enabled = false;
+        changed.b_percent = 50;
+        assert!(update(&pool, &changed).await.unwrap());
+        let fetched = get(&pool, experiment.id).await.unwrap().unwrap();
+        assert!(!fetched.enabled);
+        assert_eq!(fetched.b_percent, 50);
+
+        let err = create(&pool, &sample_experiment("llama-vs-qwen"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LbError::Conflict(_)));
+
+        assert!(delete(&pool, experiment.id).await.unwrap());
+        assert!(get(&pool, experiment.id).await.unwrap().is_none());
+    }
+}
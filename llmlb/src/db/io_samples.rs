@@ -0,0 +1,373 @@
+//! リクエスト入出力サンプルのデータベース操作
+//!
+//! 品質監視のため、推論リクエストの入出力ペアを `LLMLB_SAMPLE_IO_RATE` の割合でサンプリングし、
+//! レダクションしたうえで io_samples テーブルに保存する。採用率が 0（既定）の場合は保存しない。
+//!
+//! - エラー応答は採用率に関わらず保存する
+//! - 各サンプルは `LLMLB_SAMPLE_IO_TTL_HOURS` で期限切れになり、保存時に削除する
+//! - 保存サイズの合計が `LLMLB_SAMPLE_IO_MAX_MB` を超えた場合は古いものから削除する
+
+use crate::api::openai_util::redact_payload_for_history;
+use crate::common::protocol::{RecordStatus, RequestResponseRecord};
+use chrono::{DateTime, Utc};
+use rand::RngExt;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// 一覧取得の既定件数
+pub const SAMPLE_LIST_DEFAULT_LIMIT: i64 = 50;
+/// 一覧取得の上限件数
+pub const SAMPLE_LIST_MAX_LIMIT: i64 = 500;
+
+/// 保存したリクエスト入出力サンプル
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IoSample {
+    /// リクエスト履歴と同じID
+    pub id: Uuid,
+    /// 保存時刻
+    pub sampled_at: DateTime<Utc>,
+    /// 期限（この時刻を過ぎると削除される）
+    pub expires_at: DateTime<Utc>,
+    /// リクエストタイプ
+    pub request_type: String,
+    /// モデル名
+    pub model: String,
+    /// 処理したエンドポイントのID
+    pub endpoint_id: Uuid,
+    /// エンドポイント名
+    pub endpoint_name: String,
+    /// 結果（success / error）
+    pub status: String,
+    /// エラーメッセージ
+    pub error_message: Option<String>,
+    /// 保存理由（sampled: 採用率による / error: エラー応答）
+    pub reason: String,
+    /// レダクション済みのリクエスト本文
+    pub request_body: Value,
+    /// レダクション済みのレスポンス本文（ストリーミング応答・本文の無いエラーは None）
+    pub response_body: Option<Value>,
+}
+
+/// サンプルとして保存する理由を判定する（保存しない場合は `None`）
+///
+/// `roll` は 0.0 以上 1.0 未満の乱数。採用率が 0 以下なら何も保存しない。
+pub fn sample_reason(is_error: bool, rate: f64, roll: f64) -> Option<&'static str> {
+    if rate.is_nan() || rate <= 0.0 {
+        None
+    } else if is_error {
+        Some("error")
+    } else if roll < rate {
+        Some("sampled")
+    } else {
+        None
+    }
+}
+
+/// リクエスト履歴レコードからレダクション済みのサンプルを作成
+pub fn build_sample(
+    record: &RequestResponseRecord,
+    reason: &str,
+    now: DateTime<Utc>,
+    ttl: Duration,
+) -> IoSample {
+    let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::hours(72));
+    let (status, error_message) = match &record.status {
+        RecordStatus::Success => ("success", None),
+        RecordStatus::Error { message } => ("error", Some(message.clone())),
+    };
+    IoSample {
+        id: record.id,
+        sampled_at: now,
+        expires_at: now + ttl,
+        request_type: format!("{:?}", record.request_type),
+        model: record.model.clone(),
+        endpoint_id: record.endpoint_id,
+        endpoint_name: record.endpoint_name.clone(),
+        status: status.to_string(),
+        error_message,
+        reason: reason.to_string(),
+        request_body: redact_payload_for_history(&record.request_body),
+        response_body: record
+            .response_body
+            .as_ref()
+            .map(redact_payload_for_history),
+    }
+}
+
+/// サンプルを1件保存（同じIDのサンプルが既にあれば何もしない）
+pub async fn insert_sample(pool: &SqlitePool, sample: &IoSample) -> Result<(), sqlx::Error> {
+    let request_body = sample.request_body.to_string();
+    let response_body = sample.response_body.as_ref().map(|v| v.to_string());
+    let size_bytes = request_body.len() + response_body.as_ref().map_or(0, |v| v.len());
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO io_samples (
+            id, sampled_at, expires_at, request_type, model, endpoint_id, endpoint_name,
+            status, error_message, reason, request_body, response_body, size_bytes
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(sample.id.to_string())
+    .bind(sample.sampled_at.to_rfc3339())
+    .bind(sample.expires_at.to_rfc3339())
+    .bind(&sample.request_type)
+    .bind(&sample.model)
+    .bind(sample.endpoint_id.to_string())
+    .bind(&sample.endpoint_name)
+    .bind(&sample.status)
+    .bind(&sample.error_message)
+    .bind(&sample.reason)
+    .bind(request_body)
+    .bind(response_body)
+    .bind(size_bytes as i64)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 期限内のサンプルを新しい順に取得（`status` 指定時はその結果のみ）
+pub async fn list_samples(
+    pool: &SqlitePool,
+    status: Option<&str>,
+    limit: i64,
+    now: DateTime<Utc>,
+) -> Result<Vec<IoSample>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, IoSampleRow>(
+        r#"
+        SELECT * FROM io_samples
+        WHERE expires_at > ? AND (? IS NULL OR status = ?)
+        ORDER BY sampled_at DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(now.to_rfc3339())
+    .bind(status)
+    .bind(status)
+    .bind(limit.clamp(1, SAMPLE_LIST_MAX_LIMIT))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(IoSampleRow::into_sample)
+        .collect())
+}
+
+/// IDで期限内のサンプルを取得
+pub async fn get_sample(
+    pool: &SqlitePool,
+    id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Option<IoSample>, sqlx::Error> {
+    let row = sqlx::query_as::<_, IoSampleRow>(
+        "SELECT * FROM io_samples WHERE id = ? AND expires_at > ?",
+    )
+    .bind(id.to_string())
+    .bind(now.to_rfc3339())
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.and_then(IoSampleRow::into_sample))
+}
+
+/// 期限切れのサンプルを削除し、削除件数を返す
+pub async fn prune_expired(pool: &SqlitePool, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM io_samples WHERE expires_at <= ?")
+        .bind(now.to_rfc3339())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// 保存サイズの合計が `max_bytes` 以下になるまで古いサンプルから削除し、削除件数を返す
+pub async fn enforce_capacity(pool: &SqlitePool, max_bytes: u64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM io_samples WHERE id IN (
+            SELECT id FROM (
+                SELECT id, SUM(size_bytes) OVER (ORDER BY sampled_at DESC, id DESC) AS retained
+                FROM io_samples
+            )
+            WHERE retained > ?
+        )
+        "#,
+    )
+    .bind(i64::try_from(max_bytes).unwrap_or(i64::MAX))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// 設定に従ってリクエストの入出力をサンプリングして保存する
+///
+/// 保存に失敗してもリクエスト履歴の保存には影響させない（ログのみ）。
+pub async fn maybe_save_sample(pool: &SqlitePool, record: &RequestResponseRecord) {
+    let is_error = matches!(record.status, RecordStatus::Error { .. });
+    let roll = rand::rng().random::<f64>();
+    let Some(reason) = sample_reason(is_error, crate::config::sample_io_rate(), roll) else {
+        return;
+    };
+
+    let now = Utc::now();
+    let sample = build_sample(record, reason, now, crate::config::sample_io_ttl());
+    if let Err(e) = insert_sample(pool, &sample).await {
+        tracing::warn!("Failed to save request I/O sample: {}", e);
+        return;
+    }
+    if let Err(e) = prune_expired(pool, now).await {
+        tracing::warn!("Failed to prune expired request I/O samples: {}", e);
+    }
+    match enforce_capacity(pool, crate::config::sample_io_max_bytes()).await {
+        Ok(0) => {}
+        Ok(removed) => tracing::debug!(removed, "Removed oldest request I/O samples over capacity"),
+        Err(e) => tracing::warn!("Failed to enforce request I/O sample capacity: {}", e),
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct IoSampleRow {
+    id: String,
+    sampled_at: String,
+    expires_at: String,
+    request_type: String,
+    model: String,
+    endpoint_id: String,
+    endpoint_name: String,
+    status: String,
+    error_message: Option<String>,
+    reason: String,
+    request_body: String,
+    response_body: Option<String>,
+    #[allow(dead_code)]
+    size_bytes: i64,
+}
+
+impl IoSampleRow {
+    fn into_sample(self) -> Option<IoSample> {
+        Some(IoSample {
+            id: Uuid::parse_str(&self.id).ok()?,
+            sampled_at: DateTime::parse_from_rfc3339(&self.sampled_at)
+                .ok()?
+                .with_timezone(&Utc),
+            expires_at: DateTime::parse_from_rfc3339(&self.expires_at)
+                .ok()?
+                .with_timezone(&Utc),
+            request_type: self.request_type,
+            model: self.model,
+            endpoint_id: Uuid::parse_str(&self.endpoint_id).ok()?,
+            endpoint_name: self.endpoint_name,
+            status: self.status,
+            error_message: self.error_message,
+            reason: self.reason,
+            request_body: serde_json::from_str(&self.request_body).ok()?,
+            response_body: self
+                .response_body
+                .and_then(|body| serde_json::from_str(&body).ok()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::protocol::RequestType;
+    use axum::http::StatusCode;
+
+    fn record(status: StatusCode) -> RequestResponseRecord {
+        let mut record = RequestResponseRecord::new(
+            Uuid::new_v4(),
+            "ep".to_string(),
+            "127.0.0.1".parse().unwrap(),
+            "model-a".to_string(),
+            RequestType::Chat,
+            serde_json::json!({"model": "model-a", "api_key": "sk-secret"}),
+            status,
+            Duration::from_millis(10),
+            None,
+            None,
+        );
+        record.response_body = Some(serde_json::json!({"choices": []}));
+        record
+    }
+
+    #[test]
+    fn errors_are_sampled_preferentially() {
+        assert_eq!(sample_reason(true, 0.01, 0.99), Some("error"));
+        assert_eq!(sample_reason(false, 0.01, 0.99), None);
+        assert_eq!(sample_reason(false, 0.01, 0.001), Some("sampled"));
+        // 採用率 0 では機能自体が無効
+        assert_eq!(sample_reason(true, 0.0, 0.0), None);
+        assert_eq!(sample_reason(false, 0.0, 0.0), None);
+    }
+
+    #[tokio::test]
+    async fn test_insert_redacts_and_expires() {
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let now = Utc::now();
+
+        let sample = build_sample(
+            &record(StatusCode::OK),
+            "sampled",
+            now,
+            Duration::from_secs(3600),
+        );
+        assert_eq!(sample.request_body["api_key"], "[redacted]");
+        insert_sample(&pool, &sample).await.unwrap();
+
+        let fetched = get_sample(&pool, sample.id, now).await.unwrap().unwrap();
+        assert_eq!(fetched.request_body, sample.request_body);
+        assert_eq!(fetched.response_body, sample.response_body);
+        assert_eq!(fetched.status, "success");
+
+        let error = build_sample(
+            &record(StatusCode::BAD_GATEWAY),
+            "error",
+            now,
+            Duration::from_secs(3600),
+        );
+        insert_sample(&pool, &error).await.unwrap();
+        let errors = list_samples(&pool, Some("error"), 10, now).await.unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].id, error.id);
+        assert_eq!(list_samples(&pool, None, 10, now).await.unwrap().len(), 2);
+
+        // 期限を過ぎたサンプルは取得できず、削除される
+        let later = now + chrono::Duration::hours(2);
+        assert!(get_sample(&pool, sample.id, later).await.unwrap().is_none());
+        assert_eq!(prune_expired(&pool, later).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_capacity_removes_oldest_first() {
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let now = Utc::now();
+
+        let mut ids = Vec::new();
+        for minutes_ago in [30, 20, 10] {
+            let sample = build_sample(
+                &record(StatusCode::OK),
+                "sampled",
+                now - chrono::Duration::minutes(minutes_ago),
+                Duration::from_secs(3600),
+            );
+            insert_sample(&pool, &sample).await.unwrap();
+            ids.push(sample.id);
+        }
+        let size: i64 = sqlx::query_scalar("SELECT MAX(size_bytes) FROM io_samples")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        // 2件分の容量に収まるよう最も古い1件を削除する
+        let removed = enforce_capacity(&pool, (size * 2) as u64).await.unwrap();
+        assert_eq!(removed, 1);
+        let remaining: Vec<Uuid> = list_samples(&pool, None, 10, now)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(remaining, vec![ids[2], ids[1]]);
+    }
+}
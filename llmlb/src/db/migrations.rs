@@ -55,6 +55,25 @@ pub async fn run_migrations(pool: &SqlitePool) -> Result<(), LbError> {
     Ok(())
 }
 
+/// このバイナリに含まれる最新のマイグレーションバージョン
+pub fn latest_migration_version() -> i64 {
+    sqlx::migrate!("./migrations")
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or(0)
+}
+
+/// データベースに適用済みの最新マイグレーションバージョン（未適用なら `None`）
+pub async fn applied_migration_version(pool: &SqlitePool) -> Result<Option<i64>, LbError> {
+    sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1",
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to read migration version: {}", e)))
+}
+
 /// JSONファイルからノードデータをインポート（マイグレーション用）
 ///
 /// 注: この機能は将来的にノードデータもSQLiteに移行する際に使用
@@ -133,6 +152,16 @@ mod tests {
         assert!(result.is_ok(), "api_keys table should exist");
     }
 
+    #[tokio::test]
+    async fn test_applied_migration_version_matches_latest() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let applied = applied_migration_version(&pool).await.unwrap();
+        assert_eq!(applied, Some(latest_migration_version()));
+        assert!(latest_migration_version() >= 44);
+    }
+
     #[tokio::test]
     async fn test_import_nodes_from_json_no_file() {
         // 存在しないファイルの場合はエラーなく完了
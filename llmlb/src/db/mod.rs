@@ -24,6 +24,12 @@ pub mod endpoints;
 /// エンドポイント日次統計（SPEC-8c32349f）
 pub mod endpoint_daily_stats;
 
+/// リクエストキュー時系列
+pub mod queue_history;
+
+/// エンドポイント×モデル単位のTPS時系列
+pub mod tps_history;
+
 /// ダウンロードタスク管理（SPEC-e8e9326e）
 pub mod download_tasks;
 
@@ -33,6 +39,33 @@ pub mod audit_log;
 /// 設定管理
 pub mod settings;
 
+/// ルーティングポリシー管理
+pub mod routing_policies;
+
+/// エンドポイント容量予約管理
+pub mod reservations;
+
+/// A/Bテスト（実験）管理
+pub mod experiments;
+
+/// エンドポイント運用状態（drain / disable / maintenance）管理
+pub mod endpoint_operational_states;
+
+/// エンドポイントグループ（論理プール）管理
+pub mod endpoint_groups;
+
+/// ストリーミング出力レート上限管理
+pub mod stream_rate_limits;
+
+/// モデル別レートリミット（RPM/TPM）管理
+pub mod model_rate_limits;
+
+/// シャドウトラフィック設定管理
+pub mod shadow_targets;
+
+/// リクエスト入出力サンプル管理
+pub mod io_samples;
+
 /// Repository traitパターン（テスタビリティ向上）
 pub mod traits;
 
@@ -0,0 +1,129 @@
+//! モデル別レートリミットのストレージ層
+//!
+//! モデル単位のRPM/TPM上限をSQLiteに永続化する。
+
+use crate::balancer::ModelRateLimit;
+use crate::common::error::{LbError, RouterResult};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+#[derive(sqlx::FromRow)]
+struct ModelRateLimitRow {
+    model_id: String,
+    requests_per_minute: Option<i64>,
+    tokens_per_minute: Option<i64>,
+    updated_at: String,
+}
+
+impl From<ModelRateLimitRow> for ModelRateLimit {
+    fn from(row: ModelRateLimitRow) -> Self {
+        ModelRateLimit {
+            model_id: row.model_id,
+            requests_per_minute: row.requests_per_minute.map(|v| v.max(0) as u32),
+            tokens_per_minute: row.tokens_per_minute.map(|v| v.max(0) as u64),
+            updated_at: DateTime::parse_from_rfc3339(&row.updated_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+/// 上限設定一覧を取得
+pub async fn list(pool: &SqlitePool) -> RouterResult<Vec<ModelRateLimit>> {
+    let rows = sqlx::query_as::<_, ModelRateLimitRow>(
+        r#"
+        SELECT model_id, requests_per_minute, tokens_per_minute, updated_at
+        FROM model_rate_limits
+        ORDER BY model_id ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to list model rate limits: {}", e)))?;
+
+    Ok(rows.into_iter().map(ModelRateLimit::from).collect())
+}
+
+/// モデルの上限設定を取得
+pub async fn get(pool: &SqlitePool, model_id: &str) -> RouterResult<Option<ModelRateLimit>> {
+    let row = sqlx::query_as::<_, ModelRateLimitRow>(
+        r#"
+        SELECT model_id, requests_per_minute, tokens_per_minute, updated_at
+        FROM model_rate_limits
+        WHERE model_id = ?
+        "#,
+    )
+    .bind(model_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to get model rate limit: {}", e)))?;
+
+    Ok(row.map(ModelRateLimit::from))
+}
+
+/// モデルの上限設定を保存（既存設定は上書き）
+pub async fn upsert(pool: &SqlitePool, limit: &ModelRateLimit) -> RouterResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO model_rate_limits (model_id, requests_per_minute, tokens_per_minute, updated_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(model_id) DO UPDATE SET
+            requests_per_minute = excluded.requests_per_minute,
+            tokens_per_minute = excluded.tokens_per_minute,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&limit.model_id)
+    .bind(limit.requests_per_minute.map(|v| v as i64))
+    .bind(limit.tokens_per_minute.map(|v| v as i64))
+    .bind(limit.updated_at.to_rfc3339())
+    .execute(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to save model rate limit: {}", e)))?;
+
+    Ok(())
+}
+
+/// モデルの上限設定を削除
+pub async fn delete(pool: &SqlitePool, model_id: &str) -> RouterResult<bool> {
+    let result = sqlx::query("DELETE FROM model_rate_limits WHERE model_id = ?")
+        .bind(model_id)
+        .execute(pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to delete model rate limit: {}", e)))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::TEST_LOCK;
+
+    #[tokio::test]
+    async fn model_rate_limit_upsert_and_delete() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+
+        let mut limit = ModelRateLimit {
+            model_id: "openai:gpt-4o".to_string(),
+            requests_per_minute: Some(60),
+            tokens_per_minute: None,
+            updated_at: Utc::now(),
+        };
+        upsert(&pool, &limit).await.unwrap();
+        assert_eq!(list(&pool).await.unwrap().len(), 1);
+
+        limit.requests_per_minute = None;
+        limit.tokens_per_minute = Some(100_000);
+        upsert(&pool, &limit).await.unwrap();
+        let fetched = get(&pool, "openai:gpt-4o").await.unwrap().unwrap();
+        assert_eq!(fetched.requests_per_minute, None);
+        assert_eq!(fetched.tokens_per_minute, Some(100_000));
+        assert_eq!(list(&pool).await.unwrap().len(), 1);
+
+        assert!(delete(&pool, "openai:gpt-4o").await.unwrap());
+        assert!(get(&pool, "openai:gpt-4o").await.unwrap().is_none());
+        assert!(!delete(&pool, "openai:gpt-4o").await.unwrap());
+    }
+}
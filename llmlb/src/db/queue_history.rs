@@ -0,0 +1,149 @@
+//! リクエストキュー時系列データベース操作
+//!
+//! `LoadManager` の待機数・拒否数を定期サンプリングして queue_history テーブルに保存し、
+//! 保持期間を過ぎたサンプルはサンプリング時に削除する。
+
+use crate::balancer::LoadManager;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use std::time::Duration;
+
+/// キュー時系列の1サンプル
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct QueueHistoryPoint {
+    /// サンプリング時刻
+    pub timestamp: DateTime<Utc>,
+    /// サンプリング時点の待機数
+    pub waiting: i64,
+    /// 前回サンプルからの拒否数（キュー満杯・待機タイムアウト）
+    pub rejected: i64,
+}
+
+/// サンプルを1件保存
+pub async fn insert_sample(
+    pool: &SqlitePool,
+    point: &QueueHistoryPoint,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO queue_history (sampled_at, waiting, rejected) VALUES (?, ?, ?)")
+        .bind(point.timestamp.to_rfc3339())
+        .bind(point.waiting)
+        .bind(point.rejected)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// `since` 以降のサンプルを時刻昇順で取得
+pub async fn get_history(
+    pool: &SqlitePool,
+    since: DateTime<Utc>,
+) -> Result<Vec<QueueHistoryPoint>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, i64, i64)>(
+        "SELECT sampled_at, waiting, rejected FROM queue_history \
+         WHERE sampled_at >= ? ORDER BY sampled_at ASC",
+    )
+    .bind(since.to_rfc3339())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(sampled_at, waiting, rejected)| {
+            let timestamp = DateTime::parse_from_rfc3339(&sampled_at)
+                .ok()?
+                .with_timezone(&Utc);
+            Some(QueueHistoryPoint {
+                timestamp,
+                waiting,
+                rejected,
+            })
+        })
+        .collect())
+}
+
+/// `before` より古いサンプルを削除し、削除件数を返す
+pub async fn prune_before(pool: &SqlitePool, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM queue_history WHERE sampled_at < ?")
+        .bind(before.to_rfc3339())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// キュー時系列のサンプリングタスクを開始
+///
+/// `interval` ごとに待機数と前回からの拒否数を保存し、`retention` を過ぎたサンプルを削除する。
+pub fn start_queue_history_task(
+    pool: SqlitePool,
+    load_manager: LoadManager,
+    interval: Duration,
+    retention: Duration,
+) {
+    let retention = chrono::Duration::from_std(retention).unwrap_or(chrono::Duration::hours(24));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut last_rejections = load_manager.queue_rejections();
+        loop {
+            ticker.tick().await;
+
+            let rejections = load_manager.queue_rejections();
+            let now = Utc::now();
+            let point = QueueHistoryPoint {
+                timestamp: now,
+                waiting: load_manager.queue_waiters() as i64,
+                rejected: rejections.saturating_sub(last_rejections) as i64,
+            };
+            last_rejections = rejections;
+
+            if let Err(e) = insert_sample(&pool, &point).await {
+                tracing::warn!("Failed to save queue history sample: {}", e);
+                continue;
+            }
+            if let Err(e) = prune_before(&pool, now - retention).await {
+                tracing::warn!("Failed to prune queue history: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_insert_get_and_prune() {
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let now = Utc::now();
+
+        for (minutes_ago, waiting, rejected) in [(90, 1, 0), (30, 5, 2), (5, 3, 1)] {
+            insert_sample(
+                &pool,
+                &QueueHistoryPoint {
+                    timestamp: now - chrono::Duration::minutes(minutes_ago),
+                    waiting,
+                    rejected,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let last_hour = get_history(&pool, now - chrono::Duration::minutes(60))
+            .await
+            .unwrap();
+        assert_eq!(last_hour.len(), 2);
+        assert_eq!(last_hour[0].waiting, 5);
+        assert_eq!(last_hour[0].rejected, 2);
+        assert_eq!(last_hour[1].waiting, 3);
+
+        let pruned = prune_before(&pool, now - chrono::Duration::minutes(60))
+            .await
+            .unwrap();
+        assert_eq!(pruned, 1);
+        let all = get_history(&pool, now - chrono::Duration::days(1))
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+    }
+}
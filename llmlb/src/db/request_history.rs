@@ -22,6 +22,26 @@ const REQUEST_HISTORY_RETENTION_DAYS_ENV: &str = "LLMLB_REQUEST_HISTORY_RETENTIO
 const LEGACY_REQUEST_HISTORY_RETENTION_DAYS_ENV: &str = "REQUEST_HISTORY_RETENTION_DAYS";
 const REQUEST_HISTORY_CLEANUP_INTERVAL_ENV: &str = "LLMLB_REQUEST_HISTORY_CLEANUP_INTERVAL_SECS";
 const LEGACY_REQUEST_HISTORY_CLEANUP_INTERVAL_ENV: &str = "REQUEST_HISTORY_CLEANUP_INTERVAL_SECS";
+const REQUEST_HISTORY_MAX_ROWS_ENV: &str = "LLMLB_REQUEST_HISTORY_MAX_ROWS";
+const LEGACY_REQUEST_HISTORY_MAX_ROWS_ENV: &str = "REQUEST_HISTORY_MAX_ROWS";
+const REQUEST_HISTORY_MAX_SIZE_MB_ENV: &str = "LLMLB_REQUEST_HISTORY_MAX_SIZE_MB";
+const LEGACY_REQUEST_HISTORY_MAX_SIZE_MB_ENV: &str = "REQUEST_HISTORY_MAX_SIZE_MB";
+const REQUEST_HISTORY_CLEANUP_BATCH_SIZE_ENV: &str = "LLMLB_REQUEST_HISTORY_CLEANUP_BATCH_SIZE";
+const LEGACY_REQUEST_HISTORY_CLEANUP_BATCH_SIZE_ENV: &str = "REQUEST_HISTORY_CLEANUP_BATCH_SIZE";
+const REQUEST_HISTORY_VACUUM_INTERVAL_ENV: &str = "LLMLB_REQUEST_HISTORY_VACUUM_INTERVAL_SECS";
+const LEGACY_REQUEST_HISTORY_VACUUM_INTERVAL_ENV: &str = "REQUEST_HISTORY_VACUUM_INTERVAL_SECS";
+
+/// クリーンアップで1回のDELETEに含める既定の行数
+const DEFAULT_CLEANUP_BATCH_SIZE: u64 = 10_000;
+
+/// `query_history` の既定の取得件数
+pub const HISTORY_QUERY_DEFAULT_LIMIT: usize = 100;
+/// `query_history` の取得件数の上限
+pub const HISTORY_QUERY_MAX_LIMIT: usize = 1000;
+
+/// total_tokens が NULL の旧レコードは入出力トークンの和で扱う（式インデックスと一致させる）
+const EFFECTIVE_TOTAL_TOKENS_SQL: &str =
+    "COALESCE(total_tokens, COALESCE(input_tokens, 0) + COALESCE(output_tokens, 0))";
 
 /// リクエスト履歴ストレージ（SQLite版）
 #[derive(Clone)]
@@ -54,6 +74,7 @@ impl RequestHistoryStorage {
     /// レコードを保存
     pub async fn save_record(&self, record: &RequestResponseRecord) -> RouterResult<()> {
         self.insert_record(record, false).await?;
+        crate::db::io_samples::maybe_save_sample(&self.pool, record).await;
         Ok(())
     }
 
@@ -125,6 +146,8 @@ impl RequestHistoryStorage {
         let total_tokens = record.total_tokens.map(|v| v as i64);
 
         let api_key_id = record.api_key_id.map(|id| id.to_string());
+        let cost_usd = record.cost_usd;
+        let requested_model = record.requested_model.as_deref();
 
         let insert_sql = if ignore_conflicts {
             r#"
@@ -132,8 +155,8 @@ impl RequestHistoryStorage {
                 id, timestamp, request_type, model, endpoint_id, endpoint_name,
                 endpoint_ip, client_ip, request_body, response_body, duration_ms,
                 status, error_message, completed_at, input_tokens, output_tokens, total_tokens,
-                api_key_id
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                api_key_id, cost_usd, requested_model
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#
         } else {
             r#"
@@ -141,8 +164,8 @@ impl RequestHistoryStorage {
                 id, timestamp, request_type, model, endpoint_id, endpoint_name,
                 endpoint_ip, client_ip, request_body, response_body, duration_ms,
                 status, error_message, completed_at, input_tokens, output_tokens, total_tokens,
-                api_key_id
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                api_key_id, cost_usd, requested_model
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#
         };
 
@@ -165,6 +188,8 @@ impl RequestHistoryStorage {
             .bind(output_tokens)
             .bind(total_tokens)
             .bind(&api_key_id)
+            .bind(cost_usd)
+            .bind(requested_model)
             .execute(&self.pool)
             .await
             .map_err(|e| LbError::Database(format!("Failed to save record: {}", e)))?;
@@ -186,15 +211,118 @@ impl RequestHistoryStorage {
 
     /// 指定期間より古いレコードを削除
     pub async fn cleanup_old_records(&self, max_age: Duration) -> RouterResult<()> {
-        let cutoff = (Utc::now() - max_age).to_rfc3339();
+        self.delete_older_than(max_age, DEFAULT_CLEANUP_BATCH_SIZE)
+            .await
+            .map(|_| ())
+    }
+
+    /// 保持設定（期間・行数・DB使用サイズ）に従って古いレコードから削除し、削除件数を返す
+    ///
+    /// 期間で削除した後に行数・DB使用サイズの上限を超える分を削除するため、最も厳しい条件が適用される。
+    pub async fn cleanup_with_policy(&self, policy: &RetentionPolicy) -> RouterResult<u64> {
+        let mut deleted = 0;
+        if let Some(max_age) = policy.max_age {
+            deleted += self.delete_older_than(max_age, policy.batch_size).await?;
+        }
+        if policy.max_rows.is_none() && policy.max_bytes.is_none() {
+            return Ok(deleted);
+        }
+
+        let (rows, bytes) = self.estimated_size().await?;
+        let mut keep_rows = policy.max_rows.unwrap_or(u64::MAX);
+        if let Some(max_bytes) = policy.max_bytes.filter(|max| bytes > *max) {
+            // 平均行サイズから上限に収まる行数を見積もる
+            let fitting = (rows as u128 * max_bytes as u128 / bytes as u128) as u64;
+            keep_rows = keep_rows.min(fitting);
+        }
+        if rows > keep_rows {
+            deleted += self
+                .delete_oldest(rows - keep_rows, policy.batch_size)
+                .await?;
+        }
+        Ok(deleted)
+    }
+
+    /// 履歴の行数とDBの使用サイズ（バイト）を返す
+    ///
+    /// サイズは `page_count * page_size` から空きページ（`freelist_count`）を除いた値。
+    /// 削除後に VACUUM されるまでファイルは縮まないため、空きページは数えない。
+    pub async fn estimated_size(&self) -> RouterResult<(u64, u64)> {
+        let (rows, bytes): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM request_history),
+                ((SELECT page_count FROM pragma_page_count())
+                    - (SELECT freelist_count FROM pragma_freelist_count()))
+                    * (SELECT page_size FROM pragma_page_size())
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to estimate history size: {}", e)))?;
+        Ok((rows.max(0) as u64, bytes.max(0) as u64))
+    }
 
-        sqlx::query("DELETE FROM request_history WHERE timestamp < ?")
+    /// VACUUM でDBファイルの空き領域を解放する
+    pub async fn vacuum(&self) -> RouterResult<()> {
+        sqlx::query("VACUUM")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| LbError::Database(format!("Failed to vacuum database: {}", e)))?;
+        Ok(())
+    }
+
+    /// 指定期間より古いレコードをバッチで削除する
+    async fn delete_older_than(&self, max_age: Duration, batch_size: u64) -> RouterResult<u64> {
+        let cutoff = (Utc::now() - max_age).to_rfc3339();
+        let mut deleted = 0;
+        loop {
+            let affected = sqlx::query(
+                r#"
+                DELETE FROM request_history WHERE id IN (
+                    SELECT id FROM request_history WHERE timestamp < ? ORDER BY timestamp LIMIT ?
+                )
+                "#,
+            )
             .bind(&cutoff)
+            .bind(batch_size as i64)
             .execute(&self.pool)
             .await
-            .map_err(|e| LbError::Database(format!("Failed to cleanup records: {}", e)))?;
+            .map_err(|e| LbError::Database(format!("Failed to cleanup records: {}", e)))?
+            .rows_affected();
+            deleted += affected;
+            if affected < batch_size {
+                return Ok(deleted);
+            }
+            // 他の書き込みを長時間ブロックしないようバッチ間で譲る
+            tokio::task::yield_now().await;
+        }
+    }
 
-        Ok(())
+    /// 古い順に指定件数をバッチで削除する
+    async fn delete_oldest(&self, count: u64, batch_size: u64) -> RouterResult<u64> {
+        let mut deleted = 0;
+        while deleted < count {
+            let limit = (count - deleted).min(batch_size);
+            let affected = sqlx::query(
+                r#"
+                DELETE FROM request_history WHERE id IN (
+                    SELECT id FROM request_history ORDER BY timestamp LIMIT ?
+                )
+                "#,
+            )
+            .bind(limit as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| LbError::Database(format!("Failed to cleanup records: {}", e)))?
+            .rows_affected();
+            if affected == 0 {
+                break;
+            }
+            deleted += affected;
+            tokio::task::yield_now().await;
+        }
+        Ok(deleted)
     }
 
     /// 直近N分のリクエスト履歴を分単位で集計して返す（起動時seeding用）
@@ -224,6 +352,58 @@ impl RequestHistoryStorage {
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
 
+    /// レイテンシ・トークン数等の条件でレコードを検索（新しい順）
+    ///
+    /// 指定された条件はANDで結合する。条件が空の場合は全件を対象とし、
+    /// 取得件数は `limit`（既定 [`HISTORY_QUERY_DEFAULT_LIMIT`]、上限
+    /// [`HISTORY_QUERY_MAX_LIMIT`]）で打ち切る。
+    pub async fn query_history(
+        &self,
+        filter: &HistoryFilter,
+    ) -> RouterResult<Vec<RequestResponseRecord>> {
+        let mut query =
+            sqlx::QueryBuilder::<sqlx::Sqlite>::new("SELECT * FROM request_history WHERE 1 = 1");
+        if let Some(min_latency_ms) = filter.min_latency_ms {
+            query
+                .push(" AND duration_ms >= ")
+                .push_bind(min_latency_ms as i64);
+        }
+        if let Some(max_latency_ms) = filter.max_latency_ms {
+            query
+                .push(" AND duration_ms <= ")
+                .push_bind(max_latency_ms as i64);
+        }
+        if let Some(ref model_id) = filter.model_id {
+            query.push(" AND model = ").push_bind(model_id.clone());
+        }
+        if let Some(status) = filter.status {
+            query.push(" AND status = ").push_bind(match status {
+                FilterStatus::Success => "success",
+                FilterStatus::Error => "error",
+            });
+        }
+        if let Some(min_total_tokens) = filter.min_total_tokens {
+            query
+                .push(" AND ")
+                .push(EFFECTIVE_TOTAL_TOKENS_SQL)
+                .push(" >= ")
+                .push_bind(min_total_tokens as i64);
+        }
+        query
+            .push(" ORDER BY timestamp DESC LIMIT ")
+            .push_bind(filter.effective_limit() as i64)
+            .push(" OFFSET ")
+            .push_bind(filter.offset as i64);
+
+        let rows = query
+            .build_query_as::<RequestHistoryRow>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| LbError::Database(format!("Failed to query history: {}", e)))?;
+
+        rows.into_iter().map(|row| row.try_into()).collect()
+    }
+
     /// レコードをフィルタリング＆ページネーション
     pub async fn filter_and_paginate(
         &self,
@@ -470,7 +650,8 @@ impl RequestHistoryStorage {
                         )
                     ),
                     0
-                ) as total_tokens
+                ) as total_tokens,
+                COALESCE(SUM(cost_usd), 0.0) as total_cost_usd
             FROM request_history
             "#,
         )
@@ -482,6 +663,7 @@ impl RequestHistoryStorage {
             total_input_tokens: row.total_input_tokens as u64,
             total_output_tokens: row.total_output_tokens as u64,
             total_tokens: row.total_tokens as u64,
+            total_cost_usd: row.total_cost_usd,
         })
     }
 
@@ -638,6 +820,7 @@ impl RequestHistoryStorage {
                     ),
                     0
                 ) as total_tokens,
+                COALESCE(SUM(cost_usd), 0.0) as total_cost_usd,
                 COUNT(*) as request_count
             FROM request_history
             WHERE timestamp >= DATE('now', '-' || ? || ' months')
@@ -657,6 +840,91 @@ impl RequestHistoryStorage {
                 total_input_tokens: row.total_input_tokens as u64,
                 total_output_tokens: row.total_output_tokens as u64,
                 total_tokens: row.total_tokens as u64,
+                total_cost_usd: row.total_cost_usd,
+                request_count: row.request_count as u64,
+            })
+            .collect())
+    }
+
+    /// エンドポイントごとに直近よく使われたモデルを取得（起動時ウォームアップ用）
+    ///
+    /// `since` 以降の成功リクエストを数え、エンドポイントごとに件数の多い順で最大
+    /// `per_endpoint` 件を返す。
+    pub async fn get_top_models_by_endpoint(
+        &self,
+        since: DateTime<Utc>,
+        per_endpoint: usize,
+    ) -> RouterResult<Vec<(Uuid, String)>> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT endpoint_id, model FROM (
+                SELECT
+                    endpoint_id,
+                    model,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY endpoint_id ORDER BY COUNT(*) DESC, MAX(timestamp) DESC
+                    ) as rank
+                FROM request_history
+                WHERE status = 'success' AND timestamp >= ?
+                GROUP BY endpoint_id, model
+            )
+            WHERE rank <= ?
+            ORDER BY endpoint_id, rank
+            "#,
+        )
+        .bind(since.to_rfc3339())
+        .bind(per_endpoint as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to get top models: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(endpoint_id, model)| {
+                Uuid::parse_str(&endpoint_id)
+                    .ok()
+                    .map(|endpoint_id| (endpoint_id, model))
+            })
+            .collect())
+    }
+
+    /// クラウドプロバイダ呼び出しのトークン使用量を日次・モデル別に取得
+    ///
+    /// `endpoint_name` が `cloud:<provider>` の記録が対象。期間は `from` 以上 `to` 以下。
+    pub async fn get_cloud_daily_usage(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> RouterResult<Vec<CloudDailyUsage>> {
+        let rows = sqlx::query_as::<_, CloudDailyUsageRow>(
+            r#"
+            SELECT
+                DATE(timestamp) as date,
+                SUBSTR(endpoint_name, 7) as provider,
+                model,
+                COALESCE(SUM(input_tokens), 0) as total_input_tokens,
+                COALESCE(SUM(output_tokens), 0) as total_output_tokens,
+                COUNT(*) as request_count
+            FROM request_history
+            WHERE endpoint_name LIKE 'cloud:%' AND timestamp >= ? AND timestamp <= ?
+            GROUP BY DATE(timestamp), endpoint_name, model
+            ORDER BY date ASC, provider ASC, model ASC
+            "#,
+        )
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to get cloud usage: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| CloudDailyUsage {
+                date: row.date,
+                provider: row.provider,
+                model: row.model,
+                input_tokens: row.total_input_tokens as u64,
+                output_tokens: row.total_output_tokens as u64,
                 request_count: row.request_count as u64,
             })
             .collect())
@@ -735,6 +1003,8 @@ struct RequestHistoryRow {
     output_tokens: Option<i64>,
     total_tokens: Option<i64>,
     api_key_id: Option<String>,
+    cost_usd: Option<f64>,
+    requested_model: Option<String>,
 }
 
 impl TryFrom<RequestHistoryRow> for RequestResponseRecord {
@@ -822,6 +1092,8 @@ impl TryFrom<RequestHistoryRow> for RequestResponseRecord {
                         .map_err(|e| LbError::Database(format!("Invalid api_key_id UUID: {}", e)))
                 })
                 .transpose()?,
+            cost_usd: row.cost_usd,
+            requested_model: row.requested_model,
         })
     }
 }
@@ -918,6 +1190,35 @@ impl RecordFilter {
     }
 }
 
+/// `query_history` の検索条件（指定した条件はANDで結合）
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    /// 処理時間の下限（ミリ秒、以上）
+    pub min_latency_ms: Option<u64>,
+    /// 処理時間の上限（ミリ秒、以下）
+    pub max_latency_ms: Option<u64>,
+    /// モデルID（完全一致）
+    pub model_id: Option<String>,
+    /// ステータス
+    pub status: Option<FilterStatus>,
+    /// 合計トークン数の下限（以上）
+    pub min_total_tokens: Option<u64>,
+    /// 取得件数（未指定は既定値、上限で切り詰め）
+    pub limit: Option<usize>,
+    /// 読み飛ばす件数
+    pub offset: usize,
+}
+
+impl HistoryFilter {
+    /// 実際に適用する取得件数
+    pub fn effective_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(HISTORY_QUERY_DEFAULT_LIMIT)
+            .min(HISTORY_QUERY_MAX_LIMIT)
+    }
+}
+
 /// フィルタ用のステータス
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -950,6 +1251,9 @@ pub struct TokenStatistics {
     pub total_output_tokens: u64,
     /// 総トークン合計
     pub total_tokens: u64,
+    /// 課金額合計（USD）
+    #[serde(default)]
+    pub total_cost_usd: f64,
 }
 
 /// トークン統計（モデル別）
@@ -990,6 +1294,7 @@ struct TokenStatisticsRow {
     total_input_tokens: i64,
     total_output_tokens: i64,
     total_tokens: i64,
+    total_cost_usd: f64,
 }
 
 /// SQLiteから取得したトークン統計行（モデル別）
@@ -1030,6 +1335,35 @@ struct MonthlyTokenStatisticsRow {
     total_input_tokens: i64,
     total_output_tokens: i64,
     total_tokens: i64,
+    total_cost_usd: f64,
+    request_count: i64,
+}
+
+/// クラウドプロバイダ呼び出しの日次・モデル別トークン使用量
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloudDailyUsage {
+    /// 日付（YYYY-MM-DD、UTC）
+    pub date: String,
+    /// プロバイダ名（`cloud:` を除いたエンドポイント名）
+    pub provider: String,
+    /// モデル名
+    pub model: String,
+    /// 入力トークン合計
+    pub input_tokens: u64,
+    /// 出力トークン合計
+    pub output_tokens: u64,
+    /// リクエスト数
+    pub request_count: u64,
+}
+
+/// SQLiteから取得したクラウド使用量行（日次・モデル別）
+#[derive(sqlx::FromRow)]
+struct CloudDailyUsageRow {
+    date: String,
+    provider: String,
+    model: String,
+    total_input_tokens: i64,
+    total_output_tokens: i64,
     request_count: i64,
 }
 
@@ -1724,40 +2058,114 @@ struct ModelDistRow {
     request_count: i64,
 }
 
+/// リクエスト履歴の保持設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionPolicy {
+    /// 保持期間（`None` で無制限）
+    pub max_age: Option<Duration>,
+    /// 最大行数（`None` で無制限）
+    pub max_rows: Option<u64>,
+    /// DB使用サイズ（`page_count * page_size`）の上限（バイト、`None` で無制限）
+    pub max_bytes: Option<u64>,
+    /// 1回のDELETEで削除する行数
+    pub batch_size: u64,
+    /// VACUUM を実行する最短間隔（`None` で実行しない）
+    pub vacuum_interval: Option<std::time::Duration>,
+}
+
+impl RetentionPolicy {
+    /// 環境変数から読み込む
+    ///
+    /// - `LLMLB_REQUEST_HISTORY_RETENTION_DAYS`（既定: 7、0以下で期間による削除なし）
+    /// - `LLMLB_REQUEST_HISTORY_MAX_ROWS`（既定: 0=無制限）
+    /// - `LLMLB_REQUEST_HISTORY_MAX_SIZE_MB`（既定: 0=無制限）
+    /// - `LLMLB_REQUEST_HISTORY_CLEANUP_BATCH_SIZE`（既定: 10000）
+    /// - `LLMLB_REQUEST_HISTORY_VACUUM_INTERVAL_SECS`（既定: 0=VACUUMしない）
+    pub fn from_env() -> Self {
+        let retention_days = get_env_with_fallback_parse(
+            REQUEST_HISTORY_RETENTION_DAYS_ENV,
+            LEGACY_REQUEST_HISTORY_RETENTION_DAYS_ENV,
+            7i64,
+        );
+        let max_rows = get_env_with_fallback_parse(
+            REQUEST_HISTORY_MAX_ROWS_ENV,
+            LEGACY_REQUEST_HISTORY_MAX_ROWS_ENV,
+            0u64,
+        );
+        let max_size_mb = get_env_with_fallback_parse(
+            REQUEST_HISTORY_MAX_SIZE_MB_ENV,
+            LEGACY_REQUEST_HISTORY_MAX_SIZE_MB_ENV,
+            0u64,
+        );
+        let batch_size = get_env_with_fallback_parse(
+            REQUEST_HISTORY_CLEANUP_BATCH_SIZE_ENV,
+            LEGACY_REQUEST_HISTORY_CLEANUP_BATCH_SIZE_ENV,
+            DEFAULT_CLEANUP_BATCH_SIZE,
+        );
+        let vacuum_interval_secs = get_env_with_fallback_parse(
+            REQUEST_HISTORY_VACUUM_INTERVAL_ENV,
+            LEGACY_REQUEST_HISTORY_VACUUM_INTERVAL_ENV,
+            0u64,
+        );
+        Self {
+            max_age: (retention_days > 0).then(|| Duration::days(retention_days)),
+            max_rows: (max_rows > 0).then_some(max_rows),
+            max_bytes: (max_size_mb > 0).then(|| max_size_mb.saturating_mul(1024 * 1024)),
+            batch_size: batch_size.max(1),
+            vacuum_interval: (vacuum_interval_secs > 0)
+                .then(|| std::time::Duration::from_secs(vacuum_interval_secs)),
+        }
+    }
+
+    /// いずれかの削除条件が設定されているか
+    pub fn is_enabled(&self) -> bool {
+        self.max_age.is_some() || self.max_rows.is_some() || self.max_bytes.is_some()
+    }
+}
+
 /// 定期クリーンアップタスクを開始
 pub fn start_cleanup_task(storage: Arc<RequestHistoryStorage>) {
-    let retention_days = get_env_with_fallback_parse(
-        REQUEST_HISTORY_RETENTION_DAYS_ENV,
-        LEGACY_REQUEST_HISTORY_RETENTION_DAYS_ENV,
-        7i64,
-    );
+    let policy = RetentionPolicy::from_env();
     let interval_secs = get_env_with_fallback_parse(
         REQUEST_HISTORY_CLEANUP_INTERVAL_ENV,
         LEGACY_REQUEST_HISTORY_CLEANUP_INTERVAL_ENV,
         3600u64,
     );
 
-    if retention_days <= 0 {
-        tracing::info!("Request history cleanup disabled ({} <= 0)", retention_days);
+    if !policy.is_enabled() {
+        tracing::info!("Request history cleanup disabled (no retention days, row or size limit)");
         return;
     }
 
     tokio::spawn(async move {
-        // 起動時に1回実行
-        let retention = Duration::days(retention_days);
-        if let Err(e) = storage.cleanup_old_records(retention).await {
-            tracing::error!("Initial cleanup failed: {}", e);
-        }
+        // 起動直後の VACUUM は避け、設定間隔の経過後に初めて実行する
+        let mut last_vacuum = std::time::Instant::now();
 
-        // 1時間ごとに実行
+        // 1時間ごとに実行（初回の tick は即時なので起動時にも1回実行される）
         let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
         loop {
             interval.tick().await;
 
-            if let Err(e) = storage.cleanup_old_records(retention).await {
-                tracing::error!("Periodic cleanup failed: {}", e);
-            } else {
-                tracing::info!("Periodic cleanup completed");
+            let deleted = match storage.cleanup_with_policy(&policy).await {
+                Ok(deleted) => {
+                    tracing::info!(deleted, "Request history cleanup completed");
+                    deleted
+                }
+                Err(e) => {
+                    tracing::error!("Request history cleanup failed: {}", e);
+                    continue;
+                }
+            };
+
+            let Some(vacuum_interval) = policy.vacuum_interval else {
+                continue;
+            };
+            if deleted > 0 && last_vacuum.elapsed() >= vacuum_interval {
+                last_vacuum = std::time::Instant::now();
+                match storage.vacuum().await {
+                    Ok(()) => tracing::info!("Vacuumed database after request history cleanup"),
+                    Err(e) => tracing::warn!("Database vacuum failed: {}", e),
+                }
             }
         }
     });
@@ -1793,6 +2201,8 @@ mod tests {
             output_tokens: None,
             total_tokens: None,
             api_key_id: None,
+            cost_usd: None,
+            requested_model: None,
         }
     }
 
@@ -1809,6 +2219,86 @@ mod tests {
         assert_eq!(loaded[0].id, record.id);
     }
 
+    #[tokio::test]
+    async fn test_get_top_models_by_endpoint() {
+        let pool = create_test_pool().await;
+        let storage = RequestHistoryStorage::new(pool);
+        let now = Utc::now();
+        let endpoint_id = Uuid::new_v4();
+
+        for (model, count) in [("a", 1), ("b", 3), ("c", 2)] {
+            for _ in 0..count {
+                let mut record = create_test_record(now);
+                record.endpoint_id = endpoint_id;
+                record.model = model.to_string();
+                storage.save_record(&record).await.unwrap();
+            }
+        }
+        // 失敗したリクエストと期間外のリクエストは数えない
+        for timestamp in [now, now - Duration::days(10)] {
+            for _ in 0..5 {
+                let mut record = create_test_record(timestamp);
+                record.endpoint_id = endpoint_id;
+                record.model = "d".to_string();
+                if timestamp == now {
+                    record.status = RecordStatus::Error {
+                        message: "HTTP 500".to_string(),
+                    };
+                }
+                storage.save_record(&record).await.unwrap();
+            }
+        }
+
+        let top = storage
+            .get_top_models_by_endpoint(now - Duration::days(7), 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            top,
+            vec![
+                (endpoint_id, "b".to_string()),
+                (endpoint_id, "c".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_cloud_daily_usage() {
+        let pool = create_test_pool().await;
+        let storage = RequestHistoryStorage::new(pool);
+        let day = DateTime::parse_from_rfc3339("2026-10-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        for (offset, endpoint_name, tokens) in [
+            (0, "cloud:openai", 100),
+            (1, "cloud:openai", 200),
+            (24, "cloud:openai", 300),
+            (0, "test-node", 400),
+            (72, "cloud:openai", 500),
+        ] {
+            let mut record = create_test_record(day + Duration::hours(offset));
+            record.endpoint_name = endpoint_name.to_string();
+            record.model = "openai:gpt-4o".to_string();
+            record.input_tokens = Some(tokens);
+            record.output_tokens = Some(tokens / 10);
+            storage.save_record(&record).await.unwrap();
+        }
+
+        let usage = storage
+            .get_cloud_daily_usage(day - Duration::hours(1), day + Duration::hours(48))
+            .await
+            .unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].date, "2026-10-01");
+        assert_eq!(usage[0].provider, "openai");
+        assert_eq!(usage[0].input_tokens, 300);
+        assert_eq!(usage[0].output_tokens, 30);
+        assert_eq!(usage[0].request_count, 2);
+        assert_eq!(usage[1].date, "2026-10-02");
+        assert_eq!(usage[1].input_tokens, 300);
+    }
+
     #[tokio::test]
     async fn test_cleanup_old_records() {
         let pool = create_test_pool().await;
@@ -1913,6 +2403,19 @@ mod tests {
     }
 
     // T-6: request_historyテーブルへのトークン保存テスト
+    #[tokio::test]
+    async fn test_save_and_load_requested_model() {
+        let pool = create_test_pool().await;
+        let storage = RequestHistoryStorage::new(pool);
+
+        let mut record = create_test_record(Utc::now());
+        record.requested_model = Some("llama3.1:8b".to_string());
+        storage.save_record(&record).await.unwrap();
+
+        let loaded = storage.load_records().await.unwrap();
+        assert_eq!(loaded[0].requested_model.as_deref(), Some("llama3.1:8b"));
+    }
+
     #[tokio::test]
     async fn test_save_and_load_record_with_tokens() {
         let pool = create_test_pool().await;
@@ -2155,6 +2658,29 @@ mod tests {
         assert_eq!(stats[0].request_count, 1);
     }
 
+    #[tokio::test]
+    async fn test_cost_is_saved_and_summarized() {
+        let pool = create_test_pool().await;
+        let storage = RequestHistoryStorage::new(pool);
+
+        let mut billed = create_test_record(Utc::now());
+        billed.cost_usd = Some(0.25);
+        storage.save_record(&billed).await.unwrap();
+        let mut free = create_test_record(Utc::now());
+        free.id = Uuid::new_v4();
+        storage.save_record(&free).await.unwrap();
+
+        let loaded = storage.get_record_by_id(billed.id).await.unwrap().unwrap();
+        assert_eq!(loaded.cost_usd, Some(0.25));
+
+        let stats = storage.get_token_statistics().await.unwrap();
+        assert!((stats.total_cost_usd - 0.25).abs() < 1e-9);
+        let monthly = storage.get_monthly_token_statistics(12).await.unwrap();
+        assert_eq!(monthly.len(), 1);
+        assert!((monthly[0].total_cost_usd - 0.25).abs() < 1e-9);
+        assert_eq!(monthly[0].request_count, 2);
+    }
+
     /// T012 [US5]: get_recent_history_by_minute が分単位でリクエスト履歴を
     /// 正しく集計できることを検証
     #[tokio::test]
@@ -2348,6 +2874,92 @@ mod tests {
         assert_eq!(result.total_count, 2);
     }
 
+    #[tokio::test]
+    async fn test_query_history_filters_by_latency_tokens_and_status() {
+        let pool = create_test_pool().await;
+        let storage = RequestHistoryStorage::new(pool);
+        let now = Utc::now();
+
+        let mut slow = create_test_record(now - Duration::seconds(3));
+        slow.duration_ms = 7000;
+        slow.total_tokens = Some(900);
+        storage.save_record(&slow).await.unwrap();
+
+        // total_tokens 未記録の旧レコードは入出力の和で判定する
+        let mut slow_legacy = create_test_record(now - Duration::seconds(2));
+        slow_legacy.duration_ms = 5000;
+        slow_legacy.input_tokens = Some(300);
+        slow_legacy.output_tokens = Some(400);
+        storage.save_record(&slow_legacy).await.unwrap();
+
+        let mut slow_error = create_test_record(now - Duration::seconds(1));
+        slow_error.duration_ms = 6000;
+        slow_error.model = "other-model".to_string();
+        slow_error.status = RecordStatus::Error {
+            message: "timeout".to_string(),
+        };
+        storage.save_record(&slow_error).await.unwrap();
+
+        let fast = create_test_record(now);
+        storage.save_record(&fast).await.unwrap();
+
+        let ids = |records: Vec<RequestResponseRecord>| -> Vec<Uuid> {
+            records.into_iter().map(|r| r.id).collect()
+        };
+
+        let filter = HistoryFilter {
+            min_latency_ms: Some(5000),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(storage.query_history(&filter).await.unwrap()),
+            vec![slow_error.id, slow_legacy.id, slow.id]
+        );
+
+        let filter = HistoryFilter {
+            min_latency_ms: Some(5000),
+            max_latency_ms: Some(6500),
+            model_id: Some("test-model".to_string()),
+            status: Some(FilterStatus::Success),
+            min_total_tokens: Some(700),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(storage.query_history(&filter).await.unwrap()),
+            vec![slow_legacy.id]
+        );
+
+        let filter = HistoryFilter {
+            status: Some(FilterStatus::Error),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(storage.query_history(&filter).await.unwrap()),
+            vec![slow_error.id]
+        );
+
+        // 空のフィルタは全件（ページネーション付き）
+        let all = storage
+            .query_history(&HistoryFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 4);
+        let filter = HistoryFilter {
+            limit: Some(2),
+            offset: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(storage.query_history(&filter).await.unwrap()),
+            vec![slow_error.id, slow_legacy.id]
+        );
+        let filter = HistoryFilter {
+            limit: Some(usize::MAX),
+            ..Default::default()
+        };
+        assert_eq!(filter.effective_limit(), HISTORY_QUERY_MAX_LIMIT);
+    }
+
     #[tokio::test]
     async fn test_cleanup_does_not_remove_recent() {
         let pool = create_test_pool().await;
@@ -2366,6 +2978,50 @@ mod tests {
         assert_eq!(loaded.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_cleanup_with_policy_removes_oldest_over_row_and_size_limits() {
+        let pool = create_test_pool().await;
+        let storage = RequestHistoryStorage::new(pool);
+
+        let now = Utc::now();
+        let mut records = Vec::new();
+        for minutes_ago in (0..10).rev() {
+            let record = create_test_record(now - Duration::minutes(minutes_ago));
+            storage.save_record(&record).await.unwrap();
+            records.push(record);
+        }
+
+        // 行数上限: 古い方から削除し、バッチサイズより多くても全件処理する
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::days(7)),
+            max_rows: Some(6),
+            max_bytes: None,
+            batch_size: 3,
+            vacuum_interval: None,
+        };
+        assert_eq!(storage.cleanup_with_policy(&policy).await.unwrap(), 4);
+        let (rows, bytes) = storage.estimated_size().await.unwrap();
+        assert_eq!(rows, 6);
+        let remaining: Vec<Uuid> = storage
+            .load_records()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        assert!(!remaining.contains(&records[3].id));
+        assert!(remaining.contains(&records[4].id));
+
+        // 推定サイズ上限: 行数上限より厳しい方が適用される
+        let policy = RetentionPolicy {
+            max_bytes: Some(bytes / 2),
+            ..policy
+        };
+        assert_eq!(storage.cleanup_with_policy(&policy).await.unwrap(), 3);
+        assert_eq!(storage.estimated_size().await.unwrap().0, 3);
+        storage.vacuum().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_token_statistics_empty_db() {
         let pool = create_test_pool().await;
@@ -2642,6 +3298,7 @@ mod tests {
             total_input_tokens: 100,
             total_output_tokens: 50,
             total_tokens: 150,
+            total_cost_usd: 0.0,
         };
         let json = serde_json::to_value(&stats).unwrap();
         assert_eq!(json["total_input_tokens"], 100);
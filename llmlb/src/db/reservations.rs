@@ -0,0 +1,213 @@
+//! エンドポイント容量予約のストレージ層
+//!
+//! APIキー/テナント単位の容量予約をSQLiteに永続化する。
+
+use crate::balancer::{CapacityReservation, PrincipalType};
+use crate::common::error::{LbError, RouterResult};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow)]
+struct ReservationRow {
+    id: String,
+    endpoint_id: String,
+    principal_type: String,
+    principal_id: String,
+    slots: i64,
+    soft: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+fn parse_timestamp(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+impl From<ReservationRow> for CapacityReservation {
+    fn from(row: ReservationRow) -> Self {
+        CapacityReservation {
+            id: Uuid::parse_str(&row.id).unwrap_or_default(),
+            endpoint_id: Uuid::parse_str(&row.endpoint_id).unwrap_or_default(),
+            principal_type: row.principal_type.parse().unwrap_or(PrincipalType::ApiKey),
+            principal_id: Uuid::parse_str(&row.principal_id).unwrap_or_default(),
+            slots: row.slots.max(0) as u32,
+            soft: row.soft != 0,
+            created_at: parse_timestamp(&row.created_at),
+            updated_at: parse_timestamp(&row.updated_at),
+        }
+    }
+}
+
+/// 容量予約一覧を取得
+pub async fn list(pool: &SqlitePool) -> RouterResult<Vec<CapacityReservation>> {
+    let rows = sqlx::query_as::<_, ReservationRow>(
+        r#"
+        SELECT id, endpoint_id, principal_type, principal_id, slots, soft,
+               created_at, updated_at
+        FROM capacity_reservations
+        ORDER BY created_at ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to list reservations: {}", e)))?;
+
+    Ok(rows.into_iter().map(CapacityReservation::from).collect())
+}
+
+/// IDで容量予約を取得
+pub async fn get(pool: &SqlitePool, id: Uuid) -> RouterResult<Option<CapacityReservation>> {
+    let row = sqlx::query_as::<_, ReservationRow>(
+        r#"
+        SELECT id, endpoint_id, principal_type, principal_id, slots, soft,
+               created_at, updated_at
+        FROM capacity_reservations
+        WHERE id = ?
+        "#,
+    )
+    .bind(id.to_string())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to get reservation: {}", e)))?;
+
+    Ok(row.map(CapacityReservation::from))
+}
+
+/// 容量予約を作成
+pub async fn create(pool: &SqlitePool, reservation: &CapacityReservation) -> RouterResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO capacity_reservations (
+            id, endpoint_id, principal_type, principal_id, slots, soft,
+            created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(reservation.id.to_string())
+    .bind(reservation.endpoint_id.to_string())
+    .bind(reservation.principal_type.as_str())
+    .bind(reservation.principal_id.to_string())
+    .bind(reservation.slots as i64)
+    .bind(reservation.soft as i64)
+    .bind(reservation.created_at.to_rfc3339())
+    .bind(reservation.updated_at.to_rfc3339())
+    .execute(pool)
+    .await
+    .map_err(map_write_error)?;
+
+    Ok(())
+}
+
+/// 容量予約を更新（スロット数・soft フラグ）
+pub async fn update(pool: &SqlitePool, reservation: &CapacityReservation) -> RouterResult<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE capacity_reservations SET
+            slots = ?, soft = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(reservation.slots as i64)
+    .bind(reservation.soft as i64)
+    .bind(reservation.updated_at.to_rfc3339())
+    .bind(reservation.id.to_string())
+    .execute(pool)
+    .await
+    .map_err(map_write_error)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// 容量予約を削除
+pub async fn delete(pool: &SqlitePool, id: Uuid) -> RouterResult<bool> {
+    let result = sqlx::query("DELETE FROM capacity_reservations WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to delete reservation: {}", e)))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+fn map_write_error(e: sqlx::Error) -> LbError {
+    if e.to_string().contains("UNIQUE constraint failed") {
+        LbError::Conflict("Reservation for this principal and endpoint already exists".to_string())
+    } else {
+        LbError::Database(format!("Failed to save reservation: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::TEST_LOCK;
+    use crate::types::endpoint::{Endpoint, EndpointType};
+
+    async fn insert_endpoint(pool: &SqlitePool) -> Uuid {
+        let endpoint = Endpoint::new(
+            "reservation-endpoint".to_string(),
+            "http://localhost:11434".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        crate::db::endpoints::create_endpoint(pool, &endpoint)
+            .await
+            .unwrap();
+        endpoint.id
+    }
+
+    fn sample_reservation(endpoint_id: Uuid, principal_id: Uuid) -> CapacityReservation {
+        let now = Utc::now();
+        CapacityReservation {
+            id: Uuid::new_v4(),
+            endpoint_id,
+            principal_type: PrincipalType::Tenant,
+            principal_id,
+            slots: 2,
+            soft: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn reservation_crud_roundtrip() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let endpoint_id = insert_endpoint(&pool).await;
+
+        let reservation = sample_reservation(endpoint_id, Uuid::new_v4());
+        create(&pool, &reservation).await.unwrap();
+        assert_eq!(list(&pool).await.unwrap(), vec![reservation.clone()]);
+
+        let mut changed = reservation.clone();
+        changed.slots = 3;
+        changed.soft = false;
+        assert!(update(&pool, &changed).await.unwrap());
+        let fetched = get(&pool, reservation.id).await.unwrap().unwrap();
+        assert_eq!(fetched.slots, 3);
+        assert!(!fetched.soft);
+        assert_eq!(fetched.principal_type, PrincipalType::Tenant);
+
+        assert!(delete(&pool, reservation.id).await.unwrap());
+        assert!(get(&pool, reservation.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn duplicate_principal_on_endpoint_is_conflict() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let endpoint_id = insert_endpoint(&pool).await;
+        let principal_id = Uuid::new_v4();
+
+        create(&pool, &sample_reservation(endpoint_id, principal_id))
+            .await
+            .unwrap();
+        let err = create(&pool, &sample_reservation(endpoint_id, principal_id))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LbError::Conflict(_)));
+    }
+}
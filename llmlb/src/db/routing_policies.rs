@@ -0,0 +1,227 @@
+//! ルーティングポリシーのストレージ層
+//!
+//! ラベルベースのルーティングポリシーをSQLiteに永続化する。
+
+use crate::balancer::{NoMatchBehavior, RoutingPolicy};
+use crate::common::error::{LbError, RouterResult};
+use crate::common::protocol::TpsApiKind;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow)]
+struct RoutingPolicyRow {
+    id: String,
+    name: String,
+    model_pattern: String,
+    api_kind: Option<String>,
+    required_labels: String,
+    on_no_match: String,
+    priority: i64,
+    enabled: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+fn parse_timestamp(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+fn api_kind_to_str(api_kind: Option<TpsApiKind>) -> Option<String> {
+    api_kind
+        .and_then(|k| serde_json::to_value(k).ok())
+        .and_then(|v| v.as_str().map(String::from))
+}
+
+impl From<RoutingPolicyRow> for RoutingPolicy {
+    fn from(row: RoutingPolicyRow) -> Self {
+        RoutingPolicy {
+            id: Uuid::parse_str(&row.id).unwrap_or_default(),
+            name: row.name,
+            model_pattern: row.model_pattern,
+            api_kind: row
+                .api_kind
+                .and_then(|s| serde_json::from_value(serde_json::Value::String(s)).ok()),
+            required_labels: serde_json::from_str(&row.required_labels).unwrap_or_default(),
+            on_no_match: row.on_no_match.parse().unwrap_or(NoMatchBehavior::Error),
+            priority: row.priority as i32,
+            enabled: row.enabled != 0,
+            created_at: parse_timestamp(&row.created_at),
+            updated_at: parse_timestamp(&row.updated_at),
+        }
+    }
+}
+
+/// ルーティングポリシー一覧を取得（優先度降順）
+pub async fn list(pool: &SqlitePool) -> RouterResult<Vec<RoutingPolicy>> {
+    let rows = sqlx::query_as::<_, RoutingPolicyRow>(
+        r#"
+        SELECT id, name, model_pattern, api_kind, required_labels, on_no_match,
+               priority, enabled, created_at, updated_at
+        FROM routing_policies
+        ORDER BY priority DESC, created_at ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to list routing policies: {}", e)))?;
+
+    Ok(rows.into_iter().map(RoutingPolicy::from).collect())
+}
+
+/// IDでルーティングポリシーを取得
+pub async fn get(pool: &SqlitePool, id: Uuid) -> RouterResult<Option<RoutingPolicy>> {
+    let row = sqlx::query_as::<_, RoutingPolicyRow>(
+        r#"
+        SELECT id, name, model_pattern, api_kind, required_labels, on_no_match,
+               priority, enabled, created_at, updated_at
+        FROM routing_policies
+        WHERE id = ?
+        "#,
+    )
+    .bind(id.to_string())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to get routing policy: {}", e)))?;
+
+    Ok(row.map(RoutingPolicy::from))
+}
+
+/// ルーティングポリシーを作成
+pub async fn create(pool: &SqlitePool, policy: &RoutingPolicy) -> RouterResult<()> {
+    let required_labels =
+        serde_json::to_string(&policy.required_labels).unwrap_or_else(|_| "[]".to_string());
+    sqlx::query(
+        r#"
+        INSERT INTO routing_policies (
+            id, name, model_pattern, api_kind, required_labels, on_no_match,
+            priority, enabled, created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(policy.id.to_string())
+    .bind(&policy.name)
+    .bind(&policy.model_pattern)
+    .bind(api_kind_to_str(policy.api_kind))
+    .bind(&required_labels)
+    .bind(policy.on_no_match.as_str())
+    .bind(policy.priority as i64)
+    .bind(policy.enabled as i64)
+    .bind(policy.created_at.to_rfc3339())
+    .bind(policy.updated_at.to_rfc3339())
+    .execute(pool)
+    .await
+    .map_err(map_write_error)?;
+
+    Ok(())
+}
+
+/// ルーティングポリシーを更新
+pub async fn update(pool: &SqlitePool, policy: &RoutingPolicy) -> RouterResult<bool> {
+    let required_labels =
+        serde_json::to_string(&policy.required_labels).unwrap_or_else(|_| "[]".to_string());
+    let result = sqlx::query(
+        r#"
+        UPDATE routing_policies SET
+            name = ?, model_pattern = ?, api_kind = ?, required_labels = ?,
+            on_no_match = ?, priority = ?, enabled = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&policy.name)
+    .bind(&policy.model_pattern)
+    .bind(api_kind_to_str(policy.api_kind))
+    .bind(&required_labels)
+    .bind(policy.on_no_match.as_str())
+    .bind(policy.priority as i64)
+    .bind(policy.enabled as i64)
+    .bind(policy.updated_at.to_rfc3339())
+    .bind(policy.id.to_string())
+    .execute(pool)
+    .await
+    .map_err(map_write_error)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// ルーティングポリシーを削除
+pub async fn delete(pool: &SqlitePool, id: Uuid) -> RouterResult<bool> {
+    let result = sqlx::query("DELETE FROM routing_policies WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to delete routing policy: {}", e)))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+fn map_write_error(e: sqlx::Error) -> LbError {
+    if e.to_string().contains("UNIQUE constraint failed") {
+        LbError::Conflict("Routing policy with this name already exists".to_string())
+    } else {
+        LbError::Database(format!("Failed to save routing policy: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::TEST_LOCK;
+
+    fn sample_policy(name: &str, priority: i32) -> RoutingPolicy {
+        let now = Utc::now();
+        RoutingPolicy {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            model_pattern: "llama3*".to_string(),
+            api_kind: Some(TpsApiKind::ChatCompletions),
+            required_labels: vec!["gpu-a100".to_string()],
+            on_no_match: NoMatchBehavior::Fallback,
+            priority,
+            enabled: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn routing_policy_crud_roundtrip() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+
+        let low = sample_policy("low", 1);
+        let high = sample_policy("high", 10);
+        create(&pool, &low).await.unwrap();
+        create(&pool, &high).await.unwrap();
+
+        let listed = list(&pool).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].name, "high");
+        assert_eq!(listed[0].api_kind, Some(TpsApiKind::ChatCompletions));
+        assert_eq!(listed[0].on_no_match, NoMatchBehavior::Fallback);
+        assert_eq!(listed[0].required_labels, vec!["gpu-a100"]);
+
+        let mut changed = low.clone();
+        changed.enabled = false;
+        changed.api_kind = None;
+        assert!(update(&pool, &changed).await.unwrap());
+        let fetched = get(&pool, low.id).await.unwrap().unwrap();
+        assert!(!fetched.enabled);
+        assert_eq!(fetched.api_kind, None);
+
+        assert!(delete(&pool, low.id).await.unwrap());
+        assert!(get(&pool, low.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn routing_policy_duplicate_name_is_conflict() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+
+        create(&pool, &sample_policy("dup", 0)).await.unwrap();
+        let err = create(&pool, &sample_policy("dup", 0)).await.unwrap_err();
+        assert!(matches!(err, LbError::Conflict(_)));
+    }
+}
@@ -0,0 +1,224 @@
+//! シャドウトラフィック設定のストレージ層
+//!
+//! シャドウトラフィック設定をSQLiteに永続化する。送信統計はメモリ上で集計する。
+//! 複製先エンドポイントが削除された場合、設定は外部キーで自動的に削除される。
+
+use crate::balancer::ShadowTarget;
+use crate::common::error::{LbError, RouterResult};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow)]
+struct ShadowTargetRow {
+    id: String,
+    name: String,
+    model_pattern: String,
+    endpoint_id: String,
+    sample_percent: i64,
+    enabled: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+fn parse_timestamp(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+impl From<ShadowTargetRow> for ShadowTarget {
+    fn from(row: ShadowTargetRow) -> Self {
+        ShadowTarget {
+            id: Uuid::parse_str(&row.id).unwrap_or_default(),
+            name: row.name,
+            model_pattern: row.model_pattern,
+            endpoint_id: Uuid::parse_str(&row.endpoint_id).unwrap_or_default(),
+            sample_percent: row.sample_percent.clamp(0, 100) as u8,
+            enabled: row.enabled != 0,
+            created_at: parse_timestamp(&row.created_at),
+            updated_at: parse_timestamp(&row.updated_at),
+        }
+    }
+}
+
+/// シャドウトラフィック設定一覧を取得（作成日時順）
+pub async fn list(pool: &SqlitePool) -> RouterResult<Vec<ShadowTarget>> {
+    let rows = sqlx::query_as::<_, ShadowTargetRow>(
+        r#"
+        SELECT id, name, model_pattern, endpoint_id, sample_percent, enabled,
+               created_at, updated_at
+        FROM shadow_targets
+        ORDER BY created_at ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to list shadow targets: {}", e)))?;
+
+    Ok(rows.into_iter().map(ShadowTarget::from).collect())
+}
+
+/// IDでシャドウトラフィック設定を取得
+pub async fn get(pool: &SqlitePool, id: Uuid) -> RouterResult<Option<ShadowTarget>> {
+    let row = sqlx::query_as::<_, ShadowTargetRow>(
+        r#"
+        SELECT id, name, model_pattern, endpoint_id, sample_percent, enabled,
+               created_at, updated_at
+        FROM shadow_targets
+        WHERE id = ?
+        "#,
+    )
+    .bind(id.to_string())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to get shadow target: {}", e)))?;
+
+    Ok(row.map(ShadowTarget::from))
+}
+
+/// シャドウトラフィック設定を作成
+pub async fn create(pool: &SqlitePool, target: &ShadowTarget) -> RouterResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO shadow_targets (
+            id, name, model_pattern, endpoint_id, sample_percent, enabled,
+            created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(target.id.to_string())
+    .bind(&target.name)
+    .bind(&target.model_pattern)
+    .bind(target.endpoint_id.to_string())
+    .bind(target.sample_percent as i64)
+    .bind(target.enabled as i64)
+    .bind(target.created_at.to_rfc3339())
+    .bind(target.updated_at.to_rfc3339())
+    .execute(pool)
+    .await
+    .map_err(map_write_error)?;
+
+    Ok(())
+}
+
+/// シャドウトラフィック設定を更新
+pub async fn update(pool: &SqlitePool, target: &ShadowTarget) -> RouterResult<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE shadow_targets SET
+            name = ?, model_pattern = ?, endpoint_id = ?, sample_percent = ?,
+            enabled = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&target.name)
+    .bind(&target.model_pattern)
+    .bind(target.endpoint_id.to_string())
+    .bind(target.sample_percent as i64)
+    .bind(target.enabled as i64)
+    .bind(target.updated_at.to_rfc3339())
+    .bind(target.id.to_string())
+    .execute(pool)
+    .await
+    .map_err(map_write_error)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// シャドウトラフィック設定を削除
+pub async fn delete(pool: &SqlitePool, id: Uuid) -> RouterResult<bool> {
+    let result = sqlx::query("DELETE FROM shadow_targets WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to delete shadow target: {}", e)))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+fn map_write_error(e: sqlx::Error) -> LbError {
+    if e.to_string().contains("UNIQUE constraint failed") {
+        LbError::Conflict("Shadow target with this name already exists".to_string())
+    } else {
+        LbError::Database(format!("Failed to save shadow target: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::TEST_LOCK;
+    use crate::types::endpoint::{Endpoint, EndpointType};
+
+    async fn insert_endpoint(pool: &SqlitePool, name: &str) -> Uuid {
+        let endpoint = Endpoint::new(
+            name.to_string(),
+            format!("http://{}.local:8000", name),
+            EndpointType::Vllm,
+        );
+        crate::db::endpoints::create_endpoint(pool, &endpoint)
+            .await
+            .unwrap();
+        endpoint.id
+    }
+
+    fn sample_target(name: &str, endpoint_id: Uuid) -> ShadowTarget {
+        let now = Utc::now();
+        ShadowTarget {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            model_pattern: "llama3*".to_string(),
+            endpoint_id,
+            sample_percent: 10,
+            enabled: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn shadow_target_crud_roundtrip() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let endpoint_id = insert_endpoint(&pool, "shadow").await;
+
+        let target = sample_target("llama-shadow", endpoint_id);
+        create(&pool, &target).await.unwrap();
+
+        let listed = list(&pool).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].endpoint_id, endpoint_id);
+        assert_eq!(listed[0].sample_percent, 10);
+
+        let mut changed = target.clone();
+        changed.enabled = false;
+        changed.sample_percent = 25;
+        assert!(update(&pool, &changed).await.unwrap());
+        let fetched = get(&pool, target.id).await.unwrap().unwrap();
+        assert!(!fetched.enabled);
+        assert_eq!(fetched.sample_percent, 25);
+
+        let err = create(&pool, &sample_target("llama-shadow", endpoint_id))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LbError::Conflict(_)));
+
+        assert!(delete(&pool, target.id).await.unwrap());
+        assert!(get(&pool, target.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn shadow_target_is_removed_with_endpoint() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let endpoint_id = insert_endpoint(&pool, "shadow-gone").await;
+
+        let target = sample_target("gone", endpoint_id);
+        create(&pool, &target).await.unwrap();
+        crate::db::endpoints::delete_endpoint(&pool, endpoint_id)
+            .await
+            .unwrap();
+        assert!(get(&pool, target.id).await.unwrap().is_none());
+    }
+}
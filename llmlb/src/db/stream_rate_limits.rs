@@ -0,0 +1,184 @@
+//! ストリーミング出力レート上限のストレージ層
+//!
+//! APIキー/テナント単位のトークン/秒・バイト/秒の上限をSQLiteに永続化する。
+
+use crate::balancer::{PrincipalType, StreamRateLimit};
+use crate::common::error::{LbError, RouterResult};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow)]
+struct StreamRateLimitRow {
+    id: String,
+    principal_type: String,
+    principal_id: String,
+    max_tokens_per_sec: Option<i64>,
+    max_bytes_per_sec: Option<i64>,
+    created_at: String,
+    updated_at: String,
+}
+
+fn parse_timestamp(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+impl From<StreamRateLimitRow> for StreamRateLimit {
+    fn from(row: StreamRateLimitRow) -> Self {
+        StreamRateLimit {
+            id: Uuid::parse_str(&row.id).unwrap_or_default(),
+            principal_type: row.principal_type.parse().unwrap_or(PrincipalType::ApiKey),
+            principal_id: Uuid::parse_str(&row.principal_id).unwrap_or_default(),
+            max_tokens_per_sec: row.max_tokens_per_sec.map(|v| v.max(0) as u32),
+            max_bytes_per_sec: row.max_bytes_per_sec.map(|v| v.max(0) as u64),
+            created_at: parse_timestamp(&row.created_at),
+            updated_at: parse_timestamp(&row.updated_at),
+        }
+    }
+}
+
+/// レート上限一覧を取得
+pub async fn list(pool: &SqlitePool) -> RouterResult<Vec<StreamRateLimit>> {
+    let rows = sqlx::query_as::<_, StreamRateLimitRow>(
+        r#"
+        SELECT id, principal_type, principal_id, max_tokens_per_sec, max_bytes_per_sec,
+               created_at, updated_at
+        FROM stream_rate_limits
+        ORDER BY created_at ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to list stream rate limits: {}", e)))?;
+
+    Ok(rows.into_iter().map(StreamRateLimit::from).collect())
+}
+
+/// IDでレート上限を取得
+pub async fn get(pool: &SqlitePool, id: Uuid) -> RouterResult<Option<StreamRateLimit>> {
+    let row = sqlx::query_as::<_, StreamRateLimitRow>(
+        r#"
+        SELECT id, principal_type, principal_id, max_tokens_per_sec, max_bytes_per_sec,
+               created_at, updated_at
+        FROM stream_rate_limits
+        WHERE id = ?
+        "#,
+    )
+    .bind(id.to_string())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| LbError::Database(format!("Failed to get stream rate limit: {}", e)))?;
+
+    Ok(row.map(StreamRateLimit::from))
+}
+
+/// レート上限を作成
+pub async fn create(pool: &SqlitePool, limit: &StreamRateLimit) -> RouterResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO stream_rate_limits (
+            id, principal_type, principal_id, max_tokens_per_sec, max_bytes_per_sec,
+            created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(limit.id.to_string())
+    .bind(limit.principal_type.as_str())
+    .bind(limit.principal_id.to_string())
+    .bind(limit.max_tokens_per_sec.map(|v| v as i64))
+    .bind(limit.max_bytes_per_sec.map(|v| v as i64))
+    .bind(limit.created_at.to_rfc3339())
+    .bind(limit.updated_at.to_rfc3339())
+    .execute(pool)
+    .await
+    .map_err(map_write_error)?;
+
+    Ok(())
+}
+
+/// レート上限を更新
+pub async fn update(pool: &SqlitePool, limit: &StreamRateLimit) -> RouterResult<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE stream_rate_limits SET
+            max_tokens_per_sec = ?, max_bytes_per_sec = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(limit.max_tokens_per_sec.map(|v| v as i64))
+    .bind(limit.max_bytes_per_sec.map(|v| v as i64))
+    .bind(limit.updated_at.to_rfc3339())
+    .bind(limit.id.to_string())
+    .execute(pool)
+    .await
+    .map_err(map_write_error)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// レート上限を削除
+pub async fn delete(pool: &SqlitePool, id: Uuid) -> RouterResult<bool> {
+    let result = sqlx::query("DELETE FROM stream_rate_limits WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| LbError::Database(format!("Failed to delete stream rate limit: {}", e)))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+fn map_write_error(e: sqlx::Error) -> LbError {
+    if e.to_string().contains("UNIQUE constraint failed") {
+        LbError::Conflict("Stream rate limit for this principal already exists".to_string())
+    } else {
+        LbError::Database(format!("Failed to save stream rate limit: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::TEST_LOCK;
+
+    fn sample_limit(principal_id: Uuid) -> StreamRateLimit {
+        let now = Utc::now();
+        StreamRateLimit {
+            id: Uuid::new_v4(),
+            principal_type: PrincipalType::ApiKey,
+            principal_id,
+            max_tokens_per_sec: Some(20),
+            max_bytes_per_sec: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_rate_limit_crud_roundtrip() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = crate::db::test_utils::test_db_pool().await;
+
+        let principal_id = Uuid::new_v4();
+        let limit = sample_limit(principal_id);
+        create(&pool, &limit).await.unwrap();
+        assert_eq!(list(&pool).await.unwrap(), vec![limit.clone()]);
+
+        let mut changed = limit.clone();
+        changed.max_tokens_per_sec = None;
+        changed.max_bytes_per_sec = Some(2048);
+        assert!(update(&pool, &changed).await.unwrap());
+        let fetched = get(&pool, limit.id).await.unwrap().unwrap();
+        assert_eq!(fetched.max_tokens_per_sec, None);
+        assert_eq!(fetched.max_bytes_per_sec, Some(2048));
+
+        let err = create(&pool, &sample_limit(principal_id))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LbError::Conflict(_)));
+
+        assert!(delete(&pool, limit.id).await.unwrap());
+        assert!(get(&pool, limit.id).await.unwrap().is_none());
+    }
+}
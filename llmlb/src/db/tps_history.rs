@@ -0,0 +1,270 @@
+//! TPS時系列データベース操作
+//!
+//! `LoadManager` のエンドポイント×モデル単位のTPS（EMA）を定期的にスナップショットして
+//! tps_history テーブルに保存する。保持期間を過ぎたスナップショットは日次統計タスク
+//! （`endpoint_daily_stats::start_daily_stats_task`）で削除する。
+//!
+//! 取得時は分/時単位のバケットで平均し、サンプルの無いバケットは補間せずに省く。
+
+use crate::balancer::LoadManager;
+use crate::common::protocol::TpsApiKind;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// TPS時系列の集計粒度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TpsHistoryGranularity {
+    /// 1分単位
+    #[default]
+    Minute,
+    /// 1時間単位
+    Hour,
+}
+
+impl TpsHistoryGranularity {
+    /// RFC 3339 形式の時刻からバケットを切り出す長さ（`YYYY-MM-DDTHH:MM` / `YYYY-MM-DDTHH`）
+    fn bucket_len(self) -> i64 {
+        match self {
+            Self::Minute => 16,
+            Self::Hour => 13,
+        }
+    }
+
+    /// バケット文字列をバケット開始時刻に変換
+    fn parse_bucket(self, bucket: &str) -> Option<DateTime<Utc>> {
+        let bucket = match self {
+            Self::Minute => bucket.to_string(),
+            Self::Hour => format!("{bucket}:00"),
+        };
+        NaiveDateTime::parse_from_str(&bucket, "%Y-%m-%dT%H:%M")
+            .ok()
+            .map(|t| t.and_utc())
+    }
+}
+
+/// TPS時系列の1点（バケット内の平均）
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TpsHistoryPoint {
+    /// バケット開始時刻
+    pub timestamp: DateTime<Utc>,
+    /// モデルID
+    pub model_id: String,
+    /// API種別（chat_completions / completions / responses）
+    pub api_kind: String,
+    /// バケット内のTPS（EMA）の平均
+    pub tps: f64,
+    /// バケット終了時点のリクエスト完了数（累計）
+    pub request_count: i64,
+    /// バケット内のスナップショット数
+    pub samples: i64,
+}
+
+/// TPSのスナップショット1件
+#[derive(Debug, Clone, PartialEq)]
+pub struct TpsSnapshot {
+    /// スナップショット時刻
+    pub timestamp: DateTime<Utc>,
+    /// エンドポイントID
+    pub endpoint_id: Uuid,
+    /// モデルID
+    pub model_id: String,
+    /// API種別
+    pub api_kind: TpsApiKind,
+    /// TPS（EMA）
+    pub tps: f64,
+    /// リクエスト完了数（累計）
+    pub request_count: i64,
+}
+
+fn api_kind_str(api_kind: TpsApiKind) -> &'static str {
+    match api_kind {
+        TpsApiKind::ChatCompletions => "chat_completions",
+        TpsApiKind::Completions => "completions",
+        TpsApiKind::Responses => "responses",
+    }
+}
+
+/// スナップショットを一括保存
+pub async fn insert_snapshots(
+    pool: &SqlitePool,
+    snapshots: &[TpsSnapshot],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    for snapshot in snapshots {
+        sqlx::query(
+            "INSERT INTO tps_history (sampled_at, endpoint_id, model_id, api_kind, tps, request_count) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(snapshot.timestamp.to_rfc3339())
+        .bind(snapshot.endpoint_id.to_string())
+        .bind(&snapshot.model_id)
+        .bind(api_kind_str(snapshot.api_kind))
+        .bind(snapshot.tps)
+        .bind(snapshot.request_count)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// エンドポイントの `since` 以降のTPS時系列をバケット単位で取得
+///
+/// `model` を指定した場合はそのモデルのみ。バケット時刻・モデル・API種別の昇順で返す。
+pub async fn get_history(
+    pool: &SqlitePool,
+    endpoint_id: Uuid,
+    model: Option<&str>,
+    since: DateTime<Utc>,
+    granularity: TpsHistoryGranularity,
+) -> Result<Vec<TpsHistoryPoint>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, String, String, f64, i64, i64)>(
+        "SELECT substr(sampled_at, 1, ?) AS bucket, model_id, api_kind, \
+                AVG(tps), MAX(request_count), COUNT(*) \
+         FROM tps_history \
+         WHERE endpoint_id = ? AND sampled_at >= ? AND (? IS NULL OR model_id = ?) \
+         GROUP BY bucket, model_id, api_kind \
+         ORDER BY bucket ASC, model_id ASC, api_kind ASC",
+    )
+    .bind(granularity.bucket_len())
+    .bind(endpoint_id.to_string())
+    .bind(since.to_rfc3339())
+    .bind(model)
+    .bind(model)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(
+            |(bucket, model_id, api_kind, tps, request_count, samples)| {
+                Some(TpsHistoryPoint {
+                    timestamp: granularity.parse_bucket(&bucket)?,
+                    model_id,
+                    api_kind,
+                    tps,
+                    request_count,
+                    samples,
+                })
+            },
+        )
+        .collect())
+}
+
+/// `before` より古いスナップショットを削除し、削除件数を返す
+pub async fn prune_before(pool: &SqlitePool, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM tps_history WHERE sampled_at < ?")
+        .bind(before.to_rfc3339())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// TPS時系列のスナップショットタスクを開始
+///
+/// `interval` ごとに計測済み（TPSが算出済み）のエンドポイント×モデルのTPSを保存する。
+pub fn start_tps_history_task(pool: SqlitePool, load_manager: LoadManager, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+
+            let now = Utc::now();
+            let snapshots: Vec<TpsSnapshot> = load_manager
+                .get_all_model_tps()
+                .await
+                .into_iter()
+                .filter_map(|(endpoint_id, info)| {
+                    Some(TpsSnapshot {
+                        timestamp: now,
+                        endpoint_id,
+                        model_id: info.model_id,
+                        api_kind: info.api_kind,
+                        tps: info.tps?,
+                        request_count: info.request_count as i64,
+                    })
+                })
+                .collect();
+            if snapshots.is_empty() {
+                continue;
+            }
+            if let Err(e) = insert_snapshots(&pool, &snapshots).await {
+                tracing::warn!("Failed to save TPS history snapshot: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn test_insert_get_by_granularity_and_prune() {
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let endpoint_id = Uuid::new_v4();
+        let base = Utc.with_ymd_and_hms(2026, 10, 16, 10, 0, 0).unwrap();
+        let snapshot = |minutes: i64, model: &str, tps: f64, request_count: i64| TpsSnapshot {
+            timestamp: base + chrono::Duration::minutes(minutes) + chrono::Duration::seconds(10),
+            endpoint_id,
+            model_id: model.to_string(),
+            api_kind: TpsApiKind::ChatCompletions,
+            tps,
+            request_count,
+        };
+        // 10:02〜10:59 は欠損（補間しない）
+        insert_snapshots(
+            &pool,
+            &[
+                snapshot(0, "llama", 10.0, 1),
+                snapshot(0, "qwen", 30.0, 4),
+                snapshot(1, "llama", 20.0, 2),
+                snapshot(60, "llama", 40.0, 5),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let minutes = get_history(
+            &pool,
+            endpoint_id,
+            Some("llama"),
+            base,
+            TpsHistoryGranularity::Minute,
+        )
+        .await
+        .unwrap();
+        let timestamps: Vec<_> = minutes.iter().map(|p| p.timestamp).collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                base,
+                base + chrono::Duration::minutes(1),
+                base + chrono::Duration::minutes(60)
+            ]
+        );
+        assert_eq!(minutes[0].api_kind, "chat_completions");
+
+        let hours = get_history(&pool, endpoint_id, None, base, TpsHistoryGranularity::Hour)
+            .await
+            .unwrap();
+        assert_eq!(hours.len(), 3);
+        assert_eq!(hours[0].model_id, "llama");
+        assert_eq!(hours[0].tps, 15.0);
+        assert_eq!(hours[0].request_count, 2);
+        assert_eq!(hours[0].samples, 2);
+        assert_eq!(hours[1].model_id, "qwen");
+        assert_eq!(hours[2].timestamp, base + chrono::Duration::hours(1));
+
+        let pruned = prune_before(&pool, base + chrono::Duration::minutes(30))
+            .await
+            .unwrap();
+        assert_eq!(pruned, 3);
+    }
+}
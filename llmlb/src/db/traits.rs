@@ -19,7 +19,7 @@ use crate::types::endpoint::{
 use super::endpoint_daily_stats::{DailyStatEntry, ModelStatEntry};
 use super::endpoints::EndpointRequestTotals;
 use super::invitations::{InvitationCode, InvitationCodeWithPlaintext};
-use super::request_history::{FilteredRecords, RecordFilter, TokenStatistics};
+use super::request_history::{FilteredRecords, HistoryFilter, RecordFilter, TokenStatistics};
 
 // ---------------------------------------------------------------------------
 // EndpointRepository
@@ -216,6 +216,11 @@ pub trait RequestHistoryRepository: Send + Sync {
         page: usize,
         per_page: usize,
     ) -> crate::common::error::RouterResult<FilteredRecords>;
+    /// レイテンシ・トークン数等の条件でレコードを検索
+    async fn query_history(
+        &self,
+        filter: &HistoryFilter,
+    ) -> crate::common::error::RouterResult<Vec<RequestResponseRecord>>;
     /// トークン統計を取得
     async fn get_token_statistics(&self) -> crate::common::error::RouterResult<TokenStatistics>;
 }
@@ -590,6 +595,13 @@ impl RequestHistoryRepository for super::request_history::RequestHistoryStorage
         self.filter_and_paginate(filter, page, per_page).await
     }
 
+    async fn query_history(
+        &self,
+        filter: &HistoryFilter,
+    ) -> crate::common::error::RouterResult<Vec<RequestResponseRecord>> {
+        self.query_history(filter).await
+    }
+
     async fn get_token_statistics(&self) -> crate::common::error::RouterResult<TokenStatistics> {
         self.get_token_statistics().await
     }
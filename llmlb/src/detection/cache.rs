@@ -0,0 +1,217 @@
+//! エンドポイントタイプ検出結果のTTLキャッシュ
+//!
+//! 起動時やヘルスチェックで同じエンドポイントを繰り返し検出しないよう、
+//! `(base_url, api_key)` ごとの検出成功結果を TTL 付きで保持する。
+//! 検出に失敗した場合はキャッシュを更新しない（前回の成功値を保持する）。
+
+use super::{detect_endpoint_type_with_client, DetectionError, DetectionResult};
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// デフォルトTTL（秒）
+pub const DEFAULT_DETECTION_CACHE_TTL_SECS: u64 = 600;
+
+type CacheKey = (String, Option<String>);
+
+#[derive(Debug, Clone)]
+struct CachedDetection {
+    detected_at: Instant,
+    result: DetectionResult,
+}
+
+/// 検出結果キャッシュ
+#[derive(Debug)]
+pub struct DetectionCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<CacheKey, CachedDetection>>,
+}
+
+static GLOBAL_CACHE: Lazy<DetectionCache> = Lazy::new(DetectionCache::from_env);
+
+fn cache_key(base_url: &str, api_key: Option<&str>) -> CacheKey {
+    (
+        base_url.trim_end_matches('/').to_string(),
+        api_key.map(str::to_string),
+    )
+}
+
+impl DetectionCache {
+    /// 指定TTLでキャッシュを作成（TTL 0 はキャッシュ無効）
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// `LLMLB_DETECTION_CACHE_TTL` からTTLを読み込んでキャッシュを作成
+    pub fn from_env() -> Self {
+        Self::new(Duration::from_secs(
+            crate::config::detection_cache_ttl_secs(),
+        ))
+    }
+
+    /// プロセス共通のキャッシュ
+    pub fn global() -> &'static DetectionCache {
+        &GLOBAL_CACHE
+    }
+
+    /// キャッシュのTTL
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// TTL内の検出結果を取得（期限切れ・未検出は `None`）
+    pub fn get_fresh(&self, base_url: &str, api_key: Option<&str>) -> Option<DetectionResult> {
+        self.get_fresh_at(base_url, api_key, Instant::now())
+    }
+
+    fn get_fresh_at(
+        &self,
+        base_url: &str,
+        api_key: Option<&str>,
+        now: Instant,
+    ) -> Option<DetectionResult> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&cache_key(base_url, api_key))
+            .filter(|cached| now.saturating_duration_since(cached.detected_at) < self.ttl)
+            .map(|cached| cached.result.clone())
+    }
+
+    /// 検出成功結果を保存
+    pub fn store(&self, base_url: &str, api_key: Option<&str>, result: DetectionResult) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                cache_key(base_url, api_key),
+                CachedDetection {
+                    detected_at: Instant::now(),
+                    result,
+                },
+            );
+    }
+
+    /// キャッシュを利用してエンドポイントタイプを検出する
+    ///
+    /// `force` が `true` の場合はTTL内でも再検出する。検出に失敗した場合は
+    /// エラーを返し、キャッシュ済みの成功値はそのまま残す。
+    pub async fn detect(
+        &self,
+        client: &Client,
+        base_url: &str,
+        api_key: Option<&str>,
+        force: bool,
+    ) -> Result<DetectionResult, DetectionError> {
+        if !force {
+            if let Some(cached) = self.get_fresh(base_url, api_key) {
+                debug!(
+                    base_url = %base_url,
+                    endpoint_type = %cached.endpoint_type.as_str(),
+                    "Using cached endpoint type detection"
+                );
+                return Ok(cached);
+            }
+        }
+
+        let result = detect_endpoint_type_with_client(client, base_url, api_key).await?;
+        self.store(base_url, api_key, result.clone());
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::EndpointType;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn result(endpoint_type: EndpointType) -> DetectionResult {
+        DetectionResult {
+            endpoint_type,
+            reason: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn cached_detection_expires_after_ttl_and_ignores_trailing_slash() {
+        let cache = DetectionCache::new(Duration::from_secs(600));
+        assert!(cache.get_fresh("http://a:8080", None).is_none());
+
+        cache.store("http://a:8080/", None, result(EndpointType::Vllm));
+        let fresh = cache.get_fresh("http://a:8080", None).unwrap();
+        assert_eq!(fresh.endpoint_type, EndpointType::Vllm);
+        assert!(cache.get_fresh("http://a:8080", Some("sk-other")).is_none());
+        assert!(cache
+            .get_fresh_at(
+                "http://a:8080",
+                None,
+                Instant::now() + Duration::from_secs(601)
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn zero_ttl_disables_cache() {
+        let cache = DetectionCache::new(Duration::ZERO);
+        cache.store("http://a:8080", None, result(EndpointType::Ollama));
+        assert!(cache.get_fresh("http://a:8080", None).is_none());
+    }
+
+    #[tokio::test]
+    async fn detect_skips_probes_within_ttl_and_keeps_value_on_failure() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "models": []
+            })))
+            .mount(&server)
+            .await;
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .unwrap();
+        let cache = DetectionCache::new(Duration::from_secs(600));
+
+        let first = cache
+            .detect(&client, &server.uri(), None, false)
+            .await
+            .unwrap();
+        assert_eq!(first.endpoint_type, EndpointType::Ollama);
+        let probes = server.received_requests().await.unwrap().len();
+
+        let second = cache
+            .detect(&client, &server.uri(), None, false)
+            .await
+            .unwrap();
+        assert_eq!(second.endpoint_type, EndpointType::Ollama);
+        assert_eq!(server.received_requests().await.unwrap().len(), probes);
+
+        // 強制再検出が失敗しても前回の成功値は残る
+        server.reset().await;
+        assert!(cache
+            .detect(&client, &server.uri(), None, true)
+            .await
+            .is_err());
+        assert!(!server.received_requests().await.unwrap().is_empty());
+        assert_eq!(
+            cache
+                .get_fresh(&server.uri(), None)
+                .map(|r| r.endpoint_type),
+            Some(EndpointType::Ollama)
+        );
+    }
+}
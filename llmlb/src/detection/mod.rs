@@ -4,6 +4,7 @@
 //!
 //! Detection priority: xLLM > LM Studio > Ollama > vLLM > llama.cpp > OpenAI-compatible
 
+pub mod cache;
 mod llama_cpp;
 mod lm_studio;
 mod ollama;
@@ -17,6 +18,7 @@ use tracing::{debug, warn};
 
 use crate::types::endpoint::EndpointType;
 
+pub use cache::DetectionCache;
 pub use llama_cpp::detect_llamacpp;
 pub use lm_studio::detect_lm_studio;
 pub use ollama::detect_ollama;
@@ -194,6 +196,21 @@ pub async fn detect_endpoint_type_with_client(
     }
 }
 
+/// Detect endpoint type, reusing a cached result within `LLMLB_DETECTION_CACHE_TTL`
+///
+/// `force = true` bypasses the cache. Failed detections never overwrite
+/// the last successful result.
+pub async fn detect_endpoint_type_cached(
+    client: &Client,
+    base_url: &str,
+    api_key: Option<&str>,
+    force: bool,
+) -> Result<DetectionResult, DetectionError> {
+    DetectionCache::global()
+        .detect(client, base_url, api_key, force)
+        .await
+}
+
 /// Internal result for OpenAI-compatible detection
 enum OpenAiDetectResult {
     /// Detected as OpenAI-compatible
@@ -237,6 +254,88 @@ async fn detect_openai_compatible(
     }
 }
 
+/// Timeout for re-detecting a single registered endpoint
+pub const REDETECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of re-detecting a registered endpoint's type
+#[derive(Debug, Clone)]
+pub struct RedetectOutcome {
+    /// Type before re-detection
+    pub old_type: EndpointType,
+    /// Detected type (equal to `old_type` when unchanged)
+    pub new_type: EndpointType,
+    /// Detection reason
+    pub reason: String,
+}
+
+impl RedetectOutcome {
+    /// Whether the endpoint type was changed
+    pub fn changed(&self) -> bool {
+        self.old_type != self.new_type
+    }
+}
+
+/// Re-detection failure
+#[derive(Debug)]
+pub enum RedetectError {
+    /// Detection probes failed
+    Detection(DetectionError),
+    /// Detection did not finish within the timeout
+    Timeout(Duration),
+    /// Persisting the new type failed
+    Database(String),
+}
+
+impl std::fmt::Display for RedetectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Detection(err) => write!(f, "{}", err),
+            Self::Timeout(timeout) => {
+                write!(f, "detection timed out after {}s", timeout.as_secs())
+            }
+            Self::Database(msg) => write!(f, "failed to update endpoint type: {}", msg),
+        }
+    }
+}
+
+/// Re-detect the type of a registered endpoint and persist it when it changed
+///
+/// Shared by startup re-detection and `POST /api/endpoints/:id/redetect`.
+/// `force` bypasses the detection cache. On failure the existing type is kept.
+pub async fn redetect_endpoint(
+    registry: &crate::registry::endpoints::EndpointRegistry,
+    client: &Client,
+    endpoint: &crate::types::endpoint::Endpoint,
+    timeout: Duration,
+    force: bool,
+) -> Result<RedetectOutcome, RedetectError> {
+    let result = tokio::time::timeout(
+        timeout,
+        detect_endpoint_type_cached(
+            client,
+            &endpoint.base_url,
+            endpoint.api_key.as_deref(),
+            force,
+        ),
+    )
+    .await
+    .map_err(|_| RedetectError::Timeout(timeout))?
+    .map_err(RedetectError::Detection)?;
+
+    let outcome = RedetectOutcome {
+        old_type: endpoint.endpoint_type,
+        new_type: result.endpoint_type,
+        reason: result.reason,
+    };
+    if outcome.changed() {
+        registry
+            .update_endpoint_type(endpoint.id, outcome.new_type)
+            .await
+            .map_err(|e| RedetectError::Database(e.to_string()))?;
+    }
+    Ok(outcome)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,17 +1,28 @@
 //! ダッシュボードイベントバス
 //!
 //! エンドポイント登録・状態変化・メトリクス更新などのイベントを
-//! WebSocketクライアントにブロードキャストするための基盤
+//! WebSocket / SSE クライアントにブロードキャストするための基盤
 
-use crate::types::endpoint::EndpointStatus;
+pub mod snapshot_diff;
+
+use crate::balancer::{CircuitState, EndpointLoadSnapshot};
+use crate::types::endpoint::{EndpointStatus, EndpointType};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 use tokio::sync::broadcast;
 use uuid::Uuid;
 
 /// イベントバスのチャネル容量
 const EVENT_CHANNEL_CAPACITY: usize = 1024;
 
+/// 連番付きイベントのバックログ上限
+///
+/// SSE の購読者ごとに未送信のまま保持されるイベント数と、`Last-Event-ID` で再送できる
+/// 直近イベント数の上限。超えた分は古いものから破棄する。
+pub const EVENT_BACKLOG_CAPACITY: usize = 256;
+
 /// ダッシュボードイベント
 ///
 /// WebSocketクライアントに送信されるイベントの種類
@@ -71,14 +82,121 @@ pub enum DashboardEvent {
         /// 処理時間（ミリ秒）
         duration_ms: u64,
     },
+    /// エンドポイント月次予算超過イベント
+    ///
+    /// 当月累計コストが予算に達し、当該エンドポイントがルーティング候補から除外されたときに発行
+    EndpointBudgetExceeded {
+        /// エンドポイントID
+        endpoint_id: Uuid,
+        /// 当月累計コスト（USD）
+        monthly_cost_usd: f64,
+        /// 月次予算（USD）
+        monthly_budget_usd: f64,
+    },
+    /// エンドポイントのサーキットブレーカー状態変化イベント
+    ///
+    /// 連続エラーで open（ルーティング対象外）、クールダウン後の試験投入で half-open、
+    /// 試験リクエストの結果で closed / 再 open になったときに発行
+    EndpointCircuitStateChanged {
+        /// エンドポイントID
+        endpoint_id: Uuid,
+        /// 旧状態
+        old_state: CircuitState,
+        /// 新状態
+        new_state: CircuitState,
+        /// 連続エラー数
+        consecutive_errors: u32,
+    },
+    /// エンドポイントタイプ変更イベント
+    ///
+    /// 手動の再判別（`POST /api/endpoints/:id/redetect`）でタイプが変わったときに発行
+    EndpointTypeChanged {
+        /// エンドポイントID
+        endpoint_id: Uuid,
+        /// 旧タイプ
+        old_type: EndpointType,
+        /// 新タイプ
+        new_type: EndpointType,
+    },
+    /// モデル一覧変更イベント
+    ///
+    /// モデル同期でエンドポイントのモデルが追加・削除・更新されたときに差分のみ発行する。
+    /// 発行時点で `/v1/models` のキャッシュは破棄済みのため、購読者は再取得すれば最新の一覧を得られる
+    ModelsChanged {
+        /// エンドポイントID
+        endpoint_id: Uuid,
+        /// 追加されたモデルID
+        added: Vec<String>,
+        /// 削除されたモデルID
+        removed: Vec<String>,
+        /// 能力・正規名・max_tokens が変わったモデルID
+        updated: Vec<String>,
+    },
+    /// エンドポイント証明書期限警告イベント
+    ///
+    /// HTTPSエンドポイントのサーバ証明書の残り日数が閾値を下回ったときに発行（同じ証明書につき1回）
+    EndpointCertExpiring {
+        /// エンドポイントID
+        endpoint_id: Uuid,
+        /// 証明書の有効期限
+        cert_expires_at: DateTime<Utc>,
+        /// 残り日数（期限切れの場合は負数）
+        days_remaining: i64,
+    },
+    /// エンドポイント状態のフルスナップショット
+    ///
+    /// 差分配信の起点として最初に1回だけ発行される
+    EndpointSnapshot {
+        /// 全エンドポイントのスナップショット
+        endpoints: Vec<EndpointLoadSnapshot>,
+    },
+    /// エンドポイント状態の差分
+    ///
+    /// 前回配信以降に変化したフィールドのみを含む
+    EndpointSnapshotDelta {
+        /// 変化したエンドポイントとフィールド
+        changes: Vec<EndpointSnapshotChange>,
+        /// 削除されたエンドポイントID
+        removed: Vec<Uuid>,
+    },
+}
+
+/// エンドポイント1件分のスナップショット差分
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointSnapshotChange {
+    /// エンドポイントID（スナップショットに合わせnode_idとしてシリアライズ）
+    #[serde(rename = "node_id")]
+    pub endpoint_id: Uuid,
+    /// 変化したフィールド（値が消えたフィールドは `null`）
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 連番（イベントID）付きのダッシュボードイベント
+#[derive(Debug, Clone)]
+pub struct SequencedEvent {
+    /// 発行順の連番（1始まり、プロセス内で単調増加）
+    pub id: u64,
+    /// イベント本体
+    pub event: DashboardEvent,
+}
+
+/// 再接続時の再送用に保持する直近イベント
+#[derive(Debug, Default)]
+struct ReplayBuffer {
+    /// 最後に割り当てたイベントID
+    last_id: u64,
+    /// 直近のイベント（最大 `EVENT_BACKLOG_CAPACITY` 件）
+    events: VecDeque<SequencedEvent>,
 }
 
 /// ダッシュボードイベントバス
 ///
-/// ノード状態変化などのイベントをWebSocketクライアントにブロードキャストする
+/// ノード状態変化などのイベントをWebSocket / SSEクライアントにブロードキャストする
 #[derive(Clone)]
 pub struct DashboardEventBus {
     sender: broadcast::Sender<DashboardEvent>,
+    sequenced: broadcast::Sender<SequencedEvent>,
+    replay: Arc<Mutex<ReplayBuffer>>,
 }
 
 impl Default for DashboardEventBus {
@@ -91,7 +209,12 @@ impl DashboardEventBus {
     /// 新しいイベントバスを作成
     pub fn new() -> Self {
         let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
-        Self { sender }
+        let (sequenced, _) = broadcast::channel(EVENT_BACKLOG_CAPACITY);
+        Self {
+            sender,
+            sequenced,
+            replay: Arc::new(Mutex::new(ReplayBuffer::default())),
+        }
     }
 
     /// イベントバスを購読
@@ -101,17 +224,68 @@ impl DashboardEventBus {
         self.sender.subscribe()
     }
 
+    /// 連番付きでイベントバスを購読
+    ///
+    /// SSEハンドラーが使用する。受信が追いつかない場合は `EVENT_BACKLOG_CAPACITY` を超えた
+    /// 古いイベントから破棄され、`RecvError::Lagged` が返る。
+    pub fn subscribe_sequenced(&self) -> broadcast::Receiver<SequencedEvent> {
+        self.sequenced.subscribe()
+    }
+
     /// イベントを発行
     ///
     /// 購読者がいない場合でもエラーにはならない
     pub fn publish(&self, event: DashboardEvent) {
+        // 連番の割り当てと送信を同じロック内で行い、受信順と連番の順序を一致させる
+        let mut replay = self.replay.lock().unwrap_or_else(|e| e.into_inner());
+        replay.last_id += 1;
+        let sequenced = SequencedEvent {
+            id: replay.last_id,
+            event: event.clone(),
+        };
+        if replay.events.len() >= EVENT_BACKLOG_CAPACITY {
+            replay.events.pop_front();
+        }
+        replay.events.push_back(sequenced.clone());
         // 購読者がいない場合は送信に失敗するが、無視する
+        let _ = self.sequenced.send(sequenced);
         let _ = self.sender.send(event);
     }
 
+    /// 最後に発行したイベントのID（未発行なら0）
+    pub fn last_event_id(&self) -> u64 {
+        self.replay
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .last_id
+    }
+
+    /// `last_event_id` より後に発行されたイベント（再接続時の再送用）
+    ///
+    /// 取りこぼしたイベントが保持範囲より古い場合や、未知のID（再起動前のID等）の場合は
+    /// 差分を再現できないため `None` を返す。呼び出し側はスナップショットから再同期する。
+    pub fn events_after(&self, last_event_id: u64) -> Option<Vec<SequencedEvent>> {
+        let replay = self.replay.lock().unwrap_or_else(|e| e.into_inner());
+        if last_event_id > replay.last_id {
+            return None;
+        }
+        let oldest = replay.events.front().map_or(replay.last_id + 1, |e| e.id);
+        if last_event_id + 1 < oldest {
+            return None;
+        }
+        Some(
+            replay
+                .events
+                .iter()
+                .filter(|e| e.id > last_event_id)
+                .cloned()
+                .collect(),
+        )
+    }
+
     /// 現在の購読者数を取得
     pub fn subscriber_count(&self) -> usize {
-        self.sender.receiver_count()
+        self.sender.receiver_count() + self.sequenced.receiver_count()
     }
 }
 
@@ -195,6 +369,42 @@ mod tests {
         assert_eq!(data["duration_ms"], 2353);
     }
 
+    #[test]
+    fn test_models_changed_event_serialization() {
+        let endpoint_id = Uuid::parse_str("12345678-1234-1234-1234-123456789abc").unwrap();
+        let event = DashboardEvent::ModelsChanged {
+            endpoint_id,
+            added: vec!["llama3.2:3b".to_string()],
+            removed: vec!["qwen2.5:7b".to_string()],
+            updated: vec!["gemma2:2b".to_string()],
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "ModelsChanged");
+        assert_eq!(
+            json["data"]["endpoint_id"],
+            "12345678-1234-1234-1234-123456789abc"
+        );
+        assert_eq!(json["data"]["added"], serde_json::json!(["llama3.2:3b"]));
+        assert_eq!(json["data"]["removed"], serde_json::json!(["qwen2.5:7b"]));
+        assert_eq!(json["data"]["updated"], serde_json::json!(["gemma2:2b"]));
+    }
+
+    #[test]
+    fn test_endpoint_cert_expiring_event_serialization() {
+        let endpoint_id = Uuid::parse_str("12345678-1234-1234-1234-123456789abc").unwrap();
+        let event = DashboardEvent::EndpointCertExpiring {
+            endpoint_id,
+            cert_expires_at: "2026-11-01T00:00:00Z".parse().unwrap(),
+            days_remaining: 12,
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "EndpointCertExpiring");
+        assert_eq!(json["data"]["cert_expires_at"], "2026-11-01T00:00:00Z");
+        assert_eq!(json["data"]["days_remaining"], 12);
+    }
+
     #[test]
     fn test_update_state_changed_event_serialization() {
         let event = DashboardEvent::UpdateStateChanged;
@@ -257,6 +467,55 @@ mod tests {
         assert_eq!(bus.subscriber_count(), 0);
     }
 
+    #[test]
+    fn test_sequenced_subscription_counts_and_is_cleaned_up_on_drop() {
+        let bus = DashboardEventBus::new();
+        let receiver = bus.subscribe_sequenced();
+        assert_eq!(bus.subscriber_count(), 1);
+        drop(receiver);
+        assert_eq!(bus.subscriber_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sequenced_events_have_increasing_ids() {
+        let bus = DashboardEventBus::new();
+        let mut receiver = bus.subscribe_sequenced();
+        assert_eq!(bus.last_event_id(), 0);
+
+        bus.publish(DashboardEvent::UpdateStateChanged);
+        bus.publish(DashboardEvent::UpdateStateChanged);
+
+        assert_eq!(receiver.recv().await.unwrap().id, 1);
+        assert_eq!(receiver.recv().await.unwrap().id, 2);
+        assert_eq!(bus.last_event_id(), 2);
+    }
+
+    #[test]
+    fn test_events_after_replays_only_retained_range() {
+        let bus = DashboardEventBus::new();
+        for _ in 0..EVENT_BACKLOG_CAPACITY + 10 {
+            bus.publish(DashboardEvent::UpdateStateChanged);
+        }
+        let last = bus.last_event_id();
+
+        let missed = bus.events_after(last - 3).unwrap();
+        assert_eq!(
+            missed.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![last - 2, last - 1, last]
+        );
+        assert!(bus.events_after(last).unwrap().is_empty());
+
+        // 保持範囲より古いID・未知のIDは再送できない
+        assert!(bus.events_after(1).is_none());
+        assert!(bus.events_after(last + 1).is_none());
+        // 破棄されていない最古のイベントの直前までは再送できる
+        let oldest = last - EVENT_BACKLOG_CAPACITY as u64 + 1;
+        assert_eq!(
+            bus.events_after(oldest - 1).unwrap().len(),
+            EVENT_BACKLOG_CAPACITY
+        );
+    }
+
     #[test]
     fn test_create_shared_event_bus() {
         let shared = create_shared_event_bus();
@@ -302,6 +561,19 @@ mod tests {
         assert_eq!(data["status"], "online");
     }
 
+    #[test]
+    fn test_endpoint_type_changed_event_serialization() {
+        let event = DashboardEvent::EndpointTypeChanged {
+            endpoint_id: Uuid::nil(),
+            old_type: EndpointType::OpenaiCompatible,
+            new_type: EndpointType::Vllm,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "EndpointTypeChanged");
+        assert_eq!(json["data"]["old_type"], "openai_compatible");
+        assert_eq!(json["data"]["new_type"], "vllm");
+    }
+
     #[test]
     fn test_endpoint_status_changed_event_serialization() {
         let id = Uuid::parse_str("abcdef12-3456-7890-abcd-ef1234567890").unwrap();
@@ -0,0 +1,235 @@
+//! エンドポイントスナップショットの差分配信
+//!
+//! `LoadManager::snapshots` を一定間隔で取得して前回との差分を計算し、
+//! 初回はフルスナップショット、以後は変更フィールドのみの delta を
+//! イベントバスへ配信する。間隔内に発生した複数の変化は次の tick で
+//! 1 件の delta にまとめられる（coalescing）。
+
+use super::{DashboardEvent, EndpointSnapshotChange, SharedEventBus};
+use crate::balancer::{EndpointLoadSnapshot, LoadManager};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// スナップショット差分の配信間隔
+pub const SNAPSHOT_DIFF_INTERVAL: Duration = Duration::from_secs(1);
+
+/// IDとしてシリアライズされるフィールド（delta のフィールドからは除外する）
+const ID_FIELD: &str = "node_id";
+
+/// 前回スナップショットを保持して差分を計算する
+#[derive(Debug, Default)]
+pub struct SnapshotDiffer {
+    previous: Option<HashMap<Uuid, Map<String, Value>>>,
+}
+
+impl SnapshotDiffer {
+    /// 新しい差分計算器を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 現在のスナップショットから配信すべきイベントを計算する
+    ///
+    /// 初回は `EndpointSnapshot`（フル）、以後は変化があった場合のみ
+    /// `EndpointSnapshotDelta` を返す。変化がなければ `None`。
+    pub fn diff(&mut self, snapshots: &[EndpointLoadSnapshot]) -> Option<DashboardEvent> {
+        let current: HashMap<Uuid, Map<String, Value>> = snapshots
+            .iter()
+            .map(|snapshot| (snapshot.endpoint_id, snapshot_fields(snapshot)))
+            .collect();
+
+        let Some(previous) = self.previous.replace(current.clone()) else {
+            return Some(DashboardEvent::EndpointSnapshot {
+                endpoints: snapshots.to_vec(),
+            });
+        };
+
+        let mut changes: Vec<EndpointSnapshotChange> = snapshots
+            .iter()
+            .filter_map(|snapshot| {
+                let fields = &current[&snapshot.endpoint_id];
+                let changed = match previous.get(&snapshot.endpoint_id) {
+                    Some(old) => changed_fields(old, fields),
+                    None => fields.clone(),
+                };
+                (!changed.is_empty()).then(|| EndpointSnapshotChange {
+                    endpoint_id: snapshot.endpoint_id,
+                    fields: changed,
+                })
+            })
+            .collect();
+        changes.sort_by_key(|change| change.endpoint_id);
+
+        let mut removed: Vec<Uuid> = previous
+            .keys()
+            .filter(|id| !current.contains_key(id))
+            .copied()
+            .collect();
+        removed.sort();
+
+        if changes.is_empty() && removed.is_empty() {
+            return None;
+        }
+        Some(DashboardEvent::EndpointSnapshotDelta { changes, removed })
+    }
+}
+
+/// スナップショットをフィールド名→値のマップに変換する（IDは除外）
+fn snapshot_fields(snapshot: &EndpointLoadSnapshot) -> Map<String, Value> {
+    match serde_json::to_value(snapshot) {
+        Ok(Value::Object(mut fields)) => {
+            fields.remove(ID_FIELD);
+            fields
+        }
+        _ => Map::new(),
+    }
+}
+
+/// 変更されたフィールドのみを抽出する（消えたフィールドは `null`）
+fn changed_fields(old: &Map<String, Value>, new: &Map<String, Value>) -> Map<String, Value> {
+    let mut changed: Map<String, Value> = new
+        .iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    for key in old.keys() {
+        if !new.contains_key(key) {
+            changed.insert(key.clone(), Value::Null);
+        }
+    }
+    changed
+}
+
+/// スナップショット差分の配信タスクを起動する
+///
+/// 購読者がいない間はスナップショットの取得自体を省略する。
+pub fn spawn_snapshot_diff_task(
+    load_manager: LoadManager,
+    event_bus: SharedEventBus,
+    interval: Duration,
+) {
+    tokio::spawn(async move {
+        let mut differ = SnapshotDiffer::new();
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            if event_bus.subscriber_count() == 0 {
+                continue;
+            }
+            let snapshots = load_manager.snapshots().await;
+            if let Some(event) = differ.diff(&snapshots) {
+                event_bus.publish(event);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::EndpointStatus;
+
+    fn snapshot(endpoint_id: Uuid, active_requests: u32) -> EndpointLoadSnapshot {
+        EndpointLoadSnapshot {
+            endpoint_id,
+            machine_name: "node".to_string(),
+            status: EndpointStatus::Online,
+            cpu_usage: None,
+            memory_usage: None,
+            gpu_usage: None,
+            gpu_memory_usage: None,
+            gpu_memory_total_mb: None,
+            gpu_memory_used_mb: None,
+            gpu_temperature: None,
+            gpu_model_name: None,
+            gpu_compute_capability: None,
+            gpu_capability_score: None,
+            active_requests,
+            total_requests: 0,
+            successful_requests: 0,
+            failed_requests: 0,
+            average_response_time_ms: None,
+            p50_latency_ms: None,
+            p95_latency_ms: None,
+            ttft_ema_ms: None,
+            last_updated: None,
+            is_stale: false,
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            total_tokens: 0,
+            weight: 1,
+            effective_weight: 1.0,
+            weight_ramp_remaining_secs: None,
+            response_anomaly_models: Vec::new(),
+            circuit_state: crate::balancer::CircuitState::Closed,
+            adaptive_rate_limit_rps: None,
+            concurrency_limit: None,
+            concurrency_rtt_ms: None,
+            concurrency_baseline_rtt_ms: None,
+            latency_outliers: 0,
+        }
+    }
+
+    #[test]
+    fn first_diff_is_full_snapshot_then_only_changed_fields() {
+        let id = Uuid::new_v4();
+        let mut differ = SnapshotDiffer::new();
+
+        match differ.diff(&[snapshot(id, 0)]) {
+            Some(DashboardEvent::EndpointSnapshot { endpoints }) => {
+                assert_eq!(endpoints.len(), 1);
+            }
+            other => panic!("expected full snapshot, got {:?}", other),
+        }
+
+        // 変化がなければ配信しない
+        assert!(differ.diff(&[snapshot(id, 0)]).is_none());
+
+        let mut changed = snapshot(id, 3);
+        changed.gpu_temperature = Some(70.0);
+        match differ.diff(&[changed]) {
+            Some(DashboardEvent::EndpointSnapshotDelta { changes, removed }) => {
+                assert!(removed.is_empty());
+                assert_eq!(changes.len(), 1);
+                assert_eq!(changes[0].endpoint_id, id);
+                let keys: Vec<&str> = changes[0].fields.keys().map(String::as_str).collect();
+                assert_eq!(keys.len(), 2);
+                assert_eq!(changes[0].fields["active_requests"], 3);
+                assert_eq!(changes[0].fields["gpu_temperature"], 70.0);
+            }
+            other => panic!("expected delta, got {:?}", other),
+        }
+
+        // Some → None で消えたフィールドは null として通知する
+        match differ.diff(&[snapshot(id, 3)]) {
+            Some(DashboardEvent::EndpointSnapshotDelta { changes, .. }) => {
+                assert_eq!(changes[0].fields.len(), 1);
+                assert_eq!(changes[0].fields["gpu_temperature"], Value::Null);
+            }
+            other => panic!("expected delta, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn added_and_removed_endpoints_are_reported() {
+        let kept = Uuid::new_v4();
+        let gone = Uuid::new_v4();
+        let added = Uuid::new_v4();
+        let mut differ = SnapshotDiffer::new();
+        differ.diff(&[snapshot(kept, 0), snapshot(gone, 0)]);
+
+        match differ.diff(&[snapshot(kept, 0), snapshot(added, 1)]) {
+            Some(DashboardEvent::EndpointSnapshotDelta { changes, removed }) => {
+                assert_eq!(removed, vec![gone]);
+                assert_eq!(changes.len(), 1);
+                assert_eq!(changes[0].endpoint_id, added);
+                assert_eq!(changes[0].fields["machine_name"], "node");
+                assert!(!changes[0].fields.contains_key(ID_FIELD));
+            }
+            other => panic!("expected delta, got {:?}", other),
+        }
+    }
+}
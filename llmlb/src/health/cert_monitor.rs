@@ -0,0 +1,363 @@
+//! TLS証明書の有効期限監視
+//!
+//! HTTPSエンドポイントへ定期的に接続してサーバ証明書の有効期限（notAfter）を取得し、
+//! 期限が近づいたら `EndpointCertExpiring` イベントを発行する。
+//! 接続に失敗したエンドポイントと非HTTPSのエンドポイントは対象外。
+
+use crate::registry::endpoints::EndpointRegistry;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// 証明書取得のタイムアウト（秒）
+const CERT_FETCH_TIMEOUT_SECS: u64 = 10;
+
+/// デフォルトのチェック間隔（6時間）
+const DEFAULT_CERT_CHECK_INTERVAL_SECS: u64 = 6 * 60 * 60;
+
+/// エンドポイントごとの証明書有効期限（最後に取得できた値）
+static CERT_EXPIRIES: Lazy<Mutex<HashMap<Uuid, DateTime<Utc>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// エンドポイントの証明書有効期限（未取得・非HTTPSの場合は `None`）
+pub fn endpoint_cert_expires_at(endpoint_id: Uuid) -> Option<DateTime<Utc>> {
+    CERT_EXPIRIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&endpoint_id)
+        .copied()
+}
+
+fn record_cert_expiry(endpoint_id: Uuid, expires_at: Option<DateTime<Utc>>) {
+    let mut expiries = CERT_EXPIRIES.lock().unwrap_or_else(|e| e.into_inner());
+    match expires_at {
+        Some(expires_at) => {
+            expiries.insert(endpoint_id, expires_at);
+        }
+        None => {
+            expiries.remove(&endpoint_id);
+        }
+    }
+}
+
+fn retain_cert_expiries(endpoint_ids: &[Uuid]) {
+    CERT_EXPIRIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|id, _| endpoint_ids.contains(id));
+}
+
+/// TLS証明書の有効期限モニター
+pub struct CertExpiryMonitor {
+    /// エンドポイントレジストリ
+    registry: EndpointRegistry,
+    /// HTTPクライアント（TLS情報の取得を有効化）
+    client: Client,
+    /// チェック間隔
+    check_interval: Duration,
+    /// 警告を出す残り日数
+    warning_days: i64,
+    /// 警告済みの有効期限（同じ証明書で繰り返し警告しないため）
+    warned: HashMap<Uuid, DateTime<Utc>>,
+}
+
+impl CertExpiryMonitor {
+    /// 新しいモニターを作成
+    pub fn new(registry: EndpointRegistry) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(CERT_FETCH_TIMEOUT_SECS))
+            .tls_info(true)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            registry,
+            client,
+            check_interval: Duration::from_secs(DEFAULT_CERT_CHECK_INTERVAL_SECS),
+            warning_days: crate::config::get_cert_expiry_warning_days(),
+            warned: HashMap::new(),
+        }
+    }
+
+    /// チェック間隔を設定
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+
+    /// バックグラウンドで監視を開始
+    pub fn start(mut self) {
+        tokio::spawn(async move {
+            let mut timer = interval(self.check_interval);
+            info!(
+                interval_secs = self.check_interval.as_secs(),
+                warning_days = self.warning_days,
+                "Certificate expiry monitor started"
+            );
+            loop {
+                timer.tick().await;
+                self.check_all_endpoints().await;
+            }
+        });
+    }
+
+    /// 全エンドポイントの証明書を確認する
+    async fn check_all_endpoints(&mut self) {
+        let endpoints = self.registry.list().await;
+        let ids: Vec<Uuid> = endpoints.iter().map(|ep| ep.id).collect();
+        retain_cert_expiries(&ids);
+        self.warned.retain(|id, _| ids.contains(id));
+
+        for endpoint in endpoints {
+            if !is_https(&endpoint.base_url) {
+                record_cert_expiry(endpoint.id, None);
+                continue;
+            }
+            match self.fetch_cert_expiry(&endpoint.base_url).await {
+                Some(expires_at) => {
+                    record_cert_expiry(endpoint.id, Some(expires_at));
+                    self.check_expiry(endpoint.id, &endpoint.name, expires_at, Utc::now());
+                }
+                None => {
+                    // Keep the last known expiry; a transient connection failure says nothing
+                    // about the certificate itself.
+                    debug!(
+                        endpoint_id = %endpoint.id,
+                        "Could not obtain TLS certificate, skipping"
+                    );
+                }
+            }
+        }
+    }
+
+    /// 接続してサーバ証明書の notAfter を取得する
+    async fn fetch_cert_expiry(&self, base_url: &str) -> Option<DateTime<Utc>> {
+        let response = self.client.get(base_url).send().await.ok()?;
+        let tls_info = response.extensions().get::<reqwest::tls::TlsInfo>()?;
+        parse_certificate_not_after(tls_info.peer_certificate()?)
+    }
+
+    /// 期限が近い場合に警告イベントを発行する
+    ///
+    /// 同じ有効期限に対しては1回だけ発行し、証明書が更新されたら警告状態を解除する。
+    fn check_expiry(
+        &mut self,
+        endpoint_id: Uuid,
+        name: &str,
+        expires_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let days_remaining = (expires_at - now).num_days();
+        if days_remaining > self.warning_days {
+            self.warned.remove(&endpoint_id);
+            return false;
+        }
+        if self.warned.get(&endpoint_id) == Some(&expires_at) {
+            return false;
+        }
+        self.warned.insert(endpoint_id, expires_at);
+        warn!(
+            endpoint_id = %endpoint_id,
+            endpoint_name = %name,
+            cert_expires_at = %expires_at.to_rfc3339(),
+            days_remaining,
+            "Endpoint TLS certificate is expiring soon"
+        );
+        self.registry
+            .notify_cert_expiring(endpoint_id, expires_at, days_remaining);
+        true
+    }
+}
+
+fn is_https(base_url: &str) -> bool {
+    reqwest::Url::parse(base_url)
+        .map(|url| url.scheme() == "https")
+        .unwrap_or(false)
+}
+
+/// DERエンコードされたX.509証明書から notAfter を取り出す
+///
+/// `Certificate ::= SEQUENCE { tbsCertificate SEQUENCE { [0] version OPTIONAL,
+/// serialNumber, signature, issuer, validity SEQUENCE { notBefore, notAfter }, ... } }`
+fn parse_certificate_not_after(der: &[u8]) -> Option<DateTime<Utc>> {
+    let (tag, certificate, _) = read_tlv(der)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (tag, tbs, _) = read_tlv(certificate)?;
+    if tag != 0x30 {
+        return None;
+    }
+
+    let mut rest = tbs;
+    let (tag, _, after) = read_tlv(rest)?;
+    if tag == 0xa0 {
+        // explicit version
+        rest = after;
+    }
+    // serialNumber, signature, issuer
+    for _ in 0..3 {
+        let (_, _, after) = read_tlv(rest)?;
+        rest = after;
+    }
+    let (tag, validity, _) = read_tlv(rest)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (_, _, after_not_before) = read_tlv(validity)?;
+    let (tag, not_after, _) = read_tlv(after_not_before)?;
+    parse_asn1_time(tag, not_after)
+}
+
+/// DERのTLVを1つ読み、(タグ, 値, 残り) を返す
+fn read_tlv(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, input) = input.split_first()?;
+    let (&first, mut input) = input.split_first()?;
+    let len = if first < 0x80 {
+        first as usize
+    } else {
+        let octets = (first & 0x7f) as usize;
+        if octets == 0 || octets > 4 || input.len() < octets {
+            return None;
+        }
+        let len = input[..octets]
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        input = &input[octets..];
+        len
+    };
+    if input.len() < len {
+        return None;
+    }
+    let (value, rest) = input.split_at(len);
+    Some((tag, value, rest))
+}
+
+/// UTCTime（`YYMMDDHHMMSSZ`）または GeneralizedTime（`YYYYMMDDHHMMSSZ`）を解釈する
+fn parse_asn1_time(tag: u8, value: &[u8]) -> Option<DateTime<Utc>> {
+    let text = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        // RFC 5280: YY >= 50 is 19YY, otherwise 20YY
+        0x17 if text.len() == 12 => {
+            let yy: i32 = text[..2].parse().ok()?;
+            (if yy >= 50 { 1900 + yy } else { 2000 + yy }, &text[2..])
+        }
+        0x18 if text.len() == 14 => (text[..4].parse().ok()?, &text[4..]),
+        _ => return None,
+    };
+    let field = |i: usize| -> Option<u32> { rest.get(i..i + 2)?.parse().ok() };
+    let date = NaiveDate::from_ymd_opt(year, field(0)?, field(2)?)?;
+    let time = NaiveTime::from_hms_opt(field(4)?, field(6)?, field(8)?)?;
+    Some(NaiveDateTime::new(date, time).and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::{Endpoint, EndpointType};
+    use chrono::TimeZone;
+
+    fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        if value.len() < 0x80 {
+            out.push(value.len() as u8);
+        } else {
+            out.push(0x82);
+            out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        }
+        out.extend_from_slice(value);
+        out
+    }
+
+    fn certificate(not_after: Vec<u8>) -> Vec<u8> {
+        let validity = [tlv(0x17, b"260101000000Z"), not_after].concat();
+        let tbs = [
+            tlv(0xa0, &tlv(0x02, &[2])),
+            tlv(0x02, &[0x01, 0x23]),
+            tlv(0x30, &tlv(0x06, &[0x2a, 0x86, 0x48])),
+            // issuer long enough to need a multi-byte length
+            tlv(0x30, &[0x31; 200]),
+            tlv(0x30, &validity),
+            tlv(0x30, &[]),
+        ]
+        .concat();
+        tlv(
+            0x30,
+            &[tlv(0x30, &tbs), tlv(0x30, &[]), tlv(0x03, &[0])].concat(),
+        )
+    }
+
+    #[test]
+    fn parses_not_after_from_der() {
+        let der = certificate(tlv(0x17, b"261231235959Z"));
+        assert_eq!(
+            parse_certificate_not_after(&der),
+            Some(Utc.with_ymd_and_hms(2026, 12, 31, 23, 59, 59).unwrap())
+        );
+
+        let der = certificate(tlv(0x18, b"20500102030405Z"));
+        assert_eq!(
+            parse_certificate_not_after(&der),
+            Some(Utc.with_ymd_and_hms(2050, 1, 2, 3, 4, 5).unwrap())
+        );
+
+        assert_eq!(parse_certificate_not_after(&der[..der.len() - 10]), None);
+        assert_eq!(parse_certificate_not_after(b"not a certificate"), None);
+    }
+
+    #[test]
+    fn only_https_endpoints_are_monitored() {
+        assert!(is_https("https://api.example.com/v1"));
+        assert!(!is_https("http://localhost:8080"));
+        assert!(!is_https("not a url"));
+    }
+
+    #[tokio::test]
+    async fn warns_once_per_expiring_certificate() {
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let registry = EndpointRegistry::new(pool).await.unwrap();
+        let bus = crate::events::create_shared_event_bus();
+        registry.set_event_bus(bus.clone());
+        let mut receiver = bus.subscribe();
+
+        let ep = Endpoint::new(
+            "Secure".to_string(),
+            "https://secure.example.com".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        let mut monitor = CertExpiryMonitor::new(registry);
+        monitor.warning_days = 30;
+
+        let now = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+        let far = now + chrono::Duration::days(90);
+        let near = now + chrono::Duration::days(10);
+
+        assert!(!monitor.check_expiry(ep.id, &ep.name, far, now));
+        assert!(monitor.check_expiry(ep.id, &ep.name, near, now));
+        assert!(!monitor.check_expiry(ep.id, &ep.name, near, now));
+
+        match receiver.try_recv().unwrap() {
+            crate::events::DashboardEvent::EndpointCertExpiring {
+                endpoint_id,
+                cert_expires_at,
+                days_remaining,
+            } => {
+                assert_eq!(endpoint_id, ep.id);
+                assert_eq!(cert_expires_at, near);
+                assert_eq!(days_remaining, 10);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(receiver.try_recv().is_err());
+
+        // A renewed certificate clears the warning, so the next near expiry warns again.
+        assert!(!monitor.check_expiry(ep.id, &ep.name, far, now));
+        assert!(monitor.check_expiry(ep.id, &ep.name, near, now));
+    }
+}
@@ -0,0 +1,274 @@
+//! ヘルスチェック失敗理由の分類と対処提案
+//!
+//! エンドポイントの最後のエラー（`last_error`）を分類し、分類ごとの対処提案を
+//! 静的に対応付ける。結果は `GET /api/endpoints/{id}` の `diagnosis` として返し、
+//! ダッシュボードで表示できるよう構造化データにしている。
+
+use serde::Serialize;
+use std::error::Error;
+
+/// ヘルスチェック失敗の分類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthFailureCategory {
+    /// 認証失敗（HTTP 401）
+    Unauthorized,
+    /// 権限不足（HTTP 403）
+    Forbidden,
+    /// ヘルスチェック用のパスが存在しない（HTTP 404）
+    NotFound,
+    /// レート制限（HTTP 429）
+    RateLimited,
+    /// エンドポイント側のサーバーエラー（HTTP 5xx）
+    ServerError,
+    /// 名前解決の失敗
+    DnsResolution,
+    /// 接続拒否・ポート到達不可
+    ConnectionRefused,
+    /// タイムアウト
+    Timeout,
+    /// TLS/証明書エラー
+    Tls,
+    /// 応答の形式が不正
+    InvalidResponse,
+    /// 分類できないエラー
+    Unknown,
+}
+
+impl HealthFailureCategory {
+    /// エラーメッセージから分類する
+    pub fn classify(error: &str) -> Self {
+        let error = error.to_ascii_lowercase();
+        if let Some(status) = http_status(&error) {
+            return match status {
+                401 => Self::Unauthorized,
+                403 => Self::Forbidden,
+                404 => Self::NotFound,
+                429 => Self::RateLimited,
+                500..=599 => Self::ServerError,
+                _ => Self::Unknown,
+            };
+        }
+        let contains_any = |needles: &[&str]| needles.iter().any(|n| error.contains(n));
+        if contains_any(&[
+            "dns error",
+            "failed to lookup address",
+            "name or service not known",
+            "no such host",
+            "nodename nor servname",
+        ]) {
+            Self::DnsResolution
+        } else if contains_any(&["certificate", "tls", "ssl", "handshake"]) {
+            Self::Tls
+        } else if contains_any(&["timed out", "timeout", "deadline has elapsed"]) {
+            Self::Timeout
+        } else if contains_any(&[
+            "connection refused",
+            "tcp connect error",
+            "no route to host",
+            "network is unreachable",
+            "host is unreachable",
+            "connection reset",
+            "error sending request",
+        ]) {
+            Self::ConnectionRefused
+        } else if contains_any(&[
+            "error decoding",
+            "expected value",
+            "invalid type",
+            "eof while parsing",
+        ]) {
+            Self::InvalidResponse
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// 分類の概要
+    pub fn summary(self) -> &'static str {
+        match self {
+            Self::Unauthorized => "The endpoint rejected the API key",
+            Self::Forbidden => "The API key is not allowed to access the endpoint",
+            Self::NotFound => "The health check path was not found on the endpoint",
+            Self::RateLimited => "The endpoint is rate limiting health checks",
+            Self::ServerError => "The endpoint returned a server error",
+            Self::DnsResolution => "The endpoint host name could not be resolved",
+            Self::ConnectionRefused => "The endpoint port is unreachable",
+            Self::Timeout => "The endpoint did not respond in time",
+            Self::Tls => "The TLS connection to the endpoint failed",
+            Self::InvalidResponse => "The endpoint returned an unexpected response",
+            Self::Unknown => "The health check failed for an unknown reason",
+        }
+    }
+
+    /// 分類ごとの対処提案
+    pub fn suggestions(self) -> &'static [&'static str] {
+        match self {
+            Self::Unauthorized => &[
+                "Check the API key configured for the endpoint",
+                "Make sure the key has not expired or been revoked on the provider side",
+            ],
+            Self::Forbidden => &[
+                "Check the permissions of the API key on the provider side",
+                "Make sure the endpoint allows requests from the load balancer's address",
+            ],
+            Self::NotFound => &[
+                "Check that the base URL does not include an extra path (e.g. a trailing /v1)",
+                "Make sure the endpoint serves the OpenAI-compatible /v1/models API",
+            ],
+            Self::RateLimited => &[
+                "Increase the health check interval for the endpoint",
+                "Check the rate limits of the API key on the provider side",
+            ],
+            Self::ServerError => &[
+                "Check the endpoint server logs",
+                "Make sure a model is loaded and the server finished starting up",
+            ],
+            Self::DnsResolution => &[
+                "Check the host name in the base URL",
+                "Check the DNS settings of the load balancer host",
+            ],
+            Self::ConnectionRefused => &[
+                "Make sure the endpoint server is running",
+                "Check the host and port in the base URL",
+                "Check firewall rules between the load balancer and the endpoint",
+            ],
+            Self::Timeout => &[
+                "Check the network latency to the endpoint",
+                "Check whether the endpoint is overloaded",
+            ],
+            Self::Tls => &[
+                "Check the endpoint certificate (expiry, host name, trusted CA)",
+                "Make sure the base URL scheme (http/https) matches the endpoint",
+            ],
+            Self::InvalidResponse => &[
+                "Make sure the base URL points to an OpenAI-compatible API",
+                "Check for a proxy or login page in front of the endpoint",
+            ],
+            Self::Unknown => &["Check the last error message and the endpoint server logs"],
+        }
+    }
+}
+
+/// エンドポイントの診断結果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EndpointDiagnosis {
+    /// 失敗の分類
+    pub category: HealthFailureCategory,
+    /// 分類の概要
+    pub summary: &'static str,
+    /// 対処提案
+    pub suggestions: Vec<&'static str>,
+}
+
+impl EndpointDiagnosis {
+    /// 最後のエラーから診断結果を作る（エラーが無ければ `None`）
+    pub fn from_last_error(last_error: Option<&str>) -> Option<Self> {
+        let error = last_error.filter(|e| !e.trim().is_empty())?;
+        let category = HealthFailureCategory::classify(error);
+        Some(Self {
+            category,
+            summary: category.summary(),
+            suggestions: category.suggestions().to_vec(),
+        })
+    }
+}
+
+/// エラーメッセージに原因（`source`）の連鎖を含めて文字列化する
+///
+/// reqwest のエラーは最上位のメッセージに接続拒否・名前解決失敗などの原因が含まれないため、
+/// 分類できるよう原因を `: ` 区切りで連結する。
+pub fn error_with_sources(error: &(dyn Error + 'static)) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let cause_message = cause.to_string();
+        if !message.contains(&cause_message) {
+            message.push_str(": ");
+            message.push_str(&cause_message);
+        }
+        source = cause.source();
+    }
+    message
+}
+
+/// `HTTP 401 Unauthorized` 形式のメッセージからステータスコードを取り出す
+fn http_status(error: &str) -> Option<u16> {
+    let rest = error.strip_prefix("http ")?;
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_health_check_errors() {
+        let cases = [
+            ("HTTP 401 Unauthorized", HealthFailureCategory::Unauthorized),
+            ("HTTP 404 Not Found", HealthFailureCategory::NotFound),
+            (
+                "HTTP 503 Service Unavailable",
+                HealthFailureCategory::ServerError,
+            ),
+            (
+                "error sending request for url (http://10.0.0.5:8080/v1/models): client error (Connect): tcp connect error: Connection refused (os error 111)",
+                HealthFailureCategory::ConnectionRefused,
+            ),
+            (
+                "error sending request for url (http://gpu-1:8080/v1/models): client error (Connect): dns error: failed to lookup address information",
+                HealthFailureCategory::DnsResolution,
+            ),
+            (
+                "error sending request for url (http://gpu-1:8080/v1/models): operation timed out",
+                HealthFailureCategory::Timeout,
+            ),
+            (
+                "error decoding response body: expected value at line 1 column 1",
+                HealthFailureCategory::InvalidResponse,
+            ),
+            ("something odd", HealthFailureCategory::Unknown),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(HealthFailureCategory::classify(error), expected, "{error}");
+        }
+    }
+
+    #[test]
+    fn diagnosis_is_structured_for_dashboard() {
+        assert!(EndpointDiagnosis::from_last_error(None).is_none());
+        let diagnosis = EndpointDiagnosis::from_last_error(Some("HTTP 401 Unauthorized")).unwrap();
+        let json = serde_json::to_value(&diagnosis).unwrap();
+        assert_eq!(json["category"], "unauthorized");
+        assert!(json["summary"].is_string());
+        assert_eq!(
+            json["suggestions"][0],
+            "Check the API key configured for the endpoint"
+        );
+    }
+
+    #[test]
+    fn error_with_sources_appends_causes() {
+        #[derive(Debug)]
+        struct Outer(std::io::Error);
+        impl std::fmt::Display for Outer {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "error sending request")
+            }
+        }
+        impl Error for Outer {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        let error = Outer(std::io::Error::new(
+            std::io::ErrorKind::ConnectionRefused,
+            "Connection refused",
+        ));
+        assert_eq!(
+            error_with_sources(&error),
+            "error sending request: Connection refused"
+        );
+    }
+}
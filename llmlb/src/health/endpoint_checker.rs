@@ -8,11 +8,13 @@
 //! - `/api/health`が失敗した場合、またはxLLM以外のエンドポイントでは`/v1/models`をフォールバック
 
 use crate::db::endpoints as db;
-use crate::detection::detect_endpoint_type_with_client;
+use crate::detection::detect_endpoint_type_cached;
+use crate::health::hysteresis::{HealthHysteresisSettings, HealthHysteresisUpdate, MAX_THRESHOLD};
 use crate::registry::endpoints::EndpointRegistry;
 use crate::sync;
 use crate::types::endpoint::{Endpoint, EndpointHealthCheck, EndpointStatus, EndpointType};
 use chrono::Utc;
+use futures::StreamExt;
 use reqwest::Client;
 
 /// `/api/health`から取得したGPU情報
@@ -42,8 +44,31 @@ const HEALTH_CHECK_TIMEOUT_SECS: u64 = 5;
 /// デフォルトのチェック間隔（秒）
 const DEFAULT_CHECK_INTERVAL_SECS: u64 = 30;
 
-/// オフライン判定までの連続失敗回数
-const CONSECUTIVE_FAILURES_FOR_OFFLINE: u32 = 2;
+/// 定期チェックの同時実行数
+///
+/// 多数のエンドポイントでも1周期がチェック間隔内に収まるよう並行実行しつつ、
+/// 同時接続数とレジストリへの書き込みが一度に集中しないよう上限を設ける。
+const HEALTH_CHECK_CONCURRENCY: usize = 32;
+
+/// 疎通確認の結果（レジストリへ反映する前の値）
+struct HealthProbe {
+    success: bool,
+    error_message: Option<String>,
+    new_status: EndpointStatus,
+    gpu_info: Option<GpuInfo>,
+    latency_ms: u32,
+}
+
+/// エンドポイントごとの連続成否（バックオフとヒステリシス用）
+#[derive(Debug, Default, Clone, Copy)]
+struct CheckStreak {
+    /// 連続失敗回数
+    consecutive_failures: u32,
+    /// 連続成功回数
+    consecutive_successes: u32,
+    /// 次回の定期チェックまでに読み飛ばす周期数
+    skip_ticks: u32,
+}
 
 /// エンドポイントヘルスチェッカー
 ///
@@ -63,6 +88,10 @@ pub struct EndpointHealthChecker {
     auto_sync_models_interval: Duration,
     /// エンドポイントごとの最終モデル同期時刻（スロットリング用）
     last_auto_sync_models: Arc<RwLock<HashMap<Uuid, Instant>>>,
+    /// 復帰・失敗の閾値とバックオフ係数（`/api/health/config` で変更可能）
+    hysteresis: HealthHysteresisSettings,
+    /// エンドポイントごとの連続成否
+    streaks: Arc<RwLock<HashMap<Uuid, CheckStreak>>>,
 }
 
 impl EndpointHealthChecker {
@@ -73,6 +102,7 @@ impl EndpointHealthChecker {
             .build()
             .expect("Failed to create HTTP client");
 
+        let hysteresis = registry.health_hysteresis().clone();
         Self {
             registry,
             load_manager: None,
@@ -80,6 +110,8 @@ impl EndpointHealthChecker {
             check_interval_secs: DEFAULT_CHECK_INTERVAL_SECS,
             auto_sync_models_interval: crate::config::get_auto_sync_models_interval(),
             last_auto_sync_models: Arc::new(RwLock::new(HashMap::new())),
+            hysteresis,
+            streaks: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -95,8 +127,21 @@ impl EndpointHealthChecker {
         self
     }
 
+    /// offline から online へ戻すのに必要な連続成功回数を設定（フラッピング抑制）
+    ///
+    /// 1 の場合は1回の成功で即座に online に戻す（最小値は1）。
+    /// 設定はレジストリ経由で共有されるため、`/api/health/config` の値も更新される。
+    pub fn with_recovery_threshold(self, threshold: u32) -> Self {
+        let _ = self.hysteresis.update(HealthHysteresisUpdate {
+            recovery_threshold: Some(threshold.clamp(1, MAX_THRESHOLD)),
+            ..Default::default()
+        });
+        self
+    }
+
     /// バックグラウンドで監視を開始
     pub fn start(self) {
+        self.spawn_passive_check_handler();
         tokio::spawn(async move {
             // Run an initial parallel check to converge quickly without delaying server startup.
             if let Err(e) = self.check_all_endpoints_parallel().await {
@@ -106,14 +151,36 @@ impl EndpointHealthChecker {
         });
     }
 
+    /// パッシブ検知で suspect になったエンドポイントを即時チェックするタスクを起動
+    fn spawn_passive_check_handler(&self) {
+        let passive = super::passive::passive_health();
+        if !passive.is_enabled() {
+            return;
+        }
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        passive.set_check_trigger(tx);
+        let checker = self.clone();
+        tokio::spawn(async move {
+            while let Some(endpoint_id) = rx.recv().await {
+                if let Err(e) = checker.check_endpoint_by_id(endpoint_id).await {
+                    debug!(
+                        endpoint_id = %endpoint_id,
+                        error = %e,
+                        "Immediate health check for suspect endpoint failed"
+                    );
+                }
+                // チェックできなかった場合（削除済みなど）も suspect を残さない
+                passive.resolve(endpoint_id);
+            }
+        });
+    }
+
     /// 監視ループ
     async fn monitor_loop(&self) {
-        let mut timer = interval(Duration::from_secs(self.check_interval_secs));
+        let mut interval_secs = self.check_interval_secs;
+        let mut timer = interval(Duration::from_secs(interval_secs));
 
-        info!(
-            interval_secs = self.check_interval_secs,
-            "Endpoint health checker started"
-        );
+        info!(interval_secs, "Endpoint health checker started");
 
         // `interval()` ticks immediately on the first call. Since we already performed an initial
         // startup check, wait a full interval before the next periodic check.
@@ -130,6 +197,17 @@ impl EndpointHealthChecker {
             if let Err(e) = db::cleanup_old_health_checks(self.registry.pool()).await {
                 error!("Failed to cleanup old health checks: {}", e);
             }
+
+            // SIGHUPで再読み込みされた間隔は次回のチェックから反映する
+            if let Some(reloaded) = crate::config::reloaded_config() {
+                let reloaded_secs = reloaded.health_check_interval_secs.max(1);
+                if reloaded_secs != interval_secs {
+                    interval_secs = reloaded_secs;
+                    timer = interval(Duration::from_secs(interval_secs));
+                    timer.tick().await;
+                    info!(interval_secs, "Endpoint health check interval reloaded");
+                }
+            }
         }
     }
 
@@ -137,18 +215,21 @@ impl EndpointHealthChecker {
     pub async fn check_all_endpoints(
         &self,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let endpoints = self.registry.list().await;
+        // 一覧はスナップショットとして取得し、チェック中はレジストリのロックを保持しない
+        let endpoints = self.due_endpoints(self.registry.list().await).await;
 
-        for endpoint in endpoints {
-            if let Err(e) = self.check_endpoint(&endpoint).await {
-                debug!(
-                    endpoint_id = %endpoint.id,
-                    endpoint_name = %endpoint.name,
-                    error = %e,
-                    "Health check failed"
-                );
-            }
-        }
+        futures::stream::iter(endpoints)
+            .for_each_concurrent(HEALTH_CHECK_CONCURRENCY, |endpoint| async move {
+                if let Err(e) = self.check_endpoint(&endpoint).await {
+                    debug!(
+                        endpoint_id = %endpoint.id,
+                        endpoint_name = %endpoint.name,
+                        error = %e,
+                        "Health check failed"
+                    );
+                }
+            })
+            .await;
 
         Ok(())
     }
@@ -169,37 +250,40 @@ impl EndpointHealthChecker {
             "Starting parallel health check for all endpoints"
         );
 
-        let mut handles = Vec::with_capacity(endpoints.len());
-
-        for endpoint in endpoints {
-            let checker = self.clone();
-            handles.push(tokio::spawn(async move {
-                let result = checker.check_endpoint(&endpoint).await;
-                (endpoint.id, endpoint.name.clone(), result)
-            }));
-        }
-
         let mut success_count = 0;
         let mut failure_count = 0;
 
-        for handle in handles {
-            match handle.await {
-                Ok((id, name, result)) => {
-                    if result.is_ok() {
-                        success_count += 1;
-                    } else {
+        // 依存先のチェックが終わってから依存するエンドポイントをチェックする（段ごとに並列）
+        for wave in crate::registry::startup_order::startup_waves(endpoints) {
+            let mut handles = Vec::with_capacity(wave.len());
+
+            for endpoint in wave {
+                let checker = self.clone();
+                handles.push(tokio::spawn(async move {
+                    let result = checker.check_endpoint(&endpoint).await;
+                    (endpoint.id, endpoint.name.clone(), result)
+                }));
+            }
+
+            for handle in handles {
+                match handle.await {
+                    Ok((id, name, result)) => {
+                        if result.is_ok() {
+                            success_count += 1;
+                        } else {
+                            failure_count += 1;
+                            debug!(
+                                endpoint_id = %id,
+                                endpoint_name = %name,
+                                "Parallel health check failed"
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        error!("Task join error: {}", e);
                         failure_count += 1;
-                        debug!(
-                            endpoint_id = %id,
-                            endpoint_name = %name,
-                            "Parallel health check failed"
-                        );
                     }
                 }
-                Err(e) => {
-                    error!("Task join error: {}", e);
-                    failure_count += 1;
-                }
             }
         }
 
@@ -212,6 +296,50 @@ impl EndpointHealthChecker {
         Ok(())
     }
 
+    /// バックオフ中のエンドポイントを除外する
+    ///
+    /// 連続失敗しているエンドポイントは周期を読み飛ばし、チェック間隔を指数的に延ばす。
+    async fn due_endpoints(&self, endpoints: Vec<Endpoint>) -> Vec<Endpoint> {
+        let mut streaks = self.streaks.write().await;
+        streaks.retain(|id, _| endpoints.iter().any(|ep| ep.id == *id));
+
+        endpoints
+            .into_iter()
+            .filter(|endpoint| match streaks.get_mut(&endpoint.id) {
+                Some(streak) if streak.skip_ticks > 0 => {
+                    streak.skip_ticks -= 1;
+                    debug!(
+                        endpoint_id = %endpoint.id,
+                        endpoint_name = %endpoint.name,
+                        consecutive_failures = streak.consecutive_failures,
+                        "Health check skipped due to backoff"
+                    );
+                    false
+                }
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// チェック結果を連続成否に反映する
+    ///
+    /// 失敗時は次回チェックまでの周期数をバックオフ倍率に合わせて設定し、成功時は解除する。
+    async fn record_outcome(&self, endpoint_id: Uuid, success: bool) -> CheckStreak {
+        let hysteresis = self.hysteresis.get();
+        let mut streaks = self.streaks.write().await;
+        let streak = streaks.entry(endpoint_id).or_default();
+        if success {
+            streak.consecutive_failures = 0;
+            streak.consecutive_successes = streak.consecutive_successes.saturating_add(1);
+            streak.skip_ticks = 0;
+        } else {
+            streak.consecutive_successes = 0;
+            streak.consecutive_failures = streak.consecutive_failures.saturating_add(1);
+            streak.skip_ticks = hysteresis.backoff_multiplier(streak.consecutive_failures) - 1;
+        }
+        *streak
+    }
+
     /// 単一エンドポイントのヘルスチェック
     ///
     /// Phase 1.4: xLLMのみ`/api/health`を優先的に呼び出し、GPU情報を取得。
@@ -222,93 +350,45 @@ impl EndpointHealthChecker {
         endpoint: &Endpoint,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let status_before = endpoint.status;
-        let is_xllm = matches!(endpoint.endpoint_type, EndpointType::Xllm);
-        let (success, error_message, new_status, gpu_info, latency_ms) = if is_xllm {
-            let start = Instant::now();
-            match self.try_v0_health(endpoint).await {
-                Ok(gpu_info) => (
-                    true,
-                    None,
-                    EndpointStatus::Online,
-                    Some(gpu_info),
-                    start.elapsed().as_millis() as u32,
-                ),
-                Err(_v0_error) => {
-                    debug!(
-                        endpoint_id = %endpoint.id,
-                        endpoint_name = %endpoint.name,
-                        "/api/health failed, falling back to /v1/models"
-                    );
-
-                    let start = Instant::now();
-                    match self.try_v1_models(endpoint).await {
-                        Ok(()) => {
-                            // /v1/models 成功 → online、GPU情報なし
-                            (
-                                true,
-                                None,
-                                EndpointStatus::Online,
-                                None,
-                                start.elapsed().as_millis() as u32,
-                            )
-                        }
-                        Err(e) => {
-                            // 両方失敗
-                            let error = e.to_string();
-                            let new_status = self.determine_failure_status(endpoint, status_before);
-                            (
-                                false,
-                                Some(error),
-                                new_status,
-                                None,
-                                start.elapsed().as_millis() as u32,
-                            )
-                        }
-                    }
-                }
-            }
-        } else {
-            debug!(
+        // 疎通確認はロック外で行い、結果のみを短時間ロックで反映する
+        let HealthProbe {
+            success,
+            error_message,
+            new_status,
+            gpu_info,
+            latency_ms,
+        } = self.probe(endpoint).await;
+
+        // フラッピング抑制: offline からは連続成功が閾値に達するまで online に戻さない
+        let streak = self.record_outcome(endpoint.id, success).await;
+        let hysteresis = self.hysteresis.get();
+        let new_status = if success
+            && status_before == EndpointStatus::Offline
+            && streak.consecutive_successes < hysteresis.recovery_threshold
+        {
+            info!(
                 endpoint_id = %endpoint.id,
                 endpoint_name = %endpoint.name,
-                "non-xLLM endpoint, using /v1/models directly"
+                consecutive_successes = streak.consecutive_successes,
+                recovery_threshold = hysteresis.recovery_threshold,
+                "Endpoint responded; waiting for consecutive successes before marking online"
             );
-            let start = Instant::now();
-            match self.try_v1_models(endpoint).await {
-                Ok(()) => {
-                    // /v1/models 成功 → online、GPU情報なし
-                    (
-                        true,
-                        None,
-                        EndpointStatus::Online,
-                        None,
-                        start.elapsed().as_millis() as u32,
-                    )
-                }
-                Err(e) => {
-                    let error = e.to_string();
-                    let new_status = self.determine_failure_status(endpoint, status_before);
-                    (
-                        false,
-                        Some(error),
-                        new_status,
-                        None,
-                        start.elapsed().as_millis() as u32,
-                    )
-                }
-            }
+            EndpointStatus::Offline
+        } else {
+            new_status
         };
 
-        // ステータス更新
-        if success {
-            self.registry
-                .update_status(endpoint.id, new_status, Some(latency_ms), None)
-                .await?;
-        } else {
-            self.registry
-                .update_status(endpoint.id, new_status, None, error_message.as_deref())
-                .await?;
-        }
+        self.registry
+            .apply_health_result(
+                endpoint.id,
+                new_status,
+                success.then_some(latency_ms),
+                error_message.as_deref(),
+                gpu_info.as_ref(),
+            )
+            .await?;
+        // アクティブチェックの結果で状態が確定したため、パッシブ検知の suspect を解除する
+        super::passive::passive_health().resolve(endpoint.id);
 
         if new_status != EndpointStatus::Online {
             if let Some(load_manager) = &self.load_manager {
@@ -316,31 +396,18 @@ impl EndpointHealthChecker {
             }
         }
 
-        // GPU情報更新（/api/healthから取得した場合のみ）
-        if let Some(info) = gpu_info {
-            self.registry
-                .update_gpu_info(
-                    endpoint.id,
-                    info.gpu_device_count,
-                    info.gpu_total_memory_bytes,
-                    info.gpu_used_memory_bytes,
-                    info.gpu_capability_score,
-                    info.active_requests,
-                )
-                .await;
-        }
-
         // SPEC-e8e9326e: offline→online遷移時にタイプ再検出
         let mut endpoint_type_for_auto_sync = endpoint.endpoint_type;
         let was_offline = matches!(
             status_before,
             EndpointStatus::Offline | EndpointStatus::Error
         );
-        if success && was_offline {
-            match detect_endpoint_type_with_client(
+        if new_status == EndpointStatus::Online && was_offline {
+            match detect_endpoint_type_cached(
                 &self.client,
                 &endpoint.base_url,
                 endpoint.api_key.as_deref(),
+                false,
             )
             .await
             {
@@ -410,6 +477,8 @@ impl EndpointHealthChecker {
                 endpoint_name = %endpoint.name,
                 error = ?error_message,
                 status = %new_status.as_str(),
+                consecutive_failures = streak.consecutive_failures,
+                backoff_multiplier = hysteresis.backoff_multiplier(streak.consecutive_failures),
                 "Health check failed"
             );
         }
@@ -471,6 +540,12 @@ impl EndpointHealthChecker {
                 Ok(result) => {
                     match registry.refresh_model_mappings(endpoint_id).await {
                         Ok(()) => {
+                            registry.notify_models_changed(
+                                endpoint_id,
+                                &result.added_models,
+                                &result.removed_models,
+                                &result.updated_models,
+                            );
                             // Update timestamp on successful completion.
                             last_auto_sync_models
                                 .write()
@@ -511,6 +586,95 @@ impl EndpointHealthChecker {
         });
     }
 
+    /// エンドポイントへの疎通確認のみを行う（レジストリのロックは取得しない）
+    async fn probe(&self, endpoint: &Endpoint) -> HealthProbe {
+        let status_before = endpoint.status;
+        let is_xllm = matches!(endpoint.endpoint_type, EndpointType::Xllm);
+        let (success, error_message, new_status, gpu_info, latency_ms) = if is_xllm {
+            let start = Instant::now();
+            match self.try_v0_health(endpoint).await {
+                Ok(gpu_info) => (
+                    true,
+                    None,
+                    EndpointStatus::Online,
+                    Some(gpu_info),
+                    start.elapsed().as_millis() as u32,
+                ),
+                Err(_v0_error) => {
+                    debug!(
+                        endpoint_id = %endpoint.id,
+                        endpoint_name = %endpoint.name,
+                        "/api/health failed, falling back to /v1/models"
+                    );
+
+                    let start = Instant::now();
+                    match self.try_v1_models(endpoint).await {
+                        Ok(()) => {
+                            // /v1/models 成功 → online、GPU情報なし
+                            (
+                                true,
+                                None,
+                                EndpointStatus::Online,
+                                None,
+                                start.elapsed().as_millis() as u32,
+                            )
+                        }
+                        Err(e) => {
+                            // 両方失敗
+                            let error = super::diagnosis::error_with_sources(e.as_ref());
+                            let new_status = self.determine_failure_status(endpoint, status_before);
+                            (
+                                false,
+                                Some(error),
+                                new_status,
+                                None,
+                                start.elapsed().as_millis() as u32,
+                            )
+                        }
+                    }
+                }
+            }
+        } else {
+            debug!(
+                endpoint_id = %endpoint.id,
+                endpoint_name = %endpoint.name,
+                "non-xLLM endpoint, using /v1/models directly"
+            );
+            let start = Instant::now();
+            match self.try_v1_models(endpoint).await {
+                Ok(()) => {
+                    // /v1/models 成功 → online、GPU情報なし
+                    (
+                        true,
+                        None,
+                        EndpointStatus::Online,
+                        None,
+                        start.elapsed().as_millis() as u32,
+                    )
+                }
+                Err(e) => {
+                    let error = super::diagnosis::error_with_sources(e.as_ref());
+                    let new_status = self.determine_failure_status(endpoint, status_before);
+                    (
+                        false,
+                        Some(error),
+                        new_status,
+                        None,
+                        start.elapsed().as_millis() as u32,
+                    )
+                }
+            }
+        };
+
+        HealthProbe {
+            success,
+            error_message,
+            new_status,
+            gpu_info,
+            latency_ms,
+        }
+    }
+
     /// `/api/health`を呼び出してGPU情報を取得
     async fn try_v0_health(
         &self,
@@ -582,12 +746,13 @@ impl EndpointHealthChecker {
         endpoint: &Endpoint,
         status_before: EndpointStatus,
     ) -> EndpointStatus {
+        let failure_threshold = self.hysteresis.get().failure_threshold;
         match status_before {
             // pending状態は初回失敗で即offline
             EndpointStatus::Pending => EndpointStatus::Offline,
             // online状態は連続失敗でerror→offline
             EndpointStatus::Online => {
-                if endpoint.error_count + 1 >= CONSECUTIVE_FAILURES_FOR_OFFLINE {
+                if endpoint.error_count + 1 >= failure_threshold {
                     EndpointStatus::Offline
                 } else {
                     EndpointStatus::Error
@@ -595,7 +760,7 @@ impl EndpointHealthChecker {
             }
             // error状態は連続失敗でoffline
             EndpointStatus::Error => {
-                if endpoint.error_count + 1 >= CONSECUTIVE_FAILURES_FOR_OFFLINE {
+                if endpoint.error_count + 1 >= failure_threshold {
                     EndpointStatus::Offline
                 } else {
                     EndpointStatus::Error
@@ -635,7 +800,7 @@ mod tests {
         Arc,
     };
     use std::time::{Duration, Instant};
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{method, path, path_regex};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     async fn setup_test_db() -> SqlitePool {
@@ -1061,6 +1226,146 @@ mod tests {
 
     // --- additional coverage tests ---
 
+    #[tokio::test]
+    async fn test_hysteresis_update_applies_to_running_checker() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+        let registry = EndpointRegistry::new(pool).await.unwrap();
+        let checker = EndpointHealthChecker::new(registry.clone());
+
+        let mut endpoint = Endpoint::new(
+            "Test".to_string(),
+            "http://localhost:11434".to_string(),
+            EndpointType::Xllm,
+        );
+        endpoint.error_count = 1;
+        assert_eq!(
+            checker.determine_failure_status(&endpoint, EndpointStatus::Online),
+            EndpointStatus::Offline
+        );
+
+        // レジストリ経由の変更は作成済みのチェッカーにも即時反映される
+        registry
+            .health_hysteresis()
+            .update(HealthHysteresisUpdate {
+                failure_threshold: Some(3),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            checker.determine_failure_status(&endpoint, EndpointStatus::Online),
+            EndpointStatus::Error
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failing_endpoint_is_checked_with_backoff_and_reset_on_recovery() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+        let registry = EndpointRegistry::new(pool).await.unwrap();
+
+        let mock = MockServer::start().await;
+        let healthy = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let hits = Arc::new(AtomicUsize::new(0));
+        let (healthy_clone, hits_clone) = (healthy.clone(), hits.clone());
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(move |_: &wiremock::Request| {
+                hits_clone.fetch_add(1, Ordering::SeqCst);
+                if healthy_clone.load(Ordering::SeqCst) {
+                    ResponseTemplate::new(200).set_body_json(json!({"data": []}))
+                } else {
+                    ResponseTemplate::new(503)
+                }
+            })
+            .mount(&mock)
+            .await;
+
+        let endpoint = Endpoint::new(
+            "Flaky".to_string(),
+            mock.uri(),
+            EndpointType::OpenaiCompatible,
+        );
+        registry.add(endpoint.clone()).await.unwrap();
+        let checker = EndpointHealthChecker::new(registry.clone());
+
+        // 失敗1回目は次の周期、2回目は2周期後、3回目は4周期後にチェックする
+        let mut checked_ticks = Vec::new();
+        for tick in 0..8 {
+            let before = hits.load(Ordering::SeqCst);
+            checker.check_all_endpoints().await.unwrap();
+            if hits.load(Ordering::SeqCst) > before {
+                checked_ticks.push(tick);
+            }
+        }
+        assert_eq!(checked_ticks, vec![0, 1, 3, 7]);
+
+        // 手動チェックで復帰したら固定間隔に戻る
+        healthy.store(true, Ordering::SeqCst);
+        checker.check_endpoint_by_id(endpoint.id).await.unwrap();
+        let before = hits.load(Ordering::SeqCst);
+        checker.check_all_endpoints().await.unwrap();
+        assert!(hits.load(Ordering::SeqCst) > before);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_threshold_requires_consecutive_successes() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+        let registry = EndpointRegistry::new(pool).await.unwrap();
+        let bus = crate::events::create_shared_event_bus();
+        registry.set_event_bus(bus.clone());
+        let mut receiver = bus.subscribe();
+
+        let mock = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"data": []})))
+            .mount(&mock)
+            .await;
+
+        let mut endpoint = Endpoint::new(
+            "Recovering".to_string(),
+            mock.uri(),
+            EndpointType::OpenaiCompatible,
+        );
+        endpoint.status = EndpointStatus::Offline;
+        registry.add(endpoint.clone()).await.unwrap();
+
+        let checker = EndpointHealthChecker::new(registry.clone()).with_recovery_threshold(3);
+        for _ in 0..2 {
+            let current = registry.get(endpoint.id).await.unwrap();
+            checker.check_endpoint(&current).await.unwrap();
+            assert_eq!(
+                registry.get(endpoint.id).await.unwrap().status,
+                EndpointStatus::Offline
+            );
+        }
+        let current = registry.get(endpoint.id).await.unwrap();
+        checker.check_endpoint(&current).await.unwrap();
+        assert_eq!(
+            registry.get(endpoint.id).await.unwrap().status,
+            EndpointStatus::Online
+        );
+
+        // 遷移は1回だけ通知され、offline のままの再確認ではイベントを出さない
+        let mut transitions = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            if let crate::events::DashboardEvent::EndpointStatusChanged {
+                old_status,
+                new_status,
+                ..
+            } = event
+            {
+                transitions.push((old_status, new_status));
+            }
+        }
+        assert_eq!(
+            transitions,
+            vec![(EndpointStatus::Offline, EndpointStatus::Online)]
+        );
+    }
+
     #[test]
     fn test_gpu_info_default() {
         let info = GpuInfo::default();
@@ -1196,6 +1501,47 @@ mod tests {
         assert_eq!(updated.status, EndpointStatus::Offline);
     }
 
+    #[tokio::test]
+    async fn test_check_all_endpoints_runs_concurrently_without_blocking_registry_reads() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+        let registry = EndpointRegistry::new(pool).await.unwrap();
+
+        let mock = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path_regex(r"^/ep\d+/v1/models$"))
+            .respond_with(ResponseTemplate::new(503).set_delay(Duration::from_millis(300)))
+            .mount(&mock)
+            .await;
+
+        let endpoint_count = 8;
+        for i in 0..endpoint_count {
+            let endpoint = Endpoint::new(
+                format!("Slow {}", i),
+                format!("{}/ep{}", mock.uri(), i),
+                EndpointType::OpenaiCompatible,
+            );
+            registry.add(endpoint).await.unwrap();
+        }
+
+        let checker = EndpointHealthChecker::new(registry.clone());
+        let started = Instant::now();
+        let task = tokio::spawn(async move { checker.check_all_endpoints().await.is_ok() });
+
+        // チェック実行中でもレジストリの読み取りは待たされない
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let read_started = Instant::now();
+        assert_eq!(registry.list().await.len(), endpoint_count);
+        assert!(read_started.elapsed() < Duration::from_millis(100));
+
+        assert!(task.await.unwrap());
+        // 逐次実行なら 8 × 300ms 以上かかる
+        assert!(started.elapsed() < Duration::from_millis(300 * endpoint_count as u64));
+        for endpoint in registry.list().await {
+            assert_eq!(endpoint.status, EndpointStatus::Offline);
+        }
+    }
+
     #[tokio::test]
     async fn test_health_check_non_xllm_failure_from_online() {
         let _lock = TEST_LOCK.lock().await;
@@ -0,0 +1,185 @@
+//! ヘルスチェックのヒステリシス設定
+//!
+//! オフライン判定・復帰判定の閾値とバックオフ係数を保持する。
+//! 値はプロセス内で共有され、`/api/health/config` から変更すると次回のチェックから即時に反映される
+//! （再起動すると既定値に戻る）。
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+/// 既定の復帰判定に必要な連続成功回数
+pub const DEFAULT_RECOVERY_THRESHOLD: u32 = 1;
+
+/// 既定のオフライン判定までの連続失敗回数
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 2;
+
+/// 既定のバックオフ係数（連続失敗ごとにチェック間隔を何倍にするか）
+pub const DEFAULT_BACKOFF_FACTOR: u32 = 2;
+
+/// 連続成功・連続失敗の閾値の上限
+pub const MAX_THRESHOLD: u32 = 100;
+
+/// 連続失敗時のバックオフ倍率の上限（チェック間隔の何倍まで延ばすか）
+pub const MAX_BACKOFF_MULTIPLIER: u32 = 16;
+
+/// ヒステリシス設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HealthHysteresis {
+    /// offline から online へ戻すのに必要な連続成功回数
+    pub recovery_threshold: u32,
+    /// online/error から offline にするまでの連続失敗回数
+    pub failure_threshold: u32,
+    /// 連続失敗時のバックオフ係数（1 でバックオフ無効）
+    pub backoff_factor: u32,
+}
+
+impl Default for HealthHysteresis {
+    fn default() -> Self {
+        Self {
+            recovery_threshold: DEFAULT_RECOVERY_THRESHOLD,
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            backoff_factor: DEFAULT_BACKOFF_FACTOR,
+        }
+    }
+}
+
+impl HealthHysteresis {
+    /// 設定値を検証する
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_THRESHOLD).contains(&self.recovery_threshold) {
+            return Err(format!(
+                "recovery_threshold must be between 1 and {}",
+                MAX_THRESHOLD
+            ));
+        }
+        if !(1..=MAX_THRESHOLD).contains(&self.failure_threshold) {
+            return Err(format!(
+                "failure_threshold must be between 1 and {}",
+                MAX_THRESHOLD
+            ));
+        }
+        if !(1..=MAX_BACKOFF_MULTIPLIER).contains(&self.backoff_factor) {
+            return Err(format!(
+                "backoff_factor must be between 1 and {}",
+                MAX_BACKOFF_MULTIPLIER
+            ));
+        }
+        Ok(())
+    }
+
+    /// 連続失敗回数に応じたバックオフ倍率（1, f, f², ... 最大 `MAX_BACKOFF_MULTIPLIER`）
+    pub fn backoff_multiplier(&self, consecutive_failures: u32) -> u32 {
+        let exponent = consecutive_failures.saturating_sub(1);
+        self.backoff_factor
+            .max(1)
+            .checked_pow(exponent)
+            .unwrap_or(u32::MAX)
+            .min(MAX_BACKOFF_MULTIPLIER)
+    }
+}
+
+/// ヒステリシス設定の部分更新（指定した項目のみ変更）
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HealthHysteresisUpdate {
+    /// offline から online へ戻すのに必要な連続成功回数
+    pub recovery_threshold: Option<u32>,
+    /// online/error から offline にするまでの連続失敗回数
+    pub failure_threshold: Option<u32>,
+    /// 連続失敗時のバックオフ係数
+    pub backoff_factor: Option<u32>,
+}
+
+/// 共有されるヒステリシス設定（クローンは同じ値を参照する）
+#[derive(Debug, Clone, Default)]
+pub struct HealthHysteresisSettings {
+    inner: Arc<RwLock<HealthHysteresis>>,
+}
+
+impl HealthHysteresisSettings {
+    /// 現在の設定
+    pub fn get(&self) -> HealthHysteresis {
+        *self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 設定を部分更新し、(変更前, 変更後) を返す
+    ///
+    /// 不正な値が含まれる場合は何も変更せずにエラーを返す。
+    pub fn update(
+        &self,
+        update: HealthHysteresisUpdate,
+    ) -> Result<(HealthHysteresis, HealthHysteresis), String> {
+        let mut current = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        let previous = *current;
+        let next = HealthHysteresis {
+            recovery_threshold: update
+                .recovery_threshold
+                .unwrap_or(previous.recovery_threshold),
+            failure_threshold: update
+                .failure_threshold
+                .unwrap_or(previous.failure_threshold),
+            backoff_factor: update.backoff_factor.unwrap_or(previous.backoff_factor),
+        };
+        next.validate()?;
+        *current = next;
+        Ok((previous, next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_multiplier_follows_factor_and_is_capped() {
+        let default = HealthHysteresis::default();
+        assert_eq!(default.backoff_multiplier(0), 1);
+        assert_eq!(default.backoff_multiplier(1), 1);
+        assert_eq!(default.backoff_multiplier(2), 2);
+        assert_eq!(default.backoff_multiplier(3), 4);
+        assert_eq!(default.backoff_multiplier(5), MAX_BACKOFF_MULTIPLIER);
+        assert_eq!(default.backoff_multiplier(u32::MAX), MAX_BACKOFF_MULTIPLIER);
+
+        let triple = HealthHysteresis {
+            backoff_factor: 3,
+            ..default
+        };
+        assert_eq!(triple.backoff_multiplier(3), 9);
+
+        let disabled = HealthHysteresis {
+            backoff_factor: 1,
+            ..default
+        };
+        assert_eq!(disabled.backoff_multiplier(10), 1);
+    }
+
+    #[test]
+    fn invalid_update_keeps_current_settings() {
+        let settings = HealthHysteresisSettings::default();
+        let (previous, next) = settings
+            .update(HealthHysteresisUpdate {
+                recovery_threshold: Some(3),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(previous, HealthHysteresis::default());
+        assert_eq!(next.recovery_threshold, 3);
+
+        for update in [
+            HealthHysteresisUpdate {
+                failure_threshold: Some(0),
+                ..Default::default()
+            },
+            HealthHysteresisUpdate {
+                recovery_threshold: Some(5),
+                backoff_factor: Some(MAX_BACKOFF_MULTIPLIER + 1),
+                ..Default::default()
+            },
+        ] {
+            assert!(settings.update(update).is_err());
+        }
+        assert_eq!(settings.get(), next);
+        // クローンは同じ設定を参照する
+        assert_eq!(settings.clone().get().recovery_threshold, 3);
+    }
+}
@@ -7,6 +7,14 @@
 //! PULL型ヘルスチェックを提供する。llmlbは各エンドポイントの健康状態を確認する
 //! （xLLMのみ `/api/health` を優先利用し、非xLLMは `/v1/models` を用いてヘルスチェックする）。
 
+pub mod cert_monitor;
+pub mod diagnosis;
 pub mod endpoint_checker;
+pub mod hysteresis;
+pub mod passive;
 
+pub use cert_monitor::CertExpiryMonitor;
+pub use diagnosis::{EndpointDiagnosis, HealthFailureCategory};
 pub use endpoint_checker::EndpointHealthChecker;
+pub use hysteresis::{HealthHysteresis, HealthHysteresisSettings, HealthHysteresisUpdate};
+pub use passive::PassiveHealthMonitor;
@@ -0,0 +1,234 @@
+//! 実トラフィックのエラーによるパッシブヘルス検知
+//!
+//! `LoadManager` に記録されたリクエスト結果を監視し、短時間にエラーが連続した
+//! エンドポイントを次の定期チェックを待たずに suspect 状態にする。
+//!
+//! - `LLMLB_PASSIVE_HEALTH_WINDOW_SECS` 以内に `LLMLB_PASSIVE_HEALTH_MIN_SAMPLES` 回
+//!   連続でエラーになった場合に suspect とする（1回でも成功すれば連続数をリセット）
+//! - suspect 中のエンドポイントは他に候補がある限りルーティング対象から後回しにする
+//! - suspect になった時点で即時のアクティブチェックを要求し、その結果（online/offline）で確定する
+
+use crate::types::endpoint::Endpoint;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedSender;
+use uuid::Uuid;
+
+/// プロセス全体のパッシブヘルス検知
+static PASSIVE_HEALTH: Lazy<PassiveHealthMonitor> = Lazy::new(|| {
+    PassiveHealthMonitor::new(
+        crate::config::passive_health_min_samples(),
+        crate::config::passive_health_window(),
+    )
+});
+
+/// プロセス全体のパッシブヘルス検知を取得
+pub fn passive_health() -> &'static PassiveHealthMonitor {
+    &PASSIVE_HEALTH
+}
+
+/// エンドポイントごとの連続エラー
+#[derive(Debug, Clone, Copy)]
+struct ErrorStreak {
+    /// 連続エラー数
+    count: u32,
+    /// 連続の最初のエラー時刻
+    first_error_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct PassiveHealthState {
+    streaks: HashMap<Uuid, ErrorStreak>,
+    /// suspect 中のエンドポイント
+    suspects: HashSet<Uuid>,
+    /// 即時アクティブチェックの要求先（ヘルスチェッカー起動時に登録）
+    check_trigger: Option<UnboundedSender<Uuid>>,
+}
+
+/// 実トラフィックのエラーによるパッシブヘルス検知
+#[derive(Debug)]
+pub struct PassiveHealthMonitor {
+    /// suspect にする最小連続エラー数（0 で無効）
+    min_samples: u32,
+    /// 連続エラーを数える時間幅
+    window: Duration,
+    state: Mutex<PassiveHealthState>,
+}
+
+impl PassiveHealthMonitor {
+    /// 最小サンプル数と時間幅を指定して作成
+    pub fn new(min_samples: u32, window: Duration) -> Self {
+        Self {
+            min_samples,
+            window,
+            state: Mutex::new(PassiveHealthState::default()),
+        }
+    }
+
+    /// パッシブ検知が有効か
+    pub fn is_enabled(&self) -> bool {
+        self.min_samples > 0
+    }
+
+    /// 即時アクティブチェックの要求先を登録する
+    pub fn set_check_trigger(&self, trigger: UnboundedSender<Uuid>) {
+        self.lock().check_trigger = Some(trigger);
+    }
+
+    /// リクエスト結果を記録し、新たに suspect になった場合は `true` を返す
+    pub fn record(&self, endpoint_id: Uuid, success: bool) -> bool {
+        self.record_at(endpoint_id, success, Instant::now())
+    }
+
+    fn record_at(&self, endpoint_id: Uuid, success: bool, now: Instant) -> bool {
+        if !self.is_enabled() {
+            return false;
+        }
+        let mut state = self.lock();
+        if success {
+            state.streaks.remove(&endpoint_id);
+            return false;
+        }
+        if state.suspects.contains(&endpoint_id) {
+            return false;
+        }
+
+        let streak = state.streaks.entry(endpoint_id).or_insert(ErrorStreak {
+            count: 0,
+            first_error_at: now,
+        });
+        if now.saturating_duration_since(streak.first_error_at) > self.window {
+            *streak = ErrorStreak {
+                count: 0,
+                first_error_at: now,
+            };
+        }
+        streak.count = streak.count.saturating_add(1);
+        let count = streak.count;
+        if count < self.min_samples {
+            return false;
+        }
+
+        state.streaks.remove(&endpoint_id);
+        state.suspects.insert(endpoint_id);
+        let triggered = state
+            .check_trigger
+            .as_ref()
+            .is_some_and(|trigger| trigger.send(endpoint_id).is_ok());
+        tracing::warn!(
+            endpoint_id = %endpoint_id,
+            consecutive_errors = count,
+            window_secs = self.window.as_secs(),
+            triggered_check = triggered,
+            "Endpoint marked as suspect after consecutive request errors"
+        );
+        true
+    }
+
+    /// suspect 中か
+    pub fn is_suspect(&self, endpoint_id: Uuid) -> bool {
+        self.lock().suspects.contains(&endpoint_id)
+    }
+
+    /// アクティブチェックの結果で suspect を解除する
+    pub fn resolve(&self, endpoint_id: Uuid) {
+        let mut state = self.lock();
+        state.streaks.remove(&endpoint_id);
+        if state.suspects.remove(&endpoint_id) {
+            tracing::info!(
+                endpoint_id = %endpoint_id,
+                "Suspect endpoint resolved by active health check"
+            );
+        }
+    }
+
+    /// suspect のエンドポイントを候補から後回しにする
+    ///
+    /// suspect 以外の候補が残る場合はそれらのみを返し、全候補が suspect の場合はそのまま返す。
+    pub fn deprioritize(&self, endpoints: Vec<Endpoint>) -> Vec<Endpoint> {
+        let state = self.lock();
+        if state.suspects.is_empty() || endpoints.iter().all(|ep| state.suspects.contains(&ep.id)) {
+            return endpoints;
+        }
+        endpoints
+            .into_iter()
+            .filter(|ep| !state.suspects.contains(&ep.id))
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PassiveHealthState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::EndpointType;
+
+    fn endpoint(name: &str) -> Endpoint {
+        Endpoint::new(
+            name.to_string(),
+            format!("http://{}:8080", name),
+            EndpointType::OpenaiCompatible,
+        )
+    }
+
+    #[test]
+    fn consecutive_errors_within_window_mark_suspect() {
+        let monitor = PassiveHealthMonitor::new(3, Duration::from_secs(10));
+        let id = Uuid::new_v4();
+        let start = Instant::now();
+
+        // 成功を挟むと連続数はリセットされる
+        assert!(!monitor.record_at(id, false, start));
+        assert!(!monitor.record_at(id, false, start));
+        assert!(!monitor.record_at(id, true, start));
+        assert!(!monitor.record_at(id, false, start));
+        assert!(!monitor.record_at(id, false, start));
+        // 時間幅を過ぎたエラーは新しい連続として数える
+        let later = start + Duration::from_secs(11);
+        assert!(!monitor.record_at(id, false, later));
+        assert!(!monitor.is_suspect(id));
+        assert!(!monitor.record_at(id, false, later));
+        assert!(monitor.record_at(id, false, later));
+        assert!(monitor.is_suspect(id));
+
+        monitor.resolve(id);
+        assert!(!monitor.is_suspect(id));
+    }
+
+    #[test]
+    fn suspect_triggers_active_check_and_is_deprioritized() {
+        let monitor = PassiveHealthMonitor::new(2, Duration::from_secs(10));
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        monitor.set_check_trigger(tx);
+        let healthy = endpoint("healthy");
+        let flaky = endpoint("flaky");
+
+        monitor.record(flaky.id, false);
+        assert!(monitor.record(flaky.id, false));
+        assert_eq!(rx.try_recv().ok(), Some(flaky.id));
+        // suspect 中の追加エラーではチェックを重複して要求しない
+        assert!(!monitor.record(flaky.id, false));
+        assert!(rx.try_recv().is_err());
+
+        let selected = monitor.deprioritize(vec![flaky.clone(), healthy.clone()]);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].id, healthy.id);
+        // suspect しか候補が無い場合は除外しない
+        assert_eq!(monitor.deprioritize(vec![flaky]).len(), 1);
+    }
+
+    #[test]
+    fn zero_min_samples_disables_detection() {
+        let monitor = PassiveHealthMonitor::new(0, Duration::from_secs(10));
+        let id = Uuid::new_v4();
+        for _ in 0..10 {
+            assert!(!monitor.record(id, false));
+        }
+        assert!(!monitor.is_suspect(id));
+    }
+}
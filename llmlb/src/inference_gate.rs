@@ -2,6 +2,8 @@
 //!
 //! - Tracks in-flight `/v1/*` inference requests (including streaming).
 //! - When rejecting is enabled, new requests are rejected with 503.
+//! - Optionally caps concurrent requests per model. Over the cap, requests
+//!   either wait for a free slot or are rejected with 429 ([`ModelLimitMode`]).
 
 use axum::{
     body::{Body, Bytes},
@@ -14,15 +16,39 @@ use axum::{
 use http_body::{Body as HttpBody, Frame, SizeHint};
 use serde_json::json;
 use std::{
+    collections::HashMap,
     pin::Pin,
     sync::{
         atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::sync::Notify;
 
+/// Behavior when a model's concurrency limit is reached.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ModelLimitMode {
+    /// Reject the request with 429.
+    #[default]
+    Reject,
+    /// Wait for a free slot, rejecting with 429 once `timeout` elapses.
+    Queue {
+        /// Maximum time to wait for a slot.
+        timeout: Duration,
+    },
+}
+
+/// Reason a request could not be admitted by the gate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GateRejection {
+    /// The gate is draining for self-update (503).
+    Updating,
+    /// The model's concurrency limit is reached (429).
+    ModelLimitReached,
+}
+
 /// Gate shared across the server.
 #[derive(Clone, Debug, Default)]
 pub struct InferenceGate {
@@ -36,6 +62,16 @@ struct InferenceGateInner {
     idle_notify: Notify,
     abort_generation: AtomicU64,
     abort_notify: Notify,
+    model_limit_mode: Mutex<ModelLimitMode>,
+    model_slots: Mutex<HashMap<String, ModelSlots>>,
+    model_release_notify: Notify,
+}
+
+#[derive(Debug, Default)]
+struct ModelSlots {
+    /// Maximum concurrent requests (`0` = unlimited).
+    max: usize,
+    in_flight: usize,
 }
 
 impl InferenceGate {
@@ -50,8 +86,11 @@ impl InferenceGate {
     }
 
     /// Begin rejecting new inference requests.
+    ///
+    /// Requests waiting for a per-model slot are woken and rejected as well.
     pub fn start_rejecting(&self) {
         self.inner.rejecting.store(true, Ordering::SeqCst);
+        self.inner.model_release_notify.notify_waiters();
     }
 
     /// Stop rejecting new inference requests.
@@ -75,6 +114,93 @@ impl InferenceGate {
         self.inner.abort_notify.notify_waiters();
     }
 
+    /// Set the concurrency limit for a model (`0` removes the limit).
+    ///
+    /// Models without a limit are only bounded by the gate-wide behavior.
+    /// Lowering a limit does not affect requests that are already running.
+    pub fn set_model_limit(&self, model_id: &str, max: usize) {
+        let mut slots = self.model_slots();
+        if max == 0 {
+            if let Some(entry) = slots.get_mut(model_id) {
+                entry.max = 0;
+                if entry.in_flight == 0 {
+                    slots.remove(model_id);
+                }
+            }
+        } else {
+            slots.entry(model_id.to_string()).or_default().max = max;
+        }
+        drop(slots);
+        self.inner.model_release_notify.notify_waiters();
+    }
+
+    /// Return the concurrency limit for a model, if any.
+    pub fn model_limit(&self, model_id: &str) -> Option<usize> {
+        self.model_slots()
+            .get(model_id)
+            .map(|entry| entry.max)
+            .filter(|max| *max > 0)
+    }
+
+    /// Return the number of in-flight requests admitted for a model.
+    pub fn model_in_flight(&self, model_id: &str) -> usize {
+        self.model_slots()
+            .get(model_id)
+            .map(|entry| entry.in_flight)
+            .unwrap_or(0)
+    }
+
+    /// Set the behavior when a model's concurrency limit is reached.
+    pub fn set_model_limit_mode(&self, mode: ModelLimitMode) {
+        *self
+            .inner
+            .model_limit_mode
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = mode;
+    }
+
+    /// Return the behavior when a model's concurrency limit is reached.
+    pub fn model_limit_mode(&self) -> ModelLimitMode {
+        *self
+            .inner
+            .model_limit_mode
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Admit a request for a model, honoring its concurrency limit.
+    ///
+    /// While the gate is rejecting, every request is refused with
+    /// [`GateRejection::Updating`], including ones waiting for a slot.
+    /// The returned guard releases the slot on drop.
+    pub async fn begin_for_model(&self, model_id: &str) -> Result<ModelSlotGuard, GateRejection> {
+        let deadline = match self.model_limit_mode() {
+            ModelLimitMode::Reject => None,
+            ModelLimitMode::Queue { timeout } => Some(tokio::time::Instant::now() + timeout),
+        };
+
+        loop {
+            // Register the waiter first to avoid a lost wakeup between the check
+            // and waiting on `Notify`.
+            let notified = self.inner.model_release_notify.notified();
+            if self.is_rejecting() {
+                return Err(GateRejection::Updating);
+            }
+            if self.try_acquire_model_slot(model_id) {
+                return Ok(ModelSlotGuard {
+                    gate: self.clone(),
+                    model_id: model_id.to_string(),
+                });
+            }
+            let Some(deadline) = deadline else {
+                return Err(GateRejection::ModelLimitReached);
+            };
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return Err(GateRejection::ModelLimitReached);
+            }
+        }
+    }
+
     /// Simulate beginning an in-flight request (for testing only).
     ///
     /// Returns a guard that decrements the counter on drop.
@@ -97,6 +223,35 @@ impl InferenceGate {
         }
     }
 
+    fn model_slots(&self) -> std::sync::MutexGuard<'_, HashMap<String, ModelSlots>> {
+        self.inner
+            .model_slots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn try_acquire_model_slot(&self, model_id: &str) -> bool {
+        let mut slots = self.model_slots();
+        let entry = slots.entry(model_id.to_string()).or_default();
+        if entry.max > 0 && entry.in_flight >= entry.max {
+            return false;
+        }
+        entry.in_flight += 1;
+        true
+    }
+
+    fn release_model_slot(&self, model_id: &str) {
+        let mut slots = self.model_slots();
+        if let Some(entry) = slots.get_mut(model_id) {
+            entry.in_flight = entry.in_flight.saturating_sub(1);
+            if entry.max == 0 && entry.in_flight == 0 {
+                slots.remove(model_id);
+            }
+        }
+        drop(slots);
+        self.inner.model_release_notify.notify_waiters();
+    }
+
     fn abort_generation(&self) -> u64 {
         self.inner.abort_generation.load(Ordering::SeqCst)
     }
@@ -143,6 +298,19 @@ impl Drop for InFlightGuard {
     }
 }
 
+/// Guard for a per-model slot; releases the slot on drop.
+#[derive(Debug)]
+pub struct ModelSlotGuard {
+    gate: InferenceGate,
+    model_id: String,
+}
+
+impl Drop for ModelSlotGuard {
+    fn drop(&mut self) {
+        self.gate.release_model_slot(&self.model_id);
+    }
+}
+
 #[derive(Debug)]
 struct InFlightBody {
     inner: Body,
@@ -174,6 +342,33 @@ impl HttpBody for InFlightBody {
     }
 }
 
+/// Response body that holds a per-model slot until the body is finished or dropped.
+#[derive(Debug)]
+struct ModelSlotBody {
+    inner: Body,
+    _guard: ModelSlotGuard,
+}
+
+impl HttpBody for ModelSlotBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_frame(cx)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
 fn service_unavailable_updating_response() -> Response {
     let mut response = (
         StatusCode::SERVICE_UNAVAILABLE,
@@ -196,6 +391,69 @@ fn service_unavailable_updating_response() -> Response {
     response
 }
 
+fn model_limit_reached_response(model_id: &str) -> Response {
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(json!({
+            "error": {
+                "message": format!("Concurrency limit reached for model: {}", model_id),
+                "type": "rate_limit_exceeded",
+                "code": StatusCode::TOO_MANY_REQUESTS.as_u16(),
+            }
+        })),
+    )
+        .into_response();
+
+    if let Ok(value) = HeaderValue::from_str("1") {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static("retry-after"), value);
+    }
+
+    response
+}
+
+/// Middleware that enforces per-model concurrency limits.
+///
+/// The model is read from the body parsed by
+/// [`crate::api::body_inspection::inspect_body_middleware`], which must wrap
+/// this layer. Requests without a model (or with a non-JSON body) pass
+/// through unchanged. The slot is held until
+/// the response body, including streaming bodies, is finished or dropped.
+pub async fn model_concurrency_middleware(
+    State(gate): State<InferenceGate>,
+    req: axum::extract::Request,
+    next: Next,
+) -> Response {
+    let Some(model) = req
+        .extensions()
+        .get::<crate::api::body_inspection::InspectedBody>()
+        .and_then(|body| body.model().map(str::to_string))
+    else {
+        return next.run(req).await;
+    };
+
+    let guard = match gate.begin_for_model(&model).await {
+        Ok(guard) => guard,
+        Err(GateRejection::Updating) => return service_unavailable_updating_response(),
+        Err(GateRejection::ModelLimitReached) => {
+            tracing::warn!(
+                model = %model,
+                limit = gate.model_limit(&model).unwrap_or(0),
+                "Rejected request: model concurrency limit reached"
+            );
+            return model_limit_reached_response(&model);
+        }
+    };
+
+    let (parts, body) = next.run(req).await.into_parts();
+    let body = Body::new(ModelSlotBody {
+        inner: body,
+        _guard: guard,
+    });
+    Response::from_parts(parts, body)
+}
+
 /// Middleware that counts in-flight inference requests and rejects new ones when draining.
 pub async fn inference_gate_middleware(
     State(gate): State<InferenceGate>,
@@ -382,4 +640,93 @@ mod tests {
         );
         assert_eq!(gate.in_flight(), 0);
     }
+
+    #[tokio::test]
+    async fn begin_for_model_rejects_over_limit_and_releases_on_drop() {
+        let gate = InferenceGate::default();
+        gate.set_model_limit("heavy", 1);
+
+        let first = gate.begin_for_model("heavy").await.expect("first slot");
+        assert_eq!(gate.model_in_flight("heavy"), 1);
+        assert_eq!(
+            gate.begin_for_model("heavy").await.unwrap_err(),
+            GateRejection::ModelLimitReached
+        );
+
+        // Models without a limit are not capped.
+        let _a = gate.begin_for_model("light").await.expect("unlimited");
+        let _b = gate.begin_for_model("light").await.expect("unlimited");
+        assert_eq!(gate.model_limit("light"), None);
+
+        drop(first);
+        assert_eq!(gate.model_in_flight("heavy"), 0);
+        let _second = gate.begin_for_model("heavy").await.expect("slot released");
+    }
+
+    #[tokio::test]
+    async fn begin_for_model_queue_waits_for_slot_and_drain_rejects_waiters() {
+        let gate = InferenceGate::default();
+        gate.set_model_limit("heavy", 1);
+        gate.set_model_limit_mode(ModelLimitMode::Queue {
+            timeout: Duration::from_secs(5),
+        });
+
+        let first = gate.begin_for_model("heavy").await.expect("first slot");
+        let gate2 = gate.clone();
+        let waiter = tokio::spawn(async move { gate2.begin_for_model("heavy").await.map(|_| ()) });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+        drop(first);
+        assert_eq!(waiter.await.unwrap(), Ok(()));
+
+        let held = gate.begin_for_model("heavy").await.expect("slot");
+        let gate2 = gate.clone();
+        let waiter = tokio::spawn(async move { gate2.begin_for_model("heavy").await.map(|_| ()) });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        gate.start_rejecting();
+        assert_eq!(waiter.await.unwrap(), Err(GateRejection::Updating));
+        drop(held);
+
+        // While rejecting, every model is refused as before.
+        assert_eq!(
+            gate.begin_for_model("light").await.unwrap_err(),
+            GateRejection::Updating
+        );
+    }
+
+    #[tokio::test]
+    async fn model_concurrency_middleware_returns_429_and_holds_slot_until_body_drop() {
+        let gate = InferenceGate::default();
+        gate.set_model_limit("heavy", 1);
+
+        let app = Router::new()
+            .route("/v1/test", post(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                gate.clone(),
+                model_concurrency_middleware,
+            ))
+            .layer(middleware::from_fn(
+                crate::api::body_inspection::inspect_body_middleware,
+            ));
+        let request = || {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/test")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"model":"heavy"}"#))
+                .unwrap()
+        };
+
+        let first = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(gate.model_in_flight("heavy"), 1);
+
+        let second = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        drop(first);
+        assert_eq!(gate.model_in_flight("heavy"), 0);
+        let third = app.oneshot(request()).await.unwrap();
+        assert_eq!(third.status(), StatusCode::OK);
+    }
 }
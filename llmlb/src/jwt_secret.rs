@@ -2,11 +2,20 @@
 //!
 //! Provides automatic generation and file-based persistence of JWT secrets.
 //! The secret is stored in `~/.llmlb/jwt_secret` with permissions 600.
+//!
+//! Secrets can be rotated at runtime. Retired secrets are kept in
+//! `~/.llmlb/jwt_secret.retired.json` and remain valid for verification
+//! until their grace period expires, so tokens issued before the rotation
+//! keep working.
 
 use crate::config::get_env_with_fallback;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 use uuid::Uuid;
 
 #[cfg(unix)]
@@ -14,8 +23,15 @@ use std::os::unix::fs::PermissionsExt;
 
 /// Default JWT secret file name
 const JWT_SECRET_FILE: &str = "jwt_secret";
+/// Retired JWT secrets file name
+const RETIRED_SECRETS_FILE: &str = "jwt_secret.retired.json";
 /// Default data directory name
 const DATA_DIR: &str = ".llmlb";
+/// Default grace period for retired secrets (matches the JWT lifetime)
+pub const DEFAULT_ROTATION_GRACE_SECS: u64 = 24 * 60 * 60;
+
+/// Process-wide keyring, initialized at startup
+static KEYRING: Lazy<RwLock<Option<JwtKeyring>>> = Lazy::new(|| RwLock::new(None));
 
 /// Get or create the JWT secret
 ///
@@ -36,11 +52,9 @@ const DATA_DIR: &str = ".llmlb";
 /// ```
 pub fn get_or_create_jwt_secret() -> io::Result<String> {
     // 1. Check environment variable first
-    if let Some(secret) = get_env_with_fallback("LLMLB_JWT_SECRET", "JWT_SECRET") {
-        if !secret.is_empty() {
-            tracing::info!("Using JWT secret from environment variable");
-            return Ok(secret);
-        }
+    if let Some(secret) = secret_from_env() {
+        tracing::info!("Using JWT secret from environment variable");
+        return Ok(secret);
     }
 
     // 2. Try to read from file
@@ -79,7 +93,7 @@ fn generate_secret() -> String {
 }
 
 /// Read the secret from file
-fn read_secret_file(path: &PathBuf) -> io::Result<String> {
+fn read_secret_file(path: &Path) -> io::Result<String> {
     let mut file = File::open(path)?;
     let mut secret = String::new();
     file.read_to_string(&mut secret)?;
@@ -87,7 +101,7 @@ fn read_secret_file(path: &PathBuf) -> io::Result<String> {
 }
 
 /// Write the secret to file with secure permissions (600)
-fn write_secret_file(path: &PathBuf, secret: &str) -> io::Result<()> {
+fn write_secret_file(path: &Path, secret: &str) -> io::Result<()> {
     // Ensure parent directory exists
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
@@ -113,6 +127,250 @@ fn write_secret_file(path: &PathBuf, secret: &str) -> io::Result<()> {
     Ok(())
 }
 
+/// A previous JWT secret that is still accepted for verification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetiredJwtSecret {
+    /// The retired secret
+    pub secret: String,
+    /// When the secret was replaced
+    pub retired_at: DateTime<Utc>,
+    /// When the secret stops being accepted
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Outcome of a secret rotation (contains no secret material)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JwtRotation {
+    /// When the rotation happened
+    pub rotated_at: DateTime<Utc>,
+    /// When the previous secret stops being accepted
+    pub previous_secret_expires_at: DateTime<Utc>,
+    /// Number of retired secrets still accepted for verification
+    pub retained_previous_secrets: usize,
+}
+
+/// Current and retired JWT secrets
+///
+/// New tokens are always signed with the current secret. Verification accepts
+/// the current secret and every retired secret whose grace period has not expired.
+#[derive(Debug, Clone)]
+pub struct JwtKeyring {
+    current: String,
+    retired: Vec<RetiredJwtSecret>,
+    /// Directory holding the secret files (`None` when the secret comes from the environment)
+    storage_dir: Option<PathBuf>,
+}
+
+impl JwtKeyring {
+    /// Build a keyring for `current`, loading retired secrets from `storage_dir`
+    ///
+    /// Expired retired secrets are dropped from the file while loading.
+    pub fn load(current: String, storage_dir: Option<PathBuf>) -> io::Result<Self> {
+        let retired = match &storage_dir {
+            Some(dir) => read_retired_file(&dir.join(RETIRED_SECRETS_FILE))?,
+            None => Vec::new(),
+        };
+        let mut keyring = Self {
+            current,
+            retired,
+            storage_dir,
+        };
+        if keyring.prune(Utc::now()) > 0 {
+            keyring.persist_retired()?;
+        }
+        Ok(keyring)
+    }
+
+    /// The secret used to sign new tokens
+    pub fn signing_secret(&self) -> &str {
+        &self.current
+    }
+
+    /// Secrets accepted for verification at `now`, current secret first
+    pub fn verification_secrets(&self, now: DateTime<Utc>) -> Vec<String> {
+        std::iter::once(self.current.clone())
+            .chain(
+                self.retired
+                    .iter()
+                    .filter(|r| r.expires_at > now)
+                    .map(|r| r.secret.clone()),
+            )
+            .collect()
+    }
+
+    /// Replace the current secret with a newly generated one
+    ///
+    /// The previous secret stays valid for verification for `grace`.
+    /// Both files are written before the in-memory state changes, so a failed
+    /// write leaves the keyring untouched.
+    ///
+    /// # Errors
+    /// * `Unsupported` - The secret is provided by an environment variable
+    /// * Any I/O error while writing the secret files
+    pub fn rotate(
+        &mut self,
+        grace: chrono::Duration,
+        now: DateTime<Utc>,
+    ) -> io::Result<JwtRotation> {
+        let dir = self.storage_dir.clone().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "JWT secret is provided by LLMLB_JWT_SECRET; rotate it via the environment",
+            )
+        })?;
+
+        let expires_at = now + grace;
+        let mut retired: Vec<RetiredJwtSecret> = self
+            .retired
+            .iter()
+            .filter(|r| r.expires_at > now)
+            .cloned()
+            .collect();
+        retired.push(RetiredJwtSecret {
+            secret: self.current.clone(),
+            retired_at: now,
+            expires_at,
+        });
+        let new_secret = generate_secret();
+
+        // Persist the retired list first so the old secret is never lost
+        write_retired_file(&dir.join(RETIRED_SECRETS_FILE), &retired)?;
+        write_secret_file(&dir.join(JWT_SECRET_FILE), &new_secret)?;
+
+        self.current = new_secret;
+        self.retired = retired;
+
+        Ok(JwtRotation {
+            rotated_at: now,
+            previous_secret_expires_at: expires_at,
+            retained_previous_secrets: self.retired.len(),
+        })
+    }
+
+    /// Drop retired secrets that expired at `now`, returning how many were removed
+    pub fn prune(&mut self, now: DateTime<Utc>) -> usize {
+        let before = self.retired.len();
+        self.retired.retain(|r| r.expires_at > now);
+        before - self.retired.len()
+    }
+
+    fn persist_retired(&self) -> io::Result<()> {
+        match &self.storage_dir {
+            Some(dir) => write_retired_file(&dir.join(RETIRED_SECRETS_FILE), &self.retired),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Initialize the process-wide keyring
+///
+/// Must be called with the secret returned by [`get_or_create_jwt_secret`].
+/// Rotation is disabled when the secret comes from the environment.
+pub fn init_keyring(current: &str) -> io::Result<()> {
+    let storage_dir = if secret_from_env().is_some() {
+        None
+    } else {
+        get_jwt_secret_path()?.parent().map(Path::to_path_buf)
+    };
+    let keyring = JwtKeyring::load(current.to_string(), storage_dir)?;
+    *KEYRING.write().unwrap_or_else(|e| e.into_inner()) = Some(keyring);
+    Ok(())
+}
+
+/// Secret for signing new tokens
+///
+/// Falls back to `fallback` (usually `AppState::jwt_secret`) when the keyring
+/// has not been initialized, e.g. in tests.
+pub fn signing_secret(fallback: &str) -> String {
+    KEYRING
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|k| k.signing_secret().to_string())
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+/// Secrets accepted for verification, current secret first
+///
+/// Falls back to `[fallback]` when the keyring has not been initialized.
+pub fn verification_secrets(fallback: &str) -> Vec<String> {
+    KEYRING
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|k| k.verification_secrets(Utc::now()))
+        .unwrap_or_else(|| vec![fallback.to_string()])
+}
+
+/// Rotate the process-wide JWT secret
+///
+/// # Errors
+/// * `NotFound` - The keyring has not been initialized
+/// * See [`JwtKeyring::rotate`]
+pub fn rotate_jwt_secret(grace: chrono::Duration) -> io::Result<JwtRotation> {
+    let mut guard = KEYRING.write().unwrap_or_else(|e| e.into_inner());
+    let keyring = guard
+        .as_mut()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "JWT keyring is not initialized"))?;
+    let rotation = keyring.rotate(grace, Utc::now())?;
+    tracing::info!(
+        previous_secret_expires_at = %rotation.previous_secret_expires_at,
+        "Rotated JWT secret"
+    );
+    Ok(rotation)
+}
+
+/// Remove expired retired secrets from the process-wide keyring and its file
+pub fn prune_expired_secrets() -> io::Result<usize> {
+    let mut guard = KEYRING.write().unwrap_or_else(|e| e.into_inner());
+    let Some(keyring) = guard.as_mut() else {
+        return Ok(0);
+    };
+    let removed = keyring.prune(Utc::now());
+    if removed > 0 {
+        keyring.persist_retired()?;
+        tracing::info!(removed, "Discarded expired JWT secrets");
+    }
+    Ok(removed)
+}
+
+/// Spawn a background task that periodically discards expired retired secrets
+pub fn start_prune_task(interval: std::time::Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = prune_expired_secrets() {
+                tracing::warn!("Failed to prune expired JWT secrets: {}", e);
+            }
+        }
+    });
+}
+
+/// Get the non-empty secret from the environment, if any
+fn secret_from_env() -> Option<String> {
+    get_env_with_fallback("LLMLB_JWT_SECRET", "JWT_SECRET").filter(|s| !s.is_empty())
+}
+
+/// Read retired secrets from file (missing file means none)
+fn read_retired_file(path: &Path) -> io::Result<Vec<RetiredJwtSecret>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write retired secrets to file with secure permissions (600)
+fn write_retired_file(path: &Path, retired: &[RetiredJwtSecret]) -> io::Result<()> {
+    let content = serde_json::to_string_pretty(retired)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write_secret_file(path, &content)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,4 +434,46 @@ mod tests {
 
         std::env::remove_var("JWT_SECRET");
     }
+
+    #[test]
+    fn test_keyring_rotation_keeps_previous_secret_during_grace() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path().to_path_buf();
+        write_secret_file(&dir.join(JWT_SECRET_FILE), "old-secret").unwrap();
+
+        let mut keyring = JwtKeyring::load("old-secret".to_string(), Some(dir.clone())).unwrap();
+        let now = Utc::now();
+        let rotation = keyring.rotate(chrono::Duration::hours(1), now).unwrap();
+
+        let new_secret = keyring.signing_secret().to_string();
+        assert_ne!(new_secret, "old-secret");
+        assert_eq!(rotation.retained_previous_secrets, 1);
+        assert_eq!(
+            read_secret_file(&dir.join(JWT_SECRET_FILE)).unwrap(),
+            new_secret
+        );
+        assert_eq!(
+            keyring.verification_secrets(now),
+            vec![new_secret.clone(), "old-secret".to_string()]
+        );
+
+        // Retired secrets survive a reload
+        let reloaded = JwtKeyring::load(new_secret.clone(), Some(dir)).unwrap();
+        assert_eq!(reloaded.verification_secrets(now).len(), 2);
+
+        // After the grace period only the new secret is accepted
+        let later = now + chrono::Duration::hours(2);
+        assert_eq!(keyring.verification_secrets(later), vec![new_secret]);
+        assert_eq!(keyring.prune(later), 1);
+    }
+
+    #[test]
+    fn test_keyring_rotation_rejected_for_env_secret() {
+        let mut keyring = JwtKeyring::load("env-secret".to_string(), None).unwrap();
+        let err = keyring
+            .rotate(chrono::Duration::hours(1), Utc::now())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(keyring.signing_secret(), "env-secret");
+    }
 }
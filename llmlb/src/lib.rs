@@ -101,6 +101,9 @@ pub mod bootstrap;
 /// axumサーバー起動・シャットダウン
 pub mod server;
 
+/// 起動時のエンドポイントウォームアップ
+pub mod warmup;
+
 /// アプリケーション状態
 #[derive(Clone)]
 pub struct AppState {
@@ -5,7 +5,7 @@
 //! # 機能
 //!
 //! - クロスプラットフォームファイルロック（fs2）
-//! - ロックファイルにJSON形式でPID・起動時刻・ポートを記録
+//! - ロックファイルにJSON形式でPID・起動時刻・ポート・バインドアドレスを記録
 //! - 残留ロックの自動検出と解除（PID検証）
 //! - グレースフルシャットダウン対応（Dropトレイト）
 
@@ -13,7 +13,7 @@ use chrono::{DateTime, Utc};
 use fs2::FileExt;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
-use std::io::Write;
+use std::io::{Seek, SeekFrom, Write};
 use std::path::PathBuf;
 use tracing::{debug, warn};
 
@@ -26,6 +26,9 @@ pub struct LockInfo {
     pub started_at: DateTime<Utc>,
     /// リッスンポート番号
     pub port: u16,
+    /// バインドしたホストアドレス（記録前・旧バージョンのロックファイルでは `None`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
 }
 
 /// ロック操作に関するエラー型
@@ -191,6 +194,7 @@ pub fn list_all_locks() -> Vec<LockInfo> {
                                     pid: 0,
                                     started_at: chrono::Utc::now(),
                                     port,
+                                    host: None,
                                 });
                             }
                             _ => {}
@@ -255,6 +259,31 @@ pub fn stop_process(pid: u32) -> Result<(), std::io::Error> {
     }
 }
 
+/// 指定PIDのプロセスを強制終了する
+///
+/// # Arguments
+///
+/// * `pid` - 強制終了対象のプロセスID
+///
+/// # Platform
+///
+/// - Unix: SIGKILL を送信
+/// - Windows: taskkill /PID /F を実行（[`stop_process`] と同じ）
+#[cfg(unix)]
+pub fn kill_process(pid: u32) -> Result<(), std::io::Error> {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+
+    kill(Pid::from_raw(pid as i32), Signal::SIGKILL)
+        .map_err(|e| std::io::Error::other(e.to_string()))
+}
+
+/// 指定PIDのプロセスを強制終了する (Windows版)
+#[cfg(windows)]
+pub fn kill_process(pid: u32) -> Result<(), std::io::Error> {
+    stop_process(pid)
+}
+
 /// サーバーのファイルロックを管理する構造体
 ///
 /// RAIIパターンでロック解除を保証します。
@@ -342,14 +371,11 @@ impl ServerLock {
             pid: std::process::id(),
             started_at: Utc::now(),
             port,
+            host: None,
         };
 
         // JSON形式で書き込み
-        let json = serde_json::to_string_pretty(&info)
-            .map_err(|e| LockError::AcquireFailed(std::io::Error::other(e)))?;
-        file.write_all(json.as_bytes())
-            .map_err(LockError::AcquireFailed)?;
-        file.flush().map_err(LockError::AcquireFailed)?;
+        write_lock_info(&mut file, &info).map_err(LockError::AcquireFailed)?;
 
         // パーミッションを600に設定（Unixのみ）
         #[cfg(unix)]
@@ -373,6 +399,17 @@ impl ServerLock {
         &self.info
     }
 
+    /// バインドしたホストアドレスをロックファイルに記録する
+    ///
+    /// `llmlb status` が実際のリッスンアドレスを表示・接続に使う。
+    pub fn record_host(&mut self, host: &str) -> Result<(), LockError> {
+        self.info.host = Some(host.to_string());
+        if let Some(file) = self.lock_file.as_mut() {
+            write_lock_info(file, &self.info).map_err(LockError::AcquireFailed)?;
+        }
+        Ok(())
+    }
+
     /// ロックを明示的に解除する
     ///
     /// この関数を呼び出すと、ロックが解除されロックファイルが削除されます。
@@ -399,6 +436,15 @@ impl ServerLock {
     }
 }
 
+/// ロックファイルの内容を `info` で置き換える
+fn write_lock_info(file: &mut File, info: &LockInfo) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(info).map_err(std::io::Error::other)?;
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(json.as_bytes())?;
+    file.flush()
+}
+
 impl Drop for ServerLock {
     fn drop(&mut self) {
         if let Err(e) = self.release_internal() {
@@ -420,6 +466,7 @@ mod tests {
             pid: 12345,
             started_at: Utc.with_ymd_and_hms(2026, 1, 30, 12, 0, 0).unwrap(),
             port: 8000,
+            host: None,
         };
 
         // シリアライズ
@@ -438,6 +485,7 @@ mod tests {
             pid: 12345,
             started_at: Utc.with_ymd_and_hms(2026, 1, 30, 12, 0, 0).unwrap(),
             port: 8000,
+            host: None,
         };
 
         let json = serde_json::to_string_pretty(&info).expect("Failed to serialize");
@@ -510,6 +558,7 @@ mod tests {
             pid: 12345,
             started_at: Utc::now(),
             port,
+            host: None,
         };
         std::fs::write(&path, serde_json::to_string(&info).unwrap()).unwrap();
 
@@ -581,6 +630,25 @@ mod tests {
         assert!(!path.exists());
     }
 
+    #[test]
+    fn test_server_lock_record_host_rewrites_lock_file() {
+        let port = 57770;
+        let path = lock_path(port);
+        std::fs::remove_file(&path).ok();
+
+        let mut lock = ServerLock::acquire(port).unwrap();
+        lock.record_host("192.0.2.10").unwrap();
+        assert_eq!(lock.info().host.as_deref(), Some("192.0.2.10"));
+
+        #[cfg(not(windows))]
+        {
+            let info = read_lock_info(port).unwrap().unwrap();
+            assert_eq!(info.host.as_deref(), Some("192.0.2.10"));
+            assert_eq!(info.pid, std::process::id());
+        }
+        drop(lock);
+    }
+
     // T014: 重複ロック取得テスト
     #[test]
     fn test_server_lock_acquire_already_running() {
@@ -656,6 +724,7 @@ mod tests {
             pid: u32::MAX - 1, // 存在しないPID
             started_at: Utc::now(),
             port,
+            host: None,
         };
         std::fs::write(&path, serde_json::to_string(&stale_info).unwrap()).unwrap();
 
@@ -798,11 +867,13 @@ mod tests {
             pid: 100,
             started_at: ts,
             port: 8000,
+            host: None,
         };
         let b = LockInfo {
             pid: 100,
             started_at: ts,
             port: 8000,
+            host: None,
         };
         assert_eq!(a, b);
     }
@@ -814,11 +885,13 @@ mod tests {
             pid: 100,
             started_at: ts,
             port: 8000,
+            host: None,
         };
         let b = LockInfo {
             pid: 200,
             started_at: ts,
             port: 8000,
+            host: None,
         };
         assert_ne!(a, b);
     }
@@ -830,11 +903,13 @@ mod tests {
             pid: 100,
             started_at: ts,
             port: 8000,
+            host: None,
         };
         let b = LockInfo {
             pid: 100,
             started_at: ts,
             port: 9000,
+            host: None,
         };
         assert_ne!(a, b);
     }
@@ -848,6 +923,7 @@ mod tests {
             pid: 42,
             started_at: Utc::now(),
             port: 3000,
+            host: None,
         };
         let cloned = info.clone();
         assert_eq!(info, cloned);
@@ -1023,6 +1099,7 @@ mod tests {
             pid: 99999,
             started_at: Utc.with_ymd_and_hms(2026, 6, 15, 8, 30, 0).unwrap(),
             port: 12345,
+            host: None,
         };
 
         let pretty = serde_json::to_string_pretty(&info).unwrap();
@@ -1068,6 +1145,7 @@ mod tests {
             pid: u32::MAX - 1,
             started_at: Utc::now(),
             port,
+            host: None,
         };
         std::fs::write(&path, serde_json::to_string(&stale_info).unwrap()).unwrap();
 
@@ -11,7 +11,9 @@ use std::{
     sync::OnceLock,
 };
 use tracing_appender::{non_blocking, non_blocking::WorkerGuard};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
 
 /// ログファイルベース名（JSON Lines）
 pub const LOG_FILE_BASE: &str = "llmlb.jsonl";
@@ -32,6 +34,7 @@ const LEGACY_LOG_RETENTION_DAYS_ENV: &str = "LLM_LOG_RETENTION_DAYS";
 const ALT_LEVEL_ENV: &str = "RUST_LOG";
 
 static LOGGER_GUARD: OnceLock<Result<LoggerGuard, io::Error>> = OnceLock::new();
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
 
 struct LoggerGuard {
     _file_guard: WorkerGuard,
@@ -45,6 +48,18 @@ pub fn init() -> io::Result<()> {
     }
 }
 
+/// 実行中のログレベルを差し替える（`EnvFilter` の記法）。
+///
+/// ロガーが未初期化の場合は何もしない。
+pub fn set_log_level(directives: &str) -> io::Result<()> {
+    let filter = EnvFilter::try_new(directives)
+        .map_err(|err| Error::new(ErrorKind::InvalidInput, err.to_string()))?;
+    match FILTER_HANDLE.get() {
+        Some(handle) => handle.reload(filter).map_err(Error::other),
+        None => Ok(()),
+    }
+}
+
 /// ログディレクトリのパスを返す。
 pub fn log_dir() -> io::Result<PathBuf> {
     // 新しい環境変数名を優先
@@ -165,8 +180,12 @@ fn configure_logger() -> io::Result<LoggerGuard> {
         .with_file(false)
         .with_line_number(false);
 
+    // SIGHUPによる再読み込みでログレベルを差し替えられるようにする
+    let (filter_layer, filter_handle) = reload::Layer::new(env_filter);
+    let _ = FILTER_HANDLE.set(filter_handle);
+
     tracing_subscriber::registry()
-        .with(env_filter)
+        .with(filter_layer)
         .with(file_layer)
         .with(stdout_layer)
         .try_init()
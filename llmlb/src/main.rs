@@ -2,8 +2,9 @@
 
 use clap::Parser;
 use llmlb::cli::{Cli, Commands};
-use llmlb::config::ServerConfig;
+use llmlb::config::{ConfigFileLoad, ServerConfig};
 use llmlb::logging;
+use std::path::Path;
 
 #[cfg(any(target_os = "windows", target_os = "macos"))]
 fn main() {
@@ -39,9 +40,13 @@ fn main() {
         }
         Some(Commands::Status(args)) => {
             let runtime = tokio::runtime::Runtime::new().expect("Failed to create runtime");
-            if let Err(e) = runtime.block_on(llmlb::cli::status::execute(&args)) {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+            match runtime.block_on(llmlb::cli::status::execute(&args)) {
+                Ok(0) => {}
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
             }
             return;
         }
@@ -53,13 +58,40 @@ fn main() {
             }
             return;
         }
+        Some(Commands::Audit(args)) => {
+            let runtime = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            if let Err(e) = runtime.block_on(llmlb::cli::audit::execute(&args.command)) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Stats(args)) => {
+            let runtime = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            if let Err(e) = runtime.block_on(llmlb::cli::stats::execute(&args.command)) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Endpoints(args)) => {
+            let runtime = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            if let Err(e) = runtime.block_on(llmlb::cli::endpoints::execute(&args.command)) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
         Some(Commands::Serve(args)) => {
+            let config_file = load_config_file(cli.config.as_deref());
             logging::init().expect("failed to initialize logging");
             use llmlb::gui::tray::{run_with_system_tray, TrayOptions};
             use std::thread;
             use tokio::runtime::Builder;
 
             let config = ServerConfig::from_args(args.host, args.port);
+            config_file.log();
+            llmlb::config::log_effective_config(&config, &config_file);
             if args.no_tray {
                 let runtime = Builder::new_multi_thread()
                     .enable_all()
@@ -97,12 +129,15 @@ fn main() {
         }
     }
 
+    let config_file = load_config_file(cli.config.as_deref());
     logging::init().expect("failed to initialize logging");
     use llmlb::gui::tray::{run_with_system_tray, TrayOptions};
     use std::thread;
     use tokio::runtime::Builder;
 
     let config = ServerConfig::from_env();
+    config_file.log();
+    llmlb::config::log_effective_config(&config, &config_file);
     let tray_options = TrayOptions::new(&config.base_url(), &config.dashboard_url());
     let fallback_config = config.clone();
 
@@ -128,12 +163,17 @@ fn main() {
 }
 
 #[cfg(not(any(target_os = "windows", target_os = "macos")))]
-#[tokio::main]
-async fn main() {
+fn main() {
     let cli = Cli::parse();
+    let runtime = || {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build Tokio runtime")
+    };
 
     // Handle subcommands
-    match cli.command {
+    let (host, port) = match cli.command {
         Some(Commands::Internal(args)) => {
             logging::init().expect("failed to initialize logging");
             if let Err(e) = llmlb::cli::internal::execute(args.command) {
@@ -143,51 +183,101 @@ async fn main() {
             return;
         }
         Some(Commands::Stop(args)) => {
-            if let Err(e) = llmlb::cli::stop::execute(&args).await {
+            if let Err(e) = runtime().block_on(llmlb::cli::stop::execute(&args)) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
             return;
         }
         Some(Commands::Status(args)) => {
-            if let Err(e) = llmlb::cli::status::execute(&args).await {
+            match runtime().block_on(llmlb::cli::status::execute(&args)) {
+                Ok(0) => {}
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Commands::Assistant(args)) => {
+            if let Err(e) = runtime().block_on(llmlb::cli::assistant::execute(&args.command)) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
             return;
         }
-        Some(Commands::Assistant(args)) => {
-            if let Err(e) = llmlb::cli::assistant::execute(&args.command).await {
+        Some(Commands::Audit(args)) => {
+            if let Err(e) = runtime().block_on(llmlb::cli::audit::execute(&args.command)) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
             return;
         }
-        Some(Commands::Serve(args)) => {
-            logging::init().expect("failed to initialize logging");
-            let cfg = ServerConfig::from_args(args.host, args.port);
-            run_server(cfg).await;
+        Some(Commands::Stats(args)) => {
+            if let Err(e) = runtime().block_on(llmlb::cli::stats::execute(&args.command)) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
             return;
         }
-        None => {
-            // No subcommand - default to serve
-            logging::init().expect("failed to initialize logging");
-            let cfg = ServerConfig::from_env();
-            run_server(cfg).await;
+        Some(Commands::Endpoints(args)) => {
+            if let Err(e) = runtime().block_on(llmlb::cli::endpoints::execute(&args.command)) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Serve(args)) => (args.host, args.port),
+        // No subcommand - default to serve
+        None => (None, None),
+    };
+
+    // 設定ファイルは環境変数として適用するため、ランタイムのワーカースレッドを起動する前に読み込む
+    let config_file = load_config_file(cli.config.as_deref());
+    logging::init().expect("failed to initialize logging");
+    let cfg = ServerConfig::from_args(host, port);
+    config_file.log();
+    llmlb::config::log_effective_config(&cfg, &config_file);
+    runtime().block_on(run_server(cfg));
+}
+
+/// 設定ファイルを環境変数として適用する（読み込めない場合は終了する）
+fn load_config_file(path: Option<&Path>) -> ConfigFileLoad {
+    match llmlb::config::load_config_file(path) {
+        Ok(config_file) => config_file,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
         }
     }
 }
 
 #[cfg(any(target_os = "windows", target_os = "macos"))]
 async fn run_server(config: ServerConfig, tray_proxy: Option<llmlb::gui::tray::TrayEventProxy>) {
-    let ctx = llmlb::bootstrap::initialize(config.port, tray_proxy).await;
+    let mut ctx = llmlb::bootstrap::initialize(config.port, tray_proxy).await;
+    record_listen_host(&mut ctx, &config.host);
+    if llmlb::config::warmup_on_start() {
+        llmlb::warmup::spawn_startup_warmup(ctx.state.clone());
+    }
     llmlb::server::run(ctx.state, &config.bind_addr()).await;
     // ctx._server_lock はここでDropされ、ロックが解除される
 }
 
+/// `llmlb status` 用にバインドするホストをロックファイルへ記録する
+fn record_listen_host(ctx: &mut llmlb::bootstrap::InitContext, host: &str) {
+    if let Err(err) = ctx._server_lock.record_host(host) {
+        tracing::warn!("Failed to record listen host in lock file: {err}");
+    }
+}
+
 #[cfg(not(any(target_os = "windows", target_os = "macos")))]
 async fn run_server(config: ServerConfig) {
-    let ctx = llmlb::bootstrap::initialize(config.port).await;
+    let mut ctx = llmlb::bootstrap::initialize(config.port).await;
+    record_listen_host(&mut ctx, &config.host);
+    if llmlb::config::warmup_on_start() {
+        llmlb::warmup::spawn_startup_warmup(ctx.state.clone());
+    }
     llmlb::server::run(ctx.state, &config.bind_addr()).await;
     // ctx._server_lock はここでDropされ、ロックが解除される
 }
@@ -0,0 +1,280 @@
+//! Prometheus形式のメトリクス公開（`GET /metrics`）
+//!
+//! エンドポイント別のリクエスト数（成功/失敗）・レイテンシヒストグラムはリクエスト完了時に
+//! 記録し、処理中リクエスト数とモデル別TPSはスクレイプ時点の値をゲージとして出力する。
+//! プロンプトキャッシュのヒット率は入力トークン数とキャッシュ読み出しトークン数の比で求める。
+//! カウンタはプロセス内で単調増加し、サーバ再起動でリセットされる。
+
+use crate::common::ip::{
+    has_forwarding_headers, is_trusted_proxy, normalize_socket_ip, resolve_trusted_client_ip,
+};
+use crate::common::protocol::TpsApiKind;
+use crate::AppState;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, TextEncoder,
+};
+use std::net::SocketAddr;
+use uuid::Uuid;
+
+/// エンドポイントレイテンシヒストグラムのバケット（秒）
+const LATENCY_BUCKETS: &[f64] = &[
+    0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0,
+];
+
+static ENDPOINT_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "llmlb_endpoint_requests_total",
+            "Requests completed per endpoint since process start",
+        ),
+        &["endpoint_id", "result"],
+    )
+    .expect("counter vec");
+    super::registry().register(Box::new(counter.clone())).ok();
+    counter
+});
+
+static ENDPOINT_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
+    let opts = HistogramOpts::new(
+        "llmlb_endpoint_request_duration_seconds",
+        "Request duration per endpoint (seconds)",
+    )
+    .buckets(LATENCY_BUCKETS.to_vec());
+    let histogram = HistogramVec::new(opts, &["endpoint_id"]).expect("histogram vec");
+    super::registry().register(Box::new(histogram.clone())).ok();
+    histogram
+});
+
+static ENDPOINT_ACTIVE_REQUESTS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    let gauge = IntGaugeVec::new(
+        Opts::new(
+            "llmlb_endpoint_active_requests",
+            "Requests currently in flight per endpoint",
+        ),
+        &["endpoint_id", "endpoint_name"],
+    )
+    .expect("gauge vec");
+    super::registry().register(Box::new(gauge.clone())).ok();
+    gauge
+});
+
+static MODEL_TPS: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new(
+            "llmlb_model_tps",
+            "Output tokens per second (EMA) per endpoint and model",
+        ),
+        &["endpoint_id", "model", "api_kind"],
+    )
+    .expect("gauge vec");
+    super::registry().register(Box::new(gauge.clone())).ok();
+    gauge
+});
+
+static PROMPT_CACHE_INPUT_TOKENS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "llmlb_prompt_cache_input_tokens_total",
+            "Input tokens of responses reporting prompt cache usage, per endpoint",
+        ),
+        &["endpoint_id"],
+    )
+    .expect("counter vec");
+    super::registry().register(Box::new(counter.clone())).ok();
+    counter
+});
+
+static PROMPT_CACHE_READ_TOKENS: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "llmlb_prompt_cache_read_tokens_total",
+            "Input tokens served from the upstream prompt cache, per endpoint",
+        ),
+        &["endpoint_id"],
+    )
+    .expect("counter vec");
+    super::registry().register(Box::new(counter.clone())).ok();
+    counter
+});
+
+/// エンドポイントへのリクエスト完了を記録する
+pub fn record_endpoint_request(endpoint_id: Uuid, success: bool, duration_ms: u64) {
+    let endpoint_id = endpoint_id.to_string();
+    let result = if success { "success" } else { "error" };
+    ENDPOINT_REQUESTS
+        .with_label_values(&[endpoint_id.as_str(), result])
+        .inc();
+    ENDPOINT_LATENCY
+        .with_label_values(&[endpoint_id.as_str()])
+        .observe(duration_ms as f64 / 1000.0);
+}
+
+/// プロンプトキャッシュの利用状況を記録する（usage にキャッシュ情報を含む応答のみ）
+pub fn record_prompt_cache_usage(endpoint_id: Uuid, input_tokens: u64, cached_tokens: u64) {
+    let endpoint_id = endpoint_id.to_string();
+    PROMPT_CACHE_INPUT_TOKENS
+        .with_label_values(&[endpoint_id.as_str()])
+        .inc_by(input_tokens);
+    PROMPT_CACHE_READ_TOKENS
+        .with_label_values(&[endpoint_id.as_str()])
+        .inc_by(cached_tokens);
+}
+
+fn api_kind_label(api_kind: TpsApiKind) -> &'static str {
+    match api_kind {
+        TpsApiKind::ChatCompletions => "chat_completions",
+        TpsApiKind::Completions => "completions",
+        TpsApiKind::Responses => "responses",
+    }
+}
+
+/// スクレイプ時点の処理中リクエスト数・モデル別TPSをゲージへ反映する
+///
+/// 削除されたエンドポイントやモデルの系列が残らないよう、毎回リセットしてから設定する。
+async fn refresh_gauges(state: &AppState) {
+    let snapshots = state.load_manager.snapshots().await;
+
+    ENDPOINT_ACTIVE_REQUESTS.reset();
+    MODEL_TPS.reset();
+    for snapshot in &snapshots {
+        let endpoint_id = snapshot.endpoint_id.to_string();
+        ENDPOINT_ACTIVE_REQUESTS
+            .with_label_values(&[endpoint_id.as_str(), snapshot.machine_name.as_str()])
+            .set(i64::from(snapshot.active_requests));
+
+        for info in state.load_manager.get_model_tps(snapshot.endpoint_id).await {
+            if let Some(tps) = info.tps {
+                MODEL_TPS
+                    .with_label_values(&[
+                        endpoint_id.as_str(),
+                        info.model_id.as_str(),
+                        api_kind_label(info.api_kind),
+                    ])
+                    .set(tps);
+            }
+        }
+    }
+}
+
+/// GET /metrics - Prometheusテキスト形式でメトリクスを返す
+pub async fn export_metrics(State(state): State<AppState>) -> Response {
+    refresh_gauges(&state).await;
+
+    let encoder = TextEncoder::new();
+    let mut buf = Vec::new();
+    if let Err(e) = encoder.encode(&super::registry().gather(), &mut buf) {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("encode error: {e}"),
+        )
+            .into_response();
+    }
+    (
+        [(header::CONTENT_TYPE, encoder.format_type().to_string())],
+        buf,
+    )
+        .into_response()
+}
+
+/// ループバックからの接続のみ通すミドルウェア（`LLMLB_METRICS_AUTH=local`）
+///
+/// クライアントIPは `LLMLB_TRUSTED_PROXIES` を考慮して決定する。信頼済みでない接続元が
+/// 転送ヘッダを付けている場合は、同一ホスト上のリバースプロキシが外部からの
+/// リクエストを中継している可能性があるため拒否する。接続元が不明な場合も拒否する。
+pub async fn local_only_middleware(request: Request, next: Next) -> Response {
+    let is_local = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .is_some_and(|ConnectInfo(addr)| is_local_client(addr, request.headers()));
+    if !is_local {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    next.run(request).await
+}
+
+fn is_local_client(addr: &SocketAddr, headers: &axum::http::HeaderMap) -> bool {
+    if !is_trusted_proxy(normalize_socket_ip(addr)) && has_forwarding_headers(headers) {
+        return false;
+    }
+    resolve_trusted_client_ip(addr, headers).is_loopback()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gather_text() -> String {
+        let mut buf = Vec::new();
+        TextEncoder::new()
+            .encode(&super::super::registry().gather(), &mut buf)
+            .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn endpoint_request_counters_are_monotonic_per_result() {
+        let endpoint_id = Uuid::new_v4();
+        let id = endpoint_id.to_string();
+
+        record_endpoint_request(endpoint_id, true, 120);
+        record_endpoint_request(endpoint_id, true, 80);
+        record_endpoint_request(endpoint_id, false, 3000);
+
+        assert_eq!(
+            ENDPOINT_REQUESTS
+                .with_label_values(&[id.as_str(), "success"])
+                .get(),
+            2
+        );
+        assert_eq!(
+            ENDPOINT_REQUESTS
+                .with_label_values(&[id.as_str(), "error"])
+                .get(),
+            1
+        );
+        assert_eq!(
+            ENDPOINT_LATENCY
+                .with_label_values(&[id.as_str()])
+                .get_sample_count(),
+            3
+        );
+
+        let text = gather_text();
+        assert!(text.contains(&format!(
+            "llmlb_endpoint_requests_total{{endpoint_id=\"{id}\",result=\"success\"}} 2"
+        )));
+        assert!(text.contains("llmlb_endpoint_request_duration_seconds_bucket"));
+    }
+
+    #[test]
+    fn prompt_cache_counters_accumulate_tokens() {
+        let endpoint_id = Uuid::new_v4();
+        let id = endpoint_id.to_string();
+
+        record_prompt_cache_usage(endpoint_id, 1200, 1024);
+        record_prompt_cache_usage(endpoint_id, 300, 0);
+
+        assert_eq!(
+            PROMPT_CACHE_INPUT_TOKENS
+                .with_label_values(&[id.as_str()])
+                .get(),
+            1500
+        );
+        assert_eq!(
+            PROMPT_CACHE_READ_TOKENS
+                .with_label_values(&[id.as_str()])
+                .get(),
+            1024
+        );
+        assert!(gather_text().contains(&format!(
+            "llmlb_prompt_cache_read_tokens_total{{endpoint_id=\"{id}\"}} 1024"
+        )));
+    }
+}
@@ -3,6 +3,24 @@
 //! ノードから送信されるメトリクス（CPU使用率、メモリ使用率、アクティブリクエスト数等）を
 //! 収集・保存し、ロードバランシングの判断材料として提供する。
 
+use once_cell::sync::Lazy;
+use prometheus::Registry;
+
+/// リクエスト処理タイムライン（段階別所要時間）
+pub mod timeline;
+
+/// Prometheus形式のメトリクス公開（`GET /metrics`）
+pub mod exporter;
+
 // 将来の拡張用モジュール宣言
 // pub mod collector;
 // pub mod storage;
+
+static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+/// llmlb本体のPrometheusレジストリ
+///
+/// 各メトリクスは初回利用時にこのレジストリへ登録される。
+pub fn registry() -> &'static Registry {
+    &REGISTRY
+}
@@ -0,0 +1,153 @@
+//! リクエスト処理のタイムライン計測
+//!
+//! 受信から完了までを段階に分け、各区間の所要時間を
+//! `llmlb_request_stage_duration_seconds` ヒストグラムに `stage` ラベル付きで記録する。
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use once_cell::sync::Lazy;
+use prometheus::{HistogramOpts, HistogramVec};
+use std::time::{Duration, Instant};
+
+/// 段階別所要時間ヒストグラムのバケット（秒）
+const STAGE_BUCKETS: &[f64] = &[
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+    60.0, 120.0,
+];
+
+static STAGE_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    let opts = HistogramOpts::new(
+        "llmlb_request_stage_duration_seconds",
+        "Time spent in each request processing stage (seconds)",
+    )
+    .buckets(STAGE_BUCKETS.to_vec());
+    let histogram = HistogramVec::new(opts, &["stage"]).expect("histogram vec");
+    super::registry().register(Box::new(histogram.clone())).ok();
+    histogram
+});
+
+/// リクエスト処理の段階
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineStage {
+    /// 受信→認証完了（ドレインゲート・APIキー認証・ボディ読み込み）
+    Auth,
+    /// 認証完了→エンドポイント選択開始（リクエスト解析・モデル解決・トークン推定）
+    TokenEstimation,
+    /// エンドポイント選択（キュー待機を含む）
+    EndpointSelection,
+    /// エンドポイント選択→アップストリームのレスポンスヘッダ受信
+    UpstreamConnect,
+    /// レスポンスヘッダ受信→最初のボディチャンク受信（ストリーミングのみ）
+    Ttfb,
+    /// 最初のチャンク（非ストリーミングはヘッダ受信）→レスポンス完了
+    Completion,
+    /// 受信→レスポンス完了
+    Total,
+}
+
+impl TimelineStage {
+    /// `stage` ラベル値
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimelineStage::Auth => "auth",
+            TimelineStage::TokenEstimation => "token_estimation",
+            TimelineStage::EndpointSelection => "endpoint_selection",
+            TimelineStage::UpstreamConnect => "upstream_connect",
+            TimelineStage::Ttfb => "ttfb",
+            TimelineStage::Completion => "completion",
+            TimelineStage::Total => "total",
+        }
+    }
+}
+
+/// 1リクエストのタイムライン
+///
+/// `mark` を呼ぶたびに前回のマークからの経過時間を該当段階として記録する。
+#[derive(Debug, Clone, Copy)]
+pub struct RequestTimeline {
+    received_at: Instant,
+    last_mark: Instant,
+}
+
+impl RequestTimeline {
+    /// 現在時刻を受信時刻としてタイムラインを開始する
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            received_at: now,
+            last_mark: now,
+        }
+    }
+
+    /// 前回のマークからの経過時間を `stage` として記録する
+    pub fn mark(&mut self, stage: TimelineStage) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(self.last_mark);
+        self.last_mark = now;
+        observe(stage, elapsed);
+        elapsed
+    }
+
+    /// 受信からの経過時間を `Total` として記録する
+    pub fn finish(&self) -> Duration {
+        let elapsed = self.received_at.elapsed();
+        observe(TimelineStage::Total, elapsed);
+        elapsed
+    }
+}
+
+fn observe(stage: TimelineStage, elapsed: Duration) {
+    STAGE_DURATION
+        .with_label_values(&[stage.as_str()])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// 受信時刻を記録し、リクエスト拡張に `RequestTimeline` を格納するミドルウェア
+///
+/// 認証より外側に配置し、`Auth` 段階に認証処理の時間が含まれるようにする。
+pub async fn request_timeline_middleware(mut request: Request, next: Next) -> Response {
+    request.extensions_mut().insert(RequestTimeline::start());
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::{Encoder, TextEncoder};
+
+    fn sample_count(stage: TimelineStage) -> u64 {
+        STAGE_DURATION
+            .with_label_values(&[stage.as_str()])
+            .get_sample_count()
+    }
+
+    #[test]
+    fn mark_records_each_stage_and_total() {
+        let before_selection = sample_count(TimelineStage::EndpointSelection);
+        let before_total = sample_count(TimelineStage::Total);
+
+        let mut timeline = RequestTimeline::start();
+        timeline.mark(TimelineStage::Auth);
+        std::thread::sleep(Duration::from_millis(5));
+        let selection = timeline.mark(TimelineStage::EndpointSelection);
+        let total = timeline.finish();
+
+        assert!(selection >= Duration::from_millis(5));
+        assert!(total >= selection);
+        assert!(sample_count(TimelineStage::EndpointSelection) > before_selection);
+        assert!(sample_count(TimelineStage::Total) > before_total);
+    }
+
+    #[test]
+    fn stage_histogram_is_exposed_in_shared_registry() {
+        RequestTimeline::start().mark(TimelineStage::Ttfb);
+
+        let encoder = TextEncoder::new();
+        let mut buf = Vec::new();
+        encoder
+            .encode(&crate::metrics::registry().gather(), &mut buf)
+            .unwrap();
+        let out = String::from_utf8(buf).unwrap();
+        assert!(out.contains("llmlb_request_stage_duration_seconds"));
+        assert!(out.contains("stage=\"ttfb\""));
+    }
+}
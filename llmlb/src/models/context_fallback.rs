@@ -0,0 +1,248 @@
+//! Automatic model switching when a prompt overflows the model context.
+//!
+//! When `LLMLB_AUTO_DOWNGRADE` is enabled, the prompt size is estimated before
+//! routing. If it does not fit in the context length (`max_tokens`) reported
+//! for the requested model, the request is rewritten to the model of the same
+//! family with the smallest context that still fits. The switch is exposed to
+//! the client via [`SWITCH_HEADER`] and stored as `requested_model` in the
+//! request history.
+
+use crate::registry::endpoints::EndpointRegistry;
+use crate::types::endpoint::EndpointModel;
+use axum::http::{HeaderName, HeaderValue};
+use axum::response::Response;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Response header carrying the originally requested model after a switch.
+pub const SWITCH_HEADER: &str = "x-llmlb-auto-downgrade-from";
+
+/// A model switch applied to the current request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelSwitch {
+    /// Model requested by the client.
+    pub requested: String,
+    /// Model the request was rewritten to.
+    pub selected: String,
+    /// Estimated prompt tokens that triggered the switch.
+    pub prompt_tokens: u32,
+}
+
+impl ModelSwitch {
+    /// Add [`SWITCH_HEADER`] to the response.
+    pub fn apply_header(&self, response: &mut Response) {
+        if let Ok(value) = HeaderValue::from_str(&self.requested) {
+            response
+                .headers_mut()
+                .insert(HeaderName::from_static(SWITCH_HEADER), value);
+        }
+    }
+}
+
+/// Family key of a model ID.
+///
+/// The organization prefix, the Ollama tag and parameter-size segments are
+/// dropped, so `llama3.1:8b`, `llama3.1:70b` and `meta/llama3.1-8b` share the
+/// family `llama3.1`.
+pub fn model_family(model_id: &str) -> String {
+    let name = model_id.rsplit('/').next().unwrap_or(model_id);
+    let name = name.split(':').next().unwrap_or(name);
+    name.to_ascii_lowercase()
+        .split(['-', '_'])
+        .filter(|segment| !segment.is_empty() && !is_size_segment(segment))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// `8b`, `1.5b`, `500m`, `a3b`, `8x7b` and similar parameter-size segments.
+fn is_size_segment(segment: &str) -> bool {
+    let body = segment
+        .strip_suffix(['b', 'm'])
+        .map(|body| body.strip_prefix('a').unwrap_or(body));
+    match body {
+        Some(body) if !body.is_empty() => body
+            .split('x')
+            .all(|part| !part.is_empty() && part.parse::<f64>().is_ok()),
+        _ => false,
+    }
+}
+
+/// Text of the prompt (`messages` contents or `prompt`) used for estimation.
+pub fn prompt_text(payload: &Value) -> String {
+    let mut parts = Vec::new();
+    if let Some(messages) = payload.get("messages").and_then(Value::as_array) {
+        for message in messages {
+            match message.get("content") {
+                Some(Value::String(text)) => parts.push(text.as_str()),
+                Some(Value::Array(items)) => parts.extend(
+                    items
+                        .iter()
+                        .filter_map(|item| item.get("text").and_then(Value::as_str)),
+                ),
+                _ => {}
+            }
+        }
+    }
+    match payload.get("prompt") {
+        Some(Value::String(prompt)) => parts.push(prompt.as_str()),
+        Some(Value::Array(prompts)) => parts.extend(prompts.iter().filter_map(Value::as_str)),
+        _ => {}
+    }
+    parts.join("\n")
+}
+
+/// Pick the same-family model with the smallest context that fits `required_tokens`.
+///
+/// Returns `None` when the requested model's context is unknown, when the
+/// prompt already fits, or when no larger model of the family is available.
+pub fn select_larger_context_model(
+    requested: &str,
+    required_tokens: u32,
+    models: &[EndpointModel],
+) -> Option<String> {
+    // The same model can be served by several endpoints; use the largest context reported.
+    let mut contexts: HashMap<&str, u32> = HashMap::new();
+    for model in models {
+        if let Some(max_tokens) = model.max_tokens {
+            let entry = contexts.entry(model.model_id.as_str()).or_default();
+            *entry = (*entry).max(max_tokens);
+        }
+    }
+
+    let current = *contexts.get(requested)?;
+    if required_tokens <= current {
+        return None;
+    }
+
+    let family = model_family(requested);
+    contexts
+        .into_iter()
+        .filter(|(id, context)| {
+            *id != requested && *context >= required_tokens && model_family(id) == family
+        })
+        .min_by(|(a_id, a_ctx), (b_id, b_ctx)| a_ctx.cmp(b_ctx).then_with(|| a_id.cmp(b_id)))
+        .map(|(id, _)| id.to_string())
+}
+
+/// Decide whether the request should be switched to a larger-context model.
+///
+/// Only models on online endpoints are considered.
+pub async fn resolve_model_switch(
+    registry: &EndpointRegistry,
+    requested: &str,
+    payload: &Value,
+) -> Option<ModelSwitch> {
+    let prompt = prompt_text(payload);
+    if prompt.is_empty() {
+        return None;
+    }
+    let prompt_tokens = crate::token::estimate_tokens(&prompt, requested)?;
+
+    let mut models = Vec::new();
+    for endpoint in registry.list_online().await {
+        match registry.list_models(endpoint.id).await {
+            Ok(endpoint_models) => models.extend(endpoint_models),
+            Err(e) => tracing::debug!(
+                endpoint_id = %endpoint.id,
+                error = %e,
+                "Failed to list endpoint models for context fallback"
+            ),
+        }
+    }
+
+    let selected = select_larger_context_model(requested, prompt_tokens, &models)?;
+    tracing::info!(
+        requested = %requested,
+        selected = %selected,
+        prompt_tokens,
+        "Prompt exceeds model context; switching to a larger-context model"
+    );
+    Some(ModelSwitch {
+        requested: requested.to_string(),
+        selected,
+        prompt_tokens,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn model(id: &str, max_tokens: Option<u32>) -> EndpointModel {
+        EndpointModel {
+            endpoint_id: Uuid::new_v4(),
+            model_id: id.to_string(),
+            capabilities: None,
+            max_tokens,
+            last_checked: None,
+            supported_apis: vec![],
+            canonical_name: None,
+        }
+    }
+
+    #[test]
+    fn family_ignores_org_tag_and_size() {
+        assert_eq!(model_family("llama3.1:8b"), "llama3.1");
+        assert_eq!(model_family("llama3.1:70b"), "llama3.1");
+        assert_eq!(model_family("meta/Llama3.1-8B"), "llama3.1");
+        assert_eq!(
+            model_family("Qwen/Qwen3-Coder-30B-A3B-Instruct"),
+            "qwen3-coder-instruct"
+        );
+        assert_ne!(model_family("qwen2.5:7b"), model_family("llama3.1:8b"));
+    }
+
+    #[test]
+    fn selects_smallest_sufficient_context_in_family() {
+        let models = vec![
+            model("llama3.1:8b", Some(8192)),
+            model("llama3.1:8b-128k", Some(131072)),
+            model("llama3.1:70b", Some(32768)),
+            model("qwen2.5:7b", Some(16384)),
+            model("llama3.1:405b", None),
+        ];
+
+        assert_eq!(
+            select_larger_context_model("llama3.1:8b", 12000, &models),
+            Some("llama3.1:70b".to_string())
+        );
+        assert_eq!(
+            select_larger_context_model("llama3.1:8b", 64000, &models),
+            Some("llama3.1:8b-128k".to_string())
+        );
+        // fits, too large for every candidate, or unknown context
+        assert_eq!(
+            select_larger_context_model("llama3.1:8b", 4000, &models),
+            None
+        );
+        assert_eq!(
+            select_larger_context_model("llama3.1:8b", 200000, &models),
+            None
+        );
+        assert_eq!(
+            select_larger_context_model("llama3.1:405b", 200000, &models),
+            None
+        );
+        assert_eq!(
+            select_larger_context_model("qwen2.5:7b", 20000, &models),
+            None
+        );
+    }
+
+    #[test]
+    fn prompt_text_collects_message_contents() {
+        let payload = json!({
+            "messages": [
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "hello"},
+                    {"type": "image_url", "image_url": {"url": "data:"}}
+                ]}
+            ]
+        });
+        assert_eq!(prompt_text(&payload), "be brief\nhello");
+        assert_eq!(prompt_text(&json!({"prompt": "abc"})), "abc");
+    }
+}
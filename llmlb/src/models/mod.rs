@@ -3,4 +3,10 @@
 /// Canonical model mapping utilities for built-in runtime integrations.
 pub mod mapping;
 
+/// Larger-context model fallback when a prompt overflows the model context.
+pub mod context_fallback;
+
+/// VRAM requirement estimates used to skip endpoints without enough free GPU memory.
+pub mod vram;
+
 // GPU vendor detection was removed because it is covered by endpoint probes.
@@ -0,0 +1,244 @@
+//! GPU memory requirements of models.
+//!
+//! The VRAM needed to serve a model is estimated from its metadata (parameter
+//! count and quantization) when endpoint models are synced, and kept per
+//! endpoint. Routing compares it with the free GPU memory reported in the
+//! endpoint health metrics (`HealthMetrics.gpu_memory_*`) and skips endpoints
+//! that cannot fit the model. Models whose requirement cannot be estimated and
+//! endpoints that do not report GPU memory are routed as before.
+
+use crate::metadata::ModelMetadata;
+use crate::types::HealthMetrics;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Factor applied to the weight size for KV cache, activations and runtime buffers.
+pub const VRAM_OVERHEAD_FACTOR: f64 = 1.2;
+
+/// Bits per weight assumed when the parameter count is known but the quantization is not.
+const DEFAULT_BITS_PER_WEIGHT: f64 = 16.0;
+
+/// Estimated VRAM requirement per (endpoint, model), recorded on model sync.
+static REQUIRED_VRAM: Lazy<Mutex<HashMap<(Uuid, String), u64>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Estimate the VRAM (MiB) needed to serve a model.
+///
+/// The weight size is the parameter count times the bits per weight (from
+/// `quantization_bits` or the quantization name, 16 bits if unknown), or the
+/// model file size when the parameter count is missing. Returns `None` when
+/// neither is available.
+pub fn estimate_required_vram_mb(model_meta: &ModelMetadata) -> Option<u64> {
+    let parameters = model_meta
+        .parameter_size
+        .as_deref()
+        .and_then(parse_parameter_count);
+    let weight_bytes = match parameters {
+        Some(parameters) => {
+            let bits = model_meta
+                .quantization_bits
+                .map(f64::from)
+                .filter(|bits| *bits > 0.0)
+                .or_else(|| {
+                    model_meta
+                        .quantization
+                        .as_deref()
+                        .and_then(quantization_bits)
+                })
+                .unwrap_or(DEFAULT_BITS_PER_WEIGHT);
+            parameters * bits / 8.0
+        }
+        None => model_meta.size_bytes.filter(|size| *size > 0)? as f64,
+    };
+    Some((weight_bytes * VRAM_OVERHEAD_FACTOR / (1024.0 * 1024.0)).ceil() as u64)
+}
+
+/// Parse a parameter count such as `7B`, `70.6B`, `137M` or `8x7B`.
+pub fn parse_parameter_count(value: &str) -> Option<f64> {
+    let value = value.trim().to_ascii_uppercase();
+    let (experts, size) = match value.split_once('X') {
+        Some((experts, size)) => (experts.trim().parse::<f64>().ok()?, size.trim()),
+        None => (1.0, value.as_str()),
+    };
+    let (number, scale) = match size.chars().last()? {
+        'T' => (&size[..size.len() - 1], 1e12),
+        'B' => (&size[..size.len() - 1], 1e9),
+        'M' => (&size[..size.len() - 1], 1e6),
+        'K' => (&size[..size.len() - 1], 1e3),
+        _ => (size, 1.0),
+    };
+    let count = number.trim().parse::<f64>().ok()? * scale * experts;
+    (count.is_finite() && count > 0.0).then_some(count)
+}
+
+/// Approximate bits per weight of a quantization name (`Q4_K_M`, `Q8_0`, `F16`, ...).
+pub fn quantization_bits(name: &str) -> Option<f64> {
+    let name = name.trim().to_ascii_uppercase();
+    match name.as_str() {
+        "F32" | "FP32" => return Some(32.0),
+        "F16" | "FP16" | "BF16" => return Some(16.0),
+        "FP8" | "INT8" => return Some(8.0),
+        "INT4" | "AWQ" | "GPTQ" => return Some(4.0),
+        _ => {}
+    }
+    // GGUF Qn_* / IQn_* types store per-block scales, so they use slightly more than n bits
+    let digits = name
+        .strip_prefix("IQ")
+        .or_else(|| name.strip_prefix('Q'))?
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>();
+    let bits = digits.parse::<f64>().ok().filter(|bits| *bits > 0.0)?;
+    Some(bits + 0.5)
+}
+
+/// Record the estimated VRAM requirement of a model on an endpoint (`None` clears it).
+pub fn record_required_vram(endpoint_id: Uuid, model_id: &str, required_mb: Option<u64>) {
+    let mut required = REQUIRED_VRAM.lock().unwrap_or_else(|e| e.into_inner());
+    let key = (endpoint_id, model_id.to_string());
+    match required_mb {
+        Some(mb) => {
+            required.insert(key, mb);
+        }
+        None => {
+            required.remove(&key);
+        }
+    }
+}
+
+/// Estimated VRAM requirement of a model on an endpoint (`None` if unknown).
+pub fn required_vram_mb(endpoint_id: Uuid, model_id: &str) -> Option<u64> {
+    REQUIRED_VRAM
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&(endpoint_id, model_id.to_string()))
+        .copied()
+}
+
+/// Free GPU memory (MiB) from health metrics (`None` if not reported).
+pub fn free_vram_mb(metrics: &HealthMetrics) -> Option<u64> {
+    let total = metrics.gpu_memory_total_mb?;
+    let used = metrics.gpu_memory_used_mb?;
+    Some(total.saturating_sub(used))
+}
+
+/// Keep the candidates whose free VRAM fits the model.
+///
+/// `vram` returns `(required_mb, free_mb)` for a candidate, or `None` when
+/// either is unknown; such candidates are always kept. When every candidate is
+/// known to be short of VRAM, only the one with the most free VRAM is kept.
+pub fn filter_by_free_vram<T>(
+    candidates: Vec<T>,
+    vram: impl Fn(&T) -> Option<(u64, u64)>,
+) -> Vec<T> {
+    let mut fitting = Vec::with_capacity(candidates.len());
+    let mut roomiest: Option<(u64, T)> = None;
+    for candidate in candidates {
+        match vram(&candidate) {
+            Some((required, free)) if free < required => {
+                if roomiest.as_ref().is_none_or(|(most, _)| free > *most) {
+                    roomiest = Some((free, candidate));
+                }
+            }
+            _ => fitting.push(candidate),
+        }
+    }
+    if fitting.is_empty() {
+        fitting.extend(roomiest.map(|(_, candidate)| candidate));
+    }
+    fitting
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(parameter_size: Option<&str>, quantization: Option<&str>) -> ModelMetadata {
+        ModelMetadata {
+            model: "test".to_string(),
+            parameter_size: parameter_size.map(str::to_string),
+            quantization: quantization.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_parameter_counts() {
+        assert_eq!(parse_parameter_count("7B"), Some(7e9));
+        assert_eq!(parse_parameter_count("13b"), Some(13e9));
+        assert_eq!(parse_parameter_count("137M"), Some(137e6));
+        assert_eq!(parse_parameter_count("8x7B"), Some(56e9));
+        assert_eq!(parse_parameter_count("unknown"), None);
+        assert_eq!(parse_parameter_count(""), None);
+    }
+
+    #[test]
+    fn maps_quantization_names_to_bits() {
+        assert_eq!(quantization_bits("Q4_K_M"), Some(4.5));
+        assert_eq!(quantization_bits("q8_0"), Some(8.5));
+        assert_eq!(quantization_bits("IQ2_XS"), Some(2.5));
+        assert_eq!(quantization_bits("F16"), Some(16.0));
+        assert_eq!(quantization_bits("awq"), Some(4.0));
+        assert_eq!(quantization_bits("mystery"), None);
+    }
+
+    #[test]
+    fn estimates_vram_from_parameters_and_quantization() {
+        // 7B x 4.5 bits plus overhead
+        let q4 = estimate_required_vram_mb(&meta(Some("7B"), Some("Q4_K_M"))).unwrap();
+        assert_eq!(q4, (7e9 * 4.5 / 8.0 * 1.2 / 1048576.0_f64).ceil() as u64);
+
+        // unknown quantization is treated as 16 bits
+        let fp16 = estimate_required_vram_mb(&meta(Some("7B"), None)).unwrap();
+        assert!(fp16 > q4 * 3);
+
+        // quantization_bits takes precedence over the name
+        let mut explicit = meta(Some("7B"), Some("Q4_K_M"));
+        explicit.quantization_bits = Some(8.0);
+        assert!(estimate_required_vram_mb(&explicit).unwrap() > q4);
+
+        // falls back to the file size without a parameter count
+        let mut sized = meta(None, Some("Q4_K_M"));
+        sized.size_bytes = Some(1024 * 1024 * 1000);
+        let expected = (1024.0 * 1024.0 * 1000.0 * VRAM_OVERHEAD_FACTOR / 1048576.0).ceil();
+        assert_eq!(estimate_required_vram_mb(&sized), Some(expected as u64));
+
+        assert_eq!(estimate_required_vram_mb(&meta(None, Some("Q4_K_M"))), None);
+    }
+
+    #[test]
+    fn filters_endpoints_short_of_vram() {
+        // (name, Some((required_mb, free_mb)))
+        let candidates = vec![
+            ("small", Some((8000, 4000))),
+            ("large", Some((8000, 16000))),
+            ("unknown", None),
+        ];
+        let kept = filter_by_free_vram(candidates, |c| c.1);
+        let names: Vec<_> = kept.iter().map(|c| c.0).collect();
+        assert_eq!(names, vec!["large", "unknown"]);
+
+        // when every candidate is short, the roomiest one is kept
+        let candidates = vec![
+            ("a", Some((8000, 2000))),
+            ("b", Some((8000, 6000))),
+            ("c", Some((8000, 4000))),
+        ];
+        let kept = filter_by_free_vram(candidates, |c| c.1);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].0, "b");
+    }
+
+    #[test]
+    fn records_required_vram_per_endpoint_model() {
+        let endpoint_id = Uuid::new_v4();
+        record_required_vram(endpoint_id, "llama3:8b", Some(6000));
+        assert_eq!(required_vram_mb(endpoint_id, "llama3:8b"), Some(6000));
+        assert_eq!(required_vram_mb(Uuid::new_v4(), "llama3:8b"), None);
+
+        record_required_vram(endpoint_id, "llama3:8b", None);
+        assert_eq!(required_vram_mb(endpoint_id, "llama3:8b"), None);
+    }
+}
@@ -2,10 +2,14 @@
 //!
 //! エンドポイントの状態をメモリ内で管理し、SQLiteと同期
 
+use crate::common::error::LbError;
 use crate::db::endpoints as db;
+use crate::health::endpoint_checker::GpuInfo;
+use crate::sync::ModelListCache;
 use crate::types::endpoint::{
-    Endpoint, EndpointCapability, EndpointModel, EndpointStatus, EndpointType,
+    Endpoint, EndpointCapability, EndpointModel, EndpointStatus, EndpointType, SupportedAPI,
 };
+use serde::Deserialize;
 use sqlx::SqlitePool;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -13,6 +17,63 @@ use tokio::sync::RwLock;
 use tracing::{debug, info};
 use uuid::Uuid;
 
+/// base_url を重複判定用に正規化する
+///
+/// スキーム・ホストを小文字化し、末尾スラッシュを除去し、省略された
+/// デフォルトポート（http: 80 / https: 443）を補完する。
+/// パスとクエリの大文字小文字は区別する。
+pub fn normalize_base_url(base_url: &str) -> String {
+    let trimmed = base_url.trim();
+    let Ok(url) = reqwest::Url::parse(trimmed) else {
+        return trimmed.trim_end_matches('/').to_ascii_lowercase();
+    };
+
+    let host = url.host_str().unwrap_or_default();
+    let mut normalized = match url.port_or_known_default() {
+        Some(port) => format!("{}://{}:{}", url.scheme(), host, port),
+        None => format!("{}://{}", url.scheme(), host),
+    };
+    normalized.push_str(url.path().trim_end_matches('/'));
+    if let Some(query) = url.query() {
+        normalized.push('?');
+        normalized.push_str(query);
+    }
+    normalized
+}
+
+/// 正規化後の base_url が既存エンドポイントと重複した場合の扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnDuplicateUrl {
+    /// `LbError::Conflict` を返す
+    #[default]
+    Error,
+    /// 登録せずに既存エンドポイントのIDを返す
+    ReturnExisting,
+}
+
+/// 重複グループから残すエンドポイントを選ぶ
+///
+/// オンラインのものを優先し、同条件なら最も古く登録されたもの。
+pub fn preferred_duplicate(group: &[Endpoint]) -> Option<&Endpoint> {
+    group
+        .iter()
+        .filter(|ep| ep.status == EndpointStatus::Online)
+        .min_by_key(|ep| ep.registered_at)
+        .or_else(|| group.iter().min_by_key(|ep| ep.registered_at))
+}
+
+/// 重複エンドポイントの統合結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DedupeResult {
+    /// 正規化後のURL
+    pub normalized_url: String,
+    /// 残したエンドポイントID
+    pub kept: Uuid,
+    /// 統合して削除したエンドポイントID
+    pub removed: Vec<Uuid>,
+}
+
 fn model_lookup_keys(model_id: &str) -> Vec<String> {
     let mut keys = vec![model_id.to_string()];
     if let Some(mapping) = crate::models::mapping::find_mapping(model_id) {
@@ -72,6 +133,27 @@ fn remove_model_mapping(
     }
 }
 
+/// ステータス更新をキャッシュ上のエンドポイントへ反映する
+fn apply_status(
+    endpoint: &mut Endpoint,
+    status: EndpointStatus,
+    latency_ms: Option<u32>,
+    error: Option<&str>,
+) {
+    endpoint.status = status;
+    if let Some(v) = latency_ms {
+        endpoint.latency_ms = Some(v);
+    }
+    // DBと同様に、last_error は成功時にクリアし、error_count は status=error のときのみ加算する。
+    endpoint.last_error = error.map(String::from);
+    endpoint.error_count = if status == EndpointStatus::Error {
+        endpoint.error_count.saturating_add(1)
+    } else {
+        0
+    };
+    endpoint.last_seen = Some(chrono::Utc::now());
+}
+
 /// エンドポイントレジストリ
 ///
 /// エンドポイント情報をメモリにキャッシュし、高速な参照を提供する。
@@ -84,6 +166,14 @@ pub struct EndpointRegistry {
     model_to_endpoints: Arc<RwLock<HashMap<String, Vec<Uuid>>>>,
     /// データベースプール
     pool: SqlitePool,
+    /// `/v1/models` 用のエンドポイント別モデル一覧キャッシュ
+    model_list_cache: Arc<ModelListCache>,
+    /// モデル増減通知用のダッシュボードイベントバス
+    event_bus: Arc<std::sync::OnceLock<crate::events::SharedEventBus>>,
+    /// モデル同期の差分を記録する監査ログライター
+    audit_log_writer: Arc<std::sync::OnceLock<crate::audit::writer::AuditLogWriter>>,
+    /// ヘルスチェックのヒステリシス設定（ヘルスチェッカーとAPIで共有）
+    health_hysteresis: crate::health::HealthHysteresisSettings,
 }
 
 impl EndpointRegistry {
@@ -93,6 +183,10 @@ impl EndpointRegistry {
             endpoints: Arc::new(RwLock::new(HashMap::new())),
             model_to_endpoints: Arc::new(RwLock::new(HashMap::new())),
             pool,
+            model_list_cache: Arc::new(ModelListCache::from_env()),
+            event_bus: Arc::new(std::sync::OnceLock::new()),
+            audit_log_writer: Arc::new(std::sync::OnceLock::new()),
+            health_hysteresis: crate::health::HealthHysteresisSettings::default(),
         };
 
         // DBからエンドポイントを読み込み
@@ -102,18 +196,23 @@ impl EndpointRegistry {
     }
 
     /// DBからエンドポイントとモデルマッピングを読み込み
+    ///
+    /// DB読み込みはロック外で済ませ、キャッシュへの反映時のみ書き込みロックを取得する。
     async fn load_from_db(&self) -> Result<(), sqlx::Error> {
         let loaded_endpoints = db::list_endpoints(&self.pool).await?;
 
+        let mut loaded = Vec::with_capacity(loaded_endpoints.len());
+        for endpoint in loaded_endpoints {
+            let models = db::list_endpoint_models(&self.pool, endpoint.id).await?;
+            loaded.push((endpoint, models));
+        }
+
         let mut endpoints = self.endpoints.write().await;
         let mut model_map = self.model_to_endpoints.write().await;
 
-        for endpoint in loaded_endpoints {
+        for (endpoint, models) in loaded {
             let endpoint_id = endpoint.id;
 
-            // モデル一覧を取得
-            let models = db::list_endpoint_models(&self.pool, endpoint_id).await?;
-
             // モデルマッピングを更新
             for model in &models {
                 insert_model_mapping(&mut model_map, model, endpoint_id);
@@ -163,6 +262,17 @@ impl EndpointRegistry {
             .collect()
     }
 
+    /// 指定したタグを持つエンドポイントを取得
+    pub async fn list_by_tag(&self, tag: &str) -> Vec<Endpoint> {
+        self.endpoints
+            .read()
+            .await
+            .values()
+            .filter(|e| e.tags.iter().any(|t| t == tag))
+            .cloned()
+            .collect()
+    }
+
     /// 指定した機能を持つオンラインエンドポイントを取得
     ///
     /// 例: ImageGeneration機能を持つエンドポイント → 画像生成リクエストの転送先
@@ -247,15 +357,257 @@ impl EndpointRegistry {
         endpoints
     }
 
+    /// エンドポイントが指定モデルを指定APIで提供しているか
+    ///
+    /// モデルの `supported_apis`（[`EndpointModel::supports_api`]）で判定する。
+    /// Embeddings はエンドポイント自体が `Embeddings` 機能を持つ場合も対応とみなす。
+    pub async fn supports_api(
+        &self,
+        endpoint_id: Uuid,
+        model_id: &str,
+        api: SupportedAPI,
+    ) -> Result<bool, sqlx::Error> {
+        if api == SupportedAPI::Embeddings
+            && self
+                .get(endpoint_id)
+                .await
+                .is_some_and(|ep| ep.has_capability(EndpointCapability::Embeddings))
+        {
+            return Ok(true);
+        }
+
+        let lookup_keys = model_lookup_keys(model_id);
+        let models = db::list_endpoint_models(&self.pool, endpoint_id).await?;
+        Ok(models.iter().any(|model| {
+            model.supports_api(api)
+                && endpoint_model_lookup_keys(model)
+                    .iter()
+                    .any(|key| lookup_keys.contains(key))
+        }))
+    }
+
+    /// 正規化後の base_url が一致するエンドポイントを取得
+    pub async fn find_by_base_url(&self, base_url: &str) -> Option<Endpoint> {
+        let normalized = normalize_base_url(base_url);
+        self.endpoints
+            .read()
+            .await
+            .values()
+            .find(|ep| normalize_base_url(&ep.base_url) == normalized)
+            .cloned()
+    }
+
+    /// 正規化後の base_url が重複しているエンドポイントをグループ化して取得
+    ///
+    /// 各グループは登録日時の昇順、グループ同士は正規化URLの昇順で並ぶ。
+    pub async fn find_duplicate_groups(&self) -> Vec<(String, Vec<Endpoint>)> {
+        let mut groups: HashMap<String, Vec<Endpoint>> = HashMap::new();
+        for endpoint in self.endpoints.read().await.values() {
+            groups
+                .entry(normalize_base_url(&endpoint.base_url))
+                .or_default()
+                .push(endpoint.clone());
+        }
+
+        let mut duplicates: Vec<(String, Vec<Endpoint>)> = groups
+            .into_iter()
+            .filter(|(_, endpoints)| endpoints.len() > 1)
+            .map(|(url, mut endpoints)| {
+                endpoints.sort_by_key(|ep| ep.registered_at);
+                (url, endpoints)
+            })
+            .collect();
+        duplicates.sort_by(|a, b| a.0.cmp(&b.0));
+        duplicates
+    }
+
+    /// 優先フェイルオーバー先の設定を検証する
+    ///
+    /// 設定先が存在しない場合、自身を指す場合、既存の設定と合わせて
+    /// フェイルオーバー順序が循環する場合はエラーメッセージを返す。
+    pub async fn validate_failover_to(
+        &self,
+        endpoint_id: Uuid,
+        failover_to: Uuid,
+    ) -> Result<(), String> {
+        if failover_to == endpoint_id {
+            return Err("Endpoint cannot fail over to itself".to_string());
+        }
+        let endpoints = self.endpoints.read().await;
+        if !endpoints.contains_key(&failover_to) {
+            return Err(format!("Failover endpoint {} not found", failover_to));
+        }
+
+        let mut visited = HashSet::new();
+        let mut next = Some(failover_to);
+        while let Some(id) = next {
+            if id == endpoint_id {
+                return Err(format!(
+                    "Failover to {} would create a failover cycle",
+                    failover_to
+                ));
+            }
+            if !visited.insert(id) {
+                break;
+            }
+            next = endpoints.get(&id).and_then(|ep| ep.failover_to);
+        }
+        Ok(())
+    }
+
+    /// 依存先の設定を検証する
+    ///
+    /// 依存先が存在しない場合、自身を含む場合はエラーメッセージを返す。
+    /// 循環依存は拒否せず、起動時のスケジュールで警告してベストエフォートで扱う。
+    pub async fn validate_depends_on(
+        &self,
+        endpoint_id: Uuid,
+        depends_on: &[Uuid],
+    ) -> Result<(), String> {
+        let endpoints = self.endpoints.read().await;
+        for dependency in depends_on {
+            if *dependency == endpoint_id {
+                return Err("Endpoint cannot depend on itself".to_string());
+            }
+            if !endpoints.contains_key(dependency) {
+                return Err(format!("Dependency endpoint {} not found", dependency));
+            }
+        }
+        Ok(())
+    }
+
     /// エンドポイントを追加（DBとキャッシュ両方に保存）
-    pub async fn add(&self, endpoint: Endpoint) -> Result<(), sqlx::Error> {
+    ///
+    /// 正規化後の base_url が既存エンドポイントと一致する場合は
+    /// `LbError::Conflict` を返す。
+    pub async fn add(&self, endpoint: Endpoint) -> Result<(), LbError> {
+        self.add_with_policy(endpoint, OnDuplicateUrl::Error)
+            .await
+            .map(|_| ())
+    }
+
+    /// 重複時の扱いを指定してエンドポイントを追加し、登録された（または既存の）IDを返す
+    pub async fn add_with_policy(
+        &self,
+        endpoint: Endpoint,
+        on_duplicate: OnDuplicateUrl,
+    ) -> Result<Uuid, LbError> {
+        if let Some(existing) = self.find_by_base_url(&endpoint.base_url).await {
+            return match on_duplicate {
+                OnDuplicateUrl::ReturnExisting => Ok(existing.id),
+                OnDuplicateUrl::Error => Err(LbError::Conflict(format!(
+                    "Endpoint with URL '{}' is already registered as '{}'",
+                    endpoint.base_url, existing.name
+                ))),
+            };
+        }
+
         // DBに保存
-        db::create_endpoint(&self.pool, &endpoint).await?;
+        db::create_endpoint(&self.pool, &endpoint)
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("UNIQUE constraint failed") {
+                    LbError::Conflict("Endpoint with this name or URL already exists".to_string())
+                } else {
+                    LbError::Database(format!("Failed to create endpoint: {}", e))
+                }
+            })?;
 
         // キャッシュに追加
-        self.endpoints.write().await.insert(endpoint.id, endpoint);
+        let id = endpoint.id;
+        self.endpoints.write().await.insert(id, endpoint);
 
-        Ok(())
+        Ok(id)
+    }
+
+    /// base_url が重複しているエンドポイントを1つに統合する
+    ///
+    /// 各グループで [`preferred_duplicate`] が選んだエンドポイントを残し、
+    /// 他のエンドポイントのタグと（残す側に無ければ）APIキーを引き継いでから削除する。
+    /// 削除したエンドポイントを優先フェイルオーバー先にしていた設定は残す側へ付け替える。
+    /// `dry_run` の場合は統合内容の算出のみ行う。
+    pub async fn dedupe(&self, dry_run: bool) -> Result<Vec<DedupeResult>, LbError> {
+        let db_error =
+            |e: sqlx::Error| LbError::Database(format!("Failed to dedupe endpoints: {}", e));
+        let mut results = Vec::new();
+        for (normalized_url, group) in self.find_duplicate_groups().await {
+            let Some(keep) = preferred_duplicate(&group).cloned() else {
+                continue;
+            };
+            let removed: Vec<Endpoint> = group.into_iter().filter(|ep| ep.id != keep.id).collect();
+            results.push(DedupeResult {
+                normalized_url,
+                kept: keep.id,
+                removed: removed.iter().map(|ep| ep.id).collect(),
+            });
+            if dry_run {
+                continue;
+            }
+
+            let mut merged = keep.clone();
+            for duplicate in &removed {
+                for tag in &duplicate.tags {
+                    if !merged.tags.contains(tag) {
+                        merged.tags.push(tag.clone());
+                    }
+                }
+                if merged.api_key.is_none() {
+                    merged.api_key = duplicate.api_key.clone();
+                }
+            }
+            if merged.tags != keep.tags || merged.api_key != keep.api_key {
+                self.update(merged).await.map_err(db_error)?;
+            }
+
+            let removed_ids: HashSet<Uuid> = removed.iter().map(|ep| ep.id).collect();
+            // 削除するエンドポイントを指すフェイルオーバー先・依存先は残す側に付け替える
+            for mut endpoint in self.list().await {
+                if removed_ids.contains(&endpoint.id) {
+                    continue;
+                }
+                let mut changed = false;
+                if endpoint
+                    .failover_to
+                    .is_some_and(|target| removed_ids.contains(&target))
+                {
+                    endpoint.failover_to = (endpoint.id != keep.id).then_some(keep.id);
+                    changed = true;
+                }
+                if endpoint
+                    .depends_on
+                    .iter()
+                    .any(|id| removed_ids.contains(id))
+                {
+                    let mut depends_on = Vec::with_capacity(endpoint.depends_on.len());
+                    for id in &endpoint.depends_on {
+                        let id = if removed_ids.contains(id) {
+                            keep.id
+                        } else {
+                            *id
+                        };
+                        if id != endpoint.id && !depends_on.contains(&id) {
+                            depends_on.push(id);
+                        }
+                    }
+                    endpoint.depends_on = depends_on;
+                    changed = true;
+                }
+                if changed {
+                    self.update(endpoint).await.map_err(db_error)?;
+                }
+            }
+            for duplicate in &removed {
+                self.remove(duplicate.id).await.map_err(db_error)?;
+                info!(
+                    removed_id = %duplicate.id,
+                    removed_name = %duplicate.name,
+                    kept_id = %keep.id,
+                    kept_name = %keep.name,
+                    "Merged duplicate endpoint"
+                );
+            }
+        }
+        Ok(results)
     }
 
     /// エンドポイントをキャッシュのみに追加（DBは更新しない）
@@ -278,6 +630,102 @@ impl EndpointRegistry {
         Ok(updated)
     }
 
+    /// エンドポイントの重みを更新（DBとキャッシュ両方）
+    pub async fn update_weight(&self, id: Uuid, weight: u32) -> Result<bool, sqlx::Error> {
+        let updated = db::update_endpoint_weight(&self.pool, id, weight).await?;
+
+        if updated {
+            if let Some(endpoint) = self.endpoints.write().await.get_mut(&id) {
+                endpoint.weight = weight;
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// エンドポイントのタグを更新（DBとキャッシュ両方）
+    pub async fn update_tags(&self, id: Uuid, tags: Vec<String>) -> Result<bool, sqlx::Error> {
+        let updated = db::update_endpoint_tags(&self.pool, id, &tags).await?;
+
+        if updated {
+            if let Some(endpoint) = self.endpoints.write().await.get_mut(&id) {
+                endpoint.tags = tags;
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// 複数エンドポイントの重み・タグ・運用状態を一括更新（DBとキャッシュ両方）
+    ///
+    /// DBは1トランザクションで更新し、コミット後にキャッシュへ反映する。
+    /// 運用状態はキャッシュを持たないため、呼び出し側で `LoadManager` へ反映すること。
+    pub async fn apply_settings_updates(
+        &self,
+        updates: &[db::EndpointSettingsUpdate],
+    ) -> Result<(), sqlx::Error> {
+        db::apply_settings_updates(&self.pool, updates).await?;
+
+        let mut endpoints = self.endpoints.write().await;
+        for update in updates {
+            if let Some(endpoint) = endpoints.get_mut(&update.endpoint_id) {
+                if let Some(weight) = update.weight {
+                    endpoint.weight = weight;
+                }
+                if let Some(tags) = &update.tags {
+                    endpoint.tags = tags.clone();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// エンドポイントの単価・月次予算を更新（DBとキャッシュ両方）
+    pub async fn update_budget(
+        &self,
+        id: Uuid,
+        monthly_budget_usd: Option<f64>,
+        input_cost_per_million_tokens: Option<f64>,
+        output_cost_per_million_tokens: Option<f64>,
+    ) -> Result<bool, sqlx::Error> {
+        let updated = db::update_endpoint_budget(
+            &self.pool,
+            id,
+            monthly_budget_usd,
+            input_cost_per_million_tokens,
+            output_cost_per_million_tokens,
+        )
+        .await?;
+
+        if updated {
+            if let Some(endpoint) = self.endpoints.write().await.get_mut(&id) {
+                endpoint.monthly_budget_usd = monthly_budget_usd;
+                endpoint.input_cost_per_million_tokens = input_cost_per_million_tokens;
+                endpoint.output_cost_per_million_tokens = output_cost_per_million_tokens;
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// エンドポイントのカナリア割合を更新（DBとキャッシュ両方）
+    pub async fn update_canary_percent(
+        &self,
+        id: Uuid,
+        canary_percent: Option<u8>,
+    ) -> Result<bool, sqlx::Error> {
+        let updated = db::update_endpoint_canary_percent(&self.pool, id, canary_percent).await?;
+
+        if updated {
+            if let Some(endpoint) = self.endpoints.write().await.get_mut(&id) {
+                endpoint.canary_percent = canary_percent;
+            }
+        }
+
+        Ok(updated)
+    }
+
     /// エンドポイントのステータスを更新
     pub async fn update_status(
         &self,
@@ -291,20 +739,52 @@ impl EndpointRegistry {
 
         if updated {
             // キャッシュを更新
-            let mut endpoints = self.endpoints.write().await;
-            if let Some(endpoint) = endpoints.get_mut(&id) {
-                endpoint.status = status;
-                if let Some(v) = latency_ms {
-                    endpoint.latency_ms = Some(v);
+            if let Some(endpoint) = self.endpoints.write().await.get_mut(&id) {
+                apply_status(endpoint, status, latency_ms, error);
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// ヘルスチェック結果を反映（ステータスはDBとキャッシュ、GPU情報はキャッシュのみ）
+    ///
+    /// DB更新をロック外で行った後、ステータスとGPU情報を1回の短い書き込みロックで反映する。
+    /// ヘルスチェックがルーティング側の読み取りを長時間ブロックしないようにするため。
+    /// ステータスが変化した場合は `EndpointStatusChanged` を発行する。
+    pub async fn apply_health_result(
+        &self,
+        id: Uuid,
+        status: EndpointStatus,
+        latency_ms: Option<u32>,
+        error: Option<&str>,
+        gpu_info: Option<&GpuInfo>,
+    ) -> Result<bool, sqlx::Error> {
+        let updated = db::update_endpoint_status(&self.pool, id, status, latency_ms, error).await?;
+
+        let mut old_status = None;
+        if updated {
+            if let Some(endpoint) = self.endpoints.write().await.get_mut(&id) {
+                old_status = Some(endpoint.status);
+                apply_status(endpoint, status, latency_ms, error);
+                if let Some(info) = gpu_info {
+                    endpoint.gpu_device_count = info.gpu_device_count;
+                    endpoint.gpu_total_memory_bytes = info.gpu_total_memory_bytes;
+                    endpoint.gpu_used_memory_bytes = info.gpu_used_memory_bytes;
+                    endpoint.gpu_capability_score = info.gpu_capability_score;
+                    endpoint.active_requests = info.active_requests;
                 }
-                // DBと同様に、last_error は成功時にクリアし、error_count は status=error のときのみ加算する。
-                endpoint.last_error = error.map(String::from);
-                endpoint.error_count = if status == EndpointStatus::Error {
-                    endpoint.error_count.saturating_add(1)
-                } else {
-                    0
-                };
-                endpoint.last_seen = Some(chrono::Utc::now());
+            }
+        }
+
+        // 状態が実際に変わったときだけ通知する（同じ状態の再確認ではイベントを出さない）
+        if let Some(old_status) = old_status.filter(|old| *old != status) {
+            if let Some(bus) = self.event_bus.get() {
+                bus.publish(crate::events::DashboardEvent::EndpointStatusChanged {
+                    runtime_id: id,
+                    old_status,
+                    new_status: status,
+                });
             }
         }
 
@@ -462,6 +942,7 @@ impl EndpointRegistry {
         if deleted {
             // キャッシュから削除
             self.endpoints.write().await.remove(&id);
+            self.model_list_cache.remove(id);
         }
 
         Ok(deleted)
@@ -475,6 +956,7 @@ impl EndpointRegistry {
         // モデルマッピングを更新
         let mut model_map = self.model_to_endpoints.write().await;
         insert_model_mapping(&mut model_map, model, model.endpoint_id);
+        self.model_list_cache.invalidate(model.endpoint_id);
 
         Ok(())
     }
@@ -485,6 +967,8 @@ impl EndpointRegistry {
         endpoint_id: Uuid,
         models: Vec<EndpointModel>,
     ) -> Result<SyncResult, sqlx::Error> {
+        self.model_list_cache.invalidate(endpoint_id);
+
         // 既存モデルを取得
         let existing = db::list_endpoint_models(&self.pool, endpoint_id).await?;
         let existing_ids: std::collections::HashSet<_> =
@@ -506,14 +990,15 @@ impl EndpointRegistry {
             .cloned()
             .collect();
 
-        // DBを更新
+        // DBを更新（途中で失敗しても一覧が中途半端にならないよう1トランザクションで反映）
+        let mut tx = self.pool.begin().await?;
         for model in &added {
-            db::add_endpoint_model(&self.pool, model).await?;
+            db::add_endpoint_model(&mut *tx, model).await?;
         }
-
         for model in &removed {
-            db::delete_endpoint_model(&self.pool, endpoint_id, &model.model_id).await?;
+            db::delete_endpoint_model(&mut *tx, endpoint_id, &model.model_id).await?;
         }
+        tx.commit().await?;
 
         // モデルマッピングを更新
         {
@@ -538,6 +1023,12 @@ impl EndpointRegistry {
             "Synced endpoint models"
         );
 
+        let mut added_ids: Vec<String> = added.iter().map(|m| m.model_id.clone()).collect();
+        added_ids.sort();
+        let mut removed_ids: Vec<String> = removed.iter().map(|m| m.model_id.clone()).collect();
+        removed_ids.sort();
+        self.notify_models_changed(endpoint_id, &added_ids, &removed_ids, &[]);
+
         Ok(SyncResult {
             added: added.len(),
             removed: removed.len(),
@@ -563,13 +1054,110 @@ impl EndpointRegistry {
         });
 
         // 取得したモデルでマッピングを再構築
-        for model in models {
-            insert_model_mapping(&mut model_map, &model, endpoint_id);
+        for model in &models {
+            insert_model_mapping(&mut model_map, model, endpoint_id);
         }
 
+        // DBと同期済みの一覧としてキャッシュも更新
+        self.model_list_cache.store(endpoint_id, models);
+
         Ok(())
     }
 
+    /// ダッシュボードイベントバスを設定する。
+    ///
+    /// 設定後、モデル同期でモデルが追加・削除・更新されたときに `ModelsChanged` を発行する。
+    pub fn set_event_bus(&self, bus: crate::events::SharedEventBus) {
+        let _ = self.event_bus.set(bus);
+    }
+
+    /// 監査ログライターを設定する。
+    ///
+    /// 設定後、モデル同期の差分を監査ログへ記録する。
+    pub fn set_audit_log_writer(&self, writer: crate::audit::writer::AuditLogWriter) {
+        let _ = self.audit_log_writer.set(writer);
+    }
+
+    /// モデル同期の差分を通知する
+    ///
+    /// 追加・削除・更新がある場合のみ `/v1/models` 用キャッシュを破棄して `ModelsChanged` を発行し、
+    /// 差分を監査ログへ記録して `true` を返す。差分が無い同期（再確認のみ）では何もしない。
+    pub fn notify_models_changed(
+        &self,
+        endpoint_id: Uuid,
+        added: &[String],
+        removed: &[String],
+        updated: &[String],
+    ) -> bool {
+        if added.is_empty() && removed.is_empty() && updated.is_empty() {
+            return false;
+        }
+        self.model_list_cache.invalidate(endpoint_id);
+        info!(
+            endpoint_id = %endpoint_id,
+            added = ?added,
+            removed = ?removed,
+            updated = ?updated,
+            "Endpoint models changed"
+        );
+        if let Some(bus) = self.event_bus.get() {
+            bus.publish(crate::events::DashboardEvent::ModelsChanged {
+                endpoint_id,
+                added: added.to_vec(),
+                removed: removed.to_vec(),
+                updated: updated.to_vec(),
+            });
+        }
+        if let Some(writer) = self.audit_log_writer.get() {
+            writer.send(crate::audit::types::AuditLogEntry {
+                id: None,
+                timestamp: chrono::Utc::now(),
+                http_method: "SYSTEM".to_string(),
+                request_path: "/system/model-sync".to_string(),
+                status_code: 200,
+                actor_type: crate::audit::types::ActorType::Anonymous,
+                actor_id: Some("system".to_string()),
+                actor_username: None,
+                api_key_owner_id: None,
+                client_ip: None,
+                duration_ms: None,
+                input_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                model_name: None,
+                endpoint_id: Some(endpoint_id.to_string()),
+                detail: Some(
+                    serde_json::json!({
+                        "event": "endpoint_models_synced",
+                        "added": added,
+                        "removed": removed,
+                        "updated": updated,
+                    })
+                    .to_string(),
+                ),
+                batch_id: None,
+                is_migrated: false,
+            });
+        }
+        true
+    }
+
+    /// 証明書の期限切れが近いことを通知する
+    pub fn notify_cert_expiring(
+        &self,
+        endpoint_id: Uuid,
+        cert_expires_at: chrono::DateTime<chrono::Utc>,
+        days_remaining: i64,
+    ) {
+        if let Some(bus) = self.event_bus.get() {
+            bus.publish(crate::events::DashboardEvent::EndpointCertExpiring {
+                endpoint_id,
+                cert_expires_at,
+                days_remaining,
+            });
+        }
+    }
+
     /// 全モデルIDの一覧を取得
     pub async fn list_all_model_ids(&self) -> Vec<String> {
         self.model_to_endpoints
@@ -597,6 +1185,16 @@ impl EndpointRegistry {
         self.endpoints.read().await.len()
     }
 
+    /// モデル一覧キャッシュへの参照を取得
+    pub fn model_list_cache(&self) -> &ModelListCache {
+        &self.model_list_cache
+    }
+
+    /// ヘルスチェックのヒステリシス設定を取得
+    pub fn health_hysteresis(&self) -> &crate::health::HealthHysteresisSettings {
+        &self.health_hysteresis
+    }
+
     /// DBプールへの参照を取得
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
@@ -624,6 +1222,208 @@ mod tests {
         crate::db::test_utils::test_db_pool().await
     }
 
+    #[test]
+    fn test_normalize_base_url_rules() {
+        // スキーム・ホストの小文字化とデフォルトポート補完
+        assert_eq!(
+            normalize_base_url("HTTP://LocalHost"),
+            "http://localhost:80"
+        );
+        assert_eq!(
+            normalize_base_url("https://api.example.com"),
+            "https://api.example.com:443"
+        );
+        // 明示的なデフォルトポートと省略は同一視
+        assert_eq!(
+            normalize_base_url("https://api.example.com:443/v1"),
+            normalize_base_url("https://api.example.com/v1")
+        );
+        // 非デフォルトポートは保持
+        assert_eq!(
+            normalize_base_url("http://localhost:8080"),
+            "http://localhost:8080"
+        );
+        // 末尾スラッシュ除去（複数含む）
+        assert_eq!(
+            normalize_base_url("http://localhost:8080/v1//"),
+            "http://localhost:8080/v1"
+        );
+        assert_eq!(
+            normalize_base_url("http://localhost:8080/"),
+            "http://localhost:8080"
+        );
+        // 前後の空白は無視
+        assert_eq!(
+            normalize_base_url("  http://localhost:8080/ "),
+            "http://localhost:8080"
+        );
+        // パスの大文字小文字は区別
+        assert_ne!(
+            normalize_base_url("http://localhost:8080/V1"),
+            normalize_base_url("http://localhost:8080/v1")
+        );
+        // IPv6 ホスト
+        assert_eq!(
+            normalize_base_url("http://[::1]:8080/"),
+            "http://[::1]:8080"
+        );
+        // クエリは保持
+        assert_eq!(
+            normalize_base_url("http://localhost:8080/v1?a=1"),
+            "http://localhost:8080/v1?a=1"
+        );
+        // URLとして解釈できない値は小文字化と末尾スラッシュ除去のみ
+        assert_eq!(normalize_base_url("Not A URL/"), "not a url");
+    }
+
+    #[tokio::test]
+    async fn test_validate_failover_to_rejects_cycles() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+        let registry = EndpointRegistry::new(pool).await.unwrap();
+
+        let mut ids = Vec::new();
+        for index in 0..3 {
+            let endpoint = Endpoint::new(
+                format!("Failover {}", index),
+                format!("http://localhost:{}", 8090 + index),
+                EndpointType::OpenaiCompatible,
+            );
+            ids.push(endpoint.id);
+            registry.add(endpoint).await.unwrap();
+        }
+        // 0 → 1 → 2
+        for (from, to) in [(0, 1), (1, 2)] {
+            let mut endpoint = registry.get(ids[from]).await.unwrap();
+            registry
+                .validate_failover_to(ids[from], ids[to])
+                .await
+                .unwrap();
+            endpoint.failover_to = Some(ids[to]);
+            registry.update(endpoint).await.unwrap();
+        }
+
+        // 自身・循環・存在しない先は拒否
+        assert!(registry.validate_failover_to(ids[0], ids[0]).await.is_err());
+        assert!(registry.validate_failover_to(ids[2], ids[0]).await.is_err());
+        assert!(registry.validate_failover_to(ids[1], ids[0]).await.is_err());
+        assert!(registry
+            .validate_failover_to(ids[2], Uuid::new_v4())
+            .await
+            .is_err());
+        // 循環しない付け替えは許可
+        assert!(registry.validate_failover_to(ids[0], ids[2]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_add_rejects_normalized_duplicate_url() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+        let registry = EndpointRegistry::new(pool).await.unwrap();
+
+        let first = Endpoint::new(
+            "First".to_string(),
+            "http://localhost:8080".to_string(),
+            EndpointType::Xllm,
+        );
+        registry.add(first).await.unwrap();
+
+        let duplicate = Endpoint::new(
+            "Second".to_string(),
+            "HTTP://LOCALHOST:8080/".to_string(),
+            EndpointType::Xllm,
+        );
+        let err = registry.add(duplicate).await.unwrap_err();
+        assert!(matches!(err, LbError::Conflict(_)));
+        assert_eq!(registry.list().await.len(), 1);
+
+        // 既存の重複（キャッシュに直接入ったもの）はグループとして検出される
+        let legacy = Endpoint::new(
+            "Legacy".to_string(),
+            "http://localhost:8080/".to_string(),
+            EndpointType::Xllm,
+        );
+        registry.add_to_cache(legacy).await;
+        let groups = registry.find_duplicate_groups().await;
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].0, "http://localhost:8080");
+        assert_eq!(groups[0].1.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_add_with_policy_returns_existing_id() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+        let registry = EndpointRegistry::new(pool).await.unwrap();
+
+        let first = Endpoint::new(
+            "First".to_string(),
+            "https://gpu-1.example.com/".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        let first_id = first.id;
+        registry.add(first).await.unwrap();
+
+        let duplicate = Endpoint::new(
+            "Second".to_string(),
+            "https://GPU-1.example.com:443".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        let id = registry
+            .add_with_policy(duplicate, OnDuplicateUrl::ReturnExisting)
+            .await
+            .unwrap();
+        assert_eq!(id, first_id);
+        assert_eq!(registry.list().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dedupe_merges_duplicates_into_preferred_endpoint() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+
+        // 重複は過去のバージョンで登録されたものを想定し、DBへ直接書き込む
+        let mut oldest = Endpoint::new(
+            "Oldest".to_string(),
+            "http://localhost:9000".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        oldest.tags = vec!["a".to_string()];
+        let mut online = Endpoint::new(
+            "Online".to_string(),
+            "http://LOCALHOST:9000/".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        online.status = EndpointStatus::Online;
+        online.registered_at = oldest.registered_at + chrono::Duration::seconds(1);
+        let mut other = Endpoint::new(
+            "Other".to_string(),
+            "http://localhost:9001".to_string(),
+            EndpointType::OpenaiCompatible,
+        );
+        other.failover_to = Some(oldest.id);
+        for endpoint in [&oldest, &online, &other] {
+            db::create_endpoint(&pool, endpoint).await.unwrap();
+        }
+        let registry = EndpointRegistry::new(pool).await.unwrap();
+
+        let planned = registry.dedupe(true).await.unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].kept, online.id);
+        assert_eq!(planned[0].removed, vec![oldest.id]);
+        assert_eq!(registry.list().await.len(), 3);
+
+        assert_eq!(registry.dedupe(false).await.unwrap(), planned);
+        assert!(registry.get(oldest.id).await.is_none());
+        let kept = registry.get(online.id).await.unwrap();
+        assert_eq!(kept.tags, vec!["a".to_string()]);
+        assert_eq!(
+            registry.get(other.id).await.unwrap().failover_to,
+            Some(online.id)
+        );
+        assert!(registry.find_duplicate_groups().await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_registry_basic_operations() {
         let _lock = TEST_LOCK.lock().await;
@@ -657,6 +1457,48 @@ mod tests {
         assert!(registry.get(endpoint_id).await.is_none());
     }
 
+    #[tokio::test]
+    async fn test_list_by_tag_and_update_tags_persist() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+        let registry = EndpointRegistry::new(pool.clone()).await.unwrap();
+
+        let mut prod = Endpoint::new(
+            "Prod".to_string(),
+            "http://localhost:8081".to_string(),
+            EndpointType::Xllm,
+        );
+        prod.tags = vec!["prod".to_string()];
+        let experimental = Endpoint::new(
+            "Experimental".to_string(),
+            "http://localhost:8082".to_string(),
+            EndpointType::Xllm,
+        );
+        let experimental_id = experimental.id;
+        registry.add(prod).await.unwrap();
+        registry.add(experimental).await.unwrap();
+
+        let tagged = registry.list_by_tag("prod").await;
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].name, "Prod");
+        assert!(registry.list_by_tag("experimental").await.is_empty());
+
+        assert!(registry
+            .update_tags(experimental_id, vec!["experimental".to_string()])
+            .await
+            .unwrap());
+        assert!(!registry
+            .update_tags(Uuid::new_v4(), vec!["prod".to_string()])
+            .await
+            .unwrap());
+        assert_eq!(registry.list_by_tag("experimental").await.len(), 1);
+
+        // 再読み込み後もタグが残る
+        let reloaded = EndpointRegistry::new(pool).await.unwrap();
+        let endpoint = reloaded.get(experimental_id).await.unwrap();
+        assert_eq!(endpoint.tags, vec!["experimental"]);
+    }
+
     #[tokio::test]
     async fn test_registry_model_mapping() {
         let _lock = TEST_LOCK.lock().await;
@@ -1451,6 +2293,74 @@ mod tests {
         assert!(found.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_sync_models_publishes_only_diff() {
+        let _lock = TEST_LOCK.lock().await;
+        let pool = setup_test_db().await;
+        let registry = EndpointRegistry::new(pool).await.unwrap();
+        let bus = crate::events::create_shared_event_bus();
+        registry.set_event_bus(bus.clone());
+        let mut receiver = bus.subscribe();
+
+        let mut ep = Endpoint::new(
+            "SyncEvents".to_string(),
+            "http://localhost:9018".to_string(),
+            EndpointType::Xllm,
+        );
+        ep.status = EndpointStatus::Online;
+        let ep_id = ep.id;
+        registry.add(ep).await.unwrap();
+
+        let model = |model_id: &str| EndpointModel {
+            endpoint_id: ep_id,
+            model_id: model_id.to_string(),
+            capabilities: None,
+            max_tokens: None,
+            last_checked: None,
+            supported_apis: vec![SupportedAPI::ChatCompletions],
+            canonical_name: None,
+        };
+
+        registry
+            .sync_models(ep_id, vec![model("model-a"), model("model-b")])
+            .await
+            .unwrap();
+        match receiver.try_recv().unwrap() {
+            crate::events::DashboardEvent::ModelsChanged {
+                endpoint_id,
+                added,
+                removed,
+                updated,
+            } => {
+                assert_eq!(endpoint_id, ep_id);
+                assert_eq!(added, vec!["model-a", "model-b"]);
+                assert!(removed.is_empty());
+                assert!(updated.is_empty());
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        // 同じ一覧での再同期は通知しない
+        registry
+            .sync_models(ep_id, vec![model("model-a"), model("model-b")])
+            .await
+            .unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        // 全件置き換えでも差分のみ通知する
+        registry
+            .sync_models(ep_id, vec![model("model-b"), model("model-c")])
+            .await
+            .unwrap();
+        match receiver.try_recv().unwrap() {
+            crate::events::DashboardEvent::ModelsChanged { added, removed, .. } => {
+                assert_eq!(added, vec!["model-c"]);
+                assert_eq!(removed, vec!["model-a"]);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
     // ===== list_models テスト =====
 
     #[tokio::test]
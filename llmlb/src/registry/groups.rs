@@ -0,0 +1,41 @@
+//! エンドポイントグループ（論理プール）
+//!
+//! 複数のエンドポイントを名前付きのグループにまとめ、drain / disable / 重み設定を
+//! グループ単位で行えるようにする。1エンドポイントは複数のグループに所属でき、
+//! グループを削除してもメンバーのエンドポイントは削除されない。
+
+use super::EndpointRegistry;
+use crate::types::endpoint::Endpoint;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// エンドポイントグループ
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EndpointGroup {
+    /// グループID
+    pub id: Uuid,
+    /// グループ名（一意）
+    pub name: String,
+    /// 説明
+    pub description: Option<String>,
+    /// 所属エンドポイントID
+    pub endpoint_ids: Vec<Uuid>,
+    /// 作成日時
+    pub created_at: DateTime<Utc>,
+    /// 更新日時
+    pub updated_at: DateTime<Utc>,
+}
+
+impl EndpointGroup {
+    /// 登録済みのメンバーエンドポイントを取得する（削除済みのIDは含めない）
+    pub async fn members(&self, registry: &EndpointRegistry) -> Vec<Endpoint> {
+        let mut members = Vec::with_capacity(self.endpoint_ids.len());
+        for id in &self.endpoint_ids {
+            if let Some(endpoint) = registry.get(*id).await {
+                members.push(endpoint);
+            }
+        }
+        members
+    }
+}
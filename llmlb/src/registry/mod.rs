@@ -5,6 +5,9 @@
 //! エンドポイントの状態をメモリ内で管理し、SQLiteと同期
 
 pub mod endpoints;
+pub mod groups;
 pub mod models;
+pub mod startup_order;
 
 pub use endpoints::EndpointRegistry;
+pub use groups::EndpointGroup;
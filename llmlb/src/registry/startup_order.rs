@@ -0,0 +1,103 @@
+//! エンドポイントの起動順序（依存関係の解決）
+//!
+//! `Endpoint::depends_on` をもとに、起動時の再判別と初回ヘルスチェックを
+//! 依存先から順に実行できるよう段（wave）に分ける。同じ段のエンドポイントは並列に実行してよい。
+//!
+//! 登録されていない依存先は無視する。循環依存（または循環に依存するもの）は警告を出し、
+//! 最後の段にまとめてベストエフォートで並列実行する。
+
+use crate::types::endpoint::Endpoint;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// エンドポイントを依存順の段に分ける
+///
+/// 各段の中では入力の順序を保つ。依存関係が無ければ全エンドポイントが1段目になる。
+pub fn startup_waves(endpoints: Vec<Endpoint>) -> Vec<Vec<Endpoint>> {
+    let known: HashSet<Uuid> = endpoints.iter().map(|ep| ep.id).collect();
+    for endpoint in &endpoints {
+        for dependency in endpoint.depends_on.iter().filter(|id| !known.contains(id)) {
+            tracing::warn!(
+                endpoint_id = %endpoint.id,
+                endpoint_name = %endpoint.name,
+                dependency = %dependency,
+                "Endpoint depends on an unknown endpoint; ignoring the dependency"
+            );
+        }
+    }
+
+    let mut waves = Vec::new();
+    let mut scheduled: HashSet<Uuid> = HashSet::new();
+    let mut remaining = endpoints;
+    while !remaining.is_empty() {
+        let (ready, blocked): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|ep| {
+            ep.depends_on
+                .iter()
+                .all(|id| *id == ep.id || !known.contains(id) || scheduled.contains(id))
+        });
+        if ready.is_empty() {
+            let names: Vec<&str> = blocked.iter().map(|ep| ep.name.as_str()).collect();
+            tracing::warn!(
+                endpoints = ?names,
+                "Circular endpoint dependencies detected; starting these endpoints in parallel"
+            );
+            waves.push(blocked);
+            break;
+        }
+        scheduled.extend(ready.iter().map(|ep| ep.id));
+        waves.push(ready);
+        remaining = blocked;
+    }
+    waves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::endpoint::EndpointType;
+
+    fn endpoint(name: &str) -> Endpoint {
+        Endpoint::new(
+            name.to_string(),
+            format!("http://{}:8080", name),
+            EndpointType::OpenaiCompatible,
+        )
+    }
+
+    fn names(waves: &[Vec<Endpoint>]) -> Vec<Vec<&str>> {
+        waves
+            .iter()
+            .map(|wave| wave.iter().map(|ep| ep.name.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn orders_endpoints_after_their_dependencies() {
+        let gateway = endpoint("gateway");
+        let mut embed = endpoint("embed");
+        embed.depends_on = vec![gateway.id];
+        let mut chat = endpoint("chat");
+        chat.depends_on = vec![embed.id, Uuid::new_v4()];
+        let standalone = endpoint("standalone");
+
+        let waves = startup_waves(vec![chat, embed, gateway, standalone]);
+        assert_eq!(
+            names(&waves),
+            vec![vec!["gateway", "standalone"], vec!["embed"], vec!["chat"]]
+        );
+    }
+
+    #[test]
+    fn circular_dependencies_fall_back_to_parallel() {
+        let base = endpoint("base");
+        let mut a = endpoint("a");
+        let mut b = endpoint("b");
+        a.depends_on = vec![b.id, base.id];
+        b.depends_on = vec![a.id];
+        let mut c = endpoint("c");
+        c.depends_on = vec![a.id];
+
+        let waves = startup_waves(vec![a, b, c, base]);
+        assert_eq!(names(&waves), vec![vec!["base"], vec!["a", "b", "c"]]);
+    }
+}
@@ -17,6 +17,7 @@ pub async fn run(state: AppState, bind_addr: &str) {
 
     info!("LLM Load Balancer server listening on {}", bind_addr);
 
+    crate::shutdown::spawn_reload_on_sighup();
     let shutdown_signal = shutdown_signal(shutdown);
 
     axum::serve(
@@ -60,6 +61,8 @@ async fn shutdown_signal(shutdown: ShutdownController) {
             info!("Shutdown requested, shutting down...");
         }
     }
+    // OSシグナル（`llmlb stop` 等）による停止もコントローラー経由の停止要求として扱う
+    shutdown.request_shutdown();
 }
 
 #[cfg(test)]
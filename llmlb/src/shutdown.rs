@@ -1,6 +1,7 @@
 //! Cooperative shutdown controller.
 //!
 //! `main.rs` combines this with OS signals to perform graceful shutdown.
+//! SIGHUP triggers a graceful reload of the runtime-reconfigurable settings instead.
 
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -44,6 +45,55 @@ impl ShutdownController {
     }
 }
 
+/// Reload runtime-reconfigurable settings on every SIGHUP.
+///
+/// Only the settings in [`crate::config::RELOADABLE_ENV_VARS`] are applied; in-flight
+/// requests keep the settings they started with and the process keeps running.
+#[cfg(unix)]
+pub fn spawn_reload_on_sighup() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            tracing::warn!("Failed to install SIGHUP handler: {}", err);
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            tracing::info!("Received SIGHUP, reloading configuration...");
+            apply_reload();
+        }
+    });
+}
+
+/// SIGHUP is not available on this platform.
+#[cfg(not(unix))]
+pub fn spawn_reload_on_sighup() {}
+
+#[cfg(unix)]
+fn apply_reload() {
+    let Some(reload) = crate::config::reload_config() else {
+        return;
+    };
+    if reload.changed.contains(&"log_level") {
+        let level = reload.config.log_level.as_deref().unwrap_or("info");
+        if let Err(err) = crate::logging::set_log_level(level) {
+            tracing::warn!(level, "Failed to apply reloaded log level: {}", err);
+        }
+    }
+    tracing::info!(
+        changed = ?reload.changed,
+        ignored = ?reload.ignored,
+        health_check_interval_secs = reload.config.health_check_interval_secs,
+        load_balancer_mode = %reload.config.load_balancer_mode,
+        queue_max = reload.config.queue.max_waiters,
+        queue_timeout_secs = reload.config.queue.timeout.as_secs(),
+        "Configuration reloaded"
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
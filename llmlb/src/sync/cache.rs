@@ -0,0 +1,195 @@
+//! エンドポイントモデル一覧のTTLキャッシュ
+//!
+//! `/v1/models` のたびに全エンドポイントへ問い合わせないよう、
+//! エンドポイントごとの `EndpointModel` 取得結果を TTL 付きで保持する。
+//! 再取得はエンドポイントごとに1本へまとめ（single-flight）、
+//! TTL切れ直後の同時リクエストが同じエンドポイントへ殺到しないようにする。
+
+use crate::types::endpoint::EndpointModel;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// デフォルトTTL（秒）
+pub const DEFAULT_MODEL_LIST_TTL_SECS: u64 = 60;
+
+/// TTL切れ時の再取得タイムアウト上限（秒）
+///
+/// 応答しないエンドポイントが `/v1/models` 全体を長時間ブロックしないようにする。
+pub const MODEL_LIST_FETCH_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Clone)]
+struct CachedModels {
+    fetched_at: Instant,
+    models: Vec<EndpointModel>,
+}
+
+/// エンドポイントごとのモデル一覧キャッシュ
+#[derive(Debug)]
+pub struct ModelListCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<Uuid, CachedModels>>,
+    refresh_locks: RwLock<HashMap<Uuid, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl ModelListCache {
+    /// 指定TTLでキャッシュを作成（TTL 0 はキャッシュ無効）
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+            refresh_locks: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// `LLMLB_MODEL_LIST_TTL_SECS` からTTLを読み込んでキャッシュを作成
+    pub fn from_env() -> Self {
+        Self::new(Duration::from_secs(crate::config::model_list_ttl_secs()))
+    }
+
+    /// キャッシュのTTL
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// TTL内のモデル一覧を取得（期限切れ・未取得は `None`）
+    pub fn get_fresh(&self, endpoint_id: Uuid) -> Option<Vec<EndpointModel>> {
+        self.get_fresh_at(endpoint_id, Instant::now())
+    }
+
+    fn get_fresh_at(&self, endpoint_id: Uuid, now: Instant) -> Option<Vec<EndpointModel>> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&endpoint_id)
+            .filter(|cached| now.saturating_duration_since(cached.fetched_at) < self.ttl)
+            .map(|cached| cached.models.clone())
+    }
+
+    /// `since` 以降に保存されたモデル一覧を取得
+    ///
+    /// 再取得ロックの待機中に他のリクエストが取得を終えていれば、その結果を再利用する。
+    pub fn get_fetched_since(
+        &self,
+        endpoint_id: Uuid,
+        since: Instant,
+    ) -> Option<Vec<EndpointModel>> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&endpoint_id)
+            .filter(|cached| cached.fetched_at >= since)
+            .map(|cached| cached.models.clone())
+    }
+
+    /// エンドポイントごとの再取得ロック
+    ///
+    /// 保持している間は同じエンドポイントへの再取得が1本に制限される。
+    pub fn refresh_lock(&self, endpoint_id: Uuid) -> Arc<tokio::sync::Mutex<()>> {
+        if let Some(lock) = self
+            .refresh_locks
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&endpoint_id)
+        {
+            return lock.clone();
+        }
+        self.refresh_locks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(endpoint_id)
+            .or_default()
+            .clone()
+    }
+
+    /// 取得結果を保存
+    pub fn store(&self, endpoint_id: Uuid, models: Vec<EndpointModel>) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                endpoint_id,
+                CachedModels {
+                    fetched_at: Instant::now(),
+                    models,
+                },
+            );
+    }
+
+    /// 指定エンドポイントのキャッシュを破棄
+    pub fn invalidate(&self, endpoint_id: Uuid) {
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&endpoint_id);
+    }
+
+    /// 指定エンドポイントのキャッシュと再取得ロックを破棄（エンドポイント削除時）
+    pub fn remove(&self, endpoint_id: Uuid) {
+        self.invalidate(endpoint_id);
+        self.refresh_locks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&endpoint_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(endpoint_id: Uuid, model_id: &str) -> EndpointModel {
+        EndpointModel {
+            endpoint_id,
+            model_id: model_id.to_string(),
+            capabilities: None,
+            max_tokens: None,
+            last_checked: None,
+            supported_apis: vec![],
+            canonical_name: None,
+        }
+    }
+
+    #[test]
+    fn cached_models_expire_after_ttl() {
+        let cache = ModelListCache::new(Duration::from_secs(30));
+        let id = Uuid::new_v4();
+        assert!(cache.get_fresh(id).is_none());
+
+        cache.store(id, vec![model(id, "llama3")]);
+        let fresh = cache.get_fresh(id).unwrap();
+        assert_eq!(fresh[0].model_id, "llama3");
+        assert!(cache
+            .get_fresh_at(id, Instant::now() + Duration::from_secs(31))
+            .is_none());
+
+        cache.invalidate(id);
+        assert!(cache.get_fresh(id).is_none());
+    }
+
+    #[test]
+    fn fetched_since_only_returns_newer_entries() {
+        let cache = ModelListCache::new(Duration::from_secs(30));
+        let id = Uuid::new_v4();
+        let before = Instant::now();
+        cache.store(id, vec![model(id, "llama3")]);
+        assert!(cache.get_fetched_since(id, before).is_some());
+        assert!(cache
+            .get_fetched_since(id, Instant::now() + Duration::from_secs(1))
+            .is_none());
+        assert!(Arc::ptr_eq(
+            &cache.refresh_lock(id),
+            &cache.refresh_lock(id)
+        ));
+    }
+
+    #[test]
+    fn zero_ttl_disables_cache() {
+        let cache = ModelListCache::new(Duration::ZERO);
+        let id = Uuid::new_v4();
+        cache.store(id, vec![model(id, "llama3")]);
+        assert!(cache.get_fresh(id).is_none());
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! モデル名プレフィックスからcapabilities（chat, embeddings）を自動判定
 
+use crate::types::endpoint::SupportedAPI;
+
 /// モデルが持つ能力
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Capability {
@@ -64,6 +66,19 @@ pub fn capabilities_to_strings(capabilities: &[Capability]) -> Vec<String> {
         .collect()
 }
 
+/// capabilitiesから対応APIを決定
+///
+/// embeddingsモデルは `/v1/embeddings` 専用、それ以外は Chat Completions とする。
+pub fn supported_apis_for(capabilities: &[Capability]) -> Vec<SupportedAPI> {
+    capabilities
+        .iter()
+        .map(|c| match c {
+            Capability::Chat => SupportedAPI::ChatCompletions,
+            Capability::Embeddings => SupportedAPI::Embeddings,
+        })
+        .collect()
+}
+
 /// 文字列からCapabilityに変換
 pub fn capability_from_str(s: &str) -> Option<Capability> {
     match s.to_lowercase().as_str() {
@@ -107,6 +122,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_supported_apis_for_capabilities() {
+        assert_eq!(
+            supported_apis_for(&detect_capabilities("nomic-embed-text-v1.5")),
+            vec![SupportedAPI::Embeddings]
+        );
+        assert_eq!(
+            supported_apis_for(&detect_capabilities("llama3.2")),
+            vec![SupportedAPI::ChatCompletions]
+        );
+    }
+
     #[test]
     fn test_detect_capabilities_chat() {
         // 通常のチャットモデル
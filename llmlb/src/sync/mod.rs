@@ -2,21 +2,26 @@
 //!
 //! エンドポイントからモデル一覧を取得し、DBと同期
 
+pub mod cache;
 pub mod capabilities;
 pub mod parser;
 
+pub use cache::ModelListCache;
+
 pub use capabilities::{
-    capabilities_to_strings, capability_from_str, detect_capabilities, Capability,
+    capabilities_to_strings, capability_from_str, detect_capabilities, supported_apis_for,
+    Capability,
 };
 pub use parser::{parse_models_response, ParsedModel, ResponseFormat};
 
 use crate::db::endpoints as db;
 use crate::metadata;
-use crate::types::endpoint::{EndpointModel, EndpointType, SupportedAPI};
+use crate::registry::endpoints::EndpointRegistry;
+use crate::types::endpoint::{Endpoint, EndpointModel, EndpointType};
 use chrono::Utc;
 use reqwest::Client;
 use sqlx::SqlitePool;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use tracing::debug;
 use uuid::Uuid;
@@ -30,8 +35,14 @@ pub struct SyncResult {
     pub added: usize,
     /// 削除されたモデル数
     pub removed: usize,
-    /// 更新されたモデル数（既存モデルの再確認）
+    /// 更新されたモデル数（能力・正規名・max_tokens が変わった既存モデル）
     pub updated: usize,
+    /// 追加されたモデルID（昇順）
+    pub added_models: Vec<String>,
+    /// 削除されたモデルID（昇順）
+    pub removed_models: Vec<String>,
+    /// 更新されたモデルID（昇順）
+    pub updated_models: Vec<String>,
     /// 検出されたレスポンス形式
     pub format: ResponseFormat,
 }
@@ -68,7 +79,7 @@ impl std::error::Error for SyncError {}
 /// 1. GET /v1/models でモデル一覧を取得
 /// 2. OpenAI/Ollama形式をパース
 /// 3. 既存モデルと比較（差分計算）
-/// 4. DBを更新（削除→追加）
+/// 4. 差分のみDBへ反映（削除→追加→更新を1トランザクションで）
 /// 5. capabilitiesを自動判定
 /// 6. xLLM/Ollamaの場合はmax_tokensを取得
 pub async fn sync_models(
@@ -92,15 +103,86 @@ pub async fn sync_models(
     .await
 }
 
+/// TTLキャッシュを経由してエンドポイントのモデル一覧を取得
+///
+/// TTL内はキャッシュから返し、期限切れ（または `force_refresh`）の場合のみ
+/// エンドポイントから再取得してDBと同期する。
+/// 再取得に失敗した場合はDB上の同期済み一覧を返し、TTLの間は再試行しない。
+/// TTLが0（キャッシュ無効）の場合は `force_refresh` 時以外は再取得せずDBの一覧を返す。
+///
+/// 再取得はエンドポイントごとに1本だけ行い、待機していた他のリクエストは
+/// その結果を再利用する（`force_refresh` の場合も待機開始後の取得結果を共有する）。
+pub async fn list_endpoint_models_cached(
+    registry: &EndpointRegistry,
+    client: &Client,
+    endpoint: &Endpoint,
+    force_refresh: bool,
+) -> Result<Vec<EndpointModel>, sqlx::Error> {
+    let cache = registry.model_list_cache();
+    if !force_refresh {
+        if cache.ttl().is_zero() {
+            return registry.list_models(endpoint.id).await;
+        }
+        if let Some(models) = cache.get_fresh(endpoint.id) {
+            return Ok(models);
+        }
+    }
+
+    let waiting_since = std::time::Instant::now();
+    let lock = cache.refresh_lock(endpoint.id);
+    let _guard = lock.lock().await;
+    if let Some(models) = cache.get_fetched_since(endpoint.id, waiting_since) {
+        return Ok(models);
+    }
+
+    match sync_models_with_type(
+        registry.pool(),
+        client,
+        endpoint.id,
+        &endpoint.base_url,
+        endpoint.api_key.as_deref(),
+        (endpoint.inference_timeout_secs as u64).min(cache::MODEL_LIST_FETCH_TIMEOUT_SECS),
+        Some(endpoint.endpoint_type),
+    )
+    .await
+    {
+        Ok(result) => {
+            registry.notify_models_changed(
+                endpoint.id,
+                &result.added_models,
+                &result.removed_models,
+                &result.updated_models,
+            );
+            // マッピング再構築時にキャッシュも更新される
+            registry.refresh_model_mappings(endpoint.id).await?;
+            registry.list_models(endpoint.id).await
+        }
+        Err(e) => {
+            debug!(
+                endpoint_id = %endpoint.id,
+                error = %e,
+                "Model list refresh failed, using synced models from database"
+            );
+            let models = registry.list_models(endpoint.id).await?;
+            cache.store(endpoint.id, models.clone());
+            Ok(models)
+        }
+    }
+}
+
 /// エンドポイントからモデル一覧を取得してDBと同期（タイプ指定版）
 ///
 /// # 処理フロー
 /// 1. GET /v1/models でモデル一覧を取得
 /// 2. OpenAI/Ollama形式をパース
 /// 3. 既存モデルと比較（差分計算）
-/// 4. DBを更新（削除→追加）
+/// 4. 差分のみDBへ反映（削除→追加→更新を1トランザクションで）
 /// 5. capabilitiesを自動判定
 /// 6. xLLM/Ollamaの場合はmax_tokensを取得（SPEC-e8e9326e）
+///
+/// DBへの反映はすべて同じトランザクション内で行い、途中で失敗した場合はロールバックして
+/// `SyncError::DbError` を返す（前回の同期結果がそのまま残る）。
+/// 手順6はコミット後に行うベストエフォートの補完で、失敗しても同期自体は成功扱いとする。
 pub async fn sync_models_with_type(
     pool: &SqlitePool,
     client: &Client,
@@ -110,12 +192,6 @@ pub async fn sync_models_with_type(
     timeout_secs: u64,
     endpoint_type: Option<EndpointType>,
 ) -> Result<SyncResult, SyncError> {
-    // 既存モデルを取得
-    let existing_models: HashSet<String> = match db::list_endpoint_models(pool, endpoint_id).await {
-        Ok(models) => models.into_iter().map(|m| m.model_id).collect(),
-        Err(_) => HashSet::new(),
-    };
-
     // GET /v1/models でモデル一覧を取得
     let url = format!("{}/v1/models", base_url.trim_end_matches('/'));
 
@@ -147,73 +223,113 @@ pub async fn sync_models_with_type(
     // モデル一覧をパース
     let (parsed_models, format) = parse_models_response(&json);
 
-    // 新しいモデルIDのセット
-    let new_model_ids: HashSet<String> = parsed_models.iter().map(|m| m.id.clone()).collect();
-
-    // 差分を計算
-    let added_ids: Vec<_> = new_model_ids.difference(&existing_models).collect();
-    let removed_ids: Vec<_> = existing_models.difference(&new_model_ids).collect();
-    let updated_ids: Vec<_> = new_model_ids.intersection(&existing_models).collect();
-
-    let added = added_ids.len();
-    let removed = removed_ids.len();
-    let updated = updated_ids.len();
-
-    // 削除されたモデルを削除
-    for model_id in removed_ids {
-        let _ = db::delete_endpoint_model(pool, endpoint_id, model_id).await;
-    }
-
-    // 新しいモデルを追加（capabilitiesを自動判定 + エンドポイントからの情報を使用）
+    // capabilitiesを自動判定し、エンドポイントから得た情報で同期後のモデルを組み立てる
     let now = Utc::now();
-    let mut synced_models = Vec::new();
-
-    for model_id in &added_ids {
-        let caps = detect_capabilities(model_id);
-        let caps_vec = Some(capabilities_to_strings(&caps));
+    let mut listed_models: Vec<EndpointModel> = Vec::new();
+    let mut seen = HashSet::new();
+    for parsed in &parsed_models {
+        if !seen.insert(parsed.id.as_str()) {
+            continue;
+        }
+        let caps = detect_capabilities(&parsed.id);
 
         // マッピングテーブルからcanonical_nameを解決
         let canonical_name = endpoint_type
-            .and_then(|et| crate::models::mapping::resolve_canonical(model_id, &et))
+            .and_then(|et| crate::models::mapping::resolve_canonical(&parsed.id, &et))
             .map(|s| s.to_string());
 
-        let model = EndpointModel {
+        listed_models.push(EndpointModel {
             endpoint_id,
-            model_id: (*model_id).clone(),
-            capabilities: caps_vec,
-            max_tokens: None,
+            model_id: parsed.id.clone(),
+            capabilities: Some(capabilities_to_strings(&caps)),
+            // モデル一覧に含まれるコンテキスト長（vLLMの max_model_len 等）
+            max_tokens: parsed.context_length,
             last_checked: Some(now),
-            supported_apis: vec![SupportedAPI::ChatCompletions],
+            supported_apis: supported_apis_for(&caps),
             canonical_name,
-        };
-
-        let _ = db::add_endpoint_model(pool, &model).await;
-        synced_models.push(model);
+        });
     }
 
-    // 既存モデルのlast_checkedを更新
-    for model_id in &updated_ids {
-        let caps = detect_capabilities(model_id);
-        let caps_vec = Some(capabilities_to_strings(&caps));
+    let db_err = |e: sqlx::Error| SyncError::DbError(e.to_string());
+    let mut tx = pool.begin().await.map_err(db_err)?;
 
-        let canonical_name = endpoint_type
-            .and_then(|et| crate::models::mapping::resolve_canonical(model_id, &et))
-            .map(|s| s.to_string());
+    // 前回の同期結果（DB上の一覧）と比較して差分を計算
+    let existing: HashMap<String, EndpointModel> = db::list_endpoint_models(&mut *tx, endpoint_id)
+        .await
+        .map_err(db_err)?
+        .into_iter()
+        .map(|m| (m.model_id.clone(), m))
+        .collect();
+    let listed_ids: HashSet<&str> = listed_models.iter().map(|m| m.model_id.as_str()).collect();
+
+    let mut removed_models: Vec<String> = existing
+        .keys()
+        .filter(|id| !listed_ids.contains(id.as_str()))
+        .cloned()
+        .collect();
+    removed_models.sort();
 
-        let model = EndpointModel {
-            endpoint_id,
-            model_id: (*model_id).clone(),
-            capabilities: caps_vec,
-            max_tokens: None,
-            last_checked: Some(now),
-            supported_apis: vec![SupportedAPI::ChatCompletions],
-            canonical_name,
+    // 削除されたモデルを削除
+    for model_id in &removed_models {
+        db::delete_endpoint_model(&mut *tx, endpoint_id, model_id)
+            .await
+            .map_err(db_err)?;
+    }
+
+    let mut added_models = Vec::new();
+    let mut updated_models = Vec::new();
+    let mut synced_models = Vec::with_capacity(listed_models.len());
+
+    for mut model in listed_models {
+        let Some(previous) = existing.get(&model.model_id) else {
+            // 新しいモデルを追加
+            db::add_endpoint_model(&mut *tx, &model)
+                .await
+                .map_err(db_err)?;
+            added_models.push(model.model_id.clone());
+            synced_models.push(model);
+            continue;
         };
 
-        let _ = db::update_endpoint_model(pool, &model).await;
+        // 既存モデルは内容が変わった場合のみ書き込む
+        let listed_max_tokens = model.max_tokens;
+        model.max_tokens = previous.max_tokens;
+        let mut changed = false;
+        if model.capabilities != previous.capabilities
+            || model.canonical_name != previous.canonical_name
+        {
+            // max_tokens は下で個別に反映する（None は既存値を保持）
+            let record = EndpointModel {
+                max_tokens: None,
+                ..model.clone()
+            };
+            db::update_endpoint_model(&mut *tx, &record)
+                .await
+                .map_err(db_err)?;
+            changed = true;
+        }
+        if let Some(len) = listed_max_tokens.filter(|len| previous.max_tokens != Some(*len)) {
+            // 手動設定済みのモデルは更新されない（差分にも含めない）
+            if db::update_model_max_tokens(&mut *tx, endpoint_id, &model.model_id, len)
+                .await
+                .map_err(db_err)?
+            {
+                model.max_tokens = Some(len);
+                changed = true;
+            }
+        }
+        if changed {
+            updated_models.push(model.model_id.clone());
+        }
         synced_models.push(model);
     }
 
+    // 変化の無いモデルも含めて最終確認時刻を更新
+    db::touch_endpoint_models(&mut *tx, endpoint_id, now)
+        .await
+        .map_err(db_err)?;
+    tx.commit().await.map_err(db_err)?;
+
     // SPEC-e8e9326e: xLLM/Ollamaの場合はmax_tokensを取得
     if let Some(ep_type) = endpoint_type {
         if ep_type == EndpointType::Xllm
@@ -228,30 +344,48 @@ pub async fn sync_models_with_type(
                     .await
                 {
                     Ok(meta) => {
-                        if let Some(context_length) = meta.context_length {
-                            // max_tokensをDBに更新
-                            if let Err(e) = db::update_model_max_tokens(
+                        // GPU選択で空きVRAMと突き合わせるため必要VRAMの見積りを保持する
+                        crate::models::vram::record_required_vram(
+                            endpoint_id,
+                            &model_id,
+                            crate::models::vram::estimate_required_vram_mb(&meta),
+                        );
+                        let current = synced_models
+                            .iter()
+                            .find(|m| m.model_id == model_id)
+                            .and_then(|m| m.max_tokens);
+                        if let Some(context_length) =
+                            meta.context_length.filter(|len| current != Some(*len))
+                        {
+                            // 値が変わった場合のみmax_tokensをDBに更新
+                            let result = db::update_model_max_tokens(
                                 pool,
                                 endpoint_id,
                                 &model_id,
                                 context_length,
                             )
-                            .await
-                            {
+                            .await;
+                            let updated = matches!(result, Ok(true));
+                            if let Err(e) = result {
                                 debug!(
                                     endpoint_id = %endpoint_id,
                                     model_id = %model_id,
                                     error = %e,
                                     "Failed to update max_tokens"
                                 );
-                            } else {
-                                // synced_modelsも更新
+                            } else if updated {
+                                // synced_modelsも更新（手動設定済みのモデルは対象外）
                                 for model in &mut synced_models {
                                     if model.model_id == model_id {
                                         model.max_tokens = Some(context_length);
                                         break;
                                     }
                                 }
+                                if !added_models.contains(&model_id)
+                                    && !updated_models.contains(&model_id)
+                                {
+                                    updated_models.push(model_id.clone());
+                                }
                             }
                         }
                     }
@@ -268,11 +402,17 @@ pub async fn sync_models_with_type(
         }
     }
 
+    added_models.sort();
+    updated_models.sort();
+
     Ok(SyncResult {
         models: synced_models,
-        added,
-        removed,
-        updated,
+        added: added_models.len(),
+        removed: removed_models.len(),
+        updated: updated_models.len(),
+        added_models,
+        removed_models,
+        updated_models,
         format,
     })
 }
@@ -342,6 +482,123 @@ mod tests {
         assert_eq!(updated.len(), 2);
     }
 
+    async fn setup_endpoint(pool: &SqlitePool) -> Uuid {
+        let endpoint = Endpoint::new(
+            "sync-diff".to_string(),
+            "http://localhost:8080".to_string(),
+            EndpointType::Vllm,
+        );
+        db::create_endpoint(pool, &endpoint).await.unwrap();
+        endpoint.id
+    }
+
+    async fn mount_models(server: &wiremock::MockServer, body: serde_json::Value) {
+        use wiremock::matchers::{method, path};
+        server.reset().await;
+        wiremock::Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_sync_writes_and_reports_only_diff() {
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let endpoint_id = setup_endpoint(&pool).await;
+        let server = wiremock::MockServer::start().await;
+        let client = Client::new();
+
+        mount_models(
+            &server,
+            serde_json::json!({"object": "list", "data": [
+                {"id": "model-a", "max_model_len": 4096},
+                {"id": "model-b", "max_model_len": 8192}
+            ]}),
+        )
+        .await;
+        let first = sync_models(&pool, &client, endpoint_id, &server.uri(), None, 5)
+            .await
+            .unwrap();
+        assert_eq!(first.added_models, vec!["model-a", "model-b"]);
+        assert!(first.removed_models.is_empty());
+        assert!(first.updated_models.is_empty());
+
+        // 同じ一覧での再同期は差分なし
+        let again = sync_models(&pool, &client, endpoint_id, &server.uri(), None, 5)
+            .await
+            .unwrap();
+        assert!(again.added_models.is_empty());
+        assert!(again.removed_models.is_empty());
+        assert!(again.updated_models.is_empty());
+        assert_eq!(again.models.len(), 2);
+
+        mount_models(
+            &server,
+            serde_json::json!({"object": "list", "data": [
+                {"id": "model-b", "max_model_len": 32768},
+                {"id": "model-c"}
+            ]}),
+        )
+        .await;
+        let changed = sync_models(&pool, &client, endpoint_id, &server.uri(), None, 5)
+            .await
+            .unwrap();
+        assert_eq!(changed.added_models, vec!["model-c"]);
+        assert_eq!(changed.removed_models, vec!["model-a"]);
+        assert_eq!(changed.updated_models, vec!["model-b"]);
+        assert_eq!((changed.added, changed.removed, changed.updated), (1, 1, 1));
+
+        let mut stored = db::list_endpoint_models(&pool, endpoint_id).await.unwrap();
+        stored.sort_by(|a, b| a.model_id.cmp(&b.model_id));
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].model_id, "model-b");
+        assert_eq!(stored[0].max_tokens, Some(32768));
+        assert!(stored.iter().all(|m| m.last_checked.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_sync_rolls_back_on_partial_failure() {
+        let pool = crate::db::test_utils::test_db_pool().await;
+        let endpoint_id = setup_endpoint(&pool).await;
+        let server = wiremock::MockServer::start().await;
+        let client = Client::new();
+
+        mount_models(
+            &server,
+            serde_json::json!({"object": "list", "data": [{"id": "model-a"}]}),
+        )
+        .await;
+        sync_models(&pool, &client, endpoint_id, &server.uri(), None, 5)
+            .await
+            .unwrap();
+
+        // 2件目の追加で失敗させる（model-a の削除と model-b の追加は取り消されるべき）
+        sqlx::query(
+            "CREATE TRIGGER fail_model_insert BEFORE INSERT ON endpoint_models \
+             WHEN NEW.model_id = 'model-broken' BEGIN SELECT RAISE(ABORT, 'boom'); END",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        mount_models(
+            &server,
+            serde_json::json!({"object": "list", "data": [
+                {"id": "model-b"},
+                {"id": "model-broken"}
+            ]}),
+        )
+        .await;
+        let err = sync_models(&pool, &client, endpoint_id, &server.uri(), None, 5)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SyncError::DbError(_)));
+
+        let stored = db::list_endpoint_models(&pool, endpoint_id).await.unwrap();
+        let ids: Vec<_> = stored.iter().map(|m| m.model_id.as_str()).collect();
+        assert_eq!(ids, vec!["model-a"]);
+    }
+
     #[test]
     fn test_sync_error_display() {
         let err = SyncError::ConnectionError("timeout".to_string());
@@ -9,6 +9,24 @@ use serde::Deserialize;
 pub struct ParsedModel {
     /// モデルID/名前
     pub id: String,
+    /// コンテキスト長（レスポンスに含まれる場合）
+    pub context_length: Option<u32>,
+}
+
+/// モデル一覧の各要素からコンテキスト長を抽出
+///
+/// vLLM（`max_model_len`）、LM Studio（`max_context_length`）などの拡張フィールドに対応する。
+fn extract_context_length(model: &serde_json::Value) -> Option<u32> {
+    [
+        "max_model_len",
+        "context_length",
+        "max_context_length",
+        "context_window",
+    ]
+    .iter()
+    .find_map(|key| model.get(*key).and_then(|v| v.as_u64()))
+    .filter(|len| *len > 0)
+    .map(|len| len.min(u32::MAX as u64) as u32)
 }
 
 /// OpenAI形式のモデルレスポンス
@@ -82,7 +100,10 @@ pub fn parse_models_response(json: &serde_json::Value) -> (Vec<ParsedModel>, Res
             .iter()
             .filter_map(|model| {
                 let id = model.get("id").and_then(|id| id.as_str())?;
-                Some(ParsedModel { id: id.to_string() })
+                Some(ParsedModel {
+                    id: id.to_string(),
+                    context_length: extract_context_length(model),
+                })
             })
             .collect();
         return (models, ResponseFormat::OpenAi);
@@ -99,7 +120,10 @@ pub fn parse_models_response(json: &serde_json::Value) -> (Vec<ParsedModel>, Res
                     .and_then(|n| n.as_str())
                     .or_else(|| model.get("model").and_then(|m| m.as_str()));
                 let id = id.filter(|s| !s.is_empty())?;
-                Some(ParsedModel { id: id.to_string() })
+                Some(ParsedModel {
+                    id: id.to_string(),
+                    context_length: extract_context_length(model),
+                })
             })
             .collect();
         return (models, ResponseFormat::Ollama);
@@ -142,6 +166,26 @@ mod tests {
         assert_eq!(models[1].id, "gpt-3.5-turbo");
     }
 
+    #[test]
+    fn test_parse_context_length_fields() {
+        let json = json!({
+            "data": [
+                {"id": "vllm-model", "max_model_len": 32768},
+                {"id": "lmstudio-model", "max_context_length": 131072},
+                {"id": "generic-model", "context_length": 8192},
+                {"id": "no-context"},
+                {"id": "zero-context", "max_model_len": 0}
+            ]
+        });
+
+        let (models, _) = parse_models_response(&json);
+        assert_eq!(models[0].context_length, Some(32768));
+        assert_eq!(models[1].context_length, Some(131072));
+        assert_eq!(models[2].context_length, Some(8192));
+        assert_eq!(models[3].context_length, None);
+        assert_eq!(models[4].context_length, None);
+    }
+
     #[test]
     fn test_parse_ollama_format() {
         let json = json!({
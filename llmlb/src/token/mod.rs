@@ -2,9 +2,31 @@
 //!
 //! OpenAI互換レスポンスからトークン数を抽出し、
 //! usageフィールドがない場合はtiktokenで推定する。
+//!
+//! エンドポイントの応答形式（OpenAI / Anthropic / TGI）ごとに `usage` の
+//! 位置が異なるため、`EndpointType` から主形式を決めて形式別パーサで抽出する。
+//! 抽出・推定のどちらを使ったかは `llmlb_token_usage_source_total` に記録する。
 
+use crate::types::endpoint::EndpointType;
+use once_cell::sync::Lazy;
+use prometheus::{IntCounterVec, Opts};
 use serde_json::Value;
-use tiktoken_rs::cl100k_base;
+use tiktoken_rs::{cl100k_base, o200k_base, p50k_base, r50k_base, CoreBPE};
+
+static USAGE_SOURCES: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "llmlb_token_usage_source_total",
+            "Token usage records by source (reported by endpoint or estimated)",
+        ),
+        &["endpoint_type", "source"],
+    )
+    .expect("counter vec");
+    crate::metrics::registry()
+        .register(Box::new(counter.clone()))
+        .ok();
+    counter
+});
 
 /// トークン使用量
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -31,18 +53,193 @@ impl TokenUsage {
     pub fn is_empty(&self) -> bool {
         self.input_tokens.is_none() && self.output_tokens.is_none() && self.total_tokens.is_none()
     }
+
+    /// 未設定のフィールドを `other` の値で補完する
+    ///
+    /// totalが未設定の場合は補完後の入力・出力から再計算する。
+    fn merge_missing(self, other: &TokenUsage) -> Self {
+        let input_tokens = self.input_tokens.or(other.input_tokens);
+        let output_tokens = self.output_tokens.or(other.output_tokens);
+        let total_tokens = self
+            .total_tokens
+            .or_else(|| match (input_tokens, output_tokens) {
+                (Some(i), Some(o)) => Some(i + o),
+                _ => other.total_tokens,
+            });
+        Self::new(input_tokens, output_tokens, total_tokens)
+    }
+}
+
+/// トークン使用量の取得元
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenUsageSource {
+    /// エンドポイント応答の `usage` 等から抽出
+    Reported,
+    /// tiktokenによる推定
+    Estimated,
+}
+
+impl TokenUsageSource {
+    /// メトリクスラベル用の文字列
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenUsageSource::Reported => "reported",
+            TokenUsageSource::Estimated => "estimated",
+        }
+    }
+}
+
+/// エンドポイント応答のusage形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageFormat {
+    /// `usage.prompt_tokens` / `usage.completion_tokens`（Responses APIの `response.usage` を含む）
+    OpenAi,
+    /// `usage.input_tokens` / `usage.output_tokens`（ストリーミングは `message.usage`）
+    Anthropic,
+    /// `details.generated_tokens` / `details.prefill`（text-generation-inference）
+    Tgi,
+}
+
+impl UsageFormat {
+    /// 全形式（フォールバック時の試行順）
+    pub const ALL: [UsageFormat; 3] = [
+        UsageFormat::OpenAi,
+        UsageFormat::Anthropic,
+        UsageFormat::Tgi,
+    ];
+
+    /// エンドポイントタイプの主形式
+    ///
+    /// 現在の対応タイプはいずれもOpenAI互換APIで推論を中継するため、主形式は
+    /// OpenAI形式となる。タイプ追加時はここで形式を決めること。
+    pub fn for_endpoint_type(endpoint_type: EndpointType) -> Self {
+        match endpoint_type {
+            EndpointType::Xllm
+            | EndpointType::Ollama
+            | EndpointType::Vllm
+            | EndpointType::LmStudio
+            | EndpointType::Llamacpp
+            | EndpointType::OpenaiCompatible => UsageFormat::OpenAi,
+        }
+    }
+
+    /// この形式でusageを抽出する
+    pub fn extract(&self, response_body: &Value) -> Option<TokenUsage> {
+        match self {
+            UsageFormat::OpenAi => extract_openai_usage(response_body),
+            UsageFormat::Anthropic => extract_anthropic_usage(response_body),
+            UsageFormat::Tgi => extract_tgi_usage(response_body),
+        }
+    }
+}
+
+/// ストリーミング応答の出力トークンカウンタ
+///
+/// SSEイベントのデルタ（本文・推論・ツール呼び出し引数）を受信ごとにトークン化して数える。
+/// usage を返さないプロバイダの出力トークン数や、途中で切断されたストリームの
+/// 部分的な出力トークン数に使う。
+#[derive(Debug)]
+pub struct StreamingTokenCounter {
+    /// モデル名（エンコーディングの選択に使用）
+    model: String,
+    /// 数えた出力トークン数
+    output_tokens: u32,
+}
+
+impl StreamingTokenCounter {
+    /// 新しいカウンタを作成
+    pub fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            output_tokens: 0,
+        }
+    }
+
+    /// パース済みのSSEイベント1件からデルタを数える
+    ///
+    /// OpenAI Chat（`choices[].delta`）、Open Responses（`response.*.delta`）、
+    /// Anthropic Messages（`content_block_delta`）、TGI（`token`）に対応する。
+    pub fn observe_event(&mut self, event: &Value) {
+        if let Some(choices) = event.get("choices").and_then(Value::as_array) {
+            for delta in choices.iter().filter_map(|choice| choice.get("delta")) {
+                for key in ["content", "reasoning_content", "reasoning"] {
+                    if let Some(text) = delta.get(key).and_then(Value::as_str) {
+                        self.count_delta(text);
+                    }
+                }
+                if let Some(tool_calls) = delta.get("tool_calls").and_then(Value::as_array) {
+                    for arguments in tool_calls.iter().filter_map(|call| {
+                        call.get("function")
+                            .and_then(|function| function.get("arguments"))
+                            .and_then(Value::as_str)
+                    }) {
+                        self.count_delta(arguments);
+                    }
+                }
+            }
+        }
+
+        match event.get("type").and_then(Value::as_str) {
+            Some("response.output_text.delta")
+            | Some("response.function_call_arguments.delta")
+            | Some("response.reasoning_summary_text.delta") => {
+                if let Some(delta) = event.get("delta").and_then(Value::as_str) {
+                    self.count_delta(delta);
+                }
+            }
+            Some("content_block_delta") => {
+                if let Some(delta) = event.get("delta") {
+                    for key in ["text", "thinking", "partial_json"] {
+                        if let Some(text) = delta.get(key).and_then(Value::as_str) {
+                            self.count_delta(text);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        // TGI はイベント1件が1トークン（特殊トークンは除く）
+        if let Some(token) = event.get("token").filter(|token| token.is_object()) {
+            let special = token
+                .get("special")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            if !special {
+                self.output_tokens = self.output_tokens.saturating_add(1);
+            }
+        }
+    }
+
+    /// デルタ文字列のトークン数を加算する
+    pub fn count_delta(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let tokens = estimate_tokens_exact(&self.model, text).tokens;
+        self.output_tokens = self
+            .output_tokens
+            .saturating_add(u32::try_from(tokens).unwrap_or(u32::MAX));
+    }
+
+    /// これまでに数えた出力トークン数
+    pub fn output_tokens(&self) -> u32 {
+        self.output_tokens
+    }
 }
 
 /// SSEストリーミングレスポンスのトークン累積器
 ///
 /// OpenAI互換のSSEストリーミングレスポンスをパースし、
-/// チャンクごとにコンテンツを累積してトークン使用量を計算する
+/// チャンクごとにコンテンツを累積してトークン使用量を計算する。
+/// 出力トークン数は usage が返ればその値を優先し、返らない場合は
+/// [`StreamingTokenCounter`] で数えた値を使う（途中切断時は部分的な値になる）。
 #[derive(Debug)]
 pub struct StreamingTokenAccumulator {
-    /// モデル名（トークン推定用）
-    model: String,
     /// 累積されたコンテンツ
     accumulated_content: String,
+    /// デルタごとの出力トークンカウンタ
+    counter: StreamingTokenCounter,
     /// 入力トークン数（リクエスト時に設定可能）
     input_tokens: Option<u32>,
     /// 抽出されたusageフィールド（最終チャンクから）
@@ -55,8 +252,8 @@ impl StreamingTokenAccumulator {
     /// 新しいStreamingTokenAccumulatorを作成
     pub fn new(model: &str) -> Self {
         Self {
-            model: model.to_string(),
             accumulated_content: String::new(),
+            counter: StreamingTokenCounter::new(model),
             input_tokens: None,
             extracted_usage: None,
             done: false,
@@ -93,9 +290,15 @@ impl StreamingTokenAccumulator {
 
         // JSONパース
         if let Ok(json) = serde_json::from_str::<Value>(data) {
+            self.counter.observe_event(&json);
+
             // usageフィールドを抽出（最終チャンクに含まれる場合がある）
+            // Anthropic形式は message_start（入力）と message_delta（出力）に分かれるため補完する
             if let Some(usage) = extract_usage_from_response(&json) {
-                self.extracted_usage = Some(usage);
+                self.extracted_usage = Some(match self.extracted_usage.take() {
+                    Some(previous) => usage.merge_missing(&previous),
+                    None => usage,
+                });
             }
 
             // delta.contentを抽出して累積
@@ -124,6 +327,7 @@ impl StreamingTokenAccumulator {
                         if self.accumulated_content.is_empty() {
                             if let Some(text) = json.get("text").and_then(|t| t.as_str()) {
                                 self.accumulated_content.push_str(text);
+                                self.counter.count_delta(text);
                             }
                         }
                     }
@@ -143,20 +347,35 @@ impl StreamingTokenAccumulator {
         self.done
     }
 
+    /// デルタごとに数えた出力トークン数（usage の有無に関係なく、途中切断時は部分的な値）
+    pub fn counted_output_tokens(&self) -> u32 {
+        self.counter.output_tokens()
+    }
+
+    /// 最終的なTokenUsageの取得元
+    pub fn usage_source(&self) -> TokenUsageSource {
+        if self
+            .extracted_usage
+            .as_ref()
+            .is_some_and(|usage| usage.output_tokens.is_some())
+        {
+            TokenUsageSource::Reported
+        } else {
+            TokenUsageSource::Estimated
+        }
+    }
+
     /// 最終的なTokenUsageを計算
     pub fn finalize(&self) -> TokenUsage {
-        // usageフィールドが抽出されている場合はそれを使用
+        let counted = TokenUsage::new(self.input_tokens, Some(self.counter.output_tokens()), None);
+
+        // usageフィールドが抽出されている場合はそれを優先し、欠けている値のみ補完する
         if let Some(ref usage) = self.extracted_usage {
-            return usage.clone();
+            return usage.clone().merge_missing(&counted);
         }
 
-        // usageがない場合はtiktokenで推定
-        let output_tokens = if self.accumulated_content.is_empty() {
-            Some(0)
-        } else {
-            estimate_tokens(&self.accumulated_content, &self.model)
-        };
-
+        // usageがない場合はデルタごとに数えた値を使う
+        let output_tokens = counted.output_tokens;
         let input_tokens = self.input_tokens;
 
         // total_tokensを計算
@@ -171,15 +390,40 @@ impl StreamingTokenAccumulator {
     }
 }
 
-/// OpenAI互換レスポンスのusageフィールドからトークン数を抽出
+/// レスポンスのusageフィールドからトークン数を抽出
+///
+/// 形式が不明な場合に使用し、OpenAI → Anthropic → TGI の順に試行する。
 ///
 /// # Arguments
-/// * `response_body` - OpenAI互換APIレスポンスのJSON
+/// * `response_body` - APIレスポンスのJSON
 ///
 /// # Returns
 /// * `Some(TokenUsage)` - usageフィールドが存在する場合
 /// * `None` - usageフィールドが存在しない場合
 pub fn extract_usage_from_response(response_body: &Value) -> Option<TokenUsage> {
+    UsageFormat::ALL
+        .iter()
+        .find_map(|format| format.extract(response_body))
+}
+
+/// エンドポイントタイプに応じた形式でusageを抽出
+///
+/// 主形式で見つからない場合は他の形式も試行する。
+pub fn extract_usage_for_endpoint(
+    response_body: &Value,
+    endpoint_type: EndpointType,
+) -> Option<TokenUsage> {
+    let primary = UsageFormat::for_endpoint_type(endpoint_type);
+    primary.extract(response_body).or_else(|| {
+        UsageFormat::ALL
+            .iter()
+            .filter(|format| **format != primary)
+            .find_map(|format| format.extract(response_body))
+    })
+}
+
+/// OpenAI形式（Chat Completions / Responses API）のusageを抽出
+fn extract_openai_usage(response_body: &Value) -> Option<TokenUsage> {
     let usage = response_body
         .get("usage")
         .or_else(|| response_body.get("response").and_then(|r| r.get("usage")))?;
@@ -205,21 +449,253 @@ pub fn extract_usage_from_response(response_body: &Value) -> Option<TokenUsage>
     Some(TokenUsage::new(input_tokens, output_tokens, total_tokens))
 }
 
-/// tiktokenを使用してテキストのトークン数を推定
+/// Anthropic Messages API形式のusageを抽出
+///
+/// 非ストリーミングと `message_delta` はトップレベルの `usage`、
+/// `message_start` は `message.usage` に含まれる。total は返されないため合算する。
+fn extract_anthropic_usage(response_body: &Value) -> Option<TokenUsage> {
+    let usage = response_body
+        .get("usage")
+        .or_else(|| response_body.get("message").and_then(|m| m.get("usage")))?;
+
+    let input_tokens = usage
+        .get("input_tokens")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    let output_tokens = usage
+        .get("output_tokens")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    if input_tokens.is_none() && output_tokens.is_none() {
+        return None;
+    }
+
+    let total_tokens = match (input_tokens, output_tokens) {
+        (Some(i), Some(o)) => Some(i + o),
+        _ => None,
+    };
+    Some(TokenUsage::new(input_tokens, output_tokens, total_tokens))
+}
+
+/// text-generation-inference（`/generate`）形式のusageを抽出
+///
+/// 出力は `details.generated_tokens`、入力は `decoder_input_details` 指定時のみ
+/// `details.prefill` の要素数から得られる。バッチ応答（配列）は先頭要素を使用する。
+fn extract_tgi_usage(response_body: &Value) -> Option<TokenUsage> {
+    let body = match response_body {
+        Value::Array(items) => items.first()?,
+        other => other,
+    };
+    let details = body.get("details")?;
+
+    let output_tokens = details
+        .get("generated_tokens")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)?;
+    let input_tokens = details
+        .get("prefill")
+        .and_then(|v| v.as_array())
+        .filter(|prefill| !prefill.is_empty())
+        .map(|prefill| prefill.len() as u32);
+
+    let total_tokens = Some(input_tokens.unwrap_or(0) + output_tokens);
+    Some(TokenUsage::new(
+        input_tokens,
+        Some(output_tokens),
+        total_tokens,
+    ))
+}
+
+/// 推定用にレスポンス本文のテキストを抽出（OpenAI / Anthropic / TGI 形式）
+pub fn extract_response_text(response_body: &Value) -> String {
+    let mut text = String::new();
+    if let Some(choices) = response_body.get("choices").and_then(|c| c.as_array()) {
+        for choice in choices {
+            if let Some(content) = choice
+                .get("message")
+                .and_then(|m| m.get("content"))
+                .or_else(|| choice.get("text"))
+                .and_then(|c| c.as_str())
+            {
+                text.push_str(content);
+            }
+        }
+    } else if let Some(blocks) = response_body.get("content").and_then(|c| c.as_array()) {
+        for block in blocks {
+            if let Some(content) = block.get("text").and_then(|t| t.as_str()) {
+                text.push_str(content);
+            }
+        }
+    } else if let Some(generated) = response_body
+        .get("generated_text")
+        .or_else(|| {
+            response_body
+                .as_array()
+                .and_then(|items| items.first())
+                .and_then(|item| item.get("generated_text"))
+        })
+        .and_then(|t| t.as_str())
+    {
+        text.push_str(generated);
+    }
+    text
+}
+
+/// トークン使用量の取得元を記録する
+pub fn record_usage_source(endpoint_type: EndpointType, source: TokenUsageSource) {
+    USAGE_SOURCES
+        .with_label_values(&[endpoint_type.as_str(), source.as_str()])
+        .inc();
+    tracing::debug!(
+        endpoint_type = endpoint_type.as_str(),
+        source = source.as_str(),
+        "Token usage resolved"
+    );
+}
+
+/// 取得元別のトークン使用量記録数
+pub fn usage_source_count(endpoint_type: EndpointType, source: TokenUsageSource) -> u64 {
+    USAGE_SOURCES
+        .with_label_values(&[endpoint_type.as_str(), source.as_str()])
+        .get()
+}
+
+/// tiktoken互換のエンコーディング
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenEncoding {
+    /// GPT-4o / GPT-4.1 / GPT-5 / o系列 / gpt-oss
+    O200kBase,
+    /// GPT-4 / GPT-3.5 / text-embedding-3 / text-embedding-ada-002
+    Cl100kBase,
+    /// text-davinci-002/003 / code-davinci
+    P50kBase,
+    /// GPT-3（davinci, curie, babbage, ada）
+    R50kBase,
+}
+
+impl TokenEncoding {
+    /// エンコーディング名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenEncoding::O200kBase => "o200k_base",
+            TokenEncoding::Cl100kBase => "cl100k_base",
+            TokenEncoding::P50kBase => "p50k_base",
+            TokenEncoding::R50kBase => "r50k_base",
+        }
+    }
+
+    fn bpe(&self) -> Option<&'static CoreBPE> {
+        match self {
+            TokenEncoding::O200kBase => O200K_BASE.as_ref(),
+            TokenEncoding::Cl100kBase => CL100K_BASE.as_ref(),
+            TokenEncoding::P50kBase => P50K_BASE.as_ref(),
+            TokenEncoding::R50kBase => R50K_BASE.as_ref(),
+        }
+    }
+}
+
+// BPEの構築は重いため、エンコーディングごとに一度だけ構築して使い回す
+static O200K_BASE: Lazy<Option<CoreBPE>> = Lazy::new(|| o200k_base().ok());
+static CL100K_BASE: Lazy<Option<CoreBPE>> = Lazy::new(|| cl100k_base().ok());
+static P50K_BASE: Lazy<Option<CoreBPE>> = Lazy::new(|| p50k_base().ok());
+static R50K_BASE: Lazy<Option<CoreBPE>> = Lazy::new(|| r50k_base().ok());
+
+/// モデル名に対応するエンコーディング（前方一致の順に評価）
+const MODEL_ENCODINGS: &[(&str, TokenEncoding)] = &[
+    ("gpt-4o", TokenEncoding::O200kBase),
+    ("chatgpt-4o", TokenEncoding::O200kBase),
+    ("gpt-4.1", TokenEncoding::O200kBase),
+    ("gpt-4.5", TokenEncoding::O200kBase),
+    ("gpt-5", TokenEncoding::O200kBase),
+    ("gpt-oss", TokenEncoding::O200kBase),
+    ("o1", TokenEncoding::O200kBase),
+    ("o3", TokenEncoding::O200kBase),
+    ("o4", TokenEncoding::O200kBase),
+    ("gpt-4", TokenEncoding::Cl100kBase),
+    ("gpt-3.5", TokenEncoding::Cl100kBase),
+    ("gpt-35", TokenEncoding::Cl100kBase),
+    ("text-embedding-3", TokenEncoding::Cl100kBase),
+    ("text-embedding-ada-002", TokenEncoding::Cl100kBase),
+    ("text-davinci-002", TokenEncoding::P50kBase),
+    ("text-davinci-003", TokenEncoding::P50kBase),
+    ("code-davinci", TokenEncoding::P50kBase),
+    ("code-cushman", TokenEncoding::P50kBase),
+    ("davinci-002", TokenEncoding::Cl100kBase),
+    ("babbage-002", TokenEncoding::Cl100kBase),
+    ("text-davinci-001", TokenEncoding::R50kBase),
+    ("text-curie", TokenEncoding::R50kBase),
+    ("text-babbage", TokenEncoding::R50kBase),
+    ("text-ada", TokenEncoding::R50kBase),
+    ("davinci", TokenEncoding::R50kBase),
+    ("curie", TokenEncoding::R50kBase),
+    ("babbage", TokenEncoding::R50kBase),
+    ("ada", TokenEncoding::R50kBase),
+];
+
+/// モデル名からtiktoken互換のエンコーディングを選択する
+///
+/// `openai:gpt-4o` や `openai/gpt-4o` のようなプロバイダ接頭辞は除いて判定する。
+/// 未知のモデルは `None`。
+pub fn encoding_for_model(model: &str) -> Option<TokenEncoding> {
+    let lower = model.trim().to_ascii_lowercase();
+    let leaf = lower.rsplit('/').next().unwrap_or(&lower);
+    let candidates = [Some(leaf), leaf.split_once(':').map(|(_, rest)| rest)];
+    candidates.into_iter().flatten().find_map(|name| {
+        MODEL_ENCODINGS
+            .iter()
+            .find(|(prefix, _)| {
+                name.strip_prefix(prefix)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['-', '.', ':', '@']))
+            })
+            .map(|(_, encoding)| *encoding)
+    })
+}
+
+/// トークン数の推定結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenEstimate {
+    /// トークン数
+    pub tokens: usize,
+    /// モデルのエンコーディングで数えた正確な値か（`false` はヒューリスティック）
+    pub exact: bool,
+}
+
+/// モデルのエンコーディングでトークン数を数える
+///
+/// エンコーディングが既知のモデルは tiktoken 互換の正確な値（`exact: true`）を返す。
+/// 未知のモデルは従来どおり cl100k_base による近似（構築できない場合は4文字≒1トークン）に
+/// フォールバックし、`exact: false` を返す。
+pub fn estimate_tokens_exact(model: &str, text: &str) -> TokenEstimate {
+    if let Some(bpe) = encoding_for_model(model).and_then(|encoding| encoding.bpe()) {
+        return TokenEstimate {
+            tokens: bpe.encode_with_special_tokens(text).len(),
+            exact: true,
+        };
+    }
+
+    let tokens = match CL100K_BASE.as_ref() {
+        Some(bpe) => bpe.encode_with_special_tokens(text).len(),
+        None => text.chars().count().div_ceil(4),
+    };
+    TokenEstimate {
+        tokens,
+        exact: false,
+    }
+}
+
+/// テキストのトークン数を推定
 ///
 /// # Arguments
 /// * `text` - トークン数を推定するテキスト
-/// * `_model` - モデル名（現在は未使用、将来的にモデル別エンコーディングに対応）
+/// * `model` - モデル名（エンコーディングの選択に使用）
 ///
 /// # Returns
 /// * `Some(u32)` - 推定トークン数
 /// * `None` - 推定できない場合
-pub fn estimate_tokens(text: &str, _model: &str) -> Option<u32> {
-    // cl100k_base エンコーディングを使用（GPT-4, GPT-3.5-turbo互換）
-    // llama系モデルも概ね近い値になるため、フォールバックとして使用
-    let bpe = cl100k_base().ok()?;
-    let tokens = bpe.encode_with_special_tokens(text);
-    Some(tokens.len() as u32)
+pub fn estimate_tokens(text: &str, model: &str) -> Option<u32> {
+    // エンコーディングが既知のモデルは正確な値、それ以外は cl100k_base による近似
+    // （llama系モデルも概ね近い値になる）
+    u32::try_from(estimate_tokens_exact(model, text).tokens).ok()
 }
 
 /// トークン抽出（usageフィールド優先、フォールバックでtiktoken推定）
@@ -258,6 +734,139 @@ pub fn extract_or_estimate_tokens(
     TokenUsage::new(input_tokens, output_tokens, total_tokens)
 }
 
+/// エンドポイントタイプ別のトークン抽出（usage優先、フォールバックでtiktoken推定）
+///
+/// 出力テキストは応答本文から抽出して推定に使用し、取得元を記録して返す。
+pub fn extract_or_estimate_tokens_for_endpoint(
+    response_body: &Value,
+    request_text: Option<&str>,
+    model: &str,
+    endpoint_type: EndpointType,
+) -> (TokenUsage, TokenUsageSource) {
+    let (usage, source) = match extract_usage_for_endpoint(response_body, endpoint_type) {
+        Some(usage) => (usage, TokenUsageSource::Reported),
+        None => {
+            let response_text = extract_response_text(response_body);
+            (
+                extract_or_estimate_tokens(&Value::Null, request_text, Some(&response_text), model),
+                TokenUsageSource::Estimated,
+            )
+        }
+    };
+    record_usage_source(endpoint_type, source);
+    (usage, source)
+}
+
+/// 推定用にリクエストペイロードから入力テキストを抽出
+///
+/// Chat Completions（`messages`）、Completions（`prompt`）、Responses API
+/// （`instructions` / `input`）の各形式に対応する。
+pub fn extract_request_text(payload: &Value) -> String {
+    fn push_content(value: &Value, text: &mut String) {
+        match value {
+            Value::String(content) => text.push_str(content),
+            Value::Array(items) => items.iter().for_each(|item| push_content(item, text)),
+            Value::Object(object) => {
+                if let Some(content) = object.get("text").or_else(|| object.get("content")) {
+                    push_content(content, text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut text = String::new();
+    for key in ["instructions", "messages", "prompt", "input"] {
+        if let Some(value) = payload.get(key) {
+            push_content(value, &mut text);
+        }
+    }
+    text
+}
+
+/// usageを返さないアップストリーム向けに、推定値で応答の `usage` を補完する
+///
+/// 応答に `usage` があればそれを抽出する。無い場合は入力・出力をモデルの
+/// エンコーディングで数え、両方とも正確な値（exact）のときのみ OpenAI 形式の
+/// `usage` を応答本文に追加する。取得元は `llmlb_token_usage_source_total` に記録する。
+pub fn complete_usage_for_endpoint(
+    response_body: &mut Value,
+    request_text: Option<&str>,
+    model: &str,
+    endpoint_type: EndpointType,
+) -> (TokenUsage, TokenUsageSource) {
+    if let Some(usage) = extract_usage_for_endpoint(response_body, endpoint_type) {
+        record_usage_source(endpoint_type, TokenUsageSource::Reported);
+        return (usage, TokenUsageSource::Reported);
+    }
+
+    let output = estimate_tokens_exact(model, &extract_response_text(response_body));
+    let input = request_text.map(|text| estimate_tokens_exact(model, text));
+    let input_tokens = input.map(|estimate| estimate.tokens as u32);
+    let output_tokens = output.tokens as u32;
+    let total_tokens = input_tokens.unwrap_or(0) + output_tokens;
+
+    if let (Some(input), Some(body)) = (input, response_body.as_object_mut()) {
+        if input.exact && output.exact {
+            body.insert(
+                "usage".to_string(),
+                serde_json::json!({
+                    "prompt_tokens": input.tokens,
+                    "completion_tokens": output.tokens,
+                    "total_tokens": input.tokens + output.tokens,
+                }),
+            );
+        }
+    }
+
+    record_usage_source(endpoint_type, TokenUsageSource::Estimated);
+    (
+        TokenUsage::new(input_tokens, Some(output_tokens), Some(total_tokens)),
+        TokenUsageSource::Estimated,
+    )
+}
+
+/// Embeddings API の `input` から入力トークン数を推定
+///
+/// 文字列・文字列配列（バッチ入力）・トークンID配列・トークンID配列の配列に対応する。
+/// トークンIDはその要素数をトークン数とする。
+pub fn estimate_embedding_input_tokens(input: &Value, model: &str) -> Option<u32> {
+    match input {
+        Value::String(text) => estimate_tokens(text, model),
+        Value::Number(_) => Some(1),
+        Value::Array(items) => items.iter().try_fold(0u32, |total, item| {
+            estimate_embedding_input_tokens(item, model).map(|tokens| total + tokens)
+        }),
+        _ => None,
+    }
+}
+
+/// Embeddings 応答のトークン抽出（usage優先、フォールバックで `input` から推定）
+///
+/// Embeddings は出力トークンを持たないため、推定時の出力は0とする。
+pub fn extract_or_estimate_embedding_tokens(
+    response_body: &Value,
+    input: Option<&Value>,
+    model: &str,
+    endpoint_type: EndpointType,
+) -> (TokenUsage, TokenUsageSource) {
+    let (usage, source) = match extract_usage_for_endpoint(response_body, endpoint_type)
+        .filter(|usage| usage.input_tokens.is_some())
+    {
+        Some(usage) => (usage, TokenUsageSource::Reported),
+        None => {
+            let input_tokens =
+                input.and_then(|input| estimate_embedding_input_tokens(input, model));
+            (
+                TokenUsage::new(input_tokens, Some(0), input_tokens),
+                TokenUsageSource::Estimated,
+            )
+        }
+    };
+    record_usage_source(endpoint_type, source);
+    (usage, source)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -850,4 +1459,312 @@ mod tests {
         assert_eq!(usage.output_tokens, Some(3));
         assert_eq!(usage.total_tokens, Some(13));
     }
+
+    #[test]
+    fn test_extract_anthropic_usage_formats() {
+        let response = json!({
+            "type": "message",
+            "content": [{"type": "text", "text": "Hi"}],
+            "usage": {"input_tokens": 12, "output_tokens": 4}
+        });
+        let usage = UsageFormat::Anthropic.extract(&response).unwrap();
+        assert_eq!(usage, TokenUsage::new(Some(12), Some(4), Some(16)));
+
+        let message_start = json!({
+            "type": "message_start",
+            "message": {"usage": {"input_tokens": 7, "output_tokens": 1}}
+        });
+        assert_eq!(
+            extract_usage_from_response(&message_start),
+            Some(TokenUsage::new(Some(7), Some(1), Some(8)))
+        );
+    }
+
+    #[test]
+    fn test_extract_tgi_usage_formats() {
+        let response = json!({
+            "generated_text": "Hello world",
+            "details": {
+                "finish_reason": "length",
+                "generated_tokens": 2,
+                "prefill": [{"id": 1}, {"id": 2}, {"id": 3}]
+            }
+        });
+        let usage = UsageFormat::Tgi.extract(&response).unwrap();
+        assert_eq!(usage, TokenUsage::new(Some(3), Some(2), Some(5)));
+
+        // バッチ応答（配列）かつ prefill なし
+        let batch = json!([{"generated_text": "a", "details": {"generated_tokens": 9}}]);
+        let usage = extract_usage_from_response(&batch).unwrap();
+        assert_eq!(usage, TokenUsage::new(None, Some(9), Some(9)));
+        assert_eq!(extract_response_text(&batch), "a");
+    }
+
+    #[test]
+    fn test_extract_usage_for_endpoint_falls_back_to_other_formats() {
+        assert_eq!(
+            UsageFormat::for_endpoint_type(EndpointType::Vllm),
+            UsageFormat::OpenAi
+        );
+        let tgi = json!({"details": {"generated_tokens": 4}});
+        assert_eq!(
+            extract_usage_for_endpoint(&tgi, EndpointType::OpenaiCompatible)
+                .and_then(|u| u.output_tokens),
+            Some(4)
+        );
+        assert!(extract_usage_for_endpoint(&json!({"choices": []}), EndpointType::Vllm).is_none());
+    }
+
+    #[test]
+    fn test_extract_or_estimate_for_endpoint_records_source() {
+        let endpoint_type = EndpointType::LmStudio;
+        let reported_before = usage_source_count(endpoint_type, TokenUsageSource::Reported);
+        let estimated_before = usage_source_count(endpoint_type, TokenUsageSource::Estimated);
+
+        let with_usage = json!({
+            "choices": [{"message": {"content": "Hello"}}],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 1, "total_tokens": 4}
+        });
+        let (usage, source) =
+            extract_or_estimate_tokens_for_endpoint(&with_usage, None, "m", endpoint_type);
+        assert_eq!(source, TokenUsageSource::Reported);
+        assert_eq!(usage.total_tokens, Some(4));
+
+        let without_usage = json!({"choices": [{"message": {"content": "Hello world"}}]});
+        let (usage, source) =
+            extract_or_estimate_tokens_for_endpoint(&without_usage, None, "m", endpoint_type);
+        assert_eq!(source, TokenUsageSource::Estimated);
+        assert!(usage.output_tokens.unwrap_or(0) > 0);
+
+        assert_eq!(
+            usage_source_count(endpoint_type, TokenUsageSource::Reported),
+            reported_before + 1
+        );
+        assert_eq!(
+            usage_source_count(endpoint_type, TokenUsageSource::Estimated),
+            estimated_before + 1
+        );
+    }
+
+    #[test]
+    fn test_streaming_accumulator_merges_anthropic_usage_events() {
+        let mut accumulator = StreamingTokenAccumulator::new("test-model");
+        assert_eq!(accumulator.usage_source(), TokenUsageSource::Estimated);
+
+        accumulator.process_chunk(
+            r#"data: {"type":"message_start","message":{"usage":{"input_tokens":20,"output_tokens":1}}}"#,
+        );
+        accumulator.process_chunk(r#"data: {"type":"message_delta","usage":{"output_tokens":6}}"#);
+
+        assert_eq!(accumulator.usage_source(), TokenUsageSource::Reported);
+        let usage = accumulator.finalize();
+        assert_eq!(usage.input_tokens, Some(20));
+        assert_eq!(usage.output_tokens, Some(6));
+        assert_eq!(usage.total_tokens, Some(26));
+    }
+
+    #[test]
+    fn test_streaming_token_counter_counts_deltas_across_formats() {
+        let mut counter = StreamingTokenCounter::new("gpt-4");
+        counter.observe_event(&json!({"choices": [{"delta": {"content": "Hello"}}]}));
+        counter.observe_event(&json!({"choices": [{"delta": {"reasoning_content": "think"}}]}));
+        counter.observe_event(&json!({
+            "choices": [{"delta": {"tool_calls": [{"function": {"arguments": "{\"a\":1}"}}]}}]
+        }));
+        counter.observe_event(&json!({"type": "response.output_text.delta", "delta": "Hi"}));
+        counter.observe_event(&json!({
+            "type": "content_block_delta",
+            "delta": {"type": "text_delta", "text": "Yo"}
+        }));
+        let expected: usize = ["Hello", "think", "{\"a\":1}", "Hi", "Yo"]
+            .iter()
+            .map(|text| estimate_tokens_exact("gpt-4", text).tokens)
+            .sum();
+        assert_eq!(counter.output_tokens() as usize, expected);
+
+        // TGI はイベント1件が1トークン（特殊トークンは数えない）
+        let mut tgi = StreamingTokenCounter::new("tgi-model");
+        tgi.observe_event(&json!({"token": {"id": 1, "text": "a", "special": false}}));
+        tgi.observe_event(&json!({"token": {"id": 2, "text": "</s>", "special": true}}));
+        assert_eq!(tgi.output_tokens(), 1);
+    }
+
+    #[test]
+    fn test_streaming_accumulator_prefers_reported_output_tokens() {
+        let mut accumulator = StreamingTokenAccumulator::new("gpt-4");
+        accumulator.process_chunk(r#"data: {"choices":[{"delta":{"content":"Hello world"}}]}"#);
+        accumulator.process_chunk(r#"data: {"choices":[],"usage":{"prompt_tokens":4}}"#);
+
+        // usage に出力トークン数が無ければカウンタ値で補完する
+        let counted = accumulator.counted_output_tokens();
+        assert!(counted > 0);
+        assert_eq!(accumulator.usage_source(), TokenUsageSource::Estimated);
+        let usage = accumulator.finalize();
+        assert_eq!(usage.output_tokens, Some(counted));
+        assert_eq!(usage.total_tokens, Some(4 + counted));
+
+        accumulator.process_chunk(r#"data: {"choices":[],"usage":{"completion_tokens":42}}"#);
+        assert_eq!(accumulator.usage_source(), TokenUsageSource::Reported);
+        assert_eq!(accumulator.finalize().output_tokens, Some(42));
+    }
+
+    #[test]
+    fn test_streaming_accumulator_counts_partial_stream() {
+        let mut accumulator = StreamingTokenAccumulator::new("gpt-4");
+        accumulator.set_input_tokens(Some(10));
+        // ツール呼び出しの途中で切断（[DONE] も usage も届かない）
+        accumulator.process_chunk(
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"function":{"arguments":"{\"city\":"}}]}}]}"#,
+        );
+
+        assert!(!accumulator.is_done());
+        let usage = accumulator.finalize();
+        let partial = accumulator.counted_output_tokens();
+        assert!(partial > 0);
+        assert_eq!(usage.output_tokens, Some(partial));
+        assert_eq!(usage.total_tokens, Some(10 + partial));
+    }
+
+    #[test]
+    fn test_estimate_embedding_input_tokens_batch_input() {
+        let single = estimate_embedding_input_tokens(&json!("Hello, world!"), "embed").unwrap();
+        assert!(single > 0);
+
+        // バッチ入力は各要素の合計
+        let batch =
+            estimate_embedding_input_tokens(&json!(["Hello, world!", "Hello, world!"]), "embed")
+                .unwrap();
+        assert_eq!(batch, single * 2);
+
+        // トークンID配列は要素数
+        assert_eq!(
+            estimate_embedding_input_tokens(&json!([[1, 2, 3], [4, 5]]), "embed"),
+            Some(5)
+        );
+        assert_eq!(
+            estimate_embedding_input_tokens(&json!({"x": 1}), "embed"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract_or_estimate_embedding_tokens() {
+        let reported = json!({
+            "object": "list",
+            "data": [],
+            "usage": {"prompt_tokens": 8, "total_tokens": 8}
+        });
+        let (usage, source) = extract_or_estimate_embedding_tokens(
+            &reported,
+            Some(&json!(["a", "b"])),
+            "embed",
+            EndpointType::OpenaiCompatible,
+        );
+        assert_eq!(source, TokenUsageSource::Reported);
+        assert_eq!(usage.input_tokens, Some(8));
+
+        let (usage, source) = extract_or_estimate_embedding_tokens(
+            &json!({"object": "list", "data": []}),
+            Some(&json!([[1, 2, 3], [4]])),
+            "embed",
+            EndpointType::OpenaiCompatible,
+        );
+        assert_eq!(source, TokenUsageSource::Estimated);
+        assert_eq!(usage, TokenUsage::new(Some(4), Some(0), Some(4)));
+    }
+
+    #[test]
+    fn test_encoding_for_model() {
+        assert_eq!(
+            encoding_for_model("gpt-4o-mini"),
+            Some(TokenEncoding::O200kBase)
+        );
+        assert_eq!(
+            encoding_for_model("openai:gpt-4o"),
+            Some(TokenEncoding::O200kBase)
+        );
+        assert_eq!(
+            encoding_for_model("openai/gpt-oss-20b"),
+            Some(TokenEncoding::O200kBase)
+        );
+        assert_eq!(
+            encoding_for_model("gpt-oss:20b"),
+            Some(TokenEncoding::O200kBase)
+        );
+        assert_eq!(
+            encoding_for_model("gpt-4-turbo"),
+            Some(TokenEncoding::Cl100kBase)
+        );
+        assert_eq!(
+            encoding_for_model("GPT-3.5-Turbo"),
+            Some(TokenEncoding::Cl100kBase)
+        );
+        assert_eq!(
+            encoding_for_model("text-davinci-003"),
+            Some(TokenEncoding::P50kBase)
+        );
+        assert_eq!(encoding_for_model("davinci"), Some(TokenEncoding::R50kBase));
+        assert_eq!(encoding_for_model("llama-3.1-8b"), None);
+        assert_eq!(encoding_for_model("adapter-model"), None);
+    }
+
+    #[test]
+    fn test_estimate_tokens_exact_marks_known_models() {
+        let known = estimate_tokens_exact("gpt-4o", "Hello, world!");
+        assert!(known.exact);
+        assert!(known.tokens > 0);
+
+        // 未知モデルは従来の近似にフォールバック
+        let unknown = estimate_tokens_exact("llama-3.1-8b", "Hello, world!");
+        assert!(!unknown.exact);
+        assert_eq!(
+            Some(unknown.tokens as u32),
+            estimate_tokens("Hello, world!", "llama-3.1-8b")
+        );
+    }
+
+    #[test]
+    fn test_complete_usage_for_endpoint_fills_missing_usage() {
+        let payload = json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "system", "content": "You are helpful."},
+                {"role": "user", "content": [{"type": "text", "text": "What is 2+2?"}]}
+            ]
+        });
+        let request_text = extract_request_text(&payload);
+        assert_eq!(request_text, "You are helpful.What is 2+2?");
+
+        let mut body = json!({
+            "choices": [{"message": {"role": "assistant", "content": "2+2=4"}}]
+        });
+        let (usage, source) = complete_usage_for_endpoint(
+            &mut body,
+            Some(&request_text),
+            "gpt-4o",
+            EndpointType::OpenaiCompatible,
+        );
+        assert_eq!(source, TokenUsageSource::Estimated);
+        assert_eq!(
+            body["usage"]["prompt_tokens"].as_u64(),
+            usage.input_tokens.map(u64::from)
+        );
+        assert_eq!(
+            body["usage"]["total_tokens"].as_u64(),
+            usage.total_tokens.map(u64::from)
+        );
+
+        // 未知モデルは近似値のため応答本文には追加しない
+        let mut body = json!({
+            "choices": [{"message": {"role": "assistant", "content": "2+2=4"}}]
+        });
+        let (usage, _) = complete_usage_for_endpoint(
+            &mut body,
+            Some(&request_text),
+            "llama-3.1-8b",
+            EndpointType::OpenaiCompatible,
+        );
+        assert!(usage.input_tokens.is_some());
+        assert!(body.get("usage").is_none());
+    }
 }
@@ -118,6 +118,76 @@ impl std::fmt::Display for EndpointStatus {
     }
 }
 
+/// エンドポイントの運用状態（管理者が設定し、再起動後も維持される）
+///
+/// ヘルスチェック由来の `EndpointStatus` とは独立しており、
+/// `Active` 以外のエンドポイントはオンラインでもルーティング候補から除外される。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationalState {
+    /// 通常運用
+    #[default]
+    Active,
+    /// ドレイン中（新規リクエストを受けず、処理中のリクエストのみ完了させる）
+    Draining,
+    /// 無効化
+    Disabled,
+    /// メンテナンス中
+    Maintenance,
+}
+
+impl OperationalState {
+    /// OperationalStateを文字列に変換
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Draining => "draining",
+            Self::Disabled => "disabled",
+            Self::Maintenance => "maintenance",
+        }
+    }
+
+    /// ルーティング候補に含めてよいか
+    pub fn accepts_requests(&self) -> bool {
+        matches!(self, Self::Active)
+    }
+}
+
+impl FromStr for OperationalState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(Self::Active),
+            "draining" => Ok(Self::Draining),
+            "disabled" => Ok(Self::Disabled),
+            "maintenance" => Ok(Self::Maintenance),
+            other => Err(format!("unknown operational state: {}", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for OperationalState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// エンドポイントに設定された運用状態
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EndpointOperationalState {
+    /// エンドポイントID
+    pub endpoint_id: Uuid,
+    /// 運用状態
+    pub state: OperationalState,
+    /// 設定理由（任意）
+    pub reason: Option<String>,
+    /// 設定したユーザーID
+    pub updated_by: Option<String>,
+    /// 設定日時
+    pub updated_at: DateTime<Utc>,
+}
+
 /// エンドポイントタイプ（SPEC-e8e9326e追加要件 2026-01-26）
 ///
 /// エンドポイントの種別を表す列挙型。
@@ -371,6 +441,37 @@ pub struct Endpoint {
     /// 累計失敗リクエスト数（SPEC-8c32349f）
     #[serde(default)]
     pub failed_requests: i64,
+    /// タグ（ラベル）。ルーティングポリシーの必須ラベル判定に使用
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 重み（目標値、デフォルト1）。ramp中の実効重みはLoadManagerが管理する
+    #[serde(default = "default_endpoint_weight")]
+    pub weight: u32,
+    /// 月次予算（USD）。当月累計コストが達するとルーティング候補から除外する
+    #[serde(default)]
+    pub monthly_budget_usd: Option<f64>,
+    /// 入力100万トークンあたりの単価（USD）。未設定・0は無料扱い
+    #[serde(default)]
+    pub input_cost_per_million_tokens: Option<f64>,
+    /// 出力100万トークンあたりの単価（USD）。未設定・0は無料扱い
+    #[serde(default)]
+    pub output_cost_per_million_tokens: Option<f64>,
+    /// 優先フェイルオーバー先のエンドポイントID。選択不可時に通常選択より先に振り替える
+    #[serde(default)]
+    pub failover_to: Option<Uuid>,
+    /// カナリア割合（0〜100%）。設定時はリクエストのこの割合でのみ選択候補に含める
+    #[serde(default)]
+    pub canary_percent: Option<u8>,
+    /// 依存先のエンドポイントID。起動時の再判別・初回ヘルスチェックを依存先の後に行う
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+    /// 同時スロット数（容量予約の判定に使う）。未設定時は `LLMLB_ENDPOINT_SLOTS` の既定値
+    #[serde(default)]
+    pub slots: Option<u32>,
+}
+
+fn default_endpoint_weight() -> u32 {
+    1
 }
 
 impl Endpoint {
@@ -404,9 +505,38 @@ impl Endpoint {
             total_requests: 0,
             successful_requests: 0,
             failed_requests: 0,
+            tags: Vec::new(),
+            weight: default_endpoint_weight(),
+            monthly_budget_usd: None,
+            input_cost_per_million_tokens: None,
+            output_cost_per_million_tokens: None,
+            failover_to: None,
+            canary_percent: None,
+            depends_on: Vec::new(),
+            slots: None,
         }
     }
 
+    /// 従量課金の対象か（単価が設定されていないローカルエンドポイントは対象外）
+    pub fn is_billable(&self) -> bool {
+        self.input_cost_per_million_tokens.unwrap_or(0.0) > 0.0
+            || self.output_cost_per_million_tokens.unwrap_or(0.0) > 0.0
+    }
+
+    /// トークン数からリクエストのコスト（USD）を算出
+    pub fn request_cost_usd(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        let input = self.input_cost_per_million_tokens.unwrap_or(0.0).max(0.0);
+        let output = self.output_cost_per_million_tokens.unwrap_or(0.0).max(0.0);
+        (input_tokens as f64 * input + output_tokens as f64 * output) / 1_000_000.0
+    }
+
+    /// 指定したラベルをすべて持っているか確認
+    pub fn has_all_tags(&self, labels: &[String]) -> bool {
+        labels
+            .iter()
+            .all(|label| self.tags.iter().any(|tag| tag == label))
+    }
+
     /// 指定した機能をサポートしているか確認
     pub fn has_capability(&self, cap: EndpointCapability) -> bool {
         self.capabilities.contains(&cap)
@@ -469,6 +599,21 @@ impl EndpointModel {
     fn default_supported_apis() -> Vec<SupportedAPI> {
         vec![SupportedAPI::ChatCompletions]
     }
+
+    /// 指定APIに対応しているか
+    ///
+    /// `supported_apis` を優先し、Embeddings は capabilities の `embeddings` でも
+    /// 対応とみなす（`supported_apis` 導入前に同期されたモデルとの互換）。
+    pub fn supports_api(&self, api: SupportedAPI) -> bool {
+        if self.supported_apis.contains(&api) {
+            return true;
+        }
+        api == SupportedAPI::Embeddings
+            && self
+                .capabilities
+                .as_ref()
+                .is_some_and(|caps| caps.iter().any(|cap| cap == "embeddings"))
+    }
 }
 
 /// モデルダウンロードタスク（SPEC-e8e9326e追加要件 2026-01-26）
@@ -1726,6 +1871,26 @@ mod tests {
         assert_eq!(model.supported_apis, vec![SupportedAPI::ChatCompletions]);
     }
 
+    #[test]
+    fn test_endpoint_model_supports_api() {
+        let json = r#"{
+            "endpoint_id": "00000000-0000-0000-0000-000000000000",
+            "model_id": "nomic-embed-text",
+            "capabilities": ["embeddings"]
+        }"#;
+        let legacy: EndpointModel = serde_json::from_str(json).unwrap();
+        // supported_apis導入前のデータでもcapabilitiesからEmbeddings対応と判定する
+        assert!(legacy.supports_api(SupportedAPI::Embeddings));
+        assert!(legacy.supports_api(SupportedAPI::ChatCompletions));
+        assert!(!legacy.supports_api(SupportedAPI::Responses));
+
+        let mut chat = legacy.clone();
+        chat.capabilities = Some(vec!["chat".to_string()]);
+        assert!(!chat.supports_api(SupportedAPI::Embeddings));
+        chat.supported_apis.push(SupportedAPI::Embeddings);
+        assert!(chat.supports_api(SupportedAPI::Embeddings));
+    }
+
     #[test]
     fn test_endpoint_model_empty_model_id() {
         let json = r#"{
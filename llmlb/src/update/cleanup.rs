@@ -0,0 +1,204 @@
+//! Startup cleanup of the updates directory.
+//!
+//! Failed or interrupted updates leave payload directories and `*.tmp` files behind in
+//! `~/.llmlb/updates/`. On startup we keep the most recent successfully applied payloads
+//! (the running version plus versions recorded as applied in the update history, newest
+//! first, up to the configured number of generations) and remove everything else that
+//! looks like an update artifact. Files with a `.bak` extension are never removed.
+
+use anyhow::{Context, Result};
+use semver::Version;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Result of a cleanup pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    /// Payload directories that were removed.
+    pub removed_dirs: Vec<PathBuf>,
+    /// Number of `*.tmp` files removed outside of removed directories.
+    pub removed_tmp_files: usize,
+    /// Versions whose payload directories were kept.
+    pub kept_versions: Vec<Version>,
+    /// Total size of removed files in bytes.
+    pub freed_bytes: u64,
+}
+
+impl CleanupReport {
+    /// Returns `true` when nothing was removed.
+    pub fn is_empty(&self) -> bool {
+        self.removed_dirs.is_empty() && self.removed_tmp_files == 0
+    }
+}
+
+fn parse_dir_version(name: &str) -> Option<Version> {
+    Version::parse(name.trim_start_matches('v')).ok()
+}
+
+fn is_backup(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "bak")
+}
+
+fn is_tmp(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "tmp")
+}
+
+/// Remove `dir` recursively except for `.bak` files. Returns freed bytes.
+fn remove_dir_preserving_backups(dir: &Path) -> Result<u64> {
+    let mut freed = 0u64;
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            freed += remove_dir_preserving_backups(&path)?;
+        } else if !is_backup(&path) {
+            freed += fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+    }
+    // Leave the directory in place when it still holds a backup.
+    if fs::read_dir(dir)?.next().is_none() {
+        fs::remove_dir(dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+    }
+    Ok(freed)
+}
+
+/// Remove `*.tmp` files under `dir` recursively. Returns (count, freed bytes).
+fn remove_tmp_files(dir: &Path) -> Result<(usize, u64)> {
+    let mut removed = 0usize;
+    let mut freed = 0u64;
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let (count, bytes) = remove_tmp_files(&path)?;
+            removed += count;
+            freed += bytes;
+        } else if is_tmp(&path) {
+            freed += fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            removed += 1;
+        }
+    }
+    Ok((removed, freed))
+}
+
+/// Clean up orphaned update artifacts in `updates_dir`.
+///
+/// - Version directories (`X.Y.Z` / `vX.Y.Z`) are kept only when they belong to the running
+///   version or a version recorded as applied, limited to the newest `retain` of those.
+///   Other version directories (failed, interrupted, or older generations) are removed.
+/// - `rollback-*` directories hold restart arguments for a finished rollback and are removed.
+/// - `*.tmp` files are removed everywhere.
+/// - `.bak` files and unrelated entries are left untouched.
+pub fn cleanup_updates_dir(
+    updates_dir: &Path,
+    current_version: &Version,
+    applied_versions: &[Version],
+    retain: usize,
+) -> Result<CleanupReport> {
+    let mut report = CleanupReport::default();
+    if !updates_dir.is_dir() {
+        return Ok(report);
+    }
+
+    let mut successful: Vec<Version> = applied_versions
+        .iter()
+        .filter(|v| *v <= current_version)
+        .cloned()
+        .chain(std::iter::once(current_version.clone()))
+        .collect();
+    successful.sort_unstable_by(|a, b| b.cmp(a));
+    successful.dedup();
+    successful.truncate(retain.max(1));
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(updates_dir)
+        .with_context(|| format!("Failed to read {}", updates_dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    entries.sort();
+
+    for dir in entries {
+        let Some(name) = dir.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let remove = if name.starts_with("rollback-") {
+            true
+        } else if let Some(version) = parse_dir_version(name) {
+            if successful.contains(&version) {
+                report.kept_versions.push(version);
+                false
+            } else {
+                true
+            }
+        } else {
+            false
+        };
+
+        if remove {
+            report.freed_bytes += remove_dir_preserving_backups(&dir)?;
+            report.removed_dirs.push(dir);
+        }
+    }
+
+    let (removed_tmp_files, freed) = remove_tmp_files(updates_dir)?;
+    report.removed_tmp_files = removed_tmp_files;
+    report.freed_bytes += freed;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn cleanup_keeps_recent_successful_payloads_and_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let updates = dir.path().join("updates");
+        write(&updates.join("5.0.0/llmlb.tar.gz"), "old");
+        write(&updates.join("5.1.0/llmlb.tar.gz"), "previous");
+        write(&updates.join("5.2.0/extract/llmlb"), "current");
+        write(&updates.join("5.2.0/restart_args.json.tmp"), "partial");
+        write(&updates.join("5.3.0/llmlb.tar.gz"), "failed");
+        write(&updates.join("5.3.0/llmlb.bak"), "backup");
+        write(&updates.join("rollback-5.1.0/restart_args.json"), "{}");
+        write(&updates.join("notes/readme.txt"), "unrelated");
+
+        let current = Version::new(5, 2, 0);
+        let applied = vec![Version::new(5, 0, 0), Version::new(5, 1, 0)];
+        let report = cleanup_updates_dir(&updates, &current, &applied, 2).unwrap();
+
+        assert_eq!(
+            report.kept_versions,
+            vec![Version::new(5, 1, 0), Version::new(5, 2, 0)]
+        );
+        assert!(!updates.join("5.0.0").exists());
+        assert!(updates.join("5.1.0/llmlb.tar.gz").exists());
+        assert!(updates.join("5.2.0/extract/llmlb").exists());
+        assert!(!updates.join("5.2.0/restart_args.json.tmp").exists());
+        assert!(!updates.join("5.3.0/llmlb.tar.gz").exists());
+        assert!(updates.join("5.3.0/llmlb.bak").exists());
+        assert!(!updates.join("rollback-5.1.0").exists());
+        assert!(updates.join("notes/readme.txt").exists());
+        assert_eq!(report.removed_dirs.len(), 3);
+        assert_eq!(report.removed_tmp_files, 1);
+        assert!(report.freed_bytes > 0);
+    }
+
+    #[test]
+    fn cleanup_with_missing_dir_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        let report =
+            cleanup_updates_dir(&dir.path().join("updates"), &Version::new(1, 0, 0), &[], 1)
+                .unwrap();
+        assert!(report.is_empty());
+    }
+}
@@ -7,11 +7,13 @@
 //! - Internal helper modes (`__internal`) to safely replace binaries / run installers
 //! - Update scheduling (immediate / idle / time-based)
 //! - Update history recording
+//! - Startup cleanup of orphaned update payloads
 
+pub mod cleanup;
 pub mod history;
 pub mod schedule;
 
-use crate::{inference_gate::InferenceGate, shutdown::ShutdownController};
+use crate::{config::UpdateChannel, inference_gate::InferenceGate, shutdown::ShutdownController};
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use flate2::read::GzDecoder;
@@ -49,6 +51,8 @@ struct UpdateCacheFile {
     release_url: Option<String>,
     portable_asset_url: Option<String>,
     installer_asset_url: Option<String>,
+    #[serde(default)]
+    delta_asset_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
@@ -74,6 +78,9 @@ pub enum UpdateState {
         portable_asset_url: Option<String>,
         /// Preferred installer payload URL for this platform, if present.
         installer_asset_url: Option<String>,
+        /// Binary delta patch URL for this platform, if present (with the patched binary's
+        /// `llmlb-<artifact>.sha256` companion).
+        delta_asset_url: Option<String>,
         /// Current payload download/preparation status.
         payload: PayloadState,
         /// When this update was last checked.
@@ -235,6 +242,8 @@ struct UpdateManagerInner {
     owner: String,
     repo: String,
     ttl: Duration,
+    /// Release channel followed by update checks (`LLMLB_UPDATE_CHANNEL`).
+    channel: UpdateChannel,
 
     /// Override for GitHub API base URL (for testing).
     github_api_base_url: Option<String>,
@@ -316,11 +325,13 @@ impl UpdateManager {
         let current_version = Version::parse(env!("CARGO_PKG_VERSION"))
             .context("Failed to parse CARGO_PKG_VERSION as semver")?;
 
+        let channel = crate::config::update_channel();
         let (cache_path, updates_dir) = default_paths()?;
         let data_dir = cache_path
             .parent()
             .unwrap_or_else(|| Path::new("."))
             .to_path_buf();
+        let cache_path = channel_cache_path(&cache_path, channel);
 
         Ok(Self {
             inner: Arc::new(UpdateManagerInner {
@@ -334,6 +345,7 @@ impl UpdateManager {
                 owner,
                 repo,
                 ttl: DEFAULT_TTL,
+                channel,
                 github_api_base_url,
                 cache_path,
                 updates_dir,
@@ -359,7 +371,14 @@ impl UpdateManager {
         shutdown: ShutdownController,
         data_dir: &Path,
     ) -> Result<Self> {
-        Self::new_with_data_dir_and_config(http_client, gate, shutdown, data_dir, None)
+        Self::new_with_data_dir_and_config(
+            http_client,
+            gate,
+            shutdown,
+            data_dir,
+            None,
+            UpdateChannel::Stable,
+        )
     }
 
     #[cfg(test)]
@@ -369,11 +388,12 @@ impl UpdateManager {
         shutdown: ShutdownController,
         data_dir: &Path,
         github_api_base_url: Option<String>,
+        channel: UpdateChannel,
     ) -> Result<Self> {
         let current_version = Version::parse(env!("CARGO_PKG_VERSION"))
             .context("Failed to parse CARGO_PKG_VERSION as semver")?;
 
-        let cache_path = data_dir.join("update-check.json");
+        let cache_path = channel_cache_path(&data_dir.join("update-check.json"), channel);
         let updates_dir = data_dir.join("updates");
 
         Ok(Self {
@@ -388,6 +408,7 @@ impl UpdateManager {
                 owner: DEFAULT_OWNER.to_string(),
                 repo: DEFAULT_REPO.to_string(),
                 ttl: DEFAULT_TTL,
+                channel,
                 github_api_base_url,
                 cache_path,
                 updates_dir,
@@ -473,10 +494,11 @@ impl UpdateManager {
         }
 
         let timeout = Duration::from_secs(5);
-        let release = match fetch_latest_release(
+        let release = match fetch_channel_release(
             &self.inner.http_client,
             &self.inner.owner,
             &self.inner.repo,
+            self.inner.channel,
             timeout,
             self.inner.github_api_base_url.as_deref(),
         )
@@ -500,7 +522,7 @@ impl UpdateManager {
             }
         };
         let latest = parse_tag_to_version(&release.tag_name)?;
-        if latest <= self.inner.current_version {
+        if !is_upgrade(&self.inner.current_version, &latest) {
             *self.inner.state.write().await = UpdateState::UpToDate {
                 checked_at: Some(Utc::now()),
             };
@@ -512,6 +534,7 @@ impl UpdateManager {
                     release_url: Some(release.html_url.clone()),
                     portable_asset_url: None,
                     installer_asset_url: None,
+                    delta_asset_url: None,
                 },
             )?;
             #[cfg(any(target_os = "windows", target_os = "macos"))]
@@ -532,6 +555,8 @@ impl UpdateManager {
             installer_asset_url: installer_asset
                 .as_ref()
                 .map(|a| a.browser_download_url.clone()),
+            delta_asset_url: select_delta_asset(&release, &platform)
+                .map(|a| a.browser_download_url),
         };
         save_cache(&self.inner.cache_path, cache.clone())?;
 
@@ -542,6 +567,7 @@ impl UpdateManager {
                 release_url: release.html_url,
                 portable_asset_url: cache.portable_asset_url.clone(),
                 installer_asset_url: cache.installer_asset_url.clone(),
+                delta_asset_url: cache.delta_asset_url.clone(),
                 payload: PayloadState::NotReady,
                 checked_at: cache.last_checked_at,
             };
@@ -723,6 +749,50 @@ impl UpdateManager {
         });
     }
 
+    /// Remove orphaned update payloads and `*.tmp` files from the updates directory.
+    ///
+    /// Keeps the newest `LLMLB_UPDATE_RETAIN_GENERATIONS` successfully applied payloads
+    /// (including the running version) and all `.bak` files. Failures are logged, not returned.
+    pub fn cleanup_orphaned_updates(&self) -> Option<cleanup::CleanupReport> {
+        let applied: Vec<Version> = self
+            .get_history()
+            .into_iter()
+            .filter(|entry| entry.kind == history::HistoryEventKind::Applied)
+            .filter_map(|entry| Version::parse(entry.version.trim_start_matches('v')).ok())
+            .collect();
+        let retain = crate::config::update_retain_generations();
+
+        match cleanup::cleanup_updates_dir(
+            &self.inner.updates_dir,
+            &self.inner.current_version,
+            &applied,
+            retain,
+        ) {
+            Ok(report) => {
+                if !report.is_empty() {
+                    tracing::info!(
+                        updates_dir = %self.inner.updates_dir.display(),
+                        removed_dirs = report.removed_dirs.len(),
+                        removed_tmp_files = report.removed_tmp_files,
+                        freed_bytes = report.freed_bytes,
+                        kept_versions = ?report
+                            .kept_versions
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>(),
+                        retain,
+                        "Cleaned up orphaned update files"
+                    );
+                }
+                Some(report)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to clean up updates directory: {e}");
+                None
+            }
+        }
+    }
+
     /// Append a history entry.
     pub fn record_history(&self, entry: history::HistoryEntry) {
         if let Err(e) = self.inner.history_store.append(entry) {
@@ -746,8 +816,10 @@ impl UpdateManager {
 
     /// Request a manual rollback to the previous version.
     ///
-    /// Restores the `.bak` file and restarts. Returns `Err` if no `.bak` exists.
-    pub fn request_rollback(&self) -> Result<()> {
+    /// Restores the `.bak` file and restarts. Returns `Err` if no `.bak` exists, or if the
+    /// `.bak` binary cannot read a database migrated to `db_schema_version` and `force` is
+    /// not set. A forced incompatible rollback is recorded as such in the history.
+    pub fn request_rollback(&self, db_schema_version: Option<i64>, force: bool) -> Result<()> {
         let current_exe =
             std::env::current_exe().context("Failed to resolve current executable path")?;
         let backup = current_exe.with_extension("bak");
@@ -755,12 +827,48 @@ impl UpdateManager {
             return Err(anyhow!("No previous version available (.bak not found)"));
         }
 
-        // Record rollback in history.
         let version = env!("CARGO_PKG_VERSION").to_string();
+        let message = match check_rollback_compatibility(&backup, db_schema_version) {
+            RollbackCompatibility::Compatible { backup: info } => {
+                format!("Manual rollback from {version} to {}", info.version)
+            }
+            RollbackCompatibility::Incompatible {
+                backup: info,
+                db_schema_version,
+            } => {
+                if !force {
+                    return Err(anyhow!(
+                        "Previous version {} supports DB schema up to {} but the database is at {}; \
+                         rollback aborted (use force to roll back anyway)",
+                        info.version,
+                        info.schema_version,
+                        db_schema_version
+                    ));
+                }
+                tracing::warn!(
+                    backup_version = %info.version,
+                    backup_schema_version = info.schema_version,
+                    db_schema_version,
+                    "Forcing rollback to a version that does not support the current DB schema"
+                );
+                format!(
+                    "Manual rollback from {version} to {} (forced: DB schema {} is newer than supported {})",
+                    info.version, db_schema_version, info.schema_version
+                )
+            }
+            RollbackCompatibility::Unknown => {
+                tracing::warn!(
+                    "Rollback compatibility unknown (no backup info or DB schema version)"
+                );
+                format!("Manual rollback from {version}")
+            }
+        };
+
+        // Record rollback in history.
         self.record_history(history::HistoryEntry {
             kind: history::HistoryEventKind::Rollback,
             version: version.clone(),
-            message: Some(format!("Manual rollback from {version}")),
+            message: Some(message),
             timestamp: Utc::now(),
         });
 
@@ -809,6 +917,9 @@ impl UpdateManager {
             return;
         }
 
+        // Remove payloads left behind by failed or interrupted updates.
+        self.cleanup_orphaned_updates();
+
         // Restore any persisted schedule on startup.
         self.restore_schedule();
 
@@ -1000,16 +1111,17 @@ impl UpdateManager {
         } else {
             Duration::from_secs(2)
         };
-        let release = fetch_latest_release(
+        let release = fetch_channel_release(
             &self.inner.http_client,
             &self.inner.owner,
             &self.inner.repo,
+            self.inner.channel,
             timeout,
             self.inner.github_api_base_url.as_deref(),
         )
         .await?;
         let latest = parse_tag_to_version(&release.tag_name)?;
-        if latest <= self.inner.current_version {
+        if !is_upgrade(&self.inner.current_version, &latest) {
             *self.inner.state.write().await = UpdateState::UpToDate {
                 checked_at: Some(Utc::now()),
             };
@@ -1021,6 +1133,7 @@ impl UpdateManager {
                     release_url: Some(release.html_url.clone()),
                     portable_asset_url: None,
                     installer_asset_url: None,
+                    delta_asset_url: None,
                 },
             )?;
             #[cfg(any(target_os = "windows", target_os = "macos"))]
@@ -1041,6 +1154,8 @@ impl UpdateManager {
             installer_asset_url: installer_asset
                 .as_ref()
                 .map(|a| a.browser_download_url.clone()),
+            delta_asset_url: select_delta_asset(&release, &platform)
+                .map(|a| a.browser_download_url),
         };
         save_cache(&self.inner.cache_path, cache.clone())?;
 
@@ -1052,6 +1167,7 @@ impl UpdateManager {
                 release_url: release.html_url,
                 portable_asset_url: cache.portable_asset_url.clone(),
                 installer_asset_url: cache.installer_asset_url.clone(),
+                delta_asset_url: cache.delta_asset_url.clone(),
                 payload: PayloadState::NotReady,
                 checked_at: cache.last_checked_at,
             };
@@ -1074,7 +1190,7 @@ impl UpdateManager {
             return Ok(());
         }
         let latest = Version::parse(&latest_version).context("cached latest_version is invalid")?;
-        if latest <= self.inner.current_version {
+        if !is_upgrade(&self.inner.current_version, &latest) {
             *self.inner.state.write().await = UpdateState::UpToDate {
                 checked_at: Some(cache.last_checked_at),
             };
@@ -1092,6 +1208,7 @@ impl UpdateManager {
             release_url,
             portable_asset_url: cache.portable_asset_url.clone(),
             installer_asset_url: cache.installer_asset_url.clone(),
+            delta_asset_url: cache.delta_asset_url.clone(),
             payload: PayloadState::NotReady,
             checked_at: cache.last_checked_at,
         };
@@ -1135,7 +1252,7 @@ impl UpdateManager {
     }
 
     async fn ensure_payload_ready(&self) -> Result<PayloadKind> {
-        let (latest, release_url, portable, installer) = {
+        let (latest, release_url, portable, installer, delta) = {
             let st = self.inner.state.read().await;
             match &*st {
                 UpdateState::Available {
@@ -1143,12 +1260,14 @@ impl UpdateManager {
                     release_url,
                     portable_asset_url,
                     installer_asset_url,
+                    delta_asset_url,
                     ..
                 } => (
                     latest.clone(),
                     release_url.clone(),
                     portable_asset_url.clone(),
                     installer_asset_url.clone(),
+                    delta_asset_url.clone(),
                 ),
                 _ => return Err(anyhow!("No update is available")),
             }
@@ -1179,7 +1298,8 @@ impl UpdateManager {
             &current_exe,
             portable.as_deref(),
             installer.as_deref(),
-        );
+        )
+        .map(|plan| with_delta_plan(plan, delta.as_deref()));
 
         let Some(plan) = plan else {
             let dir = current_exe.parent().unwrap_or_else(|| Path::new("."));
@@ -1203,46 +1323,25 @@ impl UpdateManager {
         let update_dir = self.inner.updates_dir.join(&latest);
         fs::create_dir_all(&update_dir).ok();
 
-        let state_ref = self.inner.clone();
-        let progress_cb: ProgressCallback = Box::new(move |downloaded, total| {
-            if let Ok(mut st) = state_ref.state.try_write() {
-                if let UpdateState::Available { payload, .. } = &mut *st {
-                    if matches!(payload, PayloadState::Downloading { .. }) {
-                        *payload = PayloadState::Downloading {
-                            started_at: Utc::now(),
-                            downloaded_bytes: Some(downloaded),
-                            total_bytes: total,
-                        };
-                    }
-                }
-            }
-        });
-
         let kind = match plan {
             ApplyPlan::Portable { url } => {
-                let asset_name =
-                    asset_name_from_url(&url).unwrap_or_else(|| "llmlb-update".to_string());
-                let archive_path = update_dir.join(&asset_name);
-                download_to_path(
-                    &self.inner.http_client,
-                    &url,
-                    &archive_path,
-                    Some(progress_cb),
-                )
-                .await?;
-                let extract_dir = update_dir.join("extract");
-                if extract_dir.exists() {
-                    fs::remove_dir_all(&extract_dir).ok();
-                }
-                fs::create_dir_all(&extract_dir)?;
-                extract_archive(&archive_path, &extract_dir)?;
-                let binary_name = platform.binary_name();
-                let binary_path = find_extracted_binary(&extract_dir, &binary_name)?
-                    .ok_or_else(|| anyhow!("Extracted archive did not contain {binary_name}"))?;
-                PayloadKind::Portable {
-                    binary_path: binary_path.to_string_lossy().to_string(),
-                }
+                self.prepare_portable_payload(&url, &update_dir, &platform)
+                    .await?
             }
+            ApplyPlan::Delta {
+                patch_url,
+                portable_url,
+            } => match self
+                .prepare_delta_payload(&patch_url, &current_exe, &update_dir, &platform)
+                .await
+            {
+                Ok(kind) => kind,
+                Err(e) => {
+                    tracing::warn!("Delta update failed, falling back to full download: {e:#}");
+                    self.prepare_portable_payload(&portable_url, &update_dir, &platform)
+                        .await?
+                }
+            },
             ApplyPlan::Installer { url, kind } => {
                 let asset_name =
                     asset_name_from_url(&url).unwrap_or_else(|| "llmlb-installer".to_string());
@@ -1268,6 +1367,90 @@ impl UpdateManager {
         Ok(kind)
     }
 
+    /// Download the full portable archive and extract the binary.
+    async fn prepare_portable_payload(
+        &self,
+        url: &str,
+        update_dir: &Path,
+        platform: &Platform,
+    ) -> Result<PayloadKind> {
+        let asset_name = asset_name_from_url(url).unwrap_or_else(|| "llmlb-update".to_string());
+        let archive_path = update_dir.join(&asset_name);
+        download_to_path(
+            &self.inner.http_client,
+            url,
+            &archive_path,
+            Some(self.download_progress_callback()),
+        )
+        .await?;
+        let extract_dir = update_dir.join("extract");
+        if extract_dir.exists() {
+            fs::remove_dir_all(&extract_dir).ok();
+        }
+        fs::create_dir_all(&extract_dir)?;
+        extract_archive(&archive_path, &extract_dir)?;
+        let binary_name = platform.binary_name();
+        let binary_path = find_extracted_binary(&extract_dir, &binary_name)?
+            .ok_or_else(|| anyhow!("Extracted archive did not contain {binary_name}"))?;
+        Ok(PayloadKind::Portable {
+            binary_path: binary_path.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Download the delta patch and apply it to the running binary.
+    ///
+    /// Fails when the patch or the patched binary's checksum (`llmlb-<artifact>.sha256`) cannot be
+    /// downloaded, or when the patched binary does not match it (e.g. the running binary is not
+    /// the patch base).
+    async fn prepare_delta_payload(
+        &self,
+        patch_url: &str,
+        current_exe: &Path,
+        update_dir: &Path,
+        platform: &Platform,
+    ) -> Result<PayloadKind> {
+        let asset_name =
+            asset_name_from_url(patch_url).unwrap_or_else(|| "llmlb-update.patch".to_string());
+        let patch_path = update_dir.join(&asset_name);
+        download_to_path(
+            &self.inner.http_client,
+            patch_url,
+            &patch_path,
+            Some(self.download_progress_callback()),
+        )
+        .await?;
+        let expected_sha256 =
+            fetch_expected_sha256(&self.inner.http_client, &delta_checksum_url(patch_url)).await?;
+
+        let binary_path = update_dir.join("delta").join(platform.binary_name());
+        apply_delta_patch(current_exe, &patch_path, &binary_path, &expected_sha256)?;
+        tracing::info!(
+            patch = %patch_path.display(),
+            "Prepared update binary from delta patch"
+        );
+        Ok(PayloadKind::Portable {
+            binary_path: binary_path.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Progress callback reflecting download progress in the `Available` payload state.
+    fn download_progress_callback(&self) -> ProgressCallback {
+        let state_ref = self.inner.clone();
+        Box::new(move |downloaded, total| {
+            if let Ok(mut st) = state_ref.state.try_write() {
+                if let UpdateState::Available { payload, .. } = &mut *st {
+                    if matches!(payload, PayloadState::Downloading { .. }) {
+                        *payload = PayloadState::Downloading {
+                            started_at: Utc::now(),
+                            downloaded_bytes: Some(downloaded),
+                            total_bytes: total,
+                        };
+                    }
+                }
+            }
+        })
+    }
+
     async fn set_payload_error(&self, msg: String) {
         let mut st = self.inner.state.write().await;
         if let UpdateState::Available { payload, .. } = &mut *st {
@@ -1513,6 +1696,16 @@ fn default_paths() -> Result<(PathBuf, PathBuf)> {
     Ok((data_dir.join("update-check.json"), data_dir.join("updates")))
 }
 
+/// Cache file for `channel`: stable keeps `update-check.json`, other channels
+/// use `update-check-<channel>.json` so switching channels never reuses another
+/// channel's result.
+fn channel_cache_path(stable_path: &Path, channel: UpdateChannel) -> PathBuf {
+    match channel {
+        UpdateChannel::Stable => stable_path.to_path_buf(),
+        other => stable_path.with_file_name(format!("update-check-{}.json", other.as_str())),
+    }
+}
+
 fn load_cache(path: &Path) -> Result<Option<UpdateCacheFile>> {
     if !path.exists() {
         return Ok(None);
@@ -1537,6 +1730,8 @@ struct GitHubReleaseResponse {
     tag_name: String,
     html_url: String,
     assets: Vec<GitHubAsset>,
+    #[serde(default)]
+    draft: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -1584,6 +1779,100 @@ async fn fetch_latest_release(
     })
 }
 
+/// Number of releases inspected when a pre-release channel is selected.
+const CHANNEL_RELEASES_PER_PAGE: u32 = 50;
+
+/// Fetch the newest release for `channel`.
+///
+/// The stable channel uses `releases/latest`. Alpha/beta list recent releases
+/// (including pre-releases) and pick the highest version the channel accepts.
+async fn fetch_channel_release(
+    client: &reqwest::Client,
+    owner: &str,
+    repo: &str,
+    channel: UpdateChannel,
+    timeout: Duration,
+    api_base_url: Option<&str>,
+) -> Result<GitHubRelease> {
+    if channel == UpdateChannel::Stable {
+        return fetch_latest_release(client, owner, repo, timeout, api_base_url).await;
+    }
+
+    let base = api_base_url.unwrap_or("https://api.github.com");
+    let url = format!("{base}/repos/{owner}/{repo}/releases?per_page={CHANNEL_RELEASES_PER_PAGE}");
+    let user_agent = format!("llmlb/{}", env!("CARGO_PKG_VERSION"));
+    let res = client
+        .get(url)
+        .header("accept", "application/vnd.github+json")
+        .header("user-agent", user_agent)
+        .timeout(timeout)
+        .send()
+        .await
+        .context("Failed to call GitHub Releases API")?;
+    if !res.status().is_success() {
+        return Err(anyhow!("GitHub API returned {}", res.status().as_u16()));
+    }
+    let releases: Vec<GitHubReleaseResponse> = res
+        .json()
+        .await
+        .context("Failed to parse GitHub releases JSON")?;
+    select_channel_release(releases, channel).ok_or_else(|| {
+        anyhow!(
+            "No release found for the {} update channel",
+            channel.as_str()
+        )
+    })
+}
+
+/// Pick the highest non-draft release whose version `channel` accepts.
+fn select_channel_release(
+    releases: Vec<GitHubReleaseResponse>,
+    channel: UpdateChannel,
+) -> Option<GitHubRelease> {
+    releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        .filter_map(|release| {
+            let version = parse_tag_to_version(&release.tag_name).ok()?;
+            channel_accepts(channel, &version).then_some((version, release))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp_precedence(b))
+        .map(|(_, release)| GitHubRelease {
+            tag_name: release.tag_name,
+            html_url: release.html_url,
+            assets: release.assets,
+        })
+}
+
+/// Whether `channel` follows `version`.
+///
+/// Stable accepts only releases; beta also accepts `beta`/`rc` pre-releases;
+/// alpha accepts every pre-release.
+fn channel_accepts(channel: UpdateChannel, version: &Version) -> bool {
+    if version.pre.is_empty() {
+        return true;
+    }
+    match channel {
+        UpdateChannel::Stable => false,
+        UpdateChannel::Beta => {
+            let pre = version.pre.as_str();
+            pre.starts_with("beta") || pre.starts_with("rc")
+        }
+        UpdateChannel::Alpha => true,
+    }
+}
+
+/// Whether `latest` should be offered as an update to `current`.
+///
+/// Versions are compared by semver precedence, so pre-releases order before
+/// their release (`6.0.0-beta.1 < 6.0.0`) and build metadata is ignored.
+/// Anything not strictly newer is rejected, which keeps e.g. a `5.8.0-beta.2`
+/// install from being "updated" to the stable `5.7.1` after switching back
+/// to the stable channel.
+fn is_upgrade(current: &Version, latest: &Version) -> bool {
+    latest.cmp_precedence(current) == std::cmp::Ordering::Greater
+}
+
 fn parse_tag_to_version(tag: &str) -> Result<Version> {
     let normalized = tag.strip_prefix('v').unwrap_or(tag);
     Version::parse(normalized).map_err(|e| anyhow!("Invalid tag semver: {e}"))
@@ -1632,6 +1921,15 @@ impl Platform {
         })
     }
 
+    fn delta_asset_name(&self) -> Option<String> {
+        self.artifact().map(|a| format!("llmlb-{a}.patch"))
+    }
+
+    /// SHA-256 of the binary produced by the delta patch (not of the patch file itself).
+    fn delta_checksum_name(&self) -> Option<String> {
+        self.artifact().map(|a| format!("llmlb-{a}.sha256"))
+    }
+
     fn installer_asset_name(&self) -> Option<(String, InstallerKind)> {
         let artifact = self.artifact()?;
         match self.os.as_str() {
@@ -1661,10 +1959,47 @@ fn select_assets(
     (portable_asset, installer_asset)
 }
 
+/// Select the delta patch asset (only when the patched binary's checksum is also published).
+fn select_delta_asset(release: &GitHubRelease, platform: &Platform) -> Option<GitHubAsset> {
+    let name = platform.delta_asset_name()?;
+    let checksum_name = platform.delta_checksum_name()?;
+    if !release.assets.iter().any(|a| a.name == checksum_name) {
+        return None;
+    }
+    release.assets.iter().find(|a| a.name == name).cloned()
+}
+
+/// URL of the patched binary's checksum (`llmlb-<artifact>.sha256`), published next to the patch.
+fn delta_checksum_url(patch_url: &str) -> String {
+    let base = patch_url.strip_suffix(".patch").unwrap_or(patch_url);
+    format!("{base}.sha256")
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum ApplyPlan {
-    Portable { url: String },
-    Installer { url: String, kind: InstallerKind },
+    Portable {
+        url: String,
+    },
+    /// Patch the running binary, falling back to the full portable archive on failure.
+    Delta {
+        patch_url: String,
+        portable_url: String,
+    },
+    Installer {
+        url: String,
+        kind: InstallerKind,
+    },
+}
+
+/// Prefer a delta patch for in-place (portable) updates when one is published.
+fn with_delta_plan(plan: ApplyPlan, delta_url: Option<&str>) -> ApplyPlan {
+    match (plan, delta_url) {
+        (ApplyPlan::Portable { url }, Some(patch_url)) => ApplyPlan::Delta {
+            patch_url: patch_url.to_string(),
+            portable_url: url,
+        },
+        (plan, _) => plan,
+    }
 }
 
 fn choose_apply_plan(
@@ -1794,6 +2129,67 @@ fn extract_archive(archive_path: &Path, dest_dir: &Path) -> Result<()> {
     Err(anyhow!("unsupported archive format: {name}"))
 }
 
+/// Download a `sha256sum`-style checksum file and return the lowercase hex digest.
+async fn fetch_expected_sha256(client: &reqwest::Client, url: &str) -> Result<String> {
+    let res = client
+        .get(url)
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await?;
+    if !res.status().is_success() {
+        return Err(anyhow!(
+            "checksum download failed with status {}",
+            res.status()
+        ));
+    }
+    parse_sha256(&res.text().await?).ok_or_else(|| anyhow!("Invalid SHA-256 checksum file: {url}"))
+}
+
+/// Parse the first token of a checksum file as a SHA-256 hex digest.
+fn parse_sha256(content: &str) -> Option<String> {
+    let digest = content.split_whitespace().next()?.to_ascii_lowercase();
+    (digest.len() == 64 && digest.bytes().all(|b| b.is_ascii_hexdigit())).then_some(digest)
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Apply a bsdiff patch to `old_binary` and write the result to `output` if its hash matches.
+fn apply_delta_patch(
+    old_binary: &Path,
+    patch_path: &Path,
+    output: &Path,
+    expected_sha256: &str,
+) -> Result<()> {
+    let old = fs::read(old_binary).context("Failed to read current executable")?;
+    let mut patch = io::BufReader::new(fs::File::open(patch_path)?);
+    let mut new = Vec::new();
+    bsdiff::patch(&old, &mut patch, &mut new).context("Failed to apply delta patch")?;
+
+    let actual = sha256_hex(&new);
+    if actual != expected_sha256 {
+        return Err(anyhow!(
+            "Patched binary hash mismatch (expected {expected_sha256}, got {actual})"
+        ));
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(output, &new)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(output, fs::Permissions::from_mode(0o755)).ok();
+    }
+    Ok(())
+}
+
 fn find_extracted_binary(extract_dir: &Path, binary_name: &str) -> Result<Option<PathBuf>> {
     // Expected layout: dist/llmlb-<artifact>/<binary>
     let mut candidates = Vec::<PathBuf>::new();
@@ -1947,8 +2343,12 @@ pub(crate) fn internal_apply_update(
     if backup.exists() {
         let _ = fs::remove_file(&backup);
     }
-    if target.exists() {
-        let _ = fs::rename(&target, &backup);
+    let _ = fs::remove_file(backup_info_path(&backup));
+    if target.exists() && fs::rename(&target, &backup).is_ok() {
+        // This helper runs from the old binary, so it records its own versions.
+        if let Err(e) = write_backup_info(&backup) {
+            eprintln!("Failed to record backup info: {e}");
+        }
     }
 
     if let Err(e) = fs::rename(&new_binary, &target) {
@@ -1998,6 +2398,7 @@ pub(crate) fn internal_apply_update(
                     fs::set_permissions(&target, perms).ok();
                 }
             }
+            let _ = fs::remove_file(backup_info_path(&backup));
             // Record rollback in history (best-effort).
             record_auto_rollback_history(&args_file, &e.to_string());
             restart_from_args_file(&target, &args_file)?;
@@ -2144,6 +2545,75 @@ pub(crate) fn internal_run_installer(
     ))
 }
 
+/// Version info recorded next to the `.bak` binary when an update is applied.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BackupInfo {
+    /// Version of the backed-up binary.
+    pub version: String,
+    /// Latest DB migration version the backed-up binary knows.
+    pub schema_version: i64,
+}
+
+/// Whether the `.bak` binary can run against the current database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RollbackCompatibility {
+    /// The backed-up binary knows every applied migration.
+    Compatible {
+        /// Recorded backup info.
+        backup: BackupInfo,
+    },
+    /// The database has migrations the backed-up binary does not know.
+    Incompatible {
+        /// Recorded backup info.
+        backup: BackupInfo,
+        /// Latest migration version applied to the database.
+        db_schema_version: i64,
+    },
+    /// No backup info (backup made by an older version) or unknown DB schema version.
+    Unknown,
+}
+
+/// Path of the backup info file (`<backup>.json`).
+fn backup_info_path(backup: &Path) -> PathBuf {
+    let mut name = backup.as_os_str().to_os_string();
+    name.push(".json");
+    PathBuf::from(name)
+}
+
+/// Record the running binary's version and schema version for `backup`.
+fn write_backup_info(backup: &Path) -> Result<()> {
+    let info = BackupInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: crate::db::migrations::latest_migration_version(),
+    };
+    fs::write(backup_info_path(backup), serde_json::to_vec_pretty(&info)?)?;
+    Ok(())
+}
+
+fn read_backup_info(backup: &Path) -> Option<BackupInfo> {
+    let content = fs::read_to_string(backup_info_path(backup)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Compare the `.bak` binary's schema version with the database's applied migrations.
+pub fn check_rollback_compatibility(
+    backup: &Path,
+    db_schema_version: Option<i64>,
+) -> RollbackCompatibility {
+    let (Some(info), Some(db_schema_version)) = (read_backup_info(backup), db_schema_version)
+    else {
+        return RollbackCompatibility::Unknown;
+    };
+    if db_schema_version > info.schema_version {
+        RollbackCompatibility::Incompatible {
+            backup: info,
+            db_schema_version,
+        }
+    } else {
+        RollbackCompatibility::Compatible { backup: info }
+    }
+}
+
 fn spawn_internal_rollback(current_exe: &Path, backup: &Path, args_file: &Path) -> Result<()> {
     let pid = std::process::id().to_string();
     let target = current_exe.to_string_lossy().to_string();
@@ -2184,6 +2654,7 @@ pub(crate) fn internal_rollback(
             return Err(e).context("Failed to restore backup");
         }
     }
+    let _ = fs::remove_file(backup_info_path(&backup));
 
     #[cfg(unix)]
     {
@@ -2297,6 +2768,7 @@ mod tests {
             release_url: "https://example.com/release".to_string(),
             portable_asset_url: Some("https://example.com/portable.tar.gz".to_string()),
             installer_asset_url: None,
+            delta_asset_url: None,
             payload,
             checked_at: Utc::now(),
         }
@@ -2656,6 +3128,7 @@ mod tests {
                 release_url: "https://example.com/release".to_string(),
                 portable_asset_url: Some(format!("{}/download/portable.tar.gz", mock_server.uri())),
                 installer_asset_url: None,
+                delta_asset_url: None,
                 payload: PayloadState::NotReady,
                 checked_at: Utc::now(),
             };
@@ -3165,6 +3638,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rollback_compatibility_compares_schema_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let backup = dir.path().join("llmlb.bak");
+        fs::write(&backup, b"old-binary-content").unwrap();
+
+        // Backups made by older versions have no info to judge from.
+        assert_eq!(
+            check_rollback_compatibility(&backup, Some(44)),
+            RollbackCompatibility::Unknown
+        );
+
+        write_backup_info(&backup).unwrap();
+        let info = read_backup_info(&backup).unwrap();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            info.schema_version,
+            crate::db::migrations::latest_migration_version()
+        );
+
+        assert_eq!(
+            check_rollback_compatibility(&backup, Some(info.schema_version)),
+            RollbackCompatibility::Compatible {
+                backup: info.clone()
+            }
+        );
+        assert_eq!(
+            check_rollback_compatibility(&backup, Some(info.schema_version + 1)),
+            RollbackCompatibility::Incompatible {
+                backup: info.clone(),
+                db_schema_version: info.schema_version + 1,
+            }
+        );
+        assert_eq!(
+            check_rollback_compatibility(&backup, None),
+            RollbackCompatibility::Unknown
+        );
+    }
+
     #[test]
     fn internal_rollback_fails_without_backup() {
         let dir = tempfile::tempdir().unwrap();
@@ -3316,6 +3828,106 @@ mod tests {
         assert!(parse_tag_to_version("v1.2").is_err());
     }
 
+    // =======================================================================
+    // Update channels
+    // =======================================================================
+    fn release_response(tag: &str, draft: bool) -> GitHubReleaseResponse {
+        GitHubReleaseResponse {
+            tag_name: tag.to_string(),
+            html_url: format!("https://example.com/{tag}"),
+            assets: Vec::new(),
+            draft,
+        }
+    }
+
+    #[test]
+    fn select_channel_release_picks_highest_accepted_version() {
+        let releases = || {
+            vec![
+                release_response("v5.7.1", false),
+                release_response("v5.8.0-alpha.3", false),
+                release_response("v5.8.0-beta.1", false),
+                release_response("v5.8.0-rc.1", true),
+                release_response("nightly", false),
+            ]
+        };
+        let tag = |channel| {
+            select_channel_release(releases(), channel)
+                .map(|release| release.tag_name)
+                .unwrap()
+        };
+        assert_eq!(tag(UpdateChannel::Stable), "v5.7.1");
+        // ドラフトの rc は除外する
+        assert_eq!(tag(UpdateChannel::Beta), "v5.8.0-beta.1");
+        assert_eq!(tag(UpdateChannel::Alpha), "v5.8.0-beta.1");
+        assert!(select_channel_release(
+            vec![release_response("v6.0.0-alpha.1", false)],
+            UpdateChannel::Beta
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn is_upgrade_uses_prerelease_precedence_and_avoids_downgrades() {
+        let v = |s: &str| Version::parse(s).unwrap();
+        assert!(is_upgrade(&v("5.8.0-beta.1"), &v("5.8.0-beta.2")));
+        assert!(is_upgrade(&v("5.8.0-beta.2"), &v("5.8.0")));
+        assert!(is_upgrade(&v("5.7.1"), &v("5.8.0-alpha.1")));
+        // 同一メジャーの古い安定版・プレリリースへは戻さない
+        assert!(!is_upgrade(&v("5.8.0-beta.2"), &v("5.7.1")));
+        assert!(!is_upgrade(&v("5.8.0"), &v("5.8.0-rc.1")));
+        assert!(!is_upgrade(&v("5.8.0"), &v("5.8.0+build.2")));
+    }
+
+    #[test]
+    fn channel_cache_path_separates_prerelease_channels() {
+        let stable = Path::new("/data/update-check.json");
+        assert_eq!(channel_cache_path(stable, UpdateChannel::Stable), stable);
+        assert_eq!(
+            channel_cache_path(stable, UpdateChannel::Beta),
+            Path::new("/data/update-check-beta.json")
+        );
+        assert_eq!(
+            channel_cache_path(stable, UpdateChannel::Alpha),
+            Path::new("/data/update-check-alpha.json")
+        );
+    }
+
+    #[tokio::test]
+    async fn check_only_on_alpha_channel_offers_prerelease() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/akiojin/llmlb/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {"tag_name": "v99.0.0-alpha.2", "html_url": "https://example.com/a2", "assets": [], "prerelease": true},
+                {"tag_name": "v99.0.0-alpha.1", "html_url": "https://example.com/a1", "assets": [], "prerelease": true},
+                {"tag_name": "v1.0.0", "html_url": "https://example.com/s", "assets": []}
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let tmp = tempfile::tempdir().expect("create temp dir");
+        let manager = UpdateManager::new_with_data_dir_and_config(
+            reqwest::Client::new(),
+            InferenceGate::default(),
+            ShutdownController::default(),
+            tmp.path(),
+            Some(mock_server.uri()),
+            UpdateChannel::Alpha,
+        )
+        .expect("create update manager");
+
+        match manager.check_only(true).await.expect("check_only") {
+            UpdateState::Available { latest, .. } => assert_eq!(latest, "99.0.0-alpha.2"),
+            other => panic!("expected available, got {other:?}"),
+        }
+        assert!(tmp.path().join("update-check-alpha.json").exists());
+        assert!(!tmp.path().join("update-check.json").exists());
+    }
+
     // =======================================================================
     // Platform tests
     // =======================================================================
@@ -3510,6 +4122,118 @@ mod tests {
         assert!(installer.is_none());
     }
 
+    // =======================================================================
+    // Delta updates
+    // =======================================================================
+
+    fn asset(name: &str) -> GitHubAsset {
+        GitHubAsset {
+            name: name.to_string(),
+            browser_download_url: format!("https://dl.example.com/{name}"),
+        }
+    }
+
+    #[test]
+    fn select_delta_asset_requires_checksum() {
+        let platform = Platform {
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+        };
+        let mut release = GitHubRelease {
+            tag_name: "v5.0.0".to_string(),
+            html_url: "https://github.com/test/test/releases/v5.0.0".to_string(),
+            assets: vec![
+                asset("llmlb-linux-x86_64.tar.gz"),
+                asset("llmlb-linux-x86_64.patch"),
+            ],
+        };
+        assert!(select_delta_asset(&release, &platform).is_none());
+
+        // A checksum of the patch file itself is not the patched binary's checksum.
+        release
+            .assets
+            .push(asset("llmlb-linux-x86_64.patch.sha256"));
+        assert!(select_delta_asset(&release, &platform).is_none());
+
+        release.assets.push(asset("llmlb-linux-x86_64.sha256"));
+        assert_eq!(
+            select_delta_asset(&release, &platform).unwrap().name,
+            "llmlb-linux-x86_64.patch"
+        );
+        assert_eq!(
+            delta_checksum_url(
+                "https://github.com/test/test/releases/download/v5.0.0/llmlb-linux-x86_64.patch"
+            ),
+            "https://github.com/test/test/releases/download/v5.0.0/llmlb-linux-x86_64.sha256"
+        );
+
+        // Platforms without a patch keep the full download.
+        let other = Platform {
+            os: "macos".to_string(),
+            arch: "aarch64".to_string(),
+        };
+        assert!(select_delta_asset(&release, &other).is_none());
+    }
+
+    #[test]
+    fn delta_plan_only_replaces_portable_plan() {
+        let portable = ApplyPlan::Portable {
+            url: "https://example.com/portable.tar.gz".to_string(),
+        };
+        assert_eq!(with_delta_plan(portable.clone(), None), portable);
+        assert_eq!(
+            with_delta_plan(portable, Some("https://example.com/llmlb.patch")),
+            ApplyPlan::Delta {
+                patch_url: "https://example.com/llmlb.patch".to_string(),
+                portable_url: "https://example.com/portable.tar.gz".to_string(),
+            }
+        );
+
+        let installer = ApplyPlan::Installer {
+            url: "https://example.com/setup.exe".to_string(),
+            kind: InstallerKind::WindowsSetup,
+        };
+        assert_eq!(
+            with_delta_plan(installer.clone(), Some("https://example.com/llmlb.patch")),
+            installer
+        );
+    }
+
+    #[test]
+    fn parse_sha256_accepts_sha256sum_output() {
+        let digest = "A".repeat(64);
+        assert_eq!(
+            parse_sha256(&format!("{digest}  llmlb\n")),
+            Some("a".repeat(64))
+        );
+        assert_eq!(parse_sha256("abc"), None);
+        assert_eq!(parse_sha256(&"g".repeat(64)), None);
+        assert_eq!(parse_sha256(""), None);
+    }
+
+    #[test]
+    fn apply_delta_patch_verifies_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let old_binary = dir.path().join("llmlb");
+        let patch_path = dir.path().join("llmlb.patch");
+        let output = dir.path().join("delta").join("llmlb");
+
+        let old = b"llmlb old binary content".repeat(64);
+        let new = b"llmlb new binary content!".repeat(64);
+        fs::write(&old_binary, &old).unwrap();
+        let mut patch = Vec::new();
+        bsdiff::diff(&old, &new, &mut patch).unwrap();
+        fs::write(&patch_path, &patch).unwrap();
+
+        let err =
+            apply_delta_patch(&old_binary, &patch_path, &output, &"0".repeat(64)).unwrap_err();
+        assert!(err.to_string().contains("hash mismatch"));
+        assert!(!output.exists());
+
+        apply_delta_patch(&old_binary, &patch_path, &output, &sha256_hex(&new)).unwrap();
+        assert_eq!(fs::read(&output).unwrap(), new);
+    }
+
     // =======================================================================
     // choose_apply_plan
     // =======================================================================
@@ -3596,6 +4320,7 @@ mod tests {
             release_url: Some("https://example.com/release".to_string()),
             portable_asset_url: Some("https://example.com/portable.tar.gz".to_string()),
             installer_asset_url: None,
+            delta_asset_url: None,
         };
         save_cache(&cache_path, cache.clone()).unwrap();
 
@@ -3624,6 +4349,7 @@ mod tests {
             release_url: None,
             portable_asset_url: None,
             installer_asset_url: None,
+            delta_asset_url: None,
         };
         save_cache(&cache_path, cache).unwrap();
         assert!(cache_path.exists());
@@ -3640,6 +4366,7 @@ mod tests {
             release_url: Some("https://example.com/release".to_string()),
             portable_asset_url: None,
             installer_asset_url: None,
+            delta_asset_url: None,
         };
         let json = serde_json::to_string(&cache).unwrap();
         let deserialized: UpdateCacheFile = serde_json::from_str(&json).unwrap();
@@ -3727,6 +4454,7 @@ mod tests {
             release_url: "https://example.com/release".to_string(),
             portable_asset_url: Some("https://example.com/portable.tar.gz".to_string()),
             installer_asset_url: None,
+            delta_asset_url: None,
             payload: PayloadState::NotReady,
             checked_at: Utc::now(),
         };
@@ -4310,6 +5038,7 @@ mod tests {
             release_url: None,
             portable_asset_url: None,
             installer_asset_url: None,
+            delta_asset_url: None,
         };
         manager.apply_cache(cache).await.unwrap();
 
@@ -4336,6 +5065,7 @@ mod tests {
             release_url: None,
             portable_asset_url: None,
             installer_asset_url: None,
+            delta_asset_url: None,
         };
         manager.apply_cache(cache).await.unwrap();
 
@@ -4360,6 +5090,7 @@ mod tests {
             release_url: None,
             portable_asset_url: None,
             installer_asset_url: None,
+            delta_asset_url: None,
         };
         let err = manager.apply_cache(cache).await;
         assert!(err.is_err());
@@ -4552,6 +5283,7 @@ mod tests {
             ShutdownController::default(),
             tmp.path(),
             Some(mock_server.uri()),
+            UpdateChannel::Stable,
         )
         .expect("create update manager");
 
@@ -4577,6 +5309,7 @@ mod tests {
                 ),
                 portable_asset_url: Some("https://example.com/portable.tar.gz".to_string()),
                 installer_asset_url: None,
+                delta_asset_url: None,
             },
         )
         .expect("save cache");
@@ -4603,6 +5336,7 @@ mod tests {
                 release_url: "https://example.com/release".to_string(),
                 portable_asset_url: Some("https://example.com/portable.tar.gz".to_string()),
                 installer_asset_url: None,
+                delta_asset_url: None,
                 payload: PayloadState::Ready {
                     kind: PayloadKind::Portable {
                         binary_path: "/tmp/llmlb-new".to_string(),
@@ -4660,6 +5394,7 @@ mod tests {
             ShutdownController::default(),
             tmp.path(),
             Some(mock_server.uri()),
+            UpdateChannel::Stable,
         )
         .expect("create update manager");
 
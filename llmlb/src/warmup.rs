@@ -0,0 +1,246 @@
+//! 起動時のエンドポイントウォームアップ
+//!
+//! `LLMLB_WARMUP_ON_START=1` の場合、サーバ起動後にバックグラウンドで各オンライン
+//! エンドポイントへ直近よく使われたモデルの最小推論リクエストを送り、起動直後の
+//! リクエストがモデルロードを待つコールドスタートを減らす。
+//!
+//! - 待受開始はブロックしない（失敗はログのみ）
+//! - エンドポイント間は並列、同一エンドポイント内のモデルは順番に処理する
+//!   （複数モデルを同時にロードしてVRAMを圧迫しないため）
+//! - ウォームアップリクエストは優先度 `low` で送る
+
+use crate::types::endpoint::{Endpoint, EndpointModel, SupportedAPI};
+use crate::AppState;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// エンドポイントごとにウォームアップするモデル数の上限
+pub const WARMUP_MODELS_PER_ENDPOINT: usize = 3;
+
+/// 利用頻度を数える期間（日）
+const WARMUP_LOOKBACK_DAYS: i64 = 7;
+
+/// ウォームアップ対象を決める
+///
+/// `recent` は利用頻度の高い順のモデルID。エンドポイントが現在提供していないモデルと、
+/// Chat Completions / Embeddings のどちらにも対応しないモデルは除外する。
+pub fn plan_warmup(
+    models: &[EndpointModel],
+    recent: &[String],
+    limit: usize,
+) -> Vec<(String, SupportedAPI)> {
+    recent
+        .iter()
+        .filter_map(|model_id| {
+            let model = models.iter().find(|m| &m.model_id == model_id)?;
+            let api = if model.supports_api(SupportedAPI::ChatCompletions) {
+                SupportedAPI::ChatCompletions
+            } else if model.supports_api(SupportedAPI::Embeddings) {
+                SupportedAPI::Embeddings
+            } else {
+                return None;
+            };
+            Some((model_id.clone(), api))
+        })
+        .take(limit)
+        .collect()
+}
+
+/// ウォームアップリクエストのパスと本文
+fn warmup_request(model_id: &str, api: SupportedAPI) -> (&'static str, Value) {
+    match api {
+        SupportedAPI::Embeddings => (
+            "/v1/embeddings",
+            json!({"model": model_id, "input": "warmup"}),
+        ),
+        _ => (
+            "/v1/chat/completions",
+            json!({
+                "model": model_id,
+                "messages": [{"role": "user", "content": "warmup"}],
+                "max_tokens": 1,
+                "stream": false,
+            }),
+        ),
+    }
+}
+
+/// 1モデル分のウォームアップリクエストを送る
+async fn warmup_model(
+    client: &reqwest::Client,
+    endpoint: &Endpoint,
+    model_id: &str,
+    api: SupportedAPI,
+) -> Result<(), String> {
+    let (path, payload) = warmup_request(model_id, api);
+    let url = format!("{}{}", endpoint.base_url.trim_end_matches('/'), path);
+    let mut request = client
+        .post(url)
+        .json(&payload)
+        .timeout(std::time::Duration::from_secs(
+            endpoint.inference_timeout_secs as u64,
+        ));
+    if let Some(api_key) = &endpoint.api_key {
+        request = request.bearer_auth(api_key);
+    }
+    let request = crate::balancer::priority::apply_priority_header(
+        request,
+        crate::balancer::priority::RequestPriority::Low,
+    );
+
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status().as_u16()))
+    }
+}
+
+/// 1エンドポイント分のモデルを順番にウォームアップし、成功数を返す
+async fn warmup_endpoint(state: &AppState, endpoint: Endpoint, recent: Vec<String>) -> usize {
+    let models = match state.endpoint_registry.list_models(endpoint.id).await {
+        Ok(models) => models,
+        Err(e) => {
+            warn!(
+                endpoint_id = %endpoint.id,
+                error = %e,
+                "Warmup skipped: failed to list models"
+            );
+            return 0;
+        }
+    };
+
+    let mut succeeded = 0;
+    for (model_id, api) in plan_warmup(&models, &recent, WARMUP_MODELS_PER_ENDPOINT) {
+        let started = std::time::Instant::now();
+        match warmup_model(&state.http_client, &endpoint, &model_id, api).await {
+            Ok(()) => {
+                succeeded += 1;
+                debug!(
+                    endpoint_id = %endpoint.id,
+                    endpoint_name = %endpoint.name,
+                    model = %model_id,
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    "Warmed up model"
+                );
+            }
+            Err(e) => warn!(
+                endpoint_id = %endpoint.id,
+                endpoint_name = %endpoint.name,
+                model = %model_id,
+                error = %e,
+                "Model warmup failed"
+            ),
+        }
+    }
+    succeeded
+}
+
+/// 起動時ウォームアップをバックグラウンドで開始する
+pub fn spawn_startup_warmup(state: AppState) {
+    tokio::spawn(async move {
+        let since = Utc::now() - chrono::Duration::days(WARMUP_LOOKBACK_DAYS);
+        let top_models = match state
+            .request_history
+            .get_top_models_by_endpoint(since, WARMUP_MODELS_PER_ENDPOINT)
+            .await
+        {
+            Ok(top_models) => top_models,
+            Err(e) => {
+                warn!(
+                    "Startup warmup skipped: failed to load recent models: {}",
+                    e
+                );
+                return;
+            }
+        };
+        let mut recent: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for (endpoint_id, model) in top_models {
+            recent.entry(endpoint_id).or_default().push(model);
+        }
+
+        let targets: Vec<(Endpoint, Vec<String>)> = state
+            .endpoint_registry
+            .list_online()
+            .await
+            .into_iter()
+            .filter_map(|endpoint| {
+                let models = recent.remove(&endpoint.id)?;
+                Some((endpoint, models))
+            })
+            .collect();
+        if targets.is_empty() {
+            info!("No recently used models on online endpoints; skipping startup warmup");
+            return;
+        }
+
+        info!(endpoints = targets.len(), "Starting startup warmup");
+        let results = futures::future::join_all(
+            targets
+                .into_iter()
+                .map(|(endpoint, models)| warmup_endpoint(&state, endpoint, models)),
+        )
+        .await;
+        info!(
+            warmed_models = results.iter().sum::<usize>(),
+            "Startup warmup complete"
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(model_id: &str, supported_apis: Vec<SupportedAPI>) -> EndpointModel {
+        EndpointModel {
+            endpoint_id: Uuid::nil(),
+            model_id: model_id.to_string(),
+            capabilities: None,
+            max_tokens: None,
+            last_checked: None,
+            supported_apis,
+            canonical_name: None,
+        }
+    }
+
+    #[test]
+    fn plan_keeps_served_models_in_usage_order() {
+        let models = vec![
+            model("chat-a", vec![SupportedAPI::ChatCompletions]),
+            model("embed", vec![SupportedAPI::Embeddings]),
+            model("responses-only", vec![SupportedAPI::Responses]),
+            model("chat-b", vec![SupportedAPI::ChatCompletions]),
+        ];
+        let recent = vec![
+            "embed".to_string(),
+            "removed".to_string(),
+            "responses-only".to_string(),
+            "chat-b".to_string(),
+            "chat-a".to_string(),
+        ];
+
+        assert_eq!(
+            plan_warmup(&models, &recent, 2),
+            vec![
+                ("embed".to_string(), SupportedAPI::Embeddings),
+                ("chat-b".to_string(), SupportedAPI::ChatCompletions),
+            ]
+        );
+    }
+
+    #[test]
+    fn warmup_request_is_minimal() {
+        let (path, body) = warmup_request("m", SupportedAPI::ChatCompletions);
+        assert_eq!(path, "/v1/chat/completions");
+        assert_eq!(body["max_tokens"], 1);
+        assert_eq!(body["stream"], false);
+
+        let (path, body) = warmup_request("m", SupportedAPI::Embeddings);
+        assert_eq!(path, "/v1/embeddings");
+        assert_eq!(body["model"], "m");
+    }
+}
@@ -22,6 +22,7 @@ export type UpdateState =
       release_url: string
       portable_asset_url?: string | null
       installer_asset_url?: string | null
+      delta_asset_url?: string | null
       payload: UpdatePayloadState
       checked_at: string
     }
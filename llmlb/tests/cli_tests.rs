@@ -165,3 +165,15 @@ fn test_unknown_subcommand_rejected() {
     let result = Cli::try_parse_from(["llmlb", "user"]);
     assert!(result.is_err());
 }
+
+/// Test --config is accepted before and after the subcommand
+#[test]
+fn test_config_flag_parses() {
+    let cli = Cli::try_parse_from(["llmlb", "--config", "/tmp/llmlb.toml", "serve"]).unwrap();
+    assert_eq!(
+        cli.config.as_deref(),
+        Some(std::path::Path::new("/tmp/llmlb.toml"))
+    );
+    let cli = Cli::try_parse_from(["llmlb", "serve", "--config", "/tmp/llmlb.toml"]).unwrap();
+    assert!(cli.config.is_some());
+}
@@ -73,6 +73,8 @@ fn create_test_record_with_key(
         output_tokens: None,
         total_tokens: None,
         api_key_id,
+        cost_usd: None,
+        requested_model: None,
     }
 }
 
@@ -72,6 +72,8 @@ fn create_test_record(
         output_tokens: None,
         total_tokens: None,
         api_key_id: None,
+        cost_usd: None,
+        requested_model: None,
     }
 }
 
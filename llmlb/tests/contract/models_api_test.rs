@@ -314,6 +314,12 @@ async fn test_register_model_contract() {
         .mount(&mock)
         .await;
 
+    // プレフライト検証（HEAD）はすべて存在する扱い
+    Mock::given(method("HEAD"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock)
+        .await;
+
     std::env::set_var("HF_BASE_URL", mock.uri());
 
     let TestApp { app, admin_key, .. } = build_app().await;
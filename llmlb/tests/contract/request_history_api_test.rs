@@ -434,5 +434,7 @@ fn create_test_record(
         output_tokens: None,
         total_tokens: None,
         api_key_id: None,
+        cost_usd: None,
+        requested_model: None,
     }
 }
@@ -681,7 +681,7 @@ export async function createApiKeyViaUi(
   const createResp = await createApiKeyResponse
   const createRespBody = (await createResp.json()) as { id?: string; key?: string }
   const apiKey = createRespBody.key?.trim() ?? ''
-  expect(apiKey).toMatch(/^sk_/)
+  expect(apiKey).toMatch(/^llmlb_sk_/)
 
   const createdAlert = apiKeysModal.getByText('API Key Created Successfully').locator('..')
   await expect(createdAlert).toBeVisible({ timeout: 10000 })
@@ -107,7 +107,7 @@ test.describe('API Key Lifecycle @api', () => {
     const createResp = await createApiKeyResponse
     const createRespBody = (await createResp.json()) as { id?: string; key?: string }
     const apiKey = createRespBody.key?.trim() || ''
-    expect(apiKey).toMatch(/^sk_/)
+    expect(apiKey).toMatch(/^llmlb_sk_/)
     if (createRespBody.id) {
       createdKeyIds.push(createRespBody.id)
     }
@@ -117,9 +117,9 @@ test.describe('API Key Create + OpenAI API Calls @api-keys', () => {
     // Reveal and read the plaintext key (only shown at creation time).
     await createdAlert.locator('button:not(#copy-api-key)').first().click()
     const apiKeyCode = createdAlert.locator('code')
-    await expect(apiKeyCode).toContainText('sk_', { timeout: 10000 })
+    await expect(apiKeyCode).toContainText('llmlb_sk_', { timeout: 10000 })
     const apiKey = (await apiKeyCode.textContent())?.trim() || ''
-    expect(apiKey).toMatch(/^sk_/)
+    expect(apiKey).toMatch(/^llmlb_sk_/)
     expect(apiKey).not.toContain('•')
 
     // 3) Use the created key to call real APIs.
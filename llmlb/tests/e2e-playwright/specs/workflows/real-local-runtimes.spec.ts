@@ -217,7 +217,7 @@ async function createApiKeyViaUi(page: Page, keyName: string): Promise<{ id: str
   const createResp = await createApiKeyResponse
   const createRespBody = (await createResp.json()) as { id?: string; key?: string }
   const apiKey = createRespBody.key?.trim() ?? ''
-  expect(apiKey).toMatch(/^sk_/)
+  expect(apiKey).toMatch(/^llmlb_sk_/)
 
   const createdAlert = apiKeysModal.getByText('API Key Created Successfully').locator('..')
   await expect(createdAlert).toBeVisible({ timeout: 10000 })
@@ -379,7 +379,7 @@ test.describe('Real Local Runtimes @workflows @real-runtimes', () => {
     // Create an API key in the UI and verify clipboard readback.
     const createdApiKey = await createApiKeyViaUi(page, apiKeyName)
     createdApiKeyId = createdApiKey.id
-    expect(createdApiKey.key).toMatch(/^sk_/)
+    expect(createdApiKey.key).toMatch(/^llmlb_sk_/)
 
     // Wait until both synced runtime models are exposed via llmlb.
     await waitForApiModelVisible(request, createdApiKey.key, runtimeSelection.ollamaModel)
@@ -436,7 +436,7 @@ async fn me_api_keys_routes_require_jwt_and_owner_scope() {
     let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
     let created_key = created["key"].as_str().unwrap();
     let created_id = created["id"].as_str().unwrap();
-    assert!(created_key.starts_with("sk_"));
+    assert!(created_key.starts_with("llmlb_sk_"));
 
     // viewer list includes the key
     let response = app
@@ -648,3 +648,131 @@ async fn me_api_keys_create_enforces_role_based_permissions_payload() {
         json!(["openai.inference", "openai.models.read"])
     );
 }
+
+#[tokio::test]
+async fn me_api_keys_scope_preset_and_revocation() {
+    let (app, db_pool) = build_app().await;
+
+    let admin_id = create_admin_user(&db_pool).await;
+    let admin_jwt = llmlb::auth::jwt::create_jwt(
+        &admin_id.to_string(),
+        UserRole::Admin,
+        &support::lb::test_jwt_secret(),
+        false,
+    )
+    .expect("create admin jwt");
+
+    // read-only スコープで発行（平文は発行時のみ返る）
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/me/api-keys")
+                .header("authorization", format!("Bearer {}", admin_jwt))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    serde_json::to_vec(&json!({
+                        "name": "read-only-key",
+                        "scope": "read-only"
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let key = created["key"].as_str().unwrap().to_string();
+    let key_id = created["id"].as_str().unwrap().to_string();
+    assert!(key.starts_with("llmlb_sk_"));
+    assert!(!created["permissions"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("openai.inference")));
+
+    let models_request = |key: &str| {
+        Request::builder()
+            .method("GET")
+            .uri("/v1/models")
+            .header("authorization", format!("Bearer {}", key))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // 参照は許可、推論は 403
+    let response = app.clone().oneshot(models_request(&key)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .clone()
+        .oneshot(support::lb::with_connect_info(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("authorization", format!("Bearer {}", key))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    serde_json::to_vec(&json!({
+                        "model": "test-model",
+                        "messages": [{"role": "user", "content": "Hello"}]
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // 失効後は即座に 401
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/me/api-keys/{}/revoke", key_id))
+                .header("authorization", format!("Bearer {}", admin_jwt))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let revoked: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(revoked["revoked_at"].is_string());
+    assert!(revoked.get("key").is_none());
+
+    let response = app.clone().oneshot(models_request(&key)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // 失効済みのキーも一覧には残る
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/api/me/api-keys")
+                .header("authorization", format!("Bearer {}", admin_jwt))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let list: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let listed = list["api_keys"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|k| k["id"] == json!(key_id))
+        .expect("revoked key is listed");
+    assert!(listed["revoked_at"].is_string());
+}
@@ -11,7 +11,7 @@
 use std::sync::Arc;
 
 use llmlb::{
-    balancer::LoadManager,
+    balancer::{LoadManager, SelectionContext},
     common::protocol::TpsApiKind,
     db::migrations::run_migrations,
     registry::endpoints::EndpointRegistry,
@@ -127,7 +127,11 @@ async fn tps_routing_prefers_highest_tps_for_model() {
         .await;
 
     let selected = load_manager
-        .select_endpoint_by_tps_ready_for_model("shared-model", Some(TpsApiKind::ChatCompletions))
+        .select_endpoint_by_tps_ready_for_model(
+            "shared-model",
+            Some(TpsApiKind::ChatCompletions),
+            &SelectionContext::default(),
+        )
         .await
         .expect("endpoint should be selected");
 
@@ -151,7 +155,11 @@ async fn tps_routing_treats_unmeasured_endpoint_as_lowest_priority() {
         .await;
 
     let selected = load_manager
-        .select_endpoint_by_tps_ready_for_model("shared-model", Some(TpsApiKind::ChatCompletions))
+        .select_endpoint_by_tps_ready_for_model(
+            "shared-model",
+            Some(TpsApiKind::ChatCompletions),
+            &SelectionContext::default(),
+        )
         .await
         .expect("endpoint should be selected");
 
@@ -167,11 +175,19 @@ async fn tps_routing_all_unmeasured_uses_round_robin() {
     let _third = add_online_endpoint(&load_manager, "Third", &["shared-model"]).await;
 
     let selected_1 = load_manager
-        .select_endpoint_by_tps_ready_for_model("shared-model", Some(TpsApiKind::ChatCompletions))
+        .select_endpoint_by_tps_ready_for_model(
+            "shared-model",
+            Some(TpsApiKind::ChatCompletions),
+            &SelectionContext::default(),
+        )
         .await
         .expect("first selection should succeed");
     let selected_2 = load_manager
-        .select_endpoint_by_tps_ready_for_model("shared-model", Some(TpsApiKind::ChatCompletions))
+        .select_endpoint_by_tps_ready_for_model(
+            "shared-model",
+            Some(TpsApiKind::ChatCompletions),
+            &SelectionContext::default(),
+        )
         .await
         .expect("second selection should succeed");
 
@@ -200,11 +216,19 @@ async fn tps_routing_uses_round_robin_when_tps_is_tied() {
     }
 
     let selected_1 = load_manager
-        .select_endpoint_by_tps_ready_for_model("shared-model", Some(TpsApiKind::ChatCompletions))
+        .select_endpoint_by_tps_ready_for_model(
+            "shared-model",
+            Some(TpsApiKind::ChatCompletions),
+            &SelectionContext::default(),
+        )
         .await
         .expect("first selection should succeed");
     let selected_2 = load_manager
-        .select_endpoint_by_tps_ready_for_model("shared-model", Some(TpsApiKind::ChatCompletions))
+        .select_endpoint_by_tps_ready_for_model(
+            "shared-model",
+            Some(TpsApiKind::ChatCompletions),
+            &SelectionContext::default(),
+        )
         .await
         .expect("second selection should succeed");
 
@@ -249,7 +273,11 @@ async fn tps_routing_excludes_offline_error_and_initializing_endpoints() {
         .expect("error transition should succeed");
 
     let selected = load_manager
-        .select_endpoint_by_tps_ready_for_model("shared-model", Some(TpsApiKind::ChatCompletions))
+        .select_endpoint_by_tps_ready_for_model(
+            "shared-model",
+            Some(TpsApiKind::ChatCompletions),
+            &SelectionContext::default(),
+        )
         .await
         .expect("ready endpoint should remain selectable");
 
@@ -273,11 +301,19 @@ async fn tps_routing_ignores_other_api_kinds_when_request_kind_is_none() {
         .await;
 
     let first_pick = load_manager
-        .select_endpoint_by_tps_ready_for_model("shared-model", None::<TpsApiKind>)
+        .select_endpoint_by_tps_ready_for_model(
+            "shared-model",
+            None::<TpsApiKind>,
+            &SelectionContext::default(),
+        )
         .await
         .expect("first selection should succeed");
     let second_pick = load_manager
-        .select_endpoint_by_tps_ready_for_model("shared-model", None::<TpsApiKind>)
+        .select_endpoint_by_tps_ready_for_model(
+            "shared-model",
+            None::<TpsApiKind>,
+            &SelectionContext::default(),
+        )
         .await
         .expect("second selection should succeed");
 
@@ -315,7 +351,11 @@ async fn tps_routing_single_endpoint_is_selected() {
         .await;
 
     let selected = load_manager
-        .select_endpoint_by_tps_ready_for_model("shared-model", Some(TpsApiKind::ChatCompletions))
+        .select_endpoint_by_tps_ready_for_model(
+            "shared-model",
+            Some(TpsApiKind::ChatCompletions),
+            &SelectionContext::default(),
+        )
         .await
         .expect("selection should succeed");
 